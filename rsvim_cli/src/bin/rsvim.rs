@@ -10,7 +10,7 @@ use rsvim_core::res::IoResult;
 
 use clap::Parser;
 use once_cell::sync::Lazy;
-use tracing::trace;
+use tracing::{error, trace};
 
 static RSVIM_SNAPSHOT: Lazy<Box<[u8]>> = Lazy::new(|| {
   static COMPRESSED_BYTES: &[u8] =
@@ -58,7 +58,7 @@ fn main() -> IoResult<()> {
 
   // Explicitly create tokio runtime for the EventLoop.
   let evloop_tokio_runtime = tokio::runtime::Runtime::new()?;
-  evloop_tokio_runtime.block_on(async {
+  let result = evloop_tokio_runtime.block_on(async {
     // Create event loop.
     let mut event_loop = EventLoop::new(cli_opt, SnapshotData::new(&RSVIM_SNAPSHOT))?;
 
@@ -72,13 +72,42 @@ fn main() -> IoResult<()> {
     event_loop.init_buffers()?;
     event_loop.init_windows()?;
 
+    // Restore the last position of the opened file.
+    event_loop.init_positions()?;
+
+    // Load the user spell dictionary.
+    event_loop.init_spell()?;
+
+    // Restore session.
+    event_loop.init_session()?;
+
     // Finish initialize terminal.
     event_loop.init_tui_done()?;
 
     // Run loop.
     event_loop.run().await?;
 
+    // Save the last positions of the opened files.
+    if let Err(e) = event_loop.save_positions() {
+      error!("Failed to save positions:{:?}", e);
+    }
+
+    // Flush the undo history of the opened files.
+    if let Err(e) = event_loop.flush_undo_files() {
+      error!("Failed to flush undo files:{:?}", e);
+    }
+
+    // Save current session.
+    if let Err(e) = event_loop.save_session() {
+      error!("Failed to save session:{:?}", e);
+    }
+
     // Shutdown.
-    event_loop.shutdown_tui()
-  })
+    event_loop.shutdown_tui()?;
+    Ok(())
+  });
+
+  // Don't wait for the abandoned background tasks, they're already given a grace period.
+  evloop_tokio_runtime.shutdown_background();
+  result
 }
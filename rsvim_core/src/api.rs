@@ -0,0 +1,243 @@
+//! The editor API shared by the script runtimes, i.e. the js runtime (see [`js`](crate::js)) and
+//! the optional Lua runtime (see `lua`, it requires the `lua` feature).
+//!
+//! The runtime bindings only convert the arguments and the return values between the script
+//! values and the rust values, the editor logic is implemented here once. Thus a script API has
+//! the same behavior in both runtimes, i.e. `Rsvim.buf.getLines(0, 10)` in js and
+//! `rsvim.buf.get_lines(0, 10)` in Lua.
+//!
+//! The argument errors (i.e. an unknown message level) are returned as `Err(String)`, the runtimes
+//! throw them as type errors.
+
+use crate::buf::{BufferId, BufferInfo, BuffersManagerArc};
+use crate::envar;
+use crate::res::ExCommandResult;
+use crate::state::current_window_buffer;
+use crate::state::ex::ExCommandDataAccess;
+use crate::state::fsm::visual::move_cursor_to;
+use crate::state::msg::MessageLevel;
+use crate::state::register::{self, Register, RegisterKind};
+use crate::state::StateArc;
+use crate::ui::tree::TreeArc;
+use crate::{rlock, wlock};
+
+use tracing::trace;
+
+#[derive(Debug, Clone)]
+/// The editor API, it holds the same data access with the js runtime.
+pub struct EditorApi {
+  tree: TreeArc,
+  buffers: BuffersManagerArc,
+  editing_state: StateArc,
+}
+
+impl EditorApi {
+  pub fn new(tree: TreeArc, buffers: BuffersManagerArc, editing_state: StateArc) -> Self {
+    EditorApi {
+      tree,
+      buffers,
+      editing_state,
+    }
+  }
+
+  /// Execute the ex command line, i.e. `Rsvim.cmd("split")`. The command line is not saved to
+  /// history.
+  pub fn cmd(&self, command_line: &str) -> ExCommandResult<()> {
+    let mut state = wlock!(self.editing_state);
+    let mut data_access = ExCommandDataAccess::new(
+      self.tree.clone(),
+      self.buffers.clone(),
+      state.interrupt().clone(),
+      state.messages().clone(),
+    )
+    .with_registers(state.registers().entries());
+    let result = state.ex_commands().run(command_line, &mut data_access);
+    state.push_scripts(data_access.take_scripts());
+    state.push_events(data_access.take_events());
+    state.request_config_reload(data_access.take_config_reload());
+    state.request_grep(data_access.take_grep());
+    state.request_quit(data_access.take_quit());
+    state.push_shell_reads(data_access.take_shell_reads());
+    trace!("cmd: {:?}, {:?}", command_line, result);
+    result
+  }
+
+  /// Show the message to the user, it's also saved in the message history.
+  pub fn notify(&self, message: &str, level: &str) -> Result<(), String> {
+    trace!("notify: {:?}, {:?}", message, level);
+    let level: MessageLevel = level.parse()?;
+    let messages = rlock!(self.editing_state).messages().clone();
+    wlock!(messages).push(level, message);
+    Ok(())
+  }
+
+  /// Get the lines in range `[start, end)` of current buffer, without line endings. Returns
+  /// `None` if there's no current buffer.
+  pub fn buf_get_lines(&self, start: usize, end: usize) -> Option<Vec<String>> {
+    let (buffer, _viewport) = current_window_buffer(&self.tree)?;
+    let buffer = rlock!(buffer);
+    let end = std::cmp::min(end, buffer.len_lines());
+    let lines = (start..end)
+      .filter_map(|line_idx| buffer.get_line_content(line_idx))
+      .collect::<Vec<_>>();
+    trace!("buf_get_lines: {:?}..{:?}, {:?}", start, end, lines.len());
+    Some(lines)
+  }
+
+  /// Replace the lines in range `[start, end)` of current buffer with `lines`, see
+  /// [`Buffer::set_lines`](crate::buf::Buffer::set_lines). Returns `Ok(false)` if there's no
+  /// current buffer or `start` is out of range.
+  pub fn buf_set_lines(&self, start: usize, end: usize, lines: &[String]) -> Result<bool, String> {
+    if lines.iter().any(|line| line.contains(['\n', '\r'])) {
+      return Err("Lines must not contain line breaks".to_string());
+    }
+
+    let (buffer, viewport) = match current_window_buffer(&self.tree) {
+      Some(current) => current,
+      None => return Ok(false),
+    };
+    let cursor = *rlock!(viewport).cursor();
+    trace!("buf_set_lines: {:?}..{:?}, {:?}", start, end, lines.len());
+    let position = {
+      let mut buffer = wlock!(buffer);
+      if !buffer.set_lines(start, end, lines) {
+        return Ok(false);
+      }
+      // Keep the cursor in the buffer.
+      let line_idx = std::cmp::min(cursor.line_idx(), buffer.len_lines().saturating_sub(1));
+      let len_chars = buffer
+        .get_line_content(line_idx)
+        .map(|line| line.chars().count())
+        .unwrap_or(0);
+      (line_idx, std::cmp::min(cursor.char_idx(), len_chars))
+    };
+    // NOTE: The buffer lock must be released before syncing viewport.
+    wlock!(viewport).sync();
+    move_cursor_to(&self.tree, &viewport, position);
+    Ok(true)
+  }
+
+  /// List the buffers, see [`BuffersManager::list`](crate::buf::BuffersManager::list). The
+  /// unlisted buffers are included if `all`.
+  pub fn buf_list(&self, all: bool) -> Vec<BufferInfo> {
+    let list = rlock!(self.buffers).list(all);
+    trace!("buf_list: {:?}, {:?}", all, list.len());
+    list
+  }
+
+  /// Whether the buffer `id` is modified, returns `None` if the buffer doesn't exist.
+  pub fn buf_is_modified(&self, id: BufferId) -> Option<bool> {
+    let modified = rlock!(self.buffers)
+      .get(&id)
+      .map(|buffer| rlock!(buffer).is_modified());
+    trace!("buf_is_modified: {:?}, {:?}", id, modified);
+    modified
+  }
+
+  /// Get the register, returns `Ok(None)` if it's empty.
+  pub fn register_get(&self, name: &str) -> Result<Option<Register>, String> {
+    let name = parse_register_name(name)?;
+    let value = rlock!(self.editing_state).register(name);
+    trace!("register_get: {:?}, {:?}", name, value);
+    Ok(value)
+  }
+
+  /// Set the register, returns `Ok(false)` if the clipboard is not available.
+  ///
+  /// The register type is detected by the content if the type is empty string, i.e. linewise if
+  /// the content ends with line break, otherwise charwise.
+  pub fn register_set(&self, name: &str, content: String, kind: &str) -> Result<bool, String> {
+    let name = parse_register_name(name)?;
+    let value = if kind.is_empty() {
+      Register::from_text(content)
+    } else {
+      Register::new(RegisterKind::try_from(kind)?, content)
+    };
+    trace!("register_set: {:?}, {:?}", name, value);
+    Ok(wlock!(self.editing_state).set_register(name, value))
+  }
+
+  /// Add a good word to the user spell dictionary, returns `false` if the word is invalid or it's
+  /// already in the dictionary.
+  pub fn spell_add(&self, word: &str) -> bool {
+    let added = wlock!(self.buffers).spell_mut().add(word);
+    trace!("spell_add: {:?}, {:?}", word, added);
+    added
+  }
+
+  /// Whether the word is good, see
+  /// [`SpellDictionary::check`](crate::buf::spell::SpellDictionary::check).
+  pub fn spell_check(&self, word: &str) -> bool {
+    let good = rlock!(self.buffers).spell().check(word);
+    trace!("spell_check: {:?}, {:?}", word, good);
+    good
+  }
+
+  /// Get all the good words of the user spell dictionary, in sorted order.
+  pub fn spell_words(&self) -> Vec<String> {
+    let words: Vec<String> = rlock!(self.buffers)
+      .spell()
+      .words()
+      .iter()
+      .cloned()
+      .collect();
+    trace!("spell_words: {:?}", words.len());
+    words
+  }
+
+  /// Request to read the user spell dictionary file again, it runs on an async task.
+  pub fn spell_reload(&self) {
+    trace!("spell_reload");
+    wlock!(self.buffers).spell_mut().request_reload();
+  }
+}
+
+// Parse the register name, i.e. a single valid register character.
+fn parse_register_name(name: &str) -> Result<char, String> {
+  let mut chars = name.chars();
+  match (chars.next(), chars.next()) {
+    (Some(c), None) if register::is_valid_name(c) => Ok(c),
+    _ => Err(format!("Invalid register name: {:?}", name)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::buf::BuffersManager;
+  use crate::cart::U16Size;
+  use crate::state::State;
+  use crate::ui::tree::Tree;
+
+  fn make_api() -> EditorApi {
+    EditorApi::new(
+      Tree::to_arc(Tree::new(U16Size::new(10, 10))),
+      BuffersManager::to_arc(BuffersManager::new()),
+      State::to_arc(State::default()),
+    )
+  }
+
+  #[test]
+  fn notify1() {
+    let api = make_api();
+    assert!(api.notify("hello", "warn").is_ok());
+    assert!(api.notify("hello", "fatal").is_err());
+    assert_eq!(parse_register_name("a"), Ok('a'));
+    assert!(parse_register_name("ab").is_err());
+    assert!(parse_register_name("").is_err());
+  }
+
+  #[test]
+  fn buf1() {
+    let api = make_api();
+    // There's no current window.
+    assert_eq!(api.buf_get_lines(0, 10), None);
+    assert_eq!(api.buf_set_lines(0, 1, &["a".to_string()]), Ok(false));
+    assert!(api.buf_set_lines(0, 1, &["a\nb".to_string()]).is_err());
+    assert!(api.buf_list(true).is_empty());
+    assert_eq!(api.buf_is_modified(1), None);
+    assert!(api.spell_add("rsvim"));
+    assert!(api.spell_check("Rsvim"));
+    assert_eq!(api.spell_words(), vec!["rsvim".to_string()]);
+  }
+}
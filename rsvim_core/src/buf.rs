@@ -1,31 +1,73 @@
 //! Vim buffers.
 
-use crate::defaults::grapheme::AsciiControlCodeFormatter;
+use crate::buf::path::absolute_path;
+use crate::defaults::grapheme::{
+  is_printable_ascii, printable_ascii_len, AsciiControlCodeFormatter, GraphemeScanner,
+  GRAPHEME_MAX_CHARS,
+};
 // use crate::evloop::msg::WorkerToMasterMessage;
-use crate::res::IoResult;
+use crate::envar;
+use crate::res::{IoErr, IoErrKind, IoResult};
+use crate::{rlock, wlock};
 
 // Re-export
-pub use crate::buf::opt::{BufferLocalOptions, FileEncoding};
+pub use crate::buf::arglist::ArgList;
+pub use crate::buf::explorer::{DirListing, ExplorerEntry, ExplorerSort};
+pub use crate::buf::extmark::{Extmark, ExtmarkDecorations, ExtmarkId, Extmarks};
+pub use crate::buf::filetype::{FileTypeOptions, FileTypes};
+pub use crate::buf::fs::{FsProvider, FsProviderArc, MemoryFs, StdFs};
+pub use crate::buf::highlight::{HighlightNamespaces, HighlightStyle, NamespaceId};
+pub use crate::buf::load::BufferLoader;
+pub use crate::buf::mark::{LineMarks, MarkPos, Marks};
+pub use crate::buf::opt::{BufferLocalOptions, FileEncoding, FileFormat};
+pub use crate::buf::position::{LastPosition, PositionStore};
+pub use crate::buf::quickfix::{QuickfixEntry, QuickfixList};
+pub use crate::buf::remote::RemoteUrl;
+pub use crate::buf::sign::{PlacedSign, SignDefinition, SignId, Signs};
+pub use crate::buf::spell::SpellDictionary;
+pub use crate::buf::template::Templates;
+pub use crate::buf::undo::{Delta, UndoPersist, UndoState, UndoTree};
+pub use crate::buf::undofile::UndoFile;
+pub use crate::buf::write::{FsyncPolicy, WriteOptions};
 
 use ahash::AHashMap as HashMap;
 use ascii::AsciiChar;
-use compact_str::CompactString;
+use compact_str::{format_compact, CompactString, ToCompactString};
 use parking_lot::RwLock;
-use path_absolutize::Absolutize;
 use ropey::iter::Lines;
 use ropey::{Rope, RopeBuilder, RopeSlice};
 use std::collections::BTreeMap;
 use std::convert::From;
 use std::fs::Metadata;
-use std::io::Read;
+use std::ops::{Bound, Range, RangeBounds};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::{Arc, Weak};
 use std::time::Instant;
-use tracing::trace;
+use tracing::{error, trace};
 use unicode_width::UnicodeWidthChar;
 
+pub mod arglist;
+pub mod expand;
+pub mod explorer;
+pub mod extmark;
+pub mod filetype;
+pub mod fs;
+pub mod highlight;
+pub mod load;
+pub mod mark;
 pub mod opt;
+pub mod path;
+pub mod position;
+pub mod quickfix;
+pub mod remote;
+pub mod sign;
+pub mod spell;
+pub mod template;
+pub mod trash;
+pub mod undo;
+pub mod undofile;
+pub mod write;
 
 /// Buffer ID.
 pub type BufferId = i32;
@@ -38,6 +80,36 @@ pub fn next_buffer_id() -> BufferId {
   VALUE.fetch_add(1, Ordering::Relaxed)
 }
 
+// The grapheme scanner which has scanned the chars before `char_idx` in the `line`. It looks back
+// to the nearest printable ASCII char after another ASCII char (i.e. it always starts a grapheme
+// cluster), or at most `GRAPHEME_MAX_CHARS` chars.
+fn scanner_before(line: &RopeSlice, char_idx: usize) -> GraphemeScanner {
+  let lookback = char_idx.saturating_sub(GRAPHEME_MAX_CHARS);
+  let mut start = char_idx;
+  while start > lookback {
+    start -= 1;
+    let c = line.char(start);
+    if c.is_ascii()
+      && is_printable_ascii(c as u8)
+      && (start == 0 || line.char(start - 1).is_ascii())
+    {
+      break;
+    }
+  }
+  let mut scanner = GraphemeScanner::new();
+  for c in line.chars_at(start).take(char_idx - start) {
+    scanner.advance(c);
+  }
+  scanner
+}
+
+// New undo tree with the 'undolevels' and 'undomemory' limits.
+fn new_undo_tree(options: &BufferLocalOptions) -> UndoTree {
+  let mut undo_tree = UndoTree::new();
+  undo_tree.set_limits(options.undo_levels(), options.undo_memory());
+  undo_tree
+}
+
 //#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 ///// The Vim buffer's status.
 //pub enum BufferStatus {
@@ -67,6 +139,29 @@ pub struct Buffer {
   absolute_filename: Option<PathBuf>,
   metadata: Option<Metadata>,
   last_sync_time: Option<Instant>,
+  undo_tree: UndoTree,
+  // The journal of the undo history, see [`undofile`](crate::buf::undofile).
+  undo_file: Option<UndoFile>,
+  highlights: HighlightNamespaces,
+  // The count of changes, i.e. `b:changedtick`.
+  changed_tick: usize,
+  // The undo state when the buffer is loaded/saved, i.e. it's not modified at this state.
+  saved_seq: usize,
+  // Whether the file is still loading in background, the buffer is read-only while loading.
+  loading: bool,
+  // Whether the buffer is read-only, i.e. the http remote file.
+  readonly: bool,
+  // The remote file URL.
+  remote: Option<RemoteUrl>,
+  // The directory listing of the directory buffer.
+  explorer: Option<DirListing>,
+  // Whether the buffer is listed in the buffer list, i.e. the 'buflisted' option.
+  listed: bool,
+  marks: Marks,
+  // The marked lines of `:global`.
+  line_marks: LineMarks,
+  signs: Signs,
+  extmarks: Extmarks,
   // worker_send_to_master: Sender<WorkerToMasterMessage>,
 }
 
@@ -84,6 +179,7 @@ impl Buffer {
     metadata: Option<Metadata>,
     last_sync_time: Option<Instant>,
   ) -> Self {
+    let undo_tree = new_undo_tree(&options);
     Self {
       id: next_buffer_id(),
       rope,
@@ -92,12 +188,27 @@ impl Buffer {
       absolute_filename,
       metadata,
       last_sync_time,
+      undo_tree,
+      undo_file: None,
+      highlights: HighlightNamespaces::new(),
+      changed_tick: 0,
+      saved_seq: 0,
+      loading: false,
+      readonly: false,
+      remote: None,
+      explorer: None,
+      listed: true,
+      marks: Marks::new(),
+      line_marks: LineMarks::new(),
+      signs: Signs::new(),
+      extmarks: Extmarks::new(),
     }
   }
 
   /// NOTE: This API should not be used to create new buffer, please use [`BuffersManager`] APIs to
   /// manage buffer instances.
   pub fn _new_empty(options: BufferLocalOptions) -> Self {
+    let undo_tree = new_undo_tree(&options);
     Self {
       id: next_buffer_id(),
       rope: Rope::new(),
@@ -106,6 +217,20 @@ impl Buffer {
       absolute_filename: None,
       metadata: None,
       last_sync_time: None,
+      undo_tree,
+      undo_file: None,
+      highlights: HighlightNamespaces::new(),
+      changed_tick: 0,
+      saved_seq: 0,
+      loading: false,
+      readonly: false,
+      remote: None,
+      explorer: None,
+      listed: true,
+      marks: Marks::new(),
+      line_marks: LineMarks::new(),
+      signs: Signs::new(),
+      extmarks: Extmarks::new(),
     }
   }
 
@@ -141,6 +266,100 @@ impl Buffer {
     self.metadata = metadata;
   }
 
+  /// Get the count of changes, i.e. the `b:changedtick`.
+  ///
+  /// It increases on each edit (include undo/redo), and when the options that affect the display
+  /// width are changed. The owners of the derived data (i.e. the viewport) use it to detect
+  /// whether the data is outdated.
+  pub fn changed_tick(&self) -> usize {
+    self.changed_tick
+  }
+
+  /// Whether the buffer is modified since it's loaded/saved, i.e. the 'modified' option.
+  ///
+  /// NOTE: Undo back to the saved state makes it not modified.
+  pub fn is_modified(&self) -> bool {
+    self.undo_tree.current() != self.saved_seq || self.undo_tree.has_pending()
+  }
+
+  /// Mark current state as saved, i.e. after writing the buffer to its file.
+  pub fn set_saved(&mut self) {
+    self.saved_seq = self.undo_tree.current();
+    self.save_undo_point();
+  }
+
+  /// Mark the undo state `seq` as saved, i.e. the background write which started at this state is
+  /// done.
+  pub fn set_saved_seq(&mut self, seq: usize) {
+    self.saved_seq = seq;
+    self.save_undo_point();
+  }
+
+  /// Whether the file is still loading in background, see [`BufferLoader`].
+  ///
+  /// NOTE: The buffer is read-only while loading, i.e. the edits are discarded.
+  pub fn is_loading(&self) -> bool {
+    self.loading
+  }
+
+  pub fn set_loading(&mut self, loading: bool) {
+    self.loading = loading;
+  }
+
+  /// Whether the buffer is read-only, i.e. it's loading (see [`is_loading`](Buffer::is_loading))
+  /// or it's set read-only.
+  ///
+  /// NOTE: The edits are discarded on read-only buffer.
+  pub fn is_read_only(&self) -> bool {
+    self.loading || self.readonly
+  }
+
+  pub fn set_readonly(&mut self, readonly: bool) {
+    self.readonly = readonly;
+  }
+
+  /// Whether the buffer is listed in the buffer list (i.e. `:ls`), the 'buflisted' option.
+  ///
+  /// NOTE: The scratch buffers are not listed.
+  pub fn is_listed(&self) -> bool {
+    self.listed
+  }
+
+  pub fn set_listed(&mut self, value: bool) {
+    self.listed = value;
+  }
+
+  /// The display name in the buffer list, i.e. the file name, or the URL of the remote file.
+  /// Returns `None` for the unnamed buffer.
+  pub fn display_name(&self) -> Option<String> {
+    match &self.remote {
+      Some(remote) => Some(remote.url().to_string()),
+      None => self
+        .filename
+        .as_ref()
+        .map(|filename| filename.to_string_lossy().to_string()),
+    }
+  }
+
+  /// Get the remote file URL, see [`remote`](crate::buf::remote).
+  pub fn remote(&self) -> &Option<RemoteUrl> {
+    &self.remote
+  }
+
+  pub fn set_remote(&mut self, remote: Option<RemoteUrl>) {
+    self.remote = remote;
+  }
+
+  /// Get the directory listing, it's `None` if it's not a directory buffer, see
+  /// [`explorer`](crate::buf::explorer).
+  pub fn explorer(&self) -> &Option<DirListing> {
+    &self.explorer
+  }
+
+  pub fn explorer_mut(&mut self) -> &mut Option<DirListing> {
+    &mut self.explorer
+  }
+
   pub fn last_sync_time(&self) -> &Option<Instant> {
     &self.last_sync_time
   }
@@ -167,15 +386,20 @@ impl Buffer {
   /// [UnicodeWidthChar], there's another equivalent crate
   /// [icu::properties::EastAsianWidth](https://docs.rs/icu/latest/icu/properties/maps/fn.east_asian_width.html#).
   pub fn char_width(&self, c: char) -> usize {
+    // Fast path for the most common printable ASCII chars.
+    if c.is_ascii() && is_printable_ascii(c as u8) {
+      return 1;
+    }
     if c.is_ascii_control() {
       let ac = AsciiChar::from_ascii(c).unwrap();
       match ac {
         AsciiChar::Tab => self.tab_stop() as usize,
-        AsciiChar::LineFeed | AsciiChar::CarriageReturn => 0,
-        _ => {
-          let ascii_formatter = AsciiControlCodeFormatter::from(ac);
-          format!("{}", ascii_formatter).len()
-        }
+        AsciiChar::LineFeed => 0,
+        // NOTE: The line endings are normalized to `\n` for 'dos' and 'mac' file formats, the `\r`
+        // only remains in 'unix' file format (i.e. the mixed line endings), and is rendered as `^M`.
+        AsciiChar::CarriageReturn if self.file_format() != FileFormat::Unix => 0,
+        // The other control codes are rendered in the `^X` form, see [`AsciiControlCodeFormatter`].
+        _ => 2,
       }
     } else {
       UnicodeWidthChar::width_cjk(c).unwrap()
@@ -189,23 +413,173 @@ impl Buffer {
       let ac = AsciiChar::from_ascii(c).unwrap();
       match ac {
         AsciiChar::Tab => (
-          CompactString::from(" ".repeat(self.tab_stop() as usize)),
+          (0..self.tab_stop()).map(|_| ' ').collect::<CompactString>(),
           width,
         ),
-        AsciiChar::LineFeed | AsciiChar::CarriageReturn => (CompactString::new(""), width),
+        AsciiChar::LineFeed => (CompactString::new(""), width),
+        AsciiChar::CarriageReturn if width == 0 => (CompactString::new(""), width),
         _ => {
           let ascii_formatter = AsciiControlCodeFormatter::from(ac);
-          (CompactString::from(format!("{}", ascii_formatter)), width)
+          (format_compact!("{}", ascii_formatter), width)
         }
       }
     } else {
-      (CompactString::from(c.to_string()), width)
+      // NOTE: A single char is always stored inline, i.e. no heap allocation.
+      (c.to_compact_string(), width)
     }
   }
 
   /// Get the display width for a unicode `str`.
+  ///
+  /// The runs of printable ASCII chars are counted by bytes (see [`printable_ascii_len`]) before
+  /// falling back to the per-char [`char_width`](Buffer::char_width). A grapheme cluster (see
+  /// [`GraphemeScanner`]) takes the width of its first char, i.e. the rest chars in it are 0 width.
   pub fn str_width(&self, s: &str) -> usize {
-    s.chars().map(|c| self.char_width(c)).sum()
+    self.str_width_with(&mut GraphemeScanner::new(), s)
+  }
+
+  // Same with `str_width`, but continues from the `scanner`.
+  fn str_width_with(&self, scanner: &mut GraphemeScanner, s: &str) -> usize {
+    let mut width = 0_usize;
+    let mut rest = s;
+    while !rest.is_empty() {
+      let run = printable_ascii_len(rest.as_bytes());
+      width += scanner.skip_ascii(&rest[..run]);
+      rest = &rest[run..];
+      if let Some(c) = rest.chars().next() {
+        if scanner.advance(c) {
+          width += self.char_width(c);
+        }
+        rest = &rest[c.len_utf8()..];
+      }
+    }
+    width
+  }
+
+  /// Get the display width of all the chars before `char_idx` in the line `line_idx`, i.e. the
+  /// display column index where the char `char_idx` starts in the line.
+  ///
+  /// NOTE: Both `line_idx` and `char_idx` start from 0, and the `char_idx` is based on the line,
+  /// not the whole buffer. It returns 0 if the line doesn't exist.
+  pub fn width_before(&self, line_idx: usize, char_idx: usize) -> usize {
+    self.width_between(line_idx, 0..char_idx)
+  }
+
+  /// Get the display width of the chars in the `char_range` of the line `line_idx`, the range is
+  /// clamped to the line.
+  ///
+  /// NOTE: It only measures the chars inside the range, i.e. it's `O(range length)` no matter how
+  /// long the line is.
+  pub fn width_between(&self, line_idx: usize, char_range: Range<usize>) -> usize {
+    match self.rope.get_line(line_idx) {
+      Some(line) => {
+        let end = char_range.end.min(line.len_chars());
+        let start = char_range.start.min(end);
+        let mut scanner = scanner_before(&line, start);
+        line
+          .slice(start..end)
+          .chunks()
+          .map(|chunk| self.str_width_with(&mut scanner, chunk))
+          .sum()
+      }
+      None => 0,
+    }
+  }
+
+  /// Get the first grapheme cluster that starts at or after the display column `dcolumn` in the
+  /// line `line_idx`, returns its char index and its start display column.
+  ///
+  /// If the line is shorter than `dcolumn`, it returns the chars length and the display width of
+  /// the line.
+  pub fn char_at_dcolumn(&self, line_idx: usize, dcolumn: usize) -> (usize, usize) {
+    let mut char_idx = 0_usize;
+    let mut dcol = 0_usize;
+    let mut scanner = GraphemeScanner::new();
+    if let Some(line) = self.rope.get_line(line_idx) {
+      for chunk in line.chunks() {
+        let mut rest = chunk;
+        while !rest.is_empty() {
+          let run = printable_ascii_len(rest.as_bytes()).min(dcolumn.saturating_sub(dcol));
+          if run > 0 {
+            char_idx += run;
+            dcol += scanner.skip_ascii(&rest[..run]);
+            rest = &rest[run..];
+            continue;
+          }
+          let c = rest.chars().next().unwrap();
+          let boundary = scanner.advance(c);
+          if boundary && dcol >= dcolumn {
+            return (char_idx, dcol);
+          }
+          char_idx += 1;
+          if boundary {
+            dcol += self.char_width(c);
+          }
+          rest = &rest[c.len_utf8()..];
+        }
+      }
+    }
+    (char_idx, dcol)
+  }
+
+  /// Get the first char index of the grapheme cluster that contains the char `char_idx` in the
+  /// line `line_idx`.
+  ///
+  /// NOTE: It looks back at most [`GRAPHEME_MAX_CHARS`] chars, i.e. it's `O(1)` no matter how long
+  /// the line is.
+  pub fn grapheme_start(&self, line_idx: usize, char_idx: usize) -> usize {
+    match self.rope.get_line(line_idx) {
+      Some(line) if char_idx < line.len_chars() => {
+        let lookback = char_idx.saturating_sub(GRAPHEME_MAX_CHARS);
+        let mut scanner = if lookback > 0 {
+          GraphemeScanner::with_prev(line.char(lookback - 1))
+        } else {
+          GraphemeScanner::new()
+        };
+        let mut start = lookback;
+        for (i, c) in line.chars_at(lookback).enumerate() {
+          if i + lookback > char_idx {
+            break;
+          }
+          if scanner.advance(c) {
+            start = i + lookback;
+          }
+        }
+        start
+      }
+      _ => char_idx,
+    }
+  }
+
+  /// Get the first char index of the next grapheme cluster after the char `char_idx` in the line
+  /// `line_idx`, it's the chars length of the line if there's no more grapheme clusters.
+  pub fn next_grapheme(&self, line_idx: usize, char_idx: usize) -> usize {
+    match self.rope.get_line(line_idx) {
+      Some(line) if char_idx < line.len_chars() => {
+        let mut scanner = scanner_before(&line, char_idx + 1);
+        let mut next = char_idx + 1;
+        for c in line.chars_at(next) {
+          if scanner.advance(c) {
+            break;
+          }
+          next += 1;
+        }
+        next
+      }
+      Some(line) => line.len_chars(),
+      None => char_idx,
+    }
+  }
+
+  /// Get the first char index of the previous grapheme cluster before the char `char_idx` in the
+  /// line `line_idx`, it's 0 if there's no more grapheme clusters.
+  pub fn prev_grapheme(&self, line_idx: usize, char_idx: usize) -> usize {
+    let start = self.grapheme_start(line_idx, char_idx);
+    if start == 0 {
+      0
+    } else {
+      self.grapheme_start(line_idx, start - 1)
+    }
   }
 
   /// Get the printable cell symbols and the display width for a unicode `str`.
@@ -223,6 +597,13 @@ impl Buffer {
 
 // Rope {
 impl Buffer {
+  /// Get a snapshot of the text.
+  ///
+  /// NOTE: Cloning a rope is cheap, the unchanged chunks are shared with the buffer.
+  pub fn snapshot(&self) -> Rope {
+    self.rope.clone()
+  }
+
   // lines {
 
   /// Same with [`Rope::get_line`](Rope::get_line).
@@ -245,20 +626,497 @@ impl Buffer {
     self.rope.len_lines()
   }
 
+  /// Same with [`Rope::line_to_char`](Rope::line_to_char).
+  pub fn line_to_char(&self, line_idx: usize) -> usize {
+    self.rope.line_to_char(line_idx)
+  }
+
+  /// Get the line content without the line ending (`\n`, `\r\n` or `\r`).
+  pub fn get_line_content(&self, line_idx: usize) -> Option<String> {
+    self.rope.get_line(line_idx).map(|line| {
+      let mut content = line.to_string();
+      while content.ends_with('\n') || content.ends_with('\r') {
+        content.pop();
+      }
+      content
+    })
+  }
+
+  /// Replace the content of the line `line_idx` with `text`, the line ending is preserved.
+  ///
+  /// NOTE: The `text` should not contain any line ending.
+  ///
+  /// # Panics
+  ///
+  /// If the line doesn't exist.
+  pub fn replace_line_content(&mut self, line_idx: usize, text: &str) {
+    if self.is_read_only() {
+      trace!("Buffer {:?} is read-only, discard the edit", self.id);
+      return;
+    }
+    let old_content = self.get_line_content(line_idx).unwrap();
+    let start_char_idx = self.rope.line_to_char(line_idx);
+    let delta = Delta::new(start_char_idx, &old_content, text);
+    self.before_edit(&delta);
+    self
+      .rope
+      .remove(start_char_idx..start_char_idx + old_content.chars().count());
+    self.rope.insert(start_char_idx, text);
+    self.undo_tree.record(delta);
+    self.changed_tick += 1;
+  }
+
+  /// Replace the lines in range `[start_line_idx, end_line_idx)` with `lines`, i.e. the
+  /// `Rsvim.buf.setLines` API. The `end_line_idx` is clamped to the lines count, and empty range
+  /// inserts the `lines` before `start_line_idx`.
+  ///
+  /// It goes through [`remove`](Buffer::remove) and [`insert`](Buffer::insert) in an undo group,
+  /// i.e. it's recorded in the undo tree (and marks are adjusted) the same as interactive edits,
+  /// and it's undone at once.
+  ///
+  /// NOTE: The `lines` should not contain any line ending.
+  ///
+  /// Returns `false` if `start_line_idx` is out of range.
+  pub fn set_lines(
+    &mut self,
+    start_line_idx: usize,
+    end_line_idx: usize,
+    lines: &[String],
+  ) -> bool {
+    if self.is_read_only() {
+      trace!("Buffer {:?} is read-only, discard the edit", self.id);
+      return false;
+    }
+    let len_lines = self.rope.len_lines();
+    if start_line_idx > len_lines {
+      return false;
+    }
+    let end_line_idx = end_line_idx.clamp(start_line_idx, len_lines);
+    let start_char_idx = self.rope.line_to_char(start_line_idx);
+    let end_char_idx = self.rope.line_to_char(end_line_idx);
+
+    let mut text: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+    // The last line of buffer doesn't have a line break, keep it as it is.
+    let unterminated = |char_idx: usize| char_idx == 0 || self.rope.char(char_idx - 1) != '\n';
+    if !text.is_empty() && end_char_idx == self.rope.len_chars() {
+      if end_line_idx > start_line_idx {
+        // Replace the last line.
+        if end_char_idx == start_char_idx || unterminated(end_char_idx) {
+          text.pop();
+        }
+      } else if start_char_idx > 0 && unterminated(start_char_idx) {
+        // Append after the last line.
+        text.pop();
+        text.insert(0, '\n');
+      }
+    }
+
+    self.undo_tree.begin_group();
+    if end_char_idx > start_char_idx {
+      self.remove(start_char_idx..end_char_idx);
+    }
+    if !text.is_empty() {
+      self.insert(start_char_idx, &text);
+    }
+    self.undo_tree.end_group();
+    true
+  }
+
   // lines }
 
-  /// Alias to method [`Rope::write_to`](Rope::write_to).
-  pub fn write_to<T: std::io::Write>(&self, writer: T) -> std::io::Result<()> {
-    self.rope.write_to(writer)
+  // chars {
+
+  /// Same with [`Rope::len_chars`](Rope::len_chars).
+  pub fn len_chars(&self) -> usize {
+    self.rope.len_chars()
+  }
+
+  /// Same with [`Rope::get_char`](Rope::get_char).
+  pub fn get_char(&self, char_idx: usize) -> Option<char> {
+    self.rope.get_char(char_idx)
+  }
+
+  /// Same with [`Rope::char_to_line`](Rope::char_to_line).
+  pub fn char_to_line(&self, char_idx: usize) -> usize {
+    self.rope.char_to_line(char_idx)
+  }
+
+  /// Same with [`Rope::insert`](Rope::insert), the edit is recorded in the undo tree.
+  pub fn insert(&mut self, char_idx: usize, text: &str) {
+    if self.is_read_only() {
+      trace!("Buffer {:?} is read-only, discard the edit", self.id);
+      return;
+    }
+    let delta = Delta::new(char_idx, "", text);
+    self.before_edit(&delta);
+    self.rope.insert(char_idx, text);
+    self.undo_tree.record(delta);
+    self.changed_tick += 1;
+  }
+
+  /// Same with [`Rope::remove`](Rope::remove), the edit is recorded in the undo tree.
+  pub fn remove<R: RangeBounds<usize>>(&mut self, char_range: R) {
+    if self.is_read_only() {
+      trace!("Buffer {:?} is read-only, discard the edit", self.id);
+      return;
+    }
+    let start_char_idx = match char_range.start_bound() {
+      Bound::Included(i) => *i,
+      Bound::Excluded(i) => *i + 1,
+      Bound::Unbounded => 0,
+    };
+    let end_char_idx = match char_range.end_bound() {
+      Bound::Included(i) => *i + 1,
+      Bound::Excluded(i) => *i,
+      Bound::Unbounded => self.rope.len_chars(),
+    };
+    let removed = self.rope.slice(start_char_idx..end_char_idx).to_string();
+    let delta = Delta::new(start_char_idx, &removed, "");
+    self.before_edit(&delta);
+    self.rope.remove(start_char_idx..end_char_idx);
+    self.undo_tree.record(delta);
+    self.changed_tick += 1;
+  }
+
+  // chars }
+
+  /// Similar to method [`Rope::write_to`](Rope::write_to), but the line endings are converted
+  /// to the 'file-format' option, and the text is encoded with the 'file-encoding' option (with
+  /// BOM if it has).
+  ///
+  /// Returns the count of the chars which cannot be encoded, see
+  /// [`FileEncoding::encode`](FileEncoding::encode).
+  pub fn write_to<T: std::io::Write>(&self, mut writer: T) -> std::io::Result<usize> {
+    let file_format = self.file_format();
+    let file_encoding = self.file_encoding();
+    writer.write_all(file_encoding.bom())?;
+    let mut lossy = 0;
+    for chunk in self.rope.chunks() {
+      let text = file_format.denormalize(chunk);
+      let (bytes, n) = file_encoding.encode(&text);
+      writer.write_all(&bytes)?;
+      lossy += n;
+    }
+    Ok(lossy)
+  }
+
+  /// The count of the chars which cannot be encoded with the 'file-encoding' option.
+  pub fn unencodable_chars(&self) -> usize {
+    let file_encoding = self.file_encoding();
+    self
+      .rope
+      .chunks()
+      .map(|chunk| file_encoding.count_unencodable(chunk))
+      .sum()
   }
 
-  /// Alias to method [`Rope::append`](Rope::append).
+  /// Alias to method [`Rope::append`](Rope::append), the edit is recorded in the undo tree.
   pub fn append(&mut self, other: Rope) {
-    self.rope.append(other)
+    if self.is_read_only() {
+      trace!("Buffer {:?} is read-only, discard the edit", self.id);
+      return;
+    }
+    let char_idx = self.rope.len_chars();
+    let delta = Delta::new(char_idx, "", &other.to_string());
+    self.before_edit(&delta);
+    self.rope.append(other);
+    self.undo_tree.record(delta);
+    self.changed_tick += 1;
+  }
+
+  // Adjust the marks, signs and extmarks and set the `.` mark, before the edit is applied.
+  fn before_edit(&mut self, delta: &Delta) {
+    self.marks.adjust_by_delta(&self.rope, delta);
+    self.line_marks.adjust_by_delta(&self.rope, delta);
+    self.signs.adjust_by_delta(&self.rope, delta);
+    self.extmarks.adjust_by_delta(delta);
+    let line_idx = self.rope.char_to_line(delta.char_idx);
+    let char_idx = delta.char_idx - self.rope.line_to_char(line_idx);
+    self.marks.set('.', MarkPos::new(line_idx, char_idx));
+  }
+
+  /// Append the loaded `text` to the end of buffer, it's not recorded in the undo tree, i.e. the
+  /// buffer is not modified.
+  pub fn append_loaded(&mut self, text: &str) {
+    self.rope.insert(self.rope.len_chars(), text);
+    self.changed_tick += 1;
+  }
+
+  /// Replace the whole text with the loaded `text`, it's not recorded in the undo tree, i.e. the
+  /// buffer is not modified.
+  pub fn replace_loaded(&mut self, text: &str) {
+    self.rope = Rope::from_str(text);
+    self.changed_tick += 1;
   }
 }
 // Rope }
 
+// Undo {
+impl Buffer {
+  pub fn undo_tree(&self) -> &UndoTree {
+    &self.undo_tree
+  }
+
+  pub fn undo_tree_mut(&mut self) -> &mut UndoTree {
+    &mut self.undo_tree
+  }
+
+  /// Undo the last change, returns the char index where the change happened, or `None` if
+  /// there's nothing to undo.
+  pub fn undo(&mut self) -> Option<usize> {
+    if self.is_read_only() {
+      trace!("Buffer {:?} is read-only, discard the edit", self.id);
+      return None;
+    }
+    self.changed_tick += 1;
+    let (marks, line_marks, signs, extmarks) = (
+      &mut self.marks,
+      &mut self.line_marks,
+      &mut self.signs,
+      &mut self.extmarks,
+    );
+    self
+      .undo_tree
+      .undo_with(&mut self.rope, &mut |rope, delta| {
+        marks.adjust_by_delta(rope, delta);
+        line_marks.adjust_by_delta(rope, delta);
+        signs.adjust_by_delta(rope, delta);
+        extmarks.adjust_by_delta(delta);
+      })
+  }
+
+  /// Redo the last undone change, returns the char index where the change happened, or `None` if
+  /// there's nothing to redo.
+  pub fn redo(&mut self) -> Option<usize> {
+    if self.is_read_only() {
+      trace!("Buffer {:?} is read-only, discard the edit", self.id);
+      return None;
+    }
+    self.changed_tick += 1;
+    let (marks, line_marks, signs, extmarks) = (
+      &mut self.marks,
+      &mut self.line_marks,
+      &mut self.signs,
+      &mut self.extmarks,
+    );
+    self
+      .undo_tree
+      .redo_with(&mut self.rope, &mut |rope, delta| {
+        marks.adjust_by_delta(rope, delta);
+        line_marks.adjust_by_delta(rope, delta);
+        signs.adjust_by_delta(rope, delta);
+        extmarks.adjust_by_delta(delta);
+      })
+  }
+
+  pub fn undo_file(&self) -> &Option<UndoFile> {
+    &self.undo_file
+  }
+
+  pub fn undo_file_mut(&mut self) -> &mut Option<UndoFile> {
+    &mut self.undo_file
+  }
+
+  /// Attach the undo file to the newly loaded buffer. The undo history is restored from it if the
+  /// text is at the saved state. Returns whether the undo history is restored.
+  ///
+  /// NOTE: Nothing is written until the buffer is saved after a change.
+  pub fn attach_undo_file(&mut self, mut undo_file: UndoFile) -> IoResult<bool> {
+    let restored = match undo_file.load(&self.rope)? {
+      Some(journal) => {
+        let seq = journal.seq;
+        let restored = self.undo_tree.restore(journal.states, seq);
+        if restored {
+          self.saved_seq = seq;
+          undo_file.set_synced();
+        }
+        restored
+      }
+      None => false,
+    };
+    self.undo_file = Some(undo_file);
+    Ok(restored)
+  }
+
+  /// Whether there're undo states not flushed to the undo file (if has).
+  ///
+  /// NOTE: The states are only appended to a synced journal, see
+  /// [`UndoFile::is_synced`](UndoFile::is_synced).
+  pub fn undo_flush_pending(&self) -> bool {
+    self
+      .undo_file
+      .as_ref()
+      .is_some_and(|undo_file| undo_file.is_synced())
+      && !self.undo_tree.unflushed().is_empty()
+  }
+
+  /// Flush the undo states created since last flush to the undo file (if has).
+  pub fn flush_undo_file(&mut self) -> IoResult<()> {
+    match self.undo_file.as_mut() {
+      Some(undo_file) if undo_file.is_synced() => self.undo_tree.flush(undo_file),
+      _ => Ok(()),
+    }
+  }
+
+  // Compact the undo file (if has) with the whole undo history at the saved state. Nothing is
+  // written if there's no change.
+  //
+  // NOTE: The text is only known at current state, thus the other saved state (i.e. the
+  // background write is done after more edits) is not recorded.
+  fn save_undo_point(&mut self) {
+    let undo_file = match self.undo_file.as_mut() {
+      Some(undo_file) => undo_file,
+      None => return,
+    };
+    if self.saved_seq != self.undo_tree.current()
+      || self.undo_tree.has_pending()
+      || self.undo_tree.states().len() <= 1
+    {
+      return;
+    }
+    match undo_file.compact(self.saved_seq, &self.rope, self.undo_tree.states()) {
+      Ok(()) => self.undo_tree.set_flushed(),
+      Err(e) => error!("Failed to save undo file of buffer {:?}:{:?}", self.id, e),
+    }
+  }
+
+  /// Go to the undo state `seq` (in any branch), returns `false` if the state doesn't exist.
+  pub fn undo_goto(&mut self, seq: usize) -> bool {
+    if self.is_read_only() {
+      trace!("Buffer {:?} is read-only, discard the edit", self.id);
+      return false;
+    }
+    self.changed_tick += 1;
+    let (marks, line_marks, signs, extmarks) = (
+      &mut self.marks,
+      &mut self.line_marks,
+      &mut self.signs,
+      &mut self.extmarks,
+    );
+    self
+      .undo_tree
+      .goto_with(seq, &mut self.rope, &mut |rope, delta| {
+        marks.adjust_by_delta(rope, delta);
+        line_marks.adjust_by_delta(rope, delta);
+        signs.adjust_by_delta(rope, delta);
+        extmarks.adjust_by_delta(delta);
+      })
+  }
+}
+// Undo }
+
+// Marks {
+impl Buffer {
+  pub fn marks(&self) -> &Marks {
+    &self.marks
+  }
+
+  pub fn marks_mut(&mut self) -> &mut Marks {
+    &mut self.marks
+  }
+
+  /// The marked lines of `:global`, see [`LineMarks`].
+  pub fn line_marks(&self) -> &LineMarks {
+    &self.line_marks
+  }
+
+  pub fn line_marks_mut(&mut self) -> &mut LineMarks {
+    &mut self.line_marks
+  }
+}
+// Marks }
+
+// Signs {
+impl Buffer {
+  pub fn signs(&self) -> &Signs {
+    &self.signs
+  }
+
+  pub fn signs_mut(&mut self) -> &mut Signs {
+    &mut self.signs
+  }
+}
+// Signs }
+
+// Extmarks {
+impl Buffer {
+  pub fn extmarks(&self) -> &Extmarks {
+    &self.extmarks
+  }
+
+  pub fn extmarks_mut(&mut self) -> &mut Extmarks {
+    &mut self.extmarks
+  }
+
+  /// Convert the position to char index, it's clamped to the line (without line ending) and the
+  /// buffer.
+  pub fn pos_to_char(&self, pos: MarkPos) -> usize {
+    if pos.line_idx >= self.rope.len_lines() {
+      return self.rope.len_chars();
+    }
+    let len_chars = self
+      .get_line_content(pos.line_idx)
+      .map(|line| line.chars().count())
+      .unwrap_or(0);
+    self.rope.line_to_char(pos.line_idx) + pos.char_idx.min(len_chars)
+  }
+
+  /// Convert the char index to position, it's clamped to the buffer.
+  pub fn char_to_pos(&self, char_idx: usize) -> MarkPos {
+    let char_idx = char_idx.min(self.rope.len_chars());
+    let line_idx = self.rope.char_to_line(char_idx);
+    MarkPos::new(line_idx, char_idx - self.rope.line_to_char(line_idx))
+  }
+
+  /// Get the extmark decorations of the line index `lines` range, see
+  /// [`Extmarks::decorations`].
+  pub fn extmark_decorations(&self, lines: Range<usize>) -> ExtmarkDecorations {
+    self.extmarks.decorations(&self.rope, lines)
+  }
+}
+// Extmarks }
+
+// Highlight {
+impl Buffer {
+  pub fn highlights(&self) -> &HighlightNamespaces {
+    &self.highlights
+  }
+
+  pub fn highlights_mut(&mut self) -> &mut HighlightNamespaces {
+    &mut self.highlights
+  }
+}
+// Highlight }
+
+// Explorer {
+impl Buffer {
+  /// Refresh the text and the highlights of the directory buffer from its listing, the directory
+  /// is re-read if `reread`, otherwise it's only re-rendered, i.e. the sorting is changed. Returns
+  /// `Ok(false)` if it's not a directory buffer.
+  pub fn refresh_explorer(&mut self, reread: bool) -> IoResult<bool> {
+    let listing = match self.explorer.as_mut() {
+      Some(listing) => listing,
+      None => return Ok(false),
+    };
+    if reread {
+      listing.read()?;
+    }
+    let text = listing.text();
+    let highlights = listing.highlights();
+    self.replace_loaded(&text);
+    let ns_id = self.highlights.create(
+      explorer::NAMESPACE,
+      crate::defaults::highlight::EXPLORER_PRIORITY,
+    );
+    self.highlights.clear(ns_id, 0..usize::MAX);
+    for (line_idx, range, style) in highlights {
+      self.highlights.add(ns_id, line_idx, range, style);
+    }
+    Ok(true)
+  }
+}
+// Explorer }
+
 // Options {
 impl Buffer {
   pub fn options(&self) -> &BufferLocalOptions {
@@ -267,6 +1125,10 @@ impl Buffer {
 
   pub fn set_options(&mut self, options: &BufferLocalOptions) {
     self.options = options.clone();
+    self
+      .undo_tree
+      .set_limits(options.undo_levels(), options.undo_memory());
+    self.changed_tick += 1;
   }
 
   pub fn tab_stop(&self) -> u16 {
@@ -275,6 +1137,33 @@ impl Buffer {
 
   pub fn set_tab_stop(&mut self, value: u16) {
     self.options.set_tab_stop(value);
+    self.changed_tick += 1;
+  }
+
+  pub fn file_format(&self) -> FileFormat {
+    self.options.file_format()
+  }
+
+  pub fn set_file_format(&mut self, value: FileFormat) {
+    self.options.set_file_format(value);
+    self.changed_tick += 1;
+  }
+
+  pub fn file_encoding(&self) -> FileEncoding {
+    self.options.file_encoding()
+  }
+
+  pub fn set_file_encoding(&mut self, value: FileEncoding) {
+    self.options.set_file_encoding(value);
+    self.changed_tick += 1;
+  }
+
+  /// Set the 'undolevels' and 'undomemory' options, the exceeded undo states are evicted
+  /// immediately.
+  pub fn set_undo_limits(&mut self, undo_levels: usize, undo_memory: usize) {
+    self.options.set_undo_levels(undo_levels);
+    self.options.set_undo_memory(undo_memory);
+    self.undo_tree.set_limits(undo_levels, undo_memory);
   }
 }
 // Options }
@@ -298,8 +1187,56 @@ pub struct BuffersManager {
   // Buffers maps by absolute file path.
   buffers_by_path: HashMap<Option<PathBuf>, BufferArc>,
 
+  // Scratch buffers, maps to their source buffers (if has).
+  scratch_sources: BTreeMap<BufferId, Option<BufferId>>,
+
+  // Argument list.
+  arglist: ArgList,
+
+  // Quickfix list.
+  quickfix: QuickfixList,
+
+  // The scratch buffer of the quickfix window, i.e. `:copen`.
+  quickfix_buffer: Option<BufferId>,
+
+  // The scratch buffer of the quickfix preview, and the absolute path of the file loaded in it.
+  preview_buffer: Option<(BufferId, PathBuf)>,
+
   // Local options for buffers.
   local_options: BufferLocalOptions,
+
+  // Global options for writing buffers.
+  write_options: WriteOptions,
+
+  // Last positions of the recent files.
+  positions: PositionStore,
+
+  // User spell dictionary.
+  spell: SpellDictionary,
+
+  // Current named session, it's saved on exit, see [`session`](crate::state::session).
+  session: Option<String>,
+
+  // Templates for new files.
+  templates: Templates,
+
+  // Filetype detection rules and option bundles.
+  file_types: FileTypes,
+
+  // The buffers that are read since last taken, i.e. the `BufReadPost` event.
+  read_buffers: Vec<BufferId>,
+
+  // The buffers that are detected filetype since last taken, i.e. the `FileType` event.
+  typed_buffers: Vec<BufferId>,
+
+  // File system provider.
+  fs: FsProviderArc,
+
+  // The directory of the undo files, see [`undofile`](crate::buf::undofile).
+  undo_dir: Option<PathBuf>,
+
+  // The 'undofile' option.
+  undo_file: bool,
 }
 
 impl BuffersManager {
@@ -307,19 +1244,69 @@ impl BuffersManager {
     BuffersManager {
       buffers: BTreeMap::new(),
       buffers_by_path: HashMap::new(),
+      scratch_sources: BTreeMap::new(),
+      arglist: ArgList::default(),
+      quickfix: QuickfixList::default(),
+      quickfix_buffer: None,
+      preview_buffer: None,
       local_options: BufferLocalOptions::default(),
+      write_options: WriteOptions::default(),
+      positions: PositionStore::new(),
+      spell: SpellDictionary::new(),
+      session: None,
+      templates: Templates::new(),
+      file_types: FileTypes::new(),
+      read_buffers: vec![],
+      typed_buffers: vec![],
+      fs: Arc::new(StdFs),
+      undo_dir: None,
+      undo_file: crate::defaults::buf::UNDO_FILE,
     }
   }
 
-  pub fn to_arc(b: BuffersManager) -> BuffersManagerArc {
-    Arc::new(RwLock::new(b))
+  /// Get the file system provider.
+  pub fn fs(&self) -> &FsProviderArc {
+    &self.fs
   }
 
-  /// Open a file with a newly created buffer.
+  /// Set the file system provider, it's used by the buffers opened later.
+  pub fn set_fs(&mut self, fs: FsProviderArc) {
+    self.fs = fs;
+  }
+
+  /// Get the directory of the undo files.
+  pub fn undo_dir(&self) -> &Option<PathBuf> {
+    &self.undo_dir
+  }
+
+  /// Set the directory of the undo files, it's used by the buffers opened later. The undo history
+  /// is not saved if it's `None`, see [`undofile`](crate::buf::undofile).
+  pub fn set_undo_dir(&mut self, undo_dir: Option<PathBuf>) {
+    self.undo_dir = undo_dir;
+  }
+
+  /// The 'undofile' option, save the undo history of the file buffers under the undo directory.
+  /// It's used by the buffers opened later.
+  pub fn undo_file(&self) -> bool {
+    self.undo_file
+  }
+
+  pub fn set_undo_file(&mut self, value: bool) {
+    self.undo_file = value;
+  }
+
+  pub fn to_arc(b: BuffersManager) -> BuffersManagerArc {
+    Arc::new(RwLock::new(b))
+  }
+
+  /// Open a file with a newly created buffer.
   ///
-  /// The file name must be unique and not existed, there are two use cases:
+  /// The file name must be unique and not existed, there are three use cases:
   /// 1. If the file exists on filesystem, the buffer will read the file contents into buffer.
-  /// 2. If the file doesn't exist, the buffer will be empty but only set the file name.
+  /// 2. If the file doesn't exist, the buffer will be empty but only set the file name. Then it's
+  ///    populated from the matched template (if has), see [`template`](crate::buf::template).
+  /// 3. If it's a directory, the buffer is the read-only directory listing, see
+  ///    [`explorer`](crate::buf::explorer).
   ///
   /// # Returns
   ///
@@ -333,8 +1320,8 @@ impl BuffersManager {
   ///
   /// NOTE: This is a primitive API.
   pub fn new_file_buffer(&mut self, filename: &Path) -> IoResult<BufferId> {
-    let abs_filename = match filename.absolutize() {
-      Ok(abs_filename) => abs_filename.to_path_buf(),
+    let abs_filename = match absolute_path(filename) {
+      Ok(abs_filename) => abs_filename,
       Err(e) => {
         trace!("Failed to absolutize filepath {:?}:{:?}", filename, e);
         return Err(e);
@@ -345,7 +1332,12 @@ impl BuffersManager {
       .buffers_by_path
       .contains_key(&Some(abs_filename.clone())));
 
-    let existed = match std::fs::exists(abs_filename.clone()) {
+    // NOTE: The directories are always listed from the local file system.
+    if abs_filename.is_dir() {
+      return self.new_directory_buffer(filename, &abs_filename);
+    }
+
+    let existed = match self.fs.exists(&abs_filename) {
       Ok(existed) => existed,
       Err(e) => {
         trace!("Failed to detect file {:?}:{:?}", filename, e);
@@ -355,21 +1347,31 @@ impl BuffersManager {
 
     let buf = if existed {
       match self.edit_file(filename, &abs_filename) {
-        Ok(buf) => buf,
+        Ok(mut buf) => {
+          self.read_buffers.push(buf.id());
+          if let Some(pos) = self.positions.get(&abs_filename) {
+            buf.marks_mut().set('"', pos.cursor);
+          }
+          self.attach_undo_file(&mut buf, &abs_filename);
+          buf
+        }
         Err(e) => {
           return Err(e);
         }
       }
     } else {
-      Buffer::_new(
+      let mut buf = Buffer::_new(
         Rope::new(),
-        self.local_options().clone(),
+        self.file_options(&abs_filename),
         Some(filename.to_path_buf()),
         Some(abs_filename.clone()),
         None,
         None,
-      )
+      );
+      self.on_buf_new_file(&mut buf, &abs_filename);
+      buf
     };
+    self.on_file_type(&buf);
 
     let buf_id = buf.id();
     let buf = Buffer::to_arc(buf);
@@ -378,6 +1380,111 @@ impl BuffersManager {
     Ok(buf_id)
   }
 
+  // Open a directory with a newly created directory buffer, see
+  // [`explorer`](crate::buf::explorer).
+  fn new_directory_buffer(&mut self, filename: &Path, abs_filename: &Path) -> IoResult<BufferId> {
+    let mut listing = DirListing::new(abs_filename, ExplorerSort::default(), false);
+    listing.read()?;
+    let mut options = self.local_options().clone();
+    options.set_file_type(explorer::FILE_TYPE);
+    let mut buf = Buffer::_new(
+      Rope::new(),
+      options,
+      Some(filename.to_path_buf()),
+      Some(abs_filename.to_path_buf()),
+      None,
+      Some(Instant::now()),
+    );
+    *buf.explorer_mut() = Some(listing);
+    buf.refresh_explorer(false)?;
+    buf.set_readonly(true);
+    self.on_file_type(&buf);
+
+    let buf_id = buf.id();
+    let buf = Buffer::to_arc(buf);
+    self.buffers.insert(buf_id, buf.clone());
+    self
+      .buffers_by_path
+      .insert(Some(abs_filename.to_path_buf()), buf);
+    Ok(buf_id)
+  }
+
+  /// Open a huge file with a newly created buffer, the buffer is empty and read-only (see
+  /// [`Buffer::is_loading`]) until the file is loaded in background.
+  ///
+  /// # Returns
+  ///
+  /// It returns the buffer ID, the file reader and the file size in bytes.
+  ///
+  /// # Panics
+  ///
+  /// If the file name already exists.
+  ///
+  /// NOTE: This is a primitive API.
+  pub fn new_loading_file_buffer(
+    &mut self,
+    filename: &Path,
+  ) -> IoResult<(BufferId, Box<dyn std::io::Read + Send>, u64)> {
+    let abs_filename = absolute_path(filename)?;
+    assert!(!self
+      .buffers_by_path
+      .contains_key(&Some(abs_filename.clone())));
+
+    let total_bytes = self.fs.file_size(&abs_filename)?;
+    let reader = self.fs.open(&abs_filename)?;
+    let mut buf = Buffer::_new(
+      Rope::new(),
+      self.file_options(&abs_filename),
+      Some(filename.to_path_buf()),
+      Some(abs_filename.clone()),
+      None,
+      Some(Instant::now()),
+    );
+    buf.set_loading(true);
+    self.on_file_type(&buf);
+
+    let buf_id = buf.id();
+    let buf = Buffer::to_arc(buf);
+    self.buffers.insert(buf_id, buf.clone());
+    self.buffers_by_path.insert(Some(abs_filename), buf);
+    Ok((buf_id, reader, total_bytes))
+  }
+
+  /// Open a remote file with a newly created buffer, the buffer is empty and read-only (see
+  /// [`Buffer::is_loading`]) until the file is fetched in background, see
+  /// [`remote`](crate::buf::remote). The http file is still read-only after it's fetched.
+  ///
+  /// The buffer's file name is the URL.
+  ///
+  /// # Panics
+  ///
+  /// If the URL already exists.
+  ///
+  /// NOTE: This is a primitive API.
+  pub fn new_remote_buffer(&mut self, url: RemoteUrl) -> BufferId {
+    let filename = PathBuf::from(url.url());
+    assert!(!self.buffers_by_path.contains_key(&Some(filename.clone())));
+
+    let mut buf = Buffer::_new(
+      Rope::new(),
+      self.file_options(&filename),
+      Some(filename.clone()),
+      Some(filename.clone()),
+      None,
+      Some(Instant::now()),
+    );
+    buf.set_loading(true);
+    buf.set_readonly(!url.is_writable());
+    buf.set_remote(Some(url));
+    self.on_file_type(&buf);
+
+    let buf_id = buf.id();
+    let buf = Buffer::to_arc(buf);
+    self.buffers.insert(buf_id, buf.clone());
+    self.buffers_by_path.insert(Some(filename), buf);
+    buf_id
+  }
+
   /// Create new empty buffer without file name.
   ///
   /// The file name of this buffer is empty, i.e. the buffer is unnamed.
@@ -408,67 +1515,315 @@ impl BuffersManager {
     self.buffers_by_path.insert(None, buf);
     buf_id
   }
+
+  /// Create new scratch buffer with the `rope` text, i.e. the buffers for `:DiffOrig`, it's
+  /// unnamed and not associated with any file.
+  ///
+  /// The `source` is the buffer that the scratch buffer is generated from (if has).
+  ///
+  /// NOTE: Scratch buffers are not counted as the unnamed buffer, thus there can be multiple
+  /// scratch buffers.
+  pub fn new_scratch_buffer(&mut self, rope: Rope, source: Option<BufferId>) -> BufferId {
+    let mut buf = Buffer::_new(rope, self.local_options().clone(), None, None, None, None);
+    buf.set_listed(false);
+    let buf_id = buf.id();
+    self.buffers.insert(buf_id, Buffer::to_arc(buf));
+    self.scratch_sources.insert(buf_id, source);
+    buf_id
+  }
+
+  /// Find the file buffer by its file name.
+  pub fn find_by_path(&self, filename: &Path) -> Option<&BufferArc> {
+    let abs_filename = absolute_path(filename).ok()?;
+    self.buffers_by_path.get(&Some(abs_filename))
+  }
+
+  /// Find the unnamed buffer, see [`new_empty_buffer`](Self::new_empty_buffer).
+  pub fn find_unnamed(&self) -> Option<&BufferArc> {
+    self.buffers_by_path.get(&None)
+  }
+
+  /// Find the remote file buffer by its URL, see [`new_remote_buffer`](Self::new_remote_buffer).
+  pub fn find_by_url(&self, url: &str) -> Option<&BufferArc> {
+    self.buffers_by_path.get(&Some(PathBuf::from(url)))
+  }
+
+  /// Rename the file buffer `id` to `filename`, i.e. `:Rename`. The file is moved on disk (the
+  /// parent directories are created as needed) if it exists, and then the buffer's file name is
+  /// updated. The buffer is kept, thus the undo history is preserved.
+  ///
+  /// # Errors
+  ///
+  /// If the buffer doesn't have a file, or it's a remote file, or `filename` is opened by another
+  /// buffer, or it exists on disk (unless `force`), or it fails to move the file.
+  pub fn rename_buffer(&mut self, id: &BufferId, filename: &Path, force: bool) -> IoResult<()> {
+    let abs_filename = absolute_path(filename)?;
+    let buf = match self.buffers.get(id) {
+      Some(buf) => buf.clone(),
+      None => return Err(IoErr::new(IoErrKind::NotFound, "Buffer not found")),
+    };
+    let old_abs_filename = {
+      let buf = rlock!(buf);
+      match buf.absolute_filename() {
+        Some(old) if buf.remote().is_none() => old.clone(),
+        _ => {
+          return Err(IoErr::new(
+            IoErrKind::Unsupported,
+            "Buffer doesn't have a local file",
+          ))
+        }
+      }
+    };
+    if old_abs_filename == abs_filename {
+      return Ok(());
+    }
+    if self
+      .buffers_by_path
+      .contains_key(&Some(abs_filename.clone()))
+    {
+      return Err(IoErr::new(
+        IoErrKind::AlreadyExists,
+        format!("{:?} is opened by another buffer", abs_filename),
+      ));
+    }
+    if self.fs.exists(&abs_filename)? && !force {
+      return Err(IoErr::new(
+        IoErrKind::AlreadyExists,
+        format!("{:?} exists", abs_filename),
+      ));
+    }
+
+    if self.fs.exists(&old_abs_filename)? {
+      if let Some(parent) = abs_filename.parent() {
+        self.fs.create_dir_all(parent)?;
+      }
+      // The rename fails across file systems, thus it's copied and removed.
+      if let Err(e) = self.fs.rename(&old_abs_filename, &abs_filename) {
+        trace!(
+          "Failed to rename {:?} to {:?}:{:?}",
+          old_abs_filename,
+          abs_filename,
+          e
+        );
+        self.fs.copy(&old_abs_filename, &abs_filename)?;
+        self.fs.remove_file(&old_abs_filename)?;
+      }
+    }
+
+    {
+      let mut buf = wlock!(buf);
+      buf.set_filename(Some(filename.to_path_buf()));
+      buf.set_absolute_filename(Some(abs_filename.clone()));
+      buf.set_metadata(self.fs.metadata(&abs_filename).ok().flatten());
+      // The undo file follows the file.
+      if let (Some(undo_dir), Some(undo_file)) = (&self.undo_dir, buf.undo_file_mut()) {
+        let path = undofile::undo_file_path(undo_dir, &abs_filename);
+        if let Err(e) = self.fs.rename(undo_file.path(), &path) {
+          trace!("Failed to rename undo file {:?}:{:?}", undo_file.path(), e);
+        }
+        undo_file.set_path(path);
+      }
+    }
+    self.buffers_by_path.remove(&Some(old_abs_filename));
+    self.buffers_by_path.insert(Some(abs_filename), buf);
+    Ok(())
+  }
+
+  /// Wipe out the buffer `id`, i.e. it's removed from the manager and no longer associated with
+  /// its file. The windows showing it should be switched to other buffers before wiping.
+  pub fn wipe_buffer(&mut self, id: &BufferId) -> Option<BufferArc> {
+    let buf = self.remove(id)?;
+    let key = rlock!(buf).absolute_filename().clone();
+    if self
+      .buffers_by_path
+      .get(&key)
+      .is_some_and(|b| Arc::ptr_eq(b, &buf))
+    {
+      self.buffers_by_path.remove(&key);
+    }
+    Some(buf)
+  }
+
+  /// Get the buffer list sorted by buffer ID, i.e. `:ls`. The unlisted buffers are included only
+  /// if `all`, i.e. `:ls!`.
+  pub fn list(&self, all: bool) -> Vec<BufferInfo> {
+    self
+      .buffers
+      .values()
+      .filter_map(|buf| {
+        let buf = rlock!(buf);
+        if !all && !buf.is_listed() {
+          return None;
+        }
+        Some(BufferInfo {
+          id: buf.id(),
+          name: buf.display_name(),
+          listed: buf.is_listed(),
+          modified: buf.is_modified(),
+        })
+      })
+      .collect()
+  }
+
+  /// Get the `count`th next (or previous if not `forward`) listed buffer of the buffer `id`, i.e.
+  /// `:bnext`/`:bprevious`, it wraps around the buffer list. The buffer `id` itself doesn't need to
+  /// be listed.
+  ///
+  /// Returns `None` if there's no listed buffer.
+  pub fn next_listed(&self, id: BufferId, count: usize, forward: bool) -> Option<BufferId> {
+    let listed: Vec<BufferId> = self
+      .buffers
+      .iter()
+      .filter(|(_, buf)| rlock!(buf).is_listed())
+      .map(|(id, _)| *id)
+      .collect();
+    if listed.is_empty() {
+      return None;
+    }
+    let len = listed.len();
+    let count = count % len;
+    // The position of `id` in the list, or where it would be inserted.
+    let idx = match listed.binary_search(&id) {
+      Ok(idx) => idx as isize,
+      Err(idx) if forward => idx as isize - 1,
+      Err(idx) => idx as isize,
+    };
+    let next = if forward {
+      idx + count as isize
+    } else {
+      idx - count as isize
+    };
+    Some(listed[next.rem_euclid(len as isize) as usize])
+  }
+
+  /// Whether the buffer is a scratch buffer.
+  pub fn is_scratch(&self, id: &BufferId) -> bool {
+    self.scratch_sources.contains_key(id)
+  }
+
+  /// Get the source buffer of a scratch buffer.
+  pub fn scratch_source(&self, id: &BufferId) -> Option<BufferId> {
+    self.scratch_sources.get(id).copied().flatten()
+  }
 }
 
 // Primitive APIs {
 
 impl BuffersManager {
-  fn to_rope(&self, buf: &[u8], bufsize: usize) -> Rope {
-    let bufstr = self.to_str(buf, bufsize);
+  // Detect the encoding and line endings, and normalize them to UTF-8 and `\n`. The files without
+  // line endings use the default 'fileformat'.
+  fn to_rope(&self, buf: &[u8], bufsize: usize) -> (Rope, FileFormat, FileEncoding) {
+    let (bufstr, file_encoding) = self.to_str(buf, bufsize);
+    let file_format = FileFormat::detect_or(&bufstr, self.local_options().file_format());
     let mut block = RopeBuilder::new();
-    block.append(&bufstr.to_owned());
-    block.finish()
+    block.append(&file_format.normalize(&bufstr));
+    (block.finish(), file_format, file_encoding)
+  }
+
+  // Detect the encoding and decode to UTF-8.
+  fn to_str(&self, buf: &[u8], bufsize: usize) -> (String, FileEncoding) {
+    let file_encoding = FileEncoding::detect(&buf[0..bufsize]);
+    (file_encoding.decode(&buf[0..bufsize]), file_encoding)
+  }
+
+  // The local options for the file buffer, i.e. with the option bundle of its filetype.
+  fn file_options(&self, absolute_filename: &Path) -> BufferLocalOptions {
+    let mut options = self.local_options().clone();
+    if let Some(file_type) = self.file_types.apply(absolute_filename, &mut options) {
+      trace!(
+        "Detect filetype {:?} for file {:?}",
+        file_type,
+        absolute_filename
+      );
+    }
+    options
+  }
+
+  // Record the buffer is detected filetype, i.e. the `FileType` event.
+  fn on_file_type(&mut self, buf: &Buffer) {
+    if !buf.options().file_type().is_empty() {
+      self.typed_buffers.push(buf.id());
+    }
   }
 
-  fn to_str(&self, buf: &[u8], bufsize: usize) -> String {
-    let fencoding = self.local_options().file_encoding();
-    match fencoding {
-      FileEncoding::Utf8 => String::from_utf8_lossy(&buf[0..bufsize]).into_owned(),
+  // Populate the new file buffer from the first existing template, i.e. the `BufNewFile` event.
+  // The template text is recorded in the undo tree, thus the buffer is modified.
+  fn on_buf_new_file(&self, buf: &mut Buffer, absolute_filename: &Path) {
+    for template in self.templates.candidates(absolute_filename) {
+      if !self.fs.exists(&template).unwrap_or(false) {
+        continue;
+      }
+      match self.fs.read(&template) {
+        Ok((bytes, _metadata)) => {
+          let (text, _) = self.to_str(&bytes, bytes.len());
+          let text = FileFormat::detect(&text).normalize(&text);
+          let text = template::expand(&text, &self.templates.variables(absolute_filename));
+          trace!(
+            "Populate {:?} from template {:?}",
+            absolute_filename,
+            template
+          );
+          buf.insert(0, &text);
+          return;
+        }
+        Err(e) => {
+          trace!("Failed to read template {:?}:{:?}", template, e);
+        }
+      }
+    }
+  }
+
+  // Attach the undo file to the buffer loaded from file, if the 'undofile' option is on and the
+  // undo directory is set.
+  fn attach_undo_file(&self, buf: &mut Buffer, abs_filename: &Path) {
+    let undo_dir = match &self.undo_dir {
+      Some(undo_dir) if self.undo_file => undo_dir,
+      _ => return,
+    };
+    let undo_file = UndoFile::new(
+      self.fs.clone(),
+      undofile::undo_file_path(undo_dir, abs_filename),
+    );
+    match buf.attach_undo_file(undo_file) {
+      Ok(restored) => trace!(
+        "Attach undo file to {:?}, restored:{:?}",
+        abs_filename,
+        restored
+      ),
+      Err(e) => error!("Failed to attach undo file to {:?}:{:?}", abs_filename, e),
     }
   }
 
   // Implementation for [new_buffer_edit_file](new_buffer_edit_file).
   fn edit_file(&self, filename: &Path, absolute_filename: &Path) -> IoResult<Buffer> {
-    match std::fs::File::open(filename) {
-      Ok(fp) => {
-        let metadata = match fp.metadata() {
-          Ok(metadata) => metadata,
-          Err(e) => {
-            trace!("Failed to fetch metadata from file {:?}:{:?}", filename, e);
-            return Err(e);
-          }
-        };
-        let mut buf: Vec<u8> = Vec::new();
-        let mut reader = std::io::BufReader::new(fp);
-        let bytes = match reader.read_to_end(&mut buf) {
-          Ok(bytes) => bytes,
-          Err(e) => {
-            trace!("Failed to read file {:?}:{:?}", filename, e);
-            return Err(e);
-          }
-        };
-        trace!(
-          "Read {} bytes (buf: {}) from file {:?}",
-          bytes,
-          buf.len(),
-          filename
-        );
-        assert!(bytes == buf.len());
-
-        Ok(Buffer::_new(
-          self.to_rope(&buf, buf.len()),
-          self.local_options().clone(),
-          Some(filename.to_path_buf()),
-          Some(absolute_filename.to_path_buf()),
-          Some(metadata),
-          Some(Instant::now()),
-        ))
-      }
+    let (buf, metadata) = match self.fs.read(absolute_filename) {
+      Ok(result) => result,
       Err(e) => {
-        trace!("Failed to open file {:?}:{:?}", filename, e);
-        Err(e)
+        trace!("Failed to read file {:?}:{:?}", filename, e);
+        return Err(e);
       }
-    }
+    };
+    trace!("Read {} bytes from file {:?}", buf.len(), filename);
+
+    let (rope, file_format, file_encoding) = self.to_rope(&buf, buf.len());
+    let mut options = self.file_options(absolute_filename);
+    options.set_file_format(file_format);
+    options.set_file_encoding(file_encoding);
+    trace!(
+      "Detect file format {} and encoding {} for file {:?}",
+      file_format,
+      file_encoding,
+      filename
+    );
+
+    Ok(Buffer::_new(
+      rope,
+      options,
+      Some(filename.to_path_buf()),
+      Some(absolute_filename.to_path_buf()),
+      metadata,
+      Some(Instant::now()),
+    ))
   }
 }
 
@@ -485,6 +1840,17 @@ impl BuffersManager {
   }
 
   pub fn remove(&mut self, id: &BufferId) -> Option<BufferArc> {
+    self.scratch_sources.remove(id);
+    if self.quickfix_buffer == Some(*id) {
+      self.quickfix_buffer = None;
+    }
+    if self
+      .preview_buffer
+      .as_ref()
+      .is_some_and(|(buf_id, _)| buf_id == id)
+    {
+      self.preview_buffer = None;
+    }
     self.buffers.remove(id)
   }
 
@@ -524,6 +1890,173 @@ impl Default for BuffersManager {
   }
 }
 
+// Argument list {
+impl BuffersManager {
+  /// Get argument list.
+  pub fn arglist(&self) -> &ArgList {
+    &self.arglist
+  }
+
+  /// Get mutable argument list.
+  pub fn arglist_mut(&mut self) -> &mut ArgList {
+    &mut self.arglist
+  }
+
+  /// Get quickfix list.
+  pub fn quickfix(&self) -> &QuickfixList {
+    &self.quickfix
+  }
+
+  /// Get mutable quickfix list.
+  pub fn quickfix_mut(&mut self) -> &mut QuickfixList {
+    &mut self.quickfix
+  }
+
+  /// Get the buffer of the quickfix window, i.e. `:copen`.
+  pub fn quickfix_buffer(&self) -> Option<&BufferArc> {
+    self.quickfix_buffer.and_then(|id| self.buffers.get(&id))
+  }
+
+  /// Get the buffer of the quickfix window, it's created as a read-only scratch buffer if it
+  /// doesn't exist.
+  pub fn open_quickfix_buffer(&mut self) -> BufferArc {
+    if let Some(buffer) = self.quickfix_buffer() {
+      return buffer.clone();
+    }
+    let buf_id = self.new_scratch_buffer(Rope::new(), None);
+    self.quickfix_buffer = Some(buf_id);
+    let buffer = self.buffers.get(&buf_id).unwrap().clone();
+    wlock!(buffer).set_readonly(true);
+    buffer
+  }
+
+  /// Sync the quickfix list to the buffer of the quickfix window, i.e. after the list is changed
+  /// by `:grep`. Returns the buffer, or `None` if it doesn't exist.
+  pub fn sync_quickfix_buffer(&mut self) -> Option<BufferArc> {
+    let buffer = self.quickfix_buffer()?.clone();
+    wlock!(buffer).replace_loaded(&self.quickfix.window_text());
+    Some(buffer)
+  }
+
+  /// Find the preview buffer of the quickfix window if the file `filename` is loaded in it, see
+  /// [`set_preview_buffer`](Self::set_preview_buffer).
+  pub fn find_preview(&self, filename: &Path) -> Option<&BufferArc> {
+    let abs_filename = absolute_path(filename).ok()?;
+    match &self.preview_buffer {
+      Some((buf_id, path)) if *path == abs_filename => self.buffers.get(buf_id),
+      _ => None,
+    }
+  }
+
+  /// Load the `text` of the file `filename` into the preview buffer of the quickfix window, i.e.
+  /// the file that's not opened is previewed without creating a file buffer. The preview buffer is
+  /// created as a read-only scratch buffer if it doesn't exist, and it's reused by all previews.
+  pub fn set_preview_buffer(
+    &mut self,
+    filename: &Path,
+    text: &str,
+    file_format: Option<FileFormat>,
+  ) -> BufferArc {
+    let abs_filename = absolute_path(filename).unwrap_or_else(|_| filename.to_path_buf());
+    let buf_id = match &self.preview_buffer {
+      Some((buf_id, _)) if self.buffers.contains_key(buf_id) => *buf_id,
+      _ => {
+        let buf_id = self.new_scratch_buffer(Rope::new(), None);
+        wlock!(self.buffers.get(&buf_id).unwrap()).set_readonly(true);
+        buf_id
+      }
+    };
+    let buffer = self.buffers.get(&buf_id).unwrap().clone();
+    {
+      let mut buffer = wlock!(buffer);
+      buffer.set_options(&self.file_options(&abs_filename));
+      if let Some(file_format) = file_format {
+        buffer.set_file_format(file_format);
+      }
+      buffer.replace_loaded(text);
+    }
+    self.preview_buffer = Some((buf_id, abs_filename));
+    buffer
+  }
+
+  /// Get the templates for new files.
+  pub fn templates(&self) -> &Templates {
+    &self.templates
+  }
+
+  /// Get the mutable templates for new files.
+  pub fn templates_mut(&mut self) -> &mut Templates {
+    &mut self.templates
+  }
+
+  /// Get the filetype detection rules and option bundles.
+  pub fn file_types(&self) -> &FileTypes {
+    &self.file_types
+  }
+
+  /// Get the mutable filetype detection rules and option bundles.
+  pub fn file_types_mut(&mut self) -> &mut FileTypes {
+    &mut self.file_types
+  }
+}
+// Argument list }
+
+// Events {
+impl BuffersManager {
+  /// Record the buffer is read, i.e. the huge file is loaded in background. The buffers created by
+  /// [`new_file_buffer`](BuffersManager::new_file_buffer) from the existing files are recorded
+  /// automatically.
+  pub fn set_buffer_read(&mut self, id: BufferId) {
+    self.read_buffers.push(id);
+  }
+
+  /// Take the buffers that are read since last taken, i.e. the `BufReadPost` event, see
+  /// [`autocmd`](crate::state::autocmd).
+  pub fn take_read_buffers(&mut self) -> Vec<BufferId> {
+    std::mem::take(&mut self.read_buffers)
+  }
+
+  /// Take the buffers that are detected filetype since last taken, i.e. the `FileType` event, see
+  /// [`autocmd`](crate::state::autocmd).
+  pub fn take_typed_buffers(&mut self) -> Vec<BufferId> {
+    std::mem::take(&mut self.typed_buffers)
+  }
+}
+// Events }
+
+// Marks {
+impl BuffersManager {
+  /// Get the global mark (`A`-`Z`), returns the buffer it belongs to and its position.
+  pub fn global_mark(&self, name: char) -> Option<(BufferId, MarkPos)> {
+    if !mark::is_global_name(name) {
+      return None;
+    }
+    self
+      .buffers
+      .iter()
+      .find_map(|(id, buf)| rlock!(buf).marks().get(name).map(|pos| (*id, pos)))
+  }
+
+  /// Set the mark in buffer `id`, a global mark (`A`-`Z`) is removed from the other buffers.
+  /// Returns `false` if the buffer doesn't exist or the mark name is invalid.
+  pub fn set_mark(&mut self, id: &BufferId, name: char, pos: MarkPos) -> bool {
+    if !self.buffers.contains_key(id) || !mark::is_valid_name(name) {
+      return false;
+    }
+    if mark::is_global_name(name) {
+      for (other_id, buf) in self.buffers.iter() {
+        if other_id != id {
+          wlock!(buf).marks_mut().remove(name);
+        }
+      }
+    }
+    wlock!(self.buffers.get(id).unwrap())
+      .marks_mut()
+      .set(name, pos)
+  }
+}
+// Marks }
+
 // Options {
 impl BuffersManager {
   pub fn local_options(&self) -> &BufferLocalOptions {
@@ -533,9 +2066,91 @@ impl BuffersManager {
   pub fn set_local_options(&mut self, options: &BufferLocalOptions) {
     self.local_options = options.clone();
   }
+
+  /// Whether there're undo states not flushed to the undo files.
+  pub fn undo_flush_pending(&self) -> bool {
+    self
+      .buffers
+      .values()
+      .any(|buf| rlock!(buf).undo_flush_pending())
+  }
+
+  /// Flush the undo states to the undo files, i.e. on idle and before exit. It returns the last
+  /// error after all the buffers are flushed.
+  pub fn flush_undo_files(&self) -> IoResult<()> {
+    let mut result = Ok(());
+    for buf in self.buffers.values() {
+      let mut buf = wlock!(buf);
+      if buf.undo_flush_pending() {
+        if let Err(e) = buf.flush_undo_file() {
+          error!("Failed to flush undo file of buffer {:?}:{:?}", buf.id(), e);
+          result = Err(e);
+        }
+      }
+    }
+    result
+  }
+
+  /// Set the 'undolevels' and 'undomemory' options, for both the new buffers and all the existing
+  /// buffers.
+  pub fn set_undo_limits(&mut self, undo_levels: usize, undo_memory: usize) {
+    self.local_options.set_undo_levels(undo_levels);
+    self.local_options.set_undo_memory(undo_memory);
+    for buf in self.buffers.values() {
+      wlock!(buf).set_undo_limits(undo_levels, undo_memory);
+    }
+  }
+
+  pub fn write_options(&self) -> &WriteOptions {
+    &self.write_options
+  }
+
+  pub fn write_options_mut(&mut self) -> &mut WriteOptions {
+    &mut self.write_options
+  }
+
+  /// Get the last positions of the recent files.
+  pub fn positions(&self) -> &PositionStore {
+    &self.positions
+  }
+
+  /// Get the mutable last positions of the recent files.
+  pub fn positions_mut(&mut self) -> &mut PositionStore {
+    &mut self.positions
+  }
+
+  /// Get the user spell dictionary.
+  pub fn spell(&self) -> &SpellDictionary {
+    &self.spell
+  }
+
+  /// Get the mutable user spell dictionary.
+  pub fn spell_mut(&mut self) -> &mut SpellDictionary {
+    &mut self.spell
+  }
+
+  /// Get current named session, i.e. the last saved/loaded one by `:SessionSave`/`:SessionLoad`.
+  pub fn session(&self) -> &Option<String> {
+    &self.session
+  }
+
+  /// Set current named session.
+  pub fn set_session(&mut self, name: Option<String>) {
+    self.session = name;
+  }
 }
 // Options }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The buffer in the buffer list, see [`BuffersManager::list`].
+pub struct BufferInfo {
+  pub id: BufferId,
+  /// The display name, or `None` for the unnamed buffer, see [`Buffer::display_name`].
+  pub name: Option<String>,
+  pub listed: bool,
+  pub modified: bool,
+}
+
 pub type BuffersManagerArc = Arc<RwLock<BuffersManager>>;
 pub type BuffersManagerWk = Weak<RwLock<BuffersManager>>;
 pub type BuffersManagerKeys<'a> = std::collections::btree_map::Keys<'a, BufferId, BufferArc>;
@@ -545,6 +2160,10 @@ pub type BuffersManagerIter<'a> = std::collections::btree_map::Iter<'a, BufferId
 #[cfg(test)]
 mod tests {
   use super::*;
+
+  use crate::envar;
+  use crate::test::buf::make_buffer_from_lines;
+  use crate::{rlock, wlock};
   // use std::fs::File;
   // use tempfile::tempfile;
   // use tokio::sync::mpsc::Receiver;
@@ -588,6 +2207,699 @@ mod tests {
     assert!(next_buffer_id() > 0);
   }
 
+  #[test]
+  fn width_before1() {
+    let buf = make_buffer_from_lines(vec!["\tabc\n", "你好abc\n"]);
+    let buf = rlock!(buf);
+    assert_eq!(buf.width_before(0, 0), 0);
+    assert_eq!(buf.width_before(0, 1), 8);
+    assert_eq!(buf.width_before(0, 3), 10);
+    assert_eq!(buf.width_before(1, 2), 4);
+    assert_eq!(buf.width_before(1, 100), 7);
+    assert_eq!(buf.width_before(5, 1), 0);
+  }
+
+  #[test]
+  fn str_width1() {
+    let buf = make_buffer_from_lines(vec!["hello, world! \tabc\n", "abcdefghijk你好lmn\n"]);
+    let buf = rlock!(buf);
+    assert_eq!(buf.str_width(""), 0);
+    assert_eq!(buf.str_width("hello, world!"), 13);
+    assert_eq!(buf.str_width("hello, world! \tabc\n"), 25);
+    assert_eq!(buf.str_width("abcdefghijk你好lmn"), 18);
+    assert_eq!(buf.str_width("\x01\x7f"), 4);
+    assert_eq!(buf.width_before(0, 14), 14);
+    assert_eq!(buf.width_before(0, 15), 22);
+    assert_eq!(buf.width_before(1, 12), 13);
+    assert_eq!(buf.width_before(1, 13), 15);
+    assert_eq!(buf.width_before(1, 100), 18);
+    assert_eq!(buf.width_between(1, 10..13), 3);
+    assert_eq!(buf.width_between(1, 12..100), 5);
+  }
+
+  #[test]
+  fn char_at_dcolumn1() {
+    let buf = make_buffer_from_lines(vec!["hello, world! \tabc\n", "abcdefghijk你好lmn\n"]);
+    let buf = rlock!(buf);
+    assert_eq!(buf.char_at_dcolumn(0, 0), (0, 0));
+    assert_eq!(buf.char_at_dcolumn(0, 14), (14, 14));
+    // The tab starts before the display column.
+    assert_eq!(buf.char_at_dcolumn(0, 15), (15, 22));
+    assert_eq!(buf.char_at_dcolumn(1, 12), (12, 13));
+    assert_eq!(buf.char_at_dcolumn(1, 13), (12, 13));
+    // The line is too short.
+    assert_eq!(buf.char_at_dcolumn(1, 100), (17, 18));
+    assert_eq!(buf.char_at_dcolumn(5, 1), (0, 0));
+  }
+
+  #[test]
+  fn grapheme1() {
+    let buf = make_buffer_from_lines(vec![
+      "a👩\u{200D}👩\u{200D}👧b\n",
+      "cafe\u{0301}s\n",
+      "🇺🇸🇨🇳\n",
+      "xकि a\u{200D}你\n",
+      "กำ\n",
+    ]);
+    let buf = rlock!(buf);
+    // The emoji ZWJ sequence is a single 2-width cluster.
+    assert_eq!(buf.str_width("👩\u{200D}👩\u{200D}👧"), 2);
+    assert_eq!(buf.width_before(0, 1), 1);
+    assert_eq!(buf.width_before(0, 3), 3);
+    assert_eq!(buf.width_before(0, 6), 3);
+    assert_eq!(buf.width_between(0, 3..6), 0);
+    assert_eq!(buf.char_at_dcolumn(0, 2), (6, 3));
+    assert_eq!(buf.grapheme_start(0, 4), 1);
+    assert_eq!(buf.next_grapheme(0, 1), 6);
+    assert_eq!(buf.prev_grapheme(0, 6), 1);
+    assert_eq!(buf.prev_grapheme(0, 4), 0);
+
+    // The combining mark joins the previous char.
+    assert_eq!(buf.width_before(1, 5), 4);
+    assert_eq!(buf.width_between(1, 4..5), 0);
+    assert_eq!(buf.grapheme_start(1, 4), 3);
+    assert_eq!(buf.next_grapheme(1, 3), 5);
+    assert_eq!(buf.char_at_dcolumn(1, 3), (3, 3));
+    assert_eq!(buf.char_at_dcolumn(1, 4), (5, 4));
+
+    // The regional indicators are paired.
+    assert_eq!(buf.grapheme_start(2, 3), 2);
+    assert_eq!(buf.next_grapheme(2, 0), 2);
+    assert_eq!(buf.width_before(2, 2), buf.char_width('🇺'));
+
+    // The Indic and Thai spacing marks join the previous char, but the ZWJ doesn't join the
+    // non-emoji chars.
+    assert_eq!(buf.grapheme_start(3, 2), 1);
+    assert_eq!(buf.next_grapheme(3, 1), 3);
+    assert_eq!(buf.width_between(3, 2..3), 0);
+    assert_eq!(buf.next_grapheme(3, 4), 6);
+    assert_eq!(buf.grapheme_start(3, 6), 6);
+    assert_eq!(buf.width_before(3, 7), 6);
+    assert_eq!(buf.grapheme_start(4, 1), 0);
+    assert_eq!(buf.next_grapheme(4, 0), 2);
+  }
+
+  #[test]
+  fn replace_line_content1() {
+    let buf = make_buffer_from_lines(vec!["hello\n", "world\r\n", "end"]);
+    let mut buf = wlock!(buf);
+    buf.replace_line_content(1, "rsvim");
+    buf.replace_line_content(2, "done");
+    assert_eq!(buf.get_line_content(0), Some("hello".to_string()));
+    assert_eq!(buf.get_line_content(1), Some("rsvim".to_string()));
+    assert_eq!(buf.get_line(1).unwrap().to_string(), "rsvim\r\n");
+    assert_eq!(buf.get_line_content(2), Some("done".to_string()));
+  }
+
+  #[test]
+  fn file_format1() {
+    let buf = make_buffer_from_lines(vec!["hello\r\n", "world\n"]);
+    let buf = rlock!(buf);
+    // Mixed line endings, the `\r` is rendered as `^M`.
+    assert_eq!(buf.file_format(), FileFormat::Unix);
+    assert_eq!(buf.char_symbol('\r'), (CompactString::new("^M"), 2));
+    assert_eq!(buf.width_before(0, 6), 7);
+    let mut actual = vec![];
+    buf.write_to(&mut actual).unwrap();
+    assert_eq!(actual, b"hello\r\nworld\n");
+
+    let buf = make_buffer_from_lines(vec!["hello\n", "world\n"]);
+    let mut buf = wlock!(buf);
+    buf.set_file_format(FileFormat::Dos);
+    assert_eq!(buf.char_symbol('\r'), (CompactString::new(""), 0));
+    let mut actual = vec![];
+    buf.write_to(&mut actual).unwrap();
+    assert_eq!(actual, b"hello\r\nworld\r\n");
+  }
+
+  #[test]
+  fn file_encoding1() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.insert(Path::new("/rsvim/a.txt"), b"\xFF\xFEa\x00\r\x00\n\x00");
+    fs.insert(Path::new("/rsvim/b.txt"), b"caf\xe9\n");
+    let mut buffers = BuffersManager::new();
+    buffers.set_fs(fs);
+
+    // UTF-16 with BOM, it's written back with BOM.
+    let buf_id = buffers.new_file_buffer(Path::new("/rsvim/a.txt")).unwrap();
+    let buf = buffers.get(&buf_id).unwrap().clone();
+    let buf = rlock!(buf);
+    assert_eq!(buf.file_encoding(), FileEncoding::Utf16Le);
+    assert_eq!(buf.file_format(), FileFormat::Dos);
+    assert_eq!(buf.get_line_content(0), Some("a".to_string()));
+    let mut actual = vec![];
+    assert_eq!(buf.write_to(&mut actual).unwrap(), 0);
+    assert_eq!(actual, b"\xFF\xFEa\x00\r\x00\n\x00");
+
+    // Latin1, the chars out of it cannot be written.
+    let buf_id = buffers.new_file_buffer(Path::new("/rsvim/b.txt")).unwrap();
+    let buf = buffers.get(&buf_id).unwrap().clone();
+    let mut buf = wlock!(buf);
+    assert_eq!(buf.file_encoding(), FileEncoding::Latin1);
+    assert_eq!(buf.get_line_content(0), Some("café".to_string()));
+    buf.insert(0, "你");
+    assert_eq!(buf.unencodable_chars(), 1);
+    let mut actual = vec![];
+    assert_eq!(buf.write_to(&mut actual).unwrap(), 1);
+    assert_eq!(actual, b"?caf\xe9\n");
+  }
+
+  #[test]
+  fn changed_tick1() {
+    let buf = make_buffer_from_lines(vec!["hello\n"]);
+    let mut buf = wlock!(buf);
+    let tick = buf.changed_tick();
+    buf.insert(0, "a");
+    assert_eq!(buf.changed_tick(), tick + 1);
+    buf.remove(0..1);
+    buf.undo();
+    assert_eq!(buf.changed_tick(), tick + 3);
+    buf.set_tab_stop(4);
+    assert_eq!(buf.changed_tick(), tick + 4);
+    assert_eq!(buf.get_line_content(0), Some("ahello".to_string()));
+  }
+
+  #[test]
+  fn is_modified1() {
+    let buf = make_buffer_from_lines(vec!["hello\n"]);
+    let mut buf = wlock!(buf);
+    assert!(!buf.is_modified());
+    buf.insert(0, "a");
+    assert!(buf.is_modified());
+    buf.undo();
+    assert!(!buf.is_modified());
+    buf.insert(0, "b");
+    buf.set_saved();
+    assert!(!buf.is_modified());
+    buf.undo();
+    assert!(buf.is_modified());
+  }
+
+  #[test]
+  fn undo_redo1() {
+    let buf = make_buffer_from_lines(vec!["hello\n", "world\n"]);
+    let mut buf = wlock!(buf);
+    buf.insert(0, "say ");
+    buf.remove(4..9);
+    buf.replace_line_content(1, "rsvim");
+    assert_eq!(buf.get_line_content(0), Some("say ".to_string()));
+    assert_eq!(buf.undo(), Some(5));
+    assert_eq!(buf.get_line_content(1), Some("world".to_string()));
+    assert_eq!(buf.undo(), Some(4));
+    assert_eq!(buf.undo(), Some(0));
+    assert_eq!(buf.get_line_content(0), Some("hello".to_string()));
+    assert_eq!(buf.redo(), Some(0));
+    assert_eq!(buf.get_line_content(0), Some("say hello".to_string()));
+  }
+
+  #[test]
+  fn set_lines1() {
+    let lines = |v: Vec<&str>| v.into_iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let cases = vec![
+      (vec!["a\n", "b\n", "c"], 1, 2, vec!["X", "Y"], "a\nX\nY\nc"),
+      (vec!["a\n", "b\n", "c"], 2, 3, vec!["X"], "a\nb\nX"),
+      (vec!["a\n", "b\n", "c"], 3, 3, vec!["X"], "a\nb\nc\nX"),
+      (vec!["a\n", "b\n", "c"], 0, 0, vec!["X"], "X\na\nb\nc"),
+      (vec!["a\n", "b\n", "c"], 1, 100, vec![], "a\n"),
+      (vec!["a\n", "b\n"], 1, 3, vec!["X"], "a\nX\n"),
+      (vec!["a\n", "b\n"], 2, 2, vec!["X"], "a\nb\nX\n"),
+      (vec![""], 0, 1, vec!["X"], "X"),
+    ];
+    for (input, start, end, replacement, expect) in cases {
+      let buf = make_buffer_from_lines(input);
+      let mut buf = wlock!(buf);
+      assert!(buf.set_lines(start, end, &lines(replacement)));
+      assert_eq!(buf.rope.to_string(), expect);
+    }
+
+    let buf = make_buffer_from_lines(vec!["a\n"]);
+    let mut buf = wlock!(buf);
+    assert!(!buf.set_lines(5, 6, &lines(vec!["X"])));
+  }
+
+  #[test]
+  fn set_lines2() {
+    // The `setLines` edit is same with the interactive edits, i.e. the text, undo tree, marks and
+    // changed tick.
+    let interactive = make_buffer_from_lines(vec!["hello\n", "world\n", "end\n"]);
+    let mut interactive = wlock!(interactive);
+    interactive.marks_mut().set('a', MarkPos::new(2, 1));
+    interactive.undo_tree_mut().begin_group();
+    interactive.remove(6..12);
+    interactive.insert(6, "rsvim\nis\n");
+    interactive.undo_tree_mut().end_group();
+
+    let scripted = make_buffer_from_lines(vec!["hello\n", "world\n", "end\n"]);
+    let mut scripted = wlock!(scripted);
+    scripted.marks_mut().set('a', MarkPos::new(2, 1));
+    assert!(scripted.set_lines(1, 2, &["rsvim".to_string(), "is".to_string()]));
+
+    assert_eq!(scripted.rope.to_string(), interactive.rope.to_string());
+    assert_eq!(scripted.changed_tick(), interactive.changed_tick());
+    assert_eq!(scripted.marks().get('a'), interactive.marks().get('a'));
+    assert_eq!(scripted.marks().get('a'), Some(MarkPos::new(3, 1)));
+    assert_eq!(
+      scripted.undo_tree().states().len(),
+      interactive.undo_tree().states().len()
+    );
+    assert!(scripted.is_modified());
+
+    // Undone at once.
+    scripted.undo();
+    assert_eq!(scripted.rope.to_string(), "hello\nworld\nend\n");
+    assert_eq!(scripted.marks().get('a'), Some(MarkPos::new(2, 1)));
+    assert!(!scripted.is_modified());
+  }
+
+  #[test]
+  fn new_file_buffer1() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.insert(Path::new("/rsvim/a.txt"), b"hello\nworld\n");
+    let mut buffers = BuffersManager::new();
+    buffers.set_fs(fs);
+
+    let buf_id = buffers.new_file_buffer(Path::new("/rsvim/a.txt")).unwrap();
+    {
+      let buf = buffers.get(&buf_id).unwrap();
+      let buf = rlock!(buf);
+      assert_eq!(buf.get_line_content(1), Some("world".to_string()));
+      assert!(buf.metadata().is_none());
+    }
+
+    // The file doesn't exist, creates an empty buffer.
+    let buf_id = buffers.new_file_buffer(Path::new("/rsvim/b.txt")).unwrap();
+    assert_eq!(rlock!(buffers.get(&buf_id).unwrap()).len_lines(), 1);
+  }
+
+  #[test]
+  fn quickfix_buffer1() {
+    let mut buffers = BuffersManager::new();
+    assert!(buffers.quickfix_buffer().is_none());
+    assert!(buffers.sync_quickfix_buffer().is_none());
+
+    let buffer = buffers.open_quickfix_buffer();
+    let buf_id = rlock!(buffer).id();
+    assert!(rlock!(buffer).is_read_only());
+    assert!(buffers.is_scratch(&buf_id));
+    assert!(Arc::ptr_eq(&buffers.open_quickfix_buffer(), &buffer));
+
+    buffers.quickfix_mut().reset(":grep foo");
+    buffers.quickfix_mut().extend(vec![QuickfixEntry::new(
+      PathBuf::from("a.rs"),
+      1,
+      0,
+      "foo()".to_string(),
+    )]);
+    buffers.sync_quickfix_buffer().unwrap();
+    assert_eq!(
+      rlock!(buffer).get_line_content(0),
+      Some("a.rs:2:1: foo()".to_string())
+    );
+    assert!(!rlock!(buffer).is_modified());
+
+    buffers.remove(&buf_id);
+    assert!(buffers.quickfix_buffer().is_none());
+  }
+
+  #[test]
+  fn list1() {
+    let fs = Arc::new(MemoryFs::new());
+    let mut buffers = BuffersManager::new();
+    buffers.set_fs(fs);
+    let a = buffers.new_file_buffer(Path::new("/rsvim/a.txt")).unwrap();
+    let scratch = buffers.new_scratch_buffer(Rope::new(), Some(a));
+    let b = buffers.new_file_buffer(Path::new("/rsvim/b.txt")).unwrap();
+    let c = buffers.new_empty_buffer();
+    wlock!(buffers.get(&b).unwrap()).insert(0, "hello");
+
+    let list = buffers.list(false);
+    assert_eq!(list.iter().map(|b| b.id).collect::<Vec<_>>(), vec![a, b, c]);
+    assert_eq!(list[0].name, Some("/rsvim/a.txt".to_string()));
+    assert!(list[1].modified);
+    assert_eq!(list[2].name, None);
+    assert_eq!(buffers.list(true).len(), 4);
+
+    assert_eq!(buffers.next_listed(a, 1, true), Some(b));
+    assert_eq!(buffers.next_listed(a, 2, true), Some(c));
+    assert_eq!(buffers.next_listed(a, 1, false), Some(c));
+    assert_eq!(buffers.next_listed(c, 4, true), Some(a));
+    // The unlisted buffer.
+    assert_eq!(buffers.next_listed(scratch, 1, true), Some(b));
+    assert_eq!(buffers.next_listed(scratch, 1, false), Some(a));
+  }
+
+  #[test]
+  fn set_undo_limits1() {
+    let mut buffers = BuffersManager::new();
+    let a = buffers.new_empty_buffer();
+    {
+      let mut buf = wlock!(buffers.get(&a).unwrap());
+      buf.insert(0, "a");
+      buf.insert(1, "b");
+      buf.insert(2, "c");
+      assert_eq!(buf.undo_tree().levels(), 3);
+    }
+
+    buffers.set_undo_limits(1, usize::MAX);
+    assert_eq!(rlock!(buffers.get(&a).unwrap()).undo_tree().levels(), 1);
+    assert_eq!(rlock!(buffers.get(&a).unwrap()).undo_tree().evictions(), 2);
+    let b = buffers.new_scratch_buffer(Rope::new(), None);
+    assert_eq!(rlock!(buffers.get(&b).unwrap()).options().undo_levels(), 1);
+  }
+
+  #[test]
+  fn new_file_buffer2() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.insert(
+      Path::new("/config/templates/skeleton.rs"),
+      b"// {{filename}} by {{author}}\r\nfn main() {}\r\n",
+    );
+    fs.insert(Path::new("/a/sh.tpl"), b"#!/bin/sh\n");
+    let mut buffers = BuffersManager::new();
+    buffers.set_fs(fs);
+    buffers
+      .templates_mut()
+      .set_dirs(vec![PathBuf::from("/config/templates")]);
+    buffers.templates_mut().set_variable("author", "Alice");
+    buffers
+      .templates_mut()
+      .add("*.sh", PathBuf::from("/a/sh.tpl"));
+
+    // From the template directory.
+    let buf_id = buffers
+      .new_file_buffer(Path::new("/rsvim/main.rs"))
+      .unwrap();
+    let buf = buffers.get(&buf_id).unwrap().clone();
+    let buf = rlock!(buf);
+    assert_eq!(
+      buf.get_line_content(0),
+      Some("// main.rs by Alice".to_string())
+    );
+    assert_eq!(buf.get_line_content(1), Some("fn main() {}".to_string()));
+    assert!(buf.is_modified());
+
+    // From the rule.
+    let buf_id = buffers.new_file_buffer(Path::new("/rsvim/run.sh")).unwrap();
+    let buf = buffers.get(&buf_id).unwrap().clone();
+    assert_eq!(
+      rlock!(buf).get_line_content(0),
+      Some("#!/bin/sh".to_string())
+    );
+
+    // No template.
+    let buf_id = buffers.new_file_buffer(Path::new("/rsvim/a.txt")).unwrap();
+    let buf = buffers.get(&buf_id).unwrap().clone();
+    assert!(!rlock!(buf).is_modified());
+  }
+
+  #[test]
+  fn new_directory_buffer1() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("a.txt"), "hello\n").unwrap();
+    let mut buffers = BuffersManager::new();
+    let buf_id = buffers.new_file_buffer(dir.path()).unwrap();
+    let buf = buffers.get(&buf_id).unwrap().clone();
+    {
+      let buf = rlock!(buf);
+      assert_eq!(
+        buf.lines().map(|l| l.to_string()).collect::<String>(),
+        "../\nsrc/\na.txt\n"
+      );
+      assert!(buf.is_read_only() && !buf.is_modified());
+      assert_eq!(buf.options().file_type(), explorer::FILE_TYPE);
+      assert_eq!(buffers.take_typed_buffers(), vec![buf_id]);
+    }
+
+    std::fs::write(dir.path().join("b.txt"), "world\n").unwrap();
+    let mut buf = wlock!(buf);
+    buf
+      .explorer_mut()
+      .as_mut()
+      .unwrap()
+      .set_sort(ExplorerSort::Size);
+    assert!(buf.refresh_explorer(true).unwrap());
+    assert_eq!(buf.get_line_content(3), Some("b.txt".to_string()));
+    assert!(!buf.is_modified());
+  }
+
+  #[test]
+  fn rename_buffer1() {
+    let dir = tempfile::tempdir().unwrap();
+    let a = dir.path().join("a.txt");
+    let b = dir.path().join("sub").join("b.txt");
+    std::fs::write(&a, "hello\n").unwrap();
+    let mut buffers = BuffersManager::new();
+    let buf_id = buffers.new_file_buffer(&a).unwrap();
+    let other_id = buffers.new_file_buffer(&dir.path().join("c.txt")).unwrap();
+    wlock!(buffers.get(&buf_id).unwrap()).insert(0, "world\n");
+
+    // The parent directory is created, and the undo history is kept.
+    buffers.rename_buffer(&buf_id, &b, false).unwrap();
+    assert!(!a.exists());
+    assert_eq!(std::fs::read_to_string(&b).unwrap(), "hello\n");
+    assert!(buffers.find_by_path(&a).is_none());
+    let buf = buffers.find_by_path(&b).unwrap().clone();
+    assert_eq!(rlock!(buf).id(), buf_id);
+    assert_eq!(rlock!(buf).absolute_filename(), &Some(b.clone()));
+    assert!(wlock!(buf).undo().is_some());
+
+    // The file exists.
+    std::fs::write(dir.path().join("d.txt"), "").unwrap();
+    assert!(buffers
+      .rename_buffer(&buf_id, &dir.path().join("d.txt"), false)
+      .is_err());
+    buffers
+      .rename_buffer(&buf_id, &dir.path().join("d.txt"), true)
+      .unwrap();
+
+    // Opened by another buffer.
+    assert!(buffers
+      .rename_buffer(&other_id, &dir.path().join("d.txt"), true)
+      .is_err());
+  }
+
+  #[test]
+  fn undo_file1() {
+    let fs = Arc::new(MemoryFs::new());
+    let path = Path::new("/rsvim/a.txt");
+    let undo_dir = Path::new("/rsvim/undo");
+    let undo_path = undofile::undo_file_path(undo_dir, path);
+    fs.insert(path, b"hello\n");
+    let open = |fs: &Arc<MemoryFs>, undo_file: bool| {
+      let mut buffers = BuffersManager::new();
+      buffers.set_fs(fs.clone());
+      buffers.set_undo_dir(Some(undo_dir.to_path_buf()));
+      buffers.set_undo_file(undo_file);
+      let buf_id = buffers.new_file_buffer(path).unwrap();
+      (buffers.get(&buf_id).unwrap().clone(), buffers)
+    };
+    let text = |buf: &Buffer| buf.lines().map(|l| l.to_string()).collect::<String>();
+
+    // The 'undofile' option is off by default.
+    {
+      let (buf, _buffers) = open(&fs, BuffersManager::new().undo_file());
+      assert!(rlock!(buf).undo_file().is_none());
+    }
+
+    {
+      let (buf, buffers) = open(&fs, true);
+      let mut buf = wlock!(buf);
+      // Nothing is written before a change is saved.
+      buf.set_saved();
+      assert!(!fs.exists(&undo_path).unwrap());
+      buf.insert(0, "a");
+      buf.insert(0, "b");
+      buf.undo();
+      buf.insert(1, "c");
+      assert!(!buf.undo_flush_pending());
+      // Write the file.
+      fs.insert(path, text(&buf).as_bytes());
+      buf.set_saved();
+      assert!(fs.exists(&undo_path).unwrap());
+      buf.insert(0, "d");
+      assert!(buf.undo_flush_pending());
+      drop(buf);
+      buffers.flush_undo_files().unwrap();
+      assert!(!buffers.undo_flush_pending());
+    }
+
+    // The undo history (include the branch) is restored at the saved state.
+    {
+      let (buf, _buffers) = open(&fs, true);
+      let mut buf = wlock!(buf);
+      assert_eq!(text(&buf), "achello\n");
+      assert!(!buf.is_modified());
+      assert!(buf.undo().is_some());
+      assert_eq!(text(&buf), "ahello\n");
+      assert!(buf.undo().is_some());
+      assert_eq!(text(&buf), "hello\n");
+      assert!(buf.undo().is_none());
+      assert!(buf.undo_goto(4));
+      assert_eq!(text(&buf), "dachello\n");
+      assert!(buf.undo_goto(2));
+      assert_eq!(text(&buf), "bahello\n");
+    }
+
+    // The file is changed outside, the undo history is discarded.
+    fs.insert(path, b"world\n");
+    {
+      let (buf, _buffers) = open(&fs, true);
+      let mut buf = wlock!(buf);
+      assert!(buf.undo().is_none());
+      assert!(buf.undo_file().is_some());
+      assert!(!buf.undo_file().as_ref().unwrap().is_synced());
+    }
+  }
+
+  #[test]
+  fn rename_buffer2() {
+    // The file is moved in the in-memory file system.
+    let fs = Arc::new(MemoryFs::new());
+    let a = Path::new("/rsvim/a.txt");
+    let b = Path::new("/rsvim/sub/b.txt");
+    fs.insert(a, b"hello\n");
+    let mut buffers = BuffersManager::new();
+    buffers.set_fs(fs.clone());
+    let buf_id = buffers.new_file_buffer(a).unwrap();
+
+    buffers.rename_buffer(&buf_id, b, false).unwrap();
+    assert!(!fs.exists(a).unwrap());
+    assert_eq!(fs.read(b).unwrap().0, b"hello\n");
+    assert!(buffers.find_by_path(b).is_some());
+    fs.insert(a, b"");
+    assert!(buffers.rename_buffer(&buf_id, a, false).is_err());
+  }
+
+  #[test]
+  fn new_file_buffer_filetype1() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.insert(Path::new("/rsvim/a.py"), b"print(1)\n");
+    let mut buffers = BuffersManager::new();
+    buffers.set_fs(fs);
+    buffers.file_types_mut().set(
+      "python",
+      &FileTypeOptions {
+        shift_width: Some(2),
+        ..Default::default()
+      },
+    );
+
+    let buf_id = buffers.new_file_buffer(Path::new("/rsvim/a.py")).unwrap();
+    let buf = buffers.get(&buf_id).unwrap().clone();
+    {
+      let buf = rlock!(buf);
+      assert_eq!(buf.options().file_type(), "python");
+      assert_eq!(buf.options().comment_string(), "# %s");
+      assert_eq!(buf.options().shift_width(), 2);
+    }
+
+    // No filetype for the unknown file.
+    let buf_id2 = buffers.new_file_buffer(Path::new("/rsvim/README")).unwrap();
+    let buf2 = buffers.get(&buf_id2).unwrap().clone();
+    assert!(rlock!(buf2).options().file_type().is_empty());
+    assert_eq!(buffers.take_typed_buffers(), vec![buf_id]);
+    assert!(buffers.take_typed_buffers().is_empty());
+  }
+
+  #[test]
+  fn loading1() {
+    let fs = Arc::new(MemoryFs::new());
+    fs.insert(Path::new("/rsvim/a.txt"), b"hello\nworld\n");
+    let mut buffers = BuffersManager::new();
+    buffers.set_fs(fs);
+
+    let (buf_id, _reader, total_bytes) = buffers
+      .new_loading_file_buffer(Path::new("/rsvim/a.txt"))
+      .unwrap();
+    assert_eq!(total_bytes, 12);
+    let buf = buffers.get(&buf_id).unwrap();
+    let mut buf = wlock!(buf);
+    assert!(buf.is_loading());
+
+    // The edits are discarded while loading.
+    buf.append_loaded("hello\n");
+    buf.insert(0, "say ");
+    assert_eq!(buf.undo(), None);
+    assert_eq!(buf.get_line_content(0), Some("hello".to_string()));
+    assert!(!buf.is_modified());
+
+    buf.append_loaded("world\n");
+    buf.set_loading(false);
+    buf.insert(0, "say ");
+    assert_eq!(buf.get_line_content(0), Some("say hello".to_string()));
+    assert_eq!(buf.get_line_content(1), Some("world".to_string()));
+  }
+
+  #[test]
+  fn new_remote_buffer1() {
+    let mut buffers = BuffersManager::new();
+    let url = RemoteUrl::parse("https://example.com/a.txt").unwrap();
+    let buf_id = buffers.new_remote_buffer(url);
+    let buf = buffers.find_by_url("https://example.com/a.txt").unwrap();
+    assert_eq!(rlock!(buf).id(), buf_id);
+    let mut buf = wlock!(buf);
+    assert!(buf.is_loading());
+    buf.append_loaded("hello\n");
+    buf.set_loading(false);
+    // The http file is still read-only.
+    assert!(buf.is_read_only());
+    buf.insert(0, "say ");
+    assert_eq!(buf.get_line_content(0), Some("hello".to_string()));
+    drop(buf);
+
+    let url = RemoteUrl::parse("scp://example.com/a.txt").unwrap();
+    let buf_id = buffers.new_remote_buffer(url);
+    let buf = buffers.get(&buf_id).unwrap();
+    let mut buf = wlock!(buf);
+    buf.set_loading(false);
+    buf.insert(0, "say ");
+    assert!(buf.is_modified());
+    let seq = buf.undo_tree().current();
+    buf.insert(0, "x");
+    // The write is started before the last edit.
+    buf.set_saved_seq(seq);
+    assert!(buf.is_modified());
+    buf.undo();
+    assert!(!buf.is_modified());
+  }
+
+  #[test]
+  fn marks1() {
+    let mut buffers = BuffersManager::new();
+    let buf1 = buffers.new_empty_buffer();
+    let buf2 = buffers.new_empty_buffer();
+    {
+      let buf = buffers.get(&buf1).unwrap();
+      let mut buf = wlock!(buf);
+      buf.insert(0, "a\nb\nc\n");
+      assert_eq!(buf.marks().get('.'), Some(MarkPos::new(0, 0)));
+    }
+
+    assert!(buffers.set_mark(&buf1, 'a', MarkPos::new(2, 0)));
+    assert!(buffers.set_mark(&buf1, 'A', MarkPos::new(1, 0)));
+    assert!(!buffers.set_mark(&buf1, '1', MarkPos::new(1, 0)));
+    assert_eq!(buffers.global_mark('A'), Some((buf1, MarkPos::new(1, 0))));
+    assert_eq!(buffers.global_mark('a'), None);
+
+    // Delete line 0, the marks are moved up.
+    wlock!(buffers.get(&buf1).unwrap()).remove(0..2);
+    assert_eq!(buffers.global_mark('A'), Some((buf1, MarkPos::new(0, 0))));
+    assert_eq!(
+      rlock!(buffers.get(&buf1).unwrap()).marks().get('a'),
+      Some(MarkPos::new(1, 0))
+    );
+
+    // Undo, the marks are moved back.
+    wlock!(buffers.get(&buf1).unwrap()).undo();
+    assert_eq!(buffers.global_mark('A'), Some((buf1, MarkPos::new(1, 0))));
+
+    // The global mark is unique among buffers.
+    assert!(buffers.set_mark(&buf2, 'A', MarkPos::new(0, 0)));
+    assert_eq!(buffers.global_mark('A'), Some((buf2, MarkPos::new(0, 0))));
+    assert_eq!(rlock!(buffers.get(&buf1).unwrap()).marks().get('A'), None);
+  }
+
   // #[test]
   // fn buffer_unicode_width1() {
   //   let (sender, _) = make_channel();
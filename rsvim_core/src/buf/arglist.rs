@@ -0,0 +1,129 @@
+//! The argument list.
+//!
+//! The argument list (arglist) is the list of files given on the command line, i.e. `rsvim a.md
+//! b.md`. Users can navigate it with `:next`/`:previous`, extend it with `:argadd`, and execute
+//! commands on each file with `:argdo`.
+//!
+//! See: <https://vimhelp.org/editing.txt.html#arglist>.
+
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default)]
+/// The argument list.
+pub struct ArgList {
+  files: Vec<PathBuf>,
+  // Index of current file, it's `None` if the arglist is empty.
+  current: Option<usize>,
+}
+
+impl ArgList {
+  /// Make new arglist, the first file is the current file.
+  pub fn new(files: Vec<PathBuf>) -> Self {
+    let current = if files.is_empty() { None } else { Some(0) };
+    ArgList { files, current }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.files.is_empty()
+  }
+
+  pub fn len(&self) -> usize {
+    self.files.len()
+  }
+
+  /// Get all files.
+  pub fn files(&self) -> &Vec<PathBuf> {
+    &self.files
+  }
+
+  /// Get index of current file.
+  pub fn current(&self) -> Option<usize> {
+    self.current
+  }
+
+  /// Get current file.
+  pub fn current_file(&self) -> Option<&PathBuf> {
+    self.files.get(self.current?)
+  }
+
+  /// Set current file by index.
+  ///
+  /// Returns the file, or `None` if the index is out of range (and current file is not changed).
+  pub fn set_current(&mut self, idx: usize) -> Option<&PathBuf> {
+    if idx >= self.files.len() {
+      return None;
+    }
+    self.current = Some(idx);
+    self.files.get(idx)
+  }
+
+  /// Move to the `count`th next file, i.e. `:next`.
+  ///
+  /// Returns the file, or `None` if it's out of range (and current file is not changed).
+  pub fn next(&mut self, count: usize) -> Option<&PathBuf> {
+    let idx = self.current? + count;
+    self.set_current(idx)
+  }
+
+  /// Move to the `count`th previous file, i.e. `:previous`.
+  ///
+  /// Returns the file, or `None` if it's out of range (and current file is not changed).
+  pub fn prev(&mut self, count: usize) -> Option<&PathBuf> {
+    let idx = self.current?.checked_sub(count)?;
+    self.set_current(idx)
+  }
+
+  /// Add files after current file, i.e. `:argadd`. The files already in the arglist are skipped.
+  ///
+  /// Returns the added files count.
+  pub fn add(&mut self, files: Vec<PathBuf>) -> usize {
+    let mut insert_idx = self.current.map(|c| c + 1).unwrap_or(self.files.len());
+    let mut added = 0_usize;
+    for file in files {
+      if self.files.contains(&file) {
+        continue;
+      }
+      self.files.insert(insert_idx, file);
+      insert_idx += 1;
+      added += 1;
+    }
+    if self.current.is_none() && !self.files.is_empty() {
+      self.current = Some(0);
+    }
+    added
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn paths(files: &[&str]) -> Vec<PathBuf> {
+    files.iter().map(PathBuf::from).collect()
+  }
+
+  #[test]
+  fn navigate1() {
+    let mut arglist = ArgList::new(paths(&["a.md", "b.md", "c.md"]));
+    assert_eq!(arglist.current(), Some(0));
+    assert_eq!(arglist.next(1), Some(&PathBuf::from("b.md")));
+    assert_eq!(arglist.next(2), None);
+    assert_eq!(arglist.current(), Some(1));
+    assert_eq!(arglist.next(1), Some(&PathBuf::from("c.md")));
+    assert_eq!(arglist.prev(2), Some(&PathBuf::from("a.md")));
+    assert_eq!(arglist.prev(1), None);
+    assert_eq!(arglist.current_file(), Some(&PathBuf::from("a.md")));
+  }
+
+  #[test]
+  fn add1() {
+    let mut arglist = ArgList::default();
+    assert!(arglist.is_empty());
+    assert_eq!(arglist.next(1), None);
+    assert_eq!(arglist.add(paths(&["a.md", "b.md"])), 2);
+    assert_eq!(arglist.current(), Some(0));
+    assert_eq!(arglist.add(paths(&["b.md", "x.md"])), 1);
+    assert_eq!(*arglist.files(), paths(&["a.md", "x.md", "b.md"]));
+    assert_eq!(arglist.len(), 3);
+  }
+}
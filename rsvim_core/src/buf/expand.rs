@@ -0,0 +1,264 @@
+//! File path expansion, for the file arguments of ex commands and command line.
+//!
+//! The expansion is similar to shell's:
+//!
+//! 1. The `~` prefix is expanded to the home directory.
+//! 2. The environment variables `$VAR` and `${VAR}` (and `%VAR%` on Windows) are expanded, the
+//!    unknown variables are kept as it is.
+//! 3. The simple globs are expanded, i.e. the `*` and `?` wildcards (see
+//!    [`glob_match`](crate::buf::template::glob_match)) in each path component. The hidden files are
+//!    only matched if the pattern starts with `.`. The pattern is kept as it is if nothing matches.
+//!
+//! NOTE: The backtick (shell command) expansion is not supported.
+
+use crate::buf::template::glob_match;
+
+use directories::BaseDirs;
+use std::path::{Path, PathBuf};
+
+/// Expand the `~` prefix and the environment variables in the path, with the `home` directory and
+/// the `var` lookup. The `%VAR%` is expanded only if `windows` is true.
+pub fn expand_vars_with<F>(path: &str, home: Option<&Path>, windows: bool, var: F) -> String
+where
+  F: Fn(&str) -> Option<String>,
+{
+  // `~`, `~/` and `~\`
+  let path = match (path.strip_prefix('~'), home) {
+    (Some(rest), Some(home))
+      if rest.is_empty() || rest.starts_with('/') || (windows && rest.starts_with('\\')) =>
+    {
+      format!("{}{}", home.display(), rest)
+    }
+    _ => path.to_string(),
+  };
+
+  let chars: Vec<char> = path.chars().collect();
+  let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+  let mut result = String::with_capacity(path.len());
+  let mut i = 0;
+  while i < chars.len() {
+    match chars[i] {
+      // `${VAR}`
+      '$' if chars.get(i + 1) == Some(&'{') => {
+        let end = chars[i + 2..].iter().position(|c| *c == '}');
+        if let Some(end) = end {
+          let name: String = chars[i + 2..i + 2 + end].iter().collect();
+          if let Some(value) = var(&name) {
+            result.push_str(&value);
+            i += end + 3;
+            continue;
+          }
+        }
+        result.push('$');
+        i += 1;
+      }
+      // `$VAR`
+      '$' => {
+        let len = chars[i + 1..]
+          .iter()
+          .take_while(|c| is_name_char(**c))
+          .count();
+        let name: String = chars[i + 1..i + 1 + len].iter().collect();
+        match var(&name) {
+          Some(value) if len > 0 => {
+            result.push_str(&value);
+            i += len + 1;
+          }
+          _ => {
+            result.push('$');
+            i += 1;
+          }
+        }
+      }
+      // `%VAR%`
+      '%' if windows => {
+        let end = chars[i + 1..].iter().position(|c| *c == '%');
+        let value = end.and_then(|end| {
+          let name: String = chars[i + 1..i + 1 + end].iter().collect();
+          if !name.is_empty() && name.chars().all(is_name_char) {
+            var(&name).map(|value| (value, end))
+          } else {
+            None
+          }
+        });
+        match value {
+          Some((value, end)) => {
+            result.push_str(&value);
+            i += end + 2;
+          }
+          None => {
+            result.push('%');
+            i += 1;
+          }
+        }
+      }
+      c => {
+        result.push(c);
+        i += 1;
+      }
+    }
+  }
+  result
+}
+
+/// Expand the `~` prefix and the environment variables in the path.
+pub fn expand_vars(path: &str) -> String {
+  let base_dirs = BaseDirs::new();
+  expand_vars_with(
+    path,
+    base_dirs.as_ref().map(|dirs| dirs.home_dir()),
+    cfg!(target_os = "windows"),
+    |name| std::env::var(name).ok(),
+  )
+}
+
+/// Whether the path contains the glob wildcards.
+pub fn has_wildcards(path: &str) -> bool {
+  path.contains(['*', '?'])
+}
+
+// Expand the wildcards component by component, the `read_dir` lists the file names in a
+// directory.
+fn glob_with<F>(pattern: &Path, read_dir: &F) -> Vec<PathBuf>
+where
+  F: Fn(&Path) -> Vec<String>,
+{
+  let mut matched = vec![PathBuf::new()];
+  for component in pattern.components() {
+    let component = component.as_os_str().to_string_lossy();
+    if !has_wildcards(&component) {
+      matched.iter_mut().for_each(|p| p.push(&*component));
+      continue;
+    }
+    let mut next = vec![];
+    for dir in matched.iter() {
+      let list_dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+      } else {
+        dir.as_path()
+      };
+      let mut names = read_dir(list_dir)
+        .into_iter()
+        .filter(|name| !name.starts_with('.') || component.starts_with('.'))
+        .filter(|name| glob_match(&component, name))
+        .collect::<Vec<_>>();
+      names.sort();
+      next.extend(names.into_iter().map(|name| dir.join(name)));
+    }
+    matched = next;
+  }
+  matched
+}
+
+/// Expand the path with the `read_dir` to list the file names in a directory, see
+/// [`expand_path`].
+pub fn expand_path_with<F>(path: &str, read_dir: F) -> Vec<PathBuf>
+where
+  F: Fn(&Path) -> Vec<String>,
+{
+  let path = expand_vars(path);
+  if !has_wildcards(&path) {
+    return vec![PathBuf::from(path)];
+  }
+  let matched = glob_with(Path::new(&path), &read_dir);
+  if matched.is_empty() {
+    vec![PathBuf::from(path)]
+  } else {
+    matched
+  }
+}
+
+/// Expand the path on the local file system, a glob pattern can be expanded to multiple paths.
+pub fn expand_path(path: &str) -> Vec<PathBuf> {
+  expand_path_with(path, |dir| match std::fs::read_dir(dir) {
+    Ok(entries) => entries
+      .filter_map(|entry| entry.ok())
+      .map(|entry| entry.file_name().to_string_lossy().to_string())
+      .collect(),
+    Err(_) => vec![],
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn var(name: &str) -> Option<String> {
+    match name {
+      "HOME" => Some("/home/alice".to_string()),
+      "PROJECT" => Some("rsvim".to_string()),
+      _ => None,
+    }
+  }
+
+  #[test]
+  fn expand_vars1() {
+    let home = Some(Path::new("/home/alice"));
+    let expand = |path: &str| expand_vars_with(path, home, false, var);
+    assert_eq!(expand("~"), "/home/alice");
+    assert_eq!(expand("~/a.txt"), "/home/alice/a.txt");
+    assert_eq!(expand("~bob/a.txt"), "~bob/a.txt");
+    assert_eq!(expand("a~/b"), "a~/b");
+    assert_eq!(expand("$HOME/$PROJECT/a.rs"), "/home/alice/rsvim/a.rs");
+    assert_eq!(expand("${PROJECT}_x.rs"), "rsvim_x.rs");
+    assert_eq!(expand("$PROJECT_x.rs"), "$PROJECT_x.rs");
+    assert_eq!(
+      expand("$UNKNOWN/$/${}/${PROJECT"),
+      "$UNKNOWN/$/${}/${PROJECT"
+    );
+    assert_eq!(expand("100%PROJECT%"), "100%PROJECT%");
+  }
+
+  #[test]
+  fn expand_vars2() {
+    let home = Some(Path::new("C:\\Users\\alice"));
+    let expand = |path: &str| expand_vars_with(path, home, true, var);
+    assert_eq!(expand("~\\a.txt"), "C:\\Users\\alice\\a.txt");
+    assert_eq!(expand("%PROJECT%\\a.rs"), "rsvim\\a.rs");
+    assert_eq!(expand("%UNKNOWN%\\100%"), "%UNKNOWN%\\100%");
+    assert_eq!(expand("%%"), "%%");
+  }
+
+  #[test]
+  fn expand_path1() {
+    let read_dir = |dir: &Path| -> Vec<String> {
+      let names: &[&str] = match dir.to_str().unwrap() {
+        "." => &["src", "docs", ".git", "README.md"],
+        "src" => &["main.rs", "lib.rs", "buf.rs", ".hidden.rs", "a.txt"],
+        "docs" => &["guide.md"],
+        "/abs" => &["x1", "x2", "y1"],
+        _ => &[],
+      };
+      names.iter().map(|name| name.to_string()).collect()
+    };
+    assert_eq!(
+      expand_path_with("src/*.rs", read_dir),
+      vec![
+        PathBuf::from("src/buf.rs"),
+        PathBuf::from("src/lib.rs"),
+        PathBuf::from("src/main.rs"),
+      ]
+    );
+    assert_eq!(
+      expand_path_with("src/.*", read_dir),
+      vec![PathBuf::from("src/.hidden.rs")]
+    );
+    assert_eq!(
+      expand_path_with("*/*.md", read_dir),
+      vec![PathBuf::from("docs/guide.md")]
+    );
+    assert_eq!(
+      expand_path_with("/abs/x?", read_dir),
+      vec![PathBuf::from("/abs/x1"), PathBuf::from("/abs/x2")]
+    );
+    // Nothing matches.
+    assert_eq!(
+      expand_path_with("src/*.py", read_dir),
+      vec![PathBuf::from("src/*.py")]
+    );
+    assert_eq!(
+      expand_path_with("README.md", read_dir),
+      vec![PathBuf::from("README.md")]
+    );
+  }
+}
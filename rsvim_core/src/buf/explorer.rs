@@ -0,0 +1,319 @@
+//! The directory buffer, i.e. the file explorer like Vim's netrw.
+//!
+//! Editing a directory (i.e. `:edit src/` or `:Explore`) opens a read-only buffer, its text is the
+//! listing of the directory, one entry per line:
+//!
+//! - The first line is `../`, i.e. the parent directory.
+//! - The directories are listed before the files, with the `/` suffix.
+//! - The entries are sorted by name, modified time (newest first) or size (largest first), see
+//!   [`ExplorerSort`], the order can be reversed.
+//!
+//! The directory buffer is an ordinary buffer, thus the motions, the search and the key mappings
+//! work on it. Its filetype is `explorer`, i.e. the buffer-local mappings can be defined in the
+//! `FileType` event. The directories and the symbolic links are highlighted in the `explorer`
+//! highlight namespace.
+//!
+//! See [`explorer`](crate::state::ex::explorer) for the keys and the commands.
+
+use crate::buf::HighlightStyle;
+use crate::res::IoResult;
+
+use crossterm::style::{Attribute, Color};
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+/// The filetype of the directory buffer.
+pub const FILE_TYPE: &str = "explorer";
+
+/// The highlight namespace of the directory buffer.
+pub const NAMESPACE: &str = "explorer";
+
+/// The first line, i.e. the parent directory.
+pub const PARENT_LINE: &str = "../";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// The sorting of the entries, the directories are always listed before the files.
+pub enum ExplorerSort {
+  #[default]
+  /// By name.
+  Name,
+  /// By modified time, the newest first.
+  Time,
+  /// By size, the largest first.
+  Size,
+}
+
+impl ExplorerSort {
+  /// The next sorting, i.e. the `s` key cycles name, time and size.
+  pub fn next(self) -> Self {
+    match self {
+      ExplorerSort::Name => ExplorerSort::Time,
+      ExplorerSort::Time => ExplorerSort::Size,
+      ExplorerSort::Size => ExplorerSort::Name,
+    }
+  }
+}
+
+impl Display for ExplorerSort {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ExplorerSort::Name => write!(f, "name"),
+      ExplorerSort::Time => write!(f, "time"),
+      ExplorerSort::Size => write!(f, "size"),
+    }
+  }
+}
+
+impl FromStr for ExplorerSort {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "name" => Ok(ExplorerSort::Name),
+      "time" => Ok(ExplorerSort::Time),
+      "size" => Ok(ExplorerSort::Size),
+      _ => Err(format!("E475: Invalid argument: {}", s)),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An entry of the directory.
+pub struct ExplorerEntry {
+  pub name: String,
+  /// Whether it's a directory, the symbolic link is followed.
+  pub is_dir: bool,
+  pub is_symlink: bool,
+  pub size: u64,
+  pub mtime: Option<SystemTime>,
+}
+
+impl ExplorerEntry {
+  /// The line in the listing, the directory has the `/` suffix.
+  pub fn line(&self) -> String {
+    if self.is_dir {
+      format!("{}/", self.name)
+    } else {
+      self.name.clone()
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The listing of the directory buffer.
+pub struct DirListing {
+  dir: PathBuf,
+  sort: ExplorerSort,
+  reverse: bool,
+  entries: Vec<ExplorerEntry>,
+}
+
+impl DirListing {
+  /// Make an empty listing of the absolute directory `dir`, it's filled by
+  /// [`read`](DirListing::read).
+  pub fn new(dir: &Path, sort: ExplorerSort, reverse: bool) -> Self {
+    DirListing {
+      dir: dir.to_path_buf(),
+      sort,
+      reverse,
+      entries: vec![],
+    }
+  }
+
+  /// Read the directory entries, the unreadable entries are skipped.
+  pub fn read(&mut self) -> IoResult<()> {
+    let mut entries = vec![];
+    for entry in std::fs::read_dir(&self.dir)? {
+      let entry = match entry {
+        Ok(entry) => entry,
+        Err(_) => continue,
+      };
+      let is_symlink = entry.file_type().is_ok_and(|t| t.is_symlink());
+      // The broken symbolic link is listed as a file.
+      let metadata = match std::fs::metadata(entry.path()) {
+        Ok(metadata) => metadata,
+        Err(_) => match entry.metadata() {
+          Ok(metadata) => metadata,
+          Err(_) => continue,
+        },
+      };
+      entries.push(ExplorerEntry {
+        name: entry.file_name().to_string_lossy().to_string(),
+        is_dir: metadata.is_dir(),
+        is_symlink,
+        size: metadata.len(),
+        mtime: metadata.modified().ok(),
+      });
+    }
+    self.set_entries(entries);
+    Ok(())
+  }
+
+  /// Replace the entries, they're sorted.
+  pub fn set_entries(&mut self, entries: Vec<ExplorerEntry>) {
+    self.entries = entries;
+    self.sort_entries();
+  }
+
+  fn sort_entries(&mut self) {
+    let (sort, reverse) = (self.sort, self.reverse);
+    self.entries.sort_by(|a, b| {
+      let order = match sort {
+        ExplorerSort::Name => Ordering::Equal,
+        ExplorerSort::Time => b.mtime.cmp(&a.mtime),
+        ExplorerSort::Size => b.size.cmp(&a.size),
+      }
+      .then_with(|| a.name.cmp(&b.name));
+      let order = if reverse { order.reverse() } else { order };
+      b.is_dir.cmp(&a.is_dir).then(order)
+    });
+  }
+
+  /// The absolute directory.
+  pub fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  pub fn entries(&self) -> &Vec<ExplorerEntry> {
+    &self.entries
+  }
+
+  pub fn sort(&self) -> ExplorerSort {
+    self.sort
+  }
+
+  pub fn set_sort(&mut self, sort: ExplorerSort) {
+    self.sort = sort;
+    self.sort_entries();
+  }
+
+  pub fn reverse(&self) -> bool {
+    self.reverse
+  }
+
+  pub fn set_reverse(&mut self, reverse: bool) {
+    self.reverse = reverse;
+    self.sort_entries();
+  }
+
+  /// The buffer text, each line ends with a line break.
+  pub fn text(&self) -> String {
+    std::iter::once(PARENT_LINE.to_string())
+      .chain(self.entries.iter().map(|entry| entry.line()))
+      .map(|line| format!("{}\n", line))
+      .collect()
+  }
+
+  /// Get the entry of the line, returns `None` for the parent directory line.
+  pub fn entry_at(&self, line_idx: usize) -> Option<&ExplorerEntry> {
+    self.entries.get(line_idx.checked_sub(1)?)
+  }
+
+  /// Get the path of the line, the first line is the parent directory.
+  pub fn path_at(&self, line_idx: usize) -> Option<PathBuf> {
+    if line_idx == 0 {
+      return Some(self.parent());
+    }
+    self
+      .entry_at(line_idx)
+      .map(|entry| self.dir.join(&entry.name))
+  }
+
+  /// The parent directory, it's the directory itself for the root.
+  pub fn parent(&self) -> PathBuf {
+    self.dir.parent().unwrap_or(&self.dir).to_path_buf()
+  }
+
+  /// Find the line of the entry `name`.
+  pub fn line_of(&self, name: &str) -> Option<usize> {
+    self
+      .entries
+      .iter()
+      .position(|entry| entry.name == name)
+      .map(|idx| idx + 1)
+  }
+
+  /// The highlights of the lines, i.e. the directories and the symbolic links.
+  pub fn highlights(&self) -> Vec<(usize, std::ops::Range<usize>, HighlightStyle)> {
+    let dir_style = HighlightStyle::new(Some(Color::Blue), None, Attribute::Bold.into());
+    let link_style = HighlightStyle::new(Some(Color::Cyan), None, Default::default());
+    let mut result = vec![(0, 0..PARENT_LINE.len(), dir_style)];
+    for (idx, entry) in self.entries.iter().enumerate() {
+      let len = entry.line().chars().count();
+      if entry.is_symlink {
+        result.push((idx + 1, 0..len, link_style));
+      } else if entry.is_dir {
+        result.push((idx + 1, 0..len, dir_style));
+      }
+    }
+    result
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::time::Duration;
+
+  fn entry(name: &str, is_dir: bool, size: u64, mtime: u64) -> ExplorerEntry {
+    ExplorerEntry {
+      name: name.to_string(),
+      is_dir,
+      is_symlink: false,
+      size,
+      mtime: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(mtime)),
+    }
+  }
+
+  #[test]
+  fn sort1() {
+    let mut listing = DirListing::new(Path::new("/rsvim/src"), ExplorerSort::Name, false);
+    listing.set_entries(vec![
+      entry("b.rs", false, 10, 3),
+      entry("lib", true, 0, 1),
+      entry("a.rs", false, 30, 1),
+      entry("c.rs", false, 20, 2),
+    ]);
+    assert_eq!(listing.text(), "../\nlib/\na.rs\nb.rs\nc.rs\n");
+    assert_eq!(listing.path_at(0), Some(PathBuf::from("/rsvim")));
+    assert_eq!(listing.path_at(1), Some(PathBuf::from("/rsvim/src/lib")));
+    assert_eq!(listing.path_at(5), None);
+    assert_eq!(listing.line_of("b.rs"), Some(3));
+
+    listing.set_sort(listing.sort().next());
+    assert_eq!(listing.sort(), ExplorerSort::Time);
+    assert_eq!(listing.text(), "../\nlib/\nb.rs\nc.rs\na.rs\n");
+    listing.set_sort(listing.sort().next());
+    assert_eq!(listing.text(), "../\nlib/\na.rs\nc.rs\nb.rs\n");
+    listing.set_reverse(true);
+    assert_eq!(listing.text(), "../\nlib/\nb.rs\nc.rs\na.rs\n");
+
+    assert_eq!("size".parse::<ExplorerSort>(), Ok(ExplorerSort::Size));
+    assert!("foo".parse::<ExplorerSort>().is_err());
+  }
+
+  #[test]
+  fn read1() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    std::fs::create_dir(tmp.path().join("src")).unwrap();
+    std::fs::write(tmp.path().join("README.md"), "# rsvim\n").unwrap();
+    std::fs::write(tmp.path().join(".gitignore"), "target\n").unwrap();
+
+    let mut listing = DirListing::new(tmp.path(), ExplorerSort::Name, false);
+    listing.read().unwrap();
+    assert_eq!(listing.text(), "../\nsrc/\n.gitignore\nREADME.md\n");
+    assert_eq!(listing.entry_at(3).unwrap().size, 8);
+    assert_eq!(
+      listing
+        .highlights()
+        .iter()
+        .map(|(line_idx, range, _)| (*line_idx, range.clone()))
+        .collect::<Vec<_>>(),
+      vec![(0, 0..3), (1, 0..4)]
+    );
+  }
+}
@@ -0,0 +1,362 @@
+//! Buffer extmarks, i.e. the position-tracking text decorations.
+//!
+//! An extmark is anchored at a char position (and optionally an end position) in the buffer, it
+//! follows the text when the buffer is edited (unlike the [`highlight`](crate::buf::highlight)
+//! namespaces). It can decorate the text with:
+//!
+//! - A highlight style on the chars in `[start, end)`, it's layered with the highlight namespaces
+//!   by its priority.
+//! - A virtual text, shown after the end of the line where the extmark starts.
+//!
+//! The gravity decides where the position goes when text is inserted right at it: the right
+//! gravity moves it to the end of the inserted text, the left gravity keeps it at the start. When
+//! the text around the position is deleted, the position collapses to the start of the deletion.
+//! By default the start has right gravity and the end has left gravity, i.e. the text inserted at
+//! the boundaries is not decorated.
+//!
+//! See: <https://neovim.io/doc/user/api.html#api-extended-marks>.
+
+use crate::buf::highlight::{HighlightStyle, NamespaceId};
+use crate::buf::undo::Delta;
+
+use ropey::Rope;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// The extmark ID.
+pub type ExtmarkId = usize;
+
+/// Adjust the char index `pos` by an edit at char index `char_idx`, which removes
+/// `removed_chars` chars and inserts `inserted_chars` chars. The position inside the removed
+/// chars collapses to the edit start, then the gravity decides which side of the inserted chars
+/// it goes.
+pub fn adjust_char_idx(
+  pos: usize,
+  char_idx: usize,
+  removed_chars: usize,
+  inserted_chars: usize,
+  right_gravity: bool,
+) -> usize {
+  if pos < char_idx {
+    pos
+  } else if pos > char_idx + removed_chars {
+    pos - removed_chars + inserted_chars
+  } else if right_gravity {
+    char_idx + inserted_chars
+  } else {
+    char_idx
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An extmark.
+pub struct Extmark {
+  /// The namespace, i.e. a group of extmarks that can be cleared at once.
+  pub ns_id: NamespaceId,
+  /// The start char index in the buffer.
+  pub start: usize,
+  /// The end char index (exclusive) in the buffer, `None` if it's a single position.
+  pub end: Option<usize>,
+  /// Whether the start has right gravity.
+  pub right_gravity: bool,
+  /// Whether the end has right gravity.
+  pub end_right_gravity: bool,
+  /// The priority when layering the highlight style.
+  pub priority: u16,
+  /// The highlight style on the chars in `[start, end)`.
+  pub style: Option<HighlightStyle>,
+  /// The virtual text and its style, shown after the end of the start line.
+  pub virt_text: Option<(String, HighlightStyle)>,
+}
+
+impl Extmark {
+  /// Make an extmark at the char index, with the default gravities and no decoration.
+  pub fn new(ns_id: NamespaceId, start: usize, priority: u16) -> Self {
+    Extmark {
+      ns_id,
+      start,
+      end: None,
+      right_gravity: true,
+      end_right_gravity: false,
+      priority,
+      style: None,
+      virt_text: None,
+    }
+  }
+
+  /// Adjust the positions by an edit, see [`adjust_char_idx`].
+  pub fn adjust(&mut self, char_idx: usize, removed_chars: usize, inserted_chars: usize) {
+    self.start = adjust_char_idx(
+      self.start,
+      char_idx,
+      removed_chars,
+      inserted_chars,
+      self.right_gravity,
+    );
+    if let Some(end) = self.end {
+      let end = adjust_char_idx(
+        end,
+        char_idx,
+        removed_chars,
+        inserted_chars,
+        self.end_right_gravity,
+      );
+      // The range collapses if all its chars are deleted.
+      self.end = Some(end.max(self.start));
+    }
+  }
+}
+
+// The highlighted char index range in a line, with its priority, namespace and style.
+type LineHighlight = (Range<usize>, u16, NamespaceId, HighlightStyle);
+
+#[derive(Debug, Clone, Default)]
+/// The extmark decorations of the lines, see [`Extmarks::decorations`].
+pub struct ExtmarkDecorations {
+  // Maps from line index to the highlights.
+  highlights: BTreeMap<usize, Vec<LineHighlight>>,
+
+  // Maps from line index to the virtual texts and their styles.
+  virt_texts: BTreeMap<usize, Vec<(String, HighlightStyle)>>,
+}
+
+impl ExtmarkDecorations {
+  pub fn is_empty(&self) -> bool {
+    self.highlights.is_empty() && self.virt_texts.is_empty()
+  }
+
+  /// Get all the styles on the char, with their priorities and namespaces, i.e. the layers for
+  /// [`resolve`](crate::buf::highlight::resolve).
+  pub fn styles_at(
+    &self,
+    line_idx: usize,
+    char_idx: usize,
+  ) -> Vec<(u16, NamespaceId, HighlightStyle)> {
+    match self.highlights.get(&line_idx) {
+      Some(highlights) => highlights
+        .iter()
+        .filter(|(range, _, _, _)| range.contains(&char_idx))
+        .map(|(_, priority, ns_id, style)| (*priority, *ns_id, *style))
+        .collect(),
+      None => vec![],
+    }
+  }
+
+  /// Get the virtual texts after the end of the line, in the order of the extmark IDs.
+  pub fn virt_texts(&self, line_idx: usize) -> &[(String, HighlightStyle)] {
+    self
+      .virt_texts
+      .get(&line_idx)
+      .map(|texts| texts.as_slice())
+      .unwrap_or(&[])
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The extmarks of a buffer.
+pub struct Extmarks {
+  extmarks: BTreeMap<ExtmarkId, Extmark>,
+  next_id: ExtmarkId,
+}
+
+impl Extmarks {
+  pub fn new() -> Self {
+    Extmarks {
+      extmarks: BTreeMap::new(),
+      next_id: 1,
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.extmarks.is_empty()
+  }
+
+  pub fn get(&self, id: ExtmarkId) -> Option<&Extmark> {
+    self.extmarks.get(&id)
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = (&ExtmarkId, &Extmark)> {
+    self.extmarks.iter()
+  }
+
+  /// Add an extmark, returns its ID.
+  pub fn add(&mut self, extmark: Extmark) -> ExtmarkId {
+    let id = self.next_id;
+    self.next_id += 1;
+    self.extmarks.insert(id, extmark);
+    id
+  }
+
+  /// Replace an existing extmark, returns `false` if it doesn't exist.
+  pub fn replace(&mut self, id: ExtmarkId, extmark: Extmark) -> bool {
+    match self.extmarks.get_mut(&id) {
+      Some(existing) => {
+        *existing = extmark;
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub fn remove(&mut self, id: ExtmarkId) -> Option<Extmark> {
+    self.extmarks.remove(&id)
+  }
+
+  /// Remove the extmarks of the namespace, or all the extmarks if the namespace is `None`.
+  pub fn clear(&mut self, ns_id: Option<NamespaceId>) {
+    match ns_id {
+      Some(ns_id) => self.extmarks.retain(|_, extmark| extmark.ns_id != ns_id),
+      None => self.extmarks.clear(),
+    }
+  }
+
+  /// Adjust the extmarks by the `delta`, it doesn't matter whether it's applied or not since the
+  /// positions are char indexes.
+  pub fn adjust_by_delta(&mut self, delta: &Delta) {
+    let removed_chars = delta.removed.chars().count();
+    let inserted_chars = delta.inserted.chars().count();
+    if removed_chars == 0 && inserted_chars == 0 {
+      return;
+    }
+    for extmark in self.extmarks.values_mut() {
+      extmark.adjust(delta.char_idx, removed_chars, inserted_chars);
+    }
+  }
+
+  /// Collect the decorations of the extmarks intersecting the line index `lines` range, i.e. the
+  /// visible lines of a viewport.
+  pub fn decorations(&self, rope: &Rope, lines: Range<usize>) -> ExtmarkDecorations {
+    let mut result = ExtmarkDecorations::default();
+    let len_chars = rope.len_chars();
+    for extmark in self.extmarks.values() {
+      let start = extmark.start.min(len_chars);
+      let end = extmark.end.unwrap_or(extmark.start).min(len_chars);
+      let start_line = rope.char_to_line(start);
+      let end_line = rope.char_to_line(end);
+      if start_line >= lines.end || end_line < lines.start {
+        continue;
+      }
+
+      if let Some(style) = extmark.style {
+        let first = start_line.max(lines.start);
+        let last = end_line.min(lines.end.saturating_sub(1));
+        for line_idx in first..=last {
+          let line_start = rope.line_to_char(line_idx);
+          let range_start = if line_idx == start_line {
+            start - line_start
+          } else {
+            0
+          };
+          let range_end = if line_idx == end_line {
+            end - line_start
+          } else {
+            usize::MAX
+          };
+          if range_start < range_end {
+            result.highlights.entry(line_idx).or_default().push((
+              range_start..range_end,
+              extmark.priority,
+              extmark.ns_id,
+              style,
+            ));
+          }
+        }
+      }
+
+      if let Some(virt_text) = &extmark.virt_text {
+        if lines.contains(&start_line) {
+          result
+            .virt_texts
+            .entry(start_line)
+            .or_default()
+            .push(virt_text.clone());
+        }
+      }
+    }
+    result
+  }
+}
+
+impl Default for Extmarks {
+  fn default() -> Self {
+    Extmarks::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn adjust1() {
+    // Insert at the position, the gravity decides.
+    assert_eq!(adjust_char_idx(5, 5, 0, 3, true), 8);
+    assert_eq!(adjust_char_idx(5, 5, 0, 3, false), 5);
+    // Edit before/after the position.
+    assert_eq!(adjust_char_idx(5, 2, 1, 3, false), 7);
+    assert_eq!(adjust_char_idx(5, 6, 2, 0, true), 5);
+    // Delete around the position, it collapses to the deletion start.
+    assert_eq!(adjust_char_idx(5, 3, 4, 0, true), 3);
+    assert_eq!(adjust_char_idx(5, 3, 4, 2, false), 3);
+
+    // The range collapses if all its chars are deleted.
+    let mut extmark = Extmark::new(1, 2, 100);
+    extmark.end = Some(4);
+    extmark.adjust(1, 5, 0);
+    assert_eq!((extmark.start, extmark.end), (1, Some(1)));
+
+    // The text inserted at the boundaries is not decorated by default.
+    let mut extmark = Extmark::new(1, 2, 100);
+    extmark.end = Some(4);
+    extmark.adjust(2, 0, 3);
+    assert_eq!((extmark.start, extmark.end), (5, Some(7)));
+    extmark.adjust(7, 0, 1);
+    assert_eq!((extmark.start, extmark.end), (5, Some(7)));
+  }
+
+  #[test]
+  fn decorations1() {
+    let rope = Rope::from_str("hello\nworld\nfoo\nbar\n");
+    let mut extmarks = Extmarks::new();
+    let style = HighlightStyle::search();
+
+    // From `llo` of line 0 to `wo` of line 1.
+    let mut extmark = Extmark::new(1, 2, 100);
+    extmark.end = Some(8);
+    extmark.style = Some(style);
+    let id1 = extmarks.add(extmark);
+    // Virtual text at line 2.
+    let mut extmark = Extmark::new(2, 13, 100);
+    extmark.virt_text = Some(("error".to_string(), style));
+    let id2 = extmarks.add(extmark);
+    assert_ne!(id1, id2);
+
+    let decorations = extmarks.decorations(&rope, 0..4);
+    assert_eq!(decorations.styles_at(0, 1), vec![]);
+    assert_eq!(decorations.styles_at(0, 4), vec![(100, 1, style)]);
+    assert_eq!(decorations.styles_at(1, 1), vec![(100, 1, style)]);
+    assert_eq!(decorations.styles_at(1, 2), vec![]);
+    assert_eq!(decorations.virt_texts(1), &[]);
+    assert_eq!(decorations.virt_texts(2), &[("error".to_string(), style)]);
+
+    // Only the visible lines are collected.
+    let decorations = extmarks.decorations(&rope, 1..2);
+    assert_eq!(decorations.styles_at(0, 4), vec![]);
+    assert_eq!(decorations.styles_at(1, 0), vec![(100, 1, style)]);
+    assert_eq!(decorations.virt_texts(2), &[]);
+
+    // Insert a line before, the extmarks follow the text.
+    extmarks.adjust_by_delta(&Delta::new(0, "", "new\n"));
+    let rope = Rope::from_str("new\nhello\nworld\nfoo\nbar\n");
+    let decorations = extmarks.decorations(&rope, 0..5);
+    assert_eq!(decorations.styles_at(0, 4), vec![]);
+    assert_eq!(decorations.styles_at(1, 4), vec![(100, 1, style)]);
+    assert_eq!(decorations.virt_texts(3), &[("error".to_string(), style)]);
+
+    extmarks.clear(Some(1));
+    assert!(extmarks.get(id1).is_none());
+    assert!(extmarks.get(id2).is_some());
+    extmarks.clear(None);
+    assert!(extmarks.is_empty());
+  }
+}
@@ -0,0 +1,245 @@
+//! File types, i.e. Vim's ['filetype'](https://vimhelp.org/filetype.txt.html).
+//!
+//! When a buffer is created for a file, its filetype is detected from the file name by the first
+//! matched rule:
+//!
+//! 1. The rules added with `Rsvim.filetype.add(pattern, filetype)`, in the added order.
+//! 2. The built-in rules, see [`DETECT_RULES`](crate::defaults::filetype::DETECT_RULES).
+//!
+//! The pattern is matched in the same way with templates, see
+//! [`path_match`](crate::buf::template::path_match).
+//!
+//! Then the option bundle of the filetype is applied to the buffer's local options, i.e. the
+//! built-in bundle (see [`BUNDLES`](crate::defaults::filetype::BUNDLES)) overridden by the options
+//! set with `Rsvim.filetype.set(filetype, options)`. At last the `FileType` event is emitted, see
+//! [`autocmd`](crate::state::autocmd).
+
+use crate::buf::opt::BufferLocalOptions;
+use crate::buf::template::path_match;
+use crate::defaults::filetype::{FileTypeBundle, BUNDLES, DETECT_RULES};
+
+use ahash::AHashMap as HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The option bundle for a filetype, the `None` options are not changed.
+pub struct FileTypeOptions {
+  pub comment_string: Option<String>,
+  pub tab_stop: Option<u16>,
+  pub shift_width: Option<u16>,
+  pub expand_tab: Option<bool>,
+  pub suffixes_add: Option<String>,
+}
+
+impl From<&FileTypeBundle> for FileTypeOptions {
+  fn from(bundle: &FileTypeBundle) -> Self {
+    FileTypeOptions {
+      comment_string: bundle.comment_string.map(|s| s.to_string()),
+      tab_stop: bundle.tab_stop,
+      shift_width: bundle.shift_width,
+      expand_tab: bundle.expand_tab,
+      suffixes_add: bundle.suffixes_add.map(|s| s.to_string()),
+    }
+  }
+}
+
+impl FileTypeOptions {
+  /// Override with the `Some` options in `other`.
+  pub fn merge(&mut self, other: &FileTypeOptions) {
+    if other.comment_string.is_some() {
+      self.comment_string = other.comment_string.clone();
+    }
+    if other.tab_stop.is_some() {
+      self.tab_stop = other.tab_stop;
+    }
+    if other.shift_width.is_some() {
+      self.shift_width = other.shift_width;
+    }
+    if other.expand_tab.is_some() {
+      self.expand_tab = other.expand_tab;
+    }
+    if other.suffixes_add.is_some() {
+      self.suffixes_add = other.suffixes_add.clone();
+    }
+  }
+
+  /// Apply the `Some` options to the buffer's local options.
+  pub fn apply(&self, options: &mut BufferLocalOptions) {
+    if let Some(comment_string) = &self.comment_string {
+      options.set_comment_string(comment_string);
+    }
+    if let Some(tab_stop) = self.tab_stop {
+      options.set_tab_stop(tab_stop);
+    }
+    if let Some(shift_width) = self.shift_width {
+      options.set_shift_width(shift_width);
+    }
+    if let Some(expand_tab) = self.expand_tab {
+      options.set_expand_tab(expand_tab);
+    }
+    if let Some(suffixes_add) = &self.suffixes_add {
+      options.set_suffixes_add(suffixes_add);
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The filetype detection rule.
+pub struct FileTypeRule {
+  pub pattern: String,
+  pub file_type: String,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The filetype detection rules and option bundles.
+pub struct FileTypes {
+  rules: Vec<FileTypeRule>,
+  // User defined options, they override the built-in bundles.
+  overrides: HashMap<String, FileTypeOptions>,
+}
+
+impl FileTypes {
+  pub fn new() -> Self {
+    FileTypes::default()
+  }
+
+  /// Get the user defined rules.
+  pub fn rules(&self) -> &Vec<FileTypeRule> {
+    &self.rules
+  }
+
+  /// Add a rule, the user defined rules are matched in the added order, before the built-in
+  /// rules.
+  pub fn add(&mut self, pattern: &str, file_type: &str) {
+    self.rules.push(FileTypeRule {
+      pattern: pattern.to_string(),
+      file_type: file_type.to_string(),
+    });
+  }
+
+  /// Set the user defined options for the filetype, they're merged with the previously set ones.
+  pub fn set(&mut self, file_type: &str, options: &FileTypeOptions) {
+    self
+      .overrides
+      .entry(file_type.to_string())
+      .or_default()
+      .merge(options);
+  }
+
+  /// Detect the filetype of the file, returns `None` if no rule matches.
+  pub fn detect(&self, absolute_filename: &Path) -> Option<String> {
+    self
+      .rules
+      .iter()
+      .find(|rule| path_match(&rule.pattern, absolute_filename))
+      .map(|rule| rule.file_type.clone())
+      .or_else(|| {
+        DETECT_RULES
+          .iter()
+          .find(|(pattern, _)| path_match(pattern, absolute_filename))
+          .map(|(_, file_type)| file_type.to_string())
+      })
+  }
+
+  /// Get the options of the filetype, i.e. the built-in bundle overridden by the user defined
+  /// options.
+  pub fn options(&self, file_type: &str) -> FileTypeOptions {
+    let mut options = BUNDLES
+      .iter()
+      .find(|(name, _)| *name == file_type)
+      .map(|(_, bundle)| FileTypeOptions::from(bundle))
+      .unwrap_or_default();
+    if let Some(overrides) = self.overrides.get(file_type) {
+      options.merge(overrides);
+    }
+    options
+  }
+
+  /// Detect the filetype of the file and apply its options, returns the filetype.
+  pub fn apply(
+    &self,
+    absolute_filename: &Path,
+    options: &mut BufferLocalOptions,
+  ) -> Option<String> {
+    let file_type = self.detect(absolute_filename)?;
+    options.set_file_type(&file_type);
+    self.options(&file_type).apply(options);
+    Some(file_type)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn detect1() {
+    let mut file_types = FileTypes::new();
+    assert_eq!(
+      file_types.detect(Path::new("/a/main.rs")),
+      Some("rust".to_string())
+    );
+    assert_eq!(
+      file_types.detect(Path::new("/a/Makefile")),
+      Some("make".to_string())
+    );
+    assert_eq!(file_types.detect(Path::new("/a/README")), None);
+
+    // The user defined rules come first.
+    file_types.add("*.rs", "rustx");
+    file_types.add("/a/*/README", "text");
+    assert_eq!(
+      file_types.detect(Path::new("/a/main.rs")),
+      Some("rustx".to_string())
+    );
+    assert_eq!(
+      file_types.detect(Path::new("/a/b/README")),
+      Some("text".to_string())
+    );
+  }
+
+  #[test]
+  fn apply1() {
+    let mut file_types = FileTypes::new();
+    let mut options = BufferLocalOptions::default();
+    assert_eq!(
+      file_types.apply(Path::new("/a/main.py"), &mut options),
+      Some("python".to_string())
+    );
+    assert_eq!(options.file_type(), "python");
+    assert_eq!(options.comment_string(), "# %s");
+    assert_eq!(options.shift_width(), 4);
+    assert!(options.expand_tab());
+    assert_eq!(options.suffixes_add(), ".py");
+
+    // The user defined options override the built-in ones.
+    file_types.set(
+      "python",
+      &FileTypeOptions {
+        shift_width: Some(2),
+        ..Default::default()
+      },
+    );
+    file_types.set(
+      "python",
+      &FileTypeOptions {
+        tab_stop: Some(2),
+        ..Default::default()
+      },
+    );
+    let mut options = BufferLocalOptions::default();
+    file_types.apply(Path::new("/a/main.py"), &mut options);
+    assert_eq!(options.comment_string(), "# %s");
+    assert_eq!(options.shift_width(), 2);
+    assert_eq!(options.tab_stop(), 2);
+
+    // Unknown filetype keeps the defaults.
+    let mut options = BufferLocalOptions::default();
+    assert_eq!(file_types.apply(Path::new("/a/README"), &mut options), None);
+    assert!(options.file_type().is_empty());
+    assert_eq!(
+      options.comment_string(),
+      crate::defaults::buf::COMMENT_STRING
+    );
+  }
+}
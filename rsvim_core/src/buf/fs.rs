@@ -0,0 +1,349 @@
+//! File system provider for buffers.
+//!
+//! The buffers read and write files through the [`FsProvider`], i.e. opening files, `:write`,
+//! `:Delete` and `:Rename`. By default it's the local file system [`StdFs`]. When the editor core
+//! is embedded in another application (or in tests), it can be replaced with the in-memory
+//! [`MemoryFs`] or any other implementation.
+//!
+//! NOTE: The directory buffers (see [`explorer`](crate::buf::explorer)) are always on the local
+//! file system.
+
+use crate::buf::trash::{move_to_trash, trash_dir};
+use crate::buf::write::{backup_path, write_file, WriteOptions};
+use crate::res::IoResult;
+
+use ahash::AHashMap as HashMap;
+use parking_lot::RwLock;
+use std::fs::Metadata;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The file system provider.
+pub trait FsProvider: std::fmt::Debug + Send + Sync {
+  /// Whether the file exists.
+  fn exists(&self, path: &Path) -> IoResult<bool>;
+
+  /// Read all bytes of the file, and its metadata (if the provider has).
+  fn read(&self, path: &Path) -> IoResult<(Vec<u8>, Option<Metadata>)>;
+
+  /// Get the file size in bytes.
+  fn file_size(&self, path: &Path) -> IoResult<u64>;
+
+  /// Open the file for reading chunk by chunk, i.e. for the huge files.
+  fn open(&self, path: &Path) -> IoResult<Box<dyn Read + Send>>;
+
+  /// Get the file metadata (if the provider has).
+  ///
+  /// # Errors
+  ///
+  /// If the file doesn't exist.
+  fn metadata(&self, path: &Path) -> IoResult<Option<Metadata>>;
+
+  /// Write the file, the contents are written by `write`. Returns the written file path, i.e. the
+  /// symlink target. The nonexistent parent directories are created if `mkdir` or the 'mkdir'
+  /// option is on, see [`write_file`].
+  fn write_file(
+    &self,
+    path: &Path,
+    options: &WriteOptions,
+    mkdir: bool,
+    write: &mut dyn FnMut(&mut dyn Write) -> IoResult<()>,
+  ) -> IoResult<PathBuf>;
+
+  /// Append the bytes to the file, the file is created if it doesn't exist.
+  fn append(&self, path: &Path, bytes: &[u8]) -> IoResult<()>;
+
+  /// Create the directory and all its nonexistent parent directories.
+  fn create_dir_all(&self, path: &Path) -> IoResult<()>;
+
+  /// Rename the file, the target file is replaced if it exists.
+  fn rename(&self, from: &Path, to: &Path) -> IoResult<()>;
+
+  /// Copy the file, the target file is replaced if it exists.
+  fn copy(&self, from: &Path, to: &Path) -> IoResult<()>;
+
+  /// Remove the file.
+  fn remove_file(&self, path: &Path) -> IoResult<()>;
+
+  /// Move the file to the platform trash, returns the path in the trash. Returns `None` if the
+  /// trash is not supported, the file is not changed.
+  fn trash(&self, path: &Path) -> IoResult<Option<PathBuf>>;
+}
+
+pub type FsProviderArc = Arc<dyn FsProvider>;
+
+#[derive(Debug, Default, Copy, Clone)]
+/// The local file system.
+pub struct StdFs;
+
+impl FsProvider for StdFs {
+  fn exists(&self, path: &Path) -> IoResult<bool> {
+    std::fs::exists(path)
+  }
+
+  fn read(&self, path: &Path) -> IoResult<(Vec<u8>, Option<Metadata>)> {
+    let fp = std::fs::File::open(path)?;
+    let metadata = fp.metadata()?;
+    let mut buf: Vec<u8> = Vec::new();
+    let mut reader = std::io::BufReader::new(fp);
+    reader.read_to_end(&mut buf)?;
+    Ok((buf, Some(metadata)))
+  }
+
+  fn file_size(&self, path: &Path) -> IoResult<u64> {
+    Ok(std::fs::metadata(path)?.len())
+  }
+
+  fn open(&self, path: &Path) -> IoResult<Box<dyn Read + Send>> {
+    let fp = std::fs::File::open(path)?;
+    Ok(Box::new(std::io::BufReader::new(fp)))
+  }
+
+  fn metadata(&self, path: &Path) -> IoResult<Option<Metadata>> {
+    std::fs::metadata(path).map(Some)
+  }
+
+  fn write_file(
+    &self,
+    path: &Path,
+    options: &WriteOptions,
+    mkdir: bool,
+    write: &mut dyn FnMut(&mut dyn Write) -> IoResult<()>,
+  ) -> IoResult<PathBuf> {
+    write_file(path, options, mkdir, write)
+  }
+
+  fn append(&self, path: &Path, bytes: &[u8]) -> IoResult<()> {
+    let mut fp = std::fs::OpenOptions::new()
+      .append(true)
+      .create(true)
+      .open(path)?;
+    fp.write_all(bytes)
+  }
+
+  fn create_dir_all(&self, path: &Path) -> IoResult<()> {
+    std::fs::create_dir_all(path)
+  }
+
+  fn rename(&self, from: &Path, to: &Path) -> IoResult<()> {
+    std::fs::rename(from, to)
+  }
+
+  fn copy(&self, from: &Path, to: &Path) -> IoResult<()> {
+    std::fs::copy(from, to).map(|_| ())
+  }
+
+  fn remove_file(&self, path: &Path) -> IoResult<()> {
+    std::fs::remove_file(path)
+  }
+
+  fn trash(&self, path: &Path) -> IoResult<Option<PathBuf>> {
+    match trash_dir() {
+      Some(trash) => move_to_trash(path, &trash).map(Some),
+      None => Ok(None),
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+/// The in-memory file system, the files are keyed by their absolute paths.
+pub struct MemoryFs {
+  files: RwLock<HashMap<PathBuf, Vec<u8>>>,
+}
+
+impl MemoryFs {
+  pub fn new() -> Self {
+    MemoryFs::default()
+  }
+
+  /// Create or overwrite a file.
+  pub fn insert(&self, path: &Path, bytes: &[u8]) {
+    self
+      .files
+      .write()
+      .insert(path.to_path_buf(), bytes.to_vec());
+  }
+
+  /// Remove a file.
+  pub fn remove(&self, path: &Path) -> Option<Vec<u8>> {
+    self.files.write().remove(path)
+  }
+}
+
+impl FsProvider for MemoryFs {
+  fn exists(&self, path: &Path) -> IoResult<bool> {
+    Ok(self.files.read().contains_key(path))
+  }
+
+  fn read(&self, path: &Path) -> IoResult<(Vec<u8>, Option<Metadata>)> {
+    match self.files.read().get(path) {
+      Some(bytes) => Ok((bytes.clone(), None)),
+      None => Err(not_found(path)),
+    }
+  }
+
+  fn file_size(&self, path: &Path) -> IoResult<u64> {
+    match self.files.read().get(path) {
+      Some(bytes) => Ok(bytes.len() as u64),
+      None => Err(not_found(path)),
+    }
+  }
+
+  fn open(&self, path: &Path) -> IoResult<Box<dyn Read + Send>> {
+    match self.files.read().get(path) {
+      Some(bytes) => Ok(Box::new(std::io::Cursor::new(bytes.clone()))),
+      None => Err(not_found(path)),
+    }
+  }
+
+  fn metadata(&self, path: &Path) -> IoResult<Option<Metadata>> {
+    if self.files.read().contains_key(path) {
+      Ok(None)
+    } else {
+      Err(not_found(path))
+    }
+  }
+
+  // There're no directories or symlinks, and the 'backup' option is the same, i.e. the old file is
+  // kept as `{file}~`.
+  fn write_file(
+    &self,
+    path: &Path,
+    options: &WriteOptions,
+    _mkdir: bool,
+    write: &mut dyn FnMut(&mut dyn Write) -> IoResult<()>,
+  ) -> IoResult<PathBuf> {
+    let mut bytes: Vec<u8> = vec![];
+    write(&mut bytes)?;
+    let mut files = self.files.write();
+    if options.backup() {
+      if let Some(old) = files.get(path).cloned() {
+        files.insert(backup_path(path), old);
+      }
+    }
+    files.insert(path.to_path_buf(), bytes);
+    Ok(path.to_path_buf())
+  }
+
+  fn append(&self, path: &Path, bytes: &[u8]) -> IoResult<()> {
+    self
+      .files
+      .write()
+      .entry(path.to_path_buf())
+      .or_default()
+      .extend_from_slice(bytes);
+    Ok(())
+  }
+
+  fn create_dir_all(&self, _path: &Path) -> IoResult<()> {
+    Ok(())
+  }
+
+  fn rename(&self, from: &Path, to: &Path) -> IoResult<()> {
+    let mut files = self.files.write();
+    match files.remove(from) {
+      Some(bytes) => {
+        files.insert(to.to_path_buf(), bytes);
+        Ok(())
+      }
+      None => Err(not_found(from)),
+    }
+  }
+
+  fn copy(&self, from: &Path, to: &Path) -> IoResult<()> {
+    let mut files = self.files.write();
+    match files.get(from).cloned() {
+      Some(bytes) => {
+        files.insert(to.to_path_buf(), bytes);
+        Ok(())
+      }
+      None => Err(not_found(from)),
+    }
+  }
+
+  fn remove_file(&self, path: &Path) -> IoResult<()> {
+    match self.files.write().remove(path) {
+      Some(_) => Ok(()),
+      None => Err(not_found(path)),
+    }
+  }
+
+  fn trash(&self, _path: &Path) -> IoResult<Option<PathBuf>> {
+    Ok(None)
+  }
+}
+
+fn not_found(path: &Path) -> std::io::Error {
+  std::io::Error::new(
+    std::io::ErrorKind::NotFound,
+    format!("{:?} not found", path),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn memory_fs1() {
+    let fs = MemoryFs::new();
+    let path = Path::new("/tmp/a.txt");
+    assert!(!fs.exists(path).unwrap());
+    assert!(fs.read(path).is_err());
+
+    fs.insert(path, b"hello\n");
+    assert!(fs.exists(path).unwrap());
+    let (bytes, metadata) = fs.read(path).unwrap();
+    assert_eq!(bytes, b"hello\n");
+    assert!(metadata.is_none());
+    assert_eq!(fs.file_size(path).unwrap(), 6);
+    let mut text = String::new();
+    fs.open(path).unwrap().read_to_string(&mut text).unwrap();
+    assert_eq!(text, "hello\n");
+
+    fs.append(path, b"world\n").unwrap();
+    assert_eq!(fs.read(path).unwrap().0, b"hello\nworld\n");
+
+    assert_eq!(fs.remove(path), Some(b"hello\nworld\n".to_vec()));
+    assert!(!fs.exists(path).unwrap());
+    fs.append(path, b"new\n").unwrap();
+    assert_eq!(fs.read(path).unwrap().0, b"new\n");
+  }
+
+  #[test]
+  fn memory_fs2() {
+    let fs = MemoryFs::new();
+    let a = Path::new("/tmp/a.txt");
+    let b = Path::new("/tmp/b.txt");
+    let mut options = WriteOptions::default();
+    options.set_backup(true);
+    let write = |text: &'static str| {
+      move |writer: &mut dyn Write| -> IoResult<()> { writer.write_all(text.as_bytes()) }
+    };
+
+    assert_eq!(
+      fs.write_file(a, &options, false, &mut write("hello\n"))
+        .unwrap(),
+      a
+    );
+    assert!(fs.metadata(a).unwrap().is_none());
+    assert!(fs.metadata(b).is_err());
+    fs.write_file(a, &options, false, &mut write("world\n"))
+      .unwrap();
+    assert_eq!(fs.read(a).unwrap().0, b"world\n");
+    assert_eq!(fs.read(&backup_path(a)).unwrap().0, b"hello\n");
+
+    fs.copy(a, b).unwrap();
+    fs.remove_file(a).unwrap();
+    assert!(fs.remove_file(a).is_err());
+    fs.rename(b, a).unwrap();
+    assert!(!fs.exists(b).unwrap());
+    assert_eq!(fs.read(a).unwrap().0, b"world\n");
+    assert!(fs.rename(b, a).is_err());
+
+    // The file is not changed if the trash is not supported.
+    assert_eq!(fs.trash(a).unwrap(), None);
+    assert!(fs.exists(a).unwrap());
+  }
+}
@@ -0,0 +1,346 @@
+//! Buffer highlight namespaces.
+//!
+//! A namespace is a group of highlights with a priority, i.e. the tree-sitter captures, the
+//! diagnostics and the highlights added by plugins. When painting a cell, all the highlights on it
+//! are layered from low priority to high priority (the namespace created earlier first if the
+//! priorities are equal), the higher layer overrides the foreground/background colors of the
+//! lower layers, and the attributes are combined. See [`resolve`].
+//!
+//! The window-local highlights (i.e. the search matches and the visual selection) are not stored
+//! in buffer, but layered with the same rule with the builtin priorities in
+//! [`defaults::highlight`](crate::defaults::highlight).
+//!
+//! NOTE: The highlights are placed at `(line_idx, char_idx)`, they don't follow the text when the
+//! buffer is edited, the owner should clear and re-add them.
+
+use crossterm::style::{Attribute, Attributes, Color};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// The namespace ID.
+///
+/// NOTE: Start from 1, the `0` is reserved for the window-local highlights.
+pub type NamespaceId = usize;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// The highlight style, the colors are `None` if not specified.
+pub struct HighlightStyle {
+  pub fg: Option<Color>,
+  pub bg: Option<Color>,
+  pub attrs: Attributes,
+}
+
+impl HighlightStyle {
+  pub fn new(fg: Option<Color>, bg: Option<Color>, attrs: Attributes) -> Self {
+    HighlightStyle { fg, bg, attrs }
+  }
+
+  /// The search match style.
+  pub fn search() -> Self {
+    HighlightStyle::new(
+      Some(Color::Black),
+      Some(Color::Yellow),
+      Attributes::default(),
+    )
+  }
+
+  /// The visual selection style.
+  pub fn selection() -> Self {
+    HighlightStyle::new(None, None, Attribute::Reverse.into())
+  }
+
+  /// Layer the `other` style on top of this style.
+  pub fn merge(&mut self, other: &HighlightStyle) {
+    if other.fg.is_some() {
+      self.fg = other.fg;
+    }
+    if other.bg.is_some() {
+      self.bg = other.bg;
+    }
+    self.attrs.extend(other.attrs);
+  }
+}
+
+/// Parse a color, i.e. the `#rrggbb` hex code, or the color name (`red`, `dark_blue`, etc).
+pub fn parse_color(value: &str) -> Option<Color> {
+  let value = value.trim();
+  if let Some(hex) = value.strip_prefix('#') {
+    if hex.len() != 6 {
+      return None;
+    }
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    return Some(Color::Rgb {
+      r: (rgb >> 16) as u8,
+      g: (rgb >> 8) as u8,
+      b: rgb as u8,
+    });
+  }
+  Color::try_from(value).ok()
+}
+
+/// Parse comma-separated attribute names, i.e. `bold,italic,underline,reverse,strikethrough`.
+///
+/// # Errors
+///
+/// If there's invalid attribute name.
+pub fn parse_attrs(value: &str) -> Result<Attributes, String> {
+  let mut attrs = Attributes::default();
+  for name in value.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()) {
+    let attr = match name {
+      "bold" => Attribute::Bold,
+      "italic" => Attribute::Italic,
+      "underline" => Attribute::Underlined,
+      "undercurl" => Attribute::Undercurled,
+      "reverse" => Attribute::Reverse,
+      "strikethrough" => Attribute::CrossedOut,
+      _ => return Err(format!("Invalid highlight attribute: {}", name)),
+    };
+    attrs.set(attr);
+  }
+  Ok(attrs)
+}
+
+/// Layer the styles by `(priority, namespace)` from low to high, returns `None` if there's no
+/// style.
+pub fn resolve(mut layers: Vec<(u16, NamespaceId, HighlightStyle)>) -> Option<HighlightStyle> {
+  if layers.is_empty() {
+    return None;
+  }
+  layers.sort_by_key(|(priority, ns_id, _)| (*priority, *ns_id));
+  let mut result = HighlightStyle::default();
+  for (_, _, style) in layers.iter() {
+    result.merge(style);
+  }
+  Some(result)
+}
+
+#[derive(Debug, Clone)]
+/// A highlight namespace.
+pub struct Namespace {
+  name: String,
+  priority: u16,
+
+  // Maps from line index to the char index ranges and their styles.
+  highlights: BTreeMap<usize, Vec<(Range<usize>, HighlightStyle)>>,
+}
+
+impl Namespace {
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn priority(&self) -> u16 {
+    self.priority
+  }
+
+  /// Get the highlights, maps from line index to the char index ranges and their styles.
+  pub fn highlights(&self) -> &BTreeMap<usize, Vec<(Range<usize>, HighlightStyle)>> {
+    &self.highlights
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The highlight namespaces of a buffer.
+pub struct HighlightNamespaces {
+  namespaces: BTreeMap<NamespaceId, Namespace>,
+  next_id: NamespaceId,
+}
+
+impl HighlightNamespaces {
+  pub fn new() -> Self {
+    HighlightNamespaces {
+      namespaces: BTreeMap::new(),
+      next_id: 1,
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.namespaces.is_empty()
+  }
+
+  pub fn get(&self, ns_id: NamespaceId) -> Option<&Namespace> {
+    self.namespaces.get(&ns_id)
+  }
+
+  /// Get the namespace ID by name.
+  pub fn id_of(&self, name: &str) -> Option<NamespaceId> {
+    self
+      .namespaces
+      .iter()
+      .find(|(_, ns)| ns.name == name)
+      .map(|(ns_id, _)| *ns_id)
+  }
+
+  /// Create a namespace, or get the existing namespace with the same name (its priority is
+  /// updated).
+  pub fn create(&mut self, name: &str, priority: u16) -> NamespaceId {
+    if let Some(ns_id) = self.id_of(name) {
+      self.namespaces.get_mut(&ns_id).unwrap().priority = priority;
+      return ns_id;
+    }
+    let ns_id = self.next_id;
+    self.next_id += 1;
+    self.namespaces.insert(
+      ns_id,
+      Namespace {
+        name: name.to_string(),
+        priority,
+        highlights: BTreeMap::new(),
+      },
+    );
+    ns_id
+  }
+
+  /// Remove a namespace and all its highlights.
+  pub fn remove(&mut self, ns_id: NamespaceId) -> Option<Namespace> {
+    self.namespaces.remove(&ns_id)
+  }
+
+  /// Add a highlight to the char index `range` of the line, returns `false` if the namespace
+  /// doesn't exist.
+  pub fn add(
+    &mut self,
+    ns_id: NamespaceId,
+    line_idx: usize,
+    range: Range<usize>,
+    style: HighlightStyle,
+  ) -> bool {
+    match self.namespaces.get_mut(&ns_id) {
+      Some(ns) => {
+        ns.highlights
+          .entry(line_idx)
+          .or_default()
+          .push((range, style));
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Clear the highlights of the namespace in the line index `lines` range.
+  pub fn clear(&mut self, ns_id: NamespaceId, lines: Range<usize>) {
+    if let Some(ns) = self.namespaces.get_mut(&ns_id) {
+      ns.highlights
+        .retain(|line_idx, _| !lines.contains(line_idx));
+    }
+  }
+
+  /// Get all the styles on the char, with their priorities and namespaces.
+  pub fn styles_at(
+    &self,
+    line_idx: usize,
+    char_idx: usize,
+  ) -> Vec<(u16, NamespaceId, HighlightStyle)> {
+    let mut result = vec![];
+    for (ns_id, ns) in self.namespaces.iter() {
+      if let Some(highlights) = ns.highlights.get(&line_idx) {
+        for (range, style) in highlights.iter() {
+          if range.contains(&char_idx) {
+            result.push((ns.priority, *ns_id, *style));
+          }
+        }
+      }
+    }
+    result
+  }
+}
+
+impl Default for HighlightNamespaces {
+  fn default() -> Self {
+    HighlightNamespaces::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_color1() {
+    assert_eq!(
+      parse_color("#ff8000"),
+      Some(Color::Rgb {
+        r: 255,
+        g: 128,
+        b: 0
+      })
+    );
+    assert_eq!(parse_color("Red"), Some(Color::Red));
+    assert_eq!(parse_color("dark_blue"), Some(Color::DarkBlue));
+    assert_eq!(parse_color("#fff"), None);
+    assert_eq!(parse_color("unknown"), None);
+  }
+
+  #[test]
+  fn parse_attrs1() {
+    assert_eq!(
+      parse_attrs("bold, reverse"),
+      Ok(Attributes::from(Attribute::Bold).with(Attribute::Reverse))
+    );
+    assert_eq!(parse_attrs(""), Ok(Attributes::default()));
+    assert!(parse_attrs("blink").is_err());
+  }
+
+  #[test]
+  fn resolve1() {
+    let mut namespaces = HighlightNamespaces::new();
+    let low = namespaces.create("treesitter", 100);
+    let high = namespaces.create("diagnostic", 150);
+    assert_eq!(namespaces.create("treesitter", 100), low);
+
+    let bold: Attributes = Attribute::Bold.into();
+    namespaces.add(
+      high,
+      0,
+      2..4,
+      HighlightStyle::new(None, Some(Color::Red), Attributes::default()),
+    );
+    namespaces.add(
+      low,
+      0,
+      0..5,
+      HighlightStyle::new(Some(Color::Blue), None, bold),
+    );
+    namespaces.add(
+      low,
+      0,
+      3..4,
+      HighlightStyle::new(None, Some(Color::Green), Attributes::default()),
+    );
+
+    assert_eq!(resolve(namespaces.styles_at(0, 5)), None);
+    assert_eq!(
+      resolve(namespaces.styles_at(0, 0)),
+      Some(HighlightStyle::new(Some(Color::Blue), None, bold))
+    );
+    // The high priority background wins, no matter the order of adding.
+    assert_eq!(
+      resolve(namespaces.styles_at(0, 3)),
+      Some(HighlightStyle::new(
+        Some(Color::Blue),
+        Some(Color::Red),
+        bold
+      ))
+    );
+
+    // Layer window-local highlights on top.
+    let mut layers = namespaces.styles_at(0, 3);
+    layers.push((200, 0, HighlightStyle::search()));
+    assert_eq!(
+      resolve(layers),
+      Some(HighlightStyle::new(
+        Some(Color::Black),
+        Some(Color::Yellow),
+        bold
+      ))
+    );
+
+    namespaces.clear(high, 0..1);
+    assert_eq!(
+      resolve(namespaces.styles_at(0, 2)),
+      Some(HighlightStyle::new(Some(Color::Blue), None, bold))
+    );
+    assert!(namespaces.remove(low).is_some());
+    assert_eq!(resolve(namespaces.styles_at(0, 2)), None);
+  }
+}
@@ -0,0 +1,276 @@
+//! Chunked (async) file loading for huge buffers.
+//!
+//! The file is read chunk by chunk on a background task, each chunk is decoded by the
+//! [`BufferLoader`] and appended to the buffer, thus the already-loaded prefix can be rendered
+//! immediately. The buffer is read-only until the load completes.
+
+use crate::buf::opt::{FileEncoding, FileFormat};
+
+#[derive(Debug, Clone)]
+/// The incremental decoder for the file chunks.
+///
+/// The incomplete char and the trailing `\r` at the end of a chunk are kept to next chunk, thus
+/// the multi-bytes chars and the `\r\n` line endings are never split. The file encoding (see
+/// [`FileEncoding::detect`]) is detected on the first chunk, the file format (see
+/// [`FileFormat::detect`]) is detected on the first complete line ending, the text before it is
+/// kept until then.
+///
+/// NOTE: Only the first chunk is used for detecting the encoding, if it's valid UTF-8 but the
+/// following chunks are not, the invalid bytes are replaced with `U+FFFD`.
+pub struct BufferLoader {
+  encoding: FileEncoding,
+  encoding_detected: bool,
+  file_format: Option<FileFormat>,
+  pending: Vec<u8>,
+  undetected: String,
+  loaded_bytes: u64,
+  total_bytes: u64,
+}
+
+impl BufferLoader {
+  pub fn new(encoding: FileEncoding, total_bytes: u64) -> Self {
+    BufferLoader {
+      encoding,
+      encoding_detected: false,
+      file_format: None,
+      pending: Vec::new(),
+      undetected: String::new(),
+      loaded_bytes: 0,
+      total_bytes,
+    }
+  }
+
+  /// The detected file encoding, or the initial encoding if nothing is decoded yet.
+  pub fn encoding(&self) -> FileEncoding {
+    self.encoding
+  }
+
+  /// The detected file format, or `None` if there's no line ending decoded yet.
+  pub fn file_format(&self) -> Option<FileFormat> {
+    self.file_format
+  }
+
+  pub fn loaded_bytes(&self) -> u64 {
+    self.loaded_bytes
+  }
+
+  pub fn total_bytes(&self) -> u64 {
+    self.total_bytes
+  }
+
+  /// The loaded percentage, from 0 to 100.
+  pub fn percentage(&self) -> u8 {
+    (self.loaded_bytes * 100)
+      .checked_div(self.total_bytes)
+      .map_or(100, |percentage| percentage.min(100) as u8)
+  }
+
+  /// Decode the `bytes` chunk, returns the complete text with line endings normalized to `\n`.
+  pub fn push(&mut self, bytes: &[u8]) -> String {
+    self.loaded_bytes += bytes.len() as u64;
+    self.pending.extend_from_slice(bytes);
+    if !self.encoding_detected {
+      self.detect_encoding(false);
+    }
+    let rest = self
+      .pending
+      .split_off(complete_len(self.encoding, &self.pending));
+    let complete = std::mem::replace(&mut self.pending, rest);
+    self.decode(&complete)
+  }
+
+  /// Decode the remaining bytes at the end of file, the file format is still `None` if there's no
+  /// line ending in the file.
+  pub fn finish(&mut self) -> String {
+    if !self.encoding_detected {
+      self.detect_encoding(true);
+    }
+    let rest = std::mem::take(&mut self.pending);
+    let text = self.decode(&rest);
+    if self.file_format.is_none() {
+      std::mem::take(&mut self.undetected)
+    } else {
+      text
+    }
+  }
+
+  // Detect the encoding on the pending bytes and remove the BOM, it waits for more bytes if the
+  // BOM may be incomplete (unless it's the end of file).
+  fn detect_encoding(&mut self, eof: bool) {
+    let boms = [
+      FileEncoding::Utf8Bom.bom(),
+      FileEncoding::Utf16Le.bom(),
+      FileEncoding::Utf16Be.bom(),
+    ];
+    let pending = &self.pending;
+    if pending.is_empty()
+      || (!eof
+        && boms
+          .iter()
+          .any(|bom| bom.len() > pending.len() && bom.starts_with(pending)))
+    {
+      return;
+    }
+    self.encoding = match FileEncoding::detect(pending) {
+      // The last UTF-8 char is split.
+      FileEncoding::Latin1
+        if std::str::from_utf8(pending).is_err_and(|e| e.error_len().is_none()) =>
+      {
+        FileEncoding::Utf8
+      }
+      encoding => encoding,
+    };
+    self.encoding_detected = true;
+    self.pending.drain(..self.encoding.bom().len());
+  }
+
+  fn decode(&mut self, bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+      return String::new();
+    }
+    let text = self.encoding.decode_chunk(bytes);
+    match self.file_format {
+      Some(file_format) => file_format.normalize(&text),
+      None => {
+        // NOTE: The trailing `\r` is kept in `pending`, thus a `\r` here is always followed by
+        // another char, i.e. the line ending is complete.
+        self.undetected.push_str(&text);
+        if !self.undetected.contains(['\n', '\r']) {
+          return String::new();
+        }
+        let text = std::mem::take(&mut self.undetected);
+        let file_format = FileFormat::detect(&text);
+        self.file_format = Some(file_format);
+        file_format.normalize(&text)
+      }
+    }
+  }
+}
+
+// The length of the complete bytes, i.e. excludes the trailing `\r` or incomplete char.
+fn complete_len(encoding: FileEncoding, bytes: &[u8]) -> usize {
+  match encoding {
+    FileEncoding::Utf8 | FileEncoding::Utf8Bom => complete_len_utf8(bytes),
+    FileEncoding::Latin1 => {
+      if bytes.last() == Some(&b'\r') {
+        bytes.len() - 1
+      } else {
+        bytes.len()
+      }
+    }
+    FileEncoding::Utf16Le | FileEncoding::Utf16Be => {
+      let end = bytes.len() - bytes.len() % 2;
+      if end == 0 {
+        return 0;
+      }
+      let last = [bytes[end - 2], bytes[end - 1]];
+      let last = if encoding == FileEncoding::Utf16Le {
+        u16::from_le_bytes(last)
+      } else {
+        u16::from_be_bytes(last)
+      };
+      // The high surrogate, or `\r`.
+      if (0xD800..=0xDBFF).contains(&last) || last == 0x000D {
+        end - 2
+      } else {
+        end
+      }
+    }
+  }
+}
+
+fn complete_len_utf8(bytes: &[u8]) -> usize {
+  let end = bytes.len();
+  if bytes.last() == Some(&b'\r') {
+    return end - 1;
+  }
+  for i in (end.saturating_sub(3)..end).rev() {
+    let b = bytes[i];
+    // Skip the continuation bytes, i.e. `10xxxxxx`.
+    if b & 0xC0 == 0x80 {
+      continue;
+    }
+    let char_len = match b {
+      0xF0..=0xFF => 4,
+      0xE0..=0xEF => 3,
+      0xC0..=0xDF => 2,
+      _ => 1,
+    };
+    return if i + char_len > end { i } else { end };
+  }
+  end
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn push1() {
+    let mut loader = BufferLoader::new(FileEncoding::Utf8, 12);
+    let bytes = "你好\r\nab".as_bytes();
+    // Split in the middle of `好`.
+    // The file format is not detected until the first line ending.
+    assert_eq!(loader.push(&bytes[0..4]), "");
+    assert_eq!(loader.file_format(), None);
+    assert_eq!(loader.push(&bytes[4..6]), "");
+    // Split between `\r` and `\n`.
+    assert_eq!(loader.push(&bytes[6..7]), "");
+    assert_eq!(loader.file_format(), None);
+    assert_eq!(loader.push(&bytes[7..]), "你好\nab");
+    assert_eq!(loader.file_format(), Some(FileFormat::Dos));
+    assert_eq!(loader.finish(), "");
+    assert_eq!(loader.loaded_bytes(), 10);
+    assert_eq!(loader.percentage(), 83);
+  }
+
+  #[test]
+  fn push2() {
+    let mut loader = BufferLoader::new(FileEncoding::Utf8, 0);
+    assert_eq!(loader.percentage(), 100);
+    // The file format is detected on the first line ending.
+    assert_eq!(loader.push(b"a\r\nb\r"), "a\nb");
+    assert_eq!(loader.file_format(), Some(FileFormat::Dos));
+    assert_eq!(loader.push(b"\nc\r"), "\nc");
+    assert_eq!(loader.finish(), "\r");
+  }
+
+  #[test]
+  fn push_utf16() {
+    let mut loader = BufferLoader::new(FileEncoding::Utf8, 0);
+    // The BOM is split.
+    assert_eq!(loader.push(b"\xFF"), "");
+    assert_eq!(loader.push(b"\xFEa\x00\r"), "");
+    assert_eq!(loader.encoding(), FileEncoding::Utf16Le);
+    // Split between `\r` and `\n`, and in the surrogate pair of `😀`.
+    assert_eq!(loader.push(b"\x00\n\x00=\xD8"), "a\n");
+    assert_eq!(loader.file_format(), Some(FileFormat::Dos));
+    assert_eq!(loader.push(b"\x00\xDE"), "😀");
+    assert_eq!(loader.finish(), "");
+  }
+
+  #[test]
+  fn push_latin1() {
+    let mut loader = BufferLoader::new(FileEncoding::Utf8, 0);
+    assert_eq!(loader.push(b"caf\xe9\r"), "");
+    assert_eq!(loader.encoding(), FileEncoding::Latin1);
+    assert_eq!(loader.push(b"\n\xff"), "café\nÿ");
+    assert_eq!(loader.file_format(), Some(FileFormat::Dos));
+    assert_eq!(loader.finish(), "");
+  }
+
+  #[test]
+  fn finish1() {
+    // There's no line ending.
+    let mut loader = BufferLoader::new(FileEncoding::Utf8, 0);
+    assert_eq!(loader.push(b"abc"), "");
+    assert_eq!(loader.finish(), "abc");
+    assert_eq!(loader.file_format(), None);
+
+    // The only line ending is the trailing `\r`.
+    let mut loader = BufferLoader::new(FileEncoding::Utf8, 0);
+    assert_eq!(loader.push(b"abc\r"), "");
+    assert_eq!(loader.finish(), "abc\n");
+    assert_eq!(loader.file_format(), Some(FileFormat::Mac));
+  }
+}
@@ -0,0 +1,319 @@
+//! Buffer marks.
+//!
+//! - `a`-`z`: The buffer-local marks.
+//! - `A`-`Z`: The global marks, a global mark is stored in the buffer it belongs to (thus it's
+//!   adjusted along with the buffer's edits), and it's unique among all buffers, see
+//!   [`BuffersManager::set_mark`](crate::buf::BuffersManager::set_mark).
+//! - `'`: The position before the latest jump.
+//! - `"`: The position when last exiting the buffer, see [`position`](crate::buf::position).
+//! - `.`: The position where the last change was made.
+//! - `^`: The position where the insert mode was stopped.
+//! - `<`/`>`: The start/end of the last visual selection, i.e. the `:'<,'>` range.
+//!
+//! The marks are adjusted when lines are inserted/deleted, a mark on a deleted line is removed.
+//! See: <https://vimhelp.org/motion.txt.html#mark-motions>.
+//!
+//! The [`LineMarks`] are the marked lines of `:global`, they're adjusted the same way.
+
+use crate::buf::undo::Delta;
+
+use ropey::Rope;
+use std::collections::{BTreeMap, VecDeque};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// The mark position.
+pub struct MarkPos {
+  /// Line index, starts from 0.
+  pub line_idx: usize,
+  /// Char index in the line, starts from 0.
+  pub char_idx: usize,
+}
+
+impl MarkPos {
+  pub fn new(line_idx: usize, char_idx: usize) -> Self {
+    MarkPos { line_idx, char_idx }
+  }
+}
+
+/// Whether the mark name is valid.
+pub fn is_valid_name(name: char) -> bool {
+  name.is_ascii_alphabetic() || matches!(name, '\'' | '"' | '.' | '^' | '<' | '>')
+}
+
+/// Whether the mark is a global mark, i.e. `A`-`Z`.
+pub fn is_global_name(name: char) -> bool {
+  name.is_ascii_uppercase()
+}
+
+/// Adjust the line `pos_line_idx` (i.e. a mark or a sign on it) by an edit at the line `line_idx`
+/// (and char `char_idx` in the line), which removes `removed_lines` line breaks and inserts
+/// `inserted_lines` line breaks. Returns the adjusted line index, or `None` if the line is deleted.
+///
+/// A line is deleted if both its start and its line break are removed. The lines after the edit
+/// are moved up/down.
+pub fn adjust_line_idx(
+  pos_line_idx: usize,
+  line_idx: usize,
+  char_idx: usize,
+  removed_lines: usize,
+  inserted_lines: usize,
+) -> Option<usize> {
+  let end_line_idx = line_idx + removed_lines;
+  if pos_line_idx < line_idx {
+    // Before the edit.
+    Some(pos_line_idx)
+  } else if pos_line_idx > end_line_idx {
+    // After the edit.
+    Some(pos_line_idx + inserted_lines - removed_lines)
+  } else if removed_lines == 0 {
+    // The line is pushed down if the lines are inserted at its start.
+    if char_idx == 0 {
+      Some(pos_line_idx + inserted_lines)
+    } else {
+      Some(pos_line_idx)
+    }
+  } else if pos_line_idx == line_idx {
+    // The first line is deleted if the edit starts at its start.
+    if char_idx > 0 {
+      Some(pos_line_idx)
+    } else {
+      None
+    }
+  } else if pos_line_idx == end_line_idx {
+    // The last line's tail is kept, and joined after the inserted lines.
+    Some(line_idx + inserted_lines)
+  } else {
+    // The lines in the middle are deleted.
+    None
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The marks of a buffer.
+pub struct Marks {
+  marks: BTreeMap<char, MarkPos>,
+}
+
+impl Marks {
+  pub fn new() -> Self {
+    Marks::default()
+  }
+
+  pub fn get(&self, name: char) -> Option<MarkPos> {
+    self.marks.get(&name).copied()
+  }
+
+  /// Set the mark, returns `false` if the name is invalid.
+  pub fn set(&mut self, name: char, pos: MarkPos) -> bool {
+    if !is_valid_name(name) {
+      return false;
+    }
+    self.marks.insert(name, pos);
+    true
+  }
+
+  pub fn remove(&mut self, name: char) -> Option<MarkPos> {
+    self.marks.remove(&name)
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = (&char, &MarkPos)> {
+    self.marks.iter()
+  }
+
+  /// Adjust the marks by an edit, see [`adjust_line_idx`].
+  pub fn adjust(
+    &mut self,
+    line_idx: usize,
+    char_idx: usize,
+    removed_lines: usize,
+    inserted_lines: usize,
+  ) {
+    if removed_lines == 0 && inserted_lines == 0 {
+      return;
+    }
+    self.marks.retain(|_, pos| {
+      match adjust_line_idx(
+        pos.line_idx,
+        line_idx,
+        char_idx,
+        removed_lines,
+        inserted_lines,
+      ) {
+        Some(adjusted) => {
+          pos.line_idx = adjusted;
+          true
+        }
+        None => false,
+      }
+    });
+  }
+
+  /// Adjust the marks by the `delta` before it's applied to the `rope`.
+  pub fn adjust_by_delta(&mut self, rope: &Rope, delta: &Delta) {
+    let line_idx = rope.char_to_line(delta.char_idx);
+    let char_idx = delta.char_idx - rope.line_to_char(line_idx);
+    let removed_lines = delta.removed.matches('\n').count();
+    let inserted_lines = delta.inserted.matches('\n').count();
+    self.adjust(line_idx, char_idx, removed_lines, inserted_lines);
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The marked lines of `:global`, i.e. the lines matched in the first pass, then the commands are
+/// executed on them one by one in the second pass. A line deleted (or joined into the previous
+/// line) by the commands is unmarked, thus it's skipped.
+///
+/// See: <https://vimhelp.org/repeat.txt.html#%3Aglobal>.
+pub struct LineMarks {
+  // The marked line indexes in ascending order.
+  lines: VecDeque<usize>,
+}
+
+impl LineMarks {
+  pub fn new() -> Self {
+    LineMarks::default()
+  }
+
+  /// Mark the `lines` (in ascending order), the previous marked lines are cleared.
+  pub fn set(&mut self, lines: Vec<usize>) {
+    self.lines = lines.into();
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.lines.is_empty()
+  }
+
+  pub fn clear(&mut self) {
+    self.lines.clear();
+  }
+
+  /// Unmark the first marked line and returns it.
+  pub fn pop_first(&mut self) -> Option<usize> {
+    self.lines.pop_front()
+  }
+
+  /// Adjust the marked lines by the `delta` before it's applied to the `rope`, see
+  /// [`adjust_line_idx`].
+  pub fn adjust_by_delta(&mut self, rope: &Rope, delta: &Delta) {
+    if self.lines.is_empty() {
+      return;
+    }
+    let line_idx = rope.char_to_line(delta.char_idx);
+    let char_idx = delta.char_idx - rope.line_to_char(line_idx);
+    let removed_lines = delta.removed.matches('\n').count();
+    let inserted_lines = delta.inserted.matches('\n').count();
+    if removed_lines == 0 && inserted_lines == 0 {
+      return;
+    }
+    let mut lines = VecDeque::with_capacity(self.lines.len());
+    for marked in self.lines.drain(..) {
+      if let Some(adjusted) =
+        adjust_line_idx(marked, line_idx, char_idx, removed_lines, inserted_lines)
+      {
+        // The joined lines are marked once.
+        if lines.back() != Some(&adjusted) {
+          lines.push_back(adjusted);
+        }
+      }
+    }
+    self.lines = lines;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn make_marks() -> Marks {
+    let mut marks = Marks::new();
+    for (name, line_idx) in [('a', 0), ('b', 1), ('c', 2), ('d', 3)] {
+      assert!(marks.set(name, MarkPos::new(line_idx, 1)));
+    }
+    marks
+  }
+
+  fn lines(marks: &Marks) -> Vec<(char, usize)> {
+    marks
+      .iter()
+      .map(|(name, pos)| (*name, pos.line_idx))
+      .collect()
+  }
+
+  #[test]
+  fn set1() {
+    let mut marks = Marks::new();
+    assert!(!marks.set('1', MarkPos::new(0, 0)));
+    assert!(marks.set('.', MarkPos::new(0, 0)));
+    assert_eq!(marks.get('.'), Some(MarkPos::new(0, 0)));
+    assert_eq!(marks.remove('.'), Some(MarkPos::new(0, 0)));
+    assert_eq!(marks.get('.'), None);
+    assert!(is_global_name('A'));
+    assert!(!is_global_name('a'));
+  }
+
+  #[test]
+  fn adjust1() {
+    // Insert 2 lines at the start of line 1.
+    let mut marks = make_marks();
+    marks.adjust(1, 0, 0, 2);
+    assert_eq!(lines(&marks), vec![('a', 0), ('b', 3), ('c', 4), ('d', 5)]);
+
+    // Insert 1 line in the middle of line 1, i.e. split line.
+    let mut marks = make_marks();
+    marks.adjust(1, 3, 0, 1);
+    assert_eq!(lines(&marks), vec![('a', 0), ('b', 1), ('c', 3), ('d', 4)]);
+
+    // Delete line 1, i.e. `dd`.
+    let mut marks = make_marks();
+    marks.adjust(1, 0, 1, 0);
+    assert_eq!(lines(&marks), vec![('a', 0), ('c', 1), ('d', 2)]);
+
+    // Join line 1 and line 2, i.e. `J`.
+    let mut marks = make_marks();
+    marks.adjust(1, 5, 1, 0);
+    assert_eq!(lines(&marks), vec![('a', 0), ('b', 1), ('c', 1), ('d', 2)]);
+
+    // Delete from the middle of line 0 to the middle of line 2.
+    let mut marks = make_marks();
+    marks.adjust(0, 2, 2, 0);
+    assert_eq!(lines(&marks), vec![('a', 0), ('c', 0), ('d', 1)]);
+  }
+
+  #[test]
+  fn adjust_by_delta1() {
+    let rope = Rope::from_str("a\nb\nc\nd\n");
+    let mut marks = make_marks();
+    // Delete "b\n".
+    marks.adjust_by_delta(&rope, &Delta::new(2, "b\n", ""));
+    assert_eq!(lines(&marks), vec![('a', 0), ('c', 1), ('d', 2)]);
+  }
+
+  #[test]
+  fn line_marks1() {
+    let rope = Rope::from_str("a\nb\nc\nd\n");
+    let mut line_marks = LineMarks::new();
+    line_marks.set(vec![0, 1, 2, 3]);
+    assert_eq!(line_marks.pop_first(), Some(0));
+    // Delete "b\n", i.e. `:g/./+1d` on line 0.
+    line_marks.adjust_by_delta(&rope, &Delta::new(2, "b\n", ""));
+    assert_eq!(line_marks.pop_first(), Some(1));
+
+    // Move the line 2 to the top, i.e. `:g/^/m0`.
+    let rope = Rope::from_str("a\nb\nc\nd\n");
+    let mut line_marks = LineMarks::new();
+    line_marks.set(vec![2, 3]);
+    assert_eq!(line_marks.pop_first(), Some(2));
+    line_marks.adjust_by_delta(&rope, &Delta::new(4, "c\n", ""));
+    let rope = Rope::from_str("a\nb\nd\n");
+    line_marks.adjust_by_delta(&rope, &Delta::new(0, "", "c\n"));
+    assert_eq!(line_marks.pop_first(), Some(3));
+    assert!(line_marks.is_empty());
+
+    // Join line 0 and line 1.
+    let rope = Rope::from_str("a\nb\n");
+    line_marks.set(vec![0, 1]);
+    line_marks.adjust_by_delta(&rope, &Delta::new(1, "\n", ""));
+    assert_eq!(line_marks.pop_first(), Some(0));
+    assert!(line_marks.is_empty());
+  }
+}
@@ -4,14 +4,24 @@ use crate::defaults;
 
 // Re-export
 pub use file_encoding::FileEncoding;
+pub use file_format::FileFormat;
 
 pub mod file_encoding;
+pub mod file_format;
 
 #[derive(Debug, Clone)]
 /// Local buffer options.
 pub struct BufferLocalOptions {
   tab_stop: u16,
   file_encoding: FileEncoding,
+  file_format: FileFormat,
+  file_type: String,
+  comment_string: String,
+  shift_width: u16,
+  expand_tab: bool,
+  suffixes_add: String,
+  undo_levels: usize,
+  undo_memory: usize,
 }
 
 impl Default for BufferLocalOptions {
@@ -40,6 +50,72 @@ impl BufferLocalOptions {
   pub fn set_file_encoding(&mut self, value: FileEncoding) {
     self.file_encoding = value;
   }
+
+  pub fn file_format(&self) -> FileFormat {
+    self.file_format
+  }
+
+  pub fn set_file_format(&mut self, value: FileFormat) {
+    self.file_format = value;
+  }
+
+  pub fn file_type(&self) -> &str {
+    &self.file_type
+  }
+
+  pub fn set_file_type(&mut self, value: &str) {
+    self.file_type = value.to_string();
+  }
+
+  pub fn comment_string(&self) -> &str {
+    &self.comment_string
+  }
+
+  pub fn set_comment_string(&mut self, value: &str) {
+    self.comment_string = value.to_string();
+  }
+
+  pub fn shift_width(&self) -> u16 {
+    self.shift_width
+  }
+
+  pub fn set_shift_width(&mut self, value: u16) {
+    self.shift_width = value;
+  }
+
+  pub fn expand_tab(&self) -> bool {
+    self.expand_tab
+  }
+
+  pub fn set_expand_tab(&mut self, value: bool) {
+    self.expand_tab = value;
+  }
+
+  pub fn suffixes_add(&self) -> &str {
+    &self.suffixes_add
+  }
+
+  pub fn set_suffixes_add(&mut self, value: &str) {
+    self.suffixes_add = value.to_string();
+  }
+
+  /// The 'undolevels' option, the maximum count of the undoable changes.
+  pub fn undo_levels(&self) -> usize {
+    self.undo_levels
+  }
+
+  pub fn set_undo_levels(&mut self, value: usize) {
+    self.undo_levels = value;
+  }
+
+  /// The 'undomemory' option, the maximum estimated memory (in bytes) of the undo states.
+  pub fn undo_memory(&self) -> usize {
+    self.undo_memory
+  }
+
+  pub fn set_undo_memory(&mut self, value: usize) {
+    self.undo_memory = value;
+  }
 }
 
 #[derive(Debug, Clone)]
@@ -47,6 +123,14 @@ impl BufferLocalOptions {
 pub struct BufferLocalOptionsBuilder {
   tab_stop: u16,
   file_encoding: FileEncoding,
+  file_format: FileFormat,
+  file_type: String,
+  comment_string: String,
+  shift_width: u16,
+  expand_tab: bool,
+  suffixes_add: String,
+  undo_levels: usize,
+  undo_memory: usize,
 }
 
 impl BufferLocalOptionsBuilder {
@@ -60,10 +144,58 @@ impl BufferLocalOptionsBuilder {
     self
   }
 
+  pub fn file_format(&mut self, value: FileFormat) -> &mut Self {
+    self.file_format = value;
+    self
+  }
+
+  pub fn file_type(&mut self, value: &str) -> &mut Self {
+    self.file_type = value.to_string();
+    self
+  }
+
+  pub fn comment_string(&mut self, value: &str) -> &mut Self {
+    self.comment_string = value.to_string();
+    self
+  }
+
+  pub fn shift_width(&mut self, value: u16) -> &mut Self {
+    self.shift_width = value;
+    self
+  }
+
+  pub fn expand_tab(&mut self, value: bool) -> &mut Self {
+    self.expand_tab = value;
+    self
+  }
+
+  pub fn suffixes_add(&mut self, value: &str) -> &mut Self {
+    self.suffixes_add = value.to_string();
+    self
+  }
+
+  pub fn undo_levels(&mut self, value: usize) -> &mut Self {
+    self.undo_levels = value;
+    self
+  }
+
+  pub fn undo_memory(&mut self, value: usize) -> &mut Self {
+    self.undo_memory = value;
+    self
+  }
+
   pub fn build(&self) -> BufferLocalOptions {
     BufferLocalOptions {
       tab_stop: self.tab_stop,
       file_encoding: self.file_encoding,
+      file_format: self.file_format,
+      file_type: self.file_type.clone(),
+      comment_string: self.comment_string.clone(),
+      shift_width: self.shift_width,
+      expand_tab: self.expand_tab,
+      suffixes_add: self.suffixes_add.clone(),
+      undo_levels: self.undo_levels,
+      undo_memory: self.undo_memory,
     }
   }
 }
@@ -73,6 +205,14 @@ impl Default for BufferLocalOptionsBuilder {
     BufferLocalOptionsBuilder {
       tab_stop: defaults::buf::TAB_STOP,
       file_encoding: defaults::buf::FILE_ENCODING,
+      file_format: defaults::buf::FILE_FORMAT,
+      file_type: String::new(),
+      comment_string: defaults::buf::COMMENT_STRING.to_string(),
+      shift_width: defaults::buf::SHIFT_WIDTH,
+      expand_tab: defaults::buf::EXPAND_TAB,
+      suffixes_add: defaults::buf::SUFFIXES_ADD.to_string(),
+      undo_levels: defaults::buf::UNDO_LEVELS,
+      undo_memory: defaults::buf::UNDO_MEMORY,
     }
   }
 }
@@ -86,5 +226,11 @@ mod tests {
     let opt1 = BufferLocalOptions::default();
     let opt2 = BufferLocalOptionsBuilder::default().build();
     assert_eq!(opt1.tab_stop(), opt2.tab_stop());
+    assert_eq!(opt1.file_format(), opt2.file_format());
+    assert_eq!(opt1.comment_string(), opt2.comment_string());
+    assert_eq!(opt1.shift_width(), opt2.shift_width());
+    assert!(opt1.file_type().is_empty());
+    assert_eq!(opt1.undo_levels(), opt2.undo_levels());
+    assert_eq!(opt1.undo_memory(), opt2.undo_memory());
   }
 }
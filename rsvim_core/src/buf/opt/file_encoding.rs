@@ -1,21 +1,170 @@
 //! The "file-encoding" option for Vim buffer.
+//!
+//! The buffer content always uses UTF-8 internally, the file's encoding is detected on load (see
+//! [`FileEncoding::detect`]) and the content is transcoded back on save, the names are the same
+//! with EditorConfig's `charset`.
+//!
+//! NOTE: The transcoding is implemented here instead of the `encoding_rs` crate, because its
+//! encoders follow the WHATWG Encoding Standard, which always outputs UTF-8 for UTF-16, thus the
+//! UTF-16 files cannot be written back.
 
+use std::borrow::Cow;
 use std::fmt::Display;
 use std::string::ToString;
 
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub enum FileEncoding {
   Utf8,
-  // Utf16,
-  // Utf32,
+  /// UTF-8 with BOM.
+  Utf8Bom,
+  /// UTF-16 little endian, it's written with BOM.
+  Utf16Le,
+  /// UTF-16 big endian, it's written with BOM.
+  Utf16Be,
+  /// ISO-8859-1.
+  Latin1,
+}
+
+impl FileEncoding {
+  /// The BOM (byte order mark) written at the beginning of the file.
+  pub fn bom(&self) -> &'static [u8] {
+    match self {
+      FileEncoding::Utf8 | FileEncoding::Latin1 => &[],
+      FileEncoding::Utf8Bom => UTF8_BOM,
+      FileEncoding::Utf16Le => UTF16LE_BOM,
+      FileEncoding::Utf16Be => UTF16BE_BOM,
+    }
+  }
+
+  /// Detect the encoding by the BOM at the beginning of the `bytes`.
+  pub fn detect_bom(bytes: &[u8]) -> Option<FileEncoding> {
+    if bytes.starts_with(UTF8_BOM) {
+      Some(FileEncoding::Utf8Bom)
+    } else if bytes.starts_with(UTF16LE_BOM) {
+      Some(FileEncoding::Utf16Le)
+    } else if bytes.starts_with(UTF16BE_BOM) {
+      Some(FileEncoding::Utf16Be)
+    } else {
+      None
+    }
+  }
+
+  /// Detect the encoding of the file `bytes`:
+  ///
+  /// 1. The BOM, see [`detect_bom`](FileEncoding::detect_bom).
+  /// 2. If most of the odd (or even) bytes are zero, and none of the others are, it's UTF-16
+  ///    without BOM, i.e. the ASCII text encoded in UTF-16 (it's also valid UTF-8).
+  /// 3. If it's valid UTF-8, it's [`Utf8`](FileEncoding::Utf8).
+  /// 4. Otherwise it's [`Latin1`](FileEncoding::Latin1), since any bytes are valid in it.
+  pub fn detect(bytes: &[u8]) -> FileEncoding {
+    if let Some(encoding) = FileEncoding::detect_bom(bytes) {
+      return encoding;
+    }
+    let units = bytes.len() / 2;
+    let (mut even_zeros, mut odd_zeros) = (0, 0);
+    for unit in bytes.chunks_exact(2) {
+      even_zeros += (unit[0] == 0) as usize;
+      odd_zeros += (unit[1] == 0) as usize;
+    }
+    if odd_zeros * 2 > units && even_zeros == 0 {
+      return FileEncoding::Utf16Le;
+    }
+    if even_zeros * 2 > units && odd_zeros == 0 {
+      return FileEncoding::Utf16Be;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+      return FileEncoding::Utf8;
+    }
+    FileEncoding::Latin1
+  }
+
+  /// Decode the file `bytes` to string, the BOM is removed and the invalid sequences are replaced
+  /// with `U+FFFD`.
+  pub fn decode(&self, bytes: &[u8]) -> String {
+    let bom = self.bom();
+    let bytes = if !bom.is_empty() && bytes.starts_with(bom) {
+      &bytes[bom.len()..]
+    } else {
+      bytes
+    };
+    self.decode_chunk(bytes)
+  }
+
+  /// Same with [`decode`](FileEncoding::decode), but the BOM is not removed, i.e. the chunks after
+  /// the first one.
+  pub fn decode_chunk(&self, bytes: &[u8]) -> String {
+    match self {
+      FileEncoding::Utf8 | FileEncoding::Utf8Bom => String::from_utf8_lossy(bytes).into_owned(),
+      FileEncoding::Latin1 => bytes.iter().map(|b| *b as char).collect(),
+      FileEncoding::Utf16Le | FileEncoding::Utf16Be => {
+        let units = bytes.chunks_exact(2).map(|unit| match self {
+          FileEncoding::Utf16Le => u16::from_le_bytes([unit[0], unit[1]]),
+          _ => u16::from_be_bytes([unit[0], unit[1]]),
+        });
+        let mut text: String = char::decode_utf16(units)
+          .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+          .collect();
+        if bytes.len() % 2 == 1 {
+          text.push(char::REPLACEMENT_CHARACTER);
+        }
+        text
+      }
+    }
+  }
+
+  /// Encode the `text` (without BOM), returns the bytes and the count of the chars which cannot
+  /// be encoded, they're replaced with `?`.
+  pub fn encode<'a>(&self, text: &'a str) -> (Cow<'a, [u8]>, usize) {
+    match self {
+      FileEncoding::Utf8 | FileEncoding::Utf8Bom => (Cow::Borrowed(text.as_bytes()), 0),
+      FileEncoding::Latin1 => {
+        let mut lossy = 0;
+        let bytes = text
+          .chars()
+          .map(|c| match u8::try_from(c) {
+            Ok(b) => b,
+            Err(_) => {
+              lossy += 1;
+              b'?'
+            }
+          })
+          .collect();
+        (Cow::Owned(bytes), lossy)
+      }
+      FileEncoding::Utf16Le | FileEncoding::Utf16Be => {
+        let bytes = text
+          .encode_utf16()
+          .flat_map(|unit| match self {
+            FileEncoding::Utf16Le => unit.to_le_bytes(),
+            _ => unit.to_be_bytes(),
+          })
+          .collect();
+        (Cow::Owned(bytes), 0)
+      }
+    }
+  }
+
+  /// The count of the chars in `text` which cannot be encoded.
+  pub fn count_unencodable(&self, text: &str) -> usize {
+    match self {
+      FileEncoding::Latin1 => text.chars().filter(|c| *c as u32 > 0xFF).count(),
+      _ => 0,
+    }
+  }
 }
 
 impl Display for FileEncoding {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
       FileEncoding::Utf8 => write!(f, "utf-8"),
-      // FileEncoding::Utf16 => "utf-16".to_string(),
-      // FileEncoding::Utf32 => "utf-32".to_string(),
+      FileEncoding::Utf8Bom => write!(f, "utf-8-bom"),
+      FileEncoding::Utf16Le => write!(f, "utf-16le"),
+      FileEncoding::Utf16Be => write!(f, "utf-16be"),
+      FileEncoding::Latin1 => write!(f, "latin1"),
     }
   }
 }
@@ -27,8 +176,10 @@ impl TryFrom<&str> for FileEncoding {
     let lower_value = value.to_lowercase();
     match lower_value.as_str() {
       "utf-8" | "utf8" => Ok(FileEncoding::Utf8),
-      // "utf-16" | "utf16" => Ok(FileEncoding::Utf16),
-      // "utf-32" | "utf32" => Ok(FileEncoding::Utf32),
+      "utf-8-bom" | "utf8-bom" => Ok(FileEncoding::Utf8Bom),
+      "utf-16le" | "utf16le" => Ok(FileEncoding::Utf16Le),
+      "utf-16be" | "utf16be" => Ok(FileEncoding::Utf16Be),
+      "latin1" | "iso-8859-1" => Ok(FileEncoding::Latin1),
       _ => Err("Unknown FileEncoding value".to_string()),
     }
   }
@@ -42,5 +193,63 @@ mod tests {
   fn display1() {
     let actual1 = format!("{}", FileEncoding::Utf8);
     assert_eq!(actual1, "utf-8");
+    for encoding in [
+      FileEncoding::Utf8,
+      FileEncoding::Utf8Bom,
+      FileEncoding::Utf16Le,
+      FileEncoding::Utf16Be,
+      FileEncoding::Latin1,
+    ] {
+      assert_eq!(
+        FileEncoding::try_from(encoding.to_string().as_str()),
+        Ok(encoding)
+      );
+    }
+  }
+
+  #[test]
+  fn decode1() {
+    assert_eq!(FileEncoding::Utf8.decode("你好".as_bytes()), "你好");
+    assert_eq!(FileEncoding::Utf8.decode(&[b'a', 0xff]), "a\u{fffd}");
+    assert_eq!(FileEncoding::Utf8Bom.decode(b"\xEF\xBB\xBFab"), "ab");
+    assert_eq!(FileEncoding::Latin1.decode(b"caf\xe9"), "café");
+    assert_eq!(FileEncoding::Utf16Le.decode(b"\xFF\xFEa\x00`O"), "a你");
+    assert_eq!(FileEncoding::Utf16Be.decode(b"\xFE\xFF\x00aO`"), "a你");
+    assert_eq!(FileEncoding::Utf16Le.decode(b"a\x00b"), "a\u{fffd}");
+  }
+
+  #[test]
+  fn detect1() {
+    assert_eq!(FileEncoding::detect("你好".as_bytes()), FileEncoding::Utf8);
+    assert_eq!(FileEncoding::detect(b""), FileEncoding::Utf8);
+    assert_eq!(
+      FileEncoding::detect(b"\xEF\xBB\xBFab"),
+      FileEncoding::Utf8Bom
+    );
+    assert_eq!(
+      FileEncoding::detect(b"\xFF\xFEa\x00"),
+      FileEncoding::Utf16Le
+    );
+    assert_eq!(
+      FileEncoding::detect(b"\xFE\xFF\x00a"),
+      FileEncoding::Utf16Be
+    );
+    assert_eq!(FileEncoding::detect(b"a\x00b\x00"), FileEncoding::Utf16Le);
+    assert_eq!(FileEncoding::detect(b"\x00a\x00b"), FileEncoding::Utf16Be);
+    assert_eq!(FileEncoding::detect(b"caf\xe9"), FileEncoding::Latin1);
+  }
+
+  #[test]
+  fn encode1() {
+    let (bytes, lossy) = FileEncoding::Latin1.encode("café你");
+    assert_eq!((bytes.as_ref(), lossy), (&b"caf\xe9?"[..], 1));
+    assert_eq!(FileEncoding::Latin1.count_unencodable("café你好"), 2);
+
+    let (bytes, lossy) = FileEncoding::Utf16Le.encode("a你");
+    assert_eq!((bytes.as_ref(), lossy), (&b"a\x00`O"[..], 0));
+    let (bytes, _) = FileEncoding::Utf16Be.encode("a你");
+    assert_eq!(bytes.as_ref(), b"\x00aO`");
+    let (bytes, _) = FileEncoding::Utf8Bom.encode("ab");
+    assert_eq!(bytes.as_ref(), b"ab");
   }
 }
@@ -0,0 +1,158 @@
+//! The "file-format" option for Vim buffer.
+//!
+//! The buffer content always uses `\n` as the line ending internally, the file's line ending is
+//! detected on load (see [`FileFormat::detect`]) and converted back on save.
+
+use std::fmt::Display;
+use std::string::ToString;
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum FileFormat {
+  /// `\n`
+  Unix,
+  /// `\r\n`
+  Dos,
+  /// `\r`
+  Mac,
+}
+
+impl FileFormat {
+  /// Get the line ending.
+  pub fn eol(&self) -> &'static str {
+    match self {
+      FileFormat::Unix => "\n",
+      FileFormat::Dos => "\r\n",
+      FileFormat::Mac => "\r",
+    }
+  }
+
+  /// Detect the file format of the `text`:
+  ///
+  /// 1. If there's no `\n` but `\r`, it's [`Mac`](FileFormat::Mac).
+  /// 2. If all the `\n` are preceded by `\r`, it's [`Dos`](FileFormat::Dos).
+  /// 3. Otherwise it's [`Unix`](FileFormat::Unix), i.e. the mixed line endings, and the `\r`
+  ///    before `\n` is kept in buffer and rendered as `^M`.
+  pub fn detect(text: &str) -> FileFormat {
+    let mut lf = 0_usize;
+    let mut crlf = 0_usize;
+    let mut cr = false;
+    let mut last: Option<char> = None;
+    for c in text.chars() {
+      match c {
+        '\n' => {
+          lf += 1;
+          if last == Some('\r') {
+            crlf += 1;
+          }
+        }
+        '\r' => cr = true,
+        _ => { /* Skip */ }
+      }
+      last = Some(c);
+    }
+    if lf == 0 && cr {
+      FileFormat::Mac
+    } else if lf > 0 && lf == crlf {
+      FileFormat::Dos
+    } else {
+      FileFormat::Unix
+    }
+  }
+
+  /// Same with [`detect`](FileFormat::detect), but returns the `default` if there's no line
+  /// ending in `text`, i.e. the empty files and single line files.
+  pub fn detect_or(text: &str, default: FileFormat) -> FileFormat {
+    if text.contains(['\n', '\r']) {
+      FileFormat::detect(text)
+    } else {
+      default
+    }
+  }
+
+  /// Convert the line endings of `text` to `\n`.
+  pub fn normalize(&self, text: &str) -> String {
+    match self {
+      FileFormat::Unix => text.to_string(),
+      FileFormat::Dos => text.replace("\r\n", "\n"),
+      FileFormat::Mac => text.replace('\r', "\n"),
+    }
+  }
+
+  /// Convert the `\n` line endings of `text` to this file format's line ending.
+  pub fn denormalize(&self, text: &str) -> String {
+    match self {
+      FileFormat::Unix => text.to_string(),
+      _ => text.replace('\n', self.eol()),
+    }
+  }
+}
+
+impl Display for FileFormat {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      FileFormat::Unix => write!(f, "unix"),
+      FileFormat::Dos => write!(f, "dos"),
+      FileFormat::Mac => write!(f, "mac"),
+    }
+  }
+}
+
+impl TryFrom<&str> for FileFormat {
+  type Error = String;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    let lower_value = value.to_lowercase();
+    match lower_value.as_str() {
+      "unix" => Ok(FileFormat::Unix),
+      "dos" => Ok(FileFormat::Dos),
+      "mac" => Ok(FileFormat::Mac),
+      _ => Err("Unknown FileFormat value".to_string()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn display1() {
+    assert_eq!(format!("{}", FileFormat::Dos), "dos");
+    assert_eq!(FileFormat::try_from("MAC"), Ok(FileFormat::Mac));
+    assert!(FileFormat::try_from("win").is_err());
+  }
+
+  #[test]
+  fn detect1() {
+    assert_eq!(FileFormat::detect(""), FileFormat::Unix);
+    assert_eq!(FileFormat::detect("a\nb\n"), FileFormat::Unix);
+    assert_eq!(FileFormat::detect("a\r\nb\r\n"), FileFormat::Dos);
+    assert_eq!(FileFormat::detect("a\rb\r"), FileFormat::Mac);
+    // Mixed line endings.
+    assert_eq!(FileFormat::detect("a\r\nb\n"), FileFormat::Unix);
+  }
+
+  #[test]
+  fn detect_or1() {
+    assert_eq!(FileFormat::detect_or("", FileFormat::Dos), FileFormat::Dos);
+    assert_eq!(FileFormat::detect_or("a", FileFormat::Dos), FileFormat::Dos);
+    assert_eq!(
+      FileFormat::detect_or("a\nb\n", FileFormat::Dos),
+      FileFormat::Unix
+    );
+    assert_eq!(
+      FileFormat::detect_or("a\r\n", FileFormat::Unix),
+      FileFormat::Dos
+    );
+  }
+
+  #[test]
+  fn normalize1() {
+    assert_eq!(FileFormat::Dos.normalize("a\r\nb\r\n"), "a\nb\n");
+    assert_eq!(FileFormat::Mac.normalize("a\rb\r"), "a\nb\n");
+    assert_eq!(FileFormat::Unix.normalize("a\r\nb\n"), "a\r\nb\n");
+    assert_eq!(FileFormat::Dos.denormalize("a\nb\n"), "a\r\nb\r\n");
+    assert_eq!(FileFormat::Mac.denormalize("a\nb"), "a\rb");
+    assert_eq!(FileFormat::Unix.denormalize("a\nb"), "a\nb");
+  }
+}
@@ -0,0 +1,108 @@
+//! File path normalization for buffers.
+//!
+//! The file buffers are indexed by their absolute file names, thus the same file must always have
+//! the same absolute file name. On Windows, a file can be referred in different forms:
+//!
+//! - The drive letter is case-insensitive, i.e. `c:\a.txt` is `C:\a.txt`.
+//! - The `/` is also a separator, i.e. `C:/a.txt` is `C:\a.txt`.
+//! - The verbatim paths (i.e. returned by `std::fs::canonicalize`), i.e. `\\?\C:\a.txt` is
+//!   `C:\a.txt`, and `\\?\UNC\server\share\a.txt` is `\\server\share\a.txt`.
+//!
+//! They're all normalized to the last form. On other platforms, the paths are kept as it is.
+
+use crate::res::IoResult;
+
+use path_absolutize::Absolutize;
+use std::path::{Path, PathBuf};
+
+/// Normalize the path, the Windows forms are normalized only if `windows` is true.
+pub fn normalize_with(path: &str, windows: bool) -> String {
+  if !windows {
+    return path.to_string();
+  }
+
+  let path = if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+    format!(r"\\{}", rest)
+  } else if let Some(rest) = path.strip_prefix(r"\\?\").filter(|rest| has_drive(rest)) {
+    rest.to_string()
+  } else if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+    // Other verbatim paths and the device paths, i.e. `\\?\Volume{..}\` and `\\.\pipe\`.
+    return path.to_string();
+  } else {
+    path.to_string()
+  };
+
+  let path = path.replace('/', "\\");
+  if has_drive(&path) {
+    let mut chars = path.chars();
+    let drive = chars.next().unwrap().to_ascii_uppercase();
+    format!("{}{}", drive, chars.as_str())
+  } else {
+    path
+  }
+}
+
+/// Normalize the path on current platform.
+pub fn normalize(path: &Path) -> PathBuf {
+  if cfg!(target_os = "windows") {
+    PathBuf::from(normalize_with(&path.to_string_lossy(), true))
+  } else {
+    path.to_path_buf()
+  }
+}
+
+/// Get the normalized absolute path, it's the key of the file buffer.
+pub fn absolute_path(path: &Path) -> IoResult<PathBuf> {
+  Ok(normalize(&path.absolutize()?))
+}
+
+// Whether the path starts with a drive letter, i.e. `C:`.
+fn has_drive(path: &str) -> bool {
+  let bytes = path.as_bytes();
+  bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn normalize_with1() {
+    assert_eq!(normalize_with(r"c:\a\b.txt", true), r"C:\a\b.txt");
+    assert_eq!(normalize_with("C:/a/b.txt", true), r"C:\a\b.txt");
+    assert_eq!(normalize_with(r"\\?\c:\a.txt", true), r"C:\a.txt");
+    assert_eq!(
+      normalize_with(r"\\?\UNC\server\share\a.txt", true),
+      r"\\server\share\a.txt"
+    );
+    assert_eq!(
+      normalize_with("//server/share/a.txt", true),
+      r"\\server\share\a.txt"
+    );
+    assert_eq!(normalize_with(r"\\.\pipe\rsvim", true), r"\\.\pipe\rsvim");
+    assert_eq!(
+      normalize_with(r"\\?\Volume{1}\a.txt", true),
+      r"\\?\Volume{1}\a.txt"
+    );
+  }
+
+  #[test]
+  fn normalize_with2() {
+    assert_eq!(normalize_with("c:/a.txt", false), "c:/a.txt");
+    assert_eq!(normalize_with("/home/a.txt", false), "/home/a.txt");
+  }
+
+  #[cfg(target_os = "windows")]
+  #[test]
+  fn absolute_path_windows() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let file = tmp.path().join("a.txt");
+    std::fs::write(&file, "hello").unwrap();
+    let canonical = std::fs::canonicalize(&file).unwrap();
+    assert!(canonical.to_string_lossy().starts_with(r"\\?\"));
+    assert_eq!(
+      absolute_path(&canonical).unwrap(),
+      absolute_path(&file).unwrap()
+    );
+  }
+}
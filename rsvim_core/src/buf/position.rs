@@ -0,0 +1,237 @@
+//! Last positions of the files, i.e. Vim's `'"` mark which is saved in the viminfo file.
+//!
+//! When a buffer is hidden from a window (or the editor exits), its cursor position and the
+//! viewport's top line are recorded in the [`PositionStore`]. When the file is opened again, the
+//! position is restored, and it's also the buffer's `"` mark.
+//!
+//! The store is saved in the `positions` file under the data directory (see
+//! [`DATA_DIR_PATH`](crate::envar::DATA_DIR_PATH)), each line is an entry (the most recent one is
+//! the first line) with the tab-separated line index, char index, top line index and absolute
+//! file path. Only the recent [`MAX_POSITIONS`] files are kept.
+//!
+//! The files matched by the 'lastposexclude' option (comma-separated glob patterns, see
+//! [`glob_match`]) are not recorded, i.e. the commit messages and the temporary files.
+
+use crate::buf::mark::MarkPos;
+use crate::buf::template::glob_match;
+use crate::defaults;
+use crate::res::IoResult;
+
+use std::path::{Path, PathBuf};
+
+/// The positions file name under the data directory.
+pub const POSITIONS_FILE: &str = "positions";
+
+/// The max count of the recorded files.
+pub const MAX_POSITIONS: usize = 100;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// The last position of a file.
+pub struct LastPosition {
+  /// The cursor position.
+  pub cursor: MarkPos,
+  /// The viewport's top line index.
+  pub top_line_idx: usize,
+}
+
+impl LastPosition {
+  pub fn new(cursor: MarkPos, top_line_idx: usize) -> Self {
+    LastPosition {
+      cursor,
+      top_line_idx,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The last positions of the recent files.
+pub struct PositionStore {
+  // The files and their positions, the most recent one is at the front.
+  positions: Vec<(PathBuf, LastPosition)>,
+
+  // The 'lastposexclude' option.
+  exclude: String,
+}
+
+impl Default for PositionStore {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl PositionStore {
+  pub fn new() -> Self {
+    PositionStore {
+      positions: vec![],
+      exclude: defaults::buf::LAST_POS_EXCLUDE.to_string(),
+    }
+  }
+
+  /// Parse the store file contents, the invalid lines are skipped.
+  pub fn parse(&mut self, text: &str) {
+    self.positions = text
+      .lines()
+      .filter_map(|line| {
+        let mut fields = line.splitn(4, '\t');
+        let line_idx = fields.next()?.parse().ok()?;
+        let char_idx = fields.next()?.parse().ok()?;
+        let top_line_idx = fields.next()?.parse().ok()?;
+        let file = fields.next().filter(|file| !file.is_empty())?;
+        Some((
+          PathBuf::from(file),
+          LastPosition::new(MarkPos::new(line_idx, char_idx), top_line_idx),
+        ))
+      })
+      .take(MAX_POSITIONS)
+      .collect();
+  }
+
+  /// Format the store file contents.
+  pub fn format(&self) -> String {
+    self
+      .positions
+      .iter()
+      .map(|(file, pos)| {
+        format!(
+          "{}\t{}\t{}\t{}\n",
+          pos.cursor.line_idx,
+          pos.cursor.char_idx,
+          pos.top_line_idx,
+          file.to_string_lossy()
+        )
+      })
+      .collect()
+  }
+
+  /// Load the store file, the store is empty if the file doesn't exist.
+  pub fn load(&mut self, path: &Path) -> IoResult<()> {
+    match std::fs::read_to_string(path) {
+      Ok(text) => {
+        self.parse(&text);
+        Ok(())
+      }
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+      Err(e) => Err(e),
+    }
+  }
+
+  /// Save the store file, the parent directory is created if it doesn't exist.
+  pub fn save(&self, path: &Path) -> IoResult<()> {
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, self.format())
+  }
+
+  /// Get the last position of the file.
+  pub fn get(&self, file: &Path) -> Option<LastPosition> {
+    self
+      .positions
+      .iter()
+      .find(|(f, _)| f == file)
+      .map(|(_, pos)| *pos)
+  }
+
+  /// Record the last position of the file, it's ignored if the file is excluded.
+  pub fn set(&mut self, file: &Path, pos: LastPosition) {
+    if self.is_excluded(file) {
+      return;
+    }
+    self.positions.retain(|(f, _)| f != file);
+    self.positions.insert(0, (file.to_path_buf(), pos));
+    self.positions.truncate(MAX_POSITIONS);
+  }
+
+  /// Forget the file, i.e. it's deleted.
+  pub fn remove(&mut self, file: &Path) -> Option<LastPosition> {
+    let idx = self.positions.iter().position(|(f, _)| f == file)?;
+    Some(self.positions.remove(idx).1)
+  }
+
+  /// Whether the file is excluded by the 'lastposexclude' option.
+  pub fn is_excluded(&self, file: &Path) -> bool {
+    let file = file.to_string_lossy();
+    self
+      .exclude
+      .split(',')
+      .filter(|pattern| !pattern.is_empty())
+      .any(|pattern| glob_match(pattern, &file))
+  }
+
+  /// The 'lastposexclude' option.
+  pub fn exclude(&self) -> &str {
+    &self.exclude
+  }
+
+  pub fn set_exclude(&mut self, value: &str) {
+    self.exclude = value.to_string();
+  }
+
+  /// Recorded files count.
+  pub fn len(&self) -> usize {
+    self.positions.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.positions.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse1() {
+    let mut store = PositionStore::new();
+    store.parse("3\t4\t1\t/a/b.rs\ninvalid\n5\t0\t0\t/a/c d.rs\n1\t2\t3\t\n");
+    assert_eq!(store.len(), 2);
+    assert_eq!(
+      store.get(Path::new("/a/b.rs")),
+      Some(LastPosition::new(MarkPos::new(3, 4), 1))
+    );
+    assert_eq!(
+      store.get(Path::new("/a/c d.rs")),
+      Some(LastPosition::new(MarkPos::new(5, 0), 0))
+    );
+    assert_eq!(store.format(), "3\t4\t1\t/a/b.rs\n5\t0\t0\t/a/c d.rs\n");
+  }
+
+  #[test]
+  fn set1() {
+    let mut store = PositionStore::new();
+    for i in 0..MAX_POSITIONS + 1 {
+      let file = PathBuf::from(format!("/a/{}.rs", i));
+      store.set(&file, LastPosition::new(MarkPos::new(i, 0), 0));
+    }
+    assert_eq!(store.len(), MAX_POSITIONS);
+    assert!(store.get(Path::new("/a/0.rs")).is_none());
+
+    // The updated file is moved to the front.
+    store.set(
+      Path::new("/a/1.rs"),
+      LastPosition::new(MarkPos::new(9, 9), 0),
+    );
+    assert_eq!(store.len(), MAX_POSITIONS);
+    assert!(store.format().starts_with("9\t9\t0\t/a/1.rs\n"));
+
+    assert!(store.remove(Path::new("/a/1.rs")).is_some());
+    assert!(store.remove(Path::new("/a/1.rs")).is_none());
+    assert_eq!(store.len(), MAX_POSITIONS - 1);
+  }
+
+  #[test]
+  fn exclude1() {
+    let mut store = PositionStore::new();
+    assert!(store.is_excluded(Path::new("/a/.git/COMMIT_EDITMSG")));
+    assert!(store.is_excluded(Path::new("/tmp/a.txt")));
+    assert!(!store.is_excluded(Path::new("/a/b.rs")));
+
+    store.set(Path::new("/tmp/a.txt"), LastPosition::default());
+    assert!(store.is_empty());
+
+    store.set_exclude("");
+    store.set(Path::new("/tmp/a.txt"), LastPosition::default());
+    assert_eq!(store.len(), 1);
+  }
+}
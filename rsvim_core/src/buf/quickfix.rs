@@ -0,0 +1,176 @@
+//! The quickfix list.
+//!
+//! The quickfix list is the list of positions in files, i.e. the matches of `:grep`. Users can
+//! navigate it with `:cnext`/`:cprevious`/`:cfirst`/`:clast`/`:cc`, and list it with `:clist` or
+//! the quickfix window `:copen`.
+//!
+//! The list is replaced by each `:grep`, the entries are appended while the search is running,
+//! thus users can jump to the first matches before the search is finished.
+//!
+//! See: <https://vimhelp.org/quickfix.txt.html>.
+
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A quickfix entry.
+pub struct QuickfixEntry {
+  pub path: PathBuf,
+  /// Line index, starts from 0.
+  pub line_idx: usize,
+  /// Char index in the line, starts from 0.
+  pub char_idx: usize,
+  /// The line text, without the line break.
+  pub text: String,
+}
+
+impl QuickfixEntry {
+  pub fn new(path: PathBuf, line_idx: usize, char_idx: usize, text: String) -> Self {
+    QuickfixEntry {
+      path,
+      line_idx,
+      char_idx,
+      text,
+    }
+  }
+
+  /// Format the entry in `:clist`, i.e. `src/main.rs:10:5: fn main() {`. The line and column
+  /// numbers start from 1.
+  pub fn format(&self) -> String {
+    format!(
+      "{}:{}:{}: {}",
+      self.path.display(),
+      self.line_idx + 1,
+      self.char_idx + 1,
+      self.text.trim()
+    )
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The quickfix list.
+pub struct QuickfixList {
+  // The title, i.e. the `:grep` command line.
+  title: String,
+  entries: Vec<QuickfixEntry>,
+  // Index of current entry, it's `None` if the list is empty.
+  current: Option<usize>,
+}
+
+impl QuickfixList {
+  /// Replace the list with an empty list titled `title`.
+  pub fn reset(&mut self, title: &str) {
+    self.title = title.to_string();
+    self.entries.clear();
+    self.current = None;
+  }
+
+  pub fn title(&self) -> &str {
+    &self.title
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Get all entries.
+  pub fn entries(&self) -> &Vec<QuickfixEntry> {
+    &self.entries
+  }
+
+  /// Append entries, the first entry becomes current if the list was empty.
+  pub fn extend(&mut self, entries: Vec<QuickfixEntry>) {
+    self.entries.extend(entries);
+    if self.current.is_none() && !self.entries.is_empty() {
+      self.current = Some(0);
+    }
+  }
+
+  /// Get index of current entry.
+  pub fn current(&self) -> Option<usize> {
+    self.current
+  }
+
+  /// Set current entry by index, i.e. `:cc {nr}`.
+  ///
+  /// Returns the entry, or `None` if the index is out of range (and current entry is not changed).
+  pub fn set_current(&mut self, idx: usize) -> Option<&QuickfixEntry> {
+    if idx >= self.entries.len() {
+      return None;
+    }
+    self.current = Some(idx);
+    self.entries.get(idx)
+  }
+
+  /// Move to the `count`th next entry, i.e. `:cnext`.
+  ///
+  /// Returns the entry, or `None` if it's out of range (and current entry is not changed).
+  pub fn next(&mut self, count: usize) -> Option<&QuickfixEntry> {
+    let idx = self.current? + count;
+    self.set_current(idx)
+  }
+
+  /// Move to the `count`th previous entry, i.e. `:cprevious`.
+  ///
+  /// Returns the entry, or `None` if it's out of range (and current entry is not changed).
+  pub fn prev(&mut self, count: usize) -> Option<&QuickfixEntry> {
+    let idx = self.current?.checked_sub(count)?;
+    self.set_current(idx)
+  }
+
+  /// Get the text of the quickfix window, i.e. each line is an entry. It's ended with a line
+  /// break if it's not empty.
+  pub fn window_text(&self) -> String {
+    self
+      .entries
+      .iter()
+      .map(|entry| format!("{}\n", entry.format()))
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(path: &str, line_idx: usize) -> QuickfixEntry {
+    QuickfixEntry::new(
+      PathBuf::from(path),
+      line_idx,
+      0,
+      format!("line {}", line_idx),
+    )
+  }
+
+  #[test]
+  fn navigate1() {
+    let mut list = QuickfixList::default();
+    list.reset(":grep foo");
+    assert!(list.is_empty());
+    assert_eq!(list.next(1), None);
+
+    list.extend(vec![entry("a.rs", 0), entry("a.rs", 4)]);
+    assert_eq!(list.current(), Some(0));
+    list.extend(vec![entry("b.rs", 1)]);
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.next(2), Some(&entry("b.rs", 1)));
+    assert_eq!(list.next(1), None);
+    assert_eq!(list.prev(1), Some(&entry("a.rs", 4)));
+    assert_eq!(list.prev(2), None);
+    assert_eq!(list.set_current(0), Some(&entry("a.rs", 0)));
+    assert_eq!(entry("a.rs", 4).format(), "a.rs:5:1: line 4");
+
+    assert_eq!(
+      list.window_text(),
+      "a.rs:1:1: line 0\na.rs:5:1: line 4\nb.rs:2:1: line 1\n"
+    );
+
+    list.reset(":grep bar");
+    assert_eq!(list.window_text(), "");
+    assert_eq!(list.title(), ":grep bar");
+    assert_eq!(list.current(), None);
+  }
+}
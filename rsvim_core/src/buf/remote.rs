@@ -0,0 +1,243 @@
+//! Remote files, i.e. Vim's [netrw](https://vimhelp.org/pi_netrw.txt.html) for the URLs.
+//!
+//! The remote file is fetched by the external command in background, the content is streamed
+//! into a loading buffer (see [`BufferLoader`](crate::buf::BufferLoader)) same with the huge
+//! files:
+//!
+//! - `http://`/`https://`: `curl -fsSL {url}`, the buffer is read-only.
+//! - `scp://`/`sftp://`: `ssh [-p {port}] [{user}@]{host} cat {path}`, the buffer can be written
+//!   back with `:Nwrite` (see [`upload_command`](RemoteUrl::upload_command)).
+//!
+//! Like netrw, the path in `scp://host/path` is relative to the home directory, the absolute path
+//! is `scp://host//path`.
+
+use std::path::Path;
+use url::Url;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The remote URL scheme.
+pub enum RemoteScheme {
+  Http,
+  Https,
+  Scp,
+  Sftp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The remote file URL.
+pub struct RemoteUrl {
+  scheme: RemoteScheme,
+  // The original URL.
+  url: String,
+  user: Option<String>,
+  host: String,
+  port: Option<u16>,
+  // The file path on the remote host.
+  path: String,
+}
+
+/// Whether the file name is a remote URL.
+pub fn is_remote(name: &str) -> bool {
+  RemoteUrl::parse(name).is_some()
+}
+
+// Decode the percent-encoded URL path, i.e. `%20` is a space.
+fn percent_decode(s: &str) -> String {
+  let bytes = s.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    let hex = bytes
+      .get(i + 1..i + 3)
+      .and_then(|hex| std::str::from_utf8(hex).ok())
+      .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    match (bytes[i], hex) {
+      (b'%', Some(byte)) => {
+        decoded.push(byte);
+        i += 3;
+      }
+      (byte, _) => {
+        decoded.push(byte);
+        i += 1;
+      }
+    }
+  }
+  String::from_utf8_lossy(&decoded).to_string()
+}
+
+// Quote the argument for the remote shell.
+fn shell_quote(arg: &str) -> String {
+  format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+impl RemoteUrl {
+  /// Parse the URL, returns `None` if it's not a supported remote URL.
+  pub fn parse(name: &str) -> Option<Self> {
+    let url = Url::parse(name).ok()?;
+    let scheme = match url.scheme() {
+      "http" => RemoteScheme::Http,
+      "https" => RemoteScheme::Https,
+      "scp" => RemoteScheme::Scp,
+      "sftp" => RemoteScheme::Sftp,
+      _ => return None,
+    };
+    let host = url.host_str()?.to_string();
+    let user = if url.username().is_empty() {
+      None
+    } else {
+      Some(url.username().to_string())
+    };
+    let path = match scheme {
+      RemoteScheme::Http | RemoteScheme::Https => url.path().to_string(),
+      RemoteScheme::Scp | RemoteScheme::Sftp => {
+        let path = url.path().strip_prefix('/').unwrap_or(url.path());
+        if path.is_empty() {
+          return None;
+        }
+        percent_decode(path)
+      }
+    };
+    Some(RemoteUrl {
+      scheme,
+      url: name.to_string(),
+      user,
+      host,
+      port: url.port(),
+      path,
+    })
+  }
+
+  pub fn scheme(&self) -> RemoteScheme {
+    self.scheme
+  }
+
+  /// Get the original URL.
+  pub fn url(&self) -> &str {
+    &self.url
+  }
+
+  pub fn host(&self) -> &str {
+    &self.host
+  }
+
+  /// Get the file path on the remote host.
+  pub fn path(&self) -> &str {
+    &self.path
+  }
+
+  /// Whether the file can be written back, i.e. the `scp://` and `sftp://` files.
+  pub fn is_writable(&self) -> bool {
+    matches!(self.scheme, RemoteScheme::Scp | RemoteScheme::Sftp)
+  }
+
+  // The `[user@]host` destination.
+  fn destination(&self) -> String {
+    match &self.user {
+      Some(user) => format!("{}@{}", user, self.host),
+      None => self.host.clone(),
+    }
+  }
+
+  /// Get the command (program and arguments) to fetch the file content to stdout.
+  pub fn fetch_command(&self) -> (String, Vec<String>) {
+    match self.scheme {
+      RemoteScheme::Http | RemoteScheme::Https => (
+        "curl".to_string(),
+        vec!["-fsSL".to_string(), self.url.clone()],
+      ),
+      RemoteScheme::Scp | RemoteScheme::Sftp => {
+        let mut args = vec![];
+        if let Some(port) = self.port {
+          args.push("-p".to_string());
+          args.push(port.to_string());
+        }
+        args.push(self.destination());
+        args.push(format!("cat {}", shell_quote(&self.path)));
+        ("ssh".to_string(), args)
+      }
+    }
+  }
+
+  /// Get the command (program and arguments) to upload the local file to the remote file, returns
+  /// `None` if it's not writable.
+  pub fn upload_command(&self, local: &Path) -> Option<(String, Vec<String>)> {
+    if !self.is_writable() {
+      return None;
+    }
+    let mut args = vec!["-q".to_string()];
+    if let Some(port) = self.port {
+      args.push("-P".to_string());
+      args.push(port.to_string());
+    }
+    args.push(local.to_string_lossy().to_string());
+    args.push(format!("{}:{}", self.destination(), self.path));
+    Some(("scp".to_string(), args))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse1() {
+    let url = RemoteUrl::parse("scp://alice@example.com:2222//etc/hosts").unwrap();
+    assert_eq!(url.scheme(), RemoteScheme::Scp);
+    assert_eq!(url.host(), "example.com");
+    assert_eq!(url.path(), "/etc/hosts");
+    assert!(url.is_writable());
+
+    let url = RemoteUrl::parse("sftp://example.com/my%20notes.md").unwrap();
+    assert_eq!(url.path(), "my notes.md");
+
+    let url = RemoteUrl::parse("https://example.com/a/b.txt?x=1").unwrap();
+    assert_eq!(url.scheme(), RemoteScheme::Https);
+    assert_eq!(url.path(), "/a/b.txt");
+    assert!(!url.is_writable());
+
+    assert!(RemoteUrl::parse("scp://example.com/").is_none());
+    assert!(RemoteUrl::parse("ftp://example.com/a.txt").is_none());
+    assert!(!is_remote("/home/alice/a.txt"));
+    assert!(!is_remote("README.md"));
+  }
+
+  #[test]
+  fn commands1() {
+    let url = RemoteUrl::parse("scp://alice@example.com:2222/it's.txt").unwrap();
+    assert_eq!(
+      url.fetch_command(),
+      (
+        "ssh".to_string(),
+        vec![
+          "-p".to_string(),
+          "2222".to_string(),
+          "alice@example.com".to_string(),
+          "cat 'it'\\''s.txt'".to_string(),
+        ]
+      )
+    );
+    assert_eq!(
+      url.upload_command(Path::new("/tmp/a")),
+      Some((
+        "scp".to_string(),
+        vec![
+          "-q".to_string(),
+          "-P".to_string(),
+          "2222".to_string(),
+          "/tmp/a".to_string(),
+          "alice@example.com:it's.txt".to_string(),
+        ]
+      ))
+    );
+
+    let url = RemoteUrl::parse("http://example.com/a.txt").unwrap();
+    assert_eq!(
+      url.fetch_command(),
+      (
+        "curl".to_string(),
+        vec!["-fsSL".to_string(), "http://example.com/a.txt".to_string()]
+      )
+    );
+    assert!(url.upload_command(Path::new("/tmp/a")).is_none());
+  }
+}
@@ -0,0 +1,217 @@
+//! Buffer signs.
+//!
+//! A sign is a short text (i.e. `E`, `+`, `>>`) with a highlight style, which is shown in the sign
+//! column at the left side of the window, see
+//! [`WindowSign`](crate::ui::widget::window::sign::WindowSign). The plugins (i.e. the git gutter
+//! and the diagnostics) define the signs by name, and place them on the buffer lines.
+//!
+//! The placed signs are stored in the buffer, they're adjusted like the marks when lines are
+//! inserted/deleted, a sign on a deleted line is removed. If there're multiple signs on a line, the
+//! one with the highest priority is shown (the later placed one if the priorities are equal).
+//!
+//! See: <https://vimhelp.org/sign.txt.html>.
+
+use crate::buf::highlight::HighlightStyle;
+use crate::buf::mark::adjust_line_idx;
+use crate::buf::undo::Delta;
+
+use ropey::Rope;
+use std::collections::BTreeMap;
+
+/// The placed sign ID, it's unique in a buffer.
+///
+/// NOTE: Start from 1.
+pub type SignId = usize;
+
+/// The default priority of the placed sign.
+pub const DEFAULT_PRIORITY: u16 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The sign definition.
+pub struct SignDefinition {
+  /// The text shown in the sign column, it's truncated to the 'signwidth' option.
+  pub text: String,
+  pub style: HighlightStyle,
+}
+
+impl SignDefinition {
+  pub fn new(text: &str, style: HighlightStyle) -> Self {
+    SignDefinition {
+      text: text.to_string(),
+      style,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The placed sign.
+pub struct PlacedSign {
+  /// The sign definition name.
+  pub name: String,
+  /// Line index, starts from 0.
+  pub line_idx: usize,
+  /// The group, i.e. the plugin name, the signs of a group are cleared together.
+  pub group: String,
+  pub priority: u16,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The placed signs of a buffer.
+pub struct Signs {
+  signs: BTreeMap<SignId, PlacedSign>,
+  next_id: SignId,
+}
+
+impl Signs {
+  pub fn new() -> Self {
+    Signs::default()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.signs.is_empty()
+  }
+
+  pub fn get(&self, id: SignId) -> Option<&PlacedSign> {
+    self.signs.get(&id)
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = (&SignId, &PlacedSign)> {
+    self.signs.iter()
+  }
+
+  /// Place a sign, returns its ID.
+  pub fn place(&mut self, name: &str, line_idx: usize, group: &str, priority: u16) -> SignId {
+    self.next_id += 1;
+    self.signs.insert(
+      self.next_id,
+      PlacedSign {
+        name: name.to_string(),
+        line_idx,
+        group: group.to_string(),
+        priority,
+      },
+    );
+    self.next_id
+  }
+
+  /// Unplace a sign, returns `None` if it doesn't exist.
+  pub fn unplace(&mut self, id: SignId) -> Option<PlacedSign> {
+    self.signs.remove(&id)
+  }
+
+  /// Unplace the signs of the `group`, or all the signs if `group` is `None`.
+  pub fn clear(&mut self, group: Option<&str>) {
+    match group {
+      Some(group) => self.signs.retain(|_, sign| sign.group != group),
+      None => self.signs.clear(),
+    }
+  }
+
+  /// Get the shown sign on each line in the range `[start, end)`, i.e. the one with the highest
+  /// priority.
+  pub fn shown_in(&self, start: usize, end: usize) -> BTreeMap<usize, &PlacedSign> {
+    let mut shown: BTreeMap<usize, &PlacedSign> = BTreeMap::new();
+    // NOTE: The signs are iterated in placed order, thus the later one wins if the priorities
+    // are equal.
+    for sign in self.signs.values() {
+      if sign.line_idx < start || sign.line_idx >= end {
+        continue;
+      }
+      match shown.get(&sign.line_idx) {
+        Some(current) if current.priority > sign.priority => { /* Skip */ }
+        _ => {
+          shown.insert(sign.line_idx, sign);
+        }
+      }
+    }
+    shown
+  }
+
+  /// Adjust the signs by an edit, see [`adjust_line_idx`].
+  pub fn adjust(
+    &mut self,
+    line_idx: usize,
+    char_idx: usize,
+    removed_lines: usize,
+    inserted_lines: usize,
+  ) {
+    if removed_lines == 0 && inserted_lines == 0 {
+      return;
+    }
+    self.signs.retain(|_, sign| {
+      match adjust_line_idx(
+        sign.line_idx,
+        line_idx,
+        char_idx,
+        removed_lines,
+        inserted_lines,
+      ) {
+        Some(adjusted) => {
+          sign.line_idx = adjusted;
+          true
+        }
+        None => false,
+      }
+    });
+  }
+
+  /// Adjust the signs by the `delta` before it's applied to the `rope`.
+  pub fn adjust_by_delta(&mut self, rope: &Rope, delta: &Delta) {
+    let line_idx = rope.char_to_line(delta.char_idx);
+    let char_idx = delta.char_idx - rope.line_to_char(line_idx);
+    let removed_lines = delta.removed.matches('\n').count();
+    let inserted_lines = delta.inserted.matches('\n').count();
+    self.adjust(line_idx, char_idx, removed_lines, inserted_lines);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lines(signs: &Signs) -> Vec<(SignId, usize)> {
+    signs
+      .iter()
+      .map(|(id, sign)| (*id, sign.line_idx))
+      .collect()
+  }
+
+  #[test]
+  fn place1() {
+    let mut signs = Signs::new();
+    assert!(signs.is_empty());
+    let a = signs.place("error", 1, "lsp", 20);
+    let b = signs.place("added", 1, "git", DEFAULT_PRIORITY);
+    let c = signs.place("added", 3, "git", DEFAULT_PRIORITY);
+    assert_eq!((a, b, c), (1, 2, 3));
+
+    // The higher priority one is shown.
+    let shown = signs.shown_in(0, 10);
+    assert_eq!(shown.len(), 2);
+    assert_eq!(shown[&1].name, "error");
+    assert_eq!(shown[&3].name, "added");
+    assert_eq!(signs.shown_in(2, 3).len(), 0);
+
+    assert_eq!(signs.unplace(a).map(|s| s.line_idx), Some(1));
+    assert_eq!(signs.unplace(a), None);
+    assert_eq!(signs.shown_in(0, 10)[&1].name, "added");
+
+    signs.clear(Some("git"));
+    assert!(signs.is_empty());
+  }
+
+  #[test]
+  fn adjust_by_delta1() {
+    let rope = Rope::from_str("a\nb\nc\nd\n");
+    let mut signs = Signs::new();
+    for line_idx in 0..4 {
+      signs.place("s", line_idx, "", DEFAULT_PRIORITY);
+    }
+    // Delete "b\n", the sign on it is removed and the signs after it are moved up.
+    signs.adjust_by_delta(&rope, &Delta::new(2, "b\n", ""));
+    assert_eq!(lines(&signs), vec![(1, 0), (3, 1), (4, 2)]);
+    // Insert a line at the start.
+    signs.adjust_by_delta(&rope, &Delta::new(0, "", "x\n"));
+    assert_eq!(lines(&signs), vec![(1, 1), (3, 2), (4, 3)]);
+  }
+}
@@ -0,0 +1,170 @@
+//! User spell dictionary, i.e. Vim's 'spellfile'.
+//!
+//! The good words are added by `zg` (the word under cursor), `:spellgood {word}` and
+//! `Rsvim.spell.add(word)`, they're checked by `Rsvim.spell.check(word)`.
+//!
+//! The dictionary is saved in the `spell.add` file under the data directory (see
+//! [`DATA_DIR_PATH`](crate::envar::DATA_DIR_PATH)), each line is a word, the empty lines and the
+//! `#` comment lines are skipped, the Vim's flags after `/` (i.e. `word/!`) are ignored.
+//!
+//! The file is never read or written by the editor thread:
+//!
+//! 1. The added words are queued in the dictionary, then the event loop appends them to the file
+//!    on an async task.
+//! 2. The file is read on an async task when the editor starts and by `Rsvim.spell.reload()`,
+//!    then the words are merged into the dictionary. Thus the words added while the file is being
+//!    read (or by the other editor instances) are not lost.
+
+use crate::envar;
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+/// The dictionary file name under the data directory.
+pub const SPELL_FILE: &str = "spell.add";
+
+/// Get the dictionary file path.
+pub fn spell_file() -> PathBuf {
+  envar::DATA_DIR_PATH().join(SPELL_FILE)
+}
+
+/// Whether a word is valid for the dictionary, i.e. it's not empty and has no whitespaces.
+pub fn is_valid_word(word: &str) -> bool {
+  !word.is_empty() && !word.chars().any(|c| c.is_whitespace())
+}
+
+#[derive(Debug, Clone, Default)]
+/// The user spell dictionary.
+pub struct SpellDictionary {
+  // The good words.
+  words: BTreeSet<String>,
+
+  // The added words that are not saved to the file yet.
+  unsaved: Vec<String>,
+
+  // Whether to read the file, i.e. `Rsvim.spell.reload()`.
+  reload: bool,
+}
+
+impl SpellDictionary {
+  pub fn new() -> Self {
+    SpellDictionary {
+      words: BTreeSet::new(),
+      unsaved: vec![],
+      reload: false,
+    }
+  }
+
+  /// Parse the dictionary file contents, the words are merged into the dictionary.
+  ///
+  /// Returns the count of the new words.
+  pub fn merge(&mut self, text: &str) -> usize {
+    let mut count = 0_usize;
+    for line in text.lines() {
+      let line = line.trim();
+      if line.starts_with('#') {
+        continue;
+      }
+      let word = match line.split_once('/') {
+        Some((word, _flags)) => word,
+        None => line,
+      };
+      if is_valid_word(word) && self.words.insert(word.to_string()) {
+        count += 1;
+      }
+    }
+    count
+  }
+
+  /// Format the words as the dictionary file contents.
+  pub fn format(words: &[String]) -> String {
+    words.iter().map(|word| format!("{}\n", word)).collect()
+  }
+
+  /// Add a good word, it's saved to the file later.
+  ///
+  /// Returns `false` if the word is invalid or it's already in the dictionary.
+  pub fn add(&mut self, word: &str) -> bool {
+    if !is_valid_word(word) || !self.words.insert(word.to_string()) {
+      return false;
+    }
+    self.unsaved.push(word.to_string());
+    true
+  }
+
+  /// Whether the word is good, i.e. Vim's case rules: The word added in lower case also matches
+  /// its capitalized and upper case forms, otherwise the case must match exactly.
+  pub fn check(&self, word: &str) -> bool {
+    if self.words.contains(word) {
+      return true;
+    }
+    let lower = word.to_lowercase();
+    lower != word && self.words.contains(&lower) && {
+      let mut chars = word.chars();
+      let capitalized = chars
+        .next()
+        .map(|c| c.is_uppercase() && chars.as_str().to_lowercase() == chars.as_str())
+        .unwrap_or(false);
+      capitalized || word.to_uppercase() == word
+    }
+  }
+
+  /// Get all the good words, in sorted order.
+  pub fn words(&self) -> &BTreeSet<String> {
+    &self.words
+  }
+
+  /// Take the added words that are not saved yet.
+  pub fn take_unsaved(&mut self) -> Vec<String> {
+    std::mem::take(&mut self.unsaved)
+  }
+
+  /// Request to read the file again.
+  pub fn request_reload(&mut self) {
+    self.reload = true;
+  }
+
+  /// Take the reload request.
+  pub fn take_reload(&mut self) -> bool {
+    std::mem::take(&mut self.reload)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn merge1() {
+    let mut dict = SpellDictionary::new();
+    assert!(dict.add("rsvim"));
+    assert!(!dict.add("rsvim"));
+    assert!(!dict.add("two words"));
+    assert_eq!(dict.merge("# comment\nneovim\n\nrsvim\nTypeScript/!\n"), 2);
+    assert_eq!(
+      dict.words().iter().cloned().collect::<Vec<_>>(),
+      vec!["TypeScript", "neovim", "rsvim"]
+    );
+    // Only the added words are unsaved, the merged words are read from the file.
+    assert_eq!(dict.take_unsaved(), vec!["rsvim".to_string()]);
+    assert!(dict.take_unsaved().is_empty());
+    assert_eq!(
+      SpellDictionary::format(&["a".to_string(), "b".to_string()]),
+      "a\nb\n"
+    );
+  }
+
+  #[test]
+  fn check1() {
+    let mut dict = SpellDictionary::new();
+    dict.add("rsvim");
+    dict.add("TypeScript");
+    assert!(dict.check("rsvim"));
+    assert!(dict.check("Rsvim"));
+    assert!(dict.check("RSVIM"));
+    assert!(!dict.check("rSvim"));
+    assert!(dict.check("TypeScript"));
+    assert!(!dict.check("typescript"));
+    assert!(!dict.check("neovim"));
+  }
+}
@@ -0,0 +1,302 @@
+//! Template (skeleton) files for new files.
+//!
+//! When a buffer is created for a file that doesn't exist, i.e. Vim's `BufNewFile` event, it's
+//! populated from the first matched template:
+//!
+//! 1. The rules added with `Rsvim.template.add(pattern, file)`, in the added order.
+//! 2. The built-in template directory, i.e. the `templates` folder in the config directories, see
+//!    [`CONFIG_DIRS_PATH`](crate::envar::CONFIG_DIRS_PATH). The template is the file with the same
+//!    file name (i.e. `Makefile`), or the `skeleton.{ext}` file with the same extension (i.e.
+//!    `skeleton.rs`).
+//!
+//! The pattern is matched against the file name if it doesn't contain the path separator `/`,
+//! otherwise the absolute file path. It supports the wildcards `*` (any chars) and `?` (any single
+//! char).
+//!
+//! The variables `{{name}}` in the template are substituted, see [`Templates::variables`]. The
+//! unknown variables are kept as they are.
+//!
+//! See: <https://vimhelp.org/autocmd.txt.html#skeleton>.
+
+use crate::envar;
+
+use ahash::AHashMap as HashMap;
+use jiff::Zoned;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The template rule.
+pub struct TemplateRule {
+  pub pattern: String,
+  pub file: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+/// The templates for new files.
+pub struct Templates {
+  rules: Vec<TemplateRule>,
+  dirs: Vec<PathBuf>,
+  // User defined variables.
+  variables: HashMap<String, String>,
+}
+
+impl Default for Templates {
+  fn default() -> Self {
+    Templates {
+      rules: vec![],
+      dirs: envar::CONFIG_DIRS_PATH()
+        .iter()
+        .map(|dir| dir.join("templates"))
+        .collect(),
+      variables: HashMap::new(),
+    }
+  }
+}
+
+impl Templates {
+  pub fn new() -> Self {
+    Templates::default()
+  }
+
+  /// Get the rules.
+  pub fn rules(&self) -> &Vec<TemplateRule> {
+    &self.rules
+  }
+
+  /// Add a rule, the rules are matched in the added order.
+  pub fn add(&mut self, pattern: &str, file: PathBuf) {
+    self.rules.push(TemplateRule {
+      pattern: pattern.to_string(),
+      file,
+    });
+  }
+
+  /// Get the template directories.
+  pub fn dirs(&self) -> &Vec<PathBuf> {
+    &self.dirs
+  }
+
+  /// Set the template directories.
+  pub fn set_dirs(&mut self, dirs: Vec<PathBuf>) {
+    self.dirs = dirs;
+  }
+
+  /// Set the user defined variable, it overrides the built-in one with the same name.
+  pub fn set_variable(&mut self, name: &str, value: &str) {
+    self.variables.insert(name.to_string(), value.to_string());
+  }
+
+  /// Get the candidate template files for the new file, in the matched order. The caller uses the
+  /// first one that exists.
+  pub fn candidates(&self, absolute_filename: &Path) -> Vec<PathBuf> {
+    let filename = match absolute_filename.file_name().and_then(|f| f.to_str()) {
+      Some(filename) => filename,
+      None => return vec![],
+    };
+
+    let mut candidates: Vec<PathBuf> = self
+      .rules
+      .iter()
+      .filter(|rule| path_match(&rule.pattern, absolute_filename))
+      .map(|rule| rule.file.clone())
+      .collect();
+    for dir in self.dirs.iter() {
+      candidates.push(dir.join(filename));
+      if let Some(extension) = absolute_filename.extension().and_then(|e| e.to_str()) {
+        candidates.push(dir.join(format!("skeleton.{}", extension)));
+      }
+    }
+    candidates
+  }
+
+  /// Get the variables for the new file:
+  ///
+  /// - `filename`: The file name, i.e. `main.rs`.
+  /// - `name`: The file name without extension, i.e. `main`.
+  /// - `path`: The absolute file path.
+  /// - `date`: Today, i.e. `2024-01-31`.
+  /// - `year`: This year, i.e. `2024`.
+  /// - `author`: The `$USER` (or `$USERNAME` on Windows) environment variable.
+  ///
+  /// And the user defined variables.
+  pub fn variables(&self, absolute_filename: &Path) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+    let file_name = |p: Option<&std::ffi::OsStr>| {
+      p.map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_default()
+    };
+    variables.insert(
+      "filename".to_string(),
+      file_name(absolute_filename.file_name()),
+    );
+    variables.insert("name".to_string(), file_name(absolute_filename.file_stem()));
+    variables.insert(
+      "path".to_string(),
+      absolute_filename.to_string_lossy().to_string(),
+    );
+    let now = Zoned::now();
+    variables.insert(
+      "date".to_string(),
+      format!(
+        "{:0>4}-{:0>2}-{:0>2}",
+        now.date().year(),
+        now.date().month(),
+        now.date().day()
+      ),
+    );
+    variables.insert("year".to_string(), format!("{}", now.date().year()));
+    if let Ok(author) = std::env::var("USER").or_else(|_| std::env::var("USERNAME")) {
+      variables.insert("author".to_string(), author);
+    }
+    for (name, value) in self.variables.iter() {
+      variables.insert(name.clone(), value.clone());
+    }
+    variables
+  }
+}
+
+/// Substitute the variables `{{name}}` in the template text, the blanks around the name are
+/// allowed, i.e. `{{ name }}`. The unknown variables are kept as they are.
+pub fn expand(template: &str, variables: &HashMap<String, String>) -> String {
+  let mut result = String::with_capacity(template.len());
+  let mut rest = template;
+  while let Some(start) = rest.find("{{") {
+    result.push_str(&rest[..start]);
+    let after = &rest[start + 2..];
+    match after.find("}}") {
+      Some(end) => match variables.get(after[..end].trim()) {
+        Some(value) => {
+          result.push_str(value);
+          rest = &after[end + 2..];
+        }
+        None => {
+          result.push_str("{{");
+          rest = after;
+        }
+      },
+      None => {
+        result.push_str(&rest[start..]);
+        rest = "";
+      }
+    }
+  }
+  result.push_str(rest);
+  result
+}
+
+/// Match the file with the glob pattern, the pattern is matched against the file name if it
+/// doesn't contain the path separator `/`, otherwise the absolute file path.
+pub fn path_match(pattern: &str, absolute_filename: &Path) -> bool {
+  if pattern.contains('/') {
+    glob_match(pattern, &absolute_filename.to_string_lossy())
+  } else {
+    match absolute_filename.file_name().and_then(|f| f.to_str()) {
+      Some(filename) => glob_match(pattern, filename),
+      None => false,
+    }
+  }
+}
+
+/// Match the text with the glob pattern, the wildcards are `*` (any chars) and `?` (any single
+/// char).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+  let pattern: Vec<char> = pattern.chars().collect();
+  let text: Vec<char> = text.chars().collect();
+  let (mut p, mut t) = (0, 0);
+  // The last `*` position in pattern, and the text position it matches from.
+  let mut star: Option<(usize, usize)> = None;
+  while t < text.len() {
+    if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+      p += 1;
+      t += 1;
+    } else if p < pattern.len() && pattern[p] == '*' {
+      star = Some((p, t));
+      p += 1;
+    } else if let Some((star_p, star_t)) = star {
+      p = star_p + 1;
+      t = star_t + 1;
+      star = Some((star_p, star_t + 1));
+    } else {
+      return false;
+    }
+  }
+  pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn glob_match1() {
+    assert!(glob_match("*.rs", "main.rs"));
+    assert!(glob_match("*", ""));
+    assert!(glob_match("Makefile", "Makefile"));
+    assert!(glob_match("test_?.py", "test_a.py"));
+    assert!(glob_match("*/src/*.rs", "/home/a/src/main.rs"));
+    assert!(glob_match("a*b*c", "aXbYbZc"));
+    assert!(!glob_match("*.rs", "main.rsx"));
+    assert!(!glob_match("test_?.py", "test_ab.py"));
+    assert!(!glob_match("Makefile", "makefile"));
+  }
+
+  #[test]
+  fn expand1() {
+    let mut variables = HashMap::new();
+    variables.insert("name".to_string(), "main".to_string());
+    variables.insert("author".to_string(), "李雷".to_string());
+    assert_eq!(
+      expand("// {{name}} by {{ author }}\n", &variables),
+      "// main by 李雷\n"
+    );
+    // Unknown and unclosed variables.
+    assert_eq!(
+      expand("{{unknown}} {{name}}", &variables),
+      "{{unknown}} main"
+    );
+    assert_eq!(expand("{{name", &variables), "{{name");
+    assert_eq!(expand("{{{name}}}", &variables), "{{{name}}}");
+  }
+
+  #[test]
+  fn candidates1() {
+    let mut templates = Templates::new();
+    templates.set_dirs(vec![PathBuf::from("/config/templates")]);
+    templates.add("*.sh", PathBuf::from("/a/sh.tpl"));
+    templates.add("*/test/*.rs", PathBuf::from("/a/test.tpl"));
+    assert_eq!(
+      templates.candidates(Path::new("/b/test/main.rs")),
+      vec![
+        PathBuf::from("/a/test.tpl"),
+        PathBuf::from("/config/templates/main.rs"),
+        PathBuf::from("/config/templates/skeleton.rs"),
+      ]
+    );
+    assert_eq!(
+      templates.candidates(Path::new("/b/run.sh")),
+      vec![
+        PathBuf::from("/a/sh.tpl"),
+        PathBuf::from("/config/templates/run.sh"),
+        PathBuf::from("/config/templates/skeleton.sh"),
+      ]
+    );
+    assert_eq!(
+      templates.candidates(Path::new("/b/Makefile")),
+      vec![PathBuf::from("/config/templates/Makefile")]
+    );
+  }
+
+  #[test]
+  fn variables1() {
+    let mut templates = Templates::new();
+    templates.set_variable("author", "Alice");
+    templates.set_variable("license", "MIT");
+    let variables = templates.variables(Path::new("/b/main.rs"));
+    assert_eq!(variables.get("filename").unwrap(), "main.rs");
+    assert_eq!(variables.get("name").unwrap(), "main");
+    assert_eq!(variables.get("path").unwrap(), "/b/main.rs");
+    assert_eq!(variables.get("author").unwrap(), "Alice");
+    assert_eq!(variables.get("license").unwrap(), "MIT");
+    assert_eq!(variables.get("date").unwrap().len(), 10);
+  }
+}
@@ -0,0 +1,137 @@
+//! Move files to the platform trash, i.e. `:Delete`.
+//!
+//! - Linux and other unix: The FreeDesktop.org trash, i.e. `$XDG_DATA_HOME/Trash` (defaults to
+//!   `$HOME/.local/share/Trash`), the file is moved into the `files` directory and its original
+//!   path is recorded in the `info` directory, thus it can be restored by the file managers.
+//! - macOS: The `$HOME/.Trash` directory.
+//! - Windows: The recycle bin is not supported yet, the files can only be deleted permanently.
+//!
+//! The file name is suffixed with a number if it already exists in the trash, i.e. `a.txt.2`.
+//!
+//! See: <https://specifications.freedesktop.org/trash-spec/latest/>.
+
+use crate::res::IoResult;
+
+use std::io::{Error as IoErr, ErrorKind as IoErrKind};
+use std::path::{Path, PathBuf};
+
+/// The trash directory of current user, it's `None` if the platform trash is not supported.
+pub fn trash_dir() -> Option<PathBuf> {
+  let base_dirs = directories::BaseDirs::new()?;
+  if cfg!(target_os = "windows") {
+    None
+  } else if cfg!(target_os = "macos") {
+    Some(base_dirs.home_dir().join(".Trash"))
+  } else {
+    match std::env::var("XDG_DATA_HOME") {
+      Ok(data_home) if !data_home.is_empty() => Some(Path::new(&data_home).join("Trash")),
+      _ => Some(base_dirs.home_dir().join(".local/share/Trash")),
+    }
+  }
+}
+
+/// The unique name in the trash for the file `name`, the `exists` checks whether a name is taken.
+pub fn unique_name<F>(name: &str, exists: F) -> String
+where
+  F: Fn(&str) -> bool,
+{
+  if !exists(name) {
+    return name.to_string();
+  }
+  (2..)
+    .map(|i| format!("{}.{}", name, i))
+    .find(|candidate| !exists(candidate))
+    .unwrap()
+}
+
+/// The `.trashinfo` file content of the deleted file `path`, the `deletion_date` is the local time
+/// in `YYYY-MM-DDThh:mm:ss` format.
+pub fn trash_info(path: &Path, deletion_date: &str) -> String {
+  // The path is percent-encoded same with the file URL.
+  let encoded = match url::Url::from_file_path(path) {
+    Ok(url) => url.path().to_string(),
+    Err(_) => path.to_string_lossy().to_string(),
+  };
+  format!(
+    "[Trash Info]\nPath={}\nDeletionDate={}\n",
+    encoded, deletion_date
+  )
+}
+
+/// Move the file `path` (must be absolute) to the `trash` directory (see [`trash_dir`]), returns
+/// the path of the file in the trash.
+pub fn move_to_trash(path: &Path, trash: &Path) -> IoResult<PathBuf> {
+  let name = match path.file_name() {
+    Some(name) => name.to_string_lossy().to_string(),
+    None => return Err(IoErr::new(IoErrKind::InvalidInput, "Invalid file name")),
+  };
+
+  if cfg!(target_os = "macos") {
+    std::fs::create_dir_all(trash)?;
+    let name = unique_name(&name, |n| trash.join(n).exists());
+    let target = trash.join(name);
+    move_file(path, &target)?;
+    return Ok(target);
+  }
+
+  let files_dir = trash.join("files");
+  let info_dir = trash.join("info");
+  std::fs::create_dir_all(&files_dir)?;
+  std::fs::create_dir_all(&info_dir)?;
+  let name = unique_name(&name, |n| {
+    files_dir.join(n).exists() || info_dir.join(format!("{}.trashinfo", n)).exists()
+  });
+  let info_file = info_dir.join(format!("{}.trashinfo", name));
+  let deletion_date = jiff::Zoned::now().strftime("%Y-%m-%dT%H:%M:%S").to_string();
+  // The info file is written first, same with the spec.
+  std::fs::write(&info_file, trash_info(path, &deletion_date))?;
+  let target = files_dir.join(name);
+  if let Err(e) = move_file(path, &target) {
+    let _ = std::fs::remove_file(&info_file);
+    return Err(e);
+  }
+  Ok(target)
+}
+
+// The rename fails across file systems, thus it's copied and removed.
+fn move_file(from: &Path, to: &Path) -> IoResult<()> {
+  if std::fs::rename(from, to).is_err() {
+    std::fs::copy(from, to)?;
+    std::fs::remove_file(from)?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn unique_name1() {
+    let taken = ["a.txt", "a.txt.2"];
+    assert_eq!(unique_name("b.txt", |n| taken.contains(&n)), "b.txt");
+    assert_eq!(unique_name("a.txt", |n| taken.contains(&n)), "a.txt.3");
+  }
+
+  #[cfg(not(target_os = "windows"))]
+  #[test]
+  fn move_to_trash1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let trash = tmp.path().join("Trash");
+    let file = tmp.path().join("a b.txt");
+    std::fs::write(&file, "hello").unwrap();
+
+    let target = move_to_trash(&file, &trash).unwrap();
+    assert!(!file.exists());
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), "hello");
+
+    std::fs::write(&file, "world").unwrap();
+    let target2 = move_to_trash(&file, &trash).unwrap();
+    assert_ne!(target, target2);
+    if !cfg!(target_os = "macos") {
+      let info = std::fs::read_to_string(trash.join("info/a b.txt.trashinfo")).unwrap();
+      assert!(info.starts_with("[Trash Info]\nPath="));
+      assert!(info.contains("a%20b.txt\n"));
+    }
+  }
+}
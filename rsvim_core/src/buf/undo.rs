@@ -0,0 +1,701 @@
+//! Buffer undo tree.
+//!
+//! Every edit on the buffer is recorded as a [`Delta`] (the removed and inserted text at a char
+//! index), the deltas are committed into an [`UndoState`] as a node of the undo tree. Like vim's
+//! [undo branches](https://vimhelp.org/undo.txt.html#undo-branches), making a new edit after undo
+//! doesn't drop the undone states, but starts a new branch instead.
+//!
+//! The edits can be grouped, i.e. all the edits in an insert mode session are committed as one
+//! state, thus they're undone at once.
+//!
+//! Each state has a sequence number (in creation order) and a timestamp, thus `:earlier` and
+//! `:later` can be built on top of [`UndoTree::goto`] and [`UndoTree::seq_at`].
+//!
+//! The committed states can be flushed to disk with [`UndoPersist`], only the states created
+//! since last flush are passed to it. The flushed states are restored with
+//! [`UndoTree::restore`], see [`undofile`](crate::buf::undofile).
+//!
+//! The tree is bounded by the 'undolevels' (the count of the undoable states) and 'undomemory'
+//! (the estimated bytes of the states) limits, see [`UndoTree::set_limits`]. When a limit is
+//! exceeded, the oldest states are evicted:
+//!
+//! 1. The oldest leaf state which is not on the path to current state, i.e. the oldest abandoned
+//!    branch is evicted first, one state at a time.
+//! 2. Then the root state, i.e. its child becomes the new root and it can't be undone any more.
+//!
+//! The evicted states keep their sequence numbers, but they have no edits and can't be visited.
+
+use crate::res::IoResult;
+
+use ropey::Rope;
+use std::mem::size_of;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An edit on the rope, i.e. replace the `removed` text at `char_idx` with the `inserted` text.
+pub struct Delta {
+  pub char_idx: usize,
+  pub removed: String,
+  pub inserted: String,
+}
+
+impl Delta {
+  pub fn new(char_idx: usize, removed: &str, inserted: &str) -> Self {
+    Delta {
+      char_idx,
+      removed: removed.to_string(),
+      inserted: inserted.to_string(),
+    }
+  }
+
+  /// Apply the edit to the rope.
+  pub fn apply(&self, rope: &mut Rope) {
+    let removed_chars = self.removed.chars().count();
+    rope.remove(self.char_idx..self.char_idx + removed_chars);
+    rope.insert(self.char_idx, &self.inserted);
+  }
+
+  /// Revert the edit from the rope.
+  pub fn revert(&self, rope: &mut Rope) {
+    let inserted_chars = self.inserted.chars().count();
+    rope.remove(self.char_idx..self.char_idx + inserted_chars);
+    rope.insert(self.char_idx, &self.removed);
+  }
+
+  /// The inverted edit, i.e. applying it is the same with reverting this edit.
+  pub fn inverted(&self) -> Delta {
+    Delta {
+      char_idx: self.char_idx,
+      removed: self.inserted.clone(),
+      inserted: self.removed.clone(),
+    }
+  }
+
+  /// The estimated memory (in bytes) of the edit.
+  pub fn memory(&self) -> usize {
+    size_of::<Delta>() + self.removed.len() + self.inserted.len()
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An undo state, i.e. a node in the undo tree.
+pub struct UndoState {
+  /// The sequence number, the original text is `0`.
+  pub seq: usize,
+  /// The parent sequence number, the original text has no parent.
+  pub parent: Option<usize>,
+  /// The moment when the state is created.
+  pub time: SystemTime,
+  /// The edits from the parent state to this state.
+  pub deltas: Vec<Delta>,
+  /// The child to redo, i.e. the most recently created or visited child.
+  pub redo_child: Option<usize>,
+}
+
+impl UndoState {
+  /// The estimated memory (in bytes) of the state.
+  pub fn memory(&self) -> usize {
+    size_of::<UndoState>()
+      + self
+        .deltas
+        .iter()
+        .map(|delta| delta.memory())
+        .sum::<usize>()
+  }
+}
+
+/// The hook to flush the undo states to disk, i.e. the 'undofile' option.
+pub trait UndoPersist {
+  /// Flush the newly created states, they're in creation order.
+  fn flush(&mut self, states: &[UndoState]) -> IoResult<()>;
+}
+
+#[derive(Debug, Clone)]
+/// The undo tree.
+pub struct UndoTree {
+  // All the states, indexed by sequence number.
+  states: Vec<UndoState>,
+
+  // Current state.
+  current: usize,
+
+  // The edits not committed yet (in a group).
+  pending: Vec<Delta>,
+
+  // Nested group depth.
+  group_depth: usize,
+
+  // The states before this sequence number are flushed.
+  flushed: usize,
+
+  // The root state, i.e. the oldest state can be visited.
+  root: usize,
+
+  // The count of the evicted states.
+  evictions: usize,
+
+  // The estimated memory (in bytes) of the states not evicted.
+  memory: usize,
+
+  // The 'undolevels' limit.
+  max_levels: usize,
+
+  // The 'undomemory' limit.
+  max_memory: usize,
+}
+
+impl UndoTree {
+  pub fn new() -> Self {
+    let root = UndoState {
+      seq: 0,
+      parent: None,
+      time: SystemTime::now(),
+      deltas: vec![],
+      redo_child: None,
+    };
+    let memory = root.memory();
+    UndoTree {
+      states: vec![root],
+      current: 0,
+      pending: vec![],
+      group_depth: 0,
+      flushed: 1,
+      root: 0,
+      evictions: 0,
+      memory,
+      max_levels: usize::MAX,
+      max_memory: usize::MAX,
+    }
+  }
+
+  /// Set the 'undolevels' (the maximum count of the undoable states) and 'undomemory' (the
+  /// maximum estimated bytes of the states) limits, the exceeded states are evicted immediately.
+  ///
+  /// NOTE: The states on the path to current state are evicted only when there's no other states
+  /// to evict, and current state itself is never evicted. Thus the memory can still exceed the
+  /// limit when current state is too large.
+  pub fn set_limits(&mut self, max_levels: usize, max_memory: usize) {
+    self.max_levels = max_levels;
+    self.max_memory = max_memory;
+    self.evict();
+  }
+
+  /// Get the root state's sequence number, i.e. the oldest state can be visited.
+  pub fn root(&self) -> usize {
+    self.root
+  }
+
+  /// Get the count of the undoable states, i.e. the states not evicted except the root.
+  pub fn levels(&self) -> usize {
+    self.states.len() - self.evictions - 1
+  }
+
+  /// Get the estimated memory (in bytes) of the states not evicted.
+  pub fn memory(&self) -> usize {
+    self.memory
+  }
+
+  /// Get the count of the evicted states.
+  pub fn evictions(&self) -> usize {
+    self.evictions
+  }
+
+  /// Whether the state `seq` is evicted.
+  pub fn is_evicted(&self, seq: usize) -> bool {
+    seq != self.root && self.states[seq].parent.is_none()
+  }
+
+  /// Get all the states, indexed by sequence number.
+  pub fn states(&self) -> &[UndoState] {
+    &self.states
+  }
+
+  /// Get current state's sequence number.
+  pub fn current(&self) -> usize {
+    self.current
+  }
+
+  /// Whether there're edits not committed yet, i.e. in a group.
+  pub fn has_pending(&self) -> bool {
+    !self.pending.is_empty()
+  }
+
+  /// Record an edit, it's committed immediately unless it's in a group.
+  pub fn record(&mut self, delta: Delta) {
+    self.pending.push(delta);
+    if self.group_depth == 0 {
+      self.commit();
+    }
+  }
+
+  /// Begin a group, the edits until [`end_group`](UndoTree::end_group) are committed as one state.
+  pub fn begin_group(&mut self) {
+    self.group_depth += 1;
+  }
+
+  /// End a group.
+  pub fn end_group(&mut self) {
+    self.group_depth = self.group_depth.saturating_sub(1);
+    if self.group_depth == 0 {
+      self.commit();
+    }
+  }
+
+  // Commit the pending edits as a new state.
+  fn commit(&mut self) {
+    if self.pending.is_empty() {
+      return;
+    }
+    let seq = self.states.len();
+    self.states.push(UndoState {
+      seq,
+      parent: Some(self.current),
+      time: SystemTime::now(),
+      deltas: std::mem::take(&mut self.pending),
+      redo_child: None,
+    });
+    self.memory += self.states[seq].memory();
+    self.states[self.current].redo_child = Some(seq);
+    self.current = seq;
+    self.evict();
+  }
+
+  // Evict the oldest states until both limits are satisfied.
+  fn evict(&mut self) {
+    while self.levels() > self.max_levels || self.memory > self.max_memory {
+      if let Some(seq) = self.oldest_abandoned_leaf() {
+        let parent = self.states[seq].parent.take().unwrap();
+        self.memory -= self.states[seq].memory();
+        self.states[seq].deltas.clear();
+        // Redo the newest remaining child of the parent.
+        self.states[parent].redo_child = self
+          .states
+          .iter()
+          .rev()
+          .find(|state| state.parent == Some(parent))
+          .map(|state| state.seq);
+      } else if self.current != self.root {
+        // There's only one path from the root to current state, its child becomes the new root.
+        let root = self.root;
+        let child = self.states[root].redo_child.take().unwrap();
+        self.memory -= self.states[root].memory() + self.states[child].memory();
+        self.states[child].parent = None;
+        self.states[child].deltas.clear();
+        self.memory += self.states[child].memory();
+        self.root = child;
+      } else {
+        break;
+      }
+      self.evictions += 1;
+    }
+  }
+
+  // The oldest leaf state which is neither current state nor its ancestor.
+  fn oldest_abandoned_leaf(&self) -> Option<usize> {
+    let mut has_child = vec![false; self.states.len()];
+    for state in self.states.iter() {
+      if let Some(parent) = state.parent {
+        has_child[parent] = true;
+      }
+    }
+    let ancestors = self.ancestors(self.current);
+    self
+      .states
+      .iter()
+      .map(|state| state.seq)
+      .find(|seq| !has_child[*seq] && !self.is_evicted(*seq) && !ancestors.contains(seq))
+  }
+
+  /// Undo current state, returns the char index of the first reverted edit, or `None` if there's
+  /// nothing to undo.
+  ///
+  /// NOTE: The pending edits in a group are committed first.
+  pub fn undo(&mut self, rope: &mut Rope) -> Option<usize> {
+    self.undo_with(rope, &mut |_, _| {})
+  }
+
+  /// Same with [`undo`](UndoTree::undo), the `on_delta` is called before each edit is applied to
+  /// the rope, i.e. to adjust the marks. The reverted edits are passed as inverted.
+  pub fn undo_with(
+    &mut self,
+    rope: &mut Rope,
+    on_delta: &mut dyn FnMut(&Rope, &Delta),
+  ) -> Option<usize> {
+    self.commit();
+    let state = &self.states[self.current];
+    let parent = state.parent?;
+    for delta in state.deltas.iter().rev() {
+      on_delta(rope, &delta.inverted());
+      delta.revert(rope);
+    }
+    let char_idx = state.deltas.first().map(|delta| delta.char_idx);
+    self.states[parent].redo_child = Some(self.current);
+    self.current = parent;
+    char_idx
+  }
+
+  /// Redo the most recent child state, returns the char index of the first applied edit, or
+  /// `None` if there's nothing to redo.
+  pub fn redo(&mut self, rope: &mut Rope) -> Option<usize> {
+    self.redo_with(rope, &mut |_, _| {})
+  }
+
+  /// Same with [`redo`](UndoTree::redo), the `on_delta` is called before each edit is applied to
+  /// the rope.
+  pub fn redo_with(
+    &mut self,
+    rope: &mut Rope,
+    on_delta: &mut dyn FnMut(&Rope, &Delta),
+  ) -> Option<usize> {
+    self.commit();
+    let child = self.states[self.current].redo_child?;
+    for delta in self.states[child].deltas.iter() {
+      on_delta(rope, delta);
+      delta.apply(rope);
+    }
+    self.current = child;
+    self.states[child]
+      .deltas
+      .first()
+      .map(|delta| delta.char_idx)
+  }
+
+  /// Go to the state `seq` (in any branch), i.e. undo to the common ancestor then redo to the
+  /// state. Returns `false` if the state doesn't exist or is evicted.
+  pub fn goto(&mut self, seq: usize, rope: &mut Rope) -> bool {
+    self.goto_with(seq, rope, &mut |_, _| {})
+  }
+
+  /// Same with [`goto`](UndoTree::goto), the `on_delta` is called before each edit is applied to
+  /// the rope.
+  pub fn goto_with(
+    &mut self,
+    seq: usize,
+    rope: &mut Rope,
+    on_delta: &mut dyn FnMut(&Rope, &Delta),
+  ) -> bool {
+    self.commit();
+    if seq >= self.states.len() || self.is_evicted(seq) {
+      return false;
+    }
+
+    let ancestors = self.ancestors(seq);
+    while !ancestors.contains(&self.current) {
+      self.undo_with(rope, on_delta);
+    }
+    // The path from current state down to the target state.
+    let index = ancestors.iter().position(|s| *s == self.current).unwrap();
+    for child in ancestors[..index].iter().rev() {
+      self.states[self.current].redo_child = Some(*child);
+      self.redo_with(rope, on_delta);
+    }
+    true
+  }
+
+  // The state and all its ancestors, from the state up to the root.
+  fn ancestors(&self, seq: usize) -> Vec<usize> {
+    let mut result = vec![seq];
+    let mut state = &self.states[seq];
+    while let Some(parent) = state.parent {
+      result.push(parent);
+      state = &self.states[parent];
+    }
+    result
+  }
+
+  /// Get the most recent state created at or before the `time`, i.e. `:earlier {N}s`. The
+  /// evicted states are skipped, it's the root state if there's no such state.
+  pub fn seq_at(&self, time: SystemTime) -> usize {
+    self
+      .states
+      .iter()
+      .rev()
+      .find(|state| state.time <= time && !self.is_evicted(state.seq))
+      .map(|state| state.seq)
+      .unwrap_or(self.root)
+  }
+
+  /// Get the states created since last flush.
+  pub fn unflushed(&self) -> &[UndoState] {
+    &self.states[self.flushed..]
+  }
+
+  /// Flush the states created since last flush.
+  pub fn flush(&mut self, persist: &mut dyn UndoPersist) -> IoResult<()> {
+    persist.flush(self.unflushed())?;
+    self.flushed = self.states.len();
+    Ok(())
+  }
+
+  /// Set all the states are flushed, i.e. the whole undo history is written.
+  pub fn set_flushed(&mut self) {
+    self.flushed = self.states.len();
+  }
+
+  /// Restore the flushed `states` (in creation order, i.e. start from `1`) into the new tree, the
+  /// text is at the state `seq`. The root is the oldest ancestor of `seq` can be visited, and the
+  /// states not descended from it are evicted, then the limits are applied.
+  ///
+  /// Returns `false` if the tree is not new or the states are invalid, the tree is not changed.
+  pub fn restore(&mut self, states: Vec<UndoState>, seq: usize) -> bool {
+    if self.states.len() != 1
+      || self.has_pending()
+      || seq > states.len()
+      || states
+        .iter()
+        .enumerate()
+        .any(|(i, state)| state.seq != i + 1)
+    {
+      return false;
+    }
+
+    self.states.truncate(1);
+    self.states.extend(states);
+    let len = self.states.len();
+    for state in self.states.iter_mut() {
+      state.redo_child = None;
+      if state.parent.is_some_and(|parent| parent >= state.seq) {
+        state.parent = None;
+      }
+    }
+    self.current = seq;
+    self.root = *self.ancestors(seq).last().unwrap();
+
+    // The parent is always created before the child, thus a forward pass finds all descendants.
+    let mut descended = vec![false; len];
+    descended[self.root] = true;
+    for i in self.root + 1..len {
+      descended[i] = self.states[i]
+        .parent
+        .is_some_and(|parent| descended[parent]);
+    }
+    self.memory = 0;
+    self.evictions = 0;
+    for (i, descended) in descended.into_iter().enumerate() {
+      if i == self.root || !descended {
+        self.states[i].parent = None;
+        self.states[i].deltas.clear();
+      }
+      if descended {
+        self.memory += self.states[i].memory();
+        // Redo the newest child.
+        if let Some(parent) = self.states[i].parent {
+          self.states[parent].redo_child = Some(i);
+        }
+      } else {
+        self.evictions += 1;
+      }
+    }
+    self.flushed = len;
+    self.evict();
+    true
+  }
+}
+
+impl Default for UndoTree {
+  fn default() -> Self {
+    UndoTree::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn edit(tree: &mut UndoTree, rope: &mut Rope, delta: Delta) {
+    delta.apply(rope);
+    tree.record(delta);
+  }
+
+  #[test]
+  fn undo_redo1() {
+    let mut rope = Rope::from_str("hello");
+    let mut tree = UndoTree::new();
+    edit(&mut tree, &mut rope, Delta::new(5, "", " world"));
+    edit(&mut tree, &mut rope, Delta::new(0, "h", "H"));
+    assert_eq!(rope.to_string(), "Hello world");
+
+    assert_eq!(tree.undo(&mut rope), Some(0));
+    assert_eq!(rope.to_string(), "hello world");
+    assert_eq!(tree.undo(&mut rope), Some(5));
+    assert_eq!(rope.to_string(), "hello");
+    assert_eq!(tree.undo(&mut rope), None);
+
+    assert_eq!(tree.redo(&mut rope), Some(5));
+    assert_eq!(tree.redo(&mut rope), Some(0));
+    assert_eq!(tree.redo(&mut rope), None);
+    assert_eq!(rope.to_string(), "Hello world");
+  }
+
+  #[test]
+  fn group1() {
+    let mut rope = Rope::from_str("");
+    let mut tree = UndoTree::new();
+    tree.begin_group();
+    edit(&mut tree, &mut rope, Delta::new(0, "", "a"));
+    edit(&mut tree, &mut rope, Delta::new(1, "", "b"));
+    edit(&mut tree, &mut rope, Delta::new(2, "", "c"));
+    tree.end_group();
+    assert_eq!(tree.states().len(), 2);
+    assert_eq!(tree.undo(&mut rope), Some(0));
+    assert_eq!(rope.to_string(), "");
+  }
+
+  #[test]
+  fn branch1() {
+    let mut rope = Rope::from_str("one");
+    let mut tree = UndoTree::new();
+    edit(&mut tree, &mut rope, Delta::new(3, "", " two"));
+    tree.undo(&mut rope);
+    edit(&mut tree, &mut rope, Delta::new(3, "", " three"));
+    assert_eq!(rope.to_string(), "one three");
+    assert_eq!(tree.current(), 2);
+    assert_eq!(tree.states()[2].parent, Some(0));
+
+    assert!(tree.goto(1, &mut rope));
+    assert_eq!(rope.to_string(), "one two");
+    assert_eq!(tree.current(), 1);
+    assert!(tree.goto(2, &mut rope));
+    assert_eq!(rope.to_string(), "one three");
+    assert!(tree.goto(0, &mut rope));
+    assert_eq!(rope.to_string(), "one");
+    assert!(!tree.goto(3, &mut rope));
+    // Redo the most recently visited branch.
+    assert_eq!(tree.redo(&mut rope), Some(3));
+    assert_eq!(rope.to_string(), "one three");
+  }
+
+  #[test]
+  fn flush1() {
+    struct Counter(usize);
+    impl UndoPersist for Counter {
+      fn flush(&mut self, states: &[UndoState]) -> IoResult<()> {
+        self.0 += states.len();
+        Ok(())
+      }
+    }
+
+    let mut rope = Rope::from_str("");
+    let mut tree = UndoTree::new();
+    let mut counter = Counter(0);
+    edit(&mut tree, &mut rope, Delta::new(0, "", "a"));
+    edit(&mut tree, &mut rope, Delta::new(1, "", "b"));
+    assert_eq!(tree.unflushed().len(), 2);
+    tree.flush(&mut counter).unwrap();
+    assert!(tree.unflushed().is_empty());
+    edit(&mut tree, &mut rope, Delta::new(2, "", "c"));
+    tree.flush(&mut counter).unwrap();
+    assert_eq!(counter.0, 3);
+    assert_eq!(tree.seq_at(SystemTime::now()), 3);
+  }
+
+  #[test]
+  fn restore1() {
+    let mut rope = Rope::from_str("");
+    let mut tree = UndoTree::new();
+    edit(&mut tree, &mut rope, Delta::new(0, "", "a"));
+    edit(&mut tree, &mut rope, Delta::new(1, "", "b"));
+    tree.undo(&mut rope);
+    edit(&mut tree, &mut rope, Delta::new(1, "", "c"));
+    assert_eq!(rope.to_string(), "ac");
+
+    // Restore at the state `3`, with all the branches.
+    let mut restored = UndoTree::new();
+    assert!(restored.restore(tree.states()[1..].to_vec(), 3));
+    assert!(restored.unflushed().is_empty());
+    assert_eq!(restored.current(), 3);
+    assert_eq!(restored.root(), 0);
+    assert_eq!(restored.levels(), 3);
+    assert!(restored.goto(2, &mut rope));
+    assert_eq!(rope.to_string(), "ab");
+    assert_eq!(restored.undo(&mut rope), Some(1));
+    assert_eq!(restored.undo(&mut rope), Some(0));
+    assert_eq!(rope.to_string(), "");
+    assert!(!restored.restore(vec![], 0));
+
+    // The state `1` is evicted (i.e. it's the root), thus the state `0` is not descended from it.
+    let mut states = tree.states()[1..].to_vec();
+    states[0].parent = None;
+    states[0].deltas.clear();
+    let mut restored = UndoTree::new();
+    assert!(restored.restore(states.clone(), 2));
+    assert_eq!(restored.root(), 1);
+    assert!(restored.is_evicted(0));
+    assert!(!restored.is_evicted(3));
+    assert_eq!(restored.levels(), 2);
+    assert_eq!(restored.evictions(), 1);
+    let mut rope = Rope::from_str("ab");
+    assert_eq!(restored.undo(&mut rope), Some(1));
+    assert_eq!(restored.undo(&mut rope), None);
+    assert_eq!(rope.to_string(), "a");
+
+    // Invalid states.
+    assert!(!UndoTree::new().restore(states.clone(), 4));
+    assert!(!UndoTree::new().restore(states[1..].to_vec(), 0));
+  }
+
+  #[test]
+  fn evict_levels1() {
+    let mut rope = Rope::from_str("");
+    let mut tree = UndoTree::new();
+    tree.set_limits(2, usize::MAX);
+    edit(&mut tree, &mut rope, Delta::new(0, "", "a"));
+    edit(&mut tree, &mut rope, Delta::new(1, "", "b"));
+    edit(&mut tree, &mut rope, Delta::new(2, "", "c"));
+    assert_eq!(tree.levels(), 2);
+    assert_eq!(tree.evictions(), 1);
+    assert_eq!(tree.root(), 1);
+    assert!(tree.is_evicted(0));
+
+    assert_eq!(tree.undo(&mut rope), Some(2));
+    assert_eq!(tree.undo(&mut rope), Some(1));
+    assert_eq!(tree.undo(&mut rope), None);
+    assert_eq!(rope.to_string(), "a");
+    assert!(!tree.goto(0, &mut rope));
+    assert!(tree.goto(3, &mut rope));
+    assert_eq!(rope.to_string(), "abc");
+  }
+
+  #[test]
+  fn evict_branch1() {
+    let mut rope = Rope::from_str("one");
+    let mut tree = UndoTree::new();
+    tree.set_limits(2, usize::MAX);
+    edit(&mut tree, &mut rope, Delta::new(3, "", " two"));
+    tree.undo(&mut rope);
+    edit(&mut tree, &mut rope, Delta::new(3, "", " three"));
+    assert_eq!(tree.evictions(), 0);
+
+    // The abandoned branch is evicted before the root.
+    edit(&mut tree, &mut rope, Delta::new(0, "o", "O"));
+    assert_eq!(tree.evictions(), 1);
+    assert!(tree.is_evicted(1));
+    assert_eq!(tree.root(), 0);
+    assert!(!tree.goto(1, &mut rope));
+    assert!(tree.goto(0, &mut rope));
+    assert_eq!(rope.to_string(), "one");
+    assert_eq!(tree.redo(&mut rope), Some(3));
+    assert_eq!(rope.to_string(), "one three");
+  }
+
+  #[test]
+  fn evict_memory1() {
+    let mut rope = Rope::from_str("");
+    let mut tree = UndoTree::new();
+    let empty = tree.memory();
+    edit(&mut tree, &mut rope, Delta::new(0, "", "hello"));
+    let state = tree.states()[1].memory();
+    assert_eq!(tree.memory(), empty + state);
+
+    // Only one state can be undone.
+    tree.set_limits(usize::MAX, empty + state);
+    assert_eq!(tree.evictions(), 0);
+    edit(&mut tree, &mut rope, Delta::new(5, "", "world"));
+    assert_eq!(tree.evictions(), 1);
+    assert_eq!(tree.root(), 1);
+    assert_eq!(tree.memory(), empty + state);
+    assert_eq!(tree.undo(&mut rope), Some(5));
+    assert_eq!(rope.to_string(), "hello");
+    assert_eq!(tree.undo(&mut rope), None);
+  }
+}
@@ -0,0 +1,298 @@
+//! Undo file, i.e. Vim's 'undofile' option.
+//!
+//! When the 'undofile' option is on (see
+//! [`BuffersManager::set_undo_file`](crate::buf::BuffersManager::set_undo_file)), the undo history
+//! of a file buffer is saved in a journal file under the undo directory (see
+//! [`BuffersManager::set_undo_dir`](crate::buf::BuffersManager::set_undo_dir)), the journal file
+//! name is the SHA-1 of the buffer's absolute file path. Each line is a JSON object:
+//!
+//! - `{"saved": seq, "hash": sha1}`: The buffer is saved at the undo state `seq`, and the SHA-1 of
+//!   the text is `hash`. It's always the first line.
+//! - `{"seq": seq, "parent": parent, "time": millis, "deltas": [[char_idx, removed, inserted]]}`:
+//!   An undo state (see [`UndoState`]), they're in creation order.
+//!
+//! The journal is rewritten (i.e. compacted) with the whole undo history each time the buffer is
+//! saved after a change, the states created after that are appended by
+//! [`UndoTree::flush`](crate::buf::UndoTree::flush). Nothing is written if the buffer is never
+//! changed.
+//!
+//! When the file is opened again, the undo history is restored if the text is the same with the
+//! saved state. Otherwise the journal is ignored, and it's replaced on next save.
+//!
+//! NOTE: The text is only hashed when there's a journal to restore, or an undo history to save.
+//! The journal is read and written through the buffers' file system provider (see
+//! [`fs`](crate::buf::fs)).
+//!
+//! NOTE: The states evicted (see [`UndoTree::set_limits`](crate::buf::UndoTree::set_limits))
+//! before they're written are saved without edits, thus they (and their descendants) can't be
+//! restored.
+
+use crate::buf::fs::FsProviderArc;
+use crate::buf::undo::{Delta, UndoPersist, UndoState};
+use crate::buf::write::{FsyncPolicy, WriteOptions};
+use crate::res::IoResult;
+
+use ropey::Rope;
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The undo directory name under the data directory.
+pub const UNDO_DIR: &str = "undo";
+
+/// Get the SHA-1 of the text.
+pub fn text_hash(rope: &Rope) -> String {
+  let mut hasher = Sha1::new();
+  for chunk in rope.chunks() {
+    hasher.update(chunk.as_bytes());
+  }
+  format!("{:x}", hasher.finalize())
+}
+
+/// Get the journal file path of the file `absolute_filename` under the `undo_dir`.
+pub fn undo_file_path(undo_dir: &Path, absolute_filename: &Path) -> PathBuf {
+  let mut hasher = Sha1::new();
+  hasher.update(absolute_filename.to_string_lossy().as_bytes());
+  undo_dir.join(format!("{:x}", hasher.finalize()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The undo history read from the journal file.
+pub struct UndoJournal {
+  /// The states in creation order, i.e. start from `1`.
+  pub states: Vec<UndoState>,
+  /// The undo state of the text.
+  pub seq: usize,
+}
+
+#[derive(Debug, Clone)]
+/// The journal file of a buffer's undo history.
+pub struct UndoFile {
+  fs: FsProviderArc,
+  path: PathBuf,
+  // Whether the journal has the same states with the undo tree, the new states are only appended
+  // to a synced journal.
+  synced: bool,
+}
+
+impl UndoFile {
+  pub fn new(fs: FsProviderArc, path: PathBuf) -> Self {
+    UndoFile {
+      fs,
+      path,
+      synced: false,
+    }
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  pub fn set_path(&mut self, path: PathBuf) {
+    self.path = path;
+  }
+
+  /// Whether the journal has the same states with the undo tree, i.e. it's restored or compacted.
+  pub fn is_synced(&self) -> bool {
+    self.synced
+  }
+
+  /// Rewrite the journal with all the undo `states` (start from the root `0`), the `text` is
+  /// saved at the undo state `seq`. The parent directory is created if it doesn't exist.
+  pub fn compact(&mut self, seq: usize, text: &Rope, states: &[UndoState]) -> IoResult<()> {
+    let mut journal = format!("{}\n", saved_record(seq, &text_hash(text)));
+    for state in states.iter().skip(1) {
+      journal.push_str(&format!("{}\n", state_record(state)));
+    }
+    // NOTE: The journal can be rebuilt on next save, thus it doesn't need the backup or fsync.
+    let mut options = WriteOptions::default();
+    options.set_write_backup(false);
+    options.set_backup(false);
+    options.set_fsync(FsyncPolicy::Never);
+    self
+      .fs
+      .write_file(&self.path, &options, true, &mut |writer| {
+        writer.write_all(journal.as_bytes())
+      })?;
+    self.synced = true;
+    Ok(())
+  }
+
+  /// Read the undo history, the `text` is the loaded buffer text. The journal becomes synced if
+  /// the undo history is restored, see [`set_synced`](Self::set_synced).
+  ///
+  /// It returns `None` if the journal doesn't exist, or the text is not the same with the saved
+  /// state. The invalid lines are skipped, and the states after a missing state are dropped.
+  pub fn load(&self, text: &Rope) -> IoResult<Option<UndoJournal>> {
+    let bytes = match self.fs.read(&self.path) {
+      Ok((bytes, _)) => bytes,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+      Err(e) => return Err(e),
+    };
+
+    let mut states: Vec<UndoState> = vec![];
+    let mut seq: Option<usize> = None;
+    for line in String::from_utf8_lossy(&bytes).lines() {
+      let record = match serde_json::from_str::<Value>(line) {
+        Ok(record) => record,
+        Err(_) => continue,
+      };
+      if let Some(saved) = record.get("saved").and_then(Value::as_u64) {
+        let hash = record.get("hash").and_then(Value::as_str);
+        if seq.is_none() && hash.is_some_and(|hash| hash == text_hash(text)) {
+          seq = Some(saved as usize);
+        } else {
+          // The file is changed outside.
+          return Ok(None);
+        }
+      } else if let Some(state) = parse_state(&record) {
+        if state.seq == states.len() + 1 {
+          states.push(state);
+        }
+      }
+    }
+
+    Ok(
+      seq
+        .filter(|seq| *seq <= states.len())
+        .map(|seq| UndoJournal { states, seq }),
+    )
+  }
+
+  /// Set the journal is synced, i.e. the undo history is restored from it.
+  pub fn set_synced(&mut self) {
+    self.synced = true;
+  }
+}
+
+impl UndoPersist for UndoFile {
+  fn flush(&mut self, states: &[UndoState]) -> IoResult<()> {
+    let text = states
+      .iter()
+      .map(|state| format!("{}\n", state_record(state)))
+      .collect::<String>();
+    self.fs.append(&self.path, text.as_bytes())
+  }
+}
+
+fn saved_record(seq: usize, hash: &str) -> Value {
+  json!({ "saved": seq, "hash": hash })
+}
+
+fn state_record(state: &UndoState) -> Value {
+  let time = state
+    .time
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_millis() as u64;
+  let deltas = state
+    .deltas
+    .iter()
+    .map(|delta| json!([delta.char_idx, delta.removed, delta.inserted]))
+    .collect::<Vec<_>>();
+  json!({
+    "seq": state.seq,
+    "parent": state.parent,
+    "time": time,
+    "deltas": deltas,
+  })
+}
+
+fn parse_state(record: &Value) -> Option<UndoState> {
+  let seq = record.get("seq")?.as_u64()? as usize;
+  let parent = match record.get("parent")? {
+    Value::Null => None,
+    parent => Some(parent.as_u64()? as usize),
+  };
+  let time = UNIX_EPOCH + Duration::from_millis(record.get("time")?.as_u64()?);
+  let deltas = record
+    .get("deltas")?
+    .as_array()?
+    .iter()
+    .map(|delta| {
+      let char_idx = delta.get(0)?.as_u64()? as usize;
+      let removed = delta.get(1)?.as_str()?;
+      let inserted = delta.get(2)?.as_str()?;
+      Some(Delta::new(char_idx, removed, inserted))
+    })
+    .collect::<Option<Vec<_>>>()?;
+  Some(UndoState {
+    seq,
+    parent,
+    time: time.min(SystemTime::now()),
+    deltas,
+    redo_child: None,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::buf::fs::{FsProvider, MemoryFs};
+
+  use std::sync::Arc;
+
+  #[test]
+  fn undo_file1() {
+    let fs = Arc::new(MemoryFs::new());
+    let path = undo_file_path(
+      Path::new("/rsvim").join(UNDO_DIR).as_path(),
+      Path::new("/tmp/a.txt"),
+    );
+    let mut undo_file = UndoFile::new(fs.clone(), path.clone());
+    let text0 = Rope::from_str("");
+    let text1 = Rope::from_str("a\n\"b\"");
+    assert_eq!(undo_file.load(&text0).unwrap(), None);
+    assert!(!undo_file.is_synced());
+
+    let state = |seq: usize, parent: Option<usize>, deltas: Vec<Delta>| UndoState {
+      seq,
+      parent,
+      time: UNIX_EPOCH + Duration::from_millis(seq as u64),
+      deltas,
+      redo_child: None,
+    };
+    let states = vec![
+      state(0, None, vec![]),
+      state(1, Some(0), vec![Delta::new(0, "", "a\n\"b\"")]),
+      state(2, None, vec![]),
+    ];
+    undo_file.compact(1, &text1, &states).unwrap();
+    assert!(undo_file.is_synced());
+    undo_file
+      .flush(&[state(3, Some(1), vec![Delta::new(1, "\n", "")])])
+      .unwrap();
+
+    let journal = undo_file.load(&text1).unwrap().unwrap();
+    assert_eq!(journal.seq, 1);
+    assert_eq!(journal.states.len(), 3);
+    assert_eq!(
+      journal.states[0].deltas,
+      vec![Delta::new(0, "", "a\n\"b\"")]
+    );
+    assert_eq!(journal.states[1].parent, None);
+    assert_eq!(journal.states[2].parent, Some(1));
+    assert_eq!(
+      journal.states[2].time,
+      UNIX_EPOCH + Duration::from_millis(3)
+    );
+    // The text is changed outside.
+    assert_eq!(undo_file.load(&text0).unwrap(), None);
+
+    // Compact on save, the journal is rewritten.
+    undo_file.compact(0, &text0, &states[..1]).unwrap();
+    let journal = undo_file.load(&text0).unwrap().unwrap();
+    assert_eq!(journal.seq, 0);
+    assert!(journal.states.is_empty());
+    assert_eq!(
+      fs.read(&path)
+        .unwrap()
+        .0
+        .iter()
+        .filter(|b| **b == b'\n')
+        .count(),
+      1
+    );
+  }
+}
@@ -0,0 +1,442 @@
+//! Buffer writing, i.e. the `:write` command.
+//!
+//! It's the local file system [`StdFs`](crate::buf::StdFs) implementation of
+//! [`FsProvider::write_file`](crate::buf::FsProvider::write_file).
+//!
+//! The file is written atomically: the contents are written to a temporary file in the same
+//! directory, and then it's renamed to the target file. Thus the target file is either the old
+//! contents or the new contents, even if the editor crashes or the disk is full while writing.
+//!
+//! - Symlink: The link target is written, i.e. the symlink itself is kept.
+//! - Permissions: The new file has the same permissions (and owner on unix, if possible) as the
+//!   old file.
+//! - Backup: The old file is copied to `{file}~` before writing. It's kept if the 'backup' option
+//!   is on, or it's removed after the file is successfully written if only the 'writebackup'
+//!   option is on.
+//! - Fsync: The 'fsync' option decides whether the file (and its directory) is flushed to the disk,
+//!   see [`FsyncPolicy`].
+//! - Directories: If the parent directory doesn't exist, it's created when the 'mkdir' option is
+//!   on (or forced by the caller, i.e. `:write ++p`), otherwise it fails with
+//!   [`NotFound`](IoErrKind::NotFound) and nothing is created.
+//!
+//! NOTE: The renaming breaks the hard links, i.e. the other links still have the old contents.
+
+use crate::defaults;
+use crate::res::{IoErr, IoErrKind, IoResult};
+
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The backup file extension, i.e. Vim's 'backupext' option.
+pub const BACKUP_EXT: &str = "~";
+
+// The max symlinks to follow, same with Linux's `MAXSYMLINKS`.
+const MAX_SYMLINKS: usize = 40;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The 'fsync' option, i.e. how the written file is flushed to the disk.
+pub enum FsyncPolicy {
+  /// Never flush, i.e. leave it to the operating system.
+  Never,
+  /// Flush the file before renaming it to the target file.
+  File,
+  /// Flush the file, and its directory after renaming, thus the renaming is also durable.
+  Full,
+}
+
+impl Display for FsyncPolicy {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      FsyncPolicy::Never => write!(f, "never"),
+      FsyncPolicy::File => write!(f, "file"),
+      FsyncPolicy::Full => write!(f, "full"),
+    }
+  }
+}
+
+impl FromStr for FsyncPolicy {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "never" => Ok(FsyncPolicy::Never),
+      "file" => Ok(FsyncPolicy::File),
+      "full" => Ok(FsyncPolicy::Full),
+      _ => Err(format!("Unknown fsync value {:?}", s)),
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Global options for writing buffers.
+pub struct WriteOptions {
+  backup: bool,
+  write_backup: bool,
+  fsync: FsyncPolicy,
+  mkdir: bool,
+}
+
+impl Default for WriteOptions {
+  fn default() -> Self {
+    WriteOptions {
+      backup: defaults::buf::BACKUP,
+      write_backup: defaults::buf::WRITE_BACKUP,
+      fsync: defaults::buf::FSYNC,
+      mkdir: defaults::buf::MKDIR,
+    }
+  }
+}
+
+impl WriteOptions {
+  /// The 'backup' option, keep the backup file after writing.
+  pub fn backup(&self) -> bool {
+    self.backup
+  }
+
+  pub fn set_backup(&mut self, value: bool) {
+    self.backup = value;
+  }
+
+  /// The 'writebackup' option, make a backup file while writing, it's removed after the file is
+  /// successfully written (unless 'backup' is on).
+  pub fn write_backup(&self) -> bool {
+    self.write_backup
+  }
+
+  pub fn set_write_backup(&mut self, value: bool) {
+    self.write_backup = value;
+  }
+
+  /// The 'fsync' option.
+  pub fn fsync(&self) -> FsyncPolicy {
+    self.fsync
+  }
+
+  pub fn set_fsync(&mut self, value: FsyncPolicy) {
+    self.fsync = value;
+  }
+
+  /// The 'mkdir' option, create the nonexistent parent directories before writing.
+  pub fn mkdir(&self) -> bool {
+    self.mkdir
+  }
+
+  pub fn set_mkdir(&mut self, value: bool) {
+    self.mkdir = value;
+  }
+}
+
+/// Resolve the symlinks of `path`, returns the final target (it may not exist, i.e. a new file or
+/// a dangling symlink).
+pub fn resolve_symlink(path: &Path) -> IoResult<PathBuf> {
+  let mut target = path.to_path_buf();
+  for _ in 0..MAX_SYMLINKS {
+    match std::fs::symlink_metadata(&target) {
+      Ok(metadata) if metadata.file_type().is_symlink() => {
+        let link = std::fs::read_link(&target)?;
+        // The relative link is relative to the symlink's directory.
+        target = match target.parent() {
+          Some(parent) if link.is_relative() => parent.join(link),
+          _ => link,
+        };
+      }
+      _ => return Ok(target),
+    }
+  }
+  Err(IoErr::new(
+    IoErrKind::Other,
+    format!("Too many levels of symbolic links: {:?}", path),
+  ))
+}
+
+/// The backup file path of `path`, i.e. `{path}~`.
+pub fn backup_path(path: &Path) -> PathBuf {
+  let mut name = OsString::from(path.as_os_str());
+  name.push(BACKUP_EXT);
+  PathBuf::from(name)
+}
+
+/// The nearest nonexistent ancestor directory of `path`, i.e. the first directory that needs to be
+/// created for writing `path`. Returns `None` if the parent directory exists.
+pub fn missing_parent(path: &Path) -> Option<PathBuf> {
+  let mut missing = None;
+  let mut dir = path.parent();
+  while let Some(d) = dir {
+    if d.as_os_str().is_empty() || d.exists() {
+      break;
+    }
+    missing = Some(d.to_path_buf());
+    dir = d.parent();
+  }
+  missing
+}
+
+// Create the nonexistent parent directories of `target` if `mkdir`, or fails.
+fn ensure_parent(target: &Path, mkdir: bool) -> IoResult<()> {
+  if let Some(missing) = missing_parent(target) {
+    if !mkdir {
+      return Err(IoErr::new(
+        IoErrKind::NotFound,
+        format!("Directory {:?} doesn't exist", missing),
+      ));
+    }
+    if let Some(parent) = target.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+  }
+  Ok(())
+}
+
+// Create a new temporary file next to `target`, thus it can be renamed to the target.
+fn create_temp_file(target: &Path) -> IoResult<(PathBuf, File)> {
+  let dir = match target.parent() {
+    Some(parent) if !parent.as_os_str().is_empty() => parent,
+    _ => Path::new("."),
+  };
+  let name = target
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_default();
+  let pid = std::process::id();
+  let mut i = 0;
+  loop {
+    let temp = dir.join(format!(".{}.{}-{}.tmp", name, pid, i));
+    match OpenOptions::new().write(true).create_new(true).open(&temp) {
+      Ok(file) => return Ok((temp, file)),
+      Err(e) if e.kind() == IoErrKind::AlreadyExists && i < 100 => i += 1,
+      Err(e) => return Err(e),
+    }
+  }
+}
+
+// Keep the owner of the old file, it fails if the editor isn't running as the owner (or root),
+// thus it's ignored.
+#[cfg(unix)]
+fn copy_owner(metadata: &std::fs::Metadata, temp: &Path) {
+  use std::os::unix::fs::MetadataExt;
+  let _ = std::os::unix::fs::chown(temp, Some(metadata.uid()), Some(metadata.gid()));
+}
+
+#[cfg(not(unix))]
+fn copy_owner(_metadata: &std::fs::Metadata, _temp: &Path) {}
+
+// Flush the directory, thus the renaming is durable.
+#[cfg(unix)]
+fn sync_dir(target: &Path) -> IoResult<()> {
+  match target.parent() {
+    Some(parent) if !parent.as_os_str().is_empty() => File::open(parent)?.sync_all(),
+    _ => File::open(".")?.sync_all(),
+  }
+}
+
+// Windows doesn't support opening directory as file.
+#[cfg(not(unix))]
+fn sync_dir(_target: &Path) -> IoResult<()> {
+  Ok(())
+}
+
+fn write_temp_file<F>(target: &Path, options: &WriteOptions, write: F) -> IoResult<PathBuf>
+where
+  F: FnOnce(&mut dyn Write) -> IoResult<()>,
+{
+  let (temp, file) = create_temp_file(target)?;
+  let result = (|| {
+    let mut writer = BufWriter::new(file);
+    write(&mut writer)?;
+    let file = writer.into_inner().map_err(|e| e.into_error())?;
+    if options.fsync() != FsyncPolicy::Never {
+      file.sync_all()?;
+    }
+    if let Ok(metadata) = std::fs::metadata(target) {
+      copy_owner(&metadata, &temp);
+      std::fs::set_permissions(&temp, metadata.permissions())?;
+    }
+    Ok(())
+  })();
+  match result {
+    Ok(_) => Ok(temp),
+    Err(e) => {
+      let _ = std::fs::remove_file(&temp);
+      Err(e)
+    }
+  }
+}
+
+/// Write the file `path` atomically, the contents are written by `write`. Returns the written
+/// file path, i.e. the symlink target if `path` is a symlink.
+///
+/// The nonexistent parent directories are created if `mkdir` or the 'mkdir' option is on.
+///
+/// # Errors
+///
+/// If any IO error happens, the old file is not changed. If the parent directory doesn't exist and
+/// it's not created, it returns the [`NotFound`](IoErrKind::NotFound) error.
+pub fn write_file<F>(
+  path: &Path,
+  options: &WriteOptions,
+  mkdir: bool,
+  write: F,
+) -> IoResult<PathBuf>
+where
+  F: FnOnce(&mut dyn Write) -> IoResult<()>,
+{
+  let target = resolve_symlink(path)?;
+  ensure_parent(&target, mkdir || options.mkdir())?;
+
+  let backup = if (options.backup() || options.write_backup()) && target.is_file() {
+    let backup = backup_path(&target);
+    std::fs::copy(&target, &backup)?;
+    Some(backup)
+  } else {
+    None
+  };
+
+  let result = write_temp_file(&target, options, write).and_then(|temp| {
+    if let Err(e) = std::fs::rename(&temp, &target) {
+      let _ = std::fs::remove_file(&temp);
+      return Err(e);
+    }
+    if options.fsync() == FsyncPolicy::Full {
+      sync_dir(&target)?;
+    }
+    Ok(())
+  });
+
+  // The old file is not changed if it fails, thus the backup is not needed either.
+  if let Some(backup) = backup {
+    if !options.backup() {
+      let _ = std::fs::remove_file(backup);
+    }
+  }
+  result.map(|_| target)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn write_str(path: &Path, options: &WriteOptions, text: &str) -> IoResult<PathBuf> {
+    write_file(path, options, false, |writer| {
+      writer.write_all(text.as_bytes())
+    })
+  }
+
+  #[test]
+  fn fsync_policy1() {
+    for policy in [FsyncPolicy::Never, FsyncPolicy::File, FsyncPolicy::Full] {
+      assert_eq!(policy.to_string().parse::<FsyncPolicy>(), Ok(policy));
+    }
+    assert!("always".parse::<FsyncPolicy>().is_err());
+  }
+
+  #[test]
+  fn write_file1() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a.txt");
+    let options = WriteOptions::default();
+
+    // New file.
+    assert_eq!(write_str(&path, &options, "hello\n").unwrap(), path);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello\n");
+
+    // Overwrite, no backup or temporary files are left.
+    write_str(&path, &options, "world\n").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "world\n");
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+  }
+
+  #[test]
+  fn write_file_failed1() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a.txt");
+    std::fs::write(&path, "hello\n").unwrap();
+
+    let result = write_file(&path, &WriteOptions::default(), false, |writer| {
+      writer.write_all(b"partial")?;
+      Err(IoErr::new(IoErrKind::Other, "failed"))
+    });
+    assert!(result.is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello\n");
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+  }
+
+  #[test]
+  fn write_file_backup1() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a.txt");
+    std::fs::write(&path, "hello\n").unwrap();
+
+    let mut options = WriteOptions::default();
+    options.set_backup(true);
+    write_str(&path, &options, "world\n").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "world\n");
+    assert_eq!(
+      std::fs::read_to_string(backup_path(&path)).unwrap(),
+      "hello\n"
+    );
+  }
+
+  #[test]
+  fn write_file_mkdir1() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a").join("b").join("c.txt");
+    assert_eq!(missing_parent(&path), Some(dir.path().join("a")));
+
+    // Nothing is created if it fails.
+    let err = write_str(&path, &WriteOptions::default(), "hello\n").unwrap_err();
+    assert_eq!(err.kind(), IoErrKind::NotFound);
+    assert!(!dir.path().join("a").exists());
+
+    // Forced by caller.
+    let written = write_file(&path, &WriteOptions::default(), true, |writer| {
+      writer.write_all(b"hello\n")
+    })
+    .unwrap();
+    assert_eq!(std::fs::read_to_string(&written).unwrap(), "hello\n");
+    assert_eq!(missing_parent(&path), None);
+
+    // The 'mkdir' option.
+    let path = dir.path().join("d").join("e.txt");
+    let mut options = WriteOptions::default();
+    options.set_mkdir(true);
+    write_str(&path, &options, "world\n").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "world\n");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn write_file_permissions1() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a.sh");
+    std::fs::write(&path, "echo hello\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o750)).unwrap();
+
+    write_str(&path, &WriteOptions::default(), "echo world\n").unwrap();
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o750);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn write_file_symlink1() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("target.txt");
+    let link = dir.path().join("link.txt");
+    std::fs::write(&target, "hello\n").unwrap();
+    std::os::unix::fs::symlink("target.txt", &link).unwrap();
+
+    let written = write_str(&link, &WriteOptions::default(), "world\n").unwrap();
+    assert_eq!(written, target);
+    assert!(std::fs::symlink_metadata(&link)
+      .unwrap()
+      .file_type()
+      .is_symlink());
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), "world\n");
+  }
+}
@@ -1,6 +1,7 @@
 //! Command line.
 
 use clap::Parser;
+use std::path::PathBuf;
 
 // #[clap(
 //   value_name = "CMD",
@@ -41,6 +42,19 @@ pub struct CliOpt {
 
   #[arg(short = 'V', long = "version", help = "Print version")]
   version: bool,
+
+  #[arg(
+    value_name = "FILE",
+    long = "session",
+    help = "Restore the session from <FILE> (written by :mksession)"
+  )]
+  session: Option<PathBuf>,
+
+  #[arg(
+    long = "no-cache",
+    help = "Don't use the transpile cache, always transpile the TypeScript/JSX config and plugins"
+  )]
+  no_cache: bool,
 }
 
 impl CliOpt {
@@ -54,6 +68,16 @@ impl CliOpt {
     self.version
   }
 
+  /// Session file.
+  pub fn session(&self) -> &Option<PathBuf> {
+    &self.session
+  }
+
+  /// Don't use the transpile cache, see [`cache`](crate::js::cache).
+  pub fn no_cache(&self) -> bool {
+    self.no_cache
+  }
+
   // /// Commands should be execute before loading any config.
   // pub fn cmd_before(&self) -> &Option<Vec<String>> {
   //   &self.cmd_before
@@ -95,20 +119,44 @@ mod tests {
       vec!["rsvim".to_string()],
       vec!["rsvim".to_string(), "--version".to_string()],
       vec!["rsvim".to_string(), "README.md".to_string()],
+      vec![
+        "rsvim".to_string(),
+        "--session".to_string(),
+        "Session.js".to_string(),
+      ],
+      vec!["rsvim".to_string(), "--no-cache".to_string()],
     ];
 
     let expect = [
       CliOpt {
         file: vec![],
         version: false,
+        session: None,
+        no_cache: false,
       },
       CliOpt {
         file: vec![],
         version: true,
+        session: None,
+        no_cache: false,
       },
       CliOpt {
         file: vec!["README.md".to_string()],
         version: false,
+        session: None,
+        no_cache: false,
+      },
+      CliOpt {
+        file: vec![],
+        version: false,
+        session: Some(PathBuf::from("Session.js")),
+        no_cache: false,
+      },
+      CliOpt {
+        file: vec![],
+        version: false,
+        session: None,
+        no_cache: true,
       },
     ];
 
@@ -118,6 +166,8 @@ mod tests {
       let actual = CliOpt::parse_from(&input[i]);
       assert_eq!(actual.file, expect[i].file);
       assert_eq!(actual.version(), expect[i].version());
+      assert_eq!(actual.session(), expect[i].session());
+      assert_eq!(actual.no_cache(), expect[i].no_cache());
     }
   }
 }
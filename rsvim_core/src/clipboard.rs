@@ -0,0 +1,239 @@
+//! System clipboard.
+//!
+//! The system clipboard is provided by one of the providers:
+//!
+//! 1. Native: The clipboard commands shipped with the operating system or desktop environment,
+//!    i.e. `pbcopy`/`pbpaste` on macOS, `wl-copy`/`wl-paste` on Wayland, `xclip`/`xsel` on X11,
+//!    `win32yank.exe` (or `powershell.exe`) on Windows. See [`NativeClipboard`].
+//! 2. OSC 52: The terminal escape sequence that asks the terminal emulator to set the clipboard.
+//!    It works over SSH sessions where there's no native clipboard. See [`Osc52Clipboard`].
+//!
+//! The provider is selected by the 'clipboard' option, see [`ClipboardOption`].
+
+use crate::defaults;
+use crate::res::{IoErr, IoErrKind, IoResult};
+
+// Re-export
+pub use crate::clipboard::native::NativeClipboard;
+pub use crate::clipboard::osc52::Osc52Clipboard;
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+pub mod native;
+pub mod osc52;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The clipboard selection.
+pub enum ClipboardSelection {
+  /// The system clipboard, i.e. the `+` register.
+  Clipboard,
+  /// The primary selection, i.e. the `*` register.
+  ///
+  /// NOTE: For platforms that don't have primary selection (macOS/Windows), it's the same with
+  /// the system clipboard.
+  Primary,
+}
+
+impl TryFrom<char> for ClipboardSelection {
+  type Error = &'static str;
+
+  /// Parse register name to clipboard selection.
+  fn try_from(c: char) -> Result<Self, Self::Error> {
+    match c {
+      '+' => Ok(ClipboardSelection::Clipboard),
+      '*' => Ok(ClipboardSelection::Primary),
+      _ => Err("Invalid clipboard register"),
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The 'clipboard' option, it selects the clipboard provider.
+pub enum ClipboardOption {
+  /// Detect native clipboard commands first, fallback to OSC 52 if not found.
+  Auto,
+  /// Native clipboard commands only.
+  Native,
+  /// OSC 52 escape sequence only.
+  Osc52,
+  /// Disable system clipboard.
+  None,
+}
+
+impl Display for ClipboardOption {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ClipboardOption::Auto => write!(f, "auto"),
+      ClipboardOption::Native => write!(f, "native"),
+      ClipboardOption::Osc52 => write!(f, "osc52"),
+      ClipboardOption::None => write!(f, "none"),
+    }
+  }
+}
+
+impl FromStr for ClipboardOption {
+  type Err = &'static str;
+
+  /// Parse `str` to enum.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "auto" => Ok(ClipboardOption::Auto),
+      "native" => Ok(ClipboardOption::Native),
+      "osc52" => Ok(ClipboardOption::Osc52),
+      "none" => Ok(ClipboardOption::None),
+      _ => Err("Invalid clipboard option"),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The value holder for each clipboard provider.
+pub enum ClipboardProvider {
+  Native(NativeClipboard),
+  Osc52(Osc52Clipboard),
+}
+
+impl ClipboardProvider {
+  /// Provider name.
+  pub fn name(&self) -> &str {
+    match self {
+      ClipboardProvider::Native(p) => p.name(),
+      ClipboardProvider::Osc52(p) => p.name(),
+    }
+  }
+
+  /// Get clipboard contents.
+  pub fn get_contents(&self, selection: ClipboardSelection) -> IoResult<String> {
+    match self {
+      ClipboardProvider::Native(p) => p.get_contents(selection),
+      ClipboardProvider::Osc52(p) => p.get_contents(selection),
+    }
+  }
+
+  /// Set clipboard contents.
+  pub fn set_contents(&mut self, selection: ClipboardSelection, contents: &str) -> IoResult<()> {
+    match self {
+      ClipboardProvider::Native(p) => p.set_contents(selection, contents),
+      ClipboardProvider::Osc52(p) => p.set_contents(selection, contents),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The system clipboard manager, it maintains the provider selected by the 'clipboard' option.
+pub struct ClipboardManager {
+  option: ClipboardOption,
+  provider: Option<ClipboardProvider>,
+}
+
+impl ClipboardManager {
+  /// Make new clipboard manager, the provider is detected with the `option`.
+  pub fn new(option: ClipboardOption) -> Self {
+    ClipboardManager {
+      option,
+      provider: Self::detect(option),
+    }
+  }
+
+  fn detect(option: ClipboardOption) -> Option<ClipboardProvider> {
+    match option {
+      ClipboardOption::Auto => NativeClipboard::detect()
+        .map(ClipboardProvider::Native)
+        .or_else(|| Some(ClipboardProvider::Osc52(Osc52Clipboard::new()))),
+      ClipboardOption::Native => NativeClipboard::detect().map(ClipboardProvider::Native),
+      ClipboardOption::Osc52 => Some(ClipboardProvider::Osc52(Osc52Clipboard::new())),
+      ClipboardOption::None => None,
+    }
+  }
+
+  /// Get the 'clipboard' option.
+  pub fn option(&self) -> ClipboardOption {
+    self.option
+  }
+
+  /// Set the 'clipboard' option, the provider is detected again.
+  pub fn set_option(&mut self, option: ClipboardOption) {
+    self.option = option;
+    self.provider = Self::detect(option);
+  }
+
+  /// Get current provider.
+  pub fn provider(&self) -> &Option<ClipboardProvider> {
+    &self.provider
+  }
+
+  /// Get clipboard contents.
+  ///
+  /// # Errors
+  ///
+  /// If there's no provider, or the provider failed.
+  pub fn get_contents(&self, selection: ClipboardSelection) -> IoResult<String> {
+    match &self.provider {
+      Some(provider) => provider.get_contents(selection),
+      None => Err(IoErr::new(IoErrKind::Unsupported, "No clipboard provider")),
+    }
+  }
+
+  /// Set clipboard contents.
+  ///
+  /// # Errors
+  ///
+  /// If there's no provider, or the provider failed.
+  pub fn set_contents(&mut self, selection: ClipboardSelection, contents: &str) -> IoResult<()> {
+    match &mut self.provider {
+      Some(provider) => provider.set_contents(selection, contents),
+      None => Err(IoErr::new(IoErrKind::Unsupported, "No clipboard provider")),
+    }
+  }
+}
+
+impl Default for ClipboardManager {
+  fn default() -> Self {
+    ClipboardManager::new(defaults::clipboard::CLIPBOARD)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn option1() {
+    for opt in [
+      ClipboardOption::Auto,
+      ClipboardOption::Native,
+      ClipboardOption::Osc52,
+      ClipboardOption::None,
+    ] {
+      assert_eq!(ClipboardOption::from_str(&opt.to_string()), Ok(opt));
+    }
+    assert!(ClipboardOption::from_str("unnamed").is_err());
+  }
+
+  #[test]
+  fn selection1() {
+    assert_eq!(
+      ClipboardSelection::try_from('+'),
+      Ok(ClipboardSelection::Clipboard)
+    );
+    assert_eq!(
+      ClipboardSelection::try_from('*'),
+      Ok(ClipboardSelection::Primary)
+    );
+    assert!(ClipboardSelection::try_from('a').is_err());
+  }
+
+  #[test]
+  fn manager1() {
+    let mut manager = ClipboardManager::new(ClipboardOption::None);
+    assert!(manager.provider().is_none());
+    assert!(manager.get_contents(ClipboardSelection::Clipboard).is_err());
+
+    manager.set_option(ClipboardOption::Osc52);
+    assert_eq!(manager.provider().as_ref().unwrap().name(), "osc52");
+
+    manager.set_option(ClipboardOption::Auto);
+    assert!(manager.provider().is_some());
+  }
+}
@@ -0,0 +1,315 @@
+//! Native clipboard commands.
+
+use crate::clipboard::ClipboardSelection;
+use crate::res::{IoErr, IoResult};
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tracing::trace;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The external command to copy/paste clipboard contents.
+pub struct ClipboardCommand {
+  program: String,
+  args: Vec<String>,
+}
+
+impl ClipboardCommand {
+  pub fn new(program: &str, args: &[&str]) -> Self {
+    ClipboardCommand {
+      program: program.to_string(),
+      args: args.iter().map(|a| a.to_string()).collect(),
+    }
+  }
+
+  pub fn program(&self) -> &str {
+    &self.program
+  }
+
+  pub fn args(&self) -> &Vec<String> {
+    &self.args
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The native clipboard provider, implemented with external commands.
+pub struct NativeClipboard {
+  name: String,
+  copy: ClipboardCommand,
+  paste: ClipboardCommand,
+  copy_primary: ClipboardCommand,
+  paste_primary: ClipboardCommand,
+  // Whether the commands don't convert the line endings, i.e. the contents are `\r\n` separated
+  // in the clipboard, and `\n` separated in the registers.
+  crlf: bool,
+}
+
+impl NativeClipboard {
+  /// Detect native clipboard commands on current platform.
+  pub fn detect() -> Option<Self> {
+    Self::detect_with(
+      std::env::consts::OS,
+      |var| std::env::var_os(var).is_some_and(|v| !v.is_empty()),
+      executable_exists,
+    )
+  }
+
+  /// Detect native clipboard commands with the operating system name, environment variable
+  /// detector and executable detector.
+  pub fn detect_with<E, X>(os: &str, has_env: E, has_executable: X) -> Option<Self>
+  where
+    E: Fn(&str) -> bool,
+    X: Fn(&str) -> bool,
+  {
+    if os == "macos" && has_executable("pbcopy") && has_executable("pbpaste") {
+      let copy = ClipboardCommand::new("pbcopy", &[]);
+      let paste = ClipboardCommand::new("pbpaste", &[]);
+      return Some(Self::new(
+        "pbcopy",
+        copy.clone(),
+        paste.clone(),
+        copy,
+        paste,
+      ));
+    }
+
+    if os == "windows" {
+      if has_executable("win32yank.exe") {
+        let copy = ClipboardCommand::new("win32yank.exe", &["-i", "--crlf"]);
+        let paste = ClipboardCommand::new("win32yank.exe", &["-o", "--lf"]);
+        return Some(Self::new(
+          "win32yank",
+          copy.clone(),
+          paste.clone(),
+          copy,
+          paste,
+        ));
+      }
+      // The `clip.exe` and the default console encoding are not UTF-8, thus the contents are
+      // transferred with UTF-8 explicitly, and without the trailing line break of `Get-Clipboard`.
+      let copy = ClipboardCommand::new(
+        "powershell.exe",
+        &[
+          "-NoProfile",
+          "-NonInteractive",
+          "-Command",
+          "[Console]::InputEncoding = [Text.Encoding]::UTF8; Set-Clipboard -Value ([Console]::In.ReadToEnd())",
+        ],
+      );
+      let paste = ClipboardCommand::new(
+        "powershell.exe",
+        &[
+          "-NoProfile",
+          "-NonInteractive",
+          "-Command",
+          "[Console]::OutputEncoding = [Text.Encoding]::UTF8; [Console]::Out.Write((Get-Clipboard -Raw))",
+        ],
+      );
+      let mut clipboard = Self::new("win32", copy.clone(), paste.clone(), copy, paste);
+      clipboard.crlf = true;
+      return Some(clipboard);
+    }
+
+    if has_env("WAYLAND_DISPLAY") && has_executable("wl-copy") && has_executable("wl-paste") {
+      return Some(Self::new(
+        "wl-copy",
+        ClipboardCommand::new("wl-copy", &["--type", "text/plain"]),
+        ClipboardCommand::new("wl-paste", &["--no-newline"]),
+        ClipboardCommand::new("wl-copy", &["--primary", "--type", "text/plain"]),
+        ClipboardCommand::new("wl-paste", &["--no-newline", "--primary"]),
+      ));
+    }
+
+    if has_env("DISPLAY") && has_executable("xclip") {
+      return Some(Self::new(
+        "xclip",
+        ClipboardCommand::new("xclip", &["-quiet", "-i", "-selection", "clipboard"]),
+        ClipboardCommand::new("xclip", &["-o", "-selection", "clipboard"]),
+        ClipboardCommand::new("xclip", &["-quiet", "-i", "-selection", "primary"]),
+        ClipboardCommand::new("xclip", &["-o", "-selection", "primary"]),
+      ));
+    }
+
+    if has_env("DISPLAY") && has_executable("xsel") {
+      return Some(Self::new(
+        "xsel",
+        ClipboardCommand::new("xsel", &["--nodetach", "--input", "--clipboard"]),
+        ClipboardCommand::new("xsel", &["--output", "--clipboard"]),
+        ClipboardCommand::new("xsel", &["--nodetach", "--input", "--primary"]),
+        ClipboardCommand::new("xsel", &["--output", "--primary"]),
+      ));
+    }
+
+    None
+  }
+
+  fn new(
+    name: &str,
+    copy: ClipboardCommand,
+    paste: ClipboardCommand,
+    copy_primary: ClipboardCommand,
+    paste_primary: ClipboardCommand,
+  ) -> Self {
+    NativeClipboard {
+      name: name.to_string(),
+      copy,
+      paste,
+      copy_primary,
+      paste_primary,
+      crlf: false,
+    }
+  }
+
+  /// Provider name.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Get the copy command.
+  pub fn copy_command(&self, selection: ClipboardSelection) -> &ClipboardCommand {
+    match selection {
+      ClipboardSelection::Clipboard => &self.copy,
+      ClipboardSelection::Primary => &self.copy_primary,
+    }
+  }
+
+  /// Get the paste command.
+  pub fn paste_command(&self, selection: ClipboardSelection) -> &ClipboardCommand {
+    match selection {
+      ClipboardSelection::Clipboard => &self.paste,
+      ClipboardSelection::Primary => &self.paste_primary,
+    }
+  }
+
+  /// Get clipboard contents, i.e. run the paste command and read its stdout.
+  pub fn get_contents(&self, selection: ClipboardSelection) -> IoResult<String> {
+    let command = self.paste_command(selection);
+    let output = Command::new(command.program())
+      .args(command.args())
+      .stdin(Stdio::null())
+      .stderr(Stdio::null())
+      .output()?;
+    if !output.status.success() {
+      trace!("Failed to run paste command {:?}:{:?}", command, output);
+      return Err(IoErr::other(format!(
+        "Clipboard command {} failed: {}",
+        command.program(),
+        output.status
+      )));
+    }
+    let contents = String::from_utf8_lossy(&output.stdout).into_owned();
+    if self.crlf {
+      Ok(contents.replace("\r\n", "\n"))
+    } else {
+      Ok(contents)
+    }
+  }
+
+  /// Set clipboard contents, i.e. run the copy command and write to its stdin.
+  pub fn set_contents(&self, selection: ClipboardSelection, contents: &str) -> IoResult<()> {
+    let command = self.copy_command(selection);
+    let mut child = Command::new(command.program())
+      .args(command.args())
+      .stdin(Stdio::piped())
+      .stdout(Stdio::null())
+      .stderr(Stdio::null())
+      .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+      if self.crlf {
+        stdin.write_all(contents.replace('\n', "\r\n").as_bytes())?;
+      } else {
+        stdin.write_all(contents.as_bytes())?;
+      }
+    }
+    let status = child.wait()?;
+    if !status.success() {
+      trace!("Failed to run copy command {:?}:{:?}", command, status);
+      return Err(IoErr::other(format!(
+        "Clipboard command {} failed: {}",
+        command.program(),
+        status
+      )));
+    }
+    Ok(())
+  }
+}
+
+/// Whether the executable exists in `$PATH`.
+///
+/// NOTE: On Windows, the `name` without extension is also searched with the `%PATHEXT%`
+/// extensions, i.e. `win32yank` is `win32yank.exe`.
+pub fn executable_exists(name: &str) -> bool {
+  let names = if cfg!(target_os = "windows") && Path::new(name).extension().is_none() {
+    let exts = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    exts
+      .split(';')
+      .filter(|ext| !ext.is_empty())
+      .map(|ext| format!("{}{}", name, ext))
+      .collect::<Vec<_>>()
+  } else {
+    vec![name.to_string()]
+  };
+  match std::env::var_os("PATH") {
+    Some(paths) => {
+      std::env::split_paths(&paths).any(|dir| names.iter().any(|name| dir.join(name).is_file()))
+    }
+    None => false,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn detect1() {
+    let actual = NativeClipboard::detect_with("macos", |_| false, |_| true).unwrap();
+    assert_eq!(actual.name(), "pbcopy");
+    assert_eq!(
+      actual.copy_command(ClipboardSelection::Primary).program(),
+      "pbcopy"
+    );
+
+    let actual = NativeClipboard::detect_with("windows", |_| false, |_| false).unwrap();
+    assert_eq!(actual.name(), "win32");
+    assert!(actual.crlf);
+    let actual =
+      NativeClipboard::detect_with("windows", |_| false, |exe| exe == "win32yank.exe").unwrap();
+    assert_eq!(actual.name(), "win32yank");
+    assert!(!actual.crlf);
+  }
+
+  #[test]
+  fn detect2() {
+    let actual = NativeClipboard::detect_with(
+      "linux",
+      |var| var == "WAYLAND_DISPLAY" || var == "DISPLAY",
+      |_| true,
+    )
+    .unwrap();
+    assert_eq!(actual.name(), "wl-copy");
+    assert!(actual
+      .paste_command(ClipboardSelection::Primary)
+      .args()
+      .contains(&"--primary".to_string()));
+
+    let actual =
+      NativeClipboard::detect_with("linux", |var| var == "DISPLAY", |exe| exe == "xsel").unwrap();
+    assert_eq!(actual.name(), "xsel");
+  }
+
+  #[test]
+  fn detect3() {
+    // SSH session without display.
+    assert!(NativeClipboard::detect_with("linux", |_| false, |_| true).is_none());
+    assert!(NativeClipboard::detect_with("linux", |_| true, |_| false).is_none());
+  }
+
+  #[cfg(target_os = "windows")]
+  #[test]
+  fn executable_exists_windows() {
+    assert!(executable_exists("cmd"));
+    assert!(executable_exists("cmd.exe"));
+  }
+}
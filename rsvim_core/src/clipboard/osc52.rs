@@ -0,0 +1,245 @@
+//! OSC 52 clipboard.
+//!
+//! The OSC 52 escape sequence `ESC ] 52 ; {selection} ; {base64 data} BEL` asks the terminal
+//! emulator to set the system clipboard. It doesn't rely on any native clipboard commands thus
+//! works over SSH sessions.
+//!
+//! NOTE: Most terminals disallow reading clipboard with OSC 52 for security reasons, so the
+//! contents are cached when setting clipboard, and reading clipboard returns the cached contents.
+//! The terminal's response (if it has) can be parsed with [`parse_response`].
+
+use crate::clipboard::ClipboardSelection;
+use crate::res::IoResult;
+
+use std::collections::HashMap;
+use std::io::Write;
+
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode bytes with standard base64 (with padding).
+pub fn base64_encode(data: &[u8]) -> String {
+  let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+  for chunk in data.chunks(3) {
+    let b0 = chunk[0] as u32;
+    let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+    let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+    let n = (b0 << 16) | (b1 << 8) | b2;
+    result.push(BASE64_CHARS[((n >> 18) & 0x3f) as usize] as char);
+    result.push(BASE64_CHARS[((n >> 12) & 0x3f) as usize] as char);
+    if chunk.len() > 1 {
+      result.push(BASE64_CHARS[((n >> 6) & 0x3f) as usize] as char);
+    } else {
+      result.push('=');
+    }
+    if chunk.len() > 2 {
+      result.push(BASE64_CHARS[(n & 0x3f) as usize] as char);
+    } else {
+      result.push('=');
+    }
+  }
+  result
+}
+
+/// Decode standard base64 (padding is optional), returns `None` if it's invalid.
+pub fn base64_decode(data: &str) -> Option<Vec<u8>> {
+  let mut result = Vec::with_capacity(data.len() / 4 * 3);
+  let mut buffer = 0_u32;
+  let mut bits = 0_u32;
+  for c in data.bytes().filter(|c| !c.is_ascii_whitespace()) {
+    if c == b'=' {
+      break;
+    }
+    let value = BASE64_CHARS.iter().position(|b| *b == c)? as u32;
+    buffer = (buffer << 6) | value;
+    bits += 6;
+    if bits >= 8 {
+      bits -= 8;
+      result.push(((buffer >> bits) & 0xff) as u8);
+    }
+  }
+  Some(result)
+}
+
+fn selection_param(selection: ClipboardSelection) -> char {
+  match selection {
+    ClipboardSelection::Clipboard => 'c',
+    ClipboardSelection::Primary => 'p',
+  }
+}
+
+/// Wrap the escape sequence with tmux's passthrough `ESC P tmux; ... ESC \`, the `ESC` inside
+/// the sequence is doubled.
+fn tmux_passthrough(sequence: &str) -> String {
+  format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+}
+
+/// Make the OSC 52 sequence that sets the clipboard.
+pub fn make_set_sequence(selection: ClipboardSelection, contents: &str, tmux: bool) -> String {
+  let sequence = format!(
+    "\x1b]52;{};{}\x07",
+    selection_param(selection),
+    base64_encode(contents.as_bytes())
+  );
+  if tmux {
+    tmux_passthrough(&sequence)
+  } else {
+    sequence
+  }
+}
+
+/// Make the OSC 52 sequence that requests the clipboard contents.
+pub fn make_request_sequence(selection: ClipboardSelection, tmux: bool) -> String {
+  let sequence = format!("\x1b]52;{};?\x07", selection_param(selection));
+  if tmux {
+    tmux_passthrough(&sequence)
+  } else {
+    sequence
+  }
+}
+
+/// Parse the terminal's OSC 52 response `ESC ] 52 ; {selection} ; {base64 data} (BEL|ESC \)`.
+///
+/// Returns the selection and decoded contents, or `None` if it's not a valid response.
+pub fn parse_response(response: &str) -> Option<(ClipboardSelection, String)> {
+  let body = response.strip_prefix("\x1b]52;")?;
+  let body = body
+    .strip_suffix('\x07')
+    .or_else(|| body.strip_suffix("\x1b\\"))?;
+  let (param, data) = body.split_once(';')?;
+  let selection = match param.chars().next() {
+    Some('p') | Some('s') => ClipboardSelection::Primary,
+    _ => ClipboardSelection::Clipboard,
+  };
+  let contents = base64_decode(data)?;
+  Some((selection, String::from_utf8_lossy(&contents).into_owned()))
+}
+
+#[derive(Debug, Clone, Default)]
+/// The OSC 52 clipboard provider.
+pub struct Osc52Clipboard {
+  // Cached contents for each selection.
+  contents: HashMap<ClipboardSelection, String>,
+  // Whether running inside tmux.
+  tmux: bool,
+}
+
+impl Osc52Clipboard {
+  pub fn new() -> Self {
+    Osc52Clipboard {
+      contents: HashMap::new(),
+      tmux: std::env::var_os("TMUX").is_some_and(|v| !v.is_empty()),
+    }
+  }
+
+  /// Provider name.
+  pub fn name(&self) -> &str {
+    "osc52"
+  }
+
+  /// Whether wraps the sequence with tmux's passthrough.
+  pub fn tmux(&self) -> bool {
+    self.tmux
+  }
+
+  /// Get clipboard contents, i.e. the cached contents.
+  pub fn get_contents(&self, selection: ClipboardSelection) -> IoResult<String> {
+    Ok(self.contents.get(&selection).cloned().unwrap_or_default())
+  }
+
+  /// Set clipboard contents, i.e. write the OSC 52 sequence to terminal.
+  pub fn set_contents(&mut self, selection: ClipboardSelection, contents: &str) -> IoResult<()> {
+    let sequence = make_set_sequence(selection, contents, self.tmux);
+    let mut out = std::io::stdout();
+    out.write_all(sequence.as_bytes())?;
+    out.flush()?;
+    self.contents.insert(selection, contents.to_string());
+    Ok(())
+  }
+
+  /// Update the cached contents, i.e. when received the terminal's OSC 52 response.
+  pub fn update_contents(&mut self, selection: ClipboardSelection, contents: &str) {
+    self.contents.insert(selection, contents.to_string());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn base64_1() {
+    let inputs = [
+      "",
+      "f",
+      "fo",
+      "foo",
+      "foob",
+      "fooba",
+      "foobar",
+      "你好, RSVIM!",
+    ];
+    let expects = [
+      "", "Zg==", "Zm8=", "Zm9v", "Zm9vYg==", "Zm9vYmE=", "Zm9vYmFy",
+    ];
+    for (i, expect) in expects.iter().enumerate() {
+      assert_eq!(base64_encode(inputs[i].as_bytes()), *expect);
+    }
+    for input in inputs.iter() {
+      let decoded = base64_decode(&base64_encode(input.as_bytes())).unwrap();
+      assert_eq!(String::from_utf8(decoded).unwrap(), *input);
+    }
+    assert!(base64_decode("Zm9v!").is_none());
+  }
+
+  #[test]
+  fn sequence1() {
+    assert_eq!(
+      make_set_sequence(ClipboardSelection::Clipboard, "foo", false),
+      "\x1b]52;c;Zm9v\x07"
+    );
+    assert_eq!(
+      make_set_sequence(ClipboardSelection::Primary, "foo", true),
+      "\x1bPtmux;\x1b\x1b]52;p;Zm9v\x07\x1b\\"
+    );
+    assert_eq!(
+      make_request_sequence(ClipboardSelection::Clipboard, false),
+      "\x1b]52;c;?\x07"
+    );
+  }
+
+  #[test]
+  fn parse_response1() {
+    assert_eq!(
+      parse_response("\x1b]52;c;Zm9v\x07"),
+      Some((ClipboardSelection::Clipboard, "foo".to_string()))
+    );
+    assert_eq!(
+      parse_response("\x1b]52;p;Zm9vYmFy\x1b\\"),
+      Some((ClipboardSelection::Primary, "foobar".to_string()))
+    );
+    assert!(parse_response("\x1b]52;c;Zm9v").is_none());
+    assert!(parse_response("\x1b]11;rgb:0000/0000/0000\x07").is_none());
+  }
+
+  #[test]
+  fn cache1() {
+    let mut clipboard = Osc52Clipboard::default();
+    assert_eq!(
+      clipboard
+        .get_contents(ClipboardSelection::Clipboard)
+        .unwrap(),
+      ""
+    );
+    clipboard.update_contents(ClipboardSelection::Clipboard, "hello");
+    assert_eq!(
+      clipboard
+        .get_contents(ClipboardSelection::Clipboard)
+        .unwrap(),
+      "hello"
+    );
+    assert_eq!(
+      clipboard.get_contents(ClipboardSelection::Primary).unwrap(),
+      ""
+    );
+  }
+}
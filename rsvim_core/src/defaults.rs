@@ -1,5 +1,14 @@
 //! Vim's default options.
 
 pub mod buf;
+pub mod clipboard;
+pub mod cursor_word;
+pub mod filetype;
 pub mod grapheme;
+pub mod highlight;
+pub mod hint;
+pub mod keymap;
+pub mod langmap;
+pub mod paste;
+pub mod task;
 pub mod win;
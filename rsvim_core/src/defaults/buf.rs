@@ -1,6 +1,10 @@
 //! Vim buffer's default options.
 
 use crate::buf::opt::file_encoding::FileEncoding;
+use crate::buf::opt::file_format::FileFormat;
+use crate::buf::write::FsyncPolicy;
+
+use std::time::Duration;
 
 /// Buffer 'tab-stop' option.
 /// See: <https://vimhelp.org/options.txt.html#%27tabstop%27>.
@@ -9,3 +13,69 @@ pub const TAB_STOP: u16 = 8;
 /// Buffer 'file-encoding' option.
 /// See: <https://vimhelp.org/options.txt.html#%27fileencoding%27>.
 pub const FILE_ENCODING: FileEncoding = FileEncoding::Utf8;
+
+/// Buffer 'file-format' option, default to `dos` on Windows, `unix` on other platforms. It's used
+/// for the new files and the files without line endings.
+/// See: <https://vimhelp.org/options.txt.html#%27fileformat%27>.
+pub const FILE_FORMAT: FileFormat = if cfg!(target_os = "windows") {
+  FileFormat::Dos
+} else {
+  FileFormat::Unix
+};
+
+/// Buffer 'comment-string' option.
+/// See: <https://vimhelp.org/options.txt.html#%27commentstring%27>.
+pub const COMMENT_STRING: &str = "/* %s */";
+
+/// Buffer 'shift-width' option.
+/// See: <https://vimhelp.org/options.txt.html#%27shiftwidth%27>.
+pub const SHIFT_WIDTH: u16 = 8;
+
+/// Buffer 'expand-tab' option.
+/// See: <https://vimhelp.org/options.txt.html#%27expandtab%27>.
+pub const EXPAND_TAB: bool = false;
+
+/// Buffer 'suffixes-add' option.
+/// See: <https://vimhelp.org/options.txt.html#%27suffixesadd%27>.
+pub const SUFFIXES_ADD: &str = "";
+
+/// Buffer 'undo-levels' option, the maximum count of the undoable changes.
+/// See: <https://vimhelp.org/options.txt.html#%27undolevels%27>.
+pub const UNDO_LEVELS: usize = 1000;
+
+/// Buffer 'undo-memory' option, the maximum estimated memory (in bytes) of the undo states.
+pub const UNDO_MEMORY: usize = 64 * 1024 * 1024;
+
+/// Buffer 'undo-file' option, save the undo history of the file buffers, see
+/// [`undofile`](crate::buf::undofile).
+/// See: <https://vimhelp.org/options.txt.html#%27undofile%27>.
+pub const UNDO_FILE: bool = false;
+
+/// Buffer 'backup' option.
+/// See: <https://vimhelp.org/options.txt.html#%27backup%27>.
+pub const BACKUP: bool = false;
+
+/// Buffer 'write-backup' option.
+/// See: <https://vimhelp.org/options.txt.html#%27writebackup%27>.
+pub const WRITE_BACKUP: bool = true;
+
+/// Buffer 'fsync' option.
+/// See: <https://vimhelp.org/options.txt.html#%27fsync%27>.
+pub const FSYNC: FsyncPolicy = FsyncPolicy::File;
+
+/// Buffer 'mkdir' option, create the nonexistent parent directories when writing a file.
+pub const MKDIR: bool = false;
+
+/// Buffer 'lastPosExclude' option, the files not to restore the last position, i.e. the commit
+/// messages and the temporary files.
+pub const LAST_POS_EXCLUDE: &str = "*/.git/*,*/.hg/*,*/.svn/*,/tmp/*,/var/tmp/*";
+
+/// The files larger than this size (in bytes) are loaded in background, chunk by chunk.
+pub const ASYNC_LOAD_THRESHOLD: u64 = 32 * 1024 * 1024;
+
+/// The chunk size (in bytes) for loading the files in background.
+pub const LOAD_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// The idle delay before flushing the undo states to the undo files, see
+/// [`undofile`](crate::buf::undofile).
+pub const UNDO_FLUSH_DELAY: Duration = Duration::from_millis(1000);
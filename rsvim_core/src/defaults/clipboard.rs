@@ -0,0 +1,7 @@
+//! System clipboard's default options.
+
+use crate::clipboard::ClipboardOption;
+
+/// Global 'clipboard' option, it selects the system clipboard provider, default to `auto`.
+/// See: <https://vimhelp.org/options.txt.html#%27clipboard%27>.
+pub const CLIPBOARD: ClipboardOption = ClipboardOption::Auto;
@@ -0,0 +1,10 @@
+//! Cursor word highlighting's default options.
+
+use std::time::Duration;
+
+/// Global 'cursorword' option, the comma-separated mode short names (i.e. `n,v`) that enable
+/// highlighting the word under cursor, default to empty, i.e. disabled.
+pub const CURSOR_WORD: &str = "";
+
+/// The debounce delay before highlighting the word under cursor.
+pub const CURSOR_WORD_DELAY: Duration = Duration::from_millis(100);
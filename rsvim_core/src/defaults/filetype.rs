@@ -0,0 +1,112 @@
+//! File types' default options.
+//!
+//! The built-in filetype detection rules and the option bundles applied on the `FileType` event,
+//! see [`filetype`](crate::buf::filetype).
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+/// The built-in option bundle for a filetype, the `None` options are not changed.
+pub struct FileTypeBundle {
+  /// The 'commentstring' option.
+  pub comment_string: Option<&'static str>,
+  /// The 'tabstop' option.
+  pub tab_stop: Option<u16>,
+  /// The 'shiftwidth' option.
+  pub shift_width: Option<u16>,
+  /// The 'expandtab' option.
+  pub expand_tab: Option<bool>,
+  /// The 'suffixesadd' option.
+  pub suffixes_add: Option<&'static str>,
+}
+
+const fn bundle(
+  comment_string: &'static str,
+  shift_width: u16,
+  expand_tab: bool,
+  suffixes_add: &'static str,
+) -> FileTypeBundle {
+  FileTypeBundle {
+    comment_string: Some(comment_string),
+    tab_stop: None,
+    shift_width: Some(shift_width),
+    expand_tab: Some(expand_tab),
+    suffixes_add: Some(suffixes_add),
+  }
+}
+
+/// The built-in filetype detection rules, i.e. the glob pattern matched against the file name and
+/// the filetype. The rules are matched in order.
+pub const DETECT_RULES: &[(&str, &str)] = &[
+  ("*.rs", "rust"),
+  ("*.c", "c"),
+  ("*.h", "c"),
+  ("*.cc", "cpp"),
+  ("*.cpp", "cpp"),
+  ("*.hpp", "cpp"),
+  ("*.go", "go"),
+  ("*.py", "python"),
+  ("*.js", "javascript"),
+  ("*.mjs", "javascript"),
+  ("*.cjs", "javascript"),
+  ("*.jsx", "javascriptreact"),
+  ("*.ts", "typescript"),
+  ("*.mts", "typescript"),
+  ("*.cts", "typescript"),
+  ("*.tsx", "typescriptreact"),
+  ("*.json", "json"),
+  ("*.lua", "lua"),
+  ("*.sh", "sh"),
+  ("*.bash", "sh"),
+  ("*.toml", "toml"),
+  ("*.yaml", "yaml"),
+  ("*.yml", "yaml"),
+  ("*.md", "markdown"),
+  ("*.html", "html"),
+  ("*.css", "css"),
+  ("*.vim", "vim"),
+  ("Makefile", "make"),
+  ("makefile", "make"),
+  ("*.mk", "make"),
+  ("Dockerfile", "dockerfile"),
+];
+
+/// The built-in option bundles for the common filetypes.
+pub const BUNDLES: &[(&str, FileTypeBundle)] = &[
+  ("rust", bundle("// %s", 4, true, ".rs")),
+  ("c", bundle("/* %s */", 4, true, ".c,.h")),
+  ("cpp", bundle("// %s", 4, true, ".cpp,.hpp,.h")),
+  (
+    "go",
+    FileTypeBundle {
+      comment_string: Some("// %s"),
+      tab_stop: Some(4),
+      shift_width: Some(4),
+      expand_tab: Some(false),
+      suffixes_add: Some(".go"),
+    },
+  ),
+  ("python", bundle("# %s", 4, true, ".py")),
+  ("javascript", bundle("// %s", 2, true, ".js,.mjs,.cjs")),
+  ("javascriptreact", bundle("{/* %s */}", 2, true, ".jsx,.js")),
+  ("typescript", bundle("// %s", 2, true, ".ts,.d.ts,.js")),
+  ("typescriptreact", bundle("{/* %s */}", 2, true, ".tsx,.ts")),
+  ("json", bundle("", 2, true, "")),
+  ("lua", bundle("-- %s", 2, true, ".lua")),
+  ("sh", bundle("# %s", 2, true, ".sh")),
+  ("toml", bundle("# %s", 2, true, "")),
+  ("yaml", bundle("# %s", 2, true, "")),
+  ("markdown", bundle("<!-- %s -->", 2, true, ".md")),
+  ("html", bundle("<!-- %s -->", 2, true, ".html")),
+  ("css", bundle("/* %s */", 2, true, ".css")),
+  ("vim", bundle("\" %s", 2, true, ".vim")),
+  (
+    "make",
+    FileTypeBundle {
+      comment_string: Some("# %s"),
+      tab_stop: None,
+      shift_width: Some(8),
+      expand_tab: Some(false),
+      suffixes_add: None,
+    },
+  ),
+  ("dockerfile", bundle("# %s", 2, true, "")),
+];
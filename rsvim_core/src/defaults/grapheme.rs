@@ -2,6 +2,8 @@
 
 use ascii::AsciiChar;
 use std::fmt;
+use std::iter::Peekable;
+use unicode_segmentation::GraphemeCursor;
 
 /// The formatter for ASCII control code in [`AsciiChar`], helps implement the `Debug`/`Display` trait.
 pub struct AsciiControlCodeFormatter {
@@ -55,22 +57,288 @@ impl fmt::Display for AsciiControlCodeFormatter {
       AsciiChar::GS => write!(f, "^]"),
       AsciiChar::RS => write!(f, "^^"),
       AsciiChar::US => write!(f, "^_"),
+      AsciiChar::DEL => write!(f, "^?"),
       _ => unreachable!(),
     }
   }
 }
 
+/// Whether the byte is a printable ASCII char, i.e. `' '..='~'`, its display width is always 1.
+#[inline]
+pub fn is_printable_ascii(b: u8) -> bool {
+  (0x20..0x7f).contains(&b)
+}
+
+const LO_BYTES: u64 = u64::from_ne_bytes([0x01; 8]);
+const HI_BYTES: u64 = u64::from_ne_bytes([0x80; 8]);
+
+/// Get the length of the leading printable ASCII run in the bytes, i.e. the count of the
+/// single-width chars before the first non-printable-ASCII char.
+///
+/// It scans 8 bytes at a time (SWAR, "SIMD within a register"), a word is a whole run if none of
+/// its bytes is a control char (`< 0x20`), DEL (`0x7f`) or non-ASCII (`>= 0x80`).
+pub fn printable_ascii_len(bytes: &[u8]) -> usize {
+  let mut i = 0_usize;
+  while i + 8 <= bytes.len() {
+    let w = u64::from_ne_bytes(bytes[i..i + 8].try_into().unwrap());
+    // Some byte is less than 0x20.
+    let control = w.wrapping_sub(LO_BYTES * 0x20) & !w;
+    // Some byte equals 0x7f.
+    let del = (w ^ (LO_BYTES * 0x7f)).wrapping_sub(LO_BYTES) & !(w ^ (LO_BYTES * 0x7f));
+    if (control | del | w) & HI_BYTES != 0 {
+      break;
+    }
+    i += 8;
+  }
+  while i < bytes.len() && is_printable_ascii(bytes[i]) {
+    i += 1;
+  }
+  i
+}
+
+/// The max chars to look back when searching the start of a grapheme cluster, a grapheme cluster
+/// longer than it is split.
+pub const GRAPHEME_MAX_CHARS: usize = 32;
+
+#[derive(Debug, Copy, Clone, Default)]
+/// Grapheme cluster boundary scanner, it detects whether a char starts a new extended grapheme
+/// cluster (see [Unicode Standard Annex #29](https://www.unicode.org/reports/tr29/)) by scanning
+/// the chars one by one.
+///
+/// It keeps the chars of current cluster, and the boundary is detected by
+/// [`GraphemeCursor`] with them, i.e. all the rules (combining marks, Indic and Thai spacing
+/// marks, emoji ZWJ sequences, regional indicator pairs, etc) are the same with
+/// [`UnicodeSegmentation`](unicode_segmentation::UnicodeSegmentation). Unlike it, the scanner
+/// doesn't need a contiguous `str`, thus works with the chunks of the rope. The ASCII chars skip
+/// the cursor, since there's always a boundary between two ASCII chars except `\r\n`.
+pub struct GraphemeScanner {
+  // The chars of current cluster.
+  cluster: [char; GRAPHEME_MAX_CHARS],
+  // The chars count of current cluster.
+  len: usize,
+}
+
+impl GraphemeScanner {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Make a scanner starts after the char `prev`, i.e. scanning from the middle of a line, the
+  /// `prev` is treated as the start of a cluster.
+  pub fn with_prev(prev: char) -> Self {
+    let mut scanner = Self::new();
+    scanner.advance(prev);
+    scanner
+  }
+
+  /// Whether the char `c` starts a new grapheme cluster, without consuming it.
+  pub fn is_boundary(&self, c: char) -> bool {
+    if self.len == 0 {
+      return true;
+    }
+    let prev = self.cluster[self.len - 1];
+    if prev == '\r' && c == '\n' {
+      return false;
+    }
+    if prev.is_control() || c.is_control() || (prev.is_ascii() && c.is_ascii()) {
+      return true;
+    }
+    if self.len == GRAPHEME_MAX_CHARS {
+      return true;
+    }
+
+    let mut buf = [0_u8; (GRAPHEME_MAX_CHARS + 1) * 4];
+    let mut offset = 0_usize;
+    for prev in self.cluster[..self.len].iter() {
+      offset += prev.encode_utf8(&mut buf[offset..]).len();
+    }
+    let len = offset + c.encode_utf8(&mut buf[offset..]).len();
+    let text = std::str::from_utf8(&buf[..len]).unwrap();
+    // The text starts from the cluster start, thus it never needs more context.
+    GraphemeCursor::new(offset, len, true)
+      .is_boundary(text, 0)
+      .unwrap_or(true)
+  }
+
+  /// Consume the char `c`, returns whether it starts a new grapheme cluster.
+  pub fn advance(&mut self, c: char) -> bool {
+    let boundary = self.is_boundary(c);
+    if boundary {
+      self.len = 0;
+    }
+    self.cluster[self.len] = c;
+    self.len += 1;
+    boundary
+  }
+
+  /// Consume a run of printable ASCII chars, returns the count of grapheme clusters started in it.
+  /// They're single char clusters, except the first char can extend the previous cluster, i.e.
+  /// after a prepended concatenation mark (e.g. `U+0600`).
+  pub fn skip_ascii(&mut self, run: &str) -> usize {
+    let mut chars = run.chars();
+    let first = match chars.next() {
+      Some(first) => self.advance(first),
+      None => return 0,
+    };
+    if let Some(last) = chars.next_back() {
+      self.len = 0;
+      self.advance(last);
+    }
+    run.len() - usize::from(!first)
+  }
+}
+
+/// Grapheme clusters iterator over the chars, it yields the first char of each cluster and the
+/// chars count of the cluster.
+pub struct Graphemes<I: Iterator<Item = char>> {
+  chars: Peekable<I>,
+  scanner: GraphemeScanner,
+}
+
+impl<I: Iterator<Item = char>> Graphemes<I> {
+  pub fn new(chars: I) -> Self {
+    Graphemes {
+      chars: chars.peekable(),
+      scanner: GraphemeScanner::new(),
+    }
+  }
+}
+
+impl<I: Iterator<Item = char>> Iterator for Graphemes<I> {
+  type Item = (char, usize);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let c = self.chars.next()?;
+    self.scanner.advance(c);
+    let mut count = 1_usize;
+    while let Some(next) = self.chars.peek() {
+      if self.scanner.is_boundary(*next) {
+        break;
+      }
+      self.scanner.advance(*next);
+      self.chars.next();
+      count += 1;
+    }
+    Some((c, count))
+  }
+}
+
+/// Same with [`Graphemes`], but also yields the char index of each cluster, the index starts from
+/// `start_char_idx`.
+pub fn grapheme_indices<I: Iterator<Item = char>>(
+  chars: I,
+  start_char_idx: usize,
+) -> impl Iterator<Item = (usize, char, usize)> {
+  Graphemes::new(chars).scan(start_char_idx, |char_idx, (c, count)| {
+    let i = *char_idx;
+    *char_idx += count;
+    Some((i, c, count))
+  })
+}
+
 #[cfg(test)]
 mod tests {
-  use crate::defaults::grapheme::AsciiControlCodeFormatter;
+  use crate::defaults::grapheme::{
+    grapheme_indices, printable_ascii_len, AsciiControlCodeFormatter, GraphemeScanner,
+  };
   use ascii::AsciiChar;
+  use unicode_segmentation::UnicodeSegmentation;
+
+  #[test]
+  fn printable_ascii_len1() {
+    assert_eq!(printable_ascii_len(b""), 0);
+    assert_eq!(printable_ascii_len(b"hello"), 5);
+    assert_eq!(printable_ascii_len(b"hello world, rsvim!\n"), 19);
+    assert_eq!(printable_ascii_len(b"0123456789abcdef\t"), 16);
+    assert_eq!(printable_ascii_len(b"0123456\x7f89abcdef"), 7);
+    assert_eq!(printable_ascii_len(b"\x1f0123456789"), 0);
+    assert_eq!(printable_ascii_len("01234567你好".as_bytes()), 8);
+    assert_eq!(printable_ascii_len("0123你好456789".as_bytes()), 4);
+    let all: Vec<u8> = (0_u8..=255).collect();
+    for i in 0..all.len() {
+      let expect = all[i..]
+        .iter()
+        .take_while(|b| (0x20..0x7f).contains(*b))
+        .count();
+      assert_eq!(printable_ascii_len(&all[i..]), expect);
+    }
+  }
 
   #[test]
   fn display() {
-    for i in 0_u32..32_u32 {
+    for i in (0_u32..32_u32).chain(std::iter::once(127_u32)) {
       let ac = AsciiChar::from_ascii(i).unwrap();
       let fmt = AsciiControlCodeFormatter::from(ac);
       println!("{}:{}", i, fmt);
     }
   }
+
+  #[test]
+  fn graphemes1() {
+    let samples = [
+      "hello",
+      "cafe\u{0301}!",
+      "a\u{0308}\u{0301}b",
+      "👩\u{200D}👩\u{200D}👧 family",
+      "👍🏽👍",
+      "🇺🇸🇨🇳🇯",
+      "你好\u{200B}世界",
+      "\r\n\t\u{0301}",
+      "❤\u{FE0F}x",
+      // Indic spacing marks and conjuncts.
+      "कि हिन्दी क्षि",
+      // Thai.
+      "กำ น้ำ",
+      // The ZWJ doesn't join the non-emoji chars.
+      "a\u{200D}你",
+      "\u{0600}1a",
+    ];
+    for sample in samples {
+      let expect: Vec<(usize, char, usize)> = {
+        let mut char_idx = 0_usize;
+        sample
+          .graphemes(true)
+          .map(|g| {
+            let item = (char_idx, g.chars().next().unwrap(), g.chars().count());
+            char_idx += item.2;
+            item
+          })
+          .collect()
+      };
+      let actual: Vec<(usize, char, usize)> = grapheme_indices(sample.chars(), 0).collect();
+      assert_eq!(actual, expect, "sample:{:?}", sample);
+    }
+  }
+
+  #[test]
+  fn scanner1() {
+    let mut scanner = GraphemeScanner::new();
+    assert_eq!(scanner.skip_ascii("cafe"), 4);
+    assert!(!scanner.is_boundary('\u{0301}'));
+    assert!(!scanner.advance('\u{0301}'));
+    assert!(scanner.is_boundary('x'));
+
+    // Indic and Thai spacing marks.
+    let scanner = GraphemeScanner::with_prev('\u{0915}');
+    assert!(!scanner.is_boundary('\u{093F}'));
+    let scanner = GraphemeScanner::with_prev('\u{0E01}');
+    assert!(!scanner.is_boundary('\u{0E33}'));
+
+    // Emoji ZWJ sequence.
+    let mut scanner = GraphemeScanner::with_prev('👩');
+    assert!(!scanner.advance('\u{200D}'));
+    assert!(!scanner.is_boundary('👧'));
+    let scanner = GraphemeScanner::with_prev('\u{200D}');
+    assert!(scanner.is_boundary('你'));
+
+    // Regional indicator pairs.
+    let mut scanner = GraphemeScanner::with_prev('🇺');
+    assert!(!scanner.advance('🇸'));
+    assert!(scanner.is_boundary('🇨'));
+
+    // The prepended concatenation mark.
+    let mut scanner = GraphemeScanner::with_prev('\u{0600}');
+    assert_eq!(scanner.skip_ascii("12"), 1);
+    assert!(scanner.is_boundary('3'));
+  }
 }
@@ -0,0 +1,19 @@
+//! Highlight's default options.
+
+/// The priority of the directory buffer's highlights, see [`explorer`](crate::buf::explorer).
+pub const EXPLORER_PRIORITY: u16 = 50;
+
+/// The default priority of the highlight namespaces created by plugins.
+pub const DEFAULT_PRIORITY: u16 = 100;
+
+/// The priority of tree-sitter captures.
+pub const TREESITTER_PRIORITY: u16 = 100;
+
+/// The priority of diagnostics.
+pub const DIAGNOSTIC_PRIORITY: u16 = 150;
+
+/// The priority of search matches, i.e. the 'hlsearch' and 'incsearch' options.
+pub const SEARCH_PRIORITY: u16 = 200;
+
+/// The priority of visual selection.
+pub const SELECTION_PRIORITY: u16 = 250;
@@ -0,0 +1,5 @@
+//! Hint jump's default options.
+
+/// The chars to label the jump targets, the home row keys come first so the nearest targets get
+/// the easiest labels.
+pub const HINT_KEYS: &str = "asdfghjklqwertyuiopzxcvbnm";
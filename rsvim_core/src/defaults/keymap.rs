@@ -0,0 +1,23 @@
+//! Key mappings' default options.
+
+use std::time::Duration;
+
+/// Global 'mapleader' option, i.e. the `<Leader>` key.
+/// See: <https://vimhelp.org/map.txt.html#mapleader>.
+pub const LEADER: &str = "\\";
+
+/// Global 'timeoutlen' option, the time waiting for a mapped key sequence to complete.
+/// See: <https://vimhelp.org/options.txt.html#%27timeoutlen%27>.
+pub const TIMEOUT_LEN: Duration = Duration::from_millis(1000);
+
+/// Global 'ttimeout' option, whether to wait for the `Alt` modified key after a lone `Esc`.
+/// See: <https://vimhelp.org/options.txt.html#%27ttimeout%27>.
+pub const TTIMEOUT: bool = true;
+
+/// Global 'ttimeoutlen' option, the time waiting for the `Alt` modified key after a lone `Esc`.
+/// See: <https://vimhelp.org/options.txt.html#%27ttimeoutlen%27>.
+pub const TTIMEOUT_LEN: Duration = Duration::from_millis(50);
+
+/// The max depth of the recursive mappings, i.e. the 'maxmapdepth' option.
+/// See: <https://vimhelp.org/options.txt.html#%27maxmapdepth%27>.
+pub const MAX_MAP_DEPTH: usize = 1000;
@@ -0,0 +1,5 @@
+//! Keyboard layout translation's default options.
+
+/// Global 'langmap' option, default to empty, i.e. no translation.
+/// See: <https://vimhelp.org/options.txt.html#%27langmap%27>.
+pub const LANGMAP: &str = "";
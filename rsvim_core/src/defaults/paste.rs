@@ -0,0 +1,15 @@
+//! Paste mode's default options.
+
+use std::time::Duration;
+
+/// Global 'paste' option, default to `false`.
+/// See: <https://vimhelp.org/options.txt.html#%27paste%27>.
+pub const PASTE: bool = false;
+
+/// The max interval between two key presses that are considered as a paste burst, i.e. the keys
+/// arrive much faster than human typing. It's used to detect paste on terminals without bracketed
+/// paste.
+pub const PASTE_BURST_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The min count of continuous key presses that are considered as a paste burst.
+pub const PASTE_BURST_KEYS: usize = 3;
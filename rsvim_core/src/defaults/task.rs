@@ -0,0 +1,11 @@
+//! Background tasks' default options.
+
+use std::time::Duration;
+
+/// The grace period for the background tasks to stop when the editor quits, the tasks still
+/// running after it are reported and abandoned.
+pub const QUIT_GRACE_PERIOD: Duration = Duration::from_millis(1000);
+
+/// The interval to check whether the user config file is changed, it's reloaded once it's changed,
+/// see [`config`](crate::state::ex::config).
+pub const CONFIG_WATCH_INTERVAL: Duration = Duration::from_millis(1000);
@@ -1,9 +1,110 @@
 //! Vim window's default options.
 
+use crate::ui::tree::opt::JumpCenter;
+use crate::ui::widget::window::sign::SignColumn;
+
+use std::time::Duration;
+
 /// Window 'wrap' option, also known as 'line-wrap', default to `true`.
 /// See: <https://vimhelp.org/options.txt.html#%27wrap%27>.
 pub const WRAP: bool = true;
 
+/// Window 'scrolloff' option, default to `0`, i.e. the minimal rows to keep above and below the
+/// cursor.
+/// See: <https://vimhelp.org/options.txt.html#%27scrolloff%27>.
+pub const SCROLL_OFF: usize = 0;
+
+/// Window 'sidescroll' option, default to `1`, i.e. the minimal columns to scroll horizontally
+/// when the cursor goes off the screen, `0` puts the cursor at the middle of the window.
+/// See: <https://vimhelp.org/options.txt.html#%27sidescroll%27>.
+pub const SIDE_SCROLL: usize = 1;
+
+/// Window 'sidescrolloff' option, default to `0`, i.e. the minimal columns to keep on the left and
+/// right of the cursor.
+/// See: <https://vimhelp.org/options.txt.html#%27sidescrolloff%27>.
+pub const SIDE_SCROLL_OFF: usize = 0;
+
 /// Window 'line-break' option, also known as 'word-wrap', default to `false`.
 /// See: <https://vimhelp.org/options.txt.html#%27linebreak%27>.
 pub const LINE_BREAK: bool = false;
+
+/// Window 'minimap' option, default to `false`.
+pub const MINIMAP: bool = false;
+
+/// Window minimap width.
+pub const MINIMAP_WIDTH: u16 = 10;
+
+/// Global 'hlsearch' option, default to `false`.
+/// See: <https://vimhelp.org/options.txt.html#%27hlsearch%27>.
+pub const HLSEARCH: bool = false;
+
+/// Global 'incsearch' option, default to `true`.
+/// See: <https://vimhelp.org/options.txt.html#%27incsearch%27>.
+pub const INCSEARCH: bool = true;
+
+/// Global 'autopairs' option, default to `false`.
+pub const AUTOPAIRS: bool = false;
+
+/// Global 'jumpcenter' option, default to `auto`, i.e. only center the target line of the
+/// absolute line jumps when it's not visible.
+pub const JUMP_CENTER: JumpCenter = JumpCenter::Auto;
+
+/// Global 'quickfixpreview' option, default to `false`.
+pub const QUICKFIX_PREVIEW: bool = false;
+
+/// The quickfix window height, i.e. `:copen` without the height.
+pub const QUICKFIX_HEIGHT: u16 = 10;
+
+/// Global 'title' option, default to `false`, i.e. the terminal title is not changed.
+/// See: <https://vimhelp.org/options.txt.html#%27title%27>.
+pub const TITLE: bool = false;
+
+/// Global 'titlestring' option, empty means the [`TITLE_FORMAT`] is used.
+/// See: <https://vimhelp.org/options.txt.html#%27titlestring%27>.
+pub const TITLESTRING: &str = "";
+
+/// The terminal title format when the 'titlestring' is empty, it's in the 'statusline' format,
+/// i.e. the buffer name, the modified flag and the editor name.
+pub const TITLE_FORMAT: &str = "%f%m - Rsvim";
+
+/// Window 'statusline' option, empty means the statusline is not shown.
+pub const STATUSLINE: &str = "";
+
+/// Window 'winfixwidth' option, default to `false`.
+/// See: <https://vimhelp.org/options.txt.html#%27winfixwidth%27>.
+pub const WINFIXWIDTH: bool = false;
+
+/// Window 'winfixheight' option, default to `false`.
+/// See: <https://vimhelp.org/options.txt.html#%27winfixheight%27>.
+pub const WINFIXHEIGHT: bool = false;
+
+/// Window 'number' option, default to `false`.
+/// See: <https://vimhelp.org/options.txt.html#%27number%27>.
+pub const NUMBER: bool = false;
+
+/// Window 'relativenumber' option, default to `false`.
+/// See: <https://vimhelp.org/options.txt.html#%27relativenumber%27>.
+pub const RELATIVE_NUMBER: bool = false;
+
+/// Window 'numberwidth' option, default to `4`.
+/// See: <https://vimhelp.org/options.txt.html#%27numberwidth%27>.
+pub const NUMBER_WIDTH: u16 = 4;
+
+/// Window 'signcolumn' option, default to `auto`, i.e. only show the sign column when there're
+/// signs placed in the buffer.
+/// See: <https://vimhelp.org/options.txt.html#%27signcolumn%27>.
+pub const SIGN_COLUMN: SignColumn = SignColumn::Auto;
+
+/// Window 'signwidth' option, default to `2`, i.e. the width of the sign column.
+pub const SIGN_WIDTH: u16 = 2;
+
+/// The idle delay before prefetching the previous/next screenful lines of the viewports.
+pub const VIEWPORT_PREFETCH_DELAY: Duration = Duration::from_millis(50);
+
+/// The max rows prefetched on each side (previous/next) of a viewport, it caps the memory of the
+/// prefetched lines for the very tall windows.
+pub const VIEWPORT_PREFETCH_MAX_ROWS: u16 = 200;
+
+/// The max entries of the window's jumplist.
+/// See: <https://vimhelp.org/motion.txt.html#jumplist>.
+pub const JUMPLIST_SIZE: usize = 100;
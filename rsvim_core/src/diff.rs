@@ -0,0 +1,163 @@
+//! Line-based text diff.
+//!
+//! The diff is calculated with the longest common subsequence (LCS) of lines, and rendered either
+//! in the unified format (one column, prefixed with `-`/`+`) or side-by-side (two columns, the
+//! old text on the left and the new text on the right).
+
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// A diff line.
+pub enum DiffLine<'a> {
+  /// The line exists in both texts.
+  Equal(&'a str),
+  /// The line only exists in the old text.
+  Delete(&'a str),
+  /// The line only exists in the new text.
+  Insert(&'a str),
+}
+
+/// Diff the lines between `old` and `new` text.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+  let old = old.lines().collect::<Vec<_>>();
+  let new = new.lines().collect::<Vec<_>>();
+
+  // Longest common subsequence.
+  let mut lcs = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+  for i in (0..old.len()).rev() {
+    for j in (0..new.len()).rev() {
+      lcs[i][j] = if old[i] == new[j] {
+        lcs[i + 1][j + 1] + 1
+      } else {
+        lcs[i + 1][j].max(lcs[i][j + 1])
+      };
+    }
+  }
+
+  let mut result = vec![];
+  let (mut i, mut j) = (0_usize, 0_usize);
+  while i < old.len() && j < new.len() {
+    if old[i] == new[j] {
+      result.push(DiffLine::Equal(old[i]));
+      i += 1;
+      j += 1;
+    } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+      result.push(DiffLine::Delete(old[i]));
+      i += 1;
+    } else {
+      result.push(DiffLine::Insert(new[j]));
+      j += 1;
+    }
+  }
+  result.extend(old[i..].iter().map(|l| DiffLine::Delete(l)));
+  result.extend(new[j..].iter().map(|l| DiffLine::Insert(l)));
+  result
+}
+
+/// Render the diff in the unified format, the removed lines are prefixed with `-`, the added
+/// lines are prefixed with `+`, the unchanged lines are prefixed with space.
+pub fn unified_lines(old: &str, new: &str) -> Vec<String> {
+  diff_lines(old, new)
+    .into_iter()
+    .map(|line| match line {
+      DiffLine::Equal(l) => format!(" {}", l),
+      DiffLine::Delete(l) => format!("-{}", l),
+      DiffLine::Insert(l) => format!("+{}", l),
+    })
+    .collect()
+}
+
+/// Truncate or pad the `s` to the display `width`.
+fn fit_width(s: &str, width: usize) -> String {
+  let mut result = String::new();
+  let mut occupied = 0_usize;
+  for c in s.chars() {
+    let c_width = UnicodeWidthChar::width(c).unwrap_or(0);
+    if occupied + c_width > width {
+      break;
+    }
+    result.push(c);
+    occupied += c_width;
+  }
+  result.push_str(&" ".repeat(width - occupied));
+  result
+}
+
+/// Render the diff side-by-side within the display `width`, the old text is on the left and the
+/// new text is on the right, separated with `│`.
+///
+/// The consecutive removed and added lines are paired on the same rows, i.e. a changed line is
+/// shown as `-old │ +new`.
+pub fn side_by_side_lines(old: &str, new: &str, width: usize) -> Vec<String> {
+  // Each side has a marker char, and the separator takes 3 chars.
+  let column_width = width.saturating_sub(3) / 2;
+  let text_width = column_width.saturating_sub(1);
+  let row = |left: Option<(char, &str)>, right: Option<(char, &str)>| {
+    let side = |s: Option<(char, &str)>| match s {
+      Some((marker, text)) => format!("{}{}", marker, fit_width(text, text_width)),
+      None => " ".repeat(column_width),
+    };
+    format!("{} │ {}", side(left), side(right))
+      .trim_end()
+      .to_string()
+  };
+
+  let mut result = vec![];
+  let mut deleted = vec![];
+  let mut inserted = vec![];
+  let flush = |deleted: &mut Vec<&str>, inserted: &mut Vec<&str>, result: &mut Vec<String>| {
+    for i in 0..deleted.len().max(inserted.len()) {
+      result.push(row(
+        deleted.get(i).map(|l| ('-', *l)),
+        inserted.get(i).map(|l| ('+', *l)),
+      ));
+    }
+    deleted.clear();
+    inserted.clear();
+  };
+
+  for line in diff_lines(old, new) {
+    match line {
+      DiffLine::Equal(l) => {
+        flush(&mut deleted, &mut inserted, &mut result);
+        result.push(row(Some((' ', l)), Some((' ', l))));
+      }
+      DiffLine::Delete(l) => deleted.push(l),
+      DiffLine::Insert(l) => inserted.push(l),
+    }
+  }
+  flush(&mut deleted, &mut inserted, &mut result);
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn diff_lines1() {
+    assert_eq!(
+      diff_lines("a\nb\nc\n", "a\nB\nc\nd\n"),
+      vec![
+        DiffLine::Equal("a"),
+        DiffLine::Delete("b"),
+        DiffLine::Insert("B"),
+        DiffLine::Equal("c"),
+        DiffLine::Insert("d"),
+      ]
+    );
+    assert_eq!(unified_lines("", "a\n"), vec!["+a"]);
+    assert!(diff_lines("", "").is_empty());
+  }
+
+  #[test]
+  fn side_by_side_lines1() {
+    let actual = side_by_side_lines("a\nbb\nc\ngone\n", "a\nBB\nc\n", 17);
+    assert_eq!(
+      actual,
+      vec![" a      │  a", "-bb     │ +BB", " c      │  c", "-gone   │"]
+    );
+    let actual = side_by_side_lines("你好世界\n", "x\n", 11);
+    assert_eq!(actual, vec!["-你  │ +x"]);
+  }
+}
@@ -1,46 +1,102 @@
 //! Event loop.
 
-use crate::buf::{BuffersManager, BuffersManagerArc};
+#[cfg(feature = "lua")]
+use crate::api::EditorApi;
+use crate::buf::expand::expand_path;
+use crate::buf::position::POSITIONS_FILE;
+use crate::buf::spell::{spell_file, SpellDictionary};
+use crate::buf::{
+  ArgList, BufferArc, BufferId, BufferLoader, BuffersManager, BuffersManagerArc, FileFormat,
+  RemoteUrl,
+};
 use crate::cart::{IRect, U16Size};
 use crate::cli::CliOpt;
+use crate::defaults;
 use crate::envar;
-use crate::evloop::msg::WorkerToMasterMessage;
+use crate::evloop::embed::{EditorIo, InputStream};
+use crate::evloop::msg::{
+  BufferLoadDone, BufferLoadedBytes, ConfigChanged, FinderBatch, GitStatusDone, GrepBatch,
+  GrepDone, PreviewLoaded, ShellReadDone, SpellLoaded, WorkerToMasterMessage,
+};
+use crate::evloop::task::{TaskId, TaskKind, TaskRegistry};
+use crate::interrupt::{InterruptArc, InterruptWatcher};
 use crate::js::msg::{self as jsmsg, EventLoopToJsRuntimeMessage, JsRuntimeToEventLoopMessage};
-use crate::js::{JsRuntime, JsRuntimeOptions, SnapshotData};
-use crate::res::IoResult;
-use crate::state::fsm::StatefulValue;
+use crate::js::{JsFutureId, JsRuntime, JsRuntimeOptions, SnapshotData};
+#[cfg(feature = "lua")]
+use crate::lua::LuaRuntime;
+#[cfg(not(feature = "lua"))]
+use crate::res::AnyErr;
+use crate::res::{AnyResult, IoResult};
+use crate::state::autocmd::{AutocmdArgs, AutocmdEvent};
+use crate::state::ex::{quickfix, read, ExCommandDataAccess};
+use crate::state::finder::{self, FinderId, FinderItem, FinderResult, FinderSource};
+use crate::state::fsm::mark::{restore_last_position, save_window_position};
+use crate::state::fsm::{FinderStateful, StatefulValue};
+use crate::state::git;
+use crate::state::grep::{self, GrepId, GrepRequest, GrepSummary};
+use crate::state::keymap::KeymapAction;
+use crate::state::mode::Mode;
+use crate::state::msg::MessageLevel;
+use crate::state::progress::ProgressId;
+use crate::state::session::{save_named_session, sessions_dir};
 use crate::state::{State, StateArc};
 use crate::ui::canvas::{Canvas, CanvasArc, Shader, ShaderCommand};
 use crate::ui::tree::internal::Inodeable;
-use crate::ui::tree::{Tree, TreeArc, TreeNode};
+use crate::ui::tree::{Tree, TreeArc, TreeNode, TreeNodeId};
 use crate::ui::widget::{Cursor, Window};
 use crate::{rlock, wlock};
 
+use ahash::AHashMap as HashMap;
 use crossterm::event::{
-  DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, Event,
-  EventStream,
+  DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+  EnableFocusChange, EnableMouseCapture, Event, KeyEventKind,
 };
 use crossterm::{self, execute, queue};
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 use parking_lot::RwLock;
+use path_absolutize::Absolutize;
 use std::path::{Path, PathBuf};
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 // use heed::types::U16;
-use std::io::Write;
-use std::io::{BufWriter, Stdout};
+use std::io::{Read, Write};
 use std::sync::Arc;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::TaskTracker;
 use tracing::{error, trace};
 
+pub mod embed;
+#[cfg(feature = "fetch")]
+pub mod fetch;
+pub mod fs;
+pub mod job;
 pub mod msg;
 pub mod task;
 
+// Save (push) and restore (pop) the terminal title with xterm's title stack, the terminals that
+// don't support it ignore them.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
+// The things received by the main loop in an iteration, see [`EventLoop::step`].
+enum LoopEvent {
+  Input(Option<IoResult<Event>>),
+  Typeahead(Event),
+  Worker(Option<WorkerToMasterMessage>),
+  JsRequest(Option<JsRuntimeToEventLoopMessage>),
+  JsResponse(Option<EventLoopToJsRuntimeMessage>),
+  EscapeTimeout,
+  KeymapTimeout,
+  CursorWord,
+  ViewportPrefetch,
+  UndoFlush,
+  Cancelled,
+}
+
 // #[derive(Debug)]
 /// For slow tasks that are suitable to put in the background, this event loop will spawn them in
 /// tokio's async tasks and let them sync back data once they are done. The event loop controls all
-/// the tasks with [`CancellationToken`] and [`TaskTracker`].
+/// the tasks with [`CancellationToken`], [`TaskRegistry`] and [`TaskTracker`].
 ///
 /// # Terms
 ///
@@ -72,28 +128,59 @@ pub struct EventLoop {
   pub tree: TreeArc,
   /// Canvas for UI.
   pub canvas: CanvasArc,
-  /// Stdout writer for UI.
-  pub writer: BufWriter<Stdout>,
+  /// Render backend for UI, i.e. stdout for the terminal.
+  pub writer: Box<dyn Write>,
+  /// Whether the bracketed paste is enabled on the terminal, it follows the 'termpaste' option.
+  /// It's `None` before the TUI is initialized, i.e. the embedded editor never touches it.
+  pub bracketed_paste: Option<bool>,
+  /// The terminal title set by the 'title' option, it's `None` if the title is not changed.
+  pub title: Option<String>,
+  /// Input events stream, i.e. the keyboard/mouse events from the terminal.
+  pub input: InputStream,
+  /// The last time an event or message is processed, the idle tasks (i.e. viewports prefetch,
+  /// undo files flush) run after a delay since it.
+  pub last_active: Instant,
 
   /// (Global) editing state.
   pub state: StateArc,
 
+  /// Ctrl-C interrupt flag, it's shared with the editing state and js runtime.
+  pub interrupt: InterruptArc,
+  /// Watch the Ctrl-C key while the event loop is blocked.
+  pub interrupt_watcher: InterruptWatcher,
+  /// Receiver: master <= interrupt watcher, the events typed ahead while the event loop is
+  /// blocked.
+  pub typeahead_recv: Receiver<Event>,
+
   /// Vim buffers.
   pub buffers: BuffersManagerArc,
+  /// The huge files loading in background, and their progress.
+  pub buffer_loaders: HashMap<BufferId, (BufferLoader, ProgressId)>,
 
   /// Cancellation token to notify the main loop to exit.
   pub cancellation_token: CancellationToken,
-  /// Task tracker for spawned tasks, there are two trackers:
+  /// Task trackers for spawned tasks, there are two trackers:
   ///
-  /// 1. Cancellable/deteched tracker for those tasks that are safe to cancel.
+  /// 1. Cancellable/detached registry for those tasks that are safe to cancel, they're cancelled
+  ///    and given a grace period to stop when editor exit, see [`TaskRegistry`].
   /// 2. Block tracker are for dangerous tasks, user will have to wait for them complete before
   ///    exit the editor.
   ///
   /// Most write file operations are spawned with block tracker to ensure they will be safely
   /// complete to avoid damage user data files. While for most reading operations and pure CPU
   /// calculations, they will be cancelled when editor exit.
-  pub detached_tracker: TaskTracker,
+  pub detached_tasks: TaskRegistry,
   pub blocked_tracker: TaskTracker,
+  /// The running timers of js runtime, i.e. `setTimeout` and `setInterval`, by timer ID.
+  pub js_timers: HashMap<JsFutureId, TaskId>,
+  /// The running search of `:grep` and `Rsvim.grep`, by its grep ID and task ID. Only one search
+  /// runs at a time.
+  pub grep: Option<(GrepId, TaskId, GrepRequest)>,
+  /// The running file walk of the fuzzy finder, by its finder ID and task ID.
+  pub finder: Option<(FinderId, TaskId)>,
+  /// The file loading for the quickfix preview, by its path and task ID. Only the last requested
+  /// file is loaded.
+  pub preview_load: Option<(PathBuf, TaskId)>,
 
   /// Sender: workers => master.
   ///
@@ -105,6 +192,9 @@ pub struct EventLoop {
 
   /// Js runtime.
   pub js_runtime: JsRuntime,
+  /// Lua runtime, it runs the `.lua` scripts.
+  #[cfg(feature = "lua")]
+  pub lua_runtime: LuaRuntime,
   /// Receiver: master <= js runtime.
   pub master_recv_from_js_runtime: Receiver<JsRuntimeToEventLoopMessage>,
   /// Sender: master => js runtime.
@@ -116,11 +206,15 @@ pub struct EventLoop {
 }
 
 impl EventLoop {
-  /// Make new event loop.
+  /// Make new event loop with current terminal.
   pub fn new(cli_opt: CliOpt, snapshot: SnapshotData) -> IoResult<Self> {
+    Self::with_io(cli_opt, snapshot, EditorIo::terminal()?)
+  }
+
+  /// Make new event loop with the injected IO.
+  pub fn with_io(cli_opt: CliOpt, snapshot: SnapshotData, io: EditorIo) -> IoResult<Self> {
     // Canvas
-    let (cols, rows) = crossterm::terminal::size()?;
-    let canvas_size = U16Size::new(cols, rows);
+    let canvas_size = io.size;
     let canvas = Canvas::new(canvas_size);
     let canvas = Canvas::to_arc(canvas);
 
@@ -128,11 +222,23 @@ impl EventLoop {
     let tree = Tree::to_arc(Tree::new(canvas_size));
 
     // Buffers
-    let buffers_manager = BuffersManager::to_arc(BuffersManager::new());
+    let mut buffers_manager = BuffersManager::new();
+    buffers_manager.set_fs(io.fs);
+    buffers_manager.set_undo_dir(io.undo_dir);
+    let buffers_manager = BuffersManager::to_arc(buffers_manager);
 
     // State
     let state = State::to_arc(State::default());
 
+    // Interrupt
+    let interrupt = rlock!(state).interrupt().clone();
+    let (typeahead_send, typeahead_recv) = channel(envar::CHANNEL_BUF_SIZE());
+    let interrupt_watcher = if io.watch_terminal {
+      InterruptWatcher::spawn(interrupt.clone(), typeahead_send)
+    } else {
+      InterruptWatcher::detached()
+    };
+
     // Worker => master
     let (worker_send_to_master, master_recv_from_worker) = channel(envar::CHANNEL_BUF_SIZE());
 
@@ -177,7 +283,7 @@ impl EventLoop {
     let runtime_path = Arc::new(RwLock::new(runtime_path));
 
     // Task Tracker
-    let detached_tracker = TaskTracker::new();
+    let detached_tasks = TaskRegistry::new();
     let blocked_tracker = TaskTracker::new();
     let startup_moment = Instant::now();
     let startup_unix_epoch = SystemTime::now()
@@ -200,6 +306,14 @@ impl EventLoop {
       state.clone(),
     );
 
+    // Lua Runtime
+    #[cfg(feature = "lua")]
+    let lua_runtime = LuaRuntime::new(
+      EditorApi::new(tree.clone(), buffers_manager.clone(), state.clone()),
+      interrupt.clone(),
+    )
+    .map_err(|e| std::io::Error::other(e.to_string()))?;
+
     Ok(EventLoop {
       startup_moment,
       startup_unix_epoch,
@@ -208,14 +322,28 @@ impl EventLoop {
       canvas,
       tree,
       state,
+      interrupt,
+      interrupt_watcher,
+      typeahead_recv,
       buffers: buffers_manager,
-      writer: BufWriter::new(std::io::stdout()),
+      buffer_loaders: HashMap::new(),
+      writer: io.backend,
+      bracketed_paste: None,
+      title: None,
+      input: io.input,
+      last_active: Instant::now(),
       cancellation_token: CancellationToken::new(),
-      detached_tracker,
+      detached_tasks,
       blocked_tracker,
+      js_timers: HashMap::new(),
+      grep: None,
+      finder: None,
+      preview_load: None,
       worker_send_to_master,
       master_recv_from_worker,
       js_runtime,
+      #[cfg(feature = "lua")]
+      lua_runtime,
       master_recv_from_js_runtime,
       master_send_to_js_runtime,
       js_runtime_tick_dispatcher,
@@ -223,19 +351,88 @@ impl EventLoop {
     })
   }
 
-  /// Initialize user config file.
+  /// Initialize user config file, it's watched and reloaded once it's changed, see
+  /// [`config`](crate::state::ex::config).
   pub fn init_config(&mut self) -> IoResult<()> {
     if let Some(config_file) = envar::CONFIG_FILE_PATH() {
       self
-        .js_runtime
-        .execute_module(config_file.to_str().unwrap(), None)
+        .interruptible(|this| {
+          this
+            .js_runtime
+            .execute_module(config_file.to_str().unwrap(), None)
+        })
         .unwrap();
+      self.watch_config(config_file);
+    }
+    Ok(())
+  }
+
+  // Poll the modified time of the user config file, and notify master once it's changed. The
+  // missing file is ignored, i.e. it's being replaced by the editors that save atomically.
+  fn watch_config(&mut self, config_file: PathBuf) {
+    let worker_send_to_master = self.worker_send_to_master.clone();
+    let task_name = config_file.to_string_lossy().to_string();
+    self
+      .detached_tasks
+      .spawn(TaskKind::Watch, &task_name, async move {
+        let modified_time = |path: PathBuf| async move {
+          tokio::fs::metadata(path)
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok()
+        };
+        let mut last = modified_time(config_file.clone()).await;
+        loop {
+          tokio::time::sleep(defaults::task::CONFIG_WATCH_INTERVAL).await;
+          let modified = modified_time(config_file.clone()).await;
+          if modified.is_none() || modified == last {
+            continue;
+          }
+          last = modified;
+          let msg = ConfigChanged::new(config_file.clone());
+          if worker_send_to_master
+            .send(WorkerToMasterMessage::ConfigChanged(msg))
+            .await
+            .is_err()
+          {
+            break;
+          }
+        }
+      });
+  }
+
+  /// Initialize the user spell dictionary, the dictionary file is read on an async task, see
+  /// [`spell`](crate::buf::spell).
+  pub fn init_spell(&mut self) -> IoResult<()> {
+    wlock!(self.buffers).spell_mut().request_reload();
+    self.process_spell();
+    Ok(())
+  }
+
+  /// Initialize session, i.e. `--session {file}`. It's executed after the buffers and windows
+  /// are initialized.
+  pub fn init_session(&mut self) -> IoResult<()> {
+    if let Some(session_file) = self.cli_opt.session() {
+      let session_file = session_file.absolutize()?.to_path_buf();
+      wlock!(self.state).push_scripts(vec![session_file]);
+      self.process_scripts();
     }
     Ok(())
   }
 
   /// Initialize TUI.
-  pub fn init_tui(&self) -> IoResult<()> {
+  ///
+  /// NOTE: On Windows, the virtual terminal processing is enabled for the console, thus the ANSI
+  /// escape sequences are rendered by the legacy console (conhost) as well.
+  ///
+  /// NOTE: The bracketed paste is only enabled if the terminal supports it, see
+  /// [`termcap`](crate::ui::termcap).
+  pub fn init_tui(&mut self) -> IoResult<()> {
+    #[cfg(target_os = "windows")]
+    if !crossterm::ansi_support::supports_ansi() {
+      error!("Failed to enable the virtual terminal processing for Windows console");
+    }
+
     if !crossterm::terminal::is_raw_mode_enabled()? {
       crossterm::terminal::enable_raw_mode()?;
     }
@@ -249,16 +446,53 @@ impl EventLoop {
       EnableFocusChange,
     )?;
 
+    let bracketed_paste = rlock!(self.tree).termpaste();
+    if bracketed_paste {
+      execute!(out, EnableBracketedPaste)?;
+    }
+    self.bracketed_paste = Some(bracketed_paste);
+
     Ok(())
   }
 
   /// Initialize buffers.
   pub fn init_buffers(&mut self) -> IoResult<()> {
     // Initialize buffers.
-    let input_files = self.cli_opt.file().to_vec();
+    // The shell doesn't expand the paths on Windows, and the remote URLs are never expanded.
+    let input_files = self
+      .cli_opt
+      .file()
+      .iter()
+      .flat_map(|file| {
+        if RemoteUrl::parse(file).is_some() {
+          vec![file.clone()]
+        } else {
+          expand_path(file)
+            .into_iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect()
+        }
+      })
+      .collect::<Vec<_>>();
+    *wlock!(self.buffers).arglist_mut() =
+      ArgList::new(input_files.iter().map(PathBuf::from).collect());
     if !input_files.is_empty() {
-      for input_file in input_files.iter() {
-        let maybe_buf_id = wlock!(self.buffers).new_file_buffer(Path::new(input_file));
+      let progress_id = wlock!(self.state)
+        .progress_mut()
+        .begin("Loading files", false);
+      for (i, input_file) in input_files.iter().enumerate() {
+        wlock!(self.state).progress_mut().update(
+          progress_id,
+          input_file,
+          Some((i * 100 / input_files.len()) as u8),
+        );
+        let maybe_buf_id = if let Some(url) = RemoteUrl::parse(input_file) {
+          Ok(self.load_remote_buffer(url))
+        } else if self.is_huge_file(Path::new(input_file)) {
+          self.load_file_buffer(Path::new(input_file))
+        } else {
+          wlock!(self.buffers).new_file_buffer(Path::new(input_file))
+        };
         match maybe_buf_id {
           Ok(buf_id) => {
             trace!("Created file buffer {:?}:{:?}", input_file, buf_id);
@@ -268,6 +502,7 @@ impl EventLoop {
           }
         }
       }
+      wlock!(self.state).progress_mut().end(progress_id);
     } else {
       let buf_id = wlock!(self.buffers).new_empty_buffer();
       trace!("Created empty buffer {:?}", buf_id);
@@ -276,6 +511,101 @@ impl EventLoop {
     Ok(())
   }
 
+  fn is_huge_file(&self, filename: &Path) -> bool {
+    rlock!(self.buffers)
+      .fs()
+      .file_size(filename)
+      .is_ok_and(|file_size| file_size >= defaults::buf::ASYNC_LOAD_THRESHOLD)
+  }
+
+  /// Open a huge file with a read-only buffer, and read the file chunk by chunk on a blocking task.
+  /// The chunks are sent back to master and appended to the buffer, see
+  /// [`process_worker_notify`](EventLoop::process_worker_notify).
+  fn load_file_buffer(&mut self, filename: &Path) -> IoResult<BufferId> {
+    let (buf_id, mut reader, total_bytes) =
+      wlock!(self.buffers).new_loading_file_buffer(filename)?;
+    let encoding = rlock!(self.buffers).local_options().file_encoding();
+    let progress_id = wlock!(self.state)
+      .progress_mut()
+      .begin(&format!("Loading {}", filename.display()), false);
+    self.buffer_loaders.insert(
+      buf_id,
+      (BufferLoader::new(encoding, total_bytes), progress_id),
+    );
+
+    let worker_send_to_master = self.worker_send_to_master.clone();
+    let task_name = filename.to_string_lossy();
+    self
+      .detached_tasks
+      .spawn_blocking(TaskKind::Loader, &task_name, move |token| {
+        let error = loop {
+          // The editor is quit.
+          if token.is_cancelled() {
+            return;
+          }
+          let mut bytes = vec![0_u8; defaults::buf::LOAD_CHUNK_SIZE];
+          match reader.read(&mut bytes) {
+            Ok(0) => break None,
+            Ok(n) => {
+              bytes.truncate(n);
+              let msg =
+                WorkerToMasterMessage::BufferLoadedBytes(BufferLoadedBytes::new(buf_id, bytes));
+              if worker_send_to_master.blocking_send(msg).is_err() {
+                // The event loop has exited.
+                return;
+              }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => { /* Retry */ }
+            Err(e) => break Some(e.to_string()),
+          }
+        };
+        let _ = worker_send_to_master.blocking_send(WorkerToMasterMessage::BufferLoadDone(
+          BufferLoadDone::new(buf_id, error),
+        ));
+      });
+
+    Ok(buf_id)
+  }
+
+  /// Open a remote file with a loading buffer, and fetch the file by the external command (see
+  /// [`RemoteUrl::fetch_command`]) on an async task. Same with
+  /// [`load_file_buffer`](EventLoop::load_file_buffer), the stdout chunks are sent back to master.
+  fn load_remote_buffer(&mut self, url: RemoteUrl) -> BufferId {
+    if let Some(buffer) = rlock!(self.buffers).find_by_url(url.url()) {
+      return rlock!(buffer).id();
+    }
+    let (program, args) = url.fetch_command();
+    let task_name = url.url().to_string();
+    let progress_id = wlock!(self.state)
+      .progress_mut()
+      .begin(&format!("Fetching {}", url.url()), false);
+    let buf_id = wlock!(self.buffers).new_remote_buffer(url);
+    let encoding = rlock!(self.buffers).local_options().file_encoding();
+    // The total size is unknown.
+    self
+      .buffer_loaders
+      .insert(buf_id, (BufferLoader::new(encoding, 0), progress_id));
+
+    let worker_send_to_master = self.worker_send_to_master.clone();
+    self
+      .detached_tasks
+      .spawn(TaskKind::Fetch, &task_name, async move {
+        let error = match fetch_remote(buf_id, &program, &args, &worker_send_to_master).await {
+          Ok(true) => None,
+          // The event loop has exited.
+          Ok(false) => return,
+          Err(e) => Some(e),
+        };
+        let _ = worker_send_to_master
+          .send(WorkerToMasterMessage::BufferLoadDone(BufferLoadDone::new(
+            buf_id, error,
+          )))
+          .await;
+      });
+
+    buf_id
+  }
+
   /// Initialize windows.
   pub fn init_windows(&mut self) -> IoResult<()> {
     // Initialize default window.
@@ -301,7 +631,62 @@ impl EventLoop {
     let cursor = Cursor::new(cursor_shape);
     let cursor_node = TreeNode::Cursor(cursor);
     tree.bounded_insert(&window_id, cursor_node);
+    tree.sync_cursor_position();
+
+    Ok(())
+  }
 
+  /// Initialize the last positions of the recent files, and restore the last position of the
+  /// file in the default window. It's executed after the buffers and windows are initialized.
+  pub fn init_positions(&mut self) -> IoResult<()> {
+    let positions_file = envar::DATA_DIR_PATH().join(POSITIONS_FILE);
+    {
+      let mut buffers = wlock!(self.buffers);
+      if let Err(e) = buffers.positions_mut().load(&positions_file) {
+        error!("Failed to load positions {:?}:{:?}", positions_file, e);
+      }
+      // The buffers are opened before the positions are loaded.
+      for buffer in buffers.values() {
+        let mut buffer = wlock!(buffer);
+        let last = match buffer.absolute_filename() {
+          Some(file) if buffer.remote().is_none() => buffers.positions().get(file),
+          _ => None,
+        };
+        if let Some(last) = last {
+          buffer.marks_mut().set('"', last.cursor);
+        }
+      }
+    }
+    restore_last_position(&self.tree, &self.buffers);
+    Ok(())
+  }
+
+  /// Save the last positions of the recent files, i.e. the files shown in the windows.
+  pub fn save_positions(&mut self) -> IoResult<()> {
+    let window_ids = rlock!(self.tree).window_ids().clone();
+    for window_id in window_ids {
+      save_window_position(&self.tree, &self.buffers, window_id);
+    }
+    let positions_file = envar::DATA_DIR_PATH().join(POSITIONS_FILE);
+    rlock!(self.buffers).positions().save(&positions_file)
+  }
+
+  /// Flush the undo states to the undo files, see [`undofile`](crate::buf::undofile).
+  pub fn flush_undo_files(&mut self) -> IoResult<()> {
+    rlock!(self.buffers).flush_undo_files()
+  }
+
+  /// Save current named session (if any), i.e. the last one saved/loaded by
+  /// `:SessionSave`/`:SessionLoad`, see [`session`](crate::state::session).
+  pub fn save_session(&mut self) -> IoResult<()> {
+    let name = match rlock!(self.buffers).session().clone() {
+      Some(name) => name,
+      None => return Ok(()),
+    };
+    let tree = rlock!(self.tree);
+    let buffers = rlock!(self.buffers);
+    let file = save_named_session(&sessions_dir(), &name, &tree, &buffers)?;
+    trace!("Save session {:?}", file);
     Ok(())
   }
 
@@ -342,16 +727,22 @@ impl EventLoop {
       Some(Ok(event)) => {
         trace!("Polled terminal event ok: {:?}", event);
 
-        // Handle by state machine
-        let state_response = self
-          .state
-          .try_write_for(envar::MUTEX_TIMEOUT())
-          .unwrap()
-          .handle(self.tree.clone(), self.buffers.clone(), event);
-
-        // Exit loop and quit.
-        if let StatefulValue::QuitState(_) = state_response.next_stateful {
-          self.cancellation_token.cancel();
+        // The lone `Esc` is held to decode the `Alt` modified keys, see the 'ttimeout' option.
+        let keys = match event {
+          Event::Key(key_event) => wlock!(self.state)
+            .escape_mut()
+            .decode(key_event, Instant::now()),
+          _ => wlock!(self.state)
+            .escape_mut()
+            .take_pending()
+            .into_iter()
+            .collect(),
+        };
+        for key_event in keys {
+          self.process_terminal_event(Event::Key(key_event)).await;
+        }
+        if !matches!(event, Event::Key(_)) {
+          self.process_terminal_event(event).await;
         }
       }
       Some(Err(e)) => {
@@ -365,28 +756,488 @@ impl EventLoop {
     }
   }
 
+  /// Release the held `Esc` if the 'ttimeoutlen' is reached.
+  async fn process_escape_timeout(&mut self) {
+    let esc = wlock!(self.state).escape_mut().timeout(Instant::now());
+    if let Some(esc) = esc {
+      trace!("Release the held Esc on ttimeout");
+      self.process_terminal_event(Event::Key(esc)).await;
+    }
+  }
+
+  async fn process_terminal_event(&mut self, event: Event) {
+    // The key press is consumed by the waiting `getchar` request (if any), it's not handled
+    // by the editing mode.
+    let getchar = wlock!(self.state).input_mut().consume(&event);
+    if let Some((future_id, key)) = getchar {
+      trace!("Consumed key {:?} by getchar:{:?}", key, future_id);
+      let _ = self
+        .js_runtime_tick_dispatcher
+        .send(EventLoopToJsRuntimeMessage::GetcharResp(
+          jsmsg::GetcharResp::new(future_id, Some(key)),
+        ))
+        .await;
+      return;
+    }
+
+    // The repository may be changed outside of the editor.
+    if let Event::FocusGained = event {
+      let mut state = wlock!(self.state);
+      state.git_mut().request_refresh();
+      state.autocmds_mut().emit(AutocmdArgs {
+        event: AutocmdEvent::FocusGained,
+        matched: String::new(),
+        buffer_id: None,
+        window_id: None,
+        scroll_delta: None,
+      });
+    }
+
+    // The canvas is resized and the windows are re-layout with the terminal.
+    if let Event::Resize(columns, rows) = event {
+      self.process_resize(U16Size::new(columns, rows));
+    }
+
+    // The key presses are resolved with the key mappings before handled by the editing mode.
+    if let Event::Key(key_event) = event {
+      if key_event.kind == KeyEventKind::Press {
+        wlock!(self.state).feed_key(key_event);
+        self.process_keymaps();
+        return;
+      }
+    }
+
+    // Handle by state machine
+    let state_response = self.interruptible(|this| {
+      this
+        .state
+        .try_write_for(envar::MUTEX_TIMEOUT())
+        .unwrap()
+        .handle(this.tree.clone(), this.buffers.clone(), event)
+    });
+
+    // Exit loop and quit.
+    if let StatefulValue::QuitState(_) = state_response.next_stateful {
+      self.cancellation_token.cancel();
+    }
+  }
+
+  /// Resolve the queued keys with the key mappings, the resolved keys are handled by the state
+  /// machine and the mapping callbacks are called, until it's waiting for more keys.
+  fn process_keymaps(&mut self) {
+    loop {
+      let action = wlock!(self.state).next_keymap_action(&self.tree, Instant::now());
+      let state_response = match action {
+        Some(KeymapAction::Key(key_event)) => self.interruptible(|this| {
+          wlock!(this.state).handle_key(this.tree.clone(), this.buffers.clone(), key_event)
+        }),
+        Some(KeymapAction::Callback(id)) => {
+          trace!("Call keymap callback:{:?}", id);
+          self.interruptible(|this| this.js_runtime.call_keymap(id));
+          continue;
+        }
+        None => break,
+      };
+
+      // Exit loop and quit.
+      if let StatefulValue::QuitState(_) = state_response.next_stateful {
+        self.cancellation_token.cancel();
+        break;
+      }
+    }
+  }
+
+  fn process_resize(&mut self, terminal_size: U16Size) {
+    trace!("Resize terminal to {:?}", terminal_size);
+    *wlock!(self.canvas) = Canvas::new(terminal_size);
+    let mut tree = wlock!(self.tree);
+    tree.resize(terminal_size);
+    tree.request_redraw(true);
+  }
+
+  async fn process_cursor_word(&mut self) {
+    trace!("Refresh cursor word highlighting");
+    self
+      .state
+      .try_write_for(envar::MUTEX_TIMEOUT())
+      .unwrap()
+      .cursor_word_mut()
+      .refresh(&self.tree);
+  }
+
+  async fn process_viewport_prefetch(&mut self) {
+    trace!("Prefetch viewports on idle");
+    rlock!(self.tree).prefetch_viewports();
+  }
+
+  async fn process_undo_flush(&mut self) {
+    trace!("Flush undo files on idle");
+    if let Err(e) = self.flush_undo_files() {
+      error!("Failed to flush undo files:{:?}", e);
+    }
+  }
+
   async fn process_worker_notify(&mut self, msg: Option<WorkerToMasterMessage>) {
-    trace!("Received {:?} message from workers", msg);
+    if let Some(msg) = msg {
+      match msg {
+        WorkerToMasterMessage::BufferLoadedBytes(msg) => {
+          trace!(
+            "Received {} bytes of buffer {:?} from workers",
+            msg.bytes.len(),
+            msg.buffer_id
+          );
+          if let Some((loader, progress_id)) = self.buffer_loaders.get_mut(&msg.buffer_id) {
+            let text = loader.push(&msg.bytes);
+            let (file_format, progress_id) = (loader.file_format(), *progress_id);
+            // The total size of the remote file is unknown.
+            let (message, percentage) = if loader.total_bytes() == 0 {
+              (format!("{} bytes", loader.loaded_bytes()), None)
+            } else {
+              let percentage = loader.percentage();
+              (format!("{}%", percentage), Some(percentage))
+            };
+            self.append_loaded(msg.buffer_id, &text, file_format);
+            wlock!(self.state)
+              .progress_mut()
+              .update(progress_id, &message, percentage);
+          }
+        }
+        WorkerToMasterMessage::BufferLoadDone(msg) => {
+          trace!("Buffer {:?} is loaded: {:?}", msg.buffer_id, msg.error);
+          if let Some((mut loader, progress_id)) = self.buffer_loaders.remove(&msg.buffer_id) {
+            let text = loader.finish();
+            self.append_loaded(msg.buffer_id, &text, loader.file_format());
+            if let Some(buffer) = rlock!(self.buffers).get(&msg.buffer_id) {
+              let mut buffer = wlock!(buffer);
+              if buffer.file_encoding() != loader.encoding() {
+                buffer.set_file_encoding(loader.encoding());
+              }
+              buffer.set_loading(false);
+            }
+            if msg.error.is_none() {
+              wlock!(self.buffers).set_buffer_read(msg.buffer_id);
+            }
+            wlock!(self.state).progress_mut().end(progress_id);
+          }
+          if let Some(error) = msg.error {
+            error!("Failed to load buffer {:?}:{:?}", msg.buffer_id, error);
+          }
+        }
+        WorkerToMasterMessage::ConfigChanged(msg) => {
+          trace!("User config {:?} is changed", msg.path);
+          wlock!(self.state).request_config_reload(true);
+        }
+        WorkerToMasterMessage::SpellLoaded(msg) => match msg.error {
+          Some(error) => error!("Failed to read spell file:{:?}", error),
+          None => {
+            let count = wlock!(self.buffers).spell_mut().merge(&msg.text);
+            trace!("Spell file is read, {} new words", count);
+          }
+        },
+        WorkerToMasterMessage::GitStatusDone(msg) => {
+          trace!("Git status is refreshed: {:?}", msg.status);
+          let summary = msg.status.as_ref().map(|s| s.summary()).unwrap_or_default();
+          if wlock!(self.state).git_mut().finish_refresh(msg.status) {
+            wlock!(self.tree).set_statusline_component(git::COMPONENT_NAME, &summary);
+          }
+        }
+        WorkerToMasterMessage::GrepBatch(msg) => {
+          trace!("Grep {:?} found {} matches", msg.grep_id, msg.entries.len());
+          if self
+            .grep
+            .as_ref()
+            .is_some_and(|(grep_id, _, _)| *grep_id == msg.grep_id)
+          {
+            wlock!(self.buffers).quickfix_mut().extend(msg.entries);
+            quickfix::refresh_quickfix_window(&self.tree, &self.buffers);
+            wlock!(self.tree)
+              .set_statusline_component(grep::COMPONENT_NAME, &msg.summary.progress());
+          }
+        }
+        WorkerToMasterMessage::GrepDone(msg) => {
+          trace!("Grep {:?} is done: {:?}", msg.grep_id, msg.result);
+          self.finish_grep(msg).await;
+        }
+        WorkerToMasterMessage::ShellReadDone(msg) => {
+          trace!("Shell read {:?} is done", msg.request);
+          self.finish_shell_read(msg);
+        }
+        WorkerToMasterMessage::PreviewLoaded(msg) => {
+          trace!("Preview {:?} is loaded", msg.path);
+          self.finish_preview_load(msg);
+        }
+        WorkerToMasterMessage::FinderBatch(msg) => {
+          trace!(
+            "Finder {:?} found {} items, done:{:?}",
+            msg.finder_id,
+            msg.items.len(),
+            msg.done
+          );
+          if msg.done
+            && self
+              .finder
+              .is_some_and(|(finder_id, _)| finder_id == msg.finder_id)
+          {
+            self.finder = None;
+          }
+          FinderStateful::populate(
+            &mut wlock!(self.state),
+            &self.tree,
+            msg.finder_id,
+            msg.items,
+            msg.done,
+          );
+        }
+      }
+    }
+  }
+
+  /// The search is finished. For `:grep`, the result is shown and it jumps to the first match. For
+  /// `Rsvim.grep`, the promise is resolved with the matches.
+  async fn finish_grep(&mut self, msg: GrepDone) {
+    let request = if self
+      .grep
+      .as_ref()
+      .is_some_and(|(grep_id, _, _)| *grep_id == msg.grep_id)
+    {
+      self.grep.take().map(|(_, _, request)| request)
+    } else {
+      None
+    };
+    let result = match request {
+      Some(request) => {
+        wlock!(self.tree).set_statusline_component(grep::COMPONENT_NAME, "");
+        if msg.future_id.is_none() {
+          self.show_grep_result(&request, &msg.result);
+        }
+        msg
+          .result
+          .map(|_| rlock!(self.buffers).quickfix().entries().clone())
+      }
+      // The search is replaced by a newer one.
+      None => Err(grep::CANCELLED.to_string()),
+    };
+    if let Some(future_id) = msg.future_id {
+      let _ = self
+        .js_runtime_tick_dispatcher
+        .send(EventLoopToJsRuntimeMessage::GrepResp(jsmsg::GrepResp::new(
+          future_id, result,
+        )))
+        .await;
+    }
+  }
+
+  fn show_grep_result(&self, request: &GrepRequest, result: &Result<GrepSummary, String>) {
+    let messages = rlock!(self.state).messages().clone();
+    match result {
+      Err(e) if e == grep::CANCELLED => {}
+      Err(e) => wlock!(messages).push(MessageLevel::Error, e),
+      Ok(summary) if summary.matches == 0 => wlock!(messages).push(
+        MessageLevel::Error,
+        &format!("E480: No match: {}", request.options.pattern),
+      ),
+      // NOTE: The user may be typing in other modes, the cursor is only moved in normal mode. It
+      // jumps to the current entry, since the user may have navigated while searching.
+      Ok(_) if request.jump && rlock!(self.state).mode() == Mode::Normal => {
+        let mut state = wlock!(self.state);
+        let mut data_access = ExCommandDataAccess::new(
+          self.tree.clone(),
+          self.buffers.clone(),
+          state.interrupt().clone(),
+          messages.clone(),
+        );
+        if let Err(e) = state.ex_commands().run("cc", &mut data_access) {
+          wlock!(messages).push(MessageLevel::Error, &e.to_string());
+        }
+        state.push_events(data_access.take_events());
+      }
+      Ok(summary) => wlock!(messages).push(MessageLevel::Info, &summary.format()),
+    }
+  }
+
+  /// Append the loaded text to the buffer, and sync the viewports that show it.
+  fn append_loaded(&mut self, buffer_id: BufferId, text: &str, file_format: Option<FileFormat>) {
+    let buffer = match rlock!(self.buffers).get(&buffer_id) {
+      Some(buffer) => buffer.clone(),
+      None => return,
+    };
+    {
+      let mut buffer = wlock!(buffer);
+      if let Some(file_format) = file_format {
+        if buffer.file_format() != file_format {
+          buffer.set_file_format(file_format);
+        }
+      }
+      if !text.is_empty() {
+        buffer.append_loaded(text);
+      }
+    }
+
+    // NOTE: The buffer lock must be released before syncing viewport.
+    self.sync_viewports(&buffer);
+  }
+
+  /// Sync the viewports of the windows that show the buffer.
+  fn sync_viewports(&self, buffer: &BufferArc) {
+    let tree = rlock!(self.tree);
+    for window_id in tree.window_ids().iter() {
+      if let Some(TreeNode::Window(window)) = tree.node(window_id) {
+        if window
+          .buffer()
+          .upgrade()
+          .is_some_and(|b| Arc::ptr_eq(&b, buffer))
+        {
+          wlock!(window.viewport()).sync();
+        }
+      }
+    }
   }
 
   async fn process_js_runtime_request(&mut self, msg: Option<JsRuntimeToEventLoopMessage>) {
     if let Some(msg) = msg {
       match msg {
         JsRuntimeToEventLoopMessage::TimeoutReq(req) => {
-          trace!("process_js_runtime_request timeout_req:{:?}", req.future_id);
+          trace!(
+            "process_js_runtime_request timeout_req:{:?}, repeat:{:?}",
+            req.future_id,
+            req.repeat
+          );
           let js_runtime_tick_dispatcher = self.js_runtime_tick_dispatcher.clone();
-          self.detached_tracker.spawn(async move {
-            tokio::time::sleep(req.duration).await;
+          let task_name = if req.repeat {
+            format!("setInterval {:?}", req.future_id)
+          } else {
+            format!("setTimeout {:?}", req.future_id)
+          };
+          let task_id = self
+            .detached_tasks
+            .spawn(TaskKind::Timer, &task_name, async move {
+              // NOTE: The first tick of `interval` completes immediately, it must be skipped.
+              let start = tokio::time::Instant::now() + req.duration;
+              let mut interval = tokio::time::interval_at(start, req.duration);
+              // The missed ticks are not bursted when the event loop is blocked.
+              interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+              loop {
+                interval.tick().await;
+                let sent = js_runtime_tick_dispatcher
+                  .send(EventLoopToJsRuntimeMessage::TimeoutResp(
+                    jsmsg::TimeoutResp::new(req.future_id, req.duration, req.repeat),
+                  ))
+                  .await;
+                if !req.repeat || sent.is_err() {
+                  break;
+                }
+              }
+              trace!(
+                "process_js_runtime_request timeout_req:{:?} - done",
+                req.future_id
+              );
+            });
+          self.js_timers.insert(req.future_id, task_id);
+        }
+        JsRuntimeToEventLoopMessage::ClearTimeoutReq(req) => {
+          trace!(
+            "process_js_runtime_request clear_timeout_req:{:?}",
+            req.future_id
+          );
+          if let Some(task_id) = self.js_timers.remove(&req.future_id) {
+            self.detached_tasks.cancel(task_id);
+          }
+        }
+        JsRuntimeToEventLoopMessage::GetcharTimeoutReq(req) => {
+          trace!(
+            "process_js_runtime_request getchar_timeout_req:{:?}",
+            req.future_id
+          );
+          let js_runtime_tick_dispatcher = self.js_runtime_tick_dispatcher.clone();
+          let state = self.state.clone();
+          let task_name = format!("getchar {:?}", req.future_id);
+          self
+            .detached_tasks
+            .spawn(TaskKind::Timer, &task_name, async move {
+              tokio::time::sleep(req.duration).await;
+              // The request is already resolved by a key press.
+              let waiting = wlock!(state).input_mut().remove_getchar(req.future_id);
+              if !waiting {
+                return;
+              }
+              let _ = js_runtime_tick_dispatcher
+                .send(EventLoopToJsRuntimeMessage::GetcharResp(
+                  jsmsg::GetcharResp::new(req.future_id, None),
+                ))
+                .await;
+            });
+        }
+        JsRuntimeToEventLoopMessage::JobSpawnReq(req) => {
+          trace!("process_js_runtime_request job_spawn_req:{:?}", req.job_id);
+          let task_name = req.cmd.join(" ");
+          self.detached_tasks.spawn(
+            TaskKind::Job,
+            &task_name,
+            job::run_job(
+              req.job_id,
+              req.cmd,
+              req.cwd,
+              req.control,
+              self.js_runtime_tick_dispatcher.clone(),
+            ),
+          );
+        }
+        #[cfg(feature = "fetch")]
+        JsRuntimeToEventLoopMessage::FetchReq(req) => {
+          trace!(
+            "process_js_runtime_request fetch_req:{:?}, {:?}",
+            req.fetch_id,
+            req.request.url
+          );
+          let task_name = format!("{} {}", req.request.method, req.request.url);
+          self.detached_tasks.spawn(
+            TaskKind::Fetch,
+            &task_name,
+            fetch::run_fetch(
+              req.fetch_id,
+              req.request,
+              req.control,
+              self.js_runtime_tick_dispatcher.clone(),
+            ),
+          );
+        }
+        JsRuntimeToEventLoopMessage::FsReq(req) => {
+          trace!(
+            "process_js_runtime_request fs_req:{:?}, {:?}",
+            req.future_id,
+            req.op
+          );
+          let task_name = format!("fs {}", req.future_id);
+          let js_runtime_tick_dispatcher = self.js_runtime_tick_dispatcher.clone();
+          let is_write = req.op.is_write();
+          let task = async move {
+            let result = fs::run_fs(req.op).await;
             let _ = js_runtime_tick_dispatcher
-              .send(EventLoopToJsRuntimeMessage::TimeoutResp(
-                jsmsg::TimeoutResp::new(req.future_id, req.duration),
-              ))
+              .send(EventLoopToJsRuntimeMessage::FsResp(jsmsg::FsResp::new(
+                req.future_id,
+                result,
+              )))
               .await;
-            trace!(
-              "process_js_runtime_request timeout_req:{:?} - done",
-              req.future_id
-            );
-          });
+          };
+          if is_write {
+            self
+              .detached_tasks
+              .spawn_write(TaskKind::Fs, &task_name, task);
+          } else {
+            self.detached_tasks.spawn(TaskKind::Fs, &task_name, task);
+          }
+        }
+        JsRuntimeToEventLoopMessage::GrepReq(req) => {
+          trace!(
+            "process_js_runtime_request grep_req:{:?}, {:?}",
+            req.future_id,
+            req.options
+          );
+          let request = GrepRequest {
+            options: req.options,
+            jump: false,
+          };
+          self.start_grep(request, Some(req.future_id));
         }
       }
     }
@@ -395,16 +1246,193 @@ impl EventLoop {
   async fn process_js_runtime_response(&mut self, msg: Option<EventLoopToJsRuntimeMessage>) {
     if let Some(msg) = msg {
       trace!("process_js_runtime_response msg:{:?}", msg);
+      if let EventLoopToJsRuntimeMessage::TimeoutResp(resp) = &msg {
+        if !resp.repeat {
+          self.js_timers.remove(&resp.future_id);
+        }
+      }
       let _ = self.master_send_to_js_runtime.send(msg).await;
-      self.js_runtime.tick_event_loop();
+      wlock!(self.state)
+        .input_mut()
+        .set_typeahead(self.typeahead_recv.len());
+      self.interruptible(|this| this.js_runtime.tick_event_loop());
+    }
+  }
+
+  /// Run the blocking operation, it can be interrupted by Ctrl-C.
+  ///
+  /// NOTE: The Ctrl-C received before the operation is discarded, i.e. it's not interrupted
+  /// immediately by a stale Ctrl-C.
+  fn interruptible<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+    self.interrupt.reset();
+    self.interrupt_watcher.arm();
+    let result = f(self);
+    self.interrupt_watcher.disarm();
+    if self.interrupt.reset() {
+      trace!("Blocking operation is interrupted");
     }
+    result
   }
 
+  /// Wait for the blocked tasks to complete, and shut down the detached tasks within the grace
+  /// period, the tasks refuse to stop are logged (see [`TaskRegistry::shutdown`]).
   async fn process_cancellation_notify(&mut self) {
     trace!("Receive cancellation token, exit loop");
-    self.detached_tracker.close();
     self.blocked_tracker.close();
     self.blocked_tracker.wait().await;
+    let stuck = self
+      .detached_tasks
+      .shutdown(defaults::task::QUIT_GRACE_PERIOD)
+      .await;
+    for task in stuck.iter() {
+      error!("Background task refuses to stop: {}", task);
+    }
+  }
+
+  // Wait for the next event, message or timer.
+  async fn next_event(&mut self) -> LoopEvent {
+    let cursor_word_deadline = rlock!(self.state).cursor_word().deadline();
+    let cursor_word_timer = tokio::time::sleep_until(tokio::time::Instant::from_std(
+      cursor_word_deadline.unwrap_or_else(Instant::now),
+    ));
+    let keymap_deadline = rlock!(self.state).keymaps().deadline();
+    let keymap_timer = tokio::time::sleep_until(tokio::time::Instant::from_std(
+      keymap_deadline.unwrap_or_else(Instant::now),
+    ));
+    let escape_deadline = rlock!(self.state).escape().deadline();
+    let escape_timer = tokio::time::sleep_until(tokio::time::Instant::from_std(
+      escape_deadline.unwrap_or_else(Instant::now),
+    ));
+    let viewport_prefetch_pending = rlock!(self.tree).viewport_prefetch_pending();
+    let viewport_prefetch_timer = tokio::time::sleep_until(tokio::time::Instant::from_std(
+      self.last_active + defaults::win::VIEWPORT_PREFETCH_DELAY,
+    ));
+    let undo_flush_pending = rlock!(self.buffers).undo_flush_pending();
+    let undo_flush_timer = tokio::time::sleep_until(tokio::time::Instant::from_std(
+      self.last_active + defaults::buf::UNDO_FLUSH_DELAY,
+    ));
+
+    tokio::select! {
+      // Receive keyboard/mouse events
+      event = self.input.next() => LoopEvent::Input(event),
+      // Receive events typed ahead while blocked
+      Some(event) = self.typeahead_recv.recv() => LoopEvent::Typeahead(event),
+      // Receive notification from workers
+      worker_msg = self.master_recv_from_worker.recv() => LoopEvent::Worker(worker_msg),
+      // Receive notification from js runtime
+      js_req = self.master_recv_from_js_runtime.recv() => LoopEvent::JsRequest(js_req),
+      js_resp = self.js_runtime_tick_queue.recv() => LoopEvent::JsResponse(js_resp),
+      // The held `Esc` is timeout
+      _ = escape_timer, if escape_deadline.is_some() => LoopEvent::EscapeTimeout,
+      // The pending keys of the key mappings are timeout
+      _ = keymap_timer, if keymap_deadline.is_some() => LoopEvent::KeymapTimeout,
+      // Debounced cursor word highlighting
+      _ = cursor_word_timer, if cursor_word_deadline.is_some() => LoopEvent::CursorWord,
+      // Prefetch the lines around viewports on idle
+      _ = viewport_prefetch_timer, if viewport_prefetch_pending => LoopEvent::ViewportPrefetch,
+      // Flush the undo files on idle
+      _ = undo_flush_timer, if undo_flush_pending => LoopEvent::UndoFlush,
+      // Receive cancellation notify
+      _ = self.cancellation_token.cancelled() => LoopEvent::Cancelled,
+    }
+  }
+
+  // Get the next event, message or timer without waiting, returns `None` if nothing is ready.
+  fn try_next_event(&mut self) -> Option<LoopEvent> {
+    if self.cancellation_token.is_cancelled() {
+      return Some(LoopEvent::Cancelled);
+    }
+    if let Some(event) = self.input.next().now_or_never() {
+      return Some(LoopEvent::Input(event));
+    }
+    if let Ok(event) = self.typeahead_recv.try_recv() {
+      return Some(LoopEvent::Typeahead(event));
+    }
+    if let Ok(worker_msg) = self.master_recv_from_worker.try_recv() {
+      return Some(LoopEvent::Worker(Some(worker_msg)));
+    }
+    if let Ok(js_req) = self.master_recv_from_js_runtime.try_recv() {
+      return Some(LoopEvent::JsRequest(Some(js_req)));
+    }
+    if let Ok(js_resp) = self.js_runtime_tick_queue.try_recv() {
+      return Some(LoopEvent::JsResponse(Some(js_resp)));
+    }
+
+    let now = Instant::now();
+    let is_due = |deadline: Option<Instant>| deadline.is_some_and(|deadline| deadline <= now);
+    if is_due(rlock!(self.state).escape().deadline()) {
+      return Some(LoopEvent::EscapeTimeout);
+    }
+    if is_due(rlock!(self.state).keymaps().deadline()) {
+      return Some(LoopEvent::KeymapTimeout);
+    }
+    if is_due(rlock!(self.state).cursor_word().deadline()) {
+      return Some(LoopEvent::CursorWord);
+    }
+    if rlock!(self.tree).viewport_prefetch_pending()
+      && self.last_active + defaults::win::VIEWPORT_PREFETCH_DELAY <= now
+    {
+      return Some(LoopEvent::ViewportPrefetch);
+    }
+    if rlock!(self.buffers).undo_flush_pending()
+      && self.last_active + defaults::buf::UNDO_FLUSH_DELAY <= now
+    {
+      return Some(LoopEvent::UndoFlush);
+    }
+    None
+  }
+
+  /// Process an event, message or timer received by the loop, then render the terminal. It
+  /// returns `false` if the editor is quit.
+  ///
+  /// NOTE: It's the iteration shared by the blocking [`run`](EventLoop::run) and the non-blocking
+  /// [`tick`](EventLoop::tick).
+  async fn step(&mut self, event: LoopEvent) -> IoResult<bool> {
+    match event {
+      LoopEvent::Input(event) => self.process_event(event).await,
+      LoopEvent::Typeahead(event) => self.process_event(Some(Ok(event))).await,
+      LoopEvent::Worker(worker_msg) => self.process_worker_notify(worker_msg).await,
+      LoopEvent::JsRequest(js_req) => self.process_js_runtime_request(js_req).await,
+      LoopEvent::JsResponse(js_resp) => self.process_js_runtime_response(js_resp).await,
+      LoopEvent::EscapeTimeout => self.process_escape_timeout().await,
+      LoopEvent::KeymapTimeout => self.process_keymaps(),
+      LoopEvent::CursorWord => self.process_cursor_word().await,
+      // The idle tasks don't change the UI, and don't reset the idle time.
+      LoopEvent::ViewportPrefetch => {
+        self.process_viewport_prefetch().await;
+        return Ok(true);
+      }
+      LoopEvent::UndoFlush => {
+        self.process_undo_flush().await;
+        return Ok(true);
+      }
+      LoopEvent::Cancelled => {
+        self.process_cancellation_notify().await;
+        return Ok(false);
+      }
+    }
+    self.last_active = Instant::now();
+
+    // Update terminal
+    self.render()?;
+    Ok(true)
+  }
+
+  /// Process all the pending events, messages and timers without waiting, then render the
+  /// terminal. It returns `false` if the editor is quit.
+  ///
+  /// NOTE: It's the non-blocking alternative of [`run`](EventLoop::run), for the embedded editor
+  /// driven by the host application, see [`Editor`](crate::evloop::embed::Editor).
+  pub async fn tick(&mut self) -> IoResult<bool> {
+    while let Some(event) = self.try_next_event() {
+      if !self.step(event).await? {
+        return Ok(false);
+      }
+    }
+
+    // Update terminal
+    self.render()?;
+    Ok(true)
   }
 
   /// Running the loop, it repeatedly do following steps:
@@ -412,44 +1440,85 @@ impl EventLoop {
   /// 1. Receives several things:
   ///    1. User keyboard/mouse events.
   ///    2. Messages sent from workers.
-  ///    3. Cancellation request (which tells this event loop to quit).
+  ///    3. Debounced timers, i.e. cursor word highlighting, key mappings timeout, the held `Esc`
+  ///       timeout.
+  ///    4. Idle timers, i.e. viewports prefetch, undo files flush.
+  ///    5. Events typed ahead while the event loop was blocked.
+  ///    6. Cancellation request (which tells this event loop to quit).
   /// 2. Use the editing state (FSM) to handle the event.
   /// 3. Render the terminal.
   pub async fn run(&mut self) -> IoResult<()> {
-    let mut reader = EventStream::new();
-    loop {
-      tokio::select! {
-        // Receive keyboard/mouse events
-        event = reader.next() => {
-          self.process_event(event).await;
-        }
-        // Receive notification from workers
-        worker_msg = self.master_recv_from_worker.recv() => {
-          self.process_worker_notify(worker_msg).await;
-        }
-        // Receive notification from js runtime
-        js_req = self.master_recv_from_js_runtime.recv() => {
-            self.process_js_runtime_request(js_req).await;
-        }
-        js_resp = self.js_runtime_tick_queue.recv() => {
-            self.process_js_runtime_response(js_resp).await;
-        }
-        // Receive cancellation notify
-        _ = self.cancellation_token.cancelled() => {
-          self.process_cancellation_notify().await;
-          // let _ = self.master_send_to_js_worker.send(EventLoopToJsRuntimeMessage::Shutdown(jsmsg::Dummy::default())).await;
-          break;
+    // The `SIGINT` signal interrupts current operation instead of killing the editor.
+    let interrupt = self.interrupt.clone();
+    self
+      .detached_tasks
+      .spawn(TaskKind::Signal, "SIGINT", async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+          interrupt.interrupt();
         }
-      }
+      });
 
-      // Update terminal
-      self.render()?;
+    loop {
+      let event = self.next_event().await;
+      if !self.step(event).await? {
+        break;
+      }
     }
 
     Ok(())
   }
 
   fn render(&mut self) -> IoResult<()> {
+    // Sync long-running tasks' progress to the notification widget.
+    self
+      .state
+      .try_write_for(envar::MUTEX_TIMEOUT())
+      .unwrap()
+      .progress_mut()
+      .sync_widget(&mut self.tree.try_write_for(envar::MUTEX_TIMEOUT()).unwrap());
+
+    // Reload the user config, i.e. `:config reload`.
+    self.process_config_reload();
+
+    // Source the scripts, i.e. `:source`.
+    self.process_scripts();
+
+    // Start the requested git status refresh.
+    self.process_git_status();
+
+    // Save the added spell words, and start the requested spell file reading.
+    self.process_spell();
+
+    // Start the requested search, i.e. `:grep`.
+    self.process_grep();
+
+    // Run the shell commands to read, i.e. `:r !{cmd}`.
+    self.process_shell_reads();
+
+    // Load the file for the quickfix preview.
+    self.process_preview_load();
+
+    // Quit the editor, i.e. `:qa`.
+    self.process_quit();
+
+    // Populate the opened fuzzy finder, and pass the closed ones to the JS callbacks.
+    self.process_finder();
+
+    // Emit the changed events to the JS callbacks.
+    self.process_autocmds();
+
+    // Sync editing mode and the JS evaluated format to the statuslines.
+    self.sync_statuslines();
+
+    // Sync the line number gutters' width to the buffers' lines, and the sign columns to the
+    // placed signs.
+    wlock!(self.tree).sync_gutters();
+
+    // Sync the shown messages to the message box, after the scripts and JS callbacks, since they
+    // can push messages.
+    let messages = rlock!(self.state).messages().clone();
+    wlock!(messages).sync_widget(&mut wlock!(self.tree));
+
     // Draw UI components to the canvas.
     self
       .tree
@@ -464,12 +1533,515 @@ impl EventLoop {
       .unwrap()
       .shade();
 
+    // The 'termpaste' option is changed.
+    let (bracketed_paste, synchronized_output) = {
+      let tree = rlock!(self.tree);
+      (tree.termpaste(), tree.termsync())
+    };
+    if let Some(enabled) = self.bracketed_paste {
+      if enabled != bracketed_paste {
+        if bracketed_paste {
+          queue!(self.writer, EnableBracketedPaste)?;
+        } else {
+          queue!(self.writer, DisableBracketedPaste)?;
+        }
+        self.bracketed_paste = Some(bracketed_paste);
+      }
+    }
+
+    // The terminal title follows the 'title' and 'titlestring' options, i.e. the buffer name and
+    // the modified flag. The original title is saved on the terminal's title stack before the
+    // first change, and restored when the 'title' is off or the TUI is shutdown.
+    if self.bracketed_paste.is_some() {
+      let mode = rlock!(self.state).mode();
+      let title = rlock!(self.tree).render_title(mode);
+      if title != self.title {
+        match &title {
+          Some(title) => {
+            if self.title.is_none() {
+              write!(self.writer, "{}", PUSH_TITLE)?;
+            }
+            queue!(self.writer, crossterm::terminal::SetTitle(title))?;
+          }
+          None => write!(self.writer, "{}", POP_TITLE)?,
+        }
+        self.title = title;
+      }
+    }
+
+    // The terminal shows the whole frame at once with the synchronized output.
+    let synchronized_output = synchronized_output && !shader.is_empty();
+    if synchronized_output {
+      queue!(self.writer, crossterm::terminal::BeginSynchronizedUpdate)?;
+    }
     self.queue_shader(shader)?;
+    if synchronized_output {
+      queue!(self.writer, crossterm::terminal::EndSynchronizedUpdate)?;
+    }
     self.writer.flush()?;
 
     Ok(())
   }
 
+  fn process_scripts(&mut self) {
+    let scripts = wlock!(self.state).take_scripts();
+    for script in scripts {
+      let filename = script.to_string_lossy().to_string();
+      trace!("Source script {:?}", filename);
+      if is_lua_script(&script) {
+        if let Err(e) = self.execute_lua(&script) {
+          error!("Failed to source {:?}: {:?}", filename, e);
+          let messages = rlock!(self.state).messages().clone();
+          wlock!(messages).push(MessageLevel::Error, &e.to_string());
+        }
+      } else if let Err(e) = self.js_runtime.execute_module(&filename, None) {
+        error!("Failed to source {:?}: {:?}", filename, e);
+      }
+    }
+  }
+
+  /// Execute the Lua script, see [`LuaRuntime`](crate::lua::LuaRuntime).
+  #[cfg(feature = "lua")]
+  fn execute_lua(&mut self, script: &Path) -> AnyResult<()> {
+    self.interruptible(|this| this.lua_runtime.execute_file(script))
+  }
+
+  /// Execute the Lua script, it fails since the `lua` feature is not enabled.
+  #[cfg(not(feature = "lua"))]
+  fn execute_lua(&mut self, script: &Path) -> AnyResult<()> {
+    Err(AnyErr::msg(format!(
+      "E319: Lua support is not enabled, can't source {:?}",
+      script
+    )))
+  }
+
+  /// Cancel all the timers of js runtime, i.e. when the js context is torn down by a config
+  /// reload.
+  fn cancel_js_timers(&mut self) {
+    for (timer_id, task_id) in self.js_timers.drain() {
+      trace!("Cancel js timer {:?}", timer_id);
+      self.detached_tasks.cancel(task_id);
+    }
+  }
+
+  /// Run the shell commands of `:r !{cmd}` on async tasks, the output is inserted once it's
+  /// finished, see [`read`](crate::state::ex::read).
+  fn process_shell_reads(&mut self) {
+    let requests = wlock!(self.state).take_shell_reads();
+    for request in requests {
+      let title = format!(":r !{}", request.command);
+      trace!("Start shell read {:?}", request);
+      let progress_id = wlock!(self.state).progress_mut().begin(&title, false);
+      let worker_send_to_master = self.worker_send_to_master.clone();
+      self
+        .detached_tasks
+        .spawn(TaskKind::Job, &title, async move {
+          let result = read::run_shell(&request.command).await;
+          let _ = worker_send_to_master
+            .send(WorkerToMasterMessage::ShellReadDone(ShellReadDone::new(
+              request,
+              progress_id,
+              result,
+            )))
+            .await;
+        });
+    }
+  }
+
+  /// The shell command of `:r !{cmd}` is finished, the output is inserted into the buffer, or the
+  /// error is reported.
+  fn finish_shell_read(&mut self, msg: ShellReadDone) {
+    wlock!(self.state).progress_mut().end(msg.progress_id);
+    let bytes = match msg.result {
+      Ok(bytes) => bytes,
+      Err(e) => {
+        error!("Failed to read shell command {:?}:{:?}", msg.request, e);
+        let messages = rlock!(self.state).messages().clone();
+        wlock!(messages).push(MessageLevel::Error, &e);
+        return;
+      }
+    };
+    let buffer = match rlock!(self.buffers).get(&msg.request.buffer_id) {
+      Some(buffer) => buffer.clone(),
+      None => return,
+    };
+    read::read_lines(
+      &mut wlock!(buffer),
+      msg.request.line_idx,
+      &read::decode(&bytes),
+    );
+    // NOTE: The buffer lock must be released before syncing viewport.
+    self.sync_viewports(&buffer);
+  }
+
+  /// Load the file of the quickfix preview on a blocking task with the [`BufferLoader`], it's
+  /// loaded into the preview buffer and previewed once it's finished, see
+  /// [`quickfix`](crate::state::ex::quickfix). The previous loading is cancelled.
+  fn process_preview_load(&mut self) {
+    let path = match wlock!(self.state).take_preview_load() {
+      Some(path) => path,
+      None => return,
+    };
+    if let Some((_, task_id)) = self.preview_load.take() {
+      self.detached_tasks.cancel(task_id);
+    }
+    trace!("Load preview {:?}", path);
+    let (fs, encoding) = {
+      let buffers = rlock!(self.buffers);
+      (
+        buffers.fs().clone(),
+        buffers.local_options().file_encoding(),
+      )
+    };
+    let worker_send_to_master = self.worker_send_to_master.clone();
+    let task_name = path.to_string_lossy().to_string();
+    let task_path = path.clone();
+    let task_id = self
+      .detached_tasks
+      .spawn_blocking(TaskKind::Loader, &task_name, move |token| {
+        let mut reader = match fs.open(&task_path) {
+          Ok(reader) => reader,
+          Err(e) => {
+            let _ = worker_send_to_master.blocking_send(WorkerToMasterMessage::PreviewLoaded(
+              PreviewLoaded::new(task_path, Err(e.to_string())),
+            ));
+            return;
+          }
+        };
+        let mut loader = BufferLoader::new(encoding, 0);
+        let mut text = String::new();
+        let result = loop {
+          // The preview is changed, or the editor is quit.
+          if token.is_cancelled() {
+            return;
+          }
+          let mut bytes = vec![0_u8; defaults::buf::LOAD_CHUNK_SIZE];
+          match reader.read(&mut bytes) {
+            Ok(0) => {
+              text.push_str(&loader.finish());
+              break Ok((text, loader.file_format()));
+            }
+            Ok(n) => text.push_str(&loader.push(&bytes[..n])),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => { /* Retry */ }
+            Err(e) => break Err(e.to_string()),
+          }
+        };
+        let _ = worker_send_to_master.blocking_send(WorkerToMasterMessage::PreviewLoaded(
+          PreviewLoaded::new(task_path, result),
+        ));
+      });
+    self.preview_load = Some((path, task_id));
+  }
+
+  /// The file of the quickfix preview is loaded, it's set to the preview buffer and previewed
+  /// again, unless another file is requested since then.
+  fn finish_preview_load(&mut self, msg: PreviewLoaded) {
+    if !self
+      .preview_load
+      .as_ref()
+      .is_some_and(|(path, _)| *path == msg.path)
+    {
+      return;
+    }
+    self.preview_load = None;
+    match msg.result {
+      Ok((text, file_format)) => {
+        let buffer = wlock!(self.buffers).set_preview_buffer(&msg.path, &text, file_format);
+        // NOTE: The buffer lock must be released before syncing viewport.
+        self.sync_viewports(&buffer);
+        quickfix::preview_entry(&self.tree, &self.buffers);
+      }
+      Err(e) => error!("Failed to load preview {:?}:{:?}", msg.path, e),
+    }
+  }
+
+  fn process_quit(&mut self) {
+    if wlock!(self.state).take_quit() {
+      trace!("Quit editor");
+      self.cancellation_token.cancel();
+    }
+  }
+
+  fn process_config_reload(&mut self) {
+    if !wlock!(self.state).take_config_reload() {
+      return;
+    }
+    let config_file = match envar::CONFIG_FILE_PATH() {
+      Some(config_file) => config_file,
+      None => return,
+    };
+    let filename = config_file.to_string_lossy().to_string();
+    trace!("Reload user config {:?}", filename);
+
+    // Tear down the plugin state registered by the old config.
+    {
+      let mut state = wlock!(self.state);
+      state.keymaps_mut().clear();
+      state.autocmds_mut().clear();
+    }
+    wlock!(self.tree).highlights_mut().reset();
+    self.js_runtime.reset_plugins();
+    self.cancel_js_timers();
+
+    let result = self.interruptible(|this| this.js_runtime.execute_module(&filename, None));
+    let messages = rlock!(self.state).messages().clone();
+    match result {
+      Ok(()) => wlock!(messages).push(
+        MessageLevel::Info,
+        &format!("Config reloaded: {}", filename),
+      ),
+      Err(e) => {
+        error!("Failed to reload {:?}: {:?}", filename, e);
+        wlock!(messages).push(MessageLevel::Error, &e.to_string());
+      }
+    }
+  }
+
+  fn process_git_status(&mut self) {
+    if !wlock!(self.state).git_mut().start_refresh() {
+      return;
+    }
+    let dir = match std::env::current_dir() {
+      Ok(dir) => dir,
+      Err(e) => {
+        error!("Failed to get current directory: {:?}", e);
+        wlock!(self.state).git_mut().finish_refresh(None);
+        return;
+      }
+    };
+    trace!("Refresh git status in {:?}", dir);
+    let worker_send_to_master = self.worker_send_to_master.clone();
+    let task_name = dir.to_string_lossy().to_string();
+    self
+      .detached_tasks
+      .spawn(TaskKind::Git, &task_name, async move {
+        let status = git::fetch_git_status(&dir).await;
+        let _ = worker_send_to_master
+          .send(WorkerToMasterMessage::GitStatusDone(GitStatusDone::new(
+            status,
+          )))
+          .await;
+      });
+  }
+
+  fn process_spell(&mut self) {
+    let (reload, unsaved) = {
+      let mut buffers = wlock!(self.buffers);
+      let spell = buffers.spell_mut();
+      (spell.take_reload(), spell.take_unsaved())
+    };
+    let spell_file = spell_file();
+    let task_name = spell_file.to_string_lossy().to_string();
+    if !unsaved.is_empty() {
+      trace!("Save spell words {:?} to {:?}", unsaved, spell_file);
+      let spell_file = spell_file.clone();
+      self
+        .detached_tasks
+        .spawn_write(TaskKind::Spell, &task_name, async move {
+          if let Err(e) = append_spell_words(&spell_file, &unsaved).await {
+            error!("Failed to save spell words {:?}:{:?}", spell_file, e);
+          }
+        });
+    }
+    if reload {
+      trace!("Read spell file {:?}", spell_file);
+      let worker_send_to_master = self.worker_send_to_master.clone();
+      self
+        .detached_tasks
+        .spawn(TaskKind::Spell, &task_name, async move {
+          let msg = match tokio::fs::read_to_string(&spell_file).await {
+            Ok(text) => SpellLoaded::new(text, None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => SpellLoaded::default(),
+            Err(e) => SpellLoaded::new(String::new(), Some(e.to_string())),
+          };
+          let _ = worker_send_to_master
+            .send(WorkerToMasterMessage::SpellLoaded(msg))
+            .await;
+        });
+    }
+  }
+
+  fn process_grep(&mut self) {
+    let request = wlock!(self.state).take_grep();
+    if let Some(request) = request {
+      self.start_grep(request, None);
+    }
+  }
+
+  /// Start the search on a blocking task, the running search is cancelled. The quickfix list is
+  /// replaced, and the matches are appended batch by batch, see [`grep`](crate::state::grep).
+  fn start_grep(&mut self, request: GrepRequest, future_id: Option<JsFutureId>) {
+    if let Some((grep_id, task_id, _)) = self.grep.take() {
+      trace!("Cancel grep {:?}", grep_id);
+      self.detached_tasks.cancel(task_id);
+    }
+    let grep_id = grep::next_grep_id();
+    trace!("Start grep {:?}: {:?}", grep_id, request.options);
+    let title = format!(":grep {}", request.options.pattern);
+    wlock!(self.buffers).quickfix_mut().reset(&title);
+    quickfix::refresh_quickfix_window(&self.tree, &self.buffers);
+    wlock!(self.tree)
+      .set_statusline_component(grep::COMPONENT_NAME, &GrepSummary::default().progress());
+
+    let worker_send_to_master = self.worker_send_to_master.clone();
+    let options = request.options.clone();
+    let task_id = self
+      .detached_tasks
+      .spawn_blocking(TaskKind::Grep, &title, move |token| {
+        let result = grep::run_grep(
+          &options,
+          || token.is_cancelled(),
+          |entries, summary| {
+            let msg = WorkerToMasterMessage::GrepBatch(GrepBatch::new(grep_id, entries, summary));
+            // The event loop has exited.
+            worker_send_to_master.blocking_send(msg).is_ok()
+          },
+        );
+        let _ = worker_send_to_master.blocking_send(WorkerToMasterMessage::GrepDone(
+          GrepDone::new(grep_id, future_id, result),
+        ));
+      });
+    self.grep = Some((grep_id, task_id, request));
+  }
+
+  fn process_finder(&mut self) {
+    let results = wlock!(self.state).take_finder_results();
+    for result in results {
+      self.finish_finder(result);
+    }
+
+    let opened = {
+      let mut state = wlock!(self.state);
+      state
+        .finder_mut()
+        .as_mut()
+        .and_then(|(finder, _, _)| finder.take_source().map(|source| (finder.id(), source)))
+    };
+    if let Some((finder_id, source)) = opened {
+      self.start_finder(finder_id, source);
+    }
+  }
+
+  /// Populate the fuzzy finder with the builtin source. The buffers are listed at once, while the
+  /// files are walked on a blocking task and sent batch by batch, see
+  /// [`finder`](crate::state::finder).
+  fn start_finder(&mut self, finder_id: FinderId, source: FinderSource) {
+    trace!("Start finder {:?}: {:?}", finder_id, source);
+    match source {
+      FinderSource::Items => { /* Pushed by JS */ }
+      FinderSource::Buffers => {
+        let items: Vec<FinderItem> = rlock!(self.buffers)
+          .list(false)
+          .iter()
+          .map(|info| {
+            FinderItem::new(
+              info.name.as_deref().unwrap_or("[No Name]"),
+              &format!("#{}", info.id),
+            )
+          })
+          .collect();
+        FinderStateful::populate(&mut wlock!(self.state), &self.tree, finder_id, items, true);
+      }
+      FinderSource::Files(dir) => {
+        if let Some((finder_id, task_id)) = self.finder.take() {
+          trace!("Cancel finder {:?}", finder_id);
+          self.detached_tasks.cancel(task_id);
+        }
+        let worker_send_to_master = self.worker_send_to_master.clone();
+        let title = format!("finder {}", dir.display());
+        let task_id = self
+          .detached_tasks
+          .spawn_blocking(TaskKind::Finder, &title, move |token| {
+            finder::walk_files(
+              &dir,
+              || token.is_cancelled(),
+              |items| {
+                let msg =
+                  WorkerToMasterMessage::FinderBatch(FinderBatch::new(finder_id, items, false));
+                // The event loop has exited.
+                worker_send_to_master.blocking_send(msg).is_ok()
+              },
+            );
+            let _ = worker_send_to_master.blocking_send(WorkerToMasterMessage::FinderBatch(
+              FinderBatch::new(finder_id, vec![], true),
+            ));
+          });
+        self.finder = Some((finder_id, task_id));
+      }
+    }
+  }
+
+  /// The fuzzy finder is closed, its file walk is cancelled and the JS callback is called.
+  fn finish_finder(&mut self, result: FinderResult) {
+    trace!(
+      "Finder {:?} is closed: {:?}",
+      result.finder_id,
+      result.accepted
+    );
+    if self
+      .finder
+      .is_some_and(|(finder_id, _)| finder_id == result.finder_id)
+    {
+      if let Some((_, task_id)) = self.finder.take() {
+        self.detached_tasks.cancel(task_id);
+      }
+    }
+    // NOTE: The JS callback is called without locking the editor, since it may access it.
+    self.interruptible(|this| this.js_runtime.call_finder(result));
+  }
+
+  fn process_autocmds(&mut self) {
+    let matched = {
+      let mut state = wlock!(self.state);
+      let mode = state.mode();
+      let autocmds = state.autocmds_mut();
+      autocmds.watch(mode, &self.tree, &self.buffers);
+      autocmds.take_matched()
+    };
+
+    // NOTE: The JS callbacks are called without locking the editor, since they may access it.
+    for (id, args) in matched {
+      trace!("Emit autocmd {:?} to {:?}", args, id);
+      self.interruptible(|this| this.js_runtime.emit_autocmd(id, &args));
+    }
+  }
+
+  fn sync_statuslines(&mut self) {
+    let mode = rlock!(self.state).mode();
+    let window_ids: Vec<(TreeNodeId, bool)> = {
+      let tree = rlock!(self.tree);
+      tree
+        .window_ids()
+        .iter()
+        .filter_map(|id| match tree.node(id) {
+          Some(TreeNode::Window(window)) => Some((*id, window.statusline_evaluated())),
+          _ => None,
+        })
+        .collect()
+    };
+
+    // NOTE: The JS callback is evaluated without locking the tree, since it may access the tree.
+    let statuslines: Vec<(TreeNodeId, Option<String>)> = window_ids
+      .into_iter()
+      .map(|(id, evaluated)| {
+        let evaluated = if evaluated {
+          self.js_runtime.eval_statusline(id)
+        } else {
+          None
+        };
+        (id, evaluated)
+      })
+      .collect();
+
+    let mut tree = wlock!(self.tree);
+    let components = tree.statusline_components().clone();
+    for (id, evaluated) in statuslines {
+      if let Some(TreeNode::Window(window)) = tree.node_mut(&id) {
+        window.sync_statusline(mode, evaluated, &components);
+      }
+    }
+  }
+
   /// Put (render) canvas shader.
   fn queue_shader(&mut self, shader: Shader) -> IoResult<()> {
     for shader_command in shader.iter() {
@@ -531,8 +2103,13 @@ impl EventLoop {
       out,
       DisableMouseCapture,
       DisableFocusChange,
+      DisableBracketedPaste,
       crossterm::terminal::LeaveAlternateScreen,
     )?;
+    if self.title.is_some() {
+      write!(out, "{}", POP_TITLE)?;
+      out.flush()?;
+    }
 
     if crossterm::terminal::is_raw_mode_enabled()? {
       crossterm::terminal::disable_raw_mode()?;
@@ -541,3 +2118,118 @@ impl EventLoop {
     Ok(())
   }
 }
+
+/// Run the fetch command and send its stdout to master chunk by chunk. Returns `false` if the event
+/// loop has exited, or the error message (i.e. the stderr) if the command failed.
+async fn fetch_remote(
+  buffer_id: BufferId,
+  program: &str,
+  args: &[String],
+  worker_send_to_master: &Sender<WorkerToMasterMessage>,
+) -> Result<bool, String> {
+  use tokio::io::AsyncReadExt;
+
+  let mut child = tokio::process::Command::new(program)
+    .args(args)
+    .stdin(std::process::Stdio::null())
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .kill_on_drop(true)
+    .spawn()
+    .map_err(|e| format!("{}: {}", program, e))?;
+  let mut stdout = child.stdout.take().unwrap();
+  let mut stderr = child.stderr.take().unwrap();
+
+  // NOTE: The stdout and stderr are drained concurrently, otherwise the command is blocked once
+  // the stderr pipe is full. The stdout is closed once it's finished, thus the command is not
+  // blocked on writing it either.
+  let read_stdout = async move {
+    loop {
+      let mut bytes = vec![0_u8; defaults::buf::LOAD_CHUNK_SIZE];
+      let n = stdout.read(&mut bytes).await.map_err(|e| e.to_string())?;
+      if n == 0 {
+        return Ok(true);
+      }
+      bytes.truncate(n);
+      let msg = WorkerToMasterMessage::BufferLoadedBytes(BufferLoadedBytes::new(buffer_id, bytes));
+      if worker_send_to_master.send(msg).await.is_err() {
+        return Ok(false);
+      }
+    }
+  };
+  let read_stderr = async {
+    let mut bytes = vec![];
+    stderr.read_to_end(&mut bytes).await.map(|_| bytes)
+  };
+  let (sent, stderr): (Result<bool, String>, _) = tokio::join!(read_stdout, read_stderr);
+  if !sent? {
+    return Ok(false);
+  }
+  let status = child.wait().await.map_err(|e| e.to_string())?;
+  if status.success() {
+    Ok(true)
+  } else {
+    Err(format!(
+      "{} {}: {}",
+      program,
+      status,
+      String::from_utf8_lossy(&stderr.unwrap_or_default()).trim()
+    ))
+  }
+}
+
+/// Append the spell words to the user spell dictionary file, the parent directory is created if
+/// it doesn't exist.
+async fn append_spell_words(spell_file: &Path, words: &[String]) -> IoResult<()> {
+  use tokio::io::AsyncWriteExt;
+
+  if let Some(parent) = spell_file.parent() {
+    tokio::fs::create_dir_all(parent).await?;
+  }
+  let mut file = tokio::fs::OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(spell_file)
+    .await?;
+  file
+    .write_all(SpellDictionary::format(words).as_bytes())
+    .await?;
+  file.flush().await
+}
+
+// Whether the script is executed by the Lua runtime, i.e. the `.lua` file.
+fn is_lua_script(script: &Path) -> bool {
+  script.extension().is_some_and(|ext| ext == "lua")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn fetch_remote1() {
+    if cfg!(target_os = "windows") {
+      return;
+    }
+    let (sender, mut receiver) = channel(100);
+    let buffer_id: BufferId = 1;
+
+    // The stderr is larger than the pipe buffer.
+    let script = "head -c 200000 /dev/zero >&2; echo hello".to_string();
+    let args = vec!["-c".to_string(), script];
+    assert_eq!(
+      fetch_remote(buffer_id, "sh", &args, &sender).await,
+      Ok(true)
+    );
+    match receiver.recv().await {
+      Some(WorkerToMasterMessage::BufferLoadedBytes(msg)) => assert_eq!(msg.bytes, b"hello\n"),
+      _ => unreachable!(),
+    }
+
+    let args = vec!["-c".to_string(), "echo oops >&2; exit 3".to_string()];
+    let e = fetch_remote(buffer_id, "sh", &args, &sender)
+      .await
+      .unwrap_err();
+    assert!(e.ends_with("oops"));
+  }
+}
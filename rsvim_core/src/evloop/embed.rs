@@ -0,0 +1,126 @@
+//! Embedding API, i.e. run the editor core inside another Rust application.
+//!
+//! The [`Editor`] is constructed with injected IO ([`EditorIo`]):
+//!
+//! - Input stream: The keyboard/mouse/resize events.
+//! - Render backend: The terminal commands are written to it on each render. The host
+//!   application can also read the [`Canvas`](crate::ui::canvas::Canvas) frame directly.
+//! - File system provider: The buffers read files through it, see [`FsProvider`].
+//! - Undo directory: The undo history is saved in it, see [`undofile`](crate::buf::undofile).
+//!
+//! Then the host application drives it with [`Editor::tick`], instead of the blocking
+//! [`EventLoop::run`].
+//!
+//! NOTE: The embedded editor doesn't touch the terminal (i.e. raw mode, alternate screen, etc),
+//! it's the host application's job.
+
+use crate::buf::undofile::UNDO_DIR;
+use crate::buf::{FsProvider, FsProviderArc, StdFs};
+use crate::cart::U16Size;
+use crate::cli::CliOpt;
+use crate::envar;
+use crate::evloop::EventLoop;
+use crate::js::SnapshotData;
+use crate::res::IoResult;
+
+use crossterm::event::{Event, EventStream};
+use futures::Stream;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// The input events stream.
+pub type InputStream = Pin<Box<dyn Stream<Item = IoResult<Event>>>>;
+
+/// The injected IO for the editor.
+pub struct EditorIo {
+  /// Canvas size.
+  pub size: U16Size,
+  /// Input events stream, the editor quits when it's exhausted.
+  pub input: InputStream,
+  /// Render backend.
+  pub backend: Box<dyn Write>,
+  /// File system provider.
+  pub fs: FsProviderArc,
+  /// The directory of the undo files, the undo history is not saved if it's `None`.
+  pub undo_dir: Option<PathBuf>,
+  /// Whether watch the `Ctrl-C` key from terminal while the editor is blocked, see
+  /// [`InterruptWatcher`](crate::interrupt::InterruptWatcher).
+  pub watch_terminal: bool,
+}
+
+impl EditorIo {
+  /// The IO of current terminal, i.e. for the `rsvim` command line.
+  pub fn terminal() -> IoResult<Self> {
+    let (cols, rows) = crossterm::terminal::size()?;
+    Ok(EditorIo {
+      size: U16Size::new(cols, rows),
+      input: Box::pin(EventStream::new()),
+      backend: Box::new(BufWriter::new(std::io::stdout())),
+      fs: Arc::new(StdFs),
+      undo_dir: Some(envar::DATA_DIR_PATH().join(UNDO_DIR)),
+      watch_terminal: true,
+    })
+  }
+
+  /// The IO without terminal, the events are read from `input`, the rendered terminal commands
+  /// are written to `backend`, and files are read from `fs`. The undo history is not saved.
+  pub fn new(
+    size: U16Size,
+    input: InputStream,
+    backend: Box<dyn Write>,
+    fs: Arc<dyn FsProvider>,
+  ) -> Self {
+    EditorIo {
+      size,
+      input,
+      backend,
+      fs,
+      undo_dir: None,
+      watch_terminal: false,
+    }
+  }
+}
+
+/// The embedded editor.
+pub struct Editor {
+  event_loop: EventLoop,
+}
+
+impl Editor {
+  /// Make new editor, it opens the files in `cli_opt` and renders the first frame.
+  ///
+  /// NOTE: The user config is not loaded, the host application configures the editor through the
+  /// event loop, see [`Editor::event_loop_mut`].
+  pub fn new(cli_opt: CliOpt, snapshot: SnapshotData, io: EditorIo) -> IoResult<Self> {
+    let event_loop = EventLoop::with_io(cli_opt, snapshot, io)?;
+    let mut editor = Editor { event_loop };
+    editor.event_loop.init_buffers()?;
+    editor.event_loop.init_windows()?;
+    editor.event_loop.init_session()?;
+    editor.event_loop.init_tui_done()?;
+    Ok(editor)
+  }
+
+  /// Process all the pending input events, messages and timers (i.e. the idle tasks), then
+  /// render. It returns `false` if the editor is quit.
+  pub async fn tick(&mut self) -> IoResult<bool> {
+    self.event_loop.tick().await
+  }
+
+  /// Whether the editor is quit.
+  pub fn is_quit(&self) -> bool {
+    self.event_loop.cancellation_token.is_cancelled()
+  }
+
+  /// Get the event loop, i.e. the editor's state, buffers, widget tree and canvas.
+  pub fn event_loop(&self) -> &EventLoop {
+    &self.event_loop
+  }
+
+  /// Get the mutable event loop.
+  pub fn event_loop_mut(&mut self) -> &mut EventLoop {
+    &mut self.event_loop
+  }
+}
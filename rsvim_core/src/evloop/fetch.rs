@@ -0,0 +1,171 @@
+//! HTTP requests, i.e. the js `fetch` API.
+//!
+//! The request runs on an async task with the HTTP client, the response head and the body chunks
+//! are sent back to the js runtime once they're received, thus the body is streamed instead of
+//! buffered.
+
+use crate::js::msg::{self as jsmsg, EventLoopToJsRuntimeMessage};
+
+use reqwest::{Client, Method};
+use std::sync::OnceLock;
+use tokio::sync::mpsc::{Sender, UnboundedReceiver};
+use tracing::trace;
+
+/// Fetch ID.
+pub type FetchId = i32;
+
+/// The error message of the aborted request, the js runtime rejects with `AbortError`.
+pub const ABORTED: &str = "AbortError";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The control message sent to a running request.
+pub enum FetchControl {
+  /// Abort the request, i.e. the `AbortSignal` is aborted.
+  Abort,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The HTTP request.
+pub struct FetchRequest {
+  pub method: String,
+  pub url: String,
+  pub headers: Vec<(String, String)>,
+  pub body: Option<Vec<u8>>,
+}
+
+// The HTTP client is shared by all requests, thus the connections are reused.
+fn client() -> &'static Client {
+  static VALUE: OnceLock<Client> = OnceLock::new();
+  VALUE.get_or_init(|| {
+    Client::builder()
+      .user_agent(concat!("rsvim/", env!("CARGO_PKG_VERSION")))
+      .build()
+      .unwrap_or_default()
+  })
+}
+
+/// Parse the request method, i.e. `get`/`GET`, the standard methods are case insensitive.
+pub fn parse_method(method: &str) -> Option<Method> {
+  let upper = method.to_ascii_uppercase();
+  match upper.as_str() {
+    "DELETE" | "GET" | "HEAD" | "OPTIONS" | "POST" | "PUT" | "PATCH" => {
+      Method::from_bytes(upper.as_bytes()).ok()
+    }
+    _ => Method::from_bytes(method.as_bytes()).ok(),
+  }
+}
+
+// Wait for the abort message, it never completes if the control channel is closed.
+async fn aborted(control: &mut UnboundedReceiver<FetchControl>) {
+  match control.recv().await {
+    Some(FetchControl::Abort) => {}
+    None => std::future::pending().await,
+  }
+}
+
+/// Run the request until the response body is finished, the response head, the body chunks and
+/// the result are sent to the js runtime via `js_runtime_tick_dispatcher`.
+///
+/// NOTE: The result is the error message, it's [`ABORTED`] if the request is aborted.
+pub async fn run_fetch(
+  fetch_id: FetchId,
+  request: FetchRequest,
+  mut control: UnboundedReceiver<FetchControl>,
+  js_runtime_tick_dispatcher: Sender<EventLoopToJsRuntimeMessage>,
+) {
+  let send = |msg: EventLoopToJsRuntimeMessage| {
+    let js_runtime_tick_dispatcher = js_runtime_tick_dispatcher.clone();
+    async move { js_runtime_tick_dispatcher.send(msg).await.is_ok() }
+  };
+  let error = fetch(fetch_id, request, &mut control, &send).await.err();
+  trace!("Fetch done:{:?}, error:{:?}", fetch_id, error);
+  send(EventLoopToJsRuntimeMessage::FetchDone(
+    jsmsg::FetchDone::new(fetch_id, error),
+  ))
+  .await;
+}
+
+async fn fetch<F, Fut>(
+  fetch_id: FetchId,
+  request: FetchRequest,
+  control: &mut UnboundedReceiver<FetchControl>,
+  send: &F,
+) -> Result<(), String>
+where
+  F: Fn(EventLoopToJsRuntimeMessage) -> Fut,
+  Fut: std::future::Future<Output = bool>,
+{
+  let method = parse_method(&request.method)
+    .ok_or_else(|| format!("Invalid request method {:?}", request.method))?;
+  let mut builder = client().request(method, &request.url);
+  for (name, value) in request.headers.iter() {
+    builder = builder.header(name.as_str(), value.as_str());
+  }
+  if let Some(body) = request.body {
+    builder = builder.body(body);
+  }
+  trace!(
+    "Fetch:{:?}, {:?} {:?}",
+    fetch_id,
+    request.method,
+    request.url
+  );
+
+  let mut response = tokio::select! {
+    response = builder.send() => response.map_err(|e| e.to_string())?,
+    _ = aborted(control) => return Err(ABORTED.to_string()),
+  };
+  let status = response.status();
+  let headers = response
+    .headers()
+    .iter()
+    .map(|(name, value)| {
+      (
+        name.as_str().to_string(),
+        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+      )
+    })
+    .collect();
+  let head = jsmsg::FetchHead::new(
+    fetch_id,
+    status.as_u16(),
+    status.canonical_reason().unwrap_or("").to_string(),
+    response.url().to_string(),
+    headers,
+  );
+  if !send(EventLoopToJsRuntimeMessage::FetchHead(head)).await {
+    return Ok(());
+  }
+
+  loop {
+    let chunk = tokio::select! {
+      chunk = response.chunk() => chunk.map_err(|e| e.to_string())?,
+      _ = aborted(control) => return Err(ABORTED.to_string()),
+    };
+    match chunk {
+      Some(chunk) => {
+        let chunk = jsmsg::FetchChunk::new(fetch_id, chunk.to_vec());
+        if !send(EventLoopToJsRuntimeMessage::FetchChunk(chunk)).await {
+          return Ok(());
+        }
+      }
+      None => return Ok(()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_method1() {
+    assert_eq!(parse_method("get"), Some(Method::GET));
+    assert_eq!(parse_method("Post"), Some(Method::POST));
+    assert_eq!(parse_method("PROPFIND").unwrap().as_str(), "PROPFIND");
+    // The non-standard methods are case sensitive.
+    assert_eq!(parse_method("propfind").unwrap().as_str(), "propfind");
+    assert_eq!(parse_method("GE T"), None);
+    assert_eq!(parse_method(""), None);
+  }
+}
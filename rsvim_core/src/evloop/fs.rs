@@ -0,0 +1,455 @@
+//! File system operations, i.e. the `Rsvim.fs` APIs.
+//!
+//! The operations run on async tasks with `tokio::fs`, thus the plugins never block the editor.
+//! The results are sent back to the js runtime to resolve the promises.
+
+use crate::buf::path::absolute_path;
+use crate::buf::template::glob_match;
+
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::trace;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The file system operation.
+pub enum FsOp {
+  /// Read the file as UTF-8 text.
+  ReadFile(PathBuf),
+  /// Write (or append) the data to the file, it's created if not exists.
+  WriteFile {
+    path: PathBuf,
+    data: Vec<u8>,
+    append: bool,
+  },
+  /// Get the metadata, the symbolic link is followed.
+  Stat(PathBuf),
+  /// Whether the path exists.
+  Exists(PathBuf),
+  /// List the directory entries.
+  ReadDir(PathBuf),
+  /// Create the directory, and its parents if `recursive`.
+  Mkdir { path: PathBuf, recursive: bool },
+  /// Remove the file or directory, and its contents if `recursive`.
+  Remove { path: PathBuf, recursive: bool },
+  /// Find the first of the `names` in the `start` directory and its ancestors, see [`find_up`].
+  FindUp {
+    names: Vec<String>,
+    start: PathBuf,
+    kind: Option<FsEntryKind>,
+  },
+  /// Walk the directory recursively, see [`walk`].
+  Walk { root: PathBuf, options: WalkOptions },
+}
+
+impl FsOp {
+  /// Whether the operation changes the file system, i.e. it's not cancelled when the editor quits,
+  /// see [`TaskRegistry::spawn_write`](crate::evloop::task::TaskRegistry::spawn_write).
+  pub fn is_write(&self) -> bool {
+    matches!(
+      self,
+      FsOp::WriteFile { .. } | FsOp::Mkdir { .. } | FsOp::Remove { .. }
+    )
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The file type.
+pub enum FsEntryKind {
+  File,
+  Directory,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// The metadata of a file.
+pub struct FsStat {
+  pub is_file: bool,
+  pub is_directory: bool,
+  pub is_symlink: bool,
+  pub size: u64,
+  /// The last modification time in milliseconds since the Unix epoch, it's `None` if the platform
+  /// doesn't support it.
+  pub mtime: Option<f64>,
+  pub readonly: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// A directory entry.
+pub struct FsDirEntry {
+  pub name: String,
+  pub is_file: bool,
+  pub is_directory: bool,
+  pub is_symlink: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The options of [`walk`].
+pub struct WalkOptions {
+  /// The glob pattern of the file paths, it's matched against the path relative to the root
+  /// directory with `/` separators, i.e. `*.rs` or `src/*.rs`. The `*` also matches `/`.
+  pub glob: Option<String>,
+  /// Whether to include the hidden files and directories, i.e. `.git`.
+  pub hidden: bool,
+  /// The max depth, the files directly in the root directory are at depth 1.
+  pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The result value of the file system operation.
+pub enum FsValue {
+  None,
+  Bool(bool),
+  Text(String),
+  Stat(FsStat),
+  Entries(Vec<FsDirEntry>),
+  Path(Option<String>),
+  Paths(Vec<String>),
+}
+
+/// Normalize the path lexically, i.e. remove the `.` components, resolve the `..` components and
+/// the redundant separators. The file system is not accessed, thus the symbolic links are not
+/// resolved.
+pub fn normalize_path(path: &Path) -> PathBuf {
+  let mut result = PathBuf::new();
+  // The normal components count in the result, the `..` can only pop them.
+  let mut depth = 0;
+  for component in path.components() {
+    match component {
+      Component::Prefix(_) | Component::RootDir => result.push(component.as_os_str()),
+      Component::CurDir => {}
+      Component::ParentDir => {
+        if depth > 0 {
+          result.pop();
+          depth -= 1;
+        } else if !result.has_root() {
+          // The relative path keeps the leading `..`, the `..` of root is root itself.
+          result.push("..");
+        }
+      }
+      Component::Normal(name) => {
+        result.push(name);
+        depth += 1;
+      }
+    }
+  }
+  if result.as_os_str().is_empty() {
+    result.push(".");
+  }
+  result
+}
+
+/// Join the paths and normalize it, the absolute path replaces the previous ones.
+pub fn join_paths<P: AsRef<Path>>(paths: &[P]) -> PathBuf {
+  let mut result = PathBuf::new();
+  for path in paths {
+    result.push(path);
+  }
+  normalize_path(&result)
+}
+
+// Convert the io error with the path, i.e. `Failed to read "a.txt": No such file or directory`.
+fn io_err(action: &str, path: &Path, e: std::io::Error) -> String {
+  format!("Failed to {} {:?}: {}", action, path, e)
+}
+
+fn to_stat(metadata: &std::fs::Metadata) -> FsStat {
+  FsStat {
+    is_file: metadata.is_file(),
+    is_directory: metadata.is_dir(),
+    is_symlink: metadata.is_symlink(),
+    size: metadata.len(),
+    mtime: metadata
+      .modified()
+      .ok()
+      .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+      .map(|duration| duration.as_millis() as f64),
+    readonly: metadata.permissions().readonly(),
+  }
+}
+
+async fn is_kind(path: &Path, kind: Option<FsEntryKind>) -> bool {
+  match tokio::fs::metadata(path).await {
+    Ok(metadata) => match kind {
+      Some(FsEntryKind::File) => metadata.is_file(),
+      Some(FsEntryKind::Directory) => metadata.is_dir(),
+      None => true,
+    },
+    Err(_) => false,
+  }
+}
+
+/// Find the first of the `names` in the `start` directory, then its parent directory, until the
+/// root directory. The `kind` filters the file type. Returns the absolute path, or `None` if it's
+/// not found.
+///
+/// It's useful to find the project root, i.e. `find_up(&["Cargo.toml", ".git"], cwd, None)`.
+pub async fn find_up(names: &[String], start: &Path, kind: Option<FsEntryKind>) -> Option<PathBuf> {
+  // The relative path is resolved to the working directory first, thus its ancestors are found.
+  let start = absolute_path(start).unwrap_or_else(|_| normalize_path(start));
+  let mut dir = Some(start);
+  while let Some(current) = dir {
+    for name in names.iter() {
+      let path = current.join(name);
+      if is_kind(&path, kind).await {
+        return Some(path);
+      }
+    }
+    dir = current.parent().map(|parent| parent.to_path_buf());
+  }
+  None
+}
+
+/// Walk the `root` directory recursively, returns the file paths (joined with `root`) that
+/// matches the options, in sorted order.
+///
+/// NOTE: The symbolic links to directories are not followed, thus it never loops.
+pub async fn walk(root: &Path, options: &WalkOptions) -> Result<Vec<String>, String> {
+  let mut files = vec![];
+  // The directories to read, with their relative paths and depths.
+  let mut dirs = vec![(root.to_path_buf(), String::new(), 1_usize)];
+  while let Some((dir, relative, depth)) = dirs.pop() {
+    let mut entries = match tokio::fs::read_dir(&dir).await {
+      Ok(entries) => entries,
+      // The root directory must be readable, while the unreadable sub directories are skipped.
+      Err(e) if dir == root => return Err(io_err("walk", root, e)),
+      Err(e) => {
+        trace!("Skip unreadable directory {:?}: {:?}", dir, e);
+        continue;
+      }
+    };
+    while let Some(entry) = entries
+      .next_entry()
+      .await
+      .map_err(|e| io_err("walk", &dir, e))?
+    {
+      let name = entry.file_name().to_string_lossy().to_string();
+      if !options.hidden && name.starts_with('.') {
+        continue;
+      }
+      let relative = if relative.is_empty() {
+        name
+      } else {
+        format!("{}/{}", relative, name)
+      };
+      let file_type = match entry.file_type().await {
+        Ok(file_type) => file_type,
+        Err(_) => continue,
+      };
+      if file_type.is_dir() {
+        if options.max_depth.map_or(true, |max| depth < max) {
+          dirs.push((entry.path(), relative, depth + 1));
+        }
+      } else if options
+        .glob
+        .as_ref()
+        .map_or(true, |glob| glob_match(glob, &relative))
+      {
+        files.push(entry.path().to_string_lossy().to_string());
+      }
+    }
+  }
+  files.sort();
+  Ok(files)
+}
+
+/// Run the file system operation, returns the error message if it fails.
+pub async fn run_fs(op: FsOp) -> Result<FsValue, String> {
+  trace!("Run fs:{:?}", op);
+  match op {
+    FsOp::ReadFile(path) => {
+      let data = tokio::fs::read(&path)
+        .await
+        .map_err(|e| io_err("read", &path, e))?;
+      Ok(FsValue::Text(String::from_utf8_lossy(&data).into_owned()))
+    }
+    FsOp::WriteFile { path, data, append } => {
+      if append {
+        use tokio::io::AsyncWriteExt;
+        let mut fp = tokio::fs::OpenOptions::new()
+          .create(true)
+          .append(true)
+          .open(&path)
+          .await
+          .map_err(|e| io_err("open", &path, e))?;
+        fp.write_all(&data)
+          .await
+          .map_err(|e| io_err("write", &path, e))?;
+        fp.flush().await.map_err(|e| io_err("write", &path, e))?;
+      } else {
+        tokio::fs::write(&path, &data)
+          .await
+          .map_err(|e| io_err("write", &path, e))?;
+      }
+      Ok(FsValue::None)
+    }
+    FsOp::Stat(path) => {
+      let metadata = tokio::fs::metadata(&path)
+        .await
+        .map_err(|e| io_err("stat", &path, e))?;
+      let mut stat = to_stat(&metadata);
+      stat.is_symlink = tokio::fs::symlink_metadata(&path)
+        .await
+        .is_ok_and(|metadata| metadata.is_symlink());
+      Ok(FsValue::Stat(stat))
+    }
+    FsOp::Exists(path) => Ok(FsValue::Bool(
+      tokio::fs::try_exists(&path).await.unwrap_or(false),
+    )),
+    FsOp::ReadDir(path) => {
+      let mut entries = tokio::fs::read_dir(&path)
+        .await
+        .map_err(|e| io_err("read directory", &path, e))?;
+      let mut result = vec![];
+      while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| io_err("read directory", &path, e))?
+      {
+        let file_type = entry.file_type().await.ok();
+        let is_symlink = file_type.is_some_and(|t| t.is_symlink());
+        // Follow the symbolic link to get its target type.
+        let (is_file, is_directory) = if is_symlink {
+          match tokio::fs::metadata(entry.path()).await {
+            Ok(metadata) => (metadata.is_file(), metadata.is_dir()),
+            Err(_) => (false, false),
+          }
+        } else {
+          (
+            file_type.is_some_and(|t| t.is_file()),
+            file_type.is_some_and(|t| t.is_dir()),
+          )
+        };
+        result.push(FsDirEntry {
+          name: entry.file_name().to_string_lossy().to_string(),
+          is_file,
+          is_directory,
+          is_symlink,
+        });
+      }
+      result.sort_by(|a, b| a.name.cmp(&b.name));
+      Ok(FsValue::Entries(result))
+    }
+    FsOp::Mkdir { path, recursive } => {
+      let result = if recursive {
+        tokio::fs::create_dir_all(&path).await
+      } else {
+        tokio::fs::create_dir(&path).await
+      };
+      result.map_err(|e| io_err("create directory", &path, e))?;
+      Ok(FsValue::None)
+    }
+    FsOp::Remove { path, recursive } => {
+      let metadata = tokio::fs::symlink_metadata(&path)
+        .await
+        .map_err(|e| io_err("remove", &path, e))?;
+      let result = if !metadata.is_dir() {
+        tokio::fs::remove_file(&path).await
+      } else if recursive {
+        tokio::fs::remove_dir_all(&path).await
+      } else {
+        tokio::fs::remove_dir(&path).await
+      };
+      result.map_err(|e| io_err("remove", &path, e))?;
+      Ok(FsValue::None)
+    }
+    FsOp::FindUp { names, start, kind } => {
+      let found = find_up(&names, &start, kind).await;
+      Ok(FsValue::Path(
+        found.map(|path| path.to_string_lossy().to_string()),
+      ))
+    }
+    FsOp::Walk { root, options } => Ok(FsValue::Paths(walk(&root, &options).await?)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use assert_fs::prelude::*;
+
+  #[test]
+  fn normalize_path1() {
+    let cases = [
+      ("a/./b/../c", "a/c"),
+      ("/a//b/", "/a/b"),
+      ("../a/../../b", "../../b"),
+      ("/../a", "/a"),
+      ("a/..", "."),
+      ("", "."),
+    ];
+    for (path, expect) in cases {
+      assert_eq!(normalize_path(Path::new(path)), PathBuf::from(expect));
+    }
+    assert_eq!(join_paths(&["a", "b/../c", "d"]), PathBuf::from("a/c/d"));
+    assert_eq!(join_paths(&["a", "/b", "c"]), PathBuf::from("/b/c"));
+  }
+
+  #[tokio::test]
+  async fn walk1() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    tmp.child("Cargo.toml").touch().unwrap();
+    tmp.child("src/main.rs").touch().unwrap();
+    tmp.child("src/ui/mod.rs").touch().unwrap();
+    tmp.child("src/ui/README.md").touch().unwrap();
+    tmp.child(".git/config").touch().unwrap();
+    let root = tmp.path();
+    let relative = |files: Vec<String>| {
+      files
+        .into_iter()
+        .map(|file| {
+          Path::new(&file)
+            .strip_prefix(root)
+            .unwrap()
+            .to_string_lossy()
+            .replace('\\', "/")
+        })
+        .collect::<Vec<_>>()
+    };
+
+    let files = walk(root, &WalkOptions::default()).await.unwrap();
+    assert_eq!(
+      relative(files),
+      vec![
+        "Cargo.toml",
+        "src/main.rs",
+        "src/ui/README.md",
+        "src/ui/mod.rs"
+      ]
+    );
+
+    let options = WalkOptions {
+      glob: Some("*.rs".to_string()),
+      hidden: false,
+      max_depth: Some(2),
+    };
+    let files = walk(root, &options).await.unwrap();
+    assert_eq!(relative(files), vec!["src/main.rs"]);
+
+    let options = WalkOptions {
+      glob: Some(".git/*".to_string()),
+      hidden: true,
+      max_depth: None,
+    };
+    let files = walk(root, &options).await.unwrap();
+    assert_eq!(relative(files), vec![".git/config"]);
+
+    assert!(walk(&root.join("missing"), &WalkOptions::default())
+      .await
+      .is_err());
+
+    // Find the project root from the sub directory.
+    let found = find_up(
+      &["Cargo.toml".to_string()],
+      &root.join("src/ui"),
+      Some(FsEntryKind::File),
+    )
+    .await;
+    assert_eq!(found, Some(root.join("Cargo.toml")));
+    let found = find_up(
+      &["Cargo.toml".to_string()],
+      &root.join("src/ui"),
+      Some(FsEntryKind::Directory),
+    )
+    .await;
+    assert_ne!(found, Some(root.join("Cargo.toml")));
+  }
+}
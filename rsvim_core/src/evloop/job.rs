@@ -0,0 +1,297 @@
+//! Async jobs, i.e. the external processes spawned by `Rsvim.job.spawn`.
+
+use crate::js::msg::{self as jsmsg, EventLoopToJsRuntimeMessage};
+
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::mpsc::{Sender, UnboundedReceiver};
+use tracing::{error, trace};
+
+/// Job ID.
+pub type JobId = i32;
+
+// The time to drain the remaining output after the job exits.
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The signals can be sent to a job.
+pub enum JobSignal {
+  Term,
+  Kill,
+  Int,
+  Hup,
+}
+
+impl JobSignal {
+  /// Parse signal name, i.e. `SIGTERM`/`TERM`, case insensitive.
+  pub fn parse(name: &str) -> Option<Self> {
+    let name = name.to_ascii_uppercase();
+    match name.strip_prefix("SIG").unwrap_or(&name) {
+      "TERM" => Some(JobSignal::Term),
+      "KILL" => Some(JobSignal::Kill),
+      "INT" => Some(JobSignal::Int),
+      "HUP" => Some(JobSignal::Hup),
+      _ => None,
+    }
+  }
+
+  #[cfg(unix)]
+  fn as_raw(&self) -> libc::c_int {
+    match self {
+      JobSignal::Term => libc::SIGTERM,
+      JobSignal::Kill => libc::SIGKILL,
+      JobSignal::Int => libc::SIGINT,
+      JobSignal::Hup => libc::SIGHUP,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The control message sent to a running job.
+pub enum JobControl {
+  /// Write data to stdin.
+  Stdin(String),
+  /// Close stdin, i.e. send EOF.
+  CloseStdin,
+  /// Send signal.
+  Kill(JobSignal),
+}
+
+/// Decode an output line, the trailing line break (`\n` or `\r\n`) is removed and the invalid
+/// UTF-8 sequences are replaced.
+pub fn decode_line(buf: &[u8]) -> String {
+  let buf = buf.strip_suffix(b"\n").unwrap_or(buf);
+  let buf = buf.strip_suffix(b"\r").unwrap_or(buf);
+  String::from_utf8_lossy(buf).into_owned()
+}
+
+// Read next line, returns `None` if it reaches EOF (or fails).
+//
+// NOTE: The `buf` is kept by caller across the calls, since `read_until` is not cancellation safe
+// in `tokio::select!`, the partial line read by a cancelled call is still inside it.
+async fn read_line<R: AsyncBufRead + Unpin>(
+  reader: &mut Option<R>,
+  buf: &mut Vec<u8>,
+) -> Option<String> {
+  let result = reader.as_mut()?.read_until(b'\n', buf).await;
+  match result {
+    Ok(_) if !buf.is_empty() => {
+      let line = decode_line(buf);
+      buf.clear();
+      Some(line)
+    }
+    _ => None,
+  }
+}
+
+// Send the signal to the process, returns `false` if it fails.
+#[cfg(unix)]
+fn send_signal(child: &Child, signal: JobSignal) -> bool {
+  match child.id() {
+    // SAFETY: The pid belongs to the child process which is not reaped yet.
+    Some(pid) => unsafe { libc::kill(pid as libc::pid_t, signal.as_raw()) == 0 },
+    None => false,
+  }
+}
+
+// Windows doesn't have signals.
+#[cfg(not(unix))]
+fn send_signal(_child: &Child, _signal: JobSignal) -> bool {
+  false
+}
+
+fn kill(child: &mut Child, signal: JobSignal) {
+  if signal == JobSignal::Kill || !send_signal(child, signal) {
+    let _ = child.start_kill();
+  }
+}
+
+fn exit_code(status: std::io::Result<ExitStatus>) -> Option<i32> {
+  match status {
+    Ok(status) => status.code(),
+    Err(e) => {
+      error!("Failed to wait job:{:?}", e);
+      None
+    }
+  }
+}
+
+async fn write_stdin(stdin: &mut Option<ChildStdin>, data: &str) {
+  if let Some(writer) = stdin.as_mut() {
+    if let Err(e) = writer.write_all(data.as_bytes()).await {
+      trace!("Failed to write job stdin:{:?}", e);
+      *stdin = None;
+    }
+  }
+}
+
+/// Run the job until it exits, the stdout/stderr lines and the exit code are sent to the js
+/// runtime via `js_runtime_tick_dispatcher`.
+///
+/// NOTE: The exit code is `None` if the job is terminated by a signal, or failed to start.
+pub async fn run_job(
+  job_id: JobId,
+  cmd: Vec<String>,
+  cwd: Option<String>,
+  mut control: UnboundedReceiver<JobControl>,
+  js_runtime_tick_dispatcher: Sender<EventLoopToJsRuntimeMessage>,
+) {
+  let send = |msg: EventLoopToJsRuntimeMessage| {
+    let js_runtime_tick_dispatcher = js_runtime_tick_dispatcher.clone();
+    async move {
+      let _ = js_runtime_tick_dispatcher.send(msg).await;
+    }
+  };
+
+  let mut command = Command::new(&cmd[0]);
+  command
+    .args(&cmd[1..])
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true);
+  if let Some(cwd) = cwd {
+    command.current_dir(cwd);
+  }
+  let mut child = match command.spawn() {
+    Ok(child) => child,
+    Err(e) => {
+      error!("Failed to spawn job {:?}:{:?}", cmd, e);
+      send(EventLoopToJsRuntimeMessage::JobStderr(
+        jsmsg::JobOutput::new(job_id, format!("Failed to spawn {}: {}", cmd[0], e)),
+      ))
+      .await;
+      send(EventLoopToJsRuntimeMessage::JobExit(jsmsg::JobExit::new(
+        job_id, None,
+      )))
+      .await;
+      return;
+    }
+  };
+  trace!("Spawn job:{:?}, cmd:{:?}", job_id, cmd);
+
+  let mut stdin = child.stdin.take();
+  let mut stdout = child.stdout.take().map(BufReader::new);
+  let mut stderr = child.stderr.take().map(BufReader::new);
+  let mut stdout_buf = Vec::new();
+  let mut stderr_buf = Vec::new();
+  let mut controlling = true;
+
+  // The exit code, it's set once the job exits.
+  let mut exit: Option<Option<i32>> = None;
+  let drain_deadline = tokio::time::sleep(Duration::MAX);
+  tokio::pin!(drain_deadline);
+
+  // Read stdout/stderr until EOF, thus all the output lines are delivered before the exit.
+  //
+  // NOTE: The pipes can be still opened by the child processes of the job after it exits, so
+  // the remaining output is only drained for a short while.
+  while stdout.is_some() || stderr.is_some() {
+    tokio::select! {
+      line = read_line(&mut stdout, &mut stdout_buf), if stdout.is_some() => match line {
+        Some(line) => send(EventLoopToJsRuntimeMessage::JobStdout(jsmsg::JobOutput::new(job_id, line))).await,
+        None => stdout = None,
+      },
+      line = read_line(&mut stderr, &mut stderr_buf), if stderr.is_some() => match line {
+        Some(line) => send(EventLoopToJsRuntimeMessage::JobStderr(jsmsg::JobOutput::new(job_id, line))).await,
+        None => stderr = None,
+      },
+      msg = control.recv(), if controlling => match msg {
+        Some(JobControl::Stdin(data)) => write_stdin(&mut stdin, &data).await,
+        Some(JobControl::CloseStdin) => stdin = None,
+        Some(JobControl::Kill(signal)) => kill(&mut child, signal),
+        None => controlling = false,
+      },
+      status = child.wait(), if exit.is_none() => {
+        exit = Some(exit_code(status));
+        drain_deadline
+          .as_mut()
+          .reset(tokio::time::Instant::now() + DRAIN_TIMEOUT);
+      },
+      _ = &mut drain_deadline, if exit.is_some() => break,
+    }
+  }
+
+  // Flush the partial lines interrupted by the drain timeout.
+  if !stdout_buf.is_empty() {
+    let line = decode_line(&stdout_buf);
+    send(EventLoopToJsRuntimeMessage::JobStdout(
+      jsmsg::JobOutput::new(job_id, line),
+    ))
+    .await;
+  }
+  if !stderr_buf.is_empty() {
+    let line = decode_line(&stderr_buf);
+    send(EventLoopToJsRuntimeMessage::JobStderr(
+      jsmsg::JobOutput::new(job_id, line),
+    ))
+    .await;
+  }
+
+  let code = match exit {
+    Some(code) => code,
+    None => exit_code(child.wait().await),
+  };
+  trace!("Job exit:{:?}, code:{:?}", job_id, code);
+  send(EventLoopToJsRuntimeMessage::JobExit(jsmsg::JobExit::new(
+    job_id, code,
+  )))
+  .await;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn decode_line1() {
+    assert_eq!(decode_line(b"hello\n"), "hello");
+    assert_eq!(decode_line(b"hello\r\n"), "hello");
+    assert_eq!(decode_line(b"hello"), "hello");
+    assert_eq!(decode_line(b"\n"), "");
+    assert_eq!(decode_line(b"a\xffb\n"), "a\u{fffd}b");
+  }
+
+  #[test]
+  fn signal_parse1() {
+    assert_eq!(JobSignal::parse("SIGTERM"), Some(JobSignal::Term));
+    assert_eq!(JobSignal::parse("kill"), Some(JobSignal::Kill));
+    assert_eq!(JobSignal::parse("sigint"), Some(JobSignal::Int));
+    assert_eq!(JobSignal::parse("HUP"), Some(JobSignal::Hup));
+    assert_eq!(JobSignal::parse("SIGUSR1"), None);
+    assert_eq!(JobSignal::parse(""), None);
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn run_job1() {
+    let (control_send, control_recv) = tokio::sync::mpsc::unbounded_channel();
+    let (dispatcher, mut queue) = tokio::sync::mpsc::channel(8);
+    control_send
+      .send(JobControl::Stdin("a\nb\n".to_string()))
+      .unwrap();
+    control_send.send(JobControl::CloseStdin).unwrap();
+    tokio::spawn(run_job(
+      1,
+      vec!["cat".to_string()],
+      None,
+      control_recv,
+      dispatcher,
+    ));
+
+    let mut lines = vec![];
+    loop {
+      match queue.recv().await.unwrap() {
+        EventLoopToJsRuntimeMessage::JobStdout(out) => lines.push(out.line),
+        EventLoopToJsRuntimeMessage::JobExit(exit) => {
+          assert_eq!(exit.code, Some(0));
+          break;
+        }
+        msg => unreachable!("{:?}", msg),
+      }
+    }
+    assert_eq!(lines, vec!["a", "b"]);
+  }
+}
@@ -1,11 +1,192 @@
 //! Messages used inside [`EventLoop`](crate::evloop::EventLoop).
 
+use crate::buf::{BufferId, FileFormat, QuickfixEntry};
+use crate::js::JsFutureId;
+use crate::state::ex::read::ShellReadRequest;
+use crate::state::finder::{FinderId, FinderItem};
+use crate::state::git::GitStatus;
+use crate::state::grep::{GrepId, GrepSummary};
+use crate::state::progress::ProgressId;
+
+use std::path::PathBuf;
+
 // Worker to Master message {
 
 #[derive(Debug)]
 /// Message.
 pub enum WorkerToMasterMessage {
-  // BufferLoadedBytes(BufferLoadedBytes),
+  BufferLoadedBytes(BufferLoadedBytes),
+  BufferLoadDone(BufferLoadDone),
+  GitStatusDone(GitStatusDone),
+  ConfigChanged(ConfigChanged),
+  SpellLoaded(SpellLoaded),
+  GrepBatch(GrepBatch),
+  GrepDone(GrepDone),
+  FinderBatch(FinderBatch),
+  ShellReadDone(ShellReadDone),
+  PreviewLoaded(PreviewLoaded),
 }
 
 // Worker to Master message }
+
+#[derive(Debug, Default)]
+/// A chunk of the file is read, see [`BufferLoader`](crate::buf::BufferLoader).
+pub struct BufferLoadedBytes {
+  pub buffer_id: BufferId,
+  pub bytes: Vec<u8>,
+}
+
+impl BufferLoadedBytes {
+  pub fn new(buffer_id: BufferId, bytes: Vec<u8>) -> Self {
+    BufferLoadedBytes { buffer_id, bytes }
+  }
+}
+
+#[derive(Debug, Default)]
+/// The file is completely read, or failed with the `error`.
+pub struct BufferLoadDone {
+  pub buffer_id: BufferId,
+  pub error: Option<String>,
+}
+
+impl BufferLoadDone {
+  pub fn new(buffer_id: BufferId, error: Option<String>) -> Self {
+    BufferLoadDone { buffer_id, error }
+  }
+}
+
+#[derive(Debug, Default)]
+/// The git status is refreshed, it's `None` if it's not a git repository, see
+/// [`git`](crate::state::git).
+pub struct GitStatusDone {
+  pub status: Option<GitStatus>,
+}
+
+impl GitStatusDone {
+  pub fn new(status: Option<GitStatus>) -> Self {
+    GitStatusDone { status }
+  }
+}
+
+#[derive(Debug, Default)]
+/// The user config file is changed, see [`config`](crate::state::ex::config).
+pub struct ConfigChanged {
+  pub path: PathBuf,
+}
+
+impl ConfigChanged {
+  pub fn new(path: PathBuf) -> Self {
+    ConfigChanged { path }
+  }
+}
+
+#[derive(Debug, Default)]
+/// The user spell dictionary file is read, or failed with the `error`, see
+/// [`spell`](crate::buf::spell).
+pub struct SpellLoaded {
+  pub text: String,
+  pub error: Option<String>,
+}
+
+impl SpellLoaded {
+  pub fn new(text: String, error: Option<String>) -> Self {
+    SpellLoaded { text, error }
+  }
+}
+
+#[derive(Debug, Default)]
+/// A batch of the search matches, along with the progress, see [`grep`](crate::state::grep).
+pub struct GrepBatch {
+  pub grep_id: GrepId,
+  pub entries: Vec<QuickfixEntry>,
+  pub summary: GrepSummary,
+}
+
+impl GrepBatch {
+  pub fn new(grep_id: GrepId, entries: Vec<QuickfixEntry>, summary: GrepSummary) -> Self {
+    GrepBatch {
+      grep_id,
+      entries,
+      summary,
+    }
+  }
+}
+
+#[derive(Debug)]
+/// The search is finished, or failed with the error. The `future_id` is the promise of
+/// `Rsvim.grep`, it's `None` for `:grep`.
+pub struct GrepDone {
+  pub grep_id: GrepId,
+  pub future_id: Option<JsFutureId>,
+  pub result: Result<GrepSummary, String>,
+}
+
+impl GrepDone {
+  pub fn new(
+    grep_id: GrepId,
+    future_id: Option<JsFutureId>,
+    result: Result<GrepSummary, String>,
+  ) -> Self {
+    GrepDone {
+      grep_id,
+      future_id,
+      result,
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+/// A batch of the finder items populated by the builtin source, the `done` batch is the last one,
+/// see [`finder`](crate::state::finder).
+pub struct FinderBatch {
+  pub finder_id: FinderId,
+  pub items: Vec<FinderItem>,
+  pub done: bool,
+}
+
+impl FinderBatch {
+  pub fn new(finder_id: FinderId, items: Vec<FinderItem>, done: bool) -> Self {
+    FinderBatch {
+      finder_id,
+      items,
+      done,
+    }
+  }
+}
+
+#[derive(Debug)]
+/// The shell command of `:r !{cmd}` is finished with its output, or failed with the error, see
+/// [`read`](crate::state::ex::read).
+pub struct ShellReadDone {
+  pub request: ShellReadRequest,
+  pub progress_id: ProgressId,
+  pub result: Result<Vec<u8>, String>,
+}
+
+impl ShellReadDone {
+  pub fn new(
+    request: ShellReadRequest,
+    progress_id: ProgressId,
+    result: Result<Vec<u8>, String>,
+  ) -> Self {
+    ShellReadDone {
+      request,
+      progress_id,
+      result,
+    }
+  }
+}
+
+#[derive(Debug)]
+/// The file of the quickfix preview is loaded with its text and detected file format, or failed
+/// with the error, see [`quickfix`](crate::state::ex::quickfix).
+pub struct PreviewLoaded {
+  pub path: PathBuf,
+  pub result: Result<(String, Option<FileFormat>), String>,
+}
+
+impl PreviewLoaded {
+  pub fn new(path: PathBuf, result: Result<(String, Option<FileFormat>), String>) -> Self {
+    PreviewLoaded { path, result }
+  }
+}
@@ -1,12 +1,26 @@
 //! Async task.
-
-use tokio::sync::mpsc::Sender;
+//!
+//! The detached background tasks (i.e. the huge file loaders, the remote fetches, the timers, the
+//! jobs, the git status refreshes and the user config watcher) are spawned through the [`TaskRegistry`], each task has a
+//! cancellation token, thus they're all shut down within a bounded grace period when the editor
+//! quits (see [`QUIT_GRACE_PERIOD`](crate::defaults::task::QUIT_GRACE_PERIOD)).
 
 use crate::buf::BuffersManagerArc;
 use crate::evloop::msg::WorkerToMasterMessage;
 use crate::state::StateArc;
 use crate::ui::tree::TreeArc;
 
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::Sender;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::trace;
+
 #[derive(Debug, Clone)]
 /// The mutable data passed to task, and allow them access the editor.
 pub struct TaskableDataAccess {
@@ -31,3 +45,305 @@ impl TaskableDataAccess {
     }
   }
 }
+
+/// Task ID.
+pub type TaskId = usize;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The kind of a background task.
+pub enum TaskKind {
+  /// Load a huge file chunk by chunk.
+  Loader,
+  /// Fetch a remote file, or the HTTP request of `fetch`.
+  Fetch,
+  /// The timers, i.e. `setTimeout`, `setInterval` and the `getchar` timeout.
+  Timer,
+  /// The job spawned by `Rsvim.job.spawn`.
+  Job,
+  /// Refresh the git status.
+  Git,
+  /// Watch the signals, i.e. `SIGINT`.
+  Signal,
+  /// Watch the user config file.
+  Watch,
+  /// Read and write the user spell dictionary file.
+  Spell,
+  /// The file system operation of `Rsvim.fs`.
+  Fs,
+  /// Search files, i.e. `:grep` and `Rsvim.grep`.
+  Grep,
+  /// Walk the files for the fuzzy finder, i.e. `Rsvim.picker.open`.
+  Finder,
+}
+
+impl Display for TaskKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      TaskKind::Loader => write!(f, "loader"),
+      TaskKind::Fetch => write!(f, "fetch"),
+      TaskKind::Timer => write!(f, "timer"),
+      TaskKind::Job => write!(f, "job"),
+      TaskKind::Git => write!(f, "git"),
+      TaskKind::Signal => write!(f, "signal"),
+      TaskKind::Watch => write!(f, "watch"),
+      TaskKind::Spell => write!(f, "spell"),
+      TaskKind::Fs => write!(f, "fs"),
+      TaskKind::Grep => write!(f, "grep"),
+      TaskKind::Finder => write!(f, "finder"),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+/// A running background task.
+pub struct TaskInfo {
+  pub id: TaskId,
+  pub kind: TaskKind,
+  pub name: String,
+  pub started: Instant,
+}
+
+impl Display for TaskInfo {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "{} #{} {:?} (running {}ms)",
+      self.kind,
+      self.id,
+      self.name,
+      self.started.elapsed().as_millis()
+    )
+  }
+}
+
+type TaskEntries = Arc<Mutex<BTreeMap<TaskId, (TaskInfo, CancellationToken)>>>;
+
+// Remove the task from the registry once it's finished, cancelled or panicked.
+struct TaskGuard {
+  id: TaskId,
+  tasks: TaskEntries,
+}
+
+impl Drop for TaskGuard {
+  fn drop(&mut self) {
+    self.tasks.lock().remove(&self.id);
+  }
+}
+
+#[derive(Debug, Default)]
+/// The registry of the detached background tasks.
+///
+/// Each task gets a child token of the registry's cancellation token:
+///
+/// - The async tasks are dropped once they're cancelled, i.e. the child processes of the jobs are
+///   killed (they're spawned with `kill_on_drop`).
+/// - The blocking tasks must check the token by themselves, since a blocking thread cannot be
+///   aborted.
+/// - The write tasks (see [`spawn_write`](TaskRegistry::spawn_write)) are not cancelled on
+///   [`shutdown`](TaskRegistry::shutdown), they're given the grace period to finish, since
+///   cancelling them in the middle can leave a truncated file.
+///
+/// The tasks not stopped within the grace period of [`shutdown`](TaskRegistry::shutdown) are
+/// reported, and abandoned along with the tokio runtime.
+pub struct TaskRegistry {
+  tracker: TaskTracker,
+  cancellation_token: CancellationToken,
+  next_id: TaskId,
+  tasks: TaskEntries,
+}
+
+impl TaskRegistry {
+  pub fn new() -> Self {
+    TaskRegistry::default()
+  }
+
+  // Register a new task with its cancellation `token`, returns its ID and guard.
+  fn register(
+    &mut self,
+    kind: TaskKind,
+    name: &str,
+    token: CancellationToken,
+  ) -> (TaskId, TaskGuard) {
+    self.next_id += 1;
+    let id = self.next_id;
+    let info = TaskInfo {
+      id,
+      kind,
+      name: name.to_string(),
+      started: Instant::now(),
+    };
+    self.tasks.lock().insert(id, (info, token.clone()));
+    let guard = TaskGuard {
+      id,
+      tasks: self.tasks.clone(),
+    };
+    (id, guard)
+  }
+
+  /// Spawn an async task, it's dropped once it's cancelled.
+  pub fn spawn<F>(&mut self, kind: TaskKind, name: &str, future: F) -> TaskId
+  where
+    F: Future<Output = ()> + Send + 'static,
+  {
+    let token = self.cancellation_token.child_token();
+    self.spawn_with_token(kind, name, token, future)
+  }
+
+  /// Spawn an async task that writes files, it can be cancelled by [`cancel`](TaskRegistry::cancel)
+  /// but it's not cancelled on [`shutdown`](TaskRegistry::shutdown).
+  pub fn spawn_write<F>(&mut self, kind: TaskKind, name: &str, future: F) -> TaskId
+  where
+    F: Future<Output = ()> + Send + 'static,
+  {
+    self.spawn_with_token(kind, name, CancellationToken::new(), future)
+  }
+
+  fn spawn_with_token<F>(
+    &mut self,
+    kind: TaskKind,
+    name: &str,
+    token: CancellationToken,
+    future: F,
+  ) -> TaskId
+  where
+    F: Future<Output = ()> + Send + 'static,
+  {
+    let (id, guard) = self.register(kind, name, token.clone());
+    self.tracker.spawn(async move {
+      let _guard = guard;
+      tokio::select! {
+        _ = token.cancelled() => trace!("Task {:?} is cancelled", id),
+        _ = future => {}
+      }
+    });
+    id
+  }
+
+  /// Spawn a blocking task, it's given the cancellation token and must stop once the token is
+  /// cancelled.
+  pub fn spawn_blocking<F>(&mut self, kind: TaskKind, name: &str, f: F) -> TaskId
+  where
+    F: FnOnce(CancellationToken) + Send + 'static,
+  {
+    let token = self.cancellation_token.child_token();
+    let (id, guard) = self.register(kind, name, token.clone());
+    self.tracker.spawn_blocking(move || {
+      let _guard = guard;
+      f(token);
+    });
+    id
+  }
+
+  /// Cancel a task, returns `false` if it's not running.
+  pub fn cancel(&self, id: TaskId) -> bool {
+    match self.tasks.lock().get(&id) {
+      Some((_, token)) => {
+        token.cancel();
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Get the running tasks, ordered by their IDs.
+  pub fn list(&self) -> Vec<TaskInfo> {
+    self
+      .tasks
+      .lock()
+      .values()
+      .map(|(info, _)| info.clone())
+      .collect()
+  }
+
+  /// Whether the registry is shut down, i.e. no more tasks can be spawned.
+  pub fn is_closed(&self) -> bool {
+    self.tracker.is_closed()
+  }
+
+  /// Cancel all the tasks (except the write tasks) and wait for them to stop within the `grace`
+  /// period. Returns the tasks that refuse to stop, i.e. the blocking tasks that don't check their
+  /// cancellation token, or the write tasks that don't finish in time.
+  pub async fn shutdown(&mut self, grace: Duration) -> Vec<TaskInfo> {
+    self.tracker.close();
+    self.cancellation_token.cancel();
+    match tokio::time::timeout(grace, self.tracker.wait()).await {
+      Ok(()) => vec![],
+      Err(_) => self.list(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn shutdown1() {
+    let mut tasks = TaskRegistry::new();
+    tasks.spawn(TaskKind::Timer, "forever", async {
+      tokio::time::sleep(Duration::MAX).await;
+    });
+    tasks.spawn_blocking(TaskKind::Loader, "polite", |token| {
+      while !token.is_cancelled() {
+        std::thread::sleep(Duration::from_millis(5));
+      }
+    });
+    assert_eq!(tasks.list().len(), 2);
+    assert!(tasks.shutdown(Duration::from_secs(5)).await.is_empty());
+    assert!(tasks.list().is_empty());
+    assert!(tasks.is_closed());
+  }
+
+  #[tokio::test]
+  async fn shutdown2() {
+    let mut tasks = TaskRegistry::new();
+    let id = tasks.spawn_blocking(TaskKind::Loader, "stubborn", |_token| {
+      std::thread::sleep(Duration::from_millis(500));
+    });
+    let stuck = tasks.shutdown(Duration::from_millis(20)).await;
+    assert_eq!(stuck.len(), 1);
+    assert_eq!(stuck[0].id, id);
+    assert_eq!(stuck[0].kind, TaskKind::Loader);
+    assert!(stuck[0].to_string().starts_with("loader #1 \"stubborn\""));
+  }
+
+  #[tokio::test]
+  async fn shutdown_write1() {
+    let mut tasks = TaskRegistry::new();
+    let (send, recv) = tokio::sync::oneshot::channel::<()>();
+    tasks.spawn(TaskKind::Timer, "timer", async {
+      tokio::time::sleep(Duration::MAX).await;
+    });
+    tasks.spawn_write(TaskKind::Fs, "write", async move {
+      tokio::time::sleep(Duration::from_millis(20)).await;
+      let _ = send.send(());
+    });
+    // The write task is finished, the timer is cancelled.
+    assert!(tasks.shutdown(Duration::from_secs(5)).await.is_empty());
+    assert!(recv.await.is_ok());
+
+    let mut tasks = TaskRegistry::new();
+    let id = tasks.spawn_write(TaskKind::Spell, "slow write", async {
+      tokio::time::sleep(Duration::MAX).await;
+    });
+    let stuck = tasks.shutdown(Duration::from_millis(20)).await;
+    assert_eq!(stuck.len(), 1);
+    assert_eq!(stuck[0].id, id);
+  }
+
+  #[tokio::test]
+  async fn cancel1() {
+    let mut tasks = TaskRegistry::new();
+    let (send, recv) = tokio::sync::oneshot::channel::<()>();
+    let id = tasks.spawn(TaskKind::Job, "job", async move {
+      let _send = send;
+      tokio::time::sleep(Duration::MAX).await;
+    });
+    assert!(tasks.cancel(id));
+    // The future is dropped once it's cancelled.
+    assert!(recv.await.is_err());
+    tokio::task::yield_now().await;
+    assert!(tasks.list().is_empty());
+    assert!(!tasks.cancel(id));
+  }
+}
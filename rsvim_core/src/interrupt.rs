@@ -0,0 +1,215 @@
+//! Ctrl-C interrupt.
+//!
+//! The long-running operations (i.e. the ex commands iterating lines/buffers, the js scripts) run
+//! on the event loop thread and block it, thus the `Ctrl-C` key cannot be handled by the state
+//! machine. Instead they cooperatively check the [`Interrupt`] flag and abort once it's set:
+//!
+//! - The rust operations call [`Interrupt::check`] in their loops.
+//! - The js runtime registers its V8 isolate while running scripts, the isolate is terminated
+//!   immediately when interrupted.
+//!
+//! The flag is set by:
+//!
+//! - The `SIGINT` signal, i.e. `kill -INT`.
+//! - The `Ctrl-C` key, which doesn't generate `SIGINT` in raw mode. The [`InterruptWatcher`]
+//!   reads the terminal events in a background thread while the event loop is blocked, the other
+//!   events are sent back to the event loop as typeahead thus they're not lost.
+
+use crate::res::{ExCommandErr, ExCommandResult};
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use parking_lot::{Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tracing::trace;
+
+#[derive(Default)]
+/// The interrupt flag.
+pub struct Interrupt {
+  interrupted: AtomicBool,
+
+  // The V8 isolate handle, it's only registered while the js runtime is running scripts.
+  isolate: Mutex<Option<v8::IsolateHandle>>,
+}
+
+pub type InterruptArc = Arc<Interrupt>;
+
+impl Interrupt {
+  pub fn new() -> Self {
+    Interrupt::default()
+  }
+
+  /// Convert struct to Arc pointer.
+  pub fn to_arc(i: Interrupt) -> InterruptArc {
+    Arc::new(i)
+  }
+
+  /// Interrupt current operation, and terminate the running js script.
+  pub fn interrupt(&self) {
+    trace!("Interrupted");
+    self.interrupted.store(true, Ordering::SeqCst);
+    if let Some(isolate) = self.isolate.lock().as_ref() {
+      isolate.terminate_execution();
+    }
+  }
+
+  /// Whether current operation is interrupted.
+  pub fn is_interrupted(&self) -> bool {
+    self.interrupted.load(Ordering::SeqCst)
+  }
+
+  /// Check whether current operation is interrupted, the long-running operations call it
+  /// periodically.
+  ///
+  /// # Errors
+  ///
+  /// If it's interrupted.
+  pub fn check(&self) -> ExCommandResult<()> {
+    if self.is_interrupted() {
+      Err(ExCommandErr::Interrupted)
+    } else {
+      Ok(())
+    }
+  }
+
+  /// Clear the flag after current operation is done, returns whether it was interrupted.
+  pub fn reset(&self) -> bool {
+    self.interrupted.swap(false, Ordering::SeqCst)
+  }
+
+  /// Register the V8 isolate while running js scripts, it's unregistered when the returned guard
+  /// is dropped.
+  pub fn enter_js(&self, isolate: v8::IsolateHandle) -> InterruptJsGuard<'_> {
+    *self.isolate.lock() = Some(isolate);
+    InterruptJsGuard { interrupt: self }
+  }
+}
+
+impl std::fmt::Debug for Interrupt {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Interrupt")
+      .field("interrupted", &self.is_interrupted())
+      .finish()
+  }
+}
+
+/// The guard returned by [`Interrupt::enter_js`].
+pub struct InterruptJsGuard<'a> {
+  interrupt: &'a Interrupt,
+}
+
+impl Drop for InterruptJsGuard<'_> {
+  fn drop(&mut self) {
+    *self.interrupt.isolate.lock() = None;
+  }
+}
+
+/// Whether the event is the `Ctrl-C` key.
+pub fn is_ctrl_c(event: &Event) -> bool {
+  match event {
+    Event::Key(key_event) => {
+      key_event.kind == KeyEventKind::Press
+        && key_event.code == KeyCode::Char('c')
+        && key_event.modifiers == KeyModifiers::CONTROL
+    }
+    _ => false,
+  }
+}
+
+#[derive(Debug)]
+/// Watch the `Ctrl-C` key while the event loop is blocked.
+///
+/// The watcher thread sleeps until it's armed, then polls the terminal events until it's
+/// disarmed. The `Ctrl-C` key interrupts current operation, the other events are sent to the
+/// event loop as typeahead.
+pub struct InterruptWatcher {
+  armed: Arc<(Mutex<bool>, Condvar)>,
+}
+
+/// The polling interval of the watcher thread.
+const WATCH_INTERVAL: Duration = Duration::from_millis(20);
+
+impl InterruptWatcher {
+  /// Spawn the watcher thread.
+  pub fn spawn(interrupt: InterruptArc, typeahead: Sender<Event>) -> Self {
+    let armed = Arc::new((Mutex::new(false), Condvar::new()));
+    let watcher_armed = armed.clone();
+    std::thread::Builder::new()
+      .name("rsvim-interrupt".to_string())
+      .spawn(move || {
+        let (lock, cvar) = &*watcher_armed;
+        loop {
+          {
+            let mut armed = lock.lock();
+            while !*armed {
+              cvar.wait(&mut armed);
+            }
+          }
+          match crossterm::event::poll(WATCH_INTERVAL) {
+            Ok(true) if *lock.lock() => match crossterm::event::read() {
+              Ok(event) if is_ctrl_c(&event) => interrupt.interrupt(),
+              Ok(event) => {
+                if typeahead.blocking_send(event).is_err() {
+                  // The event loop has exited.
+                  return;
+                }
+              }
+              Err(_) => { /* Skip */ }
+            },
+            _ => { /* Skip */ }
+          }
+        }
+      })
+      .unwrap();
+    InterruptWatcher { armed }
+  }
+
+  /// Make a watcher without the watcher thread, i.e. it never reads the terminal events. It's for
+  /// the embedded editor, the host application owns the terminal.
+  pub fn detached() -> Self {
+    InterruptWatcher {
+      armed: Arc::new((Mutex::new(false), Condvar::new())),
+    }
+  }
+
+  /// Start watching before a blocking operation.
+  pub fn arm(&self) {
+    let (lock, cvar) = &*self.armed;
+    *lock.lock() = true;
+    cvar.notify_one();
+  }
+
+  /// Stop watching after a blocking operation.
+  pub fn disarm(&self) {
+    *self.armed.0.lock() = false;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crossterm::event::KeyEvent;
+
+  #[test]
+  fn interrupt1() {
+    let interrupt = Interrupt::new();
+    assert!(interrupt.check().is_ok());
+    interrupt.interrupt();
+    assert!(interrupt.is_interrupted());
+    assert_eq!(interrupt.check(), Err(ExCommandErr::Interrupted));
+    assert!(interrupt.reset());
+    assert!(!interrupt.reset());
+    assert!(interrupt.check().is_ok());
+  }
+
+  #[test]
+  fn is_ctrl_c1() {
+    let ctrl_c = Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+    assert!(is_ctrl_c(&ctrl_c));
+    let c = Event::Key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE));
+    assert!(!is_ctrl_c(&c));
+  }
+}
@@ -1,7 +1,20 @@
 //! JavaScript runtime.
 
+use crate::api::EditorApi;
 use crate::buf::BuffersManagerArc;
 use crate::cli::CliOpt;
+use crate::envar;
+#[cfg(feature = "fetch")]
+use crate::evloop::fetch::FetchId;
+use crate::evloop::job::JobId;
+use crate::interrupt::InterruptArc;
+use crate::js::binding::global_rsvim::fs::FsFuture;
+use crate::js::binding::global_rsvim::grep::GrepFuture;
+use crate::js::binding::global_rsvim::input::GetcharFuture;
+use crate::js::binding::global_rsvim::job::{JobFuture, JobHandle};
+#[cfg(feature = "fetch")]
+use crate::js::binding::global_this::fetch::{FetchFuture, FetchHandle, FetchValue};
+use crate::js::binding::global_this::timeout::cancel_timer;
 use crate::js::err::JsError;
 use crate::js::exception::ExceptionState;
 use crate::js::hook::module_resolve_cb;
@@ -11,8 +24,12 @@ use crate::js::module::{
 };
 use crate::js::msg::{EventLoopToJsRuntimeMessage, JsRuntimeToEventLoopMessage};
 use crate::res::AnyErr;
+use crate::rlock;
+use crate::state::autocmd::{AutocmdArgs, AutocmdId};
+use crate::state::finder::{FinderId, FinderResult};
+use crate::state::keymap::KeymapCallbackId;
 use crate::state::StateArc;
-use crate::ui::tree::TreeArc;
+use crate::ui::tree::{TreeArc, TreeNodeId};
 
 use ahash::{AHashMap as HashMap, AHashSet as HashSet};
 use once_cell::sync::Lazy;
@@ -28,6 +45,7 @@ use tokio::sync::mpsc::{Receiver, Sender};
 use tracing::{error, trace};
 
 pub mod binding;
+pub mod cache;
 pub mod constant;
 pub mod err;
 pub mod exception;
@@ -35,6 +53,7 @@ pub mod hook;
 pub mod loader;
 pub mod module;
 pub mod msg;
+pub mod source_map;
 pub mod transpiler;
 
 #[derive(Debug, Default, Clone)]
@@ -322,6 +341,25 @@ pub struct JsRuntimeState {
   // pub interrupt_handle: LoopInterruptHandle,
   /// Holds JS pending futures scheduled by the event-loop.
   pub pending_futures: HashMap<JsFutureId, Box<dyn JsFuture>>,
+  /// Promise resolvers of the waiting `Rsvim.input.getchar` requests.
+  pub getchar_resolvers: HashMap<JsFutureId, v8::Global<v8::PromiseResolver>>,
+  /// Promise resolvers of the running `Rsvim.fs` operations.
+  pub fs_resolvers: HashMap<JsFutureId, v8::Global<v8::PromiseResolver>>,
+  /// Promise resolvers of the running `Rsvim.grep` searches.
+  pub grep_resolvers: HashMap<JsFutureId, v8::Global<v8::PromiseResolver>>,
+  /// The callback of `Rsvim.opt.statusLine`, it's evaluated on each redraw.
+  pub statusline_callback: Option<v8::Global<v8::Function>>,
+  /// The callbacks of `Rsvim.on`, by subscription ID.
+  pub autocmd_callbacks: HashMap<AutocmdId, v8::Global<v8::Function>>,
+  /// The callbacks of `Rsvim.keymap.set`, by callback ID.
+  pub keymap_callbacks: HashMap<KeymapCallbackId, v8::Global<v8::Function>>,
+  /// The callbacks of `Rsvim.picker.open`, by finder ID.
+  pub picker_callbacks: HashMap<FinderId, v8::Global<v8::Function>>,
+  /// The running jobs of `Rsvim.job.spawn`, by job ID.
+  pub jobs: HashMap<JobId, JobHandle>,
+  #[cfg(feature = "fetch")]
+  /// The running requests of `fetch`, by fetch ID.
+  pub fetches: HashMap<FetchId, FetchHandle>,
   /// Indicates the start time of the process.
   pub startup_moment: Instant,
   /// Specifies the timestamp which the current process began in Unix time.
@@ -349,6 +387,17 @@ pub struct JsRuntimeState {
   // Data Access for RSVIM }
 }
 
+impl JsRuntimeState {
+  /// Get the editor API over the data access, see [`EditorApi`].
+  pub fn api(&self) -> EditorApi {
+    EditorApi::new(
+      self.tree.clone(),
+      self.buffers.clone(),
+      self.editing_state.clone(),
+    )
+  }
+}
+
 /// Snapshot data for startup.
 pub struct SnapshotData {
   pub value: &'static [u8],
@@ -368,6 +417,9 @@ pub struct JsRuntime {
   /// The state of the runtime.
   #[allow(unused)]
   pub state: Rc<RefCell<JsRuntimeState>>,
+
+  /// Ctrl-C interrupt flag, the running scripts are terminated when interrupted.
+  pub interrupt: InterruptArc,
 }
 
 impl JsRuntime {
@@ -473,6 +525,8 @@ impl JsRuntime {
       v8::Global::new(scope, context)
     };
 
+    let interrupt = rlock!(editing_state).interrupt().clone();
+
     // Store state inside the v8 isolate slot.
     // https://v8docs.nodesource.com/node-4.8/d5/dda/classv8_1_1_isolate.html#a7acadfe7965997e9c386a05f098fbe36
     let state = Rc::new(RefCell::new(JsRuntimeState {
@@ -481,6 +535,16 @@ impl JsRuntime {
       timeout_handles: HashSet::new(),
       // interrupt_handle: event_loop.interrupt_handle(),
       pending_futures: HashMap::new(),
+      getchar_resolvers: HashMap::new(),
+      fs_resolvers: HashMap::new(),
+      grep_resolvers: HashMap::new(),
+      statusline_callback: None,
+      autocmd_callbacks: HashMap::new(),
+      keymap_callbacks: HashMap::new(),
+      picker_callbacks: HashMap::new(),
+      jobs: HashMap::new(),
+      #[cfg(feature = "fetch")]
+      fetches: HashMap::new(),
       // timeout_queue: BTreeMap::new(),
       startup_moment,
       time_origin,
@@ -503,6 +567,7 @@ impl JsRuntime {
       isolate,
       // event_loop,
       state,
+      interrupt,
       // inspector,
     }
 
@@ -565,8 +630,26 @@ impl JsRuntime {
     }
   }
 
+  /// Run the js scripts, they're terminated when interrupted by Ctrl-C.
+  fn interruptible<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+    let interrupt = self.interrupt.clone();
+    let guard = interrupt.enter_js(self.isolate.thread_safe_handle());
+    let result = f(self);
+    drop(guard);
+    // NOTE: The isolate must be resumed, otherwise all the following scripts are terminated.
+    if interrupt.is_interrupted() {
+      trace!("Js runtime is interrupted");
+      self.isolate.cancel_terminate_execution();
+    }
+    result
+  }
+
   /// Executes JavaScript code as ES module.
   pub fn execute_module(&mut self, filename: &str, source: Option<&str>) -> Result<(), AnyErr> {
+    self.interruptible(|runtime| runtime.execute_module_impl(filename, source))
+  }
+
+  fn execute_module_impl(&mut self, filename: &str, source: Option<&str>) -> Result<(), AnyErr> {
     // Get a reference to v8's scope.
     let scope = &mut self.handle_scope();
 
@@ -593,8 +676,8 @@ impl JsRuntime {
       None => {
         assert!(tc_scope.has_caught());
         let exception = tc_scope.exception().unwrap();
-        let _exception = JsError::from_v8_exception(tc_scope, exception, None);
-        let e = format!("User config not found: {filename:?}");
+        let exception = JsError::from_v8_exception(tc_scope, exception, None);
+        let e = format!("Failed to load user config module {filename:?}: {exception:?}");
         error!(e);
         eprintln!("{e}");
         anyhow::bail!(e);
@@ -639,6 +722,10 @@ impl JsRuntime {
 
   /// Runs a single tick of the event-loop.
   pub fn tick_event_loop(&mut self) {
+    self.interruptible(|runtime| runtime.tick_event_loop_impl())
+  }
+
+  fn tick_event_loop_impl(&mut self) {
     let isolate_has_pending_tasks = self.isolate.has_pending_background_tasks();
     trace!(
       "Tick js runtime, isolate has pending tasks: {:?}",
@@ -703,9 +790,113 @@ impl JsRuntime {
       while let Ok(msg) = state.js_runtime_recv_from_master.try_recv() {
         match msg {
           EventLoopToJsRuntimeMessage::TimeoutResp(resp) => {
-            match state.pending_futures.remove(&resp.future_id) {
-              Some(timeout_cb) => futures.push(timeout_cb),
-              None => unreachable!("Failed to get timeout future by ID {:?}", resp.future_id),
+            if !state.timeout_handles.contains(&resp.future_id) {
+              // The timer is already cleared, or the context is torn down by a config reload.
+              // Cancel the interval timer again, in case it's cleared before it's started.
+              trace!("Ignore cleared timer {:?}", resp.future_id);
+              if resp.repeat {
+                cancel_timer(&state, resp.future_id);
+              }
+              continue;
+            }
+            // NOTE: The interval timer's future is missing if its previous tick is not run yet,
+            // i.e. the ticks are coalesced.
+            if let Some(timeout_cb) = state.pending_futures.remove(&resp.future_id) {
+              futures.push(timeout_cb);
+            }
+          }
+          EventLoopToJsRuntimeMessage::GetcharResp(resp) => {
+            match state.getchar_resolvers.remove(&resp.future_id) {
+              Some(resolver) => futures.push(Box::new(GetcharFuture {
+                future_id: resp.future_id,
+                resolver,
+                key: resp.key,
+              })),
+              None => unreachable!("Failed to get getchar future by ID {:?}", resp.future_id),
+            }
+          }
+          EventLoopToJsRuntimeMessage::JobStdout(out) => {
+            let callback = state
+              .jobs
+              .get(&out.job_id)
+              .and_then(|job| job.on_stdout.clone());
+            if let Some(callback) = callback {
+              futures.push(Box::new(JobFuture {
+                job_id: out.job_id,
+                callback,
+                value: Ok(out.line),
+              }));
+            }
+          }
+          EventLoopToJsRuntimeMessage::JobStderr(out) => {
+            let callback = state
+              .jobs
+              .get(&out.job_id)
+              .and_then(|job| job.on_stderr.clone());
+            if let Some(callback) = callback {
+              futures.push(Box::new(JobFuture {
+                job_id: out.job_id,
+                callback,
+                value: Ok(out.line),
+              }));
+            }
+          }
+          EventLoopToJsRuntimeMessage::JobExit(exit) => {
+            let callback = state.jobs.remove(&exit.job_id).and_then(|job| job.on_exit);
+            if let Some(callback) = callback {
+              futures.push(Box::new(JobFuture {
+                job_id: exit.job_id,
+                callback,
+                value: Err(exit.code),
+              }));
+            }
+          }
+          #[cfg(feature = "fetch")]
+          EventLoopToJsRuntimeMessage::FetchHead(head) => {
+            if let Some(fetch) = state.fetches.get(&head.fetch_id) {
+              futures.push(Box::new(FetchFuture {
+                fetch_id: head.fetch_id,
+                callback: fetch.on_head.clone(),
+                value: FetchValue::Head(head),
+              }));
+            }
+          }
+          #[cfg(feature = "fetch")]
+          EventLoopToJsRuntimeMessage::FetchChunk(chunk) => {
+            if let Some(fetch) = state.fetches.get(&chunk.fetch_id) {
+              futures.push(Box::new(FetchFuture {
+                fetch_id: chunk.fetch_id,
+                callback: fetch.on_chunk.clone(),
+                value: FetchValue::Chunk(chunk.chunk),
+              }));
+            }
+          }
+          #[cfg(feature = "fetch")]
+          EventLoopToJsRuntimeMessage::FetchDone(done) => {
+            if let Some(fetch) = state.fetches.remove(&done.fetch_id) {
+              futures.push(Box::new(FetchFuture {
+                fetch_id: done.fetch_id,
+                callback: fetch.on_done,
+                value: FetchValue::Done(done.error),
+              }));
+            }
+          }
+          EventLoopToJsRuntimeMessage::FsResp(resp) => {
+            if let Some(resolver) = state.fs_resolvers.remove(&resp.future_id) {
+              futures.push(Box::new(FsFuture {
+                future_id: resp.future_id,
+                resolver,
+                result: resp.result,
+              }));
+            }
+          }
+          EventLoopToJsRuntimeMessage::GrepResp(resp) => {
+            if let Some(resolver) = state.grep_resolvers.remove(&resp.future_id) {
+              futures.push(Box::new(GrepFuture {
+                future_id: resp.future_id,
+                resolver,
+                result: resp.result,
+              }));
             }
           }
         }
@@ -716,12 +907,14 @@ impl JsRuntime {
 
     for mut fut in futures {
       fut.run(scope);
+      // NOTE: Run the microtasks queued by the future before checking exceptions, thus the
+      // exceptions reported by `queueMicrotask` callbacks are checked as well.
+      run_next_tick_callbacks(scope);
       if let Some(error) = check_exceptions(scope) {
         // FIXME: Cannot simply report error and exit process, because this is inside the editor.
         error!("Js runtime timeout error:{error:?}");
         eprintln!("Js runtime timeout error:{error:?}");
       }
-      run_next_tick_callbacks(scope);
     }
   }
 
@@ -844,6 +1037,158 @@ impl JsRuntime {
     run_next_tick_callbacks(scope);
   }
 
+  /// Evaluate the `Rsvim.opt.statusLine` callback for the window `window_id`, returns the
+  /// statusline format, or `None` if there's no callback or it throws.
+  pub fn eval_statusline(&mut self, window_id: TreeNodeId) -> Option<String> {
+    let callback = self.get_state().borrow().statusline_callback.clone()?;
+    let scope = &mut self.handle_scope();
+    let undefined = v8::undefined(scope).into();
+    let callback = v8::Local::new(scope, callback);
+    let window_id = v8::Integer::new(scope, window_id).into();
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    let result = callback.call(tc_scope, undefined, &[window_id]);
+
+    // Report if callback threw an exception.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+      return None;
+    }
+    result.map(|value| value.to_rust_string_lossy(tc_scope))
+  }
+
+  /// Call the `Rsvim.on` callback of the subscription `id` with the emitted event, the callback
+  /// receives an object with `event`, `match`, `bufferId`, `windowId` and `scrollDelta` (the last
+  /// three are `null` if the event doesn't have them).
+  pub fn emit_autocmd(&mut self, id: AutocmdId, args: &AutocmdArgs) {
+    let callback = match self.get_state().borrow().autocmd_callbacks.get(&id) {
+      Some(callback) => callback.clone(),
+      None => return,
+    };
+    let scope = &mut self.handle_scope();
+    let undefined = v8::undefined(scope).into();
+    let callback = v8::Local::new(scope, callback);
+
+    let target = v8::Object::new(scope);
+    let event = v8::String::new(scope, &args.event.to_string()).unwrap();
+    binding::set_property_to(scope, target, "event", event.into());
+    let matched = v8::String::new(scope, &args.matched).unwrap();
+    binding::set_property_to(scope, target, "match", matched.into());
+    let buffer_id = match args.buffer_id {
+      Some(buffer_id) => v8::Integer::new(scope, buffer_id).into(),
+      None => v8::null(scope).into(),
+    };
+    binding::set_property_to(scope, target, "bufferId", buffer_id);
+    let window_id = match args.window_id {
+      Some(window_id) => v8::Integer::new(scope, window_id).into(),
+      None => v8::null(scope).into(),
+    };
+    binding::set_property_to(scope, target, "windowId", window_id);
+    let scroll_delta = match args.scroll_delta {
+      Some((lines, columns)) => {
+        let delta = v8::Object::new(scope);
+        let lines = v8::Number::new(scope, lines as f64);
+        binding::set_property_to(scope, delta, "lines", lines.into());
+        let columns = v8::Number::new(scope, columns as f64);
+        binding::set_property_to(scope, delta, "columns", columns.into());
+        delta.into()
+      }
+      None => v8::null(scope).into(),
+    };
+    binding::set_property_to(scope, target, "scrollDelta", scroll_delta);
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    callback.call(tc_scope, undefined, &[target.into()]);
+
+    // Report if callback threw an exception.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+    }
+  }
+
+  /// Call the key mapping callback, see `Rsvim.keymap.set`.
+  pub fn call_keymap(&mut self, id: KeymapCallbackId) {
+    let callback = match self.get_state().borrow().keymap_callbacks.get(&id) {
+      Some(callback) => callback.clone(),
+      None => return,
+    };
+    let scope = &mut self.handle_scope();
+    let undefined = v8::undefined(scope).into();
+    let callback = v8::Local::new(scope, callback);
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    callback.call(tc_scope, undefined, &[]);
+
+    // Report if callback threw an exception.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+    }
+  }
+
+  /// Call the callback of the closed fuzzy finder, see `Rsvim.picker.open`. The arguments are the
+  /// index (in the pushed order), label and description of the accepted item, or `null`s if the
+  /// finder is cancelled.
+  pub fn call_finder(&mut self, result: FinderResult) {
+    let callback = match self
+      .get_state()
+      .borrow_mut()
+      .picker_callbacks
+      .remove(&result.finder_id)
+    {
+      Some(callback) => callback,
+      None => return,
+    };
+    let scope = &mut self.handle_scope();
+    let undefined = v8::undefined(scope).into();
+    let callback = v8::Local::new(scope, callback);
+
+    let args: Vec<v8::Local<v8::Value>> = match &result.accepted {
+      Some((index, item)) => vec![
+        v8::Integer::new(scope, *index as i32).into(),
+        v8::String::new(scope, &item.label).unwrap().into(),
+        v8::String::new(scope, &item.description).unwrap().into(),
+      ],
+      None => vec![v8::null(scope).into(); 3],
+    };
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    callback.call(tc_scope, undefined, &args);
+
+    // Report if callback threw an exception.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+    }
+  }
+
+  /// Drop the JS callbacks of `Rsvim.on`, `Rsvim.keymap.set`, `Rsvim.picker.open` and the timers,
+  /// and forget the loaded ES modules, i.e. before the user config is reloaded (see
+  /// [`config`](crate::state::ex::config)). Thus the modules are fetched and evaluated again when
+  /// they're imported next time.
+  pub fn reset_plugins(&mut self) {
+    let state_rc = self.get_state();
+    let mut state = state_rc.borrow_mut();
+    state.autocmd_callbacks.clear();
+    state.keymap_callbacks.clear();
+    state.picker_callbacks.clear();
+    state.module_map = ModuleMap::new();
+    // The timers are cancelled on the event loop, see `EventLoop::cancel_js_timers`.
+    for timer_id in std::mem::take(&mut state.timeout_handles) {
+      state.pending_futures.remove(&timer_id);
+    }
+  }
+
   /// Returns if unhandled promise rejections where caught.
   pub fn has_promise_rejections(&mut self) -> bool {
     self.get_state().borrow().exceptions.has_promise_rejection()
@@ -75,6 +75,36 @@ pub fn create_new_context<'s>(scope: &mut v8::HandleScope<'s, ()>) -> v8::Local<
       "global_clear_timeout",
       global_this::timeout::clear_timeout,
     );
+    set_function_to(
+      scope,
+      vim,
+      "global_queue_microtask",
+      global_this::microtask::queue_microtask,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "global_report_error",
+      global_this::microtask::report_error,
+    );
+  }
+
+  // `globalThis.fetch`, it's only available with the `fetch` feature.
+  #[cfg(feature = "fetch")]
+  {
+    set_function_to(scope, vim, "global_fetch", global_this::fetch::fetch);
+    set_function_to(
+      scope,
+      vim,
+      "global_fetch_abort",
+      global_this::fetch::fetch_abort,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "global_decode_utf8",
+      global_this::fetch::decode_utf8,
+    );
   }
 
   // `Rsvim.opt`
@@ -93,6 +123,713 @@ pub fn create_new_context<'s>(scope: &mut v8::HandleScope<'s, ()>) -> v8::Local<
       "opt_set_line_break",
       global_rsvim::opt::set_line_break,
     );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_scroll_off",
+      global_rsvim::opt::get_scroll_off,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_scroll_off",
+      global_rsvim::opt::set_scroll_off,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_side_scroll",
+      global_rsvim::opt::get_side_scroll,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_side_scroll",
+      global_rsvim::opt::set_side_scroll,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_side_scroll_off",
+      global_rsvim::opt::get_side_scroll_off,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_side_scroll_off",
+      global_rsvim::opt::set_side_scroll_off,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_clipboard",
+      global_rsvim::opt::get_clipboard,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_clipboard",
+      global_rsvim::opt::set_clipboard,
+    );
+    set_function_to(scope, vim, "opt_get_paste", global_rsvim::opt::get_paste);
+    set_function_to(scope, vim, "opt_set_paste", global_rsvim::opt::set_paste);
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_lang_map",
+      global_rsvim::opt::get_lang_map,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_lang_map",
+      global_rsvim::opt::set_lang_map,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_map_leader",
+      global_rsvim::opt::get_map_leader,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_map_leader",
+      global_rsvim::opt::set_map_leader,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_timeout_len",
+      global_rsvim::opt::get_timeout_len,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_timeout_len",
+      global_rsvim::opt::set_timeout_len,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_t_timeout",
+      global_rsvim::opt::get_t_timeout,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_t_timeout",
+      global_rsvim::opt::set_t_timeout,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_t_timeout_len",
+      global_rsvim::opt::get_t_timeout_len,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_t_timeout_len",
+      global_rsvim::opt::set_t_timeout_len,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_cursor_word",
+      global_rsvim::opt::get_cursor_word,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_cursor_word",
+      global_rsvim::opt::set_cursor_word,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_minimap",
+      global_rsvim::opt::get_minimap,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_minimap",
+      global_rsvim::opt::set_minimap,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_hl_search",
+      global_rsvim::opt::get_hl_search,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_hl_search",
+      global_rsvim::opt::set_hl_search,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_inc_search",
+      global_rsvim::opt::get_inc_search,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_inc_search",
+      global_rsvim::opt::set_inc_search,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_auto_pairs",
+      global_rsvim::opt::get_auto_pairs,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_auto_pairs",
+      global_rsvim::opt::set_auto_pairs,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_jump_center",
+      global_rsvim::opt::get_jump_center,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_jump_center",
+      global_rsvim::opt::set_jump_center,
+    );
+    set_function_to(scope, vim, "opt_get_title", global_rsvim::opt::get_title);
+    set_function_to(scope, vim, "opt_set_title", global_rsvim::opt::set_title);
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_title_string",
+      global_rsvim::opt::get_title_string,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_title_string",
+      global_rsvim::opt::set_title_string,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_quickfix_preview",
+      global_rsvim::opt::get_quickfix_preview,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_quickfix_preview",
+      global_rsvim::opt::set_quickfix_preview,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_status_line",
+      global_rsvim::opt::get_status_line,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_status_line",
+      global_rsvim::opt::set_status_line,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_win_fix_width",
+      global_rsvim::opt::get_win_fix_width,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_win_fix_width",
+      global_rsvim::opt::set_win_fix_width,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_win_fix_height",
+      global_rsvim::opt::get_win_fix_height,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_win_fix_height",
+      global_rsvim::opt::set_win_fix_height,
+    );
+    set_function_to(scope, vim, "opt_get_number", global_rsvim::opt::get_number);
+    set_function_to(scope, vim, "opt_set_number", global_rsvim::opt::set_number);
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_relative_number",
+      global_rsvim::opt::get_relative_number,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_relative_number",
+      global_rsvim::opt::set_relative_number,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_number_width",
+      global_rsvim::opt::get_number_width,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_number_width",
+      global_rsvim::opt::set_number_width,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_sign_column",
+      global_rsvim::opt::get_sign_column,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_sign_column",
+      global_rsvim::opt::set_sign_column,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_sign_width",
+      global_rsvim::opt::get_sign_width,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_sign_width",
+      global_rsvim::opt::set_sign_width,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_wild_mode",
+      global_rsvim::opt::get_wild_mode,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_wild_mode",
+      global_rsvim::opt::set_wild_mode,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_wild_options",
+      global_rsvim::opt::get_wild_options,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_wild_options",
+      global_rsvim::opt::set_wild_options,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_term_gui_colors",
+      global_rsvim::opt::get_term_gui_colors,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_term_gui_colors",
+      global_rsvim::opt::set_term_gui_colors,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_term_sync",
+      global_rsvim::opt::get_term_sync,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_term_sync",
+      global_rsvim::opt::set_term_sync,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_term_strikethrough",
+      global_rsvim::opt::get_term_strikethrough,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_term_strikethrough",
+      global_rsvim::opt::set_term_strikethrough,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_term_undercurl",
+      global_rsvim::opt::get_term_undercurl,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_term_undercurl",
+      global_rsvim::opt::set_term_undercurl,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_term_paste",
+      global_rsvim::opt::get_term_paste,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_term_paste",
+      global_rsvim::opt::set_term_paste,
+    );
+    set_function_to(scope, vim, "opt_get_backup", global_rsvim::opt::get_backup);
+    set_function_to(scope, vim, "opt_set_backup", global_rsvim::opt::set_backup);
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_write_backup",
+      global_rsvim::opt::get_write_backup,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_write_backup",
+      global_rsvim::opt::set_write_backup,
+    );
+    set_function_to(scope, vim, "opt_get_fsync", global_rsvim::opt::get_fsync);
+    set_function_to(scope, vim, "opt_set_fsync", global_rsvim::opt::set_fsync);
+    set_function_to(scope, vim, "opt_get_mkdir", global_rsvim::opt::get_mkdir);
+    set_function_to(scope, vim, "opt_set_mkdir", global_rsvim::opt::set_mkdir);
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_last_pos_exclude",
+      global_rsvim::opt::get_last_pos_exclude,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_last_pos_exclude",
+      global_rsvim::opt::set_last_pos_exclude,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_undo_levels",
+      global_rsvim::opt::get_undo_levels,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_undo_levels",
+      global_rsvim::opt::set_undo_levels,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_undo_memory",
+      global_rsvim::opt::get_undo_memory,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_undo_memory",
+      global_rsvim::opt::set_undo_memory,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_get_undo_file",
+      global_rsvim::opt::get_undo_file,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "opt_set_undo_file",
+      global_rsvim::opt::set_undo_file,
+    );
+  }
+
+  // `Rsvim.progress`
+  {
+    set_function_to(scope, vim, "progress_begin", global_rsvim::progress::begin);
+    set_function_to(
+      scope,
+      vim,
+      "progress_update",
+      global_rsvim::progress::update,
+    );
+    set_function_to(scope, vim, "progress_end", global_rsvim::progress::end);
+    set_function_to(
+      scope,
+      vim,
+      "progress_is_cancelled",
+      global_rsvim::progress::is_cancelled,
+    );
+  }
+
+  // `Rsvim.input`
+  {
+    set_function_to(scope, vim, "input_getchar", global_rsvim::input::getchar);
+    set_function_to(
+      scope,
+      vim,
+      "input_typeahead",
+      global_rsvim::input::typeahead,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "input_batch_begin",
+      global_rsvim::input::batch_begin,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "input_batch_end",
+      global_rsvim::input::batch_end,
+    );
+    set_function_to(scope, vim, "input_is_batch", global_rsvim::input::is_batch);
+  }
+
+  // `Rsvim.hl`
+  {
+    set_function_to(
+      scope,
+      vim,
+      "hl_create_namespace",
+      global_rsvim::hl::create_namespace,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "hl_add_highlight",
+      global_rsvim::hl::add_highlight,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "hl_clear_namespace",
+      global_rsvim::hl::clear_namespace,
+    );
+    set_function_to(scope, vim, "hl_set_group", global_rsvim::hl::set_group);
+  }
+
+  // `Rsvim.ui`
+  {
+    set_function_to(scope, vim, "ui_redraw", global_rsvim::ui::redraw);
+  }
+
+  // `Rsvim.win`
+  {
+    set_function_to(scope, vim, "win_open_float", global_rsvim::win::open_float);
+    set_function_to(
+      scope,
+      vim,
+      "win_close_float",
+      global_rsvim::win::close_float,
+    );
+    set_function_to(scope, vim, "win_current", global_rsvim::win::current);
+    set_function_to(scope, vim, "win_focus", global_rsvim::win::focus);
+    set_function_to(scope, vim, "win_set_cursor", global_rsvim::win::set_cursor);
+  }
+
+  // `Rsvim.register`
+  {
+    set_function_to(scope, vim, "register_get", global_rsvim::register::get);
+    set_function_to(scope, vim, "register_set", global_rsvim::register::set);
+  }
+
+  // `Rsvim.mark`
+  {
+    set_function_to(scope, vim, "mark_get", global_rsvim::mark::get);
+    set_function_to(scope, vim, "mark_set", global_rsvim::mark::set);
+  }
+
+  // `Rsvim.template`
+  {
+    set_function_to(scope, vim, "template_add", global_rsvim::template::add);
+    set_function_to(
+      scope,
+      vim,
+      "template_set_variable",
+      global_rsvim::template::set_variable,
+    );
+  }
+
+  // `Rsvim.filetype`
+  {
+    set_function_to(scope, vim, "filetype_add", global_rsvim::filetype::add);
+    set_function_to(scope, vim, "filetype_set", global_rsvim::filetype::set);
+  }
+
+  // `Rsvim.sign`
+  {
+    set_function_to(scope, vim, "sign_define", global_rsvim::sign::define);
+    set_function_to(scope, vim, "sign_undefine", global_rsvim::sign::undefine);
+    set_function_to(scope, vim, "sign_place", global_rsvim::sign::place);
+    set_function_to(scope, vim, "sign_unplace", global_rsvim::sign::unplace);
+    set_function_to(scope, vim, "sign_clear", global_rsvim::sign::clear);
+    set_function_to(
+      scope,
+      vim,
+      "sign_get_placed",
+      global_rsvim::sign::get_placed,
+    );
+  }
+
+  // `Rsvim.plugin`
+  {
+    set_function_to(scope, vim, "plugin_load", global_rsvim::plugin::load);
+    set_function_to(scope, vim, "plugin_list", global_rsvim::plugin::list);
+  }
+
+  // `Rsvim.spell`
+  {
+    set_function_to(scope, vim, "spell_add", global_rsvim::spell::add);
+    set_function_to(scope, vim, "spell_check", global_rsvim::spell::check);
+    set_function_to(scope, vim, "spell_words", global_rsvim::spell::words);
+    set_function_to(scope, vim, "spell_reload", global_rsvim::spell::reload);
+  }
+
+  // `Rsvim.git`
+  {
+    set_function_to(scope, vim, "git_status", global_rsvim::git::status);
+    set_function_to(scope, vim, "git_refresh", global_rsvim::git::refresh);
+  }
+
+  // `Rsvim.statusline`
+  {
+    set_function_to(
+      scope,
+      vim,
+      "statusline_set_component",
+      global_rsvim::statusline::set_component,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "statusline_remove_component",
+      global_rsvim::statusline::remove_component,
+    );
+  }
+
+  // `Rsvim.on`/`Rsvim.off`
+  {
+    set_function_to(scope, vim, "autocmd_on", global_rsvim::autocmd::on);
+    set_function_to(scope, vim, "autocmd_off", global_rsvim::autocmd::off);
+  }
+
+  // `Rsvim.keymap`
+  {
+    set_function_to(scope, vim, "keymap_set", global_rsvim::keymap::set);
+    set_function_to(scope, vim, "keymap_del", global_rsvim::keymap::del);
+  }
+
+  // `Rsvim.job`
+  {
+    set_function_to(scope, vim, "job_spawn", global_rsvim::job::spawn);
+    set_function_to(scope, vim, "job_send", global_rsvim::job::send);
+    set_function_to(
+      scope,
+      vim,
+      "job_close_stdin",
+      global_rsvim::job::close_stdin,
+    );
+    set_function_to(scope, vim, "job_kill", global_rsvim::job::kill);
+  }
+
+  // `Rsvim.fs`
+  {
+    set_function_to(scope, vim, "fs_read_file", global_rsvim::fs::read_file);
+    set_function_to(scope, vim, "fs_write_file", global_rsvim::fs::write_file);
+    set_function_to(scope, vim, "fs_stat", global_rsvim::fs::stat);
+    set_function_to(scope, vim, "fs_exists", global_rsvim::fs::exists);
+    set_function_to(scope, vim, "fs_read_dir", global_rsvim::fs::read_dir);
+    set_function_to(scope, vim, "fs_mkdir", global_rsvim::fs::mkdir);
+    set_function_to(scope, vim, "fs_remove", global_rsvim::fs::remove);
+    set_function_to(scope, vim, "fs_find_up", global_rsvim::fs::find_up);
+    set_function_to(scope, vim, "fs_walk", global_rsvim::fs::walk);
+    set_function_to(scope, vim, "fs_join", global_rsvim::fs::join);
+    set_function_to(scope, vim, "fs_normalize", global_rsvim::fs::normalize);
+  }
+
+  // `Rsvim.grep`
+  {
+    set_function_to(scope, vim, "grep", global_rsvim::grep::grep);
+  }
+
+  // `Rsvim.picker`
+  {
+    set_function_to(scope, vim, "picker_open", global_rsvim::picker::open);
+    set_function_to(scope, vim, "picker_push", global_rsvim::picker::push);
+    set_function_to(scope, vim, "picker_done", global_rsvim::picker::done);
+  }
+
+  // `Rsvim.cmd`
+  {
+    set_function_to(scope, vim, "cmd", global_rsvim::cmd::execute);
+  }
+
+  // `Rsvim.notify`
+  {
+    set_function_to(scope, vim, "notify", global_rsvim::msg::notify);
+  }
+
+  // `Rsvim.buf`
+  {
+    set_function_to(scope, vim, "buf_get_lines", global_rsvim::buf::get_lines);
+    set_function_to(scope, vim, "buf_set_lines", global_rsvim::buf::set_lines);
+    set_function_to(scope, vim, "buf_list", global_rsvim::buf::list);
+    set_function_to(
+      scope,
+      vim,
+      "buf_is_modified",
+      global_rsvim::buf::is_modified,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "buf_set_extmark",
+      global_rsvim::buf::set_extmark,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "buf_get_extmark",
+      global_rsvim::buf::get_extmark,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "buf_del_extmark",
+      global_rsvim::buf::del_extmark,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "buf_clear_extmarks",
+      global_rsvim::buf::clear_extmarks,
+    );
   }
 
   // Expose low-level functions to JavaScript.
@@ -1,3 +1,26 @@
 //! APIs for `Rsvim` namespace.
 
+pub mod autocmd;
+pub mod buf;
+pub mod cmd;
+pub mod filetype;
+pub mod fs;
+pub mod git;
+pub mod grep;
+pub mod hl;
+pub mod input;
+pub mod job;
+pub mod keymap;
+pub mod mark;
+pub mod msg;
 pub mod opt;
+pub mod picker;
+pub mod plugin;
+pub mod progress;
+pub mod register;
+pub mod sign;
+pub mod spell;
+pub mod statusline;
+pub mod template;
+pub mod ui;
+pub mod win;
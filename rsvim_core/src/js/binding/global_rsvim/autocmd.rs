@@ -0,0 +1,59 @@
+//! APIs for `Rsvim.on` and `Rsvim.off`, i.e. the autocmd events.
+
+use crate::envar;
+use crate::js::{binding, JsRuntime};
+use crate::state::autocmd::AutocmdEvent;
+use crate::wlock;
+
+use tracing::trace;
+
+/// Subscribe the event with the glob pattern, returns the subscription ID.
+pub fn on(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let event = match name.parse::<AutocmdEvent>() {
+    Ok(event) => event,
+    Err(_) => {
+      binding::throw_type_error(scope, &format!("Invalid event name: {:?}", name));
+      return;
+    }
+  };
+  let pattern = args.get(1).to_rust_string_lossy(scope);
+  let callback = match v8::Local::<v8::Function>::try_from(args.get(2)) {
+    Ok(callback) => v8::Global::new(scope, callback),
+    Err(_) => {
+      binding::throw_type_error(scope, "Callback must be a function");
+      return;
+    }
+  };
+
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let id = wlock!(editing_state)
+    .autocmds_mut()
+    .subscribe(event, &pattern);
+  trace!("autocmd_on: {:?}, {:?}, {:?}", event, pattern, id);
+  state_rc.borrow_mut().autocmd_callbacks.insert(id, callback);
+  rv.set_int32(id);
+}
+
+/// Unsubscribe, returns `false` if the subscription doesn't exist.
+pub fn off(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let id = args.get(0).int32_value(scope).unwrap_or(0);
+
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let removed = wlock!(editing_state).autocmds_mut().unsubscribe(id);
+  state_rc.borrow_mut().autocmd_callbacks.remove(&id);
+  trace!("autocmd_off: {:?}, {:?}", id, removed);
+  rv.set_bool(removed);
+}
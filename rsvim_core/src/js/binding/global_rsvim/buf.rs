@@ -0,0 +1,313 @@
+//! APIs for `Rsvim.buf` namespace.
+
+use crate::buf::highlight::{parse_attrs, parse_color, HighlightStyle};
+use crate::buf::{Extmark, MarkPos};
+use crate::defaults;
+use crate::envar;
+use crate::js::{binding, JsRuntime};
+use crate::state::current_window_buffer;
+use crate::{rlock, wlock};
+
+use tracing::trace;
+
+/// Get the lines in range `[start, end)` of current buffer, see
+/// [`EditorApi::buf_get_lines`](crate::api::EditorApi::buf_get_lines). Returns `null` if there's
+/// no current buffer.
+pub fn get_lines(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let start = args.get(0).uint32_value(scope).unwrap_or(0) as usize;
+  let end = args.get(1).uint32_value(scope).unwrap_or(0) as usize;
+  let api = JsRuntime::state(scope).borrow().api();
+  let lines = match api.buf_get_lines(start, end) {
+    Some(lines) => lines,
+    None => {
+      rv.set_null();
+      return;
+    }
+  };
+
+  let elements = lines
+    .iter()
+    .map(|line| v8::String::new(scope, line).unwrap().into())
+    .collect::<Vec<v8::Local<v8::Value>>>();
+  rv.set(v8::Array::new_with_elements(scope, &elements).into());
+}
+
+/// Replace the lines in range `[start, end)` of current buffer with `lines`, see
+/// [`EditorApi::buf_set_lines`](crate::api::EditorApi::buf_set_lines).
+pub fn set_lines(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let start = args.get(0).uint32_value(scope).unwrap_or(0) as usize;
+  let end = args.get(1).uint32_value(scope).unwrap_or(0) as usize;
+  let lines = match v8::Local::<v8::Array>::try_from(args.get(2)) {
+    Ok(lines) => (0..lines.length())
+      .map(|i| {
+        let line = lines.get_index(scope, i).unwrap();
+        line.to_rust_string_lossy(scope)
+      })
+      .collect::<Vec<String>>(),
+    Err(_) => {
+      binding::throw_type_error(scope, "Lines must be an array of strings");
+      return;
+    }
+  };
+  let api = JsRuntime::state(scope).borrow().api();
+  match api.buf_set_lines(start, end, &lines) {
+    Ok(result) => rv.set_bool(result),
+    Err(e) => binding::throw_type_error(scope, &e),
+  }
+}
+
+/// List the buffers, see [`BuffersManager::list`](crate::buf::BuffersManager::list). The unlisted
+/// buffers are included if `all`.
+pub fn list(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let all = args.get(0).boolean_value(scope);
+  let api = JsRuntime::state(scope).borrow().api();
+  let list = api.buf_list(all);
+
+  let elements = list
+    .iter()
+    .map(|info| {
+      let target = v8::Object::new(scope);
+      let id = v8::Number::new(scope, info.id as f64);
+      binding::set_property_to(scope, target, "id", id.into());
+      let name: v8::Local<v8::Value> = match &info.name {
+        Some(name) => v8::String::new(scope, name).unwrap().into(),
+        None => v8::null(scope).into(),
+      };
+      binding::set_property_to(scope, target, "name", name);
+      let listed = v8::Boolean::new(scope, info.listed);
+      binding::set_property_to(scope, target, "listed", listed.into());
+      let modified = v8::Boolean::new(scope, info.modified);
+      binding::set_property_to(scope, target, "modified", modified.into());
+      target.into()
+    })
+    .collect::<Vec<v8::Local<v8::Value>>>();
+  rv.set(v8::Array::new_with_elements(scope, &elements).into());
+}
+
+/// Whether the buffer `id` is modified, see
+/// [`Buffer::is_modified`](crate::buf::Buffer::is_modified). Returns `null` if the buffer doesn't
+/// exist.
+pub fn is_modified(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let id = args.get(0).int32_value(scope).unwrap_or(0);
+  let api = JsRuntime::state(scope).borrow().api();
+  match api.buf_is_modified(id) {
+    Some(modified) => rv.set_bool(modified),
+    None => rv.set_null(),
+  }
+}
+
+// Parse the highlight style from the `fg`, `bg` (`undefined` if not specified) and the
+// comma-separated attributes arguments.
+fn parse_style(
+  scope: &mut v8::HandleScope,
+  fg: v8::Local<v8::Value>,
+  bg: v8::Local<v8::Value>,
+  attrs: v8::Local<v8::Value>,
+) -> Result<HighlightStyle, String> {
+  let mut colors = [None, None];
+  for (color, value) in colors.iter_mut().zip([fg, bg]) {
+    if value.is_string() {
+      let value = value.to_rust_string_lossy(scope);
+      match parse_color(&value) {
+        Some(c) => *color = Some(c),
+        None => return Err(format!("Invalid highlight color: {}", value)),
+      }
+    }
+  }
+  let attrs = parse_attrs(&attrs.to_rust_string_lossy(scope))?;
+  Ok(HighlightStyle::new(colors[0], colors[1], attrs))
+}
+
+/// Set an extmark at the position `(line, col)` of current buffer, see
+/// [`Extmark`](crate::buf::Extmark). Returns the extmark ID, or `0` if there's no current buffer
+/// or the ID to update doesn't exist.
+///
+/// The arguments are: namespace ID, line, col, the ID to update (`0` to create), end line (`-1`
+/// if no end), end col, whether has highlight, highlight fg/bg/attrs, virtual text (`undefined`
+/// if no virtual text), virtual text fg/bg/attrs, right gravity, end right gravity, priority
+/// (`-1` to use the namespace's priority).
+pub fn set_extmark(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 17);
+  let ns_id = args.get(0).int32_value(scope).unwrap().max(0) as usize;
+  let line_idx = args.get(1).int32_value(scope).unwrap().max(0) as usize;
+  let char_idx = args.get(2).int32_value(scope).unwrap().max(0) as usize;
+  let id = args.get(3).int32_value(scope).unwrap().max(0) as usize;
+  let end_line_idx = args.get(4).int32_value(scope).unwrap();
+  let end_char_idx = args.get(5).int32_value(scope).unwrap().max(0) as usize;
+  let style = if args.get(6).boolean_value(scope) {
+    match parse_style(scope, args.get(7), args.get(8), args.get(9)) {
+      Ok(style) => Some(style),
+      Err(e) => {
+        binding::throw_type_error(scope, &e);
+        return;
+      }
+    }
+  } else {
+    None
+  };
+  let virt_text = if args.get(10).is_string() {
+    let text = args.get(10).to_rust_string_lossy(scope);
+    match parse_style(scope, args.get(11), args.get(12), args.get(13)) {
+      Ok(style) => Some((text, style)),
+      Err(e) => {
+        binding::throw_type_error(scope, &e);
+        return;
+      }
+    }
+  } else {
+    None
+  };
+  let right_gravity = args.get(14).boolean_value(scope);
+  let end_right_gravity = args.get(15).boolean_value(scope);
+  let priority = args.get(16).int32_value(scope).unwrap();
+
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let id = match current_window_buffer(&tree) {
+    Some((buffer, _viewport)) => {
+      let mut buffer = wlock!(buffer);
+      let priority = if priority < 0 {
+        buffer
+          .highlights()
+          .get(ns_id)
+          .map(|ns| ns.priority())
+          .unwrap_or(defaults::highlight::DEFAULT_PRIORITY)
+      } else {
+        priority.clamp(0, u16::MAX as i32) as u16
+      };
+      let start = buffer.pos_to_char(MarkPos::new(line_idx, char_idx));
+      let mut extmark = Extmark::new(ns_id, start, priority);
+      if end_line_idx >= 0 {
+        let end = buffer.pos_to_char(MarkPos::new(end_line_idx as usize, end_char_idx));
+        extmark.end = Some(end.max(start));
+      }
+      extmark.right_gravity = right_gravity;
+      extmark.end_right_gravity = end_right_gravity;
+      extmark.style = style;
+      extmark.virt_text = virt_text;
+      if id == 0 {
+        buffer.extmarks_mut().add(extmark)
+      } else if buffer.extmarks_mut().replace(id, extmark) {
+        id
+      } else {
+        0
+      }
+    }
+    None => 0,
+  };
+  trace!(
+    "buf_set_extmark: {:?}, {:?}:{:?}, {:?}",
+    ns_id,
+    line_idx,
+    char_idx,
+    id
+  );
+  rv.set(v8::Number::new(scope, id as f64).into());
+}
+
+/// Get the extmark of current buffer, returns an object with `ns`, `line`, `col`, and `endLine`
+/// and `endCol` if it has end position. Returns `null` if it doesn't exist.
+pub fn get_extmark(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let id = args.get(0).int32_value(scope).unwrap().max(0) as usize;
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let position = current_window_buffer(&tree).and_then(|(buffer, _viewport)| {
+    let buffer = rlock!(buffer);
+    buffer.extmarks().get(id).map(|extmark| {
+      (
+        extmark.ns_id,
+        buffer.char_to_pos(extmark.start),
+        extmark.end.map(|end| buffer.char_to_pos(end)),
+      )
+    })
+  });
+  trace!("buf_get_extmark: {:?}, {:?}", id, position);
+
+  match position {
+    Some((ns_id, start, end)) => {
+      let target = v8::Object::new(scope);
+      let ns = v8::Number::new(scope, ns_id as f64);
+      binding::set_property_to(scope, target, "ns", ns.into());
+      let line = v8::Number::new(scope, start.line_idx as f64);
+      binding::set_property_to(scope, target, "line", line.into());
+      let col = v8::Number::new(scope, start.char_idx as f64);
+      binding::set_property_to(scope, target, "col", col.into());
+      if let Some(end) = end {
+        let end_line = v8::Number::new(scope, end.line_idx as f64);
+        binding::set_property_to(scope, target, "endLine", end_line.into());
+        let end_col = v8::Number::new(scope, end.char_idx as f64);
+        binding::set_property_to(scope, target, "endCol", end_col.into());
+      }
+      rv.set(target.into());
+    }
+    None => rv.set_null(),
+  }
+}
+
+/// Delete the extmark of current buffer, returns `false` if it doesn't exist.
+pub fn del_extmark(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let id = args.get(0).int32_value(scope).unwrap().max(0) as usize;
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  trace!("buf_del_extmark: {:?}", id);
+  let removed = match current_window_buffer(&tree) {
+    Some((buffer, _viewport)) => wlock!(buffer).extmarks_mut().remove(id).is_some(),
+    None => false,
+  };
+  rv.set_bool(removed);
+}
+
+/// Clear the extmarks of the namespace in current buffer, or all the extmarks if the namespace
+/// is `undefined`.
+pub fn clear_extmarks(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let ns_id = if args.get(0).is_number() {
+    Some(args.get(0).int32_value(scope).unwrap().max(0) as usize)
+  } else {
+    None
+  };
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  trace!("buf_clear_extmarks: {:?}", ns_id);
+  if let Some((buffer, _viewport)) = current_window_buffer(&tree) {
+    wlock!(buffer).extmarks_mut().clear(ns_id);
+  }
+}
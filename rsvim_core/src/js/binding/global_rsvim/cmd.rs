@@ -0,0 +1,19 @@
+//! APIs for `Rsvim.cmd`.
+
+use crate::js::{binding, JsRuntime};
+use crate::res::AnyErr;
+
+/// Execute the ex command line, see [`EditorApi::cmd`](crate::api::EditorApi::cmd). The error is
+/// thrown as an exception.
+pub fn execute(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let command_line = args.get(0).to_rust_string_lossy(scope);
+  let api = JsRuntime::state(scope).borrow().api();
+  if let Err(e) = api.cmd(&command_line) {
+    binding::throw_exception(scope, &AnyErr::msg(e.to_string()));
+  }
+}
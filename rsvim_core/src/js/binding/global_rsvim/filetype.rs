@@ -0,0 +1,64 @@
+//! APIs for `Rsvim.filetype` namespace.
+
+use crate::buf::FileTypeOptions;
+use crate::envar;
+use crate::js::JsRuntime;
+use crate::wlock;
+
+use tracing::trace;
+
+/// Add a filetype detection rule, the file matches the glob `pattern` is detected as `filetype`.
+pub fn add(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, _: v8::ReturnValue) {
+  assert!(args.length() == 2);
+  let pattern = args.get(0).to_rust_string_lossy(scope);
+  let file_type = args.get(1).to_rust_string_lossy(scope);
+  trace!("filetype_add: {:?}, {:?}", pattern, file_type);
+
+  let state_rc = JsRuntime::state(scope);
+  let buffers = state_rc.borrow().buffers.clone();
+  wlock!(buffers).file_types_mut().add(&pattern, &file_type);
+}
+
+/// Set the options of the filetype, they override the built-in option bundle. The `null` options
+/// are not changed.
+pub fn set(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, _: v8::ReturnValue) {
+  assert!(args.length() == 6);
+  let file_type = args.get(0).to_rust_string_lossy(scope);
+  let options = FileTypeOptions {
+    comment_string: optional_string(scope, args.get(1)),
+    tab_stop: optional_u16(scope, args.get(2)),
+    shift_width: optional_u16(scope, args.get(3)),
+    expand_tab: optional_bool(scope, args.get(4)),
+    suffixes_add: optional_string(scope, args.get(5)),
+  };
+  trace!("filetype_set: {:?}, {:?}", file_type, options);
+
+  let state_rc = JsRuntime::state(scope);
+  let buffers = state_rc.borrow().buffers.clone();
+  wlock!(buffers).file_types_mut().set(&file_type, &options);
+}
+
+// The `null`/`undefined` value is `None`.
+fn optional_string(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> Option<String> {
+  if value.is_null_or_undefined() {
+    None
+  } else {
+    Some(value.to_rust_string_lossy(scope))
+  }
+}
+
+fn optional_u16(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> Option<u16> {
+  if value.is_null_or_undefined() {
+    None
+  } else {
+    Some(value.int32_value(scope).unwrap().clamp(1, u16::MAX as i32) as u16)
+  }
+}
+
+fn optional_bool(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> Option<bool> {
+  if value.is_null_or_undefined() {
+    None
+  } else {
+    Some(value.to_boolean(scope).boolean_value(scope))
+  }
+}
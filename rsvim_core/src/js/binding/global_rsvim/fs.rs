@@ -0,0 +1,305 @@
+//! APIs for `Rsvim.fs` namespace.
+//!
+//! The file system operations run on the event loop (see [`fs`](crate::evloop::fs)), they return
+//! promises resolved once the operations are done. The path utilities are synchronous.
+
+use crate::evloop::fs::{self, FsDirEntry, FsEntryKind, FsOp, FsStat, FsValue, WalkOptions};
+use crate::js::msg::{self as jsmsg, JsRuntimeToEventLoopMessage};
+use crate::js::{self, binding, JsFuture, JsFutureId, JsRuntime};
+
+use std::path::{Path, PathBuf};
+use tracing::trace;
+
+/// The file system future, it resolves the promise with the result value, or rejects it with the
+/// error message.
+pub struct FsFuture {
+  pub future_id: JsFutureId,
+  pub resolver: v8::Global<v8::PromiseResolver>,
+  pub result: Result<FsValue, String>,
+}
+
+fn to_string_value<'s>(scope: &mut v8::HandleScope<'s>, value: &str) -> v8::Local<'s, v8::Value> {
+  v8::String::new(scope, value).unwrap().into()
+}
+
+fn to_stat_object<'s>(scope: &mut v8::HandleScope<'s>, stat: &FsStat) -> v8::Local<'s, v8::Value> {
+  let target = v8::Object::new(scope);
+  let is_file = v8::Boolean::new(scope, stat.is_file).into();
+  binding::set_property_to(scope, target, "isFile", is_file);
+  let is_directory = v8::Boolean::new(scope, stat.is_directory).into();
+  binding::set_property_to(scope, target, "isDirectory", is_directory);
+  let is_symlink = v8::Boolean::new(scope, stat.is_symlink).into();
+  binding::set_property_to(scope, target, "isSymlink", is_symlink);
+  let size = v8::Number::new(scope, stat.size as f64).into();
+  binding::set_property_to(scope, target, "size", size);
+  let mtime = match stat.mtime {
+    Some(mtime) => v8::Number::new(scope, mtime).into(),
+    None => v8::null(scope).into(),
+  };
+  binding::set_property_to(scope, target, "mtime", mtime);
+  let readonly = v8::Boolean::new(scope, stat.readonly).into();
+  binding::set_property_to(scope, target, "readonly", readonly);
+  target.into()
+}
+
+fn to_entry_object<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  entry: &FsDirEntry,
+) -> v8::Local<'s, v8::Value> {
+  let target = v8::Object::new(scope);
+  let name = to_string_value(scope, &entry.name);
+  binding::set_property_to(scope, target, "name", name);
+  let is_file = v8::Boolean::new(scope, entry.is_file).into();
+  binding::set_property_to(scope, target, "isFile", is_file);
+  let is_directory = v8::Boolean::new(scope, entry.is_directory).into();
+  binding::set_property_to(scope, target, "isDirectory", is_directory);
+  let is_symlink = v8::Boolean::new(scope, entry.is_symlink).into();
+  binding::set_property_to(scope, target, "isSymlink", is_symlink);
+  target.into()
+}
+
+impl JsFuture for FsFuture {
+  fn run(&mut self, scope: &mut v8::HandleScope) {
+    trace!(
+      "fs resolve:{:?}, ok:{:?}",
+      self.future_id,
+      self.result.is_ok()
+    );
+    let value: v8::Local<v8::Value> = match &self.result {
+      Ok(FsValue::None) => v8::undefined(scope).into(),
+      Ok(FsValue::Bool(value)) => v8::Boolean::new(scope, *value).into(),
+      Ok(FsValue::Text(text)) => to_string_value(scope, text),
+      Ok(FsValue::Stat(stat)) => to_stat_object(scope, stat),
+      Ok(FsValue::Entries(entries)) => {
+        let entries = entries
+          .iter()
+          .map(|entry| to_entry_object(scope, entry))
+          .collect::<Vec<_>>();
+        v8::Array::new_with_elements(scope, &entries).into()
+      }
+      Ok(FsValue::Path(path)) => match path {
+        Some(path) => to_string_value(scope, path),
+        None => v8::null(scope).into(),
+      },
+      Ok(FsValue::Paths(paths)) => {
+        let paths = paths
+          .iter()
+          .map(|path| to_string_value(scope, path))
+          .collect::<Vec<_>>();
+        v8::Array::new_with_elements(scope, &paths).into()
+      }
+      Err(message) => {
+        let message = v8::String::new(scope, message).unwrap();
+        let error = v8::Exception::error(scope, message);
+        self.resolver.open(scope).reject(scope, error);
+        return;
+      }
+    };
+    self.resolver.open(scope).resolve(scope, value);
+  }
+}
+
+// Send the operation to the event loop, returns the promise.
+fn send_fs_req(scope: &mut v8::HandleScope, op: FsOp, mut rv: v8::ReturnValue) {
+  let resolver = v8::PromiseResolver::new(scope).unwrap();
+  let promise = resolver.get_promise(scope);
+  let resolver = v8::Global::new(scope, resolver);
+
+  let future_id = js::next_future_id();
+  trace!("fs:{:?}, op:{:?}", future_id, op);
+  let state_rc = JsRuntime::state(scope);
+  let mut state = state_rc.borrow_mut();
+  state.fs_resolvers.insert(future_id, resolver);
+  let js_runtime_send_to_master = state.js_runtime_send_to_master.clone();
+  let current_handle = tokio::runtime::Handle::current();
+  current_handle.spawn_blocking(move || {
+    let _ = js_runtime_send_to_master.blocking_send(JsRuntimeToEventLoopMessage::FsReq(
+      jsmsg::FsReq::new(future_id, op),
+    ));
+  });
+  rv.set(promise.into());
+}
+
+fn path_arg(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> PathBuf {
+  PathBuf::from(value.to_rust_string_lossy(scope))
+}
+
+fn strings_arg(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> Option<Vec<String>> {
+  let array = v8::Local::<v8::Array>::try_from(value).ok()?;
+  Some(
+    (0..array.length())
+      .map(|i| {
+        let item = array.get_index(scope, i).unwrap();
+        item.to_rust_string_lossy(scope)
+      })
+      .collect(),
+  )
+}
+
+/// Read the file as UTF-8 text.
+pub fn read_file(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let path = path_arg(scope, args.get(0));
+  send_fs_req(scope, FsOp::ReadFile(path), rv);
+}
+
+/// Write the string or `Uint8Array` data to the file.
+pub fn write_file(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let path = path_arg(scope, args.get(0));
+  let data = if args.get(1).is_string() {
+    args.get(1).to_rust_string_lossy(scope).into_bytes()
+  } else {
+    match v8::Local::<v8::ArrayBufferView>::try_from(args.get(1)) {
+      Ok(view) => {
+        let mut data = vec![0_u8; view.byte_length()];
+        view.copy_contents(&mut data);
+        data
+      }
+      Err(_) => {
+        binding::throw_type_error(scope, "Data must be a string or an Uint8Array");
+        return;
+      }
+    }
+  };
+  let append = args.get(2).boolean_value(scope);
+  send_fs_req(scope, FsOp::WriteFile { path, data, append }, rv);
+}
+
+/// Get the file metadata.
+pub fn stat(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, rv: v8::ReturnValue) {
+  assert!(args.length() == 1);
+  let path = path_arg(scope, args.get(0));
+  send_fs_req(scope, FsOp::Stat(path), rv);
+}
+
+/// Whether the path exists.
+pub fn exists(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let path = path_arg(scope, args.get(0));
+  send_fs_req(scope, FsOp::Exists(path), rv);
+}
+
+/// List the directory entries.
+pub fn read_dir(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let path = path_arg(scope, args.get(0));
+  send_fs_req(scope, FsOp::ReadDir(path), rv);
+}
+
+/// Create the directory.
+pub fn mkdir(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let path = path_arg(scope, args.get(0));
+  let recursive = args.get(1).boolean_value(scope);
+  send_fs_req(scope, FsOp::Mkdir { path, recursive }, rv);
+}
+
+/// Remove the file or directory.
+pub fn remove(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let path = path_arg(scope, args.get(0));
+  let recursive = args.get(1).boolean_value(scope);
+  send_fs_req(scope, FsOp::Remove { path, recursive }, rv);
+}
+
+/// Find the first of the names in the directory and its ancestors, the promise is resolved with
+/// `null` if it's not found.
+pub fn find_up(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let names = match strings_arg(scope, args.get(0)) {
+    Some(names) => names,
+    None => {
+      binding::throw_type_error(scope, "Names must be an array of strings");
+      return;
+    }
+  };
+  let start = path_arg(scope, args.get(1));
+  let kind = match args.get(2).to_rust_string_lossy(scope).as_str() {
+    "file" => Some(FsEntryKind::File),
+    "directory" => Some(FsEntryKind::Directory),
+    _ => None,
+  };
+  send_fs_req(scope, FsOp::FindUp { names, start, kind }, rv);
+}
+
+/// Walk the directory recursively, the promise is resolved with the file paths.
+pub fn walk(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, rv: v8::ReturnValue) {
+  assert!(args.length() == 4);
+  let root = path_arg(scope, args.get(0));
+  let glob = if args.get(1).is_string() {
+    Some(args.get(1).to_rust_string_lossy(scope))
+  } else {
+    None
+  };
+  let hidden = args.get(2).boolean_value(scope);
+  let max_depth = if args.get(3).is_number() {
+    Some(args.get(3).integer_value(scope).unwrap().max(0) as usize)
+  } else {
+    None
+  };
+  let options = WalkOptions {
+    glob,
+    hidden,
+    max_depth,
+  };
+  send_fs_req(scope, FsOp::Walk { root, options }, rv);
+}
+
+/// Join the paths and normalize it.
+pub fn join(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let paths = match strings_arg(scope, args.get(0)) {
+    Some(paths) => paths,
+    None => {
+      binding::throw_type_error(scope, "Paths must be an array of strings");
+      return;
+    }
+  };
+  let path = fs::join_paths(&paths);
+  rv.set(to_string_value(scope, &path.to_string_lossy()));
+}
+
+/// Normalize the path lexically.
+pub fn normalize(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let path = args.get(0).to_rust_string_lossy(scope);
+  let path = fs::normalize_path(Path::new(&path));
+  rv.set(to_string_value(scope, &path.to_string_lossy()));
+}
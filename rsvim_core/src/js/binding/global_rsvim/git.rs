@@ -0,0 +1,60 @@
+//! APIs for `Rsvim.git` namespace.
+
+use crate::envar;
+use crate::js::{binding, JsRuntime};
+
+use tracing::trace;
+
+/// Get the cached git status, returns an object with `branch`, `dirty`, `ahead` and `behind`, or
+/// `null` if it's not a git repository or it's not refreshed yet.
+pub fn status(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 0);
+  let state_rc = JsRuntime::state(scope);
+  let status = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .git()
+    .status()
+    .cloned();
+  trace!("git_status: {:?}", status);
+
+  match status {
+    Some(status) => {
+      let target = v8::Object::new(scope);
+      let branch = v8::String::new(scope, &status.branch).unwrap();
+      binding::set_property_to(scope, target, "branch", branch.into());
+      let dirty = v8::Boolean::new(scope, status.dirty);
+      binding::set_property_to(scope, target, "dirty", dirty.into());
+      let ahead = v8::Number::new(scope, status.ahead as f64);
+      binding::set_property_to(scope, target, "ahead", ahead.into());
+      let behind = v8::Number::new(scope, status.behind as f64);
+      binding::set_property_to(scope, target, "behind", behind.into());
+      rv.set(target.into());
+    }
+    None => rv.set_null(),
+  }
+}
+
+/// Request a git status refresh, it runs on an async job.
+pub fn refresh(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 0);
+  trace!("git_refresh");
+  let state_rc = JsRuntime::state(scope);
+  state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .git_mut()
+    .request_refresh();
+}
@@ -0,0 +1,112 @@
+//! APIs for `Rsvim.grep`.
+//!
+//! The search runs on the event loop just like `:grep` (see [`grep`](crate::state::grep)), the
+//! quickfix list is replaced by the matches, and the promise is resolved once it's finished.
+
+use crate::buf::QuickfixEntry;
+use crate::js::msg::{self as jsmsg, JsRuntimeToEventLoopMessage};
+use crate::js::{self, binding, JsFuture, JsFutureId, JsRuntime};
+use crate::state::grep::GrepOptions;
+
+use std::path::PathBuf;
+use tracing::trace;
+
+/// The search future, it resolves the promise with the matches, or rejects it with the error
+/// message.
+pub struct GrepFuture {
+  pub future_id: JsFutureId,
+  pub resolver: v8::Global<v8::PromiseResolver>,
+  pub result: Result<Vec<QuickfixEntry>, String>,
+}
+
+fn to_entry_object<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  entry: &QuickfixEntry,
+) -> v8::Local<'s, v8::Value> {
+  let target = v8::Object::new(scope);
+  let path = v8::String::new(scope, &entry.path.to_string_lossy())
+    .unwrap()
+    .into();
+  binding::set_property_to(scope, target, "path", path);
+  let line = v8::Number::new(scope, entry.line_idx as f64).into();
+  binding::set_property_to(scope, target, "line", line);
+  let col = v8::Number::new(scope, entry.char_idx as f64).into();
+  binding::set_property_to(scope, target, "col", col);
+  let text = v8::String::new(scope, &entry.text).unwrap().into();
+  binding::set_property_to(scope, target, "text", text);
+  target.into()
+}
+
+impl JsFuture for GrepFuture {
+  fn run(&mut self, scope: &mut v8::HandleScope) {
+    trace!(
+      "grep resolve:{:?}, ok:{:?}",
+      self.future_id,
+      self.result.is_ok()
+    );
+    match &self.result {
+      Ok(entries) => {
+        let entries = entries
+          .iter()
+          .map(|entry| to_entry_object(scope, entry))
+          .collect::<Vec<_>>();
+        let value = v8::Array::new_with_elements(scope, &entries).into();
+        self.resolver.open(scope).resolve(scope, value);
+      }
+      Err(message) => {
+        let message = v8::String::new(scope, message).unwrap();
+        let error = v8::Exception::error(scope, message);
+        self.resolver.open(scope).reject(scope, error);
+      }
+    }
+  }
+}
+
+/// Search the files, the promise is resolved with the matches.
+pub fn grep(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 7);
+  let pattern = args.get(0).to_rust_string_lossy(scope);
+  let paths = match v8::Local::<v8::Array>::try_from(args.get(6)) {
+    Ok(array) => (0..array.length())
+      .map(|i| {
+        let item = array.get_index(scope, i).unwrap();
+        PathBuf::from(item.to_rust_string_lossy(scope))
+      })
+      .collect(),
+    Err(_) => {
+      binding::throw_type_error(scope, "Paths must be an array of strings");
+      return;
+    }
+  };
+  let options = GrepOptions {
+    pattern,
+    literal: args.get(1).boolean_value(scope),
+    ignore_case: args.get(2).boolean_value(scope),
+    smart_case: args.get(3).boolean_value(scope),
+    hidden: args.get(4).boolean_value(scope),
+    no_ignore: args.get(5).boolean_value(scope),
+    paths,
+  };
+
+  let resolver = v8::PromiseResolver::new(scope).unwrap();
+  let promise = resolver.get_promise(scope);
+  let resolver = v8::Global::new(scope, resolver);
+
+  let future_id = js::next_future_id();
+  trace!("grep:{:?}, options:{:?}", future_id, options);
+  let state_rc = JsRuntime::state(scope);
+  let mut state = state_rc.borrow_mut();
+  state.grep_resolvers.insert(future_id, resolver);
+  let js_runtime_send_to_master = state.js_runtime_send_to_master.clone();
+  let current_handle = tokio::runtime::Handle::current();
+  current_handle.spawn_blocking(move || {
+    let _ = js_runtime_send_to_master.blocking_send(JsRuntimeToEventLoopMessage::GrepReq(
+      jsmsg::GrepReq::new(future_id, options),
+    ));
+  });
+  rv.set(promise.into());
+}
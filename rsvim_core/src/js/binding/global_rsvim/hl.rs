@@ -0,0 +1,151 @@
+//! APIs for `Rsvim.hl` namespace.
+
+use crate::buf::highlight::{parse_attrs, parse_color, HighlightStyle};
+use crate::envar;
+use crate::js::{binding, JsRuntime};
+use crate::state::current_window_buffer;
+use crate::ui::highlight::HighlightGroup;
+use crate::wlock;
+
+use tracing::trace;
+
+// Parse the style from the `fg`, `bg` (the color is `undefined` if not specified) and `attrs`
+// (comma-separated attribute names) arguments starting at `first`, the type error is thrown if
+// it's invalid.
+fn style_from_args(
+  scope: &mut v8::HandleScope,
+  args: &v8::FunctionCallbackArguments,
+  first: i32,
+) -> Option<HighlightStyle> {
+  let mut colors = [None, None];
+  for (i, color) in colors.iter_mut().enumerate() {
+    let value = args.get(first + i as i32);
+    if value.is_string() {
+      let value = value.to_rust_string_lossy(scope);
+      match parse_color(&value) {
+        Some(c) => *color = Some(c),
+        None => {
+          binding::throw_type_error(scope, &format!("Invalid highlight color: {}", value));
+          return None;
+        }
+      }
+    }
+  }
+  let attrs = match parse_attrs(&args.get(first + 2).to_rust_string_lossy(scope)) {
+    Ok(attrs) => attrs,
+    Err(e) => {
+      binding::throw_type_error(scope, &e);
+      return None;
+    }
+  };
+  Some(HighlightStyle::new(colors[0], colors[1], attrs))
+}
+
+/// Create a highlight namespace in current buffer, returns its namespace ID (or `0` if there's no
+/// buffer).
+pub fn create_namespace(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let priority = args
+    .get(1)
+    .int32_value(scope)
+    .unwrap()
+    .clamp(0, u16::MAX as i32) as u16;
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let ns_id = match current_window_buffer(&tree) {
+    Some((buffer, _viewport)) => wlock!(buffer).highlights_mut().create(&name, priority),
+    None => 0,
+  };
+  trace!(
+    "hl_create_namespace: {:?}:{:?}, {:?}",
+    name,
+    priority,
+    ns_id
+  );
+  rv.set(v8::Number::new(scope, ns_id as f64).into());
+}
+
+/// Add a highlight to the char index range `[start, end)` of the line in current buffer, returns
+/// `false` if the namespace doesn't exist.
+pub fn add_highlight(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 7);
+  let ns_id = args.get(0).int32_value(scope).unwrap().max(0) as usize;
+  let line_idx = args.get(1).int32_value(scope).unwrap().max(0) as usize;
+  let start = args.get(2).int32_value(scope).unwrap().max(0) as usize;
+  let end = args.get(3).int32_value(scope).unwrap().max(0) as usize;
+  let style = match style_from_args(scope, &args, 4) {
+    Some(style) => style,
+    None => return,
+  };
+
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  trace!(
+    "hl_add_highlight: {:?}, {:?}:{:?}..{:?}, {:?}",
+    ns_id,
+    line_idx,
+    start,
+    end,
+    style
+  );
+  let added = match current_window_buffer(&tree) {
+    Some((buffer, _viewport)) => {
+      wlock!(buffer)
+        .highlights_mut()
+        .add(ns_id, line_idx, start..end, style)
+    }
+    None => false,
+  };
+  rv.set_bool(added);
+}
+
+/// Clear the highlights of the namespace in the line range `[start, end)` of current buffer, the
+/// `end` is the lines count if it's negative.
+pub fn clear_namespace(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let ns_id = args.get(0).int32_value(scope).unwrap().max(0) as usize;
+  let start = args.get(1).int32_value(scope).unwrap().max(0) as usize;
+  let end = args.get(2).int32_value(scope).unwrap();
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  trace!("hl_clear_namespace: {:?}, {:?}..{:?}", ns_id, start, end);
+  if let Some((buffer, _viewport)) = current_window_buffer(&tree) {
+    let end = if end < 0 { usize::MAX } else { end as usize };
+    wlock!(buffer).highlights_mut().clear(ns_id, start..end);
+  }
+}
+
+/// Set the highlight group, it links to the `link` group if it's a string.
+pub fn set_group(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 5);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let group = if args.get(4).is_string() {
+    HighlightGroup::linked(&args.get(4).to_rust_string_lossy(scope))
+  } else {
+    match style_from_args(scope, &args, 1) {
+      Some(style) => HighlightGroup::new(style),
+      None => return,
+    }
+  };
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  trace!("hl_set_group: {:?}, {:?}", name, group);
+  wlock!(tree).highlights_mut().set(&name, group);
+}
@@ -0,0 +1,159 @@
+//! APIs for `Rsvim.input` namespace.
+
+use crate::envar;
+use crate::js::msg::{self as jsmsg, JsRuntimeToEventLoopMessage};
+use crate::js::{self, JsFuture, JsFutureId, JsRuntime};
+
+use std::time::Duration;
+use tracing::trace;
+
+/// The `getchar` future, it resolves the promise with the pressed key, or `null` if timeout.
+pub struct GetcharFuture {
+  pub future_id: JsFutureId,
+  pub resolver: v8::Global<v8::PromiseResolver>,
+  pub key: Option<String>,
+}
+
+impl JsFuture for GetcharFuture {
+  fn run(&mut self, scope: &mut v8::HandleScope) {
+    trace!("getchar resolve:{:?}, key:{:?}", self.future_id, self.key);
+    let value: v8::Local<v8::Value> = match &self.key {
+      Some(key) => v8::String::new(scope, key).unwrap().into(),
+      None => v8::null(scope).into(),
+    };
+    self.resolver.open(scope).resolve(scope, value);
+  }
+}
+
+/// Wait for the next key press, returns a promise. In the batch mode, the promise is resolved with
+/// `null` immediately, see [`InputQueue`](crate::state::input::InputQueue).
+pub fn getchar(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let timeout = if args.get(0).is_number() {
+    Some(args.get(0).integer_value(scope).unwrap().max(0) as u64)
+  } else {
+    None
+  };
+
+  let resolver = v8::PromiseResolver::new(scope).unwrap();
+  let promise = resolver.get_promise(scope);
+  let resolver = v8::Global::new(scope, resolver);
+
+  let state_rc = JsRuntime::state(scope);
+  let mut state = state_rc.borrow_mut();
+  let future_id = js::next_future_id();
+  {
+    let mut editing_state = state
+      .editing_state
+      .try_write_for(envar::MUTEX_TIMEOUT())
+      .unwrap();
+    if editing_state.input().is_batch() {
+      trace!("getchar:{:?}, batch", future_id);
+      let null: v8::Local<v8::Value> = v8::null(scope).into();
+      resolver.open(scope).resolve(scope, null);
+      rv.set(promise.into());
+      return;
+    }
+    editing_state.input_mut().push_getchar(future_id);
+  }
+  state.getchar_resolvers.insert(future_id, resolver);
+
+  if let Some(millis) = timeout {
+    let js_runtime_send_to_master = state.js_runtime_send_to_master.clone();
+    let current_handle = tokio::runtime::Handle::current();
+    current_handle.spawn_blocking(move || {
+      let _ =
+        js_runtime_send_to_master.blocking_send(JsRuntimeToEventLoopMessage::GetcharTimeoutReq(
+          jsmsg::GetcharTimeoutReq::new(future_id, Duration::from_millis(millis)),
+        ));
+    });
+  }
+
+  trace!("getchar:{:?}, timeout:{:?}", future_id, timeout);
+  rv.set(promise.into());
+}
+
+/// Count of the typed ahead keys that are not handled yet.
+pub fn typeahead(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .input()
+    .typeahead();
+  trace!("typeahead: {:?}", value);
+  rv.set(v8::Number::new(scope, value as f64).into());
+}
+
+/// Begin the (nested) batch mode, the waiting `getchar` requests are resolved with `null`.
+pub fn batch_begin(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let resolvers = {
+    let mut state = state_rc.borrow_mut();
+    let future_ids = state
+      .editing_state
+      .try_write_for(envar::MUTEX_TIMEOUT())
+      .unwrap()
+      .input_mut()
+      .begin_batch();
+    future_ids
+      .into_iter()
+      .filter_map(|future_id| state.getchar_resolvers.remove(&future_id))
+      .collect::<Vec<_>>()
+  };
+  trace!("batch_begin, resolve {:?} getchar", resolvers.len());
+  for resolver in resolvers {
+    let null: v8::Local<v8::Value> = v8::null(scope).into();
+    resolver.open(scope).resolve(scope, null);
+  }
+}
+
+/// End the (nested) batch mode, returns `false` if it's not in the batch mode.
+pub fn batch_end(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .input_mut()
+    .end_batch();
+  trace!("batch_end: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Whether it's in the batch mode.
+pub fn is_batch(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .input()
+    .is_batch();
+  trace!("is_batch: {:?}", value);
+  rv.set_bool(value);
+}
@@ -0,0 +1,169 @@
+//! APIs for `Rsvim.job` namespace.
+
+use crate::evloop::job::{JobControl, JobId, JobSignal};
+use crate::js::msg::{self as jsmsg, JsRuntimeToEventLoopMessage};
+use crate::js::{self, binding, JsFuture, JsRuntime};
+
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tracing::trace;
+
+/// The running job spawned by `Rsvim.job.spawn`, it's removed when the job exits.
+pub struct JobHandle {
+  /// Sends stdin data and signals to the job.
+  ///
+  /// NOTE: The channel is unbounded so the sending is non-blocking, and keeps the order.
+  pub control: UnboundedSender<JobControl>,
+  pub on_stdout: Option<v8::Global<v8::Function>>,
+  pub on_stderr: Option<v8::Global<v8::Function>>,
+  pub on_exit: Option<v8::Global<v8::Function>>,
+}
+
+/// The job future, it calls the job callback with the output line, or the exit code.
+pub struct JobFuture {
+  pub job_id: JobId,
+  pub callback: v8::Global<v8::Function>,
+  /// The output line, or the exit code (`null` if it's terminated by a signal).
+  pub value: Result<String, Option<i32>>,
+}
+
+impl JsFuture for JobFuture {
+  fn run(&mut self, scope: &mut v8::HandleScope) {
+    trace!("job callback:{:?}, value:{:?}", self.job_id, self.value);
+    let undefined = v8::undefined(scope).into();
+    let value: v8::Local<v8::Value> = match &self.value {
+      Ok(line) => v8::String::new(scope, line).unwrap().into(),
+      Err(Some(code)) => v8::Integer::new(scope, *code).into(),
+      Err(None) => v8::null(scope).into(),
+    };
+    let callback = v8::Local::new(scope, &self.callback);
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    callback.call(tc_scope, undefined, &[value]);
+
+    // Report if callback threw an exception.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+    }
+  }
+}
+
+fn function_arg(
+  scope: &mut v8::HandleScope,
+  value: v8::Local<v8::Value>,
+) -> Option<v8::Global<v8::Function>> {
+  v8::Local::<v8::Function>::try_from(value)
+    .ok()
+    .map(|callback| v8::Global::new(scope, callback))
+}
+
+/// Spawn a job, returns the job ID.
+pub fn spawn(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 5);
+  let cmd = match v8::Local::<v8::Array>::try_from(args.get(0)) {
+    Ok(cmd) => (0..cmd.length())
+      .map(|i| {
+        let arg = cmd.get_index(scope, i).unwrap();
+        arg.to_rust_string_lossy(scope)
+      })
+      .collect::<Vec<String>>(),
+    Err(_) => {
+      binding::throw_type_error(scope, "Command must be an array of strings");
+      return;
+    }
+  };
+  if cmd.is_empty() || cmd[0].is_empty() {
+    binding::throw_type_error(scope, "Command must not be empty");
+    return;
+  }
+  let cwd = if args.get(1).is_string() {
+    Some(args.get(1).to_rust_string_lossy(scope))
+  } else {
+    None
+  };
+  let (control, control_recv) = unbounded_channel();
+  let handle = JobHandle {
+    control,
+    on_stdout: function_arg(scope, args.get(2)),
+    on_stderr: function_arg(scope, args.get(3)),
+    on_exit: function_arg(scope, args.get(4)),
+  };
+
+  let job_id = js::next_future_id();
+  trace!("job_spawn:{:?}, cmd:{:?}, cwd:{:?}", job_id, cmd, cwd);
+  let state_rc = JsRuntime::state(scope);
+  let mut state = state_rc.borrow_mut();
+  state.jobs.insert(job_id, handle);
+  let js_runtime_send_to_master = state.js_runtime_send_to_master.clone();
+  let current_handle = tokio::runtime::Handle::current();
+  current_handle.spawn_blocking(move || {
+    let _ = js_runtime_send_to_master.blocking_send(JsRuntimeToEventLoopMessage::JobSpawnReq(
+      jsmsg::JobSpawnReq::new(job_id, cmd, cwd, control_recv),
+    ));
+  });
+  rv.set(v8::Integer::new(scope, job_id).into());
+}
+
+// Send the control message to the job, returns `false` if the job is not running.
+fn send_control(scope: &mut v8::HandleScope, job_id: JobId, msg: JobControl) -> bool {
+  let state_rc = JsRuntime::state(scope);
+  let state = state_rc.borrow();
+  match state.jobs.get(&job_id) {
+    Some(handle) => handle.control.send(msg).is_ok(),
+    None => false,
+  }
+}
+
+/// Write data to the job's stdin, returns `false` if the job is not running.
+pub fn send(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let job_id = args.get(0).int32_value(scope).unwrap_or(0);
+  let data = args.get(1).to_rust_string_lossy(scope);
+  trace!("job_send:{:?}, data:{:?}", job_id, data);
+  let sent = send_control(scope, job_id, JobControl::Stdin(data));
+  rv.set_bool(sent);
+}
+
+/// Close the job's stdin, returns `false` if the job is not running.
+pub fn close_stdin(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let job_id = args.get(0).int32_value(scope).unwrap_or(0);
+  trace!("job_close_stdin:{:?}", job_id);
+  let sent = send_control(scope, job_id, JobControl::CloseStdin);
+  rv.set_bool(sent);
+}
+
+/// Send a signal to the job, returns `false` if the job is not running.
+pub fn kill(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let job_id = args.get(0).int32_value(scope).unwrap_or(0);
+  let signal = args.get(1).to_rust_string_lossy(scope);
+  let signal = match JobSignal::parse(&signal) {
+    Some(signal) => signal,
+    None => {
+      binding::throw_type_error(scope, &format!("Unknown signal {signal:?}"));
+      return;
+    }
+  };
+  trace!("job_kill:{:?}, signal:{:?}", job_id, signal);
+  let sent = send_control(scope, job_id, JobControl::Kill(signal));
+  rv.set_bool(sent);
+}
@@ -0,0 +1,127 @@
+//! APIs for `Rsvim.keymap` namespace.
+
+use crate::buf::BufferId;
+use crate::envar;
+use crate::js::{binding, JsRuntime};
+use crate::state::current_window_buffer;
+use crate::state::keymap::{parse_map_modes, Keymap, KeymapRhs};
+use crate::state::mode::Mode;
+use crate::{rlock, wlock};
+
+use tracing::trace;
+
+// Parse the modes argument, throws a type error if it's invalid.
+fn modes_arg(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> Option<Vec<Mode>> {
+  let value = value.to_rust_string_lossy(scope);
+  match parse_map_modes(&value) {
+    Ok(modes) => Some(modes),
+    Err(e) => {
+      binding::throw_type_error(scope, &e);
+      None
+    }
+  }
+}
+
+// Parse the buffer argument: `-1` is global, `0` is current buffer, otherwise it's the buffer ID.
+fn buffer_arg(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> Option<BufferId> {
+  let buffer_id = value.int32_value(scope).unwrap_or(-1);
+  match buffer_id {
+    -1 => None,
+    0 => {
+      let state_rc = JsRuntime::state(scope);
+      let tree = state_rc.borrow().tree.clone();
+      current_window_buffer(&tree).map(|(buffer, _)| rlock!(buffer).id())
+    }
+    buffer_id => Some(buffer_id),
+  }
+}
+
+// Drop the callbacks of the replaced/removed mappings, if they're not used any more.
+fn drop_callbacks(scope: &mut v8::HandleScope, keymaps: Vec<Keymap>) {
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let editing_state = rlock!(editing_state);
+  for keymap in keymaps {
+    if let KeymapRhs::Callback(id) = keymap.rhs {
+      if !editing_state.keymaps().is_callback_used(id) {
+        state_rc.borrow_mut().keymap_callbacks.remove(&id);
+      }
+    }
+  }
+}
+
+/// Define a key mapping, the `rhs` is either the keys or a callback.
+pub fn set(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, _: v8::ReturnValue) {
+  assert!(args.length() == 6);
+  let modes = match modes_arg(scope, args.get(0)) {
+    Some(modes) => modes,
+    None => return,
+  };
+  let lhs = args.get(1).to_rust_string_lossy(scope);
+  let noremap = args.get(3).boolean_value(scope);
+  let buffer_id = buffer_arg(scope, args.get(4));
+  let desc = args.get(5).to_rust_string_lossy(scope);
+
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let callback = v8::Local::<v8::Function>::try_from(args.get(2))
+    .ok()
+    .map(|callback| v8::Global::new(scope, callback));
+  let result = {
+    let mut editing_state = wlock!(editing_state);
+    let keymaps = editing_state.keymaps_mut();
+    let rhs = match &callback {
+      Some(_) => KeymapRhs::Callback(keymaps.next_callback_id()),
+      None => {
+        let rhs = args.get(2).to_rust_string_lossy(scope);
+        KeymapRhs::Keys(keymaps.parse_keys(&rhs))
+      }
+    };
+    if let (Some(callback), KeymapRhs::Callback(id)) = (callback, &rhs) {
+      state_rc.borrow_mut().keymap_callbacks.insert(*id, callback);
+    }
+    trace!(
+      "keymap_set: {:?}, {:?}, {:?}, {:?}",
+      modes,
+      lhs,
+      rhs,
+      buffer_id
+    );
+    keymaps
+      .set(&modes, buffer_id, &lhs, rhs.clone(), noremap, &desc)
+      .map_err(|e| (e, rhs))
+  };
+  match result {
+    Ok(replaced) => drop_callbacks(scope, replaced),
+    Err((e, rhs)) => {
+      if let KeymapRhs::Callback(id) = rhs {
+        state_rc.borrow_mut().keymap_callbacks.remove(&id);
+      }
+      binding::throw_type_error(scope, &e);
+    }
+  }
+}
+
+/// Remove a key mapping, returns `false` if the mapping doesn't exist.
+pub fn del(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let modes = match modes_arg(scope, args.get(0)) {
+    Some(modes) => modes,
+    None => return,
+  };
+  let lhs = args.get(1).to_rust_string_lossy(scope);
+  let buffer_id = buffer_arg(scope, args.get(2));
+
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let removed = wlock!(editing_state)
+    .keymaps_mut()
+    .del(&modes, buffer_id, &lhs);
+  trace!("keymap_del: {:?}, {:?}, {:?}", modes, lhs, removed.len());
+  rv.set_bool(!removed.is_empty());
+  drop_callbacks(scope, removed);
+}
@@ -0,0 +1,98 @@
+//! APIs for `Rsvim.mark` namespace.
+
+use crate::buf::mark::{self, MarkPos};
+use crate::envar;
+use crate::js::{binding, JsRuntime};
+use crate::state::current_window_buffer;
+use crate::{rlock, wlock};
+
+use tracing::trace;
+
+// Parse the mark name, throws type error if it's invalid.
+fn parse_name(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> Option<char> {
+  let name = value.to_rust_string_lossy(scope);
+  let mut chars = name.chars();
+  match (chars.next(), chars.next()) {
+    (Some(c), None) if mark::is_valid_name(c) => Some(c),
+    _ => {
+      binding::throw_type_error(scope, &format!("Invalid mark name: {:?}", name));
+      None
+    }
+  }
+}
+
+/// Get the mark, returns an object with `bufferId`, `line` and `col`, or `null` if it's not set.
+///
+/// The global mark (`A`-`Z`) is searched in all buffers, the other marks are in current buffer.
+pub fn get(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let name = match parse_name(scope, args.get(0)) {
+    Some(name) => name,
+    None => return,
+  };
+
+  let state_rc = JsRuntime::state(scope);
+  let (tree, buffers) = {
+    let state = state_rc.borrow();
+    (state.tree.clone(), state.buffers.clone())
+  };
+  let value = if mark::is_global_name(name) {
+    rlock!(buffers).global_mark(name)
+  } else {
+    current_window_buffer(&tree).and_then(|(buffer, _viewport)| {
+      let buffer = rlock!(buffer);
+      buffer.marks().get(name).map(|pos| (buffer.id(), pos))
+    })
+  };
+  trace!("mark_get: {:?}, {:?}", name, value);
+
+  match value {
+    Some((buffer_id, pos)) => {
+      let target = v8::Object::new(scope);
+      let buffer_id = v8::Number::new(scope, buffer_id as f64);
+      binding::set_property_to(scope, target, "bufferId", buffer_id.into());
+      let line = v8::Number::new(scope, pos.line_idx as f64);
+      binding::set_property_to(scope, target, "line", line.into());
+      let col = v8::Number::new(scope, pos.char_idx as f64);
+      binding::set_property_to(scope, target, "col", col.into());
+      rv.set(target.into());
+    }
+    None => rv.set_null(),
+  }
+}
+
+/// Set the mark in current buffer, returns `false` if there's no current buffer.
+pub fn set(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let name = match parse_name(scope, args.get(0)) {
+    Some(name) => name,
+    None => return,
+  };
+  let line = args.get(1).uint32_value(scope).unwrap_or(0) as usize;
+  let col = args.get(2).uint32_value(scope).unwrap_or(0) as usize;
+
+  let state_rc = JsRuntime::state(scope);
+  let (tree, buffers) = {
+    let state = state_rc.borrow();
+    (state.tree.clone(), state.buffers.clone())
+  };
+  let buffer_id = match current_window_buffer(&tree) {
+    Some((buffer, _viewport)) => rlock!(buffer).id(),
+    None => {
+      rv.set_bool(false);
+      return;
+    }
+  };
+  let pos = MarkPos::new(line, col);
+  trace!("mark_set: {:?}, {:?}", name, pos);
+  let result = wlock!(buffers).set_mark(&buffer_id, name, pos);
+  rv.set_bool(result);
+}
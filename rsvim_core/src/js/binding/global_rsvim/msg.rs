@@ -0,0 +1,19 @@
+//! APIs for `Rsvim.notify`.
+
+use crate::js::{binding, JsRuntime};
+
+/// Show the message to the user, i.e. `Rsvim.notify("hello", "info")`, see
+/// [`EditorApi::notify`](crate::api::EditorApi::notify).
+pub fn notify(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let message = args.get(0).to_rust_string_lossy(scope);
+  let level = args.get(1).to_rust_string_lossy(scope);
+  let api = JsRuntime::state(scope).borrow().api();
+  if let Err(e) = api.notify(&message, &level) {
+    binding::throw_type_error(scope, &e);
+  }
+}
@@ -1,8 +1,15 @@
 //! APIs for `Rsvim.opt` namespace.
 
+use crate::buf::FsyncPolicy;
+use crate::clipboard::ClipboardOption;
 use crate::envar;
-use crate::js::JsRuntime;
+use crate::js::{binding, JsRuntime};
+use crate::ui::tree::JumpCenter;
+use crate::ui::widget::window::sign::SignColumn;
+use crate::ui::widget::window::statusline;
 
+use std::str::FromStr;
+use std::time::Duration;
 use tracing::trace;
 
 /// Get the _wrap_ option.
@@ -78,3 +85,1614 @@ pub fn set_line_break(
     .unwrap()
     .set_line_break(value);
 }
+
+/// Get the _scrolloff_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27scrolloff%27>.
+pub fn get_scroll_off(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .scroll_off();
+  trace!("get_scroll_off: {:?}", value);
+  rv.set_uint32(value.min(u32::MAX as usize) as u32);
+}
+
+/// Set the _scrolloff_ option.
+pub fn set_scroll_off(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).uint32_value(scope).unwrap_or(0);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_scroll_off: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_scroll_off(value as usize);
+}
+
+/// Get the _sidescroll_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27sidescroll%27>.
+pub fn get_side_scroll(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .side_scroll();
+  trace!("get_side_scroll: {:?}", value);
+  rv.set_uint32(value.min(u32::MAX as usize) as u32);
+}
+
+/// Set the _sidescroll_ option.
+pub fn set_side_scroll(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).uint32_value(scope).unwrap_or(0);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_side_scroll: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_side_scroll(value as usize);
+}
+
+/// Get the _sidescrolloff_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27sidescrolloff%27>.
+pub fn get_side_scroll_off(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .side_scroll_off();
+  trace!("get_side_scroll_off: {:?}", value);
+  rv.set_uint32(value.min(u32::MAX as usize) as u32);
+}
+
+/// Set the _sidescrolloff_ option.
+pub fn set_side_scroll_off(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).uint32_value(scope).unwrap_or(0);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_side_scroll_off: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_side_scroll_off(value as usize);
+}
+
+/// Get the _clipboard_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27clipboard%27>
+pub fn get_clipboard(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .clipboard()
+    .option();
+  trace!("get_clipboard: {:?}", value);
+  let value = v8::String::new(scope, &value.to_string()).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _clipboard_ option.
+pub fn set_clipboard(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  let value = match ClipboardOption::from_str(&value) {
+    Ok(value) => value,
+    Err(e) => {
+      trace!("set_clipboard failed: {:?}, {:?}", value, e);
+      return;
+    }
+  };
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_clipboard: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .clipboard_mut()
+    .set_option(value);
+}
+
+/// Get the _paste_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27paste%27>
+pub fn get_paste(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .paste()
+    .option();
+  trace!("get_paste: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _paste_ option.
+pub fn set_paste(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_paste: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .paste_mut()
+    .set_option(value);
+}
+
+/// Get the _langmap_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27langmap%27>
+pub fn get_lang_map(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .langmap()
+    .option()
+    .to_string();
+  trace!("get_lang_map: {:?}", value);
+  let value = v8::String::new(scope, &value).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _langmap_ option.
+pub fn set_lang_map(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_lang_map: {:?}", value);
+  let result = state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .langmap_mut()
+    .set_option(&value);
+  if let Err(e) = result {
+    binding::throw_type_error(scope, &e);
+  }
+}
+
+/// Get the _cursor-word_ option.
+pub fn get_cursor_word(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .cursor_word()
+    .option()
+    .to_string();
+  trace!("get_cursor_word: {:?}", value);
+  let value = v8::String::new(scope, &value).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _cursor-word_ option.
+pub fn set_cursor_word(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_cursor_word: {:?}", value);
+  let result = state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .cursor_word_mut()
+    .set_option(&value);
+  if let Err(e) = result {
+    binding::throw_type_error(scope, &e);
+  }
+}
+
+/// Get the _mapleader_ option.
+/// See: <https://vimhelp.org/map.txt.html#mapleader>
+pub fn get_map_leader(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .keymaps()
+    .leader()
+    .to_string();
+  trace!("get_map_leader: {:?}", value);
+  let value = v8::String::new(scope, &value).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _mapleader_ option.
+pub fn set_map_leader(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_map_leader: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .keymaps_mut()
+    .set_leader(&value);
+}
+
+/// Get the _timeoutlen_ option, in milliseconds.
+/// See: <https://vimhelp.org/options.txt.html#%27timeoutlen%27>
+pub fn get_timeout_len(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .keymaps()
+    .timeout_len()
+    .as_millis();
+  trace!("get_timeout_len: {:?}", value);
+  rv.set_uint32(value.min(u32::MAX as u128) as u32);
+}
+
+/// Set the _timeoutlen_ option, in milliseconds.
+pub fn set_timeout_len(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).uint32_value(scope).unwrap_or(0);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_timeout_len: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .keymaps_mut()
+    .set_timeout_len(Duration::from_millis(value as u64));
+}
+
+/// Get the _ttimeout_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27ttimeout%27>
+pub fn get_t_timeout(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .escape()
+    .ttimeout();
+  trace!("get_t_timeout: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _ttimeout_ option.
+pub fn set_t_timeout(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_t_timeout: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .escape_mut()
+    .set_ttimeout(value);
+}
+
+/// Get the _ttimeoutlen_ option, in milliseconds.
+/// See: <https://vimhelp.org/options.txt.html#%27ttimeoutlen%27>
+pub fn get_t_timeout_len(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .escape()
+    .ttimeout_len()
+    .as_millis();
+  trace!("get_t_timeout_len: {:?}", value);
+  rv.set_uint32(value.min(u32::MAX as u128) as u32);
+}
+
+/// Set the _ttimeoutlen_ option, in milliseconds.
+pub fn set_t_timeout_len(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).uint32_value(scope).unwrap_or(0);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_t_timeout_len: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .escape_mut()
+    .set_ttimeout_len(Duration::from_millis(value as u64));
+}
+
+/// Get the _minimap_ option.
+pub fn get_minimap(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .minimap();
+  trace!("get_minimap: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _minimap_ option.
+pub fn set_minimap(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_minimap: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_minimap(value);
+}
+
+/// Get the _statusline_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27statusline%27>.
+///
+/// NOTE: It returns the callback function if it's set by a function.
+pub fn get_status_line(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .statusline()
+    .to_string();
+  trace!("get_status_line: {:?}", value);
+  let callback = state_rc.borrow().statusline_callback.clone();
+  match callback {
+    Some(callback) if value.starts_with(statusline::EVAL_PREFIX) => {
+      rv.set(v8::Local::new(scope, callback).into());
+    }
+    _ => rv.set(v8::String::new(scope, &value).unwrap().into()),
+  }
+}
+
+/// Set the _statusline_ option, it accepts a format string, or a callback function that returns
+/// the format string on each redraw.
+pub fn set_status_line(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let state_rc = JsRuntime::state(scope);
+  let value = match v8::Local::<v8::Function>::try_from(args.get(0)) {
+    Ok(callback) => {
+      state_rc.borrow_mut().statusline_callback = Some(v8::Global::new(scope, callback));
+      statusline::EVAL_PREFIX.to_string()
+    }
+    Err(_) => {
+      state_rc.borrow_mut().statusline_callback = None;
+      args.get(0).to_rust_string_lossy(scope)
+    }
+  };
+  trace!("set_status_line: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_statusline(&value);
+}
+
+/// Get the _winfixwidth_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27winfixwidth%27>.
+pub fn get_win_fix_width(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .winfixwidth();
+  trace!("get_win_fix_width: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _winfixwidth_ option.
+pub fn set_win_fix_width(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_win_fix_width: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_winfixwidth(value);
+}
+
+/// Get the _winfixheight_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27winfixheight%27>.
+pub fn get_win_fix_height(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .winfixheight();
+  trace!("get_win_fix_height: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _winfixheight_ option.
+pub fn set_win_fix_height(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_win_fix_height: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_winfixheight(value);
+}
+
+/// Get the _number_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27number%27>.
+pub fn get_number(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .number();
+  trace!("get_number: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _number_ option.
+pub fn set_number(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_number: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_number(value);
+}
+
+/// Get the _relativenumber_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27relativenumber%27>.
+pub fn get_relative_number(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .relative_number();
+  trace!("get_relative_number: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _relativenumber_ option.
+pub fn set_relative_number(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_relative_number: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_relative_number(value);
+}
+
+/// Get the _numberwidth_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27numberwidth%27>.
+pub fn get_number_width(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .number_width();
+  trace!("get_number_width: {:?}", value);
+  rv.set_uint32(value as u32);
+}
+
+/// Set the _numberwidth_ option.
+pub fn set_number_width(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).uint32_value(scope).unwrap_or(0);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_number_width: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_number_width(value.min(u16::MAX as u32) as u16);
+}
+
+/// Get the _signcolumn_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27signcolumn%27>.
+pub fn get_sign_column(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .sign_column()
+    .to_string();
+  trace!("get_sign_column: {:?}", value);
+  let value = v8::String::new(scope, &value).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _signcolumn_ option.
+pub fn set_sign_column(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  trace!("set_sign_column: {:?}", value);
+  let value = match SignColumn::from_str(&value) {
+    Ok(value) => value,
+    Err(e) => {
+      binding::throw_type_error(scope, e);
+      return;
+    }
+  };
+  let state_rc = JsRuntime::state(scope);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_sign_column(value);
+}
+
+/// Get the _signwidth_ option.
+pub fn get_sign_width(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .sign_width();
+  trace!("get_sign_width: {:?}", value);
+  rv.set_uint32(value as u32);
+}
+
+/// Set the _signwidth_ option.
+pub fn set_sign_width(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).uint32_value(scope).unwrap_or(0);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_sign_width: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_sign_width(value.min(u16::MAX as u32) as u16);
+}
+
+/// Get the _hlsearch_ option.
+pub fn get_hl_search(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .hlsearch();
+  trace!("get_hl_search: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _hlsearch_ option.
+pub fn set_hl_search(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_hl_search: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_hlsearch(value);
+}
+
+/// Get the _incsearch_ option.
+pub fn get_inc_search(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .incsearch();
+  trace!("get_inc_search: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _incsearch_ option.
+pub fn set_inc_search(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_inc_search: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_incsearch(value);
+}
+
+/// Get the _autopairs_ option.
+pub fn get_auto_pairs(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .autopairs();
+  trace!("get_auto_pairs: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _autopairs_ option.
+pub fn set_auto_pairs(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_auto_pairs: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_autopairs(value);
+}
+
+/// Get the _jumpcenter_ option.
+pub fn get_jump_center(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .jump_center()
+    .to_string();
+  trace!("get_jump_center: {:?}", value);
+  let value = v8::String::new(scope, &value).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _jumpcenter_ option.
+pub fn set_jump_center(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  trace!("set_jump_center: {:?}", value);
+  let value = match JumpCenter::from_str(&value) {
+    Ok(value) => value,
+    Err(e) => {
+      binding::throw_type_error(scope, e);
+      return;
+    }
+  };
+  let state_rc = JsRuntime::state(scope);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_jump_center(value);
+}
+
+/// Get the _quickfixpreview_ option.
+pub fn get_quickfix_preview(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .quickfix_preview();
+  trace!("get_quickfix_preview: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _quickfixpreview_ option.
+pub fn set_quickfix_preview(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_quickfix_preview: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_quickfix_preview(value);
+}
+
+/// Get the _title_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27title%27>.
+pub fn get_title(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .title();
+  trace!("get_title: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _title_ option.
+pub fn set_title(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_title: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_title(value);
+}
+
+/// Get the _titlestring_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27titlestring%27>.
+pub fn get_title_string(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .titlestring()
+    .to_string();
+  trace!("get_title_string: {:?}", value);
+  let value = v8::String::new(scope, &value).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _titlestring_ option.
+pub fn set_title_string(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  trace!("set_title_string: {:?}", value);
+  let state_rc = JsRuntime::state(scope);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_titlestring(&value);
+}
+
+/// Get the _wildmode_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27wildmode%27>
+pub fn get_wild_mode(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .cmdline()
+    .options()
+    .wildmode()
+    .to_string();
+  trace!("get_wild_mode: {:?}", value);
+  let value = v8::String::new(scope, &value).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _wildmode_ option.
+pub fn set_wild_mode(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_wild_mode: {:?}", value);
+  let result = state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .cmdline_mut()
+    .options_mut()
+    .set_wildmode(&value);
+  if let Err(e) = result {
+    binding::throw_type_error(scope, &e);
+  }
+}
+
+/// Get the _wildoptions_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27wildoptions%27>
+pub fn get_wild_options(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .cmdline()
+    .options()
+    .wildoptions()
+    .to_string();
+  trace!("get_wild_options: {:?}", value);
+  let value = v8::String::new(scope, &value).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _wildoptions_ option.
+pub fn set_wild_options(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_wild_options: {:?}", value);
+  let result = state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .cmdline_mut()
+    .options_mut()
+    .set_wildoptions(&value);
+  if let Err(e) = result {
+    binding::throw_type_error(scope, &e);
+  }
+}
+
+/// Get the _termguicolors_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27termguicolors%27>
+pub fn get_term_gui_colors(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .termguicolors();
+  trace!("get_term_gui_colors: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _termguicolors_ option.
+pub fn set_term_gui_colors(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_term_gui_colors: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_termguicolors(value);
+}
+
+/// Get the _termsync_ option.
+pub fn get_term_sync(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .termsync();
+  trace!("get_term_sync: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _termsync_ option.
+pub fn set_term_sync(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_term_sync: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_termsync(value);
+}
+
+/// Get the _termstrikethrough_ option.
+pub fn get_term_strikethrough(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .termstrikethrough();
+  trace!("get_term_strikethrough: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _termstrikethrough_ option.
+pub fn set_term_strikethrough(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_term_strikethrough: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_termstrikethrough(value);
+}
+
+/// Get the _termundercurl_ option.
+pub fn get_term_undercurl(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .termundercurl();
+  trace!("get_term_undercurl: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _termundercurl_ option.
+pub fn set_term_undercurl(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_term_undercurl: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_termundercurl(value);
+}
+
+/// Get the _termpaste_ option.
+pub fn get_term_paste(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .tree
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .termpaste();
+  trace!("get_term_paste: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _termpaste_ option.
+pub fn set_term_paste(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_term_paste: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_termpaste(value);
+}
+
+/// Get the _backup_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27backup%27>
+pub fn get_backup(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .buffers
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .write_options()
+    .backup();
+  trace!("get_backup: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _backup_ option.
+pub fn set_backup(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_backup: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .buffers
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .write_options_mut()
+    .set_backup(value);
+}
+
+/// Get the _writebackup_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27writebackup%27>
+pub fn get_write_backup(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .buffers
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .write_options()
+    .write_backup();
+  trace!("get_write_backup: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _writebackup_ option.
+pub fn set_write_backup(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_write_backup: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .buffers
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .write_options_mut()
+    .set_write_backup(value);
+}
+
+/// Get the _fsync_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27fsync%27>
+pub fn get_fsync(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .buffers
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .write_options()
+    .fsync()
+    .to_string();
+  trace!("get_fsync: {:?}", value);
+  let value = v8::String::new(scope, &value).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _fsync_ option.
+pub fn set_fsync(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  trace!("set_fsync: {:?}", value);
+  let value = match FsyncPolicy::from_str(&value) {
+    Ok(value) => value,
+    Err(e) => {
+      binding::throw_type_error(scope, &e);
+      return;
+    }
+  };
+  let state_rc = JsRuntime::state(scope);
+  state_rc
+    .borrow_mut()
+    .buffers
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .write_options_mut()
+    .set_fsync(value);
+}
+
+/// Get the _mkdir_ option.
+pub fn get_mkdir(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .buffers
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .write_options()
+    .mkdir();
+  trace!("get_mkdir: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _mkdir_ option.
+pub fn set_mkdir(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_mkdir: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .buffers
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .write_options_mut()
+    .set_mkdir(value);
+}
+
+/// Get the _lastposexclude_ option.
+pub fn get_last_pos_exclude(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .buffers
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .positions()
+    .exclude()
+    .to_string();
+  trace!("get_last_pos_exclude: {:?}", value);
+  let value = v8::String::new(scope, &value).unwrap();
+  rv.set(value.into());
+}
+
+/// Set the _lastposexclude_ option.
+pub fn set_last_pos_exclude(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_rust_string_lossy(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_last_pos_exclude: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .buffers
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .positions_mut()
+    .set_exclude(&value);
+}
+
+/// Get the _undolevels_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27undolevels%27>
+pub fn get_undo_levels(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .buffers
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .local_options()
+    .undo_levels();
+  trace!("get_undo_levels: {:?}", value);
+  rv.set_uint32(value.min(u32::MAX as usize) as u32);
+}
+
+/// Set the _undolevels_ option.
+pub fn set_undo_levels(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).uint32_value(scope).unwrap_or(0) as usize;
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_undo_levels: {:?}", value);
+  let state = state_rc.borrow();
+  let mut buffers = state.buffers.try_write_for(envar::MUTEX_TIMEOUT()).unwrap();
+  let undo_memory = buffers.local_options().undo_memory();
+  buffers.set_undo_limits(value, undo_memory);
+}
+
+/// Get the _undomemory_ option, in bytes.
+pub fn get_undo_memory(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .buffers
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .local_options()
+    .undo_memory();
+  trace!("get_undo_memory: {:?}", value);
+  rv.set_double(value as f64);
+}
+
+/// Set the _undomemory_ option, in bytes.
+pub fn set_undo_memory(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).number_value(scope).unwrap_or(0.0) as usize;
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_undo_memory: {:?}", value);
+  let state = state_rc.borrow();
+  let mut buffers = state.buffers.try_write_for(envar::MUTEX_TIMEOUT()).unwrap();
+  let undo_levels = buffers.local_options().undo_levels();
+  buffers.set_undo_limits(undo_levels, value);
+}
+
+/// Get the _undofile_ option.
+/// See: <https://vimhelp.org/options.txt.html#%27undofile%27>
+pub fn get_undo_file(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .buffers
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .undo_file();
+  trace!("get_undo_file: {:?}", value);
+  rv.set_bool(value);
+}
+
+/// Set the _undofile_ option.
+pub fn set_undo_file(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let value = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("set_undo_file: {:?}", value);
+  state_rc
+    .borrow_mut()
+    .buffers
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_undo_file(value);
+}
@@ -0,0 +1,124 @@
+//! APIs for `Rsvim.picker` namespace.
+
+use crate::envar;
+use crate::js::{binding, JsRuntime};
+use crate::state::finder::{next_finder_id, Finder, FinderId, FinderItem, FinderSource};
+use crate::state::fsm::FinderStateful;
+use crate::wlock;
+
+use tracing::trace;
+
+// Parse the strings array argument, the missing elements are empty strings.
+fn strings_arg(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> Option<Vec<String>> {
+  let array = v8::Local::<v8::Array>::try_from(value).ok()?;
+  Some(
+    (0..array.length())
+      .map(|i| match array.get_index(scope, i) {
+        Some(value) if !value.is_null_or_undefined() => value.to_rust_string_lossy(scope),
+        _ => String::new(),
+      })
+      .collect(),
+  )
+}
+
+/// Open the fuzzy finder, the `source` is the builtin source name (see
+/// [`FinderSource::parse`]), or `null` if the items are pushed by JS. The `callback` is called
+/// once the finder is closed, see [`JsRuntime::call_finder`].
+///
+/// Returns the finder ID.
+pub fn open(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let title = args.get(0).to_rust_string_lossy(scope);
+  let source = if args.get(1).is_null_or_undefined() {
+    FinderSource::Items
+  } else {
+    let name = args.get(1).to_rust_string_lossy(scope);
+    match FinderSource::parse(&name) {
+      Some(source) => source,
+      None => {
+        binding::throw_type_error(scope, &format!("Unknown picker source: {}", name));
+        return;
+      }
+    }
+  };
+  let callback = match v8::Local::<v8::Function>::try_from(args.get(2)) {
+    Ok(callback) => v8::Global::new(scope, callback),
+    Err(_) => {
+      binding::throw_type_error(scope, "Picker callback must be a function");
+      return;
+    }
+  };
+
+  let finder_id = next_finder_id();
+  trace!("picker_open: {:?}, {:?}, {:?}", finder_id, title, source);
+  let state_rc = JsRuntime::state(scope);
+  state_rc
+    .borrow_mut()
+    .picker_callbacks
+    .insert(finder_id, callback);
+  let tree = state_rc.borrow().tree.clone();
+  let editing_state = state_rc.borrow().editing_state.clone();
+  wlock!(editing_state).open_finder(&tree, Finder::new(finder_id, &title, source));
+  rv.set_int32(finder_id as i32);
+}
+
+// Append the items to the finder, or finish the population.
+//
+// Returns `false` if the finder is already closed.
+fn populate(
+  scope: &mut v8::HandleScope,
+  finder_id: FinderId,
+  items: Vec<FinderItem>,
+  done: bool,
+) -> bool {
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let mut editing_state = wlock!(editing_state);
+  FinderStateful::populate(&mut editing_state, &tree, finder_id, items, done)
+}
+
+/// Push the items to the finder, the `labels` and `descriptions` are in the same length.
+///
+/// Returns `false` if the finder is already closed, i.e. the population should stop.
+pub fn push(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let finder_id = args.get(0).int32_value(scope).unwrap_or(0) as FinderId;
+  let (labels, descriptions) = match (
+    strings_arg(scope, args.get(1)),
+    strings_arg(scope, args.get(2)),
+  ) {
+    (Some(labels), Some(descriptions)) => (labels, descriptions),
+    _ => {
+      binding::throw_type_error(scope, "Picker items must be arrays of strings");
+      return;
+    }
+  };
+  let items = labels
+    .iter()
+    .enumerate()
+    .map(|(i, label)| FinderItem::new(label, descriptions.get(i).map_or("", |d| d.as_str())))
+    .collect();
+  rv.set_bool(populate(scope, finder_id, items, false));
+}
+
+/// All the items are pushed to the finder.
+///
+/// Returns `false` if the finder is already closed.
+pub fn done(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let finder_id = args.get(0).int32_value(scope).unwrap_or(0) as FinderId;
+  rv.set_bool(populate(scope, finder_id, vec![], true));
+}
@@ -0,0 +1,105 @@
+//! APIs for `Rsvim.plugin` namespace.
+
+use crate::js::binding;
+use crate::js::hook::module_resolve_cb;
+use crate::js::loader::{list_plugins, plugin_dirs, resolve_package};
+use crate::js::module::fetch_module_tree;
+use crate::js::JsRuntime;
+
+use tracing::trace;
+
+/// Load the plugin package `name` (or its sub path, i.e. `name/util`), returns the module
+/// namespace. The loaded module is cached, thus it's evaluated only once, same with the `import`
+/// statement.
+///
+/// Throws error if the plugin is not found, or failed to compile or evaluate.
+pub fn load(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let path = match resolve_package(&plugin_dirs(), &name) {
+    Ok(path) => path,
+    Err(e) => {
+      binding::throw_exception(scope, &e);
+      return;
+    }
+  };
+  let cached = JsRuntime::state(scope).borrow().module_map.get(&path);
+  trace!(
+    "plugin_load: {:?}, {:?}, {:?}",
+    name,
+    path,
+    cached.is_some()
+  );
+
+  let module = match cached {
+    Some(module) => v8::Local::new(scope, module),
+    None => match fetch_module_tree(scope, &path, None) {
+      Some(module) => module,
+      // The exception is thrown to the caller.
+      None => return,
+    },
+  };
+  // The module fetched by a failed import is cached but not instantiated yet.
+  if module.get_status() == v8::ModuleStatus::Uninstantiated
+    && module
+      .instantiate_module(scope, module_resolve_cb)
+      .is_none()
+  {
+    return;
+  }
+  if module.get_status() == v8::ModuleStatus::Instantiated {
+    let _ = module.evaluate(scope);
+  }
+  if module.get_status() == v8::ModuleStatus::Errored {
+    let exception = module.get_exception();
+    scope.throw_exception(exception);
+    return;
+  }
+  rv.set(module.get_module_namespace());
+}
+
+/// List the plugin packages, returns an array of objects with `name`, `path`, `entry` and
+/// `loaded`.
+pub fn list(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 0);
+  let plugins = list_plugins(&plugin_dirs());
+  trace!("plugin_list: {:?}", plugins.len());
+
+  let state_rc = JsRuntime::state(scope);
+  let elements = plugins
+    .iter()
+    .map(|plugin| {
+      let entry = plugin
+        .entry
+        .as_ref()
+        .map(|entry| entry.to_string_lossy().to_string());
+      let loaded = entry
+        .as_ref()
+        .map(|entry| state_rc.borrow().module_map.index.contains_key(entry))
+        .unwrap_or(false);
+
+      let target = v8::Object::new(scope);
+      let name = v8::String::new(scope, &plugin.name).unwrap();
+      binding::set_property_to(scope, target, "name", name.into());
+      let path = v8::String::new(scope, &plugin.dir.to_string_lossy()).unwrap();
+      binding::set_property_to(scope, target, "path", path.into());
+      let entry: v8::Local<v8::Value> = match entry {
+        Some(entry) => v8::String::new(scope, &entry).unwrap().into(),
+        None => v8::null(scope).into(),
+      };
+      binding::set_property_to(scope, target, "entry", entry);
+      let loaded = v8::Boolean::new(scope, loaded);
+      binding::set_property_to(scope, target, "loaded", loaded.into());
+      target.into()
+    })
+    .collect::<Vec<v8::Local<v8::Value>>>();
+  rv.set(v8::Array::new_with_elements(scope, &elements).into());
+}
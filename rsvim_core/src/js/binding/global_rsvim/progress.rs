@@ -0,0 +1,88 @@
+//! APIs for `Rsvim.progress` namespace.
+
+use crate::envar;
+use crate::js::JsRuntime;
+
+use tracing::trace;
+
+/// Begin a task, returns its progress ID.
+pub fn begin(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let title = args.get(0).to_rust_string_lossy(scope);
+  let cancellable = args.get(1).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  let id = state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .progress_mut()
+    .begin(&title, cancellable);
+  trace!("progress_begin: {:?}, {:?}:{:?}", id, title, cancellable);
+  rv.set(v8::Number::new(scope, id as f64).into());
+}
+
+/// Update the task's message and percentage, the percentage is `undefined` if the total amount is
+/// unknown.
+pub fn update(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let id = args.get(0).int32_value(scope).unwrap() as usize;
+  let message = args.get(1).to_rust_string_lossy(scope);
+  let percentage = if args.get(2).is_number() {
+    Some(args.get(2).number_value(scope).unwrap().clamp(0.0, 100.0) as u8)
+  } else {
+    None
+  };
+  let state_rc = JsRuntime::state(scope);
+  trace!("progress_update: {:?}, {:?}:{:?}", id, message, percentage);
+  state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .progress_mut()
+    .update(id, &message, percentage);
+}
+
+/// End the task.
+pub fn end(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, _: v8::ReturnValue) {
+  assert!(args.length() == 1);
+  let id = args.get(0).int32_value(scope).unwrap() as usize;
+  let state_rc = JsRuntime::state(scope);
+  trace!("progress_end: {:?}", id);
+  state_rc
+    .borrow_mut()
+    .editing_state
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .progress_mut()
+    .end(id);
+}
+
+/// Whether the task is cancelled by user.
+pub fn is_cancelled(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let id = args.get(0).int32_value(scope).unwrap() as usize;
+  let state_rc = JsRuntime::state(scope);
+  let value = state_rc
+    .borrow()
+    .editing_state
+    .try_read_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .progress()
+    .is_cancelled(id);
+  trace!("progress_is_cancelled: {:?}, {:?}", id, value);
+  rv.set_bool(value);
+}
@@ -0,0 +1,52 @@
+//! APIs for `Rsvim.register` namespace.
+
+use crate::js::{binding, JsRuntime};
+
+/// Get the register, returns an object with `type` and `content`, or `null` if it's empty. See
+/// [`EditorApi::register_get`](crate::api::EditorApi::register_get).
+pub fn get(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let api = JsRuntime::state(scope).borrow().api();
+  let value = match api.register_get(&name) {
+    Ok(value) => value,
+    Err(e) => {
+      binding::throw_type_error(scope, &e);
+      return;
+    }
+  };
+
+  match value {
+    Some(value) => {
+      let target = v8::Object::new(scope);
+      let kind = v8::String::new(scope, &value.kind.to_string()).unwrap();
+      binding::set_property_to(scope, target, "type", kind.into());
+      let content = v8::String::new(scope, &value.content).unwrap();
+      binding::set_property_to(scope, target, "content", content.into());
+      rv.set(target.into());
+    }
+    None => rv.set_null(),
+  }
+}
+
+/// Set the register, returns `false` if the clipboard is not available. See
+/// [`EditorApi::register_set`](crate::api::EditorApi::register_set).
+pub fn set(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let content = args.get(1).to_rust_string_lossy(scope);
+  let kind = args.get(2).to_rust_string_lossy(scope);
+  let api = JsRuntime::state(scope).borrow().api();
+  match api.register_set(&name, content, &kind) {
+    Ok(result) => rv.set_bool(result),
+    Err(e) => binding::throw_type_error(scope, &e),
+  }
+}
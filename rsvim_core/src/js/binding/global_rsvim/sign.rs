@@ -0,0 +1,208 @@
+//! APIs for `Rsvim.sign` namespace.
+
+use crate::buf::highlight::{parse_attrs, parse_color, HighlightStyle};
+use crate::buf::{BufferArc, SignDefinition};
+use crate::envar;
+use crate::js::{binding, JsRuntime};
+use crate::state::current_window_buffer;
+use crate::{rlock, wlock};
+
+use tracing::trace;
+
+// Get the buffer by ID, or current buffer if the ID is not positive.
+fn get_buffer(scope: &mut v8::HandleScope, buffer_id: i32) -> Option<BufferArc> {
+  let state_rc = JsRuntime::state(scope);
+  let (tree, buffers) = {
+    let state = state_rc.borrow();
+    (state.tree.clone(), state.buffers.clone())
+  };
+  if buffer_id > 0 {
+    let buffer = rlock!(buffers).get(&buffer_id).cloned();
+    buffer
+  } else {
+    current_window_buffer(&tree).map(|(buffer, _viewport)| buffer)
+  }
+}
+
+/// Define a sign with the text and the highlight style, the previously defined sign with the same
+/// name is replaced.
+pub fn define(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 5);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let text = args.get(1).to_rust_string_lossy(scope);
+
+  let mut colors = [None, None];
+  for (i, color) in colors.iter_mut().enumerate() {
+    let value = args.get(2 + i as i32);
+    if value.is_string() {
+      let value = value.to_rust_string_lossy(scope);
+      match parse_color(&value) {
+        Some(c) => *color = Some(c),
+        None => {
+          binding::throw_type_error(scope, &format!("Invalid highlight color: {}", value));
+          return;
+        }
+      }
+    }
+  }
+  let attrs = match parse_attrs(&args.get(4).to_rust_string_lossy(scope)) {
+    Ok(attrs) => attrs,
+    Err(e) => {
+      binding::throw_type_error(scope, &e);
+      return;
+    }
+  };
+  let definition = SignDefinition::new(&text, HighlightStyle::new(colors[0], colors[1], attrs));
+  trace!("sign_define: {:?}, {:?}", name, definition);
+
+  let state_rc = JsRuntime::state(scope);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .define_sign(&name, definition);
+}
+
+/// Undefine a sign, returns `false` if it doesn't exist.
+pub fn undefine(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  trace!("sign_undefine: {:?}", name);
+  let state_rc = JsRuntime::state(scope);
+  let removed = state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .undefine_sign(&name);
+  rv.set_bool(removed);
+}
+
+/// Place a sign on the line of the buffer, returns the sign ID, or `0` if the buffer doesn't
+/// exist.
+pub fn place(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 5);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let line_idx = args.get(1).int32_value(scope).unwrap().max(0) as usize;
+  let buffer_id = args.get(2).int32_value(scope).unwrap_or(0);
+  let group = args.get(3).to_rust_string_lossy(scope);
+  let priority = args
+    .get(4)
+    .int32_value(scope)
+    .unwrap()
+    .clamp(0, u16::MAX as i32) as u16;
+
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let defined = rlock!(tree).sign_definitions().contains_key(&name);
+  if !defined {
+    binding::throw_type_error(scope, &format!("Undefined sign: {}", name));
+    return;
+  }
+
+  let id = match get_buffer(scope, buffer_id) {
+    Some(buffer) => wlock!(buffer)
+      .signs_mut()
+      .place(&name, line_idx, &group, priority),
+    None => 0,
+  };
+  trace!(
+    "sign_place: {:?}, {:?}:{:?}, {:?}:{:?}, {:?}",
+    name,
+    buffer_id,
+    line_idx,
+    group,
+    priority,
+    id
+  );
+  rv.set(v8::Number::new(scope, id as f64).into());
+}
+
+/// Unplace a sign from the buffer, returns `false` if it doesn't exist.
+pub fn unplace(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let id = args.get(0).int32_value(scope).unwrap().max(0) as usize;
+  let buffer_id = args.get(1).int32_value(scope).unwrap_or(0);
+  trace!("sign_unplace: {:?}, {:?}", id, buffer_id);
+  let removed = match get_buffer(scope, buffer_id) {
+    Some(buffer) => wlock!(buffer).signs_mut().unplace(id).is_some(),
+    None => false,
+  };
+  rv.set_bool(removed);
+}
+
+/// Unplace the signs of the group from the buffer, or all the signs if the group is `undefined`.
+pub fn clear(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, _: v8::ReturnValue) {
+  assert!(args.length() == 2);
+  let group = if args.get(0).is_string() {
+    Some(args.get(0).to_rust_string_lossy(scope))
+  } else {
+    None
+  };
+  let buffer_id = args.get(1).int32_value(scope).unwrap_or(0);
+  trace!("sign_clear: {:?}, {:?}", group, buffer_id);
+  if let Some(buffer) = get_buffer(scope, buffer_id) {
+    wlock!(buffer).signs_mut().clear(group.as_deref());
+  }
+}
+
+/// Get the placed signs of the buffer, returns an array of objects with `id`, `name`, `line`,
+/// `group` and `priority`, or `null` if the buffer doesn't exist.
+pub fn get_placed(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let buffer_id = args.get(0).int32_value(scope).unwrap_or(0);
+  let buffer = match get_buffer(scope, buffer_id) {
+    Some(buffer) => buffer,
+    None => {
+      rv.set_null();
+      return;
+    }
+  };
+  let placed: Vec<_> = rlock!(buffer)
+    .signs()
+    .iter()
+    .map(|(id, sign)| (*id, sign.clone()))
+    .collect();
+  trace!("sign_get_placed: {:?}, {:?}", buffer_id, placed);
+
+  let items: Vec<v8::Local<v8::Value>> = placed
+    .into_iter()
+    .map(|(id, sign)| {
+      let target = v8::Object::new(scope);
+      let id = v8::Number::new(scope, id as f64);
+      binding::set_property_to(scope, target, "id", id.into());
+      let name = v8::String::new(scope, &sign.name).unwrap();
+      binding::set_property_to(scope, target, "name", name.into());
+      let line = v8::Number::new(scope, sign.line_idx as f64);
+      binding::set_property_to(scope, target, "line", line.into());
+      let group = v8::String::new(scope, &sign.group).unwrap();
+      binding::set_property_to(scope, target, "group", group.into());
+      let priority = v8::Number::new(scope, sign.priority as f64);
+      binding::set_property_to(scope, target, "priority", priority.into());
+      target.into()
+    })
+    .collect();
+  let array = v8::Array::new_with_elements(scope, &items);
+  rv.set(array.into());
+}
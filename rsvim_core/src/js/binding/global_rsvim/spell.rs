@@ -0,0 +1,56 @@
+//! APIs for `Rsvim.spell` namespace.
+
+use crate::js::JsRuntime;
+
+/// Add a good word to the user spell dictionary, see
+/// [`EditorApi::spell_add`](crate::api::EditorApi::spell_add).
+pub fn add(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let word = args.get(0).to_rust_string_lossy(scope);
+  let api = JsRuntime::state(scope).borrow().api();
+  rv.set_bool(api.spell_add(&word));
+}
+
+/// Whether the word is good, see
+/// [`SpellDictionary::check`](crate::buf::spell::SpellDictionary::check).
+pub fn check(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let word = args.get(0).to_rust_string_lossy(scope);
+  let api = JsRuntime::state(scope).borrow().api();
+  rv.set_bool(api.spell_check(&word));
+}
+
+/// Get all the good words of the user spell dictionary, in sorted order.
+pub fn words(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 0);
+  let api = JsRuntime::state(scope).borrow().api();
+  let elements = api
+    .spell_words()
+    .iter()
+    .map(|word| v8::String::new(scope, word).unwrap().into())
+    .collect::<Vec<v8::Local<v8::Value>>>();
+  rv.set(v8::Array::new_with_elements(scope, &elements).into());
+}
+
+/// Request to read the user spell dictionary file again, it runs on an async task.
+pub fn reload(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 0);
+  let api = JsRuntime::state(scope).borrow().api();
+  api.spell_reload();
+}
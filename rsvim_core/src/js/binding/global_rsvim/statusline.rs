@@ -0,0 +1,45 @@
+//! APIs for `Rsvim.statusline` namespace.
+
+use crate::envar;
+use crate::js::JsRuntime;
+
+use tracing::trace;
+
+/// Set the cached value of the statusline component, i.e. after the `render` callback is called
+/// on its update triggers.
+pub fn set_component(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let value = args.get(1).to_rust_string_lossy(scope);
+  trace!("statusline_set_component: {:?}, {:?}", name, value);
+  let state_rc = JsRuntime::state(scope);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .set_statusline_component(&name, &value);
+}
+
+/// Remove the statusline component, returns `false` if it doesn't exist.
+pub fn remove_component(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  trace!("statusline_remove_component: {:?}", name);
+  let state_rc = JsRuntime::state(scope);
+  let removed = state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .remove_statusline_component(&name);
+  rv.set_bool(removed);
+}
@@ -0,0 +1,39 @@
+//! APIs for `Rsvim.template` namespace.
+
+use crate::envar;
+use crate::js::JsRuntime;
+use crate::wlock;
+
+use std::path::PathBuf;
+use tracing::trace;
+
+/// Add a template rule, the new file matches the glob `pattern` is populated from the template
+/// `file`.
+pub fn add(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, _: v8::ReturnValue) {
+  assert!(args.length() == 2);
+  let pattern = args.get(0).to_rust_string_lossy(scope);
+  let file = args.get(1).to_rust_string_lossy(scope);
+  trace!("template_add: {:?}, {:?}", pattern, file);
+
+  let state_rc = JsRuntime::state(scope);
+  let buffers = state_rc.borrow().buffers.clone();
+  wlock!(buffers)
+    .templates_mut()
+    .add(&pattern, PathBuf::from(file));
+}
+
+/// Set the template variable, it overrides the built-in one with the same name.
+pub fn set_variable(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let value = args.get(1).to_rust_string_lossy(scope);
+  trace!("template_set_variable: {:?}, {:?}", name, value);
+
+  let state_rc = JsRuntime::state(scope);
+  let buffers = state_rc.borrow().buffers.clone();
+  wlock!(buffers).templates_mut().set_variable(&name, &value);
+}
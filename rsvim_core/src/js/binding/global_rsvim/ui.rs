@@ -0,0 +1,24 @@
+//! APIs for `Rsvim.ui` namespace.
+
+use crate::envar;
+use crate::js::JsRuntime;
+
+use tracing::trace;
+
+/// Request to redraw the screen, with `force` the terminal is cleared and fully repainted.
+pub fn redraw(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let force = args.get(0).to_boolean(scope).boolean_value(scope);
+  let state_rc = JsRuntime::state(scope);
+  trace!("ui_redraw: {:?}", force);
+  state_rc
+    .borrow_mut()
+    .tree
+    .try_write_for(envar::MUTEX_TIMEOUT())
+    .unwrap()
+    .request_redraw(force);
+}
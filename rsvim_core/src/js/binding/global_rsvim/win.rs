@@ -0,0 +1,218 @@
+//! APIs for `Rsvim.win` namespace.
+
+use crate::envar;
+use crate::js::{binding, JsRuntime};
+use crate::state::current_window_buffer;
+use crate::state::fsm::visual::move_cursor_to;
+use crate::ui::tree::TreeNode;
+use crate::ui::widget::float::{FloatAnchor, FloatBorder, FloatConfig};
+use crate::{rlock, wlock};
+
+use ropey::Rope;
+use std::sync::Arc;
+use tracing::trace;
+
+/// Open a floating window, returns the window ID.
+///
+/// The window shows the buffer by its ID, or a new scratch buffer with the text if the buffer ID
+/// is negative.
+pub fn open_float(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 12);
+  let buffer_id = args.get(0).int32_value(scope).unwrap();
+  let text = args.get(1).to_rust_string_lossy(scope);
+  let anchor = match FloatAnchor::try_from(args.get(2).to_rust_string_lossy(scope).as_str()) {
+    Ok(anchor) => anchor,
+    Err(e) => {
+      binding::throw_type_error(scope, &e);
+      return;
+    }
+  };
+  let row = args.get(3).int32_value(scope).unwrap() as isize;
+  let col = args.get(4).int32_value(scope).unwrap() as isize;
+  let width = args
+    .get(5)
+    .int32_value(scope)
+    .unwrap()
+    .clamp(1, u16::MAX as i32) as u16;
+  let height = args
+    .get(6)
+    .int32_value(scope)
+    .unwrap()
+    .clamp(1, u16::MAX as i32) as u16;
+  let zindex = args.get(7).int32_value(scope).unwrap().max(1) as usize;
+  let border = match FloatBorder::try_from(args.get(8).to_rust_string_lossy(scope).as_str()) {
+    Ok(border) => border,
+    Err(e) => {
+      binding::throw_type_error(scope, &e);
+      return;
+    }
+  };
+  let shadow = args.get(9).to_boolean(scope).boolean_value(scope);
+  let title = args.get(10).to_rust_string_lossy(scope);
+  let enter = args.get(11).to_boolean(scope).boolean_value(scope);
+  let config = FloatConfig {
+    anchor,
+    row,
+    col,
+    width,
+    height,
+    zindex,
+    border,
+    shadow,
+    title,
+  };
+
+  let state_rc = JsRuntime::state(scope);
+  let (tree, buffers) = {
+    let state = state_rc.borrow();
+    (state.tree.clone(), state.buffers.clone())
+  };
+  let buffer = if buffer_id < 0 {
+    let mut buffers = wlock!(buffers);
+    let buffer_id = buffers.new_scratch_buffer(Rope::from_str(&text), None);
+    buffers.get(&buffer_id).cloned()
+  } else {
+    rlock!(buffers).get(&buffer_id).cloned()
+  };
+  let buffer = match buffer {
+    Some(buffer) => buffer,
+    None => {
+      binding::throw_type_error(scope, &format!("Buffer {} doesn't exist", buffer_id));
+      return;
+    }
+  };
+
+  let window_id = {
+    let mut tree = wlock!(tree);
+    let window_id = tree.open_float(Arc::downgrade(&buffer), &config);
+    if enter && tree.set_current_window_id(window_id) {
+      tree.sync_cursor_position();
+    }
+    window_id
+  };
+  trace!("win_open_float: {:?}, {:?}", config, window_id);
+  rv.set(v8::Number::new(scope, window_id as f64).into());
+}
+
+/// Close a floating window, returns `false` if it's not a floating window.
+///
+/// The scratch buffer created for the window (see [`open_float`]) is wiped out if it's not shown
+/// by other windows.
+pub fn close_float(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let window_id = args.get(0).int32_value(scope).unwrap();
+  let state_rc = JsRuntime::state(scope);
+  let (tree, buffers) = {
+    let state = state_rc.borrow();
+    (state.tree.clone(), state.buffers.clone())
+  };
+  let (closed, buffer) = {
+    let mut tree = wlock!(tree);
+    let buffer = match tree.node(&window_id) {
+      Some(TreeNode::Window(window)) => window.buffer().upgrade(),
+      _ => None,
+    };
+    let closed = tree.close_float(window_id);
+    // The buffer is still shown by other windows.
+    let shown = buffer.as_ref().is_some_and(|buffer| {
+      tree.window_ids().iter().any(|id| match tree.node(id) {
+        Some(TreeNode::Window(window)) => window
+          .buffer()
+          .upgrade()
+          .is_some_and(|b| Arc::ptr_eq(&b, buffer)),
+        _ => false,
+      })
+    });
+    (closed, buffer.filter(|_| closed && !shown))
+  };
+  if let Some(buffer) = buffer {
+    let buffer_id = rlock!(buffer).id();
+    let mut buffers = wlock!(buffers);
+    if buffers.is_scratch(&buffer_id) && buffers.scratch_source(&buffer_id).is_none() {
+      buffers.wipe_buffer(&buffer_id);
+    }
+  }
+  trace!("win_close_float: {:?}, {:?}", window_id, closed);
+  rv.set_bool(closed);
+}
+
+/// Get current window ID, returns `null` if there's no current window.
+pub fn current(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let window_id = rlock!(tree).current_window_id();
+  trace!("win_current: {:?}", window_id);
+  match window_id {
+    Some(window_id) => rv.set(v8::Number::new(scope, window_id as f64).into()),
+    None => rv.set_null(),
+  }
+}
+
+/// Move the cursor to the window, returns `false` if the window doesn't exist.
+pub fn focus(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let window_id = args.get(0).int32_value(scope).unwrap();
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let focused = {
+    let mut tree = wlock!(tree);
+    let focused = tree.set_current_window_id(window_id);
+    if focused {
+      tree.sync_cursor_position();
+    }
+    focused
+  };
+  trace!("win_focus: {:?}, {:?}", window_id, focused);
+  rv.set_bool(focused);
+}
+
+/// Move the cursor in current window, the position is clamped to the buffer. Returns `false` if
+/// there's no current window.
+pub fn set_cursor(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let line = args.get(0).uint32_value(scope).unwrap_or(0) as usize;
+  let col = args.get(1).uint32_value(scope).unwrap_or(0) as usize;
+
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let (buffer, viewport) = match current_window_buffer(&tree) {
+    Some(current) => current,
+    None => {
+      rv.set_bool(false);
+      return;
+    }
+  };
+  let position = {
+    let buffer = rlock!(buffer);
+    let line = line.min(buffer.len_lines().saturating_sub(1));
+    let line_len = buffer
+      .get_line_content(line)
+      .map(|content| content.chars().count())
+      .unwrap_or(0);
+    (line, col.min(line_len.saturating_sub(1)))
+  };
+  trace!("win_set_cursor: {:?}", position);
+  // NOTE: The buffer lock must be released before moving cursor.
+  move_cursor_to(&tree, &viewport, position);
+  rv.set_bool(true);
+}
@@ -2,4 +2,7 @@
 //! See WinterCG standard: <https://github.com/wintercg/proposal-common-minimum-api>
 //! See MDN javascript documents: <https://developer.mozilla.org/en-US/>
 
+#[cfg(feature = "fetch")]
+pub mod fetch;
+pub mod microtask;
 pub mod timeout;
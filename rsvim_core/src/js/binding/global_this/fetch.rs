@@ -0,0 +1,236 @@
+//! Fetch APIs, i.e. `fetch`.
+//!
+//! The HTTP request runs on the event loop (see [`fetch`](crate::evloop::fetch)), the js runtime
+//! receives the response head, the body chunks and the result with the callbacks, the `Response`
+//! object is implemented in javascript.
+
+use crate::evloop::fetch::{FetchControl, FetchId, FetchRequest};
+use crate::js::msg::{self as jsmsg, JsRuntimeToEventLoopMessage};
+use crate::js::{self, binding, JsFuture, JsRuntime};
+
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tracing::trace;
+
+/// The running request of `fetch`, it's removed when the request is done.
+pub struct FetchHandle {
+  /// Sends the abort signal to the request.
+  pub control: UnboundedSender<FetchControl>,
+  pub on_head: v8::Global<v8::Function>,
+  pub on_chunk: v8::Global<v8::Function>,
+  pub on_done: v8::Global<v8::Function>,
+}
+
+/// The value passed to the fetch callbacks.
+pub enum FetchValue {
+  Head(jsmsg::FetchHead),
+  Chunk(Vec<u8>),
+  /// The error message, or `null` if the response body is finished.
+  Done(Option<String>),
+}
+
+/// The fetch future, it calls the fetch callback with the response head, the body chunk or the
+/// result.
+pub struct FetchFuture {
+  pub fetch_id: FetchId,
+  pub callback: v8::Global<v8::Function>,
+  pub value: FetchValue,
+}
+
+fn to_uint8_array<'s>(scope: &mut v8::HandleScope<'s>, data: Vec<u8>) -> v8::Local<'s, v8::Value> {
+  let len = data.len();
+  let store = v8::ArrayBuffer::new_backing_store_from_vec(data).make_shared();
+  let buffer = v8::ArrayBuffer::with_backing_store(scope, &store);
+  v8::Uint8Array::new(scope, buffer, 0, len).unwrap().into()
+}
+
+fn to_head_object<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  head: &jsmsg::FetchHead,
+) -> v8::Local<'s, v8::Value> {
+  let target = v8::Object::new(scope);
+  let status = v8::Integer::new(scope, head.status as i32).into();
+  binding::set_property_to(scope, target, "status", status);
+  let status_text = v8::String::new(scope, &head.status_text).unwrap().into();
+  binding::set_property_to(scope, target, "statusText", status_text);
+  let url = v8::String::new(scope, &head.url).unwrap().into();
+  binding::set_property_to(scope, target, "url", url);
+  let headers = head
+    .headers
+    .iter()
+    .map(|(name, value)| {
+      let name = v8::String::new(scope, name).unwrap().into();
+      let value = v8::String::new(scope, value).unwrap().into();
+      v8::Array::new_with_elements(scope, &[name, value]).into()
+    })
+    .collect::<Vec<v8::Local<v8::Value>>>();
+  let headers = v8::Array::new_with_elements(scope, &headers).into();
+  binding::set_property_to(scope, target, "headers", headers);
+  target.into()
+}
+
+impl JsFuture for FetchFuture {
+  fn run(&mut self, scope: &mut v8::HandleScope) {
+    let undefined = v8::undefined(scope).into();
+    let value: v8::Local<v8::Value> = match &mut self.value {
+      FetchValue::Head(head) => {
+        trace!("fetch head:{:?}, status:{:?}", self.fetch_id, head.status);
+        to_head_object(scope, head)
+      }
+      FetchValue::Chunk(chunk) => {
+        trace!("fetch chunk:{:?}, len:{:?}", self.fetch_id, chunk.len());
+        to_uint8_array(scope, std::mem::take(chunk))
+      }
+      FetchValue::Done(error) => {
+        trace!("fetch done:{:?}, error:{:?}", self.fetch_id, error);
+        match error {
+          Some(error) => v8::String::new(scope, error).unwrap().into(),
+          None => v8::null(scope).into(),
+        }
+      }
+    };
+    let callback = v8::Local::new(scope, &self.callback);
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    callback.call(tc_scope, undefined, &[value]);
+
+    // Report if callback threw an exception.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+    }
+  }
+}
+
+fn view_bytes(view: v8::Local<v8::ArrayBufferView>) -> Vec<u8> {
+  let mut data = vec![0_u8; view.byte_length()];
+  view.copy_contents(&mut data);
+  data
+}
+
+// Get the bytes of a string or `Uint8Array` value.
+fn bytes_arg(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> Option<Vec<u8>> {
+  if value.is_string() {
+    return Some(value.to_rust_string_lossy(scope).into_bytes());
+  }
+  let view = v8::Local::<v8::ArrayBufferView>::try_from(value).ok()?;
+  Some(view_bytes(view))
+}
+
+fn function_arg(
+  scope: &mut v8::HandleScope,
+  value: v8::Local<v8::Value>,
+) -> Option<v8::Global<v8::Function>> {
+  v8::Local::<v8::Function>::try_from(value)
+    .ok()
+    .map(|callback| v8::Global::new(scope, callback))
+}
+
+/// Send the HTTP request, returns the fetch ID.
+pub fn fetch(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 7);
+  let method = args.get(0).to_rust_string_lossy(scope);
+  let url = args.get(1).to_rust_string_lossy(scope);
+  let headers = match v8::Local::<v8::Array>::try_from(args.get(2)) {
+    Ok(headers) => (0..headers.length())
+      .filter_map(|i| {
+        let header = headers.get_index(scope, i)?;
+        let header = v8::Local::<v8::Array>::try_from(header).ok()?;
+        let name = header.get_index(scope, 0)?.to_rust_string_lossy(scope);
+        let value = header.get_index(scope, 1)?.to_rust_string_lossy(scope);
+        Some((name, value))
+      })
+      .collect::<Vec<(String, String)>>(),
+    Err(_) => vec![],
+  };
+  let body = if args.get(3).is_null_or_undefined() {
+    None
+  } else {
+    match bytes_arg(scope, args.get(3)) {
+      Some(body) => Some(body),
+      None => {
+        binding::throw_type_error(scope, "Body must be a string or an Uint8Array");
+        return;
+      }
+    }
+  };
+  let (on_head, on_chunk, on_done) = match (
+    function_arg(scope, args.get(4)),
+    function_arg(scope, args.get(5)),
+    function_arg(scope, args.get(6)),
+  ) {
+    (Some(on_head), Some(on_chunk), Some(on_done)) => (on_head, on_chunk, on_done),
+    _ => {
+      binding::throw_type_error(scope, "Fetch callbacks must be functions");
+      return;
+    }
+  };
+  let (control, control_recv) = unbounded_channel();
+  let handle = FetchHandle {
+    control,
+    on_head,
+    on_chunk,
+    on_done,
+  };
+
+  let fetch_id = js::next_future_id();
+  trace!("fetch:{:?}, {:?} {:?}", fetch_id, method, url);
+  let request = FetchRequest {
+    method,
+    url,
+    headers,
+    body,
+  };
+  let state_rc = JsRuntime::state(scope);
+  let mut state = state_rc.borrow_mut();
+  state.fetches.insert(fetch_id, handle);
+  let js_runtime_send_to_master = state.js_runtime_send_to_master.clone();
+  let current_handle = tokio::runtime::Handle::current();
+  current_handle.spawn_blocking(move || {
+    let _ = js_runtime_send_to_master.blocking_send(JsRuntimeToEventLoopMessage::FetchReq(
+      jsmsg::FetchReq::new(fetch_id, request, control_recv),
+    ));
+  });
+  rv.set(v8::Integer::new(scope, fetch_id).into());
+}
+
+/// Abort the HTTP request, returns `false` if the request is done.
+pub fn fetch_abort(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let fetch_id = args.get(0).int32_value(scope).unwrap_or(0);
+  trace!("fetch_abort:{:?}", fetch_id);
+  let state_rc = JsRuntime::state(scope);
+  let state = state_rc.borrow();
+  let sent = match state.fetches.get(&fetch_id) {
+    Some(handle) => handle.control.send(FetchControl::Abort).is_ok(),
+    None => false,
+  };
+  rv.set_bool(sent);
+}
+
+/// Decode the `Uint8Array` as UTF-8 text, the invalid sequences are replaced.
+pub fn decode_utf8(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let data = match v8::Local::<v8::ArrayBufferView>::try_from(args.get(0)) {
+    Ok(view) => view_bytes(view),
+    Err(_) => {
+      binding::throw_type_error(scope, "Data must be an Uint8Array");
+      return;
+    }
+  };
+  let text = String::from_utf8_lossy(&data);
+  rv.set(v8::String::new(scope, &text).unwrap().into());
+}
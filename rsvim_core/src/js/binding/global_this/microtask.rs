@@ -0,0 +1,33 @@
+//! Microtask APIs, i.e. `queueMicrotask`.
+
+use crate::js::JsRuntime;
+
+use tracing::trace;
+
+/// Javascript `queueMicrotask` API, the callback runs at the next microtask checkpoint, i.e.
+/// after current script (or callback) is finished.
+pub fn queue_microtask(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  let callback = v8::Local::<v8::Function>::try_from(args.get(0)).unwrap();
+  scope.enqueue_microtask(callback);
+  trace!("queue_microtask");
+}
+
+/// Report the exception thrown by the microtask, since V8 doesn't report the exceptions thrown
+/// during the microtask checkpoint.
+pub fn report_error(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  let exception = v8::Global::new(scope, args.get(0));
+  let state_rc = JsRuntime::state(scope);
+  state_rc
+    .borrow_mut()
+    .exceptions
+    .capture_exception(exception);
+  trace!("report_error");
+}
@@ -1,21 +1,46 @@
-//! Timeout APIs.
+//! Timeout APIs, i.e. `setTimeout` and `setInterval`.
+//!
+//! The timers run on the event loop (see [`TaskKind::Timer`](crate::evloop::task::TaskKind)), the
+//! js runtime only keeps the callbacks. A timer is active while its ID is in the
+//! [`timeout_handles`](crate::js::JsRuntimeState::timeout_handles), the ticks of the cleared timers
+//! are ignored.
 
 use crate::js::msg::{self as jsmsg, JsRuntimeToEventLoopMessage};
-use crate::js::{self, JsFuture, JsFutureId, JsRuntime};
+use crate::js::{self, JsFuture, JsFutureId, JsRuntime, JsRuntimeState};
 
 use std::rc::Rc;
 use std::time::Duration;
 use tracing::trace;
 
+#[derive(Clone)]
 struct TimeoutFuture {
   future_id: JsFutureId,
   cb: Rc<v8::Global<v8::Function>>,
   params: Rc<Vec<v8::Global<v8::Value>>>,
+  repeat: bool,
 }
 
 impl JsFuture for TimeoutFuture {
   fn run(&mut self, scope: &mut v8::HandleScope) {
-    trace!("set_timeout callback:{:?}", self.future_id);
+    trace!(
+      "set_timeout callback:{:?}, repeat:{:?}",
+      self.future_id,
+      self.repeat
+    );
+    // The one-shot timer is done before running the callback, the interval timer waits for its
+    // next tick.
+    {
+      let state_rc = JsRuntime::state(scope);
+      let mut state = state_rc.borrow_mut();
+      if self.repeat {
+        state
+          .pending_futures
+          .insert(self.future_id, Box::new(self.clone()));
+      } else {
+        state.timeout_handles.remove(&self.future_id);
+      }
+    }
+
     let undefined = v8::undefined(scope).into();
     let callback = v8::Local::new(scope, (*self.cb).clone());
     let args: Vec<v8::Local<v8::Value>> = self
@@ -38,7 +63,18 @@ impl JsFuture for TimeoutFuture {
   }
 }
 
-/// Javascript `setTimeout` API.
+/// Cancel the timer on the event loop.
+pub fn cancel_timer(state: &JsRuntimeState, timer_id: JsFutureId) {
+  let js_runtime_send_to_master = state.js_runtime_send_to_master.clone();
+  let current_handle = tokio::runtime::Handle::current();
+  current_handle.spawn_blocking(move || {
+    let _ = js_runtime_send_to_master.blocking_send(JsRuntimeToEventLoopMessage::ClearTimeoutReq(
+      jsmsg::ClearTimeoutReq::new(timer_id),
+    ));
+  });
+}
+
+/// Javascript `setTimeout` and `setInterval` API, the 3rd argument is `true` for `setInterval`.
 pub fn set_timeout(
   scope: &mut v8::HandleScope,
   args: v8::FunctionCallbackArguments,
@@ -51,6 +87,9 @@ pub fn set_timeout(
   // Get timer's expiration time in millis.
   let millis = args.get(1).int32_value(scope).unwrap() as u64;
 
+  // Whether the timer repeats, i.e. `setInterval`.
+  let repeat = args.get(2).boolean_value(scope);
+
   // Convert params argument (Array<Local<Value>>) to Rust vector.
   let params = match v8::Local::<v8::Array>::try_from(args.get(3)) {
     Ok(params) => (0..params.length()).fold(Vec::<v8::Global<v8::Value>>::new(), |mut acc, i| {
@@ -71,21 +110,27 @@ pub fn set_timeout(
   let current_handle = tokio::runtime::Handle::current();
   current_handle.spawn_blocking(move || {
     let _ = js_runtime_send_to_master.blocking_send(JsRuntimeToEventLoopMessage::TimeoutReq(
-      jsmsg::TimeoutReq::new(timer_id, Duration::from_millis(millis)),
+      jsmsg::TimeoutReq::new(timer_id, Duration::from_millis(millis), repeat),
     ));
   });
   let timeout_cb = TimeoutFuture {
     future_id: timer_id,
     cb: Rc::clone(&callback),
     params: Rc::clone(&params),
+    repeat,
   };
   state.pending_futures.insert(timer_id, Box::new(timeout_cb));
   state.timeout_handles.insert(timer_id);
   rv.set(v8::Number::new(scope, timer_id as f64).into());
-  trace!("set_timeout:{:?}, millis:{:?}", timer_id, millis);
+  trace!(
+    "set_timeout:{:?}, millis:{:?}, repeat:{:?}",
+    timer_id,
+    millis,
+    repeat
+  );
 }
 
-/// Javascript `clearTimeout` API.
+/// Javascript `clearTimeout` and `clearInterval` API.
 pub fn clear_timeout(
   scope: &mut v8::HandleScope,
   args: v8::FunctionCallbackArguments,
@@ -94,7 +139,11 @@ pub fn clear_timeout(
   // Get timer ID, and remove it.
   let timer_id = args.get(0).int32_value(scope).unwrap();
   let state_rc = JsRuntime::state(scope);
+  let mut state = state_rc.borrow_mut();
 
-  state_rc.borrow_mut().timeout_handles.remove(&timer_id);
+  if state.timeout_handles.remove(&timer_id) {
+    state.pending_futures.remove(&timer_id);
+    cancel_timer(&state, timer_id);
+  }
   trace!("clear_timeout: {:?}", timer_id);
 }
@@ -0,0 +1,272 @@
+//! On-disk cache of the transpiled modules, thus the TypeScript/JSX config and plugins are not
+//! transpiled again on every startup.
+//!
+//! The cache entries are saved in the `transpiled` directory under the cache directory (see
+//! [`CACHE_DIR_PATH`](crate::envar::CACHE_DIR_PATH)). An entry is keyed by the hash of the file
+//! path and the hash of the source and the compiler version (see [`CacheKey`]), thus it's
+//! invalidated once the file is changed or the editor is upgraded. Each entry has two files:
+//!
+//! - `{path}-{content}.js`: The transpiled JavaScript code.
+//! - `{path}-{content}.js.map`: The source map of the JavaScript code to the original source.
+//!
+//! There's at most one entry for a file path, the stale entry is removed once the file is
+//! transpiled again. And the cache size is capped (see [`MAX_SIZE`]), the least recently written
+//! entries are removed first.
+//!
+//! The cache is bypassed by the `--no-cache` command line option. It's only an optimization, the
+//! failures of reading or writing the cache are ignored, i.e. the module is simply transpiled
+//! again.
+
+use crate::envar;
+use crate::js::transpiler::TranspileOutput;
+use crate::res::IoResult;
+
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// The cache directory name under the cache directory.
+pub const TRANSPILED_DIR: &str = "transpiled";
+
+/// The maximum total size (in bytes) of the cache entries.
+pub const MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+// Bump it once the transpiler output is changed, i.e. the compile options.
+const CACHE_VERSION: u32 = 1;
+
+/// Get the cache directory path.
+pub fn cache_dir() -> PathBuf {
+  envar::CACHE_DIR_PATH().join(TRANSPILED_DIR)
+}
+
+/// Get the compiler version, it changes with the editor version (the compiler is pinned by the
+/// editor) and the cache version.
+pub fn compiler_version() -> String {
+  format!("{}+{}", env!("CARGO_PKG_VERSION"), CACHE_VERSION)
+}
+
+// Get the hex SHA-1 hash of the parts.
+fn hash(parts: &[&str]) -> String {
+  let mut hasher = Sha1::new();
+  for part in parts {
+    hasher.update(part.as_bytes());
+    // Separate the parts, i.e. ("ab", "c") and ("a", "bc") are different.
+    hasher.update([0_u8]);
+  }
+  format!("{:x}", hasher.finalize())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The cache key of a source file.
+pub struct CacheKey {
+  /// The hex SHA-1 hash of the file path.
+  pub path: String,
+  /// The hex SHA-1 hash of the compiler version and the source.
+  pub content: String,
+}
+
+impl CacheKey {
+  // The file name of the entry without extension.
+  fn stem(&self) -> String {
+    format!("{}-{}", self.path, self.content)
+  }
+}
+
+/// Get the cache key of the source file.
+pub fn cache_key(filename: &str, source: &str) -> CacheKey {
+  CacheKey {
+    path: hash(&[filename]),
+    content: hash(&[compiler_version().as_str(), source]),
+  }
+}
+
+// Get the stem of the entry file name, or `None` if it's not an entry file, i.e. a temporary file.
+fn entry_stem(file_name: &str) -> Option<&str> {
+  file_name
+    .strip_suffix(".js.map")
+    .or_else(|| file_name.strip_suffix(".js"))
+}
+
+#[derive(Debug, Clone)]
+/// The transpile cache in a directory.
+pub struct TranspileCache {
+  dir: PathBuf,
+  max_size: u64,
+}
+
+impl TranspileCache {
+  pub fn new(dir: PathBuf) -> Self {
+    TranspileCache {
+      dir,
+      max_size: MAX_SIZE,
+    }
+  }
+
+  /// Set the maximum total size (in bytes) of the cache entries, default is [`MAX_SIZE`].
+  pub fn with_max_size(mut self, max_size: u64) -> Self {
+    self.max_size = max_size;
+    self
+  }
+
+  fn code_file(&self, key: &CacheKey) -> PathBuf {
+    self.dir.join(format!("{}.js", key.stem()))
+  }
+
+  fn source_map_file(&self, key: &CacheKey) -> PathBuf {
+    self.dir.join(format!("{}.js.map", key.stem()))
+  }
+
+  /// Get the cached output, returns `None` if it's not cached.
+  pub fn get(&self, key: &CacheKey) -> Option<TranspileOutput> {
+    let code = std::fs::read_to_string(self.code_file(key)).ok()?;
+    let source_map = std::fs::read_to_string(self.source_map_file(key)).ok()?;
+    Some(TranspileOutput { code, source_map })
+  }
+
+  /// Save the output to cache, the stale entry of the same file path is removed, and the least
+  /// recently written entries are removed if the cache size exceeds the maximum size.
+  ///
+  /// NOTE: The files are written to temporary files and then renamed, thus the other editor
+  /// instances never read a partially written entry. The source map is renamed first, since an
+  /// entry is read only if the code file exists.
+  pub fn put(&self, key: &CacheKey, output: &TranspileOutput) -> IoResult<()> {
+    std::fs::create_dir_all(&self.dir)?;
+    for (file, contents) in [
+      (self.source_map_file(key), &output.source_map),
+      (self.code_file(key), &output.code),
+    ] {
+      let mut temp = file.clone().into_os_string();
+      temp.push(format!(".{}.tmp", std::process::id()));
+      std::fs::write(&temp, contents)?;
+      std::fs::rename(&temp, &file)?;
+    }
+    self.evict(key)
+  }
+
+  // Remove the stale entries of the file path of `key`, and the least recently written entries
+  // until the cache size doesn't exceed the maximum size. The entry of `key` is kept.
+  fn evict(&self, key: &CacheKey) -> IoResult<()> {
+    let current = key.stem();
+    let stale_prefix = format!("{}-", key.path);
+
+    // The entries by stem, i.e. their last written time, size and files.
+    let mut entries: BTreeMap<String, (SystemTime, u64, Vec<PathBuf>)> = BTreeMap::new();
+    for dir_entry in std::fs::read_dir(&self.dir)? {
+      let dir_entry = dir_entry?;
+      let file_name = dir_entry.file_name().to_string_lossy().to_string();
+      let stem = match entry_stem(&file_name) {
+        Some(stem) if stem != current => stem.to_string(),
+        _ => continue,
+      };
+      if stem.starts_with(&stale_prefix) {
+        std::fs::remove_file(dir_entry.path())?;
+        continue;
+      }
+      let metadata = dir_entry.metadata()?;
+      let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+      let entry = entries
+        .entry(stem)
+        .or_insert((SystemTime::UNIX_EPOCH, 0, vec![]));
+      entry.0 = entry.0.max(modified);
+      entry.1 += metadata.len();
+      entry.2.push(dir_entry.path());
+    }
+
+    let current_size = output_size(&self.code_file(key)) + output_size(&self.source_map_file(key));
+    let mut total_size = current_size + entries.values().map(|(_, size, _)| size).sum::<u64>();
+    let mut entries: Vec<_> = entries.into_values().collect();
+    entries.sort_by_key(|(modified, _, _)| *modified);
+    for (_, size, files) in entries {
+      if total_size <= self.max_size {
+        break;
+      }
+      for file in files {
+        std::fs::remove_file(file)?;
+      }
+      total_size -= size;
+    }
+    Ok(())
+  }
+}
+
+// Get the file size, or 0 if it doesn't exist.
+fn output_size(file: &std::path::Path) -> u64 {
+  std::fs::metadata(file).map(|m| m.len()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn make_output(code: &str) -> TranspileOutput {
+    TranspileOutput {
+      code: code.to_string(),
+      source_map: "{\"version\":3}".to_string(),
+    }
+  }
+
+  fn count_files(dir: &std::path::Path) -> usize {
+    std::fs::read_dir(dir).unwrap().count()
+  }
+
+  #[test]
+  fn cache1() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let cache = TranspileCache::new(tmp.path().join(TRANSPILED_DIR));
+
+    let key = cache_key("/a/b.ts", "const a: number = 1;");
+    assert_eq!(key.path.len(), 40);
+    assert_eq!(key.content.len(), 40);
+    assert_ne!(key, cache_key("/a/b.ts", "const a: number = 2;"));
+    assert_ne!(key, cache_key("/a/c.ts", "const a: number = 1;"));
+    assert_eq!(key.path, cache_key("/a/b.ts", "const a: number = 2;").path);
+    assert_ne!(cache_key("ab", "c"), cache_key("a", "bc"));
+
+    assert_eq!(cache.get(&key), None);
+    let output = make_output("const a = 1;\n");
+    cache.put(&key, &output).unwrap();
+    assert_eq!(cache.get(&key), Some(output));
+    assert_eq!(count_files(&tmp.path().join(TRANSPILED_DIR)), 2);
+  }
+
+  #[test]
+  fn cache_stale1() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let dir = tmp.path().join(TRANSPILED_DIR);
+    let cache = TranspileCache::new(dir.clone());
+
+    let key1 = cache_key("/a/b.ts", "const a: number = 1;");
+    let key2 = cache_key("/a/b.ts", "const a: number = 2;");
+    let other = cache_key("/a/c.ts", "const a: number = 1;");
+    cache.put(&key1, &make_output("const a = 1;\n")).unwrap();
+    cache.put(&other, &make_output("const a = 1;\n")).unwrap();
+    assert_eq!(count_files(&dir), 4);
+
+    // The stale entry of the same file is replaced, the other file is kept.
+    cache.put(&key2, &make_output("const a = 2;\n")).unwrap();
+    assert_eq!(cache.get(&key1), None);
+    assert_eq!(cache.get(&key2), Some(make_output("const a = 2;\n")));
+    assert!(cache.get(&other).is_some());
+    assert_eq!(count_files(&dir), 4);
+  }
+
+  #[test]
+  fn cache_max_size1() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let dir = tmp.path().join(TRANSPILED_DIR);
+    let output = make_output("const a = 1;\n");
+    let entry_size = (output.code.len() + output.source_map.len()) as u64;
+    let cache = TranspileCache::new(dir.clone()).with_max_size(entry_size + 1);
+
+    let key1 = cache_key("/a/b.ts", "const a: number = 1;");
+    let key2 = cache_key("/a/c.ts", "const a: number = 1;");
+    cache.put(&key1, &output).unwrap();
+    cache.put(&key2, &output).unwrap();
+
+    // The old entry is removed, the new entry is always kept.
+    assert_eq!(cache.get(&key1), None);
+    assert_eq!(cache.get(&key2), Some(output));
+    assert_eq!(count_files(&dir), 2);
+  }
+}
@@ -1,5 +1,7 @@
 //! Js error.
 
+use crate::js::source_map;
+
 use std::fmt::{Debug, Display};
 
 /// Represents an exception coming from V8.
@@ -66,7 +68,7 @@ impl JsError {
       .map(|stack| stack.unwrap_or_default())
       .ok();
 
-    JsError {
+    let mut error = JsError {
       message: message_value,
       resource_name,
       source_line,
@@ -74,6 +76,43 @@ impl JsError {
       start_column,
       end_column,
       stack,
+    };
+    error.apply_source_map();
+    error
+  }
+
+  /// Map the error position and the stack trace to the original source if the module is
+  /// transpiled, see [`source_map`](crate::js::source_map). The source line is read from the
+  /// original source file.
+  pub fn apply_source_map(&mut self) {
+    self.stack = self.stack.as_deref().map(source_map::map_stack);
+
+    let (line_number, start_column) = match (self.line_number, self.start_column) {
+      (Some(line_number), Some(start_column)) if line_number > 0 && start_column >= 0 => {
+        (line_number, start_column)
+      }
+      _ => return,
+    };
+    let (line, column) = match source_map::lookup(
+      &self.resource_name,
+      (line_number - 1) as u32,
+      start_column as u32,
+    ) {
+      Some(position) => position,
+      None => return,
+    };
+    self.line_number = Some(line as i64 + 1);
+    self.start_column = Some(column as i64);
+    self.end_column = self
+      .end_column
+      .map(|end_column| column as i64 + (end_column - start_column).max(0));
+    if self.source_line.is_some() {
+      if let Some(source_line) = std::fs::read_to_string(&self.resource_name)
+        .ok()
+        .and_then(|source| source.lines().nth(line as usize).map(|l| l.to_string()))
+      {
+        self.source_line = Some(source_line);
+      }
     }
   }
 }
@@ -130,3 +169,47 @@ impl Debug for JsError {
     Ok(())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::js::transpiler::TypeScript;
+
+  #[test]
+  fn apply_source_map1() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    let path = tmp.path().join("config.ts");
+    let filename = path.to_str().unwrap();
+    let source = "const n: number = 1;\ninterface A {}\nthrow new Error(\"oops\");\n";
+    std::fs::write(&path, source).unwrap();
+    let output = TypeScript::compile_with_source_map(Some(filename), source, None).unwrap();
+    source_map::register(filename, &output.source_map);
+
+    // The interface is removed, thus the transpiled `throw` is at the 2nd line.
+    let mut error = JsError {
+      message: "Error: oops".to_string(),
+      resource_name: filename.to_string(),
+      source_line: Some("throw new Error(\"oops\");".to_string()),
+      line_number: Some(2),
+      start_column: Some(0),
+      end_column: Some(5),
+      stack: Some(format!("Error: oops\n    at {}:2:7", filename)),
+    };
+    error.apply_source_map();
+    assert_eq!(error.line_number, Some(3));
+    assert_eq!(error.start_column, Some(0));
+    assert_eq!(error.end_column, Some(5));
+    assert_eq!(
+      error.source_line.as_deref(),
+      Some("throw new Error(\"oops\");")
+    );
+    assert_eq!(
+      error.stack,
+      Some(format!("Error: oops\n    at {}:3:7", filename))
+    );
+    assert_eq!(
+      error.to_string(),
+      format!("Uncaught Error: oops ({}:3:0)", filename)
+    );
+  }
+}
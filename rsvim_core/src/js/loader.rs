@@ -1,9 +1,24 @@
 //! Js module loader.
-
+//!
+//! The module specifiers are resolved with following rules:
+//!
+//! - Absolute path, i.e. `/a/b.js`.
+//! - Relative path to the importing module, i.e. `./b.ts`, `../c/index.js`.
+//! - Bare specifier, i.e. `my-plugin` or `my-plugin/util`. It's a plugin package under the plugins
+//!   directories (see [`plugin_dirs`]), i.e. `~/.config/rsvim/plugins/my-plugin`. The package entry
+//!   is detected by the `exports`, `module` and `main` fields in its `package.json`, then the
+//!   `index.{js,ts,...}` file, see [`package_entry`]. The scoped package (i.e. `@scope/name`) is
+//!   also supported.
+//!
+//! The file extension can be omitted, the `.js`, `.ts`, etc are tried in order.
+
+use crate::envar;
+use crate::js::cache::{cache_dir, cache_key, TranspileCache};
 use crate::js::constant::WINDOWS_REGEX;
 use crate::js::module::ModulePath;
 use crate::js::module::ModuleSource;
 use crate::js::module::CORE_MODULES;
+use crate::js::source_map;
 use crate::js::transpiler::Jsx;
 use crate::js::transpiler::TypeScript;
 use crate::js::transpiler::Wasm;
@@ -18,8 +33,205 @@ use path_absolutize::Absolutize;
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
+use tracing::{error, trace};
 // use url::Url;
 
+/// The plugins directory name under the config directories.
+pub const PLUGINS_DIR: &str = "plugins";
+
+/// Get the plugins directories, i.e. the `plugins` directory under each config directory (see
+/// [`CONFIG_DIRS_PATH`](crate::envar::CONFIG_DIRS_PATH)), the former one is preferred if a plugin
+/// exists in multiple directories.
+pub fn plugin_dirs() -> Vec<PathBuf> {
+  envar::CONFIG_DIRS_PATH()
+    .iter()
+    .map(|dir| dir.join(PLUGINS_DIR))
+    .collect()
+}
+
+/// Split the bare specifier into the package name and the sub path, i.e. `@scope/name/util`
+/// is split into `@scope/name` and `util`.
+///
+/// Returns `None` if the specifier is not a bare specifier.
+pub fn split_package_specifier(specifier: &str) -> Option<(&str, Option<&str>)> {
+  if specifier.is_empty()
+    || specifier.starts_with('.')
+    || specifier.starts_with('/')
+    || specifier.contains('\\')
+    || WINDOWS_REGEX().is_match(specifier)
+  {
+    return None;
+  }
+  // The scoped package name contains a slash.
+  let name_len = if specifier.starts_with('@') {
+    let scope_len = specifier.find('/')?;
+    match specifier[scope_len + 1..].find('/') {
+      Some(n) => scope_len + 1 + n,
+      None => specifier.len(),
+    }
+  } else {
+    specifier.find('/').unwrap_or(specifier.len())
+  };
+  let (name, sub_path) = specifier.split_at(name_len);
+  let sub_path = sub_path.trim_start_matches('/');
+  if name.ends_with('/') || name.ends_with('@') {
+    return None;
+  }
+  Some((name, (!sub_path.is_empty()).then_some(sub_path)))
+}
+
+// Find the target of the `exports` field in `package.json` for the `key` (i.e. `.` or
+// `./util`), the `import` and `default` conditions are supported.
+fn exports_target<'a>(exports: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+  let conditional = |value: &'a serde_json::Value| -> Option<&'a str> {
+    match value {
+      serde_json::Value::String(target) => Some(target.as_str()),
+      serde_json::Value::Object(conditions) => ["import", "default"]
+        .iter()
+        .find_map(|c| conditions.get(*c).and_then(|target| target.as_str())),
+      _ => None,
+    }
+  };
+  match exports {
+    serde_json::Value::String(target) if key == "." => Some(target.as_str()),
+    serde_json::Value::Object(map) if map.keys().any(|k| k.starts_with('.')) => {
+      map.get(key).and_then(conditional)
+    }
+    serde_json::Value::Object(_) if key == "." => conditional(exports),
+    _ => None,
+  }
+}
+
+// Read the `package.json` of the package directory.
+fn read_package_json(dir: &Path) -> Option<serde_json::Value> {
+  let text = fs::read_to_string(dir.join("package.json")).ok()?;
+  serde_json::from_str(&text).ok()
+}
+
+/// Find the entry file of the package directory, with following orders:
+///
+/// 1. The `exports` field (either the `"."` target or the `import`/`default` condition), the
+///    `module` field and the `main` field in `package.json`.
+/// 2. The `index.{js,jsx,ts,tsx,json,wasm}` file.
+///
+/// Returns `None` if no entry file exists.
+pub fn package_entry(dir: &Path) -> Option<PathBuf> {
+  let mut candidates = vec![];
+  if let Some(package) = read_package_json(dir) {
+    let fields = [
+      package.get("exports").and_then(|e| exports_target(e, ".")),
+      package.get("module").and_then(|m| m.as_str()),
+      package.get("main").and_then(|m| m.as_str()),
+    ];
+    candidates.extend(fields.into_iter().flatten().map(|target| dir.join(target)));
+  }
+  candidates.extend(
+    FILE_EXTENSIONS
+      .iter()
+      .map(|ext| dir.join(format!("index.{ext}"))),
+  );
+  let entry = candidates.into_iter().find(|entry| entry.is_file())?;
+  Some(entry.absolutize().ok()?.to_path_buf())
+}
+
+/// Resolve the bare specifier to the plugin package under the `plugin_dirs`, the sub path is
+/// mapped by the `exports` field in `package.json` if it has, otherwise it's relative to the
+/// package directory.
+pub fn resolve_package(plugin_dirs: &[PathBuf], specifier: &str) -> AnyResult<ModulePath> {
+  let (name, sub_path) = match split_package_specifier(specifier) {
+    Some(split) => split,
+    None => bail!(format!("Module not found \"{specifier}\"")),
+  };
+  let dir = match plugin_dirs
+    .iter()
+    .map(|d| d.join(name))
+    .find(|d| d.is_dir())
+  {
+    Some(dir) => dir,
+    None => bail!(format!("Module not found \"{specifier}\"")),
+  };
+  let path = match sub_path {
+    Some(sub_path) => {
+      let key = format!("./{sub_path}");
+      let target = read_package_json(&dir)
+        .and_then(|package| package.get("exports").cloned())
+        .and_then(|exports| exports_target(&exports, &key).map(|t| t.to_string()));
+      match target {
+        Some(target) => dir.join(target),
+        None => dir.join(sub_path),
+      }
+    }
+    None => match package_entry(&dir) {
+      Some(entry) => entry,
+      None => bail!(format!(
+        "Module not found \"{specifier}\", no package entry in \"{}\"",
+        dir.display()
+      )),
+    },
+  };
+  Ok(
+    path
+      .absolutize()?
+      .to_path_buf()
+      .into_os_string()
+      .into_string()
+      .unwrap(),
+  )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The plugin package under the plugins directories.
+pub struct PluginInfo {
+  /// The package name, i.e. the directory name (or `@scope/name` for the scoped package).
+  pub name: String,
+  /// The package directory.
+  pub dir: PathBuf,
+  /// The entry file, see [`package_entry`].
+  pub entry: Option<PathBuf>,
+}
+
+/// List the plugin packages under the `plugin_dirs`, ordered by name. The plugin in the former
+/// directory hides the one with the same name in the latter directories.
+pub fn list_plugins(plugin_dirs: &[PathBuf]) -> Vec<PluginInfo> {
+  // Get the sub directories' names.
+  let sub_dirs = |dir: &Path| -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+      .into_iter()
+      .flatten()
+      .flatten()
+      .filter(|entry| entry.path().is_dir())
+      .filter_map(|entry| entry.file_name().into_string().ok())
+      .filter(|name| !name.starts_with('.'))
+      .collect();
+    names.sort();
+    names
+  };
+
+  let mut plugins: Vec<PluginInfo> = vec![];
+  for plugin_dir in plugin_dirs {
+    for name in sub_dirs(plugin_dir) {
+      let names = if name.starts_with('@') {
+        sub_dirs(&plugin_dir.join(&name))
+          .into_iter()
+          .map(|sub| format!("{name}/{sub}"))
+          .collect()
+      } else {
+        vec![name]
+      };
+      for name in names {
+        if plugins.iter().any(|p| p.name == name) {
+          continue;
+        }
+        let dir = plugin_dir.join(&name);
+        let entry = package_entry(&dir);
+        plugins.push(PluginInfo { name, dir, entry });
+      }
+    }
+  }
+  plugins.sort_by(|a, b| a.name.cmp(&b.name));
+  plugins
+}
+
 /// Defines the interface of a module loader.
 pub trait ModuleLoader {
   fn load(&self, specifier: &str) -> AnyResult<ModuleSource>;
@@ -29,7 +241,11 @@ pub trait ModuleLoader {
 static FILE_EXTENSIONS: &[&str] = &["js", "jsx", "ts", "tsx", "json", "wasm"];
 
 #[derive(Default)]
-pub struct FsModuleLoader;
+pub struct FsModuleLoader {
+  /// Ignores the transpile cache, the TypeScript/JSX modules are always transpiled, see
+  /// [`cache`](crate::js::cache).
+  pub skip_cache: bool,
+}
 
 impl FsModuleLoader {
   /// Transforms PathBuf into String.
@@ -82,6 +298,48 @@ impl FsModuleLoader {
     bail!(format!("Module not found \"{}\"", path.display()));
   }
 
+  /// Transpiles the TypeScript/JSX source, the output is read from (and saved to) the transpile
+  /// cache unless `skip_cache`. The source map is registered for the error stack traces, see
+  /// [`source_map`](crate::js::source_map).
+  fn transpile(
+    &self,
+    filename: Option<&str>,
+    extension: &str,
+    source: &str,
+  ) -> AnyResult<ModuleSource> {
+    let compile = || match extension {
+      "ts" => TypeScript::compile_with_source_map(filename, source, None),
+      "jsx" => Jsx::compile_with_source_map(filename, source),
+      _ => Jsx::compile_with_source_map(filename, source).and_then(|output| {
+        TypeScript::compile_with_source_map(filename, &output.code, Some(&output.source_map))
+      }),
+    };
+
+    let output = if self.skip_cache {
+      compile()?
+    } else {
+      let cache = TranspileCache::new(cache_dir());
+      let key = cache_key(filename.unwrap_or_default(), source);
+      match cache.get(&key) {
+        Some(output) => {
+          trace!("Transpile cache hit: {:?}, {:?}", filename, key);
+          output
+        }
+        None => {
+          let output = compile()?;
+          if let Err(e) = cache.put(&key, &output) {
+            error!("Failed to save transpile cache {:?}: {:?}", filename, e);
+          }
+          output
+        }
+      }
+    };
+    if let Some(filename) = filename {
+      source_map::register(filename, &output.source_map);
+    }
+    Ok(output.code)
+  }
+
   /// Loads import as directory using the 'index.[ext]' convention.
   fn load_as_directory(&self, path: &Path) -> AnyResult<ModuleSource> {
     for ext in FILE_EXTENSIONS {
@@ -112,7 +370,8 @@ impl ModuleLoader for FsModuleLoader {
       return Ok(self.transform(base.join(specifier).absolutize()?.to_path_buf()));
     }
 
-    bail!(format!("Module not found \"{specifier}\""));
+    // Resolve plugin package import.
+    resolve_package(&plugin_dirs(), specifier)
   }
 
   fn load(&self, specifier: &str) -> AnyResult<ModuleSource> {
@@ -139,14 +398,8 @@ impl ModuleLoader for FsModuleLoader {
     // Use a preprocessor if necessary.
     match path_extension {
       "wasm" => Ok(Wasm::parse(&source)),
-      "ts" => {
-        TypeScript::compile(fname, &source).map_err(|e| JsRuntimeErr::Message(e.to_string()).into())
-      }
-      "jsx" => {
-        Jsx::compile(fname, &source).map_err(|e| JsRuntimeErr::Message(e.to_string()).into())
-      }
-      "tsx" => Jsx::compile(fname, &source)
-        .and_then(|output| TypeScript::compile(fname, &output))
+      "ts" | "jsx" | "tsx" => self
+        .transpile(fname, path_extension, &source)
         .map_err(|e| JsRuntimeErr::Message(e.to_string()).into()),
       _ => Ok(source),
     }
@@ -285,7 +538,7 @@ mod tests {
     ];
 
     // Run tests.
-    let loader = FsModuleLoader {};
+    let loader = FsModuleLoader::default();
 
     for (base, specifier, expected) in tests {
       let path = loader.resolve(base, specifier).unwrap();
@@ -332,7 +585,7 @@ mod tests {
     ];
 
     // Run tests.
-    let loader = FsModuleLoader {};
+    let loader = FsModuleLoader::default();
 
     for specifier in tests {
       let path = format!("{}", temp_dir.child(specifier).display());
@@ -343,6 +596,89 @@ mod tests {
     }
   }
 
+  #[test]
+  fn split_package_specifier1() {
+    assert_eq!(split_package_specifier("foo"), Some(("foo", None)));
+    assert_eq!(
+      split_package_specifier("foo/lib/util"),
+      Some(("foo", Some("lib/util")))
+    );
+    assert_eq!(
+      split_package_specifier("@scope/foo"),
+      Some(("@scope/foo", None))
+    );
+    assert_eq!(
+      split_package_specifier("@scope/foo/util"),
+      Some(("@scope/foo", Some("util")))
+    );
+    assert_eq!(split_package_specifier("@scope"), None);
+    assert_eq!(split_package_specifier("./foo"), None);
+    assert_eq!(split_package_specifier("/foo"), None);
+    assert_eq!(split_package_specifier(""), None);
+  }
+
+  #[test]
+  fn resolve_package1() {
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let files = [
+      (
+        "user/a/package.json",
+        r#"{"exports": {".": {"import": "./dist/a.js"}, "./util": "./dist/util.js"}}"#,
+      ),
+      ("user/a/dist/a.js", ""),
+      ("user/a/dist/util.js", ""),
+      ("user/b/package.json", r#"{"main": "missing.js"}"#),
+      ("user/b/index.ts", ""),
+      ("user/b/lib/c.ts", ""),
+      ("user/@s/d/package.json", r#"{"module": "./d.js"}"#),
+      ("user/@s/d/d.js", ""),
+      ("user/e/README.md", ""),
+      ("home/a/index.js", ""),
+      ("home/f/index.js", ""),
+    ];
+    for (file, content) in files {
+      temp_dir.child(file).write_str(content).unwrap();
+    }
+    let dirs = vec![
+      temp_dir.child("user").to_path_buf(),
+      temp_dir.child("home").to_path_buf(),
+    ];
+    let expect = |file: &str| temp_dir.child(file).to_string_lossy().to_string();
+
+    assert_eq!(
+      resolve_package(&dirs, "a").unwrap(),
+      expect("user/a/dist/a.js")
+    );
+    assert_eq!(
+      resolve_package(&dirs, "a/util").unwrap(),
+      expect("user/a/dist/util.js")
+    );
+    assert_eq!(
+      resolve_package(&dirs, "b").unwrap(),
+      expect("user/b/index.ts")
+    );
+    assert_eq!(
+      resolve_package(&dirs, "b/lib/c").unwrap(),
+      expect("user/b/lib/c")
+    );
+    assert_eq!(
+      resolve_package(&dirs, "@s/d").unwrap(),
+      expect("user/@s/d/d.js")
+    );
+    assert_eq!(
+      resolve_package(&dirs, "f").unwrap(),
+      expect("home/f/index.js")
+    );
+    assert!(resolve_package(&dirs, "e").is_err());
+    assert!(resolve_package(&dirs, "g").is_err());
+
+    let plugins = list_plugins(&dirs);
+    let names: Vec<&str> = plugins.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["@s/d", "a", "b", "e", "f"]);
+    assert_eq!(plugins[1].dir, temp_dir.child("user/a").to_path_buf());
+    assert_eq!(plugins[3].entry, None);
+  }
+
   // #[test]
   // fn test_resolve_url_imports() {
   //   // Group of tests to be run.
@@ -1,5 +1,6 @@
 //! Js module.
 
+use crate::js::binding::throw_exception;
 use crate::js::loader::{CoreModuleLoader, FsModuleLoader, ModuleLoader};
 use crate::js::JsRuntime;
 use crate::res::AnyResult;
@@ -359,12 +360,13 @@ pub fn resolve_import(
   if is_core_module_import && !ignore_core_modules {
     CoreModuleLoader {}.resolve(base, &specifier)
   } else {
-    FsModuleLoader {}.resolve(base, &specifier)
+    FsModuleLoader::default().resolve(base, &specifier)
   }
 }
 
-/// Loads an import using the appropriate loader.
-pub fn load_import(specifier: &str, _skip_cache: bool) -> AnyResult<ModuleSource> {
+/// Loads an import using the appropriate loader, the transpile cache is ignored if `skip_cache`
+/// (see [`cache`](crate::js::cache)).
+pub fn load_import(specifier: &str, skip_cache: bool) -> AnyResult<ModuleSource> {
   // // Look the params and choose a loader.
   // let loader: Box<dyn ModuleLoader> = match (
   //   CORE_MODULES().contains_key(specifier),
@@ -384,7 +386,7 @@ pub fn load_import(specifier: &str, _skip_cache: bool) -> AnyResult<ModuleSource
   if is_core_module_import {
     CoreModuleLoader {}.load(specifier)
   } else {
-    FsModuleLoader {}.load(specifier)
+    FsModuleLoader { skip_cache }.load(specifier)
   }
 
   // // We don't actually have core modules
@@ -395,7 +397,12 @@ pub async fn load_import_async(specifier: &str, skip_cache: bool) -> AnyResult<M
   load_import(specifier, skip_cache)
 }
 
-/// Resolves module imports synchronously.
+/// Resolves module imports synchronously, the compiled modules are cached in the module map, thus
+/// a module imported by multiple modules (i.e. a plugin package) is only compiled once.
+///
+/// Returns `None` with the exception thrown in `scope` if any module failed to resolve, load or
+/// compile.
+///
 /// See: <https://source.chromium.org/chromium/v8/v8.git/+/51e736ca62bd5c7bfd82488a5587fed31dbf45d5:src/d8.cc;l=741>.
 pub fn fetch_module_tree<'a>(
   scope: &mut v8::HandleScope<'a>,
@@ -407,9 +414,16 @@ pub fn fetch_module_tree<'a>(
   let state = JsRuntime::state(scope);
 
   // Find appropriate loader if source is empty.
+  let skip_cache = state.borrow().cli_opt.no_cache();
   let source = match source {
     Some(source) => source.into(),
-    None => load_import(filename, true).unwrap(),
+    None => match load_import(filename, skip_cache) {
+      Ok(source) => source,
+      Err(e) => {
+        throw_exception(scope, &e);
+        return None;
+      }
+    },
   };
   trace!(
     "Loaded main js module filename: {:?}, source: {:?}",
@@ -442,7 +456,13 @@ pub fn fetch_module_tree<'a>(
 
     // Transform v8's ModuleRequest into Rust string.
     let specifier = request.get_specifier().to_rust_string_lossy(scope);
-    let specifier = resolve_import(Some(filename), &specifier, false, None).unwrap();
+    let specifier = match resolve_import(Some(filename), &specifier, false, None) {
+      Ok(specifier) => specifier,
+      Err(e) => {
+        throw_exception(scope, &e);
+        return None;
+      }
+    };
     trace!(
       "Resolved dependency js module base: {:?}, specifier: {:?}",
       filename,
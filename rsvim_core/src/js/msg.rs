@@ -3,7 +3,15 @@
 
 use std::time::Duration;
 
+use crate::buf::QuickfixEntry;
+#[cfg(feature = "fetch")]
+use crate::evloop::fetch::{FetchControl, FetchId, FetchRequest};
+use crate::evloop::fs::{FsOp, FsValue};
+use crate::evloop::job::{JobControl, JobId};
+use crate::state::grep::GrepOptions;
+
 use crate::js::JsFutureId;
+use tokio::sync::mpsc::UnboundedReceiver;
 
 // The message JsRuntime send to EventLoop {
 
@@ -12,6 +20,13 @@ use crate::js::JsFutureId;
 /// [`JsRuntime`](crate::js::JsRuntime).
 pub enum JsRuntimeToEventLoopMessage {
   TimeoutReq(TimeoutReq),
+  ClearTimeoutReq(ClearTimeoutReq),
+  GetcharTimeoutReq(GetcharTimeoutReq),
+  JobSpawnReq(JobSpawnReq),
+  #[cfg(feature = "fetch")]
+  FetchReq(FetchReq),
+  FsReq(FsReq),
+  GrepReq(GrepReq),
 }
 
 // The message JsRuntime send to EventLoop }
@@ -24,6 +39,18 @@ pub enum JsRuntimeToEventLoopMessage {
 pub enum EventLoopToJsRuntimeMessage {
   /// Event loop notify Js runtime to shutdown this thread.
   TimeoutResp(TimeoutResp),
+  GetcharResp(GetcharResp),
+  JobStdout(JobOutput),
+  JobStderr(JobOutput),
+  JobExit(JobExit),
+  #[cfg(feature = "fetch")]
+  FetchHead(FetchHead),
+  #[cfg(feature = "fetch")]
+  FetchChunk(FetchChunk),
+  #[cfg(feature = "fetch")]
+  FetchDone(FetchDone),
+  FsResp(FsResp),
+  GrepResp(GrepResp),
 }
 
 // The message JsRuntime receive from EventLoop }
@@ -32,13 +59,16 @@ pub enum EventLoopToJsRuntimeMessage {
 pub struct TimeoutResp {
   pub future_id: JsFutureId,
   pub duration: Duration,
+  /// Whether it's a tick of `setInterval`, i.e. the timer is still running.
+  pub repeat: bool,
 }
 
 impl TimeoutResp {
-  pub fn new(future_id: JsFutureId, duration: Duration) -> Self {
+  pub fn new(future_id: JsFutureId, duration: Duration, repeat: bool) -> Self {
     TimeoutResp {
       future_id,
       duration,
+      repeat,
     }
   }
 }
@@ -47,13 +77,251 @@ impl TimeoutResp {
 pub struct TimeoutReq {
   pub future_id: JsFutureId,
   pub duration: Duration,
+  /// Whether it's `setInterval`, i.e. the timer fires every `duration` until it's cleared.
+  pub repeat: bool,
 }
 
 impl TimeoutReq {
-  pub fn new(future_id: JsFutureId, duration: Duration) -> Self {
+  pub fn new(future_id: JsFutureId, duration: Duration, repeat: bool) -> Self {
     TimeoutReq {
       future_id,
       duration,
+      repeat,
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct ClearTimeoutReq {
+  pub future_id: JsFutureId,
+}
+
+impl ClearTimeoutReq {
+  pub fn new(future_id: JsFutureId) -> Self {
+    ClearTimeoutReq { future_id }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct GetcharTimeoutReq {
+  pub future_id: JsFutureId,
+  pub duration: Duration,
+}
+
+impl GetcharTimeoutReq {
+  pub fn new(future_id: JsFutureId, duration: Duration) -> Self {
+    GetcharTimeoutReq {
+      future_id,
+      duration,
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct GetcharResp {
+  pub future_id: JsFutureId,
+  /// The pressed key in vim's key notation, it's `None` if timeout.
+  pub key: Option<String>,
+}
+
+impl GetcharResp {
+  pub fn new(future_id: JsFutureId, key: Option<String>) -> Self {
+    GetcharResp { future_id, key }
+  }
+}
+
+#[derive(Debug)]
+pub struct JobSpawnReq {
+  pub job_id: JobId,
+  pub cmd: Vec<String>,
+  pub cwd: Option<String>,
+  /// Receives the stdin data and signals sent by js runtime.
+  pub control: UnboundedReceiver<JobControl>,
+}
+
+impl JobSpawnReq {
+  pub fn new(
+    job_id: JobId,
+    cmd: Vec<String>,
+    cwd: Option<String>,
+    control: UnboundedReceiver<JobControl>,
+  ) -> Self {
+    JobSpawnReq {
+      job_id,
+      cmd,
+      cwd,
+      control,
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct JobOutput {
+  pub job_id: JobId,
+  /// The output line, without the line break.
+  pub line: String,
+}
+
+impl JobOutput {
+  pub fn new(job_id: JobId, line: String) -> Self {
+    JobOutput { job_id, line }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct JobExit {
+  pub job_id: JobId,
+  /// The exit code, it's `None` if the job is terminated by a signal, or failed to start.
+  pub code: Option<i32>,
+}
+
+impl JobExit {
+  pub fn new(job_id: JobId, code: Option<i32>) -> Self {
+    JobExit { job_id, code }
+  }
+}
+
+#[cfg(feature = "fetch")]
+#[derive(Debug)]
+pub struct FetchReq {
+  pub fetch_id: FetchId,
+  pub request: FetchRequest,
+  /// Receives the abort signal sent by js runtime.
+  pub control: UnboundedReceiver<FetchControl>,
+}
+
+#[cfg(feature = "fetch")]
+impl FetchReq {
+  pub fn new(
+    fetch_id: FetchId,
+    request: FetchRequest,
+    control: UnboundedReceiver<FetchControl>,
+  ) -> Self {
+    FetchReq {
+      fetch_id,
+      request,
+      control,
     }
   }
 }
+
+#[cfg(feature = "fetch")]
+#[derive(Debug, Default)]
+/// The response head, i.e. the status and headers.
+pub struct FetchHead {
+  pub fetch_id: FetchId,
+  pub status: u16,
+  pub status_text: String,
+  /// The final URL after redirects.
+  pub url: String,
+  pub headers: Vec<(String, String)>,
+}
+
+#[cfg(feature = "fetch")]
+impl FetchHead {
+  pub fn new(
+    fetch_id: FetchId,
+    status: u16,
+    status_text: String,
+    url: String,
+    headers: Vec<(String, String)>,
+  ) -> Self {
+    FetchHead {
+      fetch_id,
+      status,
+      status_text,
+      url,
+      headers,
+    }
+  }
+}
+
+#[cfg(feature = "fetch")]
+#[derive(Default)]
+/// A chunk of the response body.
+pub struct FetchChunk {
+  pub fetch_id: FetchId,
+  pub chunk: Vec<u8>,
+}
+
+#[cfg(feature = "fetch")]
+impl std::fmt::Debug for FetchChunk {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("FetchChunk")
+      .field("fetch_id", &self.fetch_id)
+      .field("len", &self.chunk.len())
+      .finish()
+  }
+}
+
+#[cfg(feature = "fetch")]
+impl FetchChunk {
+  pub fn new(fetch_id: FetchId, chunk: Vec<u8>) -> Self {
+    FetchChunk { fetch_id, chunk }
+  }
+}
+
+#[cfg(feature = "fetch")]
+#[derive(Debug, Default)]
+pub struct FetchDone {
+  pub fetch_id: FetchId,
+  /// The error message, it's `None` if the response body is finished.
+  pub error: Option<String>,
+}
+
+#[cfg(feature = "fetch")]
+impl FetchDone {
+  pub fn new(fetch_id: FetchId, error: Option<String>) -> Self {
+    FetchDone { fetch_id, error }
+  }
+}
+
+#[derive(Debug)]
+pub struct FsReq {
+  pub future_id: JsFutureId,
+  pub op: FsOp,
+}
+
+impl FsReq {
+  pub fn new(future_id: JsFutureId, op: FsOp) -> Self {
+    FsReq { future_id, op }
+  }
+}
+
+#[derive(Debug)]
+pub struct FsResp {
+  pub future_id: JsFutureId,
+  /// The result value, or the error message if the operation fails.
+  pub result: Result<FsValue, String>,
+}
+
+impl FsResp {
+  pub fn new(future_id: JsFutureId, result: Result<FsValue, String>) -> Self {
+    FsResp { future_id, result }
+  }
+}
+
+#[derive(Debug)]
+pub struct GrepReq {
+  pub future_id: JsFutureId,
+  pub options: GrepOptions,
+}
+
+impl GrepReq {
+  pub fn new(future_id: JsFutureId, options: GrepOptions) -> Self {
+    GrepReq { future_id, options }
+  }
+}
+
+#[derive(Debug)]
+pub struct GrepResp {
+  pub future_id: JsFutureId,
+  /// The matches, or the error message if the search fails or it's cancelled.
+  pub result: Result<Vec<QuickfixEntry>, String>,
+}
+
+impl GrepResp {
+  pub fn new(future_id: JsFutureId, result: Result<Vec<QuickfixEntry>, String>) -> Self {
+    GrepResp { future_id, result }
+  }
+}
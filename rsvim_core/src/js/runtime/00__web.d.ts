@@ -1,4 +1,95 @@
 export interface GlobalThis {
     setTimeout(callback: (...args: any[]) => void, delay: number, ...args: any[]): number;
     clearTimeout(id: number): void;
+    setInterval(callback: (...args: any[]) => void, delay: number, ...args: any[]): number;
+    clearInterval(id: number): void;
+    queueMicrotask(callback: () => void): void;
+    fetch(url: string, init?: RequestInit): Promise<Response>;
+    Headers: typeof Headers;
+    Response: typeof Response;
+    AbortController: typeof AbortController;
+    AbortSignal: typeof AbortSignal;
+}
+export type HeadersInit = Headers | [string, string][] | Record<string, string>;
+export interface RequestInit {
+    method?: string;
+    headers?: HeadersInit;
+    body?: string | Uint8Array | ArrayBuffer | null;
+    signal?: AbortSignal | null;
+}
+export declare class Headers {
+    private _map;
+    constructor(init?: HeadersInit);
+    append(name: string, value: string): void;
+    delete(name: string): void;
+    get(name: string): string | null;
+    has(name: string): boolean;
+    set(name: string, value: string): void;
+    entries(): [string, string][];
+    forEach(callback: (value: string, name: string, headers: Headers) => void): void;
+}
+export declare class AbortSignal {
+    aborted: boolean;
+    reason: any;
+    onabort: (() => void) | null;
+    private _listeners;
+    constructor();
+    static abort(reason?: any): AbortSignal;
+    static timeout(delay: number): AbortSignal;
+    addEventListener(type: "abort", listener: () => void): void;
+    removeEventListener(type: "abort", listener: () => void): void;
+    throwIfAborted(): void;
+    private _abort;
+}
+export declare class AbortController {
+    readonly signal: AbortSignal;
+    constructor();
+    abort(reason?: any): void;
+}
+export interface ResponseBodyReadResult {
+    done: boolean;
+    value: Uint8Array | undefined;
+}
+export declare class ResponseBody {
+    private _chunks;
+    private _done;
+    private _error;
+    private _waiters;
+    private _locked;
+    private _cancel;
+    constructor(cancel: () => void);
+    get locked(): boolean;
+    getReader(): ResponseBodyReader;
+    cancel(): Promise<void>;
+    private _read;
+    private _push;
+    private _close;
+    private _fail;
+}
+export declare class ResponseBodyReader {
+    private _body;
+    constructor(body: ResponseBody);
+    read(): Promise<ResponseBodyReadResult>;
+    cancel(): Promise<void>;
+    releaseLock(): void;
+}
+export interface ResponseHead {
+    status: number;
+    statusText: string;
+    url: string;
+    headers: [string, string][];
+}
+export declare class Response {
+    readonly status: number;
+    readonly statusText: string;
+    readonly url: string;
+    readonly headers: Headers;
+    readonly body: ResponseBody;
+    constructor(head: ResponseHead, body: ResponseBody);
+    get ok(): boolean;
+    get bodyUsed(): boolean;
+    bytes(): Promise<Uint8Array>;
+    arrayBuffer(): Promise<ArrayBuffer>;
+    text(): Promise<string>;
+    json(): Promise<any>;
 }
@@ -1,37 +1,406 @@
+var Headers = (function () {
+    function Headers(init) {
+        var _this = this;
+        this._map = new Map();
+        if (init instanceof Headers) {
+            init.forEach(function (value, name) { return _this.append(name, value); });
+        }
+        else if (Array.isArray(init)) {
+            init.forEach(function (header) {
+                if (!Array.isArray(header) || header.length !== 2) {
+                    throw new TypeError("\"Headers\" init must be name-value pairs, but found ".concat(header, " (").concat(typeof header, ")"));
+                }
+                _this.append(header[0], header[1]);
+            });
+        }
+        else if (init !== undefined && init !== null) {
+            Object.keys(init).forEach(function (name) { return _this.append(name, init[name]); });
+        }
+    }
+    Headers.prototype.append = function (name, value) {
+        var key = String(name).toLowerCase();
+        var old = this._map.get(key);
+        this._map.set(key, old === undefined ? String(value) : "".concat(old, ", ").concat(value));
+    };
+    Headers.prototype.delete = function (name) {
+        this._map.delete(String(name).toLowerCase());
+    };
+    Headers.prototype.get = function (name) {
+        var value = this._map.get(String(name).toLowerCase());
+        return value === undefined ? null : value;
+    };
+    Headers.prototype.has = function (name) {
+        return this._map.has(String(name).toLowerCase());
+    };
+    Headers.prototype.set = function (name, value) {
+        this._map.set(String(name).toLowerCase(), String(value));
+    };
+    Headers.prototype.entries = function () {
+        var entries = [];
+        this._map.forEach(function (value, name) { return entries.push([name, value]); });
+        return entries.sort(function (a, b) { return (a[0] < b[0] ? -1 : a[0] > b[0] ? 1 : 0); });
+    };
+    Headers.prototype.forEach = function (callback) {
+        var _this = this;
+        this.entries().forEach(function (entry) { return callback(entry[1], entry[0], _this); });
+    };
+    return Headers;
+}());
+function createAbortError(message, name) {
+    var error = new Error(message);
+    error.name = name;
+    return error;
+}
+var AbortSignal = (function () {
+    function AbortSignal() {
+        this.aborted = false;
+        this.reason = undefined;
+        this.onabort = null;
+        this._listeners = [];
+    }
+    AbortSignal.abort = function (reason) {
+        var signal = new AbortSignal();
+        signal._abort(reason);
+        return signal;
+    };
+    AbortSignal.timeout = function (delay) {
+        var signal = new AbortSignal();
+        setTimeout(function () {
+            signal._abort(createAbortError("The operation timed out.", "TimeoutError"));
+        }, delay);
+        return signal;
+    };
+    AbortSignal.prototype.addEventListener = function (type, listener) {
+        if (type === "abort" && this._listeners.indexOf(listener) < 0) {
+            this._listeners.push(listener);
+        }
+    };
+    AbortSignal.prototype.removeEventListener = function (type, listener) {
+        var i = this._listeners.indexOf(listener);
+        if (type === "abort" && i >= 0) {
+            this._listeners.splice(i, 1);
+        }
+    };
+    AbortSignal.prototype.throwIfAborted = function () {
+        if (this.aborted) {
+            throw this.reason;
+        }
+    };
+    AbortSignal.prototype._abort = function (reason) {
+        var _this = this;
+        if (this.aborted) {
+            return;
+        }
+        this.aborted = true;
+        this.reason =
+            reason === undefined
+                ? createAbortError("The operation was aborted.", "AbortError")
+                : reason;
+        var listeners = this._listeners.slice();
+        if (this.onabort !== null) {
+            listeners.unshift(this.onabort);
+        }
+        listeners.forEach(function (listener) {
+            try {
+                listener.call(_this);
+            }
+            catch (err) {
+                __InternalRsvimGlobalObject.global_report_error(err);
+            }
+        });
+    };
+    return AbortSignal;
+}());
+var AbortController = (function () {
+    function AbortController() {
+        this.signal = new AbortSignal();
+    }
+    AbortController.prototype.abort = function (reason) {
+        this.signal["_abort"](reason);
+    };
+    return AbortController;
+}());
+var ResponseBody = (function () {
+    function ResponseBody(cancel) {
+        this._chunks = [];
+        this._done = false;
+        this._error = undefined;
+        this._waiters = [];
+        this._locked = false;
+        this._cancel = cancel;
+    }
+    Object.defineProperty(ResponseBody.prototype, "locked", {
+        get: function () {
+            return this._locked;
+        },
+        enumerable: false,
+        configurable: true
+    });
+    ResponseBody.prototype.getReader = function () {
+        if (this._locked) {
+            throw new TypeError("The response body is already locked");
+        }
+        this._locked = true;
+        return new ResponseBodyReader(this);
+    };
+    ResponseBody.prototype.cancel = function () {
+        this._chunks = [];
+        if (!this._done && this._error === undefined) {
+            this._cancel();
+            this._close();
+        }
+        return Promise.resolve();
+    };
+    ResponseBody.prototype._read = function () {
+        var _this = this;
+        var chunk = this._chunks.shift();
+        if (chunk !== undefined) {
+            return Promise.resolve({ done: false, value: chunk });
+        }
+        if (this._error !== undefined) {
+            return Promise.reject(this._error);
+        }
+        if (this._done) {
+            return Promise.resolve({ done: true, value: undefined });
+        }
+        return new Promise(function (resolve, reject) {
+            _this._waiters.push([resolve, reject]);
+        });
+    };
+    ResponseBody.prototype._push = function (chunk) {
+        var waiter = this._waiters.shift();
+        if (waiter !== undefined) {
+            waiter[0]({ done: false, value: chunk });
+        }
+        else {
+            this._chunks.push(chunk);
+        }
+    };
+    ResponseBody.prototype._close = function () {
+        this._done = true;
+        this._waiters
+            .splice(0)
+            .forEach(function (waiter) { return waiter[0]({ done: true, value: undefined }); });
+    };
+    ResponseBody.prototype._fail = function (error) {
+        this._error = error;
+        this._waiters.splice(0).forEach(function (waiter) { return waiter[1](error); });
+    };
+    return ResponseBody;
+}());
+var ResponseBodyReader = (function () {
+    function ResponseBodyReader(body) {
+        this._body = body;
+    }
+    ResponseBodyReader.prototype.read = function () {
+        return this._body["_read"]();
+    };
+    ResponseBodyReader.prototype.cancel = function () {
+        return this._body.cancel();
+    };
+    ResponseBodyReader.prototype.releaseLock = function () {
+        this._body["_locked"] = false;
+    };
+    return ResponseBodyReader;
+}());
+var Response = (function () {
+    function Response(head, body) {
+        this.status = head.status;
+        this.statusText = head.statusText;
+        this.url = head.url;
+        this.headers = new Headers(head.headers);
+        this.body = body;
+    }
+    Object.defineProperty(Response.prototype, "ok", {
+        get: function () {
+            return this.status >= 200 && this.status <= 299;
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(Response.prototype, "bodyUsed", {
+        get: function () {
+            return this.body.locked;
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Response.prototype.bytes = function () {
+        if (this.body.locked) {
+            return Promise.reject(new TypeError("The response body is already used"));
+        }
+        var reader = this.body.getReader();
+        var chunks = [];
+        var readAll = function () {
+            return reader.read().then(function (result) {
+                if (!result.done) {
+                    chunks.push(result.value);
+                    return readAll();
+                }
+                var length = 0;
+                chunks.forEach(function (chunk) { return (length += chunk.length); });
+                var bytes = new Uint8Array(length);
+                var offset = 0;
+                chunks.forEach(function (chunk) {
+                    bytes.set(chunk, offset);
+                    offset += chunk.length;
+                });
+                return bytes;
+            });
+        };
+        return readAll();
+    };
+    Response.prototype.arrayBuffer = function () {
+        return this.bytes().then(function (bytes) { return bytes.buffer; });
+    };
+    Response.prototype.text = function () {
+        return this.bytes().then(function (bytes) {
+            return __InternalRsvimGlobalObject.global_decode_utf8(bytes);
+        });
+    };
+    Response.prototype.json = function () {
+        return this.text().then(function (text) { return JSON.parse(text); });
+    };
+    return Response;
+}());
 (function (globalThis) {
     var TIMEOUT_MAX = Math.pow(2, 31) - 1;
     var nextTimerId = 1;
     var activeTimers = new Map();
-    function setTimeout(callback, delay) {
-        var args = [];
-        for (var _i = 2; _i < arguments.length; _i++) {
-            args[_i - 2] = arguments[_i];
-        }
+    function createTimer(name, callback, delay, args, repeat) {
         delay *= 1;
         if (!(delay >= 1 && delay <= TIMEOUT_MAX)) {
             delay = 1;
         }
         if (typeof callback !== "function") {
-            throw new Error("\"setTimeout\" callback must be function type, but found ".concat(callback, " (").concat(typeof callback, ")"));
+            throw new Error("\"".concat(name, "\" callback must be function type, but found ").concat(callback, " (").concat(typeof callback, ")"));
         }
         var id = nextTimerId++;
         var timer = __InternalRsvimGlobalObject.global_set_timeout(function () {
+            if (!repeat) {
+                activeTimers.delete(id);
+            }
             callback.apply(void 0, args);
-            activeTimers.delete(id);
-        }, delay);
+        }, delay, repeat);
         activeTimers.set(id, timer);
         return id;
     }
-    function clearTimeout(id) {
+    function clearTimer(name, id) {
         if (!Number.isInteger(id)) {
-            throw new Error("\"clearTimeout\" id must be integer type, but found ".concat(id, " (").concat(typeof id, ")"));
+            throw new Error("\"".concat(name, "\" id must be integer type, but found ").concat(id, " (").concat(typeof id, ")"));
         }
         if (activeTimers.has(id)) {
             __InternalRsvimGlobalObject.global_clear_timeout(activeTimers.get(id));
             activeTimers.delete(id);
         }
     }
+    function setTimeout(callback, delay) {
+        var args = [];
+        for (var _i = 2; _i < arguments.length; _i++) {
+            args[_i - 2] = arguments[_i];
+        }
+        return createTimer("setTimeout", callback, delay, args, false);
+    }
+    function clearTimeout(id) {
+        clearTimer("clearTimeout", id);
+    }
+    function setInterval(callback, delay) {
+        var args = [];
+        for (var _i = 2; _i < arguments.length; _i++) {
+            args[_i - 2] = arguments[_i];
+        }
+        return createTimer("setInterval", callback, delay, args, true);
+    }
+    function clearInterval(id) {
+        clearTimer("clearInterval", id);
+    }
+    function queueMicrotask(callback) {
+        if (typeof callback !== "function") {
+            throw new Error("\"queueMicrotask\" callback must be function type, but found ".concat(callback, " (").concat(typeof callback, ")"));
+        }
+        __InternalRsvimGlobalObject.global_queue_microtask(function () {
+            try {
+                callback();
+            }
+            catch (err) {
+                __InternalRsvimGlobalObject.global_report_error(err);
+            }
+        });
+    }
+    function fetch(url, init) {
+        return new Promise(function (resolve, reject) {
+            if (typeof __InternalRsvimGlobalObject.global_fetch !== "function") {
+                throw new TypeError("\"fetch\" is not enabled, the editor is built without the \"fetch\" feature");
+            }
+            if (typeof url !== "string") {
+                throw new TypeError("\"fetch\" url must be string type, but found ".concat(url, " (").concat(typeof url, ")"));
+            }
+            var options = init === undefined || init === null ? {} : init;
+            var method = options.method === undefined ? "GET" : String(options.method);
+            var headers = new Headers(options.headers).entries();
+            var body = options.body === undefined ? null : options.body;
+            if (body instanceof ArrayBuffer) {
+                body = new Uint8Array(body);
+            }
+            if (body !== null &&
+                typeof body !== "string" &&
+                !(body instanceof Uint8Array)) {
+                throw new TypeError("\"fetch\" body must be string, Uint8Array or ArrayBuffer type, but found ".concat(body, " (").concat(typeof body, ")"));
+            }
+            var upper = method.toUpperCase();
+            if (body !== null && (upper === "GET" || upper === "HEAD")) {
+                throw new TypeError("\"fetch\" ".concat(upper, " request cannot have body"));
+            }
+            var signal = options.signal === undefined ? null : options.signal;
+            if (signal !== null && signal.aborted) {
+                reject(signal.reason);
+                return;
+            }
+            var id = 0;
+            var response = null;
+            var abort = function () {
+                __InternalRsvimGlobalObject.global_fetch_abort(id);
+            };
+            var responseBody = new ResponseBody(abort);
+            id = __InternalRsvimGlobalObject.global_fetch(method, url, headers, body, function (head) {
+                response = new Response(head, responseBody);
+                resolve(response);
+            }, function (chunk) {
+                responseBody["_push"](chunk);
+            }, function (error) {
+                if (signal !== null) {
+                    signal.removeEventListener("abort", abort);
+                }
+                if (error === null) {
+                    responseBody["_close"]();
+                    return;
+                }
+                var reason = error === "AbortError"
+                    ? signal !== null && signal.aborted
+                        ? signal.reason
+                        : createAbortError("The operation was aborted.", "AbortError")
+                    : new TypeError("Failed to fetch ".concat(url, ": ").concat(error));
+                if (response === null) {
+                    reject(reason);
+                }
+                else {
+                    responseBody["_fail"](reason);
+                }
+            });
+            if (signal !== null) {
+                signal.addEventListener("abort", abort);
+            }
+        });
+    }
     globalThis.setTimeout = setTimeout;
     globalThis.clearTimeout = clearTimeout;
+    globalThis.setInterval = setInterval;
+    globalThis.clearInterval = clearInterval;
+    globalThis.queueMicrotask = queueMicrotask;
+    globalThis.fetch = fetch;
+    globalThis.Headers = Headers;
+    globalThis.Response = Response;
+    globalThis.AbortController = AbortController;
+    globalThis.AbortSignal = AbortSignal;
 })(globalThis);
-export {};
+export { Headers, AbortSignal, AbortController, ResponseBody, ResponseBodyReader, Response };
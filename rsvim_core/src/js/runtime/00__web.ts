@@ -33,6 +33,586 @@ export interface GlobalThis {
    * @throws {@link !Error} if ID is not an integer value.
    */
   clearTimeout(id: number): void;
+
+  /**
+   * Repeatedly executes a function, with a fixed time delay between each call. Also see {@link !setInterval}.
+   *
+   * @param {Function} callback - A function to be executed every `delay` milliseconds.
+   * @param {number} delay - The milliseconds that the timer should wait between the executions.
+   * @param {...any} [args] - Additional arguments which are passed through to the function.
+   * @returns {number} The ID (integer) which identifies the timer created.
+   * @throws {@link !Error} if callback is not a function value.
+   */
+  setInterval(
+    callback: (...args: any[]) => void,
+    delay: number,
+    ...args: any[]
+  ): number;
+
+  /**
+   * Cancels a timed, repeating action previously established by calling {@link setInterval}.
+   *
+   * @param {number} id - The ID (integer) which identifies the timer.
+   * @throws {@link !Error} if ID is not an integer value.
+   */
+  clearInterval(id: number): void;
+
+  /**
+   * Queues a microtask to be executed after current script or callback is finished, but before returning to the editor. Also see {@link !queueMicrotask}.
+   *
+   * @param {Function} callback - A function to be executed.
+   * @throws {@link !Error} if callback is not a function value.
+   */
+  queueMicrotask(callback: () => void): void;
+
+  /**
+   * Sends the HTTP request and returns the response once its status and headers are received, the body is streamed. Also see {@link !fetch}.
+   *
+   * The request runs in the background, it doesn't block the editor. The redirects are followed.
+   *
+   * @example
+   * ```javascript
+   * const controller = new AbortController();
+   * const response = await fetch("https://example.com/api", {
+   *   method: "POST",
+   *   headers: { "Content-Type": "application/json" },
+   *   body: JSON.stringify({ prompt: "hello" }),
+   *   signal: controller.signal,
+   * });
+   * const reader = response.body.getReader();
+   * for (let chunk = await reader.read(); !chunk.done; chunk = await reader.read()) {
+   *   // Handle the chunk (Uint8Array) ...
+   * }
+   * ```
+   *
+   * @param {string} url - The request URL.
+   * @param {RequestInit} init - (Optional) The request method, headers, body and abort signal.
+   * @returns {Promise<Response>} It's rejected with {@link !TypeError} if the request fails (i.e. network error), or the abort reason if it's aborted.
+   * @throws {@link !TypeError} if the arguments are invalid.
+   */
+  fetch(url: string, init?: RequestInit): Promise<Response>;
+
+  Headers: typeof Headers;
+  Response: typeof Response;
+  AbortController: typeof AbortController;
+  AbortSignal: typeof AbortSignal;
+}
+
+/**
+ * The headers of {@link RequestInit}, i.e. a {@link Headers}, an array of name-value pairs or a record.
+ */
+export type HeadersInit = Headers | [string, string][] | Record<string, string>;
+
+/**
+ * The options of {@link GlobalThis.fetch}.
+ */
+export interface RequestInit {
+  /**
+   * The request method, default is `GET`.
+   */
+  method?: string;
+
+  /**
+   * The request headers.
+   */
+  headers?: HeadersInit;
+
+  /**
+   * The request body, it's not allowed for `GET` and `HEAD` requests.
+   */
+  body?: string | Uint8Array | ArrayBuffer | null;
+
+  /**
+   * The signal to abort the request, also see {@link AbortController}.
+   */
+  signal?: AbortSignal | null;
+}
+
+/**
+ * The HTTP headers, the header names are case insensitive. Also see {@link !Headers}.
+ */
+export class Headers {
+  // Lower case name => value.
+  private _map: Map<string, string>;
+
+  /**
+   * @param {HeadersInit} init - (Optional) The initial headers.
+   */
+  constructor(init?: HeadersInit) {
+    this._map = new Map();
+    if (init instanceof Headers) {
+      init.forEach((value, name) => this.append(name, value));
+    } else if (Array.isArray(init)) {
+      init.forEach((header) => {
+        if (!Array.isArray(header) || header.length !== 2) {
+          throw new TypeError(
+            `"Headers" init must be name-value pairs, but found ${header} (${typeof header})`,
+          );
+        }
+        this.append(header[0], header[1]);
+      });
+    } else if (init !== undefined && init !== null) {
+      Object.keys(init).forEach((name) => this.append(name, init[name]));
+    }
+  }
+
+  /**
+   * Appends a value to the header, the values are joined by `", "`.
+   */
+  append(name: string, value: string): void {
+    const key = String(name).toLowerCase();
+    const old = this._map.get(key);
+    this._map.set(key, old === undefined ? String(value) : `${old}, ${value}`);
+  }
+
+  /**
+   * Deletes the header.
+   */
+  delete(name: string): void {
+    this._map.delete(String(name).toLowerCase());
+  }
+
+  /**
+   * Gets the header value, or `null` if it doesn't exist.
+   */
+  get(name: string): string | null {
+    const value = this._map.get(String(name).toLowerCase());
+    return value === undefined ? null : value;
+  }
+
+  /**
+   * Whether the header exists.
+   */
+  has(name: string): boolean {
+    return this._map.has(String(name).toLowerCase());
+  }
+
+  /**
+   * Sets the header value, it replaces the old values.
+   */
+  set(name: string, value: string): void {
+    this._map.set(String(name).toLowerCase(), String(value));
+  }
+
+  /**
+   * Gets all the headers as name-value pairs, the names are lower case and sorted.
+   */
+  entries(): [string, string][] {
+    const entries: [string, string][] = [];
+    this._map.forEach((value, name) => entries.push([name, value]));
+    return entries.sort((a, b) => (a[0] < b[0] ? -1 : a[0] > b[0] ? 1 : 0));
+  }
+
+  /**
+   * Calls the callback for each header, in the order of {@link entries}.
+   */
+  forEach(callback: (value: string, name: string, headers: Headers) => void): void {
+    this.entries().forEach((entry) => callback(entry[1], entry[0], this));
+  }
+}
+
+function createAbortError(message: string, name: string): Error {
+  const error = new Error(message);
+  error.name = name;
+  return error;
+}
+
+/**
+ * The signal to abort an operation, i.e. {@link GlobalThis.fetch}. Also see {@link !AbortSignal}.
+ *
+ * @hideconstructor
+ */
+export class AbortSignal {
+  /**
+   * Whether it's aborted.
+   */
+  aborted: boolean;
+
+  /**
+   * The abort reason, it's `undefined` if not aborted.
+   */
+  reason: any;
+
+  /**
+   * Called when it's aborted.
+   */
+  onabort: (() => void) | null;
+
+  private _listeners: (() => void)[];
+
+  constructor() {
+    this.aborted = false;
+    this.reason = undefined;
+    this.onabort = null;
+    this._listeners = [];
+  }
+
+  /**
+   * Creates an already aborted signal.
+   *
+   * @param {any} reason - (Optional) The abort reason, default is an `AbortError` error.
+   */
+  static abort(reason?: any): AbortSignal {
+    const signal = new AbortSignal();
+    signal._abort(reason);
+    return signal;
+  }
+
+  /**
+   * Creates a signal which is aborted after `delay` milliseconds, with a `TimeoutError` error.
+   *
+   * @param {number} delay - The milliseconds.
+   */
+  static timeout(delay: number): AbortSignal {
+    const signal = new AbortSignal();
+    setTimeout(() => {
+      signal._abort(
+        createAbortError("The operation timed out.", "TimeoutError"),
+      );
+    }, delay);
+    return signal;
+  }
+
+  /**
+   * Adds the listener of the `abort` event.
+   */
+  addEventListener(type: "abort", listener: () => void): void {
+    if (type === "abort" && this._listeners.indexOf(listener) < 0) {
+      this._listeners.push(listener);
+    }
+  }
+
+  /**
+   * Removes the listener of the `abort` event.
+   */
+  removeEventListener(type: "abort", listener: () => void): void {
+    const i = this._listeners.indexOf(listener);
+    if (type === "abort" && i >= 0) {
+      this._listeners.splice(i, 1);
+    }
+  }
+
+  /**
+   * Throws the abort reason if it's aborted.
+   */
+  throwIfAborted(): void {
+    if (this.aborted) {
+      throw this.reason;
+    }
+  }
+
+  private _abort(reason?: any): void {
+    if (this.aborted) {
+      return;
+    }
+    this.aborted = true;
+    this.reason =
+      reason === undefined
+        ? createAbortError("The operation was aborted.", "AbortError")
+        : reason;
+    const listeners = this._listeners.slice();
+    if (this.onabort !== null) {
+      listeners.unshift(this.onabort);
+    }
+    listeners.forEach((listener) => {
+      try {
+        listener.call(this);
+      } catch (err) {
+        // @ts-ignore Ignore __InternalRsvimGlobalObject warning
+        __InternalRsvimGlobalObject.global_report_error(err);
+      }
+    });
+  }
+}
+
+/**
+ * The controller to abort an operation with its {@link signal}. Also see {@link !AbortController}.
+ */
+export class AbortController {
+  /**
+   * The signal which is passed to the operation.
+   */
+  readonly signal: AbortSignal;
+
+  constructor() {
+    this.signal = new AbortSignal();
+  }
+
+  /**
+   * Aborts the operation.
+   *
+   * @param {any} reason - (Optional) The abort reason, default is an `AbortError` error.
+   */
+  abort(reason?: any): void {
+    this.signal["_abort"](reason);
+  }
+}
+
+/**
+ * The result of {@link ResponseBodyReader.read}.
+ */
+export interface ResponseBodyReadResult {
+  /**
+   * Whether the body is finished.
+   */
+  done: boolean;
+
+  /**
+   * The body chunk, it's `undefined` if the body is finished.
+   */
+  value: Uint8Array | undefined;
+}
+
+/**
+ * The streamed response body, it's a subset of {@link !ReadableStream}.
+ *
+ * @hideconstructor
+ */
+export class ResponseBody {
+  private _chunks: Uint8Array[];
+  private _done: boolean;
+  private _error: any;
+  private _waiters: [
+    (result: ResponseBodyReadResult) => void,
+    (error: any) => void,
+  ][];
+  private _locked: boolean;
+  private _cancel: () => void;
+
+  constructor(cancel: () => void) {
+    this._chunks = [];
+    this._done = false;
+    this._error = undefined;
+    this._waiters = [];
+    this._locked = false;
+    this._cancel = cancel;
+  }
+
+  /**
+   * Whether the body is locked by a reader.
+   */
+  get locked(): boolean {
+    return this._locked;
+  }
+
+  /**
+   * Gets the reader, the body is locked by it.
+   *
+   * @throws {@link !TypeError} if the body is already locked.
+   */
+  getReader(): ResponseBodyReader {
+    if (this._locked) {
+      throw new TypeError("The response body is already locked");
+    }
+    this._locked = true;
+    return new ResponseBodyReader(this);
+  }
+
+  /**
+   * Cancels the body, i.e. aborts the request.
+   */
+  cancel(): Promise<void> {
+    this._chunks = [];
+    if (!this._done && this._error === undefined) {
+      this._cancel();
+      this._close();
+    }
+    return Promise.resolve();
+  }
+
+  private _read(): Promise<ResponseBodyReadResult> {
+    const chunk = this._chunks.shift();
+    if (chunk !== undefined) {
+      return Promise.resolve({ done: false, value: chunk });
+    }
+    if (this._error !== undefined) {
+      return Promise.reject(this._error);
+    }
+    if (this._done) {
+      return Promise.resolve({ done: true, value: undefined });
+    }
+    return new Promise((resolve, reject) => {
+      this._waiters.push([resolve, reject]);
+    });
+  }
+
+  private _push(chunk: Uint8Array): void {
+    const waiter = this._waiters.shift();
+    if (waiter !== undefined) {
+      waiter[0]({ done: false, value: chunk });
+    } else {
+      this._chunks.push(chunk);
+    }
+  }
+
+  private _close(): void {
+    this._done = true;
+    this._waiters
+      .splice(0)
+      .forEach((waiter) => waiter[0]({ done: true, value: undefined }));
+  }
+
+  private _fail(error: any): void {
+    this._error = error;
+    this._waiters.splice(0).forEach((waiter) => waiter[1](error));
+  }
+}
+
+/**
+ * The reader of {@link ResponseBody}, it's a subset of {@link !ReadableStreamDefaultReader}.
+ *
+ * @hideconstructor
+ */
+export class ResponseBodyReader {
+  private _body: ResponseBody;
+
+  constructor(body: ResponseBody) {
+    this._body = body;
+  }
+
+  /**
+   * Reads the next body chunk.
+   *
+   * @returns {Promise<ResponseBodyReadResult>} It's rejected if the request fails or it's aborted.
+   */
+  read(): Promise<ResponseBodyReadResult> {
+    return this._body["_read"]();
+  }
+
+  /**
+   * Cancels the body, i.e. aborts the request.
+   */
+  cancel(): Promise<void> {
+    return this._body.cancel();
+  }
+
+  /**
+   * Releases the lock of the body.
+   */
+  releaseLock(): void {
+    this._body["_locked"] = false;
+  }
+}
+
+/**
+ * The head of the response received by {@link GlobalThis.fetch}, the headers are name-value
+ * pairs.
+ */
+export interface ResponseHead {
+  status: number;
+  statusText: string;
+  url: string;
+  headers: [string, string][];
+}
+
+/**
+ * The HTTP response of {@link GlobalThis.fetch}. Also see {@link !Response}.
+ *
+ * @hideconstructor
+ */
+export class Response {
+  /**
+   * The status code.
+   */
+  readonly status: number;
+
+  /**
+   * The status message, i.e. `OK`.
+   */
+  readonly statusText: string;
+
+  /**
+   * The final URL after redirects.
+   */
+  readonly url: string;
+
+  /**
+   * The response headers.
+   */
+  readonly headers: Headers;
+
+  /**
+   * The streamed response body.
+   */
+  readonly body: ResponseBody;
+
+  constructor(head: ResponseHead, body: ResponseBody) {
+    this.status = head.status;
+    this.statusText = head.statusText;
+    this.url = head.url;
+    this.headers = new Headers(head.headers);
+    this.body = body;
+  }
+
+  /**
+   * Whether the status is successful, i.e. in the range 200-299.
+   */
+  get ok(): boolean {
+    return this.status >= 200 && this.status <= 299;
+  }
+
+  /**
+   * Whether the body is already read.
+   */
+  get bodyUsed(): boolean {
+    return this.body.locked;
+  }
+
+  /**
+   * Reads the whole body.
+   *
+   * @returns {Promise<Uint8Array>} It's rejected with {@link !TypeError} if the body is already read.
+   */
+  bytes(): Promise<Uint8Array> {
+    if (this.body.locked) {
+      return Promise.reject(
+        new TypeError("The response body is already used"),
+      );
+    }
+    const reader = this.body.getReader();
+    const chunks: Uint8Array[] = [];
+    const readAll = (): Promise<Uint8Array> =>
+      reader.read().then((result) => {
+        if (!result.done) {
+          chunks.push(result.value as Uint8Array);
+          return readAll();
+        }
+        let length = 0;
+        chunks.forEach((chunk) => (length += chunk.length));
+        const bytes = new Uint8Array(length);
+        let offset = 0;
+        chunks.forEach((chunk) => {
+          bytes.set(chunk, offset);
+          offset += chunk.length;
+        });
+        return bytes;
+      });
+    return readAll();
+  }
+
+  /**
+   * Reads the whole body as {@link !ArrayBuffer}.
+   */
+  arrayBuffer(): Promise<ArrayBuffer> {
+    return this.bytes().then((bytes) => bytes.buffer as ArrayBuffer);
+  }
+
+  /**
+   * Reads the whole body as UTF-8 text, the invalid sequences are replaced.
+   */
+  text(): Promise<string> {
+    return this.bytes().then((bytes) =>
+      // @ts-ignore Ignore __InternalRsvimGlobalObject warning
+      __InternalRsvimGlobalObject.global_decode_utf8(bytes),
+    );
+  }
+
+  /**
+   * Reads the whole body and parses it as JSON.
+   */
+  json(): Promise<any> {
+    return this.text().then((text) => JSON.parse(text));
+  }
 }
 
 ((globalThis: GlobalThis) => {
@@ -42,10 +622,12 @@ export interface GlobalThis {
   let nextTimerId = 1;
   const activeTimers = new Map();
 
-  function setTimeout(
+  function createTimer(
+    name: string,
     callback: (...args: any[]) => void,
     delay: number,
-    ...args: any[]
+    args: any[],
+    repeat: boolean,
   ): number {
     // Coalesce to number or NaN.
     delay *= 1;
@@ -58,7 +640,7 @@ export interface GlobalThis {
     // Check if callback is a valid function.
     if (typeof callback !== "function") {
       throw new Error(
-        `"setTimeout" callback must be function type, but found ${callback} (${typeof callback})`,
+        `"${name}" callback must be function type, but found ${callback} (${typeof callback})`,
       );
     }
 
@@ -66,10 +648,16 @@ export interface GlobalThis {
     const id = nextTimerId++;
 
     // @ts-ignore Ignore __InternalRsvimGlobalObject warning
-    const timer = __InternalRsvimGlobalObject.global_set_timeout(() => {
-      callback(...args);
-      activeTimers.delete(id);
-    }, delay);
+    const timer = __InternalRsvimGlobalObject.global_set_timeout(
+      () => {
+        if (!repeat) {
+          activeTimers.delete(id);
+        }
+        callback(...args);
+      },
+      delay,
+      repeat,
+    );
 
     // Update `activeTimers` map.
     activeTimers.set(id, timer);
@@ -77,11 +665,11 @@ export interface GlobalThis {
     return id;
   }
 
-  function clearTimeout(id: number): void {
+  function clearTimer(name: string, id: number): void {
     // Check parameter's type.
     if (!Number.isInteger(id)) {
       throw new Error(
-        `"clearTimeout" id must be integer type, but found ${id} (${typeof id})`,
+        `"${name}" id must be integer type, but found ${id} (${typeof id})`,
       );
     }
 
@@ -92,28 +680,157 @@ export interface GlobalThis {
     }
   }
 
+  function setTimeout(
+    callback: (...args: any[]) => void,
+    delay: number,
+    ...args: any[]
+  ): number {
+    return createTimer("setTimeout", callback, delay, args, false);
+  }
+
+  function clearTimeout(id: number): void {
+    clearTimer("clearTimeout", id);
+  }
+
+  function setInterval(
+    callback: (...args: any[]) => void,
+    delay: number,
+    ...args: any[]
+  ): number {
+    return createTimer("setInterval", callback, delay, args, true);
+  }
+
+  function clearInterval(id: number): void {
+    clearTimer("clearInterval", id);
+  }
+
   // Timer API }
 
-  // const { $$queueMicrotask, reportError } = globalThis;
-  //
-  // // Note: We wrap `queueMicrotask` and manually emit the exception because
-  // // v8 doesn't provide any mechanism to handle callback exceptions during
-  // // the microtask_checkpoint phase.
-  // function queueMicrotask(callback) {
-  //   // Check if the callback argument is a valid type.
-  //   if (typeof callback !== "function") {
-  //     throw new TypeError(`The "callback" argument must be a function.`);
-  //   }
-  //
-  //   $$queueMicrotask(() => {
-  //     try {
-  //       callback();
-  //     } catch (err) {
-  //       reportError(err);
-  //     }
-  //   });
-  // }
+  // Microtask API {
+
+  // NOTE: We wrap the callback and manually report the exception because v8 doesn't provide any
+  // mechanism to handle callback exceptions during the microtask checkpoint phase.
+  function queueMicrotask(callback: () => void): void {
+    // Check if callback is a valid function.
+    if (typeof callback !== "function") {
+      throw new Error(
+        `"queueMicrotask" callback must be function type, but found ${callback} (${typeof callback})`,
+      );
+    }
+
+    // @ts-ignore Ignore __InternalRsvimGlobalObject warning
+    __InternalRsvimGlobalObject.global_queue_microtask(() => {
+      try {
+        callback();
+      } catch (err) {
+        // @ts-ignore Ignore __InternalRsvimGlobalObject warning
+        __InternalRsvimGlobalObject.global_report_error(err);
+      }
+    });
+  }
+
+  // Microtask API }
+
+  // Fetch API {
+
+  function fetch(url: string, init?: RequestInit): Promise<Response> {
+    return new Promise((resolve, reject) => {
+      // @ts-ignore Ignore __InternalRsvimGlobalObject warning
+      if (typeof __InternalRsvimGlobalObject.global_fetch !== "function") {
+        throw new TypeError(
+          `"fetch" is not enabled, the editor is built without the "fetch" feature`,
+        );
+      }
+      if (typeof url !== "string") {
+        throw new TypeError(
+          `"fetch" url must be string type, but found ${url} (${typeof url})`,
+        );
+      }
+      const options = init === undefined || init === null ? {} : init;
+      const method =
+        options.method === undefined ? "GET" : String(options.method);
+      const headers = new Headers(options.headers).entries();
+      let body = options.body === undefined ? null : options.body;
+      if (body instanceof ArrayBuffer) {
+        body = new Uint8Array(body);
+      }
+      if (
+        body !== null &&
+        typeof body !== "string" &&
+        !(body instanceof Uint8Array)
+      ) {
+        throw new TypeError(
+          `"fetch" body must be string, Uint8Array or ArrayBuffer type, but found ${body} (${typeof body})`,
+        );
+      }
+      const upper = method.toUpperCase();
+      if (body !== null && (upper === "GET" || upper === "HEAD")) {
+        throw new TypeError(`"fetch" ${upper} request cannot have body`);
+      }
+      const signal = options.signal === undefined ? null : options.signal;
+      if (signal !== null && signal.aborted) {
+        reject(signal.reason);
+        return;
+      }
+
+      let id = 0;
+      let response: Response | null = null;
+      const abort = () => {
+        // @ts-ignore Ignore __InternalRsvimGlobalObject warning
+        __InternalRsvimGlobalObject.global_fetch_abort(id);
+      };
+      const responseBody = new ResponseBody(abort);
+
+      // @ts-ignore Ignore __InternalRsvimGlobalObject warning
+      id = __InternalRsvimGlobalObject.global_fetch(
+        method,
+        url,
+        headers,
+        body,
+        (head: ResponseHead) => {
+          response = new Response(head, responseBody);
+          resolve(response);
+        },
+        (chunk: Uint8Array) => {
+          responseBody["_push"](chunk);
+        },
+        (error: string | null) => {
+          if (signal !== null) {
+            signal.removeEventListener("abort", abort);
+          }
+          if (error === null) {
+            responseBody["_close"]();
+            return;
+          }
+          const reason =
+            error === "AbortError"
+              ? signal !== null && signal.aborted
+                ? signal.reason
+                : createAbortError("The operation was aborted.", "AbortError")
+              : new TypeError(`Failed to fetch ${url}: ${error}`);
+          if (response === null) {
+            reject(reason);
+          } else {
+            responseBody["_fail"](reason);
+          }
+        },
+      );
+      if (signal !== null) {
+        signal.addEventListener("abort", abort);
+      }
+    });
+  }
+
+  // Fetch API }
 
   globalThis.setTimeout = setTimeout;
   globalThis.clearTimeout = clearTimeout;
+  globalThis.setInterval = setInterval;
+  globalThis.clearInterval = clearInterval;
+  globalThis.queueMicrotask = queueMicrotask;
+  globalThis.fetch = fetch;
+  globalThis.Headers = Headers;
+  globalThis.Response = Response;
+  globalThis.AbortController = AbortController;
+  globalThis.AbortSignal = AbortSignal;
 })(globalThis as unknown as GlobalThis);
@@ -1,9 +1,419 @@
 export declare class Rsvim {
     readonly opt: RsvimOpt;
+    readonly progress: RsvimProgress;
+    readonly input: RsvimInput;
+    readonly hl: RsvimHl;
+    readonly ui: RsvimUi;
+    readonly win: RsvimWin;
+    readonly register: RsvimRegister;
+    readonly mark: RsvimMark;
+    readonly sign: RsvimSign;
+    readonly template: RsvimTemplate;
+    readonly filetype: RsvimFiletype;
+    readonly statusline: RsvimStatusline;
+    readonly git: RsvimGit;
+    readonly spell: RsvimSpell;
+    readonly buf: RsvimBuf;
+    readonly keymap: RsvimKeymap;
+    readonly job: RsvimJob;
+    readonly plugin: RsvimPlugin;
+    readonly fs: RsvimFs;
+    readonly picker: RsvimPicker;
+    on(event: RsvimEvent, callback: (args: RsvimEventArgs) => void, pattern?: string): number;
+    off(id: number): boolean;
+    cmd(command: string): void;
+    notify(message: string, level?: RsvimMessageLevel): void;
+    print(...values: any[]): any;
+    grep(pattern: string, options?: RsvimGrepOptions): Promise<RsvimQuickfixEntry[]>;
 }
+export type RsvimMessageLevel = "info" | "warn" | "error";
+export interface RsvimGrepOptions {
+    literal?: boolean;
+    ignoreCase?: boolean;
+    smartCase?: boolean;
+    hidden?: boolean;
+    noIgnore?: boolean;
+    paths?: string[];
+}
+export interface RsvimQuickfixEntry {
+    path: string;
+    line: number;
+    col: number;
+    text: string;
+}
+export type RsvimEvent = "BufReadPost" | "BufWritePre" | "BufWritePost" | "BufFilePre" | "BufFilePost" | "FileType" | "WinResized" | "WinScrolled" | "ModeChanged" | "FocusGained" | "CursorMoved" | "TextChanged" | "StatusLineClick" | "Paste" | "BufModifiedSet";
+export interface RsvimEventArgs {
+    event: RsvimEvent;
+    match: string;
+    bufferId: number | null;
+    windowId: number | null;
+    scrollDelta: RsvimScrollDelta | null;
+}
+export interface RsvimScrollDelta {
+    lines: number;
+    columns: number;
+}
+export type RsvimStatusLineCallback = (windowId: number) => string;
 export declare class RsvimOpt {
     get wrap(): boolean;
     set wrap(value: boolean);
     get lineBreak(): boolean;
     set lineBreak(value: boolean);
+    get scrollOff(): number;
+    set scrollOff(value: number);
+    get sideScroll(): number;
+    set sideScroll(value: number);
+    get sideScrollOff(): number;
+    set sideScrollOff(value: number);
+    get clipboard(): string;
+    set clipboard(value: string);
+    get paste(): boolean;
+    set paste(value: boolean);
+    get langMap(): string;
+    set langMap(value: string);
+    get cursorWord(): string;
+    set cursorWord(value: string);
+    get mapLeader(): string;
+    set mapLeader(value: string);
+    get timeoutLen(): number;
+    set timeoutLen(value: number);
+    get tTimeout(): boolean;
+    set tTimeout(value: boolean);
+    get tTimeoutLen(): number;
+    set tTimeoutLen(value: number);
+    get minimap(): boolean;
+    set minimap(value: boolean);
+    get hlSearch(): boolean;
+    set hlSearch(value: boolean);
+    get incSearch(): boolean;
+    set incSearch(value: boolean);
+    get autoPairs(): boolean;
+    set autoPairs(value: boolean);
+    get jumpCenter(): "auto" | "always" | "never";
+    set jumpCenter(value: "auto" | "always" | "never");
+    get quickfixPreview(): boolean;
+    set quickfixPreview(value: boolean);
+    get title(): boolean;
+    set title(value: boolean);
+    get titleString(): string;
+    set titleString(value: string);
+    get termGuiColors(): boolean;
+    set termGuiColors(value: boolean);
+    get termSync(): boolean;
+    set termSync(value: boolean);
+    get termStrikethrough(): boolean;
+    set termStrikethrough(value: boolean);
+    get termUndercurl(): boolean;
+    set termUndercurl(value: boolean);
+    get termPaste(): boolean;
+    set termPaste(value: boolean);
+    get backup(): boolean;
+    set backup(value: boolean);
+    get writeBackup(): boolean;
+    set writeBackup(value: boolean);
+    get fsync(): "never" | "file" | "full";
+    set fsync(value: "never" | "file" | "full");
+    get mkdir(): boolean;
+    set mkdir(value: boolean);
+    get lastPosExclude(): string;
+    set lastPosExclude(value: string);
+    get undoLevels(): number;
+    set undoLevels(value: number);
+    get undoMemory(): number;
+    set undoMemory(value: number);
+    get undoFile(): boolean;
+    set undoFile(value: boolean);
+    get statusLine(): string | RsvimStatusLineCallback;
+    set statusLine(value: string | RsvimStatusLineCallback);
+    get winFixWidth(): boolean;
+    set winFixWidth(value: boolean);
+    get winFixHeight(): boolean;
+    set winFixHeight(value: boolean);
+    get number(): boolean;
+    set number(value: boolean);
+    get relativeNumber(): boolean;
+    set relativeNumber(value: boolean);
+    get numberWidth(): number;
+    set numberWidth(value: number);
+    get signColumn(): "auto" | "yes" | "no";
+    set signColumn(value: "auto" | "yes" | "no");
+    get signWidth(): number;
+    set signWidth(value: number);
+    get wildMode(): string;
+    set wildMode(value: string);
+    get wildOptions(): string;
+    set wildOptions(value: string);
+}
+export declare class RsvimProgress {
+    begin(title: string, options?: {
+        cancellable?: boolean;
+    }): number;
+    update(id: number, message: string, percentage?: number): void;
+    end(id: number): void;
+    isCancelled(id: number): boolean;
+}
+export declare class RsvimInput {
+    getchar(options?: {
+        timeout?: number;
+    }): Promise<string | null>;
+    typeahead(): number;
+    batchBegin(): void;
+    batchEnd(): boolean;
+    isBatch(): boolean;
+}
+export interface RsvimHlStyle {
+    fg?: string;
+    bg?: string;
+    bold?: boolean;
+    italic?: boolean;
+    underline?: boolean;
+    reverse?: boolean;
+    strikethrough?: boolean;
+}
+export interface RsvimHlGroupStyle extends RsvimHlStyle {
+    link?: string;
+}
+export declare class RsvimHl {
+    createNamespace(name: string, priority?: number): number;
+    addHighlight(ns: number, line: number, start: number, end: number, style: RsvimHlStyle): boolean;
+    clearNamespace(ns: number, start?: number, end?: number): void;
+    set(group: string, style: RsvimHlGroupStyle): void;
+}
+export interface RsvimUiInputOptions {
+    prompt?: string;
+    default?: string;
+}
+export interface RsvimUiSelectOptions<T> {
+    prompt?: string;
+    format?: (item: T) => string;
+}
+export declare class RsvimUi {
+    redraw(options?: {
+        force?: boolean;
+    }): void;
+    input(options?: RsvimUiInputOptions): Promise<string | null>;
+    select<T>(items: T[], options?: RsvimUiSelectOptions<T>): Promise<T | null>;
+}
+export interface RsvimFloatOptions {
+    buffer?: number;
+    text?: string;
+    anchor?: "editor" | "window" | "cursor";
+    row?: number;
+    col?: number;
+    width: number;
+    height: number;
+    zIndex?: number;
+    border?: "none" | "single" | "double" | "rounded";
+    shadow?: boolean;
+    title?: string;
+    enter?: boolean;
+}
+export declare class RsvimWin {
+    openFloat(options: RsvimFloatOptions): number;
+    closeFloat(id: number): boolean;
+    current(): number | null;
+    focus(id: number): boolean;
+    setCursor(line: number, col: number): boolean;
+}
+export type RsvimRegisterType = "charwise" | "linewise" | "blockwise";
+export interface RsvimRegisterValue {
+    type: RsvimRegisterType;
+    content: string;
+}
+export declare class RsvimRegister {
+    get(name: string): RsvimRegisterValue | null;
+    set(name: string, content: string, type?: RsvimRegisterType): boolean;
+}
+export interface RsvimMarkValue {
+    bufferId: number;
+    line: number;
+    col: number;
+}
+export declare class RsvimMark {
+    get(name: string): RsvimMarkValue | null;
+    set(name: string, line: number, col: number): boolean;
+}
+export interface RsvimSignDefinition {
+    text: string;
+    style?: RsvimHlStyle;
+}
+export interface RsvimSignPlaceOptions {
+    bufferId?: number;
+    group?: string;
+    priority?: number;
+}
+export interface RsvimPlacedSign {
+    id: number;
+    name: string;
+    line: number;
+    group: string;
+    priority: number;
+}
+export declare class RsvimSign {
+    define(name: string, definition: RsvimSignDefinition): void;
+    undefine(name: string): boolean;
+    place(name: string, line: number, options?: RsvimSignPlaceOptions): number;
+    unplace(id: number, bufferId?: number): boolean;
+    clear(group?: string, bufferId?: number): void;
+    getPlaced(bufferId?: number): RsvimPlacedSign[] | null;
+}
+export declare class RsvimTemplate {
+    add(pattern: string, file: string): void;
+    setVariable(name: string, value: string): void;
+}
+export interface RsvimFiletypeOptions {
+    commentstring?: string;
+    tabstop?: number;
+    shiftwidth?: number;
+    expandtab?: boolean;
+    suffixesadd?: string;
+}
+export declare class RsvimFiletype {
+    add(pattern: string, filetype: string): void;
+    set(filetype: string, options: RsvimFiletypeOptions): void;
+}
+export declare class RsvimStatusline {
+    private components;
+    component(name: string, render: () => string, events?: RsvimEvent[]): void;
+    update(name: string): boolean;
+    remove(name: string): boolean;
+}
+export interface RsvimGitStatus {
+    branch: string;
+    dirty: boolean;
+    ahead: number;
+    behind: number;
+}
+export declare class RsvimGit {
+    status(): RsvimGitStatus | null;
+    refresh(): void;
+}
+export declare class RsvimSpell {
+    add(word: string): boolean;
+    check(word: string): boolean;
+    words(): string[];
+    reload(): void;
+}
+export interface RsvimExtmarkOptions {
+    id?: number;
+    endLine?: number;
+    endCol?: number;
+    style?: RsvimHlStyle;
+    virtText?: string;
+    virtTextStyle?: RsvimHlStyle;
+    rightGravity?: boolean;
+    endRightGravity?: boolean;
+    priority?: number;
+}
+export interface RsvimExtmark {
+    ns: number;
+    line: number;
+    col: number;
+    endLine?: number;
+    endCol?: number;
+}
+export interface RsvimBufInfo {
+    id: number;
+    name: string | null;
+    listed: boolean;
+    modified: boolean;
+}
+export declare class RsvimBuf {
+    getLines(start: number, end: number): string[] | null;
+    setLines(start: number, end: number, lines: string[]): boolean;
+    list(all?: boolean): RsvimBufInfo[];
+    isModified(id: number): boolean | null;
+    setExtmark(ns: number, line: number, col: number, options?: RsvimExtmarkOptions): number;
+    getExtmark(id: number): RsvimExtmark | null;
+    delExtmark(id: number): boolean;
+    clearExtmarks(ns?: number): void;
+}
+export interface RsvimKeymapOptions {
+    noremap?: boolean;
+    buffer?: boolean | number;
+    desc?: string;
+}
+export declare class RsvimKeymap {
+    set(mode: string | string[], lhs: string, rhs: string | (() => void), options?: RsvimKeymapOptions): void;
+    del(mode: string | string[], lhs: string, options?: {
+        buffer?: boolean | number;
+    }): boolean;
+}
+export interface RsvimJobOptions {
+    cwd?: string;
+    onStdout?: (line: string) => void;
+    onStderr?: (line: string) => void;
+    onExit?: (code: number | null) => void;
+}
+export type RsvimJobSignal = "SIGTERM" | "SIGKILL" | "SIGINT" | "SIGHUP";
+export declare class RsvimJob {
+    spawn(cmd: string[], options?: RsvimJobOptions): number;
+    send(id: number, data: string): boolean;
+    closeStdin(id: number): boolean;
+    kill(id: number, signal?: RsvimJobSignal): boolean;
+}
+export interface RsvimPluginInfo {
+    name: string;
+    path: string;
+    entry: string | null;
+    loaded: boolean;
+}
+export declare class RsvimPlugin {
+    load(name: string): any;
+    list(): RsvimPluginInfo[];
+}
+export interface RsvimFsStat {
+    isFile: boolean;
+    isDirectory: boolean;
+    isSymlink: boolean;
+    size: number;
+    mtime: number | null;
+    readonly: boolean;
+}
+export interface RsvimFsDirEntry {
+    name: string;
+    isFile: boolean;
+    isDirectory: boolean;
+    isSymlink: boolean;
+}
+export interface RsvimFsFindUpOptions {
+    path?: string;
+    type?: "file" | "directory";
+}
+export interface RsvimFsWalkOptions {
+    glob?: string;
+    hidden?: boolean;
+    maxDepth?: number;
+}
+export declare class RsvimFs {
+    readFile(path: string): Promise<string>;
+    writeFile(path: string, data: string | Uint8Array, options?: {
+        append?: boolean;
+    }): Promise<void>;
+    stat(path: string): Promise<RsvimFsStat>;
+    exists(path: string): Promise<boolean>;
+    readDir(path: string): Promise<RsvimFsDirEntry[]>;
+    mkdir(path: string, options?: {
+        recursive?: boolean;
+    }): Promise<void>;
+    remove(path: string, options?: {
+        recursive?: boolean;
+    }): Promise<void>;
+    findUp(names: string | string[], options?: RsvimFsFindUpOptions): Promise<string | null>;
+    walk(path: string, options?: RsvimFsWalkOptions): Promise<string[]>;
+    join(...paths: string[]): string;
+    normalize(path: string): string;
+}
+export type RsvimPickerItem = string | {
+    label: string;
+    description?: string;
+};
+export interface RsvimPickerOptions {
+    source: "files" | "buffers" | Iterable<RsvimPickerItem> | AsyncIterable<RsvimPickerItem>;
+    onAccept: (item: RsvimPickerItem, index: number) => void;
+    onCancel?: () => void;
+    title?: string;
+}
+export declare class RsvimPicker {
+    open(options: RsvimPickerOptions): number;
 }
@@ -1,10 +1,186 @@
 var Rsvim = (function () {
     function Rsvim() {
         this.opt = new RsvimOpt();
+        this.progress = new RsvimProgress();
+        this.input = new RsvimInput();
+        this.hl = new RsvimHl();
+        this.ui = new RsvimUi();
+        this.win = new RsvimWin();
+        this.register = new RsvimRegister();
+        this.mark = new RsvimMark();
+        this.sign = new RsvimSign();
+        this.template = new RsvimTemplate();
+        this.filetype = new RsvimFiletype();
+        this.statusline = new RsvimStatusline();
+        this.git = new RsvimGit();
+        this.spell = new RsvimSpell();
+        this.buf = new RsvimBuf();
+        this.keymap = new RsvimKeymap();
+        this.job = new RsvimJob();
+        this.plugin = new RsvimPlugin();
+        this.fs = new RsvimFs();
+        this.picker = new RsvimPicker();
     }
+    Rsvim.prototype.on = function (event, callback, pattern) {
+        if (typeof callback !== "function") {
+            throw new Error("\"Rsvim.on\" callback must be function type, but found ".concat(callback, " (").concat(typeof callback, ")"));
+        }
+        if (pattern === undefined) {
+            pattern = "*";
+        }
+        if (typeof pattern !== "string") {
+            throw new Error("\"Rsvim.on\" pattern must be string type, but found ".concat(pattern, " (").concat(typeof pattern, ")"));
+        }
+        return __InternalRsvimGlobalObject.autocmd_on(event, pattern, callback);
+    };
+    Rsvim.prototype.off = function (id) {
+        if (typeof id !== "number") {
+            throw new Error("\"Rsvim.off\" id must be number type, but found ".concat(id, " (").concat(typeof id, ")"));
+        }
+        return __InternalRsvimGlobalObject.autocmd_off(id);
+    };
+    Rsvim.prototype.cmd = function (command) {
+        if (typeof command !== "string") {
+            throw new Error("\"Rsvim.cmd\" command must be string type, but found ".concat(command, " (").concat(typeof command, ")"));
+        }
+        __InternalRsvimGlobalObject.cmd(command);
+    };
+    Rsvim.prototype.notify = function (message, level) {
+        if (typeof message !== "string") {
+            throw new Error("\"Rsvim.notify\" message must be string type, but found ".concat(message, " (").concat(typeof message, ")"));
+        }
+        if (level === undefined) {
+            level = "info";
+        }
+        if (level !== "info" && level !== "warn" && level !== "error") {
+            throw new Error("\"Rsvim.notify\" level must be \"info\", \"warn\" or \"error\", but found ".concat(level, " (").concat(typeof level, ")"));
+        }
+        __InternalRsvimGlobalObject.notify(message, level);
+    };
+    Rsvim.prototype.print = function () {
+        var values = [];
+        for (var _i = 0; _i < arguments.length; _i++) {
+            values[_i] = arguments[_i];
+        }
+        var text = values
+            .map(function (value) {
+            return typeof value === "string" ? value : inspectValue(value, 0, 0, []);
+        })
+            .join(" ");
+        __InternalRsvimGlobalObject.notify(text, "info");
+        return values[0];
+    };
+    Rsvim.prototype.grep = function (pattern, options) {
+        var _a, _b, _c, _d, _e, _f;
+        if (typeof pattern !== "string") {
+            throw new Error("\"Rsvim.grep\" pattern must be string type, but found ".concat(pattern, " (").concat(typeof pattern, ")"));
+        }
+        var paths = (_a = options === null || options === void 0 ? void 0 : options.paths) !== null && _a !== void 0 ? _a : [];
+        if (!Array.isArray(paths) ||
+            paths.some(function (path) { return typeof path !== "string"; })) {
+            throw new Error("\"Rsvim.grep\" options.paths must be string array type, but found ".concat(paths, " (").concat(typeof paths, ")"));
+        }
+        return __InternalRsvimGlobalObject.grep(pattern, (_b = options === null || options === void 0 ? void 0 : options.literal) !== null && _b !== void 0 ? _b : false, (_c = options === null || options === void 0 ? void 0 : options.ignoreCase) !== null && _c !== void 0 ? _c : false, (_d = options === null || options === void 0 ? void 0 : options.smartCase) !== null && _d !== void 0 ? _d : false, (_e = options === null || options === void 0 ? void 0 : options.hidden) !== null && _e !== void 0 ? _e : false, (_f = options === null || options === void 0 ? void 0 : options.noIgnore) !== null && _f !== void 0 ? _f : false, paths);
+    };
     return Rsvim;
 }());
 export { Rsvim };
+var INSPECT_WIDTH = 80;
+function inspectContainer(value) {
+    if (value === null || typeof value !== "object") {
+        return null;
+    }
+    if (value instanceof Error || value instanceof Date) {
+        return null;
+    }
+    if (Array.isArray(value)) {
+        return ["[", "]", value.map(function (child) { return [null, child]; })];
+    }
+    if (value instanceof Set) {
+        return ["Set {", "}", Array.from(value).map(function (child) { return [null, child]; })];
+    }
+    if (value instanceof Map) {
+        return [
+            "Map {",
+            "}",
+            Array.from(value.entries()).map(function (_a) {
+                var key = _a[0], child = _a[1];
+                return [
+                    inspectPrimitive(key),
+                    child,
+                ];
+            }),
+        ];
+    }
+    return [
+        "{",
+        "}",
+        Object.keys(value).map(function (key) { return [
+            /^[A-Za-z_$][\w$]*$/.test(key) ? key : JSON.stringify(key),
+            value[key],
+        ]; }),
+    ];
+}
+function inspectPrimitive(value) {
+    switch (typeof value) {
+        case "string":
+            return JSON.stringify(value);
+        case "bigint":
+            return "".concat(value, "n");
+        case "symbol":
+            return value.toString();
+        case "function":
+            return value.name ? "[Function: ".concat(value.name, "]") : "[Function]";
+    }
+    if (value instanceof Error) {
+        return "".concat(value.name, ": ").concat(value.message);
+    }
+    if (value instanceof Date) {
+        return value.toISOString();
+    }
+    return String(value);
+}
+function inspectCompact(value, seen) {
+    var container = inspectContainer(value);
+    if (container === null) {
+        return inspectPrimitive(value);
+    }
+    if (seen.includes(value)) {
+        return "[Circular]";
+    }
+    var open = container[0], close = container[1], entries = container[2];
+    if (entries.length === 0) {
+        return "".concat(open).concat(close);
+    }
+    var inner = seen.concat([value]);
+    var items = entries
+        .map(function (_a) {
+        var key = _a[0], child = _a[1];
+        return (key === null ? "" : "".concat(key, ": ")) + inspectCompact(child, inner);
+    })
+        .join(", ");
+    return open.endsWith("{") ? "".concat(open, " ").concat(items, " ").concat(close) : "".concat(open).concat(items).concat(close);
+}
+function inspectValue(value, indent, column, seen) {
+    var compact = inspectCompact(value, seen);
+    var container = inspectContainer(value);
+    if (column + compact.length <= INSPECT_WIDTH ||
+        container === null ||
+        container[2].length === 0 ||
+        seen.includes(value)) {
+        return compact;
+    }
+    var open = container[0], close = container[1], entries = container[2];
+    var pad = " ".repeat(indent + 2);
+    var inner = seen.concat([value]);
+    var lines = entries.map(function (_a) {
+        var key = _a[0], child = _a[1];
+        var prefix = key === null ? "" : "".concat(key, ": ");
+        var text = inspectValue(child, indent + 2, indent + 2 + prefix.length, inner);
+        return "".concat(pad).concat(prefix).concat(text, ",\n");
+    });
+    return "".concat(open, "\n").concat(lines.join("")).concat(" ".repeat(indent)).concat(close);
+}
 var RsvimOpt = (function () {
     function RsvimOpt() {
     }
@@ -34,9 +210,1389 @@ var RsvimOpt = (function () {
         enumerable: false,
         configurable: true
     });
+    Object.defineProperty(RsvimOpt.prototype, "scrollOff", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_scroll_off();
+        },
+        set: function (value) {
+            if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+                throw new Error("\"Rsvim.opt.scrollOff\" value must be non-negative integer type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_scroll_off(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "sideScroll", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_side_scroll();
+        },
+        set: function (value) {
+            if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+                throw new Error("\"Rsvim.opt.sideScroll\" value must be non-negative integer type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_side_scroll(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "sideScrollOff", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_side_scroll_off();
+        },
+        set: function (value) {
+            if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+                throw new Error("\"Rsvim.opt.sideScrollOff\" value must be non-negative integer type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_side_scroll_off(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "clipboard", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_clipboard();
+        },
+        set: function (value) {
+            if (!["auto", "native", "osc52", "none"].includes(value)) {
+                throw new Error("\"Rsvim.opt.clipboard\" value must be one of \"auto\", \"native\", \"osc52\", \"none\", but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_clipboard(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "paste", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_paste();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.paste\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_paste(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "langMap", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_lang_map();
+        },
+        set: function (value) {
+            if (typeof value !== "string") {
+                throw new Error("\"Rsvim.opt.langMap\" value must be string type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_lang_map(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "cursorWord", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_cursor_word();
+        },
+        set: function (value) {
+            if (typeof value !== "string") {
+                throw new Error("\"Rsvim.opt.cursorWord\" value must be string type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_cursor_word(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "mapLeader", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_map_leader();
+        },
+        set: function (value) {
+            if (typeof value !== "string") {
+                throw new Error("\"Rsvim.opt.mapLeader\" value must be string type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_map_leader(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "timeoutLen", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_timeout_len();
+        },
+        set: function (value) {
+            if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+                throw new Error("\"Rsvim.opt.timeoutLen\" value must be non-negative integer type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_timeout_len(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "tTimeout", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_t_timeout();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.tTimeout\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_t_timeout(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "tTimeoutLen", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_t_timeout_len();
+        },
+        set: function (value) {
+            if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+                throw new Error("\"Rsvim.opt.tTimeoutLen\" value must be non-negative integer type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_t_timeout_len(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "minimap", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_minimap();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.minimap\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_minimap(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "hlSearch", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_hl_search();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.hlSearch\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_hl_search(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "incSearch", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_inc_search();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.incSearch\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_inc_search(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "autoPairs", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_auto_pairs();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.autoPairs\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_auto_pairs(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "jumpCenter", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_jump_center();
+        },
+        set: function (value) {
+            if (typeof value !== "string") {
+                throw new Error("\"Rsvim.opt.jumpCenter\" value must be string type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_jump_center(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "quickfixPreview", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_quickfix_preview();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.quickfixPreview\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_quickfix_preview(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "title", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_title();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.title\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_title(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "titleString", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_title_string();
+        },
+        set: function (value) {
+            if (typeof value !== "string") {
+                throw new Error("\"Rsvim.opt.titleString\" value must be string type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_title_string(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "termGuiColors", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_term_gui_colors();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.termGuiColors\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_term_gui_colors(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "termSync", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_term_sync();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.termSync\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_term_sync(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "termStrikethrough", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_term_strikethrough();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.termStrikethrough\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_term_strikethrough(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "termUndercurl", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_term_undercurl();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.termUndercurl\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_term_undercurl(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "termPaste", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_term_paste();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.termPaste\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_term_paste(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "backup", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_backup();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.backup\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_backup(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "writeBackup", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_write_backup();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.writeBackup\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_write_backup(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "fsync", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_fsync();
+        },
+        set: function (value) {
+            if (typeof value !== "string") {
+                throw new Error("\"Rsvim.opt.fsync\" value must be string type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_fsync(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "mkdir", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_mkdir();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.mkdir\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_mkdir(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "lastPosExclude", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_last_pos_exclude();
+        },
+        set: function (value) {
+            if (typeof value !== "string") {
+                throw new Error("\"Rsvim.opt.lastPosExclude\" value must be string type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_last_pos_exclude(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "undoLevels", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_undo_levels();
+        },
+        set: function (value) {
+            if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+                throw new Error("\"Rsvim.opt.undoLevels\" value must be non-negative integer type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_undo_levels(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "undoMemory", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_undo_memory();
+        },
+        set: function (value) {
+            if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+                throw new Error("\"Rsvim.opt.undoMemory\" value must be non-negative integer type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_undo_memory(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "undoFile", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_undo_file();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.undoFile\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_undo_file(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "statusLine", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_status_line();
+        },
+        set: function (value) {
+            if (typeof value !== "string" && typeof value !== "function") {
+                throw new Error("\"Rsvim.opt.statusLine\" value must be string or function type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_status_line(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "winFixWidth", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_win_fix_width();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.winFixWidth\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_win_fix_width(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "winFixHeight", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_win_fix_height();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.winFixHeight\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_win_fix_height(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "number", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_number();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.number\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_number(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "relativeNumber", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_relative_number();
+        },
+        set: function (value) {
+            if (typeof value !== "boolean") {
+                throw new Error("\"Rsvim.opt.relativeNumber\" value must be boolean type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_relative_number(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "numberWidth", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_number_width();
+        },
+        set: function (value) {
+            if (typeof value !== "number" || !Number.isInteger(value) || value < 1) {
+                throw new Error("\"Rsvim.opt.numberWidth\" value must be positive integer type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_number_width(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "signColumn", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_sign_column();
+        },
+        set: function (value) {
+            if (typeof value !== "string") {
+                throw new Error("\"Rsvim.opt.signColumn\" value must be string type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_sign_column(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "signWidth", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_sign_width();
+        },
+        set: function (value) {
+            if (typeof value !== "number" || !Number.isInteger(value) || value < 1) {
+                throw new Error("\"Rsvim.opt.signWidth\" value must be positive integer type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_sign_width(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "wildMode", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_wild_mode();
+        },
+        set: function (value) {
+            if (typeof value !== "string") {
+                throw new Error("\"Rsvim.opt.wildMode\" value must be string type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_wild_mode(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
+    Object.defineProperty(RsvimOpt.prototype, "wildOptions", {
+        get: function () {
+            return __InternalRsvimGlobalObject.opt_get_wild_options();
+        },
+        set: function (value) {
+            if (typeof value !== "string") {
+                throw new Error("\"Rsvim.opt.wildOptions\" value must be string type, but found ".concat(value, " (").concat(typeof value, ")"));
+            }
+            __InternalRsvimGlobalObject.opt_set_wild_options(value);
+        },
+        enumerable: false,
+        configurable: true
+    });
     return RsvimOpt;
 }());
 export { RsvimOpt };
+var RsvimProgress = (function () {
+    function RsvimProgress() {
+    }
+    RsvimProgress.prototype.begin = function (title, options) {
+        var _a;
+        if (typeof title !== "string") {
+            throw new Error("\"Rsvim.progress.begin\" title must be string type, but found ".concat(title, " (").concat(typeof title, ")"));
+        }
+        var cancellable = (_a = options === null || options === void 0 ? void 0 : options.cancellable) !== null && _a !== void 0 ? _a : false;
+        return __InternalRsvimGlobalObject.progress_begin(title, cancellable);
+    };
+    RsvimProgress.prototype.update = function (id, message, percentage) {
+        __InternalRsvimGlobalObject.progress_update(id, message, percentage);
+    };
+    RsvimProgress.prototype.end = function (id) {
+        __InternalRsvimGlobalObject.progress_end(id);
+    };
+    RsvimProgress.prototype.isCancelled = function (id) {
+        return __InternalRsvimGlobalObject.progress_is_cancelled(id);
+    };
+    return RsvimProgress;
+}());
+export { RsvimProgress };
+var RsvimInput = (function () {
+    function RsvimInput() {
+    }
+    RsvimInput.prototype.getchar = function (options) {
+        var timeout = options === null || options === void 0 ? void 0 : options.timeout;
+        if (timeout !== undefined && typeof timeout !== "number") {
+            throw new Error("\"Rsvim.input.getchar\" timeout must be number type, but found ".concat(timeout, " (").concat(typeof timeout, ")"));
+        }
+        return __InternalRsvimGlobalObject.input_getchar(timeout);
+    };
+    RsvimInput.prototype.typeahead = function () {
+        return __InternalRsvimGlobalObject.input_typeahead();
+    };
+    RsvimInput.prototype.batchBegin = function () {
+        __InternalRsvimGlobalObject.input_batch_begin();
+    };
+    RsvimInput.prototype.batchEnd = function () {
+        return __InternalRsvimGlobalObject.input_batch_end();
+    };
+    RsvimInput.prototype.isBatch = function () {
+        return __InternalRsvimGlobalObject.input_is_batch();
+    };
+    return RsvimInput;
+}());
+export { RsvimInput };
+var RsvimHl = (function () {
+    function RsvimHl() {
+    }
+    RsvimHl.prototype.createNamespace = function (name, priority) {
+        if (typeof name !== "string") {
+            throw new Error("\"Rsvim.hl.createNamespace\" name must be string type, but found ".concat(name, " (").concat(typeof name, ")"));
+        }
+        return __InternalRsvimGlobalObject.hl_create_namespace(name, priority !== null && priority !== void 0 ? priority : 100);
+    };
+    RsvimHl.prototype.addHighlight = function (ns, line, start, end, style) {
+        var attrs = ["bold", "italic", "underline", "reverse", "strikethrough"]
+            .filter(function (name) { return style[name] === true; })
+            .join(",");
+        return __InternalRsvimGlobalObject.hl_add_highlight(ns, line, start, end, style.fg, style.bg, attrs);
+    };
+    RsvimHl.prototype.clearNamespace = function (ns, start, end) {
+        __InternalRsvimGlobalObject.hl_clear_namespace(ns, start !== null && start !== void 0 ? start : 0, end !== null && end !== void 0 ? end : -1);
+    };
+    RsvimHl.prototype.set = function (group, style) {
+        if (typeof group !== "string") {
+            throw new Error("\"Rsvim.hl.set\" group must be string type, but found ".concat(group, " (").concat(typeof group, ")"));
+        }
+        var attrs = ["bold", "italic", "underline", "reverse", "strikethrough"]
+            .filter(function (name) { return style[name] === true; })
+            .join(",");
+        __InternalRsvimGlobalObject.hl_set_group(group, style.fg, style.bg, attrs, style.link);
+    };
+    return RsvimHl;
+}());
+export { RsvimHl };
+var RsvimUi = (function () {
+    function RsvimUi() {
+    }
+    RsvimUi.prototype.redraw = function (options) {
+        var _a;
+        var force = (_a = options === null || options === void 0 ? void 0 : options.force) !== null && _a !== void 0 ? _a : false;
+        __InternalRsvimGlobalObject.ui_redraw(force);
+    };
+    RsvimUi.prototype.input = function (options) {
+        var _a, _b;
+        var title = (_a = options === null || options === void 0 ? void 0 : options.prompt) !== null && _a !== void 0 ? _a : "";
+        var text = (_b = options === null || options === void 0 ? void 0 : options.default) !== null && _b !== void 0 ? _b : "";
+        if (__InternalRsvimGlobalObject.input_is_batch()) {
+            return Promise.resolve(text);
+        }
+        return new Promise(function (resolve) {
+            var win = -1;
+            var done = function (value) {
+                __InternalRsvimGlobalObject.win_close_float(win);
+                resolve(value);
+            };
+            var loop = function (value) {
+                win = openUiPrompt(win, title, [value]);
+                __InternalRsvimGlobalObject
+                    .input_getchar(undefined)
+                    .then(function (key) {
+                    var _a;
+                    if (key === null || key === "<CR>") {
+                        done(value);
+                    }
+                    else if (key === "<Esc>" || key === "<C-c>") {
+                        done(null);
+                    }
+                    else if (key === "<BS>" || key === "<C-h>") {
+                        loop(Array.from(value).slice(0, -1).join(""));
+                    }
+                    else if (key === "<C-u>") {
+                        loop("");
+                    }
+                    else {
+                        loop(value + ((_a = uiKeyChar(key)) !== null && _a !== void 0 ? _a : ""));
+                    }
+                });
+            };
+            loop(text);
+        });
+    };
+    RsvimUi.prototype.select = function (items, options) {
+        var _a, _b;
+        if (!Array.isArray(items)) {
+            throw new Error("\"Rsvim.ui.select\" items must be array type, but found ".concat(items, " (").concat(typeof items, ")"));
+        }
+        if (items.length === 0 || __InternalRsvimGlobalObject.input_is_batch()) {
+            return Promise.resolve(null);
+        }
+        var title = (_a = options === null || options === void 0 ? void 0 : options.prompt) !== null && _a !== void 0 ? _a : "";
+        var format = (_b = options === null || options === void 0 ? void 0 : options.format) !== null && _b !== void 0 ? _b : (function (item) { return String(item); });
+        var lines = items.map(function (item, i) { return "".concat(i + 1, ". ").concat(format(item)); });
+        var height = Math.min(lines.length, UI_SELECT_MAX_HEIGHT);
+        return new Promise(function (resolve) {
+            var win = -1;
+            var done = function (index) {
+                __InternalRsvimGlobalObject.win_close_float(win);
+                resolve(index === null ? null : items[index]);
+            };
+            var loop = function (index) {
+                var top = Math.max(Math.min(index - Math.floor(height / 2), lines.length - height), 0);
+                var visible = lines
+                    .slice(top, top + height)
+                    .map(function (line, i) { return (top + i === index ? "> " : "  ") + line; });
+                win = openUiPrompt(win, title, visible);
+                __InternalRsvimGlobalObject
+                    .input_getchar(undefined)
+                    .then(function (key) {
+                    if (key === "<CR>") {
+                        done(index);
+                    }
+                    else if (key === null ||
+                        key === "<Esc>" ||
+                        key === "q" ||
+                        key === "<C-c>") {
+                        done(null);
+                    }
+                    else if (["j", "<Down>", "<C-n>", "<Tab>"].includes(key)) {
+                        loop((index + 1) % lines.length);
+                    }
+                    else if (["k", "<Up>", "<C-p>", "<S-Tab>"].includes(key)) {
+                        loop((index + lines.length - 1) % lines.length);
+                    }
+                    else if (/^[1-9]$/.test(key) && Number(key) <= lines.length) {
+                        done(Number(key) - 1);
+                    }
+                    else {
+                        loop(index);
+                    }
+                });
+            };
+            loop(0);
+        });
+    };
+    return RsvimUi;
+}());
+export { RsvimUi };
+var UI_SELECT_MAX_HEIGHT = 10;
+function uiKeyChar(key) {
+    if (key === "<Space>") {
+        return " ";
+    }
+    if (key === "<lt>") {
+        return "<";
+    }
+    if (key.startsWith("<")) {
+        return null;
+    }
+    return key;
+}
+function openUiPrompt(win, title, lines) {
+    if (win >= 0) {
+        __InternalRsvimGlobalObject.win_close_float(win);
+    }
+    var width = lines.reduce(function (width, line) { return Math.max(width, line.length + 1); }, Math.max(title.length + 2, 20));
+    return __InternalRsvimGlobalObject.win_open_float(-1, lines.join("\n"), "cursor", 1, 0, width, lines.length, 250, "rounded", false, title, false);
+}
+var RsvimWin = (function () {
+    function RsvimWin() {
+    }
+    RsvimWin.prototype.openFloat = function (options) {
+        var _a, _b, _c, _d, _e, _f, _g, _h, _j, _k;
+        return __InternalRsvimGlobalObject.win_open_float((_a = options.buffer) !== null && _a !== void 0 ? _a : -1, (_b = options.text) !== null && _b !== void 0 ? _b : "", (_c = options.anchor) !== null && _c !== void 0 ? _c : "editor", (_d = options.row) !== null && _d !== void 0 ? _d : 0, (_e = options.col) !== null && _e !== void 0 ? _e : 0, options.width, options.height, (_f = options.zIndex) !== null && _f !== void 0 ? _f : 50, (_g = options.border) !== null && _g !== void 0 ? _g : "single", (_h = options.shadow) !== null && _h !== void 0 ? _h : false, (_j = options.title) !== null && _j !== void 0 ? _j : "", (_k = options.enter) !== null && _k !== void 0 ? _k : false);
+    };
+    RsvimWin.prototype.closeFloat = function (id) {
+        return __InternalRsvimGlobalObject.win_close_float(id);
+    };
+    RsvimWin.prototype.current = function () {
+        return __InternalRsvimGlobalObject.win_current();
+    };
+    RsvimWin.prototype.focus = function (id) {
+        if (typeof id !== "number") {
+            throw new Error("\"Rsvim.win.focus\" id must be number type, but found ".concat(id, " (").concat(typeof id, ")"));
+        }
+        return __InternalRsvimGlobalObject.win_focus(id);
+    };
+    RsvimWin.prototype.setCursor = function (line, col) {
+        if (typeof line !== "number" || typeof col !== "number") {
+            throw new Error("\"Rsvim.win.setCursor\" line and col must be number type, but found ".concat(line, " (").concat(typeof line, "), ").concat(col, " (").concat(typeof col, ")"));
+        }
+        return __InternalRsvimGlobalObject.win_set_cursor(line, col);
+    };
+    return RsvimWin;
+}());
+export { RsvimWin };
+var RsvimRegister = (function () {
+    function RsvimRegister() {
+    }
+    RsvimRegister.prototype.get = function (name) {
+        return __InternalRsvimGlobalObject.register_get(name);
+    };
+    RsvimRegister.prototype.set = function (name, content, type) {
+        if (typeof content !== "string") {
+            throw new Error("\"Rsvim.register.set\" content must be string type, but found ".concat(content, " (").concat(typeof content, ")"));
+        }
+        return __InternalRsvimGlobalObject.register_set(name, content, type !== null && type !== void 0 ? type : "");
+    };
+    return RsvimRegister;
+}());
+export { RsvimRegister };
+var RsvimMark = (function () {
+    function RsvimMark() {
+    }
+    RsvimMark.prototype.get = function (name) {
+        return __InternalRsvimGlobalObject.mark_get(name);
+    };
+    RsvimMark.prototype.set = function (name, line, col) {
+        if (typeof line !== "number" || typeof col !== "number") {
+            throw new Error("\"Rsvim.mark.set\" line and col must be number type, but found ".concat(line, " (").concat(typeof line, "), ").concat(col, " (").concat(typeof col, ")"));
+        }
+        return __InternalRsvimGlobalObject.mark_set(name, line, col);
+    };
+    return RsvimMark;
+}());
+export { RsvimMark };
+var RsvimSign = (function () {
+    function RsvimSign() {
+    }
+    RsvimSign.prototype.define = function (name, definition) {
+        var _a;
+        if (typeof name !== "string" || typeof (definition === null || definition === void 0 ? void 0 : definition.text) !== "string") {
+            throw new Error("\"Rsvim.sign.define\" name and text must be string type, but found ".concat(name, " (").concat(typeof name, "), ").concat(definition === null || definition === void 0 ? void 0 : definition.text, " (").concat(typeof (definition === null || definition === void 0 ? void 0 : definition.text), ")"));
+        }
+        var style = (_a = definition.style) !== null && _a !== void 0 ? _a : {};
+        var attrs = ["bold", "italic", "underline", "reverse", "strikethrough"]
+            .filter(function (name) { return style[name] === true; })
+            .join(",");
+        __InternalRsvimGlobalObject.sign_define(name, definition.text, style.fg, style.bg, attrs);
+    };
+    RsvimSign.prototype.undefine = function (name) {
+        return __InternalRsvimGlobalObject.sign_undefine(name);
+    };
+    RsvimSign.prototype.place = function (name, line, options) {
+        var _a, _b, _c;
+        if (typeof line !== "number") {
+            throw new Error("\"Rsvim.sign.place\" line must be number type, but found ".concat(line, " (").concat(typeof line, ")"));
+        }
+        return __InternalRsvimGlobalObject.sign_place(name, line, (_a = options === null || options === void 0 ? void 0 : options.bufferId) !== null && _a !== void 0 ? _a : 0, (_b = options === null || options === void 0 ? void 0 : options.group) !== null && _b !== void 0 ? _b : "", (_c = options === null || options === void 0 ? void 0 : options.priority) !== null && _c !== void 0 ? _c : 10);
+    };
+    RsvimSign.prototype.unplace = function (id, bufferId) {
+        return __InternalRsvimGlobalObject.sign_unplace(id, bufferId !== null && bufferId !== void 0 ? bufferId : 0);
+    };
+    RsvimSign.prototype.clear = function (group, bufferId) {
+        __InternalRsvimGlobalObject.sign_clear(group, bufferId !== null && bufferId !== void 0 ? bufferId : 0);
+    };
+    RsvimSign.prototype.getPlaced = function (bufferId) {
+        return __InternalRsvimGlobalObject.sign_get_placed(bufferId !== null && bufferId !== void 0 ? bufferId : 0);
+    };
+    return RsvimSign;
+}());
+export { RsvimSign };
+var RsvimTemplate = (function () {
+    function RsvimTemplate() {
+    }
+    RsvimTemplate.prototype.add = function (pattern, file) {
+        if (typeof pattern !== "string" || typeof file !== "string") {
+            throw new Error("\"Rsvim.template.add\" pattern and file must be string type, but found ".concat(pattern, " (").concat(typeof pattern, "), ").concat(file, " (").concat(typeof file, ")"));
+        }
+        __InternalRsvimGlobalObject.template_add(pattern, file);
+    };
+    RsvimTemplate.prototype.setVariable = function (name, value) {
+        if (typeof name !== "string" || typeof value !== "string") {
+            throw new Error("\"Rsvim.template.setVariable\" name and value must be string type, but found ".concat(name, " (").concat(typeof name, "), ").concat(value, " (").concat(typeof value, ")"));
+        }
+        __InternalRsvimGlobalObject.template_set_variable(name, value);
+    };
+    return RsvimTemplate;
+}());
+export { RsvimTemplate };
+var RsvimFiletype = (function () {
+    function RsvimFiletype() {
+    }
+    RsvimFiletype.prototype.add = function (pattern, filetype) {
+        if (typeof pattern !== "string" || typeof filetype !== "string") {
+            throw new Error("\"Rsvim.filetype.add\" pattern and filetype must be string type, but found ".concat(pattern, " (").concat(typeof pattern, "), ").concat(filetype, " (").concat(typeof filetype, ")"));
+        }
+        __InternalRsvimGlobalObject.filetype_add(pattern, filetype);
+    };
+    RsvimFiletype.prototype.set = function (filetype, options) {
+        var _a, _b, _c, _d, _e;
+        if (typeof filetype !== "string" || typeof options !== "object" || options === null) {
+            throw new Error("\"Rsvim.filetype.set\" filetype must be string type and options must be object type, but found ".concat(filetype, " (").concat(typeof filetype, "), ").concat(options, " (").concat(typeof options, ")"));
+        }
+        var commentstring = (_a = options.commentstring) !== null && _a !== void 0 ? _a : null;
+        var tabstop = (_b = options.tabstop) !== null && _b !== void 0 ? _b : null;
+        var shiftwidth = (_c = options.shiftwidth) !== null && _c !== void 0 ? _c : null;
+        var expandtab = (_d = options.expandtab) !== null && _d !== void 0 ? _d : null;
+        var suffixesadd = (_e = options.suffixesadd) !== null && _e !== void 0 ? _e : null;
+        if ((commentstring !== null && typeof commentstring !== "string") ||
+            (tabstop !== null && typeof tabstop !== "number") ||
+            (shiftwidth !== null && typeof shiftwidth !== "number") ||
+            (expandtab !== null && typeof expandtab !== "boolean") ||
+            (suffixesadd !== null && typeof suffixesadd !== "string")) {
+            throw new Error("\"Rsvim.filetype.set\" options have invalid types, found ".concat(JSON.stringify(options)));
+        }
+        __InternalRsvimGlobalObject.filetype_set(filetype, commentstring, tabstop, shiftwidth, expandtab, suffixesadd);
+    };
+    return RsvimFiletype;
+}());
+export { RsvimFiletype };
+var RsvimStatusline = (function () {
+    function RsvimStatusline() {
+        this.components = {};
+    }
+    RsvimStatusline.prototype.component = function (name, render, events) {
+        if (typeof name !== "string" || typeof render !== "function") {
+            throw new Error("\"Rsvim.statusline.component\" name must be string type and render must be function type, but found ".concat(name, " (").concat(typeof name, "), ").concat(render, " (").concat(typeof render, ")"));
+        }
+        if (events === undefined) {
+            events = [];
+        }
+        if (!Array.isArray(events)) {
+            throw new Error("\"Rsvim.statusline.component\" events must be array type, but found ".concat(events, " (").concat(typeof events, ")"));
+        }
+        this.remove(name);
+        var update = function () {
+            __InternalRsvimGlobalObject.statusline_set_component(name, String(render()));
+        };
+        var ids = events.map(function (event) {
+            return __InternalRsvimGlobalObject.autocmd_on(event, "*", update);
+        });
+        this.components[name] = { update: update, ids: ids };
+        update();
+    };
+    RsvimStatusline.prototype.update = function (name) {
+        var component = this.components[name];
+        if (component === undefined) {
+            return false;
+        }
+        component.update();
+        return true;
+    };
+    RsvimStatusline.prototype.remove = function (name) {
+        var component = this.components[name];
+        if (component === undefined) {
+            return false;
+        }
+        component.ids.forEach(function (id) {
+            __InternalRsvimGlobalObject.autocmd_off(id);
+        });
+        delete this.components[name];
+        return __InternalRsvimGlobalObject.statusline_remove_component(name);
+    };
+    return RsvimStatusline;
+}());
+export { RsvimStatusline };
+var RsvimGit = (function () {
+    function RsvimGit() {
+    }
+    RsvimGit.prototype.status = function () {
+        return __InternalRsvimGlobalObject.git_status();
+    };
+    RsvimGit.prototype.refresh = function () {
+        __InternalRsvimGlobalObject.git_refresh();
+    };
+    return RsvimGit;
+}());
+export { RsvimGit };
+var RsvimSpell = (function () {
+    function RsvimSpell() {
+    }
+    RsvimSpell.prototype.add = function (word) {
+        if (typeof word !== "string") {
+            throw new Error("\"Rsvim.spell.add\" word must be string type, but found ".concat(word, " (").concat(typeof word, ")"));
+        }
+        return __InternalRsvimGlobalObject.spell_add(word);
+    };
+    RsvimSpell.prototype.check = function (word) {
+        if (typeof word !== "string") {
+            throw new Error("\"Rsvim.spell.check\" word must be string type, but found ".concat(word, " (").concat(typeof word, ")"));
+        }
+        return __InternalRsvimGlobalObject.spell_check(word);
+    };
+    RsvimSpell.prototype.words = function () {
+        return __InternalRsvimGlobalObject.spell_words();
+    };
+    RsvimSpell.prototype.reload = function () {
+        __InternalRsvimGlobalObject.spell_reload();
+    };
+    return RsvimSpell;
+}());
+export { RsvimSpell };
+var RsvimBuf = (function () {
+    function RsvimBuf() {
+    }
+    RsvimBuf.prototype.getLines = function (start, end) {
+        if (typeof start !== "number" || typeof end !== "number") {
+            throw new Error("\"Rsvim.buf.getLines\" start and end must be number type, but found ".concat(start, " (").concat(typeof start, "), ").concat(end, " (").concat(typeof end, ")"));
+        }
+        return __InternalRsvimGlobalObject.buf_get_lines(start, end);
+    };
+    RsvimBuf.prototype.setLines = function (start, end, lines) {
+        if (typeof start !== "number" || typeof end !== "number") {
+            throw new Error("\"Rsvim.buf.setLines\" start and end must be number type, but found ".concat(start, " (").concat(typeof start, "), ").concat(end, " (").concat(typeof end, ")"));
+        }
+        if (!Array.isArray(lines)) {
+            throw new Error("\"Rsvim.buf.setLines\" lines must be array type, but found ".concat(lines, " (").concat(typeof lines, ")"));
+        }
+        return __InternalRsvimGlobalObject.buf_set_lines(start, end, lines);
+    };
+    RsvimBuf.prototype.list = function (all) {
+        if (all !== undefined && typeof all !== "boolean") {
+            throw new Error("\"Rsvim.buf.list\" all must be boolean type, but found ".concat(all, " (").concat(typeof all, ")"));
+        }
+        return __InternalRsvimGlobalObject.buf_list(all === true);
+    };
+    RsvimBuf.prototype.isModified = function (id) {
+        if (typeof id !== "number") {
+            throw new Error("\"Rsvim.buf.isModified\" id must be number type, but found ".concat(id, " (").concat(typeof id, ")"));
+        }
+        return __InternalRsvimGlobalObject.buf_is_modified(id);
+    };
+    RsvimBuf.prototype.setExtmark = function (ns, line, col, options) {
+        var _a, _b, _c, _d, _e, _f, _g;
+        if (typeof line !== "number" || typeof col !== "number") {
+            throw new Error("\"Rsvim.buf.setExtmark\" line and col must be number type, but found ".concat(line, " (").concat(typeof line, "), ").concat(col, " (").concat(typeof col, ")"));
+        }
+        var style = options === null || options === void 0 ? void 0 : options.style;
+        var virtTextStyle = (_a = options === null || options === void 0 ? void 0 : options.virtTextStyle) !== null && _a !== void 0 ? _a : {};
+        var toAttrs = function (style) {
+            return ["bold", "italic", "underline", "reverse", "strikethrough"]
+                .filter(function (name) { return style[name] === true; })
+                .join(",");
+        };
+        return __InternalRsvimGlobalObject.buf_set_extmark(ns, line, col, (_b = options === null || options === void 0 ? void 0 : options.id) !== null && _b !== void 0 ? _b : 0, (_c = options === null || options === void 0 ? void 0 : options.endLine) !== null && _c !== void 0 ? _c : -1, (_d = options === null || options === void 0 ? void 0 : options.endCol) !== null && _d !== void 0 ? _d : 0, style !== undefined, style === null || style === void 0 ? void 0 : style.fg, style === null || style === void 0 ? void 0 : style.bg, style !== undefined ? toAttrs(style) : "", options === null || options === void 0 ? void 0 : options.virtText, virtTextStyle.fg, virtTextStyle.bg, toAttrs(virtTextStyle), (_e = options === null || options === void 0 ? void 0 : options.rightGravity) !== null && _e !== void 0 ? _e : true, (_f = options === null || options === void 0 ? void 0 : options.endRightGravity) !== null && _f !== void 0 ? _f : false, (_g = options === null || options === void 0 ? void 0 : options.priority) !== null && _g !== void 0 ? _g : -1);
+    };
+    RsvimBuf.prototype.getExtmark = function (id) {
+        return __InternalRsvimGlobalObject.buf_get_extmark(id);
+    };
+    RsvimBuf.prototype.delExtmark = function (id) {
+        return __InternalRsvimGlobalObject.buf_del_extmark(id);
+    };
+    RsvimBuf.prototype.clearExtmarks = function (ns) {
+        __InternalRsvimGlobalObject.buf_clear_extmarks(ns);
+    };
+    return RsvimBuf;
+}());
+export { RsvimBuf };
+var RsvimKeymap = (function () {
+    function RsvimKeymap() {
+    }
+    RsvimKeymap.prototype.set = function (mode, lhs, rhs, options) {
+        var _a, _b;
+        mode = checkKeymapMode("Rsvim.keymap.set", mode);
+        if (typeof lhs !== "string") {
+            throw new Error("\"Rsvim.keymap.set\" lhs must be string type, but found ".concat(lhs, " (").concat(typeof lhs, ")"));
+        }
+        if (typeof rhs !== "string" && typeof rhs !== "function") {
+            throw new Error("\"Rsvim.keymap.set\" rhs must be string or function type, but found ".concat(rhs, " (").concat(typeof rhs, ")"));
+        }
+        options = options !== null && options !== void 0 ? options : {};
+        var noremap = (_a = options.noremap) !== null && _a !== void 0 ? _a : true;
+        var desc = (_b = options.desc) !== null && _b !== void 0 ? _b : "";
+        if (typeof noremap !== "boolean" || typeof desc !== "string") {
+            throw new Error("\"Rsvim.keymap.set\" options.noremap must be boolean type and options.desc must be string type, but found ".concat(noremap, " (").concat(typeof noremap, "), ").concat(desc, " (").concat(typeof desc, ")"));
+        }
+        var buffer = checkKeymapBuffer("Rsvim.keymap.set", options.buffer);
+        __InternalRsvimGlobalObject.keymap_set(mode, lhs, rhs, noremap, buffer, desc);
+    };
+    RsvimKeymap.prototype.del = function (mode, lhs, options) {
+        mode = checkKeymapMode("Rsvim.keymap.del", mode);
+        if (typeof lhs !== "string") {
+            throw new Error("\"Rsvim.keymap.del\" lhs must be string type, but found ".concat(lhs, " (").concat(typeof lhs, ")"));
+        }
+        var buffer = checkKeymapBuffer("Rsvim.keymap.del", options === null || options === void 0 ? void 0 : options.buffer);
+        return __InternalRsvimGlobalObject.keymap_del(mode, lhs, buffer);
+    };
+    return RsvimKeymap;
+}());
+export { RsvimKeymap };
+function checkKeymapMode(api, mode) {
+    if (Array.isArray(mode)) {
+        mode = mode.join("");
+    }
+    if (typeof mode !== "string") {
+        throw new Error("\"".concat(api, "\" mode must be string or array type, but found ").concat(mode, " (").concat(typeof mode, ")"));
+    }
+    return mode;
+}
+function checkKeymapBuffer(api, buffer) {
+    if (buffer === undefined || buffer === false) {
+        return -1;
+    }
+    if (buffer === true) {
+        return 0;
+    }
+    if (typeof buffer !== "number" || !Number.isInteger(buffer) || buffer < 0) {
+        throw new Error("\"".concat(api, "\" options.buffer must be boolean or non-negative integer type, but found ").concat(buffer, " (").concat(typeof buffer, ")"));
+    }
+    return buffer;
+}
+var RsvimJob = (function () {
+    function RsvimJob() {
+    }
+    RsvimJob.prototype.spawn = function (cmd, options) {
+        if (!Array.isArray(cmd)) {
+            throw new Error("\"Rsvim.job.spawn\" cmd must be array type, but found ".concat(cmd, " (").concat(typeof cmd, ")"));
+        }
+        var cwd = options === null || options === void 0 ? void 0 : options.cwd;
+        if (cwd !== undefined && typeof cwd !== "string") {
+            throw new Error("\"Rsvim.job.spawn\" options.cwd must be string type, but found ".concat(cwd, " (").concat(typeof cwd, ")"));
+        }
+        var onStdout = checkJobCallback("onStdout", options === null || options === void 0 ? void 0 : options.onStdout);
+        var onStderr = checkJobCallback("onStderr", options === null || options === void 0 ? void 0 : options.onStderr);
+        var onExit = checkJobCallback("onExit", options === null || options === void 0 ? void 0 : options.onExit);
+        return __InternalRsvimGlobalObject.job_spawn(cmd, cwd, onStdout, onStderr, onExit);
+    };
+    RsvimJob.prototype.send = function (id, data) {
+        if (typeof id !== "number" || typeof data !== "string") {
+            throw new Error("\"Rsvim.job.send\" id must be number type and data must be string type, but found ".concat(id, " (").concat(typeof id, "), ").concat(data, " (").concat(typeof data, ")"));
+        }
+        return __InternalRsvimGlobalObject.job_send(id, data);
+    };
+    RsvimJob.prototype.closeStdin = function (id) {
+        if (typeof id !== "number") {
+            throw new Error("\"Rsvim.job.closeStdin\" id must be number type, but found ".concat(id, " (").concat(typeof id, ")"));
+        }
+        return __InternalRsvimGlobalObject.job_close_stdin(id);
+    };
+    RsvimJob.prototype.kill = function (id, signal) {
+        if (typeof id !== "number") {
+            throw new Error("\"Rsvim.job.kill\" id must be number type, but found ".concat(id, " (").concat(typeof id, ")"));
+        }
+        if (signal === undefined) {
+            signal = "SIGTERM";
+        }
+        return __InternalRsvimGlobalObject.job_kill(id, signal);
+    };
+    return RsvimJob;
+}());
+export { RsvimJob };
+var RsvimPlugin = (function () {
+    function RsvimPlugin() {
+    }
+    RsvimPlugin.prototype.load = function (name) {
+        if (typeof name !== "string") {
+            throw new Error("\"Rsvim.plugin.load\" name must be string type, but found ".concat(name, " (").concat(typeof name, ")"));
+        }
+        return __InternalRsvimGlobalObject.plugin_load(name);
+    };
+    RsvimPlugin.prototype.list = function () {
+        return __InternalRsvimGlobalObject.plugin_list();
+    };
+    return RsvimPlugin;
+}());
+export { RsvimPlugin };
+var RsvimFs = (function () {
+    function RsvimFs() {
+    }
+    RsvimFs.prototype.readFile = function (path) {
+        checkFsPath("readFile", path);
+        return __InternalRsvimGlobalObject.fs_read_file(path);
+    };
+    RsvimFs.prototype.writeFile = function (path, data, options) {
+        var _a;
+        checkFsPath("writeFile", path);
+        if (typeof data !== "string" && !(data instanceof Uint8Array)) {
+            throw new Error("\"Rsvim.fs.writeFile\" data must be string or Uint8Array type, but found ".concat(data, " (").concat(typeof data, ")"));
+        }
+        var append = (_a = options === null || options === void 0 ? void 0 : options.append) !== null && _a !== void 0 ? _a : false;
+        return __InternalRsvimGlobalObject.fs_write_file(path, data, append);
+    };
+    RsvimFs.prototype.stat = function (path) {
+        checkFsPath("stat", path);
+        return __InternalRsvimGlobalObject.fs_stat(path);
+    };
+    RsvimFs.prototype.exists = function (path) {
+        checkFsPath("exists", path);
+        return __InternalRsvimGlobalObject.fs_exists(path);
+    };
+    RsvimFs.prototype.readDir = function (path) {
+        checkFsPath("readDir", path);
+        return __InternalRsvimGlobalObject.fs_read_dir(path);
+    };
+    RsvimFs.prototype.mkdir = function (path, options) {
+        var _a;
+        checkFsPath("mkdir", path);
+        var recursive = (_a = options === null || options === void 0 ? void 0 : options.recursive) !== null && _a !== void 0 ? _a : false;
+        return __InternalRsvimGlobalObject.fs_mkdir(path, recursive);
+    };
+    RsvimFs.prototype.remove = function (path, options) {
+        var _a;
+        checkFsPath("remove", path);
+        var recursive = (_a = options === null || options === void 0 ? void 0 : options.recursive) !== null && _a !== void 0 ? _a : false;
+        return __InternalRsvimGlobalObject.fs_remove(path, recursive);
+    };
+    RsvimFs.prototype.findUp = function (names, options) {
+        var _a, _b;
+        if (typeof names === "string") {
+            names = [names];
+        }
+        if (!Array.isArray(names)) {
+            throw new Error("\"Rsvim.fs.findUp\" names must be string or array type, but found ".concat(names, " (").concat(typeof names, ")"));
+        }
+        var path = (_a = options === null || options === void 0 ? void 0 : options.path) !== null && _a !== void 0 ? _a : ".";
+        checkFsPath("findUp", path);
+        var type = (_b = options === null || options === void 0 ? void 0 : options.type) !== null && _b !== void 0 ? _b : "";
+        return __InternalRsvimGlobalObject.fs_find_up(names, path, type);
+    };
+    RsvimFs.prototype.walk = function (path, options) {
+        var _a;
+        checkFsPath("walk", path);
+        var glob = options === null || options === void 0 ? void 0 : options.glob;
+        if (glob !== undefined && typeof glob !== "string") {
+            throw new Error("\"Rsvim.fs.walk\" options.glob must be string type, but found ".concat(glob, " (").concat(typeof glob, ")"));
+        }
+        var hidden = (_a = options === null || options === void 0 ? void 0 : options.hidden) !== null && _a !== void 0 ? _a : false;
+        var maxDepth = options === null || options === void 0 ? void 0 : options.maxDepth;
+        if (maxDepth !== undefined && typeof maxDepth !== "number") {
+            throw new Error("\"Rsvim.fs.walk\" options.maxDepth must be number type, but found ".concat(maxDepth, " (").concat(typeof maxDepth, ")"));
+        }
+        return __InternalRsvimGlobalObject.fs_walk(path, glob, hidden, maxDepth);
+    };
+    RsvimFs.prototype.join = function () {
+        var paths = [];
+        for (var _i = 0; _i < arguments.length; _i++) {
+            paths[_i] = arguments[_i];
+        }
+        for (var _a = 0, paths_1 = paths; _a < paths_1.length; _a++) {
+            var path = paths_1[_a];
+            checkFsPath("join", path);
+        }
+        return __InternalRsvimGlobalObject.fs_join(paths);
+    };
+    RsvimFs.prototype.normalize = function (path) {
+        checkFsPath("normalize", path);
+        return __InternalRsvimGlobalObject.fs_normalize(path);
+    };
+    return RsvimFs;
+}());
+export { RsvimFs };
+var RsvimPicker = (function () {
+    function RsvimPicker() {
+    }
+    RsvimPicker.prototype.open = function (options) {
+        var _a;
+        if (typeof options !== "object" || options === null) {
+            throw new Error("\"Rsvim.picker.open\" options must be object type, but found ".concat(options, " (").concat(typeof options, ")"));
+        }
+        var source = options.source;
+        var builtin = source === "files" || source === "buffers";
+        if (!builtin &&
+            !(typeof source === "object" &&
+                source !== null &&
+                (Symbol.iterator in source || Symbol.asyncIterator in source))) {
+            throw new Error("\"Rsvim.picker.open\" options.source must be \"files\", \"buffers\" or iterable type, but found ".concat(source, " (").concat(typeof source, ")"));
+        }
+        var onAccept = options.onAccept;
+        if (typeof onAccept !== "function") {
+            throw new Error("\"Rsvim.picker.open\" options.onAccept must be function type, but found ".concat(onAccept, " (").concat(typeof onAccept, ")"));
+        }
+        var onCancel = options.onCancel;
+        if (onCancel !== undefined && typeof onCancel !== "function") {
+            throw new Error("\"Rsvim.picker.open\" options.onCancel must be function type, but found ".concat(onCancel, " (").concat(typeof onCancel, ")"));
+        }
+        var title = (_a = options.title) !== null && _a !== void 0 ? _a : (source === "files" ? "Files" : source === "buffers" ? "Buffers" : "Picker");
+        if (typeof title !== "string") {
+            throw new Error("\"Rsvim.picker.open\" options.title must be string type, but found ".concat(title, " (").concat(typeof title, ")"));
+        }
+        if (__InternalRsvimGlobalObject.input_is_batch()) {
+            onCancel === null || onCancel === void 0 ? void 0 : onCancel();
+            return -1;
+        }
+        var items = [];
+        var id = __InternalRsvimGlobalObject.picker_open(title, builtin ? source : null, function (index, label, description) {
+            if (index === null) {
+                onCancel === null || onCancel === void 0 ? void 0 : onCancel();
+            }
+            else if (builtin) {
+                onAccept({ label: label, description: description }, index);
+            }
+            else {
+                onAccept(items[index], index);
+            }
+        });
+        if (!builtin) {
+            populatePicker(id, source, items);
+        }
+        return id;
+    };
+    return RsvimPicker;
+}());
+export { RsvimPicker };
+function checkJobCallback(name, callback) {
+    if (callback !== undefined && typeof callback !== "function") {
+        throw new Error("\"Rsvim.job.spawn\" options.".concat(name, " must be function type, but found ").concat(callback, " (").concat(typeof callback, ")"));
+    }
+    return callback;
+}
+function checkFsPath(api, path) {
+    if (typeof path !== "string") {
+        throw new Error("\"Rsvim.fs.".concat(api, "\" path must be string type, but found ").concat(path, " (").concat(typeof path, ")"));
+    }
+}
+var PICKER_BATCH_SIZE = 1024;
+var PICKER_BATCH_INTERVAL = 50;
+function pickerItemText(item) {
+    if (typeof item === "string") {
+        return [item, ""];
+    }
+    if (typeof item === "object" &&
+        item !== null &&
+        typeof item.label === "string") {
+        var _a = item, label = _a.label, description = _a.description;
+        return [label, description === undefined ? "" : String(description)];
+    }
+    throw new Error("\"Rsvim.picker.open\" item must be string or {label, description} type, but found ".concat(item, " (").concat(typeof item, ")"));
+}
+function populatePicker(id, source, items) {
+    var labels = [];
+    var descriptions = [];
+    var timer = undefined;
+    var closed = false;
+    var flush = function () {
+        if (timer !== undefined) {
+            clearTimeout(timer);
+            timer = undefined;
+        }
+        if (!closed && labels.length > 0) {
+            closed = !__InternalRsvimGlobalObject.picker_push(id, labels.splice(0), descriptions.splice(0));
+        }
+        return !closed;
+    };
+    var add = function (item) {
+        if (closed) {
+            return false;
+        }
+        var _a = pickerItemText(item), label = _a[0], description = _a[1];
+        items.push(item);
+        labels.push(label);
+        descriptions.push(description);
+        if (labels.length >= PICKER_BATCH_SIZE) {
+            return flush();
+        }
+        if (timer === undefined) {
+            timer = setTimeout(flush, PICKER_BATCH_INTERVAL);
+        }
+        return true;
+    };
+    var finish = function () {
+        if (flush()) {
+            __InternalRsvimGlobalObject.picker_done(id);
+        }
+    };
+    if (Symbol.asyncIterator in source) {
+        var iterator_1 = source[Symbol.asyncIterator]();
+        var step_1 = function () {
+            iterator_1.next().then(function (next) {
+                var _a;
+                if (next.done) {
+                    finish();
+                }
+                else if (add(next.value)) {
+                    step_1();
+                }
+                else {
+                    (_a = iterator_1.return) === null || _a === void 0 ? void 0 : _a.call(iterator_1);
+                }
+            }, function (error) {
+                finish();
+                throw error;
+            });
+        };
+        step_1();
+    }
+    else {
+        var iterator_2 = source[Symbol.iterator]();
+        var step_2 = function () {
+            var _a, _b;
+            for (var i = 0; i < PICKER_BATCH_SIZE; i++) {
+                var next = iterator_2.next();
+                if (next.done) {
+                    finish();
+                    return;
+                }
+                if (!add(next.value)) {
+                    (_a = iterator_2.return) === null || _a === void 0 ? void 0 : _a.call(iterator_2);
+                    return;
+                }
+            }
+            if (flush()) {
+                setTimeout(step_2, 0);
+            }
+            else {
+                (_b = iterator_2.return) === null || _b === void 0 ? void 0 : _b.call(iterator_2);
+            }
+        };
+        step_2();
+    }
+}
 (function (globalThis) {
     globalThis.Rsvim = new Rsvim();
 })(globalThis);
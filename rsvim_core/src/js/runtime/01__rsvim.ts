@@ -21,6 +21,24 @@
  * The `Rsvim` global object, it contains multiple sub fields:
  *
  * - `Rsvim.opt`: Global editor options.
+ * - `Rsvim.progress`: Progress reporting for long-running tasks.
+ * - `Rsvim.input`: Wait for key presses outside the key mappings.
+ * - `Rsvim.hl`: Highlight namespaces of current buffer.
+ * - `Rsvim.ui`: Screen redraw controls.
+ * - `Rsvim.win`: Floating windows and current window.
+ * - `Rsvim.register`: Registers, i.e. the yanked/deleted text.
+ * - `Rsvim.mark`: Marks, i.e. the remembered positions.
+ * - `Rsvim.sign`: Signs in the sign column.
+ * - `Rsvim.template`: Templates for new files.
+ * - `Rsvim.filetype`: Filetype detection and option bundles.
+ * - `Rsvim.statusline`: Statusline components.
+ * - `Rsvim.git`: Git status of current working directory.
+ * - `Rsvim.spell`: User spell dictionary.
+ * - `Rsvim.buf`: Lines of current buffer.
+ * - `Rsvim.keymap`: Key mappings.
+ * - `Rsvim.job`: Async jobs, i.e. the external processes.
+ * - `Rsvim.plugin`: Plugin packages under the plugins directory.
+ * - `Rsvim.fs`: Async file operations and path utilities.
  *
  *
  * @example
@@ -34,8 +52,468 @@
  */
 export class Rsvim {
   readonly opt: RsvimOpt = new RsvimOpt();
+  readonly progress: RsvimProgress = new RsvimProgress();
+  readonly input: RsvimInput = new RsvimInput();
+  readonly hl: RsvimHl = new RsvimHl();
+  readonly ui: RsvimUi = new RsvimUi();
+  readonly win: RsvimWin = new RsvimWin();
+  readonly register: RsvimRegister = new RsvimRegister();
+  readonly mark: RsvimMark = new RsvimMark();
+  readonly sign: RsvimSign = new RsvimSign();
+  readonly template: RsvimTemplate = new RsvimTemplate();
+  readonly filetype: RsvimFiletype = new RsvimFiletype();
+  readonly statusline: RsvimStatusline = new RsvimStatusline();
+  readonly git: RsvimGit = new RsvimGit();
+  readonly spell: RsvimSpell = new RsvimSpell();
+  readonly buf: RsvimBuf = new RsvimBuf();
+  readonly keymap: RsvimKeymap = new RsvimKeymap();
+  readonly job: RsvimJob = new RsvimJob();
+  readonly plugin: RsvimPlugin = new RsvimPlugin();
+  readonly fs: RsvimFs = new RsvimFs();
+  readonly picker: RsvimPicker = new RsvimPicker();
+
+  /**
+   * Subscribe the editor event, i.e. Vim's autocmd.
+   *
+   * The `pattern` is a glob (the wildcards are `*` and `?`), it's matched against:
+   *
+   * - `BufReadPost`, `BufWritePre`, `BufWritePost`, `TextChanged`, `CursorMoved`: The absolute
+   *   file path of the buffer, it's empty for the buffer without file.
+   * - `BufFilePre`, `BufFilePost`: The absolute file path before and after renaming, i.e.
+   *   `:Rename`.
+   * - `FileType`: The filetype, i.e. `rust`.
+   * - `WinResized`, `WinScrolled`: The window ID. The `WinScrolled` is emitted once the window's
+   *   viewport is scrolled (also in insert mode), the delta is in `args.scrollDelta`.
+   * - `ModeChanged`: The old and new mode short names, i.e. `n:i` when entering insert mode from
+   *   normal mode.
+   * - `FocusGained`: Always empty.
+   * - `StatusLineClick`: The clicked mouse button, i.e. `left`, `right` or `middle`. The clicked
+   *   window is in `args.windowId`.
+   * - `Paste`: The mode short name when pasting, i.e. `i` in insert mode. The pasted text is
+   *   inserted literally (the key mappings are not applied) as one undo state, the current window
+   *   and buffer are in `args.windowId` and `args.bufferId`.
+   * - `BufModifiedSet`: The absolute file path of the buffer whose modified flag flips, i.e. it's
+   *   edited after loaded/saved, or it's saved (or undone) back to unmodified. The buffer is in
+   *   `args.bufferId`, see {@link RsvimBuf.isModified}.
+   *
+   * The `CursorMoved` and `TextChanged` events are not emitted in insert mode, the text changes in
+   * insert mode are emitted when leaving insert mode.
+   *
+   * @see [Vim: autocmd.txt](https://vimhelp.org/autocmd.txt.html)
+   *
+   * @example
+   * ```javascript
+   * const id = Rsvim.on("BufReadPost", (args) => {
+   *   console.log(`Read ${args.match} into buffer ${args.bufferId}`);
+   * }, "*.rs");
+   * Rsvim.on("ModeChanged", () => {}, "*:i");
+   * Rsvim.off(id);
+   * ```
+   *
+   * @param {RsvimEvent} event - The event name.
+   * @param {(args: RsvimEventArgs) => void} callback - The callback, it's called after the event
+   * is emitted.
+   * @param {string} pattern - (Optional) The glob pattern, by default it's `*`.
+   * @returns {number} The subscription ID.
+   * @throws {@link !Error} if callback is not a function, or pattern is not a string value.
+   * @throws {@link !TypeError} if the event name is invalid.
+   */
+  on(
+    event: RsvimEvent,
+    callback: (args: RsvimEventArgs) => void,
+    pattern?: string,
+  ): number {
+    if (typeof callback !== "function") {
+      throw new Error(
+        `"Rsvim.on" callback must be function type, but found ${callback} (${typeof callback})`,
+      );
+    }
+    if (pattern === undefined) {
+      pattern = "*";
+    }
+    if (typeof pattern !== "string") {
+      throw new Error(
+        `"Rsvim.on" pattern must be string type, but found ${pattern} (${typeof pattern})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.autocmd_on(event, pattern, callback);
+  }
+
+  /**
+   * Unsubscribe the editor event.
+   *
+   * @param {number} id - The subscription ID returned by {@link Rsvim.on}.
+   * @returns {boolean} `false` if the subscription doesn't exist.
+   * @throws {@link !Error} if id is not a number value.
+   */
+  off(id: number): boolean {
+    if (typeof id !== "number") {
+      throw new Error(
+        `"Rsvim.off" id must be number type, but found ${id} (${typeof id})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.autocmd_off(id);
+  }
+
+  /**
+   * Execute the ex command line, i.e. Vim's `:execute`. The command line is not saved to history.
+   *
+   * @example
+   * ```javascript
+   * Rsvim.cmd("vsplit README.md");
+   * Rsvim.cmd("mksession! ~/work.js");
+   * ```
+   *
+   * @param {string} command - The command line, without the `:` prefix.
+   * @throws {@link !Error} if command is not a string value, or the command fails.
+   */
+  cmd(command: string): void {
+    if (typeof command !== "string") {
+      throw new Error(
+        `"Rsvim.cmd" command must be string type, but found ${command} (${typeof command})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.cmd(command);
+  }
+
+  /**
+   * Show the message to the user, it's also saved in the message history (see `:messages`).
+   *
+   * The message is shown at the bottom of the terminal until the next typed key. A multi-line (or
+   * too long) message is shown in more rows with a press-enter prompt, thus it doesn't overwrite
+   * the statusline.
+   *
+   * @example
+   * ```javascript
+   * Rsvim.notify("Formatted 3 files");
+   * Rsvim.notify("Failed to format:\nsyntax error at line 1", "error");
+   * ```
+   *
+   * @param {string} message - The message, it can have multiple lines.
+   * @param {RsvimMessageLevel} level - (Optional) The message level, by default it's `"info"`.
+   * @throws {@link !Error} if message is not a string value, or level is invalid.
+   */
+  notify(message: string, level?: RsvimMessageLevel): void {
+    if (typeof message !== "string") {
+      throw new Error(
+        `"Rsvim.notify" message must be string type, but found ${message} (${typeof message})`,
+      );
+    }
+    if (level === undefined) {
+      level = "info";
+    }
+    if (level !== "info" && level !== "warn" && level !== "error") {
+      throw new Error(
+        `"Rsvim.notify" level must be "info", "warn" or "error", but found ${level} (${typeof level})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.notify(message, level);
+  }
+
+  /**
+   * Pretty-print the values to the user, it's also saved in the message history (see
+   * `:messages`), i.e. Neovim's `vim.print()`.
+   *
+   * The values are separated by a space. The strings are printed as they are, the other values are
+   * inspected: the arrays, objects, maps and sets are printed in one line, or in multiple lines
+   * (with 2 spaces indent) if the line is longer than 80 columns. The circular references are
+   * printed as `[Circular]`.
+   *
+   * @example
+   * ```javascript
+   * Rsvim.print("options:", { wrap: Rsvim.opt.wrap, lineBreak: Rsvim.opt.lineBreak });
+   * // The first value is returned, thus it can wrap an expression.
+   * const id = Rsvim.print(Rsvim.on("BufReadPost", () => {}));
+   * ```
+   *
+   * @param {...any} values - The values.
+   * @returns {any} The first value.
+   */
+  print(...values: any[]): any {
+    const text = values
+      .map((value) =>
+        typeof value === "string" ? value : inspectValue(value, 0, 0, []),
+      )
+      .join(" ");
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.notify(text, "info");
+    return values[0];
+  }
+
+  /**
+   * Search the files in background, i.e. Vim's `:grep`, but it uses the built-in search engine
+   * instead of an external program. The files ignored by `.gitignore` (and `.ignore`) and the
+   * hidden files are skipped by default, the binary files are always skipped.
+   *
+   * The quickfix list (see `:clist`) is replaced by the matches, they're appended while searching
+   * and the progress is shown by the `%{grep}` item in the statusline.
+   * Only one search runs at a time, a new search (or `:grep`) cancels the running one, and its
+   * promise is rejected.
+   *
+   * @example
+   * ```javascript
+   * const matches = await Rsvim.grep("TODO", { literal: true, paths: ["src"] });
+   * for (const m of matches) {
+   *   console.log(`${m.path}:${m.line + 1}:${m.col + 1}: ${m.text}`);
+   * }
+   * ```
+   *
+   * @param {string} pattern - The regex pattern, or the literal text if `options.literal`.
+   * @param {RsvimGrepOptions} options - (Optional) The search options.
+   * @returns {Promise<RsvimQuickfixEntry[]>} The matches, ordered by the file paths. The promise
+   * is rejected with an {@link !Error} if the pattern is invalid or the search is cancelled.
+   * @throws {@link !Error} if pattern is not a string value, or options are invalid.
+   */
+  grep(
+    pattern: string,
+    options?: RsvimGrepOptions,
+  ): Promise<RsvimQuickfixEntry[]> {
+    if (typeof pattern !== "string") {
+      throw new Error(
+        `"Rsvim.grep" pattern must be string type, but found ${pattern} (${typeof pattern})`,
+      );
+    }
+    const paths = options?.paths ?? [];
+    if (
+      !Array.isArray(paths) ||
+      paths.some((path) => typeof path !== "string")
+    ) {
+      throw new Error(
+        `"Rsvim.grep" options.paths must be string array type, but found ${paths} (${typeof paths})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.grep(
+      pattern,
+      options?.literal ?? false,
+      options?.ignoreCase ?? false,
+      options?.smartCase ?? false,
+      options?.hidden ?? false,
+      options?.noIgnore ?? false,
+      paths,
+    );
+  }
+}
+
+// The max width of an array (or object) printed in one line by `Rsvim.print`.
+const INSPECT_WIDTH = 80;
+
+// Get the open/close brackets and the `[key, child]` entries of the container value, or `null`
+// if it's not a container. The key is `null` for the array items.
+function inspectContainer(
+  value: any,
+): [string, string, [string | null, any][]] | null {
+  if (value === null || typeof value !== "object") {
+    return null;
+  }
+  if (value instanceof Error || value instanceof Date) {
+    return null;
+  }
+  if (Array.isArray(value)) {
+    return ["[", "]", value.map((child) => [null, child])];
+  }
+  if (value instanceof Set) {
+    return ["Set {", "}", Array.from(value).map((child) => [null, child])];
+  }
+  if (value instanceof Map) {
+    return [
+      "Map {",
+      "}",
+      Array.from(value.entries()).map(([key, child]) => [
+        inspectPrimitive(key),
+        child,
+      ]),
+    ];
+  }
+  return [
+    "{",
+    "}",
+    Object.keys(value).map((key) => [
+      /^[A-Za-z_$][\w$]*$/.test(key) ? key : JSON.stringify(key),
+      value[key],
+    ]),
+  ];
+}
+
+// Format the non-container value, the strings are quoted.
+function inspectPrimitive(value: any): string {
+  switch (typeof value) {
+    case "string":
+      return JSON.stringify(value);
+    case "bigint":
+      return `${value}n`;
+    case "symbol":
+      return value.toString();
+    case "function":
+      return value.name ? `[Function: ${value.name}]` : "[Function]";
+  }
+  if (value instanceof Error) {
+    return `${value.name}: ${value.message}`;
+  }
+  if (value instanceof Date) {
+    return value.toISOString();
+  }
+  return String(value);
+}
+
+// Format the value in one line, the `seen` are the containers of the value, i.e. to detect the
+// circular references.
+function inspectCompact(value: any, seen: any[]): string {
+  const container = inspectContainer(value);
+  if (container === null) {
+    return inspectPrimitive(value);
+  }
+  if (seen.includes(value)) {
+    return "[Circular]";
+  }
+  const [open, close, entries] = container;
+  if (entries.length === 0) {
+    return `${open}${close}`;
+  }
+  const inner = seen.concat([value]);
+  const items = entries
+    .map(
+      ([key, child]) =>
+        (key === null ? "" : `${key}: `) + inspectCompact(child, inner),
+    )
+    .join(", ");
+  return open.endsWith("{")
+    ? `${open} ${items} ${close}`
+    : `${open}${items}${close}`;
+}
+
+// Format the value at the `column` of the line indented by `indent`, it's printed in multiple lines
+// if it's too long.
+function inspectValue(
+  value: any,
+  indent: number,
+  column: number,
+  seen: any[],
+): string {
+  const compact = inspectCompact(value, seen);
+  const container = inspectContainer(value);
+  if (
+    column + compact.length <= INSPECT_WIDTH ||
+    container === null ||
+    container[2].length === 0 ||
+    seen.includes(value)
+  ) {
+    return compact;
+  }
+  const [open, close, entries] = container;
+  const pad = " ".repeat(indent + 2);
+  const inner = seen.concat([value]);
+  const lines = entries.map(([key, child]) => {
+    const prefix = key === null ? "" : `${key}: `;
+    const text = inspectValue(
+      child,
+      indent + 2,
+      indent + 2 + prefix.length,
+      inner,
+    );
+    return `${pad}${prefix}${text},\n`;
+  });
+  return `${open}\n${lines.join("")}${" ".repeat(indent)}${close}`;
+}
+
+/**
+ * The message levels for {@link Rsvim.notify}.
+ */
+export type RsvimMessageLevel = "info" | "warn" | "error";
+
+/**
+ * The options of {@link Rsvim.grep}, they're the same with the `:grep` flags.
+ */
+export interface RsvimGrepOptions {
+  /** Match the pattern literally instead of as a regex, i.e. `-F`. Default is `false`. */
+  literal?: boolean;
+  /** Match case insensitively, i.e. `-i`. Default is `false`. */
+  ignoreCase?: boolean;
+  /** Match case insensitively if the pattern is all lowercase, i.e. `-S`. Default is `false`. */
+  smartCase?: boolean;
+  /** Search the hidden files and directories, i.e. `-.`. Default is `false`. */
+  hidden?: boolean;
+  /** Don't respect the ignore files, i.e. `.gitignore`, `-u`. Default is `false`. */
+  noIgnore?: boolean;
+  /** The files or directories to search, default is the editor's working directory. */
+  paths?: string[];
+}
+
+/**
+ * The quickfix entry, i.e. a match of {@link Rsvim.grep}.
+ */
+export interface RsvimQuickfixEntry {
+  /** The file path, it's relative if the searched path is relative. */
+  path: string;
+  /** The line index, starts from 0. */
+  line: number;
+  /** The char index of the first match in the line, starts from 0. */
+  col: number;
+  /** The line text, without the line break. */
+  text: string;
+}
+
+/**
+ * The editor event names for {@link Rsvim.on}.
+ */
+export type RsvimEvent =
+  | "BufReadPost"
+  | "BufWritePre"
+  | "BufWritePost"
+  | "BufFilePre"
+  | "BufFilePost"
+  | "FileType"
+  | "WinResized"
+  | "WinScrolled"
+  | "ModeChanged"
+  | "FocusGained"
+  | "CursorMoved"
+  | "TextChanged"
+  | "StatusLineClick"
+  | "Paste"
+  | "BufModifiedSet";
+
+/**
+ * The emitted event passed to the {@link Rsvim.on} callback.
+ */
+export interface RsvimEventArgs {
+  /** The event name. */
+  event: RsvimEvent;
+  /** The text matched with the pattern. */
+  match: string;
+  /** The buffer ID, or `null` if the event is not about a buffer. */
+  bufferId: number | null;
+  /** The window ID, or `null` if the event is not about a window. */
+  windowId: number | null;
+  /**
+   * The viewport anchor delta of `WinScrolled`, the negative is upward (or leftward), or `null`
+   * for other events.
+   */
+  scrollDelta: RsvimScrollDelta | null;
+}
+
+/**
+ * The viewport anchor delta of the `WinScrolled` event, see {@link RsvimEventArgs.scrollDelta}.
+ */
+export interface RsvimScrollDelta {
+  /** The delta of the start line. */
+  lines: number;
+  /** The delta of the start display column, it's only changed when the _wrap_ option is off. */
+  columns: number;
 }
 
+/**
+ * The `Rsvim.opt.statusLine` callback, it's called with the window ID on each redraw, and returns
+ * the statusline format.
+ *
+ * @category Editor APIs
+ */
+export type RsvimStatusLineCallback = (windowId: number) => string;
+
 /**
  * The `Rsvim.opt` object for global editor options.
  *
@@ -147,6 +625,4401 @@ export class RsvimOpt {
     // @ts-ignore Ignore warning
     __InternalRsvimGlobalObject.opt_set_line_break(value);
   }
+
+  /**
+   * Get the _scrolloff_ option.
+   *
+   * Local to window.
+   *
+   * The minimal number of rows to keep above and below the cursor, it's at most half of the
+   * window height. When the {@link wrap} option is `true`, a wrapped line counts all its rows.
+   *
+   * See: {@link https://vimhelp.org/options.txt.html#%27scrolloff%27}.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'scrolloff' option.
+   * const value = Rsvim.opt.scrollOff;
+   * // Set the 'scrolloff' option.
+   * Rsvim.opt.scrollOff = 5;
+   * ```
+   *
+   * @returns {number}
+   * @defaultValue `0`
+   */
+  get scrollOff(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_scroll_off();
+  }
+
+  /**
+   * Set the _scrolloff_ option.
+   *
+   * @param {number} value - The _scrolloff_ option.
+   * @throws {@link !Error} if value is not a non-negative integer value.
+   */
+  set scrollOff(value: number) {
+    if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+      throw new Error(
+        `"Rsvim.opt.scrollOff" value must be non-negative integer type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_scroll_off(value);
+  }
+
+  /**
+   * Get the _sidescroll_ option.
+   *
+   * Local to window.
+   *
+   * The minimal number of columns to scroll horizontally when the cursor goes off the screen,
+   * `0` puts the cursor at the middle of the window. This option is not used when the
+   * {@link wrap} option is `true`.
+   *
+   * See: {@link https://vimhelp.org/options.txt.html#%27sidescroll%27}.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'sidescroll' option.
+   * const value = Rsvim.opt.sideScroll;
+   * // Set the 'sidescroll' option.
+   * Rsvim.opt.sideScroll = 5;
+   * ```
+   *
+   * @returns {number}
+   * @defaultValue `1`
+   */
+  get sideScroll(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_side_scroll();
+  }
+
+  /**
+   * Set the _sidescroll_ option.
+   *
+   * @param {number} value - The _sidescroll_ option.
+   * @throws {@link !Error} if value is not a non-negative integer value.
+   */
+  set sideScroll(value: number) {
+    if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+      throw new Error(
+        `"Rsvim.opt.sideScroll" value must be non-negative integer type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_side_scroll(value);
+  }
+
+  /**
+   * Get the _sidescrolloff_ option.
+   *
+   * Local to window.
+   *
+   * The minimal number of columns to keep on the left and right of the cursor, it's at most half
+   * of the window width. This option is not used when the {@link wrap} option is `true`.
+   *
+   * See: {@link https://vimhelp.org/options.txt.html#%27sidescrolloff%27}.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'sidescrolloff' option.
+   * const value = Rsvim.opt.sideScrollOff;
+   * // Set the 'sidescrolloff' option.
+   * Rsvim.opt.sideScrollOff = 5;
+   * ```
+   *
+   * @returns {number}
+   * @defaultValue `0`
+   */
+  get sideScrollOff(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_side_scroll_off();
+  }
+
+  /**
+   * Set the _sidescrolloff_ option.
+   *
+   * @param {number} value - The _sidescrolloff_ option.
+   * @throws {@link !Error} if value is not a non-negative integer value.
+   */
+  set sideScrollOff(value: number) {
+    if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+      throw new Error(
+        `"Rsvim.opt.sideScrollOff" value must be non-negative integer type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_side_scroll_off(value);
+  }
+
+  /**
+   * Get the _clipboard_ option.
+   *
+   * Global.
+   *
+   * It selects the system clipboard provider used by the `+` and `*` registers:
+   *
+   * - `"auto"`: Detect native clipboard commands (`pbcopy`, `wl-copy`, `xclip`, `xsel`, `clip.exe`)
+   *   first, fallback to the OSC 52 escape sequence if not found (i.e. over SSH sessions).
+   * - `"native"`: Native clipboard commands only.
+   * - `"osc52"`: OSC 52 escape sequence only.
+   * - `"none"`: Disable system clipboard.
+   *
+   * @see [Vim: options.txt - 'clipboard'](https://vimhelp.org/options.txt.html#%27clipboard%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'clipboard' option.
+   * const value = Rsvim.opt.clipboard;
+   * // Set the 'clipboard' option.
+   * Rsvim.opt.clipboard = "osc52";
+   * ```
+   *
+   * @returns {string}
+   * @defaultValue `"auto"`
+   */
+  get clipboard(): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_clipboard();
+  }
+
+  /**
+   * Set the _clipboard_ option.
+   *
+   * @param {string} value - The _clipboard_ option.
+   * @throws {@link !Error} if value is not one of `"auto"`, `"native"`, `"osc52"`, `"none"`.
+   */
+  set clipboard(value: string) {
+    if (!["auto", "native", "osc52", "none"].includes(value)) {
+      throw new Error(
+        `"Rsvim.opt.clipboard" value must be one of "auto", "native", "osc52", "none", but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_clipboard(value);
+  }
+
+  /**
+   * Get the _paste_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the inserted text is treated literally, i.e. the key mappings,
+   * abbreviations, auto-indent and text-width are disabled. It's useful when pasting text on
+   * terminals without bracketed paste, to avoid garbled insertions.
+   *
+   * NOTE: Paste mode is also enabled automatically when handling a bracketed paste, or when the
+   * keys arrive much faster than human typing.
+   *
+   * @see [Vim: options.txt - 'paste'](https://vimhelp.org/options.txt.html#%27paste%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'paste' option.
+   * const value = Rsvim.opt.paste;
+   * // Set the 'paste' option.
+   * Rsvim.opt.paste = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get paste(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_paste();
+  }
+
+  /**
+   * Set the _paste_ option.
+   *
+   * @param {boolean} value - The _paste_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set paste(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.paste" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_paste(value);
+  }
+
+  /**
+   * Get the _langmap_ option.
+   *
+   * Global.
+   *
+   * It translates the characters typed with a non-Latin keyboard layout into Latin characters,
+   * thus the normal-mode commands can be used without switching layouts. The translation only
+   * applies to normal, visual, select and operator-pending modes.
+   *
+   * The value is a comma-separated list of `{from}{to}` pairs (i.e. `"ΑA,ΒB"`), or
+   * `{from-chars};{to-chars}` (i.e. `"ΑΒΓ;ABC"`). The special characters `;`, `,` and `\` can
+   * be escaped with a backslash.
+   *
+   * @see [Vim: options.txt - 'langmap'](https://vimhelp.org/options.txt.html#%27langmap%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'langMap' option.
+   * const value = Rsvim.opt.langMap;
+   * // Set the 'langMap' option.
+   * Rsvim.opt.langMap = "йцукенгшщзх;qwertyuiop[";
+   * ```
+   *
+   * @returns {string}
+   * @defaultValue `""`
+   */
+  get langMap(): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_lang_map();
+  }
+
+  /**
+   * Set the _langmap_ option.
+   *
+   * @param {string} value - The _langmap_ option.
+   * @throws {@link !Error} if value is not a string value.
+   * @throws {@link !TypeError} if value is not a valid 'langmap', i.e. the characters are not matched.
+   */
+  set langMap(value: string) {
+    if (typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.opt.langMap" value must be string type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_lang_map(value);
+  }
+
+  /**
+   * Get the _cursor-word_ option.
+   *
+   * Global.
+   *
+   * It highlights all the visible occurrences of the word under cursor, in the modes specified
+   * by the comma-separated mode short names:
+   *
+   * - `n`: Normal mode.
+   * - `v`: Visual mode.
+   * - `s`: Select mode.
+   * - `o`: Operator-pending mode.
+   * - `i`: Insert mode.
+   * - `c`: Command-line mode.
+   * - `t`: Terminal mode.
+   *
+   * The highlighting is debounced, i.e. it's only calculated after the cursor stays on a position
+   * for a while, and only the lines visible in the window are searched.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'cursorWord' option.
+   * const value = Rsvim.opt.cursorWord;
+   * // Set the 'cursorWord' option.
+   * Rsvim.opt.cursorWord = "n,v";
+   * ```
+   *
+   * @returns {string}
+   * @defaultValue `""`
+   */
+  get cursorWord(): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_cursor_word();
+  }
+
+  /**
+   * Set the _cursor-word_ option.
+   *
+   * @param {string} value - The _cursor-word_ option.
+   * @throws {@link !Error} if value is not a string value.
+   * @throws {@link !TypeError} if value contains invalid mode name.
+   */
+  set cursorWord(value: string) {
+    if (typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.opt.cursorWord" value must be string type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_cursor_word(value);
+  }
+
+  /**
+   * Get the _mapleader_ option.
+   *
+   * Global.
+   *
+   * The keys to replace the `<Leader>` in {@link RsvimKeymap.set}, it only affects the mappings
+   * defined after it.
+   *
+   * @see [Vim: map.txt - mapleader](https://vimhelp.org/map.txt.html#mapleader)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'mapLeader' option.
+   * const value = Rsvim.opt.mapLeader;
+   * // Set the 'mapLeader' option.
+   * Rsvim.opt.mapLeader = " ";
+   * ```
+   *
+   * @returns {string}
+   * @defaultValue `"\\"`
+   */
+  get mapLeader(): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_map_leader();
+  }
+
+  /**
+   * Set the _mapleader_ option.
+   *
+   * @param {string} value - The _mapleader_ option.
+   * @throws {@link !Error} if value is not a string value.
+   */
+  set mapLeader(value: string) {
+    if (typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.opt.mapLeader" value must be string type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_map_leader(value);
+  }
+
+  /**
+   * Get the _timeoutlen_ option.
+   *
+   * Global.
+   *
+   * The time in milliseconds waiting for a mapped key sequence to complete, i.e. when `jk` and
+   * `jkl` are both mapped, the `jk` mapping is applied if no key is typed after `jk` in the time.
+   *
+   * @see [Vim: options.txt - 'timeoutlen'](https://vimhelp.org/options.txt.html#%27timeoutlen%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'timeoutLen' option.
+   * const value = Rsvim.opt.timeoutLen;
+   * // Set the 'timeoutLen' option.
+   * Rsvim.opt.timeoutLen = 500;
+   * ```
+   *
+   * @returns {number}
+   * @defaultValue `1000`
+   */
+  get timeoutLen(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_timeout_len();
+  }
+
+  /**
+   * Set the _timeoutlen_ option.
+   *
+   * @param {number} value - The _timeoutlen_ option.
+   * @throws {@link !Error} if value is not a non-negative integer value.
+   */
+  set timeoutLen(value: number) {
+    if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+      throw new Error(
+        `"Rsvim.opt.timeoutLen" value must be non-negative integer type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_timeout_len(value);
+  }
+
+  /**
+   * Get the _ttimeout_ option.
+   *
+   * Global.
+   *
+   * The terminal sends the `Alt` modified keys as `Esc` followed by the key, when this option is
+   * `true`, a lone `Esc` is held for {@link RsvimOpt.tTimeoutLen} and combined with the next key
+   * as the `Alt` modified key. When it's `false`, the `Esc` is never held.
+   *
+   * @see [Vim: options.txt - 'ttimeout'](https://vimhelp.org/options.txt.html#%27ttimeout%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'tTimeout' option.
+   * const value = Rsvim.opt.tTimeout;
+   * // Set the 'tTimeout' option.
+   * Rsvim.opt.tTimeout = false;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `true`
+   */
+  get tTimeout(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_t_timeout();
+  }
+
+  /**
+   * Set the _ttimeout_ option.
+   *
+   * @param {boolean} value - The _ttimeout_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set tTimeout(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.tTimeout" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_t_timeout(value);
+  }
+
+  /**
+   * Get the _ttimeoutlen_ option.
+   *
+   * Global.
+   *
+   * The time in milliseconds waiting for the key after a lone `Esc`, see
+   * {@link RsvimOpt.tTimeout}. The `Esc` is never held if it's `0`.
+   *
+   * @see [Vim: options.txt - 'ttimeoutlen'](https://vimhelp.org/options.txt.html#%27ttimeoutlen%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'tTimeoutLen' option.
+   * const value = Rsvim.opt.tTimeoutLen;
+   * // Set the 'tTimeoutLen' option.
+   * Rsvim.opt.tTimeoutLen = 10;
+   * ```
+   *
+   * @returns {number}
+   * @defaultValue `50`
+   */
+  get tTimeoutLen(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_t_timeout_len();
+  }
+
+  /**
+   * Set the _ttimeoutlen_ option.
+   *
+   * @param {number} value - The _ttimeoutlen_ option.
+   * @throws {@link !Error} if value is not a non-negative integer value.
+   */
+  set tTimeoutLen(value: number) {
+    if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+      throw new Error(
+        `"Rsvim.opt.tTimeoutLen" value must be non-negative integer type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_t_timeout_len(value);
+  }
+
+  /**
+   * Get the _minimap_ option.
+   *
+   * Local to Window.
+   *
+   * When `true` (on), a minimap is shown at the right side of the window. It renders a
+   * downsampled view of the buffer with block characters, highlights the lines shown in the
+   * window, and the lines with diagnostics/search matches. Click on the minimap to jump to the
+   * line.
+   *
+   * NOTE: The minimap is not shown if the window is too narrow.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'minimap' option.
+   * const value = Rsvim.opt.minimap;
+   * // Set the 'minimap' option.
+   * Rsvim.opt.minimap = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get minimap(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_minimap();
+  }
+
+  /**
+   * Set the _minimap_ option.
+   *
+   * @param {boolean} value - The _minimap_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set minimap(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.minimap" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_minimap(value);
+  }
+
+  /**
+   * Get the _hlsearch_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), all the matches of the last search pattern are highlighted.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'hlSearch' option.
+   * const value = Rsvim.opt.hlSearch;
+   * // Set the 'hlSearch' option.
+   * Rsvim.opt.hlSearch = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get hlSearch(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_hl_search();
+  }
+
+  /**
+   * Set the _hlsearch_ option.
+   *
+   * @param {boolean} value - The _hlsearch_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set hlSearch(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.hlSearch" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_hl_search(value);
+  }
+
+  /**
+   * Get the _incsearch_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the matches are highlighted while typing the search pattern, and the cursor
+   * moves to the first match. Press `ESC` to cancel the search and restore the cursor.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'incSearch' option.
+   * const value = Rsvim.opt.incSearch;
+   * // Set the 'incSearch' option.
+   * Rsvim.opt.incSearch = false;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `true`
+   */
+  get incSearch(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_inc_search();
+  }
+
+  /**
+   * Set the _incsearch_ option.
+   *
+   * @param {boolean} value - The _incsearch_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set incSearch(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.incSearch" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_inc_search(value);
+  }
+
+  /**
+   * Get the _autopairs_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the brackets and quotes are inserted in pairs in insert mode, and typing an
+   * opening char in visual mode wraps the selection in the pair.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'autoPairs' option.
+   * const value = Rsvim.opt.autoPairs;
+   * // Set the 'autoPairs' option.
+   * Rsvim.opt.autoPairs = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get autoPairs(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_auto_pairs();
+  }
+
+  /**
+   * Set the _autopairs_ option.
+   *
+   * @param {boolean} value - The _autopairs_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set autoPairs(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.autoPairs" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_auto_pairs(value);
+  }
+
+  /**
+   * Get the _jumpcenter_ option.
+   *
+   * Global.
+   *
+   * Whether to center the viewport on the target line of the absolute line jumps, i.e. `:{N}`,
+   * `{N}G` and `{N}gg`:
+   *
+   * - `auto`: Only center it when it's not visible.
+   * - `always`: Always center it.
+   * - `never`: Never center it, the viewport scrolls minimally to show it.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'jumpcenter' option.
+   * const value = Rsvim.opt.jumpCenter;
+   * // Set the 'jumpcenter' option.
+   * Rsvim.opt.jumpCenter = "always";
+   * ```
+   *
+   * @returns {"auto" | "always" | "never"}
+   * @defaultValue `"auto"`
+   */
+  get jumpCenter(): "auto" | "always" | "never" {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_jump_center();
+  }
+
+  /**
+   * Set the _jumpcenter_ option.
+   *
+   * @param {"auto" | "always" | "never"} value - The _jumpcenter_ option.
+   * @throws {@link !Error} if value is not a string value.
+   * @throws {@link !TypeError} if value is invalid.
+   */
+  set jumpCenter(value: "auto" | "always" | "never") {
+    if (typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.opt.jumpCenter" value must be string type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_jump_center(value);
+  }
+
+  /**
+   * Get the _quickfixpreview_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), moving the cursor in the quickfix window (i.e. `:copen`) previews the entry
+   * under cursor in the other window, the cursor stays in the quickfix window.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'quickfixpreview' option.
+   * const value = Rsvim.opt.quickfixPreview;
+   * // Set the 'quickfixpreview' option.
+   * Rsvim.opt.quickfixPreview = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get quickfixPreview(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_quickfix_preview();
+  }
+
+  /**
+   * Set the _quickfixpreview_ option.
+   *
+   * @param {boolean} value - The _quickfixpreview_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set quickfixPreview(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.quickfixPreview" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_quickfix_preview(value);
+  }
+
+  /**
+   * Get the _title_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the terminal title is set to the {@link titleString}, and it's updated
+   * automatically when current buffer is changed, renamed or modified. The original title is
+   * restored when it's off, or the editor exits.
+   *
+   * @see [Vim: options.txt - 'title'](https://vimhelp.org/options.txt.html#%27title%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'title' option.
+   * const value = Rsvim.opt.title;
+   * // Set the 'title' option.
+   * Rsvim.opt.title = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get title(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_title();
+  }
+
+  /**
+   * Set the _title_ option.
+   *
+   * @param {boolean} value - The _title_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set title(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.title" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_title(value);
+  }
+
+  /**
+   * Get the _titlestring_ option.
+   *
+   * Global.
+   *
+   * The terminal title format when the _title_ option is on, it has the same items with the
+   * _statusline_ option, i.e. `%f` is the buffer name and `%m` is the modified flag. The empty
+   * string means the default format `"%f%m - Rsvim"`.
+   *
+   * @see [Vim: options.txt - 'titlestring'](https://vimhelp.org/options.txt.html#%27titlestring%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'titleString' option.
+   * const value = Rsvim.opt.titleString;
+   * // Set the 'titleString' option.
+   * Rsvim.opt.titleString = "%f%m (%s)";
+   * ```
+   *
+   * @returns {string}
+   * @defaultValue `""`
+   */
+  get titleString(): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_title_string();
+  }
+
+  /**
+   * Set the _titlestring_ option.
+   *
+   * @param {string} value - The _titlestring_ option.
+   * @throws {@link !Error} if value is not a string value.
+   */
+  set titleString(value: string) {
+    if (typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.opt.titleString" value must be string type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_title_string(value);
+  }
+
+  /**
+   * Get the _termguicolors_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the 24-bit `#rrggbb` colors are used, otherwise the colors are downgraded to
+   * the 256 colors (or 16 colors) supported by the terminal. It's detected from the terminal capabilities on startup.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'termGuiColors' option.
+   * const value = Rsvim.opt.termGuiColors;
+   * // Set the 'termGuiColors' option.
+   * Rsvim.opt.termGuiColors = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `true` if the terminal supports the true colors (i.e. `$COLORTERM` is `truecolor`)
+   */
+  get termGuiColors(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_term_gui_colors();
+  }
+
+  /**
+   * Set the _termguicolors_ option.
+   *
+   * @param {boolean} value - The _termguicolors_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set termGuiColors(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.termGuiColors" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_term_gui_colors(value);
+  }
+
+  /**
+   * Get the _termsync_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), each frame is wrapped inside the synchronized update (mode 2026), thus the
+   * terminal never shows a partially rendered frame. It's detected from the terminal capabilities on startup.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'termSync' option.
+   * const value = Rsvim.opt.termSync;
+   * // Set the 'termSync' option.
+   * Rsvim.opt.termSync = false;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `true` if the terminal supports the synchronized output
+   */
+  get termSync(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_term_sync();
+  }
+
+  /**
+   * Set the _termsync_ option.
+   *
+   * @param {boolean} value - The _termsync_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set termSync(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.termSync" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_term_sync(value);
+  }
+
+  /**
+   * Get the _termstrikethrough_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the strikethrough attribute is rendered, otherwise it's removed. It's detected from the terminal capabilities on startup.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'termStrikethrough' option.
+   * const value = Rsvim.opt.termStrikethrough;
+   * // Set the 'termStrikethrough' option.
+   * Rsvim.opt.termStrikethrough = false;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `true` unless the terminal is the linux console (or other legacy terminals)
+   */
+  get termStrikethrough(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_term_strikethrough();
+  }
+
+  /**
+   * Set the _termstrikethrough_ option.
+   *
+   * @param {boolean} value - The _termstrikethrough_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set termStrikethrough(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.termStrikethrough" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_term_strikethrough(value);
+  }
+
+  /**
+   * Get the _termundercurl_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the undercurl attribute is rendered, otherwise it's rendered as the
+   * underline. It's detected from the terminal capabilities on startup.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'termUndercurl' option.
+   * const value = Rsvim.opt.termUndercurl;
+   * // Set the 'termUndercurl' option.
+   * Rsvim.opt.termUndercurl = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `true` if the terminal supports the undercurl
+   */
+  get termUndercurl(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_term_undercurl();
+  }
+
+  /**
+   * Set the _termundercurl_ option.
+   *
+   * @param {boolean} value - The _termundercurl_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set termUndercurl(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.termUndercurl" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_term_undercurl(value);
+  }
+
+  /**
+   * Get the _termpaste_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the bracketed paste is enabled, thus the pasted text is inserted as it is,
+   * instead of being handled as the typed keys. It's detected from the terminal capabilities on startup.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'termPaste' option.
+   * const value = Rsvim.opt.termPaste;
+   * // Set the 'termPaste' option.
+   * Rsvim.opt.termPaste = false;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `true` unless the terminal is `dumb`
+   */
+  get termPaste(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_term_paste();
+  }
+
+  /**
+   * Set the _termpaste_ option.
+   *
+   * @param {boolean} value - The _termpaste_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set termPaste(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.termPaste" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_term_paste(value);
+  }
+
+  /**
+   * Get the _backup_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the old file is copied to `{file}~` before writing, and it's kept after the
+   * file is written.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'backup' option.
+   * const value = Rsvim.opt.backup;
+   * // Set the 'backup' option.
+   * Rsvim.opt.backup = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get backup(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_backup();
+  }
+
+  /**
+   * Set the _backup_ option.
+   *
+   * @param {boolean} value - The _backup_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set backup(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.backup" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_backup(value);
+  }
+
+  /**
+   * Get the _writebackup_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the old file is copied to `{file}~` before writing, and it's removed after
+   * the file is successfully written (unless the _backup_ option is on).
+   *
+   * @example
+   * ```javascript
+   * // Get the 'writebackup' option.
+   * const value = Rsvim.opt.writeBackup;
+   * // Set the 'writebackup' option.
+   * Rsvim.opt.writeBackup = false;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `true`
+   */
+  get writeBackup(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_write_backup();
+  }
+
+  /**
+   * Set the _writebackup_ option.
+   *
+   * @param {boolean} value - The _writebackup_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set writeBackup(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.writeBackup" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_write_backup(value);
+  }
+
+  /**
+   * Get the _fsync_ option.
+   *
+   * Global.
+   *
+   * The file is written to a temporary file and then renamed to the target file, this option
+   * decides how it's flushed to the disk:
+   *
+   * - `never`: Never flush, i.e. leave it to the operating system.
+   * - `file`: Flush the file before renaming it.
+   * - `full`: Flush the file, and its directory after renaming it.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'fsync' option.
+   * const value = Rsvim.opt.fsync;
+   * // Set the 'fsync' option.
+   * Rsvim.opt.fsync = "full";
+   * ```
+   *
+   * @returns {"never" | "file" | "full"}
+   * @defaultValue `"file"`
+   */
+  get fsync(): "never" | "file" | "full" {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_fsync();
+  }
+
+  /**
+   * Set the _fsync_ option.
+   *
+   * @param {"never" | "file" | "full"} value - The _fsync_ option.
+   * @throws {@link !Error} if value is not a string value.
+   * @throws {@link !TypeError} if value is invalid.
+   */
+  set fsync(value: "never" | "file" | "full") {
+    if (typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.opt.fsync" value must be string type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_fsync(value);
+  }
+
+  /**
+   * Get the _mkdir_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the nonexistent parent directories are created when writing a file. When
+   * `false` (off), the `:write` command fails unless the `++p` flag is given.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'mkdir' option.
+   * const value = Rsvim.opt.mkdir;
+   * // Set the 'mkdir' option.
+   * Rsvim.opt.mkdir = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get mkdir(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_mkdir();
+  }
+
+  /**
+   * Set the _mkdir_ option.
+   *
+   * @param {boolean} value - The _mkdir_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set mkdir(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.mkdir" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_mkdir(value);
+  }
+
+  /**
+   * Get the _lastposexclude_ option.
+   *
+   * Global.
+   *
+   * When a file is opened, the cursor is restored to its last position (i.e. the `'"` mark) when
+   * it's closed, except the files matched by this option. It's comma-separated glob patterns
+   * matched against the absolute file path, the wildcards are `*` (any chars) and `?` (any single
+   * char).
+   *
+   * @example
+   * ```javascript
+   * // Get the 'lastposexclude' option.
+   * const value = Rsvim.opt.lastPosExclude;
+   * // Set the 'lastposexclude' option.
+   * Rsvim.opt.lastPosExclude = "*\/.git/*,/tmp/*";
+   * ```
+   *
+   * @returns {string}
+   * @defaultValue `"*\/.git/*,*\/.hg/*,*\/.svn/*,/tmp/*,/var/tmp/*"`
+   */
+  get lastPosExclude(): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_last_pos_exclude();
+  }
+
+  /**
+   * Set the _lastposexclude_ option.
+   *
+   * @param {string} value - The _lastposexclude_ option.
+   * @throws {@link !Error} if value is not a string value.
+   */
+  set lastPosExclude(value: string) {
+    if (typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.opt.lastPosExclude" value must be string type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_last_pos_exclude(value);
+  }
+
+  /**
+   * Get the _undolevels_ option.
+   *
+   * Global.
+   *
+   * The maximum count of the changes can be undone in each buffer. When it's exceeded, the oldest
+   * abandoned undo branches are evicted first, then the oldest changes. Set it to `0` to disable
+   * undo.
+   *
+   * @see [Vim: options.txt - 'undolevels'](https://vimhelp.org/options.txt.html#%27undolevels%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'undolevels' option.
+   * const value = Rsvim.opt.undoLevels;
+   * // Set the 'undolevels' option.
+   * Rsvim.opt.undoLevels = 100;
+   * ```
+   *
+   * @returns {number}
+   * @defaultValue `1000`
+   */
+  get undoLevels(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_undo_levels();
+  }
+
+  /**
+   * Set the _undolevels_ option.
+   *
+   * @param {number} value - The _undolevels_ option.
+   * @throws {@link !Error} if value is not a non-negative integer value.
+   */
+  set undoLevels(value: number) {
+    if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+      throw new Error(
+        `"Rsvim.opt.undoLevels" value must be non-negative integer type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_undo_levels(value);
+  }
+
+  /**
+   * Get the _undomemory_ option.
+   *
+   * Global.
+   *
+   * The maximum estimated memory (in bytes) of the undo states in each buffer, the states are
+   * evicted in the same order with {@link RsvimOpt.undoLevels}. The current state is never
+   * evicted. Use the `:memory` command to show the undo memory usage.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'undomemory' option.
+   * const value = Rsvim.opt.undoMemory;
+   * // Set the 'undomemory' option to 16MiB.
+   * Rsvim.opt.undoMemory = 16 * 1024 * 1024;
+   * ```
+   *
+   * @returns {number}
+   * @defaultValue `67108864` (64MiB)
+   */
+  get undoMemory(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_undo_memory();
+  }
+
+  /**
+   * Set the _undomemory_ option.
+   *
+   * @param {number} value - The _undomemory_ option.
+   * @throws {@link !Error} if value is not a non-negative integer value.
+   */
+  set undoMemory(value: number) {
+    if (typeof value !== "number" || !Number.isInteger(value) || value < 0) {
+      throw new Error(
+        `"Rsvim.opt.undoMemory" value must be non-negative integer type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_undo_memory(value);
+  }
+
+  /**
+   * Get the _undofile_ option.
+   *
+   * Global.
+   *
+   * When `true` (on), the undo history of a file is saved in the data directory when the file is
+   * saved after a change, and it's restored when the file is opened again (unless the file is
+   * changed outside). It only applies to the files opened later.
+   *
+   * @see [Vim: options.txt - 'undofile'](https://vimhelp.org/options.txt.html#%27undofile%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'undofile' option.
+   * const value = Rsvim.opt.undoFile;
+   * // Set the 'undofile' option.
+   * Rsvim.opt.undoFile = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get undoFile(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_undo_file();
+  }
+
+  /**
+   * Set the _undofile_ option.
+   *
+   * @param {boolean} value - The _undofile_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set undoFile(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.undoFile" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_undo_file(value);
+  }
+
+  /**
+   * Get the _statusline_ option.
+   *
+   * Local to window.
+   *
+   * The statusline is shown at the bottom row of each window when it's not empty, with below
+   * items:
+   *
+   * - `%s`: The editing mode.
+   * - `%f`: The buffer name.
+   * - `%m`: The modified flag, i.e. `[+]`.
+   * - `%l`: The cursor line number.
+   * - `%c`: The cursor column number.
+   * - `%p`: The percentage through the buffer.
+   * - `%=`: The separation point between the left-aligned and the right-aligned sections.
+   * - `%{name}`: The cached text of the component `name`, see {@link RsvimStatusline}.
+   * - `%%`: The `%` char.
+   *
+   * It can also be a callback function, which returns the format on each redraw. Prefer the
+   * components for the expensive parts, they're only rendered on their update triggers.
+   *
+   * See: {@link https://vimhelp.org/options.txt.html#%27statusline%27}.
+   *
+   * @example
+   * ```javascript
+   * // Set the 'statusLine' option.
+   * Rsvim.opt.statusLine = " %s  %f%m%=%l:%c  %p%% ";
+   * // Set the 'statusLine' option with a callback.
+   * Rsvim.opt.statusLine = (windowId) => `${windowId} %f%m`;
+   * ```
+   *
+   * @returns {string | RsvimStatusLineCallback}
+   * @defaultValue `""`
+   */
+  get statusLine(): string | RsvimStatusLineCallback {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_status_line();
+  }
+
+  /**
+   * Set the _statusline_ option.
+   *
+   * @param {string | RsvimStatusLineCallback} value - The _statusline_ option.
+   * @throws {@link !Error} if value is neither a string value nor a function.
+   */
+  set statusLine(value: string | RsvimStatusLineCallback) {
+    if (typeof value !== "string" && typeof value !== "function") {
+      throw new Error(
+        `"Rsvim.opt.statusLine" value must be string or function type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_status_line(value);
+  }
+
+  /**
+   * Get the _winfixwidth_ option.
+   *
+   * Local to window.
+   *
+   * When `true` (on), the window keeps its width when the tiled windows are re-layout, i.e. the
+   * terminal is resized, for example the file tree sidebar.
+   *
+   * See: {@link https://vimhelp.org/options.txt.html#%27winfixwidth%27}.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'winFixWidth' option.
+   * const value = Rsvim.opt.winFixWidth;
+   * // Set the 'winFixWidth' option.
+   * Rsvim.opt.winFixWidth = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get winFixWidth(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_win_fix_width();
+  }
+
+  /**
+   * Set the _winfixwidth_ option.
+   *
+   * @param {boolean} value - The _winfixwidth_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set winFixWidth(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.winFixWidth" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_win_fix_width(value);
+  }
+
+  /**
+   * Get the _winfixheight_ option.
+   *
+   * Local to window.
+   *
+   * When `true` (on), the window keeps its height when the tiled windows are re-layout, i.e. the
+   * terminal is resized, for example the file tree sidebar.
+   *
+   * See: {@link https://vimhelp.org/options.txt.html#%27winfixheight%27}.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'winFixHeight' option.
+   * const value = Rsvim.opt.winFixHeight;
+   * // Set the 'winFixHeight' option.
+   * Rsvim.opt.winFixHeight = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get winFixHeight(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_win_fix_height();
+  }
+
+  /**
+   * Set the _winfixheight_ option.
+   *
+   * @param {boolean} value - The _winfixheight_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set winFixHeight(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.winFixHeight" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_win_fix_height(value);
+  }
+
+  /**
+   * Get the _number_ option.
+   *
+   * Local to window.
+   *
+   * When `true` (on), the line numbers are shown in the gutter column at the left side of the
+   * window. The wrapped continuation rows are blank.
+   *
+   * See: {@link https://vimhelp.org/options.txt.html#%27number%27}.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'number' option.
+   * const value = Rsvim.opt.number;
+   * // Set the 'number' option.
+   * Rsvim.opt.number = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get number(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_number();
+  }
+
+  /**
+   * Set the _number_ option.
+   *
+   * @param {boolean} value - The _number_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set number(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.number" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_number(value);
+  }
+
+  /**
+   * Get the _relativenumber_ option.
+   *
+   * Local to window.
+   *
+   * When `true` (on), the line numbers relative to the cursor line are shown in the gutter
+   * column, they're updated when the cursor moves. The cursor line shows `0`, or its absolute
+   * line number if {@link number} is also on.
+   *
+   * See: {@link https://vimhelp.org/options.txt.html#%27relativenumber%27}.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'relativenumber' option.
+   * const value = Rsvim.opt.relativeNumber;
+   * // Set the 'relativenumber' option.
+   * Rsvim.opt.relativeNumber = true;
+   * ```
+   *
+   * @returns {boolean}
+   * @defaultValue `false`
+   */
+  get relativeNumber(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_relative_number();
+  }
+
+  /**
+   * Set the _relativenumber_ option.
+   *
+   * @param {boolean} value - The _relativenumber_ option.
+   * @throws {@link !Error} if value is not a boolean value.
+   */
+  set relativeNumber(value: boolean) {
+    if (typeof value !== "boolean") {
+      throw new Error(
+        `"Rsvim.opt.relativeNumber" value must be boolean type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_relative_number(value);
+  }
+
+  /**
+   * Get the _numberwidth_ option.
+   *
+   * Local to window.
+   *
+   * The minimal width of the gutter column (including the trailing space separator), it grows
+   * when the line numbers need more digits.
+   *
+   * See: {@link https://vimhelp.org/options.txt.html#%27numberwidth%27}.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'numberwidth' option.
+   * const value = Rsvim.opt.numberWidth;
+   * // Set the 'numberwidth' option.
+   * Rsvim.opt.numberWidth = 6;
+   * ```
+   *
+   * @returns {number}
+   * @defaultValue `4`
+   */
+  get numberWidth(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_number_width();
+  }
+
+  /**
+   * Set the _numberwidth_ option.
+   *
+   * @param {number} value - The _numberwidth_ option.
+   * @throws {@link !Error} if value is not a positive integer value.
+   */
+  set numberWidth(value: number) {
+    if (typeof value !== "number" || !Number.isInteger(value) || value < 1) {
+      throw new Error(
+        `"Rsvim.opt.numberWidth" value must be positive integer type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_number_width(value);
+  }
+
+  /**
+   * Get the _signcolumn_ option.
+   *
+   * Local to window.
+   *
+   * Whether to show the sign column (see {@link RsvimSign}) at the left side of the window:
+   *
+   * - `auto`: Only show it when there're signs placed in the buffer.
+   * - `yes`: Always show it.
+   * - `no`: Never show it.
+   *
+   * See: {@link https://vimhelp.org/options.txt.html#%27signcolumn%27}.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'signcolumn' option.
+   * const value = Rsvim.opt.signColumn;
+   * // Set the 'signcolumn' option.
+   * Rsvim.opt.signColumn = "yes";
+   * ```
+   *
+   * @returns {"auto" | "yes" | "no"}
+   * @defaultValue `"auto"`
+   */
+  get signColumn(): "auto" | "yes" | "no" {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_sign_column();
+  }
+
+  /**
+   * Set the _signcolumn_ option.
+   *
+   * @param {"auto" | "yes" | "no"} value - The _signcolumn_ option.
+   * @throws {@link !Error} if value is not a string value.
+   * @throws {@link !TypeError} if value is invalid.
+   */
+  set signColumn(value: "auto" | "yes" | "no") {
+    if (typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.opt.signColumn" value must be string type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_sign_column(value);
+  }
+
+  /**
+   * Get the _signwidth_ option.
+   *
+   * Local to window.
+   *
+   * The width of the sign column, the sign text is truncated to it.
+   *
+   * @example
+   * ```javascript
+   * // Get the 'signwidth' option.
+   * const value = Rsvim.opt.signWidth;
+   * // Set the 'signwidth' option.
+   * Rsvim.opt.signWidth = 1;
+   * ```
+   *
+   * @returns {number}
+   * @defaultValue `2`
+   */
+  get signWidth(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_sign_width();
+  }
+
+  /**
+   * Set the _signwidth_ option.
+   *
+   * @param {number} value - The _signwidth_ option.
+   * @throws {@link !Error} if value is not a positive integer value.
+   */
+  set signWidth(value: number) {
+    if (typeof value !== "number" || !Number.isInteger(value) || value < 1) {
+      throw new Error(
+        `"Rsvim.opt.signWidth" value must be positive integer type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_sign_width(value);
+  }
+
+  /**
+   * Get the _wildmode_ option.
+   *
+   * Global.
+   *
+   * It's a comma-separated list of the completion stages for the `Tab` key in the `:` command
+   * line, the first `Tab` uses the first stage, the next `Tab` uses the next stage, and the last
+   * stage is repeated. Each stage is one of: `full`, `longest`, `longest:full`, `list`,
+   * `list:full`, `list:longest`.
+   *
+   * @see [Vim: options.txt - 'wildmode'](https://vimhelp.org/options.txt.html#%27wildmode%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'wildMode' option.
+   * const value = Rsvim.opt.wildMode;
+   * // Set the 'wildMode' option.
+   * Rsvim.opt.wildMode = "longest:full,full";
+   * ```
+   *
+   * @returns {string}
+   * @defaultValue `"full"`
+   */
+  get wildMode(): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_wild_mode();
+  }
+
+  /**
+   * Set the _wildmode_ option.
+   *
+   * @param {string} value - The _wildmode_ option.
+   * @throws {@link !Error} if value is not a string value.
+   * @throws {@link !TypeError} if value is not a valid 'wildmode'.
+   */
+  set wildMode(value: string) {
+    if (typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.opt.wildMode" value must be string type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_wild_mode(value);
+  }
+
+  /**
+   * Get the _wildoptions_ option.
+   *
+   * Global.
+   *
+   * When it contains `pum`, the completion candidates of the `:` command line are shown in a
+   * popup menu, otherwise they're shown in a horizontal bar above the command line.
+   *
+   * @see [Vim: options.txt - 'wildoptions'](https://vimhelp.org/options.txt.html#%27wildoptions%27)
+   *
+   * @example
+   * ```javascript
+   * // Get the 'wildOptions' option.
+   * const value = Rsvim.opt.wildOptions;
+   * // Set the 'wildOptions' option.
+   * Rsvim.opt.wildOptions = "pum";
+   * ```
+   *
+   * @returns {string}
+   * @defaultValue `""`
+   */
+  get wildOptions(): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.opt_get_wild_options();
+  }
+
+  /**
+   * Set the _wildoptions_ option.
+   *
+   * @param {string} value - The _wildoptions_ option.
+   * @throws {@link !Error} if value is not a string value.
+   * @throws {@link !TypeError} if value is not a valid 'wildoptions', i.e. only `pum` is supported.
+   */
+  set wildOptions(value: string) {
+    if (typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.opt.wildOptions" value must be string type, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.opt_set_wild_options(value);
+  }
+}
+
+/**
+ * The `Rsvim.progress` object for reporting the progress of long-running tasks.
+ *
+ * The running tasks are shown in a notification at the bottom-right corner of the editor, one
+ * line for each task. The cancellable tasks have a `[x]` button, clicking it cancels the task.
+ * The cancellation is cooperative, i.e. the task should check {@link isCancelled} and stop itself.
+ *
+ * @example
+ * ```javascript
+ * const id = Rsvim.progress.begin("Install plugins", { cancellable: true });
+ * for (let i = 0; i < plugins.length; i++) {
+ *   if (Rsvim.progress.isCancelled(id)) {
+ *     break;
+ *   }
+ *   Rsvim.progress.update(id, plugins[i].name, (i * 100) / plugins.length);
+ *   install(plugins[i]);
+ * }
+ * Rsvim.progress.end(id);
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimProgress {
+  /**
+   * Begin a task.
+   *
+   * @param {string} title - The task title.
+   * @param {{cancellable?: boolean}} options - Whether the task can be cancelled by user, default is `false`.
+   * @returns {number} The progress ID.
+   * @throws {@link !Error} if title is not a string value.
+   */
+  begin(title: string, options?: { cancellable?: boolean }): number {
+    if (typeof title !== "string") {
+      throw new Error(
+        `"Rsvim.progress.begin" title must be string type, but found ${title} (${typeof title})`,
+      );
+    }
+    const cancellable = options?.cancellable ?? false;
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.progress_begin(title, cancellable);
+  }
+
+  /**
+   * Update the task's message and percentage.
+   *
+   * @param {number} id - The progress ID.
+   * @param {string} message - The message, i.e. current processing item.
+   * @param {number} percentage - The percentage in `[0, 100]`, omit it if the total amount is unknown.
+   */
+  update(id: number, message: string, percentage?: number): void {
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.progress_update(id, message, percentage);
+  }
+
+  /**
+   * End the task, it's removed from the notification.
+   *
+   * @param {number} id - The progress ID.
+   */
+  end(id: number): void {
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.progress_end(id);
+  }
+
+  /**
+   * Whether the task is cancelled by user.
+   *
+   * @param {number} id - The progress ID.
+   * @returns {boolean}
+   */
+  isCancelled(id: number): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.progress_is_cancelled(id);
+  }
+}
+
+/**
+ * The `Rsvim.input` object for waiting key presses outside the key mappings.
+ *
+ * It's useful for the "press any key" prompt, hint jumps and custom operators. The next key press
+ * is consumed by the waiting request, i.e. it's not handled by current editing mode.
+ *
+ * In the batch mode (i.e. the macro playback, the batch commands like `:bufdo`, the headless
+ * scripts and UI tests), the prompts are not waiting for user, the `getchar` requests are answered
+ * with `null` immediately (same with timeout), thus the prompts take their default answers.
+ *
+ * @example
+ * ```javascript
+ * const key = await Rsvim.input.getchar({ timeout: 3000 });
+ * if (key === null) {
+ *   // Timeout
+ * } else if (key === "<Esc>") {
+ *   // Cancelled
+ * }
+ *
+ * // Don't hang on the prompts when executing commands in each buffer.
+ * Rsvim.input.batchBegin();
+ * try {
+ *   Rsvim.cmd("bufdo %s/foo/bar/ | update");
+ * } finally {
+ *   Rsvim.input.batchEnd();
+ * }
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimInput {
+  /**
+   * Wait for the next key press.
+   *
+   * The key is in vim's key notation, i.e. `a`, `<Esc>`, `<C-a>`, `<S-Tab>`. When there are
+   * multiple waiting requests, the key is consumed by the earliest one.
+   *
+   * @param {{timeout?: number}} options - The milliseconds to wait, wait forever if omitted.
+   * @returns {Promise<string | null>} The pressed key, or `null` if timeout.
+   * @throws {@link !Error} if timeout is not a number value.
+   */
+  getchar(options?: { timeout?: number }): Promise<string | null> {
+    const timeout = options?.timeout;
+    if (timeout !== undefined && typeof timeout !== "number") {
+      throw new Error(
+        `"Rsvim.input.getchar" timeout must be number type, but found ${timeout} (${typeof timeout})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.input_getchar(timeout);
+  }
+
+  /**
+   * Count of the typed ahead keys that are not handled yet, i.e. received while the editor is
+   * busy.
+   *
+   * @returns {number}
+   */
+  typeahead(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.input_typeahead();
+  }
+
+  /**
+   * Begin the batch mode, the waiting `getchar` requests are resolved with `null`. The batch mode
+   * can be nested, each `batchBegin` must be paired with a {@link RsvimInput.batchEnd}.
+   */
+  batchBegin(): void {
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.input_batch_begin();
+  }
+
+  /**
+   * End the batch mode, i.e. restore the interactive input once the outermost batch mode is ended.
+   *
+   * @returns {boolean} `false` if it's not in the batch mode.
+   */
+  batchEnd(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.input_batch_end();
+  }
+
+  /**
+   * Whether it's in the batch mode.
+   *
+   * @returns {boolean}
+   */
+  isBatch(): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.input_is_batch();
+  }
+}
+
+/**
+ * The highlight style, the colors are `#rrggbb` hex codes or color names (i.e. `red`, `dark_blue`).
+ */
+export interface RsvimHlStyle {
+  fg?: string;
+  bg?: string;
+  bold?: boolean;
+  italic?: boolean;
+  underline?: boolean;
+  reverse?: boolean;
+  strikethrough?: boolean;
+}
+
+/**
+ * The highlight group style, the group links to the `link` group if it's specified (the other
+ * fields are ignored).
+ */
+export interface RsvimHlGroupStyle extends RsvimHlStyle {
+  link?: string;
+}
+
+/**
+ * The `Rsvim.hl` object for the highlight namespaces of current buffer, and the highlight groups.
+ *
+ * A namespace is a group of highlights with a priority. When painting a cell, the highlights are
+ * layered from low priority to high priority, the higher one overrides the colors and the
+ * attributes are combined. The builtin priorities are:
+ *
+ * - Tree-sitter captures: `100`
+ * - Diagnostics: `150`
+ * - Search matches: `200`
+ * - Visual selection: `250`
+ *
+ * @example
+ * ```javascript
+ * const ns = Rsvim.hl.createNamespace("todo", 160);
+ * Rsvim.hl.addHighlight(ns, 0, 0, 4, { fg: "#ff8000", bold: true });
+ * // Clear all the highlights of the namespace.
+ * Rsvim.hl.clearNamespace(ns);
+ * ```
+ *
+ * All the widgets render through the highlight groups (i.e. `Normal`, `Visual`, `Search`,
+ * `StatusLine`, `LineNr`), a colorscheme is a module at `colors/{name}.js` (or `.ts`) in the
+ * config directories that sets the groups, it's loaded by the `:colorscheme {name}` command.
+ *
+ * @example
+ * ```javascript
+ * // colors/ocean.js
+ * Rsvim.hl.set("Normal", { fg: "#c0c5ce", bg: "#2b303b" });
+ * Rsvim.hl.set("StatusLine", { fg: "#2b303b", bg: "#8fa1b3", bold: true });
+ * Rsvim.hl.set("CursorLineNr", { link: "Search" });
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimHl {
+  /**
+   * Create a namespace, or get the existing namespace with the same name.
+   *
+   * @param {string} name - The namespace name.
+   * @param {number} priority - The priority, default is `100`.
+   * @returns {number} The namespace ID, or `0` if there's no buffer.
+   * @throws {@link !Error} if name is not a string value.
+   */
+  createNamespace(name: string, priority?: number): number {
+    if (typeof name !== "string") {
+      throw new Error(
+        `"Rsvim.hl.createNamespace" name must be string type, but found ${name} (${typeof name})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.hl_create_namespace(name, priority ?? 100);
+  }
+
+  /**
+   * Add a highlight to the chars `[start, end)` of the line.
+   *
+   * @param {number} ns - The namespace ID.
+   * @param {number} line - The line index (0-based).
+   * @param {number} start - The start char index (0-based, inclusive).
+   * @param {number} end - The end char index (0-based, exclusive).
+   * @param {RsvimHlStyle} style - The highlight style.
+   * @returns {boolean} `false` if the namespace doesn't exist.
+   * @throws {@link !TypeError} if the color is invalid.
+   */
+  addHighlight(
+    ns: number,
+    line: number,
+    start: number,
+    end: number,
+    style: RsvimHlStyle,
+  ): boolean {
+    const attrs = ["bold", "italic", "underline", "reverse", "strikethrough"]
+      .filter((name) => style[name as keyof RsvimHlStyle] === true)
+      .join(",");
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.hl_add_highlight(
+      ns,
+      line,
+      start,
+      end,
+      style.fg,
+      style.bg,
+      attrs,
+    );
+  }
+
+  /**
+   * Clear the highlights of the namespace in the lines `[start, end)`.
+   *
+   * @param {number} ns - The namespace ID.
+   * @param {number} start - The start line index, default is `0`.
+   * @param {number} end - The end line index, default is `-1`, i.e. the end of buffer.
+   */
+  clearNamespace(ns: number, start?: number, end?: number): void {
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.hl_clear_namespace(ns, start ?? 0, end ?? -1);
+  }
+
+  /**
+   * Set the highlight group, the changes are rendered on next redraw. The `#rrggbb` colors are
+   * rendered as the nearest 256 colors (or 16 colors) if the terminal doesn't support true colors.
+   *
+   * @param {string} group - The group name, i.e. `Normal`, `StatusLine`.
+   * @param {RsvimHlGroupStyle} style - The group style.
+   * @throws {@link !Error} if group is not a string value.
+   * @throws {@link !TypeError} if the color is invalid.
+   */
+  set(group: string, style: RsvimHlGroupStyle): void {
+    if (typeof group !== "string") {
+      throw new Error(
+        `"Rsvim.hl.set" group must be string type, but found ${group} (${typeof group})`,
+      );
+    }
+    const attrs = ["bold", "italic", "underline", "reverse", "strikethrough"]
+      .filter((name) => style[name as keyof RsvimHlStyle] === true)
+      .join(",");
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.hl_set_group(
+      group,
+      style.fg,
+      style.bg,
+      attrs,
+      style.link,
+    );
+  }
+}
+
+/**
+ * The {@link RsvimUi.input} options.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimUiInputOptions {
+  /** The prompt, it's shown as the title of the prompt window. */
+  prompt?: string;
+  /** The default text, it's also the answer in the batch mode, see {@link RsvimInput.batchBegin}. */
+  default?: string;
+}
+
+/**
+ * The {@link RsvimUi.select} options.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimUiSelectOptions<T> {
+  /** The prompt, it's shown as the title of the prompt window. */
+  prompt?: string;
+  /** Format an item to a line, by default it's `String(item)`. */
+  format?: (item: T) => string;
+}
+
+/**
+ * The `Rsvim.ui` object for screen redraw controls and the user prompts.
+ *
+ * The prompts are floating windows next to the cursor, they wait for the key presses with
+ * {@link RsvimInput.getchar}, thus the event loop is not blocked. In the batch mode (see
+ * {@link RsvimInput.batchBegin}), the prompts are not shown and answered with the defaults.
+ *
+ * The plugins (i.e. a fuzzy finder) can override the default prompts by assigning the
+ * `Rsvim.ui.input` and `Rsvim.ui.select` functions, with the same signatures.
+ *
+ * @example
+ * ```javascript
+ * // Clear the terminal and fully repaint, i.e. after an external program writes to the terminal.
+ * Rsvim.ui.redraw({ force: true });
+ *
+ * const name = await Rsvim.ui.input({ prompt: "New name", default: "foo.rs" });
+ * if (name !== null) {
+ *   Rsvim.cmd(`Rename ${name}`);
+ * }
+ * const choice = await Rsvim.ui.select(["tabs", "spaces"], { prompt: "Indent with" });
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimUi {
+  /**
+   * Redraw the screen, same with the `:redraw` ex command.
+   *
+   * The screen is redrawn after current task finishes. With `force`, the terminal is cleared and
+   * all the cells are repainted, same with `:redraw!`.
+   *
+   * @param {{force?: boolean}} options - Whether to clear the terminal and fully repaint, default is `false`.
+   */
+  redraw(options?: { force?: boolean }): void {
+    const force = options?.force ?? false;
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.ui_redraw(force);
+  }
+
+  /**
+   * Ask the user to input a text.
+   *
+   * The keys are: `<CR>` to confirm, `<Esc>` (or `<C-c>`) to cancel, `<BS>` (or `<C-h>`) to delete
+   * the last char, `<C-u>` to delete all chars.
+   *
+   * @param {RsvimUiInputOptions} options - (Optional) The prompt and the default text.
+   * @returns {Promise<string | null>} The text, or `null` if it's cancelled.
+   */
+  input(options?: RsvimUiInputOptions): Promise<string | null> {
+    const title = options?.prompt ?? "";
+    const text = options?.default ?? "";
+    // @ts-ignore Ignore warning
+    if (__InternalRsvimGlobalObject.input_is_batch()) {
+      return Promise.resolve(text);
+    }
+    return new Promise((resolve) => {
+      let win = -1;
+      const done = (value: string | null) => {
+        // @ts-ignore Ignore warning
+        __InternalRsvimGlobalObject.win_close_float(win);
+        resolve(value);
+      };
+      const loop = (value: string) => {
+        win = openUiPrompt(win, title, [value]);
+        // @ts-ignore Ignore warning
+        __InternalRsvimGlobalObject
+          .input_getchar(undefined)
+          .then((key: string | null) => {
+            if (key === null || key === "<CR>") {
+              done(value);
+            } else if (key === "<Esc>" || key === "<C-c>") {
+              done(null);
+            } else if (key === "<BS>" || key === "<C-h>") {
+              loop(Array.from(value).slice(0, -1).join(""));
+            } else if (key === "<C-u>") {
+              loop("");
+            } else {
+              loop(value + (uiKeyChar(key) ?? ""));
+            }
+          });
+      };
+      loop(text);
+    });
+  }
+
+  /**
+   * Ask the user to select an item.
+   *
+   * The keys are: `j`/`<Down>`/`<C-n>`/`<Tab>` for the next item, `k`/`<Up>`/`<C-p>`/`<S-Tab>`
+   * for the previous item, `1`-`9` to select the item directly, `<CR>` to confirm, `<Esc>` (or
+   * `q`, `<C-c>`) to cancel.
+   *
+   * @param {T[]} items - The items.
+   * @param {RsvimUiSelectOptions<T>} options - (Optional) The prompt and the format function.
+   * @returns {Promise<T | null>} The selected item, or `null` if it's cancelled, or there's no
+   * item, or it's in the batch mode.
+   * @throws {@link !Error} if items is not an array.
+   */
+  select<T>(items: T[], options?: RsvimUiSelectOptions<T>): Promise<T | null> {
+    if (!Array.isArray(items)) {
+      throw new Error(
+        `"Rsvim.ui.select" items must be array type, but found ${items} (${typeof items})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    if (items.length === 0 || __InternalRsvimGlobalObject.input_is_batch()) {
+      return Promise.resolve(null);
+    }
+    const title = options?.prompt ?? "";
+    const format = options?.format ?? ((item: T) => String(item));
+    const lines = items.map((item, i) => `${i + 1}. ${format(item)}`);
+    const height = Math.min(lines.length, UI_SELECT_MAX_HEIGHT);
+    return new Promise((resolve) => {
+      let win = -1;
+      const done = (index: number | null) => {
+        // @ts-ignore Ignore warning
+        __InternalRsvimGlobalObject.win_close_float(win);
+        resolve(index === null ? null : items[index]);
+      };
+      const loop = (index: number) => {
+        // Scroll to keep the selected item in the middle.
+        const top = Math.max(
+          Math.min(index - Math.floor(height / 2), lines.length - height),
+          0,
+        );
+        const visible = lines
+          .slice(top, top + height)
+          .map((line, i) => (top + i === index ? "> " : "  ") + line);
+        win = openUiPrompt(win, title, visible);
+        // @ts-ignore Ignore warning
+        __InternalRsvimGlobalObject
+          .input_getchar(undefined)
+          .then((key: string | null) => {
+            if (key === "<CR>") {
+              done(index);
+            } else if (
+              key === null ||
+              key === "<Esc>" ||
+              key === "q" ||
+              key === "<C-c>"
+            ) {
+              done(null);
+            } else if (["j", "<Down>", "<C-n>", "<Tab>"].includes(key)) {
+              loop((index + 1) % lines.length);
+            } else if (["k", "<Up>", "<C-p>", "<S-Tab>"].includes(key)) {
+              loop((index + lines.length - 1) % lines.length);
+            } else if (/^[1-9]$/.test(key) && Number(key) <= lines.length) {
+              done(Number(key) - 1);
+            } else {
+              loop(index);
+            }
+          });
+      };
+      loop(0);
+    });
+  }
+}
+
+// The max height of the select prompt window.
+const UI_SELECT_MAX_HEIGHT = 10;
+
+// Convert the key (in vim's key notation) to the typed char, or `null` if it's not a char.
+function uiKeyChar(key: string): string | null {
+  if (key === "<Space>") {
+    return " ";
+  }
+  if (key === "<lt>") {
+    return "<";
+  }
+  if (key.startsWith("<")) {
+    return null;
+  }
+  return key;
+}
+
+// Open the prompt window with the lines next to the cursor, the previous window (if not `-1`) is
+// closed. Returns the window ID.
+function openUiPrompt(win: number, title: string, lines: string[]): number {
+  if (win >= 0) {
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.win_close_float(win);
+  }
+  const width = lines.reduce(
+    (width, line) => Math.max(width, line.length + 1),
+    Math.max(title.length + 2, 20),
+  );
+  // @ts-ignore Ignore warning
+  return __InternalRsvimGlobalObject.win_open_float(
+    -1,
+    lines.join("\n"),
+    "cursor",
+    1,
+    0,
+    width,
+    lines.length,
+    250,
+    "rounded",
+    false,
+    title,
+    false,
+  );
+}
+
+/**
+ * The floating window options.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimFloatOptions {
+  /** The buffer ID to show, by default a new scratch buffer is created with the `text`. */
+  buffer?: number;
+  /** The text of the new scratch buffer. */
+  text?: string;
+  /** The anchor position that `row`/`col` are relative to, default is `"editor"`. */
+  anchor?: "editor" | "window" | "cursor";
+  /** Rows offset from the anchor, default is `0`. */
+  row?: number;
+  /** Columns offset from the anchor, default is `0`. */
+  col?: number;
+  /** Width of the window content (exclude the border). */
+  width: number;
+  /** Height of the window content (exclude the border). */
+  height: number;
+  /** Z-index, default is `50`. */
+  zIndex?: number;
+  /** Border style, default is `"single"`. */
+  border?: "none" | "single" | "double" | "rounded";
+  /** Whether draws the shadow, default is `false`. */
+  shadow?: boolean;
+  /** Title on the top border. */
+  title?: string;
+  /** Whether moves the cursor into the window, default is `false`. */
+  enter?: boolean;
+}
+
+/**
+ * The `Rsvim.win` object for floating windows and current window.
+ *
+ * @example
+ * ```javascript
+ * // Show a message in a floating window next to the cursor.
+ * const id = Rsvim.win.openFloat({
+ *   text: "Hello, Rsvim!",
+ *   anchor: "cursor",
+ *   row: 1,
+ *   width: 20,
+ *   height: 1,
+ * });
+ * Rsvim.win.closeFloat(id);
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimWin {
+  /**
+   * Open a floating window on top of the tiled windows.
+   *
+   * The window is moved (or shrunk) to fit inside the terminal.
+   *
+   * @param {RsvimFloatOptions} options - The floating window options.
+   * @returns {number} The window ID.
+   * @throws {@link !TypeError} if the anchor/border is invalid, or the buffer doesn't exist.
+   */
+  openFloat(options: RsvimFloatOptions): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_open_float(
+      options.buffer ?? -1,
+      options.text ?? "",
+      options.anchor ?? "editor",
+      options.row ?? 0,
+      options.col ?? 0,
+      options.width,
+      options.height,
+      options.zIndex ?? 50,
+      options.border ?? "single",
+      options.shadow ?? false,
+      options.title ?? "",
+      options.enter ?? false,
+    );
+  }
+
+  /**
+   * Close a floating window, the scratch buffer created with the `text` is wiped out as well.
+   *
+   * @param {number} id - The window ID.
+   * @returns {boolean} `false` if it's not a floating window.
+   */
+  closeFloat(id: number): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_close_float(id);
+  }
+
+  /**
+   * Get current window ID.
+   *
+   * @returns {number | null} The window ID, or `null` if there's no current window.
+   */
+  current(): number | null {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_current();
+  }
+
+  /**
+   * Move the cursor to the window, i.e. make it current window.
+   *
+   * @param {number} id - The window ID.
+   * @returns {boolean} `false` if the window doesn't exist.
+   * @throws {@link !Error} if id is not a number value.
+   */
+  focus(id: number): boolean {
+    if (typeof id !== "number") {
+      throw new Error(
+        `"Rsvim.win.focus" id must be number type, but found ${id} (${typeof id})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_focus(id);
+  }
+
+  /**
+   * Move the cursor in current window, the position is clamped to the buffer.
+   *
+   * @param {number} line - The line index, starts from 0.
+   * @param {number} col - The char index in the line, starts from 0.
+   * @returns {boolean} `false` if there's no current window.
+   * @throws {@link !Error} if line or col is not a number value.
+   */
+  setCursor(line: number, col: number): boolean {
+    if (typeof line !== "number" || typeof col !== "number") {
+      throw new Error(
+        `"Rsvim.win.setCursor" line and col must be number type, but found ${line} (${typeof line}), ${col} (${typeof col})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_set_cursor(line, col);
+  }
+}
+
+/**
+ * The register type, it decides how the content is pasted.
+ *
+ * @category Editor APIs
+ */
+export type RsvimRegisterType = "charwise" | "linewise" | "blockwise";
+
+/**
+ * The register content.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimRegisterValue {
+  /** The register type. */
+  type: RsvimRegisterType;
+  /** The content, each line of the blockwise content is separated by `\n`. */
+  content: string;
+}
+
+/**
+ * The `Rsvim.register` object for registers.
+ *
+ * The register names are:
+ *
+ * - `"`: The unnamed register, i.e. the last yanked/deleted text.
+ * - `0`-`9`: The numbered registers, `0` is the last yanked text, `1`-`9` are the last deleted lines.
+ * - `a`-`z`: The named registers, the uppercase `A`-`Z` appends to them.
+ * - `-`: The small delete register, i.e. the last deleted text within one line.
+ * - `_`: The black hole register.
+ * - `+`/`*`: The system clipboard.
+ *
+ * @see [Vim: change.txt - registers](https://vimhelp.org/change.txt.html#registers)
+ *
+ * @example
+ * ```javascript
+ * Rsvim.register.set("a", "hello\n");
+ * // Returns { type: "linewise", content: "hello\n" }
+ * Rsvim.register.get("a");
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimRegister {
+  /**
+   * Get the register.
+   *
+   * @param {string} name - The register name.
+   * @returns {RsvimRegisterValue | null} The register content, or `null` if it's empty.
+   * @throws {@link !TypeError} if the register name is invalid.
+   */
+  get(name: string): RsvimRegisterValue | null {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.register_get(name);
+  }
+
+  /**
+   * Set the register.
+   *
+   * @param {string} name - The register name.
+   * @param {string} content - The content.
+   * @param {RsvimRegisterType} type - The register type, by default it's `"linewise"` if the content ends with line break, otherwise `"charwise"`.
+   * @returns {boolean} `false` if the system clipboard is not available.
+   * @throws {@link !Error} if content is not a string value.
+   * @throws {@link !TypeError} if the register name or type is invalid.
+   */
+  set(name: string, content: string, type?: RsvimRegisterType): boolean {
+    if (typeof content !== "string") {
+      throw new Error(
+        `"Rsvim.register.set" content must be string type, but found ${content} (${typeof content})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.register_set(name, content, type ?? "");
+  }
+}
+
+/**
+ * The mark position.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimMarkValue {
+  /** The buffer ID the mark belongs to. */
+  bufferId: number;
+  /** The line index, starts from 0. */
+  line: number;
+  /** The char index in the line, starts from 0. */
+  col: number;
+}
+
+/**
+ * The `Rsvim.mark` object for marks.
+ *
+ * The mark names are:
+ *
+ * - `a`-`z`: The buffer-local marks.
+ * - `A`-`Z`: The global marks, each one belongs to only one buffer.
+ * - `'`: The position before the latest jump.
+ * - `.`: The position where the last change was made.
+ * - `^`: The position where the insert mode was stopped.
+ *
+ * The marks are adjusted when lines are inserted/deleted, a mark is removed if its line is deleted.
+ *
+ * @see [Vim: motion.txt - marks](https://vimhelp.org/motion.txt.html#mark-motions)
+ *
+ * @example
+ * ```javascript
+ * Rsvim.mark.set("a", 10, 0);
+ * // Returns { bufferId: 1, line: 10, col: 0 }
+ * Rsvim.mark.get("a");
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimMark {
+  /**
+   * Get the mark, the global mark is searched in all buffers, the other marks are in current buffer.
+   *
+   * @param {string} name - The mark name.
+   * @returns {RsvimMarkValue | null} The mark position, or `null` if it's not set.
+   * @throws {@link !TypeError} if the mark name is invalid.
+   */
+  get(name: string): RsvimMarkValue | null {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.mark_get(name);
+  }
+
+  /**
+   * Set the mark in current buffer.
+   *
+   * @param {string} name - The mark name.
+   * @param {number} line - The line index, starts from 0.
+   * @param {number} col - The char index in the line, starts from 0.
+   * @returns {boolean} `false` if there's no current buffer.
+   * @throws {@link !Error} if line or col is not a number value.
+   * @throws {@link !TypeError} if the mark name is invalid.
+   */
+  set(name: string, line: number, col: number): boolean {
+    if (typeof line !== "number" || typeof col !== "number") {
+      throw new Error(
+        `"Rsvim.mark.set" line and col must be number type, but found ${line} (${typeof line}), ${col} (${typeof col})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.mark_set(name, line, col);
+  }
+}
+
+/**
+ * The sign definition, see {@link RsvimSign.define}.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimSignDefinition {
+  /** The text shown in the sign column, i.e. `E` or `>>`, it's truncated to _signwidth_. */
+  text: string;
+  /** (Optional) The highlight style of the text. */
+  style?: RsvimHlStyle;
+}
+
+/**
+ * The options of {@link RsvimSign.place}.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimSignPlaceOptions {
+  /** The buffer ID, by default it's current buffer. */
+  bufferId?: number;
+  /** The group, i.e. the plugin name, the signs of a group are cleared together. Default is `""`. */
+  group?: string;
+  /** The priority, the sign with the highest priority is shown on a line. Default is `10`. */
+  priority?: number;
+}
+
+/**
+ * The placed sign, see {@link RsvimSign.getPlaced}.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimPlacedSign {
+  /** The sign ID, it's unique in the buffer. */
+  id: number;
+  /** The sign definition name. */
+  name: string;
+  /** The line index, starts from 0. */
+  line: number;
+  /** The group. */
+  group: string;
+  /** The priority. */
+  priority: number;
+}
+
+/**
+ * The `Rsvim.sign` object for the signs, i.e. the git changes and the diagnostics shown in the
+ * sign column at the left side of the window.
+ *
+ * The signs are defined by name, and placed on the buffer lines. The placed signs move with their
+ * lines when lines are inserted/deleted, a sign on a deleted line is removed. The sign column is
+ * controlled by the _signcolumn_ and _signwidth_ options, see {@link RsvimOpt.signColumn}.
+ *
+ * @see [Vim: sign.txt](https://vimhelp.org/sign.txt.html)
+ *
+ * @example
+ * ```javascript
+ * Rsvim.sign.define("GitAdded", { text: "+", style: { fg: "green" } });
+ * const id = Rsvim.sign.place("GitAdded", 10, { group: "git" });
+ * Rsvim.sign.unplace(id);
+ * // Unplace all the signs of the group.
+ * Rsvim.sign.clear("git");
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimSign {
+  /**
+   * Define a sign, the previously defined sign with the same name is replaced.
+   *
+   * @param {string} name - The sign name.
+   * @param {RsvimSignDefinition} definition - The sign text and highlight style.
+   * @throws {@link !Error} if name or text is not a string value.
+   * @throws {@link !TypeError} if the color is invalid.
+   */
+  define(name: string, definition: RsvimSignDefinition): void {
+    if (typeof name !== "string" || typeof definition?.text !== "string") {
+      throw new Error(
+        `"Rsvim.sign.define" name and text must be string type, but found ${name} (${typeof name}), ${definition?.text} (${typeof definition?.text})`,
+      );
+    }
+    const style = definition.style ?? {};
+    const attrs = ["bold", "italic", "underline", "reverse", "strikethrough"]
+      .filter((name) => style[name as keyof RsvimHlStyle] === true)
+      .join(",");
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.sign_define(
+      name,
+      definition.text,
+      style.fg,
+      style.bg,
+      attrs,
+    );
+  }
+
+  /**
+   * Undefine a sign, the placed signs are kept but not shown.
+   *
+   * @param {string} name - The sign name.
+   * @returns {boolean} `false` if the sign is not defined.
+   */
+  undefine(name: string): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.sign_undefine(name);
+  }
+
+  /**
+   * Place a sign on the line.
+   *
+   * @param {string} name - The sign name.
+   * @param {number} line - The line index, starts from 0.
+   * @param {RsvimSignPlaceOptions} options - (Optional) The buffer, group and priority.
+   * @returns {number} The sign ID, or `0` if the buffer doesn't exist.
+   * @throws {@link !Error} if line is not a number value.
+   * @throws {@link !TypeError} if the sign is not defined.
+   */
+  place(name: string, line: number, options?: RsvimSignPlaceOptions): number {
+    if (typeof line !== "number") {
+      throw new Error(
+        `"Rsvim.sign.place" line must be number type, but found ${line} (${typeof line})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.sign_place(
+      name,
+      line,
+      options?.bufferId ?? 0,
+      options?.group ?? "",
+      options?.priority ?? 10,
+    );
+  }
+
+  /**
+   * Unplace a sign.
+   *
+   * @param {number} id - The sign ID returned by {@link RsvimSign.place}.
+   * @param {number} bufferId - (Optional) The buffer ID, by default it's current buffer.
+   * @returns {boolean} `false` if the sign doesn't exist.
+   */
+  unplace(id: number, bufferId?: number): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.sign_unplace(id, bufferId ?? 0);
+  }
+
+  /**
+   * Unplace the signs of the group, or all the signs.
+   *
+   * @param {string} group - (Optional) The group, by default all the signs are unplaced.
+   * @param {number} bufferId - (Optional) The buffer ID, by default it's current buffer.
+   */
+  clear(group?: string, bufferId?: number): void {
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.sign_clear(group, bufferId ?? 0);
+  }
+
+  /**
+   * Get the placed signs.
+   *
+   * @param {number} bufferId - (Optional) The buffer ID, by default it's current buffer.
+   * @returns {RsvimPlacedSign[] | null} The placed signs, or `null` if the buffer doesn't exist.
+   */
+  getPlaced(bufferId?: number): RsvimPlacedSign[] | null {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.sign_get_placed(bufferId ?? 0);
+  }
+}
+
+/**
+ * The `Rsvim.template` object for templates (skeletons), the new file (i.e. the file doesn't exist)
+ * is populated from the first matched template:
+ *
+ * 1. The rules added by {@link RsvimTemplate.add}, in the added order.
+ * 2. The `templates` folder in config directories, i.e. the file with the same file name
+ *    (`Makefile`), or the `skeleton.{ext}` file with the same extension (`skeleton.rs`).
+ *
+ * The variables `{{filename}}`, `{{name}}` (file name without extension), `{{path}}`, `{{date}}`,
+ * `{{year}}` and `{{author}}` in the template are substituted.
+ *
+ * @see [Vim: autocmd.txt - skeleton](https://vimhelp.org/autocmd.txt.html#skeleton)
+ *
+ * @example
+ * ```javascript
+ * Rsvim.template.add("*.sh", "/home/user/.config/rsvim/sh.tpl");
+ * Rsvim.template.setVariable("author", "Alice");
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimTemplate {
+  /**
+   * Add a template rule.
+   *
+   * @param {string} pattern - The glob pattern, it's matched against the file name if it doesn't
+   * contain `/`, otherwise the absolute file path. The wildcards are `*` and `?`.
+   * @param {string} file - The template file path.
+   * @throws {@link !Error} if pattern or file is not a string value.
+   */
+  add(pattern: string, file: string): void {
+    if (typeof pattern !== "string" || typeof file !== "string") {
+      throw new Error(
+        `"Rsvim.template.add" pattern and file must be string type, but found ${pattern} (${typeof pattern}), ${file} (${typeof file})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.template_add(pattern, file);
+  }
+
+  /**
+   * Set the template variable, it overrides the built-in one with the same name.
+   *
+   * @param {string} name - The variable name.
+   * @param {string} value - The variable value.
+   * @throws {@link !Error} if name or value is not a string value.
+   */
+  setVariable(name: string, value: string): void {
+    if (typeof name !== "string" || typeof value !== "string") {
+      throw new Error(
+        `"Rsvim.template.setVariable" name and value must be string type, but found ${name} (${typeof name}), ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.template_set_variable(name, value);
+  }
+}
+
+/**
+ * The options of {@link RsvimFiletype.set}, the missing options are not changed.
+ */
+export interface RsvimFiletypeOptions {
+  /** The 'commentstring' option, i.e. `// %s`. */
+  commentstring?: string;
+  /** The 'tabstop' option. */
+  tabstop?: number;
+  /** The 'shiftwidth' option. */
+  shiftwidth?: number;
+  /** The 'expandtab' option. */
+  expandtab?: boolean;
+  /** The 'suffixesadd' option, i.e. `.rs`. */
+  suffixesadd?: string;
+}
+
+/**
+ * The `Rsvim.filetype` object for filetypes, the filetype of a file buffer is detected by the first
+ * matched rule:
+ *
+ * 1. The rules added by {@link RsvimFiletype.add}, in the added order.
+ * 2. The built-in rules for the common filetypes, i.e. `*.rs` is `rust`.
+ *
+ * Then the built-in option bundle of the filetype (i.e. 'commentstring', 'shiftwidth',
+ * 'expandtab' and 'suffixesadd') is applied to the buffer, overridden by the options set with
+ * {@link RsvimFiletype.set}. At last the `FileType` event is emitted, see {@link Rsvim.on}.
+ *
+ * @see [Vim: filetype.txt](https://vimhelp.org/filetype.txt.html)
+ *
+ * @example
+ * ```javascript
+ * Rsvim.filetype.add("*.mdx", "markdown");
+ * Rsvim.filetype.set("rust", { shiftwidth: 2 });
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimFiletype {
+  /**
+   * Add a filetype detection rule.
+   *
+   * @param {string} pattern - The glob pattern, it's matched against the file name if it doesn't
+   * contain `/`, otherwise the absolute file path. The wildcards are `*` and `?`.
+   * @param {string} filetype - The filetype.
+   * @throws {@link !Error} if pattern or filetype is not a string value.
+   */
+  add(pattern: string, filetype: string): void {
+    if (typeof pattern !== "string" || typeof filetype !== "string") {
+      throw new Error(
+        `"Rsvim.filetype.add" pattern and filetype must be string type, but found ${pattern} (${typeof pattern}), ${filetype} (${typeof filetype})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.filetype_add(pattern, filetype);
+  }
+
+  /**
+   * Set the options of the filetype, they override the built-in option bundle. It only affects the
+   * buffers opened later.
+   *
+   * @param {string} filetype - The filetype.
+   * @param {RsvimFiletypeOptions} options - The options.
+   * @throws {@link !Error} if filetype or options have invalid types.
+   */
+  set(filetype: string, options: RsvimFiletypeOptions): void {
+    if (typeof filetype !== "string" || typeof options !== "object" || options === null) {
+      throw new Error(
+        `"Rsvim.filetype.set" filetype must be string type and options must be object type, but found ${filetype} (${typeof filetype}), ${options} (${typeof options})`,
+      );
+    }
+    const commentstring = options.commentstring ?? null;
+    const tabstop = options.tabstop ?? null;
+    const shiftwidth = options.shiftwidth ?? null;
+    const expandtab = options.expandtab ?? null;
+    const suffixesadd = options.suffixesadd ?? null;
+    if (
+      (commentstring !== null && typeof commentstring !== "string") ||
+      (tabstop !== null && typeof tabstop !== "number") ||
+      (shiftwidth !== null && typeof shiftwidth !== "number") ||
+      (expandtab !== null && typeof expandtab !== "boolean") ||
+      (suffixesadd !== null && typeof suffixesadd !== "string")
+    ) {
+      throw new Error(
+        `"Rsvim.filetype.set" options have invalid types, found ${JSON.stringify(options)}`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.filetype_set(
+      filetype,
+      commentstring,
+      tabstop,
+      shiftwidth,
+      expandtab,
+      suffixesadd,
+    );
+  }
+}
+
+/**
+ * The registered statusline component, see {@link RsvimStatusline.component}.
+ */
+interface RsvimStatuslineComponent {
+  update: () => void;
+  ids: number[];
+}
+
+/**
+ * The `Rsvim.statusline` object for the statusline components.
+ *
+ * A component (i.e. the git branch, diagnostics count, LSP status) renders a piece of text that's
+ * shown by the `%{name}` item in the _statusline_ option. Each component declares the events
+ * that trigger its update, the `render` callback is only called when these events are emitted
+ * (and when it's registered), its result is cached between them. Thus the statusline doesn't call
+ * JS on each redraw, unlike the function _statusline_ option (see {@link RsvimOpt.statusLine}).
+ *
+ * @example
+ * ```javascript
+ * Rsvim.statusline.component("lines", () => `${Rsvim.buf.getLines(0, 100000).length}L`, [
+ *   "BufReadPost",
+ *   "TextChanged",
+ * ]);
+ * Rsvim.opt.statusLine = "%s %f%m%=%{lines} %l:%c";
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimStatusline {
+  private components: Record<string, RsvimStatuslineComponent> = {};
+
+  /**
+   * Register a component, the previously registered component with the same name is replaced.
+   *
+   * @param {string} name - The component name, it's shown by the `%{name}` item.
+   * @param {() => string} render - The callback that renders the component text.
+   * @param {RsvimEvent[]} events - (Optional) The events that trigger the update, by default the
+   * component is only rendered once, use {@link RsvimStatusline.update} to update it manually.
+   * @throws {@link !Error} if name is not a string value, render is not a function, or events is
+   * not an array.
+   * @throws {@link !TypeError} if the event name is invalid.
+   */
+  component(name: string, render: () => string, events?: RsvimEvent[]): void {
+    if (typeof name !== "string" || typeof render !== "function") {
+      throw new Error(
+        `"Rsvim.statusline.component" name must be string type and render must be function type, but found ${name} (${typeof name}), ${render} (${typeof render})`,
+      );
+    }
+    if (events === undefined) {
+      events = [];
+    }
+    if (!Array.isArray(events)) {
+      throw new Error(
+        `"Rsvim.statusline.component" events must be array type, but found ${events} (${typeof events})`,
+      );
+    }
+    this.remove(name);
+    const update = () => {
+      // @ts-ignore Ignore warning
+      __InternalRsvimGlobalObject.statusline_set_component(name, String(render()));
+    };
+    const ids = events.map((event) =>
+      // @ts-ignore Ignore warning
+      __InternalRsvimGlobalObject.autocmd_on(event, "*", update),
+    );
+    this.components[name] = { update, ids };
+    update();
+  }
+
+  /**
+   * Update the component manually, i.e. call its `render` callback and cache the result.
+   *
+   * @param {string} name - The component name.
+   * @returns {boolean} `false` if the component doesn't exist.
+   */
+  update(name: string): boolean {
+    const component = this.components[name];
+    if (component === undefined) {
+      return false;
+    }
+    component.update();
+    return true;
+  }
+
+  /**
+   * Remove the component, its update triggers are unsubscribed.
+   *
+   * @param {string} name - The component name.
+   * @returns {boolean} `false` if the component doesn't exist.
+   */
+  remove(name: string): boolean {
+    const component = this.components[name];
+    if (component === undefined) {
+      return false;
+    }
+    component.ids.forEach((id) => {
+      // @ts-ignore Ignore warning
+      __InternalRsvimGlobalObject.autocmd_off(id);
+    });
+    delete this.components[name];
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.statusline_remove_component(name);
+  }
+}
+
+/**
+ * The git status.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimGitStatus {
+  /** The branch name, or the abbreviated commit ID if the HEAD is detached. */
+  branch: string;
+  /** Whether there're uncommitted changes of the tracked files. */
+  dirty: boolean;
+  /** The commits ahead of the upstream. */
+  ahead: number;
+  /** The commits behind the upstream. */
+  behind: number;
+}
+
+/**
+ * The `Rsvim.git` object for the git status of current working directory.
+ *
+ * The status is cached, it's refreshed by running `git` on an async job when the editor starts,
+ * and on the `FocusGained` and `BufWritePost` events. It's also shown by the `%{git}` item in the
+ * _statusline_ option, i.e. `main* ↑1↓2` (the `*` means dirty, the arrows mean ahead/behind).
+ *
+ * @example
+ * ```javascript
+ * Rsvim.opt.statusLine = "%s %{git} %f%m%=%l:%c";
+ * // Returns { branch: "main", dirty: true, ahead: 1, behind: 2 }
+ * Rsvim.git.status();
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimGit {
+  /**
+   * Get the cached git status.
+   *
+   * @returns {RsvimGitStatus | null} The git status, or `null` if it's not a git repository or
+   * it's not refreshed yet.
+   */
+  status(): RsvimGitStatus | null {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.git_status();
+  }
+
+  /**
+   * Refresh the git status on an async job, i.e. after the repository is changed by the plugin.
+   */
+  refresh(): void {
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.git_refresh();
+  }
+}
+
+/**
+ * The `Rsvim.spell` object for the user spell dictionary, i.e. the good words added by `zg` and
+ * `:spellgood {word}`.
+ *
+ * The dictionary is saved in the `spell.add` file under the data directory, one word per line.
+ * The added word is checked as good immediately, it's appended to the file on an async task. The
+ * file is read on an async task when the editor starts.
+ *
+ * The word added in lower case also matches its capitalized and upper case forms, i.e. `rsvim`
+ * matches `Rsvim` and `RSVIM`, otherwise the case must match exactly.
+ *
+ * @see [Vim: spell.txt - zg](https://vimhelp.org/spell.txt.html#zg)
+ *
+ * @example
+ * ```javascript
+ * Rsvim.spell.add("rsvim");
+ * // Returns true
+ * Rsvim.spell.check("Rsvim");
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimSpell {
+  /**
+   * Add a good word to the dictionary.
+   *
+   * @param {string} word - The word, it cannot be empty or have whitespaces.
+   * @returns {boolean} `false` if the word is invalid or it's already in the dictionary.
+   * @throws {@link !Error} if word is not a string value.
+   */
+  add(word: string): boolean {
+    if (typeof word !== "string") {
+      throw new Error(
+        `"Rsvim.spell.add" word must be string type, but found ${word} (${typeof word})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.spell_add(word);
+  }
+
+  /**
+   * Check whether the word is good, i.e. it's in the dictionary.
+   *
+   * @param {string} word - The word.
+   * @returns {boolean} `true` if the word is good.
+   * @throws {@link !Error} if word is not a string value.
+   */
+  check(word: string): boolean {
+    if (typeof word !== "string") {
+      throw new Error(
+        `"Rsvim.spell.check" word must be string type, but found ${word} (${typeof word})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.spell_check(word);
+  }
+
+  /**
+   * Get all the good words in the dictionary.
+   *
+   * @returns {string[]} The words in sorted order.
+   */
+  words(): string[] {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.spell_words();
+  }
+
+  /**
+   * Read the dictionary file again on an async task, i.e. after it's changed by the other editor
+   * instances. The words in the file are merged into the dictionary, thus the words added in the
+   * editor are kept.
+   */
+  reload(): void {
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.spell_reload();
+  }
+}
+
+/**
+ * The options of {@link RsvimBuf.setExtmark}.
+ */
+export interface RsvimExtmarkOptions {
+  /** The extmark ID to update, default is to create a new extmark. */
+  id?: number;
+  /** The end line index (0-based), the extmark is a single position if omitted. */
+  endLine?: number;
+  /** The end char index (0-based, exclusive) in the end line, default is `0`. */
+  endCol?: number;
+  /** The highlight style of the chars between the start and the end. */
+  style?: RsvimHlStyle;
+  /** The virtual text shown after the end of the start line. */
+  virtText?: string;
+  /** The highlight style of the virtual text. */
+  virtTextStyle?: RsvimHlStyle;
+  /** Whether the start moves to the end of the text inserted at it, default is `true`. */
+  rightGravity?: boolean;
+  /** Whether the end moves to the end of the text inserted at it, default is `false`. */
+  endRightGravity?: boolean;
+  /** The priority of the highlight style, default is the namespace's priority. */
+  priority?: number;
+}
+
+/**
+ * The extmark position returned by {@link RsvimBuf.getExtmark}.
+ */
+export interface RsvimExtmark {
+  /** The namespace ID. */
+  ns: number;
+  /** The line index (0-based). */
+  line: number;
+  /** The char index (0-based) in the line. */
+  col: number;
+  /** The end line index (0-based), if the extmark has end position. */
+  endLine?: number;
+  /** The end char index (0-based, exclusive) in the end line, if the extmark has end position. */
+  endCol?: number;
+}
+
+/**
+ * The buffer in the buffer list, see {@link RsvimBuf.list}.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimBufInfo {
+  /** The buffer ID, it's unique and stable during the editor lifetime. */
+  id: number;
+  /** The file name (or the URL of the remote file), or `null` for the unnamed buffer. */
+  name: string | null;
+  /** Whether the buffer is listed, the scratch buffers (i.e. `:DiffOrig`) are unlisted. */
+  listed: boolean;
+  /** Whether the buffer is modified since it's loaded/saved. */
+  modified: boolean;
+}
+
+/**
+ * The `Rsvim.buf` object for the lines of current buffer.
+ *
+ * The edits are same with the interactive edits, i.e. they're recorded in the undo tree (one
+ * `setLines` call is undone at once), the marks are adjusted and the buffer is modified.
+ *
+ * @example
+ * ```javascript
+ * // Get the first 10 lines.
+ * const lines = Rsvim.buf.getLines(0, 10);
+ * // Replace the 2nd line with 2 lines.
+ * Rsvim.buf.setLines(1, 2, ["hello", "world"]);
+ * // List the buffers, i.e. `:ls`.
+ * const modified = Rsvim.buf.list().filter((b) => b.modified);
+ *
+ * // Decorate the first 5 chars of the 2nd line, the extmark follows the text when it's edited.
+ * const ns = Rsvim.hl.createNamespace("lint", 150);
+ * Rsvim.buf.setExtmark(ns, 1, 0, {
+ *   endLine: 1,
+ *   endCol: 5,
+ *   style: { underline: true },
+ *   virtText: "unused variable",
+ *   virtTextStyle: { fg: "gray" },
+ * });
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimBuf {
+  /**
+   * Get the lines of current buffer, without line endings.
+   *
+   * @param {number} start - The start line index (inclusive), starts from 0.
+   * @param {number} end - The end line index (exclusive), it's clamped to the lines count.
+   * @returns {string[] | null} The lines, or `null` if there's no current buffer.
+   * @throws {@link !Error} if start or end is not a number value.
+   */
+  getLines(start: number, end: number): string[] | null {
+    if (typeof start !== "number" || typeof end !== "number") {
+      throw new Error(
+        `"Rsvim.buf.getLines" start and end must be number type, but found ${start} (${typeof start}), ${end} (${typeof end})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_get_lines(start, end);
+  }
+
+  /**
+   * Replace the lines of current buffer, the empty range (i.e. `start == end`) inserts the lines
+   * before `start`.
+   *
+   * @param {number} start - The start line index (inclusive), starts from 0.
+   * @param {number} end - The end line index (exclusive), it's clamped to the lines count.
+   * @param {string[]} lines - The new lines, without line endings.
+   * @returns {boolean} `false` if there's no current buffer, or start is out of range.
+   * @throws {@link !Error} if start or end is not a number value, or lines is not an array.
+   * @throws {@link !TypeError} if any line contains line breaks.
+   */
+  setLines(start: number, end: number, lines: string[]): boolean {
+    if (typeof start !== "number" || typeof end !== "number") {
+      throw new Error(
+        `"Rsvim.buf.setLines" start and end must be number type, but found ${start} (${typeof start}), ${end} (${typeof end})`,
+      );
+    }
+    if (!Array.isArray(lines)) {
+      throw new Error(
+        `"Rsvim.buf.setLines" lines must be array type, but found ${lines} (${typeof lines})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_set_lines(start, end, lines);
+  }
+
+  /**
+   * List the buffers ordered by buffer ID, i.e. `:ls`.
+   *
+   * @param {boolean} all - (Optional) Include the unlisted buffers, i.e. `:ls!`. By default is `false`.
+   * @returns {RsvimBufInfo[]} The buffers.
+   * @throws {@link !Error} if all is not a boolean value.
+   */
+  list(all?: boolean): RsvimBufInfo[] {
+    if (all !== undefined && typeof all !== "boolean") {
+      throw new Error(
+        `"Rsvim.buf.list" all must be boolean type, but found ${all} (${typeof all})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_list(all === true);
+  }
+
+  /**
+   * Whether the buffer is modified since it's loaded/saved, i.e. the _modified_ option. The
+   * `BufModifiedSet` event is emitted when it flips, see {@link Rsvim.on}.
+   *
+   * @example
+   * ```javascript
+   * Rsvim.on("BufModifiedSet", (args) => {
+   *   Rsvim.notify(`modified: ${Rsvim.buf.isModified(args.bufferId)}`);
+   * });
+   * ```
+   *
+   * @param {number} id - The buffer ID, see {@link RsvimBufInfo.id}.
+   * @returns {boolean | null} Whether it's modified, or `null` if the buffer doesn't exist.
+   * @throws {@link !Error} if id is not a number value.
+   */
+  isModified(id: number): boolean | null {
+    if (typeof id !== "number") {
+      throw new Error(
+        `"Rsvim.buf.isModified" id must be number type, but found ${id} (${typeof id})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_is_modified(id);
+  }
+
+  /**
+   * Set an extmark in current buffer, i.e. a position (or a range) that follows the text when the
+   * buffer is edited, optionally with a highlight style and a virtual text.
+   *
+   * @see [Nvim: api.txt - extmarks](https://neovim.io/doc/user/api.html#api-extended-marks)
+   *
+   * @param {number} ns - The namespace ID, see {@link RsvimHl.createNamespace}.
+   * @param {number} line - The line index (0-based).
+   * @param {number} col - The char index (0-based) in the line, it's clamped to the line.
+   * @param {RsvimExtmarkOptions} options - (Optional) The end position and decorations.
+   * @returns {number} The extmark ID, or `0` if there's no current buffer or the `id` to update
+   * doesn't exist.
+   * @throws {@link !Error} if line or col is not a number value.
+   * @throws {@link !TypeError} if the color is invalid.
+   */
+  setExtmark(
+    ns: number,
+    line: number,
+    col: number,
+    options?: RsvimExtmarkOptions,
+  ): number {
+    if (typeof line !== "number" || typeof col !== "number") {
+      throw new Error(
+        `"Rsvim.buf.setExtmark" line and col must be number type, but found ${line} (${typeof line}), ${col} (${typeof col})`,
+      );
+    }
+    const style = options?.style;
+    const virtTextStyle = options?.virtTextStyle ?? {};
+    const toAttrs = (style: RsvimHlStyle) =>
+      ["bold", "italic", "underline", "reverse", "strikethrough"]
+        .filter((name) => style[name as keyof RsvimHlStyle] === true)
+        .join(",");
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_set_extmark(
+      ns,
+      line,
+      col,
+      options?.id ?? 0,
+      options?.endLine ?? -1,
+      options?.endCol ?? 0,
+      style !== undefined,
+      style?.fg,
+      style?.bg,
+      style !== undefined ? toAttrs(style) : "",
+      options?.virtText,
+      virtTextStyle.fg,
+      virtTextStyle.bg,
+      toAttrs(virtTextStyle),
+      options?.rightGravity ?? true,
+      options?.endRightGravity ?? false,
+      options?.priority ?? -1,
+    );
+  }
+
+  /**
+   * Get the extmark position in current buffer.
+   *
+   * @param {number} id - The extmark ID.
+   * @returns {RsvimExtmark | null} The extmark position, or `null` if it doesn't exist.
+   */
+  getExtmark(id: number): RsvimExtmark | null {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_get_extmark(id);
+  }
+
+  /**
+   * Delete the extmark in current buffer.
+   *
+   * @param {number} id - The extmark ID.
+   * @returns {boolean} `false` if it doesn't exist.
+   */
+  delExtmark(id: number): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_del_extmark(id);
+  }
+
+  /**
+   * Clear the extmarks of the namespace in current buffer.
+   *
+   * @param {number} ns - (Optional) The namespace ID, clear all the extmarks if omitted.
+   */
+  clearExtmarks(ns?: number): void {
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.buf_clear_extmarks(ns);
+  }
+}
+
+/**
+ * The options of {@link RsvimKeymap.set}.
+ */
+export interface RsvimKeymapOptions {
+  /** The `rhs` keys are not mapped again, default is `true`. */
+  noremap?: boolean;
+  /**
+   * The buffer-local mapping, `true` (or `0`) is current buffer, or it's the buffer ID. Default is
+   * `false`, i.e. the global mapping.
+   */
+  buffer?: boolean | number;
+  /** The description. */
+  desc?: string;
+}
+
+/**
+ * The `Rsvim.keymap` object for key mappings, i.e. Vim's `:map` commands.
+ *
+ * The mode is a string of mode short names, or an array of them:
+ *
+ * - `n`: Normal mode.
+ * - `v`: Visual and select mode.
+ * - `x`: Visual mode.
+ * - `s`: Select mode.
+ * - `o`: Operator-pending mode.
+ * - `i`: Insert mode.
+ * - `c`: Command-line mode.
+ * - `!`: Insert and command-line mode.
+ * - `t`: Terminal mode.
+ * - `""`: Normal, visual, select and operator-pending mode.
+ *
+ * The keys are in Vim's key notation, i.e. `<C-w>v`, `<Leader>ff`, `<Space>`. The `<Leader>` is
+ * replaced with {@link RsvimOpt.mapLeader}. When a typed key sequence is the prefix of a longer
+ * mapping, it waits for more keys until {@link RsvimOpt.timeoutLen}. The buffer-local mappings
+ * take precedence over the global mappings.
+ *
+ * @see [Vim: map.txt](https://vimhelp.org/map.txt.html)
+ *
+ * @example
+ * ```javascript
+ * Rsvim.opt.mapLeader = " ";
+ * Rsvim.keymap.set("n", "<Leader>w", "<C-w>w", { desc: "Next window" });
+ * Rsvim.keymap.set(["i", "c"], "jk", "<Esc>");
+ * Rsvim.keymap.set("n", "<Leader>h", () => {
+ *   Rsvim.buf.setLines(0, 0, ["// Header"]);
+ * }, { buffer: true });
+ * Rsvim.keymap.del("n", "<Leader>w");
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimKeymap {
+  /**
+   * Define a key mapping, the existing mapping of the same `lhs` is replaced.
+   *
+   * @param {string | string[]} mode - The mode short names.
+   * @param {string} lhs - The keys to be mapped.
+   * @param {string | (() => void)} rhs - The keys to be typed, or the callback.
+   * @param {RsvimKeymapOptions} options - (Optional) The options.
+   * @throws {@link !Error} if mode, lhs, rhs or options have invalid types.
+   * @throws {@link !TypeError} if mode contains invalid mode names, or lhs is empty.
+   */
+  set(
+    mode: string | string[],
+    lhs: string,
+    rhs: string | (() => void),
+    options?: RsvimKeymapOptions,
+  ): void {
+    mode = checkKeymapMode("Rsvim.keymap.set", mode);
+    if (typeof lhs !== "string") {
+      throw new Error(
+        `"Rsvim.keymap.set" lhs must be string type, but found ${lhs} (${typeof lhs})`,
+      );
+    }
+    if (typeof rhs !== "string" && typeof rhs !== "function") {
+      throw new Error(
+        `"Rsvim.keymap.set" rhs must be string or function type, but found ${rhs} (${typeof rhs})`,
+      );
+    }
+    options = options ?? {};
+    const noremap = options.noremap ?? true;
+    const desc = options.desc ?? "";
+    if (typeof noremap !== "boolean" || typeof desc !== "string") {
+      throw new Error(
+        `"Rsvim.keymap.set" options.noremap must be boolean type and options.desc must be string type, but found ${noremap} (${typeof noremap}), ${desc} (${typeof desc})`,
+      );
+    }
+    const buffer = checkKeymapBuffer("Rsvim.keymap.set", options.buffer);
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.keymap_set(mode, lhs, rhs, noremap, buffer, desc);
+  }
+
+  /**
+   * Remove a key mapping.
+   *
+   * @param {string | string[]} mode - The mode short names.
+   * @param {string} lhs - The mapped keys.
+   * @param {{buffer?: boolean | number}} options - (Optional) The buffer-local mapping, same with
+   * {@link RsvimKeymapOptions.buffer}.
+   * @returns {boolean} `false` if the mapping doesn't exist.
+   * @throws {@link !Error} if mode, lhs or options have invalid types.
+   * @throws {@link !TypeError} if mode contains invalid mode names.
+   */
+  del(
+    mode: string | string[],
+    lhs: string,
+    options?: { buffer?: boolean | number },
+  ): boolean {
+    mode = checkKeymapMode("Rsvim.keymap.del", mode);
+    if (typeof lhs !== "string") {
+      throw new Error(
+        `"Rsvim.keymap.del" lhs must be string type, but found ${lhs} (${typeof lhs})`,
+      );
+    }
+    const buffer = checkKeymapBuffer("Rsvim.keymap.del", options?.buffer);
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.keymap_del(mode, lhs, buffer);
+  }
+}
+
+// Join the mode short names.
+function checkKeymapMode(api: string, mode: string | string[]): string {
+  if (Array.isArray(mode)) {
+    mode = mode.join("");
+  }
+  if (typeof mode !== "string") {
+    throw new Error(
+      `"${api}" mode must be string or array type, but found ${mode} (${typeof mode})`,
+    );
+  }
+  return mode;
+}
+
+// Convert the buffer option: `-1` is global, `0` is current buffer.
+function checkKeymapBuffer(api: string, buffer?: boolean | number): number {
+  if (buffer === undefined || buffer === false) {
+    return -1;
+  }
+  if (buffer === true) {
+    return 0;
+  }
+  if (typeof buffer !== "number" || !Number.isInteger(buffer) || buffer < 0) {
+    throw new Error(
+      `"${api}" options.buffer must be boolean or non-negative integer type, but found ${buffer} (${typeof buffer})`,
+    );
+  }
+  return buffer;
+}
+
+/**
+ * The options of {@link RsvimJob.spawn}.
+ */
+export interface RsvimJobOptions {
+  /** The working directory, default is the editor's working directory. */
+  cwd?: string;
+  /** Called with each line of stdout, without the line break. */
+  onStdout?: (line: string) => void;
+  /** Called with each line of stderr, without the line break. */
+  onStderr?: (line: string) => void;
+  /**
+   * Called when the job exits, the exit code is `null` if it's terminated by a signal or failed
+   * to start. It's called after all the output lines.
+   */
+  onExit?: (code: number | null) => void;
+}
+
+/**
+ * The signals can be sent to a job, on Windows they all simply kill the job.
+ */
+export type RsvimJobSignal = "SIGTERM" | "SIGKILL" | "SIGINT" | "SIGHUP";
+
+/**
+ * The `Rsvim.job` object for the async jobs, i.e. the external processes like linters,
+ * formatters and fuzzy finders.
+ *
+ * The job runs in the background, its stdout/stderr are line-buffered and delivered to the
+ * callbacks, it never blocks the editor.
+ *
+ * @example
+ * ```javascript
+ * const id = Rsvim.job.spawn(["rg", "--vimgrep", "TODO"], {
+ *   onStdout: (line) => console.log(line),
+ *   onExit: (code) => console.log(`rg exit: ${code}`),
+ * });
+ * Rsvim.job.kill(id);
+ *
+ * const sort = Rsvim.job.spawn(["sort"], { onStdout: (line) => console.log(line) });
+ * Rsvim.job.send(sort, "b\na\n");
+ * Rsvim.job.closeStdin(sort);
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimJob {
+  /**
+   * Spawn a job.
+   *
+   * @param {string[]} cmd - The program and its arguments, it's not run in a shell.
+   * @param {RsvimJobOptions} options - (Optional) The working directory and the callbacks.
+   * @returns {number} The job ID.
+   * @throws Throws {@link !TypeError} if the command is empty.
+   */
+  spawn(cmd: string[], options?: RsvimJobOptions): number {
+    if (!Array.isArray(cmd)) {
+      throw new Error(
+        `"Rsvim.job.spawn" cmd must be array type, but found ${cmd} (${typeof cmd})`,
+      );
+    }
+    const cwd = options?.cwd;
+    if (cwd !== undefined && typeof cwd !== "string") {
+      throw new Error(
+        `"Rsvim.job.spawn" options.cwd must be string type, but found ${cwd} (${typeof cwd})`,
+      );
+    }
+    const onStdout = checkJobCallback("onStdout", options?.onStdout);
+    const onStderr = checkJobCallback("onStderr", options?.onStderr);
+    const onExit = checkJobCallback("onExit", options?.onExit);
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.job_spawn(
+      cmd,
+      cwd,
+      onStdout,
+      onStderr,
+      onExit,
+    );
+  }
+
+  /**
+   * Write data to the job's stdin.
+   *
+   * @param {number} id - The job ID.
+   * @param {string} data - The data, the line breaks are not added.
+   * @returns {boolean} It returns `false` if the job is not running.
+   */
+  send(id: number, data: string): boolean {
+    if (typeof id !== "number" || typeof data !== "string") {
+      throw new Error(
+        `"Rsvim.job.send" id must be number type and data must be string type, but found ${id} (${typeof id}), ${data} (${typeof data})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.job_send(id, data);
+  }
+
+  /**
+   * Close the job's stdin, i.e. send EOF.
+   *
+   * @param {number} id - The job ID.
+   * @returns {boolean} It returns `false` if the job is not running.
+   */
+  closeStdin(id: number): boolean {
+    if (typeof id !== "number") {
+      throw new Error(
+        `"Rsvim.job.closeStdin" id must be number type, but found ${id} (${typeof id})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.job_close_stdin(id);
+  }
+
+  /**
+   * Send a signal to the job.
+   *
+   * @param {number} id - The job ID.
+   * @param {RsvimJobSignal} signal - (Optional) The signal, default is `SIGTERM`.
+   * @returns {boolean} It returns `false` if the job is not running.
+   * @throws Throws {@link !TypeError} if the signal is unknown.
+   */
+  kill(id: number, signal?: RsvimJobSignal): boolean {
+    if (typeof id !== "number") {
+      throw new Error(
+        `"Rsvim.job.kill" id must be number type, but found ${id} (${typeof id})`,
+      );
+    }
+    if (signal === undefined) {
+      signal = "SIGTERM";
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.job_kill(id, signal);
+  }
+}
+
+/**
+ * The plugin package info, see {@link RsvimPlugin.list}.
+ */
+export interface RsvimPluginInfo {
+  /** The package name, i.e. `my-plugin` or `@scope/my-plugin`. */
+  name: string;
+  /** The package directory. */
+  path: string;
+  /** The entry file, or `null` if the package has no entry. */
+  entry: string | null;
+  /** Whether the entry module is loaded, either by {@link RsvimPlugin.load} or `import`. */
+  loaded: boolean;
+}
+
+/**
+ * The `Rsvim.plugin` object for the plugin packages.
+ *
+ * A plugin is a directory (i.e. a multi-file TypeScript project) under the `plugins` directory of
+ * the config directories, i.e. `~/.config/rsvim/plugins/my-plugin`. It's imported by the package
+ * name (the bare specifier) from the user config or the other plugins:
+ *
+ * - `import { setup } from "my-plugin"` imports the package entry, it's detected by the `exports`,
+ *   `module` and `main` fields in its `package.json`, then the `index.{js,ts,...}` file.
+ * - `import { util } from "my-plugin/util"` imports the sub path, it's mapped by the `exports`
+ *   field if it has, otherwise it's relative to the package directory.
+ * - The scoped package is placed in the scope directory, i.e. `plugins/@scope/my-plugin`.
+ *
+ * The modules are compiled once and cached, thus the module imported by multiple plugins shares
+ * the same instance. The cache is cleared when the user config is reloaded, i.e. `:config reload`.
+ *
+ * @example
+ * ```javascript
+ * // Load "~/.config/rsvim/plugins/my-plugin/package.json" entry.
+ * const myPlugin = Rsvim.plugin.load("my-plugin");
+ * myPlugin.setup({ enabled: true });
+ *
+ * // Returns [{ name: "my-plugin", path: "...", entry: ".../index.ts", loaded: true }]
+ * Rsvim.plugin.list();
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimPlugin {
+  /**
+   * Load the plugin package, it's same with the `import` statement but can be called at runtime,
+   * i.e. lazy loading in a key mapping.
+   *
+   * @param {string} name - The package name, or its sub path, i.e. `my-plugin/util`.
+   * @returns {any} The module namespace object, i.e. the exports of the module.
+   * @throws {@link !Error} if name is not a string value, or the plugin is not found, or failed to
+   * evaluate.
+   */
+  load(name: string): any {
+    if (typeof name !== "string") {
+      throw new Error(
+        `"Rsvim.plugin.load" name must be string type, but found ${name} (${typeof name})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.plugin_load(name);
+  }
+
+  /**
+   * List the plugin packages under the plugins directories.
+   *
+   * @returns {RsvimPluginInfo[]} The plugins ordered by name.
+   */
+  list(): RsvimPluginInfo[] {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.plugin_list();
+  }
+}
+
+/**
+ * The file metadata, see {@link RsvimFs.stat}.
+ */
+export interface RsvimFsStat {
+  /** Whether it's a regular file, the symbolic link is followed. */
+  isFile: boolean;
+  /** Whether it's a directory, the symbolic link is followed. */
+  isDirectory: boolean;
+  /** Whether the path itself is a symbolic link. */
+  isSymlink: boolean;
+  /** The file size in bytes. */
+  size: number;
+  /**
+   * The last modification time in milliseconds since the Unix epoch, or `null` if the platform
+   * doesn't support it.
+   */
+  mtime: number | null;
+  /** Whether the file is read-only. */
+  readonly: boolean;
+}
+
+/**
+ * The directory entry, see {@link RsvimFs.readDir}.
+ */
+export interface RsvimFsDirEntry {
+  /** The file name, without the directory. */
+  name: string;
+  /** Whether it's a regular file, the symbolic link is followed. */
+  isFile: boolean;
+  /** Whether it's a directory, the symbolic link is followed. */
+  isDirectory: boolean;
+  /** Whether the entry itself is a symbolic link. */
+  isSymlink: boolean;
+}
+
+/**
+ * The options of {@link RsvimFs.findUp}.
+ */
+export interface RsvimFsFindUpOptions {
+  /** The directory to start from, default is the editor's working directory. */
+  path?: string;
+  /** Only find the files or the directories, default is both. */
+  type?: "file" | "directory";
+}
+
+/**
+ * The options of {@link RsvimFs.walk}.
+ */
+export interface RsvimFsWalkOptions {
+  /**
+   * The glob pattern (the wildcards are `*` and `?`), it's matched against the file path relative
+   * to the walked directory with `/` separators, i.e. `*.ts` or `src/*.ts`. The `*` also matches
+   * `/`. Default is all files.
+   */
+  glob?: string;
+  /** Whether to include the hidden files and directories, i.e. `.git`. Default is `false`. */
+  hidden?: boolean;
+  /** The max depth, the files directly in the walked directory are at depth 1. */
+  maxDepth?: number;
+}
+
+/**
+ * The `Rsvim.fs` object for the file system.
+ *
+ * The file operations are async, they run in the background and return promises, thus the plugins
+ * never block the editor. The promise is rejected with an {@link !Error} if the operation fails.
+ * The relative paths are relative to the editor's working directory.
+ *
+ * @example
+ * ```javascript
+ * const root = await Rsvim.fs.findUp([".git", "package.json"]);
+ * if (root !== null) {
+ *   const dir = Rsvim.fs.join(root, "..");
+ *   const files = await Rsvim.fs.walk(dir, { glob: "*.ts" });
+ *   for (const file of files) {
+ *     const text = await Rsvim.fs.readFile(file);
+ *   }
+ * }
+ *
+ * await Rsvim.fs.mkdir("/tmp/rsvim/notes", { recursive: true });
+ * await Rsvim.fs.writeFile("/tmp/rsvim/notes/todo.md", "- [ ] todo\n", { append: true });
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimFs {
+  /**
+   * Read the file as UTF-8 text, the invalid sequences are replaced.
+   *
+   * @param {string} path - The file path.
+   * @returns {Promise<string>} The file contents.
+   */
+  readFile(path: string): Promise<string> {
+    checkFsPath("readFile", path);
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_read_file(path);
+  }
+
+  /**
+   * Write the data to the file, the file is created if not exists.
+   *
+   * @param {string} path - The file path.
+   * @param {string | Uint8Array} data - The data, the string is written in UTF-8.
+   * @param {{append?: boolean}} options - (Optional) Append to the file instead of truncating it,
+   * default is `false`.
+   * @returns {Promise<void>}
+   */
+  writeFile(
+    path: string,
+    data: string | Uint8Array,
+    options?: { append?: boolean },
+  ): Promise<void> {
+    checkFsPath("writeFile", path);
+    if (typeof data !== "string" && !(data instanceof Uint8Array)) {
+      throw new Error(
+        `"Rsvim.fs.writeFile" data must be string or Uint8Array type, but found ${data} (${typeof data})`,
+      );
+    }
+    const append = options?.append ?? false;
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_write_file(path, data, append);
+  }
+
+  /**
+   * Get the file metadata, the symbolic link is followed.
+   *
+   * @param {string} path - The file path.
+   * @returns {Promise<RsvimFsStat>} The metadata.
+   */
+  stat(path: string): Promise<RsvimFsStat> {
+    checkFsPath("stat", path);
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_stat(path);
+  }
+
+  /**
+   * Whether the file or directory exists, it never rejects.
+   *
+   * @param {string} path - The file path.
+   * @returns {Promise<boolean>}
+   */
+  exists(path: string): Promise<boolean> {
+    checkFsPath("exists", path);
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_exists(path);
+  }
+
+  /**
+   * List the directory entries, without `.` and `..`.
+   *
+   * @param {string} path - The directory path.
+   * @returns {Promise<RsvimFsDirEntry[]>} The entries ordered by name.
+   */
+  readDir(path: string): Promise<RsvimFsDirEntry[]> {
+    checkFsPath("readDir", path);
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_read_dir(path);
+  }
+
+  /**
+   * Create the directory.
+   *
+   * @param {string} path - The directory path.
+   * @param {{recursive?: boolean}} options - (Optional) Create the parent directories as well,
+   * and don't fail if the directory exists. Default is `false`.
+   * @returns {Promise<void>}
+   */
+  mkdir(path: string, options?: { recursive?: boolean }): Promise<void> {
+    checkFsPath("mkdir", path);
+    const recursive = options?.recursive ?? false;
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_mkdir(path, recursive);
+  }
+
+  /**
+   * Remove the file or directory, the symbolic link itself is removed instead of its target.
+   *
+   * @param {string} path - The file path.
+   * @param {{recursive?: boolean}} options - (Optional) Remove the non-empty directory with its
+   * contents. Default is `false`.
+   * @returns {Promise<void>}
+   */
+  remove(path: string, options?: { recursive?: boolean }): Promise<void> {
+    checkFsPath("remove", path);
+    const recursive = options?.recursive ?? false;
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_remove(path, recursive);
+  }
+
+  /**
+   * Find the first of the names in the directory, then its parent directory, until the root
+   * directory. It's useful to find the project root.
+   *
+   * @param {string | string[]} names - The file names, i.e. `[".git", "package.json"]`.
+   * @param {RsvimFsFindUpOptions} options - (Optional) The start directory and the file type.
+   * @returns {Promise<string | null>} The found path, or `null` if it's not found.
+   */
+  findUp(
+    names: string | string[],
+    options?: RsvimFsFindUpOptions,
+  ): Promise<string | null> {
+    if (typeof names === "string") {
+      names = [names];
+    }
+    if (!Array.isArray(names)) {
+      throw new Error(
+        `"Rsvim.fs.findUp" names must be string or array type, but found ${names} (${typeof names})`,
+      );
+    }
+    const path = options?.path ?? ".";
+    checkFsPath("findUp", path);
+    const type = options?.type ?? "";
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_find_up(names, path, type);
+  }
+
+  /**
+   * Walk the directory recursively, the symbolic links to directories are not followed.
+   *
+   * @param {string} path - The directory path.
+   * @param {RsvimFsWalkOptions} options - (Optional) The glob filter, hidden files and max depth.
+   * @returns {Promise<string[]>} The file paths (joined with `path`) in sorted order.
+   */
+  walk(path: string, options?: RsvimFsWalkOptions): Promise<string[]> {
+    checkFsPath("walk", path);
+    const glob = options?.glob;
+    if (glob !== undefined && typeof glob !== "string") {
+      throw new Error(
+        `"Rsvim.fs.walk" options.glob must be string type, but found ${glob} (${typeof glob})`,
+      );
+    }
+    const hidden = options?.hidden ?? false;
+    const maxDepth = options?.maxDepth;
+    if (maxDepth !== undefined && typeof maxDepth !== "number") {
+      throw new Error(
+        `"Rsvim.fs.walk" options.maxDepth must be number type, but found ${maxDepth} (${typeof maxDepth})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_walk(path, glob, hidden, maxDepth);
+  }
+
+  /**
+   * Join the paths and normalize it, the absolute path replaces the previous ones.
+   *
+   * @example
+   * ```javascript
+   * Rsvim.fs.join("a", "b/../c"); // "a/c"
+   * Rsvim.fs.join("a", "/b"); // "/b"
+   * ```
+   *
+   * @param {...string} paths - The paths.
+   * @returns {string} The joined path.
+   */
+  join(...paths: string[]): string {
+    for (const path of paths) {
+      checkFsPath("join", path);
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_join(paths);
+  }
+
+  /**
+   * Normalize the path lexically, i.e. remove the `.` components and resolve the `..` components.
+   * The file system is not accessed, thus the symbolic links are not resolved.
+   *
+   * @param {string} path - The path.
+   * @returns {string} The normalized path, it's `.` if the path is empty.
+   */
+  normalize(path: string): string {
+    checkFsPath("normalize", path);
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.fs_normalize(path);
+  }
+}
+
+/**
+ * The item of {@link RsvimPicker.open}, it's the label, or the label with a description shown
+ * after it. The items are fuzzy matched on the labels.
+ *
+ * @category Editor APIs
+ */
+export type RsvimPickerItem = string | { label: string; description?: string };
+
+/**
+ * The {@link RsvimPicker.open} options.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimPickerOptions {
+  /**
+   * The items, it's either:
+   *
+   * - `"files"`: The files in the working directory, the ignore files (i.e. `.gitignore`) and the
+   *   hidden files are skipped. The files are walked in the background.
+   * - `"buffers"`: The listed buffers, the description is the buffer ID, i.e. `#3`.
+   * - An array, or an iterable, or an async iterable of the items. They're pushed to the picker
+   *   batch by batch, and the iteration stops once the picker is closed.
+   */
+  source:
+    | "files"
+    | "buffers"
+    | Iterable<RsvimPickerItem>
+    | AsyncIterable<RsvimPickerItem>;
+  /**
+   * Called with the accepted item and its index in the source. For the `"files"` and `"buffers"`
+   * sources, the item is `{label, description}`.
+   */
+  onAccept: (item: RsvimPickerItem, index: number) => void;
+  /** (Optional) Called if the picker is cancelled. */
+  onCancel?: () => void;
+  /** (Optional) The title, by default it's `Files`, `Buffers` or `Picker`. */
+  title?: string;
+}
+
+/**
+ * The `Rsvim.picker` object for the fuzzy finder.
+ *
+ * The picker is a floating prompt with the results list on top of the windows, the items are
+ * fuzzy matched on the query (smart case) and sorted by the score, the matched chars are
+ * highlighted with the `PmenuMatch` group. The items are populated while the picker is shown, the
+ * title shows the count of the matched items and all the items, with the `...` suffix while
+ * populating.
+ *
+ * The keys are: `<CR>` to accept, `<Esc>` (or `<C-c>`) to cancel, `<Down>`/`<C-n>`/`<Tab>` for
+ * the next item, `<Up>`/`<C-p>`/`<S-Tab>` for the previous item, `<BS>` to delete the last char,
+ * `<C-u>` to clear the query.
+ *
+ * @example
+ * ```javascript
+ * Rsvim.keymap.set("n", "<leader>f", () => {
+ *   Rsvim.picker.open({
+ *     source: "files",
+ *     onAccept: (item) => Rsvim.cmd(`edit ${item.label}`),
+ *   });
+ * });
+ *
+ * // Stream the items from an async generator.
+ * async function* todos() {
+ *   for (const file of await Rsvim.fs.walk(".", { glob: "*.md" })) {
+ *     const text = await Rsvim.fs.readFile(file);
+ *     for (const line of text.split("\n")) {
+ *       if (line.includes("TODO")) {
+ *         yield { label: line.trim(), description: file };
+ *       }
+ *     }
+ *   }
+ * }
+ * Rsvim.picker.open({
+ *   source: todos(),
+ *   title: "TODO",
+ *   onAccept: (item) => Rsvim.cmd(`edit ${item.description}`),
+ * });
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimPicker {
+  /**
+   * Open the picker, the opened picker is cancelled and replaced. In the batch mode (see
+   * {@link RsvimInput.batchBegin}), the picker is not opened and it's cancelled.
+   *
+   * @param {RsvimPickerOptions} options - The source, the callbacks and the title.
+   * @returns {number} The picker ID, or `-1` in the batch mode.
+   * @throws {@link !Error} if the options are invalid.
+   */
+  open(options: RsvimPickerOptions): number {
+    if (typeof options !== "object" || options === null) {
+      throw new Error(
+        `"Rsvim.picker.open" options must be object type, but found ${options} (${typeof options})`,
+      );
+    }
+    const source = options.source;
+    const builtin = source === "files" || source === "buffers";
+    if (
+      !builtin &&
+      !(
+        typeof source === "object" &&
+        source !== null &&
+        (Symbol.iterator in source || Symbol.asyncIterator in source)
+      )
+    ) {
+      throw new Error(
+        `"Rsvim.picker.open" options.source must be "files", "buffers" or iterable type, but found ${source} (${typeof source})`,
+      );
+    }
+    const onAccept = options.onAccept;
+    if (typeof onAccept !== "function") {
+      throw new Error(
+        `"Rsvim.picker.open" options.onAccept must be function type, but found ${onAccept} (${typeof onAccept})`,
+      );
+    }
+    const onCancel = options.onCancel;
+    if (onCancel !== undefined && typeof onCancel !== "function") {
+      throw new Error(
+        `"Rsvim.picker.open" options.onCancel must be function type, but found ${onCancel} (${typeof onCancel})`,
+      );
+    }
+    const title =
+      options.title ??
+      (source === "files" ? "Files" : source === "buffers" ? "Buffers" : "Picker");
+    if (typeof title !== "string") {
+      throw new Error(
+        `"Rsvim.picker.open" options.title must be string type, but found ${title} (${typeof title})`,
+      );
+    }
+
+    // @ts-ignore Ignore warning
+    if (__InternalRsvimGlobalObject.input_is_batch()) {
+      onCancel?.();
+      return -1;
+    }
+    // The pushed items, the accepted index is in them.
+    const items: RsvimPickerItem[] = [];
+    // @ts-ignore Ignore warning
+    const id: number = __InternalRsvimGlobalObject.picker_open(
+      title,
+      builtin ? source : null,
+      (index: number | null, label: string | null, description: string | null) => {
+        if (index === null) {
+          onCancel?.();
+        } else if (builtin) {
+          onAccept({ label: label as string, description: description as string }, index);
+        } else {
+          onAccept(items[index], index);
+        }
+      },
+    );
+    if (!builtin) {
+      populatePicker(
+        id,
+        source as Iterable<RsvimPickerItem> | AsyncIterable<RsvimPickerItem>,
+        items,
+      );
+    }
+    return id;
+  }
+}
+
+// Check the job callback is a function or undefined.
+function checkJobCallback(
+  name: string,
+  callback: unknown,
+): ((arg: unknown) => void) | undefined {
+  if (callback !== undefined && typeof callback !== "function") {
+    throw new Error(
+      `"Rsvim.job.spawn" options.${name} must be function type, but found ${callback} (${typeof callback})`,
+    );
+  }
+  return callback as ((arg: unknown) => void) | undefined;
+}
+
+// Check the path of `Rsvim.fs` APIs is a string.
+function checkFsPath(api: string, path: unknown) {
+  if (typeof path !== "string") {
+    throw new Error(
+      `"Rsvim.fs.${api}" path must be string type, but found ${path} (${typeof path})`,
+    );
+  }
+}
+
+// The max items pushed to the picker at once.
+const PICKER_BATCH_SIZE = 1024;
+
+// The max milliseconds the popped items wait before they're pushed to the picker.
+const PICKER_BATCH_INTERVAL = 50;
+
+// Get the label and description of the picker item.
+function pickerItemText(item: unknown): [string, string] {
+  if (typeof item === "string") {
+    return [item, ""];
+  }
+  if (
+    typeof item === "object" &&
+    item !== null &&
+    typeof (item as { label: unknown }).label === "string"
+  ) {
+    const { label, description } = item as { label: string; description?: unknown };
+    return [label, description === undefined ? "" : String(description)];
+  }
+  throw new Error(
+    `"Rsvim.picker.open" item must be string or {label, description} type, but found ${item} (${typeof item})`,
+  );
+}
+
+// Push the items of the source to the picker batch by batch, the editor renders and handles the
+// keys between the batches. It stops once the picker is closed.
+function populatePicker(
+  id: number,
+  source: Iterable<RsvimPickerItem> | AsyncIterable<RsvimPickerItem>,
+  items: RsvimPickerItem[],
+) {
+  const labels: string[] = [];
+  const descriptions: string[] = [];
+  let timer: ReturnType<typeof setTimeout> | undefined = undefined;
+  let closed = false;
+
+  // Push the popped items, returns `false` if the picker is closed.
+  const flush = (): boolean => {
+    if (timer !== undefined) {
+      clearTimeout(timer);
+      timer = undefined;
+    }
+    if (!closed && labels.length > 0) {
+      // @ts-ignore Ignore warning
+      closed = !__InternalRsvimGlobalObject.picker_push(
+        id,
+        labels.splice(0),
+        descriptions.splice(0),
+      );
+    }
+    return !closed;
+  };
+  // Pop an item, returns `false` if the picker is closed.
+  const add = (item: RsvimPickerItem): boolean => {
+    if (closed) {
+      return false;
+    }
+    const [label, description] = pickerItemText(item);
+    items.push(item);
+    labels.push(label);
+    descriptions.push(description);
+    if (labels.length >= PICKER_BATCH_SIZE) {
+      return flush();
+    }
+    if (timer === undefined) {
+      timer = setTimeout(flush, PICKER_BATCH_INTERVAL);
+    }
+    return true;
+  };
+  const finish = () => {
+    if (flush()) {
+      // @ts-ignore Ignore warning
+      __InternalRsvimGlobalObject.picker_done(id);
+    }
+  };
+
+  if (Symbol.asyncIterator in source) {
+    const iterator = (source as AsyncIterable<RsvimPickerItem>)[
+      Symbol.asyncIterator
+    ]();
+    const step = () => {
+      iterator.next().then(
+        (next) => {
+          if (next.done) {
+            finish();
+          } else if (add(next.value)) {
+            step();
+          } else {
+            iterator.return?.();
+          }
+        },
+        (error) => {
+          finish();
+          throw error;
+        },
+      );
+    };
+    step();
+  } else {
+    const iterator = (source as Iterable<RsvimPickerItem>)[Symbol.iterator]();
+    const step = () => {
+      for (let i = 0; i < PICKER_BATCH_SIZE; i++) {
+        const next = iterator.next();
+        if (next.done) {
+          finish();
+          return;
+        }
+        if (!add(next.value)) {
+          iterator.return?.();
+          return;
+        }
+      }
+      if (flush()) {
+        setTimeout(step, 0);
+      } else {
+        iterator.return?.();
+      }
+    };
+    step();
+  }
 }
 
 (function (globalThis: { Rsvim: Rsvim }) {
@@ -0,0 +1,128 @@
+//! Source maps of the transpiled modules (see [`transpiler`](crate::js::transpiler)), thus the
+//! errors thrown from the TypeScript/JSX modules point at the original source, i.e. the error
+//! position and the stack trace shown in the messages and written to the log.
+//!
+//! The source map is registered by the module loader once the module is transpiled or read from
+//! the transpile cache (see [`cache`](crate::js::cache)). It's keyed by the module file path, which
+//! is also the script resource name in V8.
+
+use ahash::AHashMap as HashMap;
+use parking_lot::RwLock;
+use regex::Regex;
+use sourcemap::SourceMap;
+use std::sync::OnceLock;
+use tracing::error;
+
+fn source_maps() -> &'static RwLock<HashMap<String, SourceMap>> {
+  static VALUE: OnceLock<RwLock<HashMap<String, SourceMap>>> = OnceLock::new();
+  VALUE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+// The position at the end of a stack frame, i.e. `at foo (/a/b.ts:3:9)` or `at /a/b.ts:3:9`.
+fn stack_frame_regex() -> &'static Regex {
+  static VALUE: OnceLock<Regex> = OnceLock::new();
+  VALUE.get_or_init(|| {
+    Regex::new(
+      r"^(?P<prefix>\s*at (?:.*\()?)(?P<file>.+?):(?P<line>\d+):(?P<column>\d+)(?P<suffix>\)?)$",
+    )
+    .unwrap()
+  })
+}
+
+/// Register the source map (in JSON) of the module `filename`, it replaces the old one. The
+/// invalid source map is ignored.
+pub fn register(filename: &str, source_map: &str) {
+  match SourceMap::from_slice(source_map.as_bytes()) {
+    Ok(source_map) => {
+      source_maps()
+        .write()
+        .insert(filename.to_string(), source_map);
+    }
+    Err(e) => error!("Invalid source map of {:?}: {:?}", filename, e),
+  }
+}
+
+/// Map the position in the transpiled code of module `filename` to the original source, the
+/// `line` and `column` are 0-based. Returns `None` if the module has no source map.
+pub fn lookup(filename: &str, line: u32, column: u32) -> Option<(u32, u32)> {
+  let source_maps = source_maps().read();
+  let token = source_maps.get(filename)?.lookup_token(line, column)?;
+  Some((token.get_src_line(), token.get_src_col()))
+}
+
+/// Map the positions in the V8 stack trace to the original source, the frames of the modules
+/// without source map are kept.
+pub fn map_stack(stack: &str) -> String {
+  stack
+    .lines()
+    .map(|frame| {
+      let captures = match stack_frame_regex().captures(frame) {
+        Some(captures) => captures,
+        None => return frame.to_string(),
+      };
+      let file = &captures["file"];
+      // The stack trace positions are 1-based.
+      let position = captures["line"]
+        .parse::<u32>()
+        .ok()
+        .zip(captures["column"].parse::<u32>().ok())
+        .and_then(|(line, column)| lookup(file, line.saturating_sub(1), column.saturating_sub(1)));
+      match position {
+        Some((line, column)) => format!(
+          "{}{}:{}:{}{}",
+          &captures["prefix"],
+          file,
+          line + 1,
+          column + 1,
+          &captures["suffix"]
+        ),
+        None => frame.to_string(),
+      }
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::js::transpiler::TypeScript;
+
+  #[test]
+  fn map_stack1() {
+    let filename = "/source_map/map_stack1.ts";
+    let source = "type T = string;\n\nfunction fail(message: T): void {\n  throw new Error(message);\n}\nfail(\"oops\");\n";
+    let output = TypeScript::compile_with_source_map(Some(filename), source, None).unwrap();
+    // The type alias is removed, thus the transpiled `throw` is at the 2nd line.
+    let (line, column) = output
+      .code
+      .lines()
+      .enumerate()
+      .find_map(|(i, line)| line.find("throw").map(|column| (i as u32, column as u32)))
+      .unwrap();
+    assert_eq!(line, 1);
+
+    assert_eq!(lookup(filename, line, column), None);
+    register(filename, &output.source_map);
+    assert_eq!(lookup(filename, line, column), Some((3, 2)));
+
+    let stack = format!(
+      "Error: oops\n    at fail ({}:{}:{})\n    at {}:4:1\n    at /other.js:1:1",
+      filename,
+      line + 1,
+      column + 1,
+      filename
+    );
+    assert_eq!(
+      map_stack(&stack),
+      format!(
+        "Error: oops\n    at fail ({}:4:3)\n    at {}:6:1\n    at /other.js:1:1",
+        filename, filename
+      )
+    );
+
+    // The invalid source map is ignored.
+    register("/source_map/invalid.ts", "{");
+    assert_eq!(lookup("/source_map/invalid.ts", 0, 0), None);
+  }
+}
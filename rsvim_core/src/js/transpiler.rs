@@ -8,7 +8,9 @@ use std::sync::OnceLock;
 use swc_common::comments::SingleThreadedComments;
 use swc_common::errors::ColorConfig;
 use swc_common::errors::Handler;
+use swc_common::source_map::LineCol;
 use swc_common::sync::Lrc;
+use swc_common::BytePos;
 use swc_common::FileName;
 use swc_common::Globals;
 use swc_common::Mark;
@@ -35,11 +37,48 @@ fn init_pragma_regex() -> Regex {
   Regex::new(r"@jsx\s+([^\s]+)").unwrap()
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The transpiled module.
+pub struct TranspileOutput {
+  /// The JavaScript code.
+  pub code: String,
+  /// The source map (in JSON) of the JavaScript code to the source code.
+  pub source_map: String,
+}
+
+// Build the source map (in JSON) from the mappings of the emitted code, the `input_map` is the
+// source map of the source code itself (if any).
+fn build_source_map(
+  cm: &SourceMap,
+  mappings: &[(BytePos, LineCol)],
+  input_map: Option<&str>,
+) -> AnyResult<String> {
+  let input_map = input_map
+    .map(|input_map| sourcemap::SourceMap::from_slice(input_map.as_bytes()))
+    .transpose()?;
+  let source_map = cm.build_source_map_from(mappings, input_map.as_ref());
+  let mut buffer = vec![];
+  source_map.to_writer(&mut buffer)?;
+  Ok(String::from_utf8_lossy(&buffer).to_string())
+}
+
 pub struct TypeScript;
 
 impl TypeScript {
   /// Compiles TypeScript code into JavaScript.
   pub fn compile(filename: Option<&str>, source: &str) -> AnyResult<String> {
+    Self::compile_with_source_map(filename, source, None).map(|output| output.code)
+  }
+
+  /// Compiles TypeScript code into JavaScript, with the source map to the TypeScript code.
+  ///
+  /// The `input_map` is the source map of the TypeScript code if it's generated (i.e. from TSX),
+  /// then the output source map points to the original source.
+  pub fn compile_with_source_map(
+    filename: Option<&str>,
+    source: &str,
+    input_map: Option<&str>,
+  ) -> AnyResult<TranspileOutput> {
     let globals = Globals::default();
     let cm: Lrc<SourceMap> = Default::default();
     let handler = Handler::with_tty_emitter(ColorConfig::Auto, true, false, Some(cm.clone()));
@@ -76,6 +115,7 @@ impl TypeScript {
 
     // This is where we're gonna store the JavaScript output.
     let mut buffer = vec![];
+    let mut mappings = vec![];
 
     GLOBALS.set(&globals, || {
       // Apply the rest SWC transforms to generated code.
@@ -90,14 +130,17 @@ impl TypeScript {
           cfg: swc_ecma_codegen::Config::default(),
           cm: cm.clone(),
           comments: None,
-          wr: JsWriter::new(cm, "\n", &mut buffer, None),
+          wr: JsWriter::new(cm.clone(), "\n", &mut buffer, Some(&mut mappings)),
         };
 
         emitter.emit_program(&program).unwrap();
       }
     });
 
-    Ok(String::from_utf8_lossy(&buffer).to_string())
+    Ok(TranspileOutput {
+      code: String::from_utf8_lossy(&buffer).to_string(),
+      source_map: build_source_map(&cm, &mappings, input_map)?,
+    })
   }
 }
 
@@ -106,6 +149,14 @@ pub struct Jsx;
 impl Jsx {
   /// Compiles JSX code into JavaScript.
   pub fn compile(filename: Option<&str>, source: &str) -> AnyResult<String> {
+    Self::compile_with_source_map(filename, source).map(|output| output.code)
+  }
+
+  /// Compiles JSX code into JavaScript, with the source map to the JSX code.
+  pub fn compile_with_source_map(
+    filename: Option<&str>,
+    source: &str,
+  ) -> AnyResult<TranspileOutput> {
     let globals = Globals::default();
     let cm: Lrc<SourceMap> = Default::default();
     let handler = Handler::with_tty_emitter(ColorConfig::Auto, true, false, Some(cm.clone()));
@@ -144,6 +195,7 @@ impl Jsx {
 
     // This is where we're gonna store the JavaScript output.
     let mut buffer = vec![];
+    let mut mappings = vec![];
 
     // Look for the JSX pragma in the source code.
     // https://www.gatsbyjs.com/blog/2019-08-02-what-is-jsx-pragma/
@@ -172,14 +224,17 @@ impl Jsx {
           cfg: swc_ecma_codegen::Config::default(),
           cm: cm.clone(),
           comments: None,
-          wr: JsWriter::new(cm, "\n", &mut buffer, None),
+          wr: JsWriter::new(cm.clone(), "\n", &mut buffer, Some(&mut mappings)),
         };
 
         emitter.emit_module(&module).unwrap();
       }
     });
 
-    Ok(String::from_utf8_lossy(&buffer).to_string())
+    Ok(TranspileOutput {
+      code: String::from_utf8_lossy(&buffer).to_string(),
+      source_map: build_source_map(&cm, &mappings, None)?,
+    })
   }
 }
 
@@ -1,14 +1,20 @@
 //! The core library for the [RSVIM](https://github.com/rsvim/rsvim) editor.
 
+pub mod api;
 pub mod buf;
 pub mod cart;
 pub mod cli;
+pub mod clipboard;
 pub mod defaults;
+pub mod diff;
 pub mod envar;
 pub mod evloop;
+pub mod interrupt;
 pub mod js;
 pub mod locks;
 pub mod log;
+#[cfg(feature = "lua")]
+pub mod lua;
 pub mod res;
 pub mod state;
 pub mod test;
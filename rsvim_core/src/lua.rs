@@ -0,0 +1,157 @@
+//! The Lua runtime, it requires the `lua` feature.
+//!
+//! It runs the `.lua` scripts alongside the js runtime, i.e. `:source {file}.lua`, thus the Neovim
+//! plugins can be ported with less changes. The editor API is exposed as the global `rsvim` table
+//! (see [`binding`]), it has the same functions with the `Rsvim` js namespace in snake case, and
+//! both of them are implemented by the shared [`api`](crate::api) layer.
+//!
+//! The modules are searched by `require` in the `lua` directory under each config directory (see
+//! [`CONFIG_DIRS_PATH`](crate::envar::CONFIG_DIRS_PATH)) and under each plugin package (see
+//! [`plugin_dirs`]), same with Neovim's runtime path.
+
+use crate::api::EditorApi;
+use crate::envar;
+use crate::interrupt::InterruptArc;
+use crate::js::loader::plugin_dirs;
+use crate::res::AnyResult;
+
+use mlua::{HookTriggers, Lua};
+use std::path::{Path, PathBuf};
+use tracing::trace;
+
+pub mod binding;
+
+/// The Lua modules directory name, under the config directories and the plugin packages.
+pub const LUA_DIR: &str = "lua";
+
+// Check the Ctrl-C interrupt every N instructions.
+const INTERRUPT_CHECK_INSTRUCTIONS: u32 = 10000;
+
+/// Get the Lua modules directories, i.e. the `lua` directory under each config directory and each
+/// plugin package. The former one is preferred if a module exists in multiple directories.
+pub fn lua_dirs(config_dirs: &[PathBuf], plugin_dirs: &[PathBuf]) -> Vec<PathBuf> {
+  let mut dirs: Vec<PathBuf> = config_dirs.iter().map(|dir| dir.join(LUA_DIR)).collect();
+  for plugin_dir in plugin_dirs {
+    let mut packages = match std::fs::read_dir(plugin_dir) {
+      Ok(entries) => entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(LUA_DIR))
+        .filter(|dir| dir.is_dir())
+        .collect::<Vec<_>>(),
+      Err(_) => continue,
+    };
+    packages.sort();
+    dirs.extend(packages);
+  }
+  dirs
+}
+
+/// Format the `package.path` templates of the Lua modules directories, i.e. `{dir}/?.lua` and
+/// `{dir}/?/init.lua`.
+pub fn package_path(dirs: &[PathBuf]) -> String {
+  dirs
+    .iter()
+    .flat_map(|dir| {
+      [
+        dir.join("?.lua").to_string_lossy().to_string(),
+        dir.join("?").join("init.lua").to_string_lossy().to_string(),
+      ]
+    })
+    .collect::<Vec<_>>()
+    .join(";")
+}
+
+/// The Lua runtime.
+pub struct LuaRuntime {
+  lua: Lua,
+}
+
+impl std::fmt::Debug for LuaRuntime {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("LuaRuntime").finish()
+  }
+}
+
+impl LuaRuntime {
+  /// Make new Lua runtime over the editor API, the running script is stopped once the
+  /// `interrupt` is set, i.e. Ctrl-C.
+  pub fn new(api: EditorApi, interrupt: InterruptArc) -> AnyResult<Self> {
+    let lua = Lua::new();
+    binding::create_globals(&lua, api)?;
+    lua.set_hook(
+      HookTriggers::new().every_nth_instruction(INTERRUPT_CHECK_INSTRUCTIONS),
+      move |_, _| {
+        if interrupt.is_interrupted() {
+          Err(mlua::Error::RuntimeError("Interrupted".to_string()))
+        } else {
+          Ok(())
+        }
+      },
+    );
+
+    // Prepend the editor's directories, the default search paths are kept.
+    let dirs = lua_dirs(&envar::CONFIG_DIRS_PATH(), &plugin_dirs());
+    {
+      let package: mlua::Table = lua.globals().get("package")?;
+      let default_path: String = package.get("path")?;
+      let path = format!("{};{}", package_path(&dirs), default_path);
+      trace!("Lua package.path: {:?}", path);
+      package.set("path", path)?;
+    }
+
+    Ok(LuaRuntime { lua })
+  }
+
+  /// Execute the Lua script file.
+  pub fn execute_file(&self, filename: &Path) -> AnyResult<()> {
+    let source = std::fs::read_to_string(filename)?;
+    self.execute(&source, &filename.to_string_lossy())
+  }
+
+  /// Execute the Lua source code, the `name` is shown in the error messages and the tracebacks.
+  pub fn execute(&self, source: &str, name: &str) -> AnyResult<()> {
+    trace!("Execute Lua chunk {:?}", name);
+    self
+      .lua
+      .load(source)
+      .set_name(format!("@{}", name))
+      .exec()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use assert_fs::prelude::*;
+
+  #[test]
+  fn lua_dirs1() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    tmp.child("plugins/b/lua/b.lua").touch().unwrap();
+    tmp.child("plugins/a/lua/a/init.lua").touch().unwrap();
+    tmp.child("plugins/js/index.js").touch().unwrap();
+
+    let config_dir = tmp.path().to_path_buf();
+    let dirs = lua_dirs(
+      std::slice::from_ref(&config_dir),
+      &[config_dir.join("plugins"), config_dir.join("missing")],
+    );
+    assert_eq!(
+      dirs,
+      vec![
+        config_dir.join("lua"),
+        config_dir.join("plugins/a/lua"),
+        config_dir.join("plugins/b/lua"),
+      ]
+    );
+    assert_eq!(
+      package_path(&[PathBuf::from("/a")]),
+      format!(
+        "{};{}",
+        Path::new("/a").join("?.lua").display(),
+        Path::new("/a").join("?").join("init.lua").display()
+      )
+    );
+  }
+}
@@ -0,0 +1,181 @@
+//! The Lua bindings of the editor API, i.e. the global `rsvim` table:
+//!
+//! - `rsvim.cmd(command_line)`
+//! - `rsvim.notify(message, level?)`, the `level` is `"info"`, `"warn"` or `"error"`.
+//! - `rsvim.buf.get_lines(start, end)`, `rsvim.buf.set_lines(start, end, lines)`,
+//!   `rsvim.buf.list(all?)`, `rsvim.buf.is_modified(id)`
+//! - `rsvim.register.get(name)`, `rsvim.register.set(name, content, type?)`
+//! - `rsvim.spell.add(word)`, `rsvim.spell.check(word)`, `rsvim.spell.words()`,
+//!   `rsvim.spell.reload()`
+//!
+//! The line ranges are 0-based and end-exclusive, same with the js APIs. The argument errors are
+//! raised as Lua errors. The global `print` is replaced by `rsvim.notify`, since the terminal is
+//! owned by the editor.
+
+use crate::api::EditorApi;
+
+use mlua::{Lua, Result as LuaResult, Table, Value};
+
+// Convert the API errors to Lua errors.
+fn lua_err(e: impl ToString) -> mlua::Error {
+  mlua::Error::RuntimeError(e.to_string())
+}
+
+/// Create the global `rsvim` table.
+pub fn create_globals(lua: &Lua, api: EditorApi) -> LuaResult<()> {
+  let rsvim = lua.create_table()?;
+
+  // `rsvim.cmd`
+  {
+    let api = api.clone();
+    let cmd = lua
+      .create_function(move |_, command_line: String| api.cmd(&command_line).map_err(lua_err))?;
+    rsvim.set("cmd", cmd)?;
+  }
+
+  // `rsvim.notify`
+  {
+    let api = api.clone();
+    let notify = lua.create_function(move |_, (message, level): (String, Option<String>)| {
+      api
+        .notify(&message, level.as_deref().unwrap_or("info"))
+        .map_err(lua_err)
+    })?;
+    rsvim.set("notify", notify)?;
+  }
+
+  rsvim.set("buf", create_buf(lua, &api)?)?;
+  rsvim.set("register", create_register(lua, &api)?)?;
+  rsvim.set("spell", create_spell(lua, &api)?)?;
+
+  lua.globals().set("rsvim", rsvim)?;
+  lua
+    .load(
+      r#"
+      print = function(...)
+        local parts = {}
+        for i = 1, select('#', ...) do
+          parts[i] = tostring(select(i, ...))
+        end
+        rsvim.notify(table.concat(parts, "\t"))
+      end
+      "#,
+    )
+    .set_name("=rsvim")
+    .exec()
+}
+
+// `rsvim.buf`
+fn create_buf<'lua>(lua: &'lua Lua, api: &EditorApi) -> LuaResult<Table<'lua>> {
+  let buf = lua.create_table()?;
+
+  let get_lines = {
+    let api = api.clone();
+    lua.create_function(move |_, (start, end): (usize, usize)| Ok(api.buf_get_lines(start, end)))?
+  };
+  buf.set("get_lines", get_lines)?;
+
+  let set_lines = {
+    let api = api.clone();
+    lua.create_function(move |_, (start, end, lines): (usize, usize, Vec<String>)| {
+      api.buf_set_lines(start, end, &lines).map_err(lua_err)
+    })?
+  };
+  buf.set("set_lines", set_lines)?;
+
+  let list = {
+    let api = api.clone();
+    lua.create_function(move |lua, all: Option<bool>| {
+      api
+        .buf_list(all.unwrap_or(false))
+        .iter()
+        .map(|info| {
+          let target = lua.create_table()?;
+          target.set("id", info.id)?;
+          target.set("name", info.name.clone())?;
+          target.set("listed", info.listed)?;
+          target.set("modified", info.modified)?;
+          Ok(target)
+        })
+        .collect::<LuaResult<Vec<Table>>>()
+    })?
+  };
+  buf.set("list", list)?;
+
+  let is_modified = {
+    let api = api.clone();
+    lua.create_function(move |_, id: i32| Ok(api.buf_is_modified(id)))?
+  };
+  buf.set("is_modified", is_modified)?;
+
+  Ok(buf)
+}
+
+// `rsvim.register`
+fn create_register<'lua>(lua: &'lua Lua, api: &EditorApi) -> LuaResult<Table<'lua>> {
+  let register = lua.create_table()?;
+
+  let get = {
+    let api = api.clone();
+    lua.create_function(move |lua, name: String| {
+      match api.register_get(&name).map_err(lua_err)? {
+        Some(value) => {
+          let target = lua.create_table()?;
+          target.set("type", value.kind.to_string())?;
+          target.set("content", value.content)?;
+          Ok(Value::Table(target))
+        }
+        None => Ok(Value::Nil),
+      }
+    })?
+  };
+  register.set("get", get)?;
+
+  let set = {
+    let api = api.clone();
+    lua.create_function(
+      move |_, (name, content, kind): (String, String, Option<String>)| {
+        api
+          .register_set(&name, content, kind.as_deref().unwrap_or(""))
+          .map_err(lua_err)
+      },
+    )?
+  };
+  register.set("set", set)?;
+
+  Ok(register)
+}
+
+// `rsvim.spell`
+fn create_spell<'lua>(lua: &'lua Lua, api: &EditorApi) -> LuaResult<Table<'lua>> {
+  let spell = lua.create_table()?;
+
+  let add = {
+    let api = api.clone();
+    lua.create_function(move |_, word: String| Ok(api.spell_add(&word)))?
+  };
+  spell.set("add", add)?;
+
+  let check = {
+    let api = api.clone();
+    lua.create_function(move |_, word: String| Ok(api.spell_check(&word)))?
+  };
+  spell.set("check", check)?;
+
+  let words = {
+    let api = api.clone();
+    lua.create_function(move |_, ()| Ok(api.spell_words()))?
+  };
+  spell.set("words", words)?;
+
+  let reload = {
+    let api = api.clone();
+    lua.create_function(move |_, ()| {
+      api.spell_reload();
+      Ok(())
+    })?
+  };
+  spell.set("reload", reload)?;
+
+  Ok(spell)
+}
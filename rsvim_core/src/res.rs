@@ -39,6 +39,38 @@ pub type JsRuntimeResult<T> = std::result::Result<T, JsRuntimeErr>;
 
 // Js Runtime }
 
+// Ex Command {
+
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+/// Ex command error code implemented by [`thiserror::Error`].
+pub enum ExCommandErr {
+  #[error("E492: Not an editor command: {0}")]
+  NotEditorCommand(String),
+
+  #[error("E16: Invalid range")]
+  InvalidRange,
+
+  #[error("E471: Argument required")]
+  ArgumentRequired,
+
+  #[error("E486: Pattern not found: {0}")]
+  PatternNotFound(String),
+
+  #[error("E383: Invalid search string: {0}")]
+  InvalidPattern(String),
+
+  #[error("Interrupted")]
+  Interrupted,
+
+  #[error("Error: {0}")]
+  Message(String),
+}
+
+/// [`std::result::Result`] with `T` if ok, [`ExCommandErr`] if error.
+pub type ExCommandResult<T> = std::result::Result<T, ExCommandErr>;
+
+// Ex Command }
+
 // Buffer {
 
 // #[derive(Debug, ThisError)]
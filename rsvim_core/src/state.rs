@@ -1,18 +1,91 @@
 //! Vim editing mode.
 
-use crossterm::event::Event;
+use crossterm::event::{Event, KeyEvent, KeyEventKind};
 use parking_lot::RwLock;
+use std::path::PathBuf;
 use std::sync::{Arc, Weak};
+use std::time::Instant;
 use tracing::trace;
 
-use crate::buf::BuffersManagerArc;
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
+use crate::buf::{BufferArc, BuffersManagerArc, MarkPos};
+use crate::clipboard::{ClipboardManager, ClipboardSelection};
+use crate::interrupt::{Interrupt, InterruptArc};
+use crate::state::autocmd::{mode_short_name, AutocmdArgs, AutocmdEvent, Autocmds};
+use crate::state::autopair::InsertRecord;
+use crate::state::cmdline::CmdlineState;
+use crate::state::cursor_word::CursorWordState;
+use crate::state::escape::EscapeDecoder;
+use crate::state::ex::quickfix::QuickfixPreview;
+use crate::state::ex::read::ShellReadRequest;
+use crate::state::ex::ExCommandsManager;
+use crate::state::finder::{Finder, FinderResult};
+use crate::state::fsm::operator_pending::Operator;
+use crate::state::fsm::{FinderStateful, Stateful, StatefulDataAccess, StatefulValue};
+use crate::state::git::GitStatusCache;
+use crate::state::grep::GrepRequest;
+use crate::state::hint::HintJump;
+use crate::state::input::InputQueue;
+use crate::state::keymap::{KeymapAction, Keymaps};
+use crate::state::langmap::LangMap;
 use crate::state::mode::Mode;
-use crate::ui::tree::TreeArc;
+use crate::state::msg::{Messages, MessagesArc};
+use crate::state::palette::CommandPalette;
+use crate::state::paste::PasteState;
+use crate::state::progress::ProgressManager;
+use crate::state::register::{Register, Registers};
+use crate::state::repeat::LastChange;
+use crate::state::search::SearchState;
+use crate::state::surround::SurroundKeys;
+use crate::ui::tree::{TreeArc, TreeNode, TreeNodeId};
+use crate::ui::widget::window::ViewportArc;
+use crate::{envar, rlock, wlock};
 
+pub mod autocmd;
+pub mod autopair;
+pub mod cmdline;
 pub mod command;
+pub mod cursor_word;
+pub mod escape;
+pub mod ex;
+pub mod finder;
 pub mod fsm;
+pub mod fuzzy;
+pub mod git;
+pub mod grep;
+pub mod hint;
+pub mod input;
+pub mod inspector;
+pub mod keymap;
+pub mod langmap;
 pub mod mode;
+pub mod motion;
+pub mod mouse;
+pub mod msg;
+pub mod palette;
+pub mod paste;
+pub mod progress;
+pub mod put;
+pub mod register;
+pub mod repeat;
+pub mod search;
+pub mod session;
+pub mod surround;
+pub mod textobj;
+pub mod undotree;
+pub mod visual;
+
+/// Get the buffer and viewport of current window.
+pub fn current_window_buffer(tree: &TreeArc) -> Option<(BufferArc, ViewportArc)> {
+  let tree = rlock!(tree);
+  let window_id = tree.current_window_id()?;
+  match tree.node(&window_id) {
+    Some(TreeNode::Window(window)) => window
+      .buffer()
+      .upgrade()
+      .map(|buffer| (buffer, window.viewport())),
+    _ => None,
+  }
+}
 
 #[derive(Debug, Clone)]
 pub struct State {
@@ -21,6 +94,95 @@ pub struct State {
 
   // Editing mode.
   mode: Mode,
+
+  // Ex commands.
+  ex_commands: ExCommandsManager,
+
+  // System clipboard.
+  clipboard: ClipboardManager,
+
+  // Paste mode.
+  paste: PasteState,
+
+  // Keyboard layout translation.
+  langmap: LangMap,
+
+  // Cursor word highlighting.
+  cursor_word: CursorWordState,
+
+  // Quickfix window preview.
+  quickfix_preview: QuickfixPreview,
+
+  // Opened command palette, and its picker widget ID.
+  command_palette: Option<(CommandPalette, TreeNodeId)>,
+
+  // Opened fuzzy finder, its picker widget ID, and the stateful before it's opened.
+  finder: Option<(Finder, TreeNodeId, StatefulValue)>,
+
+  // Closed fuzzy finders, they're passed to the JS callbacks.
+  finder_results: Vec<FinderResult>,
+
+  // Running hint jump.
+  hint_jump: Option<HintJump>,
+
+  // Last search pattern, and the pattern being typed.
+  search: SearchState,
+
+  // The `:` command line being typed, and the completion options.
+  cmdline: CmdlineState,
+
+  // Ctrl-C interrupt flag, it's shared with the event loop and js runtime.
+  interrupt: InterruptArc,
+
+  // Long-running tasks' progress.
+  progress: ProgressManager,
+
+  // Message history and the shown messages, it's shared with the ex commands.
+  messages: MessagesArc,
+
+  // Git status cache of current working directory.
+  git: GitStatusCache,
+
+  // Waiting `getchar` requests and typed ahead keys.
+  input: InputQueue,
+
+  // The escape decoder, i.e. the 'ttimeout' option.
+  escape: EscapeDecoder,
+
+  // Registers, i.e. the yanked/deleted text.
+  registers: Registers,
+
+  // Running surround command.
+  surround: Option<SurroundKeys>,
+
+  // Running insert mode session.
+  insert_record: InsertRecord,
+  // Whether the running insert mode session is started by the change operator, i.e. `cw`.
+  insert_after_operator: bool,
+
+  // The last change, for dot-repeat.
+  last_change: Option<LastChange>,
+
+  // Autocmd subscriptions and emitted events.
+  autocmds: Autocmds,
+
+  // Key mappings.
+  keymaps: Keymaps,
+
+  // The scripts to source, i.e. `:source`.
+  scripts: Vec<PathBuf>,
+
+  // Whether to reload the user config, i.e. `:config reload`.
+  config_reload: bool,
+
+  // The search to start, i.e. `:grep`.
+  grep: Option<GrepRequest>,
+
+  // Whether to quit the editor, i.e. `:qa`.
+  quit: bool,
+
+  // The shell commands to read, i.e. `:r !{cmd}`.
+  shell_reads: Vec<ShellReadRequest>,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -47,6 +209,36 @@ impl State {
       stateful: StatefulValue::default(),
       last_stateful: StatefulValue::default(),
       mode: Mode::Normal,
+      ex_commands: ExCommandsManager::new(),
+      clipboard: ClipboardManager::default(),
+      paste: PasteState::default(),
+      langmap: LangMap::default(),
+      cursor_word: CursorWordState::default(),
+      quickfix_preview: QuickfixPreview::default(),
+      command_palette: None,
+      finder: None,
+      finder_results: vec![],
+      hint_jump: None,
+      search: SearchState::new(),
+      cmdline: CmdlineState::new(),
+      interrupt: Interrupt::to_arc(Interrupt::new()),
+      progress: ProgressManager::default(),
+      messages: Messages::to_arc(Messages::new()),
+      git: GitStatusCache::new(),
+      input: InputQueue::new(),
+      escape: EscapeDecoder::new(),
+      registers: Registers::new(),
+      surround: None,
+      insert_record: InsertRecord::new(),
+      insert_after_operator: false,
+      last_change: None,
+      autocmds: Autocmds::new(),
+      keymaps: Keymaps::new(),
+      scripts: vec![],
+      config_reload: false,
+      grep: None,
+      quit: false,
+      shell_reads: vec![],
     }
   }
 
@@ -70,36 +262,527 @@ impl State {
     event: Event,
   ) -> StateHandleResponse {
     // Update current mode.
-    let state_mode = match self.stateful {
-      StatefulValue::NormalMode(_) => Some(Mode::Normal),
-      StatefulValue::VisualMode(_) => Some(Mode::Visual),
-      StatefulValue::SelectMode(_) => Some(Mode::Select),
-      StatefulValue::OperatorPendingMode(_) => Some(Mode::OperatorPending),
-      StatefulValue::InsertMode(_) => Some(Mode::Insert),
-      StatefulValue::CommandLineMode(_) => Some(Mode::CommandLine),
-      StatefulValue::TerminalMode(_) => Some(Mode::Terminal),
-      _ => None,
+    let state_mode = Self::stateful_mode(&self.stateful);
+    if let Some(mode) = state_mode {
+      self.mode = mode;
+    }
+
+    // Update paste mode, i.e. the bracketed paste and paste burst heuristics.
+    match &event {
+      Event::Paste(_) => self.paste.begin_bracketed(),
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        self.paste.on_key(Instant::now());
+      }
+      _ => { /* Skip */ }
+    }
+
+    // Translate keyboard layout before resolving key mappings.
+    // NOTE: The internal states (i.e. the command palette) receive the typed text, thus it's not
+    // translated.
+    let event = match event {
+      Event::Key(key_event) if !self.paste.is_active() && state_mode.is_some() => {
+        Event::Key(self.langmap.translate_key(self.mode, key_event))
+      }
+      _ => event,
     };
+
+    let pasted = matches!(event, Event::Paste(_));
+    let response = self.dispatch(tree.clone(), buffers, event);
+    if let (true, Some(mode)) = (pasted, state_mode) {
+      self.emit_paste(&tree, mode);
+    }
+    response
+  }
+
+  // Emit the `Paste` event, with the mode short name when pasting and current window/buffer.
+  fn emit_paste(&mut self, tree: &TreeArc, mode: Mode) {
+    let window_id = rlock!(tree).current_window_id();
+    let buffer_id = current_window_buffer(tree).map(|(buffer, _)| rlock!(buffer).id());
+    self.autocmds.emit(AutocmdArgs {
+      event: AutocmdEvent::Paste,
+      matched: mode_short_name(mode).to_string(),
+      buffer_id,
+      window_id,
+      scroll_delta: None,
+    });
+  }
+
+  /// Queue the typed key to the key mappings (see [`Keymaps`]), the keyboard layout is translated
+  /// before resolving. The mappings are disabled while pasting.
+  ///
+  /// NOTE: The queued keys are resolved with [`next_keymap_action`](State::next_keymap_action).
+  pub fn feed_key(&mut self, key_event: KeyEvent) {
+    let state_mode = Self::stateful_mode(&self.stateful);
     if let Some(mode) = state_mode {
       self.mode = mode;
     }
+    self.paste.on_key(Instant::now());
+    if self.paste.is_active() {
+      self.keymaps.feed(key_event, false);
+      return;
+    }
+    let key_event = match state_mode {
+      Some(_) => self.langmap.translate_key(self.mode, key_event),
+      None => key_event,
+    };
+    self.keymaps.feed(key_event, true);
+  }
+
+  /// Resolve the queued keys with the key mappings of current mode and buffer, returns the next
+  /// action, see [`Keymaps::next`].
+  pub fn next_keymap_action(&mut self, tree: &TreeArc, now: Instant) -> Option<KeymapAction> {
+    let mode = Self::stateful_mode(&self.stateful);
+    let buffer_id = current_window_buffer(tree).map(|(buffer, _)| rlock!(buffer).id());
+    self.keymaps.next(mode, buffer_id, now)
+  }
+
+  /// Handle the key resolved by [`next_keymap_action`](State::next_keymap_action), it's not
+  /// translated or mapped again.
+  pub fn handle_key(
+    &mut self,
+    tree: TreeArc,
+    buffers: BuffersManagerArc,
+    key_event: KeyEvent,
+  ) -> StateHandleResponse {
+    if let Some(mode) = Self::stateful_mode(&self.stateful) {
+      self.mode = mode;
+    }
+    self.dispatch(tree, buffers, Event::Key(key_event))
+  }
 
+  // Handle the event with current stateful.
+  fn dispatch(
+    &mut self,
+    tree: TreeArc,
+    buffers: BuffersManagerArc,
+    event: Event,
+  ) -> StateHandleResponse {
     // Current stateful
     let stateful = self.stateful;
 
-    let data_access = StatefulDataAccess::new(self, tree, buffers, event);
+    // Dismiss the shown messages, the key on the press-enter prompt is consumed.
+    if let Event::Key(key_event) = &event {
+      if key_event.kind == KeyEventKind::Press && wlock!(self.messages).on_key(key_event) {
+        return StateHandleResponse::new(stateful, stateful);
+      }
+    }
+
+    let data_access = StatefulDataAccess::new(self, tree.clone(), buffers.clone(), event);
     let next_stateful = stateful.handle(data_access);
+    Self::group_insert_edits(&tree, &stateful, &next_stateful);
+    self.record_insert(&stateful, &next_stateful);
+    self.paste.end_bracketed();
+    self.cursor_word.on_cursor(self.mode, &tree, Instant::now());
+    self.quickfix_preview.on_cursor(&tree, &buffers);
+    self.search.refresh(&tree);
     trace!("Stateful now:{:?}, next:{:?}", stateful, next_stateful);
 
     // Save current stateful
     self.last_stateful = stateful;
-    // Set next stateful
+    // Set next stateful, and update current mode, i.e. the `ModeChanged` event is emitted right
+    // after the mode is changed.
     self.stateful = next_stateful;
+    if let Some(mode) = Self::stateful_mode(&next_stateful) {
+      self.mode = mode;
+    }
 
     StateHandleResponse::new(stateful, next_stateful)
   }
 
+  // Get the editing mode of the stateful, the internal states are not editing modes.
+  fn stateful_mode(stateful: &StatefulValue) -> Option<Mode> {
+    match stateful {
+      StatefulValue::NormalMode(_) => Some(Mode::Normal),
+      StatefulValue::VisualMode(_) => Some(Mode::Visual),
+      StatefulValue::SelectMode(_) => Some(Mode::Select),
+      StatefulValue::OperatorPendingMode(_) => Some(Mode::OperatorPending),
+      StatefulValue::InsertMode(_) => Some(Mode::Insert),
+      StatefulValue::CommandLineMode(_) => Some(Mode::CommandLine),
+      StatefulValue::TerminalMode(_) => Some(Mode::Terminal),
+      _ => None,
+    }
+  }
+
+  // Group the edits in an insert mode session as one undo state, and set the `^` mark when the
+  // insert mode is stopped.
+  fn group_insert_edits(tree: &TreeArc, stateful: &StatefulValue, next_stateful: &StatefulValue) {
+    let was_insert = matches!(stateful, StatefulValue::InsertMode(_));
+    let is_insert = matches!(next_stateful, StatefulValue::InsertMode(_));
+    if was_insert == is_insert {
+      return;
+    }
+    if let Some((buffer, viewport)) = current_window_buffer(tree) {
+      let cursor = {
+        let viewport = rlock!(viewport);
+        MarkPos::new(viewport.cursor().line_idx(), viewport.cursor().char_idx())
+      };
+      let mut buffer = wlock!(buffer);
+      if is_insert {
+        buffer.undo_tree_mut().begin_group();
+      } else {
+        buffer.undo_tree_mut().end_group();
+        buffer.marks_mut().set('^', cursor);
+      }
+    }
+  }
+
+  // Start recording the insert mode session, and save it as the last change when it's stopped. If
+  // it's started by the change operator, it's saved as the inserted text of the operator change.
+  fn record_insert(&mut self, stateful: &StatefulValue, next_stateful: &StatefulValue) {
+    let was_insert = matches!(stateful, StatefulValue::InsertMode(_));
+    let is_insert = matches!(next_stateful, StatefulValue::InsertMode(_));
+    if was_insert == is_insert {
+      return;
+    }
+    if is_insert {
+      self.insert_record = InsertRecord::new();
+      self.insert_after_operator = matches!(stateful, StatefulValue::OperatorPendingMode(_));
+    } else {
+      let record = std::mem::take(&mut self.insert_record);
+      match &mut self.last_change {
+        Some(LastChange::Operator(change))
+          if self.insert_after_operator && change.operator == Operator::Change =>
+        {
+          change.insert = Some(record);
+        }
+        _ => {
+          if !record.is_empty() {
+            self.last_change = Some(LastChange::Insert(record));
+          }
+        }
+      }
+      self.insert_after_operator = false;
+    }
+  }
+
   pub fn mode(&self) -> Mode {
     self.mode
   }
+
+  /// Get ex commands manager.
+  pub fn ex_commands(&self) -> &ExCommandsManager {
+    &self.ex_commands
+  }
+
+  /// Get mutable ex commands manager.
+  pub fn ex_commands_mut(&mut self) -> &mut ExCommandsManager {
+    &mut self.ex_commands
+  }
+
+  /// Get system clipboard.
+  pub fn clipboard(&self) -> &ClipboardManager {
+    &self.clipboard
+  }
+
+  /// Get mutable system clipboard.
+  pub fn clipboard_mut(&mut self) -> &mut ClipboardManager {
+    &mut self.clipboard
+  }
+
+  /// Get paste mode state.
+  pub fn paste(&self) -> &PasteState {
+    &self.paste
+  }
+
+  /// Get mutable paste mode state.
+  pub fn paste_mut(&mut self) -> &mut PasteState {
+    &mut self.paste
+  }
+
+  /// Get keyboard layout translation.
+  pub fn langmap(&self) -> &LangMap {
+    &self.langmap
+  }
+
+  /// Get mutable keyboard layout translation.
+  pub fn langmap_mut(&mut self) -> &mut LangMap {
+    &mut self.langmap
+  }
+
+  /// Get cursor word highlighting state.
+  pub fn cursor_word(&self) -> &CursorWordState {
+    &self.cursor_word
+  }
+
+  /// Get mutable cursor word highlighting state.
+  pub fn cursor_word_mut(&mut self) -> &mut CursorWordState {
+    &mut self.cursor_word
+  }
+
+  /// Get opened command palette, and its picker widget ID.
+  pub fn command_palette(&self) -> &Option<(CommandPalette, TreeNodeId)> {
+    &self.command_palette
+  }
+
+  /// Get mutable opened command palette, and its picker widget ID.
+  pub fn command_palette_mut(&mut self) -> &mut Option<(CommandPalette, TreeNodeId)> {
+    &mut self.command_palette
+  }
+
+  /// Get opened fuzzy finder, its picker widget ID, and the stateful before it's opened.
+  pub fn finder(&self) -> &Option<(Finder, TreeNodeId, StatefulValue)> {
+    &self.finder
+  }
+
+  /// Get mutable opened fuzzy finder, its picker widget ID, and the stateful before it's opened.
+  pub fn finder_mut(&mut self) -> &mut Option<(Finder, TreeNodeId, StatefulValue)> {
+    &mut self.finder
+  }
+
+  /// Open the fuzzy finder and enter the finder state, i.e. by the `Rsvim.picker.open` JS API, see
+  /// [`FinderStateful`].
+  pub fn open_finder(&mut self, tree: &TreeArc, finder: Finder) {
+    let back = self.stateful;
+    self.stateful = FinderStateful::open(self, tree, finder, back);
+  }
+
+  /// Add the closed fuzzy finder, it's passed to the JS callback by the event loop.
+  pub fn push_finder_result(&mut self, result: FinderResult) {
+    self.finder_results.push(result);
+  }
+
+  /// Take the closed fuzzy finders.
+  pub fn take_finder_results(&mut self) -> Vec<FinderResult> {
+    std::mem::take(&mut self.finder_results)
+  }
+
+  /// Get running hint jump.
+  pub fn hint_jump(&self) -> &Option<HintJump> {
+    &self.hint_jump
+  }
+
+  /// Get mutable running hint jump.
+  pub fn hint_jump_mut(&mut self) -> &mut Option<HintJump> {
+    &mut self.hint_jump
+  }
+
+  /// Get search state.
+  pub fn search(&self) -> &SearchState {
+    &self.search
+  }
+
+  /// Get mutable search state.
+  pub fn search_mut(&mut self) -> &mut SearchState {
+    &mut self.search
+  }
+
+  /// Get the `:` command line state.
+  pub fn cmdline(&self) -> &CmdlineState {
+    &self.cmdline
+  }
+
+  /// Get the mutable `:` command line state.
+  pub fn cmdline_mut(&mut self) -> &mut CmdlineState {
+    &mut self.cmdline
+  }
+
+  /// Get Ctrl-C interrupt flag.
+  pub fn interrupt(&self) -> &InterruptArc {
+    &self.interrupt
+  }
+
+  /// Get long-running tasks' progress.
+  pub fn progress(&self) -> &ProgressManager {
+    &self.progress
+  }
+
+  /// Get mutable long-running tasks' progress.
+  pub fn progress_mut(&mut self) -> &mut ProgressManager {
+    &mut self.progress
+  }
+
+  /// Get message history and the shown messages.
+  pub fn messages(&self) -> &MessagesArc {
+    &self.messages
+  }
+
+  /// Get git status cache.
+  pub fn git(&self) -> &GitStatusCache {
+    &self.git
+  }
+
+  /// Get mutable git status cache.
+  pub fn git_mut(&mut self) -> &mut GitStatusCache {
+    &mut self.git
+  }
+
+  /// Get input queue.
+  pub fn input(&self) -> &InputQueue {
+    &self.input
+  }
+
+  /// Get mutable input queue.
+  pub fn input_mut(&mut self) -> &mut InputQueue {
+    &mut self.input
+  }
+
+  /// Get escape decoder.
+  pub fn escape(&self) -> &EscapeDecoder {
+    &self.escape
+  }
+
+  /// Get mutable escape decoder.
+  pub fn escape_mut(&mut self) -> &mut EscapeDecoder {
+    &mut self.escape
+  }
+
+  /// Get registers.
+  pub fn registers(&self) -> &Registers {
+    &self.registers
+  }
+
+  /// Get mutable registers.
+  pub fn registers_mut(&mut self) -> &mut Registers {
+    &mut self.registers
+  }
+
+  /// Get mutable running insert mode session.
+  pub fn insert_record_mut(&mut self) -> &mut InsertRecord {
+    &mut self.insert_record
+  }
+
+  /// Get the last change, i.e. for dot-repeat.
+  pub fn last_change(&self) -> &Option<LastChange> {
+    &self.last_change
+  }
+
+  /// Set the last change.
+  pub fn set_last_change(&mut self, change: LastChange) {
+    self.last_change = Some(change);
+  }
+
+  /// Get autocmd subscriptions and emitted events.
+  pub fn autocmds(&self) -> &Autocmds {
+    &self.autocmds
+  }
+
+  /// Get mutable autocmd subscriptions and emitted events.
+  pub fn autocmds_mut(&mut self) -> &mut Autocmds {
+    &mut self.autocmds
+  }
+
+  /// Emit the autocmd events emitted by the ex commands. The git status is refreshed along with
+  /// the `BufWritePost` event.
+  pub fn push_events(&mut self, events: Vec<AutocmdArgs>) {
+    for args in events {
+      if args.event == AutocmdEvent::BufWritePost {
+        self.git.request_refresh();
+      }
+      self.autocmds.emit(args);
+    }
+  }
+
+  /// Get key mappings.
+  pub fn keymaps(&self) -> &Keymaps {
+    &self.keymaps
+  }
+
+  /// Get mutable key mappings.
+  pub fn keymaps_mut(&mut self) -> &mut Keymaps {
+    &mut self.keymaps
+  }
+
+  /// Add the scripts to source, they're executed by the js runtime in the event loop.
+  pub fn push_scripts(&mut self, scripts: Vec<PathBuf>) {
+    self.scripts.extend(scripts);
+  }
+
+  /// Take the scripts to source.
+  pub fn take_scripts(&mut self) -> Vec<PathBuf> {
+    std::mem::take(&mut self.scripts)
+  }
+
+  /// Request to reload the user config, it's reloaded by the js runtime in the event loop, see
+  /// [`config`](crate::state::ex::config).
+  pub fn request_config_reload(&mut self, reload: bool) {
+    self.config_reload |= reload;
+  }
+
+  /// Take whether to reload the user config.
+  pub fn take_config_reload(&mut self) -> bool {
+    std::mem::take(&mut self.config_reload)
+  }
+
+  /// Request to start the search, it's started by the event loop, see
+  /// [`grep`](crate::state::grep). The previous request is replaced.
+  pub fn request_grep(&mut self, request: Option<GrepRequest>) {
+    if request.is_some() {
+      self.grep = request;
+    }
+  }
+
+  /// Take the search request.
+  pub fn take_grep(&mut self) -> Option<GrepRequest> {
+    self.grep.take()
+  }
+
+  /// Request to quit the editor, it's quit by the event loop, see
+  /// [`quit`](crate::state::ex::quit).
+  pub fn request_quit(&mut self, quit: bool) {
+    self.quit |= quit;
+  }
+
+  /// Take whether to quit the editor.
+  pub fn take_quit(&mut self) -> bool {
+    std::mem::take(&mut self.quit)
+  }
+
+  /// Add the shell commands to read, they're run by the event loop, see
+  /// [`read`](crate::state::ex::read).
+  pub fn push_shell_reads(&mut self, requests: Vec<ShellReadRequest>) {
+    self.shell_reads.extend(requests);
+  }
+
+  /// Take the shell commands to read.
+  pub fn take_shell_reads(&mut self) -> Vec<ShellReadRequest> {
+    std::mem::take(&mut self.shell_reads)
+  }
+
+  /// Take the file to load for the quickfix preview, it's loaded by the event loop, see
+  /// [`QuickfixPreview`].
+  pub fn take_preview_load(&mut self) -> Option<PathBuf> {
+    self.quickfix_preview.take_load()
+  }
+
+  /// Get running surround command.
+  pub fn surround(&self) -> &Option<SurroundKeys> {
+    &self.surround
+  }
+
+  /// Get mutable running surround command.
+  pub fn surround_mut(&mut self) -> &mut Option<SurroundKeys> {
+    &mut self.surround
+  }
+
+  /// Get the register by name, the clipboard registers `+`/`*` are read from system clipboard.
+  ///
+  /// Returns `None` if the register is empty, or the clipboard is not available.
+  pub fn register(&self, name: char) -> Option<Register> {
+    match ClipboardSelection::try_from(name) {
+      Ok(selection) => match self.clipboard.get_contents(selection) {
+        Ok(contents) => Some(Register::from_text(contents)),
+        Err(e) => {
+          trace!("Failed to get clipboard register {:?}: {:?}", name, e);
+          None
+        }
+      },
+      Err(_) => self.registers.get(name).cloned(),
+    }
+  }
+
+  /// Set the register by name, the clipboard registers `+`/`*` are written to system clipboard.
+  ///
+  /// Returns `false` if the name is invalid, or the clipboard is not available.
+  pub fn set_register(&mut self, name: char, register: Register) -> bool {
+    match ClipboardSelection::try_from(name) {
+      Ok(selection) => match self.clipboard.set_contents(selection, &register.content) {
+        Ok(_) => true,
+        Err(e) => {
+          trace!("Failed to set clipboard register {:?}: {:?}", name, e);
+          false
+        }
+      },
+      Err(_) => self.registers.set(name, register),
+    }
+  }
 }
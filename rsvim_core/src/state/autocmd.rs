@@ -0,0 +1,685 @@
+//! Autocmd events, i.e. Vim's [autocommands](https://vimhelp.org/autocmd.txt.html).
+//!
+//! The JS plugins subscribe the editor events with `Rsvim.on(event, callback, pattern)`, the
+//! subscriptions are kept here and the callbacks are kept in the js runtime.
+//!
+//! The events are emitted in two ways:
+//!
+//! 1. Explicitly: The buffers manager records the buffers that are read and the buffers that are
+//!    detected filetype, they're taken on each watch and emitted as `BufReadPost` and `FileType`.
+//!    The `BufWritePre` and `BufWritePost` are emitted with [`Autocmds::emit`] by the `:write`
+//!    command (see [`write`](crate::state::ex::write)), the `BufFilePre` and `BufFilePost` are
+//!    emitted by the `:Rename` command (see [`rename`](crate::state::ex::rename)), the
+//!    `FocusGained` is emitted by the event loop when the terminal gains focus, the
+//!    `StatusLineClick` is emitted when the mouse clicks a window's statusline (see
+//!    [`mouse`](crate::state::mouse)), the `Paste` is emitted after the bracketed paste is
+//!    handled (see [`paste`](crate::state::paste)).
+//! 2. Watched: Like Vim's main loop, the event loop calls [`Autocmds::watch`] before each render,
+//!    it compares current editing mode, cursor, buffer changed tick, buffers' modified flags,
+//!    window sizes and viewport anchors with last watch, and emits `ModeChanged`, `CursorMoved`,
+//!    `TextChanged`, `BufModifiedSet`, `WinResized` and `WinScrolled`.
+//!
+//! The emitted events are queued, the event loop takes the matched subscriptions with
+//! [`Autocmds::take_matched`] and calls the JS callbacks without locking the editor.
+//!
+//! The pattern is a glob (see [`glob_match`]), it's matched against:
+//!
+//! - `BufReadPost`, `BufWritePre`, `BufWritePost`, `TextChanged`, `CursorMoved`,
+//!   `BufModifiedSet`: The absolute file path of the buffer, it's empty for the buffer without
+//!   file.
+//! - `BufFilePre`, `BufFilePost`: The absolute file path before and after renaming.
+//! - `FileType`: The filetype, i.e. `rust`.
+//! - `WinResized`, `WinScrolled`: The window ID.
+//! - `ModeChanged`: The old and new mode short names, i.e. `n:i` when entering insert mode from
+//!   normal mode.
+//! - `FocusGained`: Always empty.
+//! - `StatusLineClick`: The clicked mouse button, i.e. `left`, `right` or `middle`.
+//! - `Paste`: The mode short name when pasting, i.e. `i` in insert mode.
+
+use crate::buf::template::glob_match;
+use crate::buf::{BufferId, BuffersManagerArc};
+use crate::state::mode::Mode;
+use crate::ui::tree::internal::Inodeable;
+use crate::ui::tree::{TreeArc, TreeNode, TreeNodeId};
+use crate::{envar, rlock, wlock};
+
+use ahash::AHashMap as HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Autocmd event.
+pub enum AutocmdEvent {
+  /// After reading the file into the buffer.
+  BufReadPost,
+  /// Before writing the buffer to the file.
+  BufWritePre,
+  /// After writing the buffer to the file, it also refreshes the git status, see
+  /// [`git`](crate::state::git).
+  BufWritePost,
+  /// Before renaming the buffer's file, i.e. `:Rename`.
+  BufFilePre,
+  /// After renaming the buffer's file.
+  BufFilePost,
+  /// After the filetype is detected and its option bundle is applied, see
+  /// [`filetype`](crate::buf::filetype).
+  FileType,
+  /// After the window is resized.
+  WinResized,
+  /// After the window's viewport is scrolled, i.e. its anchor (the start line or the start
+  /// display column) is changed. The delta is passed in [`AutocmdArgs::scroll_delta`], it's also
+  /// emitted in insert mode.
+  WinScrolled,
+  /// After the editing mode is changed.
+  ModeChanged,
+  /// After the terminal gains focus, it also refreshes the git status.
+  FocusGained,
+  /// After the cursor is moved, not in insert mode.
+  CursorMoved,
+  /// After the text of the buffer is changed, not in insert mode. The changes in insert mode are
+  /// emitted when leaving insert mode.
+  TextChanged,
+  /// After the statusline of a window is clicked, the statusline components can handle the click
+  /// in the JS callback.
+  StatusLineClick,
+  /// After the bracketed paste is inserted into current buffer (or the command line).
+  Paste,
+  /// After the modified flag of a buffer flips, i.e. it's edited after loaded/saved, or it's
+  /// saved (or undone) back to unmodified. It's also emitted in insert mode.
+  BufModifiedSet,
+}
+
+impl Display for AutocmdEvent {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      AutocmdEvent::BufReadPost => write!(f, "BufReadPost"),
+      AutocmdEvent::BufWritePre => write!(f, "BufWritePre"),
+      AutocmdEvent::BufWritePost => write!(f, "BufWritePost"),
+      AutocmdEvent::BufFilePre => write!(f, "BufFilePre"),
+      AutocmdEvent::BufFilePost => write!(f, "BufFilePost"),
+      AutocmdEvent::FileType => write!(f, "FileType"),
+      AutocmdEvent::WinResized => write!(f, "WinResized"),
+      AutocmdEvent::WinScrolled => write!(f, "WinScrolled"),
+      AutocmdEvent::ModeChanged => write!(f, "ModeChanged"),
+      AutocmdEvent::FocusGained => write!(f, "FocusGained"),
+      AutocmdEvent::CursorMoved => write!(f, "CursorMoved"),
+      AutocmdEvent::TextChanged => write!(f, "TextChanged"),
+      AutocmdEvent::StatusLineClick => write!(f, "StatusLineClick"),
+      AutocmdEvent::Paste => write!(f, "Paste"),
+      AutocmdEvent::BufModifiedSet => write!(f, "BufModifiedSet"),
+    }
+  }
+}
+
+impl FromStr for AutocmdEvent {
+  type Err = &'static str;
+
+  /// Parse `str` to enum, case-insensitive like Vim.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_ascii_lowercase().as_str() {
+      "bufreadpost" => Ok(AutocmdEvent::BufReadPost),
+      "bufwritepre" => Ok(AutocmdEvent::BufWritePre),
+      "bufwritepost" => Ok(AutocmdEvent::BufWritePost),
+      "buffilepre" => Ok(AutocmdEvent::BufFilePre),
+      "buffilepost" => Ok(AutocmdEvent::BufFilePost),
+      "filetype" => Ok(AutocmdEvent::FileType),
+      "winresized" => Ok(AutocmdEvent::WinResized),
+      "winscrolled" => Ok(AutocmdEvent::WinScrolled),
+      "modechanged" => Ok(AutocmdEvent::ModeChanged),
+      "focusgained" => Ok(AutocmdEvent::FocusGained),
+      "cursormoved" => Ok(AutocmdEvent::CursorMoved),
+      "textchanged" => Ok(AutocmdEvent::TextChanged),
+      "statuslineclick" => Ok(AutocmdEvent::StatusLineClick),
+      "paste" => Ok(AutocmdEvent::Paste),
+      "bufmodifiedset" => Ok(AutocmdEvent::BufModifiedSet),
+      _ => Err("Invalid autocmd event name"),
+    }
+  }
+}
+
+/// Autocmd subscription ID.
+pub type AutocmdId = i32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The emitted event, it's passed to the JS callback.
+pub struct AutocmdArgs {
+  pub event: AutocmdEvent,
+  /// The text matched with the pattern.
+  pub matched: String,
+  pub buffer_id: Option<BufferId>,
+  pub window_id: Option<TreeNodeId>,
+  /// The viewport anchor delta of `WinScrolled`, i.e. `(lines, columns)`, the negative is upward
+  /// (or leftward).
+  pub scroll_delta: Option<(isize, isize)>,
+}
+
+#[derive(Debug, Clone)]
+struct Subscription {
+  id: AutocmdId,
+  event: AutocmdEvent,
+  pattern: String,
+}
+
+#[derive(Debug, Clone, Default)]
+// The snapshot of last watch.
+struct Snapshot {
+  mode: Option<Mode>,
+  // Current window, and its cursor (line index and char index).
+  cursor: Option<(TreeNodeId, usize, usize)>,
+  // Current buffer, and its changed tick.
+  changed_tick: Option<(BufferId, usize)>,
+  // Window sizes, i.e. width and height.
+  window_sizes: HashMap<TreeNodeId, (u16, u16)>,
+  // Viewport anchors, i.e. start line index and start display column index.
+  anchors: HashMap<TreeNodeId, (usize, usize)>,
+  // Buffers' modified flags.
+  modified: HashMap<BufferId, bool>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The autocmd subscriptions and emitted events.
+pub struct Autocmds {
+  subscriptions: Vec<Subscription>,
+  next_id: AutocmdId,
+  pending: Vec<AutocmdArgs>,
+  snapshot: Snapshot,
+}
+
+/// Get the mode short name, i.e. the `n` for normal mode.
+pub fn mode_short_name(mode: Mode) -> &'static str {
+  match mode {
+    Mode::Normal => "n",
+    Mode::Visual => "v",
+    Mode::Select => "s",
+    Mode::OperatorPending => "no",
+    Mode::Insert => "i",
+    Mode::CommandLine => "c",
+    Mode::Terminal => "t",
+  }
+}
+
+impl Autocmds {
+  pub fn new() -> Self {
+    Autocmds::default()
+  }
+
+  /// Subscribe the event with glob `pattern`, returns the subscription ID.
+  pub fn subscribe(&mut self, event: AutocmdEvent, pattern: &str) -> AutocmdId {
+    self.next_id += 1;
+    self.subscriptions.push(Subscription {
+      id: self.next_id,
+      event,
+      pattern: pattern.to_string(),
+    });
+    self.next_id
+  }
+
+  /// Unsubscribe, returns `false` if the subscription doesn't exist.
+  pub fn unsubscribe(&mut self, id: AutocmdId) -> bool {
+    let len = self.subscriptions.len();
+    self.subscriptions.retain(|s| s.id != id);
+    self.subscriptions.len() != len
+  }
+
+  /// Unsubscribe all and discard the emitted events, i.e. before the user config is reloaded. The
+  /// subscription IDs are not reused.
+  pub fn clear(&mut self) {
+    self.subscriptions.clear();
+    self.pending.clear();
+  }
+
+  /// Whether the event is subscribed.
+  pub fn is_subscribed(&self, event: AutocmdEvent) -> bool {
+    self.subscriptions.iter().any(|s| s.event == event)
+  }
+
+  /// Emit an event, it's discarded if it's not subscribed.
+  pub fn emit(&mut self, args: AutocmdArgs) {
+    if self.is_subscribed(args.event) {
+      self.pending.push(args);
+    }
+  }
+
+  /// Take the emitted events paired with the matched subscriptions, in emitted order.
+  pub fn take_matched(&mut self) -> Vec<(AutocmdId, AutocmdArgs)> {
+    let pending = std::mem::take(&mut self.pending);
+    let mut matched = vec![];
+    for args in pending {
+      for subscription in self.subscriptions.iter() {
+        if subscription.event == args.event && glob_match(&subscription.pattern, &args.matched) {
+          matched.push((subscription.id, args.clone()));
+        }
+      }
+    }
+    matched
+  }
+
+  /// Compare the editor with last watch, and emit the changed events, see the module document.
+  pub fn watch(&mut self, mode: Mode, tree: &TreeArc, buffers: &BuffersManagerArc) {
+    let read = wlock!(buffers).take_read_buffers();
+    for buffer_id in read {
+      let matched = buffer_path(buffers, buffer_id);
+      self.emit(AutocmdArgs {
+        event: AutocmdEvent::BufReadPost,
+        matched,
+        buffer_id: Some(buffer_id),
+        window_id: None,
+        scroll_delta: None,
+      });
+    }
+    let typed = wlock!(buffers).take_typed_buffers();
+    for buffer_id in typed {
+      let matched = buffer_file_type(buffers, buffer_id);
+      self.emit(AutocmdArgs {
+        event: AutocmdEvent::FileType,
+        matched,
+        buffer_id: Some(buffer_id),
+        window_id: None,
+        scroll_delta: None,
+      });
+    }
+
+    // Mode
+    if let Some(last_mode) = self.snapshot.mode {
+      if last_mode != mode {
+        self.emit(AutocmdArgs {
+          event: AutocmdEvent::ModeChanged,
+          matched: format!("{}:{}", mode_short_name(last_mode), mode_short_name(mode)),
+          buffer_id: None,
+          window_id: None,
+          scroll_delta: None,
+        });
+      }
+    }
+    self.snapshot.mode = Some(mode);
+
+    // Window sizes and viewport anchors
+    let (current, window_sizes, anchors, window_buffers) = {
+      let tree = rlock!(tree);
+      let mut window_sizes: HashMap<TreeNodeId, (u16, u16)> = HashMap::new();
+      let mut anchors: HashMap<TreeNodeId, (usize, usize)> = HashMap::new();
+      let mut window_buffers: HashMap<TreeNodeId, BufferId> = HashMap::new();
+      for id in tree.window_ids().iter() {
+        if let Some(TreeNode::Window(window)) = tree.node(id) {
+          let shape = window.actual_shape();
+          window_sizes.insert(*id, (shape.width(), shape.height()));
+          let viewport = window.viewport();
+          let viewport = rlock!(viewport);
+          anchors.insert(*id, (viewport.start_line_idx(), viewport.start_dcol_idx()));
+          if let Some(buffer) = window.buffer().upgrade() {
+            window_buffers.insert(*id, rlock!(buffer).id());
+          }
+        }
+      }
+      let current = tree
+        .current_window_id()
+        .and_then(|id| match tree.node(&id) {
+          Some(TreeNode::Window(window)) => {
+            let cursor = *rlock!(window.viewport()).cursor();
+            window
+              .buffer()
+              .upgrade()
+              .map(|buffer| (id, buffer, cursor.line_idx(), cursor.char_idx()))
+          }
+          _ => None,
+        });
+      (current, window_sizes, anchors, window_buffers)
+    };
+    let mut resized: Vec<TreeNodeId> = window_sizes
+      .iter()
+      .filter(|(id, size)| {
+        self
+          .snapshot
+          .window_sizes
+          .get(*id)
+          .is_some_and(|last| last != *size)
+      })
+      .map(|(id, _)| *id)
+      .collect();
+    resized.sort();
+    for window_id in resized {
+      self.emit(AutocmdArgs {
+        event: AutocmdEvent::WinResized,
+        matched: window_id.to_string(),
+        buffer_id: None,
+        window_id: Some(window_id),
+        scroll_delta: None,
+      });
+    }
+    self.snapshot.window_sizes = window_sizes;
+    let mut scrolled: Vec<(TreeNodeId, (isize, isize))> = anchors
+      .iter()
+      .filter_map(|(id, (line, column))| {
+        let (last_line, last_column) = self.snapshot.anchors.get(id)?;
+        let delta = (
+          *line as isize - *last_line as isize,
+          *column as isize - *last_column as isize,
+        );
+        (delta != (0, 0)).then_some((*id, delta))
+      })
+      .collect();
+    scrolled.sort();
+    for (window_id, delta) in scrolled {
+      self.emit(AutocmdArgs {
+        event: AutocmdEvent::WinScrolled,
+        matched: window_id.to_string(),
+        buffer_id: window_buffers.get(&window_id).copied(),
+        window_id: Some(window_id),
+        scroll_delta: Some(delta),
+      });
+    }
+    self.snapshot.anchors = anchors;
+
+    // Modified flags, the new buffers only take the snapshot.
+    let modified: HashMap<BufferId, bool> = rlock!(buffers)
+      .iter()
+      .map(|(id, buffer)| (*id, rlock!(buffer).is_modified()))
+      .collect();
+    let mut flipped: Vec<BufferId> = modified
+      .iter()
+      .filter(|(id, flag)| {
+        self
+          .snapshot
+          .modified
+          .get(*id)
+          .is_some_and(|last| last != *flag)
+      })
+      .map(|(id, _)| *id)
+      .collect();
+    flipped.sort();
+    for buffer_id in flipped {
+      let matched = buffer_path(buffers, buffer_id);
+      self.emit(AutocmdArgs {
+        event: AutocmdEvent::BufModifiedSet,
+        matched,
+        buffer_id: Some(buffer_id),
+        window_id: None,
+        scroll_delta: None,
+      });
+    }
+    self.snapshot.modified = modified;
+
+    // Cursor and text, they're not emitted in insert mode.
+    let (window_id, buffer, line_idx, char_idx) = match current {
+      Some(current) => current,
+      None => return,
+    };
+    if mode == Mode::Insert {
+      return;
+    }
+    let (buffer_id, changed_tick, matched) = {
+      let buffer = rlock!(buffer);
+      (
+        buffer.id(),
+        buffer.changed_tick(),
+        buffer
+          .absolute_filename()
+          .as_ref()
+          .map(|f| f.to_string_lossy().to_string())
+          .unwrap_or_default(),
+      )
+    };
+    let text_changed = matches!(self.snapshot.changed_tick, Some((last_id, last_tick)) if last_id == buffer_id && last_tick != changed_tick);
+    if text_changed {
+      self.emit(AutocmdArgs {
+        event: AutocmdEvent::TextChanged,
+        matched: matched.clone(),
+        buffer_id: Some(buffer_id),
+        window_id: Some(window_id),
+        scroll_delta: None,
+      });
+    }
+    self.snapshot.changed_tick = Some((buffer_id, changed_tick));
+    let cursor = Some((window_id, line_idx, char_idx));
+    if self.snapshot.cursor.is_some() && self.snapshot.cursor != cursor {
+      self.emit(AutocmdArgs {
+        event: AutocmdEvent::CursorMoved,
+        matched,
+        buffer_id: Some(buffer_id),
+        window_id: Some(window_id),
+        scroll_delta: None,
+      });
+    }
+    self.snapshot.cursor = cursor;
+  }
+}
+
+// Get the absolute file path of the buffer, it's empty if the buffer has no file.
+fn buffer_path(buffers: &BuffersManagerArc, buffer_id: BufferId) -> String {
+  let buffers = rlock!(buffers);
+  buffers
+    .get(&buffer_id)
+    .and_then(|buffer| {
+      rlock!(buffer)
+        .absolute_filename()
+        .as_ref()
+        .map(|f| f.to_string_lossy().to_string())
+    })
+    .unwrap_or_default()
+}
+
+// Get the filetype of the buffer.
+fn buffer_file_type(buffers: &BuffersManagerArc, buffer_id: BufferId) -> String {
+  let buffers = rlock!(buffers);
+  buffers
+    .get(&buffer_id)
+    .map(|buffer| rlock!(buffer).options().file_type().to_string())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::buf::BuffersManager;
+  use crate::cart::{IRect, U16Size};
+  use crate::test::buf::make_buffer_from_lines;
+  use crate::ui::tree::layout::LayoutDirection;
+  use crate::ui::tree::Tree;
+  use crate::ui::widget::{Cursor, Window};
+
+  use std::sync::Arc;
+
+  fn args(event: AutocmdEvent, matched: &str) -> AutocmdArgs {
+    AutocmdArgs {
+      event,
+      matched: matched.to_string(),
+      buffer_id: None,
+      window_id: None,
+      scroll_delta: None,
+    }
+  }
+
+  #[test]
+  fn event1() {
+    assert_eq!(
+      "BufReadPost".parse::<AutocmdEvent>(),
+      Ok(AutocmdEvent::BufReadPost)
+    );
+    assert_eq!(
+      "textchanged".parse::<AutocmdEvent>(),
+      Ok(AutocmdEvent::TextChanged)
+    );
+    assert_eq!(
+      "FileType".parse::<AutocmdEvent>(),
+      Ok(AutocmdEvent::FileType)
+    );
+    assert_eq!(
+      "StatusLineClick".parse::<AutocmdEvent>(),
+      Ok(AutocmdEvent::StatusLineClick)
+    );
+    assert_eq!("paste".parse::<AutocmdEvent>(), Ok(AutocmdEvent::Paste));
+    assert_eq!(
+      "WinScrolled".parse::<AutocmdEvent>(),
+      Ok(AutocmdEvent::WinScrolled)
+    );
+    assert_eq!(
+      "BufModifiedSet".parse::<AutocmdEvent>(),
+      Ok(AutocmdEvent::BufModifiedSet)
+    );
+    assert!("BufRead".parse::<AutocmdEvent>().is_err());
+    assert_eq!(AutocmdEvent::ModeChanged.to_string(), "ModeChanged");
+  }
+
+  #[test]
+  fn take_matched1() {
+    let mut autocmds = Autocmds::new();
+    let id1 = autocmds.subscribe(AutocmdEvent::BufReadPost, "*.rs");
+    let id2 = autocmds.subscribe(AutocmdEvent::BufReadPost, "*");
+    let id3 = autocmds.subscribe(AutocmdEvent::ModeChanged, "*:i");
+
+    autocmds.emit(args(AutocmdEvent::BufReadPost, "/a/main.rs"));
+    autocmds.emit(args(AutocmdEvent::BufReadPost, "/a/Makefile"));
+    autocmds.emit(args(AutocmdEvent::ModeChanged, "i:n"));
+    autocmds.emit(args(AutocmdEvent::ModeChanged, "n:i"));
+    // Not subscribed, it's discarded.
+    autocmds.emit(args(AutocmdEvent::CursorMoved, ""));
+
+    let matched: Vec<(AutocmdId, String)> = autocmds
+      .take_matched()
+      .into_iter()
+      .map(|(id, args)| (id, args.matched))
+      .collect();
+    assert_eq!(
+      matched,
+      vec![
+        (id1, "/a/main.rs".to_string()),
+        (id2, "/a/main.rs".to_string()),
+        (id2, "/a/Makefile".to_string()),
+        (id3, "n:i".to_string()),
+      ]
+    );
+    assert!(autocmds.take_matched().is_empty());
+
+    assert!(autocmds.unsubscribe(id1));
+    assert!(!autocmds.unsubscribe(id1));
+    autocmds.emit(args(AutocmdEvent::BufReadPost, "/a/main.rs"));
+    assert_eq!(autocmds.take_matched().len(), 1);
+
+    autocmds.emit(args(AutocmdEvent::BufReadPost, "/a/main.rs"));
+    autocmds.clear();
+    assert!(!autocmds.is_subscribed(AutocmdEvent::BufReadPost));
+    assert!(autocmds.take_matched().is_empty());
+    assert!(autocmds.subscribe(AutocmdEvent::BufReadPost, "*") > id3);
+  }
+
+  #[test]
+  fn watch1() {
+    let buffer = make_buffer_from_lines(vec!["hello\n", "world\n"]);
+    let buffers = BuffersManager::to_arc(BuffersManager::new());
+    let mut tree = Tree::new(U16Size::new(20, 10));
+    let root_id = tree.root_id();
+    let window = Window::new(
+      IRect::new((0, 0), (20, 10)),
+      Arc::downgrade(&buffer),
+      tree.local_options(),
+    );
+    let window_id = window.id();
+    let viewport = window.viewport();
+    tree.bounded_insert(&root_id, TreeNode::Window(window));
+    tree.bounded_insert(
+      &window_id,
+      TreeNode::Cursor(Cursor::new(IRect::new((0, 0), (1, 1)))),
+    );
+    tree.layout_tiled_windows();
+    let tree = Tree::to_arc(tree);
+
+    let mut autocmds = Autocmds::new();
+    for event in [
+      AutocmdEvent::TextChanged,
+      AutocmdEvent::ModeChanged,
+      AutocmdEvent::WinResized,
+      AutocmdEvent::WinScrolled,
+    ] {
+      autocmds.subscribe(event, "*");
+    }
+    let watch = |autocmds: &mut Autocmds, mode: Mode| {
+      autocmds.watch(mode, &tree, &buffers);
+      autocmds
+        .take_matched()
+        .into_iter()
+        .map(|(_, args)| (args.event, args.matched))
+        .collect::<Vec<_>>()
+    };
+
+    // The first watch only takes the snapshot.
+    assert!(watch(&mut autocmds, Mode::Normal).is_empty());
+    wlock!(buffer).insert(0, "a");
+    assert_eq!(
+      watch(&mut autocmds, Mode::Normal),
+      vec![(AutocmdEvent::TextChanged, "".to_string())]
+    );
+
+    // The text changes in insert mode are emitted when leaving insert mode.
+    assert_eq!(
+      watch(&mut autocmds, Mode::Insert),
+      vec![(AutocmdEvent::ModeChanged, "n:i".to_string())]
+    );
+    wlock!(buffer).insert(0, "b");
+    assert!(watch(&mut autocmds, Mode::Insert).is_empty());
+    assert_eq!(
+      watch(&mut autocmds, Mode::Normal),
+      vec![
+        (AutocmdEvent::ModeChanged, "i:n".to_string()),
+        (AutocmdEvent::TextChanged, "".to_string())
+      ]
+    );
+
+    wlock!(tree).split_window(LayoutDirection::Row).unwrap();
+    assert_eq!(
+      watch(&mut autocmds, Mode::Normal),
+      vec![(AutocmdEvent::WinResized, window_id.to_string())]
+    );
+
+    // The scroll is emitted with the delta of the viewport anchor.
+    wlock!(viewport).scroll_vertically(1);
+    autocmds.watch(Mode::Normal, &tree, &buffers);
+    let scrolled: Vec<_> = autocmds
+      .take_matched()
+      .into_iter()
+      .map(|(_, args)| (args.event, args.matched, args.scroll_delta))
+      .collect();
+    assert_eq!(
+      scrolled,
+      vec![(
+        AutocmdEvent::WinScrolled,
+        window_id.to_string(),
+        Some((1, 0))
+      )]
+    );
+  }
+
+  #[test]
+  fn watch_modified1() {
+    let buffers = BuffersManager::to_arc(BuffersManager::new());
+    let buffer_id = wlock!(buffers).new_empty_buffer();
+    let buffer = rlock!(buffers).get(&buffer_id).unwrap().clone();
+    let tree = Tree::to_arc(Tree::new(U16Size::new(20, 10)));
+
+    let mut autocmds = Autocmds::new();
+    autocmds.subscribe(AutocmdEvent::BufModifiedSet, "*");
+    let watch = |autocmds: &mut Autocmds, mode: Mode| {
+      autocmds.watch(mode, &tree, &buffers);
+      autocmds
+        .take_matched()
+        .into_iter()
+        .map(|(_, args)| (args.event, args.buffer_id))
+        .collect::<Vec<_>>()
+    };
+
+    assert!(watch(&mut autocmds, Mode::Normal).is_empty());
+    // It's emitted in insert mode, and only when the flag flips.
+    wlock!(buffer).insert(0, "a");
+    assert_eq!(
+      watch(&mut autocmds, Mode::Insert),
+      vec![(AutocmdEvent::BufModifiedSet, Some(buffer_id))]
+    );
+    wlock!(buffer).insert(0, "b");
+    assert!(watch(&mut autocmds, Mode::Insert).is_empty());
+    wlock!(buffer).set_saved();
+    assert_eq!(
+      watch(&mut autocmds, Mode::Normal),
+      vec![(AutocmdEvent::BufModifiedSet, Some(buffer_id))]
+    );
+  }
+}
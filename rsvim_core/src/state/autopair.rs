@@ -0,0 +1,351 @@
+//! Auto-pairs in insert mode, i.e. the brackets and quotes are inserted in pairs.
+//!
+//! When the 'autopairs' option is on:
+//!
+//! - Typing an opening char also inserts the closing char after the cursor, i.e. `(` inserts
+//!   `()`. It's only paired before a blank char, a closing char or the end of line, and the quotes
+//!   are not paired after a word char (i.e. `don't`).
+//! - Typing a closing char moves the cursor over it, if it's the next char.
+//! - `BACKSPACE` inside an empty pair deletes both chars.
+//! - In visual mode, typing an opening char wraps the selection in the pair.
+//!
+//! Some chars are not paired in some languages (detected by the file extension), i.e. `'` in Rust
+//! is mostly the lifetimes.
+//!
+//! The insert mode session is recorded as its net edit [`InsertRecord`], i.e. the auto-paired
+//! chars are recorded as plain text, thus the dot-repeat doesn't depend on the auto-pairs.
+
+use crate::buf::Buffer;
+use crate::state::visual::{Selection, VisualKind};
+
+/// The pairs, i.e. the opening char and the closing char.
+pub const PAIRS: &[(char, char)] = &[
+  ('(', ')'),
+  ('[', ']'),
+  ('{', '}'),
+  ('"', '"'),
+  ('\'', '\''),
+  ('`', '`'),
+];
+
+// The chars not paired in the languages, keyed by the file extension.
+const EXCEPTIONS: &[(&str, &[char])] = &[
+  // Lifetimes and labels.
+  ("rs", &['\'']),
+  // Type variables.
+  ("ml", &['\'']),
+  ("hs", &['\'']),
+  // Quote and quasiquote.
+  ("lisp", &['\'', '`']),
+  ("el", &['\'', '`']),
+  ("clj", &['\'', '`']),
+  ("scm", &['\'', '`']),
+  // Comments.
+  ("vim", &['"']),
+];
+
+/// Get the closing char of the opening char `c`.
+pub fn closing_of(c: char) -> Option<char> {
+  PAIRS
+    .iter()
+    .find(|(open, _)| *open == c)
+    .map(|(_, close)| *close)
+}
+
+fn is_closing(c: char) -> bool {
+  PAIRS.iter().any(|(_, close)| *close == c)
+}
+
+fn is_quote(c: char) -> bool {
+  matches!(c, '"' | '\'' | '`')
+}
+
+fn is_disabled(c: char, extension: Option<&str>) -> bool {
+  match extension {
+    Some(extension) => EXCEPTIONS
+      .iter()
+      .any(|(ext, chars)| ext.eq_ignore_ascii_case(extension) && chars.contains(&c)),
+    None => false,
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The edit of the typed char.
+pub enum PairEdit {
+  /// Insert the pair, and the cursor is between them.
+  Pair(char, char),
+  /// Move the cursor over the next char, i.e. the closing char.
+  Skip,
+}
+
+/// Get the edit when typing `c` at the char index `char_idx` in the `line`, the `extension` is the
+/// buffer's file extension.
+///
+/// Returns `None` if the char is inserted as it is.
+pub fn on_char(line: &str, char_idx: usize, c: char, extension: Option<&str>) -> Option<PairEdit> {
+  let chars: Vec<char> = line.chars().collect();
+  let prev = char_idx.checked_sub(1).and_then(|i| chars.get(i)).copied();
+  let next = chars.get(char_idx).copied();
+
+  if is_closing(c) && next == Some(c) {
+    return Some(PairEdit::Skip);
+  }
+  let close = closing_of(c)?;
+  if is_disabled(c, extension) {
+    return None;
+  }
+  if let Some(next) = next {
+    if !next.is_whitespace() && !is_closing(next) {
+      return None;
+    }
+  }
+  if is_quote(c) && prev.is_some_and(|p| p.is_alphanumeric() || p == '_') {
+    return None;
+  }
+  Some(PairEdit::Pair(c, close))
+}
+
+/// Whether the `BACKSPACE` at the char index `char_idx` in the `line` deletes an empty pair.
+pub fn on_backspace(line: &str, char_idx: usize) -> bool {
+  if char_idx == 0 {
+    return false;
+  }
+  let mut chars = line.chars().skip(char_idx - 1);
+  match (chars.next(), chars.next()) {
+    (Some(prev), Some(next)) => closing_of(prev) == Some(next),
+    _ => false,
+  }
+}
+
+/// Wrap the selection in the `open` and `close` text, as one undo state.
+///
+/// - Charwise: Wrap the selected text.
+/// - Linewise: Wrap from the start of the first line to the end of the last line.
+/// - Blockwise: Wrap the selected text in each line.
+pub fn wrap_selection(buffer: &mut Buffer, selection: &Selection, open: &str, close: &str) {
+  let (start, end) = selection.ordered();
+  let line_len =
+    |buffer: &Buffer, line_idx: usize| buffer.get_line_content(line_idx).map(|l| l.chars().count());
+  // The positions to insert chars, in reverse order, thus the earlier positions are not shifted.
+  let mut edits: Vec<((usize, usize), &str)> = vec![];
+  match selection.kind() {
+    VisualKind::Charwise => {
+      let end_len = line_len(buffer, end.0).unwrap_or(0);
+      edits.push(((end.0, std::cmp::min(end.1 + 1, end_len)), close));
+      edits.push((start, open));
+    }
+    VisualKind::Linewise => {
+      let end_len = line_len(buffer, end.0).unwrap_or(0);
+      edits.push(((end.0, end_len), close));
+      edits.push(((start.0, 0), open));
+    }
+    VisualKind::Blockwise => {
+      for line_idx in selection.line_range().rev() {
+        let len = match line_len(buffer, line_idx) {
+          Some(len) => len,
+          None => continue,
+        };
+        if let Some(range) = selection.char_range(line_idx, len) {
+          if !range.is_empty() {
+            edits.push(((line_idx, range.end), close));
+            edits.push(((line_idx, range.start), open));
+          }
+        }
+      }
+    }
+  }
+
+  buffer.undo_tree_mut().begin_group();
+  for ((line_idx, char_idx), text) in edits {
+    let char_idx = buffer.line_to_char(line_idx) + char_idx;
+    buffer.insert(char_idx, text);
+  }
+  buffer.undo_tree_mut().end_group();
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The net edit of an insert mode session, for dot-repeat.
+///
+/// It's replayed as: Delete `deleted` chars before the cursor and `deleted_after` chars after the
+/// cursor, then insert the `text`, the cursor is put at the `cursor` char in the `text`.
+pub struct InsertRecord {
+  deleted: usize,
+  deleted_after: usize,
+  text: String,
+  cursor: usize,
+}
+
+impl InsertRecord {
+  pub fn new() -> Self {
+    InsertRecord::default()
+  }
+
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.deleted == 0 && self.deleted_after == 0 && self.text.is_empty()
+  }
+
+  fn byte_idx(&self, char_idx: usize) -> usize {
+    self
+      .text
+      .char_indices()
+      .nth(char_idx)
+      .map(|(i, _)| i)
+      .unwrap_or(self.text.len())
+  }
+
+  /// Record the `text` inserted at the cursor, and the cursor moves `cursor` chars forward.
+  pub fn insert(&mut self, text: &str, cursor: usize) {
+    let byte_idx = self.byte_idx(self.cursor);
+    self.text.insert_str(byte_idx, text);
+    self.cursor += cursor;
+  }
+
+  /// Record the cursor moves over the next char `c`.
+  pub fn skip(&mut self, c: char) {
+    if self.cursor < self.text.chars().count() {
+      self.cursor += 1;
+    } else {
+      // The char is not inserted in this session, it's same with replacing it.
+      self.deleted_after += 1;
+      self.text.push(c);
+      self.cursor += 1;
+    }
+  }
+
+  /// Record the `BACKSPACE`, it also deletes the next char if `pair`.
+  pub fn backspace(&mut self, pair: bool) {
+    if self.cursor > 0 {
+      self.cursor -= 1;
+      let byte_idx = self.byte_idx(self.cursor);
+      self.text.remove(byte_idx);
+    } else {
+      self.deleted += 1;
+    }
+    if pair {
+      if self.cursor < self.text.chars().count() {
+        let byte_idx = self.byte_idx(self.cursor);
+        self.text.remove(byte_idx);
+      } else {
+        self.deleted_after += 1;
+      }
+    }
+  }
+
+  /// Replay the edit at the char index `char_idx` of the buffer, returns the char index of the
+  /// cursor.
+  pub fn replay(&self, buffer: &mut Buffer, char_idx: usize) -> usize {
+    let start_char_idx = char_idx.saturating_sub(self.deleted);
+    let end_char_idx = std::cmp::min(char_idx + self.deleted_after, buffer.len_chars());
+    if start_char_idx < end_char_idx {
+      buffer.remove(start_char_idx..end_char_idx);
+    }
+    if !self.text.is_empty() {
+      buffer.insert(start_char_idx, &self.text);
+    }
+    start_char_idx + self.cursor
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::envar;
+  use crate::test::buf::make_buffer_from_lines;
+  use crate::wlock;
+
+  #[test]
+  fn on_char1() {
+    let pair = Some(PairEdit::Pair('(', ')'));
+    assert_eq!(on_char("", 0, '(', None), pair);
+    assert_eq!(on_char("a b", 1, '(', None), pair);
+    assert_eq!(on_char("a)", 1, '(', None), pair);
+    // Not paired before a word char.
+    assert_eq!(on_char("ab", 1, '(', None), None);
+    // Skip over the closing char.
+    assert_eq!(on_char("()", 1, ')', None), Some(PairEdit::Skip));
+    assert_eq!(on_char("''", 1, '\'', None), Some(PairEdit::Skip));
+    // Not a pair char.
+    assert_eq!(on_char("", 0, 'a', None), None);
+    assert_eq!(on_char("", 0, ')', None), None);
+  }
+
+  #[test]
+  fn on_char2() {
+    // Quotes after word chars.
+    assert_eq!(on_char("don", 3, '\'', None), None);
+    assert_eq!(
+      on_char("x = ", 4, '"', None),
+      Some(PairEdit::Pair('"', '"'))
+    );
+    // Language exceptions.
+    assert_eq!(on_char("&", 1, '\'', Some("rs")), None);
+    assert_eq!(
+      on_char("&", 1, '\'', Some("py")),
+      Some(PairEdit::Pair('\'', '\''))
+    );
+    assert_eq!(on_char("", 0, '"', Some("vim")), None);
+  }
+
+  #[test]
+  fn on_backspace1() {
+    assert!(on_backspace("()", 1));
+    assert!(on_backspace("a[]b", 2));
+    assert!(!on_backspace("(a)", 1));
+    assert!(!on_backspace("()", 0));
+    assert!(!on_backspace("()", 2));
+  }
+
+  #[test]
+  fn wrap_selection1() {
+    let buf = make_buffer_from_lines(vec!["hello world\n", "foo bar\n"]);
+    let mut buf = wlock!(buf);
+    let mut selection = Selection::new(VisualKind::Charwise, (0, 6));
+    selection.set_cursor((1, 2));
+    wrap_selection(&mut buf, &selection, "(", ")");
+    assert_eq!(buf.get_line_content(0), Some("hello (world".to_string()));
+    assert_eq!(buf.get_line_content(1), Some("foo) bar".to_string()));
+
+    // Undo as one state.
+    buf.undo();
+    assert_eq!(buf.get_line_content(0), Some("hello world".to_string()));
+    assert_eq!(buf.get_line_content(1), Some("foo bar".to_string()));
+
+    let mut selection = Selection::new(VisualKind::Blockwise, (0, 0));
+    selection.set_cursor((1, 2));
+    wrap_selection(&mut buf, &selection, "[", "]");
+    assert_eq!(buf.get_line_content(0), Some("[hel]lo world".to_string()));
+    assert_eq!(buf.get_line_content(1), Some("[foo] bar".to_string()));
+  }
+
+  #[test]
+  fn insert_record1() {
+    // Type `f(a` with auto-pairs, i.e. `f()` then `a` between the pair, then `)` skips.
+    let mut record = InsertRecord::new();
+    record.insert("f", 1);
+    record.insert("()", 1);
+    record.insert("a", 1);
+    record.skip(')');
+    assert_eq!(record.text(), "f(a)");
+
+    // Backspace the empty pair, then backspace before the session.
+    let mut record = InsertRecord::new();
+    record.insert("[]", 1);
+    record.backspace(true);
+    record.backspace(false);
+    assert_eq!(record.text(), "");
+    assert!(!record.is_empty());
+
+    let buf = make_buffer_from_lines(vec!["ab\n"]);
+    let mut buf = wlock!(buf);
+    let mut record = InsertRecord::new();
+    record.backspace(false);
+    record.insert("(x)", 2);
+    assert_eq!(record.replay(&mut buf, 1), 2);
+    assert_eq!(buf.get_line_content(0), Some("(x)b".to_string()));
+  }
+}
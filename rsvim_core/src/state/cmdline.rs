@@ -0,0 +1,701 @@
+//! The ex command line (i.e. `:`) and its completion, i.e. the wildmenu.
+//!
+//! The `Tab` completes the word at the end of the command line: the command name, or the file name
+//! for the commands taking file arguments (i.e. `:edit`). The candidates are shown in the wildmenu
+//! above the command line, as a horizontal bar or a popup menu (if 'wildoptions' contains `pum`).
+//!
+//! The 'wildmode' option is a comma-separated list of the stages, the first `Tab` uses the first
+//! stage, the next `Tab` uses the next stage, the last stage is repeated. Each stage is:
+//!
+//! - `full`: Complete the next full match, i.e. cycle the candidates (and the original word).
+//! - `longest`: Complete till the longest common string. If it's not longer, use the next stage.
+//! - `longest:full`: Like `longest`, but also show the wildmenu.
+//! - `list`: Only show the wildmenu.
+//! - `list:full`: Show the wildmenu and complete the next full match, i.e. same as `full`.
+//! - `list:longest`: Show the wildmenu and complete till the longest common string.
+//!
+//! See: <https://vimhelp.org/options.txt.html#%27wildmode%27>.
+//!
+//! The command line (and the search pattern) is edited in the readline style, see [`LineEdit`].
+//! The command history (see [`ExCommandsManager::history`]) is listed in the command-line window
+//! (i.e. `Ctrl-F`), thus the long commands can be edited with the normal mode keys.
+
+use crate::state::ex::ExCommandsManager;
+use crate::ui::tree::TreeNodeId;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::Path;
+
+/// The default 'wildmode' option.
+pub const DEFAULT_WILDMODE: &str = "full";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// The 'wildmode' stage.
+pub struct WildStage {
+  /// Complete till the longest common string.
+  pub longest: bool,
+  /// Complete the next full match.
+  pub full: bool,
+  /// Show the wildmenu.
+  pub list: bool,
+}
+
+/// Parse the 'wildmode' option.
+pub fn parse_wildmode(option: &str) -> Result<Vec<WildStage>, String> {
+  let stages = option
+    .split(',')
+    .map(|stage| match stage {
+      "full" | "list:full" => Ok(WildStage {
+        longest: false,
+        full: true,
+        list: true,
+      }),
+      "longest" => Ok(WildStage {
+        longest: true,
+        full: false,
+        list: false,
+      }),
+      "longest:full" | "list:longest" => Ok(WildStage {
+        longest: true,
+        full: false,
+        list: true,
+      }),
+      "list" => Ok(WildStage {
+        longest: false,
+        full: false,
+        list: true,
+      }),
+      _ => Err(format!("E474: Invalid argument: wildmode={}", option)),
+    })
+    .collect::<Result<Vec<_>, String>>()?;
+  if stages.len() > 4 {
+    return Err(format!("E474: Invalid argument: wildmode={}", option));
+  }
+  Ok(stages)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The completion options, i.e. 'wildmode' and 'wildoptions'.
+pub struct WildOptions {
+  wildmode: String,
+  stages: Vec<WildStage>,
+  wildoptions: String,
+}
+
+impl Default for WildOptions {
+  fn default() -> Self {
+    WildOptions {
+      wildmode: DEFAULT_WILDMODE.to_string(),
+      stages: parse_wildmode(DEFAULT_WILDMODE).unwrap(),
+      wildoptions: String::new(),
+    }
+  }
+}
+
+impl WildOptions {
+  /// Get the 'wildmode' option.
+  pub fn wildmode(&self) -> &str {
+    &self.wildmode
+  }
+
+  /// Set the 'wildmode' option, the option is not changed if the value is invalid.
+  pub fn set_wildmode(&mut self, value: &str) -> Result<(), String> {
+    self.stages = parse_wildmode(value)?;
+    self.wildmode = value.to_string();
+    Ok(())
+  }
+
+  /// Get the parsed 'wildmode' stages.
+  pub fn stages(&self) -> &Vec<WildStage> {
+    &self.stages
+  }
+
+  /// Get the 'wildoptions' option.
+  pub fn wildoptions(&self) -> &str {
+    &self.wildoptions
+  }
+
+  /// Set the 'wildoptions' option, it's a comma-separated list and only `pum` is supported.
+  pub fn set_wildoptions(&mut self, value: &str) -> Result<(), String> {
+    if value
+      .split(',')
+      .any(|item| !item.is_empty() && item != "pum")
+    {
+      return Err(format!("E474: Invalid argument: wildoptions={}", value));
+    }
+    self.wildoptions = value.to_string();
+    Ok(())
+  }
+
+  /// Whether shows the wildmenu as a popup menu.
+  pub fn pum(&self) -> bool {
+    self.wildoptions.split(',').any(|item| item == "pum")
+  }
+}
+
+/// Get the longest common prefix of the candidates.
+pub fn longest_common_prefix(candidates: &[String]) -> String {
+  let first = match candidates.first() {
+    Some(first) => first,
+    None => return String::new(),
+  };
+  let mut len = first.len();
+  for candidate in candidates.iter().skip(1) {
+    len = first
+      .char_indices()
+      .zip(candidate.chars())
+      .take_while(|((i, a), b)| *i < len && a == b)
+      .map(|((i, a), _)| i + a.len_utf8())
+      .last()
+      .unwrap_or(0);
+  }
+  first[..len].to_string()
+}
+
+/// Find the completion candidates for the end of the command `line`, with the `read_dir` to list
+/// the file names (and whether it's a directory) in a directory.
+///
+/// Returns the byte offset of the completed word, and the candidates.
+pub fn complete_with<F>(
+  line: &str,
+  commands: &ExCommandsManager,
+  read_dir: F,
+) -> Option<(usize, Vec<String>)>
+where
+  F: Fn(&Path) -> Vec<(String, bool)>,
+{
+  // Skip the range, i.e. `:1,10`.
+  let name_start = line
+    .find(|c: char| c.is_ascii_alphabetic())
+    .unwrap_or(line.len());
+  if line[..name_start]
+    .chars()
+    .any(|c| !(c.is_ascii_digit() || " \t:,.$%'+-<>".contains(c)))
+  {
+    return None;
+  }
+  let name_len = line[name_start..]
+    .find(|c: char| !c.is_ascii_alphabetic())
+    .unwrap_or(line.len() - name_start);
+  let name = &line[name_start..name_start + name_len];
+  let rest = &line[name_start + name_len..];
+
+  // The command name.
+  if rest.is_empty() {
+    let candidates = commands
+      .iter()
+      .map(|(command_name, _)| command_name.to_string())
+      .filter(|command_name| command_name.starts_with(name))
+      .collect::<Vec<_>>();
+    return Some((name_start, candidates));
+  }
+
+  // The file name, after the `!` and whitespaces.
+  let definition = commands.get(name)?;
+  if !definition.complete_files() {
+    return None;
+  }
+  let args = rest.strip_prefix('!').unwrap_or(rest);
+  if !args.starts_with(char::is_whitespace) {
+    return None;
+  }
+  let word_start = line.rfind(char::is_whitespace).unwrap() + 1;
+  let word = &line[word_start..];
+  let (dir, prefix) = match word.rfind('/') {
+    Some(i) => (&word[..i + 1], &word[i + 1..]),
+    None => ("", word),
+  };
+  let list_dir = if dir.is_empty() { "." } else { dir };
+  let mut entries = read_dir(Path::new(list_dir))
+    .into_iter()
+    .filter(|(name, _)| name.starts_with(prefix))
+    .filter(|(name, _)| !name.starts_with('.') || prefix.starts_with('.'))
+    .collect::<Vec<_>>();
+  entries.sort();
+  let candidates = entries
+    .into_iter()
+    .map(|(name, is_dir)| format!("{}{}{}", dir, name, if is_dir { "/" } else { "" }))
+    .collect();
+  Some((word_start, candidates))
+}
+
+/// Find the completion candidates on the local file system, see [`complete_with`].
+pub fn complete(line: &str, commands: &ExCommandsManager) -> Option<(usize, Vec<String>)> {
+  complete_with(line, commands, |dir| match std::fs::read_dir(dir) {
+    Ok(entries) => entries
+      .filter_map(|entry| entry.ok())
+      .map(|entry| {
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        (entry.file_name().to_string_lossy().to_string(), is_dir)
+      })
+      .collect(),
+    Err(_) => vec![],
+  })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The running completion.
+pub struct Completion {
+  /// The byte offset of the completed word in the command line.
+  pub start: usize,
+  /// The typed word (or the longest common string), it's restored when cycling back.
+  pub original: String,
+  /// The candidates.
+  pub candidates: Vec<String>,
+  /// The selected candidate, `None` means the original word.
+  pub selected: Option<usize>,
+  /// The wildmode stage of the next `Tab`.
+  pub stage: usize,
+  /// Whether the wildmenu is shown.
+  pub shown: bool,
+}
+
+impl Completion {
+  pub fn new(start: usize, original: &str, candidates: Vec<String>) -> Self {
+    Completion {
+      start,
+      original: original.to_string(),
+      candidates,
+      selected: None,
+      stage: 0,
+      shown: false,
+    }
+  }
+
+  /// The completed word, i.e. the selected candidate or the original word.
+  pub fn word(&self) -> &str {
+    match self.selected {
+      Some(i) => &self.candidates[i],
+      None => &self.original,
+    }
+  }
+
+  // Select the next (or previous) candidate, the original word is in the cycle if there're
+  // multiple candidates.
+  fn cycle(&mut self, forward: bool) {
+    let n = self.candidates.len();
+    if n == 0 {
+      return;
+    }
+    self.selected = match (self.selected, forward) {
+      (None, true) => Some(0),
+      (None, false) => Some(n - 1),
+      (Some(i), true) if i + 1 < n => Some(i + 1),
+      (Some(i), false) if i > 0 => Some(i - 1),
+      (Some(i), _) if n == 1 => Some(i),
+      (Some(_), _) => None,
+    };
+  }
+
+  /// Complete on the next `Tab`, by the 'wildmode' `stages`.
+  pub fn next(&mut self, stages: &[WildStage]) {
+    if stages.is_empty() {
+      return;
+    }
+    loop {
+      let stage = stages[self.stage.min(stages.len() - 1)];
+      self.stage += 1;
+      if stage.list {
+        self.shown = true;
+      }
+      if stage.full {
+        self.cycle(true);
+        return;
+      }
+      if stage.longest {
+        let longest = longest_common_prefix(&self.candidates);
+        let longer = longest.chars().count() > self.word().chars().count();
+        if longer {
+          self.original = longest;
+          self.selected = None;
+        }
+        if longer || self.stage >= stages.len() {
+          return;
+        }
+        continue;
+      }
+      return;
+    }
+  }
+
+  /// Select the previous candidate, i.e. `Shift-Tab`.
+  pub fn prev(&mut self) {
+    self.shown = true;
+    self.cycle(false);
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The readline-style editing of the command line.
+pub enum LineEdit {
+  /// Insert the char before the cursor.
+  Insert(char),
+  /// Delete the char before the cursor, i.e. `Backspace`/`Ctrl-H`.
+  DeleteBackward,
+  /// Delete the char under the cursor, i.e. `Delete`.
+  DeleteForward,
+  /// Delete the word before the cursor, i.e. `Ctrl-W`.
+  DeleteWord,
+  /// Delete all the chars before the cursor, i.e. `Ctrl-U`.
+  DeleteToStart,
+  /// Move the cursor one char left, i.e. `Left`.
+  Left,
+  /// Move the cursor one char right, i.e. `Right`.
+  Right,
+  /// Move the cursor one WORD left, i.e. `Ctrl-Left`/`Shift-Left`.
+  WordLeft,
+  /// Move the cursor one WORD right, i.e. `Ctrl-Right`/`Shift-Right`.
+  WordRight,
+  /// Move the cursor to the start, i.e. `Ctrl-A`/`Ctrl-B`/`Home`.
+  Start,
+  /// Move the cursor to the end, i.e. `Ctrl-E`/`End`.
+  End,
+}
+
+impl LineEdit {
+  /// Get the editing of the key, returns `None` if it's not an editing key.
+  pub fn from_key(key_event: &KeyEvent) -> Option<Self> {
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    let shift = key_event.modifiers.contains(KeyModifiers::SHIFT);
+    let edit = match key_event.code {
+      KeyCode::Char('h') if ctrl => LineEdit::DeleteBackward,
+      KeyCode::Char('w') if ctrl => LineEdit::DeleteWord,
+      KeyCode::Char('u') if ctrl => LineEdit::DeleteToStart,
+      KeyCode::Char('a' | 'b') if ctrl => LineEdit::Start,
+      KeyCode::Char('e') if ctrl => LineEdit::End,
+      KeyCode::Char(_) if ctrl || key_event.modifiers.contains(KeyModifiers::ALT) => return None,
+      KeyCode::Char(c) => LineEdit::Insert(c),
+      KeyCode::Backspace => LineEdit::DeleteBackward,
+      KeyCode::Delete => LineEdit::DeleteForward,
+      KeyCode::Left if ctrl || shift => LineEdit::WordLeft,
+      KeyCode::Right if ctrl || shift => LineEdit::WordRight,
+      KeyCode::Left => LineEdit::Left,
+      KeyCode::Right => LineEdit::Right,
+      KeyCode::Home => LineEdit::Start,
+      KeyCode::End => LineEdit::End,
+      _ => return None,
+    };
+    Some(edit)
+  }
+
+  /// Apply the editing on the `text`, the `cursor` is the byte offset in the text.
+  pub fn apply(&self, text: &mut String, cursor: &mut usize) {
+    *cursor = (*cursor).min(text.len());
+    let prev = |at: usize| text[..at].chars().next_back();
+    let next = |at: usize| text[at..].chars().next();
+    match self {
+      LineEdit::Insert(c) => {
+        text.insert(*cursor, *c);
+        *cursor += c.len_utf8();
+      }
+      LineEdit::DeleteBackward => {
+        if let Some(c) = prev(*cursor) {
+          *cursor -= c.len_utf8();
+          text.remove(*cursor);
+        }
+      }
+      LineEdit::DeleteForward => {
+        if *cursor < text.len() {
+          text.remove(*cursor);
+        }
+      }
+      LineEdit::DeleteWord => {
+        let mut start = skip_back(text, *cursor, char::is_whitespace);
+        if let Some(c) = prev(start) {
+          start = if is_keyword(c) {
+            skip_back(text, start, is_keyword)
+          } else {
+            skip_back(text, start, |c| !is_keyword(c) && !c.is_whitespace())
+          };
+        }
+        text.replace_range(start..*cursor, "");
+        *cursor = start;
+      }
+      LineEdit::DeleteToStart => {
+        text.replace_range(..*cursor, "");
+        *cursor = 0;
+      }
+      LineEdit::Left => {
+        if let Some(c) = prev(*cursor) {
+          *cursor -= c.len_utf8();
+        }
+      }
+      LineEdit::Right => {
+        if let Some(c) = next(*cursor) {
+          *cursor += c.len_utf8();
+        }
+      }
+      LineEdit::WordLeft => {
+        let start = skip_back(text, *cursor, char::is_whitespace);
+        *cursor = skip_back(text, start, |c| !c.is_whitespace());
+      }
+      LineEdit::WordRight => {
+        let end = skip_forward(text, *cursor, |c| !c.is_whitespace());
+        *cursor = skip_forward(text, end, char::is_whitespace);
+      }
+      LineEdit::Start => *cursor = 0,
+      LineEdit::End => *cursor = text.len(),
+    }
+  }
+}
+
+// The keyword chars of the `Ctrl-W`, i.e. Vim's default 'iskeyword'.
+fn is_keyword(c: char) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+// Skip the chars matching `f` backward from the byte offset `at`, returns the new offset.
+fn skip_back<F: Fn(char) -> bool>(text: &str, at: usize, f: F) -> usize {
+  text[..at]
+    .char_indices()
+    .rev()
+    .take_while(|(_, c)| f(*c))
+    .last()
+    .map(|(i, _)| i)
+    .unwrap_or(at)
+}
+
+// Skip the chars matching `f` forward from the byte offset `at`, returns the new offset.
+fn skip_forward<F: Fn(char) -> bool>(text: &str, at: usize, f: F) -> usize {
+  text[at..]
+    .char_indices()
+    .find(|(_, c)| !f(*c))
+    .map(|(i, _)| at + i)
+    .unwrap_or(text.len())
+}
+
+#[derive(Debug, Clone)]
+/// The ex command line being typed.
+pub struct ExPrompt {
+  /// Typed command line.
+  pub text: String,
+  /// The cursor position in the command line, i.e. the byte offset.
+  pub cursor: usize,
+  /// The command-line widget ID.
+  pub cmdline_id: TreeNodeId,
+  /// The running completion.
+  pub completion: Option<Completion>,
+  /// The wildmenu widget ID.
+  pub wildmenu_id: Option<TreeNodeId>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The ex command line state.
+pub struct CmdlineState {
+  // The command line being typed (if has).
+  prompt: Option<ExPrompt>,
+
+  // Completion options.
+  options: WildOptions,
+
+  // The command-line window (if has).
+  cmdwin: Option<TreeNodeId>,
+}
+
+impl CmdlineState {
+  pub fn new() -> Self {
+    CmdlineState::default()
+  }
+
+  /// Get the command line being typed.
+  pub fn prompt(&self) -> &Option<ExPrompt> {
+    &self.prompt
+  }
+
+  /// Get mutable command line being typed.
+  pub fn prompt_mut(&mut self) -> &mut Option<ExPrompt> {
+    &mut self.prompt
+  }
+
+  /// Get completion options.
+  pub fn options(&self) -> &WildOptions {
+    &self.options
+  }
+
+  /// Get mutable completion options.
+  pub fn options_mut(&mut self) -> &mut WildOptions {
+    &mut self.options
+  }
+
+  /// Get the command-line window ID (if has).
+  pub fn cmdwin(&self) -> Option<TreeNodeId> {
+    self.cmdwin
+  }
+
+  pub fn set_cmdwin(&mut self, window_id: Option<TreeNodeId>) {
+    self.cmdwin = window_id;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stages(option: &str) -> Vec<WildStage> {
+    parse_wildmode(option).unwrap()
+  }
+
+  fn candidates(items: &[&str]) -> Vec<String> {
+    items.iter().map(|item| item.to_string()).collect()
+  }
+
+  #[test]
+  fn parse_wildmode1() {
+    assert_eq!(stages("full"), stages("list:full"));
+    assert_eq!(stages("longest,full").len(), 2);
+    assert!(stages("longest")[0].longest);
+    assert!(!stages("longest")[0].list);
+    assert!(stages("longest:full")[0].list);
+    assert!(parse_wildmode("").is_err());
+    assert!(parse_wildmode("full,abc").is_err());
+    assert!(parse_wildmode("full,full,full,full,full").is_err());
+
+    let mut options = WildOptions::default();
+    assert!(options.set_wildmode("bad").is_err());
+    assert_eq!(options.wildmode(), "full");
+    assert!(!options.pum());
+    options.set_wildoptions("pum").unwrap();
+    assert!(options.pum());
+    assert!(options.set_wildoptions("tagfile").is_err());
+  }
+
+  #[test]
+  fn longest_common_prefix1() {
+    assert_eq!(longest_common_prefix(&[]), "");
+    assert_eq!(
+      longest_common_prefix(&candidates(&["split", "spell"])),
+      "sp"
+    );
+    assert_eq!(longest_common_prefix(&candidates(&["中文", "中国"])), "中");
+    assert_eq!(longest_common_prefix(&candidates(&["abc", "xyz"])), "");
+    assert_eq!(longest_common_prefix(&candidates(&["edit"])), "edit");
+  }
+
+  #[test]
+  fn complete1() {
+    let commands = ExCommandsManager::new();
+    let read_dir = |dir: &Path| -> Vec<(String, bool)> {
+      let entries: &[(&str, bool)] = match dir.to_str().unwrap() {
+        "." => &[("src", true), ("README.md", false), (".git", true)],
+        "src/" => &[("lib.rs", false), ("main.rs", false), ("state", true)],
+        _ => &[],
+      };
+      entries
+        .iter()
+        .map(|(name, is_dir)| (name.to_string(), *is_dir))
+        .collect()
+    };
+
+    let (start, actual) = complete_with("v", &commands, read_dir).unwrap();
+    assert_eq!(start, 0);
+    assert_eq!(actual, vec!["vertical", "vsplit"]);
+    let (start, actual) = complete_with("1,3al", &commands, read_dir).unwrap();
+    assert_eq!(start, 3);
+    assert_eq!(actual, vec!["align"]);
+    let (start, actual) = complete_with("e ", &commands, read_dir).unwrap();
+    assert_eq!(start, 2);
+    assert_eq!(actual, vec!["README.md", "src/"]);
+    let (start, actual) = complete_with("edit src/", &commands, read_dir).unwrap();
+    assert_eq!(start, 5);
+    assert_eq!(actual, vec!["src/lib.rs", "src/main.rs", "src/state/"]);
+    let (_, actual) = complete_with("e .", &commands, read_dir).unwrap();
+    assert_eq!(actual, vec![".git/"]);
+    let (_, actual) = complete_with("mksession! src/m", &commands, read_dir).unwrap();
+    assert_eq!(actual, vec!["src/main.rs"]);
+    // The command doesn't take file arguments.
+    assert!(complete_with("align ", &commands, read_dir).is_none());
+    assert!(complete_with("notexist ", &commands, read_dir).is_none());
+    assert!(complete_with("e!", &commands, read_dir).is_none());
+  }
+
+  #[test]
+  fn next1() {
+    // full
+    let mut completion = Completion::new(0, "sp", candidates(&["split", "spell"]));
+    let full = stages("full");
+    completion.next(&full);
+    assert_eq!(completion.word(), "split");
+    assert!(completion.shown);
+    completion.next(&full);
+    assert_eq!(completion.word(), "spell");
+    completion.next(&full);
+    assert_eq!(completion.word(), "sp");
+    completion.prev();
+    assert_eq!(completion.word(), "spell");
+
+    // longest,full
+    let mut completion = Completion::new(0, "s", candidates(&["split", "spell"]));
+    let longest_full = stages("longest,full");
+    completion.next(&longest_full);
+    assert_eq!(completion.word(), "sp");
+    assert!(!completion.shown);
+    completion.next(&longest_full);
+    assert_eq!(completion.word(), "split");
+    assert!(completion.shown);
+
+    // The longest common string is not longer, use the next stage.
+    let mut completion = Completion::new(0, "sp", candidates(&["split", "spell"]));
+    completion.next(&longest_full);
+    assert_eq!(completion.word(), "split");
+
+    // longest:full
+    let mut completion = Completion::new(0, "s", candidates(&["split", "spell"]));
+    completion.next(&stages("longest:full"));
+    assert_eq!(completion.word(), "sp");
+    assert!(completion.shown);
+    completion.next(&stages("longest:full"));
+    assert_eq!(completion.word(), "sp");
+
+    // The single candidate is not cycled.
+    let mut completion = Completion::new(0, "vs", candidates(&["vsplit"]));
+    completion.next(&full);
+    completion.next(&full);
+    assert_eq!(completion.word(), "vsplit");
+  }
+
+  fn edit(text: &str, cursor: usize, edits: &[LineEdit]) -> (String, usize) {
+    let mut text = text.to_string();
+    let mut cursor = cursor;
+    for e in edits {
+      e.apply(&mut text, &mut cursor);
+    }
+    (text, cursor)
+  }
+
+  #[test]
+  fn line_edit1() {
+    let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+    assert_eq!(LineEdit::from_key(&ctrl('w')), Some(LineEdit::DeleteWord));
+    assert_eq!(LineEdit::from_key(&ctrl('a')), Some(LineEdit::Start));
+    assert_eq!(LineEdit::from_key(&ctrl('x')), None);
+    assert_eq!(
+      LineEdit::from_key(&KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT)),
+      Some(LineEdit::WordLeft)
+    );
+    assert_eq!(
+      LineEdit::from_key(&KeyEvent::new(KeyCode::Char('X'), KeyModifiers::SHIFT)),
+      Some(LineEdit::Insert('X'))
+    );
+
+    use LineEdit::*;
+    assert_eq!(
+      edit("edit", 4, &[Left, Insert('中')]),
+      ("edi中t".to_string(), 6)
+    );
+    assert_eq!(edit("中文", 6, &[DeleteBackward]), ("中".to_string(), 3));
+    assert_eq!(edit("abc", 0, &[DeleteForward, End]), ("bc".to_string(), 2));
+    assert_eq!(edit("abc", 0, &[DeleteBackward]), ("abc".to_string(), 0));
+    assert_eq!(
+      edit("e src/main.rs  ", 15, &[DeleteWord]),
+      ("e src/main.".to_string(), 11)
+    );
+    assert_eq!(edit("e src/", 6, &[DeleteWord]), ("e src".to_string(), 5));
+    assert_eq!(edit("e src", 5, &[DeleteWord]), ("e ".to_string(), 2));
+    assert_eq!(edit("e src", 3, &[DeleteToStart]), ("rc".to_string(), 0));
+    assert_eq!(edit("s/a/b/ g", 8, &[WordLeft]).1, 7);
+    assert_eq!(edit("s/a/b/ g", 8, &[WordLeft, WordLeft]).1, 0);
+    assert_eq!(edit("s/a/b/ g", 0, &[WordRight]).1, 7);
+    assert_eq!(edit("s/a/b/ g", 0, &[WordRight, WordRight]).1, 8);
+    assert_eq!(edit("s/a/b/ g", 3, &[Start, Right]).1, 1);
+  }
+}
@@ -0,0 +1,281 @@
+//! Cursor word highlighting.
+//!
+//! It highlights all the visible occurrences of the word under cursor in current window, i.e.
+//! the [vim-illuminate](https://github.com/RRethy/vim-illuminate) plugin.
+//!
+//! The highlighting is:
+//!
+//! 1. Per-mode: Only enabled in the modes specified by the 'cursorword' option.
+//! 2. Debounced: Only calculated after the cursor stays on a position for a while, thus moving
+//!    the cursor fast (i.e. holding `j`) doesn't calculate it on every key press.
+//! 3. Viewport-limited: Only the lines visible in the viewport are searched.
+
+use crate::defaults;
+use crate::envar;
+use crate::state::current_window_buffer;
+use crate::state::mode::{Mode, Modes};
+use crate::ui::tree::TreeArc;
+use crate::{rlock, wlock};
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// Whether a char is a word char, i.e. the 'iskeyword' option.
+fn is_word_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+/// Find the word under cursor, i.e. the `char_idx` of the `line`.
+///
+/// Returns `None` if the cursor is not on a word char.
+pub fn find_cursor_word(line: &str, char_idx: usize) -> Option<String> {
+  let chars: Vec<char> = line.chars().collect();
+  if char_idx >= chars.len() || !is_word_char(chars[char_idx]) {
+    return None;
+  }
+  let mut start = char_idx;
+  while start > 0 && is_word_char(chars[start - 1]) {
+    start -= 1;
+  }
+  let mut end = char_idx + 1;
+  while end < chars.len() && is_word_char(chars[end]) {
+    end += 1;
+  }
+  Some(chars[start..end].iter().collect())
+}
+
+/// Find all the whole-word occurrences of the `word` in the `line`.
+///
+/// Returns the char index ranges.
+pub fn find_word_occurrences(line: &str, word: &str) -> Vec<Range<usize>> {
+  let chars: Vec<char> = line.chars().collect();
+  let word: Vec<char> = word.chars().collect();
+  let mut result = vec![];
+  if word.is_empty() || chars.len() < word.len() {
+    return result;
+  }
+  let mut i = 0_usize;
+  while i + word.len() <= chars.len() {
+    if chars[i..i + word.len()] == word[..]
+      && (i == 0 || !is_word_char(chars[i - 1]))
+      && (i + word.len() == chars.len() || !is_word_char(chars[i + word.len()]))
+    {
+      result.push(i..i + word.len());
+      i += word.len();
+    } else {
+      i += 1;
+    }
+  }
+  result
+}
+
+/// Parse the 'cursorword' option value, i.e. comma-separated mode short names.
+///
+/// # Errors
+///
+/// If there's invalid mode name.
+pub fn parse_modes(value: &str) -> Result<Modes, String> {
+  let mut modes = Modes::new();
+  for name in value.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()) {
+    let mode = match name {
+      "n" => Mode::Normal,
+      "v" => Mode::Visual,
+      "s" => Mode::Select,
+      "o" => Mode::OperatorPending,
+      "i" => Mode::Insert,
+      "c" => Mode::CommandLine,
+      "t" => Mode::Terminal,
+      _ => return Err(format!("E474: Invalid argument: cursorword={}", value)),
+    };
+    modes.set(mode);
+  }
+  Ok(modes)
+}
+
+#[derive(Debug, Clone)]
+/// The cursor word highlighting state.
+pub struct CursorWordState {
+  // The 'cursorword' option.
+  option: String,
+  modes: Modes,
+
+  // Debounce delay.
+  delay: Duration,
+
+  // Last cursor position, i.e. line index and char index.
+  last_cursor: Option<(usize, usize)>,
+
+  // The moment to calculate highlighting.
+  deadline: Option<Instant>,
+}
+
+impl CursorWordState {
+  /// Make new state with the 'cursorword' option value.
+  ///
+  /// # Errors
+  ///
+  /// If the value is invalid.
+  pub fn new(option: &str) -> Result<Self, String> {
+    Ok(CursorWordState {
+      option: option.to_string(),
+      modes: parse_modes(option)?,
+      delay: defaults::cursor_word::CURSOR_WORD_DELAY,
+      last_cursor: None,
+      deadline: None,
+    })
+  }
+
+  /// Get the 'cursorword' option.
+  pub fn option(&self) -> &str {
+    &self.option
+  }
+
+  /// Set the 'cursorword' option, the option is not changed if the value is invalid.
+  ///
+  /// # Errors
+  ///
+  /// If the value is invalid.
+  pub fn set_option(&mut self, option: &str) -> Result<(), String> {
+    self.modes = parse_modes(option)?;
+    self.option = option.to_string();
+    // Force re-calculate on next cursor update.
+    self.last_cursor = None;
+    Ok(())
+  }
+
+  /// Whether highlighting is enabled in the `mode`.
+  pub fn is_enabled(&self, mode: Mode) -> bool {
+    self.modes.contains(&mode)
+  }
+
+  /// Get the moment to calculate highlighting (if there's a pending one).
+  pub fn deadline(&self) -> Option<Instant> {
+    self.deadline
+  }
+
+  /// Update cursor position after handling an event, it clears the highlighting and schedules a
+  /// new calculation if the cursor is moved.
+  pub fn on_cursor(&mut self, mode: Mode, tree: &TreeArc, now: Instant) {
+    let (_buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return,
+    };
+
+    if !self.is_enabled(mode) {
+      if self.last_cursor.is_some() || self.deadline.is_some() {
+        self.last_cursor = None;
+        self.deadline = None;
+        wlock!(viewport).set_word_highlights(BTreeMap::new());
+      }
+      return;
+    }
+
+    let cursor = {
+      let viewport = rlock!(viewport);
+      (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+    };
+    if self.last_cursor != Some(cursor) {
+      self.last_cursor = Some(cursor);
+      self.deadline = Some(now + self.delay);
+      wlock!(viewport).set_word_highlights(BTreeMap::new());
+    }
+  }
+
+  /// Calculate highlighting for the visible lines in current window.
+  pub fn refresh(&mut self, tree: &TreeArc) {
+    self.deadline = None;
+    let (buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return,
+    };
+
+    let (start_line_idx, end_line_idx, cursor_line_idx, cursor_char_idx) = {
+      let viewport = rlock!(viewport);
+      if viewport.is_empty() {
+        return;
+      }
+      (
+        viewport.start_line_idx(),
+        viewport.end_line_idx(),
+        viewport.cursor().line_idx(),
+        viewport.cursor().char_idx(),
+      )
+    };
+
+    let mut word_highlights = BTreeMap::new();
+    {
+      let buffer = rlock!(buffer);
+      let word = buffer
+        .get_line_content(cursor_line_idx)
+        .and_then(|line| find_cursor_word(&line, cursor_char_idx));
+      if let Some(word) = word {
+        for line_idx in start_line_idx..end_line_idx {
+          if let Some(line) = buffer.get_line_content(line_idx) {
+            let ranges = find_word_occurrences(&line, &word);
+            if !ranges.is_empty() {
+              word_highlights.insert(line_idx, ranges);
+            }
+          }
+        }
+      }
+    }
+    // NOTE: The buffer lock must be released before locking viewport.
+    wlock!(viewport).set_word_highlights(word_highlights);
+  }
+}
+
+impl Default for CursorWordState {
+  fn default() -> Self {
+    CursorWordState::new(defaults::cursor_word::CURSOR_WORD).unwrap()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn find_cursor_word1() {
+    let line = "let foo_bar = foo(bar);";
+    assert_eq!(find_cursor_word(line, 0), Some("let".to_string()));
+    assert_eq!(find_cursor_word(line, 6), Some("foo_bar".to_string()));
+    assert_eq!(find_cursor_word(line, 3), None);
+    assert_eq!(find_cursor_word(line, 14), Some("foo".to_string()));
+    assert_eq!(find_cursor_word(line, 100), None);
+    assert_eq!(find_cursor_word("你好 世界", 1), Some("你好".to_string()));
+  }
+
+  #[test]
+  fn find_word_occurrences1() {
+    let line = "foo foo_bar foo(foo) xfoo foo";
+    assert_eq!(
+      find_word_occurrences(line, "foo"),
+      vec![0..3, 12..15, 16..19, 26..29]
+    );
+    assert!(find_word_occurrences(line, "bar").is_empty());
+    assert!(find_word_occurrences("", "foo").is_empty());
+  }
+
+  #[test]
+  fn parse_modes1() {
+    let modes = parse_modes("n, v").unwrap();
+    assert_eq!(modes.len(), 2);
+    assert!(modes.contains(&Mode::Normal));
+    assert!(modes.contains(&Mode::Visual));
+    assert!(parse_modes("").unwrap().is_empty());
+    assert!(parse_modes("n,x").is_err());
+  }
+
+  #[test]
+  fn option1() {
+    let mut state = CursorWordState::default();
+    assert!(!state.is_enabled(Mode::Normal));
+    state.set_option("n").unwrap();
+    assert!(state.is_enabled(Mode::Normal));
+    assert!(!state.is_enabled(Mode::Insert));
+    assert!(state.set_option("z").is_err());
+    assert_eq!(state.option(), "n");
+    assert!(state.deadline().is_none());
+  }
+}
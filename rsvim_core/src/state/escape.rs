@@ -0,0 +1,200 @@
+//! Terminal escape timing, i.e. the 'ttimeout' and 'ttimeoutlen' options.
+//!
+//! The terminal sends the `Alt` modified keys as `Esc` followed by the key, i.e. `Alt+j` is sent
+//! as `\x1bj`. The event reader decodes them as `Alt+j` only if both bytes are read at once, they
+//! arrive separately over slow connections (i.e. ssh, tmux) and decoded as a lone `Esc` and `j`.
+//!
+//! Thus the lone `Esc` is held in the decode stage (before the key mappings and the editing mode),
+//! if a key sent as a single byte (i.e. a char, `Enter`) arrives within 'ttimeoutlen', they're
+//! combined as the `Alt` modified key. Otherwise the `Esc` is released on the next key or when
+//! 'ttimeoutlen' is reached. The 'ttimeoutlen' is much shorter than 'timeoutlen', thus the `Esc`
+//! doesn't lag.
+//!
+//! When 'ttimeout' is off, the `Esc` is never held.
+//!
+//! See: <https://vimhelp.org/options.txt.html#%27ttimeout%27>.
+
+use crate::defaults;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::time::{Duration, Instant};
+
+// Whether the key is a lone `Esc` press.
+fn is_lone_esc(key_event: &KeyEvent) -> bool {
+  key_event.code == KeyCode::Esc
+    && key_event.kind == KeyEventKind::Press
+    && key_event.modifiers.is_empty()
+}
+
+#[derive(Debug, Clone)]
+/// The escape decoder.
+pub struct EscapeDecoder {
+  ttimeout: bool,
+  ttimeout_len: Duration,
+  // The held `Esc`, and its deadline.
+  pending: Option<(KeyEvent, Instant)>,
+}
+
+impl Default for EscapeDecoder {
+  fn default() -> Self {
+    EscapeDecoder {
+      ttimeout: defaults::keymap::TTIMEOUT,
+      ttimeout_len: defaults::keymap::TTIMEOUT_LEN,
+      pending: None,
+    }
+  }
+}
+
+impl EscapeDecoder {
+  pub fn new() -> Self {
+    EscapeDecoder::default()
+  }
+
+  /// Get the 'ttimeout' option.
+  pub fn ttimeout(&self) -> bool {
+    self.ttimeout
+  }
+
+  pub fn set_ttimeout(&mut self, value: bool) {
+    self.ttimeout = value;
+  }
+
+  /// Get the 'ttimeoutlen' option.
+  pub fn ttimeout_len(&self) -> Duration {
+    self.ttimeout_len
+  }
+
+  pub fn set_ttimeout_len(&mut self, value: Duration) {
+    self.ttimeout_len = value;
+  }
+
+  /// Get the deadline of the held `Esc`, it's released when the deadline is reached.
+  pub fn deadline(&self) -> Option<Instant> {
+    self.pending.map(|(_, deadline)| deadline)
+  }
+
+  /// Decode the key, returns the keys to handle in order, it's empty if the key is held.
+  pub fn decode(&mut self, key_event: KeyEvent, now: Instant) -> Vec<KeyEvent> {
+    // The key releases/repeats don't affect the held `Esc`.
+    if key_event.kind != KeyEventKind::Press {
+      return vec![key_event];
+    }
+
+    let mut keys = vec![];
+    if let Some((esc, deadline)) = self.pending.take() {
+      // The keys sent as a single byte by the terminal, i.e. the chars, the `CTRL` chars, `Enter`,
+      // `Tab` and `Backspace`.
+      let combinable = matches!(
+        key_event.code,
+        KeyCode::Char(_) | KeyCode::Enter | KeyCode::Tab | KeyCode::Backspace
+      ) && !key_event.modifiers.contains(KeyModifiers::ALT);
+      if combinable && now <= deadline {
+        let mut alt_key = key_event;
+        alt_key.modifiers |= KeyModifiers::ALT;
+        return vec![alt_key];
+      }
+      keys.push(esc);
+    }
+
+    if self.ttimeout && !self.ttimeout_len.is_zero() && is_lone_esc(&key_event) {
+      self.pending = Some((key_event, now + self.ttimeout_len));
+    } else {
+      keys.push(key_event);
+    }
+    keys
+  }
+
+  /// Release the held `Esc` if its deadline is reached.
+  pub fn timeout(&mut self, now: Instant) -> Option<KeyEvent> {
+    match self.pending {
+      Some((_, deadline)) if deadline <= now => self.take_pending(),
+      _ => None,
+    }
+  }
+
+  /// Release the held `Esc` immediately, i.e. before handling the non-key events.
+  pub fn take_pending(&mut self) -> Option<KeyEvent> {
+    self.pending.take().map(|(esc, _)| esc)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+  }
+
+  #[test]
+  fn decode1() {
+    let mut decoder = EscapeDecoder::new();
+    let now = Instant::now();
+
+    // `Esc` followed by a char in time is `Alt` modified.
+    assert!(decoder.decode(key(KeyCode::Esc), now).is_empty());
+    assert_eq!(decoder.deadline(), Some(now + decoder.ttimeout_len()));
+    assert_eq!(
+      decoder.decode(key(KeyCode::Char('j')), now),
+      vec![KeyEvent::new(KeyCode::Char('j'), KeyModifiers::ALT)]
+    );
+    assert_eq!(decoder.deadline(), None);
+
+    // `Esc` followed by a char too late.
+    assert!(decoder.decode(key(KeyCode::Esc), now).is_empty());
+    let late = now + decoder.ttimeout_len() * 2;
+    assert_eq!(
+      decoder.decode(key(KeyCode::Char('j')), late),
+      vec![key(KeyCode::Esc), key(KeyCode::Char('j'))]
+    );
+
+    // `Esc` followed by `Enter` or a `CTRL` char.
+    assert!(decoder.decode(key(KeyCode::Esc), now).is_empty());
+    assert_eq!(
+      decoder.decode(key(KeyCode::Enter), now),
+      vec![KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT)]
+    );
+    assert!(decoder.decode(key(KeyCode::Esc), now).is_empty());
+    assert_eq!(
+      decoder.decode(
+        KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL),
+        now
+      ),
+      vec![KeyEvent::new(
+        KeyCode::Char('a'),
+        KeyModifiers::CONTROL | KeyModifiers::ALT
+      )]
+    );
+
+    // `Esc` followed by a key that's not a single byte, or another `Esc`.
+    assert!(decoder.decode(key(KeyCode::Esc), now).is_empty());
+    assert_eq!(
+      decoder.decode(key(KeyCode::Up), now),
+      vec![key(KeyCode::Esc), key(KeyCode::Up)]
+    );
+    assert!(decoder.decode(key(KeyCode::Esc), now).is_empty());
+    assert_eq!(
+      decoder.decode(key(KeyCode::Esc), now),
+      vec![key(KeyCode::Esc)]
+    );
+    assert_eq!(decoder.timeout(now), None);
+    assert_eq!(decoder.timeout(late), Some(key(KeyCode::Esc)));
+    assert_eq!(decoder.deadline(), None);
+  }
+
+  #[test]
+  fn decode2() {
+    // The `Esc` is never held when 'ttimeout' is off.
+    let mut decoder = EscapeDecoder::new();
+    decoder.set_ttimeout(false);
+    let now = Instant::now();
+    assert_eq!(
+      decoder.decode(key(KeyCode::Esc), now),
+      vec![key(KeyCode::Esc)]
+    );
+    assert_eq!(
+      decoder.decode(key(KeyCode::Char('j')), now),
+      vec![key(KeyCode::Char('j'))]
+    );
+  }
+}
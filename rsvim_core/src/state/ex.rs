@@ -0,0 +1,1100 @@
+//! Ex commands.
+//!
+//! The ex commands are the commands typed in the command-line mode (after the `:` prefix), for
+//! example `:w`, `:q`, `:1,10align =`, etc. A command line is parsed into [`ExCommandLine`], which
+//! contains an optional line range, the command name, the `!` (bang) flag and the arguments. Then
+//! the command name is resolved (either full name or abbreviation) with [`ExCommandsManager`] and
+//! the command handler is invoked.
+//!
+//! A command line with only the line range (i.e. `:10`, `:$`, `:'a`) jumps to the (last) line of
+//! the range.
+
+use crate::buf::{Buffer, BufferArc, BuffersManagerArc, Marks};
+use crate::envar;
+use crate::interrupt::InterruptArc;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::autocmd::AutocmdArgs;
+use crate::state::current_window_buffer;
+use crate::state::ex::read::ShellReadRequest;
+use crate::state::fsm::mark::{jump_to_line, restore_last_position, save_last_position};
+use crate::state::grep::GrepRequest;
+use crate::state::msg::{MessageLevel, MessagesArc};
+use crate::state::register::RegisterEntry;
+use crate::ui::tree::internal::Inodeable;
+use crate::ui::tree::{TreeArc, TreeNode};
+use crate::ui::widget::window::ViewportArc;
+use crate::{rlock, wlock};
+
+use compact_str::CompactString;
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub mod align;
+pub mod arglist;
+pub mod buffer;
+pub mod colorscheme;
+pub mod config;
+pub mod delete;
+pub mod diff_orig;
+pub mod echo;
+pub mod explorer;
+pub mod global;
+pub mod inspector;
+pub mod iteration;
+pub mod lines;
+pub mod memory;
+pub mod messages;
+pub mod quickfix;
+pub mod quit;
+pub mod read;
+pub mod redraw;
+pub mod remote;
+pub mod rename;
+pub mod session;
+pub mod sort;
+pub mod spell;
+pub mod substitute;
+pub mod undotree;
+pub mod window;
+pub mod write;
+
+/// The max count of recent command lines, i.e. the 'history' option.
+pub const HISTORY_SIZE: usize = 50;
+
+/// The max nesting depth of the do-family commands.
+pub const MAX_DO_DEPTH: usize = 10;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The line address in an ex command range.
+pub enum ExLineAddress {
+  /// The cursor line, i.e. `.`.
+  Current,
+  /// The last line in the buffer, i.e. `$`.
+  Last,
+  /// The absolute line number, starts from 1.
+  Number(usize),
+  /// The line relative to the cursor line, i.e. `.+N`, `-N`.
+  Offset(isize),
+  /// The line of the mark, i.e. `'a`, `'<`.
+  Mark(char),
+}
+
+impl ExLineAddress {
+  /// Resolve to the line index, starts from 0.
+  ///
+  /// # Errors
+  ///
+  /// If the mark is not set.
+  pub fn resolve(
+    &self,
+    current_line_idx: usize,
+    len_lines: usize,
+    marks: &Marks,
+  ) -> ExCommandResult<usize> {
+    match self {
+      ExLineAddress::Current => Ok(current_line_idx),
+      ExLineAddress::Last => Ok(len_lines.saturating_sub(1)),
+      ExLineAddress::Number(n) => Ok(n.saturating_sub(1)),
+      ExLineAddress::Offset(n) => Ok(current_line_idx.saturating_add_signed(*n)),
+      ExLineAddress::Mark(name) => marks
+        .get(*name)
+        .map(|pos| pos.line_idx)
+        .ok_or_else(|| ExCommandErr::Message("E20: Mark not set".to_string())),
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The line range of an ex command.
+pub enum ExLineRange {
+  /// All lines in the buffer, i.e. `%`.
+  All,
+  /// The lines between two addresses (both inclusive), i.e. `{start},{end}`.
+  Lines(ExLineAddress, ExLineAddress),
+}
+
+impl ExLineRange {
+  /// Resolve to the line index range (left-inclusive, right-exclusive), starts from 0.
+  ///
+  /// NOTE: A backward range is swapped.
+  ///
+  /// # Errors
+  ///
+  /// If the range is outside of the buffer, or the mark is not set.
+  pub fn resolve(
+    &self,
+    current_line_idx: usize,
+    len_lines: usize,
+    marks: &Marks,
+  ) -> ExCommandResult<Range<usize>> {
+    match self {
+      ExLineRange::All => Ok(0..len_lines),
+      ExLineRange::Lines(start, end) => {
+        let start = start.resolve(current_line_idx, len_lines, marks)?;
+        let end = end.resolve(current_line_idx, len_lines, marks)?;
+        let (start, end) = if start <= end {
+          (start, end)
+        } else {
+          (end, start)
+        };
+        if end >= len_lines {
+          return Err(ExCommandErr::InvalidRange);
+        }
+        Ok(start..end + 1)
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The parsed ex command line, i.e. `:[range]{command}[!] [args]`.
+pub struct ExCommandLine {
+  range: Option<ExLineRange>,
+  name: CompactString,
+  bang: bool,
+  args: String,
+}
+
+impl ExCommandLine {
+  /// Get the line range.
+  pub fn range(&self) -> &Option<ExLineRange> {
+    &self.range
+  }
+
+  /// Get the command name (or its abbreviation) as it's typed.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Whether the command has the `!` (bang) flag.
+  pub fn bang(&self) -> bool {
+    self.bang
+  }
+
+  /// Get the arguments, leading/trailing whitespaces are trimmed.
+  pub fn args(&self) -> &str {
+    &self.args
+  }
+
+  /// Resolve the line range, it fallbacks to `default` if there's no range.
+  pub fn resolve_range(
+    &self,
+    default: ExLineRange,
+    current_line_idx: usize,
+    len_lines: usize,
+    marks: &Marks,
+  ) -> ExCommandResult<Range<usize>> {
+    self
+      .range
+      .unwrap_or(default)
+      .resolve(current_line_idx, len_lines, marks)
+  }
+}
+
+// Parse the `+N`/`-N` offset, the `N` is 1 if it's omitted.
+fn parse_offset(s: &str) -> ExCommandResult<Option<(isize, &str)>> {
+  let sign = match s.chars().next() {
+    Some('+') => 1,
+    Some('-') => -1,
+    _ => return Ok(None),
+  };
+  let s = &s[1..];
+  let digits = s.chars().take_while(|c| c.is_ascii_digit()).count();
+  let n = if digits > 0 {
+    s[..digits]
+      .parse::<isize>()
+      .map_err(|_| ExCommandErr::InvalidRange)?
+  } else {
+    1
+  };
+  Ok(Some((sign * n, &s[digits..])))
+}
+
+/// Parse the line address at the start of `s`, returns the address (or `None` if there's no
+/// address) and the rest.
+pub fn parse_address(s: &str) -> ExCommandResult<(Option<ExLineAddress>, &str)> {
+  if let Some((n, rest)) = parse_offset(s)? {
+    return Ok((Some(ExLineAddress::Offset(n)), rest));
+  }
+  if let Some(rest) = s.strip_prefix('.') {
+    return match parse_offset(rest)? {
+      Some((n, rest)) => Ok((Some(ExLineAddress::Offset(n)), rest)),
+      None => Ok((Some(ExLineAddress::Current), rest)),
+    };
+  }
+  if let Some(rest) = s.strip_prefix('\'') {
+    let name = rest.chars().next().ok_or(ExCommandErr::InvalidRange)?;
+    return Ok((Some(ExLineAddress::Mark(name)), &rest[name.len_utf8()..]));
+  }
+  if let Some(rest) = s.strip_prefix('$') {
+    return Ok((Some(ExLineAddress::Last), rest));
+  }
+  let digits = s.chars().take_while(|c| c.is_ascii_digit()).count();
+  if digits > 0 {
+    let n = s[..digits]
+      .parse::<usize>()
+      .map_err(|_| ExCommandErr::InvalidRange)?;
+    return Ok((Some(ExLineAddress::Number(n)), &s[digits..]));
+  }
+  Ok((None, s))
+}
+
+fn parse_range(s: &str) -> ExCommandResult<(Option<ExLineRange>, &str)> {
+  if let Some(rest) = s.strip_prefix('%') {
+    return Ok((Some(ExLineRange::All), rest));
+  }
+  let (start, rest) = parse_address(s)?;
+  match start {
+    Some(start) => match rest.strip_prefix(',') {
+      Some(rest) => {
+        let (end, rest) = parse_address(rest)?;
+        let end = end.ok_or(ExCommandErr::InvalidRange)?;
+        Ok((Some(ExLineRange::Lines(start, end)), rest))
+      }
+      None => Ok((Some(ExLineRange::Lines(start, start)), rest)),
+    },
+    None => Ok((None, rest)),
+  }
+}
+
+impl FromStr for ExCommandLine {
+  type Err = ExCommandErr;
+
+  /// Parse `str` to ex command line, the leading `:` is optional.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim_start().trim_start_matches(':').trim_start();
+    let (range, rest) = parse_range(s)?;
+    let rest = rest.trim_start();
+    let name_len = rest.chars().take_while(|c| c.is_ascii_alphabetic()).count();
+    if name_len == 0 {
+      // Only the line range, i.e. `:{N}`.
+      if range.is_some() && rest.trim().is_empty() {
+        return Ok(ExCommandLine {
+          range,
+          name: CompactString::default(),
+          bang: false,
+          args: String::new(),
+        });
+      }
+      return Err(ExCommandErr::NotEditorCommand(s.to_string()));
+    }
+    let name = CompactString::new(&rest[..name_len]);
+    let rest = &rest[name_len..];
+    let (bang, rest) = match rest.strip_prefix('!') {
+      Some(rest) => (true, rest),
+      None => (false, rest),
+    };
+    Ok(ExCommandLine {
+      range,
+      name,
+      bang,
+      args: rest.trim().to_string(),
+    })
+  }
+}
+
+/// Split the command line on the unescaped `|`, the escaped `\|` is kept as a literal `|`.
+///
+/// NOTE: Only the commands taking other commands as arguments (i.e. `:argdo`) split on `|`.
+pub fn split_bar(s: &str) -> Vec<String> {
+  let mut commands = vec![];
+  let mut command = String::new();
+  let mut chars = s.chars().peekable();
+  while let Some(c) = chars.next() {
+    if c == '\\' && chars.peek() == Some(&'|') {
+      command.push('|');
+      chars.next();
+    } else if c == '|' {
+      commands.push(std::mem::take(&mut command));
+    } else {
+      command.push(c);
+    }
+  }
+  commands.push(command);
+  commands
+    .into_iter()
+    .map(|c| c.trim().to_string())
+    .filter(|c| !c.is_empty())
+    .collect()
+}
+
+/// Split the delimited arguments into at most `count` fields, i.e. the `/{pattern}/{string}/` in
+/// `:s/{pattern}/{string}/[flags]`. The delimiter is the first char, the escaped delimiter is kept
+/// as a literal delimiter, and the trailing delimiters are optional.
+///
+/// Returns the fields (missing ones are empty) and the rest after the last delimiter, or `None` if
+/// the delimiter is alphanumeric, `"`, `|` or `\`.
+pub fn split_delimited(s: &str, count: usize) -> Option<(Vec<String>, &str)> {
+  let delimiter = s.chars().next()?;
+  if delimiter.is_alphanumeric() || matches!(delimiter, '"' | '|' | '\\') {
+    return None;
+  }
+  let mut fields = vec![String::new()];
+  let mut chars = s.char_indices().skip(1).peekable();
+  while let Some((i, c)) = chars.next() {
+    if c == '\\' && chars.peek().map(|(_, next)| *next) == Some(delimiter) {
+      fields.last_mut().unwrap().push(delimiter);
+      chars.next();
+    } else if c == delimiter {
+      if fields.len() == count {
+        return Some((fields, &s[i + c.len_utf8()..]));
+      }
+      fields.push(String::new());
+    } else {
+      fields.last_mut().unwrap().push(c);
+    }
+  }
+  fields.resize(count, String::new());
+  Some((fields, ""))
+}
+
+/// Exclude the empty line after the trailing line break from `line_range`, it's not a real line,
+/// i.e. `:%sort` doesn't move it to the top.
+pub fn trim_eof_line(buffer: &Buffer, line_range: Range<usize>) -> Range<usize> {
+  let len_lines = buffer.len_lines();
+  if line_range.end == len_lines
+    && line_range.end > line_range.start
+    && len_lines > 1
+    && buffer
+      .get_line(len_lines - 1)
+      .is_some_and(|l| l.len_chars() == 0)
+  {
+    line_range.start..line_range.end - 1
+  } else {
+    line_range
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The data passed to each ex command handler, and allow them access the editor.
+pub struct ExCommandDataAccess {
+  pub tree: TreeArc,
+  pub buffers: BuffersManagerArc,
+  pub interrupt: InterruptArc,
+  pub messages: MessagesArc,
+
+  // The nesting depth of the do-family commands.
+  do_depth: usize,
+
+  // The scripts to source, i.e. `:source`.
+  scripts: Vec<PathBuf>,
+
+  // The emitted autocmd events, i.e. `BufWritePost`.
+  events: Vec<AutocmdArgs>,
+
+  // Whether to reload the user config, i.e. `:config reload`.
+  config_reload: bool,
+
+  // The search to start, i.e. `:grep`.
+  grep: Option<GrepRequest>,
+
+  // The registers snapshot, i.e. `:registers`.
+  registers: Vec<RegisterEntry>,
+
+  // Whether to quit the editor, i.e. `:qa`.
+  quit: bool,
+
+  // The shell commands to read, i.e. `:r !{cmd}`.
+  shell_reads: Vec<ShellReadRequest>,
+}
+
+impl ExCommandDataAccess {
+  pub fn new(
+    tree: TreeArc,
+    buffers: BuffersManagerArc,
+    interrupt: InterruptArc,
+    messages: MessagesArc,
+  ) -> Self {
+    ExCommandDataAccess {
+      tree,
+      buffers,
+      interrupt,
+      messages,
+      do_depth: 0,
+      scripts: vec![],
+      events: vec![],
+      config_reload: false,
+      grep: None,
+      registers: vec![],
+      quit: false,
+      shell_reads: vec![],
+    }
+  }
+
+  /// Set the registers snapshot, i.e. for `:registers`, since the editing state is not accessible
+  /// here.
+  pub fn with_registers(mut self, registers: Vec<RegisterEntry>) -> Self {
+    self.registers = registers;
+    self
+  }
+
+  /// Get the registers snapshot.
+  pub fn registers(&self) -> &Vec<RegisterEntry> {
+    &self.registers
+  }
+
+  /// Show the message to the user, i.e. `:echo`, it's also saved in the message history.
+  pub fn echo(&self, level: MessageLevel, text: &str) {
+    wlock!(self.messages).push(level, text);
+  }
+
+  /// Source the script file, i.e. `:source {file}`. The script is executed by the js runtime
+  /// after the command, since the js runtime is not accessible here.
+  pub fn source(&mut self, filename: PathBuf) {
+    self.scripts.push(filename);
+  }
+
+  /// Take the scripts to source.
+  pub fn take_scripts(&mut self) -> Vec<PathBuf> {
+    std::mem::take(&mut self.scripts)
+  }
+
+  /// Emit the autocmd event, it's queued to the editing state after the command, since the state
+  /// is not accessible here.
+  pub fn emit(&mut self, args: AutocmdArgs) {
+    self.events.push(args);
+  }
+
+  /// Take the emitted autocmd events.
+  pub fn take_events(&mut self) -> Vec<AutocmdArgs> {
+    std::mem::take(&mut self.events)
+  }
+
+  /// Reload the user config, i.e. `:config reload`. It's reloaded by the event loop after the
+  /// command, since the js runtime is not accessible here.
+  pub fn reload_config(&mut self) {
+    self.config_reload = true;
+  }
+
+  /// Take whether to reload the user config.
+  pub fn take_config_reload(&mut self) -> bool {
+    std::mem::take(&mut self.config_reload)
+  }
+
+  /// Start the search, i.e. `:grep`. It's started by the event loop after the command, the
+  /// previous request is replaced.
+  pub fn grep(&mut self, request: GrepRequest) {
+    self.grep = Some(request);
+  }
+
+  /// Take the search request.
+  pub fn take_grep(&mut self) -> Option<GrepRequest> {
+    self.grep.take()
+  }
+
+  /// Quit the editor, i.e. `:qa`. It's quit by the event loop after the command.
+  pub fn quit(&mut self) {
+    self.quit = true;
+  }
+
+  /// Take whether to quit the editor.
+  pub fn take_quit(&mut self) -> bool {
+    std::mem::take(&mut self.quit)
+  }
+
+  /// Read the shell command's output, i.e. `:r !{cmd}`. The command is run by the event loop after
+  /// the command.
+  pub fn read_shell(&mut self, request: ShellReadRequest) {
+    self.shell_reads.push(request);
+  }
+
+  /// Take the shell commands to read.
+  pub fn take_shell_reads(&mut self) -> Vec<ShellReadRequest> {
+    std::mem::take(&mut self.shell_reads)
+  }
+
+  /// Execute the command lines in the do-family commands, i.e. `:argdo {cmd}`.
+  ///
+  /// # Errors
+  ///
+  /// If the do-family commands are nested too deep (i.e. `:windo windo windo ...`), or it's
+  /// interrupted by `Ctrl-C`, or any command fails (the remaining commands are not executed).
+  pub fn run_nested(
+    &mut self,
+    commands: &ExCommandsManager,
+    command_lines: &[String],
+  ) -> ExCommandResult<()> {
+    if self.do_depth >= MAX_DO_DEPTH {
+      return Err(ExCommandErr::Message(
+        "E169: Command too recursive".to_string(),
+      ));
+    }
+    self.do_depth += 1;
+    let result = command_lines.iter().try_for_each(|line| {
+      self.interrupt.check()?;
+      commands.run(line, self)
+    });
+    self.do_depth -= 1;
+    result
+  }
+
+  /// Get the buffer and viewport of current window.
+  pub fn current_window_buffer(&self) -> Option<(BufferArc, ViewportArc)> {
+    current_window_buffer(&self.tree)
+  }
+
+  /// Show the `buffer` in current window, and move the cursor to the last position of the buffer
+  /// (see [`position`](crate::buf::position)), or the top-left corner.
+  pub fn show_buffer(&self, buffer: &BufferArc) {
+    save_last_position(&self.tree, &self.buffers);
+    self.show_buffer_at_top_left(buffer);
+    restore_last_position(&self.tree, &self.buffers);
+  }
+
+  /// Switch the windows showing the `buffer` to the next listed buffer (or the unnamed buffer if
+  /// there's no other one), i.e. before the buffer is wiped out by `:bdelete`/`:Delete`. Returns
+  /// the buffer that is switched to.
+  ///
+  /// NOTE: If the `buffer` is the last unnamed buffer, nothing is switched and it's returned.
+  pub fn switch_away_from(&self, buffer: &BufferArc) -> BufferArc {
+    let buffer_id = rlock!(buffer).id();
+    let next_buffer: BufferArc = {
+      let mut buffers = wlock!(self.buffers);
+      let other = buffers
+        .next_listed(buffer_id, 1, true)
+        .filter(|id| *id != buffer_id)
+        .and_then(|id| buffers.get(&id).cloned());
+      match other {
+        Some(other) => other,
+        None => match buffers.find_unnamed() {
+          Some(unnamed) => unnamed.clone(),
+          None => {
+            let unnamed_id = buffers.new_empty_buffer();
+            buffers.get(&unnamed_id).unwrap().clone()
+          }
+        },
+      }
+    };
+    if Arc::ptr_eq(&next_buffer, buffer) {
+      return next_buffer;
+    }
+
+    if self
+      .current_window_buffer()
+      .is_some_and(|(current, _)| Arc::ptr_eq(&current, buffer))
+    {
+      self.show_buffer(&next_buffer);
+    }
+    let mut tree = wlock!(self.tree);
+    let window_ids = tree.window_ids().iter().copied().collect::<Vec<_>>();
+    for window_id in window_ids {
+      if let Some(TreeNode::Window(window)) = tree.node_mut(&window_id) {
+        if window
+          .buffer()
+          .upgrade()
+          .is_some_and(|b| Arc::ptr_eq(&b, buffer))
+        {
+          window.set_buffer(Arc::downgrade(&next_buffer));
+          window.sync_gutters();
+        }
+      }
+    }
+    next_buffer
+  }
+
+  fn show_buffer_at_top_left(&self, buffer: &BufferArc) {
+    let mut tree = wlock!(self.tree);
+    if let Some(window_id) = tree.current_window_id() {
+      let mut content_offset = 0;
+      if let Some(TreeNode::Window(window)) = tree.node_mut(&window_id) {
+        window.set_buffer(Arc::downgrade(buffer));
+        window.sync_gutters();
+        content_offset = window.content_offset() as isize;
+      }
+      if let Some(cursor_id) = tree.cursor_id() {
+        let cursor_pos = tree.node(&cursor_id).unwrap().shape().min();
+        tree.bounded_move_by(cursor_id, content_offset - cursor_pos.x, -cursor_pos.y);
+      }
+    }
+  }
+
+  /// Edit the file in current window, i.e. `:edit {file}`. The file buffer is created if it's not
+  /// opened yet.
+  ///
+  /// NOTE: The remote files are fetched by the event loop, thus only the opened ones can be edited,
+  /// see [`remote`](crate::buf::remote).
+  pub fn edit_file(&self, filename: &Path) -> ExCommandResult<()> {
+    let buffer = {
+      let mut buffers = wlock!(self.buffers);
+      let name = filename.to_string_lossy();
+      if crate::buf::remote::is_remote(&name) {
+        match buffers.find_by_url(&name) {
+          Some(buffer) => buffer.clone(),
+          None => {
+            return Err(ExCommandErr::Message(format!(
+              "E484: Can't open remote file {:?}, it's not opened",
+              name
+            )))
+          }
+        }
+      } else {
+        match buffers.find_by_path(filename) {
+          Some(buffer) => buffer.clone(),
+          None => {
+            let buffer_id = buffers.new_file_buffer(filename).map_err(|e| {
+              ExCommandErr::Message(format!("E484: Can't open file {:?}: {}", filename, e))
+            })?;
+            buffers.get(&buffer_id).unwrap().clone()
+          }
+        }
+      }
+    };
+    self.show_buffer(&buffer);
+    Ok(())
+  }
+}
+
+/// The ex command handler.
+///
+/// The ex commands manager is passed to the handler thus it can execute other commands, i.e.
+/// `:argdo {cmd}`.
+pub type ExCommandHandler =
+  fn(&ExCommandLine, &mut ExCommandDataAccess, &ExCommandsManager) -> ExCommandResult<()>;
+
+#[derive(Debug, Clone)]
+/// The ex command definition.
+pub struct ExCommandDefinition {
+  name: CompactString,
+  abbr_len: usize,
+  description: CompactString,
+  handler: ExCommandHandler,
+  complete_files: bool,
+}
+
+impl ExCommandDefinition {
+  /// Make new definition, the `abbr` is the shortest abbreviation for the command name, i.e. the
+  /// `w` for `:w[rite]`.
+  ///
+  /// # Panics
+  ///
+  /// If `abbr` is not a prefix of `name`.
+  pub fn new(name: &str, abbr: &str, description: &str, handler: ExCommandHandler) -> Self {
+    assert!(!abbr.is_empty());
+    assert!(name.starts_with(abbr));
+    ExCommandDefinition {
+      name: CompactString::new(name),
+      abbr_len: abbr.len(),
+      description: CompactString::new(description),
+      handler,
+      complete_files: false,
+    }
+  }
+
+  /// Complete the arguments as file names, i.e. `:edit {file}`, see
+  /// [`cmdline`](crate::state::cmdline).
+  pub fn with_file_completion(mut self) -> Self {
+    self.complete_files = true;
+    self
+  }
+
+  /// Whether the arguments are completed as file names.
+  pub fn complete_files(&self) -> bool {
+    self.complete_files
+  }
+
+  /// Get the full command name.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Get the shortest abbreviation.
+  pub fn abbr(&self) -> &str {
+    &self.name[..self.abbr_len]
+  }
+
+  /// Get the description.
+  pub fn description(&self) -> &str {
+    &self.description
+  }
+
+  /// Get the handler.
+  pub fn handler(&self) -> ExCommandHandler {
+    self.handler
+  }
+
+  /// Whether the typed `name` matches this command, i.e. it's the full name or a valid
+  /// abbreviation.
+  pub fn matches(&self, name: &str) -> bool {
+    name.len() >= self.abbr_len && self.name.starts_with(name)
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The manager for all ex commands, i.e. the ex command registry.
+pub struct ExCommandsManager {
+  commands: BTreeMap<CompactString, ExCommandDefinition>,
+
+  // Recent command lines, the most recent one is at the back.
+  history: VecDeque<CompactString>,
+}
+
+impl ExCommandsManager {
+  /// Make new manager, with all the builtin ex commands registered.
+  pub fn new() -> Self {
+    let mut manager = ExCommandsManager {
+      commands: BTreeMap::new(),
+      history: VecDeque::new(),
+    };
+    manager.register(align::definition());
+    manager.register(delete::definition());
+    manager.register(diff_orig::definition());
+    manager.register(colorscheme::definition());
+    manager.register(config::definition());
+    manager.register(memory::definition());
+    manager.register(messages::definition());
+    manager.register(read::definition());
+    manager.register(redraw::definition());
+    manager.register(remote::definition());
+    manager.register(sort::definition());
+    manager.register(spell::definition());
+    manager.register(substitute::definition());
+    for definition in arglist::definitions()
+      .into_iter()
+      .chain(buffer::definitions())
+      .chain(echo::definitions())
+      .chain(explorer::definitions())
+      .chain(global::definitions())
+      .chain(inspector::definitions())
+      .chain(iteration::definitions())
+      .chain(lines::definitions())
+      .chain(quickfix::definitions())
+      .chain(quit::definitions())
+      .chain(rename::definitions())
+      .chain(session::definitions())
+      .chain(undotree::definitions())
+      .chain(window::definitions())
+      .chain(write::definitions())
+    {
+      manager.register(definition);
+    }
+    manager
+  }
+
+  /// Register an ex command, returns the previous definition if the name is already registered.
+  pub fn register(&mut self, definition: ExCommandDefinition) -> Option<ExCommandDefinition> {
+    self
+      .commands
+      .insert(CompactString::new(definition.name()), definition)
+  }
+
+  /// Unregister an ex command by its full name.
+  pub fn unregister(&mut self, name: &str) -> Option<ExCommandDefinition> {
+    self.commands.remove(name)
+  }
+
+  /// Find the ex command by its full name or abbreviation.
+  pub fn get(&self, name: &str) -> Option<&ExCommandDefinition> {
+    match self.commands.get(name) {
+      Some(definition) => Some(definition),
+      None => self.commands.values().find(|d| d.matches(name)),
+    }
+  }
+
+  /// Whether the manager is empty.
+  pub fn is_empty(&self) -> bool {
+    self.commands.is_empty()
+  }
+
+  /// Ex commands count.
+  pub fn len(&self) -> usize {
+    self.commands.len()
+  }
+
+  /// Get the iterator of all ex commands, ordered by name.
+  pub fn iter(&self) -> ExCommandsManagerIter {
+    self.commands.iter()
+  }
+
+  /// Get the recent command lines, the most recent one is at the back.
+  pub fn history(&self) -> &VecDeque<CompactString> {
+    &self.history
+  }
+
+  // Save the command line to history, the duplicated one is moved to the back.
+  fn push_history(&mut self, command_line: &str) {
+    let command_line = command_line.trim().trim_start_matches(':').trim();
+    if command_line.is_empty() {
+      return;
+    }
+    self.history.retain(|h| h != command_line);
+    self.history.push_back(CompactString::new(command_line));
+    while self.history.len() > HISTORY_SIZE {
+      self.history.pop_front();
+    }
+  }
+
+  /// Parse and execute the command line, the command line is saved to history.
+  pub fn execute(
+    &mut self,
+    command_line: &str,
+    data_access: &mut ExCommandDataAccess,
+  ) -> ExCommandResult<()> {
+    self.push_history(command_line);
+    self.run(command_line, data_access)
+  }
+
+  /// Parse and execute the command line, without saving to history, i.e. the commands executed
+  /// by other commands.
+  pub fn run(
+    &self,
+    command_line: &str,
+    data_access: &mut ExCommandDataAccess,
+  ) -> ExCommandResult<()> {
+    let command_line = ExCommandLine::from_str(command_line)?;
+    if command_line.name().is_empty() {
+      return goto_line(&command_line, data_access);
+    }
+    match self.get(command_line.name()) {
+      Some(definition) => (definition.handler())(&command_line, data_access, self),
+      None => Err(ExCommandErr::NotEditorCommand(
+        command_line.name().to_string(),
+      )),
+    }
+  }
+}
+
+// Jump to the (last) line of the range, i.e. `:{N}`. The line is clamped to the buffer.
+fn goto_line(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+) -> ExCommandResult<()> {
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let current_line_idx = rlock!(viewport).cursor().line_idx();
+  let line_idx = {
+    let buffer = rlock!(buffer);
+    match command_line.range() {
+      Some(ExLineRange::Lines(_, end)) => {
+        end.resolve(current_line_idx, buffer.len_lines(), buffer.marks())?
+      }
+      Some(ExLineRange::All) | None => usize::MAX,
+    }
+  };
+  jump_to_line(&data_access.tree, line_idx);
+  Ok(())
+}
+
+impl Default for ExCommandsManager {
+  fn default() -> Self {
+    ExCommandsManager::new()
+  }
+}
+
+pub type ExCommandsManagerIter<'a> =
+  std::collections::btree_map::Iter<'a, CompactString, ExCommandDefinition>;
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::buf::MarkPos;
+  use crate::test::buf::make_buffer_from_lines;
+
+  #[test]
+  fn parse1() {
+    let cmd = ExCommandLine::from_str(":align =").unwrap();
+    assert_eq!(*cmd.range(), None);
+    assert_eq!(cmd.name(), "align");
+    assert!(!cmd.bang());
+    assert_eq!(cmd.args(), "=");
+
+    let cmd = ExCommandLine::from_str("%align! ,  ").unwrap();
+    assert_eq!(*cmd.range(), Some(ExLineRange::All));
+    assert!(cmd.bang());
+    assert_eq!(cmd.args(), ",");
+
+    let cmd = ExCommandLine::from_str("3,$align").unwrap();
+    assert_eq!(
+      *cmd.range(),
+      Some(ExLineRange::Lines(
+        ExLineAddress::Number(3),
+        ExLineAddress::Last
+      ))
+    );
+    assert_eq!(cmd.args(), "");
+
+    let cmd = ExCommandLine::from_str(".align :").unwrap();
+    assert_eq!(
+      *cmd.range(),
+      Some(ExLineRange::Lines(
+        ExLineAddress::Current,
+        ExLineAddress::Current
+      ))
+    );
+  }
+
+  #[test]
+  fn parse_marks1() {
+    let cmd = ExCommandLine::from_str("'<,'>align =").unwrap();
+    assert_eq!(
+      *cmd.range(),
+      Some(ExLineRange::Lines(
+        ExLineAddress::Mark('<'),
+        ExLineAddress::Mark('>')
+      ))
+    );
+    assert_eq!(cmd.name(), "align");
+
+    let cmd = ExCommandLine::from_str(".,.+2align").unwrap();
+    assert_eq!(
+      *cmd.range(),
+      Some(ExLineRange::Lines(
+        ExLineAddress::Current,
+        ExLineAddress::Offset(2)
+      ))
+    );
+    let cmd = ExCommandLine::from_str("-,+align").unwrap();
+    assert_eq!(
+      *cmd.range(),
+      Some(ExLineRange::Lines(
+        ExLineAddress::Offset(-1),
+        ExLineAddress::Offset(1)
+      ))
+    );
+    assert!(ExCommandLine::from_str("'").is_err());
+  }
+
+  #[test]
+  fn parse2() {
+    assert!(ExCommandLine::from_str("").is_err());
+    assert!(ExCommandLine::from_str("1,").is_err());
+    assert!(ExCommandLine::from_str("10#").is_err());
+    assert!(ExCommandLine::from_str("10 !").is_err());
+  }
+
+  #[test]
+  fn parse_goto1() {
+    let cmd = ExCommandLine::from_str(":10").unwrap();
+    assert_eq!(
+      *cmd.range(),
+      Some(ExLineRange::Lines(
+        ExLineAddress::Number(10),
+        ExLineAddress::Number(10)
+      ))
+    );
+    assert_eq!(cmd.name(), "");
+    let cmd = ExCommandLine::from_str(" $ ").unwrap();
+    assert_eq!(
+      *cmd.range(),
+      Some(ExLineRange::Lines(ExLineAddress::Last, ExLineAddress::Last))
+    );
+    assert_eq!(cmd.name(), "");
+  }
+
+  #[test]
+  fn resolve_range1() {
+    let mut marks = Marks::new();
+    assert_eq!(ExLineRange::All.resolve(3, 10, &marks), Ok(0..10));
+    let range = ExLineRange::Lines(ExLineAddress::Current, ExLineAddress::Last);
+    assert_eq!(range.resolve(3, 10, &marks), Ok(3..10));
+    let range = ExLineRange::Lines(ExLineAddress::Number(5), ExLineAddress::Number(2));
+    assert_eq!(range.resolve(0, 10, &marks), Ok(1..5));
+    let range = ExLineRange::Lines(ExLineAddress::Number(1), ExLineAddress::Number(11));
+    assert_eq!(
+      range.resolve(0, 10, &marks),
+      Err(ExCommandErr::InvalidRange)
+    );
+    let range = ExLineRange::Lines(ExLineAddress::Offset(-1), ExLineAddress::Offset(2));
+    assert_eq!(range.resolve(3, 10, &marks), Ok(2..6));
+
+    let range = ExLineRange::Lines(ExLineAddress::Mark('<'), ExLineAddress::Mark('>'));
+    assert!(range.resolve(0, 10, &marks).is_err());
+    marks.set('<', MarkPos::new(2, 3));
+    marks.set('>', MarkPos::new(4, 0));
+    assert_eq!(range.resolve(0, 10, &marks), Ok(2..5));
+  }
+
+  #[test]
+  fn get1() {
+    let manager = ExCommandsManager::new();
+    assert_eq!(manager.get("align").unwrap().name(), "align");
+    assert_eq!(manager.get("ali").unwrap().name(), "align");
+    assert_eq!(manager.get("sor").unwrap().name(), "sort");
+    assert_eq!(manager.get("s").unwrap().name(), "substitute");
+    assert_eq!(manager.get("g").unwrap().name(), "global");
+    assert_eq!(manager.get("v").unwrap().name(), "vglobal");
+    assert!(manager.get("al").is_none());
+    assert_eq!(manager.get("redr").unwrap().name(), "redraw");
+    assert_eq!(manager.get("sp").unwrap().name(), "split");
+    assert_eq!(manager.get("vs").unwrap().name(), "vsplit");
+    assert_eq!(manager.get("Nw").unwrap().name(), "Nwrite");
+    assert_eq!(manager.get("e").unwrap().name(), "edit");
+    assert_eq!(manager.get("on").unwrap().name(), "only");
+    assert_eq!(manager.get("mks").unwrap().name(), "mksession");
+    assert_eq!(manager.get("conf").unwrap().name(), "config");
+    assert_eq!(manager.get("so").unwrap().name(), "source");
+    assert_eq!(manager.get("spe").unwrap().name(), "spellgood");
+    assert_eq!(manager.get("w").unwrap().name(), "write");
+    assert_eq!(manager.get("up").unwrap().name(), "update");
+    assert_eq!(manager.get("Ren").unwrap().name(), "Rename");
+    assert_eq!(manager.get("Mov").unwrap().name(), "Move");
+    assert!(manager.get("notexist").is_none());
+  }
+
+  #[test]
+  fn split_bar1() {
+    assert_eq!(
+      split_bar("%s/foo/bar/ | update"),
+      vec!["%s/foo/bar/", "update"]
+    );
+    assert_eq!(split_bar("align a\\|b"), vec!["align a|b"]);
+    assert!(split_bar(" | ").is_empty());
+  }
+
+  #[test]
+  fn split_delimited1() {
+    let fields = |v: Vec<&str>| v.into_iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+      split_delimited("/foo/bar/g", 2),
+      Some((fields(vec!["foo", "bar"]), "g"))
+    );
+    assert_eq!(
+      split_delimited("#a\\#b#c", 2),
+      Some((fields(vec!["a#b", "c"]), ""))
+    );
+    assert_eq!(
+      split_delimited("/foo", 2),
+      Some((fields(vec!["foo", ""]), ""))
+    );
+    assert_eq!(
+      split_delimited("/x\\d+/s/a/b/", 1),
+      Some((fields(vec!["x\\d+"]), "s/a/b/"))
+    );
+    assert_eq!(split_delimited("afoo", 1), None);
+    assert_eq!(split_delimited("", 1), None);
+  }
+
+  #[test]
+  fn trim_eof_line1() {
+    let buffer = make_buffer_from_lines(vec!["a\n", "b\n"]);
+    let buffer = rlock!(buffer);
+    assert_eq!(trim_eof_line(&buffer, 0..3), 0..2);
+    assert_eq!(trim_eof_line(&buffer, 1..2), 1..2);
+    assert_eq!(trim_eof_line(&buffer, 2..3), 2..2);
+
+    let buffer = make_buffer_from_lines(vec!["a\n", "b"]);
+    assert_eq!(trim_eof_line(&rlock!(buffer), 0..2), 0..2);
+  }
+
+  #[test]
+  fn history1() {
+    let mut manager = ExCommandsManager::new();
+    manager.push_history(":align =");
+    manager.push_history("  ");
+    manager.push_history("w");
+    manager.push_history("align =");
+    assert_eq!(
+      manager.history().iter().collect::<Vec<_>>(),
+      vec!["w", "align ="]
+    );
+    for i in 0..HISTORY_SIZE {
+      manager.push_history(&format!("{}align", i + 1));
+    }
+    assert_eq!(manager.history().len(), HISTORY_SIZE);
+    assert_eq!(manager.history().front().unwrap(), "1align");
+  }
+}
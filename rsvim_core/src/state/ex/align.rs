@@ -0,0 +1,200 @@
+//! The `:align` ex command.
+//!
+//! `:[range]ali[gn] {pattern}` aligns the lines in `[range]` (default is all lines) on the first
+//! match of `{pattern}`, i.e. pads whitespaces before the matched delimiter so they start at the
+//! same display column. Lines that don't contain the delimiter are not changed.
+//!
+//! The alignment is based on the display width (not the chars count), thus the tab and CJK
+//! characters are still aligned correctly.
+
+use crate::buf::Buffer;
+use crate::envar;
+use crate::interrupt::Interrupt;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager, ExLineRange,
+};
+use crate::{rlock, wlock};
+
+use regex::Regex;
+use std::ops::Range;
+
+/// The `:align` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new(
+    "align",
+    "ali",
+    "Align lines on the first match of a pattern",
+    handle,
+  )
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if command_line.args().is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+  let pattern =
+    Regex::new(command_line.args()).map_err(|e| ExCommandErr::Message(e.to_string()))?;
+
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let current_line_idx = rlock!(viewport).cursor().line_idx();
+
+  {
+    let mut buffer = wlock!(buffer);
+    let line_range = command_line.resolve_range(
+      ExLineRange::All,
+      current_line_idx,
+      buffer.len_lines(),
+      buffer.marks(),
+    )?;
+    align_lines(&mut buffer, line_range, &pattern, &data_access.interrupt)?;
+  }
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+  Ok(())
+}
+
+/// Align the lines in `line_range` on the first match of `pattern`.
+///
+/// Returns the count of changed lines.
+///
+/// # Errors
+///
+/// If it's interrupted by `Ctrl-C` while scanning the lines, the buffer is not changed.
+pub fn align_lines(
+  buffer: &mut Buffer,
+  line_range: Range<usize>,
+  pattern: &Regex,
+  interrupt: &Interrupt,
+) -> ExCommandResult<usize> {
+  // The prefix (before the delimiter, trailing whitespaces are trimmed) and its display width for
+  // each matched line.
+  let mut prefixes: Vec<(usize, String, String, usize)> = vec![];
+  for line_idx in line_range {
+    interrupt.check()?;
+    let content = match buffer.get_line_content(line_idx) {
+      Some(content) => content,
+      None => break,
+    };
+    if let Some(m) = pattern.find(&content) {
+      let prefix = content[..m.start()].trim_end().to_string();
+      let suffix = content[m.start()..].to_string();
+      let width = buffer.width_before(line_idx, prefix.chars().count());
+      prefixes.push((line_idx, prefix, suffix, width));
+    }
+  }
+
+  let target_width = match prefixes.iter().map(|(_, _, _, width)| *width).max() {
+    Some(target_width) => target_width,
+    None => return Ok(0),
+  };
+
+  let mut changed = 0_usize;
+  for (line_idx, prefix, suffix, width) in prefixes.iter() {
+    let padding = if target_width > 0 {
+      target_width - width + 1
+    } else {
+      0
+    };
+    let aligned = format!("{}{}{}", prefix, " ".repeat(padding), suffix);
+    if buffer.get_line_content(*line_idx).as_deref() != Some(aligned.as_str()) {
+      buffer.replace_line_content(*line_idx, &aligned);
+      changed += 1;
+    }
+  }
+  Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::test::buf::make_buffer_from_lines;
+
+  fn lines(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.len_lines())
+      .map(|i| buffer.get_line_content(i).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn align_lines1() {
+    let buffer = make_buffer_from_lines(vec!["a = 1\n", "bbb= 2\n", "no delimiter\n", "cc =3\n"]);
+    let mut buffer = wlock!(buffer);
+    let changed = align_lines(
+      &mut buffer,
+      0..4,
+      &Regex::new("=").unwrap(),
+      &Interrupt::new(),
+    )
+    .unwrap();
+    assert_eq!(changed, 3);
+    assert_eq!(
+      lines(&buffer),
+      vec!["a   = 1", "bbb = 2", "no delimiter", "cc  =3", ""]
+    );
+  }
+
+  #[test]
+  fn align_lines2() {
+    let buffer = make_buffer_from_lines(vec!["你好=1\n", "abc=2\n", "\tx=3\n"]);
+    let mut buffer = wlock!(buffer);
+    align_lines(
+      &mut buffer,
+      0..3,
+      &Regex::new("=").unwrap(),
+      &Interrupt::new(),
+    )
+    .unwrap();
+    let actual = lines(&buffer);
+    assert_eq!(actual[0], format!("你好{}=1", " ".repeat(6)));
+    assert_eq!(actual[1], format!("abc{}=2", " ".repeat(7)));
+    assert_eq!(actual[2], "\tx =3");
+    for (i, line) in actual.iter().take(3).enumerate() {
+      let c = line.find('=').unwrap();
+      let char_idx = line[..c].chars().count();
+      assert_eq!(buffer.width_before(i, char_idx), 10);
+    }
+  }
+
+  #[test]
+  fn align_lines3() {
+    let buffer = make_buffer_from_lines(vec!["a, b\n", "ccc, d\n"]);
+    let changed = align_lines(
+      &mut wlock!(buffer),
+      1..2,
+      &Regex::new(",").unwrap(),
+      &Interrupt::new(),
+    )
+    .unwrap();
+    assert_eq!(changed, 1);
+    assert_eq!(lines(&rlock!(buffer)), vec!["a, b", "ccc , d", ""]);
+  }
+
+  #[test]
+  fn align_lines4() {
+    // Interrupted.
+    let buffer = make_buffer_from_lines(vec![
+      "a = 1
+", "bbb= 2
+",
+    ]);
+    let interrupt = Interrupt::new();
+    interrupt.interrupt();
+    let actual = align_lines(
+      &mut wlock!(buffer),
+      0..2,
+      &Regex::new("=").unwrap(),
+      &interrupt,
+    );
+    assert_eq!(actual, Err(ExCommandErr::Interrupted));
+    assert_eq!(lines(&rlock!(buffer)), vec!["a = 1", "bbb= 2", ""]);
+  }
+}
@@ -0,0 +1,210 @@
+//! The argument list ex commands.
+//!
+//! - `:ar[gs]` lists the arglist, the current file is wrapped with `[]`.
+//! - `:[count]n[ext]` edits the `[count]`th next file.
+//! - `:[count]prev[ious]` (or `:[count]N[ext]`) edits the `[count]`th previous file.
+//! - `:arga[dd] {file} ..` adds files after the current file, the files are expanded (see
+//!   [`expand`](crate::buf::expand)).
+//! - `:drop {file} ..` opens the files as buffers, sets the arglist to them and edits the first
+//!   file, the special chars in file names are escaped with `\`.
+//! - `:argdo {cmd}` edits each file in the arglist and executes `{cmd}`, multiple commands can be
+//!   separated with `|`, i.e. `:argdo %s/foo/bar/ | update`.
+//!
+//! NOTE: The `[count]` is given as the range, i.e. `:2next`.
+
+use crate::buf::expand::expand_path;
+use crate::buf::ArgList;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  split_bar, ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+  ExLineAddress, ExLineRange,
+};
+use crate::state::msg::MessageLevel;
+use crate::state::paste::parse_dropped_paths;
+use crate::{rlock, wlock};
+
+use std::path::PathBuf;
+
+/// The arglist definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new("args", "ar", "List the argument list", handle_args),
+    ExCommandDefinition::new(
+      "next",
+      "n",
+      "Edit the next file in the argument list",
+      handle_next,
+    ),
+    ExCommandDefinition::new(
+      "previous",
+      "prev",
+      "Edit the previous file in the argument list",
+      handle_previous,
+    ),
+    ExCommandDefinition::new(
+      "Next",
+      "N",
+      "Edit the previous file in the argument list",
+      handle_previous,
+    ),
+    ExCommandDefinition::new(
+      "argadd",
+      "arga",
+      "Add files to the argument list",
+      handle_argadd,
+    )
+    .with_file_completion(),
+    ExCommandDefinition::new(
+      "drop",
+      "dr",
+      "Open files and set the argument list",
+      handle_drop,
+    )
+    .with_file_completion(),
+    ExCommandDefinition::new(
+      "argdo",
+      "argdo",
+      "Execute commands on each file in the argument list",
+      handle_argdo,
+    ),
+  ]
+}
+
+/// Get the `[count]` from the range, default is 1.
+fn count(command_line: &ExCommandLine) -> usize {
+  match command_line.range() {
+    Some(ExLineRange::Lines(_, ExLineAddress::Number(n))) if *n > 0 => *n,
+    _ => 1,
+  }
+}
+
+/// Format the `:args` output, the current file is wrapped with `[]`.
+pub fn format_args(files: &[PathBuf], current: Option<usize>) -> String {
+  files
+    .iter()
+    .enumerate()
+    .map(|(i, file)| {
+      if Some(i) == current {
+        format!("[{}]", file.display())
+      } else {
+        file.display().to_string()
+      }
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn handle_args(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let buffers = rlock!(data_access.buffers);
+  let arglist = buffers.arglist();
+  data_access.echo(
+    MessageLevel::Info,
+    &format_args(arglist.files(), arglist.current()),
+  );
+  Ok(())
+}
+
+fn handle_next(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let file = wlock!(data_access.buffers)
+    .arglist_mut()
+    .next(count(command_line))
+    .cloned()
+    .ok_or_else(|| ExCommandErr::Message("E165: Cannot go beyond last file".to_string()))?;
+  data_access.edit_file(&file)
+}
+
+fn handle_previous(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let file = wlock!(data_access.buffers)
+    .arglist_mut()
+    .prev(count(command_line))
+    .cloned()
+    .ok_or_else(|| ExCommandErr::Message("E164: Cannot go before first file".to_string()))?;
+  data_access.edit_file(&file)
+}
+
+fn handle_argadd(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let files = command_line
+    .args()
+    .split_whitespace()
+    .flat_map(expand_path)
+    .collect::<Vec<_>>();
+  if files.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+  wlock!(data_access.buffers).arglist_mut().add(files);
+  Ok(())
+}
+
+fn handle_drop(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let files = parse_dropped_paths(command_line.args()).ok_or(ExCommandErr::ArgumentRequired)?;
+  {
+    let mut buffers = wlock!(data_access.buffers);
+    for file in files.iter() {
+      if buffers.find_by_path(file).is_none() {
+        buffers
+          .new_file_buffer(file)
+          .map_err(|e| ExCommandErr::Message(format!("E484: Can't open file {:?}: {}", file, e)))?;
+      }
+    }
+    *buffers.arglist_mut() = ArgList::new(files.clone());
+  }
+  data_access.edit_file(&files[0])
+}
+
+fn handle_argdo(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let command_lines = split_bar(command_line.args());
+  if command_lines.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+
+  let files_count = rlock!(data_access.buffers).arglist().len();
+  for i in 0..files_count {
+    let file = wlock!(data_access.buffers)
+      .arglist_mut()
+      .set_current(i)
+      .cloned();
+    if let Some(file) = file {
+      data_access.edit_file(&file)?;
+      // The error stops the iteration, i.e. Vim stops at the first error.
+      data_access.run_nested(commands, &command_lines)?;
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn format_args1() {
+    let files = vec![PathBuf::from("a.md"), PathBuf::from("b.md")];
+    assert_eq!(format_args(&files, Some(1)), "a.md [b.md]");
+    assert_eq!(format_args(&files, None), "a.md b.md");
+  }
+}
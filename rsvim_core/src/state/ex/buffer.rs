@@ -0,0 +1,259 @@
+//! The buffer ex commands.
+//!
+//! - `:e[dit] {file}` edits `{file}` in current window, the file is expanded (see
+//!   [`expand`](crate::buf::expand)), and it must be a single file.
+//! - `:b[uffer] {N}` (or `:{N}b[uffer]`) shows the buffer `{N}` in current window.
+//! - `:[count]bn[ext]` shows the `[count]`th next listed buffer, it wraps around the buffer list.
+//! - `:[count]bp[revious]` (or `:[count]bN[ext]`) shows the `[count]`th previous listed buffer.
+//! - `:bd[elete][!] [N]` deletes the buffer `[N]` (default is current buffer), the windows showing
+//!   it are switched to another buffer. The modified buffer is not deleted without `!`.
+//! - `:ls[!]` (or `:buffers[!]`, `:files[!]`) lists the listed buffers, the unlisted buffers (i.e.
+//!   the scratch buffers) are included with `!`.
+//!
+//! See [`BuffersManager`](crate::buf::BuffersManager) for the buffer list.
+
+use crate::buf::expand::expand_path;
+use crate::buf::{BufferArc, BufferId, BufferInfo};
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager, ExLineAddress,
+  ExLineRange,
+};
+use crate::state::msg::MessageLevel;
+use crate::{rlock, wlock};
+
+use std::sync::Arc;
+
+/// The buffer definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new("edit", "e", "Edit a file in current window", handle_edit)
+      .with_file_completion(),
+    ExCommandDefinition::new(
+      "buffer",
+      "b",
+      "Show a buffer in current window",
+      handle_buffer,
+    ),
+    ExCommandDefinition::new(
+      "bnext",
+      "bn",
+      "Show the next buffer in current window",
+      handle_bnext,
+    ),
+    ExCommandDefinition::new(
+      "bprevious",
+      "bp",
+      "Show the previous buffer in current window",
+      handle_bprevious,
+    ),
+    ExCommandDefinition::new(
+      "bNext",
+      "bN",
+      "Show the previous buffer in current window",
+      handle_bprevious,
+    ),
+    ExCommandDefinition::new("bdelete", "bd", "Delete a buffer", handle_bdelete),
+    ExCommandDefinition::new("ls", "ls", "List the buffers", handle_ls),
+    ExCommandDefinition::new("buffers", "buffers", "List the buffers", handle_ls),
+    ExCommandDefinition::new("files", "files", "List the buffers", handle_ls),
+  ]
+}
+
+/// Get the `[count]` from the range, default is 1.
+fn count(command_line: &ExCommandLine) -> usize {
+  match command_line.range() {
+    Some(ExLineRange::Lines(_, ExLineAddress::Number(n))) if *n > 0 => *n,
+    _ => 1,
+  }
+}
+
+/// Get the buffer number from the arguments or the range, i.e. `:buffer 2` or `:2buffer`.
+fn buffer_number(command_line: &ExCommandLine) -> ExCommandResult<Option<BufferId>> {
+  let args = command_line.args().trim();
+  if !args.is_empty() {
+    return match args.parse::<BufferId>() {
+      Ok(id) => Ok(Some(id)),
+      Err(_) => Err(ExCommandErr::Message(format!(
+        "E94: No matching buffer for {}",
+        args
+      ))),
+    };
+  }
+  match command_line.range() {
+    Some(ExLineRange::Lines(_, ExLineAddress::Number(n))) => Ok(Some(*n as BufferId)),
+    _ => Ok(None),
+  }
+}
+
+/// Find the buffer by number.
+fn find_buffer(data_access: &ExCommandDataAccess, id: BufferId) -> ExCommandResult<BufferArc> {
+  rlock!(data_access.buffers)
+    .get(&id)
+    .cloned()
+    .ok_or_else(|| ExCommandErr::Message(format!("E86: Buffer {} does not exist", id)))
+}
+
+/// Format the `:ls` output, one buffer per line. The flags are:
+///
+/// - `u`: Unlisted buffer.
+/// - `%`: The buffer in current window.
+/// - `+`: Modified buffer.
+pub fn format_buffer_list(list: &[BufferInfo], current: Option<BufferId>) -> String {
+  list
+    .iter()
+    .map(|info| {
+      format!(
+        "{:>3}{}{} {} \"{}\"",
+        info.id,
+        if info.listed { ' ' } else { 'u' },
+        if Some(info.id) == current { '%' } else { ' ' },
+        if info.modified { '+' } else { ' ' },
+        info.name.as_deref().unwrap_or("[No Name]")
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+fn handle_edit(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let filename = command_line.args().trim();
+  if filename.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+  let files = expand_path(filename);
+  if files.len() > 1 {
+    return Err(ExCommandErr::Message(
+      "E77: Too many file names".to_string(),
+    ));
+  }
+  data_access.edit_file(&files[0])
+}
+
+fn handle_buffer(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if let Some(id) = buffer_number(command_line)? {
+    let buffer = find_buffer(data_access, id)?;
+    data_access.show_buffer(&buffer);
+  }
+  Ok(())
+}
+
+fn show_next(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  forward: bool,
+) -> ExCommandResult<()> {
+  let (current, _) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let current_id = rlock!(current).id();
+  let next = {
+    let buffers = rlock!(data_access.buffers);
+    buffers
+      .next_listed(current_id, count(command_line), forward)
+      .and_then(|id| buffers.get(&id).cloned())
+  };
+  if let Some(next) = next.filter(|next| !Arc::ptr_eq(next, &current)) {
+    data_access.show_buffer(&next);
+  }
+  Ok(())
+}
+
+fn handle_bnext(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  show_next(command_line, data_access, true)
+}
+
+fn handle_bprevious(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  show_next(command_line, data_access, false)
+}
+
+fn handle_bdelete(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let buffer = match buffer_number(command_line)? {
+    Some(id) => find_buffer(data_access, id)?,
+    None => match data_access.current_window_buffer() {
+      Some((current, _)) => current,
+      None => return Ok(()),
+    },
+  };
+  let buffer_id = {
+    let buffer = rlock!(buffer);
+    if buffer.is_modified() && !command_line.bang() {
+      return Err(ExCommandErr::Message(format!(
+        "E89: No write since last change for buffer {} (add ! to override)",
+        buffer.id()
+      )));
+    }
+    buffer.id()
+  };
+
+  let next = data_access.switch_away_from(&buffer);
+  if Arc::ptr_eq(&next, &buffer) {
+    return Err(ExCommandErr::Message(
+      "E90: Cannot unload last buffer".to_string(),
+    ));
+  }
+  wlock!(data_access.buffers).wipe_buffer(&buffer_id);
+  Ok(())
+}
+
+fn handle_ls(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let current = data_access
+    .current_window_buffer()
+    .map(|(buffer, _)| rlock!(buffer).id());
+  let list = rlock!(data_access.buffers).list(command_line.bang());
+  data_access.echo(MessageLevel::Info, &format_buffer_list(&list, current));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn format_buffer_list1() {
+    let list = vec![
+      BufferInfo {
+        id: 1,
+        name: Some("a.md".to_string()),
+        listed: true,
+        modified: true,
+      },
+      BufferInfo {
+        id: 12,
+        name: None,
+        listed: false,
+        modified: false,
+      },
+    ];
+    assert_eq!(
+      format_buffer_list(&list, Some(1)),
+      "  1 % + \"a.md\"\n 12u    \"[No Name]\""
+    );
+  }
+}
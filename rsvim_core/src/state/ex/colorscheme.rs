@@ -0,0 +1,109 @@
+//! The `:colorscheme` ex command, see [`highlight`](crate::ui::highlight).
+//!
+//! - `:colo[rscheme]` shows the loaded colorscheme name, or `default` if there's none.
+//! - `:colo[rscheme] {name}` resets the highlight groups to the defaults, then executes the js
+//!   module `colors/{name}.ts` (or `.js`) in the config directories. The `default` colorscheme
+//!   only resets the highlight groups if there's no such module.
+
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::state::msg::MessageLevel;
+use crate::{rlock, wlock};
+
+use std::path::{Path, PathBuf};
+use tracing::trace;
+
+/// The builtin colorscheme, i.e. the default highlight groups.
+pub const DEFAULT_COLORSCHEME: &str = "default";
+
+/// The `:colorscheme` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new("colorscheme", "colo", "Load a colorscheme", handle)
+}
+
+/// Find the colorscheme module `colors/{name}.ts` (or `.js`) in the `dirs` by order, the
+/// typescript file is preferred in the same directory.
+pub fn find_colorscheme(dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+  dirs
+    .iter()
+    .flat_map(|dir| ["ts", "js"].map(|ext| colors_file(dir, name, ext)))
+    .find(|file| file.is_file())
+}
+
+fn colors_file(dir: &Path, name: &str, ext: &str) -> PathBuf {
+  dir.join("colors").join(format!("{}.{}", name, ext))
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let name = command_line.args().trim();
+  if name.is_empty() {
+    let colors_name = rlock!(data_access.tree)
+      .highlights()
+      .colors_name()
+      .unwrap_or(DEFAULT_COLORSCHEME)
+      .to_string();
+    data_access.echo(MessageLevel::Info, &colors_name);
+    return Ok(());
+  }
+  if name.contains(['/', '\\']) {
+    return Err(ExCommandErr::Message(format!(
+      "E185: Cannot find color scheme '{}'",
+      name
+    )));
+  }
+
+  let file = find_colorscheme(&envar::CONFIG_DIRS_PATH(), name);
+  if file.is_none() && name != DEFAULT_COLORSCHEME {
+    return Err(ExCommandErr::Message(format!(
+      "E185: Cannot find color scheme '{}'",
+      name
+    )));
+  }
+  trace!("colorscheme: {:?}, {:?}", name, file);
+  {
+    let mut tree = wlock!(data_access.tree);
+    let highlights = tree.highlights_mut();
+    highlights.reset();
+    if file.is_some() {
+      highlights.set_colors_name(Some(name));
+    }
+  }
+  if let Some(file) = file {
+    data_access.source(file);
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn find_colorscheme1() {
+    let tmp1 = assert_fs::TempDir::new().unwrap();
+    let tmp2 = assert_fs::TempDir::new().unwrap();
+    std::fs::create_dir(tmp1.path().join("colors")).unwrap();
+    std::fs::create_dir(tmp2.path().join("colors")).unwrap();
+    std::fs::write(tmp1.path().join("colors").join("a.js"), "").unwrap();
+    std::fs::write(tmp2.path().join("colors").join("a.ts"), "").unwrap();
+    std::fs::write(tmp2.path().join("colors").join("b.js"), "").unwrap();
+    let dirs = vec![tmp1.path().to_path_buf(), tmp2.path().to_path_buf()];
+
+    assert_eq!(
+      find_colorscheme(&dirs, "a"),
+      Some(tmp1.path().join("colors").join("a.js"))
+    );
+    assert_eq!(
+      find_colorscheme(&dirs, "b"),
+      Some(tmp2.path().join("colors").join("b.js"))
+    );
+    assert_eq!(find_colorscheme(&dirs, "c"), None);
+  }
+}
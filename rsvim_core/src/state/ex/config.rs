@@ -0,0 +1,94 @@
+//! The `:config` ex command, i.e. the hot-reload of the user config (`rsvim.js` or `rsvim.ts`).
+//!
+//! - `:config` shows the user config file path.
+//! - `:config reload` reloads the user config without restarting the editor.
+//!
+//! The user config is also watched by the event loop, it's reloaded automatically when the file
+//! is saved, see [`CONFIG_WATCH_INTERVAL`](crate::defaults::task::CONFIG_WATCH_INTERVAL).
+//!
+//! Before reloading, the plugin state registered by the old config is torn down:
+//!
+//! - The key mappings, and the 'mapleader' and 'timeoutlen' options, see
+//!   [`Keymaps::clear`](crate::state::keymap::Keymaps::clear).
+//! - The autocmd subscriptions, see [`Autocmds::clear`](crate::state::autocmd::Autocmds::clear).
+//! - The highlight groups (and the colorscheme), they're reset to the defaults like
+//!   `:colorscheme default`.
+//! - The JS callbacks and the loaded ES modules, thus the modules imported by the config are
+//!   evaluated again.
+//!
+//! Then the entry module is evaluated again in the same js runtime. The other editor state (i.e.
+//! the buffers, windows, options and running jobs) is kept.
+
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::state::msg::MessageLevel;
+
+use tracing::trace;
+
+/// The `:config` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new("config", "conf", "Show or reload the user config", handle)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The `:config` action.
+pub enum ConfigAction {
+  /// Show the user config file path, i.e. `:config`.
+  Show,
+  /// Reload the user config, i.e. `:config reload`.
+  Reload,
+}
+
+impl ConfigAction {
+  /// Parse the `:config` arguments.
+  ///
+  /// # Errors
+  ///
+  /// If the argument is not `reload`.
+  pub fn parse(args: &str) -> ExCommandResult<Self> {
+    match args.trim() {
+      "" => Ok(ConfigAction::Show),
+      "reload" => Ok(ConfigAction::Reload),
+      args => Err(ExCommandErr::Message(format!(
+        "E475: Invalid argument: {}",
+        args
+      ))),
+    }
+  }
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let action = ConfigAction::parse(command_line.args())?;
+  let config_file = envar::CONFIG_FILE_PATH()
+    .ok_or_else(|| ExCommandErr::Message("No user config file".to_string()))?;
+  trace!("config: {:?}, {:?}", action, config_file);
+  match action {
+    ConfigAction::Show => {
+      data_access.echo(MessageLevel::Info, &config_file.to_string_lossy());
+    }
+    ConfigAction::Reload => data_access.reload_config(),
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse1() {
+    assert_eq!(ConfigAction::parse("").unwrap(), ConfigAction::Show);
+    assert_eq!(
+      ConfigAction::parse(" reload ").unwrap(),
+      ConfigAction::Reload
+    );
+    assert!(ConfigAction::parse("load").is_err());
+  }
+}
@@ -0,0 +1,103 @@
+//! The `:Del[ete][!]` ex command, it deletes the file of current buffer.
+//!
+//! The file is moved to the platform trash (see [`trash`](crate::buf::trash)), or deleted
+//! permanently with `!` (or the platform trash is not supported), through the buffers' file system
+//! provider (see [`FsProvider::trash`]). Then the buffer is wiped out, the
+//! windows showing it are switched to another buffer (or the unnamed buffer), and the file is
+//! removed from the recent files (see [`position`](crate::buf::position)).
+//!
+//! The modified buffer is not deleted, since the changes cannot be restored from the trash.
+
+use crate::buf::FsProvider;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::{rlock, wlock};
+
+use std::path::Path;
+use tracing::trace;
+
+/// The `:Delete` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new(
+    "Delete",
+    "Del",
+    "Delete the file of current buffer (to the trash)",
+    handle,
+  )
+}
+
+// Delete the file, returns the path in the trash, or `None` if it's deleted permanently.
+fn delete_file(
+  fs: &dyn FsProvider,
+  path: &Path,
+  permanent: bool,
+) -> std::io::Result<Option<std::path::PathBuf>> {
+  let trashed = if permanent { None } else { fs.trash(path)? };
+  match trashed {
+    Some(trashed) => Ok(Some(trashed)),
+    None => fs.remove_file(path).map(|_| None),
+  }
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if !command_line.args().trim().is_empty() {
+    return Err(ExCommandErr::Message(
+      "E488: Trailing characters".to_string(),
+    ));
+  }
+  let (buffer, _) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let (buffer_id, filename) = {
+    let buffer = rlock!(buffer);
+    match buffer.absolute_filename() {
+      Some(filename) if buffer.remote().is_none() => (buffer.id(), filename.clone()),
+      _ => return Err(ExCommandErr::Message("E32: No file name".to_string())),
+    }
+  };
+  if rlock!(buffer).is_modified() {
+    return Err(ExCommandErr::Message(
+      "E89: No write since last change for buffer (write or undo the changes first)".to_string(),
+    ));
+  }
+
+  let fs = rlock!(data_access.buffers).fs().clone();
+  if fs.exists(&filename).unwrap_or(false) {
+    let trashed = delete_file(fs.as_ref(), &filename, command_line.bang())
+      .map_err(|e| ExCommandErr::Message(format!("E212: Can't delete {:?}: {}", filename, e)))?;
+    trace!("Delete {:?} to {:?}", filename, trashed);
+  }
+
+  data_access.switch_away_from(&buffer);
+
+  let mut buffers = wlock!(data_access.buffers);
+  buffers.wipe_buffer(&buffer_id);
+  buffers.positions_mut().remove(&filename);
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::buf::MemoryFs;
+
+  #[test]
+  fn delete_file1() {
+    let fs = MemoryFs::new();
+    let path = Path::new("/tmp/a.txt");
+    fs.insert(path, b"hello\n");
+    // The in-memory file system has no trash, thus it's deleted permanently.
+    assert_eq!(delete_file(&fs, path, false).unwrap(), None);
+    assert!(!fs.exists(path).unwrap());
+    assert!(delete_file(&fs, path, true).is_err());
+  }
+}
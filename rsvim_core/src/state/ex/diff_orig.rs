@@ -0,0 +1,105 @@
+//! The `:DiffOrig` ex command.
+//!
+//! `:DiffO[rig]` shows the side-by-side diff between the on-disk version of current buffer (on
+//! the left) and the in-memory buffer (on the right) in a scratch buffer, thus users can review
+//! the unsaved changes. Running it again in the scratch buffer goes back to the source buffer.
+
+use crate::buf::Buffer;
+use crate::diff::side_by_side_lines;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::{rlock, wlock};
+
+use ropey::Rope;
+
+/// The `:DiffOrig` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new(
+    "DiffOrig",
+    "DiffO",
+    "Show the diff between the buffer and the saved file",
+    handle,
+  )
+}
+
+/// Get the buffer text.
+fn buffer_text(buffer: &Buffer) -> String {
+  buffer.lines().map(|line| line.to_string()).collect()
+}
+
+/// Make the diff text, it's ended with a line break.
+pub fn diff_text(saved: &str, current: &str, width: usize) -> String {
+  let mut text = side_by_side_lines(saved, current, width).join("\n");
+  text.push('\n');
+  text
+}
+
+fn handle(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let buffer_id = rlock!(buffer).id();
+
+  // Go back to the source buffer.
+  let source = {
+    let buffers = rlock!(data_access.buffers);
+    if buffers.is_scratch(&buffer_id) {
+      match buffers.scratch_source(&buffer_id) {
+        Some(source_id) => buffers.get(&source_id).cloned(),
+        None => None,
+      }
+    } else {
+      None
+    }
+  };
+
+  let (next_buffer, removed_scratch_id) = match source {
+    Some(source) => (source, Some(buffer_id)),
+    None => {
+      let (filename, current) = {
+        let buffer = rlock!(buffer);
+        let filename = buffer
+          .absolute_filename()
+          .clone()
+          .ok_or_else(|| ExCommandErr::Message("E32: No file name".to_string()))?;
+        (filename, buffer_text(&buffer))
+      };
+      let saved = std::fs::read_to_string(&filename).map_err(|e| {
+        ExCommandErr::Message(format!("E484: Can't open file {:?}: {}", filename, e))
+      })?;
+      let width = rlock!(viewport).actual_shape().width() as usize;
+      let text = diff_text(&saved, &current, width);
+
+      let mut buffers = wlock!(data_access.buffers);
+      let scratch_id = buffers.new_scratch_buffer(Rope::from_str(&text), Some(buffer_id));
+      (buffers.get(&scratch_id).unwrap().clone(), None)
+    }
+  };
+
+  data_access.show_buffer(&next_buffer);
+
+  // The scratch buffer is discarded after it's no longer shown.
+  if let Some(scratch_id) = removed_scratch_id {
+    wlock!(data_access.buffers).remove(&scratch_id);
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn diff_text1() {
+    assert_eq!(diff_text("a\nb\n", "a\nB\n", 11), " a   │  a\n-b   │ +B\n");
+    assert_eq!(diff_text("", "", 11), "\n");
+  }
+}
@@ -0,0 +1,358 @@
+//! The `:echo` and `:echomsg` ex commands.
+//!
+//! - `:ec[ho] {expr1} ..` shows the values separated by a space, it's not saved in the message
+//!   history.
+//! - `:echom[sg] {expr1} ..` same with `:echo`, but it's saved in the message history, i.e. it
+//!   can be reviewed with `:messages` later.
+//!
+//! The ex commands don't evaluate expressions (the scripts are in JS, see `Rsvim.print()`), thus
+//! the `{expr}` is a literal value:
+//!
+//! - String: `"a\tb"` (the backslash escapes are translated) or `'it''s'`.
+//! - Number: `42`, `-1.5`.
+//! - Special: `v:true`, `v:false`, `v:null`.
+//! - List: `[1, "a", [2, 3]]`.
+//! - Dictionary: `{"a": 1, b: [2, 3]}`, the keys are strings or bare words.
+//!
+//! The top-level strings are shown without quotes. The lists and dictionaries are shown in one
+//! line, or pretty-printed in multiple lines (with 2 spaces indent) if the line is longer than
+//! [`INSPECT_WIDTH`].
+
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::state::msg::MessageLevel;
+use crate::wlock;
+
+use std::iter::Peekable;
+use std::str::Chars;
+use unicode_width::UnicodeWidthStr;
+
+/// The max width of a list (or dictionary) shown in one line.
+pub const INSPECT_WIDTH: usize = 80;
+
+/// The `:echo` and `:echomsg` definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new("echo", "ec", "Show the values", handle_echo),
+    ExCommandDefinition::new(
+      "echomsg",
+      "echom",
+      "Show the values and save them in the message history",
+      handle_echomsg,
+    ),
+  ]
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The literal value of `:echo`.
+pub enum EchoValue {
+  Null,
+  Bool(bool),
+  /// The number text as it's typed.
+  Number(String),
+  String(String),
+  List(Vec<EchoValue>),
+  Dict(Vec<(String, EchoValue)>),
+}
+
+impl EchoValue {
+  /// Format the value, the list (or dictionary) is pretty-printed in multiple lines if it's longer
+  /// than [`INSPECT_WIDTH`].
+  pub fn inspect(&self) -> String {
+    self.pretty(0, 0)
+  }
+
+  // Format the value at the `column` of the line indented by `indent`.
+  fn pretty(&self, indent: usize, column: usize) -> String {
+    let compact = self.compact();
+    if column + compact.width() <= INSPECT_WIDTH {
+      return compact;
+    }
+    let pad = " ".repeat(indent + 2);
+    match self {
+      EchoValue::List(items) if !items.is_empty() => {
+        let items: String = items
+          .iter()
+          .map(|item| format!("{}{},\n", pad, item.pretty(indent + 2, indent + 2)))
+          .collect();
+        format!("[\n{}{}]", items, " ".repeat(indent))
+      }
+      EchoValue::Dict(entries) if !entries.is_empty() => {
+        let entries: String = entries
+          .iter()
+          .map(|(key, value)| {
+            let key = quote(key);
+            let value = value.pretty(indent + 2, indent + 2 + key.width() + 2);
+            format!("{}{}: {},\n", pad, key, value)
+          })
+          .collect();
+        format!("{{\n{}{}}}", entries, " ".repeat(indent))
+      }
+      _ => compact,
+    }
+  }
+
+  // Format the value in one line, the strings are quoted.
+  fn compact(&self) -> String {
+    match self {
+      EchoValue::Null => "v:null".to_string(),
+      EchoValue::Bool(true) => "v:true".to_string(),
+      EchoValue::Bool(false) => "v:false".to_string(),
+      EchoValue::Number(n) => n.clone(),
+      EchoValue::String(s) => quote(s),
+      EchoValue::List(items) => {
+        let items: Vec<String> = items.iter().map(|item| item.compact()).collect();
+        format!("[{}]", items.join(", "))
+      }
+      EchoValue::Dict(entries) => {
+        let entries: Vec<String> = entries
+          .iter()
+          .map(|(key, value)| format!("{}: {}", quote(key), value.compact()))
+          .collect();
+        format!("{{{}}}", entries.join(", "))
+      }
+    }
+  }
+}
+
+// Quote the string with single quotes, i.e. Vim's `string()`.
+fn quote(s: &str) -> String {
+  format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Parse the `:echo` arguments into the values.
+pub fn parse_values(args: &str) -> Result<Vec<EchoValue>, String> {
+  let mut chars = args.chars().peekable();
+  let mut values = vec![];
+  loop {
+    skip_spaces(&mut chars);
+    if chars.peek().is_none() {
+      return Ok(values);
+    }
+    values.push(parse_value(&mut chars)?);
+  }
+}
+
+/// Format the `:echo` arguments, the values are separated by a space.
+pub fn format_values(values: &[EchoValue]) -> String {
+  let mut text = String::new();
+  for (i, value) in values.iter().enumerate() {
+    if i > 0 {
+      text.push(' ');
+    }
+    match value {
+      EchoValue::String(s) => text.push_str(s),
+      _ => {
+        let column = text.rsplit('\n').next().unwrap_or_default().width();
+        text.push_str(&value.pretty(0, column));
+      }
+    }
+  }
+  text
+}
+
+fn skip_spaces(chars: &mut Peekable<Chars>) {
+  while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<EchoValue, String> {
+  match chars.peek().copied() {
+    Some('"') => {
+      chars.next();
+      parse_double_quoted(chars).map(EchoValue::String)
+    }
+    Some('\'') => {
+      chars.next();
+      parse_single_quoted(chars).map(EchoValue::String)
+    }
+    Some('[') => {
+      chars.next();
+      let mut items = vec![];
+      loop {
+        skip_spaces(chars);
+        if chars.next_if_eq(&']').is_some() {
+          return Ok(EchoValue::List(items));
+        }
+        items.push(parse_value(chars)?);
+        skip_spaces(chars);
+        match chars.next() {
+          Some(',') => {}
+          Some(']') => return Ok(EchoValue::List(items)),
+          _ => return Err("Missing comma in List".to_string()),
+        }
+      }
+    }
+    Some('{') => {
+      chars.next();
+      let mut entries = vec![];
+      loop {
+        skip_spaces(chars);
+        if chars.next_if_eq(&'}').is_some() {
+          return Ok(EchoValue::Dict(entries));
+        }
+        let key = match parse_value(chars)? {
+          EchoValue::String(key) | EchoValue::Number(key) => key,
+          _ => return Err("Dictionary key must be a string".to_string()),
+        };
+        skip_spaces(chars);
+        if chars.next() != Some(':') {
+          return Err(format!("Missing colon in Dictionary: {}", key));
+        }
+        skip_spaces(chars);
+        entries.push((key, parse_value(chars)?));
+        skip_spaces(chars);
+        match chars.next() {
+          Some(',') => {}
+          Some('}') => return Ok(EchoValue::Dict(entries)),
+          _ => return Err("Missing comma in Dictionary".to_string()),
+        }
+      }
+    }
+    Some(_) => {
+      let mut word = take_word(chars);
+      if word == "v" && chars.next_if_eq(&':').is_some() {
+        word.push(':');
+        word.push_str(&take_word(chars));
+      }
+      match word.as_str() {
+        "" => Err(format!("Invalid expression: {}", chars.collect::<String>())),
+        "v:null" => Ok(EchoValue::Null),
+        "v:true" => Ok(EchoValue::Bool(true)),
+        "v:false" => Ok(EchoValue::Bool(false)),
+        _ if is_number(&word) => Ok(EchoValue::Number(word)),
+        _ => {
+          // The bare word is only allowed as the dictionary key.
+          skip_spaces(chars);
+          if chars.peek() == Some(&':') {
+            Ok(EchoValue::String(word))
+          } else {
+            Err(format!("Invalid expression: {}", word))
+          }
+        }
+      }
+    }
+    None => Err("Missing value".to_string()),
+  }
+}
+
+// Whether the word is a number, i.e. `inf` and `NaN` are not.
+fn is_number(word: &str) -> bool {
+  word.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+')
+    && word.parse::<f64>().is_ok()
+}
+
+// Take the chars of a bare word or number.
+fn take_word(chars: &mut Peekable<Chars>) -> String {
+  let mut word = String::new();
+  while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || "_.-+".contains(*c)) {
+    word.push(c);
+  }
+  word
+}
+
+fn parse_double_quoted(chars: &mut Peekable<Chars>) -> Result<String, String> {
+  let mut s = String::new();
+  loop {
+    match chars.next() {
+      Some('"') => return Ok(s),
+      Some('\\') => match chars.next() {
+        Some('n') => s.push('\n'),
+        Some('t') => s.push('\t'),
+        Some('r') => s.push('\r'),
+        Some('e') => s.push('\x1b'),
+        Some(c) => s.push(c),
+        None => return Err("Missing quote".to_string()),
+      },
+      Some(c) => s.push(c),
+      None => return Err("Missing quote".to_string()),
+    }
+  }
+}
+
+fn parse_single_quoted(chars: &mut Peekable<Chars>) -> Result<String, String> {
+  let mut s = String::new();
+  loop {
+    match chars.next() {
+      // The `''` inside the single quoted string is a single quote.
+      Some('\'') if chars.next_if_eq(&'\'').is_some() => s.push('\''),
+      Some('\'') => return Ok(s),
+      Some(c) => s.push(c),
+      None => return Err("Missing quote".to_string()),
+    }
+  }
+}
+
+// Parse and format the arguments.
+fn echo_text(command_line: &ExCommandLine) -> ExCommandResult<Option<String>> {
+  let values =
+    parse_values(command_line.args()).map_err(|e| ExCommandErr::Message(format!("E15: {}", e)))?;
+  if values.is_empty() {
+    return Ok(None);
+  }
+  Ok(Some(format_values(&values)))
+}
+
+fn handle_echo(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if let Some(text) = echo_text(command_line)? {
+    wlock!(data_access.messages).show(MessageLevel::Info, &text);
+  }
+  Ok(())
+}
+
+fn handle_echomsg(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if let Some(text) = echo_text(command_line)? {
+    data_access.echo(MessageLevel::Info, &text);
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn echo(args: &str) -> String {
+    format_values(&parse_values(args).unwrap())
+  }
+
+  #[test]
+  fn parse_values1() {
+    assert_eq!(echo(r#""hello\tworld" 'it''s' 42"#), "hello\tworld it's 42");
+    assert_eq!(
+      echo("[1, 'a', [v:true, v:null], []]"),
+      "[1, 'a', [v:true, v:null], []]"
+    );
+    assert_eq!(
+      echo(r#"{"a": -1.5, b: {}, 'c d': ["x"]}"#),
+      "{'a': -1.5, 'b': {}, 'c d': ['x']}"
+    );
+    assert_eq!(echo("  "), "");
+    assert!(parse_values("abc").is_err());
+    assert!(parse_values("[1 2]").is_err());
+    assert!(parse_values("{1}").is_err());
+    assert!(parse_values("'abc").is_err());
+    assert!(parse_values("[1,").is_err());
+  }
+
+  #[test]
+  fn inspect1() {
+    let long = "x".repeat(INSPECT_WIDTH);
+    let actual = echo(&format!("'list:' [1, '{}', {{a: [2]}}]", long));
+    let expect = format!("list: [\n  1,\n  '{}',\n  {{'a': [2]}},\n]", long);
+    assert_eq!(actual, expect);
+
+    let actual = echo(&format!("{{key: ['{}']}}", long));
+    let expect = format!("{{\n  'key': [\n    '{}',\n  ],\n}}", long);
+    assert_eq!(actual, expect);
+  }
+}
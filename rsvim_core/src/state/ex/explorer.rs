@@ -0,0 +1,464 @@
+//! The file explorer ex commands, see [`explorer`](crate::buf::explorer) for the directory buffer.
+//!
+//! - `:Ex[plore] [dir]` opens the directory buffer of `[dir]`, default is the directory of current
+//!   file (or the current working directory), the cursor is on current file.
+//! - `:ExploreOpen` opens the entry under the cursor, the `../` line opens the parent directory.
+//! - `:ExploreUp` opens the parent directory, the cursor is on the directory just left.
+//! - `:ExploreNew {name}` creates the file `{name}` in the directory, or the directory if `{name}`
+//!   ends with `/`.
+//! - `:ExploreRename[!] {name}` renames the entry under the cursor to `{name}`, the opened buffer
+//!   of the file is renamed as well (see
+//!   [`BuffersManager::rename_buffer`](crate::buf::BuffersManager::rename_buffer)). The existing `{name}`
+//!   is overwritten only with `!`.
+//! - `:ExploreDelete[!]` deletes the entry under the cursor to the platform trash (see
+//!   [`trash`](crate::buf::trash)), or permanently with `!`. The opened buffer of the file is wiped
+//!   out, unless it's modified.
+//! - `:ExploreSort[!] [name|time|size]` sorts the entries, it cycles the sorting without the
+//!   argument. With `!` the order is reversed instead, i.e. the sorting is only changed if it's
+//!   given.
+//!
+//! The keys in the directory buffer (see [`NormalStateful`](crate::state::fsm::NormalStateful)):
+//!
+//! - `<CR>`: `:ExploreOpen`.
+//! - `-`: `:ExploreUp`.
+//! - `%`: Type `:ExploreNew `.
+//! - `R`: Type `:ExploreRename {name}` with current name.
+//! - `D`: Type `:ExploreDelete`, i.e. it waits for the confirmation.
+//! - `s`: `:ExploreSort`.
+//! - `r`: `:ExploreSort!`.
+
+use crate::buf::trash::{move_to_trash, trash_dir};
+use crate::buf::{BufferArc, ExplorerSort};
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::current_window_buffer;
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::state::fsm::visual::move_cursor_to;
+use crate::state::msg::MessageLevel;
+use crate::ui::tree::TreeArc;
+use crate::ui::widget::window::ViewportArc;
+use crate::{rlock, wlock};
+
+use std::path::{Component, Path, PathBuf};
+use tracing::trace;
+
+/// The file explorer definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new("Explore", "Ex", "Open the directory buffer", handle_explore)
+      .with_file_completion(),
+    ExCommandDefinition::new(
+      "ExploreOpen",
+      "ExploreO",
+      "Open the entry under the cursor",
+      handle_open,
+    ),
+    ExCommandDefinition::new(
+      "ExploreUp",
+      "ExploreU",
+      "Open the parent directory",
+      handle_up,
+    ),
+    ExCommandDefinition::new(
+      "ExploreNew",
+      "ExploreN",
+      "Create a file (or a directory) in the directory buffer",
+      handle_new,
+    ),
+    ExCommandDefinition::new(
+      "ExploreRename",
+      "ExploreR",
+      "Rename the entry under the cursor",
+      handle_rename,
+    ),
+    ExCommandDefinition::new(
+      "ExploreDelete",
+      "ExploreD",
+      "Delete the entry under the cursor (to the trash)",
+      handle_delete,
+    ),
+    ExCommandDefinition::new(
+      "ExploreSort",
+      "ExploreS",
+      "Sort the entries of the directory buffer",
+      handle_sort,
+    ),
+  ]
+}
+
+/// Whether current window shows a directory buffer.
+pub fn in_explorer(tree: &TreeArc) -> bool {
+  current_window_buffer(tree).is_some_and(|(buffer, _)| rlock!(buffer).explorer().is_some())
+}
+
+/// Get the name of the entry under the cursor in the directory buffer, i.e. the `R` key.
+pub fn entry_under_cursor(tree: &TreeArc) -> Option<String> {
+  let (buffer, viewport) = current_window_buffer(tree)?;
+  let line_idx = rlock!(viewport).cursor().line_idx();
+  let buffer = rlock!(buffer);
+  let listing = buffer.explorer().as_ref()?;
+  listing.entry_at(line_idx).map(|entry| entry.name.clone())
+}
+
+// The first component of the relative `name`, i.e. the entry to put the cursor on after creating
+// `a/b.rs`.
+fn first_component(name: &str) -> Option<String> {
+  match Path::new(name).components().next() {
+    Some(Component::Normal(first)) => Some(first.to_string_lossy().to_string()),
+    _ => None,
+  }
+}
+
+// Get the directory buffer of current window, and the cursor line.
+fn current_explorer(
+  data_access: &ExCommandDataAccess,
+) -> ExCommandResult<(BufferArc, ViewportArc, usize)> {
+  match data_access.current_window_buffer() {
+    Some((buffer, viewport)) if rlock!(buffer).explorer().is_some() => {
+      let line_idx = rlock!(viewport).cursor().line_idx();
+      Ok((buffer, viewport, line_idx))
+    }
+    _ => Err(ExCommandErr::Message("Not a directory buffer".to_string())),
+  }
+}
+
+// Get the directory and the path of the entry under the cursor, the `../` line is not an entry.
+fn current_entry(
+  data_access: &ExCommandDataAccess,
+) -> ExCommandResult<(BufferArc, ViewportArc, usize, PathBuf)> {
+  let (buffer, viewport, line_idx) = current_explorer(data_access)?;
+  let path = {
+    let buffer = rlock!(buffer);
+    let listing = buffer.explorer().as_ref().unwrap();
+    match listing.entry_at(line_idx) {
+      Some(entry) => listing.dir().join(&entry.name),
+      None => {
+        return Err(ExCommandErr::Message(
+          "No entry under the cursor".to_string(),
+        ))
+      }
+    }
+  };
+  Ok((buffer, viewport, line_idx, path))
+}
+
+// Refresh the directory buffer (re-read the directory if `reread`), and move the cursor to the
+// entry `focus`, or keep it on the line `line_idx`.
+fn refresh(
+  data_access: &ExCommandDataAccess,
+  buffer: &BufferArc,
+  viewport: &ViewportArc,
+  reread: bool,
+  focus: Option<&str>,
+  line_idx: usize,
+) -> ExCommandResult<()> {
+  let line_idx = {
+    let mut buffer = wlock!(buffer);
+    buffer
+      .refresh_explorer(reread)
+      .map_err(|e| ExCommandErr::Message(format!("E484: Can't read directory: {}", e)))?;
+    let listing = buffer.explorer().as_ref().unwrap();
+    focus
+      .and_then(|name| listing.line_of(name))
+      .unwrap_or(std::cmp::min(line_idx, listing.entries().len()))
+  };
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+  move_cursor_to(&data_access.tree, viewport, (line_idx, 0));
+  Ok(())
+}
+
+// Open the directory buffer of `dir` in current window, it's re-read if it's already opened.
+fn open_dir(
+  data_access: &ExCommandDataAccess,
+  dir: &Path,
+  focus: Option<&str>,
+) -> ExCommandResult<()> {
+  data_access.edit_file(dir)?;
+  if let Some((buffer, viewport)) = data_access.current_window_buffer() {
+    let line_idx = rlock!(viewport).cursor().line_idx();
+    refresh(data_access, &buffer, &viewport, true, focus, line_idx)?;
+  }
+  Ok(())
+}
+
+// Delete the file or directory, returns the path in the trash, or `None` if it's deleted
+// permanently.
+fn delete_path(path: &Path, permanent: bool) -> std::io::Result<Option<PathBuf>> {
+  match trash_dir() {
+    Some(trash) if !permanent => move_to_trash(path, &trash).map(Some),
+    _ if path.is_dir() => std::fs::remove_dir_all(path).map(|_| None),
+    _ => std::fs::remove_file(path).map(|_| None),
+  }
+}
+
+fn handle_explore(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let args = command_line.args().trim();
+  let current = data_access.current_window_buffer().and_then(|(buffer, _)| {
+    let buffer = rlock!(buffer);
+    match buffer.explorer() {
+      Some(listing) => Some(listing.dir().to_path_buf()),
+      None if buffer.remote().is_none() => buffer.absolute_filename().clone(),
+      None => None,
+    }
+  });
+  if !args.is_empty() {
+    return open_dir(data_access, Path::new(args), None);
+  }
+  match current {
+    Some(current) if !current.is_dir() => {
+      let focus = current.file_name().map(|n| n.to_string_lossy().to_string());
+      let dir = current.parent().unwrap_or(&current).to_path_buf();
+      open_dir(data_access, &dir, focus.as_deref())
+    }
+    Some(current) => open_dir(data_access, &current, None),
+    None => open_dir(data_access, Path::new("."), None),
+  }
+}
+
+fn handle_open(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let (buffer, _, line_idx) = current_explorer(data_access)?;
+  let entry = {
+    let buffer = rlock!(buffer);
+    let listing = buffer.explorer().as_ref().unwrap();
+    listing
+      .entry_at(line_idx)
+      .map(|entry| (listing.dir().join(&entry.name), entry.is_dir))
+  };
+  match entry {
+    Some((path, true)) => open_dir(data_access, &path, None),
+    Some((path, false)) => data_access.edit_file(&path),
+    None => handle_up(command_line, data_access, commands),
+  }
+}
+
+fn handle_up(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let (buffer, _, _) = current_explorer(data_access)?;
+  let (parent, focus) = {
+    let buffer = rlock!(buffer);
+    let listing = buffer.explorer().as_ref().unwrap();
+    let focus = listing
+      .dir()
+      .file_name()
+      .map(|name| name.to_string_lossy().to_string());
+    (listing.parent(), focus)
+  };
+  open_dir(data_access, &parent, focus.as_deref())
+}
+
+fn handle_new(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let name = command_line.args().trim();
+  if name.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+  let (buffer, viewport, line_idx) = current_explorer(data_access)?;
+  let dir = rlock!(buffer)
+    .explorer()
+    .as_ref()
+    .unwrap()
+    .dir()
+    .to_path_buf();
+  let path = dir.join(name);
+  let result = if name.ends_with('/') {
+    std::fs::create_dir_all(&path)
+  } else if path.exists() {
+    return Err(ExCommandErr::Message(format!(
+      "E13: File exists: {:?}",
+      path
+    )));
+  } else {
+    path
+      .parent()
+      .map_or(Ok(()), std::fs::create_dir_all)
+      .and_then(|_| std::fs::File::create_new(&path).map(|_| ()))
+  };
+  result.map_err(|e| ExCommandErr::Message(format!("E212: Can't create {:?}: {}", path, e)))?;
+  trace!("Create {:?}", path);
+  refresh(
+    data_access,
+    &buffer,
+    &viewport,
+    true,
+    first_component(name).as_deref(),
+    line_idx,
+  )
+}
+
+fn handle_rename(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let name = command_line.args().trim();
+  if name.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+  let (buffer, viewport, line_idx, path) = current_entry(data_access)?;
+  let target = path
+    .parent()
+    .unwrap_or(&path)
+    .join(name.trim_end_matches('/'));
+  let force = command_line.bang();
+  let error = |e: std::io::Error| {
+    let hint = if e.kind() == std::io::ErrorKind::AlreadyExists {
+      " (add ! to override)"
+    } else {
+      ""
+    };
+    ExCommandErr::Message(format!(
+      "E212: Can't rename {:?} to {:?}: {}{}",
+      path, target, e, hint
+    ))
+  };
+
+  // The opened buffer of the file is renamed with the file.
+  let opened = {
+    let buffers = rlock!(data_access.buffers);
+    buffers
+      .find_by_path(&path)
+      .filter(|b| rlock!(b).explorer().is_none())
+      .map(|b| rlock!(b).id())
+  };
+  match opened {
+    Some(buffer_id) => wlock!(data_access.buffers)
+      .rename_buffer(&buffer_id, &target, force)
+      .map_err(error)?,
+    None => {
+      if target.exists() && !force {
+        return Err(error(std::io::Error::new(
+          std::io::ErrorKind::AlreadyExists,
+          format!("{:?} exists", target),
+        )));
+      }
+      target
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::rename(&path, &target))
+        .map_err(error)?
+    }
+  }
+  trace!("Rename {:?} to {:?}", path, target);
+  refresh(
+    data_access,
+    &buffer,
+    &viewport,
+    true,
+    first_component(name).as_deref(),
+    line_idx,
+  )
+}
+
+fn handle_delete(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if !command_line.args().trim().is_empty() {
+    return Err(ExCommandErr::Message(
+      "E488: Trailing characters".to_string(),
+    ));
+  }
+  let (buffer, viewport, line_idx, path) = current_entry(data_access)?;
+  let opened = rlock!(data_access.buffers)
+    .find_by_path(&path)
+    .filter(|b| rlock!(b).explorer().is_none())
+    .cloned();
+  if opened.as_ref().is_some_and(|b| rlock!(b).is_modified()) {
+    return Err(ExCommandErr::Message(
+      "E89: No write since last change for buffer (write or undo the changes first)".to_string(),
+    ));
+  }
+
+  let trashed = delete_path(&path, command_line.bang())
+    .map_err(|e| ExCommandErr::Message(format!("E212: Can't delete {:?}: {}", path, e)))?;
+  trace!("Delete {:?} to {:?}", path, trashed);
+
+  if let Some(opened) = opened {
+    data_access.switch_away_from(&opened);
+    let buffer_id = rlock!(opened).id();
+    let mut buffers = wlock!(data_access.buffers);
+    buffers.wipe_buffer(&buffer_id);
+    buffers.positions_mut().remove(&path);
+  }
+  refresh(data_access, &buffer, &viewport, true, None, line_idx)
+}
+
+fn handle_sort(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let args = command_line.args().trim();
+  let sort = if args.is_empty() {
+    None
+  } else {
+    Some(
+      args
+        .parse::<ExplorerSort>()
+        .map_err(ExCommandErr::Message)?,
+    )
+  };
+  let (buffer, viewport, line_idx) = current_explorer(data_access)?;
+  let (focus, message) = {
+    let mut buffer = wlock!(buffer);
+    let listing = buffer.explorer_mut().as_mut().unwrap();
+    let focus = listing.entry_at(line_idx).map(|entry| entry.name.clone());
+    match (command_line.bang(), sort) {
+      (true, sort) => {
+        if let Some(sort) = sort {
+          listing.set_sort(sort);
+        }
+        listing.set_reverse(!listing.reverse());
+      }
+      (false, Some(sort)) => listing.set_sort(sort),
+      (false, None) => listing.set_sort(listing.sort().next()),
+    }
+    let message = format!(
+      "Sorted by {}{}",
+      listing.sort(),
+      if listing.reverse() { ", reversed" } else { "" }
+    );
+    (focus, message)
+  };
+  refresh(
+    data_access,
+    &buffer,
+    &viewport,
+    false,
+    focus.as_deref(),
+    line_idx,
+  )?;
+  data_access.echo(MessageLevel::Info, &message);
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn first_component1() {
+    assert_eq!(first_component("a.rs"), Some("a.rs".to_string()));
+    assert_eq!(first_component("src/"), Some("src".to_string()));
+    assert_eq!(first_component("src/lib/a.rs"), Some("src".to_string()));
+    assert_eq!(first_component("../a.rs"), None);
+  }
+}
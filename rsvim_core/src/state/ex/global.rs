@@ -0,0 +1,337 @@
+//! The `:global` ex commands.
+//!
+//! - `:[range]g[lobal]/{pattern}/{cmd}` executes `{cmd}` on each line in `[range]` (default is all
+//!   lines) matching `{pattern}`.
+//! - `:[range]g[lobal]!/{pattern}/{cmd}` and `:[range]v[global]/{pattern}/{cmd}` execute `{cmd}`
+//!   on each line not matching `{pattern}`.
+//!
+//! The delimiter can be any non-alphanumeric char (except `"`, `|` and `\`), and multiple commands
+//! can be separated with `|`, i.e. `:g/TODO/s/TODO/DONE/ | update`. It's two passes same with Vim:
+//! the matched lines are marked first (see [`LineMarks`](crate::buf::LineMarks)), then the cursor
+//! is moved to each marked line and `{cmd}` is executed. The marks follow the lines changed by
+//! `{cmd}`, and a deleted line is unmarked, i.e. `:g/^/m0` reverses the lines and `:g/x/+1d`
+//! deletes the line after each `x` line. It stops at the first error.
+//!
+//! The `:global` cannot be used recursively, i.e. `:g/x/g/y/d`.
+
+use crate::buf::BufferArc;
+use crate::envar;
+use crate::interrupt::Interrupt;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  split_bar, split_delimited, trim_eof_line, ExCommandDataAccess, ExCommandDefinition,
+  ExCommandLine, ExCommandsManager, ExLineRange,
+};
+use crate::state::fsm::mark::jump_to_line;
+use crate::{rlock, wlock};
+
+use regex::Regex;
+use std::ops::Range;
+
+/// The `:global` definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new(
+      "global",
+      "g",
+      "Execute commands on the matched lines",
+      handle_global,
+    ),
+    ExCommandDefinition::new(
+      "vglobal",
+      "v",
+      "Execute commands on the not matched lines",
+      handle_vglobal,
+    ),
+  ]
+}
+
+fn handle_global(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  handle(command_line, data_access, commands, command_line.bang())
+}
+
+fn handle_vglobal(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  handle(command_line, data_access, commands, true)
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  commands: &ExCommandsManager,
+  invert: bool,
+) -> ExCommandResult<()> {
+  let (pattern, rest) = match split_delimited(command_line.args(), 1) {
+    Some((mut fields, rest)) if !fields[0].is_empty() => (fields.remove(0), rest),
+    _ => return Err(ExCommandErr::ArgumentRequired),
+  };
+  let command_lines = split_bar(rest);
+  if command_lines.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+  let pattern = Regex::new(&pattern).map_err(|_| ExCommandErr::InvalidPattern(pattern.clone()))?;
+
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let current_line_idx = rlock!(viewport).cursor().line_idx();
+  let line_range = {
+    let buffer = rlock!(buffer);
+    let line_range = command_line.resolve_range(
+      ExLineRange::All,
+      current_line_idx,
+      buffer.len_lines(),
+      buffer.marks(),
+    )?;
+    trim_eof_line(&buffer, line_range)
+  };
+
+  let interrupt = data_access.interrupt.clone();
+  global_lines(
+    &buffer,
+    line_range,
+    &pattern,
+    invert,
+    &interrupt,
+    |line_idx| {
+      jump_to_line(&data_access.tree, line_idx);
+      data_access.run_nested(commands, &command_lines)
+    },
+  )?;
+  Ok(())
+}
+
+/// Execute `f` on each line in `line_range` matching `pattern` (or not matching if `invert`), the
+/// argument is the line index. The matched lines are marked first, then `f` is executed on each
+/// marked line. The buffer is not locked while executing `f`, thus `f` can change the buffer, the
+/// marked lines are adjusted along with the edits.
+///
+/// Returns the count of executed lines.
+///
+/// # Errors
+///
+/// If it's interrupted by `Ctrl-C` while matching or executing the lines (the lines already
+/// executed are kept), or `f` fails, or it's called recursively on the same buffer.
+pub fn global_lines<F>(
+  buffer: &BufferArc,
+  line_range: Range<usize>,
+  pattern: &Regex,
+  invert: bool,
+  interrupt: &Interrupt,
+  mut f: F,
+) -> ExCommandResult<usize>
+where
+  F: FnMut(usize) -> ExCommandResult<()>,
+{
+  // Mark the matched lines.
+  {
+    let mut buffer = wlock!(buffer);
+    if !buffer.line_marks().is_empty() {
+      return Err(ExCommandErr::Message(
+        "E147: Cannot do :global recursive".to_string(),
+      ));
+    }
+    let mut matched: Vec<usize> = vec![];
+    for line_idx in line_range {
+      interrupt.check()?;
+      let content = match buffer.get_line_content(line_idx) {
+        Some(content) => content,
+        None => break,
+      };
+      if pattern.is_match(&content) != invert {
+        matched.push(line_idx);
+      }
+    }
+    buffer.line_marks_mut().set(matched);
+  }
+
+  // Execute on the marked lines.
+  let mut executed = 0_usize;
+  let result = loop {
+    if let Err(e) = interrupt.check() {
+      break Err(e);
+    }
+    let line_idx = match wlock!(buffer).line_marks_mut().pop_first() {
+      Some(line_idx) => line_idx,
+      None => break Ok(executed),
+    };
+    if let Err(e) = f(line_idx) {
+      break Err(e);
+    }
+    executed += 1;
+  };
+  wlock!(buffer).line_marks_mut().clear();
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::buf::{Buffer, BuffersManager};
+  use crate::cart::{IRect, U16Size};
+  use crate::interrupt::Interrupt;
+  use crate::state::ex::substitute::substitute_lines;
+  use crate::state::msg::Messages;
+  use crate::test::buf::make_buffer_from_lines;
+  use crate::ui::tree::internal::Inodeable;
+  use crate::ui::tree::{Tree, TreeNode};
+  use crate::ui::widget::{Cursor, Window};
+
+  use std::sync::Arc;
+
+  fn lines(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.len_lines())
+      .map(|i| buffer.get_line_content(i).unwrap())
+      .collect()
+  }
+
+  // Make a 20x20 editor with a window showing an unnamed buffer of the `text`.
+  fn make_editor(text: &str) -> (ExCommandDataAccess, BufferArc) {
+    let mut buffers = BuffersManager::new();
+    let buf_id = buffers.new_empty_buffer();
+    let buffer = buffers.get(&buf_id).unwrap().clone();
+    wlock!(buffer).replace_loaded(text);
+
+    let mut tree = Tree::new(U16Size::new(20, 20));
+    let root_id = tree.root_id();
+    let shape = IRect::new((0, 0), (20, 20));
+    let window = Window::new(shape, Arc::downgrade(&buffer), tree.local_options());
+    let window_id = window.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(window));
+    tree.bounded_insert(
+      &window_id,
+      TreeNode::Cursor(Cursor::new(IRect::new((0, 0), (1, 1)))),
+    );
+    tree.layout_tiled_windows();
+
+    let data_access = ExCommandDataAccess::new(
+      Tree::to_arc(tree),
+      BuffersManager::to_arc(buffers),
+      Interrupt::to_arc(Interrupt::new()),
+      Messages::to_arc(Messages::new()),
+    );
+    (data_access, buffer)
+  }
+
+  #[test]
+  fn global1() {
+    let commands = ExCommandsManager::new();
+    let cases = vec![
+      // Reverse the lines.
+      ("a\nb\nc\nd\n", "g/^/m0", vec!["d", "c", "b", "a", ""]),
+      // Delete the line after each `x` line, the deleted `x` line is skipped.
+      ("x1\nx2\ny\nx3\nz\n", "g/x/+1d", vec!["x1", "y", "x3", ""]),
+      ("a\nb\nc\n", "v/b/m$", vec!["b", "a", "c", ""]),
+    ];
+    for (text, command_line, expect) in cases {
+      let (mut data_access, buffer) = make_editor(text);
+      commands.run(command_line, &mut data_access).unwrap();
+      assert_eq!(lines(&rlock!(buffer)), expect, "command:{:?}", command_line);
+      assert!(rlock!(buffer).line_marks().is_empty());
+    }
+  }
+
+  #[test]
+  fn global2() {
+    // Cannot be recursive.
+    let commands = ExCommandsManager::new();
+    let (mut data_access, buffer) = make_editor("a\nb\n");
+    assert!(commands.run("g/a/g/b/d", &mut data_access).is_err());
+    assert_eq!(lines(&rlock!(buffer)), vec!["a", "b", ""]);
+    assert!(rlock!(buffer).line_marks().is_empty());
+  }
+
+  #[test]
+  fn definitions1() {
+    let definitions = definitions();
+    assert_eq!(definitions[0].name(), "global");
+    assert_eq!(definitions[0].abbr(), "g");
+    assert_eq!(definitions[1].name(), "vglobal");
+    assert_eq!(definitions[1].abbr(), "v");
+  }
+
+  #[test]
+  fn global_lines1() {
+    let buffer = make_buffer_from_lines(vec!["foo 1\n", "bar\n", "foo 2\n", "baz\n"]);
+    let pattern = Regex::new("foo").unwrap();
+    let cases = vec![(false, vec![0, 2]), (true, vec![1, 3])];
+    for (invert, expect) in cases {
+      let mut actual = vec![];
+      let executed = global_lines(&buffer, 0..4, &pattern, invert, &Interrupt::new(), |i| {
+        actual.push(i);
+        Ok(())
+      })
+      .unwrap();
+      assert_eq!(executed, expect.len());
+      assert_eq!(actual, expect);
+    }
+  }
+
+  #[test]
+  fn global_lines2() {
+    // The marked lines are adjusted by the removed lines.
+    let buffer = make_buffer_from_lines(vec!["x\n", "x\n", "y\n", "x\n"]);
+    let pattern = Regex::new("x").unwrap();
+    let mut actual = vec![];
+    let executed = global_lines(&buffer, 0..4, &pattern, false, &Interrupt::new(), |i| {
+      actual.push(i);
+      let mut buffer = wlock!(buffer);
+      let start = buffer.line_to_char(i);
+      let end = buffer.line_to_char(i + 1);
+      buffer.remove(start..end);
+      Ok(())
+    })
+    .unwrap();
+    assert_eq!(executed, 3);
+    assert_eq!(actual, vec![0, 0, 1]);
+    assert_eq!(lines(&rlock!(buffer)), vec!["y", ""]);
+  }
+
+  #[test]
+  fn global_lines3() {
+    // Interrupted while executing, the executed lines are kept and the rest are skipped.
+    let buffer = make_buffer_from_lines(vec!["a\n", "a\n", "a\n", "a\n"]);
+    let pattern = Regex::new("a").unwrap();
+    let interrupt = Interrupt::new();
+    let actual = global_lines(&buffer, 0..4, &pattern, false, &interrupt, |i| {
+      substitute_lines(
+        &mut wlock!(buffer),
+        i..i + 1,
+        &pattern,
+        "b",
+        false,
+        &interrupt,
+      )?;
+      if i == 1 {
+        // i.e. the `Ctrl-C` is pressed while executing the 2nd line.
+        interrupt.interrupt();
+      }
+      Ok(())
+    });
+    assert_eq!(actual, Err(ExCommandErr::Interrupted));
+    assert_eq!(lines(&rlock!(buffer)), vec!["b", "b", "a", "a", ""]);
+  }
+
+  #[test]
+  fn global_lines4() {
+    // The error stops the execution.
+    let buffer = make_buffer_from_lines(vec!["a\n", "a\n"]);
+    let pattern = Regex::new("a").unwrap();
+    let mut count = 0;
+    let actual = global_lines(&buffer, 0..2, &pattern, false, &Interrupt::new(), |_| {
+      count += 1;
+      Err(ExCommandErr::ArgumentRequired)
+    });
+    assert_eq!(actual, Err(ExCommandErr::ArgumentRequired));
+    assert_eq!(count, 1);
+  }
+}
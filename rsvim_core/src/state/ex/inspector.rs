@@ -0,0 +1,341 @@
+//! The marks/registers inspector ex commands, see [`inspector`](crate::state::inspector) for the
+//! views.
+//!
+//! - `:marks` lists the marks of current buffer and the global marks (`A`-`Z`) of other buffers in
+//!   a read-only scratch buffer.
+//! - `:reg[isters]` lists the non-empty registers in a read-only scratch buffer.
+//! - `:MarksJump` jumps to the mark under the cursor in the `:marks` buffer, the scratch buffer is
+//!   discarded.
+//!
+//! Running `:marks`/`:registers` again in the scratch buffer goes back to the source buffer.
+//!
+//! The keys in the `:marks` buffer (see [`NormalStateful`](crate::state::fsm::NormalStateful)):
+//!
+//! - `<CR>`: `:MarksJump`.
+
+use crate::buf::mark::is_global_name;
+use crate::buf::BufferArc;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::current_window_buffer;
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::state::fsm::mark::jump_to_mark;
+use crate::state::fsm::visual::move_cursor_to;
+use crate::state::inspector::{
+  Inspector, InspectorEntries, MarkEntry, MARKS_FILE_TYPE, REGISTERS_FILE_TYPE,
+};
+use crate::ui::tree::TreeArc;
+use crate::{rlock, wlock};
+
+use ropey::Rope;
+use std::sync::Arc;
+
+/// The inspector definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new("marks", "marks", "List the marks", handle_marks),
+    ExCommandDefinition::new("registers", "reg", "List the registers", handle_registers),
+    ExCommandDefinition::new(
+      "MarksJump",
+      "MarksJ",
+      "Jump to the mark under the cursor",
+      handle_jump,
+    ),
+  ]
+}
+
+/// Whether current window shows a `:marks` buffer.
+pub fn in_marks(tree: &TreeArc) -> bool {
+  current_window_buffer(tree)
+    .is_some_and(|(buffer, _)| rlock!(buffer).options().file_type() == MARKS_FILE_TYPE)
+}
+
+// The `:marks` order, i.e. `'`, `a`-`z`, `A`-`Z`, then the others.
+fn mark_order(name: char) -> (u8, char) {
+  match name {
+    '\'' => (0, name),
+    'a'..='z' => (1, name),
+    'A'..='Z' => (2, name),
+    _ => (3, name),
+  }
+}
+
+// Collect the marks of the `buffer`, and the global marks of other buffers.
+fn mark_entries(data_access: &ExCommandDataAccess, buffer: &BufferArc) -> Vec<MarkEntry> {
+  let mut entries = vec![];
+  for (_, other) in rlock!(data_access.buffers).iter() {
+    let is_current = Arc::ptr_eq(other, buffer);
+    let other = rlock!(other);
+    for (name, pos) in other.marks().iter() {
+      if is_current {
+        entries.push(MarkEntry {
+          name: *name,
+          line_idx: pos.line_idx,
+          char_idx: pos.char_idx,
+          file: None,
+          text: other.get_line_content(pos.line_idx).unwrap_or_default(),
+        });
+      } else if is_global_name(*name) {
+        entries.push(MarkEntry {
+          name: *name,
+          line_idx: pos.line_idx,
+          char_idx: pos.char_idx,
+          file: other.filename().clone(),
+          text: String::new(),
+        });
+      }
+    }
+  }
+  entries.sort_by_key(|entry| mark_order(entry.name));
+  entries
+}
+
+// Get the source buffer if `buffer` is an inspector buffer.
+fn inspector_source(data_access: &ExCommandDataAccess, buffer: &BufferArc) -> Option<BufferArc> {
+  let buffer = rlock!(buffer);
+  let file_type = buffer.options().file_type();
+  if file_type != MARKS_FILE_TYPE && file_type != REGISTERS_FILE_TYPE {
+    return None;
+  }
+  let buffers = rlock!(data_access.buffers);
+  let source_id = buffers.scratch_source(&buffer.id())?;
+  buffers.get(&source_id).cloned()
+}
+
+// Go back to the source buffer if current buffer is an inspector buffer, the inspector buffer is
+// discarded. Returns `false` if current buffer is not an inspector buffer.
+fn back_to_source(data_access: &ExCommandDataAccess) -> bool {
+  let (buffer, _) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return false,
+  };
+  match inspector_source(data_access, &buffer) {
+    Some(source) => {
+      data_access.show_buffer(&source);
+      let buffer_id = rlock!(buffer).id();
+      wlock!(data_access.buffers).remove(&buffer_id);
+      true
+    }
+    None => false,
+  }
+}
+
+// Show the inspector in a read-only scratch buffer, the cursor is on the selected entry.
+fn show_inspector(
+  data_access: &ExCommandDataAccess,
+  entries: InspectorEntries,
+  file_type: &str,
+) -> ExCommandResult<()> {
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let inspector = Inspector::new(entries);
+  let mut text = inspector.lines().join("\n");
+  text.push('\n');
+
+  let buffer_id = rlock!(buffer).id();
+  let scratch = {
+    let mut buffers = wlock!(data_access.buffers);
+    let scratch_id = buffers.new_scratch_buffer(Rope::from_str(&text), Some(buffer_id));
+    buffers.get(&scratch_id).unwrap().clone()
+  };
+  {
+    let mut scratch = wlock!(scratch);
+    let mut options = scratch.options().clone();
+    options.set_file_type(file_type);
+    scratch.set_options(&options);
+    scratch.set_readonly(true);
+  }
+
+  data_access.show_buffer(&scratch);
+  move_cursor_to(
+    &data_access.tree,
+    &viewport,
+    (inspector.selected_line_idx().unwrap_or(0), 0),
+  );
+  Ok(())
+}
+
+fn handle_marks(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if back_to_source(data_access) {
+    return Ok(());
+  }
+  let buffer = match data_access.current_window_buffer() {
+    Some((buffer, _)) => buffer,
+    None => return Ok(()),
+  };
+  let entries = mark_entries(data_access, &buffer);
+  if entries.is_empty() {
+    return Err(ExCommandErr::Message("E283: No marks matching".to_string()));
+  }
+  show_inspector(
+    data_access,
+    InspectorEntries::Marks(entries),
+    MARKS_FILE_TYPE,
+  )
+}
+
+fn handle_registers(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if back_to_source(data_access) {
+    return Ok(());
+  }
+  let entries = data_access.registers().clone();
+  show_inspector(
+    data_access,
+    InspectorEntries::Registers(entries),
+    REGISTERS_FILE_TYPE,
+  )
+}
+
+fn handle_jump(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  if !in_marks(&data_access.tree) {
+    return Err(ExCommandErr::Message("Not a marks buffer".to_string()));
+  }
+
+  // NOTE: The mark name is the second char of the line, the first line is the header.
+  let line_idx = rlock!(viewport).cursor().line_idx();
+  let name = match line_idx {
+    0 => None,
+    _ => rlock!(buffer)
+      .get_line_content(line_idx)
+      .and_then(|line| line.chars().nth(1)),
+  };
+  let name = match name {
+    Some(name) => name,
+    None => return Ok(()),
+  };
+
+  back_to_source(data_access);
+  if !jump_to_mark(&data_access.tree, &data_access.buffers, name, true) {
+    return Err(ExCommandErr::Message("E20: Mark not set".to_string()));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::buf::{BuffersManager, MarkPos};
+  use crate::cart::{IRect, U16Size};
+  use crate::interrupt::Interrupt;
+  use crate::state::msg::Messages;
+  use crate::state::register::{RegisterEntry, RegisterKind};
+  use crate::ui::tree::internal::Inodeable;
+  use crate::ui::tree::{Tree, TreeNode};
+  use crate::ui::widget::{Cursor, Window};
+
+  // Make a 20x20 editor with a window showing a 10 lines unnamed buffer, the buffer has the marks
+  // `a` (line 4) and `B` (line 8).
+  fn make_editor() -> (ExCommandDataAccess, BufferArc) {
+    let mut buffers = BuffersManager::new();
+    let buf_id = buffers.new_empty_buffer();
+    let buffer = buffers.get(&buf_id).unwrap().clone();
+    let text = (0..10).map(|i| format!("line {}\n", i)).collect::<String>();
+    wlock!(buffer).replace_loaded(&text);
+    buffers.set_mark(&buf_id, 'a', MarkPos::new(3, 2));
+    buffers.set_mark(&buf_id, 'B', MarkPos::new(7, 0));
+
+    let mut tree = Tree::new(U16Size::new(20, 20));
+    let root_id = tree.root_id();
+    let shape = IRect::new((0, 0), (20, 20));
+    let window = Window::new(shape, Arc::downgrade(&buffer), tree.local_options());
+    let window_id = window.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(window));
+    tree.bounded_insert(
+      &window_id,
+      TreeNode::Cursor(Cursor::new(IRect::new((0, 0), (1, 1)))),
+    );
+    tree.layout_tiled_windows();
+
+    let data_access = ExCommandDataAccess::new(
+      Tree::to_arc(tree),
+      BuffersManager::to_arc(buffers),
+      Interrupt::to_arc(Interrupt::new()),
+      Messages::to_arc(Messages::new()),
+    );
+    (data_access, buffer)
+  }
+
+  fn current_text(data_access: &ExCommandDataAccess) -> String {
+    let (buffer, _) = data_access.current_window_buffer().unwrap();
+    let text = rlock!(buffer)
+      .lines()
+      .map(|line| line.to_string())
+      .collect();
+    text
+  }
+
+  fn cursor_line(data_access: &ExCommandDataAccess) -> usize {
+    let (_, viewport) = data_access.current_window_buffer().unwrap();
+    let viewport = rlock!(viewport);
+    viewport.cursor().line_idx()
+  }
+
+  #[test]
+  fn marks1() {
+    let (mut data_access, source) = make_editor();
+    let commands = ExCommandsManager::new();
+    commands.run("marks", &mut data_access).unwrap();
+    assert!(in_marks(&data_access.tree));
+    assert_eq!(
+      current_text(&data_access),
+      "mark line  col file/text\n a      4    2 line 3\n B      8    0 line 7\n"
+    );
+    assert_eq!(cursor_line(&data_access), 1);
+
+    // Jump to the mark `B`, the marks buffer is discarded.
+    let (scratch, viewport) = data_access.current_window_buffer().unwrap();
+    let scratch_id = rlock!(scratch).id();
+    move_cursor_to(&data_access.tree, &viewport, (2, 0));
+    commands.run("MarksJump", &mut data_access).unwrap();
+    let (current, _) = data_access.current_window_buffer().unwrap();
+    assert!(Arc::ptr_eq(&current, &source));
+    assert_eq!(cursor_line(&data_access), 7);
+    assert!(rlock!(data_access.buffers).get(&scratch_id).is_none());
+    assert!(commands.run("MarksJump", &mut data_access).is_err());
+  }
+
+  #[test]
+  fn registers1() {
+    let (data_access, source) = make_editor();
+    let mut data_access = data_access.with_registers(vec![RegisterEntry {
+      name: 'a',
+      kind: RegisterKind::Charwise,
+      content: "foo\nbar".to_string(),
+    }]);
+    let commands = ExCommandsManager::new();
+    commands.run("reg", &mut data_access).unwrap();
+    assert!(!in_marks(&data_access.tree));
+    assert_eq!(
+      current_text(&data_access),
+      "Type Name Content\n  c  \"a   foo^Jbar\n"
+    );
+    let (scratch, _) = data_access.current_window_buffer().unwrap();
+    assert!(rlock!(scratch).is_read_only());
+
+    // Go back to the source buffer.
+    commands.run("registers", &mut data_access).unwrap();
+    let (current, _) = data_access.current_window_buffer().unwrap();
+    assert!(Arc::ptr_eq(&current, &source));
+  }
+}
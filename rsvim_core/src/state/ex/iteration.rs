@@ -0,0 +1,149 @@
+//! The do-family ex commands.
+//!
+//! - `:windo {cmd}` executes `{cmd}` in each window.
+//! - `:bufdo {cmd}` executes `{cmd}` in each buffer (the scratch buffers are skipped).
+//! - `:tabdo {cmd}` executes `{cmd}` in each tab page.
+//!
+//! Multiple commands can be separated with `|`, i.e. `:bufdo %s/foo/bar/ | update`. The iteration
+//! stops at the first error. The nesting depth is limited by [`MAX_DO_DEPTH`](crate::state::ex::MAX_DO_DEPTH),
+//! and the focus (current window/buffer) is restored afterward, even if the commands fail.
+//!
+//! NOTE: There're no tab pages yet, all windows belong to the only (implicit) tab page, thus
+//! `:tabdo` executes the commands once.
+
+use crate::buf::BufferId;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  split_bar, ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::ui::tree::TreeNodeId;
+use crate::{rlock, wlock};
+
+/// The do-family definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new(
+      "windo",
+      "windo",
+      "Execute commands in each window",
+      handle_windo,
+    ),
+    ExCommandDefinition::new(
+      "bufdo",
+      "bufdo",
+      "Execute commands in each buffer",
+      handle_bufdo,
+    ),
+    ExCommandDefinition::new(
+      "tabdo",
+      "tabdo",
+      "Execute commands in each tab page",
+      handle_tabdo,
+    ),
+  ]
+}
+
+/// Get the command lines from the arguments.
+fn command_lines(command_line: &ExCommandLine) -> ExCommandResult<Vec<String>> {
+  let command_lines = split_bar(command_line.args());
+  if command_lines.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+  Ok(command_lines)
+}
+
+fn handle_windo(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let command_lines = command_lines(command_line)?;
+
+  let (current_window_id, window_ids) = {
+    let tree = rlock!(data_access.tree);
+    let window_ids = tree
+      .window_ids()
+      .iter()
+      .copied()
+      .collect::<Vec<TreeNodeId>>();
+    (tree.current_window_id(), window_ids)
+  };
+
+  let result = window_ids.into_iter().try_for_each(|window_id| {
+    if wlock!(data_access.tree).set_current_window_id(window_id) {
+      data_access.run_nested(commands, &command_lines)
+    } else {
+      Ok(())
+    }
+  });
+
+  if let Some(window_id) = current_window_id {
+    wlock!(data_access.tree).set_current_window_id(window_id);
+  }
+  result
+}
+
+fn handle_bufdo(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let command_lines = command_lines(command_line)?;
+
+  let current_buffer = data_access
+    .current_window_buffer()
+    .map(|(buffer, _viewport)| buffer);
+  let buffer_ids = {
+    let buffers = rlock!(data_access.buffers);
+    buffers
+      .keys()
+      .filter(|id| !buffers.is_scratch(id))
+      .copied()
+      .collect::<Vec<BufferId>>()
+  };
+
+  let result = buffer_ids.into_iter().try_for_each(|buffer_id| {
+    // The buffer could be removed by previous commands.
+    let buffer = rlock!(data_access.buffers).get(&buffer_id).cloned();
+    match buffer {
+      Some(buffer) => {
+        data_access.show_buffer(&buffer);
+        data_access.run_nested(commands, &command_lines)
+      }
+      None => Ok(()),
+    }
+  });
+
+  if let Some(buffer) = current_buffer {
+    data_access.show_buffer(&buffer);
+  }
+  result
+}
+
+fn handle_tabdo(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let command_lines = command_lines(command_line)?;
+  data_access.run_nested(commands, &command_lines)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn definitions1() {
+    let definitions = definitions();
+    let names = definitions
+      .iter()
+      .map(|definition| definition.name())
+      .collect::<Vec<_>>();
+    assert_eq!(names, vec!["windo", "bufdo", "tabdo"]);
+    assert!(definitions
+      .iter()
+      .all(|definition| definition.matches(definition.name())));
+  }
+}
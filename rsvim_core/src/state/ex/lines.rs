@@ -0,0 +1,237 @@
+//! The line editing ex commands.
+//!
+//! - `:[range]d[elete]` deletes the lines in `[range]` (default is current line).
+//! - `:[range]m[ove] {address}` moves the lines in `[range]` (default is current line) below the
+//!   line `{address}`, the `:m0` moves them above the first line.
+//!
+//! They're mostly used with `:global`, i.e. `:g/^/m0` reverses the lines, see
+//! [`global`](crate::state::ex::global). The cursor is moved to the line after the deleted lines,
+//! or the last moved line.
+//!
+//! NOTE: The deleted lines are not saved to the registers yet.
+
+use crate::buf::Buffer;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  parse_address, trim_eof_line, ExCommandDataAccess, ExCommandDefinition, ExCommandLine,
+  ExCommandsManager, ExLineAddress, ExLineRange,
+};
+use crate::state::fsm::mark::jump_to_line;
+use crate::{rlock, wlock};
+
+use std::ops::Range;
+
+/// The line editing definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new("delete", "d", "Delete the lines", handle_delete),
+    ExCommandDefinition::new("move", "m", "Move the lines below a line", handle_move),
+  ]
+}
+
+/// Delete the lines in `line_range`. If the last line (without a line break) is deleted, the line
+/// break before it is also deleted.
+pub fn delete_lines(buffer: &mut Buffer, line_range: Range<usize>) {
+  if line_range.is_empty() {
+    return;
+  }
+  let start = buffer.line_to_char(line_range.start);
+  let end = buffer.line_to_char(line_range.end.min(buffer.len_lines()));
+  let start = if end == buffer.len_chars()
+    && start > 0
+    && buffer.get_char(end.saturating_sub(1)) != Some('\n')
+  {
+    start - 1
+  } else {
+    start
+  };
+  if end > start {
+    buffer.remove(start..end);
+  }
+}
+
+/// Move the lines in `line_range` below the line `line_idx`, or above the first line if it's
+/// `None`. The deletion and insertion are a single undo step.
+///
+/// Returns the line index of the last moved line.
+///
+/// # Errors
+///
+/// If `line_idx` is inside `line_range` (except the last line, it's not moved).
+pub fn move_lines(
+  buffer: &mut Buffer,
+  line_range: Range<usize>,
+  line_idx: Option<usize>,
+) -> ExCommandResult<usize> {
+  if let Some(line_idx) = line_idx {
+    if line_range.start <= line_idx && line_idx + 1 < line_range.end {
+      return Err(ExCommandErr::Message(
+        "E134: Cannot move a range of lines into itself".to_string(),
+      ));
+    }
+    if line_idx + 1 == line_range.end {
+      return Ok(line_idx);
+    }
+  }
+  let lines: Vec<String> = line_range
+    .clone()
+    .filter_map(|i| buffer.get_line_content(i))
+    .collect();
+  let insert_line_idx = match line_idx {
+    None => 0,
+    Some(line_idx) if line_idx >= line_range.end => line_idx + 1 - lines.len(),
+    Some(line_idx) => line_idx + 1,
+  };
+  buffer.undo_tree_mut().begin_group();
+  delete_lines(buffer, line_range);
+  buffer.set_lines(insert_line_idx, insert_line_idx, &lines);
+  buffer.undo_tree_mut().end_group();
+  Ok(insert_line_idx + lines.len() - 1)
+}
+
+// Resolve the `[range]` of current buffer, default is current line.
+fn line_range(
+  command_line: &ExCommandLine,
+  buffer: &Buffer,
+  current_line_idx: usize,
+) -> ExCommandResult<Range<usize>> {
+  let line_range = command_line.resolve_range(
+    ExLineRange::Lines(ExLineAddress::Current, ExLineAddress::Current),
+    current_line_idx,
+    buffer.len_lines(),
+    buffer.marks(),
+  )?;
+  Ok(trim_eof_line(buffer, line_range))
+}
+
+fn handle_delete(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if !command_line.args().is_empty() {
+    return Err(ExCommandErr::Message(format!(
+      "E488: Trailing characters: {}",
+      command_line.args()
+    )));
+  }
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let current_line_idx = rlock!(viewport).cursor().line_idx();
+  let cursor_line_idx = {
+    let mut buffer = wlock!(buffer);
+    let line_range = line_range(command_line, &buffer, current_line_idx)?;
+    delete_lines(&mut buffer, line_range.clone());
+    line_range.start.min(
+      trim_eof_line(&buffer, 0..buffer.len_lines())
+        .end
+        .saturating_sub(1),
+    )
+  };
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+  jump_to_line(&data_access.tree, cursor_line_idx);
+  Ok(())
+}
+
+fn handle_move(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let address = match parse_address(command_line.args())? {
+    (Some(address), rest) if rest.trim().is_empty() => address,
+    (Some(_), rest) => {
+      return Err(ExCommandErr::Message(format!(
+        "E488: Trailing characters: {}",
+        rest.trim()
+      )))
+    }
+    (None, _) => return Err(ExCommandErr::InvalidRange),
+  };
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let current_line_idx = rlock!(viewport).cursor().line_idx();
+  let cursor_line_idx = {
+    let mut buffer = wlock!(buffer);
+    let line_range = line_range(command_line, &buffer, current_line_idx)?;
+    let line_idx = match address {
+      ExLineAddress::Number(0) => None,
+      address => {
+        let len_lines = trim_eof_line(&buffer, 0..buffer.len_lines()).end;
+        let line_idx = address.resolve(current_line_idx, len_lines, buffer.marks())?;
+        if line_idx >= len_lines {
+          return Err(ExCommandErr::InvalidRange);
+        }
+        Some(line_idx)
+      }
+    };
+    move_lines(&mut buffer, line_range, line_idx)?
+  };
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+  jump_to_line(&data_access.tree, cursor_line_idx);
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::test::buf::make_buffer_from_lines;
+
+  fn contents(buffer: &Buffer) -> String {
+    buffer.lines().map(|line| line.to_string()).collect()
+  }
+
+  #[test]
+  fn delete_lines1() {
+    let buffer = make_buffer_from_lines(vec!["a\n", "b\n", "c\n"]);
+    let mut buffer = wlock!(buffer);
+    delete_lines(&mut buffer, 1..2);
+    assert_eq!(contents(&buffer), "a\nc\n");
+    delete_lines(&mut buffer, 0..2);
+    assert_eq!(contents(&buffer), "");
+
+    // The last line doesn't have a line break.
+    let buffer = make_buffer_from_lines(vec!["a\n", "b\n", "c"]);
+    let mut buffer = wlock!(buffer);
+    delete_lines(&mut buffer, 2..3);
+    assert_eq!(contents(&buffer), "a\nb");
+  }
+
+  #[test]
+  fn move_lines1() {
+    let buffer = make_buffer_from_lines(vec!["a\n", "b\n", "c\n", "d\n"]);
+    let mut buffer = wlock!(buffer);
+    assert_eq!(move_lines(&mut buffer, 2..3, None), Ok(0));
+    assert_eq!(contents(&buffer), "c\na\nb\nd\n");
+    assert_eq!(move_lines(&mut buffer, 0..2, Some(3)), Ok(3));
+    assert_eq!(contents(&buffer), "b\nd\nc\na\n");
+    assert_eq!(move_lines(&mut buffer, 2..4, Some(0)), Ok(2));
+    assert_eq!(contents(&buffer), "b\nc\na\nd\n");
+    // Moved as a single undo step.
+    buffer.undo();
+    assert_eq!(contents(&buffer), "b\nd\nc\na\n");
+
+    assert!(move_lines(&mut buffer, 0..3, Some(1)).is_err());
+    assert_eq!(move_lines(&mut buffer, 0..3, Some(2)), Ok(2));
+    assert_eq!(contents(&buffer), "b\nd\nc\na\n");
+  }
+
+  #[test]
+  fn move_lines2() {
+    // The last line doesn't have a line break.
+    let buffer = make_buffer_from_lines(vec!["a\n", "b"]);
+    let mut buffer = wlock!(buffer);
+    assert_eq!(move_lines(&mut buffer, 1..2, None), Ok(0));
+    assert_eq!(contents(&buffer), "b\na");
+    assert_eq!(move_lines(&mut buffer, 0..1, Some(1)), Ok(1));
+    assert_eq!(contents(&buffer), "a\nb");
+  }
+}
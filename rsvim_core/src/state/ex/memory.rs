@@ -0,0 +1,118 @@
+//! The `:memory` ex command.
+//!
+//! - `:mem[ory]` shows the undo memory usage of each buffer, i.e. the count of the undoable
+//!   states, the estimated memory and the count of the evicted states (see
+//!   [`UndoTree`](crate::buf::UndoTree) for the eviction).
+
+use crate::buf::BufferId;
+use crate::envar;
+use crate::res::ExCommandResult;
+use crate::rlock;
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::state::msg::MessageLevel;
+
+/// The `:memory` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new("memory", "mem", "Show the undo memory usage", handle)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The undo memory usage of a buffer.
+pub struct BufferMemory {
+  pub id: BufferId,
+  pub name: Option<String>,
+  /// The count of the undoable states.
+  pub undo_levels: usize,
+  /// The estimated memory (in bytes) of the undo states.
+  pub undo_memory: usize,
+  /// The count of the evicted undo states.
+  pub undo_evictions: usize,
+}
+
+/// Format the memory usage as `:memory` output, one buffer per line after the header.
+pub fn format_memory(list: &[BufferMemory]) -> String {
+  let mut lines = vec![format!(
+    "{:>3} {:>6} {:>10} {:>8} name",
+    "buf", "undo", "memory", "evicted"
+  )];
+  for info in list {
+    lines.push(format!(
+      "{:>3} {:>6} {:>10} {:>8} \"{}\"",
+      info.id,
+      info.undo_levels,
+      format_bytes(info.undo_memory),
+      info.undo_evictions,
+      info.name.as_deref().unwrap_or("[No Name]")
+    ));
+  }
+  let total: usize = list.iter().map(|info| info.undo_memory).sum();
+  lines.push(format!("Total undo memory: {}", format_bytes(total)));
+  lines.join("\n")
+}
+
+/// Format the bytes with the binary unit, i.e. `512B`, `1.5KiB`, `64.0MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+  const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+  if bytes < 1024 {
+    return format!("{}B", bytes);
+  }
+  let mut value = bytes as f64 / 1024.0;
+  let mut unit = 0;
+  while value >= 1024.0 && unit + 1 < UNITS.len() {
+    value /= 1024.0;
+    unit += 1;
+  }
+  format!("{:.1}{}", value, UNITS[unit])
+}
+
+fn handle(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let list: Vec<BufferMemory> = rlock!(data_access.buffers)
+    .values()
+    .map(|buf| {
+      let buf = rlock!(buf);
+      BufferMemory {
+        id: buf.id(),
+        name: buf.display_name(),
+        undo_levels: buf.undo_tree().levels(),
+        undo_memory: buf.undo_tree().memory(),
+        undo_evictions: buf.undo_tree().evictions(),
+      }
+    })
+    .collect();
+  data_access.echo(MessageLevel::Info, &format_memory(&list));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn format_bytes1() {
+    assert_eq!(format_bytes(512), "512B");
+    assert_eq!(format_bytes(1536), "1.5KiB");
+    assert_eq!(format_bytes(64 * 1024 * 1024), "64.0MiB");
+    assert_eq!(format_bytes(4096 * 1024 * 1024), "4.0GiB");
+  }
+
+  #[test]
+  fn format_memory1() {
+    let list = vec![BufferMemory {
+      id: 1,
+      name: Some("a.md".to_string()),
+      undo_levels: 1000,
+      undo_memory: 2048,
+      undo_evictions: 5,
+    }];
+    assert_eq!(
+      format_memory(&list),
+      "buf   undo     memory  evicted name\n  1   1000     2.0KiB        5 \"a.md\"\nTotal undo memory: 2.0KiB"
+    );
+  }
+}
@@ -0,0 +1,65 @@
+//! The `:messages` ex command.
+//!
+//! - `:mes[sages]` shows the message history (see [`Messages`](crate::state::msg::Messages)) in a
+//!   scratch buffer, in a new window above current window. The cursor is on the latest message.
+//! - `:mes[sages] clear` clears the message history.
+
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::state::fsm::mark::jump_to_line;
+use crate::ui::tree::layout::LayoutDirection;
+use crate::{rlock, wlock};
+
+use ropey::Rope;
+
+/// The `:messages` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new("messages", "mes", "Show the message history", handle)
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  match command_line.args().trim() {
+    "" => {}
+    "clear" => {
+      wlock!(data_access.messages).clear_history();
+      return Ok(());
+    }
+    args => {
+      return Err(ExCommandErr::Message(format!(
+        "E488: Trailing characters: {}",
+        args
+      )))
+    }
+  }
+
+  let (text, len) = {
+    let messages = rlock!(data_access.messages);
+    (messages.format_history(), messages.history().len())
+  };
+  if len == 0 {
+    return Ok(());
+  }
+
+  if wlock!(data_access.tree)
+    .split_window(LayoutDirection::Column)
+    .is_none()
+  {
+    return Err(ExCommandErr::Message("E36: Not enough room".to_string()));
+  }
+  let buffer = {
+    let mut buffers = wlock!(data_access.buffers);
+    let scratch_id = buffers.new_scratch_buffer(Rope::from_str(&text), None);
+    buffers.get(&scratch_id).unwrap().clone()
+  };
+  data_access.show_buffer(&buffer);
+  // The line index is clamped, i.e. it's the last line.
+  jump_to_line(&data_access.tree, usize::MAX);
+  Ok(())
+}
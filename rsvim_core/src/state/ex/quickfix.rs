@@ -0,0 +1,784 @@
+//! The quickfix ex commands.
+//!
+//! - `:gr[ep][!] [flags] {pattern} [path] ..` searches the files in background (see
+//!   [`grep`](crate::state::grep)), the matches are added to the quickfix list while searching.
+//!   It jumps to the first match once the search is finished, unless `!` is given. The flags are:
+//!   - `-F`: Match the pattern literally instead of as a regex.
+//!   - `-i`: Match case insensitively.
+//!   - `-S`: Match case insensitively if the pattern is all lowercase.
+//!   - `-.`: Search the hidden files and directories.
+//!   - `-u`: Don't respect the ignore files, i.e. `.gitignore`.
+//!   - `--`: Stop parsing the flags, i.e. `:grep -- -foo`.
+//! - `:cl[ist]` lists the quickfix list, the current entry is marked with `>`.
+//! - `:[count]cn[ext]` jumps to the `[count]`th next entry.
+//! - `:[count]cp[revious]` (or `:[count]cN[ext]`) jumps to the `[count]`th previous entry.
+//! - `:cfir[st]`/`:cla[st]` jumps to the first/last entry.
+//! - `:cc [nr]` (or `:[nr]cc`) jumps to the entry `[nr]` (starts from 1), default is current entry.
+//! - `:cope[n] [height]` opens the quickfix window below current window (default height is
+//!   [`QUICKFIX_HEIGHT`](crate::defaults::win::QUICKFIX_HEIGHT)), each line is an entry. The cursor
+//!   moves to the quickfix window if it's already opened.
+//! - `:ccl[ose]` closes the quickfix window.
+//!
+//! The "other window" of the quickfix window is the previous window in the layout order (or the
+//! next one if it's the first), i.e. the window above it. The jumps from the quickfix window go to
+//! the other window, and when the 'quickfixpreview' option is on, moving the cursor in the quickfix
+//! window previews the entry under cursor in the other window, i.e. its file is shown, and its
+//! viewport and cursor are moved to the entry, while the cursor stays in the quickfix window. The
+//! file that's not opened is loaded in background into the preview buffer instead of a file
+//! buffer, the preview buffer is an unlisted scratch buffer reused by all previews.
+//!
+//! See [`QuickfixList`](crate::buf::QuickfixList) for the quickfix list.
+
+use crate::buf::{BufferArc, BuffersManagerArc, QuickfixEntry, QuickfixList};
+use crate::defaults;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::current_window_buffer;
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager, ExLineAddress,
+  ExLineRange,
+};
+use crate::state::fsm::mark::jump_to_line;
+use crate::state::fsm::visual::move_cursor_to;
+use crate::state::grep::{GrepOptions, GrepRequest};
+use crate::state::msg::MessageLevel;
+use crate::ui::tree::layout::{LayoutDirection, ResizeAmount};
+use crate::ui::tree::{JumpCenter, Tree, TreeArc, TreeNode, TreeNodeId};
+use crate::ui::widget::window::Viewport;
+use crate::{rlock, wlock};
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::trace;
+
+/// The quickfix definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new("grep", "gr", "Search files in background", handle_grep)
+      .with_file_completion(),
+    ExCommandDefinition::new("clist", "cl", "List the quickfix list", handle_clist),
+    ExCommandDefinition::new(
+      "cnext",
+      "cn",
+      "Jump to the next quickfix entry",
+      handle_cnext,
+    ),
+    ExCommandDefinition::new(
+      "cprevious",
+      "cp",
+      "Jump to the previous quickfix entry",
+      handle_cprevious,
+    ),
+    ExCommandDefinition::new(
+      "cNext",
+      "cN",
+      "Jump to the previous quickfix entry",
+      handle_cprevious,
+    ),
+    ExCommandDefinition::new(
+      "cfirst",
+      "cfir",
+      "Jump to the first quickfix entry",
+      handle_cfirst,
+    ),
+    ExCommandDefinition::new(
+      "clast",
+      "cla",
+      "Jump to the last quickfix entry",
+      handle_clast,
+    ),
+    ExCommandDefinition::new("cc", "cc", "Jump to a quickfix entry", handle_cc),
+    ExCommandDefinition::new("copen", "cope", "Open the quickfix window", handle_copen),
+    ExCommandDefinition::new("cclose", "ccl", "Close the quickfix window", handle_cclose),
+  ]
+}
+
+/// Get the `[count]` from the range, default is 1.
+fn count(command_line: &ExCommandLine) -> usize {
+  match command_line.range() {
+    Some(ExLineRange::Lines(_, ExLineAddress::Number(n))) if *n > 0 => *n,
+    _ => 1,
+  }
+}
+
+/// Format the `:clist` output, one entry per line, i.e. `>  2 src/main.rs:10:5: fn main() {`.
+pub fn format_quickfix_list(list: &QuickfixList) -> String {
+  list
+    .entries()
+    .iter()
+    .enumerate()
+    .map(|(idx, entry)| {
+      format!(
+        "{}{:>3} {}",
+        if Some(idx) == list.current() {
+          '>'
+        } else {
+          ' '
+        },
+        idx + 1,
+        entry.format()
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Get the windows showing the quickfix `buffer`, in the layout order.
+pub fn quickfix_window_ids(tree: &Tree, buffer: &BufferArc) -> Vec<TreeNodeId> {
+  tree
+    .tiled_window_ids()
+    .into_iter()
+    .filter(|id| match tree.node(id) {
+      Some(TreeNode::Window(window)) => window
+        .buffer()
+        .upgrade()
+        .is_some_and(|b| Arc::ptr_eq(&b, buffer)),
+      _ => false,
+    })
+    .collect()
+}
+
+/// Get the other window of the quickfix window `window_id`, i.e. the previous window in the
+/// layout order, or the next one if it's the first.
+///
+/// Returns `None` if it's the only tiled window.
+pub fn other_window_id(tree: &Tree, window_id: TreeNodeId) -> Option<TreeNodeId> {
+  let window_ids = tree.tiled_window_ids();
+  let idx = window_ids.iter().position(|id| *id == window_id)?;
+  if idx > 0 {
+    Some(window_ids[idx - 1])
+  } else {
+    window_ids.get(1).copied()
+  }
+}
+
+/// Sync the quickfix list to the quickfix window if it's opened, i.e. after the list is changed
+/// by `:grep`. The cursor goes back to the first line if its line is removed.
+pub fn refresh_quickfix_window(tree: &TreeArc, buffers: &BuffersManagerArc) {
+  let buffer = match wlock!(buffers).sync_quickfix_buffer() {
+    Some(buffer) => buffer,
+    None => return,
+  };
+  let len_lines = rlock!(buffer).len_lines();
+
+  // NOTE: The buffer lock must be released before syncing viewport.
+  let mut tree = wlock!(tree);
+  for window_id in quickfix_window_ids(&tree, &buffer) {
+    if let Some(TreeNode::Window(window)) = tree.node(&window_id) {
+      let viewport = window.viewport();
+      let mut viewport = wlock!(viewport);
+      viewport.sync();
+      if viewport.cursor().line_idx() + 1 >= len_lines {
+        viewport.sync_to_line(0);
+      }
+    }
+  }
+  tree.sync_cursor_position();
+}
+
+// Move the viewport and cursor to the `position` (line index and char index), the line is
+// centered with the 'jumpcenter' option, the buffer has `len_lines` lines. It doesn't move the
+// cursor widget, i.e. the viewport is not current window's.
+fn move_viewport_to(
+  viewport: &mut Viewport,
+  jump_center: JumpCenter,
+  len_lines: usize,
+  position: (usize, usize),
+) {
+  if viewport.is_empty() {
+    return;
+  }
+  let visible = viewport.start_line_idx()..viewport.end_line_idx();
+  let height = viewport.actual_shape().height() as usize;
+  if let Some(start_line_idx) = jump_center.start_line_idx(position.0, visible, height, len_lines) {
+    viewport.sync_from_top_left(start_line_idx, 0);
+  }
+  let (start_line_idx, start_dcol_idx) = viewport.search_anchor(position.0, position.1);
+  if start_line_idx != viewport.start_line_idx() || start_dcol_idx != viewport.start_dcol_idx() {
+    viewport.sync_from_top_left(start_line_idx, start_dcol_idx);
+  }
+  if let Some(cursor) = viewport.cursor_of(position.0, position.1) {
+    viewport.set_cursor(cursor);
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The result of [`preview_entry`].
+pub enum Preview {
+  /// Current window is not the quickfix window, or the entry can't be shown.
+  None,
+  /// The entry is shown in the other window.
+  Shown,
+  /// The entry's file is not opened or loaded in the preview buffer, it needs to be loaded in
+  /// background first, see [`BuffersManager::set_preview_buffer`](crate::buf::BuffersManager).
+  Load(PathBuf),
+}
+
+/// Preview the entry under cursor in the other window, when current window is the quickfix
+/// window. The entry's file buffer is shown if it's opened, otherwise the preview buffer is shown
+/// if the file is loaded in it, i.e. the previewed files are not opened as (listed) buffers.
+pub fn preview_entry(tree: &TreeArc, buffers: &BuffersManagerArc) -> Preview {
+  let (buffer, viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return Preview::None,
+  };
+  let line_idx = rlock!(viewport).cursor().line_idx();
+  let (entry, file_buffer) = {
+    let buffers = rlock!(buffers);
+    if !buffers
+      .quickfix_buffer()
+      .is_some_and(|b| Arc::ptr_eq(b, &buffer))
+    {
+      return Preview::None;
+    }
+    let entry = match buffers.quickfix().entries().get(line_idx) {
+      Some(entry) => entry.clone(),
+      None => return Preview::None,
+    };
+    let file_buffer = match buffers
+      .find_by_path(&entry.path)
+      .or_else(|| buffers.find_preview(&entry.path))
+    {
+      Some(file_buffer) => file_buffer.clone(),
+      None => return Preview::Load(entry.path),
+    };
+    (entry, file_buffer)
+  };
+  let len_lines = rlock!(file_buffer).len_lines();
+
+  let mut tree = wlock!(tree);
+  let jump_center = tree.jump_center();
+  let window_id = match tree
+    .current_window_id()
+    .and_then(|id| other_window_id(&tree, id))
+  {
+    Some(window_id) => window_id,
+    None => return Preview::None,
+  };
+  match tree.node_mut(&window_id) {
+    Some(TreeNode::Window(window)) => {
+      if !window
+        .buffer()
+        .upgrade()
+        .is_some_and(|b| Arc::ptr_eq(&b, &file_buffer))
+      {
+        window.set_buffer(Arc::downgrade(&file_buffer));
+        window.sync_gutters();
+      }
+      move_viewport_to(
+        &mut wlock!(window.viewport()),
+        jump_center,
+        len_lines,
+        (
+          std::cmp::min(entry.line_idx, len_lines.saturating_sub(1)),
+          entry.char_idx,
+        ),
+      );
+      Preview::Shown
+    }
+    _ => Preview::None,
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The quickfix window preview state, i.e. the 'quickfixpreview' option.
+pub struct QuickfixPreview {
+  // The quickfix window and the cursor line of last preview.
+  last_cursor: Option<(TreeNodeId, usize)>,
+  // The file to load for the preview.
+  load: Option<PathBuf>,
+}
+
+impl QuickfixPreview {
+  /// Preview the entry under cursor (see [`preview_entry`]) if the 'quickfixpreview' option is on
+  /// and the cursor is moved to another line, it's called after each event is handled.
+  pub fn on_cursor(&mut self, tree: &TreeArc, buffers: &BuffersManagerArc) {
+    let cursor = {
+      let tree = rlock!(tree);
+      if !tree.quickfix_preview() {
+        self.last_cursor = None;
+        return;
+      }
+      tree
+        .current_window_id()
+        .and_then(|id| match tree.node(&id) {
+          Some(TreeNode::Window(window)) => {
+            Some((id, rlock!(window.viewport()).cursor().line_idx()))
+          }
+          _ => None,
+        })
+    };
+    if cursor == self.last_cursor {
+      return;
+    }
+    self.last_cursor = cursor;
+    if let Preview::Load(path) = preview_entry(tree, buffers) {
+      self.load = Some(path);
+    }
+  }
+
+  /// Take the file to load for the preview, it's loaded by the event loop on a background task,
+  /// then previewed again. The previous loading is cancelled.
+  pub fn take_load(&mut self) -> Option<PathBuf> {
+    self.load.take()
+  }
+}
+
+// Edit the file of the entry, and move the cursor to its position. If current window is the
+// quickfix window, the other window is used.
+fn jump_to_entry(
+  data_access: &mut ExCommandDataAccess,
+  idx: usize,
+  len: usize,
+  entry: QuickfixEntry,
+) -> ExCommandResult<()> {
+  let quickfix_buffer = rlock!(data_access.buffers).quickfix_buffer().cloned();
+  if let (Some(quickfix_buffer), Some((buffer, _))) =
+    (quickfix_buffer, data_access.current_window_buffer())
+  {
+    if Arc::ptr_eq(&quickfix_buffer, &buffer) {
+      let mut tree = wlock!(data_access.tree);
+      if let Some(window_id) = tree
+        .current_window_id()
+        .and_then(|id| other_window_id(&tree, id))
+      {
+        tree.set_current_window_id(window_id);
+        tree.sync_cursor_position();
+      }
+    }
+  }
+  data_access.edit_file(&entry.path)?;
+  jump_to_line(&data_access.tree, entry.line_idx);
+  if let Some((_, viewport)) = data_access.current_window_buffer() {
+    move_cursor_to(
+      &data_access.tree,
+      &viewport,
+      (entry.line_idx, entry.char_idx),
+    );
+  }
+  data_access.echo(
+    MessageLevel::Info,
+    &format!("({} of {}): {}", idx + 1, len, entry.text.trim()),
+  );
+  Ok(())
+}
+
+// Select the entry with `select`, and jump to it.
+fn select_and_jump<F>(data_access: &mut ExCommandDataAccess, select: F) -> ExCommandResult<()>
+where
+  F: FnOnce(&mut QuickfixList) -> Option<QuickfixEntry>,
+{
+  let (idx, len, entry) = {
+    let mut buffers = wlock!(data_access.buffers);
+    let list = buffers.quickfix_mut();
+    if list.is_empty() {
+      return Err(ExCommandErr::Message("E42: No Errors".to_string()));
+    }
+    match select(list) {
+      Some(entry) => (list.current().unwrap_or(0), list.len(), entry),
+      None => return Err(ExCommandErr::Message("E553: No more items".to_string())),
+    }
+  };
+  jump_to_entry(data_access, idx, len, entry)
+}
+
+fn handle_grep(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let args = command_line.args().trim();
+  if args.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+  let options = GrepOptions::parse(args).map_err(ExCommandErr::Message)?;
+  data_access.grep(GrepRequest {
+    options,
+    jump: !command_line.bang(),
+  });
+  Ok(())
+}
+
+fn handle_clist(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let text = {
+    let buffers = rlock!(data_access.buffers);
+    let list = buffers.quickfix();
+    if list.is_empty() {
+      return Err(ExCommandErr::Message("E42: No Errors".to_string()));
+    }
+    format!("{}\n{}", list.title(), format_quickfix_list(list))
+  };
+  data_access.echo(MessageLevel::Info, &text);
+  Ok(())
+}
+
+fn handle_cnext(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let count = count(command_line);
+  select_and_jump(data_access, |list| list.next(count).cloned())
+}
+
+fn handle_cprevious(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let count = count(command_line);
+  select_and_jump(data_access, |list| list.prev(count).cloned())
+}
+
+fn handle_cfirst(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  select_and_jump(data_access, |list| list.set_current(0).cloned())
+}
+
+fn handle_clast(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  select_and_jump(data_access, |list| {
+    let last = list.len().saturating_sub(1);
+    list.set_current(last).cloned()
+  })
+}
+
+fn handle_cc(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let args = command_line.args().trim();
+  let nr = if !args.is_empty() {
+    match args.parse::<usize>() {
+      Ok(nr) if nr > 0 => Some(nr),
+      _ => {
+        return Err(ExCommandErr::Message(format!(
+          "E475: Invalid argument: {}",
+          args
+        )))
+      }
+    }
+  } else {
+    match command_line.range() {
+      Some(ExLineRange::Lines(_, ExLineAddress::Number(n))) if *n > 0 => Some(*n),
+      _ => None,
+    }
+  };
+  select_and_jump(data_access, |list| {
+    let idx = match nr {
+      Some(nr) => nr - 1,
+      None => list.current()?,
+    };
+    list.set_current(idx).cloned()
+  })
+}
+
+fn handle_copen(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let args = command_line.args().trim();
+  let height = if args.is_empty() {
+    defaults::win::QUICKFIX_HEIGHT
+  } else {
+    match args.parse::<u16>() {
+      Ok(height) if height > 0 => height,
+      _ => {
+        return Err(ExCommandErr::Message(format!(
+          "E475: Invalid argument: {}",
+          args
+        )))
+      }
+    }
+  };
+
+  let buffer = wlock!(data_access.buffers).open_quickfix_buffer();
+  refresh_quickfix_window(&data_access.tree, &data_access.buffers);
+  let window_id = {
+    let mut tree = wlock!(data_access.tree);
+    if let Some(window_id) = quickfix_window_ids(&tree, &buffer).first() {
+      tree.set_current_window_id(*window_id);
+      tree.sync_cursor_position();
+      return Ok(());
+    }
+    let window_id = match tree.current_window_id() {
+      Some(window_id) => window_id,
+      None => return Ok(()),
+    };
+    // The new window (above) keeps showing current buffer, and current window shows the quickfix
+    // list, thus the quickfix window is below.
+    if tree.split_window(LayoutDirection::Column).is_none() {
+      return Err(ExCommandErr::Message("E36: Not enough room".to_string()));
+    }
+    if let Some(TreeNode::Window(window)) = tree.node_mut(&window_id) {
+      window.set_buffer(Arc::downgrade(&buffer));
+      window.sync_gutters();
+      window.set_winfixheight(true);
+    }
+    tree.resize_window(window_id, LayoutDirection::Column, ResizeAmount::To(height));
+    tree.set_current_window_id(window_id);
+    tree.sync_cursor_position();
+    window_id
+  };
+  trace!("Open quickfix window: {:?}", window_id);
+
+  // Move the cursor to current entry.
+  let current = rlock!(data_access.buffers).quickfix().current();
+  if let (Some(idx), Some((_, viewport))) = (current, data_access.current_window_buffer()) {
+    move_cursor_to(&data_access.tree, &viewport, (idx, 0));
+  }
+  Ok(())
+}
+
+fn handle_cclose(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let buffer = match rlock!(data_access.buffers).quickfix_buffer() {
+    Some(buffer) => buffer.clone(),
+    None => return Ok(()),
+  };
+  let mut tree = wlock!(data_access.tree);
+  for window_id in quickfix_window_ids(&tree, &buffer) {
+    if !tree.close_window(window_id) {
+      return Err(ExCommandErr::Message(
+        "E444: Cannot close last window".to_string(),
+      ));
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::buf::fs::MemoryFs;
+  use crate::buf::BuffersManager;
+  use crate::cart::{IRect, U16Size};
+  use crate::interrupt::Interrupt;
+  use crate::state::msg::Messages;
+  use crate::ui::tree::internal::Inodeable;
+  use crate::ui::widget::{Cursor, Window};
+
+  use std::path::{Path, PathBuf};
+
+  // Make a 20x20 editor with a window showing `/rsvim/a.rs` (40 lines), and the quickfix list of
+  // the lines 2 and 30 of `/rsvim/a.rs`, and the line 1 of `/rsvim/b.rs`.
+  fn make_editor() -> ExCommandDataAccess {
+    let fs = Arc::new(MemoryFs::new());
+    let text = (0..40).map(|i| format!("line {}\n", i)).collect::<String>();
+    fs.insert(Path::new("/rsvim/a.rs"), text.as_bytes());
+    fs.insert(Path::new("/rsvim/b.rs"), b"fn b() {}\nfn c() {}\n");
+    let mut buffers = BuffersManager::new();
+    buffers.set_fs(fs);
+    let buf_id = buffers.new_file_buffer(Path::new("/rsvim/a.rs")).unwrap();
+    let buffer = buffers.get(&buf_id).unwrap().clone();
+    buffers.quickfix_mut().reset(":grep line");
+    buffers.quickfix_mut().extend(vec![
+      QuickfixEntry::new(PathBuf::from("/rsvim/a.rs"), 2, 5, "line 2".to_string()),
+      QuickfixEntry::new(PathBuf::from("/rsvim/a.rs"), 30, 0, "line 30".to_string()),
+      QuickfixEntry::new(PathBuf::from("/rsvim/b.rs"), 1, 3, "fn c() {}".to_string()),
+    ]);
+
+    let mut tree = Tree::new(U16Size::new(20, 20));
+    let root_id = tree.root_id();
+    let shape = IRect::new((0, 0), (20, 20));
+    let window = Window::new(shape, Arc::downgrade(&buffer), tree.local_options());
+    let window_id = window.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(window));
+    tree.bounded_insert(
+      &window_id,
+      TreeNode::Cursor(Cursor::new(IRect::new((0, 0), (1, 1)))),
+    );
+    tree.layout_tiled_windows();
+
+    ExCommandDataAccess::new(
+      Tree::to_arc(tree),
+      BuffersManager::to_arc(buffers),
+      Interrupt::to_arc(Interrupt::new()),
+      Messages::to_arc(Messages::new()),
+    )
+  }
+
+  // Get the buffer's file name and the cursor of the window.
+  fn window_cursor(tree: &TreeArc, window_id: TreeNodeId) -> (String, usize, usize) {
+    let tree = rlock!(tree);
+    match tree.node(&window_id) {
+      Some(TreeNode::Window(window)) => {
+        let buffer = window.buffer().upgrade().unwrap();
+        let name = rlock!(buffer)
+          .absolute_filename()
+          .as_ref()
+          .map(|f| f.to_string_lossy().to_string())
+          .unwrap_or_default();
+        let viewport = window.viewport();
+        let viewport = rlock!(viewport);
+        (
+          name,
+          viewport.cursor().line_idx(),
+          viewport.cursor().char_idx(),
+        )
+      }
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn copen1() {
+    let mut data_access = make_editor();
+    let commands = ExCommandsManager::new();
+    let file_window_id = rlock!(data_access.tree).current_window_id().unwrap();
+    commands.run("copen 5", &mut data_access).unwrap();
+
+    // The quickfix window is below, and becomes current window.
+    let (window_ids, current_id) = {
+      let tree = rlock!(data_access.tree);
+      (tree.tiled_window_ids(), tree.current_window_id().unwrap())
+    };
+    assert_eq!(window_ids.len(), 2);
+    assert_eq!(window_ids[1], file_window_id);
+    assert_eq!(current_id, file_window_id);
+    {
+      let tree = rlock!(data_access.tree);
+      let height = |id: &TreeNodeId| tree.node(id).unwrap().actual_shape().height();
+      assert!(height(&current_id) < height(&window_ids[0]));
+    }
+    let buffer = rlock!(data_access.buffers)
+      .quickfix_buffer()
+      .unwrap()
+      .clone();
+    assert_eq!(
+      rlock!(buffer).get_line_content(2),
+      Some("/rsvim/b.rs:2:4: fn c() {}".to_string())
+    );
+    assert_eq!(
+      quickfix_window_ids(&rlock!(data_access.tree), &buffer),
+      vec![file_window_id]
+    );
+    assert_eq!(
+      other_window_id(&rlock!(data_access.tree), file_window_id),
+      Some(window_ids[0])
+    );
+
+    // Open again, the cursor moves to the quickfix window.
+    wlock!(data_access.tree).set_current_window_id(window_ids[0]);
+    commands.run("copen", &mut data_access).unwrap();
+    assert_eq!(rlock!(data_access.tree).tiled_window_ids().len(), 2);
+    assert_eq!(
+      rlock!(data_access.tree).current_window_id(),
+      Some(file_window_id)
+    );
+
+    commands.run("cclose", &mut data_access).unwrap();
+    assert_eq!(
+      rlock!(data_access.tree).tiled_window_ids(),
+      vec![window_ids[0]]
+    );
+    assert!(commands.run("copen 0", &mut data_access).is_err());
+  }
+
+  #[test]
+  fn preview_entry1() {
+    let mut data_access = make_editor();
+    let commands = ExCommandsManager::new();
+    commands.run("copen", &mut data_access).unwrap();
+    let (other_id, quickfix_id) = {
+      let tree = rlock!(data_access.tree);
+      let window_ids = tree.tiled_window_ids();
+      (window_ids[0], window_ids[1])
+    };
+    let tree = data_access.tree.clone();
+    let buffers = data_access.buffers.clone();
+
+    // The option is off.
+    let mut preview = QuickfixPreview::default();
+    preview.on_cursor(&tree, &buffers);
+    assert_eq!(
+      window_cursor(&tree, other_id),
+      ("/rsvim/a.rs".to_string(), 0, 0)
+    );
+
+    wlock!(tree).set_quickfix_preview(true);
+    preview.on_cursor(&tree, &buffers);
+    assert_eq!(
+      window_cursor(&tree, other_id),
+      ("/rsvim/a.rs".to_string(), 2, 5)
+    );
+
+    // The other window scrolls to the entry, the cursor stays in the quickfix window.
+    let viewport = data_access.current_window_buffer().unwrap().1;
+    move_cursor_to(&tree, &viewport, (1, 0));
+    preview.on_cursor(&tree, &buffers);
+    assert_eq!(
+      window_cursor(&tree, other_id),
+      ("/rsvim/a.rs".to_string(), 30, 0)
+    );
+    if let Some(TreeNode::Window(window)) = rlock!(tree).node(&other_id) {
+      assert!(rlock!(window.viewport()).start_line_idx() > 0);
+    }
+    assert_eq!(rlock!(tree).current_window_id(), Some(quickfix_id));
+
+    // The file is not opened, it's loaded into the preview buffer.
+    move_cursor_to(&tree, &viewport, (2, 0));
+    preview.on_cursor(&tree, &buffers);
+    assert_eq!(
+      window_cursor(&tree, other_id),
+      ("/rsvim/a.rs".to_string(), 30, 0)
+    );
+    assert_eq!(preview.take_load(), Some(PathBuf::from("/rsvim/b.rs")));
+    assert_eq!(preview.take_load(), None);
+    let preview_buffer =
+      wlock!(buffers).set_preview_buffer(Path::new("/rsvim/b.rs"), "fn b() {}\nfn c() {}\n", None);
+    assert_eq!(preview_entry(&tree, &buffers), Preview::Shown);
+    assert_eq!(window_cursor(&tree, other_id), ("".to_string(), 1, 3));
+    {
+      let buffers = rlock!(buffers);
+      assert!(buffers.find_by_path(Path::new("/rsvim/b.rs")).is_none());
+      assert!(!rlock!(preview_buffer).is_listed());
+      assert!(rlock!(preview_buffer).is_read_only());
+      assert!(buffers
+        .list(false)
+        .iter()
+        .all(|info| info.id != rlock!(preview_buffer).id()));
+    }
+
+    // Jump from the quickfix window goes to the other window.
+    commands.run("cc 1", &mut data_access).unwrap();
+    assert_eq!(rlock!(tree).current_window_id(), Some(other_id));
+    assert_eq!(
+      window_cursor(&tree, other_id),
+      ("/rsvim/a.rs".to_string(), 2, 5)
+    );
+    // Not in the quickfix window.
+    assert_eq!(preview_entry(&tree, &buffers), Preview::None);
+  }
+
+  #[test]
+  fn format_quickfix_list1() {
+    let mut list = QuickfixList::default();
+    list.reset(":grep foo");
+    list.extend(vec![
+      QuickfixEntry::new(PathBuf::from("a.rs"), 0, 3, "let foo = 1;".to_string()),
+      QuickfixEntry::new(PathBuf::from("b.rs"), 9, 0, "  foo();".to_string()),
+    ]);
+    list.next(1);
+    assert_eq!(
+      format_quickfix_list(&list),
+      "   1 a.rs:1:4: let foo = 1;\n>  2 b.rs:10:1: foo();"
+    );
+  }
+}
@@ -0,0 +1,224 @@
+//! The quit ex commands.
+//!
+//! - `:q[uit][!]` closes current window, or quits the editor if it's the last window.
+//! - `:qa[ll][!]` quits the editor.
+//! - `:wq[!] [++p] [file]` writes current buffer (same with `:write`, see
+//!   [`write`](crate::state::ex::write)), then same with `:quit`.
+//!
+//! The editor is not quit if any listed buffer is modified, unless with `!` (the changes are
+//! discarded). The editor is quit by the event loop after the command, the background tasks are
+//! shut down within a grace period, see [`TaskRegistry`](crate::evloop::task::TaskRegistry).
+
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::write::write_buffer;
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::{rlock, wlock};
+
+use tracing::trace;
+
+/// The quit definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new(
+      "quit",
+      "q",
+      "Close current window, or quit if it's the last window",
+      handle_quit,
+    ),
+    ExCommandDefinition::new("qall", "qa", "Quit the editor", handle_qall),
+    ExCommandDefinition::new(
+      "wq",
+      "wq",
+      "Write current buffer to file, then close current window or quit",
+      handle_wq,
+    )
+    .with_file_completion(),
+  ]
+}
+
+// Check the modified buffers before quitting, current buffer is checked first.
+fn check_modified(data_access: &ExCommandDataAccess) -> ExCommandResult<()> {
+  let current_id = data_access
+    .current_window_buffer()
+    .map(|(buffer, _)| rlock!(buffer).id());
+  let modified: Vec<_> = rlock!(data_access.buffers)
+    .list(false)
+    .into_iter()
+    .filter(|info| info.modified)
+    .collect();
+  if modified.iter().any(|info| Some(info.id) == current_id) {
+    return Err(ExCommandErr::Message(
+      "E37: No write since last change (add ! to override)".to_string(),
+    ));
+  }
+  match modified.first() {
+    Some(info) => Err(ExCommandErr::Message(format!(
+      "E162: No write since last change for buffer {:?}",
+      info.name.as_deref().unwrap_or("[No Name]")
+    ))),
+    None => Ok(()),
+  }
+}
+
+// Quit the editor, the modified buffers are checked unless `force`.
+fn quit_all(data_access: &mut ExCommandDataAccess, force: bool) -> ExCommandResult<()> {
+  if !force {
+    check_modified(data_access)?;
+  }
+  trace!("Quit editor, force:{:?}", force);
+  data_access.quit();
+  Ok(())
+}
+
+// Close current window, or quit the editor if it's the last window.
+fn quit(data_access: &mut ExCommandDataAccess, force: bool) -> ExCommandResult<()> {
+  let closed = {
+    let mut tree = wlock!(data_access.tree);
+    match tree.current_window_id() {
+      Some(window_id) => tree.close_window(window_id),
+      None => false,
+    }
+  };
+  if closed {
+    return Ok(());
+  }
+  quit_all(data_access, force)
+}
+
+fn handle_quit(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if command_line.range().is_some() {
+    return Err(ExCommandErr::InvalidRange);
+  }
+  quit(data_access, command_line.bang())
+}
+
+fn handle_qall(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  if command_line.range().is_some() {
+    return Err(ExCommandErr::InvalidRange);
+  }
+  quit_all(data_access, command_line.bang())
+}
+
+fn handle_wq(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  write_buffer(command_line, data_access, false)?;
+  quit(data_access, command_line.bang())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::buf::fs::MemoryFs;
+  use crate::buf::{BufferArc, BuffersManager};
+  use crate::cart::{IRect, U16Size};
+  use crate::interrupt::Interrupt;
+  use crate::state::msg::Messages;
+  use crate::ui::tree::internal::Inodeable;
+  use crate::ui::tree::layout::LayoutDirection;
+  use crate::ui::tree::{Tree, TreeNode};
+  use crate::ui::widget::{Cursor, Window};
+
+  use std::path::Path;
+  use std::sync::Arc;
+
+  // Make a 20x20 editor with a window showing the file `/rsvim/a.txt`, and the unnamed buffer is
+  // not shown.
+  fn make_editor() -> (ExCommandDataAccess, BufferArc, BufferArc) {
+    let mut buffers = BuffersManager::new();
+    buffers.set_fs(Arc::new(MemoryFs::new()));
+    let buf_id = buffers.new_file_buffer(Path::new("/rsvim/a.txt")).unwrap();
+    let buffer = buffers.get(&buf_id).unwrap().clone();
+    let other_id = buffers.new_empty_buffer();
+    let other = buffers.get(&other_id).unwrap().clone();
+
+    let mut tree = Tree::new(U16Size::new(20, 20));
+    let root_id = tree.root_id();
+    let shape = IRect::new((0, 0), (20, 20));
+    let window = Window::new(shape, Arc::downgrade(&buffer), tree.local_options());
+    let window_id = window.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(window));
+    tree.bounded_insert(
+      &window_id,
+      TreeNode::Cursor(Cursor::new(IRect::new((0, 0), (1, 1)))),
+    );
+    tree.layout_tiled_windows();
+
+    let data_access = ExCommandDataAccess::new(
+      Tree::to_arc(tree),
+      BuffersManager::to_arc(buffers),
+      Interrupt::to_arc(Interrupt::new()),
+      Messages::to_arc(Messages::new()),
+    );
+    (data_access, buffer, other)
+  }
+
+  fn error_of(result: ExCommandResult<()>) -> String {
+    result.unwrap_err().to_string()
+  }
+
+  #[test]
+  fn quit1() {
+    let (mut data_access, _buffer, _other) = make_editor();
+    let commands = ExCommandsManager::new();
+    wlock!(data_access.tree).split_window(LayoutDirection::Column);
+
+    // Close the split window.
+    commands.run("q", &mut data_access).unwrap();
+    assert_eq!(rlock!(data_access.tree).window_ids().len(), 1);
+    assert!(!data_access.take_quit());
+
+    // Quit on the last window.
+    commands.run("quit", &mut data_access).unwrap();
+    assert!(data_access.take_quit());
+  }
+
+  #[test]
+  fn quit_modified1() {
+    let (mut data_access, buffer, other) = make_editor();
+    let commands = ExCommandsManager::new();
+    wlock!(other).insert(0, "hello");
+    assert!(error_of(commands.run("qa", &mut data_access)).contains("E162"));
+    wlock!(buffer).insert(0, "hello");
+    assert!(error_of(commands.run("q", &mut data_access)).contains("E37"));
+    assert!(error_of(commands.run("qall", &mut data_access)).contains("E37"));
+    assert!(!data_access.take_quit());
+
+    commands.run("qa!", &mut data_access).unwrap();
+    assert!(data_access.take_quit());
+  }
+
+  #[test]
+  fn wq1() {
+    let (mut data_access, buffer, other) = make_editor();
+    let commands = ExCommandsManager::new();
+    wlock!(buffer).insert(0, "hello\n");
+    wlock!(other).insert(0, "world");
+    // The buffer is written, but the other buffer is still modified.
+    assert!(error_of(commands.run("wq", &mut data_access)).contains("E162"));
+    assert!(!rlock!(buffer).is_modified());
+    let fs = rlock!(data_access.buffers).fs().clone();
+    assert_eq!(
+      fs.read(Path::new("/rsvim/a.txt")).unwrap().0,
+      b"hello\n".to_vec()
+    );
+    assert!(!data_access.take_quit());
+
+    commands.run("wq!", &mut data_access).unwrap();
+    assert!(data_access.take_quit());
+  }
+}
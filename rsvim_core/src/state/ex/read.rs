@@ -0,0 +1,252 @@
+//! The `:read` ex command.
+//!
+//! - `:[line]r[ead] {file}` inserts the contents of `{file}` below `[line]` (default is the cursor
+//!   line).
+//! - `:[line]r[ead] !{cmd}` inserts the standard output of the shell command `{cmd}` below
+//!   `[line]`.
+//!
+//! The `:0r` inserts above the first line. The file encoding and line endings of the contents are
+//! detected same with loading a file (see [`BufferLoader`]), and they're inserted in one edit thus
+//! it's a single undo step.
+//!
+//! The file is read through the buffers' file system provider, see [`fs`](crate::buf::fs). The
+//! shell command is run by the event loop on an async task (it's killed when the editor quits),
+//! the output is inserted once the command is finished. If the command fails, i.e. the exit status
+//! is not zero, nothing is inserted and the stderr is reported.
+
+use crate::buf::{Buffer, BufferId, BufferLoader, FileEncoding};
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager, ExLineAddress,
+  ExLineRange,
+};
+use crate::{rlock, wlock};
+
+use std::path::Path;
+use std::process::Stdio;
+
+/// The `:read` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new(
+    "read",
+    "r",
+    "Insert a file or a command's output below the cursor line",
+    handle,
+  )
+  .with_file_completion()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The `:r !{cmd}` request, the command is run by the event loop, see [`run_shell`].
+pub struct ShellReadRequest {
+  pub command: String,
+  pub buffer_id: BufferId,
+  /// Insert below this line, or above the first line if it's `None`.
+  pub line_idx: Option<usize>,
+}
+
+/// Run the shell command and returns its standard output. Returns the error message with the exit
+/// status and the standard error if the command fails.
+///
+/// NOTE: The command is killed if the future is dropped, i.e. the task is cancelled.
+pub async fn run_shell(cmd: &str) -> Result<Vec<u8>, String> {
+  let mut command = if cfg!(target_os = "windows") {
+    let mut command = tokio::process::Command::new("cmd");
+    command.arg("/C");
+    command
+  } else {
+    let mut command = tokio::process::Command::new("sh");
+    command.arg("-c");
+    command
+  };
+  let output = command
+    .arg(cmd)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true)
+    .output()
+    .await
+    .map_err(|e| format!("E282: Cannot execute shell command {:?}: {}", cmd, e))?;
+  if output.status.success() {
+    Ok(output.stdout)
+  } else {
+    Err(format!(
+      "Shell command {:?} failed with {}: {}",
+      cmd,
+      output.status,
+      String::from_utf8_lossy(&output.stderr).trim()
+    ))
+  }
+}
+
+/// Decode the file contents or the command output, the encoding and line endings are detected
+/// same with loading a file, see [`BufferLoader`].
+pub fn decode(bytes: &[u8]) -> String {
+  let mut loader = BufferLoader::new(FileEncoding::Utf8, bytes.len() as u64);
+  let mut text = loader.push(bytes);
+  text.push_str(&loader.finish());
+  text
+}
+
+/// Insert the `text` below the line `line_idx`, or above the first line if it's `None`.
+///
+/// Returns the inserted lines count.
+pub fn read_lines(buffer: &mut Buffer, line_idx: Option<usize>, text: &str) -> usize {
+  // Normalize the line breaks.
+  let text = text.replace("\r\n", "\n");
+  if text.is_empty() {
+    return 0;
+  }
+  let lines_count = text.lines().count();
+
+  let insert_line_idx = line_idx.map(|l| l + 1).unwrap_or(0);
+  let text = if text.ends_with('\n') {
+    text
+  } else {
+    format!("{}\n", text)
+  };
+  if insert_line_idx == 0 {
+    buffer.insert(0, &text);
+    return lines_count;
+  }
+
+  // The last line is empty if the buffer ends with a line break.
+  let ends_with_line_break = buffer
+    .get_line(buffer.len_lines() - 1)
+    .map(|line| line.len_chars() == 0)
+    .unwrap_or(true);
+  let content_lines = if ends_with_line_break {
+    buffer.len_lines() - 1
+  } else {
+    buffer.len_lines()
+  };
+  if insert_line_idx < content_lines {
+    let char_idx = buffer.line_to_char(insert_line_idx);
+    buffer.insert(char_idx, &text);
+  } else if ends_with_line_break {
+    let char_idx = buffer.len_chars();
+    buffer.insert(char_idx, &text);
+  } else {
+    // The buffer doesn't end with a line break, thus the text is appended after a new line break,
+    // and its trailing line break is removed.
+    let char_idx = buffer.len_chars();
+    buffer.insert(char_idx, &format!("\n{}", text.trim_end_matches('\n')));
+  }
+  lines_count
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let args = command_line.args();
+  let (is_shell, target) = match args.strip_prefix('!') {
+    Some(cmd) => (true, cmd.trim()),
+    None => (command_line.bang(), args),
+  };
+  if target.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+
+  let current_line_idx = rlock!(viewport).cursor().line_idx();
+  let (buffer_id, line_idx) = {
+    let buffer = rlock!(buffer);
+    let line_idx = match command_line.range() {
+      Some(ExLineRange::Lines(_, ExLineAddress::Number(0))) => None,
+      _ => {
+        let current = ExLineRange::Lines(ExLineAddress::Current, ExLineAddress::Current);
+        let range = command_line.resolve_range(
+          current,
+          current_line_idx,
+          buffer.len_lines(),
+          buffer.marks(),
+        )?;
+        Some(range.end - 1)
+      }
+    };
+    (buffer.id(), line_idx)
+  };
+
+  if is_shell {
+    data_access.read_shell(ShellReadRequest {
+      command: target.to_string(),
+      buffer_id,
+      line_idx,
+    });
+    return Ok(());
+  }
+
+  let fs = rlock!(data_access.buffers).fs().clone();
+  let (bytes, _) = fs
+    .read(Path::new(target))
+    .map_err(|e| ExCommandErr::Message(format!("E484: Can't open file {}: {}", target, e)))?;
+  read_lines(&mut wlock!(buffer), line_idx, &decode(&bytes));
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::test::buf::make_buffer_from_lines;
+
+  fn contents(buffer: &Buffer) -> String {
+    buffer.lines().map(|line| line.to_string()).collect()
+  }
+
+  #[test]
+  fn read_lines1() {
+    let buffer = make_buffer_from_lines(vec!["a\n", "b\n"]);
+    let mut buffer = wlock!(buffer);
+    assert_eq!(read_lines(&mut buffer, Some(0), "x\r\ny"), 2);
+    assert_eq!(contents(&buffer), "a\nx\ny\nb\n");
+    assert_eq!(read_lines(&mut buffer, None, "top\n"), 1);
+    assert_eq!(contents(&buffer), "top\na\nx\ny\nb\n");
+    assert_eq!(read_lines(&mut buffer, Some(4), "end\n"), 1);
+    assert_eq!(contents(&buffer), "top\na\nx\ny\nb\nend\n");
+    assert_eq!(read_lines(&mut buffer, Some(0), ""), 0);
+  }
+
+  #[test]
+  fn decode1() {
+    assert_eq!(decode(b"a\r\nb\r\n"), "a\nb\n");
+    assert_eq!(decode(b"caf\xe9\n"), "café\n");
+    assert_eq!(decode(b"\xFF\xFEa\x00\n\x00"), "a\n");
+    assert_eq!(decode(b""), "");
+  }
+
+  #[tokio::test]
+  async fn run_shell1() {
+    if cfg!(target_os = "windows") {
+      return;
+    }
+    assert_eq!(run_shell("echo hello").await.unwrap(), b"hello\n".to_vec());
+    let e = run_shell("echo oops >&2; exit 3").await.unwrap_err();
+    assert!(e.contains("3"));
+    assert!(e.ends_with("oops"));
+  }
+
+  #[test]
+  fn read_lines2() {
+    // No trailing line break.
+    let buffer = make_buffer_from_lines(vec!["a\n", "b"]);
+    let mut buffer = wlock!(buffer);
+    read_lines(&mut buffer, Some(1), "x\n");
+    assert_eq!(contents(&buffer), "a\nb\nx");
+
+    let buffer = make_buffer_from_lines(vec![]);
+    let mut buffer = wlock!(buffer);
+    read_lines(&mut buffer, Some(0), "x");
+    assert_eq!(contents(&buffer), "x\n");
+  }
+}
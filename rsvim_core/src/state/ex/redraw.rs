@@ -0,0 +1,26 @@
+//! The `:redraw` ex command.
+//!
+//! - `:redr[aw]` redraws the screen.
+//! - `:redr[aw]!` clears the terminal and fully repaints the screen, i.e. after external programs
+//!   scribble on the terminal.
+
+use crate::envar;
+use crate::res::ExCommandResult;
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::wlock;
+
+/// The `:redraw` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new("redraw", "redr", "Redraw the screen", handle)
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  wlock!(data_access.tree).request_redraw(command_line.bang());
+  Ok(())
+}
@@ -0,0 +1,107 @@
+//! The remote file ex commands, see [`remote`](crate::buf::remote).
+//!
+//! - `:Nw[rite]` writes current buffer back to its `scp://`/`sftp://` file. The buffer is written
+//!   to a temporary file, and then uploaded with `scp` on a background thread, the buffer is marked
+//!   as saved (at the state when the write started) only if the upload is succeeded.
+
+use crate::buf::BufferArc;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::{rlock, wlock};
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::{error, trace};
+
+/// The `:Nwrite` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new(
+    "Nwrite",
+    "Nw",
+    "Write current buffer back to its remote file",
+    handle,
+  )
+}
+
+// Upload the temporary file, and mark the buffer as saved at `seq` if succeeded.
+fn upload(
+  buffer: BufferArc,
+  seq: usize,
+  program: String,
+  args: Vec<String>,
+  local: PathBuf,
+) -> Result<(), String> {
+  let output = Command::new(&program)
+    .args(&args)
+    .stdin(Stdio::null())
+    .output();
+  let _ = std::fs::remove_file(&local);
+  let output = output.map_err(|e| format!("{}: {}", program, e))?;
+  if !output.status.success() {
+    return Err(format!(
+      "{} {}: {}",
+      program,
+      output.status,
+      String::from_utf8_lossy(&output.stderr).trim()
+    ));
+  }
+  wlock!(buffer).set_saved_seq(seq);
+  Ok(())
+}
+
+fn handle(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let (buffer, _viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+
+  let (url, seq, local) = {
+    let buffer = rlock!(buffer);
+    let url = match buffer.remote() {
+      Some(url) => url.clone(),
+      None => return Err(ExCommandErr::Message("E32: Not a remote file".to_string())),
+    };
+    if buffer.is_loading() {
+      return Err(ExCommandErr::Message(format!(
+        "E505: {} is still loading",
+        url.url()
+      )));
+    }
+    if !url.is_writable() {
+      return Err(ExCommandErr::Message(format!(
+        "E505: {} is read-only",
+        url.url()
+      )));
+    }
+    let local = std::env::temp_dir().join(format!(
+      "rsvim-nwrite-{}-{}",
+      std::process::id(),
+      buffer.id()
+    ));
+    let file = std::fs::File::create(&local)
+      .map_err(|e| ExCommandErr::Message(format!("E212: Can't open file {:?}: {}", local, e)))?;
+    let mut writer = std::io::BufWriter::new(file);
+    buffer
+      .write_to(&mut writer)
+      .and_then(|_| writer.flush())
+      .map_err(|e| ExCommandErr::Message(format!("E514: Write error {:?}: {}", local, e)))?;
+    (url, buffer.undo_tree().current(), local)
+  };
+
+  let (program, args) = url.upload_command(Path::new(&local)).unwrap();
+  trace!("Nwrite {:?}: {:?} {:?}", url.url(), program, args);
+  std::thread::spawn(move || {
+    if let Err(e) = upload(buffer, seq, program, args, local) {
+      error!("Failed to write {:?}: {}", url.url(), e);
+    }
+  });
+  Ok(())
+}
@@ -0,0 +1,175 @@
+//! The rename ex commands.
+//!
+//! - `:Ren[ame][!] {file}` renames current buffer's file, `{file}` is relative to the directory of
+//!   the current file.
+//! - `:Mov[e][!] {file}` moves current buffer's file, `{file}` is relative to current working
+//!   directory. If it's an existing directory, the file is moved into it with the same file name.
+//!
+//! The file is moved on disk (the parent directories are created as needed), and the buffer's
+//! file name is updated, the undo history is kept, see
+//! [`BuffersManager::rename_buffer`](crate::buf::BuffersManager::rename_buffer). The existing file
+//! is only overwritten with `!`. The `BufFilePre` and `BufFilePost` events are emitted before and
+//! after renaming.
+
+use crate::buf::expand::expand_path;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::autocmd::{AutocmdArgs, AutocmdEvent};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::{rlock, wlock};
+
+use path_absolutize::Absolutize;
+use std::path::{Path, PathBuf};
+use tracing::trace;
+
+/// The rename definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new(
+      "Rename",
+      "Ren",
+      "Rename the file of current buffer",
+      handle_rename,
+    )
+    .with_file_completion(),
+    ExCommandDefinition::new(
+      "Move",
+      "Mov",
+      "Move the file of current buffer",
+      handle_move,
+    )
+    .with_file_completion(),
+  ]
+}
+
+/// Resolve the target file of `:Rename` (relative to the directory of `current`) and `:Move`
+/// (relative to current working directory, and moves into the `target` directory).
+pub fn resolve_target(current: &Path, target: &Path, is_move: bool) -> PathBuf {
+  let target = if is_move || target.is_absolute() {
+    target.to_path_buf()
+  } else {
+    match current.parent() {
+      Some(parent) => parent.join(target),
+      None => target.to_path_buf(),
+    }
+  };
+  if is_move && target.is_dir() {
+    if let Some(name) = current.file_name() {
+      return target.join(name);
+    }
+  }
+  target
+}
+
+fn handle_rename(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  rename_buffer(command_line, data_access, false)
+}
+
+fn handle_move(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  rename_buffer(command_line, data_access, true)
+}
+
+fn rename_buffer(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  is_move: bool,
+) -> ExCommandResult<()> {
+  let filename = command_line.args().trim();
+  if filename.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+  let files = expand_path(filename);
+  if files.len() > 1 {
+    return Err(ExCommandErr::Message(
+      "E77: Too many file names".to_string(),
+    ));
+  }
+  let (buffer, _) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let (buffer_id, current) = {
+    let buffer = rlock!(buffer);
+    match buffer.absolute_filename() {
+      Some(current) if buffer.remote().is_none() => (buffer.id(), current.clone()),
+      _ => return Err(ExCommandErr::Message("E32: No file name".to_string())),
+    }
+  };
+  let target = resolve_target(&current, &files[0], is_move);
+  let target = match target.absolutize() {
+    Ok(target) => target.to_path_buf(),
+    Err(e) => {
+      return Err(ExCommandErr::Message(format!(
+        "E212: Can't rename to {:?}: {}",
+        target, e
+      )))
+    }
+  };
+
+  let event_args = |event: AutocmdEvent, file: &Path| AutocmdArgs {
+    event,
+    matched: file.to_string_lossy().to_string(),
+    buffer_id: Some(buffer_id),
+    window_id: None,
+    scroll_delta: None,
+  };
+  data_access.emit(event_args(AutocmdEvent::BufFilePre, &current));
+  wlock!(data_access.buffers)
+    .rename_buffer(&buffer_id, &target, command_line.bang())
+    .map_err(|e| {
+      let hint = if e.kind() == std::io::ErrorKind::AlreadyExists {
+        " (add ! to override)"
+      } else {
+        ""
+      };
+      ExCommandErr::Message(format!(
+        "E212: Can't rename {:?} to {:?}: {}{}",
+        current, target, e, hint
+      ))
+    })?;
+  trace!(
+    "Rename buffer {:?} from {:?} to {:?}",
+    buffer_id,
+    current,
+    target
+  );
+  data_access.emit(event_args(AutocmdEvent::BufFilePost, &target));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn resolve_target1() {
+    let dir = tempfile::tempdir().unwrap();
+    let current = dir.path().join("a.txt");
+    assert_eq!(
+      resolve_target(&current, Path::new("b.txt"), false),
+      dir.path().join("b.txt")
+    );
+    assert_eq!(
+      resolve_target(&current, Path::new("/x/b.txt"), false),
+      PathBuf::from("/x/b.txt")
+    );
+    assert_eq!(
+      resolve_target(&current, Path::new("b.txt"), true),
+      PathBuf::from("b.txt")
+    );
+    assert_eq!(
+      resolve_target(&current, dir.path(), true),
+      dir.path().join("a.txt")
+    );
+  }
+}
@@ -0,0 +1,190 @@
+//! The session ex commands, see [`session`](crate::state::session).
+//!
+//! - `:mks[ession][!] [file]` writes the session to `[file]` (default is `Session.js`), the
+//!   existing file is overwritten only with `!`.
+//! - `:so[urce] {file}` executes the JS script `{file}`, i.e. restores a session. The `.lua` file
+//!   is executed by the Lua runtime, it requires the `lua` feature.
+//! - `:SessionS[ave] [name]` saves the named session, and makes it current session.
+//! - `:SessionL[oad] [name]` saves current session (if any), then restores the named session and
+//!   makes it current session.
+//!
+//! The `[name]` defaults to the name of current working directory, see
+//! [`directory_session_name`].
+//!
+//! The `[file]`/`{file}` is expanded (see [`expand`](crate::buf::expand)), and it must be a single
+//! file.
+
+use crate::buf::expand::expand_path;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::state::session::{
+  directory_session_name, is_valid_session_name, save_named_session, session_file, sessions_dir,
+  Session, DEFAULT_SESSION_FILE,
+};
+use crate::{rlock, wlock};
+
+use path_absolutize::Absolutize;
+use std::path::PathBuf;
+use tracing::trace;
+
+/// The session definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new(
+      "mksession",
+      "mks",
+      "Write the session to a file",
+      handle_mksession,
+    )
+    .with_file_completion(),
+    ExCommandDefinition::new("source", "so", "Execute a JS script", handle_source)
+      .with_file_completion(),
+    ExCommandDefinition::new(
+      "SessionSave",
+      "SessionS",
+      "Save the named session",
+      handle_session_save,
+    ),
+    ExCommandDefinition::new(
+      "SessionLoad",
+      "SessionL",
+      "Load the named session",
+      handle_session_load,
+    ),
+  ]
+}
+
+// Expand the file argument, it must be a single file.
+fn single_file(filename: &str) -> ExCommandResult<PathBuf> {
+  let mut files = expand_path(filename);
+  if files.len() > 1 {
+    return Err(ExCommandErr::Message(
+      "E77: Too many file names".to_string(),
+    ));
+  }
+  Ok(files.remove(0))
+}
+
+fn handle_mksession(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let filename = match command_line.args().trim() {
+    "" => PathBuf::from(DEFAULT_SESSION_FILE),
+    filename => single_file(filename)?,
+  };
+  if filename.exists() && !command_line.bang() {
+    return Err(ExCommandErr::Message(format!(
+      "E189: {:?} exists (add ! to override)",
+      filename
+    )));
+  }
+
+  let script = {
+    let tree = rlock!(data_access.tree);
+    let buffers = rlock!(data_access.buffers);
+    Session::new(&tree, &buffers).to_script()
+  };
+  std::fs::write(&filename, script)
+    .map_err(|e| ExCommandErr::Message(format!("E190: Cannot open {:?}: {}", filename, e)))?;
+  trace!("mksession: {:?}", filename);
+  Ok(())
+}
+
+fn handle_source(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let filename = command_line.args().trim();
+  if filename.is_empty() {
+    return Err(ExCommandErr::ArgumentRequired);
+  }
+  let filename = single_file(filename)?;
+  if !filename.is_file() {
+    return Err(ExCommandErr::Message(format!(
+      "E484: Can't open file {:?}",
+      filename
+    )));
+  }
+  let filename = filename
+    .absolutize()
+    .map_err(|e| ExCommandErr::Message(format!("E484: Can't open file {:?}: {}", filename, e)))?
+    .to_path_buf();
+  trace!("source: {:?}", filename);
+  data_access.source(filename);
+  Ok(())
+}
+
+// The session name argument, defaults to the name of current working directory.
+fn session_name(command_line: &ExCommandLine) -> ExCommandResult<String> {
+  let name = match command_line.args().trim() {
+    "" => {
+      let cwd = std::env::current_dir()
+        .map_err(|e| ExCommandErr::Message(format!("E499: Can't get current directory: {}", e)))?;
+      directory_session_name(&cwd)
+    }
+    name => name.to_string(),
+  };
+  if !is_valid_session_name(&name) {
+    return Err(ExCommandErr::Message(format!(
+      "E474: Invalid session name {:?}",
+      name
+    )));
+  }
+  Ok(name)
+}
+
+// Save the named session, and make it current session.
+fn save_session(data_access: &ExCommandDataAccess, name: &str) -> ExCommandResult<()> {
+  let dir = sessions_dir();
+  let file = {
+    let tree = rlock!(data_access.tree);
+    let buffers = rlock!(data_access.buffers);
+    save_named_session(&dir, name, &tree, &buffers)
+      .map_err(|e| ExCommandErr::Message(format!("E190: Cannot save session {:?}: {}", name, e)))?
+  };
+  trace!("session save: {:?}", file);
+  wlock!(data_access.buffers).set_session(Some(name.to_string()));
+  Ok(())
+}
+
+fn handle_session_save(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let name = session_name(command_line)?;
+  save_session(data_access, &name)
+}
+
+fn handle_session_load(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let name = session_name(command_line)?;
+  let file = session_file(&sessions_dir(), &name);
+  if !file.is_file() {
+    return Err(ExCommandErr::Message(format!(
+      "E484: Session {:?} doesn't exist",
+      name
+    )));
+  }
+
+  // Save current session before switching.
+  let current = rlock!(data_access.buffers).session().clone();
+  if let Some(current) = current {
+    if current != name {
+      save_session(data_access, &current)?;
+    }
+  }
+  trace!("session load: {:?}", file);
+  wlock!(data_access.buffers).set_session(Some(name));
+  data_access.source(file);
+  Ok(())
+}
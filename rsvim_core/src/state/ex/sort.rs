@@ -0,0 +1,269 @@
+//! The `:sort` ex command.
+//!
+//! `:[range]sor[t][!] [i][n][u]` sorts the lines in `[range]` (default is all lines), with `!` the
+//! order is reversed. The flags are:
+//!
+//! - `i`: Ignore case.
+//! - `n`: Sort on the first decimal number in the line, the lines without number are sorted
+//!   before the lines with number, in their original order.
+//! - `u`: Only keep the first of a sequence of identical lines (ignore case if with `i`).
+//!
+//! The sort is stable, and the sorted lines are replaced in one undo group.
+
+use crate::buf::Buffer;
+use crate::envar;
+use crate::interrupt::Interrupt;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  trim_eof_line, ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+  ExLineRange,
+};
+use crate::{rlock, wlock};
+
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// The `:sort` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new("sort", "sor", "Sort lines", handle)
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+/// The `:sort` flags.
+pub struct SortOptions {
+  /// Reverse the order, i.e. `!`.
+  pub reverse: bool,
+  /// Ignore case, i.e. `i`.
+  pub ignore_case: bool,
+  /// Sort on the first decimal number, i.e. `n`.
+  pub numeric: bool,
+  /// Remove the duplicated lines, i.e. `u`.
+  pub unique: bool,
+}
+
+impl SortOptions {
+  /// Parse the flags, the whitespaces are ignored.
+  ///
+  /// # Errors
+  ///
+  /// If there's an unknown flag.
+  pub fn parse(bang: bool, flags: &str) -> ExCommandResult<Self> {
+    let mut options = SortOptions {
+      reverse: bang,
+      ..Default::default()
+    };
+    for c in flags.chars().filter(|c| !c.is_whitespace()) {
+      match c {
+        'i' => options.ignore_case = true,
+        'n' => options.numeric = true,
+        'u' => options.unique = true,
+        _ => {
+          return Err(ExCommandErr::Message(format!(
+            "E474: Invalid argument: {}",
+            c
+          )))
+        }
+      }
+    }
+    Ok(options)
+  }
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let options = SortOptions::parse(command_line.bang(), command_line.args())?;
+
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let current_line_idx = rlock!(viewport).cursor().line_idx();
+
+  {
+    let mut buffer = wlock!(buffer);
+    let line_range = command_line.resolve_range(
+      ExLineRange::All,
+      current_line_idx,
+      buffer.len_lines(),
+      buffer.marks(),
+    )?;
+    let line_range = trim_eof_line(&buffer, line_range);
+    sort_lines(&mut buffer, line_range, options, &data_access.interrupt)?;
+  }
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+  Ok(())
+}
+
+// The first decimal number in the line, i.e. the `n` flag.
+fn first_number(line: &str) -> Option<i64> {
+  let start = line.find(|c: char| c.is_ascii_digit())?;
+  let negative = line[..start].ends_with('-');
+  let digits = line[start..]
+    .find(|c: char| !c.is_ascii_digit())
+    .map_or(&line[start..], |end| &line[start..start + end]);
+  // The too large number is saturated.
+  let n = digits.parse::<i64>().unwrap_or(i64::MAX);
+  Some(if negative { -n } else { n })
+}
+
+fn compare(a: &str, b: &str, options: SortOptions) -> Ordering {
+  if options.numeric {
+    first_number(a).cmp(&first_number(b))
+  } else if options.ignore_case {
+    a.to_lowercase().cmp(&b.to_lowercase())
+  } else {
+    a.cmp(b)
+  }
+}
+
+/// Sort the lines in `line_range`.
+///
+/// Returns whether the buffer is changed.
+///
+/// # Errors
+///
+/// If it's interrupted by `Ctrl-C` while reading or deduplicating the lines, the buffer is not
+/// changed.
+pub fn sort_lines(
+  buffer: &mut Buffer,
+  line_range: Range<usize>,
+  options: SortOptions,
+  interrupt: &Interrupt,
+) -> ExCommandResult<bool> {
+  let mut lines: Vec<String> = vec![];
+  for line_idx in line_range.clone() {
+    interrupt.check()?;
+    match buffer.get_line_content(line_idx) {
+      Some(content) => lines.push(content),
+      None => break,
+    }
+  }
+  let original = lines.clone();
+
+  lines.sort_by(|a, b| {
+    let ordering = compare(a, b, options);
+    if options.reverse {
+      ordering.reverse()
+    } else {
+      ordering
+    }
+  });
+  if options.unique {
+    let mut unique: Vec<String> = Vec::with_capacity(lines.len());
+    for line in lines {
+      interrupt.check()?;
+      let duplicated = unique.last().is_some_and(|last| {
+        if options.ignore_case {
+          last.to_lowercase() == line.to_lowercase()
+        } else {
+          *last == line
+        }
+      });
+      if !duplicated {
+        unique.push(line);
+      }
+    }
+    lines = unique;
+  }
+
+  if lines == original {
+    return Ok(false);
+  }
+  Ok(buffer.set_lines(line_range.start, line_range.start + original.len(), &lines))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::test::buf::make_buffer_from_lines;
+
+  fn lines(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.len_lines())
+      .map(|i| buffer.get_line_content(i).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn parse1() {
+    assert_eq!(
+      SortOptions::parse(true, "i u").unwrap(),
+      SortOptions {
+        reverse: true,
+        ignore_case: true,
+        numeric: false,
+        unique: true,
+      }
+    );
+    assert_eq!(
+      SortOptions::parse(false, "").unwrap(),
+      SortOptions::default()
+    );
+    assert!(SortOptions::parse(false, "x").is_err());
+  }
+
+  #[test]
+  fn sort_lines1() {
+    let cases: Vec<(&str, Vec<&str>)> = vec![
+      ("", vec!["B", "a", "b", "c", "c"]),
+      ("!", vec!["c", "c", "b", "a", "B"]),
+      ("i", vec!["a", "B", "b", "c", "c"]),
+      ("u", vec!["B", "a", "b", "c"]),
+      ("iu", vec!["a", "B", "c"]),
+    ];
+    for (flags, expect) in cases {
+      let buffer = make_buffer_from_lines(vec!["c\n", "B\n", "a\n", "c\n", "b\n"]);
+      let mut buffer = wlock!(buffer);
+      let options =
+        SortOptions::parse(flags.starts_with('!'), flags.trim_start_matches('!')).unwrap();
+      let range = trim_eof_line(&buffer, 0..buffer.len_lines());
+      assert!(sort_lines(&mut buffer, range, options, &Interrupt::new()).unwrap());
+      let mut expect = expect;
+      expect.push("");
+      assert_eq!(lines(&buffer), expect, "flags:{:?}", flags);
+    }
+  }
+
+  #[test]
+  fn sort_lines2() {
+    // Numeric.
+    let buffer = make_buffer_from_lines(vec!["x10\n", "none\n", "x-2\n", "x9 8\n", "also\n"]);
+    let mut buffer = wlock!(buffer);
+    let options = SortOptions::parse(false, "n").unwrap();
+    assert!(sort_lines(&mut buffer, 0..5, options, &Interrupt::new()).unwrap());
+    assert_eq!(
+      lines(&buffer),
+      vec!["none", "also", "x-2", "x9 8", "x10", ""]
+    );
+  }
+
+  #[test]
+  fn sort_lines3() {
+    // Only the range is sorted, and the sorted lines are not changed.
+    let buffer = make_buffer_from_lines(vec!["d\n", "c\n", "a\n", "b\n"]);
+    let mut buffer = wlock!(buffer);
+    assert!(sort_lines(&mut buffer, 1..3, SortOptions::default(), &Interrupt::new()).unwrap());
+    assert_eq!(lines(&buffer), vec!["d", "a", "c", "b", ""]);
+    assert!(!sort_lines(&mut buffer, 1..3, SortOptions::default(), &Interrupt::new()).unwrap());
+  }
+
+  #[test]
+  fn sort_lines4() {
+    // Interrupted.
+    let buffer = make_buffer_from_lines(vec!["b\n", "a\n"]);
+    let interrupt = Interrupt::new();
+    interrupt.interrupt();
+    let actual = sort_lines(
+      &mut wlock!(buffer),
+      0..2,
+      SortOptions::default(),
+      &interrupt,
+    );
+    assert_eq!(actual, Err(ExCommandErr::Interrupted));
+    assert_eq!(lines(&rlock!(buffer)), vec!["b", "a", ""]);
+  }
+}
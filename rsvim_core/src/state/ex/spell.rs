@@ -0,0 +1,68 @@
+//! The `:spellgood` ex command, see [`spell`](crate::buf::spell).
+//!
+//! - `:spe[llgood] {word}` adds `{word}` to the user spell dictionary, i.e. `zg` on the word under
+//!   cursor.
+
+use crate::buf::spell::{is_valid_word, spell_file};
+use crate::buf::BuffersManagerArc;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::state::msg::{MessageLevel, MessagesArc};
+use crate::wlock;
+
+use tracing::trace;
+
+/// The `:spellgood` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new(
+    "spellgood",
+    "spe",
+    "Add a word to the user spell dictionary",
+    handle,
+  )
+}
+
+/// Add the good word to the user spell dictionary, it's saved to the dictionary file on an async
+/// task.
+///
+/// # Errors
+///
+/// If the word is empty or has whitespaces.
+pub fn add_good_word(
+  buffers: &BuffersManagerArc,
+  messages: &MessagesArc,
+  word: &str,
+) -> ExCommandResult<()> {
+  if !is_valid_word(word) {
+    return Err(ExCommandErr::Message(format!(
+      "E475: Invalid argument: {}",
+      word
+    )));
+  }
+  let added = wlock!(buffers).spell_mut().add(word);
+  trace!("spellgood: {:?}, {:?}", word, added);
+  wlock!(messages).push(
+    MessageLevel::Info,
+    &format!(
+      "Word '{}' added to {}",
+      word,
+      spell_file().to_string_lossy()
+    ),
+  );
+  Ok(())
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  add_good_word(
+    &data_access.buffers,
+    &data_access.messages,
+    command_line.args().trim(),
+  )
+}
@@ -0,0 +1,284 @@
+//! The `:substitute` ex command.
+//!
+//! `:[range]s[ubstitute]/{pattern}/{string}/[flags]` replaces the first match of `{pattern}` with
+//! `{string}` in each line in `[range]` (default is current line). The delimiter can be any
+//! non-alphanumeric char (except `"`, `|` and `\`). The flags are:
+//!
+//! - `g`: Replace all matches in the line.
+//! - `i`: Ignore case.
+//!
+//! In `{string}`, the `&` and `\0` are the whole match, `\1` to `\9` are the capture groups,
+//! `\n` and `\r` break the line, `\t` is a tab, and `\&`, `\\` are the literal `&`, `\`.
+
+use crate::buf::Buffer;
+use crate::envar;
+use crate::interrupt::Interrupt;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  split_delimited, trim_eof_line, ExCommandDataAccess, ExCommandDefinition, ExCommandLine,
+  ExCommandsManager, ExLineAddress, ExLineRange,
+};
+use crate::{rlock, wlock};
+
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+/// The `:substitute` definition.
+pub fn definition() -> ExCommandDefinition {
+  ExCommandDefinition::new(
+    "substitute",
+    "s",
+    "Replace the matches of a pattern",
+    handle,
+  )
+}
+
+/// Convert the `{string}` to the replacement template of [`Regex::replace`], see the module
+/// documentation.
+pub fn replacement_template(s: &str) -> String {
+  let mut template = String::new();
+  let mut chars = s.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '&' => template.push_str("${0}"),
+      '$' => template.push_str("$$"),
+      '\\' => match chars.next() {
+        Some(d) if d.is_ascii_digit() => template.push_str(&format!("${{{}}}", d)),
+        Some('$') => template.push_str("$$"),
+        Some('n') | Some('r') => template.push('\n'),
+        Some('t') => template.push('\t'),
+        Some(other) => template.push(other),
+        None => template.push('\\'),
+      },
+      _ => template.push(c),
+    }
+  }
+  template
+}
+
+fn handle(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let (fields, flags) = match split_delimited(command_line.args(), 2) {
+    Some(split) => split,
+    None => return Err(ExCommandErr::ArgumentRequired),
+  };
+  let (pattern, replacement) = (&fields[0], &fields[1]);
+  if pattern.is_empty() {
+    return Err(ExCommandErr::Message(
+      "E35: No previous regular expression".to_string(),
+    ));
+  }
+  let mut global = false;
+  let mut ignore_case = false;
+  for c in flags.trim().chars() {
+    match c {
+      'g' => global = true,
+      'i' => ignore_case = true,
+      _ => {
+        return Err(ExCommandErr::Message(format!(
+          "E488: Trailing characters: {}",
+          flags.trim()
+        )))
+      }
+    }
+  }
+  let regex = RegexBuilder::new(pattern)
+    .case_insensitive(ignore_case)
+    .build()
+    .map_err(|_| ExCommandErr::InvalidPattern(pattern.to_string()))?;
+
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let current_line_idx = rlock!(viewport).cursor().line_idx();
+
+  let substituted = {
+    let mut buffer = wlock!(buffer);
+    let line_range = command_line.resolve_range(
+      ExLineRange::Lines(ExLineAddress::Current, ExLineAddress::Current),
+      current_line_idx,
+      buffer.len_lines(),
+      buffer.marks(),
+    )?;
+    let line_range = trim_eof_line(&buffer, line_range);
+    substitute_lines(
+      &mut buffer,
+      line_range,
+      &regex,
+      &replacement_template(replacement),
+      global,
+      &data_access.interrupt,
+    )?
+  };
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+
+  if substituted == 0 {
+    return Err(ExCommandErr::PatternNotFound(pattern.to_string()));
+  }
+  Ok(())
+}
+
+/// Replace the first match (or all matches if `global`) of `pattern` with the `template` (see
+/// [`replacement_template`]) in each line in `line_range`.
+///
+/// The lines broken by the replacement are not searched again, i.e. the rest of `line_range` is
+/// moved down with them.
+///
+/// Returns the count of replaced matches.
+///
+/// # Errors
+///
+/// If it's interrupted by `Ctrl-C`, the lines already replaced are kept.
+pub fn substitute_lines(
+  buffer: &mut Buffer,
+  line_range: Range<usize>,
+  pattern: &Regex,
+  template: &str,
+  global: bool,
+  interrupt: &Interrupt,
+) -> ExCommandResult<usize> {
+  let limit = if global { 0 } else { 1 };
+  let mut substituted = 0_usize;
+  let (mut line_idx, mut end) = (line_range.start, line_range.end);
+  while line_idx < end {
+    interrupt.check()?;
+    let content = match buffer.get_line_content(line_idx) {
+      Some(content) => content,
+      None => break,
+    };
+    let matches = pattern.find_iter(&content).count();
+    if matches == 0 {
+      line_idx += 1;
+      continue;
+    }
+    let replaced = pattern.replacen(&content, limit, template);
+    let lines: Vec<String> = replaced.split('\n').map(|line| line.to_string()).collect();
+    if lines.len() > 1 {
+      buffer.set_lines(line_idx, line_idx + 1, &lines);
+    } else if replaced != content {
+      buffer.replace_line_content(line_idx, &replaced);
+    }
+    substituted += if global { matches } else { 1 };
+    line_idx += lines.len();
+    end += lines.len() - 1;
+  }
+  Ok(substituted)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::test::buf::make_buffer_from_lines;
+
+  fn lines(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.len_lines())
+      .map(|i| buffer.get_line_content(i).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn replacement_template1() {
+    let cases = vec![
+      ("bar", "bar"),
+      ("<&>", "<${0}>"),
+      ("\\2-\\1", "${2}-${1}"),
+      ("\\&\\\\", "&\\"),
+      ("$1", "$$1"),
+      ("end\\", "end\\"),
+      ("a\\nb\\rc\\td", "a\nb\nc\td"),
+    ];
+    for (input, expect) in cases {
+      assert_eq!(replacement_template(input), expect, "input:{:?}", input);
+    }
+  }
+
+  #[test]
+  fn substitute_lines1() {
+    let cases = vec![
+      (false, 1, vec!["x-foo-foo", "bar", "foo-foo", ""]),
+      (true, 3, vec!["x-x-x", "bar", "foo-foo", ""]),
+    ];
+    let pattern = Regex::new("foo").unwrap();
+    for (global, count, expect) in cases {
+      let buffer = make_buffer_from_lines(vec!["foo-foo-foo\n", "bar\n", "foo-foo\n"]);
+      let mut buffer = wlock!(buffer);
+      let actual = substitute_lines(
+        &mut buffer,
+        0..2,
+        &pattern,
+        &replacement_template("x"),
+        global,
+        &Interrupt::new(),
+      )
+      .unwrap();
+      assert_eq!(actual, count);
+      assert_eq!(lines(&buffer), expect);
+    }
+  }
+
+  #[test]
+  fn substitute_lines2() {
+    // Capture groups, and no match.
+    let buffer = make_buffer_from_lines(vec!["key = value\n", "no match\n"]);
+    let mut buffer = wlock!(buffer);
+    let actual = substitute_lines(
+      &mut buffer,
+      0..2,
+      &Regex::new(r"(\w+) = (\w+)").unwrap(),
+      &replacement_template(r"\2 = \1 (&)"),
+      false,
+      &Interrupt::new(),
+    )
+    .unwrap();
+    assert_eq!(actual, 1);
+    assert_eq!(
+      lines(&buffer),
+      vec!["value = key (key = value)", "no match", ""]
+    );
+  }
+
+  #[test]
+  fn substitute_lines3() {
+    // Interrupted.
+    let buffer = make_buffer_from_lines(vec!["foo\n", "foo\n"]);
+    let interrupt = Interrupt::new();
+    interrupt.interrupt();
+    let actual = substitute_lines(
+      &mut wlock!(buffer),
+      0..2,
+      &Regex::new("foo").unwrap(),
+      "bar",
+      false,
+      &interrupt,
+    );
+    assert_eq!(actual, Err(ExCommandErr::Interrupted));
+    assert_eq!(lines(&rlock!(buffer)), vec!["foo", "foo", ""]);
+  }
+
+  #[test]
+  fn substitute_lines4() {
+    // Break the lines, the inserted lines are not searched again.
+    let buffer = make_buffer_from_lines(vec!["a,b\n", "c,d\n", "e\n"]);
+    let mut buffer = wlock!(buffer);
+    let actual = substitute_lines(
+      &mut buffer,
+      0..2,
+      &Regex::new(",").unwrap(),
+      &replacement_template(r"\n,"),
+      true,
+      &Interrupt::new(),
+    )
+    .unwrap();
+    assert_eq!(actual, 2);
+    assert_eq!(lines(&buffer), vec!["a", ",b", "c", ",d", "e", ""]);
+    // Each line is replaced at once.
+    buffer.undo();
+    assert_eq!(lines(&buffer), vec!["a", ",b", "c,d", "e", ""]);
+  }
+}
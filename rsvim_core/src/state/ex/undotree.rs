@@ -0,0 +1,261 @@
+//! The undo tree ex commands, see [`undotree`](crate::state::undotree) for the browser.
+//!
+//! - `:Undot[ree]` shows the undo tree of current buffer in a read-only scratch buffer, the cursor
+//!   is on the current state. Running it again in the undo tree buffer goes back to the source
+//!   buffer.
+//! - `:UndotreeJump` jumps the source buffer to the undo state under the cursor, and re-renders
+//!   the undo tree buffer.
+//!
+//! The keys in the undo tree buffer (see [`NormalStateful`](crate::state::fsm::NormalStateful)):
+//!
+//! - `<CR>`: `:UndotreeJump`.
+
+use crate::buf::{Buffer, BufferArc};
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::current_window_buffer;
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::state::fsm::visual::move_cursor_to;
+use crate::state::undotree::{entries, UndoTreeBrowser, FILE_TYPE};
+use crate::ui::tree::TreeArc;
+use crate::{rlock, wlock};
+
+use ropey::Rope;
+use std::time::SystemTime;
+
+/// The undo tree definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new(
+      "Undotree",
+      "Undot",
+      "Show the undo tree of the buffer",
+      handle_undotree,
+    ),
+    ExCommandDefinition::new(
+      "UndotreeJump",
+      "UndotreeJ",
+      "Jump to the undo state under the cursor",
+      handle_jump,
+    ),
+  ]
+}
+
+/// Whether current window shows an undo tree buffer.
+pub fn in_undotree(tree: &TreeArc) -> bool {
+  current_window_buffer(tree)
+    .is_some_and(|(buffer, _)| rlock!(buffer).options().file_type() == FILE_TYPE)
+}
+
+// Make the browser of the buffer's undo tree.
+fn browser_of(buffer: &Buffer) -> UndoTreeBrowser {
+  let undo_tree = buffer.undo_tree();
+  UndoTreeBrowser::new(
+    entries(undo_tree, &buffer.snapshot()),
+    undo_tree.current(),
+    SystemTime::now(),
+  )
+}
+
+// Get the source buffer if `buffer` is an undo tree buffer.
+fn undotree_source(data_access: &ExCommandDataAccess, buffer: &BufferArc) -> Option<BufferArc> {
+  let buffer = rlock!(buffer);
+  if buffer.options().file_type() != FILE_TYPE {
+    return None;
+  }
+  let buffers = rlock!(data_access.buffers);
+  let source_id = buffers.scratch_source(&buffer.id())?;
+  buffers.get(&source_id).cloned()
+}
+
+fn handle_undotree(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let buffer_id = rlock!(buffer).id();
+
+  // Go back to the source buffer, the undo tree buffer is discarded.
+  if let Some(source) = undotree_source(data_access, &buffer) {
+    data_access.show_buffer(&source);
+    wlock!(data_access.buffers).remove(&buffer_id);
+    return Ok(());
+  }
+
+  let browser = browser_of(&rlock!(buffer));
+  let scratch = {
+    let mut buffers = wlock!(data_access.buffers);
+    let scratch_id = buffers.new_scratch_buffer(Rope::from_str(&browser.text()), Some(buffer_id));
+    buffers.get(&scratch_id).unwrap().clone()
+  };
+  {
+    let mut scratch = wlock!(scratch);
+    let mut options = scratch.options().clone();
+    options.set_file_type(FILE_TYPE);
+    scratch.set_options(&options);
+    scratch.set_readonly(true);
+  }
+
+  data_access.show_buffer(&scratch);
+  move_cursor_to(&data_access.tree, &viewport, (browser.selected(), 0));
+  Ok(())
+}
+
+fn handle_jump(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let (buffer, viewport) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let source = undotree_source(data_access, &buffer)
+    .ok_or_else(|| ExCommandErr::Message("Not an undo tree buffer".to_string()))?;
+
+  // NOTE: The undo tree buffer is rendered from the source buffer, thus the line index is the same
+  // with the browser lines.
+  let line_idx = rlock!(viewport).cursor().line_idx();
+  let seq = browser_of(&rlock!(source))
+    .lines()
+    .get(line_idx)
+    .and_then(|line| line.seq);
+  let seq = match seq {
+    Some(seq) => seq,
+    None => return Ok(()),
+  };
+  if !wlock!(source).undo_goto(seq) {
+    return Err(ExCommandErr::Message(format!(
+      "E830: Undo number {} not found",
+      seq
+    )));
+  }
+
+  let browser = browser_of(&rlock!(source));
+  wlock!(buffer).replace_loaded(&browser.text());
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+  move_cursor_to(&data_access.tree, &viewport, (browser.selected(), 0));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::buf::BuffersManager;
+  use crate::cart::{IRect, U16Size};
+  use crate::interrupt::Interrupt;
+  use crate::state::msg::Messages;
+  use crate::ui::tree::internal::Inodeable;
+  use crate::ui::tree::{Tree, TreeNode};
+  use crate::ui::widget::{Cursor, Window};
+
+  use std::sync::Arc;
+
+  // Make a 20x20 editor with a window showing an unnamed buffer, the buffer has the undo states:
+  //
+  // ```text
+  // * [3] (current) "x\na\nb\n"
+  // | * [2] "a\nb\nc\n"
+  // |/
+  // * [1] "a\nb\n"
+  // * [0] "a\n"
+  // ```
+  fn make_editor() -> (ExCommandDataAccess, BufferArc) {
+    let mut buffers = BuffersManager::new();
+    let buf_id = buffers.new_empty_buffer();
+    let buffer = buffers.get(&buf_id).unwrap().clone();
+    {
+      let mut buffer = wlock!(buffer);
+      buffer.replace_loaded("a\n");
+      buffer.insert(2, "b\n");
+      buffer.insert(4, "c\n");
+      buffer.undo();
+      buffer.insert(0, "x\n");
+    }
+
+    let mut tree = Tree::new(U16Size::new(20, 20));
+    let root_id = tree.root_id();
+    let shape = IRect::new((0, 0), (20, 20));
+    let window = Window::new(shape, Arc::downgrade(&buffer), tree.local_options());
+    let window_id = window.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(window));
+    tree.bounded_insert(
+      &window_id,
+      TreeNode::Cursor(Cursor::new(IRect::new((0, 0), (1, 1)))),
+    );
+    tree.layout_tiled_windows();
+
+    let data_access = ExCommandDataAccess::new(
+      Tree::to_arc(tree),
+      BuffersManager::to_arc(buffers),
+      Interrupt::to_arc(Interrupt::new()),
+      Messages::to_arc(Messages::new()),
+    );
+    (data_access, buffer)
+  }
+
+  fn buffer_text(buffer: &BufferArc) -> String {
+    rlock!(buffer)
+      .lines()
+      .map(|line| line.to_string())
+      .collect()
+  }
+
+  fn cursor_line(data_access: &ExCommandDataAccess) -> usize {
+    let (_, viewport) = data_access.current_window_buffer().unwrap();
+    let viewport = rlock!(viewport);
+    viewport.cursor().line_idx()
+  }
+
+  #[test]
+  fn undotree1() {
+    let (mut data_access, source) = make_editor();
+    let commands = ExCommandsManager::new();
+    assert_eq!(buffer_text(&source), "x\na\nb\n");
+
+    commands.run("Undotree", &mut data_access).unwrap();
+    assert!(in_undotree(&data_access.tree));
+    let (scratch, _) = data_access.current_window_buffer().unwrap();
+    assert!(rlock!(scratch).is_read_only());
+    // NOTE: All the states are just created.
+    assert_eq!(
+      buffer_text(&scratch),
+      "* [3] 0s ago (current)\n| * [2] 0s ago\n|/\n* [1] 0s ago\n* [0] original\n\n+x\n a\n b\n"
+    );
+    assert_eq!(cursor_line(&data_access), 0);
+
+    // Jump to the state `2`, the source buffer is restored.
+    let (_, viewport) = data_access.current_window_buffer().unwrap();
+    move_cursor_to(&data_access.tree, &viewport, (1, 0));
+    commands.run("UndotreeJump", &mut data_access).unwrap();
+    assert_eq!(buffer_text(&source), "a\nb\nc\n");
+    assert_eq!(cursor_line(&data_access), 1);
+    assert_eq!(
+      buffer_text(&scratch),
+      "* [3] 0s ago\n| * [2] 0s ago (current)\n|/\n* [1] 0s ago\n* [0] original\n\n a\n b\n+c\n"
+    );
+
+    // Go back to the source buffer, the undo tree buffer is removed.
+    let scratch_id = rlock!(scratch).id();
+    commands.run("Undotree", &mut data_access).unwrap();
+    assert!(!in_undotree(&data_access.tree));
+    let (current, _) = data_access.current_window_buffer().unwrap();
+    assert!(Arc::ptr_eq(&current, &source));
+    assert!(rlock!(data_access.buffers).get(&scratch_id).is_none());
+  }
+
+  #[test]
+  fn undotree_jump1() {
+    let (mut data_access, _source) = make_editor();
+    let commands = ExCommandsManager::new();
+    assert!(commands.run("UndotreeJump", &mut data_access).is_err());
+  }
+}
@@ -0,0 +1,219 @@
+//! The window ex commands.
+//!
+//! - `:sp[lit] [file]` splits current window horizontally, i.e. the new window is above. The new
+//!   window edits `[file]` if it's given.
+//! - `:vs[plit] [file]` splits current window vertically, i.e. the new window is on the left side.
+//! - `:clo[se]` closes current window, the last window can't be closed.
+//! - `:on[ly]` closes all other windows, including the floating windows.
+//! - `:res[ize] [+-]N` sets current window height to `N`, or increases/decreases by `N`.
+//! - `:vert[ical] res[ize] [+-]N` sets current window width to `N`, or increases/decreases by `N`.
+//!
+//! The `[file]` is expanded (see [`expand`](crate::buf::expand)), and it must be a single file.
+
+use crate::buf::expand::expand_path;
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::ui::tree::layout::{LayoutDirection, ResizeAmount};
+use crate::{rlock, wlock};
+
+use tracing::trace;
+
+/// The window definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new(
+      "split",
+      "sp",
+      "Split current window horizontally",
+      handle_split,
+    )
+    .with_file_completion(),
+    ExCommandDefinition::new(
+      "vsplit",
+      "vs",
+      "Split current window vertically",
+      handle_vsplit,
+    )
+    .with_file_completion(),
+    ExCommandDefinition::new("close", "clo", "Close current window", handle_close),
+    ExCommandDefinition::new("only", "on", "Close all other windows", handle_only),
+    ExCommandDefinition::new(
+      "resize",
+      "res",
+      "Set or change current window height",
+      handle_resize,
+    ),
+    ExCommandDefinition::new(
+      "vertical",
+      "vert",
+      "Set or change current window width, i.e. `:vertical resize`",
+      handle_vertical,
+    ),
+  ]
+}
+
+fn split(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  direction: LayoutDirection,
+) -> ExCommandResult<()> {
+  let filename = command_line.args().trim();
+  let files = if filename.is_empty() {
+    vec![]
+  } else {
+    expand_path(filename)
+  };
+  if files.len() > 1 {
+    return Err(ExCommandErr::Message(
+      "E77: Too many file names".to_string(),
+    ));
+  }
+
+  let window_id = wlock!(data_access.tree).split_window(direction);
+  trace!("Split window {:?}: {:?}", direction, window_id);
+  if window_id.is_none() {
+    return Err(ExCommandErr::Message("E36: Not enough room".to_string()));
+  }
+  if let Some(file) = files.first() {
+    data_access.edit_file(file)?;
+  }
+  Ok(())
+}
+
+fn handle_split(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  split(command_line, data_access, LayoutDirection::Column)
+}
+
+fn handle_vsplit(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  split(command_line, data_access, LayoutDirection::Row)
+}
+
+fn handle_close(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let mut tree = wlock!(data_access.tree);
+  let closed = match tree.current_window_id() {
+    Some(window_id) => tree.close_window(window_id),
+    None => false,
+  };
+  if closed {
+    Ok(())
+  } else {
+    Err(ExCommandErr::Message(
+      "E444: Cannot close last window".to_string(),
+    ))
+  }
+}
+
+fn handle_only(
+  _command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let mut tree = wlock!(data_access.tree);
+  if let Some(window_id) = tree.current_window_id() {
+    let others = tree
+      .float_ids()
+      .into_iter()
+      .chain(tree.tiled_window_ids())
+      .filter(|id| *id != window_id)
+      .collect::<Vec<_>>();
+    for id in others {
+      tree.close_window(id);
+    }
+    trace!("Only window: {:?}", window_id);
+  }
+  Ok(())
+}
+
+/// Parse the resize argument, i.e. `N`, `+N` or `-N`.
+pub fn parse_resize_amount(args: &str) -> ExCommandResult<ResizeAmount> {
+  let args = args.trim();
+  let invalid = || ExCommandErr::Message(format!("E475: Invalid argument: {}", args));
+  if let Some(n) = args.strip_prefix('+') {
+    n.parse::<usize>()
+      .map(|n| ResizeAmount::By(n as isize))
+      .map_err(|_| invalid())
+  } else if let Some(n) = args.strip_prefix('-') {
+    n.parse::<usize>()
+      .map(|n| ResizeAmount::By(-(n as isize)))
+      .map_err(|_| invalid())
+  } else if args.is_empty() {
+    Err(ExCommandErr::ArgumentRequired)
+  } else {
+    args
+      .parse::<u16>()
+      .map(ResizeAmount::To)
+      .map_err(|_| invalid())
+  }
+}
+
+fn resize(
+  data_access: &mut ExCommandDataAccess,
+  direction: LayoutDirection,
+  args: &str,
+) -> ExCommandResult<()> {
+  let amount = parse_resize_amount(args)?;
+  let window_id = rlock!(data_access.tree).current_window_id();
+  if let Some(window_id) = window_id {
+    let resized = wlock!(data_access.tree).resize_window(window_id, direction, amount);
+    trace!("Resize window {:?} {:?}: {:?}", direction, amount, resized);
+  }
+  Ok(())
+}
+
+fn handle_resize(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  resize(data_access, LayoutDirection::Column, command_line.args())
+}
+
+fn handle_vertical(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  let args = command_line.args().trim();
+  let (name, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+  if name.len() >= 3 && "resize".starts_with(name) {
+    resize(data_access, LayoutDirection::Row, rest)
+  } else {
+    Err(ExCommandErr::Message(format!(
+      "E492: Not an editor command: vertical {}",
+      args
+    )))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_resize_amount1() {
+    assert_eq!(parse_resize_amount("10").unwrap(), ResizeAmount::To(10));
+    assert_eq!(parse_resize_amount(" +3 ").unwrap(), ResizeAmount::By(3));
+    assert_eq!(parse_resize_amount("-2").unwrap(), ResizeAmount::By(-2));
+    assert!(matches!(
+      parse_resize_amount(""),
+      Err(ExCommandErr::ArgumentRequired)
+    ));
+    assert!(parse_resize_amount("abc").is_err());
+    assert!(parse_resize_amount("+-1").is_err());
+  }
+}
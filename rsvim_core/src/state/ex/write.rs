@@ -0,0 +1,215 @@
+//! The write ex commands, see [`write`](crate::buf::write).
+//!
+//! - `:w[rite][!] [++p] [file]` writes current buffer to its file, or to `[file]` (it's expanded,
+//!   see [`expand`](crate::buf::expand)).
+//! - `:up[date][!] [++p] [file]` is the same as `:write`, but only writes when the buffer is
+//!   modified.
+//!
+//! If the file's parent directory doesn't exist, it's created with `++p` or the 'mkdir' option,
+//! otherwise it fails with `E212`.
+//!
+//! The read-only buffer, or the read-only file (i.e. it doesn't have the write permission) is only
+//! written with `!`. The buffer is encoded with its 'file-encoding' option, if some chars cannot be
+//! encoded (i.e. the conversion is lossy, they're replaced with `?`), it's also only written with
+//! `!`. The `BufWritePre` and `BufWritePost` events are emitted before and after
+//! writing the file.
+//!
+//! The file is written through the buffers' file system provider, see [`fs`](crate::buf::fs).
+//!
+//! NOTE: The event callbacks are called by the js runtime after the command, thus the
+//! `BufWritePre` callbacks cannot change the written contents.
+
+use crate::buf::expand::expand_path;
+use crate::buf::{write, BufferArc};
+use crate::envar;
+use crate::res::{ExCommandErr, ExCommandResult, IoErrKind};
+use crate::state::autocmd::{AutocmdArgs, AutocmdEvent};
+use crate::state::ex::{
+  ExCommandDataAccess, ExCommandDefinition, ExCommandLine, ExCommandsManager,
+};
+use crate::{rlock, wlock};
+
+use path_absolutize::Absolutize;
+use std::path::PathBuf;
+use tracing::{trace, warn};
+
+/// The write definitions.
+pub fn definitions() -> Vec<ExCommandDefinition> {
+  vec![
+    ExCommandDefinition::new("write", "w", "Write current buffer to file", handle_write)
+      .with_file_completion(),
+    ExCommandDefinition::new(
+      "update",
+      "up",
+      "Write current buffer to file if it's modified",
+      handle_update,
+    )
+    .with_file_completion(),
+  ]
+}
+
+fn handle_write(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  write_buffer(command_line, data_access, false)
+}
+
+fn handle_update(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  _commands: &ExCommandsManager,
+) -> ExCommandResult<()> {
+  write_buffer(command_line, data_access, true)
+}
+
+/// Split the `++p` flag from the arguments, returns whether it's given and the `[file]` argument.
+pub fn parse_args(args: &str) -> (bool, &str) {
+  let args = args.trim();
+  match args.strip_prefix("++p") {
+    Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => (true, rest.trim()),
+    _ => (false, args),
+  }
+}
+
+// Get the file to write, i.e. the `[file]` argument, or the buffer's file.
+fn target_file(filename: &str, buffer: &BufferArc) -> ExCommandResult<PathBuf> {
+  if filename.is_empty() {
+    let buffer = rlock!(buffer);
+    if buffer.remote().is_some() {
+      return Err(ExCommandErr::Message(
+        "E382: Cannot write remote file, use :Nwrite".to_string(),
+      ));
+    }
+    return buffer
+      .absolute_filename()
+      .clone()
+      .ok_or_else(|| ExCommandErr::Message("E32: No file name".to_string()));
+  }
+  let files = expand_path(filename);
+  if files.len() > 1 {
+    return Err(ExCommandErr::Message(
+      "E77: Too many file names".to_string(),
+    ));
+  }
+  match files[0].absolutize() {
+    Ok(file) => Ok(file.to_path_buf()),
+    Err(e) => Err(ExCommandErr::Message(format!(
+      "E212: Can't open file {:?} for writing: {}",
+      files[0], e
+    ))),
+  }
+}
+
+/// Write current buffer, or write it only if it's modified when `update`, see `:write` and
+/// `:update`.
+pub fn write_buffer(
+  command_line: &ExCommandLine,
+  data_access: &mut ExCommandDataAccess,
+  update: bool,
+) -> ExCommandResult<()> {
+  if command_line.range().is_some() {
+    return Err(ExCommandErr::InvalidRange);
+  }
+  let (buffer, _) = match data_access.current_window_buffer() {
+    Some(current) => current,
+    None => return Ok(()),
+  };
+  let (mkdir, filename) = parse_args(command_line.args());
+  let file = target_file(filename, &buffer)?;
+  let force = command_line.bang();
+
+  let (buffer_id, is_own_file, unencodable) = {
+    let buffer = rlock!(buffer);
+    if buffer.is_loading() {
+      return Err(ExCommandErr::Message(format!(
+        "E505: {:?} is still loading",
+        file
+      )));
+    }
+    let is_own_file = buffer.absolute_filename().as_ref() == Some(&file);
+    if update && is_own_file && !buffer.is_modified() {
+      return Ok(());
+    }
+    if buffer.is_read_only() && !force {
+      return Err(ExCommandErr::Message(
+        "E45: 'readonly' option is set (add ! to override)".to_string(),
+      ));
+    }
+    let unencodable = buffer.unencodable_chars();
+    if unencodable > 0 && !force {
+      return Err(ExCommandErr::Message(format!(
+        "E513: Write error, conversion failed: {} chars cannot be encoded in {} (add ! to override)",
+        unencodable,
+        buffer.file_encoding()
+      )));
+    }
+    (buffer.id(), is_own_file, unencodable)
+  };
+  let fs = rlock!(data_access.buffers).fs().clone();
+  let readonly_file = fs
+    .metadata(&file)
+    .ok()
+    .flatten()
+    .is_some_and(|metadata| metadata.permissions().readonly());
+  if readonly_file && !force {
+    return Err(ExCommandErr::Message(format!(
+      "E505: {:?} is read-only (add ! to override)",
+      file
+    )));
+  }
+
+  let event_args = |event: AutocmdEvent| AutocmdArgs {
+    event,
+    matched: file.to_string_lossy().to_string(),
+    buffer_id: Some(buffer_id),
+    window_id: None,
+    scroll_delta: None,
+  };
+  data_access.emit(event_args(AutocmdEvent::BufWritePre));
+
+  let options = *rlock!(data_access.buffers).write_options();
+  let written = {
+    let buffer = rlock!(buffer);
+    fs.write_file(&file, &options, mkdir, &mut |writer| {
+      buffer.write_to(writer).map(|_| ())
+    })
+  }
+  .map_err(|e| match write::missing_parent(&file) {
+    Some(missing) if e.kind() == IoErrKind::NotFound => ExCommandErr::Message(format!(
+      "E212: Can't open file {:?} for writing: directory {:?} doesn't exist (add ++p to create it)",
+      file, missing
+    )),
+    _ => ExCommandErr::Message(format!("E514: Write error {:?}: {}", file, e)),
+  })?;
+  trace!("Write buffer {:?} to {:?}", buffer_id, written);
+  if unencodable > 0 {
+    warn!(
+      "Lossy conversion for {:?}, {} chars are replaced",
+      written, unencodable
+    );
+  }
+
+  if is_own_file {
+    let mut buffer = wlock!(buffer);
+    buffer.set_saved();
+    buffer.set_metadata(fs.metadata(&written).ok().flatten());
+  }
+  data_access.emit(event_args(AutocmdEvent::BufWritePost));
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_args1() {
+    assert_eq!(parse_args(""), (false, ""));
+    assert_eq!(parse_args(" a.txt "), (false, "a.txt"));
+    assert_eq!(parse_args("++p"), (true, ""));
+    assert_eq!(parse_args("++p  a/b.txt"), (true, "a/b.txt"));
+    assert_eq!(parse_args("++pa.txt"), (false, "++pa.txt"));
+  }
+}
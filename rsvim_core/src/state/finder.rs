@@ -0,0 +1,395 @@
+//! Fuzzy finder.
+//!
+//! The finder is a fuzzy searchable list over the items, it's rendered by the
+//! [`Picker`](crate::ui::widget::picker::Picker) widget (see
+//! [`FinderStateful`](crate::state::fsm::FinderStateful) for the keys), and opened by the
+//! `Rsvim.picker.open` JS API. The items are populated while the finder is shown, i.e. they're
+//! streamed from:
+//!
+//! - The JS source, i.e. an array or an (async) iterable, the items are pushed batch by batch. The
+//!   population stops once the finder is closed.
+//! - The builtin sources, see [`FinderSource`]. The files are walked on the tokio blocking pool, the
+//!   walk is cancelled once the finder is closed.
+//!
+//! The items are sorted by the [`fuzzy_match`] score on the label, the items with the same score
+//! keep the populated order. Once the finder is closed, the accepted item (or the cancellation) is
+//! passed to the JS callback, see [`FinderResult`].
+
+use crate::state::fuzzy::fuzzy_match;
+use crate::ui::widget::picker::PickerRow;
+
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tracing::trace;
+
+/// The max items in a batch of the builtin source sent to the event loop.
+pub const BATCH_SIZE: usize = 1024;
+
+/// The max interval between the batches of the builtin source.
+pub const BATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The finder ID.
+pub type FinderId = usize;
+
+/// Get the next finder ID, it's unique in the process.
+pub fn next_finder_id() -> FinderId {
+  static VALUE: AtomicUsize = AtomicUsize::new(1);
+  VALUE.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The source of the finder items.
+pub enum FinderSource {
+  /// The items are pushed by JS.
+  Items,
+  /// The files in the directory (relative to it), the ignore files (i.e. `.gitignore`) and the
+  /// hidden files are skipped.
+  Files(PathBuf),
+  /// The listed buffers, the description is the buffer ID, i.e. `#3`.
+  Buffers,
+}
+
+impl FinderSource {
+  /// Parse the builtin source name, i.e. `files` and `buffers`.
+  pub fn parse(name: &str) -> Option<Self> {
+    match name {
+      "files" => Some(FinderSource::Files(PathBuf::from("."))),
+      "buffers" => Some(FinderSource::Buffers),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The finder item.
+pub struct FinderItem {
+  pub label: String,
+  pub description: String,
+}
+
+impl FinderItem {
+  pub fn new(label: &str, description: &str) -> Self {
+    FinderItem {
+      label: label.to_string(),
+      description: description.to_string(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The closed finder, it has the accepted item (and its index in the populated order), or `None`
+/// if it's cancelled.
+pub struct FinderResult {
+  pub finder_id: FinderId,
+  pub accepted: Option<(usize, FinderItem)>,
+}
+
+#[derive(Debug, Clone)]
+// The matched item.
+struct Matched {
+  index: usize,
+  score: i64,
+  positions: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+/// The finder state.
+pub struct Finder {
+  id: FinderId,
+  title: String,
+  // The builtin source, it's taken by the event loop to start the population.
+  source: Option<FinderSource>,
+  items: Vec<FinderItem>,
+  query: String,
+  // The matched items, sorted by score.
+  matched: Vec<Matched>,
+  // Index of the selected item in `matched`.
+  selected: usize,
+  // Whether the items are still being populated.
+  loading: bool,
+}
+
+impl Finder {
+  pub fn new(id: FinderId, title: &str, source: FinderSource) -> Self {
+    Finder {
+      id,
+      title: title.to_string(),
+      source: Some(source).filter(|s| *s != FinderSource::Items),
+      items: vec![],
+      query: String::new(),
+      matched: vec![],
+      selected: 0,
+      loading: true,
+    }
+  }
+
+  pub fn id(&self) -> FinderId {
+    self.id
+  }
+
+  pub fn query(&self) -> &str {
+    &self.query
+  }
+
+  pub fn items(&self) -> &Vec<FinderItem> {
+    &self.items
+  }
+
+  pub fn is_loading(&self) -> bool {
+    self.loading
+  }
+
+  /// Take the builtin source to populate, it's `None` if the items are pushed by JS, or it's
+  /// already taken.
+  pub fn take_source(&mut self) -> Option<FinderSource> {
+    self.source.take()
+  }
+
+  /// The title of the picker widget, with the count of the matched items and all the items, i.e.
+  /// `Files 12/340`. It has the `...` suffix while the items are being populated.
+  pub fn display_title(&self) -> String {
+    format!(
+      "{} {}/{}{}",
+      self.title,
+      self.matched.len(),
+      self.items.len(),
+      if self.loading { "..." } else { "" }
+    )
+  }
+
+  // Match the item with current query.
+  fn match_item(&self, index: usize) -> Option<Matched> {
+    fuzzy_match(&self.query, &self.items[index].label).map(|m| Matched {
+      index,
+      score: m.score,
+      positions: m.positions,
+    })
+  }
+
+  // Sort the matched items by score, and then by the populated order.
+  fn sort_matched(&mut self) {
+    self
+      .matched
+      .sort_by(|a, b| b.score.cmp(&a.score).then(a.index.cmp(&b.index)));
+  }
+
+  // Re-calculate the matched items, the first item is selected.
+  fn filter(&mut self) {
+    self.matched = (0..self.items.len())
+      .filter_map(|index| self.match_item(index))
+      .collect();
+    self.sort_matched();
+    self.selected = 0;
+  }
+
+  /// Append the populated items, the selected item is kept.
+  pub fn extend(&mut self, items: Vec<FinderItem>) {
+    let selected = self.matched.get(self.selected).map(|m| m.index);
+    let start = self.items.len();
+    self.items.extend(items);
+    for index in start..self.items.len() {
+      if let Some(matched) = self.match_item(index) {
+        self.matched.push(matched);
+      }
+    }
+    self.sort_matched();
+    self.selected = selected
+      .and_then(|selected| self.matched.iter().position(|m| m.index == selected))
+      .unwrap_or(0);
+  }
+
+  /// All the items are populated.
+  pub fn finish_loading(&mut self) {
+    self.loading = false;
+  }
+
+  /// Append a char to the query.
+  pub fn push_char(&mut self, c: char) {
+    self.query.push(c);
+    self.filter();
+  }
+
+  /// Remove the last char of the query.
+  pub fn pop_char(&mut self) {
+    self.query.pop();
+    self.filter();
+  }
+
+  /// Clear the query.
+  pub fn clear_query(&mut self) {
+    self.query.clear();
+    self.filter();
+  }
+
+  /// Get the index of selected item in the matched items.
+  pub fn selected(&self) -> Option<usize> {
+    if self.matched.is_empty() {
+      None
+    } else {
+      Some(self.selected)
+    }
+  }
+
+  /// Get the selected item, and its index in the populated order.
+  pub fn selected_item(&self) -> Option<(usize, &FinderItem)> {
+    self
+      .matched
+      .get(self.selected)
+      .map(|m| (m.index, &self.items[m.index]))
+  }
+
+  /// Select next item, it wraps to the first one at the end.
+  pub fn select_next(&mut self) {
+    if !self.matched.is_empty() {
+      self.selected = (self.selected + 1) % self.matched.len();
+    }
+  }
+
+  /// Select previous item, it wraps to the last one at the beginning.
+  pub fn select_prev(&mut self) {
+    if !self.matched.is_empty() {
+      self.selected = (self.selected + self.matched.len() - 1) % self.matched.len();
+    }
+  }
+
+  /// Get the first `limit` rows for picker widget, with the matched chars.
+  ///
+  /// NOTE: There can be lots of items, only the rows can be shown are converted.
+  pub fn rows(&self, limit: usize) -> Vec<PickerRow> {
+    self
+      .matched
+      .iter()
+      .take(limit)
+      .map(|m| {
+        let item = &self.items[m.index];
+        PickerRow::new(&item.label, &item.description).with_matches(m.positions.clone())
+      })
+      .collect()
+  }
+}
+
+/// Walk the files in the `dir`, it blocks the current thread until it's finished or cancelled.
+/// The paths (relative to the `dir`) are passed to `on_batch` in the order of file names, it stops
+/// the walk if it returns `false`. The walk also stops once `is_cancelled` returns `true`.
+pub fn walk_files<C, F>(dir: &Path, is_cancelled: C, mut on_batch: F)
+where
+  C: Fn() -> bool,
+  F: FnMut(Vec<FinderItem>) -> bool,
+{
+  let mut batch = vec![];
+  let mut last_sent = Instant::now();
+  let walker = WalkBuilder::new(dir)
+    .sort_by_file_name(|a, b| a.cmp(b))
+    .build();
+  for entry in walker {
+    if is_cancelled() {
+      return;
+    }
+    let entry = match entry {
+      Ok(entry) => entry,
+      Err(e) => {
+        trace!("Skip unreadable entry: {:?}", e);
+        continue;
+      }
+    };
+    if !entry.file_type().is_some_and(|t| t.is_file()) {
+      continue;
+    }
+    let path = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+    batch.push(FinderItem::new(&path.to_string_lossy(), ""));
+    if batch.len() >= BATCH_SIZE || last_sent.elapsed() >= BATCH_INTERVAL {
+      if !on_batch(std::mem::take(&mut batch)) {
+        return;
+      }
+      last_sent = Instant::now();
+    }
+  }
+  if !batch.is_empty() {
+    on_batch(batch);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use assert_fs::prelude::*;
+
+  fn labels(finder: &Finder) -> Vec<String> {
+    finder
+      .rows(usize::MAX)
+      .into_iter()
+      .map(|r| r.label)
+      .collect()
+  }
+
+  #[test]
+  fn finder1() {
+    let mut finder = Finder::new(1, "Files", FinderSource::Items);
+    assert!(finder.take_source().is_none());
+    finder.extend(vec![
+      FinderItem::new("src/main.rs", ""),
+      FinderItem::new("README.md", ""),
+    ]);
+    assert_eq!(finder.display_title(), "Files 2/2...");
+    finder.push_char('m');
+    assert_eq!(labels(&finder), vec!["src/main.rs", "README.md"]);
+    finder.push_char('r');
+    assert_eq!(labels(&finder), vec!["src/main.rs"]);
+    assert_eq!(finder.rows(1)[0].matches, vec![4, 9]);
+
+    // The streamed items are matched, the selected item is kept.
+    finder.pop_char();
+    finder.select_next();
+    assert_eq!(finder.selected_item().unwrap().1.label, "README.md");
+    finder.extend(vec![FinderItem::new("Makefile", "")]);
+    finder.finish_loading();
+    assert_eq!(
+      labels(&finder),
+      vec!["Makefile", "src/main.rs", "README.md"]
+    );
+    assert_eq!(finder.selected_item().unwrap().0, 1);
+    assert_eq!(finder.display_title(), "Files 3/3");
+
+    finder.push_char('z');
+    assert!(finder.selected_item().is_none());
+    finder.clear_query();
+    assert_eq!(finder.selected(), Some(0));
+    finder.select_prev();
+    assert_eq!(finder.selected_item().unwrap().1.label, "Makefile");
+  }
+
+  #[test]
+  fn walk_files1() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    tmp.child(".gitignore").write_str("target\n").unwrap();
+    tmp.child("src/main.rs").write_str("").unwrap();
+    tmp.child("target/debug/rsvim").write_str("").unwrap();
+    tmp.child("Cargo.toml").write_str("").unwrap();
+    std::fs::create_dir(tmp.path().join(".git")).unwrap();
+
+    let mut actual = vec![];
+    walk_files(
+      tmp.path(),
+      || false,
+      |items| {
+        actual.extend(items.into_iter().map(|item| item.label));
+        true
+      },
+    );
+    assert_eq!(actual, vec!["Cargo.toml", "src/main.rs"]);
+
+    let mut count = 0;
+    walk_files(
+      tmp.path(),
+      || true,
+      |_| {
+        count += 1;
+        true
+      },
+    );
+    assert_eq!(count, 0);
+  }
+}
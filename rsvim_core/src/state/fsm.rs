@@ -11,6 +11,12 @@
 //! user, but help maintaining the internal state of the editor:
 //!
 //! * Quit state: The editor should quit on this state.
+//! * Command palette state: The command palette is opened and handles user inputs.
+//! * Finder state: The fuzzy finder is opened and handles user inputs.
+//! * Hint jump state: The jump targets are labeled and wait for the label chars.
+//! * Mark state: Wait for the mark name after `m`, `'` or `` ` ``.
+//! * Surround state: Wait for the target/motion/replacement keys of `ds`/`cs`/`ys`/`S`.
+//! * Window command state: Wait for the window command key after `Ctrl-W`.
 
 use crossterm::event::Event;
 
@@ -20,22 +26,34 @@ use crate::ui::tree::TreeArc;
 
 // Re-export
 pub use crate::state::fsm::command_line::CommandLineStateful;
+pub use crate::state::fsm::command_palette::CommandPaletteStateful;
+pub use crate::state::fsm::finder::FinderStateful;
+pub use crate::state::fsm::hint_jump::HintJumpStateful;
 pub use crate::state::fsm::insert::InsertStateful;
+pub use crate::state::fsm::mark::MarkStateful;
 pub use crate::state::fsm::normal::NormalStateful;
 pub use crate::state::fsm::operator_pending::OperatorPendingStateful;
 pub use crate::state::fsm::quit::QuitStateful;
 pub use crate::state::fsm::select::SelectStateful;
+pub use crate::state::fsm::surround::SurroundStateful;
 pub use crate::state::fsm::terminal::TerminalStateful;
 pub use crate::state::fsm::visual::VisualStateful;
+pub use crate::state::fsm::window_command::WindowCommandStateful;
 
 pub mod command_line;
+pub mod command_palette;
+pub mod finder;
+pub mod hint_jump;
 pub mod insert;
+pub mod mark;
 pub mod normal;
 pub mod operator_pending;
 pub mod quit;
 pub mod select;
+pub mod surround;
 pub mod terminal;
 pub mod visual;
+pub mod window_command;
 
 #[derive(Debug)]
 /// The mutable data passed to each state handler, and allow them access the editor.
@@ -83,6 +101,12 @@ pub enum StatefulValue {
   TerminalMode(TerminalStateful),
   // Internal states.
   QuitState(QuitStateful),
+  CommandPaletteState(CommandPaletteStateful),
+  FinderState(FinderStateful),
+  HintJumpState(HintJumpStateful),
+  MarkState(MarkStateful),
+  SurroundState(SurroundStateful),
+  WindowCommandState(WindowCommandStateful),
 }
 
 impl Default for StatefulValue {
@@ -107,6 +131,12 @@ impl Stateful for StatefulValue {
       StatefulValue::CommandLineMode(s) => s.handle(data_access),
       StatefulValue::TerminalMode(s) => s.handle(data_access),
       StatefulValue::QuitState(s) => s.handle(data_access),
+      StatefulValue::CommandPaletteState(s) => s.handle(data_access),
+      StatefulValue::FinderState(s) => s.handle(data_access),
+      StatefulValue::HintJumpState(s) => s.handle(data_access),
+      StatefulValue::MarkState(s) => s.handle(data_access),
+      StatefulValue::SurroundState(s) => s.handle(data_access),
+      StatefulValue::WindowCommandState(s) => s.handle(data_access),
     }
   }
 }
@@ -1,13 +1,472 @@
 //! The command-line mode.
+//!
+//! It's entered from normal mode by `/` (search forward) or `?` (search backward), the typed
+//! pattern is shown in the command-line widget at the bottom of terminal. The keys are:
+//!
+//! - `ESC`/`CTRL-C`: Cancel the search, restore the cursor and back to normal mode.
+//! - `ENTER`: Search the pattern (or the last pattern if it's empty) and back to normal mode.
+//! - `BACKSPACE`: Delete the char before the cursor, cancel the search if the pattern is empty.
+//! - Other chars: Insert before the cursor.
+//!
+//! It's also entered by `:` to type an ex command (the `'<,'>` range is prefilled in visual mode,
+//! see [`VISUAL_RANGE`](crate::state::fsm::visual::VISUAL_RANGE)), the keys are:
+//!
+//! - `ESC`/`CTRL-C`: Cancel the command and back to normal mode.
+//! - `ENTER`: Execute the command and back to normal mode.
+//! - `TAB`/`CTRL-N`: Complete the command name or file name, by the 'wildmode' option, see
+//!   [`cmdline`](crate::state::cmdline).
+//! - `SHIFT-TAB`/`CTRL-P`: Select the previous completion candidate.
+//! - `CTRL-F`: Open the command-line window, i.e. a window above current window lists the command
+//!   history and the typed command, `ENTER` in normal mode executes the command under the cursor
+//!   and closes the window.
+//! - `BACKSPACE`: Delete the char before the cursor, cancel the command if it's empty.
+//! - Other chars: Insert before the cursor.
+//!
+//! Both the pattern and the command are edited in the readline style (see
+//! [`LineEdit`](crate::state::cmdline::LineEdit)):
+//!
+//! - `CTRL-W`: Delete the word before the cursor.
+//! - `CTRL-U`: Delete all the chars before the cursor.
+//! - `CTRL-A`/`CTRL-B`/`HOME`, `CTRL-E`/`END`: Move the cursor to the start/end.
+//! - `LEFT`/`RIGHT`: Move the cursor one char left/right.
+//! - `CTRL-LEFT`/`SHIFT-LEFT`, `CTRL-RIGHT`/`SHIFT-RIGHT`: Move the cursor one WORD left/right.
+//! - `DELETE`: Delete the char under the cursor.
+//!
+//! The bracketed paste inserts the first line of the pasted text at the cursor.
 
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
+use crate::buf::BuffersManagerArc;
+use crate::cart::U16Size;
+use crate::envar;
+use crate::state::cmdline::{complete, Completion, ExPrompt, LineEdit};
+use crate::state::current_window_buffer;
+use crate::state::ex::ExCommandDataAccess;
+use crate::state::fsm::mark::{jump_to_line, record_jump_from};
+use crate::state::fsm::visual::move_cursor_to;
+use crate::state::fsm::{NormalStateful, Stateful, StatefulDataAccess, StatefulValue};
+use crate::state::msg::MessageLevel;
+use crate::state::paste::normalize_pasted_text;
+use crate::state::search::{find_in_buffer, SearchPosition, SearchPrompt};
+use crate::state::State;
+use crate::ui::tree::internal::Inodeable;
+use crate::ui::tree::layout::LayoutDirection;
+use crate::ui::tree::{Tree, TreeArc, TreeNode, TreeNodeId};
+use crate::ui::widget::cmdline::{bottom_shape, CommandLine};
+use crate::ui::widget::wildmenu::{bar_shape, popup_shape, WildMenu, WildMenuStyle};
+use crate::{rlock, wlock};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ropey::Rope;
+use tracing::{error, trace};
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Copy, Clone, Default)]
 /// The command-line editing mode.
 pub struct CommandLineStateful {}
 
+impl CommandLineStateful {
+  /// Start searching, i.e. insert the command-line widget with the `/` (or `?`) prompt.
+  pub fn start_search(state: &mut State, tree: &TreeArc, forward: bool) -> StatefulValue {
+    let origin = match current_window_buffer(tree) {
+      Some((_, viewport)) => {
+        let viewport = rlock!(viewport);
+        (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+      }
+      None => return StatefulValue::NormalMode(NormalStateful::default()),
+    };
+
+    let prompt = if forward { "/" } else { "?" };
+    let cmdline_id = insert_cmdline(&mut wlock!(tree), prompt);
+    *state.search_mut().prompt_mut() = Some(SearchPrompt {
+      forward,
+      query: String::new(),
+      cursor: 0,
+      origin,
+      cmdline_id,
+    });
+    StatefulValue::CommandLineMode(CommandLineStateful::default())
+  }
+
+  /// Start typing an ex command, i.e. insert the command-line widget with the `:` prompt, and
+  /// the prefilled `text` (i.e. the `'<,'>` range in visual mode).
+  pub fn start_ex(state: &mut State, tree: &TreeArc, text: &str) -> StatefulValue {
+    let cmdline_id = insert_cmdline(&mut wlock!(tree), ":");
+    *state.cmdline_mut().prompt_mut() = Some(ExPrompt {
+      text: text.to_string(),
+      cursor: text.len(),
+      cmdline_id,
+      completion: None,
+      wildmenu_id: None,
+    });
+    Self::update_ex(state, tree);
+    StatefulValue::CommandLineMode(CommandLineStateful::default())
+  }
+
+  // Close the command-line widget, and move the cursor to the `position`.
+  fn finish(state: &mut State, tree: &TreeArc, position: SearchPosition) -> StatefulValue {
+    if let Some(prompt) = state.search_mut().prompt_mut().take() {
+      wlock!(tree).remove(prompt.cmdline_id);
+    }
+    if let Some((_, viewport)) = current_window_buffer(tree) {
+      move_cursor_to(tree, &viewport, position);
+    }
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+
+  // Search the pattern, or the last pattern if it's empty.
+  fn accept(state: &mut State, tree: &TreeArc) -> StatefulValue {
+    let prompt = state.search().prompt().clone().unwrap();
+    let pattern = if prompt.query.is_empty() {
+      match state.search().last() {
+        Some((pattern, _)) => pattern.clone(),
+        None => return Self::finish(state, tree, prompt.origin),
+      }
+    } else {
+      prompt.query.clone()
+    };
+    state.search_mut().set_last(&pattern, prompt.forward);
+    let target = match find_in_buffer(
+      tree,
+      &pattern,
+      prompt.origin,
+      prompt.forward,
+      state.interrupt(),
+    ) {
+      Ok(target) => target,
+      Err(e) => {
+        wlock!(state.messages()).push(MessageLevel::Error, &e.to_string());
+        None
+      }
+    };
+    trace!("Search {:?} to {:?}", pattern, target);
+    if target.is_some() {
+      record_jump_from(tree, prompt.origin);
+    }
+    Self::finish(state, tree, target.unwrap_or(prompt.origin))
+  }
+
+  // Edit the pattern, returns `false` if it's not an editing key.
+  //
+  // NOTE: The search prompt is checked before handling keys.
+  fn edit(state: &mut State, key_event: &KeyEvent) -> bool {
+    let prompt = state.search_mut().prompt_mut().as_mut().unwrap();
+    match LineEdit::from_key(key_event) {
+      Some(edit) => {
+        edit.apply(&mut prompt.query, &mut prompt.cursor);
+        true
+      }
+      None => false,
+    }
+  }
+
+  // Sync the typed pattern to the command-line widget, and move the cursor to the first match if
+  // 'incsearch'.
+  fn update(state: &State, tree: &TreeArc) {
+    let prompt = state.search().prompt().as_ref().unwrap();
+    let incsearch = {
+      let mut tree = wlock!(tree);
+      if let Some(TreeNode::CommandLine(cmdline)) = tree.node_mut(&prompt.cmdline_id) {
+        cmdline.set_text(&prompt.query);
+        cmdline.set_cursor(prompt.cursor);
+      }
+      tree.incsearch()
+    };
+    if incsearch {
+      // NOTE: The incomplete pattern is invalid while typing, thus the errors are skipped.
+      let target = if prompt.query.is_empty() {
+        None
+      } else {
+        find_in_buffer(
+          tree,
+          &prompt.query,
+          prompt.origin,
+          prompt.forward,
+          state.interrupt(),
+        )
+        .unwrap_or(None)
+      };
+      if let Some((_, viewport)) = current_window_buffer(tree) {
+        move_cursor_to(tree, &viewport, target.unwrap_or(prompt.origin));
+      }
+    }
+  }
+
+  // Close the command-line and wildmenu widgets.
+  fn finish_ex(state: &mut State, tree: &TreeArc) -> StatefulValue {
+    if let Some(prompt) = state.cmdline_mut().prompt_mut().take() {
+      let mut tree = wlock!(tree);
+      tree.remove(prompt.cmdline_id);
+      if let Some(wildmenu_id) = prompt.wildmenu_id {
+        tree.remove(wildmenu_id);
+      }
+    }
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+
+  // Execute the typed command.
+  fn accept_ex(state: &mut State, tree: TreeArc, buffers: BuffersManagerArc) -> StatefulValue {
+    let text = state.cmdline().prompt().as_ref().unwrap().text.clone();
+    let next = Self::finish_ex(state, &tree);
+    Self::execute_ex(state, tree, buffers, &text);
+    next
+  }
+
+  // Execute the command, it's saved in the history.
+  fn execute_ex(state: &mut State, tree: TreeArc, buffers: BuffersManagerArc, text: &str) {
+    if !text.trim().is_empty() {
+      let mut data_access = ExCommandDataAccess::new(
+        tree,
+        buffers,
+        state.interrupt().clone(),
+        state.messages().clone(),
+      )
+      .with_registers(state.registers().entries());
+      if let Err(e) = state.ex_commands_mut().execute(text, &mut data_access) {
+        error!("Failed to execute command {:?}:{:?}", text, e);
+        data_access.echo(MessageLevel::Error, &e.to_string());
+      }
+      state.push_scripts(data_access.take_scripts());
+      state.push_events(data_access.take_events());
+      state.request_config_reload(data_access.take_config_reload());
+      state.request_grep(data_access.take_grep());
+      state.request_quit(data_access.take_quit());
+      state.push_shell_reads(data_access.take_shell_reads());
+    }
+  }
+
+  // Open the command-line window, i.e. a scratch buffer lists the command history and the typed
+  // command, in a new window above current window. The cursor is on the typed command.
+  fn open_cmdwin(state: &mut State, tree: TreeArc, buffers: BuffersManagerArc) -> StatefulValue {
+    let text = state.cmdline().prompt().as_ref().unwrap().text.clone();
+    let next = Self::finish_ex(state, &tree);
+    let data_access = ExCommandDataAccess::new(
+      tree.clone(),
+      buffers.clone(),
+      state.interrupt().clone(),
+      state.messages().clone(),
+    );
+    let window_id = match wlock!(tree).split_window(LayoutDirection::Column) {
+      Some(window_id) => window_id,
+      None => {
+        data_access.echo(MessageLevel::Error, "E36: Not enough room");
+        return next;
+      }
+    };
+    let mut lines: Vec<String> = state
+      .ex_commands()
+      .history()
+      .iter()
+      .map(|line| line.to_string())
+      .collect();
+    lines.push(text);
+    let buffer = {
+      let mut buffers = wlock!(buffers);
+      let scratch_id = buffers.new_scratch_buffer(Rope::from_str(&lines.join("\n")), None);
+      buffers.get(&scratch_id).unwrap().clone()
+    };
+    data_access.show_buffer(&buffer);
+    jump_to_line(&tree, usize::MAX);
+    state.cmdline_mut().set_cmdwin(Some(window_id));
+    next
+  }
+
+  /// Whether current window is the command-line window.
+  pub fn in_cmdwin(state: &State, tree: &TreeArc) -> bool {
+    state.cmdline().cmdwin().is_some()
+      && rlock!(tree).current_window_id() == state.cmdline().cmdwin()
+  }
+
+  /// Execute the command under the cursor in the command-line window, and close the window, i.e.
+  /// `ENTER` in the command-line window.
+  pub fn execute_cmdwin(state: &mut State, tree: TreeArc, buffers: BuffersManagerArc) {
+    let text = match current_window_buffer(&tree) {
+      Some((buffer, viewport)) => {
+        let line_idx = rlock!(viewport).cursor().line_idx();
+        let buffer = rlock!(buffer);
+        match buffer.get_line(line_idx) {
+          Some(line) => line.to_string().trim_end_matches(['\n', '\r']).to_string(),
+          None => String::new(),
+        }
+      }
+      None => return,
+    };
+    if let Some(window_id) = state.cmdline().cmdwin() {
+      wlock!(tree).close_window(window_id);
+    }
+    state.cmdline_mut().set_cmdwin(None);
+    Self::execute_ex(state, tree, buffers, &text);
+  }
+
+  // Complete the next (or previous) candidate, the completion starts on the first `Tab`.
+  fn complete_ex(state: &mut State, forward: bool) {
+    let stages = state.cmdline().options().stages().clone();
+    let candidates = {
+      let prompt = state.cmdline().prompt().as_ref().unwrap();
+      match prompt.completion {
+        Some(_) => None,
+        None => complete(&prompt.text, state.ex_commands()),
+      }
+    };
+    let prompt = state.cmdline_mut().prompt_mut().as_mut().unwrap();
+    if let Some((start, candidates)) = candidates {
+      if candidates.is_empty() {
+        return;
+      }
+      prompt.completion = Some(Completion::new(start, &prompt.text[start..], candidates));
+    }
+    if let Some(completion) = prompt.completion.as_mut() {
+      if forward {
+        completion.next(&stages);
+      } else {
+        completion.prev();
+      }
+      prompt.text = format!("{}{}", &prompt.text[..completion.start], completion.word());
+      prompt.cursor = prompt.text.len();
+    }
+    trace!("Complete {:?}", prompt.completion);
+  }
+
+  // Sync the typed command to the command-line widget, and the completion to the wildmenu.
+  fn update_ex(state: &mut State, tree: &TreeArc) {
+    let pum = state.cmdline().options().pum();
+    let prompt = state.cmdline_mut().prompt_mut().as_mut().unwrap();
+    let mut tree = wlock!(tree);
+    if let Some(TreeNode::CommandLine(cmdline)) = tree.node_mut(&prompt.cmdline_id) {
+      cmdline.set_text(&prompt.text);
+      cmdline.set_cursor(prompt.cursor);
+    }
+    if let Some(wildmenu_id) = prompt.wildmenu_id.take() {
+      tree.remove(wildmenu_id);
+    }
+    if let Some(completion) = prompt.completion.as_ref().filter(|c| c.shown) {
+      let terminal_size = terminal_size(&tree);
+      let (shape, style) = if pum {
+        // The prompt `:` occupies the first column.
+        let column = 1 + prompt.text[..completion.start].width();
+        (
+          popup_shape(terminal_size, column, &completion.candidates),
+          WildMenuStyle::Popup,
+        )
+      } else {
+        (bar_shape(terminal_size), WildMenuStyle::Bar)
+      };
+      let wildmenu = WildMenu::new(
+        shape,
+        style,
+        completion.candidates.clone(),
+        completion.selected,
+      );
+      prompt.wildmenu_id = Some(wildmenu.id());
+      let root_id = tree.root_id();
+      tree.bounded_insert(&root_id, TreeNode::WildMenu(wildmenu));
+    }
+  }
+
+  // Insert the first line of the pasted text at the cursor, i.e. the line breaks are not inserted
+  // in the command line.
+  fn paste(state: &mut State, tree: &TreeArc, text: &str) {
+    let text = normalize_pasted_text(text);
+    let line = text.lines().next().unwrap_or_default();
+    let insert = |target: &mut String, cursor: &mut usize| {
+      *cursor = (*cursor).min(target.len());
+      target.insert_str(*cursor, line);
+      *cursor += line.len();
+    };
+    if let Some(prompt) = state.search_mut().prompt_mut().as_mut() {
+      insert(&mut prompt.query, &mut prompt.cursor);
+      Self::update(state, tree);
+    } else if let Some(prompt) = state.cmdline_mut().prompt_mut().as_mut() {
+      prompt.completion = None;
+      insert(&mut prompt.text, &mut prompt.cursor);
+      Self::update_ex(state, tree);
+    }
+  }
+
+  fn handle_ex(
+    state: &mut State,
+    tree: TreeArc,
+    buffers: BuffersManagerArc,
+    key_event: KeyEvent,
+  ) -> StatefulValue {
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    match key_event.code {
+      KeyCode::Char('c') if ctrl => return Self::finish_ex(state, &tree),
+      KeyCode::Esc => return Self::finish_ex(state, &tree),
+      KeyCode::Enter => return Self::accept_ex(state, tree, buffers),
+      KeyCode::Tab => Self::complete_ex(state, true),
+      KeyCode::Char('n') if ctrl => Self::complete_ex(state, true),
+      KeyCode::BackTab => Self::complete_ex(state, false),
+      KeyCode::Char('p') if ctrl => Self::complete_ex(state, false),
+      KeyCode::Char('f') if ctrl => return Self::open_cmdwin(state, tree, buffers),
+      KeyCode::Backspace if state.cmdline().prompt().as_ref().unwrap().text.is_empty() => {
+        return Self::finish_ex(state, &tree);
+      }
+      _ => {
+        if let Some(edit) = LineEdit::from_key(&key_event) {
+          let prompt = state.cmdline_mut().prompt_mut().as_mut().unwrap();
+          prompt.completion = None;
+          edit.apply(&mut prompt.text, &mut prompt.cursor);
+        }
+      }
+    }
+    Self::update_ex(state, &tree);
+    StatefulValue::CommandLineMode(CommandLineStateful::default())
+  }
+}
+
+// Get the terminal size, i.e. the root container size.
+fn terminal_size(tree: &Tree) -> U16Size {
+  let root_shape = tree.node(&tree.root_id()).unwrap().actual_shape();
+  U16Size::new(root_shape.width(), root_shape.height())
+}
+
+// Insert the command-line widget with the `prompt`.
+fn insert_cmdline(tree: &mut Tree, prompt: &str) -> TreeNodeId {
+  let cmdline = CommandLine::new(bottom_shape(terminal_size(tree)), prompt);
+  let cmdline_id = cmdline.id();
+  let root_id = tree.root_id();
+  tree.bounded_insert(&root_id, TreeNode::CommandLine(cmdline));
+  cmdline_id
+}
+
 impl Stateful for CommandLineStateful {
-  fn handle(&self, _data_access: StatefulDataAccess) -> StatefulValue {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let state = data_access.state;
+    let tree = data_access.tree;
+    let buffers = data_access.buffers;
+    let event = data_access.event;
+
+    let key_event = match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+      Event::Paste(ref paste_string) => {
+        Self::paste(state, &tree, paste_string);
+        return StatefulValue::CommandLineMode(CommandLineStateful::default());
+      }
+      _ => return StatefulValue::CommandLineMode(CommandLineStateful::default()),
+    };
+
+    let origin = match state.search().prompt() {
+      Some(prompt) => prompt.origin,
+      None if state.cmdline().prompt().is_some() => {
+        return Self::handle_ex(state, tree, buffers, key_event);
+      }
+      None => return StatefulValue::NormalMode(NormalStateful::default()),
+    };
+    match key_event.code {
+      KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+        return Self::finish(state, &tree, origin);
+      }
+      KeyCode::Esc => return Self::finish(state, &tree, origin),
+      KeyCode::Enter => return Self::accept(state, &tree),
+      KeyCode::Backspace if state.search().prompt().as_ref().unwrap().query.is_empty() => {
+        return Self::finish(state, &tree, origin);
+      }
+      _ => {
+        if !Self::edit(state, &key_event) {
+          return StatefulValue::CommandLineMode(CommandLineStateful::default());
+        }
+      }
+    }
+    Self::update(state, &tree);
     StatefulValue::CommandLineMode(CommandLineStateful::default())
   }
 }
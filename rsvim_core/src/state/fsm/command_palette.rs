@@ -0,0 +1,184 @@
+//! The command palette state.
+//!
+//! It's an internal state (not an editing mode), entered from normal mode by `CTRL-P` (the named
+//! sessions are also listed, i.e. the session switcher), or when files are dropped to the terminal
+//! in normal mode (only the items to open them are listed). The keys are:
+//!
+//! - `ESC`/`CTRL-C`: Close the palette and back to normal mode.
+//! - `ENTER`: Accept the selected item, i.e. execute the ex command or feed the key to normal
+//!   mode.
+//! - `UP`/`CTRL-P`, `DOWN`/`CTRL-N`: Select previous/next item.
+//! - `BACKSPACE`: Delete the last char of the query.
+//! - Other chars: Append to the query.
+
+use crate::buf::BuffersManagerArc;
+use crate::cart::U16Size;
+use crate::envar;
+use crate::state::ex::ExCommandDataAccess;
+use crate::state::fsm::{NormalStateful, Stateful, StatefulDataAccess, StatefulValue};
+use crate::state::msg::MessageLevel;
+use crate::state::palette::{
+  collect_items, dropped_items, session_items, CommandPalette, PaletteAction,
+};
+use crate::state::session::{list_sessions, sessions_dir};
+use crate::state::State;
+use crate::ui::tree::internal::Inodeable;
+use crate::ui::tree::{TreeArc, TreeNode};
+use crate::ui::widget::picker::{centered_shape, Picker};
+use crate::wlock;
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use std::path::PathBuf;
+use tracing::error;
+
+/// The picker title.
+const TITLE: &str = "Command Palette";
+
+/// The picker title for the dropped files.
+const DROPPED_FILES_TITLE: &str = "Dropped Files";
+
+#[derive(Debug, Copy, Clone, Default)]
+/// The command palette state.
+pub struct CommandPaletteStateful {}
+
+impl CommandPaletteStateful {
+  /// Open the command palette, i.e. insert the picker widget on top of the windows. The named
+  /// sessions are listed after the ex commands and key mappings.
+  pub fn open(state: &mut State, tree: TreeArc) -> StatefulValue {
+    let mut items = collect_items(state.ex_commands());
+    items.extend(session_items(&list_sessions(&sessions_dir())));
+    let palette = CommandPalette::new(items);
+    Self::open_with(state, tree, palette, TITLE)
+  }
+
+  /// Open the command palette to offer opening the dropped files, see
+  /// [`parse_dropped_paths`](crate::state::paste::parse_dropped_paths).
+  pub fn open_dropped_files(state: &mut State, tree: TreeArc, paths: &[PathBuf]) -> StatefulValue {
+    let palette = CommandPalette::new(dropped_items(paths));
+    Self::open_with(state, tree, palette, DROPPED_FILES_TITLE)
+  }
+
+  fn open_with(
+    state: &mut State,
+    tree: TreeArc,
+    palette: CommandPalette,
+    title: &str,
+  ) -> StatefulValue {
+    let mut tree = wlock!(tree);
+    let root_id = tree.root_id();
+    let terminal_size = {
+      let root_shape = tree.node(&root_id).unwrap().actual_shape();
+      U16Size::new(root_shape.width(), root_shape.height())
+    };
+    let mut picker = Picker::new(centered_shape(terminal_size), title);
+    picker.set_contents(palette.query(), palette.rows(), palette.selected());
+    let picker_id = picker.id();
+    tree.bounded_insert(&root_id, TreeNode::Picker(picker));
+    *state.command_palette_mut() = Some((palette, picker_id));
+    StatefulValue::CommandPaletteState(CommandPaletteStateful::default())
+  }
+
+  // Close the command palette, i.e. remove the picker widget.
+  fn close(state: &mut State, tree: &TreeArc) {
+    if let Some((_, picker_id)) = state.command_palette_mut().take() {
+      wlock!(tree).remove(picker_id);
+    }
+  }
+
+  // Sync the palette to the picker widget.
+  fn update_picker(state: &State, tree: &TreeArc) {
+    if let Some((palette, picker_id)) = state.command_palette() {
+      if let Some(TreeNode::Picker(picker)) = wlock!(tree).node_mut(picker_id) {
+        picker.set_contents(palette.query(), palette.rows(), palette.selected());
+      }
+    }
+  }
+
+  // NOTE: The command palette is checked before handling keys.
+  fn palette_mut(state: &mut State) -> &mut CommandPalette {
+    &mut state.command_palette_mut().as_mut().unwrap().0
+  }
+
+  // Accept the selected item.
+  fn accept(state: &mut State, tree: TreeArc, buffers: BuffersManagerArc) -> StatefulValue {
+    let selected = state
+      .command_palette()
+      .as_ref()
+      .and_then(|(palette, _)| palette.selected_item().cloned());
+    Self::close(state, &tree);
+
+    match selected {
+      Some(item) => match item.action() {
+        PaletteAction::ExCommand => {
+          let mut data_access = ExCommandDataAccess::new(
+            tree,
+            buffers,
+            state.interrupt().clone(),
+            state.messages().clone(),
+          )
+          .with_registers(state.registers().entries());
+          if let Err(e) = state
+            .ex_commands_mut()
+            .execute(item.label(), &mut data_access)
+          {
+            error!("Failed to execute command {:?}:{:?}", item.label(), e);
+            data_access.echo(MessageLevel::Error, &e.to_string());
+          }
+          state.push_scripts(data_access.take_scripts());
+          state.push_events(data_access.take_events());
+          state.request_config_reload(data_access.take_config_reload());
+          state.request_grep(data_access.take_grep());
+          state.request_quit(data_access.take_quit());
+          state.push_shell_reads(data_access.take_shell_reads());
+          StatefulValue::NormalMode(NormalStateful::default())
+        }
+        PaletteAction::Key(key_event) => {
+          let data_access = StatefulDataAccess::new(state, tree, buffers, Event::Key(key_event));
+          NormalStateful::default().handle(data_access)
+        }
+      },
+      None => StatefulValue::NormalMode(NormalStateful::default()),
+    }
+  }
+}
+
+impl Stateful for CommandPaletteStateful {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let state = data_access.state;
+    let tree = data_access.tree;
+    let buffers = data_access.buffers;
+    let event = data_access.event;
+
+    if state.command_palette().is_none() {
+      return StatefulValue::NormalMode(NormalStateful::default());
+    }
+
+    if let Event::Key(key_event) = event {
+      if key_event.kind != KeyEventKind::Press {
+        return StatefulValue::CommandPaletteState(CommandPaletteStateful::default());
+      }
+      let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+      match key_event.code {
+        KeyCode::Esc => {
+          Self::close(state, &tree);
+          return StatefulValue::NormalMode(NormalStateful::default());
+        }
+        KeyCode::Char('c') if ctrl => {
+          Self::close(state, &tree);
+          return StatefulValue::NormalMode(NormalStateful::default());
+        }
+        KeyCode::Enter => return Self::accept(state, tree, buffers),
+        KeyCode::Up => Self::palette_mut(state).select_prev(),
+        KeyCode::Char('p') if ctrl => Self::palette_mut(state).select_prev(),
+        KeyCode::Down => Self::palette_mut(state).select_next(),
+        KeyCode::Char('n') if ctrl => Self::palette_mut(state).select_next(),
+        KeyCode::Backspace => Self::palette_mut(state).pop_char(),
+        KeyCode::Char(c) if !ctrl => Self::palette_mut(state).push_char(c),
+        _ => { /* Skip */ }
+      }
+      Self::update_picker(state, &tree);
+    }
+
+    StatefulValue::CommandPaletteState(CommandPaletteStateful::default())
+  }
+}
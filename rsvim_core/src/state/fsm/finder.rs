@@ -0,0 +1,166 @@
+//! The fuzzy finder state.
+//!
+//! It's an internal state (not an editing mode), entered when the finder is opened by the
+//! `Rsvim.picker.open` JS API, see [`Finder`]. Once the finder is closed, it goes back to the
+//! state before it's opened. The keys are:
+//!
+//! - `ESC`/`CTRL-C`: Close the finder, i.e. cancel.
+//! - `ENTER`: Accept the selected item.
+//! - `UP`/`CTRL-P`/`SHIFT-TAB`, `DOWN`/`CTRL-N`/`TAB`: Select previous/next item.
+//! - `BACKSPACE`: Delete the last char of the query.
+//! - `CTRL-U`: Clear the query.
+//! - Other chars: Append to the query.
+
+use crate::cart::U16Size;
+use crate::envar;
+use crate::state::finder::{Finder, FinderId, FinderItem, FinderResult};
+use crate::state::fsm::{NormalStateful, Stateful, StatefulDataAccess, StatefulValue};
+use crate::state::State;
+use crate::ui::tree::internal::Inodeable;
+use crate::ui::tree::{TreeArc, TreeNode};
+use crate::ui::widget::picker::{centered_shape, Picker, PICKER_MAX_HEIGHT};
+use crate::wlock;
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+
+#[derive(Debug, Copy, Clone, Default)]
+/// The fuzzy finder state.
+pub struct FinderStateful {}
+
+impl FinderStateful {
+  /// Open the finder, i.e. insert the picker widget on top of the windows. The opened finder is
+  /// cancelled and replaced.
+  ///
+  /// Returns the finder state, and it goes back to `back` once the finder is closed.
+  pub fn open(
+    state: &mut State,
+    tree: &TreeArc,
+    finder: Finder,
+    back: StatefulValue,
+  ) -> StatefulValue {
+    let back = match state.finder().as_ref().map(|(_, _, back)| *back) {
+      Some(opened_back) => {
+        Self::close(state, tree, false);
+        opened_back
+      }
+      None => back,
+    };
+
+    let picker_id = {
+      let mut tree = wlock!(tree);
+      let root_id = tree.root_id();
+      let terminal_size = {
+        let root_shape = tree.node(&root_id).unwrap().actual_shape();
+        U16Size::new(root_shape.width(), root_shape.height())
+      };
+      let picker = Picker::new(centered_shape(terminal_size), &finder.display_title());
+      let picker_id = picker.id();
+      tree.bounded_insert(&root_id, TreeNode::Picker(picker));
+      picker_id
+    };
+    *state.finder_mut() = Some((finder, picker_id, back));
+    Self::update_picker(state, tree);
+    StatefulValue::FinderState(FinderStateful::default())
+  }
+
+  /// Close the finder, i.e. remove the picker widget. The selected item is accepted if `accept` is
+  /// `true`, the result is passed to the JS callback by the event loop.
+  ///
+  /// Returns the state before the finder is opened.
+  pub fn close(state: &mut State, tree: &TreeArc, accept: bool) -> StatefulValue {
+    match state.finder_mut().take() {
+      Some((finder, picker_id, back)) => {
+        wlock!(tree).remove(picker_id);
+        let accepted = if accept {
+          finder
+            .selected_item()
+            .map(|(index, item)| (index, item.clone()))
+        } else {
+          None
+        };
+        state.push_finder_result(FinderResult {
+          finder_id: finder.id(),
+          accepted,
+        });
+        back
+      }
+      None => StatefulValue::NormalMode(NormalStateful::default()),
+    }
+  }
+
+  /// Append the populated items to the finder `finder_id`, and finish the population if `done`.
+  ///
+  /// Returns `false` if the finder is already closed.
+  pub fn populate(
+    state: &mut State,
+    tree: &TreeArc,
+    finder_id: FinderId,
+    items: Vec<FinderItem>,
+    done: bool,
+  ) -> bool {
+    match state.finder_mut() {
+      Some((finder, _, _)) if finder.id() == finder_id => {
+        finder.extend(items);
+        if done {
+          finder.finish_loading();
+        }
+      }
+      _ => return false,
+    }
+    Self::update_picker(state, tree);
+    true
+  }
+
+  /// Sync the finder to the picker widget, i.e. after the query is changed or the items are
+  /// populated.
+  pub fn update_picker(state: &State, tree: &TreeArc) {
+    if let Some((finder, picker_id, _)) = state.finder() {
+      if let Some(TreeNode::Picker(picker)) = wlock!(tree).node_mut(picker_id) {
+        // Only the rows can be shown are converted, the picker scrolls to the selected row.
+        let limit = finder.selected().unwrap_or(0) + PICKER_MAX_HEIGHT as usize;
+        picker.set_title(&finder.display_title());
+        picker.set_contents(finder.query(), finder.rows(limit), finder.selected());
+      }
+    }
+  }
+
+  // NOTE: The finder is checked before handling keys.
+  fn finder_mut(state: &mut State) -> &mut Finder {
+    &mut state.finder_mut().as_mut().unwrap().0
+  }
+}
+
+impl Stateful for FinderStateful {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let state = data_access.state;
+    let tree = data_access.tree;
+    let event = data_access.event;
+
+    if state.finder().is_none() {
+      return StatefulValue::NormalMode(NormalStateful::default());
+    }
+
+    if let Event::Key(key_event) = event {
+      if key_event.kind != KeyEventKind::Press {
+        return StatefulValue::FinderState(FinderStateful::default());
+      }
+      let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+      match key_event.code {
+        KeyCode::Esc => return Self::close(state, &tree, false),
+        KeyCode::Char('c') if ctrl => return Self::close(state, &tree, false),
+        KeyCode::Enter => return Self::close(state, &tree, true),
+        KeyCode::Up | KeyCode::BackTab => Self::finder_mut(state).select_prev(),
+        KeyCode::Char('p') if ctrl => Self::finder_mut(state).select_prev(),
+        KeyCode::Down | KeyCode::Tab => Self::finder_mut(state).select_next(),
+        KeyCode::Char('n') if ctrl => Self::finder_mut(state).select_next(),
+        KeyCode::Backspace => Self::finder_mut(state).pop_char(),
+        KeyCode::Char('u') if ctrl => Self::finder_mut(state).clear_query(),
+        KeyCode::Char(c) if !ctrl => Self::finder_mut(state).push_char(c),
+        _ => { /* Skip */ }
+      }
+      Self::update_picker(state, &tree);
+    }
+
+    StatefulValue::FinderState(FinderStateful::default())
+  }
+}
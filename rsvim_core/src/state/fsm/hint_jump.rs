@@ -0,0 +1,143 @@
+//! The hint jump state.
+//!
+//! It's an internal state (not an editing mode), entered from normal mode by `s` (two-char
+//! search) or `S` (word starts), see [`HintJump`](crate::state::hint::HintJump). The keys are:
+//!
+//! - `ESC`/`CTRL-C`: Cancel and back to normal mode.
+//! - Other chars: The search chars, or the label chars of the jump target.
+
+use crate::envar;
+use crate::state::current_window_buffer;
+use crate::state::fsm::visual::move_cursor_to;
+use crate::state::fsm::{NormalStateful, Stateful, StatefulDataAccess, StatefulValue};
+use crate::state::hint::{find_pattern_starts, find_word_starts, HintInput, HintJump, HintKind};
+use crate::state::State;
+use crate::ui::tree::TreeArc;
+use crate::{rlock, wlock};
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use std::collections::BTreeMap;
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, Default)]
+/// The hint jump state.
+pub struct HintJumpStateful {}
+
+impl HintJumpStateful {
+  /// Start hint jump, the word starts are labeled immediately, while the two-char search waits
+  /// for the chars.
+  pub fn start(state: &mut State, tree: &TreeArc, kind: HintKind) -> StatefulValue {
+    let mut hint = HintJump::new(kind);
+    if kind == HintKind::Word && !Self::label(&mut hint, tree, None) {
+      return StatefulValue::NormalMode(NormalStateful::default());
+    }
+    *state.hint_jump_mut() = Some(hint);
+    StatefulValue::HintJumpState(HintJumpStateful::default())
+  }
+
+  // Cancel hint jump, i.e. clear the labels.
+  fn cancel(state: &mut State, tree: &TreeArc) -> StatefulValue {
+    state.hint_jump_mut().take();
+    if let Some((_, viewport)) = current_window_buffer(tree) {
+      wlock!(viewport).set_hint_labels(BTreeMap::new());
+    }
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+
+  // Label the visible targets (word starts, or occurrences of the pattern) in current window.
+  //
+  // Returns `false` if there's no target.
+  fn label(hint: &mut HintJump, tree: &TreeArc, pattern: Option<&str>) -> bool {
+    let (buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return false,
+    };
+
+    let (line_range, cursor) = {
+      let viewport = rlock!(viewport);
+      if viewport.is_empty() {
+        return false;
+      }
+      (
+        viewport.start_line_idx()..viewport.end_line_idx(),
+        (viewport.cursor().line_idx(), viewport.cursor().char_idx()),
+      )
+    };
+    let candidates = {
+      let buffer = rlock!(buffer);
+      let mut candidates = vec![];
+      for line_idx in line_range {
+        if let Some(line) = buffer.get_line_content(line_idx) {
+          let starts = match pattern {
+            Some(pattern) => find_pattern_starts(&line, pattern),
+            None => find_word_starts(&line),
+          };
+          candidates.extend(starts.into_iter().map(|char_idx| (line_idx, char_idx)));
+        }
+      }
+      candidates
+    };
+
+    // NOTE: The buffer lock must be released before locking viewport.
+    let mut viewport = wlock!(viewport);
+    let targets = candidates
+      .into_iter()
+      .filter(|(line_idx, char_idx)| viewport.is_char_visible(*line_idx, *char_idx))
+      .collect();
+    let labeled = hint.label_targets(targets, cursor);
+    trace!("Hint jump labels:{:?}", hint.labels());
+    viewport.set_hint_labels(hint.labels());
+    labeled
+  }
+}
+
+impl Stateful for HintJumpStateful {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let state = data_access.state;
+    let tree = data_access.tree;
+    let event = data_access.event;
+
+    if state.hint_jump().is_none() {
+      return StatefulValue::NormalMode(NormalStateful::default());
+    }
+
+    let key_event = match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+      _ => return StatefulValue::HintJumpState(HintJumpStateful::default()),
+    };
+    let c = match key_event.code {
+      KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+        return Self::cancel(state, &tree);
+      }
+      KeyCode::Char(c) => c,
+      _ => return Self::cancel(state, &tree),
+    };
+
+    let mut hint = state.hint_jump_mut().take().unwrap();
+    match hint.input(c) {
+      HintInput::Pending => {
+        if let Some((_, viewport)) = current_window_buffer(&tree) {
+          wlock!(viewport).set_hint_labels(hint.labels());
+        }
+        *state.hint_jump_mut() = Some(hint);
+        StatefulValue::HintJumpState(HintJumpStateful::default())
+      }
+      HintInput::Search(pattern) => {
+        if !Self::label(&mut hint, &tree, Some(&pattern)) {
+          return Self::cancel(state, &tree);
+        }
+        *state.hint_jump_mut() = Some(hint);
+        StatefulValue::HintJumpState(HintJumpStateful::default())
+      }
+      HintInput::Jump(target) => {
+        trace!("Hint jump to {:?}", target);
+        if let Some((_, viewport)) = current_window_buffer(&tree) {
+          wlock!(viewport).set_hint_labels(BTreeMap::new());
+          move_cursor_to(&tree, &viewport, target);
+        }
+        StatefulValue::NormalMode(NormalStateful::default())
+      }
+      HintInput::Cancel => Self::cancel(state, &tree),
+    }
+  }
+}
@@ -1,13 +1,208 @@
 //! The insert mode.
+//!
+//! The keys are:
+//!
+//! - `ESC`/`CTRL-C`: Back to normal mode, the cursor moves left by one char.
+//! - `ENTER`/`TAB`: Insert line break/tab.
+//! - `BACKSPACE`: Delete the char before the cursor.
+//! - Other chars: Insert the char, the brackets and quotes are paired if the 'autopairs' option is
+//!   on, see [`autopair`](crate::state::autopair).
 
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
+use crate::envar;
+use crate::state::autopair::{self, InsertRecord, PairEdit};
+use crate::state::current_window_buffer;
+use crate::state::fsm::visual::move_cursor_to;
+use crate::state::fsm::{NormalStateful, Stateful, StatefulDataAccess, StatefulValue};
+use crate::state::paste::normalize_pasted_text;
+use crate::state::State;
+use crate::ui::tree::TreeArc;
+use crate::{rlock, wlock};
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use tracing::trace;
 
 #[derive(Debug, Copy, Clone, Default)]
 /// The insert editing mode.
 pub struct InsertStateful {}
 
 impl Stateful for InsertStateful {
-  fn handle(&self, _data_access: StatefulDataAccess) -> StatefulValue {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let state = data_access.state;
+    let tree = data_access.tree;
+    let event = data_access.event;
+
+    match event {
+      Event::Paste(ref paste_string) => {
+        // NOTE: The pasted text is inserted literally, i.e. key mappings, abbreviations,
+        // auto-indent, auto-pairs and text-width are not applied when paste mode is active.
+        debug_assert!(state.paste().is_active());
+        let text = normalize_pasted_text(paste_string);
+        self.insert_text(state, &tree, &text, text.chars().count());
+      }
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => match key_event.code {
+        KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+          return self.stop(&tree);
+        }
+        KeyCode::Esc => return self.stop(&tree),
+        KeyCode::Enter => self.insert_text(state, &tree, "\n", 1),
+        KeyCode::Tab => self.insert_text(state, &tree, "\t", 1),
+        KeyCode::Backspace => self.backspace(state, &tree),
+        KeyCode::Char(c)
+          if !key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+          self.type_char(state, &tree, c)
+        }
+        _ => { /* Skip */ }
+      },
+      _ => { /* Skip */ }
+    }
+
     StatefulValue::InsertMode(InsertStateful::default())
   }
 }
+
+impl InsertStateful {
+  // Back to normal mode, the cursor moves left by one char.
+  fn stop(&self, tree: &TreeArc) -> StatefulValue {
+    if let Some((_, viewport)) = current_window_buffer(tree) {
+      let (line_idx, char_idx) = {
+        let viewport = rlock!(viewport);
+        (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+      };
+      move_cursor_to(tree, &viewport, (line_idx, char_idx.saturating_sub(1)));
+    }
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+
+  // Type the char `c`, it's paired if the 'autopairs' option is on.
+  fn type_char(&self, state: &mut State, tree: &TreeArc, c: char) {
+    let edit = if rlock!(tree).autopairs() {
+      let (buffer, viewport) = match current_window_buffer(tree) {
+        Some(current) => current,
+        None => return,
+      };
+      let (line_idx, char_idx) = {
+        let viewport = rlock!(viewport);
+        (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+      };
+      let buffer = rlock!(buffer);
+      let line = buffer.get_line_content(line_idx).unwrap_or_default();
+      let extension = buffer
+        .filename()
+        .as_ref()
+        .and_then(|filename| filename.extension())
+        .and_then(|extension| extension.to_str());
+      autopair::on_char(&line, char_idx, c, extension)
+    } else {
+      None
+    };
+    trace!("Type char {:?}: {:?}", c, edit);
+
+    match edit {
+      Some(PairEdit::Pair(open, close)) => {
+        self.insert_text(state, tree, &format!("{}{}", open, close), 1)
+      }
+      Some(PairEdit::Skip) => {
+        if let Some((_, viewport)) = current_window_buffer(tree) {
+          let (line_idx, char_idx) = {
+            let viewport = rlock!(viewport);
+            (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+          };
+          move_cursor_to(tree, &viewport, (line_idx, char_idx + 1));
+          state.insert_record_mut().skip(c);
+        }
+      }
+      None => self.insert_text(state, tree, &c.to_string(), 1),
+    }
+  }
+
+  /// Insert text at the cursor of current window, and the cursor moves `cursor` chars forward.
+  fn insert_text(&self, state: &mut State, tree: &TreeArc, text: &str, cursor: usize) {
+    let (buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return,
+    };
+
+    let cursor_pos = *rlock!(viewport).cursor();
+    let position = {
+      let mut buffer = wlock!(buffer);
+      if buffer.get_line(cursor_pos.line_idx()).is_none() {
+        return;
+      }
+      let char_idx = buffer.line_to_char(cursor_pos.line_idx()) + cursor_pos.char_idx();
+      trace!("Insert text at {:?}: {:?}", char_idx, text);
+      buffer.insert(char_idx, text);
+      let char_idx = char_idx + cursor;
+      let line_idx = buffer.char_to_line(char_idx);
+      (line_idx, char_idx - buffer.line_to_char(line_idx))
+    };
+    state.insert_record_mut().insert(text, cursor);
+    // NOTE: The buffer lock must be released before syncing viewport.
+    wlock!(viewport).sync();
+    move_cursor_to(tree, &viewport, position);
+  }
+
+  // Delete the char before the cursor, and the closing char if it's an empty pair and the
+  // 'autopairs' option is on.
+  fn backspace(&self, state: &mut State, tree: &TreeArc) {
+    let autopairs = rlock!(tree).autopairs();
+    let (buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return,
+    };
+
+    let cursor_pos = *rlock!(viewport).cursor();
+    let (position, pair) = {
+      let mut buffer = wlock!(buffer);
+      if buffer.get_line(cursor_pos.line_idx()).is_none() {
+        return;
+      }
+      let char_idx = buffer.line_to_char(cursor_pos.line_idx()) + cursor_pos.char_idx();
+      if char_idx == 0 {
+        return;
+      }
+      let pair = autopairs
+        && buffer
+          .get_line_content(cursor_pos.line_idx())
+          .is_some_and(|line| autopair::on_backspace(&line, cursor_pos.char_idx()));
+      let end_char_idx = if pair { char_idx + 1 } else { char_idx };
+      trace!("Backspace at {:?}, pair:{:?}", char_idx, pair);
+      buffer.remove(char_idx - 1..end_char_idx);
+      let char_idx = char_idx - 1;
+      let line_idx = buffer.char_to_line(char_idx);
+      ((line_idx, char_idx - buffer.line_to_char(line_idx)), pair)
+    };
+    state.insert_record_mut().backspace(pair);
+    // NOTE: The buffer lock must be released before syncing viewport.
+    wlock!(viewport).sync();
+    move_cursor_to(tree, &viewport, position);
+  }
+}
+
+/// Replay the insert mode session `record` at the cursor of current window, i.e. dot-repeat. The
+/// cursor is put on the last inserted char, same with leaving insert mode.
+pub fn replay_insert(tree: &TreeArc, record: &InsertRecord) {
+  let (buffer, viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return,
+  };
+  let cursor_pos = *rlock!(viewport).cursor();
+  let position = {
+    let mut buffer = wlock!(buffer);
+    if buffer.get_line(cursor_pos.line_idx()).is_none() {
+      return;
+    }
+    let char_idx = buffer.line_to_char(cursor_pos.line_idx()) + cursor_pos.char_idx();
+    buffer.undo_tree_mut().begin_group();
+    let char_idx = record.replay(&mut buffer, char_idx);
+    buffer.undo_tree_mut().end_group();
+    let char_idx = std::cmp::min(char_idx.saturating_sub(1), buffer.len_chars());
+    let line_idx = buffer.char_to_line(char_idx);
+    (line_idx, char_idx - buffer.line_to_char(line_idx))
+  };
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+  move_cursor_to(tree, &viewport, position);
+}
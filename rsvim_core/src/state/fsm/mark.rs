@@ -0,0 +1,347 @@
+//! The mark state.
+//!
+//! It's an internal state (not an editing mode), entered from normal mode by `m` (set mark), `'`
+//! (jump to the first non-blank char of the mark's line) or `` ` `` (jump to the mark's exact
+//! position), then waits for the mark name, see [`mark`](crate::buf::mark). Other keys cancel and
+//! back to normal mode.
+//!
+//! The jumps are recorded in the window's [`JumpList`](crate::ui::widget::window::jumplist::JumpList),
+//! and navigated with `Ctrl-O`/`Ctrl-I` in normal mode.
+//!
+//! The absolute line jumps (`:{N}`, `{N}G`, `{N}gg`) are recorded as well, see [`jump_to_line`].
+//!
+//! The last position of a buffer (i.e. the `"` mark) is recorded when it's hidden from the window,
+//! and restored when it's shown again, see [`position`](crate::buf::position).
+
+use crate::buf::mark::is_global_name;
+use crate::buf::{BufferArc, BuffersManagerArc, LastPosition, MarkPos};
+use crate::state::current_window_buffer;
+use crate::state::fsm::visual::move_cursor_to;
+use crate::state::fsm::{NormalStateful, Stateful, StatefulDataAccess, StatefulValue};
+use crate::ui::tree::{TreeArc, TreeNode, TreeNodeId};
+use crate::ui::widget::window::jumplist::{Jump, JumpList};
+use crate::ui::widget::window::{Viewport, ViewportArc};
+use crate::{envar, rlock, wlock};
+
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use std::sync::Arc;
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The action on the mark name.
+pub enum MarkAction {
+  /// `m`
+  Set,
+  /// `'`
+  JumpLine,
+  /// `` ` ``
+  JumpExact,
+}
+
+#[derive(Debug, Copy, Clone)]
+/// The mark state.
+pub struct MarkStateful {
+  action: MarkAction,
+}
+
+impl MarkStateful {
+  /// Start waiting for the mark name.
+  pub fn start(action: MarkAction) -> StatefulValue {
+    StatefulValue::MarkState(MarkStateful { action })
+  }
+}
+
+impl Stateful for MarkStateful {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let tree = data_access.tree;
+    let buffers = data_access.buffers;
+    let event = data_access.event;
+
+    let key_event = match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+      _ => return StatefulValue::MarkState(*self),
+    };
+    if let KeyCode::Char(name) = key_event.code {
+      let done = match self.action {
+        MarkAction::Set => set_mark(&tree, &buffers, name),
+        MarkAction::JumpLine => jump_to_mark(&tree, &buffers, name, false),
+        MarkAction::JumpExact => jump_to_mark(&tree, &buffers, name, true),
+      };
+      trace!("Mark {:?} {:?}:{:?}", self.action, name, done);
+    }
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+}
+
+// Get the buffer, viewport and cursor position of current window.
+fn current_jump(tree: &TreeArc) -> Option<(BufferArc, ViewportArc, Jump)> {
+  let (buffer, viewport) = current_window_buffer(tree)?;
+  let buffer_id = rlock!(buffer).id();
+  let pos = {
+    let viewport = rlock!(viewport);
+    MarkPos::new(viewport.cursor().line_idx(), viewport.cursor().char_idx())
+  };
+  Some((buffer, viewport, Jump::new(buffer_id, pos)))
+}
+
+/// Record the `position` (i.e. line index and char index) before a jump, in the jumplist of
+/// current window, and as the `'` mark of current buffer.
+pub fn record_jump_from(tree: &TreeArc, position: (usize, usize)) {
+  let (buffer, _viewport, mut jump) = match current_jump(tree) {
+    Some(current) => current,
+    None => return,
+  };
+  jump.pos = MarkPos::new(position.0, position.1);
+  wlock!(buffer).marks_mut().set('\'', jump.pos);
+  let mut tree = wlock!(tree);
+  if let Some(window_id) = tree.current_window_id() {
+    if let Some(TreeNode::Window(window)) = tree.node_mut(&window_id) {
+      window.jumplist_mut().push(jump);
+    }
+  }
+}
+
+/// Same with [`record_jump_from`], records the cursor position.
+pub fn record_jump(tree: &TreeArc) {
+  if let Some((_, _, jump)) = current_jump(tree) {
+    record_jump_from(tree, (jump.pos.line_idx, jump.pos.char_idx));
+  }
+}
+
+/// Set the mark `name` at the cursor position of current window, returns `false` if the name is
+/// invalid.
+pub fn set_mark(tree: &TreeArc, buffers: &BuffersManagerArc, name: char) -> bool {
+  match current_jump(tree) {
+    Some((_, _, jump)) => wlock!(buffers).set_mark(&jump.buffer_id, name, jump.pos),
+    None => false,
+  }
+}
+
+/// Jump to the mark `name`, the global mark (`A`-`Z`) shows its buffer in current window. The
+/// cursor is moved to the exact position if `exact`, otherwise the first non-blank char of the
+/// line.
+///
+/// Returns `false` if the mark is not set.
+pub fn jump_to_mark(tree: &TreeArc, buffers: &BuffersManagerArc, name: char, exact: bool) -> bool {
+  let target = if is_global_name(name) {
+    rlock!(buffers).global_mark(name)
+  } else {
+    current_jump(tree).and_then(|(buffer, _, jump)| {
+      let pos = rlock!(buffer).marks().get(name)?;
+      Some((jump.buffer_id, pos))
+    })
+  };
+  match target {
+    Some((buffer_id, pos)) => {
+      // NOTE: The target is read first, thus `''` jumps back and forth.
+      record_jump(tree);
+      jump_to(tree, buffers, Jump::new(buffer_id, pos), exact)
+    }
+    None => false,
+  }
+}
+
+/// Go to the older position in the jumplist of current window, i.e. `Ctrl-O`.
+pub fn jump_older(tree: &TreeArc, buffers: &BuffersManagerArc) -> bool {
+  let from = match current_jump(tree) {
+    Some((_, _, jump)) => jump,
+    None => return false,
+  };
+  let target = navigate_jumplist(tree, |jumplist| jumplist.back(from));
+  match target {
+    Some(jump) => jump_to(tree, buffers, jump, true),
+    None => false,
+  }
+}
+
+/// Go to the newer position in the jumplist of current window, i.e. `Ctrl-I`.
+pub fn jump_newer(tree: &TreeArc, buffers: &BuffersManagerArc) -> bool {
+  let target = navigate_jumplist(tree, |jumplist| jumplist.forward());
+  match target {
+    Some(jump) => jump_to(tree, buffers, jump, true),
+    None => false,
+  }
+}
+
+fn navigate_jumplist<F>(tree: &TreeArc, f: F) -> Option<Jump>
+where
+  F: FnOnce(&mut JumpList) -> Option<Jump>,
+{
+  let mut tree = wlock!(tree);
+  let window_id = tree.current_window_id()?;
+  match tree.node_mut(&window_id) {
+    Some(TreeNode::Window(window)) => f(window.jumplist_mut()),
+    _ => None,
+  }
+}
+
+// Move the cursor to the jump position, the buffer is shown in current window if it's not. The
+// position is clamped if the lines are deleted.
+fn jump_to(tree: &TreeArc, buffers: &BuffersManagerArc, jump: Jump, exact: bool) -> bool {
+  let buffer = match rlock!(buffers).get(&jump.buffer_id) {
+    Some(buffer) => buffer.clone(),
+    None => return false,
+  };
+  let (current_buffer, viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return false,
+  };
+  if !Arc::ptr_eq(&current_buffer, &buffer) {
+    save_last_position(tree, buffers);
+    let mut tree = wlock!(tree);
+    if let Some(window_id) = tree.current_window_id() {
+      if let Some(TreeNode::Window(window)) = tree.node_mut(&window_id) {
+        window.set_buffer(Arc::downgrade(&buffer));
+      }
+    }
+  }
+
+  let position = {
+    let buffer = rlock!(buffer);
+    let line_idx = std::cmp::min(jump.pos.line_idx, buffer.len_lines().saturating_sub(1));
+    let line = buffer.get_line_content(line_idx).unwrap_or_default();
+    let char_idx = if exact {
+      std::cmp::min(jump.pos.char_idx, line.chars().count().saturating_sub(1))
+    } else {
+      line.chars().position(|c| !c.is_whitespace()).unwrap_or(0)
+    };
+    (line_idx, char_idx)
+  };
+  move_cursor_to(tree, &viewport, position);
+  true
+}
+
+/// Record the cursor position and the viewport's top line of the window `window_id`, as the last
+/// position of its buffer, i.e. the `"` mark. The file buffer's position is also recorded in the
+/// [`PositionStore`](crate::buf::PositionStore).
+pub fn save_window_position(tree: &TreeArc, buffers: &BuffersManagerArc, window_id: TreeNodeId) {
+  let (buffer, viewport) = match rlock!(tree).node(&window_id) {
+    Some(TreeNode::Window(window)) => match window.buffer().upgrade() {
+      Some(buffer) => (buffer, window.viewport()),
+      None => return,
+    },
+    _ => return,
+  };
+  let (pos, top_line_idx) = {
+    let viewport = rlock!(viewport);
+    (
+      MarkPos::new(viewport.cursor().line_idx(), viewport.cursor().char_idx()),
+      viewport.start_line_idx(),
+    )
+  };
+  let file = {
+    let mut buffer = wlock!(buffer);
+    buffer.marks_mut().set('"', pos);
+    match buffer.remote() {
+      Some(_) => None,
+      None => buffer.absolute_filename().clone(),
+    }
+  };
+  if let Some(file) = file {
+    trace!("Save last position {:?}:{:?}", file, pos);
+    wlock!(buffers)
+      .positions_mut()
+      .set(&file, LastPosition::new(pos, top_line_idx));
+  }
+}
+
+/// Same with [`save_window_position`], records the last position of current window's buffer.
+pub fn save_last_position(tree: &TreeArc, buffers: &BuffersManagerArc) {
+  let window_id = rlock!(tree).current_window_id();
+  if let Some(window_id) = window_id {
+    save_window_position(tree, buffers, window_id);
+  }
+}
+
+/// Restore the last position (i.e. the `"` mark) of current window's buffer, the viewport's top
+/// line is restored as well if it's recorded. The position is clamped if the lines are deleted.
+///
+/// Returns `false` if the buffer doesn't have the last position.
+pub fn restore_last_position(tree: &TreeArc, buffers: &BuffersManagerArc) -> bool {
+  let (buffer, viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return false,
+  };
+  let (position, file, len_lines) = {
+    let buffer = rlock!(buffer);
+    let pos = match buffer.marks().get('"') {
+      Some(pos) => pos,
+      None => return false,
+    };
+    let len_lines = buffer.len_lines();
+    let line_idx = std::cmp::min(pos.line_idx, len_lines.saturating_sub(1));
+    let line = buffer.get_line_content(line_idx).unwrap_or_default();
+    let char_idx = std::cmp::min(pos.char_idx, line.chars().count().saturating_sub(1));
+    (
+      (line_idx, char_idx),
+      buffer.absolute_filename().clone(),
+      len_lines,
+    )
+  };
+  let last = file.and_then(|file| rlock!(buffers).positions().get(&file));
+  if let Some(last) = last {
+    let top_line_idx = std::cmp::min(last.top_line_idx, position.0);
+    let mut viewport = wlock!(viewport);
+    let start_dcol_idx = horizontal_anchor(&viewport);
+    viewport.sync_from_top_left(
+      std::cmp::min(top_line_idx, len_lines.saturating_sub(1)),
+      start_dcol_idx,
+    );
+  }
+  trace!("Restore last position {:?}", position);
+  move_cursor_to(tree, &viewport, position);
+  true
+}
+
+// The `start_dcolumn` anchor kept when the viewport scrolls to another start line. When 'wrap' is
+// `true`, it's the skipped rows of current start line, thus it's reset.
+fn horizontal_anchor(viewport: &Viewport) -> usize {
+  if viewport.options().wrap {
+    0
+  } else {
+    viewport.start_dcol_idx()
+  }
+}
+
+/// Jump to the first non-blank char of the line, i.e. `:{N}`, `{N}G` and `{N}gg`. The line is
+/// clamped to the buffer, and the viewport is centered on it by the 'jumpcenter' option, see
+/// [`JumpCenter`](crate::ui::tree::JumpCenter).
+///
+/// Returns `false` if there's no current window.
+pub fn jump_to_line(tree: &TreeArc, line_idx: usize) -> bool {
+  let (buffer, viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return false,
+  };
+  let (position, len_lines) = {
+    let buffer = rlock!(buffer);
+    // The empty line after the trailing line break is not counted.
+    let mut len_lines = buffer.len_lines();
+    if len_lines > 1
+      && buffer
+        .get_line(len_lines - 1)
+        .is_some_and(|l| l.len_chars() == 0)
+    {
+      len_lines -= 1;
+    }
+    let line_idx = std::cmp::min(line_idx, len_lines.saturating_sub(1));
+    let line = buffer.get_line_content(line_idx).unwrap_or_default();
+    let char_idx = line.chars().position(|c| !c.is_whitespace()).unwrap_or(0);
+    ((line_idx, char_idx), len_lines)
+  };
+  record_jump(tree);
+
+  let jump_center = rlock!(tree).jump_center();
+  {
+    let mut viewport = wlock!(viewport);
+    let visible = viewport.start_line_idx()..viewport.end_line_idx();
+    let height = viewport.actual_shape().height() as usize;
+    if let Some(start_line_idx) = jump_center.start_line_idx(position.0, visible, height, len_lines)
+    {
+      let start_dcol_idx = horizontal_anchor(&viewport);
+      viewport.sync_from_top_left(start_line_idx, start_dcol_idx);
+    }
+  }
+  move_cursor_to(tree, &viewport, position);
+  true
+}
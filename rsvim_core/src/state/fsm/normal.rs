@@ -2,26 +2,67 @@
 
 #![allow(unused_imports)]
 
+use crate::buf::BuffersManagerArc;
 use crate::envar;
+use crate::res::ExCommandErr;
 use crate::state::command::Command;
+use crate::state::current_window_buffer;
+use crate::state::cursor_word::find_cursor_word;
+use crate::state::ex::{explorer, inspector, spell, undotree, ExCommandDataAccess};
+use crate::state::fsm::command_line::CommandLineStateful;
+use crate::state::fsm::command_palette::CommandPaletteStateful;
+use crate::state::fsm::hint_jump::HintJumpStateful;
+use crate::state::fsm::insert::replay_insert;
+use crate::state::fsm::mark::{
+  jump_newer, jump_older, jump_to_line, record_jump, MarkAction, MarkStateful,
+};
+use crate::state::fsm::operator_pending::{
+  apply_target, repeat_change, Operator, OperatorPendingStateful,
+};
 use crate::state::fsm::quit::QuitStateful;
+use crate::state::fsm::surround::{self, SurroundStateful};
+use crate::state::fsm::visual::{clamp_position, move_cursor_to, VisualStateful};
+use crate::state::fsm::window_command::WindowCommandStateful;
 use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
+use crate::state::hint::HintKind;
 use crate::state::mode::Mode;
-use crate::ui::tree::TreeNode;
+use crate::state::motion::{self, Motion};
+use crate::state::mouse;
+use crate::state::msg::MessageLevel;
+use crate::state::paste::{normalize_pasted_text, parse_dropped_paths};
+use crate::state::put::{self, PutCommand};
+use crate::state::register::Register;
+use crate::state::repeat::{LastChange, OperatorTarget};
+use crate::state::search::find_in_buffer;
+use crate::state::visual::VisualKind;
+use crate::state::State;
+use crate::ui::tree::internal::Inodeable;
+use crate::ui::tree::{TreeArc, TreeNode};
 use crate::ui::widget::window::CursorViewport;
-use crate::wlock;
+use crate::{rlock, wlock};
 
-use crossterm::event::{Event, KeyCode, KeyEventKind, KeyEventState, KeyModifiers};
+use crossterm::event::{
+  Event, KeyCode, KeyEventKind, KeyEventState, KeyModifiers, MouseButton, MouseEventKind,
+};
+use geo::point;
+use path_absolutize::Absolutize;
+use std::path::PathBuf;
 use std::time::Duration;
 
 #[derive(Debug, Copy, Clone, Default)]
 /// The normal editing mode.
-pub struct NormalStateful {}
+pub struct NormalStateful {
+  // The typed count, i.e. the `10` in `10G`, `0` means no count.
+  count: usize,
+  // The typed prefix, i.e. the `g` of `gg`, the `z` of `zh`.
+  prefix: Option<char>,
+}
 
 impl Stateful for NormalStateful {
   fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
-    let _state = data_access.state;
+    let state = data_access.state;
     let tree = data_access.tree;
+    let buffers = data_access.buffers;
     let event = data_access.event;
 
     match event {
@@ -29,13 +70,200 @@ impl Stateful for NormalStateful {
       Event::FocusLost => {}
       Event::Key(key_event) => match key_event.kind {
         KeyEventKind::Press => {
+          match (self.prefix, key_event.code) {
+            (Some('g'), KeyCode::Char('g')) => {
+              // Go to the line `count` (default to the first line)
+              jump_to_line(&tree, self.count.max(1) - 1);
+            }
+            (Some('z'), KeyCode::Char(c @ ('h' | 'l' | 'H' | 'L'))) => {
+              // Scroll horizontally by `count` columns, or half the window width
+              let columns = match c {
+                'h' | 'l' => self.count.max(1) as isize,
+                _ => self.half_width(&tree) as isize,
+              };
+              let columns = if c == 'h' || c == 'H' {
+                -columns
+              } else {
+                columns
+              };
+              self.scroll_horizontally(&tree, columns);
+            }
+            (Some('z'), KeyCode::Char('g')) => {
+              // Add the word under cursor to the user spell dictionary
+              self.spell_good(state, &tree, &buffers);
+            }
+            (Some(prefix @ ('g' | ']' | '[')), KeyCode::Char(c @ ('p' | 'P'))) => {
+              // Put with the cursor after the new text, or with the indent adjusted
+              self.put(state, &tree, put::parse(Some(prefix), c).unwrap());
+            }
+            (Some(_), _) => { /* Cancel */ }
+            (None, _) => { /* No prefix */ }
+          }
+          if self.prefix.is_some() {
+            return StatefulValue::NormalMode(NormalStateful::default());
+          }
           match key_event.code {
+            KeyCode::Char(c @ '0'..='9')
+              if key_event.modifiers.is_empty() && (c != '0' || self.count > 0) =>
+            {
+              // Count
+              let digit = c.to_digit(10).unwrap() as usize;
+              return StatefulValue::NormalMode(NormalStateful {
+                count: self.count.saturating_mul(10).saturating_add(digit),
+                prefix: None,
+              });
+            }
+            KeyCode::Char(c @ ('g' | 'z' | ']' | '[')) if key_event.modifiers.is_empty() => {
+              // Wait for the second key of `gg`, `zh`, `]p`, etc
+              return StatefulValue::NormalMode(NormalStateful {
+                count: self.count,
+                prefix: Some(c),
+              });
+            }
+            KeyCode::Char('G') => {
+              // Go to the line `count` (default to the last line)
+              let line_idx = if self.count > 0 {
+                self.count - 1
+              } else {
+                usize::MAX
+              };
+              jump_to_line(&tree, line_idx);
+            }
+            KeyCode::Char('p') if key_event.modifiers == KeyModifiers::CONTROL => {
+              // Open command palette
+              return CommandPaletteStateful::open(state, tree);
+            }
+            KeyCode::Char(c @ ('p' | 'P')) => {
+              // Put after or before the cursor
+              self.put(state, &tree, put::parse(None, c).unwrap());
+            }
+            KeyCode::Char('v') if key_event.modifiers == KeyModifiers::CONTROL => {
+              // Blockwise visual mode
+              return VisualStateful::start(&tree, VisualKind::Blockwise);
+            }
+            KeyCode::Char('v') => {
+              // Charwise visual mode
+              return VisualStateful::start(&tree, VisualKind::Charwise);
+            }
+            KeyCode::Char('V') => {
+              // Linewise visual mode
+              return VisualStateful::start(&tree, VisualKind::Linewise);
+            }
+            KeyCode::Char('r') if key_event.modifiers == KeyModifiers::CONTROL => {
+              // Redo
+              self.undo_redo(&tree, true);
+            }
+            KeyCode::Char('w') if key_event.modifiers == KeyModifiers::CONTROL => {
+              // Window command
+              return WindowCommandStateful::start();
+            }
+            KeyCode::Char('l') if key_event.modifiers == KeyModifiers::CONTROL => {
+              // Clear and redraw the screen
+              wlock!(tree).request_redraw(true);
+            }
+            KeyCode::Char('o') if key_event.modifiers == KeyModifiers::CONTROL => {
+              // Older position in jumplist
+              jump_older(&tree, &buffers);
+            }
+            KeyCode::Tab => {
+              // Newer position in jumplist, i.e. `Ctrl-I`
+              jump_newer(&tree, &buffers);
+            }
+            KeyCode::Char('i') if key_event.modifiers == KeyModifiers::CONTROL => {
+              // Newer position in jumplist
+              jump_newer(&tree, &buffers);
+            }
+            KeyCode::Enter | KeyCode::Char('-' | '%' | 'R' | 'D' | 's' | 'r')
+              if explorer::in_explorer(&tree) =>
+            {
+              // The keys of the directory buffer
+              return self.explorer_key(state, tree, buffers, key_event.code);
+            }
+            KeyCode::Enter if undotree::in_undotree(&tree) => {
+              // Jump to the undo state under the cursor
+              return self.run_ex_command(state, tree, buffers, "UndotreeJump");
+            }
+            KeyCode::Enter if inspector::in_marks(&tree) => {
+              // Jump to the mark under the cursor
+              return self.run_ex_command(state, tree, buffers, "MarksJump");
+            }
+            KeyCode::Char('m') => {
+              // Set mark
+              return MarkStateful::start(MarkAction::Set);
+            }
+            KeyCode::Char('\'') => {
+              // Jump to the line of mark
+              return MarkStateful::start(MarkAction::JumpLine);
+            }
+            KeyCode::Char('`') => {
+              // Jump to the position of mark
+              return MarkStateful::start(MarkAction::JumpExact);
+            }
+            KeyCode::Char('u') => {
+              // Undo
+              self.undo_redo(&tree, false);
+            }
+            KeyCode::Char(c @ ('d' | 'c' | 'y')) => {
+              // Operators, wait for the motion or text object
+              return OperatorPendingStateful::start(Operator::from_char(c).unwrap(), self.count);
+            }
+            KeyCode::Char('x') | KeyCode::Delete => {
+              // Delete `count` chars, i.e. `dl`
+              return apply_target(
+                state,
+                &tree,
+                Operator::Delete,
+                OperatorTarget::Motion(Motion::Right),
+                self.count,
+              );
+            }
+            KeyCode::Char(c @ ('w' | 'W' | 'e' | 'E' | 'b' | 'B' | '0' | '^' | '$')) => {
+              // Word and line motions
+              self.move_by(&tree, motion::parse(c).unwrap());
+            }
+            KeyCode::Char('.') => {
+              // Repeat last change
+              self.repeat_last_change(state, &tree);
+            }
+            KeyCode::Char('s') => {
+              // Hint jump to two-char search targets
+              return HintJumpStateful::start(state, &tree, HintKind::Pair);
+            }
+            KeyCode::Char('S') => {
+              // Hint jump to word starts
+              return HintJumpStateful::start(state, &tree, HintKind::Word);
+            }
+            KeyCode::Char(':') => {
+              // Type an ex command
+              return CommandLineStateful::start_ex(state, &tree, "");
+            }
+            KeyCode::Enter if CommandLineStateful::in_cmdwin(state, &tree) => {
+              // Execute the command under the cursor in the command-line window
+              CommandLineStateful::execute_cmdwin(state, tree, buffers);
+              return StatefulValue::NormalMode(NormalStateful::default());
+            }
+            KeyCode::Char('/') => {
+              // Search forward
+              return CommandLineStateful::start_search(state, &tree, true);
+            }
+            KeyCode::Char('?') => {
+              // Search backward
+              return CommandLineStateful::start_search(state, &tree, false);
+            }
+            KeyCode::Char('n') => {
+              // Next match
+              self.search_next(state, &tree, false);
+            }
+            KeyCode::Char('N') => {
+              // Previous match
+              self.search_next(state, &tree, true);
+            }
             KeyCode::Up | KeyCode::Char('k') => {
               // Up
               let mut tree = wlock!(tree);
               match tree.cursor_id() {
                 Some(cursor_id) => {
-                  tree.bounded_move_up_by(cursor_id, 1);
+                  tree.bounded_move_up_by(cursor_id, self.count.max(1));
                 }
                 None => { /* Skip */ }
               }
@@ -45,7 +273,7 @@ impl Stateful for NormalStateful {
               let mut tree = wlock!(tree);
               match tree.cursor_id() {
                 Some(cursor_id) => {
-                  tree.bounded_move_down_by(cursor_id, 1);
+                  tree.bounded_move_down_by(cursor_id, self.count.max(1));
                 }
                 None => { /* Skip */ }
               }
@@ -55,7 +283,7 @@ impl Stateful for NormalStateful {
               let mut tree = wlock!(tree);
               match tree.cursor_id() {
                 Some(cursor_id) => {
-                  tree.bounded_move_left_by(cursor_id, 1);
+                  tree.bounded_move_left_by(cursor_id, self.count.max(1));
                 }
                 None => { /* Skip */ }
               }
@@ -65,7 +293,7 @@ impl Stateful for NormalStateful {
               let mut tree = wlock!(tree);
               match tree.cursor_id() {
                 Some(cursor_id) => {
-                  tree.bounded_move_right_by(cursor_id, 1);
+                  tree.bounded_move_right_by(cursor_id, self.count.max(1));
                 }
                 None => { /* Skip */ }
               }
@@ -76,8 +304,45 @@ impl Stateful for NormalStateful {
         KeyEventKind::Repeat => {}
         KeyEventKind::Release => {}
       },
-      Event::Mouse(_mouse_event) => {}
-      Event::Paste(ref _paste_string) => {}
+      Event::Mouse(mouse_event) => {
+        let (column, row) = (mouse_event.column, mouse_event.row);
+        match mouse_event.kind {
+          MouseEventKind::Down(button) => {
+            if button == MouseButton::Left && self.click_widgets(state, &tree, column, row) {
+              return StatefulValue::NormalMode(NormalStateful::default());
+            }
+            // Click to move cursor and focus window.
+            mouse::click(state, &tree, column, row, button);
+          }
+          MouseEventKind::Drag(MouseButton::Left) => {
+            // Drag to start visual mode at the clicked char.
+            if let Some(position) = mouse::position_at(&tree, column, row) {
+              if let StatefulValue::VisualMode(visual) =
+                VisualStateful::start(&tree, VisualKind::Charwise)
+              {
+                VisualStateful::drag_to(&tree, position);
+                return StatefulValue::VisualMode(visual);
+              }
+            }
+          }
+          MouseEventKind::ScrollUp => {
+            mouse::scroll(&tree, column, row, -(mouse::SCROLL_LINES as isize));
+          }
+          MouseEventKind::ScrollDown => {
+            mouse::scroll(&tree, column, row, mouse::SCROLL_LINES as isize);
+          }
+          _ => { /* Skip */ }
+        }
+      }
+      Event::Paste(ref paste_string) => {
+        // Offer to open the dropped files.
+        if let Some(paths) = self.dropped_files(&tree, &buffers, paste_string) {
+          return CommandPaletteStateful::open_dropped_files(state, tree, &paths);
+        }
+        // Otherwise put the pasted text after the cursor, i.e. `p`.
+        let register = Register::from_text(normalize_pasted_text(paste_string));
+        put_text(&tree, &register, put::parse(None, 'p').unwrap(), 1);
+      }
       Event::Resize(_columns, _rows) => {}
     }
 
@@ -95,9 +360,312 @@ impl Stateful for NormalStateful {
   }
 }
 
+impl NormalStateful {
+  // Add the word under cursor to the user spell dictionary, i.e. `zg`.
+  fn spell_good(&self, state: &State, tree: &TreeArc, buffers: &BuffersManagerArc) {
+    let word = current_window_buffer(tree).and_then(|(buffer, viewport)| {
+      let (line_idx, char_idx) = {
+        let viewport = rlock!(viewport);
+        (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+      };
+      rlock!(buffer)
+        .get_line_content(line_idx)
+        .and_then(|line| find_cursor_word(&line, char_idx))
+    });
+    let result = match word {
+      Some(word) => spell::add_good_word(buffers, state.messages(), &word),
+      None => Err(ExCommandErr::Message(
+        "E348: No string under cursor".to_string(),
+      )),
+    };
+    if let Err(e) = result {
+      wlock!(state.messages()).push(MessageLevel::Error, &e.to_string());
+    }
+  }
+
+  // Handle the keys of the directory buffer, see [`explorer`](crate::state::ex::explorer).
+  fn explorer_key(
+    &self,
+    state: &mut State,
+    tree: TreeArc,
+    buffers: BuffersManagerArc,
+    code: KeyCode,
+  ) -> StatefulValue {
+    let command = match code {
+      KeyCode::Enter => "ExploreOpen",
+      KeyCode::Char('-') => "ExploreUp",
+      KeyCode::Char('s') => "ExploreSort",
+      KeyCode::Char('r') => "ExploreSort!",
+      KeyCode::Char('%') => return CommandLineStateful::start_ex(state, &tree, "ExploreNew "),
+      KeyCode::Char('D') => return CommandLineStateful::start_ex(state, &tree, "ExploreDelete"),
+      KeyCode::Char('R') => {
+        return match explorer::entry_under_cursor(&tree) {
+          Some(name) => {
+            CommandLineStateful::start_ex(state, &tree, &format!("ExploreRename {}", name))
+          }
+          None => StatefulValue::NormalMode(NormalStateful::default()),
+        }
+      }
+      _ => return StatefulValue::NormalMode(NormalStateful::default()),
+    };
+    self.run_ex_command(state, tree, buffers, command)
+  }
+
+  // Run the ex command, i.e. the keys of the directory buffer, the undo tree buffer and the marks
+  // buffer.
+  fn run_ex_command(
+    &self,
+    state: &mut State,
+    tree: TreeArc,
+    buffers: BuffersManagerArc,
+    command: &str,
+  ) -> StatefulValue {
+    let mut data_access = ExCommandDataAccess::new(
+      tree,
+      buffers,
+      state.interrupt().clone(),
+      state.messages().clone(),
+    );
+    if let Err(e) = state.ex_commands().run(command, &mut data_access) {
+      data_access.echo(MessageLevel::Error, &e.to_string());
+    }
+    state.push_events(data_access.take_events());
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+
+  // Undo/redo the last change of current buffer, and move cursor to the changed position.
+  fn undo_redo(&self, tree: &TreeArc, redo: bool) {
+    let (buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return,
+    };
+    let position = {
+      let mut buffer = wlock!(buffer);
+      let char_idx = if redo { buffer.redo() } else { buffer.undo() };
+      match char_idx {
+        Some(char_idx) => {
+          let char_idx = std::cmp::min(char_idx, buffer.len_chars());
+          let line_idx = buffer.char_to_line(char_idx);
+          (line_idx, char_idx - buffer.line_to_char(line_idx))
+        }
+        None => return,
+      }
+    };
+    // NOTE: The buffer lock must be released before syncing viewport.
+    wlock!(viewport).sync();
+    move_cursor_to(tree, &viewport, position);
+  }
+
+  // Half of current window width, at least 1.
+  fn half_width(&self, tree: &TreeArc) -> usize {
+    match current_window_buffer(tree) {
+      Some((_, viewport)) => (rlock!(viewport).actual_shape().width() as usize / 2).max(1),
+      None => 1,
+    }
+  }
+
+  // Scroll current window horizontally by `columns` (negative is leftward) when 'wrap' is
+  // `false`, the cursor moves to keep it visible, see
+  // [`nearest_visible_char`](crate::ui::widget::window::Viewport::nearest_visible_char).
+  // Click on the floating widgets, i.e. the progress cancel button and the minimap. Returns `true`
+  // if the click is handled.
+  fn click_widgets(&self, state: &mut State, tree: &TreeArc, column: u16, row: u16) -> bool {
+    // Click on the progress cancel button.
+    let mut tree = wlock!(tree);
+    let progress = state.progress_mut();
+    if progress.cancel_at(&tree, column, row).is_some() {
+      progress.sync_widget(&mut tree);
+      return true;
+    }
+
+    // Click on minimap to jump.
+    let pos = point!(x: column, y: row);
+    let window_ids = tree.window_ids().iter().copied().collect::<Vec<_>>();
+    for window_id in window_ids {
+      let jumped = match tree.node_mut(&window_id) {
+        Some(TreeNode::Window(window)) => match window.minimap_line_at(pos) {
+          Some(line_idx) => {
+            window.jump_to_line(line_idx);
+            Some(window.content_offset() as isize)
+          }
+          None => None,
+        },
+        _ => None,
+      };
+      if let Some(content_offset) = jumped {
+        // Move cursor to the top-left corner of the window content.
+        if let Some(cursor_id) = tree.cursor_id() {
+          if tree.parent_id(&cursor_id) == Some(&window_id) {
+            let cursor_pos = tree.node(&cursor_id).unwrap().shape().min();
+            tree.bounded_move_by(cursor_id, content_offset - cursor_pos.x, -cursor_pos.y);
+          }
+        }
+        return true;
+      }
+    }
+    false
+  }
+
+  fn scroll_horizontally(&self, tree: &TreeArc, columns: isize) {
+    let (_buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return,
+    };
+    let position = {
+      let mut viewport = wlock!(viewport);
+      if viewport.options().wrap || viewport.is_empty() {
+        return;
+      }
+      let start_dcol_idx = (viewport.start_dcol_idx() as isize + columns).max(0) as usize;
+      if start_dcol_idx == viewport.start_dcol_idx() {
+        return;
+      }
+      let start_line_idx = viewport.start_line_idx();
+      viewport.sync_from_top_left(start_line_idx, start_dcol_idx);
+      let line_idx = viewport.cursor().line_idx();
+      let char_idx = viewport.cursor().char_idx();
+      (line_idx, viewport.nearest_visible_char(line_idx, char_idx))
+    };
+    move_cursor_to(tree, &viewport, position);
+  }
+
+  // Move cursor by the `motion` with the typed count.
+  fn move_by(&self, tree: &TreeArc, motion: Motion) {
+    let (buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return,
+    };
+    let cursor = {
+      let viewport = rlock!(viewport);
+      (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+    };
+    let position = {
+      let buffer = rlock!(buffer);
+      let position = motion::target(&buffer, cursor, motion, self.count);
+      clamp_position(buffer.len_lines(), |l| buffer.get_line_content(l), position)
+    };
+    move_cursor_to(tree, &viewport, position);
+  }
+
+  // Repeat the last change at the cursor, as one undo state. The typed count replaces the count of
+  // the change.
+  fn repeat_last_change(&self, state: &mut State, tree: &TreeArc) {
+    match state.last_change().clone() {
+      Some(LastChange::Insert(record)) => {
+        replay_insert(tree, &record);
+      }
+      Some(LastChange::Surround(command)) => {
+        surround::run(tree, &command);
+      }
+      Some(LastChange::Operator(change)) => {
+        repeat_change(state, tree, &change, self.count);
+      }
+      Some(LastChange::Put { command, count }) => {
+        let count = if self.count > 0 { self.count } else { count };
+        put_register(state, tree, command, count);
+      }
+      None => { /* Skip */ }
+    }
+  }
+
+  // Put the unnamed register with the typed count, and save it as the last change.
+  fn put(&self, state: &mut State, tree: &TreeArc, command: PutCommand) {
+    put_register(state, tree, command, self.count);
+    state.set_last_change(LastChange::Put {
+      command,
+      count: self.count,
+    });
+  }
+
+  // Parse the pasted text as the dropped files, i.e. all the paths exist and the current buffer is
+  // not modified.
+  fn dropped_files(
+    &self,
+    tree: &TreeArc,
+    buffers: &BuffersManagerArc,
+    text: &str,
+  ) -> Option<Vec<PathBuf>> {
+    let paths = parse_dropped_paths(text)?;
+    {
+      let buffers = rlock!(buffers);
+      let exists = |path: &PathBuf| match path.absolutize() {
+        Ok(abs_path) => buffers.fs().exists(&abs_path).unwrap_or(false),
+        Err(_) => false,
+      };
+      if !paths.iter().all(exists) {
+        return None;
+      }
+    }
+    let (buffer, _viewport) = current_window_buffer(tree)?;
+    if rlock!(buffer).is_modified() {
+      return None;
+    }
+    Some(paths)
+  }
+
+  // Move cursor to the next match of last search pattern, in the opposite direction if `reverse`.
+  fn search_next(&self, state: &State, tree: &TreeArc, reverse: bool) {
+    let (pattern, forward) = match state.search().last() {
+      Some(last) => last.clone(),
+      None => return,
+    };
+    let viewport = match current_window_buffer(tree) {
+      Some((_, viewport)) => viewport,
+      None => return,
+    };
+    let cursor = {
+      let viewport = rlock!(viewport);
+      (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+    };
+    match find_in_buffer(
+      tree,
+      &pattern,
+      cursor,
+      forward != reverse,
+      state.interrupt(),
+    ) {
+      Ok(Some(target)) => {
+        record_jump(tree);
+        move_cursor_to(tree, &viewport, target);
+      }
+      Ok(None) => { /* Skip */ }
+      Err(e) => wlock!(state.messages()).push(MessageLevel::Error, &e.to_string()),
+    }
+  }
+}
+
+// Put the unnamed register `count` times at the cursor of current window, as one undo state.
+fn put_register(state: &State, tree: &TreeArc, command: PutCommand, count: usize) {
+  if let Some(register) = state.register('"') {
+    put_text(tree, &register, command, count);
+  }
+}
+
+// Put the `register` text at the cursor of current window, as one undo state.
+fn put_text(tree: &TreeArc, register: &Register, command: PutCommand, count: usize) {
+  let (buffer, viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return,
+  };
+  let cursor = {
+    let viewport = rlock!(viewport);
+    (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+  };
+  let position = {
+    let mut buffer = wlock!(buffer);
+    buffer.undo_tree_mut().begin_group();
+    let position = put::put(&mut buffer, cursor, register, count, command);
+    buffer.undo_tree_mut().end_group();
+    clamp_position(buffer.len_lines(), |l| buffer.get_line_content(l), position)
+  };
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+  move_cursor_to(tree, &viewport, position);
+}
+
 //impl NormalStateful {
 //  fn handle_cursor_move(&self, data_access: StatefulDataAccess, command: Command) {
-//    let _state = data_access.state;
+//    let state = data_access.state;
 //    let tree = data_access.tree;
 //
 //    let mut tree = wlock!(tree);
@@ -1,13 +1,351 @@
 //! The operator-pending mode.
+//!
+//! It's entered from normal mode by the operators `d` (delete), `c` (change) and `y` (yank), then
+//! waits for a motion (see [`motion`](crate::state::motion)) or a text object (see
+//! [`textobj`](crate::state::textobj)), and the operator is applied to the text. The keys are:
+//!
+//! - `{count}`: The motion count, it's multiplied with the operator count, i.e. `2d3w` deletes 6
+//!   words.
+//! - The motions, and the same key as the operator, i.e. `dd`, `cc`, `yy` operate current line.
+//! - `i{object}`/`a{object}`: The text objects.
+//! - `s`: The surround commands, i.e. `ds`, `cs`, `ys`, see [`surround`](crate::state::surround).
+//! - `ESC`/`CTRL-C`, or other keys: Cancel and back to normal mode.
 
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
+use crate::envar;
+use crate::state::current_window_buffer;
+use crate::state::fsm::insert::replay_insert;
+use crate::state::fsm::visual::{clamp_position, move_cursor_to, start_position, update_selection};
+use crate::state::fsm::{
+  InsertStateful, NormalStateful, Stateful, StatefulDataAccess, StatefulValue, SurroundStateful,
+};
+use crate::state::motion::{self, Motion};
+use crate::state::register::Register;
+use crate::state::repeat::{LastChange, OperatorChange, OperatorTarget};
+use crate::state::surround::SurroundInput;
+use crate::state::textobj;
+use crate::state::visual::{delete_selection, selected_text, Selection, VisualKind};
+use crate::state::State;
+use crate::ui::tree::TreeArc;
+use crate::{rlock, wlock};
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// The operator.
+pub enum Operator {
+  /// `d`
+  #[default]
+  Delete,
+  /// `c`
+  Change,
+  /// `y`
+  Yank,
+}
+
+impl Operator {
+  pub fn from_char(c: char) -> Option<Self> {
+    match c {
+      'd' => Some(Operator::Delete),
+      'c' => Some(Operator::Change),
+      'y' => Some(Operator::Yank),
+      _ => None,
+    }
+  }
+
+  pub fn to_char(&self) -> char {
+    match self {
+      Operator::Delete => 'd',
+      Operator::Change => 'c',
+      Operator::Yank => 'y',
+    }
+  }
+}
 
 #[derive(Debug, Copy, Clone, Default)]
 /// The operator-pending editing mode.
-pub struct OperatorPendingStateful {}
+pub struct OperatorPendingStateful {
+  operator: Operator,
+  // The count typed before the operator, `0` means no count.
+  count: usize,
+  // The count typed after the operator.
+  motion_count: usize,
+  // The typed prefix, i.e. `i`/`a` of the text objects, or the `g` of `gg`.
+  prefix: Option<char>,
+}
+
+impl OperatorPendingStateful {
+  /// Start the `operator` with the `count` typed in normal mode.
+  pub fn start(operator: Operator, count: usize) -> StatefulValue {
+    StatefulValue::OperatorPendingMode(OperatorPendingStateful {
+      operator,
+      count,
+      motion_count: 0,
+      prefix: None,
+    })
+  }
+
+  /// The total count, i.e. the operator count multiplied with the motion count, `0` means no count.
+  pub fn total_count(&self) -> usize {
+    if self.count == 0 && self.motion_count == 0 {
+      0
+    } else {
+      self.count.max(1).saturating_mul(self.motion_count.max(1))
+    }
+  }
+
+  fn apply(&self, state: &mut State, tree: &TreeArc, target: OperatorTarget) -> StatefulValue {
+    apply_target(state, tree, self.operator, target, self.total_count())
+  }
+}
 
 impl Stateful for OperatorPendingStateful {
-  fn handle(&self, _data_access: StatefulDataAccess) -> StatefulValue {
-    StatefulValue::OperatorPendingMode(OperatorPendingStateful::default())
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let state = data_access.state;
+    let tree = data_access.tree;
+    let event = data_access.event;
+
+    let key_event = match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+      _ => return StatefulValue::OperatorPendingMode(*self),
+    };
+    let c = match key_event.code {
+      KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+        return StatefulValue::NormalMode(NormalStateful::default());
+      }
+      KeyCode::Char(c) => c,
+      KeyCode::Left => 'h',
+      KeyCode::Right => 'l',
+      KeyCode::Up => 'k',
+      KeyCode::Down => 'j',
+      _ => return StatefulValue::NormalMode(NormalStateful::default()),
+    };
+
+    match self.prefix {
+      Some(prefix @ ('i' | 'a')) => {
+        return match textobj::parse(c) {
+          Some(object) => self.apply(
+            state,
+            &tree,
+            OperatorTarget::TextObject {
+              object,
+              inner: prefix == 'i',
+            },
+          ),
+          None => StatefulValue::NormalMode(NormalStateful::default()),
+        };
+      }
+      Some('g') => {
+        return if c == 'g' {
+          self.apply(state, &tree, OperatorTarget::Motion(Motion::FirstLine))
+        } else {
+          StatefulValue::NormalMode(NormalStateful::default())
+        };
+      }
+      _ => { /* No prefix */ }
+    }
+
+    match c {
+      '0'..='9' if c != '0' || self.motion_count > 0 => {
+        // Motion count
+        let digit = c.to_digit(10).unwrap() as usize;
+        StatefulValue::OperatorPendingMode(OperatorPendingStateful {
+          motion_count: self.motion_count.saturating_mul(10).saturating_add(digit),
+          ..*self
+        })
+      }
+      'i' | 'a' | 'g' => StatefulValue::OperatorPendingMode(OperatorPendingStateful {
+        prefix: Some(c),
+        ..*self
+      }),
+      's' if self.motion_count == 0 => {
+        // Surround commands, i.e. `ds`, `cs`, `ys`
+        let next = SurroundStateful::start(state, self.operator.to_char());
+        match state.surround_mut().as_mut().map(|keys| keys.input('s')) {
+          Some(SurroundInput::Pending) => next,
+          _ => {
+            state.surround_mut().take();
+            StatefulValue::NormalMode(NormalStateful::default())
+          }
+        }
+      }
+      c if c == self.operator.to_char() => {
+        self.apply(state, &tree, OperatorTarget::Motion(Motion::CurrentLine))
+      }
+      c => match motion::parse(c) {
+        Some(motion) => self.apply(state, &tree, OperatorTarget::Motion(motion)),
+        None => StatefulValue::NormalMode(NormalStateful::default()),
+      },
+    }
+  }
+}
+
+/// Apply the `operator` to the `target` from the cursor of current window, i.e. `d3w`, `x`, the
+/// `count` is the typed count (`0` means no count).
+///
+/// The change (except yank) is saved as the last change for dot-repeat. Returns the next state.
+pub fn apply_target(
+  state: &mut State,
+  tree: &TreeArc,
+  operator: Operator,
+  target: OperatorTarget,
+  count: usize,
+) -> StatefulValue {
+  let (buffer, viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return StatefulValue::NormalMode(NormalStateful::default()),
+  };
+  let cursor = {
+    let viewport = rlock!(viewport);
+    (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+  };
+  let selection = target.selection(&rlock!(buffer), cursor, operator, count);
+  trace!("Operator {:?} with {:?}: {:?}", operator, target, selection);
+  let selection = match selection {
+    Some(selection) => selection,
+    None => return StatefulValue::NormalMode(NormalStateful::default()),
+  };
+  if operator != Operator::Yank {
+    state.set_last_change(LastChange::Operator(OperatorChange {
+      operator,
+      target,
+      count,
+      insert: None,
+    }));
+  }
+  let next = apply_operator(state, tree, operator, &selection);
+  if operator == Operator::Yank
+    && selection.kind() == VisualKind::Linewise
+    && matches!(target, OperatorTarget::Motion(_))
+  {
+    // The linewise yank keeps the cursor column, i.e. `yy`, `yj`.
+    let position = {
+      let buffer = rlock!(buffer);
+      clamp_position(
+        buffer.len_lines(),
+        |l| buffer.get_line_content(l),
+        (selection.ordered().0 .0, cursor.1),
+      )
+    };
+    move_cursor_to(tree, &viewport, position);
+  }
+  next
+}
+
+/// Repeat the operator `change` at the cursor of current window, i.e. the `.` key. The `count`
+/// replaces the count of the change if it's not `0`.
+///
+/// The change operator `c` also inserts the recorded text, and stays in normal mode. The whole
+/// change is one undo state.
+pub fn repeat_change(state: &mut State, tree: &TreeArc, change: &OperatorChange, count: usize) {
+  let count = if count > 0 { count } else { change.count };
+  let (buffer, viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return,
+  };
+  let cursor = {
+    let viewport = rlock!(viewport);
+    (viewport.cursor().line_idx(), viewport.cursor().char_idx())
+  };
+  let selection = change
+    .target
+    .selection(&rlock!(buffer), cursor, change.operator, count);
+  trace!("Repeat {:?}: {:?}", change, selection);
+  let selection = match selection {
+    Some(selection) => selection,
+    None => return,
+  };
+  wlock!(buffer).undo_tree_mut().begin_group();
+  apply_operator(state, tree, change.operator, &selection);
+  if let Some(record) = &change.insert {
+    replay_insert(tree, record);
+  }
+  wlock!(buffer).undo_tree_mut().end_group();
+}
+
+/// Apply the `operator` to the `selection` of current window, it's shared by the operator-pending
+/// mode and visual mode.
+///
+/// - Yank: The text is yanked to the register, and the cursor moves to the start.
+/// - Delete: The text is deleted to the register, and the cursor moves to the start.
+/// - Change: Same with delete, then starts insert mode. For linewise, an empty line is kept.
+///
+/// The visual selection (if any) is cleared. Returns the next state.
+pub fn apply_operator(
+  state: &mut State,
+  tree: &TreeArc,
+  operator: Operator,
+  selection: &Selection,
+) -> StatefulValue {
+  let (buffer, viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return StatefulValue::NormalMode(NormalStateful::default()),
+  };
+  let start = start_position(selection);
+  let cursor = match operator {
+    Operator::Yank => {
+      let text = selected_text(&rlock!(buffer), selection);
+      trace!("Yank {:?}: {:?}", selection, text);
+      state
+        .registers_mut()
+        .yank(None, Register::new(selection.kind().into(), text));
+      start
+    }
+    Operator::Delete | Operator::Change => {
+      let mut buffer = wlock!(buffer);
+      let text = delete_selection(&mut buffer, selection);
+      trace!("Delete {:?}: {:?}", selection, text);
+      state
+        .registers_mut()
+        .delete(None, Register::new(selection.kind().into(), text));
+      if operator == Operator::Change && selection.kind() == VisualKind::Linewise {
+        // Keep an empty line to insert text.
+        let char_idx = buffer.line_to_char(start.0);
+        buffer.insert(char_idx, "\n");
+      }
+      if operator == Operator::Change {
+        // The insert mode cursor can be after the last char, i.e. `cw` on the last word.
+        let line_len = buffer
+          .get_line_content(start.0)
+          .map(|l| l.chars().count())
+          .unwrap_or(0);
+        (start.0, std::cmp::min(start.1, line_len))
+      } else {
+        clamp_position(buffer.len_lines(), |l| buffer.get_line_content(l), start)
+      }
+    }
+  };
+  // NOTE: The buffer lock must be released before syncing viewport.
+  if operator != Operator::Yank {
+    wlock!(viewport).sync();
+  }
+  update_selection(tree, &viewport, None);
+  move_cursor_to(tree, &viewport, cursor);
+  if operator == Operator::Change {
+    StatefulValue::InsertMode(InsertStateful::default())
+  } else {
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn total_count1() {
+    let pending = |count, motion_count| OperatorPendingStateful {
+      operator: Operator::Delete,
+      count,
+      motion_count,
+      prefix: None,
+    };
+    assert_eq!(pending(0, 0).total_count(), 0);
+    assert_eq!(pending(2, 0).total_count(), 2);
+    assert_eq!(pending(0, 3).total_count(), 3);
+    assert_eq!(pending(2, 3).total_count(), 6);
+    for c in ['d', 'c', 'y'] {
+      assert_eq!(Operator::from_char(c).map(|op| op.to_char()), Some(c));
+    }
   }
 }
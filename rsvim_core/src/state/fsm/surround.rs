@@ -0,0 +1,124 @@
+//! The surround state.
+//!
+//! It's an internal state (not an editing mode), entered from operator-pending mode by `s` (i.e.
+//! `ds`, `cs`, `ys`) or from visual mode by `S`, then waits for the rest keys of the surround command, see
+//! [`surround`](crate::state::surround). The keys are:
+//!
+//! - `ESC`/`CTRL-C`: Cancel and back to normal mode.
+//! - Other chars: The keys of the command.
+
+use crate::state::current_window_buffer;
+use crate::state::fsm::visual::{move_cursor_to, update_selection};
+use crate::state::fsm::{NormalStateful, Stateful, StatefulDataAccess, StatefulValue};
+use crate::state::repeat::LastChange;
+use crate::state::surround::{self, SurroundCommand, SurroundInput, SurroundKeys, SurroundMotion};
+use crate::state::State;
+use crate::ui::tree::TreeArc;
+use crate::{envar, rlock, wlock};
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, Default)]
+/// The surround state.
+pub struct SurroundStateful {}
+
+impl SurroundStateful {
+  /// Start the surround command with the first key.
+  pub fn start(state: &mut State, first: char) -> StatefulValue {
+    *state.surround_mut() = Some(SurroundKeys::new(first));
+    StatefulValue::SurroundState(SurroundStateful::default())
+  }
+
+  // Cancel the surround command, the visual selection (if any) is cleared.
+  fn cancel(state: &mut State, tree: &TreeArc) -> StatefulValue {
+    state.surround_mut().take();
+    if let Some((_, viewport)) = current_window_buffer(tree) {
+      let selection = *rlock!(viewport).selection();
+      if let Some(selection) = selection {
+        update_selection(tree, &viewport, None);
+        move_cursor_to(tree, &viewport, selection.cursor());
+      }
+    }
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+}
+
+impl Stateful for SurroundStateful {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let state = data_access.state;
+    let tree = data_access.tree;
+    let event = data_access.event;
+
+    if state.surround().is_none() {
+      return StatefulValue::NormalMode(NormalStateful::default());
+    }
+
+    let key_event = match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+      _ => return StatefulValue::SurroundState(SurroundStateful::default()),
+    };
+    let c = match key_event.code {
+      KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+        return Self::cancel(state, &tree);
+      }
+      KeyCode::Char(c) => c,
+      _ => return Self::cancel(state, &tree),
+    };
+
+    let mut keys = state.surround_mut().take().unwrap();
+    match keys.input(c) {
+      SurroundInput::Pending => {
+        *state.surround_mut() = Some(keys);
+        StatefulValue::SurroundState(SurroundStateful::default())
+      }
+      SurroundInput::Done(command) => {
+        trace!("Surround {:?}", command);
+        if run(&tree, &command)
+          && !matches!(command, SurroundCommand::Add(SurroundMotion::Selection, _))
+        {
+          state.set_last_change(LastChange::Surround(command));
+        }
+        StatefulValue::NormalMode(NormalStateful::default())
+      }
+      SurroundInput::Cancel => Self::cancel(state, &tree),
+    }
+  }
+}
+
+/// Run the surround command at the cursor (or the visual selection) of current window, returns
+/// `false` if the target is not found.
+pub fn run(tree: &TreeArc, command: &SurroundCommand) -> bool {
+  let (buffer, viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return false,
+  };
+  let (cursor, selection) = {
+    let viewport = rlock!(viewport);
+    (
+      (viewport.cursor().line_idx(), viewport.cursor().char_idx()),
+      *viewport.selection(),
+    )
+  };
+  let position = {
+    let mut buffer = wlock!(buffer);
+    surround::apply(&mut buffer, cursor, command, selection.as_ref())
+  };
+  // NOTE: The buffer lock must be released before syncing viewport.
+  wlock!(viewport).sync();
+  if selection.is_some() {
+    update_selection(tree, &viewport, None);
+  }
+  match position {
+    Some(position) => {
+      move_cursor_to(tree, &viewport, position);
+      true
+    }
+    None => {
+      if let Some(selection) = selection {
+        move_cursor_to(tree, &viewport, selection.cursor());
+      }
+      false
+    }
+  }
+}
@@ -1,13 +1,438 @@
 //! The visual mode.
 
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
+use crate::buf::MarkPos;
+use crate::envar;
+use crate::state::autopair::{self, wrap_selection};
+use crate::state::current_window_buffer;
+use crate::state::fsm::command_line::CommandLineStateful;
+use crate::state::fsm::operator_pending::{apply_operator, Operator};
+use crate::state::fsm::{
+  NormalStateful, Stateful, StatefulDataAccess, StatefulValue, SurroundStateful,
+};
+use crate::state::mouse;
+use crate::state::textobj;
+use crate::state::visual::{Selection, VisualKind, VisualPosition};
+use crate::state::State;
+use crate::ui::tree::internal::Inodeable;
+use crate::ui::tree::{TreeArc, TreeNode};
+use crate::ui::widget::window::ViewportArc;
+use crate::{rlock, wlock};
+
+use crossterm::event::{
+  Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::collections::BTreeMap;
+use tracing::trace;
+
+/// The range prefilled in the command line when typing `:` in visual mode.
+pub const VISUAL_RANGE: &str = "'<,'>";
 
 #[derive(Debug, Copy, Clone, Default)]
 /// The visual editing mode.
-pub struct VisualStateful {}
+///
+/// The selection is stored in the viewport of current window, see
+/// [`Selection`](crate::state::visual::Selection).
+pub struct VisualStateful {
+  // The typed `i`/`a` prefix of the text objects.
+  prefix: Option<char>,
+}
 
 impl Stateful for VisualStateful {
-  fn handle(&self, _data_access: StatefulDataAccess) -> StatefulValue {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let state = data_access.state;
+    let tree = data_access.tree;
+    let event = data_access.event;
+
+    let (buffer, viewport) = match current_window_buffer(&tree) {
+      Some(current) => current,
+      None => return StatefulValue::NormalMode(NormalStateful::default()),
+    };
+    let mut selection = match *rlock!(viewport).selection() {
+      Some(selection) => selection,
+      None => return StatefulValue::NormalMode(NormalStateful::default()),
+    };
+
+    let key_event = match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+      Event::Mouse(mouse_event) => {
+        return self.handle_mouse(state, &tree, &viewport, selection, mouse_event);
+      }
+      _ => return StatefulValue::VisualMode(VisualStateful::default()),
+    };
+
+    if let Some(prefix) = self.prefix {
+      // Select the text object, i.e. `viw`, `va(`.
+      let object = match key_event.code {
+        KeyCode::Char(c) => textobj::parse(c),
+        _ => None,
+      };
+      if let Some(object) = object {
+        let selected = textobj::select(
+          &rlock!(buffer),
+          selection.cursor(),
+          object,
+          prefix == 'i',
+          1,
+        );
+        trace!("Select {:?}: {:?}", object, selected);
+        if let Some(selected) = selected {
+          update_selection(&tree, &viewport, Some(selected));
+        }
+      }
+      return StatefulValue::VisualMode(VisualStateful::default());
+    }
+
+    let (line_idx, char_idx) = selection.cursor();
+    // Move left/right by grapheme clusters.
+    let cursor = match key_event.code {
+      KeyCode::Up | KeyCode::Char('k') => Some((line_idx.saturating_sub(1), char_idx)),
+      KeyCode::Down | KeyCode::Char('j') => Some((line_idx + 1, char_idx)),
+      KeyCode::Left | KeyCode::Char('h') => {
+        Some((line_idx, rlock!(buffer).prev_grapheme(line_idx, char_idx)))
+      }
+      KeyCode::Right | KeyCode::Char('l') => {
+        Some((line_idx, rlock!(buffer).next_grapheme(line_idx, char_idx)))
+      }
+      _ => None,
+    };
+    if let Some(cursor) = cursor {
+      let cursor = {
+        let buffer = rlock!(buffer);
+        let (line_idx, char_idx) =
+          clamp_position(buffer.len_lines(), |l| buffer.get_line_content(l), cursor);
+        (line_idx, buffer.grapheme_start(line_idx, char_idx))
+      };
+      selection.set_cursor(cursor);
+      update_selection(&tree, &viewport, Some(selection));
+      return StatefulValue::VisualMode(VisualStateful::default());
+    }
+
+    match key_event.code {
+      KeyCode::Esc => {
+        let cursor = selection.cursor();
+        update_selection(&tree, &viewport, None);
+        move_cursor_to(&tree, &viewport, cursor);
+        StatefulValue::NormalMode(NormalStateful::default())
+      }
+      KeyCode::Char('o') => {
+        selection.swap();
+        update_selection(&tree, &viewport, Some(selection));
+        StatefulValue::VisualMode(VisualStateful::default())
+      }
+      KeyCode::Char('v') | KeyCode::Char('V') => {
+        let kind = if key_event.modifiers == KeyModifiers::CONTROL {
+          VisualKind::Blockwise
+        } else if key_event.code == KeyCode::Char('V') {
+          VisualKind::Linewise
+        } else {
+          VisualKind::Charwise
+        };
+        if kind == selection.kind() {
+          // Press the same key again quits visual mode.
+          let cursor = selection.cursor();
+          update_selection(&tree, &viewport, None);
+          move_cursor_to(&tree, &viewport, cursor);
+          return StatefulValue::NormalMode(NormalStateful::default());
+        }
+        selection.set_kind(kind);
+        update_selection(&tree, &viewport, Some(selection));
+        StatefulValue::VisualMode(VisualStateful::default())
+      }
+      KeyCode::Char(c @ ('i' | 'a')) => {
+        StatefulValue::VisualMode(VisualStateful { prefix: Some(c) })
+      }
+      KeyCode::Char('y') => apply_operator(state, &tree, Operator::Yank, &selection),
+      KeyCode::Char('d') | KeyCode::Char('x') | KeyCode::Delete => {
+        apply_operator(state, &tree, Operator::Delete, &selection)
+      }
+      KeyCode::Char('c') => apply_operator(state, &tree, Operator::Change, &selection),
+      KeyCode::Char(':') => {
+        // Type an ex command on the selected lines.
+        let cursor = selection.cursor();
+        update_selection(&tree, &viewport, None);
+        move_cursor_to(&tree, &viewport, cursor);
+        CommandLineStateful::start_ex(state, &tree, VISUAL_RANGE)
+      }
+      KeyCode::Char('S') => {
+        // Surround the selection, the selection is kept until the replacement is typed.
+        SurroundStateful::start(state, 'S')
+      }
+      KeyCode::Char(c) if rlock!(tree).autopairs() && autopair::closing_of(c).is_some() => {
+        // Wrap the selection in the pair.
+        let start = start_position(&selection);
+        {
+          let mut buffer = wlock!(buffer);
+          let close = autopair::closing_of(c).unwrap();
+          wrap_selection(&mut buffer, &selection, &c.to_string(), &close.to_string());
+          trace!("Wrap selection {:?} in {:?}", selection, c);
+        }
+        // NOTE: The buffer lock must be released before syncing viewport.
+        wlock!(viewport).sync();
+        update_selection(&tree, &viewport, None);
+        move_cursor_to(&tree, &viewport, start);
+        StatefulValue::NormalMode(NormalStateful::default())
+      }
+      _ => StatefulValue::VisualMode(VisualStateful::default()),
+    }
+  }
+}
+
+impl VisualStateful {
+  /// Start visual mode at the cursor of current window.
+  pub fn start(tree: &TreeArc, kind: VisualKind) -> StatefulValue {
+    let (_buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return StatefulValue::NormalMode(NormalStateful::default()),
+    };
+
+    // The cursor widget position (relative to the window content) is the anchor.
+    let cursor_pos = {
+      let tree = rlock!(tree);
+      let cursor_id = match tree.cursor_id() {
+        Some(cursor_id) => cursor_id,
+        None => return StatefulValue::NormalMode(NormalStateful::default()),
+      };
+      let content_offset = match tree.parent_id(&cursor_id).and_then(|id| tree.node(id)) {
+        Some(TreeNode::Window(window)) => window.content_offset() as isize,
+        _ => 0,
+      };
+      match tree.node(&cursor_id) {
+        Some(cursor) => {
+          let pos = cursor.shape().min();
+          (pos.x - content_offset, pos.y)
+        }
+        None => return StatefulValue::NormalMode(NormalStateful::default()),
+      }
+    };
+    let cursor = {
+      let mut viewport = wlock!(viewport);
+      let cursor = match viewport.cursor_at(cursor_pos.0.max(0) as u16, cursor_pos.1.max(0) as u16)
+      {
+        Some(cursor) => cursor,
+        None => return StatefulValue::NormalMode(NormalStateful::default()),
+      };
+      viewport.set_cursor(cursor);
+      cursor
+    };
+
+    let selection = Selection::new(kind, (cursor.line_idx(), cursor.char_idx()));
+    trace!("Start visual mode: {:?}", selection);
+    update_selection(tree, &viewport, Some(selection));
     StatefulValue::VisualMode(VisualStateful::default())
   }
+
+  /// Extend the selection of current window to `position`, i.e. where the mouse drags to.
+  pub fn drag_to(tree: &TreeArc, position: VisualPosition) {
+    let (_buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return,
+    };
+    let mut selection = match *rlock!(viewport).selection() {
+      Some(selection) => selection,
+      None => return,
+    };
+    selection.set_cursor(position);
+    update_selection(tree, &viewport, Some(selection));
+  }
+
+  // Handle the mouse events, see [`mouse`](crate::state::mouse).
+  fn handle_mouse(
+    &self,
+    state: &mut State,
+    tree: &TreeArc,
+    viewport: &ViewportArc,
+    mut selection: Selection,
+    mouse_event: MouseEvent,
+  ) -> StatefulValue {
+    let (column, row) = (mouse_event.column, mouse_event.row);
+    match mouse_event.kind {
+      MouseEventKind::Drag(MouseButton::Left) => {
+        if let Some(position) = mouse::position_at(tree, column, row) {
+          VisualStateful::drag_to(tree, position);
+        }
+      }
+      MouseEventKind::Down(button) => {
+        // Click quits visual mode, and moves the cursor to the clicked char.
+        let cursor = selection.cursor();
+        update_selection(tree, viewport, None);
+        move_cursor_to(tree, viewport, cursor);
+        mouse::click(state, tree, column, row, button);
+        return StatefulValue::NormalMode(NormalStateful::default());
+      }
+      MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+        let lines = if mouse_event.kind == MouseEventKind::ScrollUp {
+          -(mouse::SCROLL_LINES as isize)
+        } else {
+          mouse::SCROLL_LINES as isize
+        };
+        mouse::scroll(tree, column, row, lines);
+        // The selection follows the cursor if it's moved by scrolling.
+        let cursor = *rlock!(viewport).cursor();
+        let cursor = (cursor.line_idx(), cursor.char_idx());
+        if cursor != selection.cursor() {
+          selection.set_cursor(cursor);
+          update_selection(tree, viewport, Some(selection));
+        }
+      }
+      _ => { /* Skip */ }
+    }
+    StatefulValue::VisualMode(VisualStateful::default())
+  }
+}
+
+/// The position that the operators put the cursor at, i.e. the start of selection.
+pub fn start_position(selection: &Selection) -> VisualPosition {
+  let (start, _end) = selection.ordered();
+  match selection.kind() {
+    VisualKind::Charwise => start,
+    VisualKind::Linewise => (start.0, 0),
+    VisualKind::Blockwise => (
+      start.0,
+      std::cmp::min(selection.anchor().1, selection.cursor().1),
+    ),
+  }
+}
+
+/// Clamp the position inside the buffer, the last empty line (after the last line ending) is not
+/// a valid position unless it's the only line.
+pub fn clamp_position<F>(
+  len_lines: usize,
+  line_content: F,
+  position: VisualPosition,
+) -> VisualPosition
+where
+  F: Fn(usize) -> Option<String>,
+{
+  let mut last_line_idx = len_lines.saturating_sub(1);
+  if last_line_idx > 0
+    && line_content(last_line_idx)
+      .map(|l| l.is_empty())
+      .unwrap_or(false)
+  {
+    last_line_idx -= 1;
+  }
+  let line_idx = std::cmp::min(position.0, last_line_idx);
+  let line_len = line_content(line_idx)
+    .map(|l| l.chars().count())
+    .unwrap_or(0);
+  (
+    line_idx,
+    std::cmp::min(position.1, line_len.saturating_sub(1)),
+  )
+}
+
+/// Update the selection in viewport (and its selected ranges), and move the cursor to the
+/// selection's cursor.
+///
+/// When the selection is cleared, i.e. quit visual mode, the `<`/`>` marks are set to the start/end
+/// of the last selection.
+pub fn update_selection(tree: &TreeArc, viewport: &ViewportArc, selection: Option<Selection>) {
+  let selection = match selection {
+    Some(selection) => selection,
+    None => {
+      let (buffer, last) = {
+        let mut viewport = wlock!(viewport);
+        let last = *viewport.selection();
+        viewport.set_selection(None, BTreeMap::new());
+        (viewport.buffer(), last)
+      };
+      // NOTE: The viewport lock must be released before locking buffer.
+      if let (Some(buffer), Some(last)) = (buffer.upgrade(), last) {
+        let (start, end) = last.ordered();
+        let mut buffer = wlock!(buffer);
+        buffer.marks_mut().set('<', MarkPos::new(start.0, start.1));
+        buffer.marks_mut().set('>', MarkPos::new(end.0, end.1));
+      }
+      return;
+    }
+  };
+
+  move_cursor_to(tree, viewport, selection.cursor());
+  let (buffer, line_range) = {
+    let viewport = rlock!(viewport);
+    (
+      viewport.buffer(),
+      viewport.start_line_idx()..viewport.end_line_idx(),
+    )
+  };
+  let selection_ranges = match buffer.upgrade() {
+    Some(buffer) => selection.char_ranges(&rlock!(buffer), line_range),
+    None => BTreeMap::new(),
+  };
+  // NOTE: The buffer lock must be released before locking viewport.
+  wlock!(viewport).set_selection(Some(selection), selection_ranges);
+}
+
+/// Move the cursor to the position, the viewport scrolls if the position is not visible.
+pub fn move_cursor_to(tree: &TreeArc, viewport: &ViewportArc, position: VisualPosition) {
+  let (line_idx, char_idx) = position;
+  let cursor_pos = {
+    let mut viewport = wlock!(viewport);
+    if viewport.is_empty() {
+      return;
+    }
+    let (start_line_idx, start_dcol_idx) = viewport.search_anchor(line_idx, char_idx);
+    if start_line_idx != viewport.start_line_idx() || start_dcol_idx != viewport.start_dcol_idx() {
+      viewport.sync_from_top_left(start_line_idx, start_dcol_idx);
+    }
+    let cursor = match viewport.cursor_of(line_idx, char_idx) {
+      Some(cursor) => cursor,
+      None => return,
+    };
+    viewport.set_cursor(cursor);
+    let (row_start_dcol_idx, start_fills) = viewport
+      .lines()
+      .get(&line_idx)
+      .and_then(|line| {
+        line
+          .rows()
+          .get(&cursor.row_idx())
+          .map(|row| (row.start_dcol_idx(), line.start_filled_columns()))
+      })
+      .unwrap_or((0, 0));
+    (
+      (cursor.start_dcol_idx() + start_fills).saturating_sub(row_start_dcol_idx) as isize,
+      cursor.row_idx() as isize,
+    )
+  };
+
+  let mut tree = wlock!(tree);
+  if let Some(cursor_id) = tree.cursor_id() {
+    let content_offset = match tree.parent_id(&cursor_id).and_then(|id| tree.node(id)) {
+      Some(TreeNode::Window(window)) => window.content_offset() as isize,
+      _ => 0,
+    };
+    let current_pos = tree.node(&cursor_id).unwrap().shape().min();
+    tree.bounded_move_by(
+      cursor_id,
+      cursor_pos.0 + content_offset - current_pos.x,
+      cursor_pos.1 - current_pos.y,
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn clamp_position1() {
+    let lines = ["hello", "", "world", ""];
+    let line_content = |l: usize| lines.get(l).map(|l| l.to_string());
+    assert_eq!(clamp_position(4, line_content, (0, 10)), (0, 4));
+    assert_eq!(clamp_position(4, line_content, (1, 3)), (1, 0));
+    assert_eq!(clamp_position(4, line_content, (5, 2)), (2, 2));
+    assert_eq!(clamp_position(1, |_| Some(String::new()), (3, 3)), (0, 0));
+  }
+
+  #[test]
+  fn start_position1() {
+    let mut selection = Selection::new(VisualKind::Charwise, (2, 3));
+    selection.set_cursor((1, 5));
+    assert_eq!(start_position(&selection), (1, 5));
+    selection.set_kind(VisualKind::Linewise);
+    assert_eq!(start_position(&selection), (1, 0));
+    selection.set_kind(VisualKind::Blockwise);
+    assert_eq!(start_position(&selection), (1, 3));
+  }
 }
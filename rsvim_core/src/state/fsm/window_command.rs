@@ -0,0 +1,82 @@
+//! The window command state.
+//!
+//! It's an internal state (not an editing mode), entered from normal mode by `Ctrl-W`, then waits
+//! for the window command key:
+//!
+//! - `s`/`S`/`Ctrl-S`: Split current window horizontally, i.e. `:split`.
+//! - `v`/`Ctrl-V`: Split current window vertically, i.e. `:vsplit`.
+//! - `c`/`q`: Close current window, i.e. `:close`.
+//! - `h`/`j`/`k`/`l` (or arrow keys, or with `Ctrl`): Move the cursor to the left/below/above/right
+//!   window.
+//! - `w`/`Ctrl-W`: Move the cursor to the next window.
+//! - `=`: Make all windows (almost) the same size.
+//! - `+`/`-`: Increase/decrease current window height by 1.
+//! - `>`/`<`: Increase/decrease current window width by 1.
+//!
+//! Other keys cancel and back to normal mode.
+
+use crate::envar;
+use crate::state::fsm::{NormalStateful, Stateful, StatefulDataAccess, StatefulValue};
+use crate::ui::tree::layout::{FocusDirection, LayoutDirection, ResizeAmount};
+use crate::ui::tree::Tree;
+use crate::wlock;
+
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, Default)]
+/// The window command state.
+pub struct WindowCommandStateful {}
+
+impl WindowCommandStateful {
+  /// Start waiting for the window command key.
+  pub fn start() -> StatefulValue {
+    StatefulValue::WindowCommandState(WindowCommandStateful::default())
+  }
+}
+
+impl Stateful for WindowCommandStateful {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let tree = data_access.tree;
+    let event = data_access.event;
+
+    let key_event = match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+      _ => return StatefulValue::WindowCommandState(*self),
+    };
+
+    let mut tree = wlock!(tree);
+    let resize = |tree: &mut Tree, direction, n| match tree.current_window_id() {
+      Some(window_id) => tree.resize_window(window_id, direction, ResizeAmount::By(n)),
+      None => false,
+    };
+    let done = match key_event.code {
+      KeyCode::Char('s') | KeyCode::Char('S') => {
+        tree.split_window(LayoutDirection::Column).is_some()
+      }
+      KeyCode::Char('v') => tree.split_window(LayoutDirection::Row).is_some(),
+      KeyCode::Char('c') | KeyCode::Char('q') => match tree.current_window_id() {
+        Some(window_id) => tree.close_window(window_id),
+        None => false,
+      },
+      KeyCode::Char('h') | KeyCode::Left | KeyCode::Backspace => {
+        tree.focus_window(FocusDirection::Left)
+      }
+      KeyCode::Char('j') | KeyCode::Down => tree.focus_window(FocusDirection::Down),
+      KeyCode::Char('k') | KeyCode::Up => tree.focus_window(FocusDirection::Up),
+      KeyCode::Char('l') | KeyCode::Right => tree.focus_window(FocusDirection::Right),
+      KeyCode::Char('w') => tree.focus_next_window(),
+      KeyCode::Char('=') => {
+        tree.equalize_windows();
+        true
+      }
+      KeyCode::Char('+') => resize(&mut tree, LayoutDirection::Column, 1),
+      KeyCode::Char('-') => resize(&mut tree, LayoutDirection::Column, -1),
+      KeyCode::Char('>') => resize(&mut tree, LayoutDirection::Row, 1),
+      KeyCode::Char('<') => resize(&mut tree, LayoutDirection::Row, -1),
+      _ => false,
+    };
+    trace!("Window command {:?}:{:?}", key_event.code, done);
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+}
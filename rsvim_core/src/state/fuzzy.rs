@@ -0,0 +1,218 @@
+//! Fuzzy matching, i.e. the scoring of the fuzzy finder (see [`finder`](crate::state::finder)).
+//!
+//! It's the [fzy](https://github.com/jhawthorn/fzy) algorithm: the query chars must appear in the
+//! text in order, and the best positions are found with dynamic programming. The score prefers:
+//!
+//! - The consecutive matched chars.
+//! - The matched chars at the word starts, i.e. after `/`, `-`, `_`, space, `.`, or the uppercase
+//!   chars in camel case.
+//! - The shorter gaps between the matched chars, and the shorter texts.
+//!
+//! It's smart case, i.e. the query is matched case-sensitively only if it contains uppercase
+//! chars.
+
+/// The score of the exact match, it's the best.
+pub const SCORE_MAX: i64 = i64::MAX / 2;
+
+/// The score of the impossible match.
+pub const SCORE_MIN: i64 = i64::MIN / 2;
+
+// The text longer than this is not scored, it's matched but with the worst score.
+const MATCH_MAX_LEN: usize = 1024;
+
+// The scores are scaled by 1000, i.e. the consecutive match is 1.0 in fzy.
+const SCORE_GAP_LEADING: i64 = -5;
+const SCORE_GAP_TRAILING: i64 = -5;
+const SCORE_GAP_INNER: i64 = -10;
+const SCORE_MATCH_CONSECUTIVE: i64 = 1000;
+const SCORE_MATCH_SLASH: i64 = 900;
+const SCORE_MATCH_WORD: i64 = 800;
+const SCORE_MATCH_CAPITAL: i64 = 700;
+const SCORE_MATCH_DOT: i64 = 600;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The fuzzy match result.
+pub struct FuzzyMatch {
+  /// The score, the greater is the better.
+  pub score: i64,
+  /// The char indexes of the matched chars in the text.
+  pub positions: Vec<usize>,
+}
+
+// The bonus of matching the char `c` after the char `prev`.
+fn bonus(prev: char, c: char) -> i64 {
+  if c.is_lowercase() || c.is_numeric() {
+    match prev {
+      '/' | '\\' => SCORE_MATCH_SLASH,
+      '-' | '_' | ' ' => SCORE_MATCH_WORD,
+      '.' => SCORE_MATCH_DOT,
+      _ => 0,
+    }
+  } else if c.is_uppercase() {
+    match prev {
+      '/' | '\\' => SCORE_MATCH_SLASH,
+      '-' | '_' | ' ' => SCORE_MATCH_WORD,
+      '.' => SCORE_MATCH_DOT,
+      _ if prev.is_lowercase() => SCORE_MATCH_CAPITAL,
+      _ => 0,
+    }
+  } else {
+    0
+  }
+}
+
+/// Fuzzy match the `query` on the `text`, returns `None` if not matched. The empty query matches
+/// any text with score 0.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+  let case_sensitive = query.chars().any(|c| c.is_uppercase());
+  let normalize = |c: char| {
+    if case_sensitive {
+      c
+    } else {
+      c.to_lowercase().next().unwrap_or(c)
+    }
+  };
+  let needle: Vec<char> = query.chars().map(normalize).collect();
+  let chars: Vec<char> = text.chars().collect();
+  let haystack: Vec<char> = chars.iter().map(|c| normalize(*c)).collect();
+  let (n, m) = (needle.len(), haystack.len());
+
+  // Find the leftmost positions, it also checks whether the text matches.
+  let mut positions = Vec::with_capacity(n);
+  let mut j = 0_usize;
+  for q in needle.iter() {
+    let found = haystack[j..].iter().position(|c| c == q)?;
+    positions.push(j + found);
+    j += found + 1;
+  }
+  if n == 0 {
+    return Some(FuzzyMatch {
+      score: 0,
+      positions,
+    });
+  }
+  if n == m {
+    // The positions are `0..n`, i.e. it's the exact match.
+    return Some(FuzzyMatch {
+      score: SCORE_MAX,
+      positions,
+    });
+  }
+  if m > MATCH_MAX_LEN {
+    return Some(FuzzyMatch {
+      score: SCORE_MIN,
+      positions,
+    });
+  }
+
+  let bonuses: Vec<i64> = (0..m)
+    .map(|j| {
+      let prev = if j == 0 { '/' } else { chars[j - 1] };
+      bonus(prev, chars[j])
+    })
+    .collect();
+
+  // `d[i][j]` is the best score that `needle[i]` is matched at `haystack[j]`, `best[i][j]` is the
+  // best score that `needle[..=i]` is matched in `haystack[..=j]`.
+  let mut d = vec![vec![SCORE_MIN; m]; n];
+  let mut best = vec![vec![SCORE_MIN; m]; n];
+  for i in 0..n {
+    let gap = if i == n - 1 {
+      SCORE_GAP_TRAILING
+    } else {
+      SCORE_GAP_INNER
+    };
+    let mut prev_score = SCORE_MIN;
+    for j in 0..m {
+      if needle[i] == haystack[j] {
+        let score = if i == 0 {
+          j as i64 * SCORE_GAP_LEADING + bonuses[j]
+        } else if j > 0 && best[i - 1][j - 1] != SCORE_MIN {
+          std::cmp::max(
+            best[i - 1][j - 1] + bonuses[j],
+            d[i - 1][j - 1] + SCORE_MATCH_CONSECUTIVE,
+          )
+        } else {
+          SCORE_MIN
+        };
+        d[i][j] = score;
+        prev_score = std::cmp::max(score, prev_score + gap);
+      } else {
+        prev_score += gap;
+      }
+      // The impossible match stays impossible.
+      prev_score = std::cmp::max(prev_score, SCORE_MIN);
+      best[i][j] = prev_score;
+    }
+  }
+
+  // Backtrack the positions, prefer the consecutive matches.
+  let mut match_required = false;
+  let mut j = m;
+  for i in (0..n).rev() {
+    while j > 0 {
+      j -= 1;
+      if d[i][j] != SCORE_MIN && (match_required || d[i][j] == best[i][j]) {
+        match_required = i > 0 && j > 0 && best[i][j] == d[i - 1][j - 1] + SCORE_MATCH_CONSECUTIVE;
+        positions[i] = j;
+        break;
+      }
+    }
+  }
+
+  Some(FuzzyMatch {
+    score: best[n - 1][m - 1],
+    positions,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn score(query: &str, text: &str) -> i64 {
+    fuzzy_match(query, text).unwrap().score
+  }
+
+  #[test]
+  fn fuzzy_match1() {
+    assert!(fuzzy_match("ga", "align").is_none());
+    assert_eq!(fuzzy_match("", "align").unwrap().score, 0);
+    assert_eq!(score("align", "align"), SCORE_MAX);
+
+    // Prefer the word starts.
+    assert!(score("amor", "app/models/order") > score("amor", "app/models/zrder"));
+    // Prefer the consecutive chars.
+    assert!(score("amo", "app/models/foo") > score("amo", "app/m/foo"));
+    assert!(score("gemfil", "Gemfile") > score("gemfil", "Gemfile.lock"));
+    // Prefer the shorter gaps and texts.
+    assert!(score("abce", "abcdef") > score("abce", "abc de"));
+    assert!(score("test", "tests") > score("test", "testing"));
+    // Prefer the camel case.
+    assert!(score("fb", "fooBar") > score("fb", "foobar"));
+  }
+
+  #[test]
+  fn positions1() {
+    assert_eq!(
+      fuzzy_match("amo", "app/models/foo").unwrap().positions,
+      vec![0, 4, 5]
+    );
+    assert_eq!(
+      fuzzy_match("amor", "app/models/order").unwrap().positions,
+      vec![0, 4, 11, 12]
+    );
+    assert_eq!(
+      fuzzy_match("mr", "src/main.rs").unwrap().positions,
+      vec![4, 9]
+    );
+    assert!(fuzzy_match("", "main.rs").unwrap().positions.is_empty());
+  }
+
+  #[test]
+  fn smart_case1() {
+    assert!(fuzzy_match("fb", "FooBar").is_some());
+    assert!(fuzzy_match("FB", "FooBar").is_some());
+    assert!(fuzzy_match("Fb", "foobar").is_none());
+  }
+}
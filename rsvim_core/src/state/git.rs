@@ -0,0 +1,212 @@
+//! Git status cache for the statusline.
+//!
+//! The status (branch name, dirty flag, ahead/behind the upstream) of the repository at current
+//! working directory is cached here. It's refreshed by running `git status` on an async job, when:
+//!
+//! - The editor starts.
+//! - The `FocusGained` event, i.e. the repository may be changed outside of the editor.
+//! - The `BufWritePost` event, i.e. the write path requests the refresh along with the event.
+//! - `Rsvim.git.refresh()`.
+//!
+//! The job result is sent back to the event loop, and then it's cached and rendered in the `git`
+//! statusline component (i.e. `%{git}`, see [`statusline`](crate::ui::widget::window::statusline)),
+//! it's also returned by `Rsvim.git.status()`.
+//!
+//! NOTE: Only one job runs at a time, the refresh requested while a job is running starts another
+//! job after it's finished.
+
+use std::path::Path;
+
+/// The statusline component name of the git status.
+pub const COMPONENT_NAME: &str = "git";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The git status of a repository.
+pub struct GitStatus {
+  /// The branch name, or the abbreviated commit ID if the HEAD is detached.
+  pub branch: String,
+  /// Whether there're uncommitted changes of the tracked files.
+  pub dirty: bool,
+  /// The commits ahead of the upstream.
+  pub ahead: usize,
+  /// The commits behind the upstream.
+  pub behind: usize,
+}
+
+impl GitStatus {
+  /// Parse the output of `git status --porcelain=v2 --branch`.
+  pub fn parse(output: &str) -> Self {
+    let mut status = GitStatus::default();
+    let mut oid = "";
+    for line in output.lines() {
+      if let Some(header) = line.strip_prefix("# ") {
+        let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+        match key {
+          "branch.oid" => oid = value,
+          "branch.head" => status.branch = value.to_string(),
+          "branch.ab" => {
+            for count in value.split_whitespace() {
+              if let Some(ahead) = count.strip_prefix('+') {
+                status.ahead = ahead.parse().unwrap_or(0);
+              } else if let Some(behind) = count.strip_prefix('-') {
+                status.behind = behind.parse().unwrap_or(0);
+              }
+            }
+          }
+          _ => { /* Skip */ }
+        }
+      } else if !line.is_empty() && !line.starts_with('?') && !line.starts_with('!') {
+        status.dirty = true;
+      }
+    }
+    if status.branch == "(detached)" {
+      status.branch = oid.chars().take(7).collect();
+    }
+    status
+  }
+
+  /// The text rendered in the statusline component, i.e. `main* ↑1↓2`.
+  pub fn summary(&self) -> String {
+    let mut summary = self.branch.clone();
+    if self.dirty {
+      summary.push('*');
+    }
+    if self.ahead > 0 || self.behind > 0 {
+      summary.push(' ');
+      if self.ahead > 0 {
+        summary.push_str(&format!("↑{}", self.ahead));
+      }
+      if self.behind > 0 {
+        summary.push_str(&format!("↓{}", self.behind));
+      }
+    }
+    summary
+  }
+}
+
+/// Run `git status` in the directory, returns `None` if it's not a git repository or git is not
+/// available.
+pub async fn fetch_git_status(dir: &Path) -> Option<GitStatus> {
+  let output = tokio::process::Command::new("git")
+    .args([
+      "status",
+      "--porcelain=v2",
+      "--branch",
+      "--untracked-files=no",
+    ])
+    .current_dir(dir)
+    .stdin(std::process::Stdio::null())
+    .kill_on_drop(true)
+    .output()
+    .await
+    .ok()?;
+  if !output.status.success() {
+    return None;
+  }
+  Some(GitStatus::parse(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[derive(Debug, Clone)]
+/// The cached git status, and the refresh requests.
+pub struct GitStatusCache {
+  status: Option<GitStatus>,
+  // Whether a refresh is requested.
+  requested: bool,
+  // Whether a job is running.
+  running: bool,
+}
+
+impl Default for GitStatusCache {
+  fn default() -> Self {
+    // Refresh when the editor starts.
+    GitStatusCache {
+      status: None,
+      requested: true,
+      running: false,
+    }
+  }
+}
+
+impl GitStatusCache {
+  pub fn new() -> Self {
+    GitStatusCache::default()
+  }
+
+  /// Get the cached status, it's `None` if it's not a git repository.
+  pub fn status(&self) -> Option<&GitStatus> {
+    self.status.as_ref()
+  }
+
+  /// Request a refresh, it's started by the event loop.
+  pub fn request_refresh(&mut self) {
+    self.requested = true;
+  }
+
+  /// Take the refresh request if there's no running job, the job is marked as running.
+  pub fn start_refresh(&mut self) -> bool {
+    if !self.requested || self.running {
+      return false;
+    }
+    self.requested = false;
+    self.running = true;
+    true
+  }
+
+  /// Cache the job result, returns `true` if the status is changed.
+  pub fn finish_refresh(&mut self, status: Option<GitStatus>) -> bool {
+    self.running = false;
+    if self.status == status {
+      return false;
+    }
+    self.status = status;
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse1() {
+    let output = "# branch.oid 1234567890abcdef\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +2 -1\n1 .M N... 100644 100644 100644 abc abc src/main.rs\n";
+    let status = GitStatus::parse(output);
+    assert_eq!(
+      status,
+      GitStatus {
+        branch: "main".to_string(),
+        dirty: true,
+        ahead: 2,
+        behind: 1,
+      }
+    );
+    assert_eq!(status.summary(), "main* ↑2↓1");
+
+    // Detached HEAD, no upstream.
+    let output = "# branch.oid 1234567890abcdef\n# branch.head (detached)\n";
+    let status = GitStatus::parse(output);
+    assert_eq!(status.branch, "1234567");
+    assert!(!status.dirty);
+    assert_eq!(status.summary(), "1234567");
+  }
+
+  #[test]
+  fn cache1() {
+    let mut cache = GitStatusCache::new();
+    // Requested on start.
+    assert!(cache.start_refresh());
+    // Only one job runs at a time.
+    cache.request_refresh();
+    assert!(!cache.start_refresh());
+    let status = GitStatus {
+      branch: "main".to_string(),
+      ..Default::default()
+    };
+    assert!(cache.finish_refresh(Some(status.clone())));
+    assert_eq!(cache.status(), Some(&status));
+    // The pending request starts after the job is finished.
+    assert!(cache.start_refresh());
+    assert!(!cache.finish_refresh(Some(status)));
+    assert!(!cache.start_refresh());
+  }
+}
@@ -0,0 +1,394 @@
+//! Project search, i.e. `:grep` and `Rsvim.grep`.
+//!
+//! The files are walked with the ignore rules (i.e. `.gitignore`, `.ignore` and the hidden files
+//! are skipped), and searched with a regex or a literal pattern. The search runs on the tokio
+//! blocking pool, the matches are sent back to the event loop batch by batch, and appended to the
+//! quickfix list (see [`quickfix`](crate::buf::quickfix)) while the search is running.
+//!
+//! The progress is rendered in the `grep` statusline component (i.e. `%{grep}`, see
+//! [`statusline`](crate::ui::widget::window::statusline)), it's cleared once the search is
+//! finished. Only one search runs at a time, a new search cancels the running one.
+
+use crate::buf::QuickfixEntry;
+
+use grep_matcher::Matcher;
+use grep_regex::RegexMatcherBuilder;
+use grep_searcher::sinks::Lossy;
+use grep_searcher::{BinaryDetection, SearcherBuilder};
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tracing::trace;
+
+/// The statusline component name of the search progress.
+pub const COMPONENT_NAME: &str = "grep";
+
+/// The error message of the cancelled search.
+pub const CANCELLED: &str = "Search is cancelled";
+
+/// The max entries in a batch sent to the event loop.
+pub const BATCH_SIZE: usize = 512;
+
+/// The max interval between the batches, the (maybe empty) batch also reports the progress.
+pub const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The search options.
+pub struct GrepOptions {
+  /// The regex pattern, or the literal text if `literal`.
+  pub pattern: String,
+  /// Match the pattern literally instead of as a regex, i.e. `-F`.
+  pub literal: bool,
+  /// Match case insensitively, i.e. `-i`.
+  pub ignore_case: bool,
+  /// Match case insensitively if the pattern is all lowercase, i.e. `-S`.
+  pub smart_case: bool,
+  /// Search the hidden files and directories, i.e. `-.`.
+  pub hidden: bool,
+  /// Don't respect the ignore files, i.e. `.gitignore`, `-u`.
+  pub no_ignore: bool,
+  /// The files or directories to search, default is the working directory.
+  pub paths: Vec<PathBuf>,
+}
+
+// Split the arguments on whitespaces. The `"..."` and `'...'` are quoted, the backslash only
+// escapes the whitespace and the quote chars, thus the regex escapes (i.e. `\bfoo\b`) are kept.
+fn split_args(args: &str) -> Result<Vec<String>, String> {
+  let mut result = vec![];
+  let mut arg: Option<String> = None;
+  let mut quote: Option<char> = None;
+  let mut chars = args.chars().peekable();
+  while let Some(c) = chars.next() {
+    match quote {
+      Some(q) if c == q => quote = None,
+      Some('"') if c == '\\' && chars.peek() == Some(&'"') => {
+        arg.get_or_insert_with(String::new).push('"');
+        chars.next();
+      }
+      Some(_) => arg.get_or_insert_with(String::new).push(c),
+      None if c == '"' || c == '\'' => {
+        quote = Some(c);
+        arg.get_or_insert_with(String::new);
+      }
+      None
+        if c == '\\'
+          && chars
+            .peek()
+            .is_some_and(|n| n.is_whitespace() || *n == '"' || *n == '\'') =>
+      {
+        arg
+          .get_or_insert_with(String::new)
+          .push(chars.next().unwrap());
+      }
+      None if c.is_whitespace() => result.extend(arg.take()),
+      None => arg.get_or_insert_with(String::new).push(c),
+    }
+  }
+  if quote.is_some() {
+    return Err("E114: Missing quote".to_string());
+  }
+  result.extend(arg);
+  Ok(result)
+}
+
+impl GrepOptions {
+  /// Parse the `:grep` arguments, i.e. `[-F] [-i] [-S] [-.] [-u] [--] {pattern} [path ..]`. The
+  /// flags can be combined, i.e. `-Fi`.
+  ///
+  /// # Errors
+  ///
+  /// If the pattern is missing, or the flag is unknown.
+  pub fn parse(args: &str) -> Result<Self, String> {
+    let mut options = GrepOptions::default();
+    let mut args = split_args(args)?.into_iter();
+    let mut pattern = None;
+    for arg in args.by_ref() {
+      if arg == "--" {
+        break;
+      }
+      match arg.strip_prefix('-') {
+        Some(flags) if !flags.is_empty() => {
+          for flag in flags.chars() {
+            match flag {
+              'F' => options.literal = true,
+              'i' => options.ignore_case = true,
+              'S' => options.smart_case = true,
+              '.' => options.hidden = true,
+              'u' => options.no_ignore = true,
+              _ => return Err(format!("E475: Invalid argument: -{}", flag)),
+            }
+          }
+        }
+        _ => {
+          pattern = Some(arg);
+          break;
+        }
+      }
+    }
+    let pattern = match pattern.or_else(|| args.next()) {
+      Some(pattern) if !pattern.is_empty() => pattern,
+      _ => return Err("E471: Argument required".to_string()),
+    };
+    options.pattern = pattern;
+    options.paths = args.map(PathBuf::from).collect();
+    Ok(options)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The search request of `:grep`, it's started by the event loop.
+pub struct GrepRequest {
+  pub options: GrepOptions,
+  /// Jump to the first match once the search is finished, i.e. it's `false` for `:grep!`.
+  pub jump: bool,
+}
+
+/// Grep ID.
+pub type GrepId = usize;
+
+/// Get the next grep ID, it's unique in the process.
+pub fn next_grep_id() -> GrepId {
+  static VALUE: AtomicUsize = AtomicUsize::new(1);
+  VALUE.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+/// The search progress.
+pub struct GrepSummary {
+  /// The matched lines.
+  pub matches: usize,
+  /// The files that have matches.
+  pub matched_files: usize,
+  /// The searched files.
+  pub searched_files: usize,
+}
+
+impl GrepSummary {
+  /// The text rendered in the statusline component while searching, i.e. `grep: 12 matches (340
+  /// files)`.
+  pub fn progress(&self) -> String {
+    format!(
+      "grep: {} matches ({} files)",
+      self.matches, self.searched_files
+    )
+  }
+
+  /// The message shown once the search is finished, i.e. `12 matches in 3 files`.
+  pub fn format(&self) -> String {
+    format!("{} matches in {} files", self.matches, self.matched_files)
+  }
+}
+
+// The displayed path, the `./` prefix of the working directory is removed.
+fn display_path(path: &Path) -> PathBuf {
+  path.strip_prefix(".").unwrap_or(path).to_path_buf()
+}
+
+/// Run the search, it blocks the current thread until the search is finished or cancelled. The
+/// matches are passed to `on_batch` along with the progress, in the order of file paths, it stops
+/// the search if it returns `false`. The search also stops once `is_cancelled` returns `true`.
+///
+/// The binary files and the unreadable files are skipped.
+///
+/// # Errors
+///
+/// If the pattern is invalid, or it's [`CANCELLED`].
+pub fn run_grep<C, F>(
+  options: &GrepOptions,
+  is_cancelled: C,
+  mut on_batch: F,
+) -> Result<GrepSummary, String>
+where
+  C: Fn() -> bool,
+  F: FnMut(Vec<QuickfixEntry>, GrepSummary) -> bool,
+{
+  let matcher = RegexMatcherBuilder::new()
+    .fixed_strings(options.literal)
+    .case_insensitive(options.ignore_case)
+    .case_smart(options.smart_case)
+    .build(&options.pattern)
+    .map_err(|e| format!("E383: Invalid search string {:?}: {}", options.pattern, e))?;
+  let paths = if options.paths.is_empty() {
+    vec![PathBuf::from(".")]
+  } else {
+    options.paths.clone()
+  };
+  let mut walker = WalkBuilder::new(&paths[0]);
+  for path in paths.iter().skip(1) {
+    walker.add(path);
+  }
+  walker
+    .standard_filters(!options.no_ignore)
+    .hidden(!options.hidden)
+    .sort_by_file_name(|a, b| a.cmp(b));
+  let mut searcher = SearcherBuilder::new()
+    .binary_detection(BinaryDetection::quit(b'\x00'))
+    .line_number(true)
+    .build();
+
+  let mut summary = GrepSummary::default();
+  let mut batch = vec![];
+  let mut last_sent = Instant::now();
+  for entry in walker.build() {
+    if is_cancelled() {
+      return Err(CANCELLED.to_string());
+    }
+    let entry = match entry {
+      Ok(entry) => entry,
+      Err(e) => {
+        trace!("Skip unreadable entry: {:?}", e);
+        continue;
+      }
+    };
+    if !entry.file_type().is_some_and(|t| t.is_file()) {
+      continue;
+    }
+    let path = display_path(entry.path());
+    let matched = batch.len();
+    let result = searcher.search_path(
+      &matcher,
+      entry.path(),
+      Lossy(|line_number, line| {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let char_idx = match matcher.find(line.as_bytes()) {
+          Ok(Some(m)) => line[..m.start()].chars().count(),
+          _ => 0,
+        };
+        batch.push(QuickfixEntry::new(
+          path.clone(),
+          (line_number as usize).saturating_sub(1),
+          char_idx,
+          line.to_string(),
+        ));
+        Ok(!is_cancelled())
+      }),
+    );
+    if let Err(e) = result {
+      trace!("Skip unreadable file {:?}: {:?}", path, e);
+    }
+    summary.searched_files += 1;
+    if batch.len() > matched {
+      summary.matches += batch.len() - matched;
+      summary.matched_files += 1;
+    }
+    if batch.len() >= BATCH_SIZE || last_sent.elapsed() >= BATCH_INTERVAL {
+      if !on_batch(std::mem::take(&mut batch), summary) {
+        return Err(CANCELLED.to_string());
+      }
+      last_sent = Instant::now();
+    }
+  }
+  if is_cancelled() {
+    return Err(CANCELLED.to_string());
+  }
+  if !batch.is_empty() && !on_batch(batch, summary) {
+    return Err(CANCELLED.to_string());
+  }
+  Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use assert_fs::prelude::*;
+
+  #[test]
+  fn parse1() {
+    let options = GrepOptions::parse("-Fi -- -foo src lib").unwrap();
+    assert!(options.literal && options.ignore_case);
+    assert_eq!(options.pattern, "-foo");
+    assert_eq!(
+      options.paths,
+      vec![PathBuf::from("src"), PathBuf::from("lib")]
+    );
+
+    let options = GrepOptions::parse(r#""fn main" 'a b'/c\ d"#).unwrap();
+    assert_eq!(options.pattern, "fn main");
+    assert_eq!(options.paths, vec![PathBuf::from("a b/c d")]);
+
+    // The regex escapes are kept.
+    let options = GrepOptions::parse(r#"-S \bfoo\b "say \"hi\"""#).unwrap();
+    assert!(options.smart_case);
+    assert_eq!(options.pattern, r"\bfoo\b");
+    assert_eq!(options.paths, vec![PathBuf::from(r#"say "hi""#)]);
+
+    assert!(GrepOptions::parse("").is_err());
+    assert!(GrepOptions::parse("-F").is_err());
+    assert!(GrepOptions::parse("-x foo").is_err());
+    assert!(GrepOptions::parse("\"foo").is_err());
+  }
+
+  #[test]
+  fn run_grep1() {
+    let tmp = assert_fs::TempDir::new().unwrap();
+    tmp.child(".gitignore").write_str("target\n").unwrap();
+    tmp
+      .child("src/main.rs")
+      .write_str("fn main() {\n  let a = foo(1);\n}\n")
+      .unwrap();
+    tmp
+      .child("src/lib.rs")
+      .write_str("pub fn foo(a: i32) -> i32 {\n  a + 1\n}\n")
+      .unwrap();
+    tmp.child("target/out.rs").write_str("foo\n").unwrap();
+    tmp.child("data.bin").write_binary(b"foo\x00\x01").unwrap();
+    // The ignore files are only respected in git repositories by default.
+    tmp.child(".git/HEAD").touch().unwrap();
+
+    let run = |options: &GrepOptions| {
+      let mut entries = vec![];
+      let summary = run_grep(
+        options,
+        || false,
+        |batch, _| {
+          entries.extend(batch);
+          true
+        },
+      );
+      summary.map(|summary| (summary, entries))
+    };
+    let options = GrepOptions {
+      pattern: r"foo\(".to_string(),
+      paths: vec![tmp.path().to_path_buf()],
+      ..Default::default()
+    };
+    let (summary, entries) = run(&options).unwrap();
+    assert_eq!(summary.matches, 2);
+    assert_eq!(summary.matched_files, 2);
+    assert_eq!(entries[0].path, tmp.path().join("src/lib.rs"));
+    assert_eq!((entries[0].line_idx, entries[0].char_idx), (0, 7));
+    assert_eq!(entries[1].path, tmp.path().join("src/main.rs"));
+    assert_eq!((entries[1].line_idx, entries[1].char_idx), (1, 10));
+    assert_eq!(entries[1].text, "  let a = foo(1);");
+
+    // The ignored files are searched with `no_ignore`, the binary files are always skipped.
+    let options = GrepOptions {
+      pattern: "FOO".to_string(),
+      literal: true,
+      ignore_case: true,
+      no_ignore: true,
+      paths: vec![tmp.path().to_path_buf()],
+      ..Default::default()
+    };
+    let (summary, entries) = run(&options).unwrap();
+    assert_eq!(summary.matches, 3);
+    assert_eq!(entries[2].path, tmp.path().join("target/out.rs"));
+
+    let options = GrepOptions {
+      pattern: "(".to_string(),
+      ..Default::default()
+    };
+    assert!(run(&options).is_err());
+
+    let options = GrepOptions {
+      pattern: "foo".to_string(),
+      paths: vec![tmp.path().to_path_buf()],
+      ..Default::default()
+    };
+    assert_eq!(
+      run_grep(&options, || true, |_, _| true),
+      Err(CANCELLED.to_string())
+    );
+  }
+}
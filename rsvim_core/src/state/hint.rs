@@ -0,0 +1,252 @@
+//! Hint-based jump motion.
+//!
+//! It labels the visible positions in current window with hint chars, then jumps to a position by
+//! typing its label, i.e. the [easymotion](https://github.com/easymotion/vim-easymotion) and
+//! [leap](https://github.com/ggandor/leap.nvim) plugins. There are two kinds of targets:
+//!
+//! 1. Word starts: Triggered by `S` in normal mode, all the visible word starts are labeled.
+//! 2. Two-char search: Triggered by `s` in normal mode, it reads 2 chars first, then all the
+//!    visible occurrences of them are labeled.
+//!
+//! When there are more targets than the hint keys, the labels have 2 chars, and the nearest
+//! targets (to the cursor) come first.
+
+use crate::defaults;
+
+use std::collections::BTreeMap;
+
+/// The position of a jump target, i.e. `(line_idx, char_idx)`.
+pub type HintPosition = (usize, usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The kind of jump targets.
+pub enum HintKind {
+  /// Word starts.
+  Word,
+  /// Occurrences of 2 typed chars.
+  Pair,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The result of typing a char in hint jump.
+pub enum HintInput {
+  /// Need more chars, i.e. the search chars or the rest of a 2-char label.
+  Pending,
+  /// The search chars are complete, the targets need to be labeled.
+  Search(String),
+  /// Jump to the target.
+  Jump(HintPosition),
+  /// No target matches.
+  Cancel,
+}
+
+/// Whether a char is a word char, i.e. the 'iskeyword' option.
+fn is_word_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+/// Find all the word starts in the line, returns the char indexes.
+pub fn find_word_starts(line: &str) -> Vec<usize> {
+  let mut result = vec![];
+  let mut last_is_word = false;
+  for (i, c) in line.chars().enumerate() {
+    let is_word = is_word_char(c);
+    if is_word && !last_is_word {
+      result.push(i);
+    }
+    last_is_word = is_word;
+  }
+  result
+}
+
+/// Find all the occurrences of the pattern in the line, returns the char indexes.
+///
+/// NOTE: The occurrences can overlap, i.e. `aa` in `aaa` are 0 and 1.
+pub fn find_pattern_starts(line: &str, pattern: &str) -> Vec<usize> {
+  let chars: Vec<char> = line.chars().collect();
+  let pattern: Vec<char> = pattern.chars().collect();
+  if pattern.is_empty() || chars.len() < pattern.len() {
+    return vec![];
+  }
+  (0..=chars.len() - pattern.len())
+    .filter(|i| chars[*i..*i + pattern.len()] == pattern[..])
+    .collect()
+}
+
+/// Generate `count` labels with the hint keys, the labels have 1 char if the keys are enough, or
+/// 2 chars otherwise. The count is truncated to the maximum `keys * keys`.
+pub fn generate_labels(count: usize, keys: &str) -> Vec<String> {
+  let keys: Vec<char> = keys.chars().collect();
+  if count <= keys.len() {
+    return keys.iter().take(count).map(|c| c.to_string()).collect();
+  }
+  let count = std::cmp::min(count, keys.len() * keys.len());
+  (0..count)
+    .map(|i| format!("{}{}", keys[i / keys.len()], keys[i % keys.len()]))
+    .collect()
+}
+
+#[derive(Debug, Clone)]
+/// The hint jump state.
+pub struct HintJump {
+  kind: HintKind,
+
+  // Typed search chars, only for the two-char search.
+  pattern: String,
+
+  // Labeled targets.
+  targets: Vec<(String, HintPosition)>,
+
+  // Typed label chars.
+  typed: String,
+}
+
+impl HintJump {
+  pub fn new(kind: HintKind) -> Self {
+    HintJump {
+      kind,
+      pattern: String::new(),
+      targets: vec![],
+      typed: String::new(),
+    }
+  }
+
+  pub fn kind(&self) -> HintKind {
+    self.kind
+  }
+
+  /// Whether the targets are labeled, i.e. it's waiting for the label chars.
+  pub fn is_labeled(&self) -> bool {
+    !self.targets.is_empty()
+  }
+
+  /// Label the targets, the nearest targets to the cursor get the labels first.
+  ///
+  /// Returns `false` if there's no target.
+  pub fn label_targets(&mut self, mut targets: Vec<HintPosition>, cursor: HintPosition) -> bool {
+    targets.retain(|target| *target != cursor);
+    targets.sort_by_key(|(line_idx, char_idx)| {
+      (line_idx.abs_diff(cursor.0), char_idx.abs_diff(cursor.1))
+    });
+    let labels = generate_labels(targets.len(), defaults::hint::HINT_KEYS);
+    self.targets = labels.into_iter().zip(targets).collect();
+    self.typed.clear();
+    !self.targets.is_empty()
+  }
+
+  /// Type a char.
+  pub fn input(&mut self, c: char) -> HintInput {
+    if !self.is_labeled() {
+      if self.kind == HintKind::Word {
+        return HintInput::Cancel;
+      }
+      self.pattern.push(c);
+      return if self.pattern.chars().count() < 2 {
+        HintInput::Pending
+      } else {
+        HintInput::Search(self.pattern.clone())
+      };
+    }
+
+    self.typed.push(c);
+    if let Some((_, target)) = self.targets.iter().find(|(label, _)| *label == self.typed) {
+      return HintInput::Jump(*target);
+    }
+    if self
+      .targets
+      .iter()
+      .any(|(label, _)| label.starts_with(&self.typed))
+    {
+      HintInput::Pending
+    } else {
+      HintInput::Cancel
+    }
+  }
+
+  /// The labels to display, i.e. the label chars not typed yet, maps from buffer line index to
+  /// char index and the label char. A 2-char label occupies the target char and its next char.
+  pub fn labels(&self) -> BTreeMap<usize, BTreeMap<usize, char>> {
+    let mut result: BTreeMap<usize, BTreeMap<usize, char>> = BTreeMap::new();
+    for (label, (line_idx, char_idx)) in self.targets.iter() {
+      if let Some(rest) = label.strip_prefix(self.typed.as_str()) {
+        let line = result.entry(*line_idx).or_default();
+        for (i, c) in rest.chars().enumerate() {
+          // Don't overwrite the first label char of another target.
+          line.entry(char_idx + i).or_insert(c);
+        }
+      }
+    }
+    result
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn find_word_starts1() {
+    assert_eq!(
+      find_word_starts("let foo_bar = foo(bar);"),
+      vec![0, 4, 14, 18]
+    );
+    assert_eq!(find_word_starts(""), Vec::<usize>::new());
+    assert_eq!(find_word_starts("  你好 世界"), vec![2, 5]);
+  }
+
+  #[test]
+  fn find_pattern_starts1() {
+    assert_eq!(find_pattern_starts("foo bar foo", "fo"), vec![0, 8]);
+    assert_eq!(find_pattern_starts("aaa", "aa"), vec![0, 1]);
+    assert_eq!(find_pattern_starts("a", "aa"), Vec::<usize>::new());
+  }
+
+  #[test]
+  fn generate_labels1() {
+    assert_eq!(generate_labels(3, "abc"), vec!["a", "b", "c"]);
+    assert_eq!(
+      generate_labels(5, "abc"),
+      vec!["aa", "ab", "ac", "ba", "bb"]
+    );
+    assert_eq!(generate_labels(100, "ab").len(), 4);
+  }
+
+  #[test]
+  fn input1() {
+    let mut hint = HintJump::new(HintKind::Pair);
+    assert_eq!(hint.input('f'), HintInput::Pending);
+    assert_eq!(hint.input('o'), HintInput::Search("fo".to_string()));
+    assert!(hint.label_targets(vec![(0, 0), (3, 2), (1, 4)], (0, 0)));
+    assert_eq!(
+      hint.labels(),
+      BTreeMap::from([
+        (1, BTreeMap::from([(4, 'a')])),
+        (3, BTreeMap::from([(2, 's')])),
+      ])
+    );
+    assert_eq!(hint.input('s'), HintInput::Jump((3, 2)));
+
+    let mut hint = HintJump::new(HintKind::Word);
+    assert!(!hint.label_targets(vec![], (0, 0)));
+    assert_eq!(hint.input('a'), HintInput::Cancel);
+  }
+
+  #[test]
+  fn input2() {
+    let mut hint = HintJump::new(HintKind::Word);
+    let targets = (0..30).map(|i| (i, 0)).collect();
+    assert!(hint.label_targets(targets, (0, 5)));
+    assert_eq!(
+      hint.labels().get(&0),
+      Some(&BTreeMap::from([(0, 'a'), (1, 'a')]))
+    );
+    assert_eq!(hint.input('a'), HintInput::Pending);
+    assert_eq!(hint.labels().get(&0), Some(&BTreeMap::from([(0, 'a')])));
+    assert_eq!(hint.labels().get(&29), None);
+    assert_eq!(hint.input('s'), HintInput::Jump((1, 0)));
+
+    let mut hint = HintJump::new(HintKind::Word);
+    assert!(hint.label_targets(vec![(0, 1)], (0, 5)));
+    assert_eq!(hint.input('z'), HintInput::Cancel);
+  }
+}
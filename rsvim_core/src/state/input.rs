@@ -0,0 +1,477 @@
+//! Input queue.
+//!
+//! The plugins can wait for a single key outside the key mappings with `Rsvim.input.getchar()`,
+//! i.e. the "press any key" prompt, hint jumps, custom operators. The waiting requests are queued
+//! here, the next key press is consumed by the first request instead of the editing mode, and the
+//! request is dropped if it's timeout.
+//!
+//! The queue also tracks the typed ahead keys (received while the event loop is blocked) that are
+//! not handled yet, see `Rsvim.input.typeahead()`.
+//!
+//! In the batch mode (i.e. the macro playback, the batch commands like `:bufdo`, the headless
+//! scripts and UI tests), the prompts must not wait for user, otherwise the editor hangs. The
+//! `getchar` requests are answered with `null` immediately (same with timeout) thus the prompts
+//! take their default answers, see `Rsvim.input.batchBegin()`/`Rsvim.input.batchEnd()`. The batch
+//! mode can be nested, it's ended by the outermost `batchEnd`.
+
+use crate::js::JsFutureId;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::collections::VecDeque;
+
+/// Format the key in vim's key notation, i.e. `a`, `<Esc>`, `<C-a>`, `<S-Tab>`.
+///
+/// Returns `None` if the key cannot be represented, i.e. the media keys.
+pub fn key_notation(key_event: &KeyEvent) -> Option<String> {
+  let name = match key_event.code {
+    KeyCode::Char(' ') => "Space".to_string(),
+    KeyCode::Char('<') => "lt".to_string(),
+    KeyCode::Char(c) => c.to_string(),
+    KeyCode::Backspace => "BS".to_string(),
+    KeyCode::Enter => "CR".to_string(),
+    KeyCode::Left => "Left".to_string(),
+    KeyCode::Right => "Right".to_string(),
+    KeyCode::Up => "Up".to_string(),
+    KeyCode::Down => "Down".to_string(),
+    KeyCode::Home => "Home".to_string(),
+    KeyCode::End => "End".to_string(),
+    KeyCode::PageUp => "PageUp".to_string(),
+    KeyCode::PageDown => "PageDown".to_string(),
+    KeyCode::Tab => "Tab".to_string(),
+    KeyCode::BackTab => "Tab".to_string(),
+    KeyCode::Delete => "Del".to_string(),
+    KeyCode::Insert => "Insert".to_string(),
+    KeyCode::F(n) => format!("F{}", n),
+    KeyCode::Esc => "Esc".to_string(),
+    _ => return None,
+  };
+
+  let mut prefix = String::new();
+  if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+    prefix.push_str("C-");
+  }
+  if key_event.modifiers.contains(KeyModifiers::ALT) {
+    prefix.push_str("M-");
+  }
+  // The shift is already applied to the chars, i.e. `A`.
+  let is_char = matches!(key_event.code, KeyCode::Char(c) if c != ' ' && c != '<');
+  let shift =
+    key_event.modifiers.contains(KeyModifiers::SHIFT) || key_event.code == KeyCode::BackTab;
+  if shift && !is_char {
+    prefix.push_str("S-");
+  }
+
+  if prefix.is_empty() && is_char {
+    Some(name)
+  } else {
+    Some(format!("<{}{}>", prefix, name))
+  }
+}
+
+/// Parse a key in vim's key notation, i.e. `a`, `<Esc>`, `<C-a>`, `<S-Tab>`. The key names and
+/// modifiers are case-insensitive, the `A-` is an alias of `M-`. It's the reverse of
+/// [`key_notation`].
+///
+/// Returns `None` if the notation is invalid.
+pub fn parse_key_notation(notation: &str) -> Option<KeyEvent> {
+  let mut chars = notation.chars();
+  if let (Some(c), None) = (chars.next(), chars.next()) {
+    let modifiers = if c.is_ascii_uppercase() {
+      KeyModifiers::SHIFT
+    } else {
+      KeyModifiers::NONE
+    };
+    return Some(KeyEvent::new(KeyCode::Char(c), modifiers));
+  }
+
+  let mut rest = notation.strip_prefix('<')?.strip_suffix('>')?;
+  let mut modifiers = KeyModifiers::NONE;
+  // The modifiers prefix, i.e. `C-`, but the `-` itself is a key name, i.e. `<C-->`.
+  while rest.len() > 2 && rest.as_bytes()[1] == b'-' {
+    match rest.as_bytes()[0].to_ascii_uppercase() {
+      b'C' => modifiers |= KeyModifiers::CONTROL,
+      b'M' | b'A' => modifiers |= KeyModifiers::ALT,
+      b'S' => modifiers |= KeyModifiers::SHIFT,
+      _ => return None,
+    }
+    rest = &rest[2..];
+  }
+
+  let mut chars = rest.chars();
+  let code = match (chars.next(), chars.next()) {
+    // The char without modifiers is not a key notation, i.e. `<b>`.
+    (Some(c), None) if !modifiers.is_empty() => KeyCode::Char(c),
+    _ => match rest.to_ascii_lowercase().as_str() {
+      "space" => KeyCode::Char(' '),
+      "lt" => KeyCode::Char('<'),
+      "bar" => KeyCode::Char('|'),
+      "bslash" => KeyCode::Char('\\'),
+      "bs" | "backspace" => KeyCode::Backspace,
+      "cr" | "enter" | "return" => KeyCode::Enter,
+      "left" => KeyCode::Left,
+      "right" => KeyCode::Right,
+      "up" => KeyCode::Up,
+      "down" => KeyCode::Down,
+      "home" => KeyCode::Home,
+      "end" => KeyCode::End,
+      "pageup" => KeyCode::PageUp,
+      "pagedown" => KeyCode::PageDown,
+      "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+      "tab" => KeyCode::Tab,
+      "del" | "delete" => KeyCode::Delete,
+      "insert" => KeyCode::Insert,
+      "esc" => KeyCode::Esc,
+      name => match name.strip_prefix('f').map(|n| n.parse::<u8>()) {
+        Some(Ok(n)) if (1..=12).contains(&n) => KeyCode::F(n),
+        _ => return None,
+      },
+    },
+  };
+
+  // The `CTRL` chars are case-insensitive, and the shift is applied to other chars.
+  let code = match code {
+    KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+      modifiers -= KeyModifiers::SHIFT;
+      KeyCode::Char(c.to_ascii_lowercase())
+    }
+    KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+      KeyCode::Char(c.to_ascii_uppercase())
+    }
+    KeyCode::Char(c) if c.is_ascii_uppercase() => {
+      modifiers |= KeyModifiers::SHIFT;
+      code
+    }
+    _ => code,
+  };
+  Some(KeyEvent::new(code, modifiers))
+}
+
+/// Parse the keys sequence in vim's key notation, i.e. `<C-w>v`, `<Leader>ff`. The `<Leader>` is
+/// replaced with the `leader` keys. The `<` without a valid key notation is the `<` key itself,
+/// i.e. `a<b`.
+pub fn parse_keys(keys: &str, leader: &str) -> Vec<KeyEvent> {
+  let mut result = vec![];
+  let mut rest = keys;
+  while let Some(c) = rest.chars().next() {
+    if c == '<' {
+      if let Some(end) = rest[1..].find('>') {
+        let notation = &rest[..end + 2];
+        if notation.eq_ignore_ascii_case("<Leader>") {
+          result.extend(parse_keys(leader, ""));
+          rest = &rest[notation.len()..];
+          continue;
+        }
+        if let Some(key_event) = parse_key_notation(notation) {
+          result.push(key_event);
+          rest = &rest[notation.len()..];
+          continue;
+        }
+      }
+    }
+    result.push(parse_key_notation(&c.to_string()).unwrap());
+    rest = &rest[c.len_utf8()..];
+  }
+  result
+}
+
+/// Normalize the key decoded from the terminal to the same form parsed by [`parse_key_notation`],
+/// thus the same key typed in different terminals is mapped by the same notation:
+///
+/// - The `Meta` modifier is the `Alt` modifier, i.e. `<M-j>` is `<A-j>`. The `Alt` chords are
+///   either encoded as `Esc` prefixed keys (i.e. xterm, iTerm2, tmux) which are decoded with the
+///   `Alt` modifier (see [`escape`](crate::state::escape)), or reported with the `Alt`/`Meta`
+///   modifier (i.e. the kitty keyboard protocol, Windows console).
+/// - The `CTRL` chars are case-insensitive, i.e. `<C-A>` is `<C-a>`.
+/// - The shift is applied to the letters, i.e. the kitty keyboard protocol reports `Alt+Shift+j`
+///   as `j` with `Shift`, it's `<M-J>`.
+/// - The key kind (press/repeat/release) and state are dropped.
+pub fn normalize_key(key_event: KeyEvent) -> KeyEvent {
+  let mut modifiers = key_event.modifiers;
+  if modifiers.contains(KeyModifiers::META) {
+    modifiers = (modifiers - KeyModifiers::META) | KeyModifiers::ALT;
+  }
+  let code = match key_event.code {
+    KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+      modifiers -= KeyModifiers::SHIFT;
+      KeyCode::Char(c.to_ascii_lowercase())
+    }
+    KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+      KeyCode::Char(c.to_ascii_uppercase())
+    }
+    KeyCode::Char(c) if c.is_ascii_uppercase() => {
+      modifiers |= KeyModifiers::SHIFT;
+      key_event.code
+    }
+    code => code,
+  };
+  KeyEvent::new(code, modifiers)
+}
+
+#[derive(Debug, Clone, Default)]
+/// The input queue.
+pub struct InputQueue {
+  // The waiting `getchar` requests, ordered by request time.
+  getchars: VecDeque<JsFutureId>,
+
+  // The count of typed ahead keys that are not handled yet.
+  typeahead: usize,
+
+  // The nesting depth of the batch mode.
+  batch: usize,
+}
+
+impl InputQueue {
+  pub fn new() -> Self {
+    InputQueue::default()
+  }
+
+  /// Queue a `getchar` request.
+  pub fn push_getchar(&mut self, future_id: JsFutureId) {
+    self.getchars.push_back(future_id);
+  }
+
+  /// Remove a `getchar` request (i.e. it's timeout), returns `false` if it's already resolved.
+  pub fn remove_getchar(&mut self, future_id: JsFutureId) -> bool {
+    match self.getchars.iter().position(|id| *id == future_id) {
+      Some(index) => {
+        self.getchars.remove(index);
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Count of the waiting `getchar` requests.
+  pub fn getchars(&self) -> usize {
+    self.getchars.len()
+  }
+
+  /// Consume the event with the first `getchar` request, returns the request and the key.
+  ///
+  /// Returns `None` if there's no waiting request, or the event is not a key press (it's handled
+  /// by the editing mode as usual).
+  pub fn consume(&mut self, event: &Event) -> Option<(JsFutureId, String)> {
+    if self.getchars.is_empty() {
+      return None;
+    }
+    let key = match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        key_notation(&normalize_key(*key_event))?
+      }
+      _ => return None,
+    };
+    self.getchars.pop_front().map(|future_id| (future_id, key))
+  }
+
+  /// Count of the typed ahead keys that are not handled yet.
+  pub fn typeahead(&self) -> usize {
+    self.typeahead
+  }
+
+  pub fn set_typeahead(&mut self, value: usize) {
+    self.typeahead = value;
+  }
+
+  /// Whether it's in the batch mode, i.e. the `getchar` requests are answered with `null`
+  /// immediately.
+  pub fn is_batch(&self) -> bool {
+    self.batch > 0
+  }
+
+  /// Begin the (nested) batch mode, returns the waiting `getchar` requests, they should be answered
+  /// with `null` as well.
+  pub fn begin_batch(&mut self) -> Vec<JsFutureId> {
+    self.batch += 1;
+    self.getchars.drain(..).collect()
+  }
+
+  /// End the (nested) batch mode, returns `false` if it's not in the batch mode.
+  pub fn end_batch(&mut self) -> bool {
+    if self.batch == 0 {
+      return false;
+    }
+    self.batch -= 1;
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn key_notation1() {
+    let key = |code, modifiers| key_notation(&KeyEvent::new(code, modifiers));
+    assert_eq!(key(KeyCode::Char('a'), KeyModifiers::NONE).unwrap(), "a");
+    assert_eq!(key(KeyCode::Char('A'), KeyModifiers::SHIFT).unwrap(), "A");
+    assert_eq!(
+      key(KeyCode::Char('a'), KeyModifiers::CONTROL).unwrap(),
+      "<C-a>"
+    );
+    assert_eq!(
+      key(KeyCode::Char(' '), KeyModifiers::NONE).unwrap(),
+      "<Space>"
+    );
+    assert_eq!(key(KeyCode::Char('<'), KeyModifiers::NONE).unwrap(), "<lt>");
+    assert_eq!(key(KeyCode::Esc, KeyModifiers::NONE).unwrap(), "<Esc>");
+    assert_eq!(key(KeyCode::Enter, KeyModifiers::ALT).unwrap(), "<M-CR>");
+    assert_eq!(key(KeyCode::Tab, KeyModifiers::SHIFT).unwrap(), "<S-Tab>");
+    assert_eq!(
+      key(KeyCode::BackTab, KeyModifiers::NONE).unwrap(),
+      "<S-Tab>"
+    );
+    assert_eq!(key(KeyCode::F(5), KeyModifiers::NONE).unwrap(), "<F5>");
+    assert!(key(KeyCode::Null, KeyModifiers::NONE).is_none());
+  }
+
+  #[test]
+  fn parse_key_notation1() {
+    let key = |code, modifiers| Some(KeyEvent::new(code, modifiers));
+    assert_eq!(
+      parse_key_notation("a"),
+      key(KeyCode::Char('a'), KeyModifiers::NONE)
+    );
+    assert_eq!(
+      parse_key_notation("A"),
+      key(KeyCode::Char('A'), KeyModifiers::SHIFT)
+    );
+    assert_eq!(
+      parse_key_notation("<c-A>"),
+      key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+    );
+    assert_eq!(
+      parse_key_notation("<A-cr>"),
+      key(KeyCode::Enter, KeyModifiers::ALT)
+    );
+    assert_eq!(
+      parse_key_notation("<S-Tab>"),
+      key(KeyCode::BackTab, KeyModifiers::SHIFT)
+    );
+    assert_eq!(
+      parse_key_notation("<C-->"),
+      key(KeyCode::Char('-'), KeyModifiers::CONTROL)
+    );
+    assert_eq!(
+      parse_key_notation("<F12>"),
+      key(KeyCode::F(12), KeyModifiers::NONE)
+    );
+    assert_eq!(parse_key_notation("<F13>"), None);
+    assert_eq!(parse_key_notation("<b>"), None);
+    assert_eq!(parse_key_notation("<X-a>"), None);
+    assert_eq!(parse_key_notation("<Unknown>"), None);
+
+    // Round trip.
+    for notation in [
+      "a", "<C-a>", "<Space>", "<lt>", "<M-CR>", "<S-Tab>", "<F5>", "<Esc>",
+    ] {
+      let key_event = parse_key_notation(notation).unwrap();
+      assert_eq!(key_notation(&key_event).unwrap(), notation);
+    }
+  }
+
+  #[test]
+  fn batch1() {
+    let mut queue = InputQueue::new();
+    queue.push_getchar(1);
+    queue.push_getchar(2);
+    assert!(!queue.is_batch());
+
+    assert_eq!(queue.begin_batch(), vec![1, 2]);
+    assert_eq!(queue.getchars(), 0);
+    assert!(queue.begin_batch().is_empty());
+    assert!(queue.end_batch());
+    assert!(queue.is_batch());
+    assert!(queue.end_batch());
+    assert!(!queue.is_batch());
+    assert!(!queue.end_batch());
+  }
+
+  #[test]
+  fn parse_keys1() {
+    let keys = |keys: &str| {
+      parse_keys(keys, ",")
+        .iter()
+        .map(|key_event| key_notation(key_event).unwrap())
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(keys("<C-w>v"), vec!["<C-w>", "v"]);
+    assert_eq!(keys("<leader>ff"), vec![",", "f", "f"]);
+    assert_eq!(keys("a<b>"), vec!["a", "<lt>", "b", ">"]);
+    assert_eq!(keys("<>"), vec!["<lt>", ">"]);
+    assert_eq!(keys("你<Esc>"), vec!["你", "<Esc>"]);
+    assert!(keys("").is_empty());
+  }
+
+  #[test]
+  fn normalize_key1() {
+    let key = |code, modifiers| normalize_key(KeyEvent::new(code, modifiers));
+    let notation = |code, modifiers| key_notation(&key(code, modifiers)).unwrap();
+
+    // xterm/iTerm2/tmux: `Esc` prefixed, decoded with `Alt`.
+    assert_eq!(notation(KeyCode::Char('j'), KeyModifiers::ALT), "<M-j>");
+    assert_eq!(
+      notation(KeyCode::Char('J'), KeyModifiers::ALT | KeyModifiers::SHIFT),
+      "<M-J>"
+    );
+    assert_eq!(notation(KeyCode::Enter, KeyModifiers::ALT), "<M-CR>");
+    assert_eq!(
+      notation(
+        KeyCode::Char('a'),
+        KeyModifiers::ALT | KeyModifiers::CONTROL
+      ),
+      "<C-M-a>"
+    );
+    // kitty keyboard protocol: the `Meta` modifier, and the shift is not applied.
+    assert_eq!(notation(KeyCode::Char('j'), KeyModifiers::META), "<M-j>");
+    assert_eq!(
+      notation(KeyCode::Char('j'), KeyModifiers::ALT | KeyModifiers::SHIFT),
+      "<M-J>"
+    );
+    // Windows console: the uppercase without shift, and the `CTRL` char with shift.
+    assert_eq!(notation(KeyCode::Char('J'), KeyModifiers::ALT), "<M-J>");
+    assert_eq!(
+      notation(
+        KeyCode::Char('A'),
+        KeyModifiers::CONTROL | KeyModifiers::SHIFT
+      ),
+      "<C-a>"
+    );
+
+    // Same with the parsed notations.
+    for (notation, code, modifiers) in [
+      ("<M-j>", KeyCode::Char('j'), KeyModifiers::META),
+      (
+        "<A-J>",
+        KeyCode::Char('j'),
+        KeyModifiers::ALT | KeyModifiers::SHIFT,
+      ),
+      ("<M-Enter>", KeyCode::Enter, KeyModifiers::ALT),
+      (
+        "<M-C-a>",
+        KeyCode::Char('A'),
+        KeyModifiers::CONTROL | KeyModifiers::SHIFT | KeyModifiers::META,
+      ),
+    ] {
+      assert_eq!(key(code, modifiers), parse_key_notation(notation).unwrap());
+    }
+  }
+
+  #[test]
+  fn consume1() {
+    let mut queue = InputQueue::new();
+    let a = Event::Key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+    assert_eq!(queue.consume(&a), None);
+
+    queue.push_getchar(1);
+    queue.push_getchar(2);
+    queue.push_getchar(3);
+    assert_eq!(queue.getchars(), 3);
+    assert!(queue.remove_getchar(2));
+    assert!(!queue.remove_getchar(2));
+    assert_eq!(queue.consume(&Event::FocusGained), None);
+    assert_eq!(queue.consume(&a), Some((1, "a".to_string())));
+    assert_eq!(queue.consume(&a), Some((3, "a".to_string())));
+    assert_eq!(queue.consume(&a), None);
+    assert_eq!(queue.getchars(), 0);
+  }
+}
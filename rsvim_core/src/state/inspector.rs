@@ -0,0 +1,267 @@
+//! Marks/registers inspector.
+//!
+//! The `:marks` and `:registers` views list the marks/registers in a structured scratch buffer,
+//! one per line after a header line. For example:
+//!
+//! ```text
+//! mark line  col file/text
+//!  a      10    4 let foo = bar;
+//!  B       1    0 ~/src/main.rs
+//! ```
+//!
+//! ```text
+//! Type Name Content
+//!   c  "a   foo^Jbar
+//!   l  "+   hello world^J
+//! ```
+//!
+//! Moving on a register previews its full content, pressing `ENTER` on a mark jumps to it.
+//!
+//! NOTE: The inspector is data-driven, it only needs the mark/register entries and doesn't modify
+//! the editor itself. The marks/registers owners are responsible for providing the entries and
+//! applying the jump.
+
+use crate::state::register::RegisterEntry;
+
+use std::path::PathBuf;
+
+/// The file type of the `:marks` buffer, see [`inspector`](crate::state::ex::inspector).
+pub const MARKS_FILE_TYPE: &str = "marks";
+
+/// The file type of the `:registers` buffer, see [`inspector`](crate::state::ex::inspector).
+pub const REGISTERS_FILE_TYPE: &str = "registers";
+
+/// Escape the control chars to the printable `^X` form, i.e. line break is `^J`.
+pub fn escape_content(content: &str) -> String {
+  let mut result = String::new();
+  for c in content.chars() {
+    if c.is_ascii_control() {
+      result.push('^');
+      result.push(((c as u8) ^ 0x40) as char);
+    } else {
+      result.push(c);
+    }
+  }
+  result
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A mark entry.
+pub struct MarkEntry {
+  /// Mark name, i.e. `a`-`z`, `A`-`Z`, `'`.
+  pub name: char,
+  /// Line index, starts from 0.
+  pub line_idx: usize,
+  /// Char index, starts from 0.
+  pub char_idx: usize,
+  /// The file of the mark, only for the file marks (i.e. `A`-`Z`) in other buffers.
+  pub file: Option<PathBuf>,
+  /// The line content of the mark, only for the marks in current buffer.
+  pub text: String,
+}
+
+/// Format the `:marks` lines, the first line is the header.
+pub fn format_marks(marks: &[MarkEntry]) -> Vec<String> {
+  let mut lines = vec!["mark line  col file/text".to_string()];
+  for mark in marks {
+    let text = match &mark.file {
+      Some(file) => file.display().to_string(),
+      None => escape_content(mark.text.trim_start()),
+    };
+    lines.push(format!(
+      " {} {:>6} {:>4} {}",
+      mark.name,
+      mark.line_idx + 1,
+      mark.char_idx,
+      text
+    ));
+  }
+  lines
+}
+
+/// Format the `:registers` lines, the first line is the header.
+pub fn format_registers(registers: &[RegisterEntry]) -> Vec<String> {
+  let mut lines = vec!["Type Name Content".to_string()];
+  for register in registers {
+    lines.push(format!(
+      "  {}  \"{}   {}",
+      register.kind.short_name(),
+      register.name,
+      escape_content(&register.content)
+    ));
+  }
+  lines
+}
+
+#[derive(Debug, Clone)]
+/// The entries shown in the inspector.
+pub enum InspectorEntries {
+  Marks(Vec<MarkEntry>),
+  Registers(Vec<RegisterEntry>),
+}
+
+#[derive(Debug, Clone)]
+/// The marks/registers inspector.
+pub struct Inspector {
+  entries: InspectorEntries,
+  lines: Vec<String>,
+  // Index of the selected entry.
+  selected: usize,
+}
+
+impl Inspector {
+  pub fn new(entries: InspectorEntries) -> Self {
+    let lines = match &entries {
+      InspectorEntries::Marks(marks) => format_marks(marks),
+      InspectorEntries::Registers(registers) => format_registers(registers),
+    };
+    Inspector {
+      entries,
+      lines,
+      selected: 0,
+    }
+  }
+
+  /// Get the scratch buffer lines, the first line is the header.
+  pub fn lines(&self) -> &Vec<String> {
+    &self.lines
+  }
+
+  fn len(&self) -> usize {
+    match &self.entries {
+      InspectorEntries::Marks(marks) => marks.len(),
+      InspectorEntries::Registers(registers) => registers.len(),
+    }
+  }
+
+  /// Get the selected entry index, or `None` if there's no entry.
+  pub fn selected(&self) -> Option<usize> {
+    if self.len() == 0 {
+      None
+    } else {
+      Some(self.selected)
+    }
+  }
+
+  /// Get the line index of the selected entry in the scratch buffer.
+  pub fn selected_line_idx(&self) -> Option<usize> {
+    // Skip the header line.
+    self.selected().map(|selected| selected + 1)
+  }
+
+  /// Select the next entry.
+  pub fn select_next(&mut self) {
+    if self.selected + 1 < self.len() {
+      self.selected += 1;
+    }
+  }
+
+  /// Select the previous entry.
+  pub fn select_prev(&mut self) {
+    self.selected = self.selected.saturating_sub(1);
+  }
+
+  /// Preview the full content of the selected register, it's empty for marks.
+  pub fn preview(&self) -> Vec<String> {
+    match &self.entries {
+      InspectorEntries::Registers(registers) => match registers.get(self.selected) {
+        Some(register) => register.content.lines().map(|l| l.to_string()).collect(),
+        None => vec![],
+      },
+      InspectorEntries::Marks(_) => vec![],
+    }
+  }
+
+  /// Accept the selected entry, i.e. the `ENTER` key.
+  ///
+  /// Returns the mark to jump to, or `None` for registers.
+  pub fn accept(&self) -> Option<&MarkEntry> {
+    match &self.entries {
+      InspectorEntries::Marks(marks) => marks.get(self.selected),
+      InspectorEntries::Registers(_) => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::state::register::RegisterKind;
+
+  #[test]
+  fn escape_content1() {
+    assert_eq!(escape_content("foo\nbar\t"), "foo^Jbar^I");
+    assert_eq!(escape_content("\x1b"), "^[");
+    assert_eq!(escape_content("你好"), "你好");
+  }
+
+  #[test]
+  fn marks1() {
+    let marks = vec![
+      MarkEntry {
+        name: 'a',
+        line_idx: 9,
+        char_idx: 4,
+        file: None,
+        text: "  let foo = bar;".to_string(),
+      },
+      MarkEntry {
+        name: 'B',
+        line_idx: 0,
+        char_idx: 0,
+        file: Some(PathBuf::from("main.rs")),
+        text: String::new(),
+      },
+    ];
+    let mut inspector = Inspector::new(InspectorEntries::Marks(marks));
+    assert_eq!(
+      *inspector.lines(),
+      vec![
+        "mark line  col file/text",
+        " a     10    4 let foo = bar;",
+        " B      1    0 main.rs",
+      ]
+    );
+    assert_eq!(inspector.selected_line_idx(), Some(1));
+    assert!(inspector.preview().is_empty());
+    inspector.select_next();
+    inspector.select_next();
+    assert_eq!(inspector.selected(), Some(1));
+    assert_eq!(inspector.accept().unwrap().name, 'B');
+    inspector.select_prev();
+    assert_eq!(inspector.accept().unwrap().line_idx, 9);
+  }
+
+  #[test]
+  fn registers1() {
+    let registers = vec![
+      RegisterEntry {
+        name: 'a',
+        kind: RegisterKind::Charwise,
+        content: "foo\nbar".to_string(),
+      },
+      RegisterEntry {
+        name: '+',
+        kind: RegisterKind::Linewise,
+        content: "hello world\n".to_string(),
+      },
+    ];
+    let mut inspector = Inspector::new(InspectorEntries::Registers(registers));
+    assert_eq!(
+      *inspector.lines(),
+      vec![
+        "Type Name Content",
+        "  c  \"a   foo^Jbar",
+        "  l  \"+   hello world^J",
+      ]
+    );
+    assert_eq!(inspector.preview(), vec!["foo", "bar"]);
+    inspector.select_next();
+    assert_eq!(inspector.preview(), vec!["hello world"]);
+    assert!(inspector.accept().is_none());
+
+    let inspector = Inspector::new(InspectorEntries::Registers(vec![]));
+    assert_eq!(inspector.selected(), None);
+    assert!(inspector.preview().is_empty());
+  }
+}
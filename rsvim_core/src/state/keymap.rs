@@ -0,0 +1,758 @@
+//! Key mappings, i.e. Vim's `:map` commands.
+//!
+//! The mappings of each mode are stored in a trie keyed by the key notations (see
+//! [`key_notation`]), thus a typed key sequence can be resolved key by key:
+//!
+//! 1. The typed keys are queued in the typeahead, and resolved before they're handled by the
+//!    editing mode.
+//! 2. If the pending keys are the prefix of a longer mapping, the resolver waits for more keys
+//!    until the 'timeoutlen' is reached.
+//! 3. Otherwise the longest mapping matched with the pending keys is applied, its `{rhs}` keys are
+//!    inserted in front of the typeahead, and they're resolved again unless it's `noremap`. The
+//!    unmatched keys are handled by the editing mode as usual.
+//!
+//! The buffer-local mappings take precedence over the global mappings. The `<Leader>` in `{lhs}`
+//! and `{rhs}` is replaced with the 'mapleader' when the mapping is defined.
+//!
+//! See: <https://vimhelp.org/map.txt.html>.
+
+use crate::buf::BufferId;
+use crate::defaults;
+use crate::state::input::{key_notation, normalize_key, parse_keys};
+use crate::state::mode::Mode;
+
+use ahash::AHashMap as HashMap;
+use crossterm::event::KeyEvent;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tracing::error;
+
+/// The mapping callback ID, the callback is stored in the js runtime.
+pub type KeymapCallbackId = i32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The `{rhs}` of a mapping.
+pub enum KeymapRhs {
+  /// The keys to be typed.
+  Keys(Vec<KeyEvent>),
+  /// The js callback.
+  Callback(KeymapCallbackId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The key mapping.
+pub struct Keymap {
+  pub lhs: Vec<KeyEvent>,
+  pub rhs: KeymapRhs,
+  /// The `{rhs}` keys are not mapped again.
+  pub noremap: bool,
+  pub desc: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The resolved action.
+pub enum KeymapAction {
+  /// The key is handled by the editing mode.
+  Key(KeyEvent),
+  /// The mapping callback is called.
+  Callback(KeymapCallbackId),
+}
+
+/// Parse the mode short names of a mapping, i.e. `n`, `nv`, `""` (normal, visual, select and
+/// operator-pending), `!` (insert and command-line).
+///
+/// # Errors
+///
+/// If there's invalid mode name.
+pub fn parse_map_modes(value: &str) -> Result<Vec<Mode>, String> {
+  if value.is_empty() {
+    return Ok(vec![
+      Mode::Normal,
+      Mode::Visual,
+      Mode::Select,
+      Mode::OperatorPending,
+    ]);
+  }
+  let mut modes = vec![];
+  for c in value.chars() {
+    let names: &[Mode] = match c {
+      'n' => &[Mode::Normal],
+      'v' => &[Mode::Visual, Mode::Select],
+      'x' => &[Mode::Visual],
+      's' => &[Mode::Select],
+      'o' => &[Mode::OperatorPending],
+      'i' => &[Mode::Insert],
+      'c' => &[Mode::CommandLine],
+      '!' => &[Mode::Insert, Mode::CommandLine],
+      't' => &[Mode::Terminal],
+      _ => return Err(format!("Invalid mode name {:?} in {:?}", c, value)),
+    };
+    for mode in names {
+      if !modes.contains(mode) {
+        modes.push(*mode);
+      }
+    }
+  }
+  Ok(modes)
+}
+
+#[derive(Debug, Clone, Default)]
+struct KeymapTrie {
+  children: HashMap<String, KeymapTrie>,
+  keymap: Option<Keymap>,
+}
+
+impl KeymapTrie {
+  fn insert(&mut self, keys: &[String], keymap: Keymap) -> Option<Keymap> {
+    let mut node = self;
+    for key in keys {
+      node = node.children.entry(key.clone()).or_default();
+    }
+    node.keymap.replace(keymap)
+  }
+
+  fn remove(&mut self, keys: &[String]) -> Option<Keymap> {
+    match keys.split_first() {
+      None => self.keymap.take(),
+      Some((key, rest)) => {
+        let child = self.children.get_mut(key)?;
+        let removed = child.remove(rest);
+        // Prune the empty node.
+        if child.keymap.is_none() && child.children.is_empty() {
+          self.children.remove(key);
+        }
+        removed
+      }
+    }
+  }
+
+  fn find(&self, keys: &[String]) -> Option<&KeymapTrie> {
+    keys
+      .iter()
+      .try_fold(self, |node, key| node.children.get(key))
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The key mappings and the resolver.
+pub struct Keymaps {
+  global: HashMap<Mode, KeymapTrie>,
+  local: HashMap<(BufferId, Mode), KeymapTrie>,
+  leader: String,
+  timeout_len: Duration,
+  next_callback_id: KeymapCallbackId,
+
+  // The keys waiting for a longer mapping, and the deadline.
+  pending: Vec<KeyEvent>,
+  deadline: Option<Instant>,
+  // The keys to be resolved, with the flag whether it can be mapped.
+  typeahead: VecDeque<(KeyEvent, bool)>,
+  // The count of mappings applied since the typeahead is empty.
+  depth: usize,
+}
+
+impl Default for Keymaps {
+  fn default() -> Self {
+    Keymaps::new()
+  }
+}
+
+impl Keymaps {
+  pub fn new() -> Self {
+    Keymaps {
+      global: HashMap::new(),
+      local: HashMap::new(),
+      leader: defaults::keymap::LEADER.to_string(),
+      timeout_len: defaults::keymap::TIMEOUT_LEN,
+      next_callback_id: 1,
+      pending: vec![],
+      deadline: None,
+      typeahead: VecDeque::new(),
+      depth: 0,
+    }
+  }
+
+  /// Get the 'mapleader' option.
+  pub fn leader(&self) -> &str {
+    &self.leader
+  }
+
+  /// Set the 'mapleader' option, it only affects the mappings defined after it.
+  pub fn set_leader(&mut self, leader: &str) {
+    self.leader = leader.to_string();
+  }
+
+  /// Get the 'timeoutlen' option.
+  pub fn timeout_len(&self) -> Duration {
+    self.timeout_len
+  }
+
+  pub fn set_timeout_len(&mut self, timeout_len: Duration) {
+    self.timeout_len = timeout_len;
+  }
+
+  /// Allocate a new callback ID.
+  pub fn next_callback_id(&mut self) -> KeymapCallbackId {
+    let id = self.next_callback_id;
+    self.next_callback_id += 1;
+    id
+  }
+
+  /// Parse the keys in key notation, the `<Leader>` is replaced with 'mapleader'.
+  pub fn parse_keys(&self, keys: &str) -> Vec<KeyEvent> {
+    parse_keys(keys, &self.leader)
+      .into_iter()
+      .map(normalize_key)
+      .collect()
+  }
+
+  fn notations(keys: &[KeyEvent]) -> Option<Vec<String>> {
+    keys.iter().map(key_notation).collect()
+  }
+
+  fn trie_mut(&mut self, mode: Mode, buffer_id: Option<BufferId>) -> &mut KeymapTrie {
+    match buffer_id {
+      Some(buffer_id) => self.local.entry((buffer_id, mode)).or_default(),
+      None => self.global.entry(mode).or_default(),
+    }
+  }
+
+  fn trie(&self, mode: Mode, buffer_id: Option<BufferId>) -> Option<&KeymapTrie> {
+    match buffer_id {
+      Some(buffer_id) => self.local.get(&(buffer_id, mode)),
+      None => self.global.get(&mode),
+    }
+  }
+
+  /// Define a mapping in the modes, it's buffer-local if `buffer_id` is given. Returns the
+  /// replaced mappings.
+  ///
+  /// # Errors
+  ///
+  /// If the `{lhs}` is empty or contains invalid keys.
+  pub fn set(
+    &mut self,
+    modes: &[Mode],
+    buffer_id: Option<BufferId>,
+    lhs: &str,
+    rhs: KeymapRhs,
+    noremap: bool,
+    desc: &str,
+  ) -> Result<Vec<Keymap>, String> {
+    let lhs_keys = self.parse_keys(lhs);
+    let notations = match Self::notations(&lhs_keys) {
+      Some(notations) if !notations.is_empty() => notations,
+      _ => return Err(format!("Invalid mapping lhs {:?}", lhs)),
+    };
+    let keymap = Keymap {
+      lhs: lhs_keys,
+      rhs,
+      noremap,
+      desc: desc.to_string(),
+    };
+    let replaced = modes
+      .iter()
+      .filter_map(|mode| {
+        self
+          .trie_mut(*mode, buffer_id)
+          .insert(&notations, keymap.clone())
+      })
+      .collect();
+    Ok(replaced)
+  }
+
+  /// Remove the mapping in the modes, returns the removed mappings.
+  pub fn del(&mut self, modes: &[Mode], buffer_id: Option<BufferId>, lhs: &str) -> Vec<Keymap> {
+    let notations = match Self::notations(&self.parse_keys(lhs)) {
+      Some(notations) => notations,
+      None => return vec![],
+    };
+    modes
+      .iter()
+      .filter_map(|mode| self.trie_mut(*mode, buffer_id).remove(&notations))
+      .collect()
+  }
+
+  /// Get the mapping of the `{lhs}`.
+  pub fn get(&self, mode: Mode, buffer_id: Option<BufferId>, lhs: &str) -> Option<&Keymap> {
+    let notations = Self::notations(&self.parse_keys(lhs))?;
+    self
+      .trie(mode, buffer_id)?
+      .find(&notations)?
+      .keymap
+      .as_ref()
+  }
+
+  /// Remove all the mappings and the pending keys, and reset the 'mapleader' and 'timeoutlen'
+  /// options, i.e. before the user config is reloaded. The callback IDs are not reused.
+  pub fn clear(&mut self) {
+    *self = Keymaps {
+      next_callback_id: self.next_callback_id,
+      typeahead: std::mem::take(&mut self.typeahead),
+      ..Keymaps::new()
+    };
+  }
+
+  /// Whether the callback is still used by any mapping, i.e. it's not replaced or removed.
+  pub fn is_callback_used(&self, id: KeymapCallbackId) -> bool {
+    let mut nodes = self
+      .global
+      .values()
+      .chain(self.local.values())
+      .collect::<Vec<_>>();
+    while let Some(node) = nodes.pop() {
+      if node
+        .keymap
+        .as_ref()
+        .is_some_and(|keymap| keymap.rhs == KeymapRhs::Callback(id))
+      {
+        return true;
+      }
+      nodes.extend(node.children.values());
+    }
+    false
+  }
+
+  /// Remove all the buffer-local mappings of the buffer, i.e. it's deleted. Returns the removed
+  /// mappings.
+  pub fn clear_buffer(&mut self, buffer_id: BufferId) -> Vec<Keymap> {
+    let mut removed = vec![];
+    self.local.retain(|(id, _), trie| {
+      if *id != buffer_id {
+        return true;
+      }
+      let mut nodes = vec![std::mem::take(trie)];
+      while let Some(node) = nodes.pop() {
+        removed.extend(node.keymap);
+        nodes.extend(node.children.into_values());
+      }
+      false
+    });
+    removed
+  }
+
+  // Lookup the keys, returns the exactly matched mapping and whether there're longer mappings.
+  fn lookup(
+    &self,
+    mode: Mode,
+    buffer_id: Option<BufferId>,
+    keys: &[KeyEvent],
+  ) -> (Option<&Keymap>, bool) {
+    let notations = match Self::notations(keys) {
+      Some(notations) => notations,
+      None => return (None, false),
+    };
+    let local = buffer_id
+      .and_then(|buffer_id| self.trie(mode, Some(buffer_id)))
+      .and_then(|trie| trie.find(&notations));
+    let global = self.trie(mode, None).and_then(|trie| trie.find(&notations));
+    let keymap = local
+      .and_then(|node| node.keymap.as_ref())
+      .or_else(|| global.and_then(|node| node.keymap.as_ref()));
+    let longer = [local, global]
+      .iter()
+      .flatten()
+      .any(|node| !node.children.is_empty());
+    (keymap, longer)
+  }
+
+  /// Queue the typed key, the key is not mapped if `remap` is false.
+  pub fn feed(&mut self, key_event: KeyEvent, remap: bool) {
+    self.typeahead.push_back((normalize_key(key_event), remap));
+  }
+
+  /// Whether there're keys waiting for a longer mapping.
+  pub fn is_pending(&self) -> bool {
+    !self.pending.is_empty()
+  }
+
+  /// The deadline of the pending keys, the pending keys are resolved when it's reached.
+  pub fn deadline(&self) -> Option<Instant> {
+    self.deadline
+  }
+
+  // Insert the keys in front of the typeahead.
+  fn unread(&mut self, keys: impl DoubleEndedIterator<Item = (KeyEvent, bool)>) {
+    for key in keys.rev() {
+      self.typeahead.push_front(key);
+    }
+  }
+
+  /// Resolve the typeahead keys in current `mode` and buffer, returns the next action, or `None`
+  /// if there's no more keys or it's waiting for more keys.
+  ///
+  /// The internal states (i.e. the `mode` is `None`) are not mapped.
+  pub fn next(
+    &mut self,
+    mode: Option<Mode>,
+    buffer_id: Option<BufferId>,
+    now: Instant,
+  ) -> Option<KeymapAction> {
+    loop {
+      if self.typeahead.is_empty() {
+        let timeout = self.deadline.is_some_and(|deadline| deadline <= now);
+        if self.pending.is_empty() || !timeout {
+          self.depth = 0;
+          return None;
+        }
+      } else {
+        let (key_event, remap) = self.typeahead.pop_front().unwrap();
+        match mode {
+          Some(mode) if remap => {
+            self.pending.push(key_event);
+            let (_, longer) = self.lookup(mode, buffer_id, &self.pending);
+            if longer {
+              self.deadline = Some(now + self.timeout_len);
+              continue;
+            }
+          }
+          _ => {
+            if self.pending.is_empty() {
+              return Some(KeymapAction::Key(key_event));
+            }
+            // The not mapped key stops waiting for the pending keys.
+            self.typeahead.push_front((key_event, remap));
+          }
+        }
+      }
+
+      // Apply the longest mapping.
+      let keys = std::mem::take(&mut self.pending);
+      self.deadline = None;
+      let matched = mode.and_then(|mode| {
+        (1..=keys.len()).rev().find_map(|len| {
+          self
+            .lookup(mode, buffer_id, &keys[..len])
+            .0
+            .map(|keymap| (len, keymap.clone()))
+        })
+      });
+      let (len, keymap) = match matched {
+        Some(matched) => matched,
+        None => {
+          self.unread(keys[1..].iter().map(|key_event| (*key_event, true)));
+          return Some(KeymapAction::Key(keys[0]));
+        }
+      };
+      self.unread(keys[len..].iter().map(|key_event| (*key_event, true)));
+
+      self.depth += 1;
+      if self.depth > defaults::keymap::MAX_MAP_DEPTH {
+        error!("E223: Recursive mapping: {:?}", keymap.lhs);
+        self.typeahead.clear();
+        self.depth = 0;
+        return None;
+      }
+      match keymap.rhs {
+        KeymapRhs::Keys(rhs) => {
+          // The `{rhs}` started with `{lhs}` is not mapped again, i.e. `nmap x xp`.
+          let starts_with_lhs = rhs.starts_with(&keymap.lhs);
+          self.unread(rhs.iter().enumerate().map(|(i, key_event)| {
+            let remap = !(keymap.noremap || (starts_with_lhs && i < keymap.lhs.len()));
+            (*key_event, remap)
+          }));
+        }
+        KeymapRhs::Callback(id) => return Some(KeymapAction::Callback(id)),
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn keys(keymaps: &Keymaps, keys: &str) -> Vec<KeymapAction> {
+    keymaps
+      .parse_keys(keys)
+      .into_iter()
+      .map(KeymapAction::Key)
+      .collect()
+  }
+
+  // Type the keys and collect the resolved actions.
+  fn type_keys(
+    keymaps: &mut Keymaps,
+    mode: Mode,
+    buffer_id: Option<BufferId>,
+    typed: &str,
+    now: Instant,
+  ) -> Vec<KeymapAction> {
+    for key_event in keymaps.parse_keys(typed) {
+      keymaps.feed(key_event, true);
+    }
+    std::iter::from_fn(|| keymaps.next(Some(mode), buffer_id, now)).collect()
+  }
+
+  #[test]
+  fn parse_map_modes1() {
+    assert_eq!(
+      parse_map_modes("nx").unwrap(),
+      vec![Mode::Normal, Mode::Visual]
+    );
+    assert_eq!(parse_map_modes("").unwrap().len(), 4);
+    assert_eq!(
+      parse_map_modes("!i").unwrap(),
+      vec![Mode::Insert, Mode::CommandLine]
+    );
+    assert!(parse_map_modes("nz").is_err());
+  }
+
+  #[test]
+  fn set1() {
+    let mut keymaps = Keymaps::new();
+    keymaps.set_leader(",");
+    let rhs = KeymapRhs::Keys(keymaps.parse_keys("<C-W>v"));
+    let replaced = keymaps
+      .set(
+        &[Mode::Normal],
+        None,
+        "<leader>v",
+        rhs.clone(),
+        true,
+        "split",
+      )
+      .unwrap();
+    assert!(replaced.is_empty());
+    let keymap = keymaps.get(Mode::Normal, None, ",v").unwrap();
+    assert_eq!(keymap.rhs, rhs);
+    assert_eq!(keymap.desc, "split");
+    assert!(keymaps.get(Mode::Insert, None, ",v").is_none());
+    assert!(keymaps.get(Mode::Normal, Some(1), ",v").is_none());
+
+    let replaced = keymaps
+      .set(
+        &[Mode::Normal],
+        None,
+        ",v",
+        KeymapRhs::Callback(1),
+        true,
+        "",
+      )
+      .unwrap();
+    assert_eq!(replaced.len(), 1);
+    assert!(keymaps.is_callback_used(1));
+    assert!(!keymaps.is_callback_used(2));
+    assert!(keymaps
+      .set(&[Mode::Normal], None, "", KeymapRhs::Callback(2), true, "")
+      .is_err());
+
+    assert_eq!(keymaps.del(&[Mode::Normal], None, ",v").len(), 1);
+    assert!(!keymaps.is_callback_used(1));
+    assert!(keymaps.get(Mode::Normal, None, ",v").is_none());
+    assert!(keymaps.global[&Mode::Normal].children.is_empty());
+
+    keymaps
+      .set(&[Mode::Insert], Some(1), "jk", rhs, true, "")
+      .unwrap();
+    let id = keymaps.next_callback_id();
+    keymaps.clear();
+    assert!(keymaps.get(Mode::Insert, Some(1), "jk").is_none());
+    assert_eq!(keymaps.leader(), defaults::keymap::LEADER);
+    assert!(keymaps.next_callback_id() > id);
+  }
+
+  #[test]
+  fn next1() {
+    let mut keymaps = Keymaps::new();
+    let now = Instant::now();
+    let rhs = |keymaps: &Keymaps, keys: &str| KeymapRhs::Keys(keymaps.parse_keys(keys));
+    let (gg, dd) = (rhs(&keymaps, "G"), rhs(&keymaps, "x"));
+    keymaps
+      .set(&[Mode::Normal], None, "jk", gg, true, "")
+      .unwrap();
+    keymaps
+      .set(&[Mode::Normal], None, "jkl", dd, true, "")
+      .unwrap();
+    keymaps
+      .set(
+        &[Mode::Normal],
+        None,
+        "<Space>f",
+        KeymapRhs::Callback(7),
+        true,
+        "",
+      )
+      .unwrap();
+
+    // Not mapped.
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, None, "ab", now),
+      keys(&keymaps, "ab")
+    );
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Insert, None, "jk", now),
+      keys(&keymaps, "jk")
+    );
+    // The longest mapping.
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, None, "jkla", now),
+      keys(&keymaps, "xa")
+    );
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, None, "jkj", now),
+      keys(&keymaps, "G")
+    );
+    // The `j` is waiting for more keys.
+    assert!(keymaps.is_pending());
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, None, "a", now),
+      keys(&keymaps, "ja")
+    );
+    // Callback.
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, None, " fa", now),
+      vec![
+        KeymapAction::Callback(7),
+        KeymapAction::Key(keymaps.parse_keys("a")[0])
+      ]
+    );
+  }
+
+  #[test]
+  fn next2() {
+    // Timeout.
+    let mut keymaps = Keymaps::new();
+    let now = Instant::now();
+    let rhs = KeymapRhs::Keys(keymaps.parse_keys("G"));
+    keymaps
+      .set(&[Mode::Normal], None, "jk", rhs.clone(), true, "")
+      .unwrap();
+    keymaps
+      .set(&[Mode::Normal], None, "jkl", rhs, true, "")
+      .unwrap();
+    assert!(type_keys(&mut keymaps, Mode::Normal, None, "jk", now).is_empty());
+    let deadline = keymaps.deadline().unwrap();
+    assert_eq!(deadline, now + keymaps.timeout_len());
+    assert_eq!(keymaps.next(Some(Mode::Normal), None, now), None);
+    assert_eq!(
+      keymaps.next(Some(Mode::Normal), None, deadline),
+      keys(&keymaps, "G").pop()
+    );
+    assert!(!keymaps.is_pending());
+    assert_eq!(keymaps.deadline(), None);
+  }
+
+  #[test]
+  fn next3() {
+    // Remapping and buffer-local mappings.
+    let mut keymaps = Keymaps::new();
+    let now = Instant::now();
+    let rhs = |keymaps: &Keymaps, keys: &str| KeymapRhs::Keys(keymaps.parse_keys(keys));
+    let (a, b, x) = (rhs(&keymaps, "b"), rhs(&keymaps, "c"), rhs(&keymaps, "xp"));
+    keymaps
+      .set(&[Mode::Normal], None, "a", a, false, "")
+      .unwrap();
+    keymaps
+      .set(&[Mode::Normal], None, "b", b, false, "")
+      .unwrap();
+    keymaps
+      .set(&[Mode::Normal], None, "x", x, false, "")
+      .unwrap();
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, None, "a", now),
+      keys(&keymaps, "c")
+    );
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, None, "x", now),
+      keys(&keymaps, "xp")
+    );
+
+    let noremap = rhs(&keymaps, "b");
+    keymaps
+      .set(&[Mode::Normal], Some(1), "a", noremap, true, "")
+      .unwrap();
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, Some(1), "a", now),
+      keys(&keymaps, "b")
+    );
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, Some(2), "a", now),
+      keys(&keymaps, "c")
+    );
+    assert_eq!(keymaps.clear_buffer(1).len(), 1);
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, Some(1), "a", now),
+      keys(&keymaps, "c")
+    );
+
+    // Recursive mapping.
+    let c = rhs(&keymaps, "a");
+    keymaps
+      .set(&[Mode::Normal], None, "c", c, false, "")
+      .unwrap();
+    assert!(type_keys(&mut keymaps, Mode::Normal, None, "ad", now).is_empty());
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, None, "d", now),
+      keys(&keymaps, "d")
+    );
+  }
+
+  #[test]
+  fn next_alt1() {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    let mut keymaps = Keymaps::new();
+    let now = Instant::now();
+    keymaps
+      .set(
+        &[Mode::Normal],
+        None,
+        "<M-j>",
+        KeymapRhs::Callback(1),
+        true,
+        "",
+      )
+      .unwrap();
+    keymaps
+      .set(
+        &[Mode::Normal],
+        None,
+        "<A-J>",
+        KeymapRhs::Callback(2),
+        true,
+        "",
+      )
+      .unwrap();
+    keymaps
+      .set(
+        &[Mode::Normal],
+        None,
+        "<M-Enter>",
+        KeymapRhs::Callback(3),
+        true,
+        "",
+      )
+      .unwrap();
+
+    // The same keys encoded by different terminals.
+    for (code, modifiers, expect) in [
+      (KeyCode::Char('j'), KeyModifiers::ALT, 1),
+      (KeyCode::Char('j'), KeyModifiers::META, 1),
+      (
+        KeyCode::Char('J'),
+        KeyModifiers::ALT | KeyModifiers::SHIFT,
+        2,
+      ),
+      (
+        KeyCode::Char('j'),
+        KeyModifiers::ALT | KeyModifiers::SHIFT,
+        2,
+      ),
+      (KeyCode::Char('J'), KeyModifiers::ALT, 2),
+      (KeyCode::Enter, KeyModifiers::ALT, 3),
+    ] {
+      keymaps.feed(KeyEvent::new(code, modifiers), true);
+      assert_eq!(
+        keymaps.next(Some(Mode::Normal), None, now),
+        Some(KeymapAction::Callback(expect))
+      );
+    }
+
+    // The `j` is not mapped.
+    assert_eq!(
+      type_keys(&mut keymaps, Mode::Normal, None, "j", now),
+      keys(&keymaps, "j")
+    );
+  }
+}
@@ -0,0 +1,259 @@
+//! Keyboard layout translation, i.e. the 'langmap' option.
+//!
+//! The 'langmap' option translates the characters typed with a non-Latin keyboard layout into
+//! the Latin characters, thus users can use normal-mode commands without switching layouts. The
+//! translation happens in the key decoding stage, i.e. before the key mappings are resolved, and
+//! only applies to the normal, visual, select and operator-pending modes.
+//!
+//! The option value is a comma-separated list of parts, each part is either:
+//!
+//! 1. `{from}{to}` pairs, i.e. `ΑA,ΒB`.
+//! 2. `{from-chars};{to-chars}`, i.e. `ΑΒΓ;ABC`.
+//!
+//! The special characters `;`, `,` and `\` can be escaped with a backslash `\`.
+//!
+//! NOTE: Dead keys are composed by the terminal (or the input method), so the composed character
+//! is received and translated.
+//!
+//! See: <https://vimhelp.org/options.txt.html#%27langmap%27>.
+
+use crate::defaults;
+use crate::state::mode::Mode;
+
+use ahash::AHashMap as HashMap;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Split `s` on the unescaped `sep`, the escaped chars are kept as they are.
+fn split_unescaped(s: &str, sep: char) -> Vec<String> {
+  let mut parts = vec![];
+  let mut part = String::new();
+  let mut chars = s.chars();
+  while let Some(c) = chars.next() {
+    if c == '\\' {
+      part.push(c);
+      if let Some(next) = chars.next() {
+        part.push(next);
+      }
+    } else if c == sep {
+      parts.push(std::mem::take(&mut part));
+    } else {
+      part.push(c);
+    }
+  }
+  parts.push(part);
+  parts
+}
+
+/// Remove the escape backslashes.
+fn unescape(s: &str) -> Vec<char> {
+  let mut result = vec![];
+  let mut chars = s.chars();
+  while let Some(c) = chars.next() {
+    if c == '\\' {
+      if let Some(next) = chars.next() {
+        result.push(next);
+      }
+    } else {
+      result.push(c);
+    }
+  }
+  result
+}
+
+/// Parse the 'langmap' option value.
+///
+/// # Errors
+///
+/// If the value is invalid, i.e. the from/to characters are not matched.
+pub fn parse_langmap(value: &str) -> Result<HashMap<char, char>, String> {
+  let mut map = HashMap::new();
+  if value.is_empty() {
+    return Ok(map);
+  }
+
+  for part in split_unescaped(value, ',') {
+    if part.is_empty() {
+      continue;
+    }
+    let sides = split_unescaped(&part, ';');
+    match sides.len() {
+      1 => {
+        let chars = unescape(&sides[0]);
+        if chars.len() % 2 != 0 {
+          return Err(format!(
+            "E357: 'langmap': Matching character missing for {}",
+            chars[chars.len() - 1]
+          ));
+        }
+        for pair in chars.chunks(2) {
+          map.insert(pair[0], pair[1]);
+        }
+      }
+      2 => {
+        let from = unescape(&sides[0]);
+        let to = unescape(&sides[1]);
+        if from.len() > to.len() {
+          return Err(format!(
+            "E357: 'langmap': Matching character missing for {}",
+            from[to.len()]
+          ));
+        }
+        if from.len() < to.len() {
+          return Err(format!(
+            "E358: 'langmap': Extra characters after semicolon: {}",
+            to[from.len()..].iter().collect::<String>()
+          ));
+        }
+        for (f, t) in from.into_iter().zip(to) {
+          map.insert(f, t);
+        }
+      }
+      _ => {
+        return Err(format!(
+          "E358: 'langmap': Extra characters after semicolon: {}",
+          part
+        ))
+      }
+    }
+  }
+
+  Ok(map)
+}
+
+#[derive(Debug, Clone)]
+/// The keyboard layout translation.
+pub struct LangMap {
+  option: String,
+  map: HashMap<char, char>,
+}
+
+impl LangMap {
+  /// Make new translation with the 'langmap' option value.
+  ///
+  /// # Errors
+  ///
+  /// If the value is invalid.
+  pub fn new(option: &str) -> Result<Self, String> {
+    Ok(LangMap {
+      option: option.to_string(),
+      map: parse_langmap(option)?,
+    })
+  }
+
+  /// Get the 'langmap' option.
+  pub fn option(&self) -> &str {
+    &self.option
+  }
+
+  /// Set the 'langmap' option, the option is not changed if the value is invalid.
+  ///
+  /// # Errors
+  ///
+  /// If the value is invalid.
+  pub fn set_option(&mut self, option: &str) -> Result<(), String> {
+    self.map = parse_langmap(option)?;
+    self.option = option.to_string();
+    Ok(())
+  }
+
+  /// Whether there's no translation.
+  pub fn is_empty(&self) -> bool {
+    self.map.is_empty()
+  }
+
+  /// Translate a character, returns itself if there's no translation.
+  pub fn translate(&self, c: char) -> char {
+    self.map.get(&c).copied().unwrap_or(c)
+  }
+
+  /// Translate a key event in the `mode`.
+  ///
+  /// Only the characters without `CTRL`/`ALT` modifiers are translated, and only in the normal,
+  /// visual, select and operator-pending modes.
+  pub fn translate_key(&self, mode: Mode, key_event: KeyEvent) -> KeyEvent {
+    if self.is_empty() {
+      return key_event;
+    }
+    match mode {
+      Mode::Normal | Mode::Visual | Mode::Select | Mode::OperatorPending => {}
+      _ => return key_event,
+    }
+    if !(key_event.modifiers - KeyModifiers::SHIFT).is_empty() {
+      return key_event;
+    }
+    match key_event.code {
+      KeyCode::Char(c) => {
+        let mut translated = key_event;
+        translated.code = KeyCode::Char(self.translate(c));
+        translated
+      }
+      _ => key_event,
+    }
+  }
+}
+
+impl Default for LangMap {
+  fn default() -> Self {
+    LangMap::new(defaults::langmap::LANGMAP).unwrap()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse1() {
+    let actual = parse_langmap("ΑA,ΒB").unwrap();
+    assert_eq!(actual.len(), 2);
+    assert_eq!(actual.get(&'Α'), Some(&'A'));
+    assert_eq!(actual.get(&'Β'), Some(&'B'));
+
+    let actual = parse_langmap("йцук;qwer,ЙЦ;QW").unwrap();
+    assert_eq!(actual.len(), 6);
+    assert_eq!(actual.get(&'й'), Some(&'q'));
+    assert_eq!(actual.get(&'р'), None);
+    assert_eq!(actual.get(&'Ц'), Some(&'W'));
+
+    let actual = parse_langmap("ж\\;,б\\,").unwrap();
+    assert_eq!(actual.get(&'ж'), Some(&';'));
+    assert_eq!(actual.get(&'б'), Some(&','));
+
+    assert!(parse_langmap("").unwrap().is_empty());
+  }
+
+  #[test]
+  fn parse2() {
+    assert!(parse_langmap("ΑAΒ").is_err());
+    assert!(parse_langmap("abc;xy").is_err());
+    assert!(parse_langmap("ab;xyz").is_err());
+    assert!(parse_langmap("a;b;c").is_err());
+  }
+
+  #[test]
+  fn translate1() {
+    let mut langmap = LangMap::default();
+    assert!(langmap.is_empty());
+    assert!(langmap.set_option("ab;").is_err());
+    assert_eq!(langmap.option(), "");
+    langmap.set_option("ролд;hjkl").unwrap();
+    assert_eq!(langmap.option(), "ролд;hjkl");
+    assert_eq!(langmap.translate('о'), 'j');
+    assert_eq!(langmap.translate('x'), 'x');
+
+    let key = KeyEvent::new(KeyCode::Char('р'), KeyModifiers::NONE);
+    assert_eq!(
+      langmap.translate_key(Mode::Normal, key).code,
+      KeyCode::Char('h')
+    );
+    assert_eq!(
+      langmap.translate_key(Mode::Insert, key).code,
+      KeyCode::Char('р')
+    );
+    let key = KeyEvent::new(KeyCode::Char('р'), KeyModifiers::CONTROL);
+    assert_eq!(
+      langmap.translate_key(Mode::Normal, key).code,
+      KeyCode::Char('р')
+    );
+  }
+}
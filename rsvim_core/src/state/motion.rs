@@ -0,0 +1,417 @@
+//! Motions for the operators, i.e. the `w` in `dw`.
+//!
+//! - `h`/`l`: Left/right chars in current line.
+//! - `j`/`k`: Down/up lines.
+//! - `w`/`W`: The start of next word/WORD, the empty line is also a word.
+//! - `e`/`E`: The end of word/WORD.
+//! - `b`/`B`: The start of previous word/WORD.
+//! - `0`, `^`, `$`: The start, first non-blank char and end of line.
+//! - `gg`/`G`: The first/last line, or the line `count`.
+//! - The operator itself, i.e. `dd`, `cc`, `yy`: Current line, and `count - 1` lines below.
+//!
+//! Each motion is exclusive, inclusive or linewise (see [`MotionKind`]), the operator is applied
+//! to the text between the cursor and the motion target, see [`operator_selection`]. Same with
+//! Vim, there're some special cases:
+//!
+//! - `cw` is `ce` when the cursor is on a word, i.e. the blanks after the word are not changed.
+//! - `dw` on the last word of line stops at the end of line, not the first word of next line.
+//! - The exclusive motion ends at the start of a line is moved to the end of previous line.
+//!
+//! See: <https://vimhelp.org/motion.txt.html#exclusive>.
+
+use crate::buf::Buffer;
+use crate::state::visual::{Selection, VisualPosition};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The motion.
+pub enum Motion {
+  /// `h`
+  Left,
+  /// `l`
+  Right,
+  /// `k`
+  Up,
+  /// `j`
+  Down,
+  /// `w`, or `W` if `big`.
+  WordForward { big: bool },
+  /// `e`, or `E` if `big`.
+  WordEnd { big: bool },
+  /// `b`, or `B` if `big`.
+  WordBackward { big: bool },
+  /// `0`
+  LineStart,
+  /// `^`
+  FirstNonBlank,
+  /// `$`
+  LineEnd,
+  /// `gg`
+  FirstLine,
+  /// `G`
+  LastLine,
+  /// `dd`, `cc`, `yy`
+  CurrentLine,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The motion kind, i.e. how the text between the cursor and target is operated.
+pub enum MotionKind {
+  /// The target char is not included.
+  Exclusive,
+  /// The target char is included.
+  Inclusive,
+  /// All the lines are included.
+  Linewise,
+}
+
+impl Motion {
+  pub fn kind(&self) -> MotionKind {
+    match self {
+      Motion::Left
+      | Motion::Right
+      | Motion::WordForward { .. }
+      | Motion::WordBackward { .. }
+      | Motion::LineStart
+      | Motion::FirstNonBlank => MotionKind::Exclusive,
+      Motion::WordEnd { .. } | Motion::LineEnd => MotionKind::Inclusive,
+      Motion::Up | Motion::Down | Motion::FirstLine | Motion::LastLine | Motion::CurrentLine => {
+        MotionKind::Linewise
+      }
+    }
+  }
+}
+
+/// Parse the single key motion, i.e. except `gg` and the operator itself.
+pub fn parse(c: char) -> Option<Motion> {
+  let motion = match c {
+    'h' => Motion::Left,
+    'l' | ' ' => Motion::Right,
+    'k' => Motion::Up,
+    'j' => Motion::Down,
+    'w' => Motion::WordForward { big: false },
+    'W' => Motion::WordForward { big: true },
+    'e' => Motion::WordEnd { big: false },
+    'E' => Motion::WordEnd { big: true },
+    'b' => Motion::WordBackward { big: false },
+    'B' => Motion::WordBackward { big: true },
+    '0' => Motion::LineStart,
+    '^' => Motion::FirstNonBlank,
+    '$' => Motion::LineEnd,
+    'G' => Motion::LastLine,
+    _ => return None,
+  };
+  Some(motion)
+}
+
+/// The last line index of the `buffer`, the last empty line (after the last line ending) is not
+/// counted unless it's the only line.
+pub fn last_line_idx(buffer: &Buffer) -> usize {
+  let last_line_idx = buffer.len_lines().saturating_sub(1);
+  if last_line_idx > 0
+    && buffer
+      .get_line_content(last_line_idx)
+      .is_some_and(|l| l.is_empty())
+  {
+    last_line_idx - 1
+  } else {
+    last_line_idx
+  }
+}
+
+fn line_chars(buffer: &Buffer, line_idx: usize) -> Vec<char> {
+  buffer
+    .get_line_content(line_idx)
+    .map(|l| l.chars().collect())
+    .unwrap_or_default()
+}
+
+fn is_blank(c: char) -> bool {
+  c == ' ' || c == '\t'
+}
+
+// The word class, `0` is blank.
+fn word_class(c: char, big: bool) -> u8 {
+  if is_blank(c) {
+    0
+  } else if big || c.is_alphanumeric() || c == '_' {
+    1
+  } else {
+    2
+  }
+}
+
+// The start of next word, or the end of last line.
+fn next_word_start(buffer: &Buffer, position: VisualPosition, big: bool) -> VisualPosition {
+  let last = last_line_idx(buffer);
+  let (mut line_idx, mut char_idx) = position;
+  let mut line = line_chars(buffer, line_idx);
+  if char_idx < line.len() {
+    let class = word_class(line[char_idx], big);
+    if class != 0 {
+      while char_idx < line.len() && word_class(line[char_idx], big) == class {
+        char_idx += 1;
+      }
+    }
+  }
+  loop {
+    while char_idx < line.len() && is_blank(line[char_idx]) {
+      char_idx += 1;
+    }
+    if char_idx < line.len() {
+      return (line_idx, char_idx);
+    }
+    if line_idx >= last {
+      return (line_idx, line.len());
+    }
+    line_idx += 1;
+    line = line_chars(buffer, line_idx);
+    char_idx = 0;
+    if line.is_empty() {
+      return (line_idx, 0);
+    }
+  }
+}
+
+// The end of current word (if the cursor is not on its end) or next word, or the `position` itself
+// if there's no more words.
+fn next_word_end(buffer: &Buffer, position: VisualPosition, big: bool) -> VisualPosition {
+  let last = last_line_idx(buffer);
+  let (mut line_idx, mut char_idx) = (position.0, position.1 + 1);
+  let mut line = line_chars(buffer, line_idx);
+  loop {
+    while char_idx < line.len() && is_blank(line[char_idx]) {
+      char_idx += 1;
+    }
+    if char_idx < line.len() {
+      break;
+    }
+    if line_idx >= last {
+      return position;
+    }
+    line_idx += 1;
+    line = line_chars(buffer, line_idx);
+    char_idx = 0;
+  }
+  let class = word_class(line[char_idx], big);
+  while char_idx + 1 < line.len() && word_class(line[char_idx + 1], big) == class {
+    char_idx += 1;
+  }
+  (line_idx, char_idx)
+}
+
+// The start of current word (if the cursor is not on its start) or previous word, the empty line is
+// also a word.
+fn prev_word_start(buffer: &Buffer, position: VisualPosition, big: bool) -> VisualPosition {
+  let (mut line_idx, mut char_idx) = position;
+  let mut line = line_chars(buffer, line_idx);
+  char_idx = std::cmp::min(char_idx, line.len());
+  loop {
+    if char_idx == 0 {
+      if line_idx == 0 {
+        return (0, 0);
+      }
+      line_idx -= 1;
+      line = line_chars(buffer, line_idx);
+      char_idx = line.len();
+      if line.is_empty() {
+        return (line_idx, 0);
+      }
+    }
+    char_idx -= 1;
+    if !is_blank(line[char_idx]) {
+      break;
+    }
+  }
+  let class = word_class(line[char_idx], big);
+  while char_idx > 0 && word_class(line[char_idx - 1], big) == class {
+    char_idx -= 1;
+  }
+  (line_idx, char_idx)
+}
+
+/// The target position of the `motion` from the `cursor`, the `count` is the typed count (`0`
+/// means no count).
+pub fn target(
+  buffer: &Buffer,
+  cursor: VisualPosition,
+  motion: Motion,
+  count: usize,
+) -> VisualPosition {
+  let last = last_line_idx(buffer);
+  let n = count.max(1);
+  let (line_idx, char_idx) = cursor;
+  let line_len = line_chars(buffer, line_idx).len();
+  let repeat = |next: &dyn Fn(VisualPosition) -> VisualPosition| {
+    (0..n).fold(cursor, |position, _| next(position))
+  };
+  match motion {
+    Motion::Left => (line_idx, char_idx.saturating_sub(n)),
+    Motion::Right => (line_idx, std::cmp::min(char_idx + n, line_len)),
+    Motion::Up => (line_idx.saturating_sub(n), char_idx),
+    Motion::Down => (std::cmp::min(line_idx + n, last), char_idx),
+    Motion::WordForward { big } => repeat(&|p| next_word_start(buffer, p, big)),
+    Motion::WordEnd { big } => repeat(&|p| next_word_end(buffer, p, big)),
+    Motion::WordBackward { big } => repeat(&|p| prev_word_start(buffer, p, big)),
+    Motion::LineStart => (line_idx, 0),
+    Motion::FirstNonBlank => {
+      let line = line_chars(buffer, line_idx);
+      (
+        line_idx,
+        line
+          .iter()
+          .position(|c| !is_blank(*c))
+          .unwrap_or(line.len()),
+      )
+    }
+    Motion::LineEnd => {
+      let line_idx = std::cmp::min(line_idx + n - 1, last);
+      let len = line_chars(buffer, line_idx).len();
+      (line_idx, len.saturating_sub(1))
+    }
+    Motion::FirstLine => (std::cmp::min(n - 1, last), 0),
+    Motion::LastLine => {
+      let line_idx = if count > 0 { count - 1 } else { last };
+      (std::cmp::min(line_idx, last), 0)
+    }
+    Motion::CurrentLine => (std::cmp::min(line_idx + n - 1, last), char_idx),
+  }
+}
+
+/// The text operated by the operator with the `motion` from the `cursor`, the `count` is the typed
+/// count (`0` means no count). The `change` is for the `c` operator, i.e. `cw` is `ce`.
+///
+/// Returns `None` if there's no text to operate, i.e. `dh` at the start of line.
+pub fn operator_selection(
+  buffer: &Buffer,
+  cursor: VisualPosition,
+  motion: Motion,
+  count: usize,
+  change: bool,
+) -> Option<Selection> {
+  let n = count.max(1);
+  let line = line_chars(buffer, cursor.0);
+  let abs = |position: VisualPosition| buffer.line_to_char(position.0) + position.1;
+  // The end of line, without line ending.
+  let line_end =
+    |line_idx: usize| buffer.line_to_char(line_idx) + line_chars(buffer, line_idx).len();
+
+  let (motion, target) = match motion {
+    Motion::WordForward { big } if change && cursor.1 < line.len() && !is_blank(line[cursor.1]) => {
+      // `cw` on a word: The end of current word, then the ends of next words.
+      let class = word_class(line[cursor.1], big);
+      let mut end = cursor.1;
+      while end + 1 < line.len() && word_class(line[end + 1], big) == class {
+        end += 1;
+      }
+      let target = (1..n).fold((cursor.0, end), |p, _| next_word_end(buffer, p, big));
+      (Motion::WordEnd { big }, target)
+    }
+    Motion::WordForward { big } => {
+      // The last word moved over is at the end of line, stops at the end of that line.
+      let before_last = (1..n).fold(cursor, |p, _| next_word_start(buffer, p, big));
+      let target = next_word_start(buffer, before_last, big);
+      if target.0 > before_last.0 {
+        let len = line_chars(buffer, before_last.0).len();
+        (motion, (before_last.0, len))
+      } else {
+        (motion, target)
+      }
+    }
+    _ => (motion, target(buffer, cursor, motion, count)),
+  };
+
+  let (start, end) = (std::cmp::min(cursor, target), std::cmp::max(cursor, target));
+  match motion.kind() {
+    MotionKind::Linewise => Selection::from_line_range(start.0..end.0 + 1),
+    MotionKind::Inclusive => {
+      let end_char_idx = std::cmp::min(abs(end) + 1, line_end(end.0));
+      Selection::from_char_range(buffer, abs(start)..end_char_idx)
+    }
+    MotionKind::Exclusive => {
+      let end_char_idx = if end.1 == 0 && end.0 > start.0 {
+        line_end(end.0 - 1)
+      } else {
+        abs(end)
+      };
+      Selection::from_char_range(buffer, abs(start)..std::cmp::max(abs(start), end_char_idx))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::state::visual::selected_text;
+  use crate::test::buf::make_buffer_from_lines;
+  use crate::{envar, rlock};
+
+  #[test]
+  fn target1() {
+    let buffer = make_buffer_from_lines(vec!["foo bar.baz\n", "\n", "  qux\n"]);
+    let buffer = rlock!(buffer);
+    let w = Motion::WordForward { big: false };
+    assert_eq!(target(&buffer, (0, 0), w, 0), (0, 4));
+    assert_eq!(target(&buffer, (0, 4), w, 0), (0, 7));
+    assert_eq!(target(&buffer, (0, 4), w, 3), (1, 0));
+    assert_eq!(target(&buffer, (0, 0), w, 9), (2, 5));
+    assert_eq!(
+      target(&buffer, (0, 4), Motion::WordForward { big: true }, 1),
+      (1, 0)
+    );
+    let e = Motion::WordEnd { big: false };
+    assert_eq!(target(&buffer, (0, 0), e, 1), (0, 2));
+    assert_eq!(target(&buffer, (0, 2), e, 1), (0, 6));
+    assert_eq!(target(&buffer, (0, 8), e, 2), (2, 4));
+    let b = Motion::WordBackward { big: false };
+    assert_eq!(target(&buffer, (2, 2), b, 1), (1, 0));
+    assert_eq!(target(&buffer, (2, 2), b, 2), (0, 8));
+    assert_eq!(target(&buffer, (0, 5), b, 1), (0, 4));
+    assert_eq!(target(&buffer, (2, 4), Motion::FirstNonBlank, 0), (2, 2));
+    assert_eq!(target(&buffer, (0, 4), Motion::LineEnd, 1), (0, 10));
+    assert_eq!(target(&buffer, (0, 4), Motion::LastLine, 0), (2, 0));
+    assert_eq!(target(&buffer, (2, 4), Motion::LastLine, 2), (1, 0));
+    assert_eq!(target(&buffer, (0, 0), Motion::Down, 9), (2, 0));
+  }
+
+  #[test]
+  fn operator_selection1() {
+    let buffer = make_buffer_from_lines(vec!["foo bar\n", "baz qux\n", "\n"]);
+    let buffer = rlock!(buffer);
+    let text = |cursor, motion, count, change| {
+      operator_selection(&buffer, cursor, motion, count, change)
+        .map(|selection| selected_text(&buffer, &selection))
+    };
+    let w = Motion::WordForward { big: false };
+    assert_eq!(text((0, 0), w, 1, false), Some("foo ".to_string()));
+    // `cw` is `ce`.
+    assert_eq!(text((0, 0), w, 1, true), Some("foo".to_string()));
+    assert_eq!(text((0, 0), w, 2, true), Some("foo bar".to_string()));
+    // `dw` stops at the end of line.
+    assert_eq!(text((0, 4), w, 1, false), Some("bar".to_string()));
+    assert_eq!(text((0, 0), w, 3, false), Some("foo bar\nbaz ".to_string()));
+    // `db` at the start of line stops at the end of previous line.
+    assert_eq!(
+      text((1, 0), Motion::WordBackward { big: false }, 1, false),
+      Some("bar".to_string())
+    );
+    assert_eq!(
+      text((1, 4), Motion::LineEnd, 0, false),
+      Some("qux".to_string())
+    );
+    assert_eq!(text((2, 0), Motion::LineEnd, 0, false), None);
+    assert_eq!(text((0, 0), Motion::Left, 0, false), None);
+    assert_eq!(
+      text((0, 1), Motion::Right, 2, false),
+      Some("oo".to_string())
+    );
+    assert_eq!(
+      text((0, 1), Motion::CurrentLine, 2, false),
+      Some("foo bar\nbaz qux\n".to_string())
+    );
+    assert_eq!(
+      text((1, 1), Motion::Up, 0, false),
+      Some("foo bar\nbaz qux\n".to_string())
+    );
+  }
+}
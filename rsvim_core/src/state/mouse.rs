@@ -0,0 +1,136 @@
+//! Mouse support.
+//!
+//! The mouse capture is enabled on the terminal (see
+//! [`EventLoop::init_tui`](crate::evloop::EventLoop::init_tui)), the mouse events are translated
+//! into the editor actions by the editing modes:
+//!
+//! - Click: Move the cursor to the clicked char, the clicked window becomes current window. The
+//!   click on the statusline focuses the window and emits the `StatusLineClick` event (see
+//!   [`autocmd`](crate::state::autocmd)), thus the statusline components can handle it in the JS
+//!   callbacks.
+//! - Drag: Start the (charwise) visual mode at the clicked char, and extend the selection to the
+//!   dragged char.
+//! - Wheel: Scroll the window under the mouse by [`SCROLL_LINES`] lines, the window doesn't need
+//!   to be current window.
+
+use crate::envar;
+use crate::state::autocmd::{AutocmdArgs, AutocmdEvent};
+use crate::state::visual::VisualPosition;
+use crate::state::State;
+use crate::ui::tree::{Tree, TreeArc, TreeNode, TreeNodeId};
+use crate::ui::widget::window::WindowRegion;
+use crate::{rlock, wlock};
+
+use crossterm::event::MouseButton;
+use geo::point;
+
+/// The lines scrolled by each mouse wheel step, i.e. Vim's default 'mousescroll'.
+pub const SCROLL_LINES: usize = 3;
+
+/// Get the mouse button name, i.e. the matched text of the `StatusLineClick` event.
+pub fn button_name(button: MouseButton) -> &'static str {
+  match button {
+    MouseButton::Left => "left",
+    MouseButton::Right => "right",
+    MouseButton::Middle => "middle",
+  }
+}
+
+/// Click at the terminal position `(column, row)`.
+///
+/// Returns `false` if it's not on any window.
+pub fn click(
+  state: &mut State,
+  tree: &TreeArc,
+  column: u16,
+  row: u16,
+  button: MouseButton,
+) -> bool {
+  let mut tree = wlock!(tree);
+  let (window_id, region) = match tree.window_at(point!(x: column, y: row)) {
+    Some(found) => found,
+    None => return false,
+  };
+  match region {
+    WindowRegion::StatusLine(_) => {
+      tree.set_current_window_id(window_id);
+      state.autocmds_mut().emit(AutocmdArgs {
+        event: AutocmdEvent::StatusLineClick,
+        matched: button_name(button).to_string(),
+        buffer_id: None,
+        window_id: Some(window_id),
+        scroll_delta: None,
+      });
+    }
+    WindowRegion::Content(pos) if button == MouseButton::Left => {
+      move_cursor_at(&mut tree, window_id, pos.x(), pos.y());
+    }
+    WindowRegion::Gutter(row) if button == MouseButton::Left => {
+      move_cursor_at(&mut tree, window_id, 0, row);
+    }
+    _ => { /* Skip */ }
+  }
+  true
+}
+
+// Move the cursor to the window position `(x, y)` of the window content, the window becomes
+// current window.
+fn move_cursor_at(tree: &mut Tree, window_id: TreeNodeId, x: u16, y: u16) {
+  tree.set_current_window_id(window_id);
+  if let Some(TreeNode::Window(window)) = tree.node(&window_id) {
+    let viewport = window.viewport();
+    let mut viewport = wlock!(viewport);
+    if let Some(cursor) = viewport.cursor_at(x, y) {
+      viewport.set_cursor(cursor);
+    }
+  }
+  tree.sync_cursor_position();
+}
+
+/// Get the char at the terminal position `(column, row)` in current window, i.e. where the mouse
+/// drags to. The position above (below) the window content is clamped to the first (last) row.
+///
+/// Returns `None` if it's not on current window.
+pub fn position_at(tree: &TreeArc, column: u16, row: u16) -> Option<VisualPosition> {
+  let tree = rlock!(tree);
+  let window_id = tree.current_window_id()?;
+  let window = match tree.node(&window_id) {
+    Some(TreeNode::Window(window)) => window,
+    _ => return None,
+  };
+  let (x, y) = match window.region_at(point!(x: column, y: row))? {
+    WindowRegion::Content(pos) => (pos.x(), pos.y()),
+    WindowRegion::Gutter(row) => (0, row),
+    WindowRegion::StatusLine(_) => (0, u16::MAX),
+    WindowRegion::Minimap => return None,
+  };
+  let viewport = window.viewport();
+  let viewport = rlock!(viewport);
+  let last_row_idx = viewport
+    .lines()
+    .values()
+    .flat_map(|line| line.rows().keys())
+    .max()
+    .copied()?;
+  let cursor = viewport.cursor_at(x, y.min(last_row_idx))?;
+  Some((cursor.line_idx(), cursor.char_idx()))
+}
+
+/// Scroll the window at the terminal position `(column, row)` by `lines` (the negative is
+/// upward), i.e. the mouse wheel.
+///
+/// Returns `false` if it's not on any window.
+pub fn scroll(tree: &TreeArc, column: u16, row: u16, lines: isize) -> bool {
+  let mut tree = wlock!(tree);
+  let window_id = match tree.window_at(point!(x: column, y: row)) {
+    Some((window_id, _region)) => window_id,
+    None => return false,
+  };
+  if let Some(TreeNode::Window(window)) = tree.node(&window_id) {
+    wlock!(window.viewport()).scroll_vertically(lines);
+  }
+  if tree.current_window_id() == Some(window_id) {
+    tree.sync_cursor_position();
+  }
+  true
+}
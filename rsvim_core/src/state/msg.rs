@@ -0,0 +1,302 @@
+//! Messages to the user, i.e. Vim's message area.
+//!
+//! A message has a severity level (see [`MessageLevel`]), it's pushed by the ex commands (i.e.
+//! `:ls` and the failed commands) or the js scripts (i.e. `Rsvim.notify()`). The messages are:
+//!
+//! - Saved in the history, it's a ring buffer keeps the last [`MAX_HISTORY`] messages, i.e. the
+//!   `:messages` command.
+//! - Shown in the [`MessageBox`](crate::ui::widget::MessageBox) widget until the next typed key.
+//!   A single short message takes only the last row. The multi-line (or too long) messages are
+//!   shown in more rows with a press-enter prompt, the next key dismisses them, and it's consumed
+//!   if it's `ENTER`, `ESC` or `SPACE`.
+
+use crate::cart::U16Size;
+use crate::ui::tree::internal::Inodeable;
+use crate::ui::tree::{Tree, TreeNode, TreeNodeId};
+use crate::ui::widget::message::bottom_shape;
+use crate::ui::widget::MessageBox;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use parking_lot::RwLock;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Arc;
+use unicode_width::UnicodeWidthChar;
+
+/// The max messages kept in the history.
+pub const MAX_HISTORY: usize = 200;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The message severity level.
+pub enum MessageLevel {
+  Info,
+  Warn,
+  Error,
+}
+
+impl Display for MessageLevel {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      MessageLevel::Info => write!(f, "info"),
+      MessageLevel::Warn => write!(f, "warn"),
+      MessageLevel::Error => write!(f, "error"),
+    }
+  }
+}
+
+impl FromStr for MessageLevel {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "info" => Ok(MessageLevel::Info),
+      "warn" => Ok(MessageLevel::Warn),
+      "error" => Ok(MessageLevel::Error),
+      _ => Err(format!("Unknown message level {:?}", s)),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A message, the text can have multiple lines.
+pub struct Message {
+  pub level: MessageLevel,
+  pub text: String,
+}
+
+impl Message {
+  pub fn new(level: MessageLevel, text: &str) -> Self {
+    Message {
+      level,
+      text: text.to_string(),
+    }
+  }
+}
+
+/// Wrap the messages into the lines within the `width`, each line has the level of its message.
+pub fn wrap_lines(messages: &[Message], width: usize) -> Vec<(MessageLevel, String)> {
+  let width = width.max(1);
+  let mut lines = vec![];
+  for message in messages {
+    for text in message.text.lines() {
+      let mut line = String::new();
+      let mut occupied = 0_usize;
+      for c in text.chars() {
+        let c_width = UnicodeWidthChar::width(c).unwrap_or(0);
+        if occupied + c_width > width {
+          lines.push((message.level, std::mem::take(&mut line)));
+          occupied = 0;
+        }
+        line.push(c);
+        occupied += c_width;
+      }
+      lines.push((message.level, line));
+    }
+  }
+  lines
+}
+
+#[derive(Debug, Clone, Default)]
+/// The message history and the shown messages.
+pub struct Messages {
+  history: VecDeque<Message>,
+
+  // The messages shown since the last typed key.
+  shown: Vec<Message>,
+
+  // Whether the shown messages wait for the press-enter prompt.
+  press_enter: bool,
+
+  // The message box widget ID, it's only created when there're shown messages.
+  widget_id: Option<TreeNodeId>,
+
+  // Whether the shown messages are changed since last sync to the widget.
+  changed: bool,
+}
+
+pub type MessagesArc = Arc<RwLock<Messages>>;
+
+impl Messages {
+  pub fn new() -> Self {
+    Messages::default()
+  }
+
+  /// Convert struct to Arc pointer.
+  pub fn to_arc(m: Messages) -> MessagesArc {
+    Arc::new(RwLock::new(m))
+  }
+
+  /// Push a message, it's saved in the history and shown until the next typed key.
+  pub fn push(&mut self, level: MessageLevel, text: &str) {
+    let message = Message::new(level, text);
+    if self.history.len() >= MAX_HISTORY {
+      self.history.pop_front();
+    }
+    self.history.push_back(message.clone());
+    self.shown.push(message);
+    self.changed = true;
+  }
+
+  /// Show a message until the next typed key, it's not saved in the history, i.e. `:echo`.
+  pub fn show(&mut self, level: MessageLevel, text: &str) {
+    self.shown.push(Message::new(level, text));
+    self.changed = true;
+  }
+
+  /// Get the message history, from the oldest to the latest.
+  pub fn history(&self) -> &VecDeque<Message> {
+    &self.history
+  }
+
+  /// Clear the message history, i.e. `:messages clear`.
+  pub fn clear_history(&mut self) {
+    self.history.clear();
+  }
+
+  /// Get the messages shown since the last typed key.
+  pub fn shown(&self) -> &Vec<Message> {
+    &self.shown
+  }
+
+  /// Whether the shown messages wait for the press-enter prompt.
+  pub fn is_press_enter(&self) -> bool {
+    self.press_enter
+  }
+
+  /// Dismiss the shown messages on the typed key.
+  ///
+  /// Returns `true` if the key is consumed, i.e. `ENTER`, `ESC` or `SPACE` on the press-enter
+  /// prompt, otherwise the key is handled as usual.
+  pub fn on_key(&mut self, key_event: &KeyEvent) -> bool {
+    if self.shown.is_empty() {
+      return false;
+    }
+    let consumed = self.press_enter
+      && matches!(
+        key_event.code,
+        KeyCode::Enter | KeyCode::Esc | KeyCode::Char(' ')
+      );
+    self.shown.clear();
+    self.press_enter = false;
+    self.changed = true;
+    consumed
+  }
+
+  /// Sync the shown messages to the message box widget, the widget is created when a message is
+  /// pushed and removed after the messages are dismissed.
+  pub fn sync_widget(&mut self, tree: &mut Tree) {
+    if !self.changed {
+      return;
+    }
+    self.changed = false;
+    if let Some(widget_id) = self.widget_id.take() {
+      tree.remove(widget_id);
+    }
+    if self.shown.is_empty() {
+      return;
+    }
+
+    let root_id = tree.root_id();
+    let terminal_size = {
+      let root_shape = tree.node(&root_id).unwrap().actual_shape();
+      U16Size::new(root_shape.width(), root_shape.height())
+    };
+    let lines = wrap_lines(&self.shown, terminal_size.width() as usize);
+    self.press_enter = lines.len() > 1;
+    let height = if self.press_enter {
+      lines.len() + 1
+    } else {
+      lines.len()
+    };
+    let shape = bottom_shape(terminal_size, height.min(u16::MAX as usize) as u16);
+    let mut message_box = MessageBox::new(shape);
+    message_box.set_lines(lines);
+    message_box.set_press_enter(self.press_enter);
+    self.widget_id = Some(message_box.id());
+    tree.bounded_insert(&root_id, TreeNode::MessageBox(message_box));
+  }
+
+  /// Format the message history as `:messages` output, one line for each message line.
+  pub fn format_history(&self) -> String {
+    let mut text = String::new();
+    for message in self.history.iter() {
+      text.push_str(&message.text);
+      if !message.text.ends_with('\n') {
+        text.push('\n');
+      }
+    }
+    text
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crossterm::event::KeyModifiers;
+
+  #[test]
+  fn wrap_lines1() {
+    let messages = vec![
+      Message::new(MessageLevel::Info, "hello world"),
+      Message::new(MessageLevel::Error, "a\nb"),
+    ];
+    assert_eq!(
+      wrap_lines(&messages, 5),
+      vec![
+        (MessageLevel::Info, "hello".to_string()),
+        (MessageLevel::Info, " worl".to_string()),
+        (MessageLevel::Info, "d".to_string()),
+        (MessageLevel::Error, "a".to_string()),
+        (MessageLevel::Error, "b".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn history1() {
+    let mut messages = Messages::new();
+    for i in 0..MAX_HISTORY + 2 {
+      messages.push(MessageLevel::Info, &i.to_string());
+    }
+    assert_eq!(messages.history().len(), MAX_HISTORY);
+    assert_eq!(messages.history().front().unwrap().text, "2");
+    assert_eq!(messages.shown().len(), MAX_HISTORY + 2);
+
+    messages.show(MessageLevel::Info, "echo");
+    assert_eq!(
+      messages.history().back().unwrap().text,
+      (MAX_HISTORY + 1).to_string()
+    );
+    assert_eq!(messages.shown().last().unwrap().text, "echo");
+
+    messages.clear_history();
+    assert!(messages.history().is_empty());
+    assert_eq!(messages.format_history(), "");
+  }
+
+  #[test]
+  fn on_key1() {
+    let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+    let colon = KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE);
+    let mut messages = Messages::new();
+    assert!(!messages.on_key(&enter));
+
+    messages.push(MessageLevel::Info, "hello");
+    assert!(!messages.on_key(&enter));
+    assert!(messages.shown().is_empty());
+
+    messages.push(MessageLevel::Error, "E1: a\nE2: b");
+    messages.press_enter = true;
+    assert!(!messages.on_key(&colon));
+    messages.push(MessageLevel::Error, "E1: a\nE2: b");
+    messages.press_enter = true;
+    assert!(messages.on_key(&enter));
+    assert!(!messages.is_press_enter());
+    assert_eq!(
+      messages.format_history(),
+      "hello\nE1: a\nE2: b\nE1: a\nE2: b\n"
+    );
+  }
+}
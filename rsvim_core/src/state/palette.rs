@@ -0,0 +1,391 @@
+//! Command palette.
+//!
+//! The command palette is a searchable list over all the registered ex commands, the builtin key
+//! mappings (with descriptions) and the recent command lines. It's rendered by the
+//! [`Picker`](crate::ui::widget::picker::Picker) widget, and invoked by `CTRL-P` in normal mode.
+//!
+//! The named sessions are also listed, thus it's a switcher for the sessions.
+//!
+//! The items are filtered with the fuzzy match (see [`fuzzy`](crate::state::fuzzy)) on the query,
+//! the matches on the item label come first, then the matches on the description.
+
+use crate::state::ex::ExCommandsManager;
+use crate::state::fuzzy::fuzzy_match;
+use crate::state::paste::escape_file_arg;
+use crate::ui::widget::picker::PickerRow;
+
+use compact_str::CompactString;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::PathBuf;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The kind of palette item.
+pub enum PaletteItemKind {
+  /// Registered ex command.
+  ExCommand,
+  /// Key mapping.
+  Keymap,
+  /// Recent command line.
+  Recent,
+  /// Dropped file, i.e. the drag-and-drop files delivered as paste text.
+  DroppedFile,
+  /// Named session, see [`session`](crate::state::session).
+  Session,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The action when a palette item is accepted.
+pub enum PaletteAction {
+  /// Execute the ex command line.
+  ExCommand,
+  /// Feed the key to normal mode.
+  Key(KeyEvent),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The palette item.
+pub struct PaletteItem {
+  kind: PaletteItemKind,
+  label: CompactString,
+  description: CompactString,
+  action: PaletteAction,
+}
+
+impl PaletteItem {
+  pub fn new(kind: PaletteItemKind, label: &str, description: &str, action: PaletteAction) -> Self {
+    PaletteItem {
+      kind,
+      label: CompactString::new(label),
+      description: CompactString::new(description),
+      action,
+    }
+  }
+
+  pub fn kind(&self) -> PaletteItemKind {
+    self.kind
+  }
+
+  /// The label, for ex commands and recent commands it's also the command line to execute.
+  pub fn label(&self) -> &str {
+    &self.label
+  }
+
+  pub fn description(&self) -> &str {
+    &self.description
+  }
+
+  pub fn action(&self) -> PaletteAction {
+    self.action
+  }
+}
+
+/// The builtin key mappings in normal mode.
+fn builtin_keymaps() -> Vec<(&'static str, &'static str, KeyCode)> {
+  vec![
+    ("h", "Move cursor left", KeyCode::Char('h')),
+    ("j", "Move cursor down", KeyCode::Char('j')),
+    ("k", "Move cursor up", KeyCode::Char('k')),
+    ("l", "Move cursor right", KeyCode::Char('l')),
+    ("v", "Start charwise visual mode", KeyCode::Char('v')),
+    ("V", "Start linewise visual mode", KeyCode::Char('V')),
+    ("u", "Undo", KeyCode::Char('u')),
+    ("s", "Hint jump to 2-char target", KeyCode::Char('s')),
+    ("S", "Hint jump to word start", KeyCode::Char('S')),
+    ("/", "Search forward", KeyCode::Char('/')),
+    ("?", "Search backward", KeyCode::Char('?')),
+    ("n", "Search next match", KeyCode::Char('n')),
+    ("N", "Search previous match", KeyCode::Char('N')),
+  ]
+}
+
+/// Collect all the palette items, the recent command lines come first (the most recent one is
+/// the first), then the ex commands and key mappings.
+pub fn collect_items(ex_commands: &ExCommandsManager) -> Vec<PaletteItem> {
+  let mut items = vec![];
+  for command_line in ex_commands.history().iter().rev() {
+    items.push(PaletteItem::new(
+      PaletteItemKind::Recent,
+      command_line,
+      "Recent command",
+      PaletteAction::ExCommand,
+    ));
+  }
+  for (name, definition) in ex_commands.iter() {
+    items.push(PaletteItem::new(
+      PaletteItemKind::ExCommand,
+      name,
+      definition.description(),
+      PaletteAction::ExCommand,
+    ));
+  }
+  for (key, description, code) in builtin_keymaps() {
+    items.push(PaletteItem::new(
+      PaletteItemKind::Keymap,
+      key,
+      description,
+      PaletteAction::Key(KeyEvent::new(code, KeyModifiers::NONE)),
+    ));
+  }
+  items
+}
+
+/// Collect the palette items to load the named sessions, i.e. the `:SessionLoad` commands.
+pub fn session_items(names: &[String]) -> Vec<PaletteItem> {
+  names
+    .iter()
+    .map(|name| {
+      PaletteItem::new(
+        PaletteItemKind::Session,
+        &format!("SessionLoad {}", name),
+        "Load the session",
+        PaletteAction::ExCommand,
+      )
+    })
+    .collect()
+}
+
+/// Collect the palette items to open the dropped files, i.e. the `:drop` commands for all the
+/// files, and for each file.
+pub fn dropped_items(paths: &[PathBuf]) -> Vec<PaletteItem> {
+  let drop_command = |paths: &[PathBuf]| {
+    let args: Vec<String> = paths.iter().map(|p| escape_file_arg(p)).collect();
+    format!("drop {}", args.join(" "))
+  };
+  let mut items = vec![];
+  if paths.len() > 1 {
+    items.push(PaletteItem::new(
+      PaletteItemKind::DroppedFile,
+      &drop_command(paths),
+      "Open all the dropped files",
+      PaletteAction::ExCommand,
+    ));
+  }
+  for path in paths.iter() {
+    items.push(PaletteItem::new(
+      PaletteItemKind::DroppedFile,
+      &drop_command(std::slice::from_ref(path)),
+      "Open the dropped file",
+      PaletteAction::ExCommand,
+    ));
+  }
+  items
+}
+
+#[derive(Debug, Clone)]
+// The matched item.
+struct Matched {
+  index: usize,
+  // Whether it's matched on the description, i.e. the label is not matched.
+  on_description: bool,
+  score: i64,
+  // The char indexes of the matched chars in the label.
+  positions: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+/// The command palette state.
+pub struct CommandPalette {
+  items: Vec<PaletteItem>,
+  query: String,
+  // The matched items, sorted by score.
+  matched: Vec<Matched>,
+  // Index of the selected item in `matched`.
+  selected: usize,
+}
+
+impl CommandPalette {
+  pub fn new(items: Vec<PaletteItem>) -> Self {
+    let mut palette = CommandPalette {
+      items,
+      query: String::new(),
+      matched: vec![],
+      selected: 0,
+    };
+    palette.filter();
+    palette
+  }
+
+  pub fn query(&self) -> &str {
+    &self.query
+  }
+
+  /// Get the matched items.
+  pub fn matched_items(&self) -> Vec<&PaletteItem> {
+    self.matched.iter().map(|m| &self.items[m.index]).collect()
+  }
+
+  /// Get the index of selected item in the matched items.
+  pub fn selected(&self) -> Option<usize> {
+    if self.matched.is_empty() {
+      None
+    } else {
+      Some(self.selected)
+    }
+  }
+
+  /// Get the selected item.
+  pub fn selected_item(&self) -> Option<&PaletteItem> {
+    self
+      .matched
+      .get(self.selected)
+      .map(|m| &self.items[m.index])
+  }
+
+  // Match the item with current query, matches on description have lower priority.
+  fn match_item(&self, index: usize) -> Option<Matched> {
+    let item = &self.items[index];
+    match fuzzy_match(&self.query, item.label()) {
+      Some(m) => Some(Matched {
+        index,
+        on_description: false,
+        score: m.score,
+        positions: m.positions,
+      }),
+      None => fuzzy_match(&self.query, item.description()).map(|m| Matched {
+        index,
+        on_description: true,
+        score: m.score,
+        positions: vec![],
+      }),
+    }
+  }
+
+  // Re-calculate the matched items, sorted by score, and then by the original order.
+  fn filter(&mut self) {
+    self.matched = (0..self.items.len())
+      .filter_map(|index| self.match_item(index))
+      .collect();
+    self.matched.sort_by(|a, b| {
+      a.on_description
+        .cmp(&b.on_description)
+        .then(b.score.cmp(&a.score))
+        .then(a.index.cmp(&b.index))
+    });
+    self.selected = 0;
+  }
+
+  /// Append a char to the query.
+  pub fn push_char(&mut self, c: char) {
+    self.query.push(c);
+    self.filter();
+  }
+
+  /// Remove the last char of the query.
+  pub fn pop_char(&mut self) {
+    self.query.pop();
+    self.filter();
+  }
+
+  /// Select next item, it wraps to the first one at the end.
+  pub fn select_next(&mut self) {
+    if !self.matched.is_empty() {
+      self.selected = (self.selected + 1) % self.matched.len();
+    }
+  }
+
+  /// Select previous item, it wraps to the last one at the beginning.
+  pub fn select_prev(&mut self) {
+    if !self.matched.is_empty() {
+      self.selected = (self.selected + self.matched.len() - 1) % self.matched.len();
+    }
+  }
+
+  /// Get the rows for picker widget.
+  pub fn rows(&self) -> Vec<PickerRow> {
+    self
+      .matched
+      .iter()
+      .map(|m| {
+        let item = &self.items[m.index];
+        PickerRow::new(item.label(), item.description()).with_matches(m.positions.clone())
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn palette1() {
+    let ex_commands = ExCommandsManager::new();
+    let items = collect_items(&ex_commands);
+    assert!(items.iter().any(|i| i.kind() == PaletteItemKind::ExCommand));
+    assert!(items.iter().any(|i| i.kind() == PaletteItemKind::Keymap));
+
+    let mut palette = CommandPalette::new(items);
+    assert_eq!(palette.selected(), Some(0));
+    palette.push_char('a');
+    palette.push_char('l');
+    assert_eq!(palette.selected_item().unwrap().label(), "align");
+    assert_eq!(palette.rows()[0].matches, vec![0, 1]);
+    palette.select_prev();
+    assert_eq!(palette.selected(), Some(palette.matched_items().len() - 1));
+    palette.select_next();
+    assert_eq!(palette.selected(), Some(0));
+
+    palette.push_char('z');
+    palette.push_char('z');
+    assert!(palette.selected_item().is_none());
+    palette.pop_char();
+    palette.pop_char();
+    assert_eq!(palette.query(), "al");
+    assert!(palette.selected_item().is_some());
+
+    // Matches on the label come first, then matches on the description.
+    let mut palette = CommandPalette::new(vec![
+      PaletteItem::new(
+        PaletteItemKind::Keymap,
+        "u",
+        "Undo",
+        PaletteAction::ExCommand,
+      ),
+      PaletteItem::new(
+        PaletteItemKind::ExCommand,
+        "buffer",
+        "Edit buffer",
+        PaletteAction::ExCommand,
+      ),
+      PaletteItem::new(
+        PaletteItemKind::ExCommand,
+        "update",
+        "Write the buffer if modified",
+        PaletteAction::ExCommand,
+      ),
+    ]);
+    palette.push_char('u');
+    let labels: Vec<&str> = palette.matched_items().iter().map(|i| i.label()).collect();
+    assert_eq!(labels, vec!["u", "update", "buffer"]);
+    palette.push_char('n');
+    let labels: Vec<&str> = palette.matched_items().iter().map(|i| i.label()).collect();
+    assert_eq!(labels, vec!["u"]);
+    assert!(palette.rows()[0].matches.is_empty());
+  }
+
+  #[test]
+  fn dropped_items1() {
+    let items = dropped_items(&[PathBuf::from("/tmp/a b.txt"), PathBuf::from("/tmp/c.txt")]);
+    let labels: Vec<&str> = items.iter().map(|i| i.label()).collect();
+    assert_eq!(
+      labels,
+      vec![
+        "drop /tmp/a\\ b.txt /tmp/c.txt",
+        "drop /tmp/a\\ b.txt",
+        "drop /tmp/c.txt"
+      ]
+    );
+    assert!(items
+      .iter()
+      .all(|i| i.kind() == PaletteItemKind::DroppedFile));
+  }
+
+  #[test]
+  fn session_items1() {
+    let items = session_items(&["%home%project".to_string(), "work".to_string()]);
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].label(), "SessionLoad %home%project");
+    assert_eq!(items[1].kind(), PaletteItemKind::Session);
+    assert_eq!(items[1].action(), PaletteAction::ExCommand);
+  }
+}
@@ -0,0 +1,293 @@
+//! Paste mode.
+//!
+//! When paste mode is active, the inserted text is treated literally, i.e. the key mappings,
+//! abbreviations, auto-indent and text-width are disabled, thus the pasted text will not be
+//! garbled.
+//!
+//! Paste mode is active when either:
+//!
+//! 1. The 'paste' option is `true`.
+//! 2. A bracketed paste is being handled, i.e. the [`Event::Paste`](crossterm::event::Event::Paste)
+//!    event.
+//! 3. A paste burst is detected, i.e. on terminals without bracketed paste, the pasted text is
+//!    received as continuous key presses that arrive much faster than human typing.
+//!
+//! The bracketed paste is handled as a whole, i.e. it doesn't go through the key mappings and it's
+//! one undo state:
+//!
+//! - Insert mode: Insert the text at the cursor.
+//! - Normal mode: Put the text after the cursor, i.e. `p`.
+//! - Command-line mode: Insert the first line of the text at the cursor.
+//!
+//! The `Paste` event is emitted after it's handled, see [`autocmd`](crate::state::autocmd).
+//!
+//! Terminals also deliver the drag-and-drop files as paste text, see [`parse_dropped_paths`].
+
+use crate::defaults;
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Copy, Clone)]
+/// The paste mode state.
+pub struct PasteState {
+  // The 'paste' option.
+  option: bool,
+
+  // Whether a bracketed paste is being handled.
+  bracketed: bool,
+
+  // Paste burst heuristics.
+  burst_interval: Duration,
+  burst_keys: usize,
+  last_key_at: Option<Instant>,
+  continuous_keys: usize,
+}
+
+impl PasteState {
+  pub fn new(option: bool) -> Self {
+    PasteState {
+      option,
+      bracketed: false,
+      burst_interval: defaults::paste::PASTE_BURST_INTERVAL,
+      burst_keys: defaults::paste::PASTE_BURST_KEYS,
+      last_key_at: None,
+      continuous_keys: 0,
+    }
+  }
+
+  /// Get the 'paste' option.
+  pub fn option(&self) -> bool {
+    self.option
+  }
+
+  /// Set the 'paste' option.
+  pub fn set_option(&mut self, value: bool) {
+    self.option = value;
+  }
+
+  /// Whether paste mode is active, i.e. the key mappings, abbreviations, auto-indent and
+  /// text-width should be disabled.
+  pub fn is_active(&self) -> bool {
+    self.option || self.bracketed || self.is_burst()
+  }
+
+  /// Whether a bracketed paste is being handled.
+  pub fn is_bracketed(&self) -> bool {
+    self.bracketed
+  }
+
+  /// Start handling a bracketed paste.
+  pub fn begin_bracketed(&mut self) {
+    self.bracketed = true;
+  }
+
+  /// Finish handling a bracketed paste.
+  pub fn end_bracketed(&mut self) {
+    self.bracketed = false;
+  }
+
+  /// Whether a paste burst is detected.
+  pub fn is_burst(&self) -> bool {
+    self.continuous_keys >= self.burst_keys
+  }
+
+  /// Record a key press at the moment `now`, and update the paste burst heuristics.
+  ///
+  /// Returns whether a paste burst is detected.
+  pub fn on_key(&mut self, now: Instant) -> bool {
+    match self.last_key_at {
+      Some(last) if now.saturating_duration_since(last) <= self.burst_interval => {
+        self.continuous_keys += 1;
+      }
+      _ => {
+        self.continuous_keys = 1;
+      }
+    }
+    self.last_key_at = Some(now);
+    self.is_burst()
+  }
+}
+
+impl Default for PasteState {
+  fn default() -> Self {
+    PasteState::new(defaults::paste::PASTE)
+  }
+}
+
+/// Normalize the pasted text, i.e. the line breaks `\r\n` and `\r` (terminals usually send `\r`
+/// for line break in raw mode) are converted into `\n`.
+pub fn normalize_pasted_text(text: &str) -> String {
+  text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Parse the pasted text as the dropped file paths, terminals deliver the dropped files in
+/// different styles:
+///
+/// - Separated by whitespaces, the special chars are escaped with `\`, i.e. `/a\ b.txt /c.txt`.
+/// - Quoted, i.e. `'/a b.txt' '/c.txt'`.
+/// - File URIs, i.e. `file:///a%20b.txt`.
+///
+/// Returns `None` if there's no path, or the text is not well-formed, i.e. the unclosed quote.
+///
+/// NOTE: It doesn't check whether the files exist.
+pub fn parse_dropped_paths(text: &str) -> Option<Vec<PathBuf>> {
+  let mut paths = vec![];
+  let mut token = String::new();
+  let mut in_token = false;
+  let mut quote: Option<char> = None;
+  let mut chars = text.chars();
+  while let Some(c) = chars.next() {
+    match quote {
+      Some(q) if c == q => quote = None,
+      Some('"') if c == '\\' => token.push(chars.next()?),
+      Some(_) => token.push(c),
+      None => match c {
+        '\'' | '"' => {
+          quote = Some(c);
+          in_token = true;
+        }
+        '\\' => {
+          token.push(chars.next()?);
+          in_token = true;
+        }
+        c if c.is_whitespace() => {
+          if in_token {
+            paths.push(to_dropped_path(&std::mem::take(&mut token)));
+            in_token = false;
+          }
+        }
+        c => {
+          token.push(c);
+          in_token = true;
+        }
+      },
+    }
+  }
+  if quote.is_some() {
+    return None;
+  }
+  if in_token {
+    paths.push(to_dropped_path(&token));
+  }
+  if paths.is_empty() {
+    None
+  } else {
+    Some(paths)
+  }
+}
+
+// Convert the token to path, the file URI is decoded.
+fn to_dropped_path(token: &str) -> PathBuf {
+  match token.strip_prefix("file://") {
+    Some(uri) => PathBuf::from(percent_decode(uri)),
+    None => PathBuf::from(token),
+  }
+}
+
+// Decode the `%XX` escapes.
+fn percent_decode(text: &str) -> String {
+  let bytes = text.as_bytes();
+  let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+  let mut i = 0_usize;
+  while i < bytes.len() {
+    let hex = bytes
+      .get(i + 1..i + 3)
+      .and_then(|hex| std::str::from_utf8(hex).ok())
+      .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    match hex {
+      Some(b) if bytes[i] == b'%' => {
+        decoded.push(b);
+        i += 3;
+      }
+      _ => {
+        decoded.push(bytes[i]);
+        i += 1;
+      }
+    }
+  }
+  String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Escape the file path as an ex command argument, i.e. the whitespaces, quotes and `\` are
+/// escaped with `\`, it can be parsed back by [`parse_dropped_paths`].
+pub fn escape_file_arg(path: &Path) -> String {
+  let mut escaped = String::new();
+  for c in path.to_string_lossy().chars() {
+    if c.is_whitespace() || matches!(c, '\\' | '\'' | '"') {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn option1() {
+    let mut paste = PasteState::default();
+    assert!(!paste.option());
+    assert!(!paste.is_active());
+    paste.set_option(true);
+    assert!(paste.is_active());
+    paste.set_option(false);
+    paste.begin_bracketed();
+    assert!(paste.is_active());
+    paste.end_bracketed();
+    assert!(!paste.is_active());
+  }
+
+  #[test]
+  fn burst1() {
+    let mut paste = PasteState::default();
+    let start = Instant::now();
+    assert!(!paste.on_key(start));
+    assert!(!paste.on_key(start + Duration::from_millis(1)));
+    assert!(paste.on_key(start + Duration::from_millis(2)));
+    assert!(paste.is_active());
+    // Human typing.
+    assert!(!paste.on_key(start + Duration::from_millis(200)));
+    assert!(!paste.is_active());
+    assert!(!paste.on_key(start + Duration::from_millis(400)));
+  }
+
+  #[test]
+  fn normalize_pasted_text1() {
+    assert_eq!(normalize_pasted_text("a\r\nb\rc\n"), "a\nb\nc\n");
+    assert_eq!(normalize_pasted_text("hello"), "hello");
+  }
+
+  #[test]
+  fn parse_dropped_paths1() {
+    let paths = |v: Vec<&str>| Some(v.into_iter().map(PathBuf::from).collect::<Vec<_>>());
+    assert_eq!(
+      parse_dropped_paths("/tmp/a\\ b.txt /tmp/c.txt "),
+      paths(vec!["/tmp/a b.txt", "/tmp/c.txt"])
+    );
+    assert_eq!(
+      parse_dropped_paths("'/tmp/a b.txt'\n\"/tmp/it's.txt\""),
+      paths(vec!["/tmp/a b.txt", "/tmp/it's.txt"])
+    );
+    assert_eq!(
+      parse_dropped_paths("file:///tmp/a%20b.txt file:///tmp/%E4%BD%A0.txt"),
+      paths(vec!["/tmp/a b.txt", "/tmp/你.txt"])
+    );
+    assert_eq!(parse_dropped_paths("  "), None);
+    assert_eq!(parse_dropped_paths("'/tmp/a"), None);
+    assert_eq!(parse_dropped_paths("/tmp/a\\"), None);
+  }
+
+  #[test]
+  fn escape_file_arg1() {
+    let path = Path::new("/tmp/it's a\\b.txt");
+    let escaped = escape_file_arg(path);
+    assert_eq!(escaped, "/tmp/it\\'s\\ a\\\\b.txt");
+    assert_eq!(
+      parse_dropped_paths(&escaped),
+      Some(vec![path.to_path_buf()])
+    );
+  }
+}
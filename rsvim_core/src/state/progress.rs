@@ -0,0 +1,252 @@
+//! Progress reporting for long-running tasks.
+//!
+//! A task reports its progress with [`begin`](ProgressManager::begin),
+//! [`update`](ProgressManager::update) and [`end`](ProgressManager::end), the running tasks are
+//! rendered in the [`ProgressNotification`](crate::ui::widget::ProgressNotification) widget at the
+//! bottom-right corner, one line for each task, for example:
+//!
+//! ```text
+//! Loading files: b.md 50% [x]
+//! ```
+//!
+//! The cancellable tasks have a `[x]` button, clicking it cancels the task. The cancellation is
+//! cooperative, i.e. the task checks [`is_cancelled`](ProgressManager::is_cancelled) and stops
+//! itself.
+
+use crate::cart::U16Size;
+use crate::ui::tree::internal::Inodeable;
+use crate::ui::tree::{Tree, TreeNode, TreeNodeId};
+use crate::ui::widget::progress::{bottom_right_shape, ProgressNotification};
+
+use std::collections::BTreeMap;
+
+/// The progress ID.
+pub type ProgressId = usize;
+
+/// The cancel button of the cancellable tasks.
+pub const CANCEL_BUTTON: &str = "[x]";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The progress of a task.
+pub struct Progress {
+  title: String,
+  message: String,
+  // Percentage in `[0, 100]`, it's `None` if the total amount is unknown.
+  percentage: Option<u8>,
+  cancellable: bool,
+  cancelled: bool,
+}
+
+impl Progress {
+  pub fn title(&self) -> &str {
+    &self.title
+  }
+
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+
+  pub fn percentage(&self) -> Option<u8> {
+    self.percentage
+  }
+
+  pub fn cancellable(&self) -> bool {
+    self.cancellable
+  }
+
+  pub fn cancelled(&self) -> bool {
+    self.cancelled
+  }
+
+  /// Format the progress line, i.e. `title: message 50% [x]`.
+  pub fn format(&self) -> String {
+    let mut line = self.title.clone();
+    if !self.message.is_empty() {
+      line.push_str(": ");
+      line.push_str(&self.message);
+    }
+    if let Some(percentage) = self.percentage {
+      line.push_str(&format!(" {}%", percentage));
+    }
+    if self.cancelled {
+      line.push_str(" (cancelled)");
+    } else if self.cancellable {
+      line.push(' ');
+      line.push_str(CANCEL_BUTTON);
+    }
+    line
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The running tasks' progress.
+pub struct ProgressManager {
+  tasks: BTreeMap<ProgressId, Progress>,
+  next_id: ProgressId,
+
+  // The notification widget ID, it's only created when there're running tasks.
+  widget_id: Option<TreeNodeId>,
+  // Whether the tasks are changed since last sync to the widget.
+  changed: bool,
+}
+
+impl ProgressManager {
+  pub fn new() -> Self {
+    ProgressManager::default()
+  }
+
+  /// Begin a task, returns its progress ID.
+  pub fn begin(&mut self, title: &str, cancellable: bool) -> ProgressId {
+    self.next_id += 1;
+    self.changed = true;
+    self.tasks.insert(
+      self.next_id,
+      Progress {
+        title: title.to_string(),
+        message: String::new(),
+        percentage: None,
+        cancellable,
+        cancelled: false,
+      },
+    );
+    self.next_id
+  }
+
+  /// Update the task's message and percentage (it's clamped to 100).
+  ///
+  /// Returns `false` if the task doesn't exist.
+  pub fn update(&mut self, id: ProgressId, message: &str, percentage: Option<u8>) -> bool {
+    match self.tasks.get_mut(&id) {
+      Some(progress) => {
+        self.changed = true;
+        progress.message = message.to_string();
+        progress.percentage = percentage.map(|p| p.min(100));
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// End the task, returns its last progress.
+  pub fn end(&mut self, id: ProgressId) -> Option<Progress> {
+    let progress = self.tasks.remove(&id);
+    self.changed = self.changed || progress.is_some();
+    progress
+  }
+
+  /// Cancel the task, returns `false` if the task doesn't exist or it's not cancellable.
+  pub fn cancel(&mut self, id: ProgressId) -> bool {
+    match self.tasks.get_mut(&id) {
+      Some(progress) if progress.cancellable => {
+        self.changed = true;
+        progress.cancelled = true;
+        true
+      }
+      _ => false,
+    }
+  }
+
+  /// Whether the task is cancelled.
+  pub fn is_cancelled(&self, id: ProgressId) -> bool {
+    self.tasks.get(&id).map(|p| p.cancelled).unwrap_or(false)
+  }
+
+  pub fn get(&self, id: ProgressId) -> Option<&Progress> {
+    self.tasks.get(&id)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.tasks.is_empty()
+  }
+
+  pub fn len(&self) -> usize {
+    self.tasks.len()
+  }
+
+  /// Get the progress lines and their task IDs, ordered by begin time.
+  pub fn lines(&self) -> Vec<(ProgressId, String)> {
+    self
+      .tasks
+      .iter()
+      .map(|(id, progress)| (*id, progress.format()))
+      .collect()
+  }
+
+  /// Sync the running tasks to the notification widget, the widget is created when the first task
+  /// begins and removed after the last task ends.
+  pub fn sync_widget(&mut self, tree: &mut Tree) {
+    if !self.changed {
+      return;
+    }
+    self.changed = false;
+    if let Some(widget_id) = self.widget_id.take() {
+      tree.remove(widget_id);
+    }
+    if self.tasks.is_empty() {
+      return;
+    }
+
+    let root_id = tree.root_id();
+    let terminal_size = {
+      let root_shape = tree.node(&root_id).unwrap().actual_shape();
+      U16Size::new(root_shape.width(), root_shape.height())
+    };
+    let lines = self.lines();
+    let shape = bottom_right_shape(terminal_size, &lines);
+    let mut notification = ProgressNotification::new(shape);
+    notification.set_lines(lines);
+    self.widget_id = Some(notification.id());
+    tree.bounded_insert(&root_id, TreeNode::ProgressNotification(notification));
+  }
+
+  /// Cancel the task whose cancel button is at the terminal position `(x, y)`.
+  ///
+  /// Returns the cancelled task ID.
+  pub fn cancel_at(&mut self, tree: &Tree, x: u16, y: u16) -> Option<ProgressId> {
+    let id = match self.widget_id.and_then(|widget_id| tree.node(&widget_id)) {
+      Some(TreeNode::ProgressNotification(notification)) => notification.cancel_button_at(x, y)?,
+      _ => return None,
+    };
+    if self.cancel(id) {
+      Some(id)
+    } else {
+      None
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn progress1() {
+    let mut manager = ProgressManager::new();
+    let loading = manager.begin("Loading files", true);
+    let grep = manager.begin("Grep", false);
+    assert_eq!(manager.len(), 2);
+    assert!(manager.update(loading, "b.md", Some(50)));
+    assert!(manager.update(grep, "", Some(120)));
+    assert_eq!(
+      manager.lines(),
+      vec![
+        (loading, "Loading files: b.md 50% [x]".to_string()),
+        (grep, "Grep 100%".to_string()),
+      ]
+    );
+
+    assert!(!manager.cancel(grep));
+    assert!(manager.cancel(loading));
+    assert!(manager.is_cancelled(loading));
+    assert_eq!(
+      manager.get(loading).unwrap().format(),
+      "Loading files: b.md 50% (cancelled)"
+    );
+
+    assert!(manager.end(loading).is_some());
+    assert!(manager.end(loading).is_none());
+    assert!(!manager.update(loading, "", None));
+    assert!(!manager.is_cancelled(loading));
+    assert_eq!(manager.len(), 1);
+  }
+}
@@ -0,0 +1,302 @@
+//! Put (paste) the register text, i.e. the `p` key in normal mode.
+//!
+//! The text is put by the register kind, see: <https://vimhelp.org/change.txt.html#p>:
+//!
+//! - Charwise: After (`p`) or before (`P`) the cursor char. The cursor is on the last char of the
+//!   new text, or the first char if the text contains multiple lines.
+//! - Linewise: Below (`p`) or above (`P`) current line. The cursor is on the first non-blank char
+//!   of the first new line.
+//! - Blockwise: Each line of the block is put in the column after (`p`) or before (`P`) the cursor
+//!   on the consecutive lines, the short lines are padded with spaces and the missing lines are
+//!   appended. The cursor is on the top-left corner of the new block.
+//!
+//! The variants:
+//!
+//! - `]p`, `[p`/`[P`/`]P`: Same with `p`/`P`, but the indent of the linewise text is adjusted to
+//!   current line.
+//! - `gp`, `gP`: Same with `p`/`P`, but the cursor is just after the new text.
+//!
+//! The `{count}` puts the text `count` times.
+
+use crate::buf::Buffer;
+use crate::state::register::{Register, RegisterKind};
+use crate::state::visual::VisualPosition;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The put command.
+pub struct PutCommand {
+  /// Put after the cursor (`p`), or before the cursor (`P`).
+  pub after: bool,
+  /// Adjust the indent of the linewise text to current line, i.e. `]p`.
+  pub reindent: bool,
+  /// Put the cursor just after the new text, i.e. `gp`.
+  pub cursor_after: bool,
+}
+
+/// Parse the put command from the `prefix` (i.e. the `g` of `gp`) and the key `c`.
+pub fn parse(prefix: Option<char>, c: char) -> Option<PutCommand> {
+  let after = match c {
+    'p' => true,
+    'P' => false,
+    _ => return None,
+  };
+  let command = |after, reindent, cursor_after| {
+    Some(PutCommand {
+      after,
+      reindent,
+      cursor_after,
+    })
+  };
+  match prefix {
+    None => command(after, false, false),
+    Some('g') => command(after, false, true),
+    // Only `]p` puts after the cursor.
+    Some(']') => command(after, true, false),
+    Some('[') => command(false, true, false),
+    _ => None,
+  }
+}
+
+// The leading blanks of the line.
+fn leading_blanks(line: &str) -> &str {
+  let trimmed = line.trim_start_matches([' ', '\t']);
+  &line[..line.len() - trimmed.len()]
+}
+
+// The display width of the indent.
+fn indent_width(indent: &str, tab_stop: usize) -> usize {
+  indent.chars().fold(0, |width, c| match c {
+    '\t' => (width / tab_stop + 1) * tab_stop,
+    _ => width + 1,
+  })
+}
+
+// Make the indent of `width`, with tabs unless `expand_tab`.
+fn make_indent(width: usize, tab_stop: usize, expand_tab: bool) -> String {
+  if expand_tab {
+    " ".repeat(width)
+  } else {
+    format!(
+      "{}{}",
+      "\t".repeat(width / tab_stop),
+      " ".repeat(width % tab_stop)
+    )
+  }
+}
+
+/// Adjust the indent of the lines in `text` to the `indent`, i.e. the first non-blank line has the
+/// same indent, and the other lines keep the relative indent to it. The blank lines are not
+/// changed.
+pub fn reindent(text: &str, indent: &str, tab_stop: usize, expand_tab: bool) -> String {
+  let tab_stop = tab_stop.max(1);
+  let is_blank = |line: &str| line.trim().is_empty();
+  let first = match text.split('\n').find(|line| !is_blank(line)) {
+    Some(first) => indent_width(leading_blanks(first), tab_stop),
+    None => return text.to_string(),
+  };
+  let target = indent_width(indent, tab_stop);
+  text
+    .split_inclusive('\n')
+    .map(|line| {
+      if is_blank(line) {
+        return line.to_string();
+      }
+      let blanks = leading_blanks(line);
+      let width = (indent_width(blanks, tab_stop) + target).saturating_sub(first);
+      format!(
+        "{}{}",
+        make_indent(width, tab_stop, expand_tab),
+        &line[blanks.len()..]
+      )
+    })
+    .collect()
+}
+
+/// Put the `register` text `count` times at the `cursor`, returns the new cursor position.
+///
+/// NOTE: The cursor position may be after the last char of the line, i.e. `gp`, the caller should
+/// clamp it.
+pub fn put(
+  buffer: &mut Buffer,
+  cursor: VisualPosition,
+  register: &Register,
+  count: usize,
+  command: PutCommand,
+) -> VisualPosition {
+  let count = count.max(1);
+  let (line_idx, char_idx) = cursor;
+  let line = buffer.get_line_content(line_idx).unwrap_or_default();
+  let line_len = line.chars().count();
+
+  match register.kind {
+    RegisterKind::Charwise => {
+      let text = register.content.repeat(count);
+      let col = if command.after && line_len > 0 {
+        std::cmp::min(char_idx + 1, line_len)
+      } else {
+        std::cmp::min(char_idx, line_len)
+      };
+      let start = buffer.line_to_char(line_idx) + col;
+      buffer.insert(start, &text);
+      let len = text.chars().count();
+      let cursor_char = if command.cursor_after {
+        start + len
+      } else if text.contains('\n') {
+        start
+      } else {
+        start + len.saturating_sub(1)
+      };
+      let cursor_line = buffer.char_to_line(cursor_char);
+      (cursor_line, cursor_char - buffer.line_to_char(cursor_line))
+    }
+    RegisterKind::Linewise => {
+      let mut text = register.content.repeat(count);
+      if command.reindent {
+        let tab_stop = buffer.tab_stop() as usize;
+        let expand_tab = buffer.options().expand_tab();
+        text = reindent(&text, leading_blanks(&line), tab_stop, expand_tab);
+      }
+      let new_line_idx = if command.after {
+        std::cmp::min(line_idx + 1, buffer.len_lines())
+      } else {
+        line_idx
+      };
+      let start = buffer.line_to_char(new_line_idx);
+      let new_lines = text.matches('\n').count();
+      if start > 0 && buffer.get_char(start - 1) != Some('\n') {
+        // The last line doesn't have line ending.
+        buffer.insert(start, &format!("\n{}", text.trim_end_matches('\n')));
+      } else {
+        buffer.insert(start, &text);
+      }
+      if command.cursor_after {
+        (new_line_idx + new_lines, 0)
+      } else {
+        let first = buffer.get_line_content(new_line_idx).unwrap_or_default();
+        (
+          new_line_idx,
+          first.chars().take_while(|c| c.is_whitespace()).count(),
+        )
+      }
+    }
+    RegisterKind::Blockwise => {
+      let rows: Vec<&str> = register.content.split('\n').collect();
+      let width = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+      let col = if command.after && line_len > 0 {
+        char_idx + 1
+      } else {
+        char_idx
+      };
+
+      // Append the missing lines after the last line.
+      let last_line_idx = crate::state::motion::last_line_idx(buffer);
+      let end_line_idx = line_idx + rows.len() - 1;
+      if end_line_idx > last_line_idx {
+        let last_line = buffer.get_line_content(last_line_idx).unwrap_or_default();
+        let end = buffer.line_to_char(last_line_idx) + last_line.chars().count();
+        buffer.insert(end, &"\n".repeat(end_line_idx - last_line_idx));
+      }
+
+      for (i, row) in rows.iter().enumerate() {
+        let target = buffer.get_line_content(line_idx + i).unwrap_or_default();
+        let target_len = target.chars().count();
+        let padded = format!("{:<width$}", row, width = width).repeat(count);
+        let mut text = " ".repeat(col.saturating_sub(target_len));
+        if col >= target_len {
+          // No trailing spaces at the end of line.
+          text.push_str(padded.trim_end_matches(' '));
+        } else {
+          text.push_str(&padded);
+        }
+        let start = buffer.line_to_char(line_idx + i) + std::cmp::min(col, target_len);
+        buffer.insert(start, &text);
+      }
+      if command.cursor_after {
+        (end_line_idx, col + width * count)
+      } else {
+        (line_idx, col)
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::envar;
+  use crate::test::buf::make_buffer_from_lines;
+  use crate::wlock;
+
+  fn text(buffer: &Buffer) -> String {
+    buffer.lines().map(|l| l.to_string()).collect()
+  }
+
+  #[test]
+  fn parse1() {
+    let p = parse(None, 'p').unwrap();
+    assert!(p.after && !p.reindent && !p.cursor_after);
+    assert!(!parse(None, 'P').unwrap().after);
+    assert!(parse(Some('g'), 'P').unwrap().cursor_after);
+    assert!(parse(Some(']'), 'p').unwrap().after);
+    assert!(!parse(Some('['), 'p').unwrap().after);
+    assert!(parse(Some(']'), 'P').unwrap().reindent);
+    assert_eq!(parse(Some('z'), 'p'), None);
+    assert_eq!(parse(None, 'x'), None);
+  }
+
+  #[test]
+  fn reindent1() {
+    assert_eq!(
+      reindent("  a\n    b\n\nc\n", "\t", 4, true),
+      "    a\n      b\n\n  c\n"
+    );
+    assert_eq!(reindent("    a\n      b\n", "", 4, true), "a\n  b\n");
+    assert_eq!(reindent("a\n  b\n", "    ", 4, false), "\ta\n\t  b\n");
+  }
+
+  #[test]
+  fn put_charwise1() {
+    let buffer = make_buffer_from_lines(vec!["abc\n", "\n"]);
+    let mut buffer = wlock!(buffer);
+    let register = Register::new(RegisterKind::Charwise, "XY".to_string());
+    let p = parse(None, 'p').unwrap();
+    assert_eq!(put(&mut buffer, (0, 0), &register, 2, p), (0, 4));
+    assert_eq!(text(&buffer), "aXYXYbc\n");
+    let gp = parse(Some('g'), 'P').unwrap();
+    assert_eq!(put(&mut buffer, (0, 0), &register, 1, gp), (0, 2));
+    assert_eq!(text(&buffer), "XYaXYXYbc\n");
+
+    let register = Register::new(RegisterKind::Charwise, "1\n2".to_string());
+    assert_eq!(put(&mut buffer, (0, 8), &register, 1, p), (0, 9));
+    assert_eq!(text(&buffer), "XYaXYXYbc1\n2\n");
+  }
+
+  #[test]
+  fn put_linewise1() {
+    let buffer = make_buffer_from_lines(vec!["  foo\n", "bar"]);
+    let mut buffer = wlock!(buffer);
+    let register = Register::new(RegisterKind::Linewise, "x\n  y\n".to_string());
+    let p = parse(None, 'p').unwrap();
+    assert_eq!(put(&mut buffer, (1, 0), &register, 1, p), (2, 0));
+    assert_eq!(text(&buffer), "  foo\nbar\nx\n  y");
+
+    let bracket_p = parse(Some(']'), 'p').unwrap();
+    assert_eq!(put(&mut buffer, (0, 0), &register, 1, bracket_p), (1, 2));
+    assert_eq!(text(&buffer), "  foo\n  x\n    y\nbar\nx\n  y");
+
+    let gp = parse(Some('g'), 'P').unwrap();
+    assert_eq!(put(&mut buffer, (0, 3), &register, 1, gp), (2, 0));
+    assert_eq!(text(&buffer), "x\n  y\n  foo\n  x\n    y\nbar\nx\n  y");
+  }
+
+  #[test]
+  fn put_blockwise1() {
+    let buffer = make_buffer_from_lines(vec!["abcd\n", "a\n"]);
+    let mut buffer = wlock!(buffer);
+    let register = Register::new(RegisterKind::Blockwise, "1\n22\n3".to_string());
+    let p = parse(None, 'p').unwrap();
+    assert_eq!(put(&mut buffer, (0, 1), &register, 1, p), (0, 2));
+    assert_eq!(text(&buffer), "ab1 cd\na 22\n  3\n");
+  }
+}
@@ -0,0 +1,333 @@
+//! Registers.
+//!
+//! The yanked/deleted text is stored in registers, see:
+//! <https://vimhelp.org/change.txt.html#registers>. There're several kinds of registers:
+//!
+//! 1. The unnamed register `"`: The last yanked/deleted text, no matter which register it's
+//!    stored in.
+//! 2. The numbered registers `0`-`9`: `0` is the last yanked text, `1` is the last deleted text
+//!    that is linewise or contains multiple lines, the previous contents of `1`-`8` are shifted to
+//!    `2`-`9`.
+//! 3. The small delete register `-`: The last deleted text within one line.
+//! 4. The named registers `a`-`z`: Only written when the register name is specified, the
+//!    uppercase `A`-`Z` appends to the lowercase register.
+//! 5. The black hole register `_`: Writing to it does nothing, reading it is always empty.
+//! 6. The clipboard registers `+`/`*`: They're not stored here, but read from and written to the
+//!    system clipboard, see [`State::register`](crate::state::State::register).
+
+use crate::state::visual::VisualKind;
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The register content type.
+pub enum RegisterKind {
+  Charwise,
+  Linewise,
+  Blockwise,
+}
+
+impl RegisterKind {
+  /// The short name shown in `:registers`.
+  pub fn short_name(&self) -> char {
+    match self {
+      RegisterKind::Charwise => 'c',
+      RegisterKind::Linewise => 'l',
+      RegisterKind::Blockwise => 'b',
+    }
+  }
+}
+
+impl From<VisualKind> for RegisterKind {
+  fn from(kind: VisualKind) -> Self {
+    match kind {
+      VisualKind::Charwise => RegisterKind::Charwise,
+      VisualKind::Linewise => RegisterKind::Linewise,
+      VisualKind::Blockwise => RegisterKind::Blockwise,
+    }
+  }
+}
+
+impl TryFrom<&str> for RegisterKind {
+  type Error = String;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    match value.to_lowercase().as_str() {
+      "c" | "charwise" => Ok(RegisterKind::Charwise),
+      "l" | "linewise" => Ok(RegisterKind::Linewise),
+      "b" | "blockwise" => Ok(RegisterKind::Blockwise),
+      _ => Err(format!("Unknown register type: {}", value)),
+    }
+  }
+}
+
+impl std::fmt::Display for RegisterKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RegisterKind::Charwise => write!(f, "charwise"),
+      RegisterKind::Linewise => write!(f, "linewise"),
+      RegisterKind::Blockwise => write!(f, "blockwise"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The register content.
+///
+/// For blockwise register, each line of the block is separated by `\n`. For linewise register,
+/// the content always ends with `\n`.
+pub struct Register {
+  pub kind: RegisterKind,
+  pub content: String,
+}
+
+impl Register {
+  pub fn new(kind: RegisterKind, content: String) -> Self {
+    Register { kind, content }
+  }
+
+  /// Make register from text, it's linewise if the text ends with line break, otherwise
+  /// charwise. For example the system clipboard content.
+  pub fn from_text(content: String) -> Self {
+    let kind = if content.ends_with('\n') {
+      RegisterKind::Linewise
+    } else {
+      RegisterKind::Charwise
+    };
+    Register { kind, content }
+  }
+
+  /// Whether the content contains multiple lines.
+  pub fn is_multiline(&self) -> bool {
+    self.kind == RegisterKind::Linewise || self.content.contains('\n')
+  }
+
+  // Append the `other` register to this register, the result is linewise if any of them is
+  // linewise.
+  fn append(&mut self, other: Register) {
+    if other.kind == RegisterKind::Linewise && self.kind != RegisterKind::Linewise {
+      if !self.content.ends_with('\n') {
+        self.content.push('\n');
+      }
+      self.kind = RegisterKind::Linewise;
+    } else if self.kind == RegisterKind::Linewise && !self.content.ends_with('\n') {
+      self.content.push('\n');
+    }
+    self.content.push_str(&other.content);
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A register entry.
+pub struct RegisterEntry {
+  /// Register name, i.e. `"`, `a`-`z`, `+`.
+  pub name: char,
+  pub kind: RegisterKind,
+  pub content: String,
+}
+
+/// Whether the register name is valid, i.e. can be read or written.
+pub fn is_valid_name(name: char) -> bool {
+  matches!(name, '"' | '0'..='9' | 'a'..='z' | 'A'..='Z' | '-' | '_' | '+' | '*')
+}
+
+/// Whether the register is the system clipboard, i.e. `+`/`*`.
+pub fn is_clipboard_name(name: char) -> bool {
+  matches!(name, '+' | '*')
+}
+
+#[derive(Debug, Clone, Default)]
+/// The registers store, except the clipboard registers.
+pub struct Registers {
+  // The unnamed register `"`.
+  unnamed: Option<Register>,
+
+  // The numbered registers `0`-`9`, the index is the register number.
+  numbered: [Option<Register>; 10],
+
+  // The small delete register `-`.
+  small_delete: Option<Register>,
+
+  // The named registers `a`-`z`.
+  named: BTreeMap<char, Register>,
+}
+
+impl Registers {
+  pub fn new() -> Self {
+    Registers::default()
+  }
+
+  /// Get the register, returns `None` if it's empty or the name is invalid.
+  ///
+  /// NOTE: The uppercase `A`-`Z` is the same with the lowercase register. The clipboard registers
+  /// `+`/`*` are always `None`.
+  pub fn get(&self, name: char) -> Option<&Register> {
+    match name {
+      '"' => self.unnamed.as_ref(),
+      '0'..='9' => self.numbered[name as usize - '0' as usize].as_ref(),
+      '-' => self.small_delete.as_ref(),
+      'a'..='z' | 'A'..='Z' => self.named.get(&name.to_ascii_lowercase()),
+      _ => None,
+    }
+  }
+
+  /// Set the register directly, i.e. the `:let @a = ...` and the JS API, returns `false` if the
+  /// name is invalid.
+  ///
+  /// NOTE: The uppercase `A`-`Z` appends to the lowercase register. Setting a register (except
+  /// the unnamed register itself) also updates the unnamed register.
+  pub fn set(&mut self, name: char, register: Register) -> bool {
+    match name {
+      '"' => self.unnamed = Some(register),
+      '0'..='9' => {
+        self.numbered[name as usize - '0' as usize] = Some(register.clone());
+        self.unnamed = Some(register);
+      }
+      '-' => {
+        self.small_delete = Some(register.clone());
+        self.unnamed = Some(register);
+      }
+      'a'..='z' | 'A'..='Z' => self.set_named(name, register),
+      '_' => { /* Black hole */ }
+      _ => return false,
+    }
+    true
+  }
+
+  // Set the named register, the uppercase name appends to it.
+  fn set_named(&mut self, name: char, register: Register) {
+    let lower = name.to_ascii_lowercase();
+    let register = match self.named.remove(&lower) {
+      Some(mut old) if name.is_ascii_uppercase() => {
+        old.append(register);
+        old
+      }
+      _ => register,
+    };
+    self.named.insert(lower, register.clone());
+    self.unnamed = Some(register);
+  }
+
+  /// Store the yanked text, to the `name` register if specified, otherwise to the `0` register.
+  pub fn yank(&mut self, name: Option<char>, register: Register) {
+    match name {
+      Some('_') => { /* Black hole */ }
+      Some(name) if name != '"' => {
+        self.set(name, register);
+      }
+      _ => {
+        self.numbered[0] = Some(register.clone());
+        self.unnamed = Some(register);
+      }
+    }
+  }
+
+  /// Store the deleted text, to the `name` register if specified. If the text is linewise or
+  /// contains multiple lines, it's also stored to the `1` register (and the numbered registers
+  /// are shifted), otherwise it's stored to the `-` register when the name isn't specified.
+  pub fn delete(&mut self, name: Option<char>, register: Register) {
+    let name = name.filter(|n| *n != '"');
+    if name == Some('_') {
+      return;
+    }
+    if register.is_multiline() {
+      self.numbered[1..].rotate_right(1);
+      self.numbered[1] = Some(register.clone());
+    } else if name.is_none() {
+      self.small_delete = Some(register.clone());
+    }
+    match name {
+      Some(name) => {
+        self.set(name, register);
+      }
+      None => self.unnamed = Some(register),
+    }
+  }
+
+  /// Get the non-empty registers in `:registers` order, i.e. `"`, `0`-`9`, `a`-`z`, `-`.
+  pub fn entries(&self) -> Vec<RegisterEntry> {
+    let names = std::iter::once('"')
+      .chain('0'..='9')
+      .chain('a'..='z')
+      .chain(std::iter::once('-'));
+    names
+      .filter_map(|name| {
+        self.get(name).map(|register| RegisterEntry {
+          name,
+          kind: register.kind,
+          content: register.content.clone(),
+        })
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn charwise(content: &str) -> Register {
+    Register::new(RegisterKind::Charwise, content.to_string())
+  }
+
+  fn linewise(content: &str) -> Register {
+    Register::new(RegisterKind::Linewise, content.to_string())
+  }
+
+  #[test]
+  fn yank1() {
+    let mut registers = Registers::new();
+    registers.yank(None, charwise("foo"));
+    assert_eq!(registers.get('"'), Some(&charwise("foo")));
+    assert_eq!(registers.get('0'), Some(&charwise("foo")));
+
+    registers.yank(Some('a'), linewise("bar\n"));
+    assert_eq!(registers.get('a'), Some(&linewise("bar\n")));
+    assert_eq!(registers.get('"'), Some(&linewise("bar\n")));
+    assert_eq!(registers.get('0'), Some(&charwise("foo")));
+
+    // Append to named register.
+    registers.yank(Some('A'), charwise("baz"));
+    assert_eq!(registers.get('a'), Some(&linewise("bar\nbaz")));
+    registers.yank(Some('_'), charwise("qux"));
+    assert_eq!(registers.get('"'), Some(&linewise("bar\nbaz")));
+  }
+
+  #[test]
+  fn delete1() {
+    let mut registers = Registers::new();
+    registers.delete(None, charwise("word"));
+    assert_eq!(registers.get('-'), Some(&charwise("word")));
+    assert_eq!(registers.get('1'), None);
+
+    for i in 0..10 {
+      registers.delete(None, linewise(&format!("line{}\n", i)));
+    }
+    assert_eq!(registers.get('1'), Some(&linewise("line9\n")));
+    assert_eq!(registers.get('9'), Some(&linewise("line1\n")));
+    assert_eq!(registers.get('"'), Some(&linewise("line9\n")));
+    assert_eq!(registers.get('-'), Some(&charwise("word")));
+
+    // Multiple lines charwise/blockwise text is shifted too.
+    let block = Register::new(RegisterKind::Blockwise, "ab\ncd".to_string());
+    registers.delete(Some('b'), block.clone());
+    assert_eq!(registers.get('1'), Some(&block));
+    assert_eq!(registers.get('B'), Some(&block));
+    assert_eq!(registers.get('2'), Some(&linewise("line9\n")));
+  }
+
+  #[test]
+  fn entries1() {
+    let mut registers = Registers::new();
+    assert!(!registers.set('?', charwise("x")));
+    assert!(registers.set('z', charwise("x")));
+    registers.yank(None, charwise("y"));
+    let names: Vec<char> = registers.entries().iter().map(|e| e.name).collect();
+    assert_eq!(names, vec!['"', '0', 'z']);
+    assert_eq!(RegisterKind::try_from("L"), Ok(RegisterKind::Linewise));
+    assert_eq!(
+      Register::from_text("a\n".to_string()).kind,
+      RegisterKind::Linewise
+    );
+  }
+}
@@ -0,0 +1,97 @@
+//! Dot-repeat, i.e. the `.` key in normal mode repeats the last change at the cursor.
+//!
+//! The `{count}` before `.` replaces the count of the last change, i.e. `d2w` then `3.` deletes 3
+//! words.
+
+use crate::buf::Buffer;
+use crate::state::autopair::InsertRecord;
+use crate::state::fsm::operator_pending::Operator;
+use crate::state::motion::{self, Motion};
+use crate::state::put::PutCommand;
+use crate::state::surround::SurroundCommand;
+use crate::state::textobj::{self, TextObject};
+use crate::state::visual::{Selection, VisualPosition};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The last repeatable change.
+pub enum LastChange {
+  /// The insert mode session.
+  Insert(InsertRecord),
+  /// The surround command, except the visual `S`.
+  Surround(SurroundCommand),
+  /// The operator with a motion or text object, except yank.
+  Operator(OperatorChange),
+  /// The put command, the `count` is the typed count (`0` means no count).
+  Put { command: PutCommand, count: usize },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The text that an operator is applied to.
+pub enum OperatorTarget {
+  /// The motion, i.e. `dw`.
+  Motion(Motion),
+  /// The text object, i.e. `ci(`.
+  TextObject { object: TextObject, inner: bool },
+}
+
+impl OperatorTarget {
+  /// The text operated by the `operator` from the `cursor`, the `count` is the typed count (`0`
+  /// means no count).
+  pub fn selection(
+    &self,
+    buffer: &Buffer,
+    cursor: VisualPosition,
+    operator: Operator,
+    count: usize,
+  ) -> Option<Selection> {
+    match self {
+      OperatorTarget::Motion(motion) => {
+        motion::operator_selection(buffer, cursor, *motion, count, operator == Operator::Change)
+      }
+      OperatorTarget::TextObject { object, inner } => {
+        textobj::select(buffer, cursor, *object, *inner, count)
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The operator applied to a target, i.e. `d3w`, `ci"`.
+pub struct OperatorChange {
+  pub operator: Operator,
+  pub target: OperatorTarget,
+  /// The total count, `0` means no count.
+  pub count: usize,
+  /// The insert mode session after the change operator `c`.
+  pub insert: Option<InsertRecord>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::envar;
+  use crate::rlock;
+  use crate::state::visual::selected_text;
+  use crate::test::buf::make_buffer_from_lines;
+
+  #[test]
+  fn selection1() {
+    let buffer = make_buffer_from_lines(vec!["foo (bar baz)\n"]);
+    let buffer = rlock!(buffer);
+    let text = |target: OperatorTarget, operator, count| {
+      target
+        .selection(&buffer, (0, 0), operator, count)
+        .map(|selection| selected_text(&buffer, &selection))
+    };
+    let w = OperatorTarget::Motion(Motion::WordForward { big: false });
+    assert_eq!(text(w, Operator::Delete, 0), Some("foo ".to_string()));
+    assert_eq!(text(w, Operator::Change, 0), Some("foo".to_string()));
+    assert_eq!(text(w, Operator::Delete, 2), Some("foo (".to_string()));
+    let object = OperatorTarget::TextObject {
+      object: TextObject::Word { big: false },
+      inner: true,
+    };
+    assert_eq!(text(object, Operator::Delete, 1), Some("foo".to_string()));
+  }
+}
@@ -0,0 +1,391 @@
+//! Search with `/` and `?`.
+//!
+//! The search pattern is a Vim regex with the 'magic' option, it's translated into the Rust
+//! [`regex`] syntax, see [`translate`]. The matches are searched line by line from the cursor, and
+//! wrap around the end (or start) of the buffer, i.e. the 'wrapscan' option.
+//!
+//! The matches are highlighted through the viewport:
+//!
+//! 1. While typing the pattern (the 'incsearch' option), the matches of the typed pattern are
+//!    highlighted, and the cursor moves to the first match.
+//! 2. After the search (the 'hlsearch' option), the matches of the last pattern are highlighted.
+//!
+//! Only the lines visible in the viewport are searched for highlighting.
+//!
+//! Searching a large buffer blocks the event loop, thus it checks the [`Interrupt`] periodically
+//! and stops on `Ctrl-C`.
+
+use crate::envar;
+use crate::interrupt::Interrupt;
+use crate::res::{ExCommandErr, ExCommandResult};
+use crate::state::current_window_buffer;
+use crate::ui::tree::{TreeArc, TreeNodeId};
+use crate::{rlock, wlock};
+
+use regex::{Regex, RegexBuilder};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use tracing::trace;
+
+/// The position of a match, i.e. `(line_idx, char_idx)`.
+pub type SearchPosition = (usize, usize);
+
+/// The interrupt is checked every this many lines.
+pub const INTERRUPT_CHECK_LINES: usize = 1000;
+
+/// Translate a Vim regex (with the 'magic' option) into the Rust regex syntax.
+///
+/// Returns the translated pattern, and whether it's case-insensitive, i.e. `\c` and `\C`.
+///
+/// NOTE: Only the common items are translated:
+///
+/// - `\(`, `\)`, `\|`, `\+`, `\=`, `\?`, `\{`: The special chars, while `(`, `)`, `|`, `+`,
+///   `?`, `{`, `}` are literal chars.
+/// - `\<`, `\>`: The word boundary.
+/// - `\c`, `\C`: Ignore case, match case.
+/// - `\/`: The literal `/`.
+pub fn translate(pattern: &str) -> (String, bool) {
+  let mut result = String::new();
+  let mut ignore_case = false;
+  // Inside a `\{n,m}` multi.
+  let mut in_brace = false;
+  let mut chars = pattern.chars();
+  while let Some(c) = chars.next() {
+    match c {
+      '\\' => match chars.next() {
+        Some(c @ ('(' | ')' | '|' | '+' | '?')) => result.push(c),
+        Some('=') => result.push('?'),
+        Some('{') => {
+          in_brace = true;
+          result.push('{');
+        }
+        Some('<') | Some('>') => result.push_str("\\b"),
+        Some('c') => ignore_case = true,
+        Some('C') => ignore_case = false,
+        Some('/') => result.push('/'),
+        Some(c) => {
+          result.push('\\');
+          result.push(c);
+        }
+        None => result.push_str("\\\\"),
+      },
+      '}' if in_brace => {
+        in_brace = false;
+        result.push('}');
+      }
+      '(' | ')' | '|' | '+' | '?' | '{' | '}' | '~' => {
+        result.push('\\');
+        result.push(c);
+      }
+      _ => result.push(c),
+    }
+  }
+  (result, ignore_case)
+}
+
+/// Compile a Vim regex.
+///
+/// # Errors
+///
+/// If the pattern is invalid.
+pub fn compile(pattern: &str) -> ExCommandResult<Regex> {
+  let (translated, ignore_case) = translate(pattern);
+  RegexBuilder::new(&translated)
+    .case_insensitive(ignore_case)
+    .build()
+    .map_err(|e| {
+      trace!("Failed to compile search pattern {:?}:{:?}", pattern, e);
+      ExCommandErr::InvalidPattern(pattern.to_string())
+    })
+}
+
+/// Find all the matches in the line, returns the char index ranges.
+///
+/// NOTE: The line break at the end of line is not searched.
+pub fn find_matches(regex: &Regex, line: &str) -> Vec<Range<usize>> {
+  let line = line.trim_end_matches(['\n', '\r']);
+  let mut result = vec![];
+  // The byte index and char index of last match end.
+  let mut last_byte_idx = 0_usize;
+  let mut last_char_idx = 0_usize;
+  for m in regex.find_iter(line) {
+    let start = last_char_idx + line[last_byte_idx..m.start()].chars().count();
+    let end = start + m.as_str().chars().count();
+    result.push(start..end);
+    last_byte_idx = m.end();
+    last_char_idx = end;
+  }
+  result
+}
+
+/// Find the next match from the position `from` (exclusive), it wraps around the end of buffer
+/// when `forward`, or the start of buffer otherwise.
+///
+/// The `line` gets the line content by line index, and `len_lines` is the lines count.
+///
+/// # Errors
+///
+/// If it's interrupted by `Ctrl-C`, it's checked every [`INTERRUPT_CHECK_LINES`] lines.
+pub fn find_next<F>(
+  regex: &Regex,
+  len_lines: usize,
+  line: F,
+  from: SearchPosition,
+  forward: bool,
+  interrupt: &Interrupt,
+) -> ExCommandResult<Option<SearchPosition>>
+where
+  F: Fn(usize) -> Option<String>,
+{
+  if len_lines == 0 {
+    return Ok(None);
+  }
+  let (from_line_idx, from_char_idx) = from;
+  let starts = |line_idx: usize| -> Vec<usize> {
+    match line(line_idx) {
+      Some(content) => find_matches(regex, &content)
+        .into_iter()
+        .map(|r| r.start)
+        .collect(),
+      None => vec![],
+    }
+  };
+
+  // Visit all the lines from the `from` line, and back to it at last.
+  for i in 0..=len_lines {
+    if i % INTERRUPT_CHECK_LINES == 0 {
+      interrupt.check()?;
+    }
+    let line_idx = if forward {
+      (from_line_idx + i) % len_lines
+    } else {
+      (from_line_idx + len_lines * 2 - i) % len_lines
+    };
+    let starts = starts(line_idx);
+    let found = match (i, forward) {
+      (0, true) => starts.into_iter().find(|s| *s > from_char_idx),
+      (0, false) => starts.into_iter().rev().find(|s| *s < from_char_idx),
+      (_, true) => starts.into_iter().next(),
+      (_, false) => starts.into_iter().next_back(),
+    };
+    if let Some(char_idx) = found {
+      return Ok(Some((line_idx, char_idx)));
+    }
+  }
+  Ok(None)
+}
+
+/// Find the next match in current window's buffer, see [`find_next`].
+///
+/// # Errors
+///
+/// If the pattern is invalid, or it's interrupted by `Ctrl-C`.
+pub fn find_in_buffer(
+  tree: &TreeArc,
+  pattern: &str,
+  from: SearchPosition,
+  forward: bool,
+  interrupt: &Interrupt,
+) -> ExCommandResult<Option<SearchPosition>> {
+  let regex = compile(pattern)?;
+  let (buffer, _viewport) = match current_window_buffer(tree) {
+    Some(current) => current,
+    None => return Ok(None),
+  };
+  let buffer = rlock!(buffer);
+  find_next(
+    &regex,
+    buffer.len_lines(),
+    |line_idx| buffer.get_line_content(line_idx),
+    from,
+    forward,
+    interrupt,
+  )
+}
+
+#[derive(Debug, Clone)]
+/// The search pattern being typed in command-line.
+pub struct SearchPrompt {
+  /// Search forward (`/`) or backward (`?`).
+  pub forward: bool,
+  /// Typed pattern.
+  pub query: String,
+  /// The cursor position in the pattern, i.e. the byte offset.
+  pub cursor: usize,
+  /// The cursor position before search, the cursor is restored to it when cancelled.
+  pub origin: SearchPosition,
+  /// The command-line widget ID.
+  pub cmdline_id: TreeNodeId,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The search state.
+pub struct SearchState {
+  // Last search pattern, and its direction.
+  last: Option<(String, bool)>,
+
+  // The search pattern being typed (if has).
+  prompt: Option<SearchPrompt>,
+}
+
+impl SearchState {
+  pub fn new() -> Self {
+    SearchState::default()
+  }
+
+  /// Get last search pattern, and whether it searches forward.
+  pub fn last(&self) -> &Option<(String, bool)> {
+    &self.last
+  }
+
+  /// Set last search pattern.
+  pub fn set_last(&mut self, pattern: &str, forward: bool) {
+    self.last = Some((pattern.to_string(), forward));
+  }
+
+  /// Get the search pattern being typed.
+  pub fn prompt(&self) -> &Option<SearchPrompt> {
+    &self.prompt
+  }
+
+  /// Get mutable search pattern being typed.
+  pub fn prompt_mut(&mut self) -> &mut Option<SearchPrompt> {
+    &mut self.prompt
+  }
+
+  /// Highlight the visible matches in current window, i.e. the typed pattern if 'incsearch', or
+  /// the last pattern if 'hlsearch'.
+  pub fn refresh(&self, tree: &TreeArc) {
+    let (hlsearch, incsearch) = {
+      let tree = rlock!(tree);
+      (tree.hlsearch(), tree.incsearch())
+    };
+    let pattern = match &self.prompt {
+      Some(prompt) if incsearch => Some(prompt.query.as_str()),
+      Some(_) => None,
+      None if hlsearch => self.last.as_ref().map(|(pattern, _)| pattern.as_str()),
+      None => None,
+    };
+
+    let (buffer, viewport) = match current_window_buffer(tree) {
+      Some(current) => current,
+      None => return,
+    };
+    let regex = match pattern.filter(|p| !p.is_empty()).map(compile) {
+      Some(Ok(regex)) => regex,
+      _ => {
+        let mut viewport = wlock!(viewport);
+        if !viewport.search_highlights().is_empty() {
+          viewport.set_search_highlights(BTreeMap::new());
+        }
+        return;
+      }
+    };
+
+    let line_range = {
+      let viewport = rlock!(viewport);
+      viewport.start_line_idx()..viewport.end_line_idx()
+    };
+    let mut search_highlights = BTreeMap::new();
+    {
+      let buffer = rlock!(buffer);
+      for line_idx in line_range {
+        if let Some(line) = buffer.get_line_content(line_idx) {
+          let ranges: Vec<Range<usize>> = find_matches(&regex, &line)
+            .into_iter()
+            .filter(|r| !r.is_empty())
+            .collect();
+          if !ranges.is_empty() {
+            search_highlights.insert(line_idx, ranges);
+          }
+        }
+      }
+    }
+    // NOTE: The buffer lock must be released before locking viewport.
+    wlock!(viewport).set_search_highlights(search_highlights);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn translate1() {
+    assert_eq!(translate("foo"), ("foo".to_string(), false));
+    assert_eq!(translate("\\<foo\\>"), ("\\bfoo\\b".to_string(), false));
+    assert_eq!(
+      translate("\\(a\\|b\\)\\+(c)"),
+      ("(a|b)+\\(c\\)".to_string(), false)
+    );
+    assert_eq!(translate("a\\{2,3}{"), ("a{2,3}\\{".to_string(), false));
+    assert_eq!(translate("\\cFoo"), ("Foo".to_string(), true));
+    assert_eq!(translate("a\\/b\\s"), ("a/b\\s".to_string(), false));
+  }
+
+  #[test]
+  fn find_matches1() {
+    let regex = compile("o\\+").unwrap();
+    assert_eq!(find_matches(&regex, "foo boo\n"), vec![1..3, 5..7]);
+    let regex = compile("\\c世界").unwrap();
+    assert_eq!(find_matches(&regex, "你好 世界 世界"), vec![3..5, 6..8]);
+    let regex = compile("\\cFOO").unwrap();
+    assert_eq!(find_matches(&regex, "foo"), vec![0..3]);
+    assert!(compile("\\(").is_err());
+  }
+
+  #[test]
+  fn find_next1() {
+    let lines = ["foo bar", "", "bar foo", "baz"];
+    let line = |line_idx: usize| lines.get(line_idx).map(|l| l.to_string());
+    let interrupt = Interrupt::new();
+    let find = |regex: &Regex, from: SearchPosition, forward: bool| {
+      find_next(regex, 4, line, from, forward, &interrupt).unwrap()
+    };
+    let regex = compile("foo").unwrap();
+
+    assert_eq!(find(&regex, (0, 0), true), Some((2, 4)));
+    assert_eq!(find(&regex, (2, 4), true), Some((0, 0)));
+    assert_eq!(find(&regex, (2, 4), false), Some((0, 0)));
+    assert_eq!(find(&regex, (0, 0), false), Some((2, 4)));
+    assert_eq!(find(&regex, (3, 1), false), Some((2, 4)));
+
+    // The only match is the `from` position.
+    let regex = compile("baz").unwrap();
+    assert_eq!(find(&regex, (3, 0), true), Some((3, 0)));
+    let regex = compile("qux").unwrap();
+    assert_eq!(find(&regex, (0, 0), true), None);
+  }
+
+  #[test]
+  fn find_next2() {
+    // Interrupted while scanning the lines, the match after the interrupt is not found.
+    let len_lines = INTERRUPT_CHECK_LINES * 3;
+    let interrupt = Interrupt::new();
+    let scanned = std::cell::Cell::new(0_usize);
+    let line = |line_idx: usize| {
+      scanned.set(scanned.get() + 1);
+      if scanned.get() == INTERRUPT_CHECK_LINES + 1 {
+        // i.e. the `Ctrl-C` is pressed while scanning.
+        interrupt.interrupt();
+      }
+      if line_idx == len_lines - 1 {
+        Some("foo".to_string())
+      } else {
+        Some(String::new())
+      }
+    };
+    let regex = compile("foo").unwrap();
+    let actual = find_next(&regex, len_lines, line, (0, 0), true, &interrupt);
+    assert_eq!(actual, Err(ExCommandErr::Interrupted));
+    assert_eq!(scanned.get(), INTERRUPT_CHECK_LINES * 2);
+  }
+
+  #[test]
+  fn compile1() {
+    assert_eq!(
+      compile("a\\(").unwrap_err().to_string(),
+      "E383: Invalid search string: a\\("
+    );
+  }
+}
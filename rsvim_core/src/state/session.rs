@@ -0,0 +1,454 @@
+//! Sessions, i.e. `:mksession`.
+//!
+//! A session is saved as a JS script which replays the API calls (i.e. `Rsvim.opt`, `Rsvim.cmd`
+//! and `Rsvim.win`), thus the js runtime is the restore mechanism: the session is loaded with
+//! `rsvim --session {file}` or `:source {file}`. It contains:
+//!
+//! - The global options, i.e. 'wrap', 'linebreak', 'minimap', 'hlsearch', 'incsearch' and
+//!   'autopairs'.
+//! - The opened file buffers.
+//! - The tiled windows layout, the file, cursor position and size of each window, and current
+//!   window.
+//!
+//! The layout is rebuilt with `:split`/`:vsplit`: a split with `N` children splits its window
+//! `N-1` times (each new window is placed above or on the left side of current window), then each
+//! child is restored recursively in its own window.
+//!
+//! The named sessions are saved under the `sessions` directory of the data directory (see
+//! [`DATA_DIR_PATH`](crate::envar::DATA_DIR_PATH)) by `:SessionSave [name]`, and switched by
+//! `:SessionLoad [name]` or the command palette. The default name is derived from current working
+//! directory (see [`directory_session_name`]), thus each project has its own session. The last
+//! saved/loaded named session is the current session, it's saved again on exit and before
+//! switching to another session.
+//!
+//! NOTE: The floating windows, the buffers without file (i.e. scratch buffers) and the remote files
+//! are not saved. The window sizes are restored with `:resize`, thus they are approximate if the
+//! terminal size is changed.
+
+use crate::buf::{Buffer, BuffersManager};
+use crate::envar;
+use crate::res::IoResult;
+use crate::rlock;
+use crate::ui::tree::internal::Inodeable;
+use crate::ui::tree::layout::{LayoutDirection, LayoutNode};
+use crate::ui::tree::{Tree, TreeNode};
+
+use std::path::{Path, PathBuf};
+
+/// The default session file, i.e. `:mksession` without file name.
+pub const DEFAULT_SESSION_FILE: &str = "Session.js";
+
+/// The named sessions directory name under the data directory.
+pub const SESSIONS_DIR: &str = "sessions";
+
+/// The named sessions directory, i.e. `$XDG_DATA_HOME/rsvim/sessions`.
+pub fn sessions_dir() -> PathBuf {
+  envar::DATA_DIR_PATH().join(SESSIONS_DIR)
+}
+
+/// The default session name of the directory, i.e. the absolute path with the path separators
+/// (and the drive colon on Windows) replaced by `%`, for example `/home/user/project` is
+/// `%home%user%project`.
+pub fn directory_session_name(dir: &Path) -> String {
+  dir
+    .to_string_lossy()
+    .chars()
+    .map(|c| match c {
+      '/' | '\\' | ':' => '%',
+      c => c,
+    })
+    .collect()
+}
+
+/// Whether the session name is valid, i.e. it's a plain file name.
+pub fn is_valid_session_name(name: &str) -> bool {
+  !name.is_empty()
+    && name != "."
+    && name != ".."
+    && !name.contains(['/', '\\'])
+    && !name.chars().any(|c| c.is_control())
+}
+
+/// The file of the named session in the `dir`, i.e. `{name}.js`.
+pub fn session_file(dir: &Path, name: &str) -> PathBuf {
+  dir.join(format!("{}.js", name))
+}
+
+/// List the named sessions in the `dir`, sorted by name. It's empty if the directory doesn't
+/// exist.
+pub fn list_sessions(dir: &Path) -> Vec<String> {
+  let mut names: Vec<String> = match std::fs::read_dir(dir) {
+    Ok(entries) => entries
+      .filter_map(|entry| {
+        let path = entry.ok()?.path();
+        if !path.is_file() || path.extension()? != "js" {
+          return None;
+        }
+        Some(path.file_stem()?.to_string_lossy().to_string())
+      })
+      .collect(),
+    Err(_) => vec![],
+  };
+  names.sort();
+  names
+}
+
+/// Save the session of the editor as the named session in the `dir`, the directory is created if
+/// it doesn't exist.
+pub fn save_named_session(
+  dir: &Path,
+  name: &str,
+  tree: &Tree,
+  buffers: &BuffersManager,
+) -> IoResult<PathBuf> {
+  std::fs::create_dir_all(dir)?;
+  let file = session_file(dir, name);
+  std::fs::write(&file, Session::new(tree, buffers).to_script())?;
+  Ok(file)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The saved tiled window.
+pub struct SessionWindow {
+  /// The file name, or `None` if the buffer is not saved.
+  pub file: Option<String>,
+  /// The cursor line index and char index, start from 0.
+  pub cursor: (usize, usize),
+  /// The width and height.
+  pub size: (u16, u16),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The saved layout tree, see [`LayoutNode`].
+pub enum SessionLayout {
+  Window(SessionWindow),
+  Split(LayoutDirection, Vec<SessionLayout>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The saved session.
+pub struct Session {
+  /// The global boolean options, by their `Rsvim.opt` names, i.e. `lineBreak`.
+  pub options: Vec<(&'static str, bool)>,
+  /// The opened file names.
+  pub files: Vec<String>,
+  /// The tiled windows layout.
+  pub layout: SessionLayout,
+  /// The index of current window in the layout order, i.e. from top-left to bottom-right.
+  pub current: usize,
+}
+
+// The saved file name of the buffer.
+fn file_of(buffer: &Buffer) -> Option<String> {
+  if buffer.remote().is_some() {
+    return None;
+  }
+  buffer
+    .filename()
+    .as_ref()
+    .map(|filename| filename.to_string_lossy().to_string())
+}
+
+fn layout_of(tree: &Tree, node: &LayoutNode) -> SessionLayout {
+  match node {
+    LayoutNode::Window(window_id) => {
+      let window = match tree.node(window_id) {
+        Some(TreeNode::Window(window)) => {
+          let file = window
+            .buffer()
+            .upgrade()
+            .and_then(|buffer| file_of(&rlock!(buffer)));
+          let viewport = window.viewport();
+          let viewport = rlock!(viewport);
+          let shape = window.actual_shape();
+          SessionWindow {
+            file,
+            cursor: (viewport.cursor().line_idx(), viewport.cursor().char_idx()),
+            size: (shape.width(), shape.height()),
+          }
+        }
+        _ => SessionWindow {
+          file: None,
+          cursor: (0, 0),
+          size: (0, 0),
+        },
+      };
+      SessionLayout::Window(window)
+    }
+    LayoutNode::Split(split) => SessionLayout::Split(
+      split.direction,
+      split
+        .children
+        .iter()
+        .map(|child| layout_of(tree, child))
+        .collect(),
+    ),
+  }
+}
+
+/// Quote the string as a JS string literal.
+pub fn js_string(s: &str) -> String {
+  let mut result = String::with_capacity(s.len() + 2);
+  result.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => result.push_str("\\\""),
+      '\\' => result.push_str("\\\\"),
+      '\n' => result.push_str("\\n"),
+      '\r' => result.push_str("\\r"),
+      '\t' => result.push_str("\\t"),
+      // The line/paragraph separators are line terminators in JS.
+      c if c.is_control() || c == '\u{2028}' || c == '\u{2029}' => {
+        result.push_str(&format!("\\u{{{:x}}}", c as u32))
+      }
+      c => result.push(c),
+    }
+  }
+  result.push('"');
+  result
+}
+
+fn cmd(command_line: &str) -> String {
+  format!("Rsvim.cmd({});", js_string(command_line))
+}
+
+// Split the window `name` for the layout `node`, the windows are collected in the layout order.
+fn split_windows<'a>(
+  node: &'a SessionLayout,
+  name: String,
+  lines: &mut Vec<String>,
+  windows: &mut Vec<(String, &'a SessionWindow)>,
+  next: &mut usize,
+) {
+  match node {
+    SessionLayout::Window(window) => windows.push((name, window)),
+    SessionLayout::Split(direction, children) => {
+      let split = match direction {
+        LayoutDirection::Row => "vsplit",
+        LayoutDirection::Column => "split",
+      };
+      let mut names = vec![name.clone(); children.len()];
+      if children.len() > 1 {
+        lines.push(format!("Rsvim.win.focus({});", name));
+      }
+      for i in (0..children.len().saturating_sub(1)).rev() {
+        names[i] = format!("w{}", next);
+        *next += 1;
+        lines.push(cmd(split));
+        lines.push(format!("const {} = Rsvim.win.current();", names[i]));
+      }
+      for (child, name) in children.iter().zip(names) {
+        split_windows(child, name, lines, windows, next);
+      }
+    }
+  }
+}
+
+impl Session {
+  /// Make the session of the editor.
+  pub fn new(tree: &Tree, buffers: &BuffersManager) -> Self {
+    let options = vec![
+      ("wrap", tree.wrap()),
+      ("lineBreak", tree.line_break()),
+      ("minimap", tree.minimap()),
+      ("hlSearch", tree.hlsearch()),
+      ("incSearch", tree.incsearch()),
+      ("autoPairs", tree.autopairs()),
+    ];
+    let files = buffers
+      .values()
+      .filter_map(|buffer| file_of(&rlock!(buffer)))
+      .collect();
+    let current = tree
+      .current_window_id()
+      .and_then(|id| tree.layout().window_ids().iter().position(|w| *w == id))
+      .unwrap_or(0);
+    Session {
+      options,
+      files,
+      layout: layout_of(tree, tree.layout()),
+      current,
+    }
+  }
+
+  /// Generate the JS script to restore the session.
+  pub fn to_script(&self) -> String {
+    let mut lines = vec![
+      "// Rsvim session file, generated by `:mksession`.".to_string(),
+      "// Restore it with `rsvim --session {file}` or `:source {file}`.".to_string(),
+      String::new(),
+    ];
+    for (name, value) in self.options.iter() {
+      lines.push(format!("Rsvim.opt.{} = {};", name, value));
+    }
+
+    // Load the files in current window, and close other windows.
+    lines.push(String::new());
+    lines.push(cmd("only"));
+    for file in self.files.iter() {
+      lines.push(cmd(&format!("edit {}", file)));
+    }
+
+    // Rebuild the layout.
+    lines.push(String::new());
+    lines.push("const w0 = Rsvim.win.current();".to_string());
+    let mut windows = vec![];
+    split_windows(
+      &self.layout,
+      "w0".to_string(),
+      &mut lines,
+      &mut windows,
+      &mut 1,
+    );
+    for (name, window) in windows.iter() {
+      lines.push(format!("Rsvim.win.focus({});", name));
+      if let Some(file) = &window.file {
+        lines.push(cmd(&format!("edit {}", file)));
+      }
+      lines.push(format!(
+        "Rsvim.win.setCursor({}, {});",
+        window.cursor.0, window.cursor.1
+      ));
+    }
+    if windows.len() > 1 {
+      for (name, window) in windows.iter() {
+        lines.push(format!("Rsvim.win.focus({});", name));
+        lines.push(cmd(&format!("resize {}", window.size.1)));
+        lines.push(cmd(&format!("vertical resize {}", window.size.0)));
+      }
+    }
+    if let Some((name, _)) = windows.get(self.current) {
+      lines.push(format!("Rsvim.win.focus({});", name));
+    }
+
+    let mut script = lines.join("\n");
+    script.push('\n');
+    script
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn window(file: &str, cursor: (usize, usize)) -> SessionLayout {
+    SessionLayout::Window(SessionWindow {
+      file: Some(file.to_string()),
+      cursor,
+      size: (40, 10),
+    })
+  }
+
+  #[test]
+  fn directory_session_name1() {
+    assert_eq!(
+      directory_session_name(Path::new("/home/user/project")),
+      "%home%user%project"
+    );
+    assert_eq!(
+      directory_session_name(Path::new("C:\\Users\\project")),
+      "C%%Users%project"
+    );
+    assert!(is_valid_session_name("%home%user%project"));
+    assert!(is_valid_session_name("work"));
+    assert!(!is_valid_session_name(""));
+    assert!(!is_valid_session_name(".."));
+    assert!(!is_valid_session_name("a/b"));
+  }
+
+  #[test]
+  fn list_sessions1() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    assert!(list_sessions(&dir.path().join("missing")).is_empty());
+    std::fs::write(session_file(dir.path(), "b"), "").unwrap();
+    std::fs::write(session_file(dir.path(), "a"), "").unwrap();
+    std::fs::write(dir.path().join("c.txt"), "").unwrap();
+    std::fs::create_dir(dir.path().join("d.js")).unwrap();
+    assert_eq!(list_sessions(dir.path()), vec!["a", "b"]);
+  }
+
+  #[test]
+  fn js_string1() {
+    assert_eq!(js_string("a.rs"), "\"a.rs\"");
+    assert_eq!(
+      js_string("C:\\a \"b\"\n\u{1b}"),
+      "\"C:\\\\a \\\"b\\\"\\n\\u{1b}\""
+    );
+  }
+
+  #[test]
+  fn to_script1() {
+    let session = Session {
+      options: vec![("wrap", false)],
+      files: vec!["a.rs".to_string()],
+      layout: SessionLayout::Split(LayoutDirection::Column, vec![window("a.rs", (3, 2))]),
+      current: 0,
+    };
+    let expect = [
+      "// Rsvim session file, generated by `:mksession`.",
+      "// Restore it with `rsvim --session {file}` or `:source {file}`.",
+      "",
+      "Rsvim.opt.wrap = false;",
+      "",
+      "Rsvim.cmd(\"only\");",
+      "Rsvim.cmd(\"edit a.rs\");",
+      "",
+      "const w0 = Rsvim.win.current();",
+      "Rsvim.win.focus(w0);",
+      "Rsvim.cmd(\"edit a.rs\");",
+      "Rsvim.win.setCursor(3, 2);",
+      "Rsvim.win.focus(w0);",
+    ];
+    assert_eq!(session.to_script(), expect.join("\n") + "\n");
+  }
+
+  #[test]
+  fn to_script2() {
+    // +---+-------+
+    // |   |   b   |
+    // | a +-------+
+    // |   |   c   |
+    // +---+-------+
+    let session = Session {
+      options: vec![],
+      files: vec![],
+      layout: SessionLayout::Split(
+        LayoutDirection::Row,
+        vec![
+          window("a.rs", (0, 0)),
+          SessionLayout::Split(
+            LayoutDirection::Column,
+            vec![window("b.rs", (1, 0)), window("c.rs", (2, 0))],
+          ),
+        ],
+      ),
+      current: 2,
+    };
+    let script = session.to_script();
+    let lines: Vec<&str> = script
+      .lines()
+      .skip_while(|l| !l.starts_with("const"))
+      .collect();
+    let expect = [
+      "const w0 = Rsvim.win.current();",
+      "Rsvim.win.focus(w0);",
+      "Rsvim.cmd(\"vsplit\");",
+      "const w1 = Rsvim.win.current();",
+      "Rsvim.win.focus(w0);",
+      "Rsvim.cmd(\"split\");",
+      "const w2 = Rsvim.win.current();",
+      "Rsvim.win.focus(w1);",
+      "Rsvim.cmd(\"edit a.rs\");",
+      "Rsvim.win.setCursor(0, 0);",
+      "Rsvim.win.focus(w2);",
+      "Rsvim.cmd(\"edit b.rs\");",
+      "Rsvim.win.setCursor(1, 0);",
+      "Rsvim.win.focus(w0);",
+      "Rsvim.cmd(\"edit c.rs\");",
+      "Rsvim.win.setCursor(2, 0);",
+    ];
+    assert_eq!(lines[..expect.len()], expect);
+    assert_eq!(lines.last(), Some(&"Rsvim.win.focus(w0);"));
+    assert!(lines.contains(&"Rsvim.cmd(\"vertical resize 40\");"));
+  }
+}
@@ -0,0 +1,735 @@
+//! Surround editing, i.e. add/change/delete the surrounding pairs, quotes, tags and function calls.
+//!
+//! - `ds{target}`: Delete the surrounding.
+//! - `cs{target}{replacement}`: Change the surrounding.
+//! - `ys{motion}{replacement}`: Add the surrounding, the motions are `s` (current line without the
+//!   leading blanks), `iw` (inner word), `iW` (inner WORD) and `$` (to the end of line).
+//! - `S{replacement}` in visual mode: Add the surrounding to the selection.
+//!
+//! The targets are:
+//!
+//! - `)`/`b`, `]`/`r`, `}`/`B`, `>`/`a`: The pairs, the opening char `(`, `[`, `{`, `<` also
+//!   deletes the blanks inside the pair.
+//! - `"`, `'`, `` ` ``: The quotes in current line.
+//! - `t`: The innermost HTML/XML tags.
+//! - `f`: The function call, i.e. `name(` and `)`.
+//!
+//! The replacements are:
+//!
+//! - `)`/`b`, `]`/`r`, `}`/`B`, `>`/`a`: The pairs, the opening char `(`, `[`, `{` adds a space
+//!   inside the pair.
+//! - `"`, `'`, `` ` ``: The quotes.
+//! - `<{tag}>` or `t{tag}>`: The tags, i.e. `<div class="a">` adds `<div class="a">` and `</div>`.
+//! - `f{name}(`: The function call, i.e. `fprint(` adds `print(` and `)`.
+//!
+//! The pairs and tags are searched across lines, at most [`SEARCH_LINES`] lines around the cursor.
+//! Each command is one undo state, and it's repeatable with `.`.
+//!
+//! See: <https://github.com/tpope/vim-surround>.
+
+use crate::buf::Buffer;
+use crate::state::autopair::wrap_selection;
+use crate::state::visual::{Selection, VisualPosition};
+
+use std::ops::Range;
+
+/// The max lines searched before/after the cursor for the pairs and tags.
+pub const SEARCH_LINES: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The surrounding text to add.
+pub struct Surrounding {
+  pub open: String,
+  pub close: String,
+}
+
+impl Surrounding {
+  pub fn new(open: &str, close: &str) -> Self {
+    Surrounding {
+      open: open.to_string(),
+      close: close.to_string(),
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The surrounding to delete/change.
+pub enum Target {
+  /// The pair, the blanks inside are trimmed if `trim`.
+  Pair { open: char, close: char, trim: bool },
+  /// The quotes in current line.
+  Quote(char),
+  /// The HTML/XML tags.
+  Tag,
+  /// The function call.
+  Call,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The text to add the surrounding.
+pub enum SurroundMotion {
+  /// `s`, current line without the leading blanks.
+  Line,
+  /// `iw`
+  InnerWord,
+  /// `iW`
+  InnerBigWord,
+  /// `$`
+  ToLineEnd,
+  /// The visual selection.
+  Selection,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The surround command.
+pub enum SurroundCommand {
+  Delete(Target),
+  Change(Target, Surrounding),
+  Add(SurroundMotion, Surrounding),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The result of the typed keys.
+pub enum SurroundInput {
+  /// Wait for more keys.
+  Pending,
+  /// The command is complete.
+  Done(SurroundCommand),
+  /// The keys are invalid.
+  Cancel,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The typed keys of a running surround command.
+pub struct SurroundKeys {
+  keys: Vec<char>,
+}
+
+impl SurroundKeys {
+  /// Start with the first key, i.e. the operator `d`, `c`, `y`, or `S` in visual mode.
+  pub fn new(first: char) -> Self {
+    SurroundKeys { keys: vec![first] }
+  }
+
+  /// Type a key.
+  pub fn input(&mut self, c: char) -> SurroundInput {
+    self.keys.push(c);
+    parse_command(&self.keys)
+  }
+}
+
+// The parsed result of the keys.
+enum Parsed<T> {
+  Pending,
+  Done(T),
+  Invalid,
+}
+
+fn parse_command(keys: &[char]) -> SurroundInput {
+  let first = match keys.first() {
+    Some(first) => *first,
+    None => return SurroundInput::Pending,
+  };
+  if first == 'S' {
+    return match parse_replacement(&keys[1..]) {
+      Parsed::Pending => SurroundInput::Pending,
+      Parsed::Done(s) => SurroundInput::Done(SurroundCommand::Add(SurroundMotion::Selection, s)),
+      Parsed::Invalid => SurroundInput::Cancel,
+    };
+  }
+  if !matches!(first, 'd' | 'c' | 'y') {
+    return SurroundInput::Cancel;
+  }
+  match keys.get(1) {
+    Some('s') => { /* Continue */ }
+    Some(_) => return SurroundInput::Cancel,
+    None => return SurroundInput::Pending,
+  }
+
+  if first == 'y' {
+    let (motion, len) = match (keys.get(2), keys.get(3)) {
+      (None, _) => return SurroundInput::Pending,
+      (Some('s'), _) => (SurroundMotion::Line, 3),
+      (Some('$'), _) => (SurroundMotion::ToLineEnd, 3),
+      (Some('i'), None) => return SurroundInput::Pending,
+      (Some('i'), Some('w')) => (SurroundMotion::InnerWord, 4),
+      (Some('i'), Some('W')) => (SurroundMotion::InnerBigWord, 4),
+      _ => return SurroundInput::Cancel,
+    };
+    return match parse_replacement(&keys[len..]) {
+      Parsed::Pending => SurroundInput::Pending,
+      Parsed::Done(s) => SurroundInput::Done(SurroundCommand::Add(motion, s)),
+      Parsed::Invalid => SurroundInput::Cancel,
+    };
+  }
+
+  let target = match keys.get(2) {
+    Some(c) => match parse_target(*c) {
+      Some(target) => target,
+      None => return SurroundInput::Cancel,
+    },
+    None => return SurroundInput::Pending,
+  };
+  if first == 'd' {
+    return SurroundInput::Done(SurroundCommand::Delete(target));
+  }
+  match parse_replacement(&keys[3..]) {
+    Parsed::Pending => SurroundInput::Pending,
+    Parsed::Done(s) => SurroundInput::Done(SurroundCommand::Change(target, s)),
+    Parsed::Invalid => SurroundInput::Cancel,
+  }
+}
+
+/// Parse the target char.
+pub fn parse_target(c: char) -> Option<Target> {
+  let pair = |open, close, trim| Some(Target::Pair { open, close, trim });
+  match c {
+    ')' | 'b' => pair('(', ')', false),
+    '(' => pair('(', ')', true),
+    ']' | 'r' => pair('[', ']', false),
+    '[' => pair('[', ']', true),
+    '}' | 'B' => pair('{', '}', false),
+    '{' => pair('{', '}', true),
+    '>' | 'a' => pair('<', '>', false),
+    '<' => pair('<', '>', true),
+    '"' | '\'' | '`' => Some(Target::Quote(c)),
+    't' => Some(Target::Tag),
+    'f' => Some(Target::Call),
+    _ => None,
+  }
+}
+
+fn parse_replacement(keys: &[char]) -> Parsed<Surrounding> {
+  let first = match keys.first() {
+    Some(first) => *first,
+    None => return Parsed::Pending,
+  };
+  let rest: String = keys[1..].iter().collect();
+  let s = match first {
+    ')' | 'b' => Surrounding::new("(", ")"),
+    '(' => Surrounding::new("( ", " )"),
+    ']' | 'r' => Surrounding::new("[", "]"),
+    '[' => Surrounding::new("[ ", " ]"),
+    '}' | 'B' => Surrounding::new("{", "}"),
+    '{' => Surrounding::new("{ ", " }"),
+    '>' | 'a' => Surrounding::new("<", ">"),
+    '"' | '\'' | '`' => Surrounding::new(&first.to_string(), &first.to_string()),
+    '<' | 't' => {
+      // The tag is complete on `>`.
+      let tag = match rest.strip_suffix('>') {
+        Some(tag) => tag.trim(),
+        None => return Parsed::Pending,
+      };
+      let name = match tag.split_whitespace().next() {
+        Some(name) => name,
+        None => return Parsed::Invalid,
+      };
+      Surrounding::new(&format!("<{}>", tag), &format!("</{}>", name))
+    }
+    'f' => {
+      // The function name is complete on `(`.
+      let name = match rest.strip_suffix('(') {
+        Some(name) => name.trim(),
+        None => return Parsed::Pending,
+      };
+      if name.is_empty() || !name.chars().all(is_call_char) {
+        return Parsed::Invalid;
+      }
+      Surrounding::new(&format!("{}(", name), ")")
+    }
+    _ => return Parsed::Invalid,
+  };
+  Parsed::Done(s)
+}
+
+fn is_word_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+fn is_call_char(c: char) -> bool {
+  is_word_char(c) || c == '.' || c == ':'
+}
+
+fn is_blank(c: char) -> bool {
+  c == ' ' || c == '\t'
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The found surrounding, i.e. the char index ranges of the opening and closing text.
+pub struct Found {
+  pub open: Range<usize>,
+  pub close: Range<usize>,
+}
+
+/// Find the surrounding `target` of the `cursor` in the `chars`.
+pub fn find_target(chars: &[char], cursor: usize, target: Target) -> Option<Found> {
+  match target {
+    Target::Pair { open, close, trim } => {
+      let (start, end) = find_pair(chars, cursor, open, close)?;
+      let mut found = Found {
+        open: start..start + 1,
+        close: end..end + 1,
+      };
+      if trim {
+        while found.open.end < found.close.start && is_blank(chars[found.open.end]) {
+          found.open.end += 1;
+        }
+        while found.close.start > found.open.end && is_blank(chars[found.close.start - 1]) {
+          found.close.start -= 1;
+        }
+      }
+      Some(found)
+    }
+    Target::Quote(q) => find_quote(chars, cursor, q),
+    Target::Tag => find_tag(chars, cursor),
+    Target::Call => find_call(chars, cursor),
+  }
+}
+
+// Find the pair around the cursor (the cursor can be on the opening/closing char), returns the
+// char index of the opening char and closing char.
+fn find_pair(chars: &[char], cursor: usize, open: char, close: char) -> Option<(usize, usize)> {
+  let start = if chars.get(cursor) == Some(&open) {
+    cursor
+  } else {
+    let mut depth = 0_usize;
+    let mut i = std::cmp::min(cursor, chars.len());
+    loop {
+      if i == 0 {
+        return None;
+      }
+      i -= 1;
+      if chars[i] == close {
+        depth += 1;
+      } else if chars[i] == open {
+        if depth == 0 {
+          break i;
+        }
+        depth -= 1;
+      }
+    }
+  };
+  let mut depth = 0_usize;
+  for (i, c) in chars.iter().enumerate().skip(start + 1) {
+    if *c == open {
+      depth += 1;
+    } else if *c == close {
+      if depth == 0 {
+        return Some((start, i));
+      }
+      depth -= 1;
+    }
+  }
+  None
+}
+
+// The char index range of the line that contains the `cursor`, without the line ending.
+fn line_range(chars: &[char], cursor: usize) -> Range<usize> {
+  let cursor = std::cmp::min(cursor, chars.len());
+  let start = chars[..cursor]
+    .iter()
+    .rposition(|c| *c == '\n')
+    .map(|i| i + 1)
+    .unwrap_or(0);
+  let end = chars[cursor..]
+    .iter()
+    .position(|c| *c == '\n')
+    .map(|i| cursor + i)
+    .unwrap_or(chars.len());
+  start..end
+}
+
+// Find the quotes around the cursor in current line, the escaped quotes are skipped.
+fn find_quote(chars: &[char], cursor: usize, q: char) -> Option<Found> {
+  let line = line_range(chars, cursor);
+  let quotes: Vec<usize> = line
+    .clone()
+    .filter(|i| chars[*i] == q && (*i == line.start || chars[*i - 1] != '\\'))
+    .collect();
+  quotes
+    .chunks_exact(2)
+    .find(|pair| pair[0] <= cursor && cursor <= pair[1])
+    .map(|pair| Found {
+      open: pair[0]..pair[0] + 1,
+      close: pair[1]..pair[1] + 1,
+    })
+}
+
+// Find the innermost tags around the cursor.
+fn find_tag(chars: &[char], cursor: usize) -> Option<Found> {
+  // The opening tags not closed yet, i.e. the tag name and the char index range.
+  let mut stack: Vec<(String, Range<usize>)> = vec![];
+  let mut i = 0_usize;
+  while i < chars.len() {
+    if chars[i] != '<' {
+      i += 1;
+      continue;
+    }
+    let end = match chars[i + 1..].iter().position(|c| *c == '>' || *c == '<') {
+      Some(n) if chars[i + 1 + n] == '>' => i + 1 + n,
+      Some(n) => {
+        i += 1 + n;
+        continue;
+      }
+      None => break,
+    };
+    let text: String = chars[i + 1..end].iter().collect();
+    let range = i..end + 1;
+    i = end + 1;
+    if let Some(name) = text.strip_prefix('/') {
+      let name = name.trim();
+      if let Some(pos) = stack.iter().rposition(|(n, _)| n == name) {
+        let (_, open) = stack[pos].clone();
+        stack.truncate(pos);
+        // The inner tags are closed before the outer tags, thus the first one is innermost.
+        if open.start <= cursor && cursor < range.end {
+          return Some(Found { open, close: range });
+        }
+      }
+    } else if !text.ends_with('/') && !text.starts_with('!') && !text.starts_with('?') {
+      if let Some(name) = text.split_whitespace().next() {
+        stack.push((name.to_string(), range));
+      }
+    }
+  }
+  None
+}
+
+// Find the function call around the cursor, i.e. the `name(` and `)`.
+fn find_call(chars: &[char], cursor: usize) -> Option<Found> {
+  let mut pos = cursor;
+  loop {
+    let (start, end) = find_pair(chars, pos, '(', ')')?;
+    if end < cursor {
+      return None;
+    }
+    let mut name_start = start;
+    while name_start > 0 && is_call_char(chars[name_start - 1]) {
+      name_start -= 1;
+    }
+    if name_start < start {
+      return Some(Found {
+        open: name_start..start + 1,
+        close: end..end + 1,
+      });
+    }
+    // Not a function call, try the outer pair.
+    if start == 0 {
+      return None;
+    }
+    pos = start - 1;
+  }
+}
+
+/// Get the char index range of the `motion` at the `cursor`, returns `None` if it's empty.
+pub fn motion_range(chars: &[char], cursor: usize, motion: SurroundMotion) -> Option<Range<usize>> {
+  let line = line_range(chars, cursor);
+  let range = match motion {
+    SurroundMotion::Line => {
+      let start = line.clone().find(|i| !is_blank(chars[*i]))?;
+      start..line.end
+    }
+    SurroundMotion::ToLineEnd => cursor..line.end,
+    SurroundMotion::InnerWord | SurroundMotion::InnerBigWord => {
+      if cursor >= line.end {
+        return None;
+      }
+      let class = |c: char| {
+        if is_blank(c) {
+          0
+        } else if motion == SurroundMotion::InnerBigWord || is_word_char(c) {
+          1
+        } else {
+          2
+        }
+      };
+      let cursor_class = class(chars[cursor]);
+      let mut start = cursor;
+      while start > line.start && class(chars[start - 1]) == cursor_class {
+        start -= 1;
+      }
+      let mut end = cursor + 1;
+      while end < line.end && class(chars[end]) == cursor_class {
+        end += 1;
+      }
+      start..end
+    }
+    SurroundMotion::Selection => return None,
+  };
+  if range.is_empty() {
+    None
+  } else {
+    Some(range)
+  }
+}
+
+/// Run the surround command at the `cursor` of the `buffer` (or the visual `selection`), as one
+/// undo state.
+///
+/// Returns the new cursor position, i.e. the start of the surrounding, or `None` if the target is
+/// not found.
+pub fn apply(
+  buffer: &mut Buffer,
+  cursor: VisualPosition,
+  command: &SurroundCommand,
+  selection: Option<&Selection>,
+) -> Option<VisualPosition> {
+  if let SurroundCommand::Add(SurroundMotion::Selection, s) = command {
+    let selection = selection?;
+    wrap_selection(buffer, selection, &s.open, &s.close);
+    return Some(selection.ordered().0);
+  }
+
+  let start_line_idx = cursor.0.saturating_sub(SEARCH_LINES);
+  let end_line_idx = std::cmp::min(cursor.0 + SEARCH_LINES + 1, buffer.len_lines());
+  let base = buffer.line_to_char(start_line_idx);
+  let mut chars: Vec<char> = vec![];
+  for line_idx in start_line_idx..end_line_idx {
+    if let Some(line) = buffer.get_line(line_idx) {
+      chars.extend(line.chars());
+    }
+  }
+  let cursor_idx = buffer.line_to_char(cursor.0) + cursor.1 - base;
+
+  // The replacements in reverse order, thus the earlier ranges are not shifted.
+  let edits: Vec<(Range<usize>, &str)> = match command {
+    SurroundCommand::Delete(target) => {
+      let found = find_target(&chars, cursor_idx, *target)?;
+      vec![(found.close, ""), (found.open, "")]
+    }
+    SurroundCommand::Change(target, s) => {
+      let found = find_target(&chars, cursor_idx, *target)?;
+      vec![
+        (found.close, s.close.as_str()),
+        (found.open, s.open.as_str()),
+      ]
+    }
+    SurroundCommand::Add(motion, s) => {
+      let range = motion_range(&chars, cursor_idx, *motion)?;
+      vec![
+        (range.end..range.end, s.close.as_str()),
+        (range.start..range.start, s.open.as_str()),
+      ]
+    }
+  };
+  let start_char_idx = base + edits.last().unwrap().0.start;
+
+  buffer.undo_tree_mut().begin_group();
+  for (range, text) in edits {
+    let range = base + range.start..base + range.end;
+    if !range.is_empty() {
+      buffer.remove(range.clone());
+    }
+    if !text.is_empty() {
+      buffer.insert(range.start, text);
+    }
+  }
+  buffer.undo_tree_mut().end_group();
+
+  let line_idx = buffer.char_to_line(start_char_idx);
+  Some((line_idx, start_char_idx - buffer.line_to_char(line_idx)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::envar;
+  use crate::state::visual::VisualKind;
+  use crate::test::buf::make_buffer_from_lines;
+  use crate::wlock;
+
+  fn input(keys: &str) -> SurroundInput {
+    let mut chars = keys.chars();
+    let mut surround = SurroundKeys::new(chars.next().unwrap());
+    let mut result = SurroundInput::Pending;
+    for c in chars {
+      result = surround.input(c);
+    }
+    result
+  }
+
+  fn chars(s: &str) -> Vec<char> {
+    s.chars().collect()
+  }
+
+  #[test]
+  fn input1() {
+    assert_eq!(input("ds"), SurroundInput::Pending);
+    assert_eq!(
+      input("ds)"),
+      SurroundInput::Done(SurroundCommand::Delete(Target::Pair {
+        open: '(',
+        close: ')',
+        trim: false
+      }))
+    );
+    assert_eq!(input("dx"), SurroundInput::Cancel);
+    assert_eq!(input("ds1"), SurroundInput::Cancel);
+    assert_eq!(input("cs\"<di"), SurroundInput::Pending);
+    assert_eq!(
+      input("cs\"<div id=\"a\">"),
+      SurroundInput::Done(SurroundCommand::Change(
+        Target::Quote('"'),
+        Surrounding::new("<div id=\"a\">", "</div>")
+      ))
+    );
+    assert_eq!(
+      input("ysiwfprint("),
+      SurroundInput::Done(SurroundCommand::Add(
+        SurroundMotion::InnerWord,
+        Surrounding::new("print(", ")")
+      ))
+    );
+    assert_eq!(
+      input("yss{"),
+      SurroundInput::Done(SurroundCommand::Add(
+        SurroundMotion::Line,
+        Surrounding::new("{ ", " }")
+      ))
+    );
+    assert_eq!(
+      input("S'"),
+      SurroundInput::Done(SurroundCommand::Add(
+        SurroundMotion::Selection,
+        Surrounding::new("'", "'")
+      ))
+    );
+    assert_eq!(input("ysix"), SurroundInput::Cancel);
+  }
+
+  #[test]
+  fn find_target1() {
+    let text = chars("f(a, (b), c)");
+    let paren = parse_target(')').unwrap();
+    // Cursor on `a`.
+    assert_eq!(
+      find_target(&text, 2, paren),
+      Some(Found {
+        open: 1..2,
+        close: 11..12
+      })
+    );
+    // Cursor on the inner `(`.
+    assert_eq!(
+      find_target(&text, 5, paren),
+      Some(Found {
+        open: 5..6,
+        close: 7..8
+      })
+    );
+    // Cursor on the inner `)`.
+    assert_eq!(find_target(&text, 7, paren).unwrap().open, 5..6);
+    // Function call.
+    assert_eq!(
+      find_target(&text, 6, Target::Call),
+      Some(Found {
+        open: 0..2,
+        close: 11..12
+      })
+    );
+
+    // Trim blanks, across lines.
+    let text = chars("{ a\n  b }");
+    assert_eq!(
+      find_target(&text, 6, parse_target('{').unwrap()),
+      Some(Found {
+        open: 0..2,
+        close: 7..9
+      })
+    );
+  }
+
+  #[test]
+  fn find_target2() {
+    // Quotes, the escaped one is skipped.
+    let text = chars("x = \"a\\\"b\" + 'c'");
+    assert_eq!(
+      find_target(&text, 6, Target::Quote('"')),
+      Some(Found {
+        open: 4..5,
+        close: 9..10
+      })
+    );
+    assert_eq!(find_target(&text, 12, Target::Quote('"')), None);
+
+    // Tags, multi-byte chars.
+    let text = chars("<p>你<b class=\"x\">好</b><br/></p>");
+    assert_eq!(
+      find_target(&text, 17, Target::Tag),
+      Some(Found {
+        open: 4..17,
+        close: 18..22
+      })
+    );
+    assert_eq!(
+      find_target(&text, 3, Target::Tag),
+      Some(Found {
+        open: 0..3,
+        close: 27..31
+      })
+    );
+  }
+
+  #[test]
+  fn motion_range1() {
+    let text = chars("  foo.bar baz\nnext");
+    assert_eq!(motion_range(&text, 4, SurroundMotion::Line), Some(2..13));
+    assert_eq!(
+      motion_range(&text, 4, SurroundMotion::InnerWord),
+      Some(2..5)
+    );
+    assert_eq!(
+      motion_range(&text, 4, SurroundMotion::InnerBigWord),
+      Some(2..9)
+    );
+    assert_eq!(
+      motion_range(&text, 10, SurroundMotion::ToLineEnd),
+      Some(10..13)
+    );
+    assert_eq!(motion_range(&text, 13, SurroundMotion::InnerWord), None);
+  }
+
+  #[test]
+  fn apply1() {
+    let buf = make_buffer_from_lines(vec!["say(\n", "  \"你好\")\n"]);
+    let mut buf = wlock!(buf);
+
+    // Change the quotes to tags.
+    let command = SurroundCommand::Change(Target::Quote('"'), Surrounding::new("<b>", "</b>"));
+    assert_eq!(apply(&mut buf, (1, 3), &command, None), Some((1, 2)));
+    assert_eq!(buf.get_line_content(1), Some("  <b>你好</b>)".to_string()));
+
+    // Delete the function call across lines.
+    let command = SurroundCommand::Delete(Target::Call);
+    assert_eq!(apply(&mut buf, (1, 5), &command, None), Some((0, 0)));
+    assert_eq!(buf.get_line_content(0), Some("".to_string()));
+    assert_eq!(buf.get_line_content(1), Some("  <b>你好</b>".to_string()));
+
+    // Undo as one state.
+    buf.undo();
+    assert_eq!(buf.get_line_content(0), Some("say(".to_string()));
+
+    // Add to the word.
+    let command = SurroundCommand::Add(SurroundMotion::InnerWord, Surrounding::new("[", "]"));
+    assert_eq!(apply(&mut buf, (1, 5), &command, None), Some((1, 5)));
+    assert_eq!(
+      buf.get_line_content(1),
+      Some("  <b>[你好]</b>)".to_string())
+    );
+
+    // Not found.
+    let command = SurroundCommand::Delete(Target::Quote('\''));
+    assert_eq!(apply(&mut buf, (1, 5), &command, None), None);
+
+    // Selection.
+    let mut selection = Selection::new(VisualKind::Charwise, (0, 0));
+    selection.set_cursor((0, 2));
+    let command = SurroundCommand::Add(SurroundMotion::Selection, Surrounding::new("'", "'"));
+    assert_eq!(
+      apply(&mut buf, (0, 2), &command, Some(&selection)),
+      Some((0, 0))
+    );
+    assert_eq!(buf.get_line_content(0), Some("'say'(".to_string()));
+  }
+}
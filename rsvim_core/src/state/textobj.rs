@@ -0,0 +1,436 @@
+//! Text objects, i.e. the `iw`, `a"`, `i(` after an operator (see
+//! [`operator_pending`](crate::state::fsm::operator_pending)) or in visual mode.
+//!
+//! The `i` (inner) objects select the text inside, the `a` (around) objects include the
+//! surrounding blanks, quotes, pairs or tags:
+//!
+//! - `w`/`W`: The word/WORD, `aw` includes the trailing blanks (or the leading blanks if there's
+//!   none), the count selects more words.
+//! - `"`, `'`, `` ` ``: The quoted string in current line, `a"` includes the quotes and the
+//!   trailing blanks.
+//! - `(`/`)`/`b`, `[`/`]`, `{`/`}`/`B`, `<`/`>`: The pair, the count selects the outer pairs. When
+//!   the pair is across lines, `i{` excludes the line ending after `{` and the indent before `}`.
+//! - `t`: The innermost HTML/XML tags.
+//! - `p`: The paragraph, i.e. the consecutive non-blank lines, `ap` includes the trailing blank
+//!   lines. It's linewise.
+//!
+//! The text objects are resolved over the [`Buffer`] by [`select`], thus they're shared by the
+//! operators, visual mode and the plugins.
+//!
+//! See: <https://vimhelp.org/motion.txt.html#text-objects>.
+
+use crate::buf::Buffer;
+use crate::state::motion::last_line_idx;
+use crate::state::surround::{find_target, Target, SEARCH_LINES};
+use crate::state::visual::{Selection, VisualPosition};
+
+use std::ops::Range;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The text object.
+pub enum TextObject {
+  /// `w`, or `W` if `big`.
+  Word { big: bool },
+  /// The quotes.
+  Quote(char),
+  /// The pair.
+  Pair { open: char, close: char },
+  /// The HTML/XML tags.
+  Tag,
+  /// The paragraph.
+  Paragraph,
+}
+
+/// Parse the text object char after `i`/`a`.
+pub fn parse(c: char) -> Option<TextObject> {
+  let object = match c {
+    'w' => TextObject::Word { big: false },
+    'W' => TextObject::Word { big: true },
+    '"' | '\'' | '`' => TextObject::Quote(c),
+    '(' | ')' | 'b' => TextObject::Pair {
+      open: '(',
+      close: ')',
+    },
+    '[' | ']' => TextObject::Pair {
+      open: '[',
+      close: ']',
+    },
+    '{' | '}' | 'B' => TextObject::Pair {
+      open: '{',
+      close: '}',
+    },
+    '<' | '>' => TextObject::Pair {
+      open: '<',
+      close: '>',
+    },
+    't' => TextObject::Tag,
+    'p' => TextObject::Paragraph,
+    _ => return None,
+  };
+  Some(object)
+}
+
+fn is_blank(c: char) -> bool {
+  c == ' ' || c == '\t'
+}
+
+fn is_word_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+// The word class, `0` is blank.
+fn word_class(c: char, big: bool) -> u8 {
+  if is_blank(c) {
+    0
+  } else if big || is_word_char(c) {
+    1
+  } else {
+    2
+  }
+}
+
+// Select `count` runs (the consecutive items of the same class) from the run at `idx` in `0..len`,
+// the class `0` is blank.
+//
+// For the inner objects each run counts. For the around objects each run includes the following
+// blank run (or the preceding one if there's none), and if it starts on blanks, each one is the
+// blank run and the following run.
+fn select_runs<F>(len: usize, idx: usize, class: F, inner: bool, count: usize) -> Range<usize>
+where
+  F: Fn(usize) -> u8,
+{
+  let run_end = |i: usize| {
+    let mut end = i;
+    while end < len && class(end) == class(i) {
+      end += 1;
+    }
+    end
+  };
+  let mut start = idx;
+  while start > 0 && class(start - 1) == class(idx) {
+    start -= 1;
+  }
+  let on_blank = class(idx) == 0;
+  let mut trailing = false;
+  let mut end = start;
+  for _ in 0..count {
+    if end >= len {
+      break;
+    }
+    if inner {
+      end = run_end(end);
+    } else if on_blank {
+      if class(end) == 0 {
+        end = run_end(end);
+      }
+      if end < len && class(end) != 0 {
+        end = run_end(end);
+      }
+    } else {
+      if class(end) != 0 {
+        end = run_end(end);
+      }
+      trailing = end < len && class(end) == 0;
+      if trailing {
+        end = run_end(end);
+      }
+    }
+  }
+  if !inner && !on_blank && !trailing {
+    while start > 0 && class(start - 1) == 0 {
+      start -= 1;
+    }
+  }
+  start..end
+}
+
+// Find the `count`-th pair around the cursor (the cursor can be on the opening/closing char),
+// returns the char index of the opening char and closing char.
+fn find_pair(
+  chars: &[char],
+  cursor: usize,
+  open: char,
+  close: char,
+  count: usize,
+) -> Option<(usize, usize)> {
+  let mut remaining = count.max(1);
+  let mut start = None;
+  if chars.get(cursor) == Some(&open) {
+    remaining -= 1;
+    if remaining == 0 {
+      start = Some(cursor);
+    }
+  }
+  let mut depth = 0_usize;
+  let mut i = std::cmp::min(cursor, chars.len());
+  while start.is_none() && i > 0 {
+    i -= 1;
+    if chars[i] == close {
+      depth += 1;
+    } else if chars[i] == open {
+      if depth == 0 {
+        remaining -= 1;
+        if remaining == 0 {
+          start = Some(i);
+        }
+      } else {
+        depth -= 1;
+      }
+    }
+  }
+  let start = start?;
+  let mut depth = 0_usize;
+  for (i, c) in chars.iter().enumerate().skip(start + 1) {
+    if *c == open {
+      depth += 1;
+    } else if *c == close {
+      if depth == 0 {
+        return Some((start, i));
+      }
+      depth -= 1;
+    }
+  }
+  None
+}
+
+// Extend the `range` with the trailing blanks, or the leading blanks if there's none.
+fn with_blanks(chars: &[char], range: Range<usize>) -> Range<usize> {
+  let mut end = range.end;
+  while end < chars.len() && is_blank(chars[end]) {
+    end += 1;
+  }
+  if end > range.end {
+    return range.start..end;
+  }
+  let mut start = range.start;
+  while start > 0 && is_blank(chars[start - 1]) {
+    start -= 1;
+  }
+  start..range.end
+}
+
+/// Select the text object in the `chars` at the `cursor` index, returns the char index range.
+///
+/// NOTE: The paragraph is linewise, use [`select`] for it.
+pub fn select_chars(
+  chars: &[char],
+  cursor: usize,
+  object: TextObject,
+  inner: bool,
+  count: usize,
+) -> Option<Range<usize>> {
+  let count = count.max(1);
+  let range = match object {
+    TextObject::Word { big } => {
+      // The words are in current line.
+      let cursor = std::cmp::min(cursor, chars.len());
+      let line_start = chars[..cursor]
+        .iter()
+        .rposition(|c| *c == '\n')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+      let line_end = chars[cursor..]
+        .iter()
+        .position(|c| *c == '\n')
+        .map(|i| cursor + i)
+        .unwrap_or(chars.len());
+      if cursor >= line_end {
+        return None;
+      }
+      let line = &chars[line_start..line_end];
+      let range = select_runs(
+        line.len(),
+        cursor - line_start,
+        |i| word_class(line[i], big),
+        inner,
+        count,
+      );
+      line_start + range.start..line_start + range.end
+    }
+    TextObject::Quote(q) => {
+      let found = find_target(chars, cursor, Target::Quote(q))?;
+      if inner {
+        found.open.end..found.close.start
+      } else {
+        with_blanks(chars, found.open.start..found.close.end)
+      }
+    }
+    TextObject::Pair { open, close } => {
+      let (start, end) = find_pair(chars, cursor, open, close, count)?;
+      if inner {
+        let mut inner_start = start + 1;
+        if chars.get(inner_start) == Some(&'\n') {
+          inner_start += 1;
+        }
+        // Exclude the indent before the closing char, but keep the line ending.
+        let mut inner_end = end;
+        while inner_end > inner_start && is_blank(chars[inner_end - 1]) {
+          inner_end -= 1;
+        }
+        if !(inner_end > inner_start && chars[inner_end - 1] == '\n') {
+          inner_end = end;
+        }
+        inner_start..std::cmp::max(inner_start, inner_end)
+      } else {
+        start..end + 1
+      }
+    }
+    TextObject::Tag => {
+      let found = find_target(chars, cursor, Target::Tag)?;
+      if inner {
+        found.open.end..found.close.start
+      } else {
+        found.open.start..found.close.end
+      }
+    }
+    TextObject::Paragraph => return None,
+  };
+  if range.is_empty() {
+    None
+  } else {
+    Some(range)
+  }
+}
+
+/// Select the text object at the `cursor` of the `buffer`, the `count` (`0` is the same with `1`)
+/// selects more words/paragraphs or the outer pairs.
+///
+/// Returns the selection (the anchor is the start, the cursor is the end), or `None` if the object
+/// is not found or empty.
+pub fn select(
+  buffer: &Buffer,
+  cursor: VisualPosition,
+  object: TextObject,
+  inner: bool,
+  count: usize,
+) -> Option<Selection> {
+  if object == TextObject::Paragraph {
+    let len = last_line_idx(buffer) + 1;
+    let line_idx = std::cmp::min(cursor.0, len - 1);
+    let is_blank_line = |l: usize| {
+      buffer
+        .get_line_content(l)
+        .map(|line| line.trim().is_empty())
+        .unwrap_or(true)
+    };
+    let lines = select_runs(
+      len,
+      line_idx,
+      |l| u8::from(!is_blank_line(l)),
+      inner,
+      count.max(1),
+    );
+    return Selection::from_line_range(lines);
+  }
+
+  // The pairs and tags are searched across lines.
+  let start_line_idx = cursor.0.saturating_sub(SEARCH_LINES);
+  let end_line_idx = std::cmp::min(cursor.0 + SEARCH_LINES + 1, buffer.len_lines());
+  let base = buffer.line_to_char(start_line_idx);
+  let mut chars: Vec<char> = vec![];
+  for line_idx in start_line_idx..end_line_idx {
+    if let Some(line) = buffer.get_line(line_idx) {
+      chars.extend(line.chars());
+    }
+  }
+  let cursor_idx = buffer.line_to_char(cursor.0) + cursor.1 - base;
+  let range = select_chars(&chars, cursor_idx, object, inner, count)?;
+  Selection::from_char_range(buffer, base + range.start..base + range.end)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::state::visual::{selected_text, VisualKind};
+  use crate::test::buf::make_buffer_from_lines;
+  use crate::{envar, rlock};
+
+  fn select_str(s: &str, cursor: usize, object: char, inner: bool, count: usize) -> Option<&str> {
+    let chars: Vec<char> = s.chars().collect();
+    let range = select_chars(&chars, cursor, parse(object)?, inner, count)?;
+    let start = s.char_indices().nth(range.start).unwrap().0;
+    let end = s
+      .char_indices()
+      .nth(range.end)
+      .map(|(i, _)| i)
+      .unwrap_or(s.len());
+    Some(&s[start..end])
+  }
+
+  #[test]
+  fn word1() {
+    let s = "foo bar.baz  qux";
+    assert_eq!(select_str(s, 5, 'w', true, 1), Some("bar"));
+    // No trailing blanks, includes the leading blanks.
+    assert_eq!(select_str(s, 5, 'w', false, 1), Some(" bar"));
+    assert_eq!(select_str(s, 5, 'W', true, 1), Some("bar.baz"));
+    assert_eq!(select_str(s, 5, 'W', false, 1), Some("bar.baz  "));
+    assert_eq!(select_str(s, 1, 'w', false, 1), Some("foo "));
+    assert_eq!(select_str(s, 1, 'w', true, 3), Some("foo bar"));
+    assert_eq!(select_str(s, 1, 'w', false, 2), Some("foo bar"));
+    // The blanks.
+    assert_eq!(select_str(s, 12, 'w', true, 1), Some("  "));
+    assert_eq!(select_str(s, 12, 'w', false, 1), Some("  qux"));
+    // The last word includes the leading blanks.
+    assert_eq!(select_str(s, 14, 'w', false, 1), Some("  qux"));
+    assert_eq!(select_str("a\nb c", 2, 'w', false, 1), Some("b "));
+    assert_eq!(select_str("a\n\nb", 2, 'w', true, 1), None);
+  }
+
+  #[test]
+  fn quote_pair1() {
+    let s = "x = f(\"a b\" , (c)) ";
+    assert_eq!(select_str(s, 8, '"', true, 1), Some("a b"));
+    assert_eq!(select_str(s, 8, '"', false, 1), Some("\"a b\" "));
+    assert_eq!(select_str(s, 15, 'b', true, 1), Some("c"));
+    assert_eq!(select_str(s, 15, '(', false, 1), Some("(c)"));
+    assert_eq!(select_str(s, 15, ')', true, 2), Some("\"a b\" , (c)"));
+    // On the opening/closing char.
+    assert_eq!(select_str(s, 5, '(', true, 1), Some("\"a b\" , (c)"));
+    assert_eq!(select_str(s, 17, '(', false, 1), Some("(\"a b\" , (c))"));
+    assert_eq!(select_str(s, 15, '(', false, 3), None);
+    assert_eq!(select_str("f()", 1, '(', true, 1), None);
+    // Across lines, the line ending after `{` and the indent before `}` are excluded.
+    let s = "fn a() {\n  b;\n  c;\n}\n";
+    assert_eq!(select_str(s, 11, '{', true, 1), Some("  b;\n  c;\n"));
+    assert_eq!(select_str(s, 11, 'B', false, 1), Some("{\n  b;\n  c;\n}"));
+    assert_eq!(select_str("<a><b>x</b></a>", 6, 't', true, 1), Some("x"));
+    assert_eq!(
+      select_str("<a><b>x</b></a>", 6, 't', false, 1),
+      Some("<b>x</b>")
+    );
+  }
+
+  #[test]
+  fn select1() {
+    let buffer = make_buffer_from_lines(vec!["a\n", "b\n", "\n", "\n", "c\n", "d (e\n", "f) g\n"]);
+    let buffer = rlock!(buffer);
+    let paragraph = |cursor, inner, count| {
+      select(&buffer, cursor, TextObject::Paragraph, inner, count)
+        .map(|s| (s.kind(), s.line_range()))
+    };
+    assert_eq!(
+      paragraph((0, 0), true, 1),
+      Some((VisualKind::Linewise, 0..2))
+    );
+    assert_eq!(
+      paragraph((1, 0), false, 1),
+      Some((VisualKind::Linewise, 0..4))
+    );
+    assert_eq!(
+      paragraph((2, 0), true, 2),
+      Some((VisualKind::Linewise, 2..7))
+    );
+    // The last paragraph includes the leading blank lines.
+    assert_eq!(
+      paragraph((5, 0), false, 1),
+      Some((VisualKind::Linewise, 2..7))
+    );
+
+    let selection = select(&buffer, (6, 0), parse('(').unwrap(), true, 1).unwrap();
+    assert_eq!(selection.kind(), VisualKind::Charwise);
+    assert_eq!(selection.ordered(), ((5, 3), (6, 0)));
+    assert_eq!(selected_text(&buffer, &selection), "e\nf");
+  }
+}
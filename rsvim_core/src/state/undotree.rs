@@ -0,0 +1,435 @@
+//! Undo tree browser.
+//!
+//! It's an interactive view over the undo tree of a buffer, i.e. the
+//! [undotree](https://github.com/mbbill/undotree) and [mundo](https://github.com/simnalamburt/vim-mundo)
+//! plugins. The undo states are rendered as a graph, the most recent state is on the top and each
+//! branch takes a column:
+//!
+//! ```text
+//! * [4] 2s ago (current)
+//! | * [3] 10s ago
+//! |/
+//! * [2] 1m ago
+//! * [1] 5m ago
+//! * [0] original
+//! ```
+//!
+//! Moving on a state previews the diff between it and its parent state, pressing `ENTER` jumps
+//! the buffer to the selected state.
+//!
+//! NOTE: The browser is data-driven, it only needs the undo states (sequence number, parent,
+//! timestamp and text snapshot) and doesn't modify the buffer itself. The buffer undo history is
+//! responsible for providing the states and restoring the selected one.
+
+use crate::buf::undo::UndoTree;
+use crate::diff::unified_lines;
+
+use ropey::Rope;
+use std::cmp::Reverse;
+use std::time::{Duration, SystemTime};
+
+/// The file type of the undo tree buffer, see [`undotree`](crate::state::ex::undotree).
+pub const FILE_TYPE: &str = "undotree";
+
+#[derive(Debug, Clone)]
+/// An undo state, i.e. a node in the undo tree.
+pub struct UndoTreeEntry {
+  /// The sequence number, the original text is `0`.
+  pub seq: usize,
+  /// The parent sequence number, the original text has no parent.
+  pub parent: Option<usize>,
+  /// The moment when the state is created.
+  pub time: SystemTime,
+  /// The text snapshot of the state.
+  ///
+  /// NOTE: Cloning a rope is cheap, the unchanged chunks are shared between snapshots.
+  pub text: Rope,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A rendered line in the browser.
+pub struct UndoTreeLine {
+  /// The undo state on this line, the branch-merging lines have no state.
+  pub seq: Option<usize>,
+  /// The rendered text.
+  pub text: String,
+}
+
+/// Format the elapsed duration, i.e. `5s ago`, `3m ago`.
+pub fn format_elapsed(elapsed: Duration) -> String {
+  let secs = elapsed.as_secs();
+  if secs < 60 {
+    format!("{}s ago", secs)
+  } else if secs < 60 * 60 {
+    format!("{}m ago", secs / 60)
+  } else if secs < 24 * 60 * 60 {
+    format!("{}h ago", secs / (60 * 60))
+  } else {
+    format!("{}d ago", secs / (24 * 60 * 60))
+  }
+}
+
+/// Layout the undo tree as a graph.
+///
+/// The entries are rendered in the descending order of the sequence numbers, a child state always
+/// has a larger sequence number than its parent, thus the parent is rendered after all its
+/// children, and the children branches are merged into the parent's column.
+pub fn layout(entries: &[UndoTreeEntry], current: usize, now: SystemTime) -> Vec<UndoTreeLine> {
+  let mut sorted = entries.iter().collect::<Vec<_>>();
+  sorted.sort_by_key(|entry| Reverse(entry.seq));
+
+  // Each column is waiting for a state, i.e. the parent of the last rendered state in it.
+  let mut columns: Vec<usize> = vec![];
+  let mut lines = vec![];
+
+  for entry in sorted {
+    let matched = columns
+      .iter()
+      .enumerate()
+      .filter(|(_, expected)| **expected == entry.seq)
+      .map(|(i, _)| i)
+      .collect::<Vec<_>>();
+    let col = match matched.first() {
+      Some(col) => *col,
+      None => {
+        // A new branch head.
+        columns.push(entry.seq);
+        columns.len() - 1
+      }
+    };
+
+    // Merge the other branches waiting for this state, from right to left.
+    for j in matched.iter().skip(1).rev() {
+      let mut text = String::new();
+      for i in 0..columns.len() {
+        if i < *j {
+          if i > 0 {
+            text.push(' ');
+          }
+          text.push('|');
+        } else {
+          text.push('/');
+          if i + 1 < columns.len() {
+            text.push(' ');
+          }
+        }
+      }
+      lines.push(UndoTreeLine {
+        seq: None,
+        text: text.trim_end().to_string(),
+      });
+      columns.remove(*j);
+    }
+
+    let graph = (0..columns.len())
+      .map(|i| if i == col { "*" } else { "|" })
+      .collect::<Vec<_>>()
+      .join(" ");
+    let time = if entry.parent.is_none() {
+      "original".to_string()
+    } else {
+      format_elapsed(now.duration_since(entry.time).unwrap_or_default())
+    };
+    let mut text = format!("{} [{}] {}", graph, entry.seq, time);
+    if entry.seq == current {
+      text.push_str(" (current)");
+    }
+    lines.push(UndoTreeLine {
+      seq: Some(entry.seq),
+      text,
+    });
+
+    match entry.parent {
+      Some(parent) => columns[col] = parent,
+      None => {
+        columns.remove(col);
+      }
+    }
+  }
+
+  lines
+}
+
+/// Collect the undo states of the buffer undo tree, `text` is the buffer text at current state.
+///
+/// The text snapshots are rebuilt from the deltas: The states on the path to current state are
+/// reverted from `text`, then the other states are applied from their parents. The evicted states
+/// are skipped.
+pub fn entries(undo_tree: &UndoTree, text: &Rope) -> Vec<UndoTreeEntry> {
+  let states = undo_tree.states();
+  let mut texts: Vec<Option<Rope>> = vec![None; states.len()];
+  texts[undo_tree.current()] = Some(text.clone());
+
+  // Revert to the root.
+  let mut seq = undo_tree.current();
+  while seq != undo_tree.root() {
+    let parent = match states[seq].parent {
+      Some(parent) => parent,
+      None => break,
+    };
+    let mut rope = texts[seq].clone().unwrap();
+    for delta in states[seq].deltas.iter().rev() {
+      delta.revert(&mut rope);
+    }
+    texts[parent] = Some(rope);
+    seq = parent;
+  }
+
+  // NOTE: A child state always has a larger sequence number than its parent, thus the parent
+  // snapshot is already built.
+  for state in states.iter().skip(undo_tree.root() + 1) {
+    if texts[state.seq].is_some() || undo_tree.is_evicted(state.seq) {
+      continue;
+    }
+    let mut rope = match state.parent.and_then(|parent| texts[parent].clone()) {
+      Some(rope) => rope,
+      None => continue,
+    };
+    for delta in state.deltas.iter() {
+      delta.apply(&mut rope);
+    }
+    texts[state.seq] = Some(rope);
+  }
+
+  states
+    .iter()
+    .zip(texts)
+    .filter_map(|(state, text)| {
+      text.map(|text| UndoTreeEntry {
+        seq: state.seq,
+        parent: if state.seq == undo_tree.root() {
+          None
+        } else {
+          state.parent
+        },
+        time: state.time,
+        text,
+      })
+    })
+    .collect()
+}
+
+#[derive(Debug, Clone)]
+/// The undo tree browser.
+pub struct UndoTreeBrowser {
+  entries: Vec<UndoTreeEntry>,
+  current: usize,
+  lines: Vec<UndoTreeLine>,
+  // Index of the selected line, it's always on a line with undo state.
+  selected: usize,
+}
+
+impl UndoTreeBrowser {
+  /// Make new browser with the undo states, and the current state of the buffer.
+  pub fn new(entries: Vec<UndoTreeEntry>, current: usize, now: SystemTime) -> Self {
+    let lines = layout(&entries, current, now);
+    let selected = lines
+      .iter()
+      .position(|line| line.seq == Some(current))
+      .unwrap_or(0);
+    UndoTreeBrowser {
+      entries,
+      current,
+      lines,
+      selected,
+    }
+  }
+
+  /// Get the rendered lines.
+  pub fn lines(&self) -> &Vec<UndoTreeLine> {
+    &self.lines
+  }
+
+  /// Get the current state of the buffer.
+  pub fn current(&self) -> usize {
+    self.current
+  }
+
+  /// Get the selected line index.
+  pub fn selected(&self) -> usize {
+    self.selected
+  }
+
+  /// Get the selected undo state.
+  pub fn selected_seq(&self) -> Option<usize> {
+    self.lines.get(self.selected).and_then(|line| line.seq)
+  }
+
+  /// Select the next (older) state, the branch-merging lines are skipped.
+  pub fn select_next(&mut self) {
+    if let Some(i) = (self.selected + 1..self.lines.len()).find(|i| self.lines[*i].seq.is_some()) {
+      self.selected = i;
+    }
+  }
+
+  /// Select the previous (newer) state, the branch-merging lines are skipped.
+  pub fn select_prev(&mut self) {
+    if let Some(i) = (0..self.selected)
+      .rev()
+      .find(|i| self.lines[*i].seq.is_some())
+    {
+      self.selected = i;
+    }
+  }
+
+  fn entry(&self, seq: usize) -> Option<&UndoTreeEntry> {
+    self.entries.iter().find(|e| e.seq == seq)
+  }
+
+  /// Preview the diff between the selected state and its parent state.
+  pub fn preview(&self) -> Vec<String> {
+    let entry = match self.selected_seq().and_then(|seq| self.entry(seq)) {
+      Some(entry) => entry,
+      None => return vec![],
+    };
+    let old = entry
+      .parent
+      .and_then(|parent| self.entry(parent))
+      .map(|parent| parent.text.to_string())
+      .unwrap_or_default();
+    unified_lines(&old, &entry.text.to_string())
+  }
+
+  /// Accept the selected state, i.e. the `ENTER` key.
+  ///
+  /// Returns the text snapshot to restore, or `None` if it's already the current state.
+  pub fn accept(&mut self, now: SystemTime) -> Option<(usize, Rope)> {
+    let seq = self.selected_seq()?;
+    if seq == self.current {
+      return None;
+    }
+    let text = self.entry(seq)?.text.clone();
+    self.current = seq;
+    self.lines = layout(&self.entries, self.current, now);
+    Some((seq, text))
+  }
+
+  /// Render the browser as the text of the undo tree buffer: The graph lines, an empty line, and
+  /// the preview of the selected state. It's ended with a line break.
+  ///
+  /// NOTE: The graph is on the top, thus the line index in the buffer is the index of
+  /// [`lines`](Self::lines).
+  pub fn text(&self) -> String {
+    let mut text = String::new();
+    for line in self.lines.iter() {
+      text.push_str(&line.text);
+      text.push('\n');
+    }
+    text.push('\n');
+    for line in self.preview() {
+      text.push_str(&line);
+      text.push('\n');
+    }
+    text
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::buf::undo::Delta;
+
+  // NOTE: The `now` is 600s later than `base`, thus all the entries can be created before it.
+  fn entries(base: SystemTime) -> Vec<UndoTreeEntry> {
+    let make = |seq: usize, parent: Option<usize>, secs: u64, text: &str| UndoTreeEntry {
+      seq,
+      parent,
+      time: base + Duration::from_secs(600 - secs),
+      text: Rope::from_str(text),
+    };
+    vec![
+      make(0, None, 600, "a\n"),
+      make(1, Some(0), 300, "a\nb\n"),
+      make(2, Some(1), 60, "a\nb\nc\n"),
+      make(3, Some(2), 10, "a\nB\nc\n"),
+      make(4, Some(2), 2, "a\nb\nc\nd\n"),
+    ]
+  }
+
+  #[test]
+  fn format_elapsed1() {
+    assert_eq!(format_elapsed(Duration::from_secs(5)), "5s ago");
+    assert_eq!(format_elapsed(Duration::from_secs(130)), "2m ago");
+    assert_eq!(format_elapsed(Duration::from_secs(7200)), "2h ago");
+    assert_eq!(format_elapsed(Duration::from_secs(172800)), "2d ago");
+  }
+
+  #[test]
+  fn layout1() {
+    let base = SystemTime::now();
+    let now = base + Duration::from_secs(600);
+    let actual = layout(&entries(base), 4, now)
+      .into_iter()
+      .map(|line| line.text)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      actual,
+      vec![
+        "* [4] 2s ago (current)",
+        "| * [3] 10s ago",
+        "|/",
+        "* [2] 1m ago",
+        "* [1] 5m ago",
+        "* [0] original",
+      ]
+    );
+  }
+
+  #[test]
+  fn entries1() {
+    let mut rope = Rope::from_str("a\n");
+    let mut undo_tree = UndoTree::new();
+    let edit = |undo_tree: &mut UndoTree, rope: &mut Rope, delta: Delta| {
+      delta.apply(rope);
+      undo_tree.record(delta);
+    };
+    edit(&mut undo_tree, &mut rope, Delta::new(2, "", "b\n"));
+    edit(&mut undo_tree, &mut rope, Delta::new(4, "", "c\n"));
+    undo_tree.undo(&mut rope);
+    edit(&mut undo_tree, &mut rope, Delta::new(0, "a", "A"));
+    assert_eq!(rope.to_string(), "A\nb\n");
+
+    let actual = super::entries(&undo_tree, &rope)
+      .into_iter()
+      .map(|entry| (entry.seq, entry.parent, entry.text.to_string()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      actual,
+      vec![
+        (0, None, "a\n".to_string()),
+        (1, Some(0), "a\nb\n".to_string()),
+        (2, Some(1), "a\nb\nc\n".to_string()),
+        (3, Some(1), "A\nb\n".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn browser1() {
+    let base = SystemTime::now();
+    let now = base + Duration::from_secs(600);
+    let mut browser = UndoTreeBrowser::new(entries(base), 4, now);
+    assert_eq!(browser.selected_seq(), Some(4));
+    assert_eq!(browser.preview(), vec![" a", " b", " c", "+d"]);
+    assert!(browser.accept(now).is_none());
+
+    browser.select_next();
+    assert_eq!(browser.selected_seq(), Some(3));
+    // Skip the branch-merging line.
+    browser.select_next();
+    assert_eq!(browser.selected_seq(), Some(2));
+    browser.select_prev();
+    assert_eq!(browser.selected_seq(), Some(3));
+    assert_eq!(browser.preview(), vec![" a", "-b", "+B", " c"]);
+
+    let (seq, text) = browser.accept(now).unwrap();
+    assert_eq!(seq, 3);
+    assert_eq!(text.to_string(), "a\nB\nc\n");
+    assert_eq!(browser.current(), 3);
+    assert_eq!(browser.lines()[1].text, "| * [3] 10s ago (current)");
+    assert_eq!(
+      browser.text(),
+      "* [4] 2s ago\n| * [3] 10s ago (current)\n|/\n* [2] 1m ago\n* [1] 5m ago\n* [0] original\n\n a\n-b\n+B\n c\n"
+    );
+  }
+}
@@ -0,0 +1,319 @@
+//! Visual mode selection.
+//!
+//! The selection is the region between the anchor (where visual mode started) and the cursor, both
+//! are inclusive. There're 3 kinds of selection:
+//!
+//! - Charwise (`v`): From the first position to the last position, crossing lines.
+//! - Linewise (`V`): All the lines between the anchor and cursor.
+//! - Blockwise (`CTRL-V`): The rectangle between the anchor and cursor.
+//!
+//! The selection is stored per window in the [`Viewport`](crate::ui::widget::window::Viewport),
+//! and the operators (`d`, `y`, `c`) are applied with [`selected_text`] and [`delete_selection`].
+//!
+//! NOTE: The blockwise columns are char indexes, i.e. the wide chars (CJK, tab) are not aligned by
+//! the display width yet.
+
+use crate::buf::Buffer;
+
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The kind of selection.
+pub enum VisualKind {
+  Charwise,
+  Linewise,
+  Blockwise,
+}
+
+/// The position in buffer, i.e. `(line_idx, char_idx)`.
+pub type VisualPosition = (usize, usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The visual selection.
+pub struct Selection {
+  kind: VisualKind,
+  anchor: VisualPosition,
+  cursor: VisualPosition,
+}
+
+impl Selection {
+  /// Start selection at the cursor, i.e. the anchor is the cursor.
+  pub fn new(kind: VisualKind, cursor: VisualPosition) -> Self {
+    Selection {
+      kind,
+      anchor: cursor,
+      cursor,
+    }
+  }
+
+  pub fn kind(&self) -> VisualKind {
+    self.kind
+  }
+
+  pub fn set_kind(&mut self, kind: VisualKind) {
+    self.kind = kind;
+  }
+
+  pub fn anchor(&self) -> VisualPosition {
+    self.anchor
+  }
+
+  pub fn cursor(&self) -> VisualPosition {
+    self.cursor
+  }
+
+  pub fn set_cursor(&mut self, cursor: VisualPosition) {
+    self.cursor = cursor;
+  }
+
+  /// Swap the anchor and cursor, i.e. the `o` key.
+  pub fn swap(&mut self) {
+    std::mem::swap(&mut self.anchor, &mut self.cursor);
+  }
+
+  /// The charwise selection of the absolute char index `range` (end exclusive) in the `buffer`,
+  /// returns `None` if it's empty.
+  pub fn from_char_range(buffer: &Buffer, range: Range<usize>) -> Option<Self> {
+    if range.is_empty() {
+      return None;
+    }
+    let position = |char_idx: usize| {
+      let line_idx = buffer.char_to_line(char_idx);
+      (line_idx, char_idx - buffer.line_to_char(line_idx))
+    };
+    let mut selection = Selection::new(VisualKind::Charwise, position(range.start));
+    selection.set_cursor(position(range.end - 1));
+    Some(selection)
+  }
+
+  /// The linewise selection of the `lines` (end exclusive), returns `None` if it's empty.
+  pub fn from_line_range(lines: Range<usize>) -> Option<Self> {
+    if lines.is_empty() {
+      return None;
+    }
+    let mut selection = Selection::new(VisualKind::Linewise, (lines.start, 0));
+    selection.set_cursor((lines.end - 1, 0));
+    Some(selection)
+  }
+
+  /// The first and last position.
+  pub fn ordered(&self) -> (VisualPosition, VisualPosition) {
+    (min(self.anchor, self.cursor), max(self.anchor, self.cursor))
+  }
+
+  /// The selected lines.
+  pub fn line_range(&self) -> Range<usize> {
+    let (start, end) = self.ordered();
+    start.0..end.0 + 1
+  }
+
+  /// The selected char index range in the line, the `line_len` is the chars count of the line
+  /// (without line ending).
+  ///
+  /// Returns `None` if the line is not selected.
+  pub fn char_range(&self, line_idx: usize, line_len: usize) -> Option<Range<usize>> {
+    let (start, end) = self.ordered();
+    if line_idx < start.0 || line_idx > end.0 {
+      return None;
+    }
+    let range = match self.kind {
+      VisualKind::Charwise => {
+        let start_char_idx = if line_idx == start.0 { start.1 } else { 0 };
+        let end_char_idx = if line_idx == end.0 {
+          end.1 + 1
+        } else {
+          // The line ending is selected as well.
+          line_len + 1
+        };
+        start_char_idx..end_char_idx
+      }
+      VisualKind::Linewise => 0..line_len + 1,
+      VisualKind::Blockwise => {
+        let start_char_idx = min(self.anchor.1, self.cursor.1);
+        let end_char_idx = max(self.anchor.1, self.cursor.1) + 1;
+        min(start_char_idx, line_len)..min(end_char_idx, line_len)
+      }
+    };
+    Some(range)
+  }
+
+  /// The selected char index ranges in the lines `line_range`, maps from buffer line index to char
+  /// index range, for rendering.
+  pub fn char_ranges(
+    &self,
+    buffer: &Buffer,
+    line_range: Range<usize>,
+  ) -> BTreeMap<usize, Range<usize>> {
+    let selected = self.line_range();
+    let line_range = max(line_range.start, selected.start)..min(line_range.end, selected.end);
+    line_range
+      .filter_map(|line_idx| {
+        let line_len = buffer.get_line_content(line_idx)?.chars().count();
+        self
+          .char_range(line_idx, line_len)
+          .map(|range| (line_idx, range))
+      })
+      .collect()
+  }
+}
+
+/// The absolute char index range in buffer for the selected lines.
+fn line_char_range(buffer: &Buffer, line_idx: usize, range: &Range<usize>) -> Range<usize> {
+  let start_char_idx = buffer.line_to_char(line_idx);
+  let line_len_chars = buffer
+    .get_line(line_idx)
+    .map(|l| l.len_chars())
+    .unwrap_or(0);
+  start_char_idx + min(range.start, line_len_chars)..start_char_idx + min(range.end, line_len_chars)
+}
+
+/// Get the selected text.
+///
+/// For linewise selection the text always ends with a line ending, for blockwise selection each
+/// line of the block ends with `\n`.
+pub fn selected_text(buffer: &Buffer, selection: &Selection) -> String {
+  let mut text = String::new();
+  for line_idx in selection.line_range() {
+    let line = match buffer.get_line(line_idx) {
+      Some(line) => line,
+      None => break,
+    };
+    let line_len = buffer.get_line_content(line_idx).unwrap().chars().count();
+    let range = selection.char_range(line_idx, line_len).unwrap();
+    let range = min(range.start, line.len_chars())..min(range.end, line.len_chars());
+    text.push_str(&line.slice(range).to_string());
+    if selection.kind() == VisualKind::Blockwise {
+      text.push('\n');
+    }
+  }
+  if selection.kind() == VisualKind::Linewise && !text.ends_with('\n') {
+    text.push('\n');
+  }
+  text
+}
+
+/// Delete the selected text.
+///
+/// Returns the deleted text, same with [`selected_text`].
+pub fn delete_selection(buffer: &mut Buffer, selection: &Selection) -> String {
+  let text = selected_text(buffer, selection);
+  let line_range = selection.line_range();
+  match selection.kind() {
+    VisualKind::Charwise | VisualKind::Linewise => {
+      let (start, end) = selection.ordered();
+      let start_line_len = buffer
+        .get_line_content(start.0)
+        .map(|l| l.chars().count())
+        .unwrap_or(0);
+      let end_line_len = buffer
+        .get_line_content(end.0)
+        .map(|l| l.chars().count())
+        .unwrap_or(0);
+      let start_range = selection.char_range(start.0, start_line_len).unwrap();
+      let end_range = selection.char_range(end.0, end_line_len).unwrap();
+      let start_char_idx = line_char_range(buffer, start.0, &start_range).start;
+      let end_char_idx = line_char_range(buffer, end.0, &end_range).end;
+      buffer.remove(start_char_idx..end_char_idx);
+    }
+    VisualKind::Blockwise => {
+      // Remove from the last line, thus the char indexes of previous lines are not changed.
+      for line_idx in line_range.rev() {
+        let line_len = match buffer.get_line_content(line_idx) {
+          Some(line) => line.chars().count(),
+          None => continue,
+        };
+        let range = selection.char_range(line_idx, line_len).unwrap();
+        let range = line_char_range(buffer, line_idx, &range);
+        buffer.remove(range);
+      }
+    }
+  }
+  text
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crate::test::buf::make_buffer_from_lines;
+  use crate::{envar, rlock, wlock};
+
+  fn lines(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.len_lines())
+      .map(|i| buffer.get_line_content(i).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn char_range1() {
+    let mut selection = Selection::new(VisualKind::Charwise, (2, 3));
+    selection.set_cursor((0, 1));
+    assert_eq!(selection.ordered(), ((0, 1), (2, 3)));
+    assert_eq!(selection.line_range(), 0..3);
+    assert_eq!(selection.char_range(0, 5), Some(1..6));
+    assert_eq!(selection.char_range(1, 5), Some(0..6));
+    assert_eq!(selection.char_range(2, 5), Some(0..4));
+    assert_eq!(selection.char_range(3, 5), None);
+
+    selection.set_kind(VisualKind::Linewise);
+    assert_eq!(selection.char_range(2, 5), Some(0..6));
+
+    selection.set_kind(VisualKind::Blockwise);
+    assert_eq!(selection.char_range(0, 5), Some(1..4));
+    assert_eq!(selection.char_range(1, 2), Some(1..2));
+    assert_eq!(selection.char_range(2, 0), Some(0..0));
+
+    selection.swap();
+    assert_eq!(selection.anchor(), (0, 1));
+    assert_eq!(selection.cursor(), (2, 3));
+  }
+
+  #[test]
+  fn selected_text1() {
+    let buffer = make_buffer_from_lines(vec!["hello\n", "world\n", "foo bar\n"]);
+    let buffer = rlock!(buffer);
+    let mut selection = Selection::new(VisualKind::Charwise, (0, 3));
+    selection.set_cursor((1, 1));
+    assert_eq!(selected_text(&buffer, &selection), "lo\nwo");
+    selection.set_kind(VisualKind::Linewise);
+    assert_eq!(selected_text(&buffer, &selection), "hello\nworld\n");
+    selection.set_kind(VisualKind::Blockwise);
+    assert_eq!(selected_text(&buffer, &selection), "ell\norl\n");
+    assert_eq!(
+      selection.char_ranges(&buffer, 1..3),
+      BTreeMap::from([(1, 1..4)])
+    );
+  }
+
+  #[test]
+  fn delete_selection1() {
+    let buffer = make_buffer_from_lines(vec!["hello\n", "world\n", "foo bar\n"]);
+    let mut buffer = wlock!(buffer);
+    let mut selection = Selection::new(VisualKind::Charwise, (0, 3));
+    selection.set_cursor((1, 1));
+    assert_eq!(delete_selection(&mut buffer, &selection), "lo\nwo");
+    assert_eq!(lines(&buffer), vec!["helrld", "foo bar", ""]);
+  }
+
+  #[test]
+  fn delete_selection2() {
+    let buffer = make_buffer_from_lines(vec!["hello\n", "world\n", "foo bar\n"]);
+    let mut buffer = wlock!(buffer);
+    let mut selection = Selection::new(VisualKind::Linewise, (1, 3));
+    selection.set_cursor((0, 0));
+    assert_eq!(delete_selection(&mut buffer, &selection), "hello\nworld\n");
+    assert_eq!(lines(&buffer), vec!["foo bar", ""]);
+  }
+
+  #[test]
+  fn delete_selection3() {
+    let buffer = make_buffer_from_lines(vec!["hello\n", "wo\n", "foo bar\n"]);
+    let mut buffer = wlock!(buffer);
+    let mut selection = Selection::new(VisualKind::Blockwise, (0, 1));
+    selection.set_cursor((2, 3));
+    assert_eq!(delete_selection(&mut buffer, &selection), "ell\no\noo \n");
+    assert_eq!(lines(&buffer), vec!["ho", "w", "fbar", ""]);
+  }
+}
@@ -1,5 +1,7 @@
 //! User interface.
 
 pub mod canvas;
+pub mod highlight;
+pub mod termcap;
 pub mod tree;
 pub mod widget;
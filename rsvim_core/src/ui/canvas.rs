@@ -1,6 +1,8 @@
 //! Canvas.
 
+use crate::buf::highlight::HighlightStyle;
 use crate::cart::{U16Pos, U16Size};
+use crate::ui::highlight::HighlightGroups;
 
 // Re-export
 pub use crate::ui::canvas::frame::cell::Cell;
@@ -9,7 +11,6 @@ pub use crate::ui::canvas::frame::cursor::{
 };
 pub use crate::ui::canvas::frame::Frame;
 
-use compact_str::ToCompactString;
 use crossterm;
 use geo::point;
 use parking_lot::RwLock;
@@ -29,10 +30,22 @@ pub mod internal;
 /// diff-algorithm to compare the TUI changes, thus only flushing the changed parts to reduce IO
 /// operations.
 ///
+/// The frame tracks the damaged area (see [`Frame::dirty_cols`]) when the widgets draw on it, i.e.
+/// a cursor-only move damages nothing and a single-line edit damages only one row. The diff only
+/// scans the damaged area, and the previous frame only copies the damaged area.
+///
 /// NOTE: APIs named without `prev_` are current frame, with `prev_` are for previous frame.
 pub struct Canvas {
   frame: Frame,
   prev_frame: Frame,
+
+  // Whether the previous frame is invalidated, i.e. the terminal needs to be cleared and fully
+  // repainted.
+  invalidated: bool,
+
+  // The highlight groups, the widgets render through them, see
+  // [`highlight`](crate::ui::highlight).
+  highlights: Arc<HighlightGroups>,
 }
 
 pub type CanvasArc = Arc<RwLock<Canvas>>;
@@ -43,6 +56,8 @@ impl Canvas {
     Canvas {
       prev_frame: Frame::new(size, Cursor::default()),
       frame: Frame::new(size, Cursor::default()),
+      invalidated: false,
+      highlights: Arc::new(HighlightGroups::default()),
     }
   }
 
@@ -105,17 +120,70 @@ impl Canvas {
     self.prev_frame.cursor()
   }
 
+  /// Invalidate previous frame, i.e. the terminal is scribbled by external programs. The next
+  /// shade clears the terminal and re-renders all the cells.
+  pub fn invalidate(&mut self) {
+    self.prev_frame = Frame::new(self.prev_size(), *self.prev_cursor());
+    self.invalidated = true;
+  }
+
+  /// Whether the previous frame is invalidated.
+  pub fn invalidated(&self) -> bool {
+    self.invalidated
+  }
+
   // Previous frame }
 
+  // Highlights {
+
+  pub fn highlights(&self) -> &HighlightGroups {
+    &self.highlights
+  }
+
+  /// Set the highlight groups, i.e. the widget tree shares its highlight groups before drawing.
+  pub fn set_highlights(&mut self, highlights: Arc<HighlightGroups>) {
+    self.highlights = highlights;
+  }
+
+  /// Get the style of the highlight group, it's layered on top of the `Normal` group, see
+  /// [`HighlightGroups::style`].
+  pub fn highlight(&self, name: &str) -> HighlightStyle {
+    self.highlights.style(name)
+  }
+
+  /// Get the style of the highlight group that's layered on top of other styles, see
+  /// [`HighlightGroups::resolve`].
+  pub fn highlight_layer(&self, name: &str) -> HighlightStyle {
+    self.highlights.resolve(name)
+  }
+
+  // Highlights }
+
   /// Get the shader commands that should print to the terminal device, it internally uses a
   /// diff-algorithm to reduce the outputs.
   pub fn shade(&mut self) -> Shader {
     let mut shader = Shader::new();
 
+    // For invalidated frame, clear the terminal first.
+    if self.invalidated {
+      shader.push(ShaderCommand::TerminalClear(crossterm::terminal::Clear(
+        crossterm::terminal::ClearType::All,
+      )));
+    }
+
     // For cells, it needs extra save and restore cursor position
     let mut cells_shaders = self._shade_cells();
     let saved_cursor_pos = self.cursor().pos();
-    shader.append(&mut cells_shaders);
+    if !cells_shaders.is_empty() {
+      // Avoid flickering, the terminal renders the changed cells at once.
+      shader.push(ShaderCommand::TerminalBeginSynchronizedUpdate(
+        crossterm::terminal::BeginSynchronizedUpdate,
+      ));
+      shader.append(&mut cells_shaders);
+      shader.push(ShaderCommand::TerminalEndSynchronizedUpdate(
+        crossterm::terminal::EndSynchronizedUpdate,
+      ));
+    }
     shader.push(ShaderCommand::CursorMoveTo(crossterm::cursor::MoveTo(
       saved_cursor_pos.x(),
       saved_cursor_pos.y(),
@@ -133,10 +201,16 @@ impl Canvas {
 
   /// Shade done.
   pub fn _shade_done(&mut self) {
-    // Save current frame.
-    self.prev_frame = self.frame.clone();
+    // Save current frame, the previous frame's grid is reused instead of re-allocating. When the
+    // size is not changed, only the damaged area is copied.
+    if self.size() == self.prev_size() && !self.invalidated {
+      self.prev_frame.copy_damaged_from(&self.frame);
+    } else {
+      self.prev_frame.copy_from(&self.frame);
+    }
     // Reset the `dirty` fields.
     self.frame.reset_dirty_rows();
+    self.invalidated = false;
   }
 
   /// Shade cursor and append results into shader vector.
@@ -181,11 +255,12 @@ impl Canvas {
 
   /// Shade cells and append results into shader vector.
   pub fn _shade_cells(&mut self) -> Vec<ShaderCommand> {
-    if self.size() == self.prev_size() {
+    if self.size() == self.prev_size() && !self.invalidated {
       // When terminal size remains the same, use dirty-marks diff-algorithm.
       self._dirty_marks_diff()
     } else {
-      // When terminal size remains the same, use brute-force diff-algorithm.
+      // When terminal size is changed or previous frame is invalidated, use brute-force
+      // diff-algorithm.
       self._brute_force_diff()
     }
   }
@@ -222,22 +297,19 @@ impl Canvas {
       point!(x: start_col, y: row),
       end_col as usize - start_col as usize,
     );
-    let new_contents = new_cells
-      .iter()
-      .map(|c| {
-        if c.symbol().is_empty() {
-          " ".to_compact_string()
-        } else {
-          c.symbol().clone()
-        }
-      })
-      .collect::<Vec<_>>()
-      .join("");
+    let mut new_contents = String::with_capacity(new_cells.len());
+    for c in new_cells.iter() {
+      if c.symbol().is_empty() {
+        new_contents.push(' ');
+      } else {
+        new_contents.push_str(c.symbol());
+      }
+    }
     shaders.push(ShaderCommand::CursorMoveTo(crossterm::cursor::MoveTo(
       start_col, row,
     )));
     shaders.push(ShaderCommand::StylePrintString(crossterm::style::Print(
-      new_contents.to_string(),
+      new_contents,
     )));
     shaders
   }
@@ -285,7 +357,7 @@ impl Canvas {
   }
 
   /// Dirty marks diff-algorithm, it only iterates on the area that has been marked as dirty by UI
-  /// widgets.
+  /// widgets, i.e. the damaged columns of the dirty rows.
   ///
   /// This algorithm is more performant when the whole terminal size remains unchanged.
   pub fn _dirty_marks_diff(&mut self) -> Vec<ShaderCommand> {
@@ -298,10 +370,10 @@ impl Canvas {
     let mut shaders = vec![];
 
     if !frame.zero_sized() {
-      for (row, dirty) in frame.dirty_rows().iter().enumerate() {
-        if row < size.height() as usize && *dirty {
-          let mut col = 0_u16;
-          while col < size.width() {
+      for (row, dirty_cols) in frame.dirty_cols().iter().enumerate() {
+        if let Some((start_col, end_col)) = dirty_cols.filter(|_| row < size.height() as usize) {
+          let mut col = start_col;
+          while col < end_col.min(size.width()) {
             // Skip unchanged columns
             let pos: U16Pos = point!(x: col, y: row as u16);
             let cell = frame.get_cell(pos);
@@ -544,11 +616,16 @@ impl Shader {
   pub fn iter(&self) -> Iter<ShaderCommand> {
     self.commands.iter()
   }
+
+  /// Whether there's no commands, i.e. nothing changed since last draw.
+  pub fn is_empty(&self) -> bool {
+    self.commands.is_empty()
+  }
 }
 
 #[cfg(test)]
 mod tests {
-  use compact_str::CompactString;
+  use compact_str::{CompactString, ToCompactString};
   use std::sync::Once;
   use tracing::info;
 
@@ -904,4 +981,94 @@ mod tests {
       assert_eq!(*contents, "ABCD".to_string());
     }
   }
+
+  #[test]
+  fn shade_reuse_grid1() {
+    let terminal_size = U16Size::new(4, 2);
+    let mut canvas = Canvas::new(terminal_size);
+    let prev_cells_ptr = canvas.prev_cells().as_ptr();
+    canvas
+      .frame_mut()
+      .set_cells_at(point!(x: 1, y: 1), vec![Cell::from('a'), Cell::from('b')]);
+    let shader = canvas.shade();
+    assert!(shader.iter().any(|command| matches!(
+      command,
+      ShaderCommand::StylePrintString(crossterm::style::Print(s)) if s == "ab"
+    )));
+    assert_eq!(canvas.prev_cells().as_ptr(), prev_cells_ptr);
+    assert_eq!(canvas.prev_cells(), canvas.cells());
+    assert!(canvas.frame().dirty_rows().iter().all(|dirty| !dirty));
+  }
+
+  #[test]
+  fn shade_damage1() {
+    let terminal_size = U16Size::new(6, 3);
+    let mut canvas = Canvas::new(terminal_size);
+    canvas.frame_mut().set_cells_at(
+      point!(x: 0, y: 1),
+      "abcdef".chars().map(Cell::from).collect(),
+    );
+    let _ = canvas.shade();
+
+    // Re-draw the row with a single changed cell, only the cell is printed.
+    canvas.frame_mut().set_cells_at(
+      point!(x: 0, y: 1),
+      "abXdef".chars().map(Cell::from).collect(),
+    );
+    let shader = canvas.shade();
+    let printed = shader
+      .iter()
+      .filter_map(|command| match command {
+        ShaderCommand::StylePrintString(crossterm::style::Print(s)) => Some(s.clone()),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(printed, vec!["X"]);
+    assert_eq!(canvas.prev_cells(), canvas.cells());
+
+    // Cursor-only move prints nothing.
+    canvas.frame_mut().set_cells_at(
+      point!(x: 0, y: 1),
+      "abXdef".chars().map(Cell::from).collect(),
+    );
+    let shader = canvas.shade();
+    assert!(!shader.iter().any(|command| matches!(
+      command,
+      ShaderCommand::StylePrintString(_) | ShaderCommand::TerminalBeginSynchronizedUpdate(_)
+    )));
+  }
+
+  #[test]
+  fn invalidate1() {
+    INIT.call_once(test_log_init);
+    let mut can = Canvas::new(U16Size::new(10, 10));
+    can.frame_mut().set_cells_at(
+      point!(x:2,y:3),
+      (0..4)
+        .map(|i| Cell::with_char(int2letter(i)))
+        .collect::<Vec<_>>(),
+    );
+    let _ = can.shade();
+
+    // Nothing changed.
+    let actual1 = can.shade();
+    assert!(!actual1
+      .iter()
+      .any(|c| matches!(c, ShaderCommand::StylePrintString(_))));
+
+    // Clear the terminal and re-render all the cells.
+    can.invalidate();
+    assert!(can.invalidated());
+    let actual2 = can.shade();
+    info!("invalidated:{:?}", actual2.iter().collect::<Vec<_>>());
+    assert!(matches!(
+      actual2.iter().next(),
+      Some(ShaderCommand::TerminalClear(_))
+    ));
+    assert!(actual2.iter().any(|c| matches!(
+      c,
+      ShaderCommand::StylePrintString(crossterm::style::Print(contents)) if contents == "ABCD"
+    )));
+    assert!(!can.invalidated());
+  }
 }
@@ -96,6 +96,18 @@ impl Frame {
     self.iframe.set_size(size)
   }
 
+  /// Copy the cells and cursor from `other`, the allocated cells grid is reused.
+  pub fn copy_from(&mut self, other: &Frame) {
+    self.iframe.copy_from(&other.iframe);
+    self.cursor = other.cursor;
+  }
+
+  /// Copy the damaged cells and cursor from `other`, see [`Iframe::copy_damaged_from`].
+  pub fn copy_damaged_from(&mut self, other: &Frame) {
+    self.iframe.copy_damaged_from(&other.iframe);
+    self.cursor = other.cursor;
+  }
+
   /// Whether index is inside frame cells.
   pub fn contains_index(&self, index: usize) -> bool {
     self.iframe.contains_index(index)
@@ -223,6 +235,11 @@ impl Frame {
     self.iframe.dirty_rows()
   }
 
+  /// Get the damaged columns of each row.
+  pub fn dirty_cols(&self) -> &Vec<Option<(u16, u16)>> {
+    self.iframe.dirty_cols()
+  }
+
   /// Reset/clean all dirty components.
   ///
   /// NOTE: This method should be called after current frame flushed to terminal device.
@@ -5,7 +5,7 @@
 use compact_str::{CompactString, ToCompactString};
 use crossterm::style::{Attributes, Color};
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq)]
 /// Single character/grapheme rendering unit, it accepts ansi/unicode/emoji/nerd font symbol.
 pub struct Cell {
   // The character/grapheme.
@@ -18,6 +18,25 @@ pub struct Cell {
   attrs: Attributes,
 }
 
+impl Clone for Cell {
+  fn clone(&self) -> Self {
+    Cell {
+      symbol: self.symbol.clone(),
+      fg: self.fg,
+      bg: self.bg,
+      attrs: self.attrs,
+    }
+  }
+
+  /// Reuse the symbol's storage, i.e. when copying the frame grid across frames.
+  fn clone_from(&mut self, source: &Self) {
+    self.symbol.clone_from(&source.symbol);
+    self.fg = source.fg;
+    self.bg = source.bg;
+    self.attrs = source.attrs;
+  }
+}
+
 impl Cell {
   /// Get symbol.
   pub fn symbol(&self) -> &CompactString {
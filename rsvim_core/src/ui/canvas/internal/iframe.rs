@@ -19,6 +19,12 @@ pub struct Iframe {
   /// NOTE: This is for fast locating the changed rows inside the terminal device, i.e. the whole
   /// TUI screen instead of the rows inside UI widget window.
   dirty_rows: Vec<bool>,
+
+  /// The damaged columns (left-inclusive, right-exclusive) of each dirty row.
+  ///
+  /// NOTE: A cell is damaged only if it's replaced with a different cell, thus re-drawing the same
+  /// contents (i.e. the cursor-only moves) doesn't damage any rows.
+  dirty_cols: Vec<Option<(u16, u16)>>,
 }
 
 impl Iframe {
@@ -29,7 +35,22 @@ impl Iframe {
       size,
       cells: vec![Cell::default(); n],
       dirty_rows: vec![false; size.height() as usize], // When a frame first create, it's not dirty.
+      dirty_cols: vec![None; size.height() as usize],
+    }
+  }
+
+  // Mark the cell at the index as damaged.
+  fn damage(&mut self, index: usize) {
+    let (x, y) = self.idx2xy(index);
+    if y >= self.dirty_rows.len() {
+      return;
     }
+    let x = x as u16;
+    self.dirty_rows[y] = true;
+    self.dirty_cols[y] = Some(match self.dirty_cols[y] {
+      Some((start, end)) => (start.min(x), end.max(x + 1)),
+      None => (x, x + 1),
+    });
   }
 
   // Utils {
@@ -104,9 +125,35 @@ impl Iframe {
       Cell::default(),
     );
     self.dirty_rows = vec![true; size.height() as usize];
+    self.dirty_cols = vec![Some((0, size.width())); size.height() as usize];
     old_size
   }
 
+  /// Copy the size, cells and dirty rows from `other`.
+  ///
+  /// NOTE: The allocated cells grid (and the symbols storage inside each cell) is reused, thus
+  /// there's no allocation when the size is not changed.
+  pub fn copy_from(&mut self, other: &Iframe) {
+    self.size = other.size;
+    self.cells.clone_from(&other.cells);
+    self.dirty_rows.clone_from(&other.dirty_rows);
+    self.dirty_cols.clone_from(&other.dirty_cols);
+  }
+
+  /// Copy only the damaged cells from `other`, i.e. the dirty columns of the dirty rows.
+  ///
+  /// NOTE: The frames must have the same size, and the cells outside of the damaged area must be
+  /// the same, i.e. `other` is drawn from this frame's copy.
+  pub fn copy_damaged_from(&mut self, other: &Iframe) {
+    debug_assert_eq!(self.size, other.size);
+    for (row, cols) in other.dirty_cols.iter().enumerate() {
+      if let Some((start, end)) = cols {
+        let range = self.xy2idx(*start as usize, row)..self.xy2idx(*end as usize, row);
+        self.cells[range.clone()].clone_from_slice(&other.cells[range]);
+      }
+    }
+  }
+
   /// Whether index is inside frame cells.
   pub fn contains_index(&self, index: usize) -> bool {
     index < self.cells.len()
@@ -159,15 +206,16 @@ impl Iframe {
   pub fn try_set_cell(&mut self, pos: U16Pos, cell: Cell) -> Option<Cell> {
     let index = self.pos2idx(pos);
     if self.contains_index(index) {
-      let old_cell = self.cells[index].clone();
       trace!(
         "try set cell at index:{:?}, new cell:{:?}, old cell:{:?}",
         index,
         cell,
-        old_cell
+        self.cells[index]
       );
-      self.cells[index] = cell;
-      self.dirty_rows[pos.y() as usize] = true;
+      if self.cells[index] != cell {
+        self.damage(index);
+      }
+      let old_cell = std::mem::replace(&mut self.cells[index], cell);
       Some(old_cell)
     } else {
       trace!("try set cell invalid index:{:?}, cell:{:?}", index, cell);
@@ -275,19 +323,12 @@ impl Iframe {
       self.cells.len()
     );
     if self.contains_range(&range) {
-      let end_at = self.idx2pos(range.end);
-      trace!("try set dirty rows for pos:{:?}, end_at:{:?}", pos, end_at);
-      for row in pos.y()..(end_at.y() + 1) {
-        trace!("try set dirty rows at row:{:?}", row);
-        if (row as usize) < self.dirty_rows.len() {
-          self.dirty_rows[row as usize] = true;
+      for (i, cell) in cells.iter().enumerate() {
+        if self.cells[range.start + i] != *cell {
+          self.damage(range.start + i);
         }
       }
-      trace!(
-        "try set cells dirty at row range:{:?}-{:?}",
-        pos.y(),
-        end_at.y() + 1
-      );
+      trace!("try set dirty rows for range:{:?}", range);
       Some(self.cells.splice(range, cells).collect())
     } else {
       None
@@ -314,11 +355,17 @@ impl Iframe {
     &self.dirty_rows
   }
 
+  /// Get the damaged columns of each row, see [`dirty_rows`](Iframe::dirty_rows).
+  pub fn dirty_cols(&self) -> &Vec<Option<(u16, u16)>> {
+    &self.dirty_cols
+  }
+
   /// Reset/clean all dirty components.
   ///
   /// NOTE: This method should be called after current frame flushed to terminal device.
   pub fn reset_dirty_rows(&mut self) {
-    self.dirty_rows = vec![false; self.size.height() as usize];
+    self.dirty_rows.fill(false);
+    self.dirty_cols.fill(None);
   }
 }
 
@@ -642,4 +689,44 @@ mod tests {
       assert_eq!(actual, expect);
     }
   }
+
+  #[test]
+  fn damage1() {
+    let mut frame = Iframe::new(U16Size::new(10, 3));
+    frame.set_cells_at(
+      point!(x: 2, y: 0),
+      "abc".chars().map(Cell::with_char).collect(),
+    );
+    assert_eq!(frame.dirty_rows(), &vec![true, false, false]);
+    assert_eq!(frame.dirty_cols(), &vec![Some((2, 5)), None, None]);
+    frame.reset_dirty_rows();
+
+    // Re-draw the same contents doesn't damage the row.
+    frame.set_cells_at(
+      point!(x: 2, y: 0),
+      "abc".chars().map(Cell::with_char).collect(),
+    );
+    frame.set_cell(point!(x: 3, y: 0), Cell::with_char('b'));
+    assert_eq!(frame.dirty_rows(), &vec![false, false, false]);
+
+    // Only the changed cells are damaged, and the cells can be wrapped to next row.
+    frame.set_cells_at(
+      point!(x: 2, y: 0),
+      "axc".chars().map(Cell::with_char).collect(),
+    );
+    frame.set_cells_at(
+      point!(x: 9, y: 0),
+      "yz".chars().map(Cell::with_char).collect(),
+    );
+    assert_eq!(frame.dirty_rows(), &vec![true, true, false]);
+    assert_eq!(frame.dirty_cols(), &vec![Some((3, 10)), Some((0, 1)), None]);
+
+    let mut prev = Iframe::new(U16Size::new(10, 3));
+    prev.set_cells_at(
+      point!(x: 2, y: 0),
+      "abc".chars().map(Cell::with_char).collect(),
+    );
+    prev.copy_damaged_from(&frame);
+    assert_eq!(prev.get_cells(), frame.get_cells());
+  }
 }
@@ -0,0 +1,493 @@
+//! Highlight groups, i.e. the colorscheme.
+//!
+//! All the widgets render through the named highlight groups (i.e. `Normal`, `StatusLine`,
+//! `LineNr`) instead of the hardcoded colors, see [`BUILTIN_GROUPS`] for the builtin groups and
+//! their default styles. A group can also link to another group, i.e. `Rsvim.hl.set("IncSearch",
+//! { link: "Search" })`.
+//!
+//! The colors are downgraded by the terminal capability (see [`ColorSupport`]), thus a
+//! colorscheme can use the `#rrggbb` colors, they're rendered as the nearest 256 colors or 16
+//! colors in the terminals don't support true colors. The attributes are degraded as well (see
+//! [`TermCaps`]), i.e. the undercurl is rendered as the underline.
+//!
+//! A colorscheme is a js module at `colors/{name}.js` (or `.ts`) in the config directories, it's
+//! loaded by the `:colorscheme {name}` command (see
+//! [`colorscheme`](crate::state::ex::colorscheme)), the highlight groups are reset to the
+//! defaults before it's executed.
+
+use crate::buf::highlight::HighlightStyle;
+use crate::ui::canvas::Cell;
+use crate::ui::termcap::TermCaps;
+
+use crossterm::style::{Attribute, Attributes, Color};
+use std::collections::BTreeMap;
+
+/// The max depth of the group links, the deeper links (i.e. the circular links) are ignored.
+pub const MAX_LINK_DEPTH: usize = 16;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The colors supported by the terminal.
+pub enum ColorSupport {
+  /// The 16 ANSI colors.
+  Ansi16,
+  /// The 256 colors.
+  Ansi256,
+  /// The 24-bit RGB colors.
+  TrueColor,
+}
+
+impl ColorSupport {
+  /// Detect the color support with the `$COLORTERM` and `$TERM` environment variables.
+  pub fn detect_with(colorterm: Option<&str>, term: Option<&str>) -> Self {
+    if let Some(colorterm) = colorterm {
+      if colorterm == "truecolor" || colorterm == "24bit" {
+        return ColorSupport::TrueColor;
+      }
+    }
+    match term {
+      Some(term) if term.ends_with("-direct") => ColorSupport::TrueColor,
+      Some(term) if term.contains("256color") => ColorSupport::Ansi256,
+      _ => ColorSupport::Ansi16,
+    }
+  }
+
+  /// Detect the color support of current terminal.
+  ///
+  /// NOTE: On Windows, the Windows Terminal (i.e. `$WT_SESSION` is set) supports true colors.
+  pub fn detect() -> Self {
+    if cfg!(target_os = "windows") && std::env::var_os("WT_SESSION").is_some() {
+      return ColorSupport::TrueColor;
+    }
+    ColorSupport::detect_with(
+      std::env::var("COLORTERM").ok().as_deref(),
+      std::env::var("TERM").ok().as_deref(),
+    )
+  }
+}
+
+// The 16 ANSI colors and their RGB values (the xterm defaults), ordered by the ANSI value.
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+  (Color::Black, (0, 0, 0)),
+  (Color::DarkRed, (205, 0, 0)),
+  (Color::DarkGreen, (0, 205, 0)),
+  (Color::DarkYellow, (205, 205, 0)),
+  (Color::DarkBlue, (0, 0, 238)),
+  (Color::DarkMagenta, (205, 0, 205)),
+  (Color::DarkCyan, (0, 205, 205)),
+  (Color::Grey, (229, 229, 229)),
+  (Color::DarkGrey, (127, 127, 127)),
+  (Color::Red, (255, 0, 0)),
+  (Color::Green, (0, 255, 0)),
+  (Color::Yellow, (255, 255, 0)),
+  (Color::Blue, (92, 92, 255)),
+  (Color::Magenta, (255, 0, 255)),
+  (Color::Cyan, (0, 255, 255)),
+  (Color::White, (255, 255, 255)),
+];
+
+// The levels of the 6x6x6 color cube in the 256 colors.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+  let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+  d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+// The RGB value of the 256 colors.
+fn ansi256_rgb(value: u8) -> (u8, u8, u8) {
+  match value {
+    0..=15 => ANSI16[value as usize].1,
+    16..=231 => {
+      let i = value - 16;
+      (
+        CUBE_LEVELS[(i / 36) as usize],
+        CUBE_LEVELS[((i / 6) % 6) as usize],
+        CUBE_LEVELS[(i % 6) as usize],
+      )
+    }
+    _ => {
+      let gray = 8 + 10 * (value - 232);
+      (gray, gray, gray)
+    }
+  }
+}
+
+// The nearest 256 color (in the color cube or the grayscale ramp) of the RGB value.
+fn nearest_ansi256(rgb: (u8, u8, u8)) -> u8 {
+  let level = |v: u8| -> u8 {
+    match v {
+      0..=47 => 0,
+      48..=114 => 1,
+      _ => (v - 35) / 40,
+    }
+  };
+  let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+  let avg = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+  let gray = 232 + (avg.saturating_sub(3) / 10).min(23) as u8;
+  if distance(ansi256_rgb(gray), rgb) < distance(ansi256_rgb(cube), rgb) {
+    gray
+  } else {
+    cube
+  }
+}
+
+// The nearest 16 color of the RGB value.
+fn nearest_ansi16(rgb: (u8, u8, u8)) -> Color {
+  ANSI16
+    .iter()
+    .min_by_key(|(_, value)| distance(*value, rgb))
+    .map(|(color, _)| *color)
+    .unwrap()
+}
+
+/// Downgrade the color to the nearest color supported by the terminal, i.e. the RGB color is
+/// rendered as the nearest 256 color (or 16 color).
+pub fn downgrade(color: Color, support: ColorSupport) -> Color {
+  match (color, support) {
+    (Color::Rgb { r, g, b }, ColorSupport::Ansi256) => Color::AnsiValue(nearest_ansi256((r, g, b))),
+    (Color::Rgb { r, g, b }, ColorSupport::Ansi16) => nearest_ansi16((r, g, b)),
+    (Color::AnsiValue(value), ColorSupport::Ansi16) => match value {
+      0..=15 => ANSI16[value as usize].0,
+      _ => nearest_ansi16(ansi256_rgb(value)),
+    },
+    _ => color,
+  }
+}
+
+/// Set the colors and attributes of the style to the cell, the unspecified ones are kept.
+pub fn apply_style(cell: &mut Cell, style: &HighlightStyle) {
+  if let Some(fg) = style.fg {
+    cell.set_fg(fg);
+  }
+  if let Some(bg) = style.bg {
+    cell.set_bg(bg);
+  }
+  if !style.attrs.is_empty() {
+    cell.set_attrs(style.attrs);
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// A highlight group, its style is ignored if it links to another group.
+pub struct HighlightGroup {
+  pub style: HighlightStyle,
+  pub link: Option<String>,
+}
+
+impl HighlightGroup {
+  pub fn new(style: HighlightStyle) -> Self {
+    HighlightGroup { style, link: None }
+  }
+
+  pub fn linked(link: &str) -> Self {
+    HighlightGroup {
+      style: HighlightStyle::default(),
+      link: Some(link.to_string()),
+    }
+  }
+}
+
+/// The builtin group, i.e. the name, the default foreground/background colors and attributes.
+pub type BuiltinGroup = (
+  &'static str,
+  Option<Color>,
+  Option<Color>,
+  &'static [Attribute],
+);
+
+/// The builtin groups.
+pub const BUILTIN_GROUPS: [BuiltinGroup; 25] = [
+  // The window text, all the other groups are layered on top of it.
+  ("Normal", None, None, &[]),
+  ("Visual", None, None, &[Attribute::Reverse]),
+  ("Search", Some(Color::Black), Some(Color::Yellow), &[]),
+  // The word under cursor, i.e. the 'cursorword' option.
+  ("CursorWord", None, None, &[Attribute::Underlined]),
+  // The jump hint labels.
+  (
+    "HintLabel",
+    None,
+    None,
+    &[Attribute::Bold, Attribute::Reverse],
+  ),
+  ("LineNr", Some(Color::DarkYellow), None, &[]),
+  ("CursorLineNr", Some(Color::Yellow), None, &[]),
+  ("SignColumn", None, None, &[]),
+  ("StatusLine", None, None, &[Attribute::Reverse]),
+  // The command-line.
+  ("MsgArea", None, None, &[]),
+  ("ErrorMsg", Some(Color::Red), None, &[]),
+  ("WarningMsg", Some(Color::Yellow), None, &[]),
+  // The press-enter prompt.
+  ("MoreMsg", Some(Color::Green), None, &[]),
+  // The picker and the wildmenu popup.
+  ("Pmenu", None, None, &[]),
+  ("PmenuSel", None, None, &[Attribute::Reverse]),
+  // The fuzzy matched chars in the picker.
+  ("PmenuMatch", None, None, &[Attribute::Bold]),
+  // The selected candidate on the wildmenu bar.
+  ("WildMenu", None, None, &[Attribute::Reverse]),
+  ("FloatBorder", None, None, &[]),
+  (
+    "FloatShadow",
+    Some(Color::DarkGrey),
+    Some(Color::Black),
+    &[],
+  ),
+  // The progress notifications.
+  ("Progress", None, None, &[Attribute::Reverse]),
+  ("MinimapViewport", None, Some(Color::DarkGrey), &[]),
+  ("MinimapError", Some(Color::Red), None, &[]),
+  ("MinimapWarning", Some(Color::Yellow), None, &[]),
+  ("MinimapInfo", Some(Color::Blue), None, &[]),
+  ("MinimapSearch", Some(Color::Magenta), None, &[]),
+];
+
+#[derive(Debug, Clone)]
+/// The highlight groups registry.
+pub struct HighlightGroups {
+  groups: BTreeMap<String, HighlightGroup>,
+  termcaps: TermCaps,
+
+  // The loaded colorscheme name.
+  colors_name: Option<String>,
+}
+
+impl HighlightGroups {
+  /// Make the registry with the builtin groups.
+  pub fn new(termcaps: TermCaps) -> Self {
+    let mut groups = HighlightGroups {
+      groups: BTreeMap::new(),
+      termcaps,
+      colors_name: None,
+    };
+    groups.reset();
+    groups
+  }
+
+  pub fn color_support(&self) -> ColorSupport {
+    self.termcaps.color_support()
+  }
+
+  pub fn termcaps(&self) -> &TermCaps {
+    &self.termcaps
+  }
+
+  pub fn set_termcaps(&mut self, termcaps: TermCaps) {
+    self.termcaps = termcaps;
+  }
+
+  /// Get the loaded colorscheme name.
+  pub fn colors_name(&self) -> Option<&str> {
+    self.colors_name.as_deref()
+  }
+
+  pub fn set_colors_name(&mut self, name: Option<&str>) {
+    self.colors_name = name.map(|n| n.to_string());
+  }
+
+  /// Get all the groups, maps from the group name to the group.
+  pub fn groups(&self) -> &BTreeMap<String, HighlightGroup> {
+    &self.groups
+  }
+
+  pub fn get(&self, name: &str) -> Option<&HighlightGroup> {
+    self.groups.get(name)
+  }
+
+  /// Set (or create) the group.
+  pub fn set(&mut self, name: &str, group: HighlightGroup) {
+    self.groups.insert(name.to_string(), group);
+  }
+
+  /// Reset all the groups to the builtin groups, and clear the colorscheme name.
+  pub fn reset(&mut self) {
+    self.groups.clear();
+    for (name, fg, bg, attrs) in BUILTIN_GROUPS.iter() {
+      let attrs = attrs
+        .iter()
+        .fold(Attributes::default(), |acc, attr| acc.with(*attr));
+      self.set(
+        name,
+        HighlightGroup::new(HighlightStyle::new(*fg, *bg, attrs)),
+      );
+    }
+    self.colors_name = None;
+  }
+
+  /// Resolve the group style (the links are followed), the colors and attributes are degraded by
+  /// the terminal capabilities. Returns the empty style if the group doesn't exist.
+  ///
+  /// NOTE: This is the style layered on top of other styles, i.e. the `Search` and `Visual`.
+  pub fn resolve(&self, name: &str) -> HighlightStyle {
+    let mut name = name;
+    let mut style = HighlightStyle::default();
+    for _ in 0..MAX_LINK_DEPTH {
+      match self.groups.get(name) {
+        Some(group) => match group.link.as_deref() {
+          Some(link) => name = link,
+          None => {
+            style = group.style;
+            break;
+          }
+        },
+        None => break,
+      }
+    }
+    let color_support = self.termcaps.color_support();
+    style.fg = style.fg.map(|c| downgrade(c, color_support));
+    style.bg = style.bg.map(|c| downgrade(c, color_support));
+    style.attrs = self.termcaps.degrade_attributes(style.attrs);
+    style
+  }
+
+  /// Resolve the group style layered on top of the `Normal` group, i.e. the style of the
+  /// widgets.
+  pub fn style(&self, name: &str) -> HighlightStyle {
+    let mut style = self.resolve("Normal");
+    if name != "Normal" {
+      style.merge(&self.resolve(name));
+    }
+    style
+  }
+}
+
+impl Default for HighlightGroups {
+  fn default() -> Self {
+    HighlightGroups::new(TermCaps::detect())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn detect_with1() {
+    assert_eq!(
+      ColorSupport::detect_with(Some("truecolor"), Some("xterm")),
+      ColorSupport::TrueColor
+    );
+    assert_eq!(
+      ColorSupport::detect_with(None, Some("xterm-256color")),
+      ColorSupport::Ansi256
+    );
+    assert_eq!(
+      ColorSupport::detect_with(None, Some("xterm-direct")),
+      ColorSupport::TrueColor
+    );
+    assert_eq!(
+      ColorSupport::detect_with(Some(""), Some("xterm")),
+      ColorSupport::Ansi16
+    );
+    assert_eq!(ColorSupport::detect_with(None, None), ColorSupport::Ansi16);
+  }
+
+  #[test]
+  fn downgrade1() {
+    let orange = Color::Rgb {
+      r: 255,
+      g: 135,
+      b: 0,
+    };
+    assert_eq!(downgrade(orange, ColorSupport::TrueColor), orange);
+    assert_eq!(
+      downgrade(orange, ColorSupport::Ansi256),
+      Color::AnsiValue(208)
+    );
+    assert_eq!(downgrade(orange, ColorSupport::Ansi16), Color::DarkYellow);
+
+    let gray = Color::Rgb {
+      r: 128,
+      g: 128,
+      b: 130,
+    };
+    assert_eq!(
+      downgrade(gray, ColorSupport::Ansi256),
+      Color::AnsiValue(244)
+    );
+    assert_eq!(downgrade(gray, ColorSupport::Ansi16), Color::DarkGrey);
+
+    assert_eq!(
+      downgrade(Color::AnsiValue(9), ColorSupport::Ansi16),
+      Color::Red
+    );
+    assert_eq!(
+      downgrade(Color::AnsiValue(21), ColorSupport::Ansi16),
+      Color::DarkBlue
+    );
+    assert_eq!(
+      downgrade(Color::AnsiValue(21), ColorSupport::Ansi256),
+      Color::AnsiValue(21)
+    );
+    assert_eq!(
+      downgrade(Color::Yellow, ColorSupport::Ansi16),
+      Color::Yellow
+    );
+  }
+
+  // The terminal capabilities of the linux console, i.e. 16 colors without strikethrough and
+  // undercurl.
+  fn linux_termcaps() -> TermCaps {
+    TermCaps::detect_with(|name| (name == "TERM").then(|| "linux".to_string()), &[])
+  }
+
+  #[test]
+  fn resolve1() {
+    let mut groups = HighlightGroups::new(linux_termcaps());
+    assert_eq!(groups.resolve("Search"), HighlightStyle::search());
+    assert_eq!(groups.resolve("Visual"), HighlightStyle::selection());
+    assert_eq!(groups.resolve("NotExist"), HighlightStyle::default());
+
+    let blue = Color::Rgb { r: 0, g: 0, b: 255 };
+    groups.set(
+      "Normal",
+      HighlightGroup::new(HighlightStyle::new(
+        Some(Color::White),
+        Some(blue),
+        Attributes::default(),
+      )),
+    );
+    groups.set("IncSearch", HighlightGroup::linked("Search"));
+    assert_eq!(groups.resolve("IncSearch"), HighlightStyle::search());
+    assert_eq!(
+      groups.style("StatusLine"),
+      HighlightStyle::new(
+        Some(Color::White),
+        Some(Color::DarkBlue),
+        Attribute::Reverse.into()
+      )
+    );
+
+    // The circular links are ignored.
+    groups.set("A", HighlightGroup::linked("B"));
+    groups.set("B", HighlightGroup::linked("A"));
+    assert_eq!(groups.resolve("A"), HighlightStyle::default());
+
+    groups.set_colors_name(Some("blue"));
+    groups.reset();
+    assert_eq!(groups.colors_name(), None);
+    assert!(groups.get("A").is_none());
+    assert_eq!(groups.style("Normal"), HighlightStyle::default());
+  }
+
+  #[test]
+  fn resolve2() {
+    let mut groups = HighlightGroups::new(linux_termcaps());
+    let attrs = Attributes::from(Attribute::Undercurled) | Attribute::CrossedOut;
+    groups.set(
+      "SpellBad",
+      HighlightGroup::new(HighlightStyle::new(None, None, attrs)),
+    );
+    assert_eq!(
+      groups.resolve("SpellBad").attrs,
+      Attributes::from(Attribute::Underlined)
+    );
+
+    let mut termcaps = linux_termcaps();
+    termcaps.set_undercurl(true);
+    termcaps.set_strikethrough(true);
+    groups.set_termcaps(termcaps);
+    assert_eq!(groups.resolve("SpellBad").attrs, attrs);
+  }
+}
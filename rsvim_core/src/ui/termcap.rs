@@ -0,0 +1,313 @@
+//! Terminal capabilities.
+//!
+//! The capabilities are probed from the environment variables (i.e. `$COLORTERM`, `$TERM`,
+//! `$TERM_PROGRAM`) and the extended capabilities of the terminfo entry (i.e. `Tc`/`RGB`, `Sync`,
+//! `Smulx` and `smxx`), they gate the rendering features:
+//!
+//! - The 24-bit colors, otherwise the colors are downgraded to the 256 colors or 16 colors (see
+//!   [`ColorSupport`]), i.e. the 'termguicolors' option.
+//! - The synchronized output (mode 2026), each frame is wrapped inside the begin/end synchronized
+//!   update, thus the terminal never shows a partially rendered frame, i.e. the 'termsync'
+//!   option.
+//! - The strikethrough attribute, otherwise it's removed, i.e. the 'termstrikethrough' option.
+//! - The undercurl attribute, otherwise it fallbacks to the underline, i.e. the 'termundercurl'
+//!   option.
+//! - The bracketed paste, i.e. the 'termpaste' option.
+//!
+//! The probed capabilities are only the defaults, they can be overridden by the global options,
+//! i.e. `Rsvim.opt.termGuiColors = true`.
+
+use crate::ui::highlight::ColorSupport;
+
+use crossterm::style::{Attribute, Attributes};
+use std::path::PathBuf;
+
+// The `$TERM` (prefix) of the terminals support the synchronized output and undercurl.
+const MODERN_TERMS: [&str; 6] = [
+  "xterm-kitty",
+  "xterm-ghostty",
+  "wezterm",
+  "foot",
+  "alacritty",
+  "contour",
+];
+
+// The `$TERM_PROGRAM` of the terminals support the true colors, synchronized output and
+// undercurl.
+const MODERN_TERM_PROGRAMS: [&str; 4] = ["WezTerm", "iTerm.app", "ghostty", "vscode"];
+
+// The `$TERM` (prefix) of the terminals don't support the strikethrough and bracketed paste.
+const LEGACY_TERMS: [&str; 5] = ["dumb", "linux", "vt100", "vt220", "cons25"];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The terminal capabilities.
+pub struct TermCaps {
+  color_support: ColorSupport,
+  // The color support when the true colors are turned off.
+  fallback_color_support: ColorSupport,
+  synchronized_output: bool,
+  strikethrough: bool,
+  undercurl: bool,
+  bracketed_paste: bool,
+}
+
+impl TermCaps {
+  /// Detect the capabilities with the environment variable getter `var` and the extended
+  /// capability names of the terminfo entry.
+  pub fn detect_with<F>(var: F, terminfo: &[String]) -> Self
+  where
+    F: Fn(&str) -> Option<String>,
+  {
+    let term = var("TERM").unwrap_or_default();
+    let term_program = var("TERM_PROGRAM").unwrap_or_default();
+    let has = |name: &str| terminfo.iter().any(|cap| cap == name);
+    let is_modern = MODERN_TERMS.iter().any(|t| term.starts_with(t))
+      || MODERN_TERM_PROGRAMS.contains(&term_program.as_str());
+    let is_legacy = term.is_empty() || LEGACY_TERMS.iter().any(|t| term.starts_with(t));
+
+    let detected = ColorSupport::detect_with(var("COLORTERM").as_deref(), Some(&term));
+    let fallback_color_support = detected.min(ColorSupport::Ansi256);
+    let color_support =
+      if has("Tc") || has("RGB") || MODERN_TERM_PROGRAMS.contains(&term_program.as_str()) {
+        ColorSupport::TrueColor
+      } else {
+        detected
+      };
+
+    TermCaps {
+      color_support,
+      fallback_color_support,
+      synchronized_output: has("Sync") || is_modern,
+      strikethrough: has("smxx") || !is_legacy,
+      undercurl: has("Smulx") || is_modern,
+      bracketed_paste: term != "dumb",
+    }
+  }
+
+  /// Detect the capabilities of current terminal.
+  ///
+  /// NOTE: On Windows, the Windows Terminal (i.e. `$WT_SESSION` is set) supports the true colors
+  /// and synchronized output, the legacy console (conhost) doesn't set `$TERM`.
+  pub fn detect() -> Self {
+    let var = |name: &str| std::env::var(name).ok();
+    let term = var("TERM").unwrap_or_default();
+    let mut caps = TermCaps::detect_with(var, &terminfo_capabilities(&term));
+    if cfg!(target_os = "windows") && std::env::var_os("WT_SESSION").is_some() {
+      caps.color_support = ColorSupport::TrueColor;
+      caps.synchronized_output = true;
+      caps.strikethrough = true;
+      caps.bracketed_paste = true;
+    }
+    caps
+  }
+
+  pub fn color_support(&self) -> ColorSupport {
+    self.color_support
+  }
+
+  /// Whether the 24-bit colors are supported, i.e. the 'termguicolors' option.
+  pub fn true_color(&self) -> bool {
+    self.color_support == ColorSupport::TrueColor
+  }
+
+  pub fn set_true_color(&mut self, value: bool) {
+    self.color_support = if value {
+      ColorSupport::TrueColor
+    } else {
+      self.fallback_color_support
+    };
+  }
+
+  /// Whether the synchronized output (mode 2026) is supported, i.e. the 'termsync' option.
+  pub fn synchronized_output(&self) -> bool {
+    self.synchronized_output
+  }
+
+  pub fn set_synchronized_output(&mut self, value: bool) {
+    self.synchronized_output = value;
+  }
+
+  /// Whether the strikethrough attribute is supported, i.e. the 'termstrikethrough' option.
+  pub fn strikethrough(&self) -> bool {
+    self.strikethrough
+  }
+
+  pub fn set_strikethrough(&mut self, value: bool) {
+    self.strikethrough = value;
+  }
+
+  /// Whether the undercurl attribute is supported, i.e. the 'termundercurl' option.
+  pub fn undercurl(&self) -> bool {
+    self.undercurl
+  }
+
+  pub fn set_undercurl(&mut self, value: bool) {
+    self.undercurl = value;
+  }
+
+  /// Whether the bracketed paste is supported, i.e. the 'termpaste' option.
+  pub fn bracketed_paste(&self) -> bool {
+    self.bracketed_paste
+  }
+
+  pub fn set_bracketed_paste(&mut self, value: bool) {
+    self.bracketed_paste = value;
+  }
+
+  /// Remove the attributes not supported by the terminal, the undercurl fallbacks to the
+  /// underline.
+  pub fn degrade_attributes(&self, attrs: Attributes) -> Attributes {
+    let mut attrs = attrs;
+    if !self.strikethrough && attrs.has(Attribute::CrossedOut) {
+      attrs.unset(Attribute::CrossedOut);
+    }
+    if !self.undercurl && attrs.has(Attribute::Undercurled) {
+      attrs.unset(Attribute::Undercurled);
+      attrs.set(Attribute::Underlined);
+    }
+    attrs
+  }
+}
+
+impl Default for TermCaps {
+  fn default() -> Self {
+    TermCaps::detect()
+  }
+}
+
+// The terminfo directories, in the same order with ncurses.
+fn terminfo_dirs() -> Vec<PathBuf> {
+  let mut dirs = vec![];
+  if let Ok(dir) = std::env::var("TERMINFO") {
+    dirs.push(PathBuf::from(dir));
+  }
+  if let Some(base_dirs) = directories::BaseDirs::new() {
+    dirs.push(base_dirs.home_dir().join(".terminfo"));
+  }
+  if let Ok(terminfo_dirs) = std::env::var("TERMINFO_DIRS") {
+    dirs.extend(
+      terminfo_dirs
+        .split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from),
+    );
+  }
+  for dir in [
+    "/etc/terminfo",
+    "/lib/terminfo",
+    "/usr/share/terminfo",
+    "/usr/lib/terminfo",
+  ] {
+    dirs.push(PathBuf::from(dir));
+  }
+  dirs
+}
+
+/// Parse the names inside the compiled terminfo entry, i.e. the extended capability names (such as
+/// `Tc` and `Smulx`) are the NUL-terminated strings at the end of the entry.
+pub fn parse_terminfo_names(bytes: &[u8]) -> Vec<String> {
+  bytes
+    .split(|b| *b == 0)
+    .filter(|name| !name.is_empty() && name.iter().all(|b| b.is_ascii_alphanumeric()))
+    .map(|name| String::from_utf8_lossy(name).to_string())
+    .collect()
+}
+
+/// Read the names inside the terminfo entry of `term`, returns empty if it's not found.
+///
+/// NOTE: The entry is at `{dir}/{first char}/{term}`, or `{dir}/{hex of first char}/{term}` on
+/// macOS.
+pub fn terminfo_capabilities(term: &str) -> Vec<String> {
+  let first = match term.chars().next() {
+    Some(first) if !term.contains(['/', '\\']) => first,
+    _ => return vec![],
+  };
+  for dir in terminfo_dirs() {
+    for sub in [first.to_string(), format!("{:x}", first as u32)] {
+      if let Ok(bytes) = std::fs::read(dir.join(sub).join(term)) {
+        return parse_terminfo_names(&bytes);
+      }
+    }
+  }
+  vec![]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::collections::HashMap;
+
+  fn detect(vars: &[(&str, &str)], terminfo: &[&str]) -> TermCaps {
+    let vars: HashMap<String, String> = vars
+      .iter()
+      .map(|(k, v)| (k.to_string(), v.to_string()))
+      .collect();
+    let terminfo: Vec<String> = terminfo.iter().map(|cap| cap.to_string()).collect();
+    TermCaps::detect_with(|name| vars.get(name).cloned(), &terminfo)
+  }
+
+  #[test]
+  fn detect_with1() {
+    let caps = detect(&[("TERM", "xterm-256color")], &[]);
+    assert_eq!(caps.color_support(), ColorSupport::Ansi256);
+    assert!(!caps.synchronized_output());
+    assert!(caps.strikethrough());
+    assert!(!caps.undercurl());
+    assert!(caps.bracketed_paste());
+
+    let caps = detect(
+      &[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")],
+      &[],
+    );
+    assert!(caps.true_color());
+
+    let caps = detect(&[("TERM", "xterm-kitty")], &["Tc", "Smulx", "Sync"]);
+    assert!(caps.true_color());
+    assert!(caps.synchronized_output());
+    assert!(caps.undercurl());
+
+    let caps = detect(
+      &[("TERM", "xterm-256color"), ("TERM_PROGRAM", "WezTerm")],
+      &[],
+    );
+    assert!(caps.true_color());
+    assert!(caps.synchronized_output());
+
+    let caps = detect(&[("TERM", "linux")], &[]);
+    assert_eq!(caps.color_support(), ColorSupport::Ansi16);
+    assert!(!caps.strikethrough());
+
+    let caps = detect(&[("TERM", "dumb")], &[]);
+    assert!(!caps.bracketed_paste());
+  }
+
+  #[test]
+  fn overrides1() {
+    let mut caps = detect(&[("TERM", "xterm-256color")], &[]);
+    caps.set_true_color(true);
+    assert_eq!(caps.color_support(), ColorSupport::TrueColor);
+    caps.set_true_color(false);
+    assert_eq!(caps.color_support(), ColorSupport::Ansi256);
+
+    let attrs = Attributes::from(Attribute::Undercurled) | Attribute::CrossedOut;
+    let mut caps = detect(&[("TERM", "linux")], &[]);
+    let degraded = caps.degrade_attributes(attrs);
+    assert!(degraded.has(Attribute::Underlined));
+    assert!(!degraded.has(Attribute::Undercurled));
+    assert!(!degraded.has(Attribute::CrossedOut));
+    caps.set_undercurl(true);
+    caps.set_strikethrough(true);
+    assert_eq!(caps.degrade_attributes(attrs), attrs);
+  }
+
+  #[test]
+  fn parse_terminfo_names1() {
+    let bytes = b"\x1e\x02xterm-kitty|KovIdTTY\0\x01\0\x1b[%p1%d;%p2%dm\0Tc\0Smulx\0Sync\0";
+    let names = parse_terminfo_names(bytes);
+    assert!(names.contains(&"Tc".to_string()));
+    assert!(names.contains(&"Smulx".to_string()));
+    assert!(names.contains(&"Sync".to_string()));
+    assert!(!names.contains(&"Setulc".to_string()));
+  }
+}
@@ -2,22 +2,39 @@
 
 #![allow(dead_code)]
 
-use crate::cart::{IRect, U16Rect, U16Size};
+use crate::buf::{BufferWk, SignDefinition};
+use crate::cart::{IRect, U16Pos, U16Rect, U16Size};
+use crate::defaults;
 use crate::envar;
+use crate::rlock;
+use crate::state::mode::Mode;
 use crate::ui::canvas::{Canvas, CanvasArc};
-use crate::ui::tree::internal::{InodeId, Inodeable, Itree};
-use crate::ui::widget::window::WindowLocalOptions;
-use crate::ui::widget::{Cursor, RootContainer, Widgetable, Window};
+use crate::ui::highlight::HighlightGroups;
+use crate::ui::termcap::TermCaps;
+use crate::ui::tree::internal::{shapes, InodeId, Inodeable, Itree};
+use crate::ui::tree::layout::{
+  FocusDirection, LayoutDirection, LayoutNode, ResizeAmount, SizeConstraint,
+};
+use crate::ui::widget::float::{float_shapes, FloatAnchor, FloatConfig};
+use crate::ui::widget::window::sign::SignColumn;
+use crate::ui::widget::window::statusline;
+use crate::ui::widget::window::{WindowLocalOptions, WindowRegion};
+use crate::ui::widget::{
+  CommandLine, Cursor, FloatFrame, MessageBox, Picker, ProgressNotification, RootContainer,
+  Widgetable, WildMenu, Window,
+};
+use crate::wlock;
 
 // Re-export
-pub use crate::ui::tree::opt::{WindowGlobalOptions, WindowGlobalOptionsBuilder};
+pub use crate::ui::tree::opt::{JumpCenter, WindowGlobalOptions, WindowGlobalOptionsBuilder};
 
 use parking_lot::RwLock;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::{Arc, Weak};
 // use tracing::trace;
 
 pub mod internal;
+pub mod layout;
 pub mod opt;
 
 #[derive(Debug, Clone)]
@@ -26,6 +43,12 @@ pub enum TreeNode {
   RootContainer(RootContainer),
   Window(Window),
   Cursor(Cursor),
+  Picker(Picker),
+  ProgressNotification(ProgressNotification),
+  CommandLine(CommandLine),
+  WildMenu(WildMenu),
+  FloatFrame(FloatFrame),
+  MessageBox(MessageBox),
 }
 
 macro_rules! tree_node_generate_dispatch {
@@ -34,6 +57,12 @@ macro_rules! tree_node_generate_dispatch {
       TreeNode::RootContainer(n) => n.$method_name(),
       TreeNode::Window(n) => n.$method_name(),
       TreeNode::Cursor(n) => n.$method_name(),
+      TreeNode::Picker(n) => n.$method_name(),
+      TreeNode::ProgressNotification(n) => n.$method_name(),
+      TreeNode::CommandLine(n) => n.$method_name(),
+      TreeNode::WildMenu(n) => n.$method_name(),
+      TreeNode::FloatFrame(n) => n.$method_name(),
+      TreeNode::MessageBox(n) => n.$method_name(),
     }
   };
 }
@@ -44,6 +73,12 @@ impl TreeNode {
       TreeNode::RootContainer(n) => n.id(),
       TreeNode::Window(n) => n.id(),
       TreeNode::Cursor(n) => n.id(),
+      TreeNode::Picker(n) => n.id(),
+      TreeNode::ProgressNotification(n) => n.id(),
+      TreeNode::CommandLine(n) => n.id(),
+      TreeNode::WildMenu(n) => n.id(),
+      TreeNode::FloatFrame(n) => n.id(),
+      TreeNode::MessageBox(n) => n.id(),
     }
   }
 }
@@ -109,6 +144,12 @@ impl Widgetable for TreeNode {
       TreeNode::RootContainer(w) => w.draw(canvas),
       TreeNode::Window(w) => w.draw(canvas),
       TreeNode::Cursor(w) => w.draw(canvas),
+      TreeNode::Picker(w) => w.draw(canvas),
+      TreeNode::ProgressNotification(w) => w.draw(canvas),
+      TreeNode::CommandLine(w) => w.draw(canvas),
+      TreeNode::WildMenu(w) => w.draw(canvas),
+      TreeNode::FloatFrame(w) => w.draw(canvas),
+      TreeNode::MessageBox(w) => w.draw(canvas),
     }
   }
 }
@@ -218,6 +259,12 @@ pub struct Tree {
 
   // All [`Window`](crate::ui::widget::Window) node IDs.
   window_ids: BTreeSet<TreeNodeId>,
+
+  // Floating window IDs, maps to their frame widget IDs.
+  float_frame_ids: BTreeMap<TreeNodeId, TreeNodeId>,
+
+  // The layout tree of tiled windows.
+  layout: LayoutNode,
   // Cursor and window state }
 
   // Global options for windows.
@@ -225,6 +272,20 @@ pub struct Tree {
 
   // Local options for windows.
   local_options: WindowLocalOptions,
+
+  // Whether a full repaint is requested, i.e. `:redraw!`.
+  full_repaint: bool,
+
+  // The cached values of the statusline components, see
+  // [`statusline`](crate::ui::widget::window::statusline).
+  statusline_components: BTreeMap<String, String>,
+
+  // The sign definitions, see [`sign`](crate::buf::sign).
+  sign_definitions: BTreeMap<String, SignDefinition>,
+
+  // The highlight groups, they're shared with the canvas when drawing, see
+  // [`highlight`](crate::ui::highlight).
+  highlights: Arc<HighlightGroups>,
 }
 
 pub type TreeArc = Arc<RwLock<Tree>>;
@@ -252,8 +313,14 @@ impl Tree {
       base: Itree::new(root_node),
       cursor_id: None,
       window_ids: BTreeSet::new(),
+      float_frame_ids: BTreeMap::new(),
+      layout: LayoutNode::default(),
       global_options: WindowGlobalOptions::default(),
       local_options: WindowLocalOptions::default(),
+      full_repaint: false,
+      statusline_components: BTreeMap::new(),
+      sign_definitions: BTreeMap::new(),
+      highlights: Arc::new(HighlightGroups::default()),
     }
   }
 
@@ -342,6 +409,51 @@ impl Tree {
   pub fn window_ids(&self) -> &BTreeSet<TreeNodeId> {
     &self.window_ids
   }
+
+  /// Whether any window's viewport moved since last prefetch, see
+  /// [`Viewport::prefetch`](crate::ui::widget::window::Viewport::prefetch).
+  pub fn viewport_prefetch_pending(&self) -> bool {
+    self.window_ids.iter().any(|id| match self.node(id) {
+      Some(TreeNode::Window(window)) => rlock!(window.viewport()).prefetch_pending(),
+      _ => false,
+    })
+  }
+
+  /// Prefetch the lines around all the (moved) windows' viewports, it's called on idle.
+  pub fn prefetch_viewports(&self) {
+    for id in self.window_ids.iter() {
+      if let Some(TreeNode::Window(window)) = self.node(id) {
+        let viewport = window.viewport();
+        let mut viewport = wlock!(viewport);
+        if viewport.prefetch_pending() {
+          viewport.prefetch();
+        }
+      }
+    }
+  }
+
+  /// Set current window, i.e. move the cursor widget into the window `window_id`.
+  ///
+  /// Returns `false` if the window doesn't exist, or there's no cursor widget.
+  pub fn set_current_window_id(&mut self, window_id: TreeNodeId) -> bool {
+    if !self.window_ids.contains(&window_id) {
+      return false;
+    }
+    if self.current_window_id() == Some(window_id) {
+      return true;
+    }
+    let cursor_id = match self.cursor_id {
+      Some(cursor_id) => cursor_id,
+      None => return false,
+    };
+    match self.remove(cursor_id) {
+      Some(cursor) => {
+        self.bounded_insert(&window_id, cursor);
+        true
+      }
+      None => false,
+    }
+  }
 }
 // Node {
 
@@ -379,6 +491,11 @@ impl Tree {
       self.cursor_id = None;
     }
     self.window_ids.remove(id);
+    self.layout.remove(*id);
+    // If the removed ID is a floating window, also remove its frame.
+    if let Some(frame_id) = self.float_frame_ids.remove(id) {
+      self.base.remove(frame_id);
+    }
   }
 
   /// See [`Itree::insert`].
@@ -405,6 +522,450 @@ impl Tree {
 }
 // Insert/Remove }
 
+// Float {
+impl Tree {
+  /// Open a floating window for the `buffer`, returns the window ID.
+  ///
+  /// The window is placed by the [`FloatConfig`] (relative to the anchor position), with its
+  /// border and shadow drawn by a [`FloatFrame`] widget right below it.
+  ///
+  /// NOTE: The buffer must not be locked when calling this method.
+  pub fn open_float(&mut self, buffer: BufferWk, config: &FloatConfig) -> TreeNodeId {
+    let root_id = self.root_id();
+    let root_shape = *self.node(&root_id).unwrap().actual_shape();
+    let terminal_size = U16Size::new(root_shape.width(), root_shape.height());
+    let anchor_pos = match config.anchor {
+      FloatAnchor::Editor => None,
+      FloatAnchor::Window => self
+        .current_window_id()
+        .and_then(|id| self.node(&id))
+        .map(|node| node.actual_shape().min()),
+      FloatAnchor::Cursor => self
+        .cursor_id
+        .and_then(|id| self.node(&id))
+        .map(|node| node.actual_shape().min()),
+    }
+    .map(|pos| (pos.x as isize, pos.y as isize))
+    .unwrap_or((0, 0));
+    let (frame_shape, window_shape) = float_shapes(config, anchor_pos, terminal_size);
+
+    let frame = FloatFrame::new(frame_shape, config);
+    let frame_id = frame.id();
+    self.bounded_insert(&root_id, TreeNode::FloatFrame(frame));
+
+    // NOTE: The floating window doesn't show the minimap and statusline.
+    let mut local_options = self.local_options.clone();
+    local_options.set_minimap(false);
+    local_options.set_statusline("");
+    let mut window = Window::new(window_shape, buffer, &local_options);
+    *window.zindex_mut() = config.zindex;
+    let window_id = window.id();
+    self.bounded_insert(&root_id, TreeNode::Window(window));
+    self.float_frame_ids.insert(window_id, frame_id);
+    window_id
+  }
+
+  /// Close a floating window, returns `false` if it's not a floating window.
+  ///
+  /// NOTE: If the cursor is inside it, the cursor is moved back to the first tiled window.
+  pub fn close_float(&mut self, window_id: TreeNodeId) -> bool {
+    if !self.is_float(&window_id) {
+      return false;
+    }
+    if self.current_window_id() == Some(window_id) {
+      let tiled_window_id = self
+        .window_ids
+        .iter()
+        .find(|id| !self.float_frame_ids.contains_key(id))
+        .copied();
+      if let Some(tiled_window_id) = tiled_window_id {
+        self.set_current_window_id(tiled_window_id);
+        self.sync_cursor_position();
+      }
+    }
+    self.remove(window_id);
+    true
+  }
+
+  /// Move the cursor widget to the cursor position of current window's viewport, i.e. after the
+  /// cursor is moved into another window.
+  pub fn sync_cursor_position(&mut self) {
+    let (cursor_id, window_id) = match (self.cursor_id, self.current_window_id()) {
+      (Some(cursor_id), Some(window_id)) => (cursor_id, window_id),
+      _ => return,
+    };
+    let (x, y) = match self.node(&window_id) {
+      Some(TreeNode::Window(window)) => {
+        let viewport = window.viewport();
+        let viewport = rlock!(viewport);
+        let cursor = viewport.cursor();
+        let row_start_dcol_idx = viewport
+          .lines()
+          .get(&cursor.line_idx())
+          .and_then(|line| line.rows().get(&cursor.row_idx()))
+          .map(|row| row.start_dcol_idx())
+          .unwrap_or(0);
+        (
+          (cursor.start_dcol_idx().saturating_sub(row_start_dcol_idx)
+            + window.content_offset() as usize) as isize,
+          cursor.row_idx() as isize,
+        )
+      }
+      _ => return,
+    };
+    let current_pos = self.node(&cursor_id).unwrap().shape().min();
+    self.bounded_move_by(cursor_id, x - current_pos.x, y - current_pos.y);
+  }
+
+  /// Whether the window is a floating window.
+  pub fn is_float(&self, window_id: &TreeNodeId) -> bool {
+    self.float_frame_ids.contains_key(window_id)
+  }
+
+  /// Get all the floating window IDs.
+  pub fn float_ids(&self) -> Vec<TreeNodeId> {
+    self.float_frame_ids.keys().copied().collect()
+  }
+
+  /// Get the window and its region at the terminal position `pos`, i.e. where the mouse clicks.
+  /// The floating windows are on top of the tiled windows, the one with the highest z-index wins.
+  pub fn window_at(&self, pos: U16Pos) -> Option<(TreeNodeId, WindowRegion)> {
+    let region_at = |window_id: &TreeNodeId| match self.node(window_id) {
+      Some(TreeNode::Window(window)) => window.region_at(pos).map(|region| (*window_id, region)),
+      _ => None,
+    };
+    let float = self
+      .float_frame_ids
+      .keys()
+      .filter_map(|window_id| {
+        let zindex = *self.node(window_id)?.zindex();
+        region_at(window_id).map(|found| (zindex, found))
+      })
+      .max_by_key(|(zindex, _)| *zindex)
+      .map(|(_, found)| found);
+    float.or_else(|| self.tiled_window_ids().iter().find_map(region_at))
+  }
+}
+// Float }
+
+// Layout {
+impl Tree {
+  /// Get the tiled (i.e. not floating) window IDs, in the layout order, i.e. from top-left to
+  /// bottom-right.
+  ///
+  /// NOTE: The windows not placed in the layout yet are at the end, they're appended to the bottom
+  /// on next re-layout.
+  pub fn tiled_window_ids(&self) -> Vec<TreeNodeId> {
+    let mut window_ids = self.layout.window_ids();
+    window_ids.extend(
+      self
+        .window_ids
+        .iter()
+        .filter(|id| !self.is_float(id) && !self.layout.contains(**id)),
+    );
+    window_ids
+  }
+
+  /// Get the layout tree of tiled windows.
+  pub fn layout(&self) -> &LayoutNode {
+    &self.layout
+  }
+
+  /// Resize the terminal, the tiled windows are re-layout.
+  pub fn resize(&mut self, terminal_size: U16Size) {
+    let shape = IRect::new(
+      (0, 0),
+      (
+        terminal_size.width() as isize,
+        terminal_size.height() as isize,
+      ),
+    );
+    let root_id = self.root_id();
+    let root = self.base.node_mut(&root_id).unwrap();
+    *root.shape_mut() = shape;
+    *root.actual_shape_mut() =
+      U16Rect::new((0, 0), (terminal_size.width(), terminal_size.height()));
+    self.layout_tiled_windows();
+  }
+
+  // The area for tiled windows, i.e. the whole terminal.
+  fn layout_area(&self) -> IRect {
+    let root_id = self.root_id();
+    let root_actual_shape = *self.node(&root_id).unwrap().actual_shape();
+    IRect::new(
+      (0, 0),
+      (
+        root_actual_shape.width() as isize,
+        root_actual_shape.height() as isize,
+      ),
+    )
+  }
+
+  // The size constraint of the window along the direction.
+  fn window_constraint(&self, id: TreeNodeId, direction: LayoutDirection) -> SizeConstraint {
+    match (self.node(&id), direction) {
+      (Some(TreeNode::Window(window)), LayoutDirection::Row) => window.width_constraint(),
+      (Some(TreeNode::Window(window)), LayoutDirection::Column) => window.height_constraint(),
+      _ => SizeConstraint::Flex,
+    }
+  }
+
+  // Compute the shapes of tiled windows.
+  fn tiled_window_shapes(&self) -> Vec<(TreeNodeId, IRect)> {
+    self.layout.compute(self.layout_area(), &|id, direction| {
+      self.window_constraint(id, direction)
+    })
+  }
+
+  /// Re-layout the tiled windows by the layout tree and their size constraints, i.e. the
+  /// 'winfixwidth'/'winfixheight' options and the percentage shares, see
+  /// [`layout`](crate::ui::tree::layout).
+  ///
+  /// NOTE: The new tiled windows are appended to the bottom.
+  pub fn layout_tiled_windows(&mut self) {
+    for id in self.tiled_window_ids() {
+      if !self.layout.contains(id) {
+        self.layout.push(id);
+      }
+    }
+    let root_id = self.root_id();
+    let root_actual_shape = *self.node(&root_id).unwrap().actual_shape();
+    for (id, shape) in self.tiled_window_shapes() {
+      let actual_shape = shapes::make_actual_shape(shape, root_actual_shape);
+      if let Some(TreeNode::Window(window)) = self.base.node_mut(&id) {
+        window.resize(shape, actual_shape);
+      }
+    }
+    self.sync_cursor_position();
+  }
+
+  /// Split current window in the `direction`, i.e. `:split` (in [`LayoutDirection::Column`]) or
+  /// `:vsplit` (in [`LayoutDirection::Row`]). The new window shows the same buffer, with a copy of
+  /// the local options, jumplist and view, it's placed above (or on the left side of) current
+  /// window and becomes current window.
+  ///
+  /// Returns the new window ID, or `None` if there's no current tiled window or not enough room.
+  pub fn split_window(&mut self, direction: LayoutDirection) -> Option<TreeNodeId> {
+    let window_id = self.current_window_id()?;
+    if self.is_float(&window_id) {
+      return None;
+    }
+    let new_window = match self.node(&window_id) {
+      Some(TreeNode::Window(window)) => {
+        let size = match direction {
+          LayoutDirection::Row => window.actual_shape().width(),
+          LayoutDirection::Column => window.actual_shape().height(),
+        };
+        if size < 2 {
+          return None;
+        }
+        let mut new_window = Window::new(*window.shape(), window.buffer(), window.options());
+        *new_window.jumplist_mut() = window.jumplist().clone();
+        new_window
+      }
+      _ => return None,
+    };
+    let (view, cursor) = match self.node(&window_id) {
+      Some(TreeNode::Window(window)) => {
+        let viewport = window.viewport();
+        let viewport = rlock!(viewport);
+        (
+          (viewport.start_line_idx(), viewport.start_dcol_idx()),
+          (viewport.cursor().line_idx(), viewport.cursor().char_idx()),
+        )
+      }
+      _ => return None,
+    };
+
+    let new_window_id = new_window.id();
+    let root_id = self.root_id();
+    self.bounded_insert(&root_id, TreeNode::Window(new_window));
+    self.layout.split(window_id, new_window_id, direction);
+    self.layout_tiled_windows();
+    if let Some(TreeNode::Window(window)) = self.node(&new_window_id) {
+      let viewport = window.viewport();
+      let mut viewport = wlock!(viewport);
+      if !viewport.is_empty() {
+        viewport.sync_from_top_left(view.0, view.1);
+        let (start_line_idx, start_dcol_idx) = viewport.search_anchor(cursor.0, cursor.1);
+        if start_line_idx != viewport.start_line_idx()
+          || start_dcol_idx != viewport.start_dcol_idx()
+        {
+          viewport.sync_from_top_left(start_line_idx, start_dcol_idx);
+        }
+        if let Some(cursor) = viewport.cursor_of(cursor.0, cursor.1) {
+          viewport.set_cursor(cursor);
+        }
+      }
+    }
+    self.set_current_window_id(new_window_id);
+    self.sync_cursor_position();
+    Some(new_window_id)
+  }
+
+  /// Close the window, i.e. `:close`. If it's current window, the cursor moves to the previous
+  /// window in the layout order (or the next one if it's the first).
+  ///
+  /// Returns `false` if the window doesn't exist, or it's the last tiled window.
+  pub fn close_window(&mut self, window_id: TreeNodeId) -> bool {
+    if self.is_float(&window_id) {
+      return self.close_float(window_id);
+    }
+    let window_ids = self.tiled_window_ids();
+    let idx = match window_ids.iter().position(|id| *id == window_id) {
+      Some(idx) => idx,
+      None => return false,
+    };
+    if window_ids.len() <= 1 {
+      return false;
+    }
+    if self.current_window_id() == Some(window_id) {
+      let next_id = if idx > 0 {
+        window_ids[idx - 1]
+      } else {
+        window_ids[idx + 1]
+      };
+      self.set_current_window_id(next_id);
+    }
+    self.remove(window_id);
+    self.layout_tiled_windows();
+    true
+  }
+
+  /// Make all the tiled windows (almost) the same size, i.e. `Ctrl-W =`. The windows with
+  /// 'winfixwidth'/'winfixheight' keep their sizes.
+  pub fn equalize_windows(&mut self) {
+    self.layout.clear_percents();
+    for id in self.tiled_window_ids() {
+      if let Some(TreeNode::Window(window)) = self.base.node_mut(&id) {
+        window.set_width_percent(None);
+        window.set_height_percent(None);
+      }
+    }
+    self.layout_tiled_windows();
+  }
+
+  /// Resize the window in the `direction`, i.e. the width in [`LayoutDirection::Row`] and the
+  /// height in [`LayoutDirection::Column`]. If the window is inside a nested split, the split
+  /// containing it is resized, i.e. `:resize` in a window on the right side of `:vsplit` resizes
+  /// the whole column.
+  ///
+  /// The new size is saved as the percentage share, thus it persists across terminal resizes.
+  ///
+  /// Returns `false` if the window is not a tiled window, or it can't be resized in the direction
+  /// (i.e. it's the only window in the direction).
+  pub fn resize_window(
+    &mut self,
+    window_id: TreeNodeId,
+    direction: LayoutDirection,
+    amount: ResizeAmount,
+  ) -> bool {
+    let path = match self.layout.path(window_id) {
+      Some(path) => path,
+      None => return false,
+    };
+    // The nearest split in the direction.
+    let mut depth = None;
+    let mut node = &self.layout;
+    for (i, idx) in path.iter().enumerate() {
+      if let LayoutNode::Split(split) = node {
+        if split.direction == direction && split.children.len() > 1 {
+          depth = Some(i);
+        }
+        node = &split.children[*idx];
+      }
+    }
+    let depth = match depth {
+      Some(depth) => depth,
+      None => return false,
+    };
+
+    let size_of = |shape: IRect| match direction {
+      LayoutDirection::Row => shape.width().max(0) as usize,
+      LayoutDirection::Column => shape.height().max(0) as usize,
+    };
+    let area = self.layout_area();
+    let window_constraint = |id, direction| self.window_constraint(id, direction);
+    let (total, size) = match (
+      self
+        .layout
+        .shape_at(area, &path[..depth], &window_constraint),
+      self
+        .layout
+        .shape_at(area, &path[..depth + 1], &window_constraint),
+    ) {
+      (Some(parent_shape), Some(shape)) => (size_of(parent_shape), size_of(shape)),
+      _ => return false,
+    };
+    if total == 0 {
+      return false;
+    }
+    let new_size = match amount {
+      ResizeAmount::By(n) => (size as isize + n).max(1) as usize,
+      ResizeAmount::To(n) => (n as usize).max(1),
+    };
+    let new_size = new_size.min(total);
+    // NOTE: Round up, thus the size is exact when the total size is less than 100.
+    let percent = new_size.saturating_mul(100).div_ceil(total).clamp(1, 100) as u16;
+
+    match self.layout.node_at_mut(&path[..depth + 1]) {
+      Some(LayoutNode::Split(split)) => split.percent = Some(percent),
+      Some(LayoutNode::Window(id)) => {
+        let id = *id;
+        if let Some(TreeNode::Window(window)) = self.base.node_mut(&id) {
+          match direction {
+            LayoutDirection::Row => window.set_width_percent(Some(percent)),
+            LayoutDirection::Column => window.set_height_percent(Some(percent)),
+          }
+        }
+      }
+      None => return false,
+    }
+    self.layout_tiled_windows();
+    true
+  }
+
+  /// Move the cursor to the neighbor window in the `direction`, i.e. `Ctrl-W h/j/k/l`.
+  ///
+  /// Returns `false` if there's no window in the direction.
+  pub fn focus_window(&mut self, direction: FocusDirection) -> bool {
+    let (window_id, cursor_id) = match (self.current_window_id(), self.cursor_id) {
+      (Some(window_id), Some(cursor_id)) => (window_id, cursor_id),
+      _ => return false,
+    };
+    let cursor_pos = self.node(&cursor_id).unwrap().actual_shape().min();
+    let pos = (cursor_pos.x as isize, cursor_pos.y as isize);
+    match layout::neighbor(&self.tiled_window_shapes(), window_id, pos, direction) {
+      Some(target_id) => {
+        self.set_current_window_id(target_id);
+        self.sync_cursor_position();
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Move the cursor to the next tiled window in the layout order (or the first one if it's the
+  /// last), i.e. `Ctrl-W w`.
+  pub fn focus_next_window(&mut self) -> bool {
+    let window_ids = self.tiled_window_ids();
+    let next_id = match self
+      .current_window_id()
+      .and_then(|id| window_ids.iter().position(|w| *w == id))
+    {
+      Some(idx) => window_ids[(idx + 1) % window_ids.len()],
+      None => match window_ids.first() {
+        Some(id) => *id,
+        None => return false,
+      },
+    };
+    self.set_current_window_id(next_id);
+    self.sync_cursor_position();
+    true
+  }
+}
+// Layout }
+
 // Movement {
 impl Tree {
   /// See [`Itree::bounded_move_by`].
@@ -483,14 +1044,347 @@ impl Tree {
   pub fn set_line_break(&mut self, value: bool) {
     self.local_options.set_line_break(value);
   }
+
+  pub fn scroll_off(&self) -> usize {
+    self.local_options.scroll_off()
+  }
+
+  pub fn set_scroll_off(&mut self, value: usize) {
+    self.local_options.set_scroll_off(value);
+  }
+
+  pub fn side_scroll(&self) -> usize {
+    self.local_options.side_scroll()
+  }
+
+  pub fn set_side_scroll(&mut self, value: usize) {
+    self.local_options.set_side_scroll(value);
+  }
+
+  pub fn side_scroll_off(&self) -> usize {
+    self.local_options.side_scroll_off()
+  }
+
+  pub fn set_side_scroll_off(&mut self, value: usize) {
+    self.local_options.set_side_scroll_off(value);
+  }
+
+  pub fn minimap(&self) -> bool {
+    self.local_options.minimap()
+  }
+
+  pub fn set_minimap(&mut self, value: bool) {
+    self.local_options.set_minimap(value);
+  }
+
+  pub fn statusline(&self) -> &str {
+    self.local_options.statusline()
+  }
+
+  pub fn set_statusline(&mut self, value: &str) {
+    self.local_options.set_statusline(value);
+  }
+
+  pub fn winfixwidth(&self) -> bool {
+    self.local_options.winfixwidth()
+  }
+
+  pub fn set_winfixwidth(&mut self, value: bool) {
+    self.local_options.set_winfixwidth(value);
+  }
+
+  pub fn winfixheight(&self) -> bool {
+    self.local_options.winfixheight()
+  }
+
+  pub fn set_winfixheight(&mut self, value: bool) {
+    self.local_options.set_winfixheight(value);
+  }
+
+  pub fn number(&self) -> bool {
+    self.local_options.number()
+  }
+
+  pub fn set_number(&mut self, value: bool) {
+    self.local_options.set_number(value);
+  }
+
+  pub fn relative_number(&self) -> bool {
+    self.local_options.relative_number()
+  }
+
+  pub fn set_relative_number(&mut self, value: bool) {
+    self.local_options.set_relative_number(value);
+  }
+
+  pub fn number_width(&self) -> u16 {
+    self.local_options.number_width()
+  }
+
+  pub fn set_number_width(&mut self, value: u16) {
+    self.local_options.set_number_width(value);
+  }
+
+  pub fn sign_column(&self) -> SignColumn {
+    self.local_options.sign_column()
+  }
+
+  pub fn set_sign_column(&mut self, value: SignColumn) {
+    self.local_options.set_sign_column(value);
+  }
+
+  pub fn sign_width(&self) -> u16 {
+    self.local_options.sign_width()
+  }
+
+  pub fn set_sign_width(&mut self, value: u16) {
+    self.local_options.set_sign_width(value);
+  }
+
+  pub fn hlsearch(&self) -> bool {
+    self.global_options.hlsearch()
+  }
+
+  pub fn set_hlsearch(&mut self, value: bool) {
+    self.global_options.set_hlsearch(value);
+  }
+
+  pub fn incsearch(&self) -> bool {
+    self.global_options.incsearch()
+  }
+
+  pub fn set_incsearch(&mut self, value: bool) {
+    self.global_options.set_incsearch(value);
+  }
+
+  pub fn autopairs(&self) -> bool {
+    self.global_options.autopairs()
+  }
+
+  pub fn set_autopairs(&mut self, value: bool) {
+    self.global_options.set_autopairs(value);
+  }
+
+  pub fn jump_center(&self) -> JumpCenter {
+    self.global_options.jump_center()
+  }
+
+  pub fn set_jump_center(&mut self, value: JumpCenter) {
+    self.global_options.set_jump_center(value);
+  }
+
+  pub fn quickfix_preview(&self) -> bool {
+    self.global_options.quickfix_preview()
+  }
+
+  pub fn set_quickfix_preview(&mut self, value: bool) {
+    self.global_options.set_quickfix_preview(value);
+  }
+
+  pub fn title(&self) -> bool {
+    self.global_options.title()
+  }
+
+  pub fn set_title(&mut self, value: bool) {
+    self.global_options.set_title(value);
+  }
+
+  pub fn titlestring(&self) -> &str {
+    self.global_options.titlestring()
+  }
+
+  pub fn set_titlestring(&mut self, value: &str) {
+    self.global_options.set_titlestring(value);
+  }
+
+  /// Render the terminal title of current window in editing `mode`, with the 'titlestring' (or
+  /// the [`TITLE_FORMAT`](defaults::win::TITLE_FORMAT) if it's empty) in the 'statusline' format.
+  ///
+  /// Returns `None` if the 'title' option is off, or there's no current window.
+  pub fn render_title(&self, mode: Mode) -> Option<String> {
+    if !self.title() {
+      return None;
+    }
+    let window = match self.node(&self.current_window_id()?) {
+      Some(TreeNode::Window(window)) => window,
+      _ => return None,
+    };
+    let info = statusline::make_info(
+      &window.buffer(),
+      &Arc::downgrade(&window.viewport()),
+      mode,
+      &self.statusline_components,
+    )?;
+    let format = match self.titlestring() {
+      "" => defaults::win::TITLE_FORMAT,
+      titlestring => titlestring,
+    };
+    Some(statusline::render_statusline(format, &info, 0))
+  }
 }
 // Global options }
 
+// Statusline {
+impl Tree {
+  /// Get the cached values of the statusline components.
+  pub fn statusline_components(&self) -> &BTreeMap<String, String> {
+    &self.statusline_components
+  }
+
+  /// Set the cached value of the statusline component `name`, it's rendered on next redraw.
+  pub fn set_statusline_component(&mut self, name: &str, value: &str) {
+    self
+      .statusline_components
+      .insert(name.to_string(), value.to_string());
+  }
+
+  /// Remove the statusline component `name`, returns `false` if it doesn't exist.
+  pub fn remove_statusline_component(&mut self, name: &str) -> bool {
+    self.statusline_components.remove(name).is_some()
+  }
+}
+// Statusline }
+
+// Signs {
+impl Tree {
+  pub fn sign_definitions(&self) -> &BTreeMap<String, SignDefinition> {
+    &self.sign_definitions
+  }
+
+  /// Define the sign `name`, the previously defined sign with the same name is replaced.
+  pub fn define_sign(&mut self, name: &str, definition: SignDefinition) {
+    self.sign_definitions.insert(name.to_string(), definition);
+  }
+
+  /// Undefine the sign `name`, returns `false` if it doesn't exist. The placed signs are kept but
+  /// not shown.
+  pub fn undefine_sign(&mut self, name: &str) -> bool {
+    self.sign_definitions.remove(name).is_some()
+  }
+}
+// Signs }
+
+// Highlights {
+impl Tree {
+  pub fn highlights(&self) -> &HighlightGroups {
+    &self.highlights
+  }
+
+  /// Get mutable highlight groups, the changes are rendered on next redraw.
+  pub fn highlights_mut(&mut self) -> &mut HighlightGroups {
+    Arc::make_mut(&mut self.highlights)
+  }
+}
+// Highlights }
+
+// Terminal capabilities {
+impl Tree {
+  /// Get the terminal capabilities, they're detected when the tree is created and can be
+  /// overridden by the global options, see [`termcap`](crate::ui::termcap).
+  pub fn termcaps(&self) -> &TermCaps {
+    self.highlights.termcaps()
+  }
+
+  pub fn set_termcaps(&mut self, termcaps: TermCaps) {
+    self.highlights_mut().set_termcaps(termcaps);
+  }
+
+  fn update_termcaps<F>(&mut self, f: F)
+  where
+    F: FnOnce(&mut TermCaps),
+  {
+    let mut termcaps = *self.termcaps();
+    f(&mut termcaps);
+    self.set_termcaps(termcaps);
+  }
+
+  pub fn termguicolors(&self) -> bool {
+    self.termcaps().true_color()
+  }
+
+  pub fn set_termguicolors(&mut self, value: bool) {
+    self.update_termcaps(|termcaps| termcaps.set_true_color(value));
+  }
+
+  pub fn termsync(&self) -> bool {
+    self.termcaps().synchronized_output()
+  }
+
+  pub fn set_termsync(&mut self, value: bool) {
+    self.update_termcaps(|termcaps| termcaps.set_synchronized_output(value));
+  }
+
+  pub fn termstrikethrough(&self) -> bool {
+    self.termcaps().strikethrough()
+  }
+
+  pub fn set_termstrikethrough(&mut self, value: bool) {
+    self.update_termcaps(|termcaps| termcaps.set_strikethrough(value));
+  }
+
+  pub fn termundercurl(&self) -> bool {
+    self.termcaps().undercurl()
+  }
+
+  pub fn set_termundercurl(&mut self, value: bool) {
+    self.update_termcaps(|termcaps| termcaps.set_undercurl(value));
+  }
+
+  pub fn termpaste(&self) -> bool {
+    self.termcaps().bracketed_paste()
+  }
+
+  pub fn set_termpaste(&mut self, value: bool) {
+    self.update_termcaps(|termcaps| termcaps.set_bracketed_paste(value));
+  }
+}
+// Terminal capabilities }
+
 // Draw {
 impl Tree {
-  /// Draw the widget tree to canvas.
-  pub fn draw(&self, canvas: CanvasArc) {
+  /// Request to redraw the widget tree, with `force` the terminal is cleared and fully
+  /// repainted, i.e. after external programs scribble on it.
+  ///
+  /// NOTE: The widget tree is always drawn after handling each event, thus only the full repaint
+  /// needs to be recorded.
+  pub fn request_redraw(&mut self, force: bool) {
+    self.full_repaint |= force;
+  }
+
+  /// Whether a full repaint is requested.
+  pub fn full_repaint(&self) -> bool {
+    self.full_repaint
+  }
+
+  /// Re-layout the windows whose line number gutter width or sign column width is changed, i.e.
+  /// the buffer grows to more digits lines, the cursor is moved along with the window content.
+  /// The sign definitions are synced to the sign columns.
+  ///
+  /// NOTE: The buffers must not be locked when calling this method.
+  pub fn sync_gutters(&mut self) {
+    let window_ids = self.window_ids.iter().copied().collect::<Vec<_>>();
+    let definitions = std::mem::take(&mut self.sign_definitions);
+    let mut changed = false;
+    for window_id in window_ids {
+      if let Some(TreeNode::Window(window)) = self.node_mut(&window_id) {
+        changed |= window.sync_gutters();
+        window.sync_sign_definitions(&definitions);
+      }
+    }
+    self.sign_definitions = definitions;
+    if changed {
+      self.sync_cursor_position();
+    }
+  }
+
+  /// Draw the widget tree to canvas, the canvas is invalidated if a full repaint is requested.
+  pub fn draw(&mut self, canvas: CanvasArc) {
     let mut canvas = canvas.try_write_for(envar::MUTEX_TIMEOUT()).unwrap();
+    if self.full_repaint {
+      self.full_repaint = false;
+      canvas.invalidate();
+    }
+    canvas.set_highlights(self.highlights.clone());
     for node in self.base.iter() {
       // trace!("Draw tree:{:?}", node);
       node.draw(&mut canvas);
@@ -502,10 +1396,257 @@ impl Tree {
 #[cfg(test)]
 mod tests {
   use crate::cart::U16Size;
+  use crate::test::buf::make_buffer_from_lines;
+  use crate::ui::canvas::Canvas;
+  use crate::ui::widget::float::FLOAT_ZINDEX;
   // use crate::test::log::init as test_log_init;
 
   use super::*;
 
+  use geo::point;
+
+  #[test]
+  fn request_redraw1() {
+    let terminal_size = U16Size::new(18, 10);
+    let mut tree = Tree::new(terminal_size);
+    let canvas = Canvas::to_arc(Canvas::new(terminal_size));
+    tree.request_redraw(false);
+    assert!(!tree.full_repaint());
+    tree.request_redraw(true);
+    assert!(tree.full_repaint());
+    tree.draw(canvas.clone());
+    assert!(!tree.full_repaint());
+    assert!(canvas.read().invalidated());
+  }
+
+  #[test]
+  fn open_float1() {
+    let terminal_size = U16Size::new(30, 10);
+    let mut tree = Tree::new(terminal_size);
+    let buffer = make_buffer_from_lines(vec!["Hello, RSVIM!\n"]);
+    let config = FloatConfig {
+      row: 1,
+      col: 2,
+      width: 10,
+      height: 3,
+      ..Default::default()
+    };
+    let window_id = tree.open_float(Arc::downgrade(&buffer), &config);
+    assert!(tree.is_float(&window_id));
+    assert_eq!(tree.float_ids(), vec![window_id]);
+    assert_eq!(tree.len(), 3);
+    let window = tree.node(&window_id).unwrap();
+    assert_eq!(*window.zindex(), FLOAT_ZINDEX);
+    assert_eq!(
+      *window.actual_shape(),
+      U16Rect::new((3_u16, 2_u16), (13_u16, 5_u16))
+    );
+
+    let canvas = Canvas::to_arc(Canvas::new(terminal_size));
+    tree.draw(canvas.clone());
+    let actual = canvas.read().frame().raw_symbols()[2].join("");
+    // The cells outside of any widget are empty.
+    assert_eq!(actual, "│Hello, RSV│");
+
+    assert!(tree.close_float(window_id));
+    assert!(!tree.close_float(window_id));
+    assert_eq!(tree.len(), 1);
+  }
+
+  #[test]
+  fn window_at1() {
+    let terminal_size = U16Size::new(30, 10);
+    let mut tree = Tree::new(terminal_size);
+    let buffer = make_buffer_from_lines(vec!["Hello, RSVIM!\n"]);
+    let root_id = tree.root_id();
+    let window = Window::new(
+      IRect::new((0, 0), (30, 10)),
+      Arc::downgrade(&buffer),
+      tree.local_options(),
+    );
+    let window_id = window.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(window));
+    let config = FloatConfig {
+      row: 1,
+      col: 2,
+      width: 10,
+      height: 3,
+      ..Default::default()
+    };
+    let float_id = tree.open_float(Arc::downgrade(&buffer), &config);
+
+    // The floating window is on top of the tiled window.
+    assert_eq!(
+      tree.window_at(point!(x: 5_u16, y: 3_u16)),
+      Some((float_id, WindowRegion::Content(point!(x: 2_u16, y: 1_u16))))
+    );
+    assert_eq!(
+      tree.window_at(point!(x: 20_u16, y: 3_u16)),
+      Some((
+        window_id,
+        WindowRegion::Content(point!(x: 20_u16, y: 3_u16))
+      ))
+    );
+    assert_eq!(tree.window_at(point!(x: 30_u16, y: 3_u16)), None);
+  }
+
+  #[test]
+  fn render_title1() {
+    let mut tree = Tree::new(U16Size::new(20, 10));
+    let buffer = make_buffer_from_lines(vec!["Hello, RSVIM!\n"]);
+    let root_id = tree.root_id();
+    let shape = IRect::new((0, 0), (20, 10));
+    let window = Window::new(shape, Arc::downgrade(&buffer), tree.local_options());
+    let window_id = window.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(window));
+    tree.bounded_insert(
+      &window_id,
+      TreeNode::Cursor(Cursor::new(IRect::new((0, 0), (1, 1)))),
+    );
+
+    assert_eq!(tree.render_title(Mode::Normal), None);
+    tree.set_title(true);
+    assert_eq!(
+      tree.render_title(Mode::Normal).as_deref(),
+      Some("[No Name] - Rsvim")
+    );
+    wlock!(buffer).insert(0, "a");
+    assert_eq!(
+      tree.render_title(Mode::Normal).as_deref(),
+      Some("[No Name][+] - Rsvim")
+    );
+    tree.set_titlestring("%s %m%f");
+    assert_eq!(
+      tree.render_title(Mode::Insert).as_deref(),
+      Some("INSERT [+][No Name]")
+    );
+  }
+
+  #[test]
+  fn resize1() {
+    let terminal_size = U16Size::new(20, 10);
+    let mut tree = Tree::new(terminal_size);
+    let buffer = make_buffer_from_lines(vec!["Hello, RSVIM!\n"]);
+    let root_id = tree.root_id();
+    let shape = IRect::new((0, 0), (20, 10));
+    let options = WindowLocalOptions::builder().winfixheight(true).build();
+    let mut sidebar = Window::new(shape, Arc::downgrade(&buffer), &options);
+    sidebar.resize(
+      IRect::new((0, 0), (20, 3)),
+      U16Rect::new((0_u16, 0_u16), (20_u16, 3_u16)),
+    );
+    let sidebar_id = sidebar.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(sidebar));
+    let mut window = Window::new(shape, Arc::downgrade(&buffer), tree.local_options());
+    window.set_height_percent(Some(50));
+    let window_id = window.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(window));
+    let last_window = Window::new(shape, Arc::downgrade(&buffer), tree.local_options());
+    let last_window_id = last_window.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(last_window));
+
+    tree.resize(U16Size::new(30, 20));
+    let actual_shape = |tree: &Tree, id: &TreeNodeId| *tree.node(id).unwrap().actual_shape();
+    assert_eq!(
+      actual_shape(&tree, &sidebar_id),
+      U16Rect::new((0_u16, 0_u16), (30_u16, 3_u16))
+    );
+    assert_eq!(
+      actual_shape(&tree, &window_id),
+      U16Rect::new((0_u16, 3_u16), (30_u16, 13_u16))
+    );
+    assert_eq!(
+      actual_shape(&tree, &last_window_id),
+      U16Rect::new((0_u16, 13_u16), (30_u16, 20_u16))
+    );
+    if let Some(TreeNode::Window(window)) = tree.node(&window_id) {
+      let viewport = window.viewport();
+      assert_eq!(viewport.read().actual_shape().height(), 10);
+    }
+  }
+
+  #[test]
+  fn split_window1() {
+    let terminal_size = U16Size::new(20, 10);
+    let mut tree = Tree::new(terminal_size);
+    let buffer = make_buffer_from_lines(vec!["Hello, RSVIM!\n"]);
+    let root_id = tree.root_id();
+    let shape = IRect::new((0, 0), (20, 10));
+    let window = Window::new(shape, Arc::downgrade(&buffer), tree.local_options());
+    let window_id = window.id();
+    tree.bounded_insert(&root_id, TreeNode::Window(window));
+    tree.bounded_insert(
+      &window_id,
+      TreeNode::Cursor(Cursor::new(IRect::new((0, 0), (1, 1)))),
+    );
+    tree.layout_tiled_windows();
+    let actual_shape = |tree: &Tree, id: &TreeNodeId| *tree.node(id).unwrap().actual_shape();
+
+    // The new window is on the left side, and becomes current window.
+    let left_id = tree.split_window(LayoutDirection::Row).unwrap();
+    assert_eq!(tree.current_window_id(), Some(left_id));
+    assert_eq!(
+      actual_shape(&tree, &left_id),
+      U16Rect::new((0_u16, 0_u16), (10_u16, 10_u16))
+    );
+    // Then split the left window, the new window is above.
+    let top_id = tree.split_window(LayoutDirection::Column).unwrap();
+    assert_eq!(tree.tiled_window_ids(), vec![top_id, left_id, window_id]);
+    assert_eq!(
+      actual_shape(&tree, &top_id),
+      U16Rect::new((0_u16, 0_u16), (10_u16, 5_u16))
+    );
+
+    // Focus movement.
+    assert!(tree.focus_window(FocusDirection::Right));
+    assert_eq!(tree.current_window_id(), Some(window_id));
+    assert!(!tree.focus_window(FocusDirection::Right));
+    assert!(tree.focus_window(FocusDirection::Left));
+    assert_eq!(tree.current_window_id(), Some(top_id));
+    assert!(tree.focus_window(FocusDirection::Down));
+    assert_eq!(tree.current_window_id(), Some(left_id));
+    assert!(tree.focus_next_window());
+    assert_eq!(tree.current_window_id(), Some(window_id));
+
+    // Resize the left column.
+    assert!(tree.resize_window(left_id, LayoutDirection::Row, ResizeAmount::By(3)));
+    assert_eq!(
+      actual_shape(&tree, &top_id),
+      U16Rect::new((0_u16, 0_u16), (13_u16, 5_u16))
+    );
+    assert!(tree.resize_window(top_id, LayoutDirection::Column, ResizeAmount::To(3)));
+    assert_eq!(
+      actual_shape(&tree, &left_id),
+      U16Rect::new((0_u16, 3_u16), (13_u16, 10_u16))
+    );
+    // The percentage shares persist across terminal resizes.
+    tree.resize(U16Size::new(40, 20));
+    assert_eq!(
+      actual_shape(&tree, &left_id),
+      U16Rect::new((0_u16, 6_u16), (26_u16, 20_u16))
+    );
+    tree.equalize_windows();
+    assert_eq!(
+      actual_shape(&tree, &left_id),
+      U16Rect::new((0_u16, 10_u16), (20_u16, 20_u16))
+    );
+
+    // Close windows.
+    assert!(tree.close_window(window_id));
+    assert_eq!(tree.current_window_id(), Some(left_id));
+    assert_eq!(
+      actual_shape(&tree, &left_id),
+      U16Rect::new((0_u16, 10_u16), (40_u16, 20_u16))
+    );
+    assert!(tree.close_window(left_id));
+    assert_eq!(tree.current_window_id(), Some(top_id));
+    assert!(!tree.close_window(top_id));
+    assert_eq!(
+      actual_shape(&tree, &top_id),
+      U16Rect::new((0_u16, 0_u16), (40_u16, 20_u16))
+    );
+  }
+
   #[test]
   fn new() {
     // test_log_init();
@@ -0,0 +1,615 @@
+//! Tiled windows layout.
+//!
+//! The tiled windows are placed side by side (in a row) or stacked (in a column), each window has
+//! a size constraint along the layout direction:
+//!
+//! - Fixed: The window keeps its size, i.e. the 'winfixwidth'/'winfixheight' options, for
+//!   example the file tree sidebar.
+//! - Percent: The window takes a percentage share of the total size, it persists across terminal
+//!   resizes and opening/closing other windows.
+//! - Flex: The window shares the remaining size evenly with other flexible windows.
+//!
+//! When the total size is not enough, the percentage windows are shrunk first, then the fixed
+//! windows. Each window keeps at least 1 row/column if possible.
+//!
+//! The windows are organized in the [`LayoutNode`] tree, i.e. `:split` stacks the new window above
+//! current window, `:vsplit` places it on the left side. A split in the same direction as the
+//! parent is merged into the parent, thus the tree is always alternating rows and columns.
+
+use crate::cart::IRect;
+use crate::ui::tree::internal::InodeId;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The size constraint of a tiled window along the layout direction.
+pub enum SizeConstraint {
+  /// Fixed size (rows or columns).
+  Fixed(u16),
+  /// Percentage share of the total size, from 0 to 100.
+  Percent(u16),
+  /// Shares the remaining size evenly.
+  Flex,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The layout direction of the tiled windows.
+pub enum LayoutDirection {
+  /// Side by side, i.e. split the width.
+  Row,
+  /// Stacked, i.e. split the height.
+  Column,
+}
+
+/// Distribute the `total` size to the windows by their `constraints`.
+pub fn distribute(total: u16, constraints: &[SizeConstraint]) -> Vec<u16> {
+  if constraints.is_empty() {
+    return vec![];
+  }
+  let total = total as usize;
+  let mut sizes: Vec<usize> = constraints
+    .iter()
+    .map(|c| match c {
+      SizeConstraint::Fixed(size) => *size as usize,
+      SizeConstraint::Percent(percent) => total * (*percent).min(100) as usize / 100,
+      SizeConstraint::Flex => 0,
+    })
+    .collect();
+  let flex_idxes: Vec<usize> = constraints
+    .iter()
+    .enumerate()
+    .filter(|(_, c)| **c == SizeConstraint::Flex)
+    .map(|(i, _)| i)
+    .collect();
+
+  let preferred: usize = sizes.iter().sum::<usize>() + flex_idxes.len();
+  if preferred <= total {
+    let remaining = total - (preferred - flex_idxes.len());
+    if flex_idxes.is_empty() {
+      // The remaining size goes to the last non-fixed window, or the last window.
+      let idx = constraints
+        .iter()
+        .rposition(|c| !matches!(c, SizeConstraint::Fixed(_)))
+        .unwrap_or(constraints.len() - 1);
+      sizes[idx] += remaining;
+    } else {
+      let n = flex_idxes.len();
+      for (i, idx) in flex_idxes.iter().enumerate() {
+        sizes[*idx] = remaining / n + if i < remaining % n { 1 } else { 0 };
+      }
+    }
+  } else {
+    for idx in flex_idxes.iter() {
+      sizes[*idx] = 1;
+    }
+    let mut excess = preferred - total;
+    for min_size in [1, 0] {
+      for is_percent in [true, false] {
+        for (i, c) in constraints.iter().enumerate().rev() {
+          let matched = match c {
+            SizeConstraint::Percent(_) => is_percent,
+            SizeConstraint::Fixed(_) => !is_percent,
+            SizeConstraint::Flex => !is_percent && min_size == 0,
+          };
+          if matched && excess > 0 {
+            let shrink = excess.min(sizes[i].saturating_sub(min_size));
+            sizes[i] -= shrink;
+            excess -= shrink;
+          }
+        }
+      }
+    }
+  }
+  sizes.into_iter().map(|size| size as u16).collect()
+}
+
+/// Split the `area` into the windows' shapes by the `direction` and the `constraints`.
+pub fn split(
+  area: IRect,
+  direction: LayoutDirection,
+  constraints: &[SizeConstraint],
+) -> Vec<IRect> {
+  let (min_x, min_y) = (area.min().x, area.min().y);
+  let (max_x, max_y) = (area.max().x, area.max().y);
+  let total = match direction {
+    LayoutDirection::Row => area.width(),
+    LayoutDirection::Column => area.height(),
+  };
+  let mut offset = 0_isize;
+  distribute(total.clamp(0, u16::MAX as isize) as u16, constraints)
+    .into_iter()
+    .map(|size| {
+      let start = offset;
+      offset += size as isize;
+      match direction {
+        LayoutDirection::Row => IRect::new((min_x + start, min_y), (min_x + offset, max_y)),
+        LayoutDirection::Column => IRect::new((min_x, min_y + start), (max_x, min_y + offset)),
+      }
+    })
+    .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The layout tree node of the tiled windows.
+pub enum LayoutNode {
+  /// The window.
+  Window(InodeId),
+  /// The windows (or nested splits) placed in a direction.
+  Split(LayoutSplit),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The split of the layout tree.
+pub struct LayoutSplit {
+  pub direction: LayoutDirection,
+  pub children: Vec<LayoutNode>,
+  /// The percentage share in the parent split, i.e. after the split is resized.
+  pub percent: Option<u16>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The resize amount of a window.
+pub enum ResizeAmount {
+  /// Increase (or decrease if negative) by rows/columns.
+  By(isize),
+  /// Set to rows/columns.
+  To(u16),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The direction to move the focus to another window, i.e. `Ctrl-W h/j/k/l`.
+pub enum FocusDirection {
+  Left,
+  Down,
+  Up,
+  Right,
+}
+
+impl Default for LayoutNode {
+  /// The empty root, the windows are stacked from top to bottom.
+  fn default() -> Self {
+    LayoutNode::Split(LayoutSplit {
+      direction: LayoutDirection::Column,
+      children: vec![],
+      percent: None,
+    })
+  }
+}
+
+impl LayoutNode {
+  /// Get all the window IDs, from top-left to bottom-right.
+  pub fn window_ids(&self) -> Vec<InodeId> {
+    match self {
+      LayoutNode::Window(id) => vec![*id],
+      LayoutNode::Split(split) => split
+        .children
+        .iter()
+        .flat_map(|child| child.window_ids())
+        .collect(),
+    }
+  }
+
+  /// Whether the window is in the layout.
+  pub fn contains(&self, id: InodeId) -> bool {
+    match self {
+      LayoutNode::Window(window_id) => *window_id == id,
+      LayoutNode::Split(split) => split.children.iter().any(|child| child.contains(id)),
+    }
+  }
+
+  /// Append the window to the bottom of the root.
+  pub fn push(&mut self, id: InodeId) {
+    match self {
+      LayoutNode::Split(split) if split.direction == LayoutDirection::Column => {
+        split.children.push(LayoutNode::Window(id));
+      }
+      _ => {
+        let root = std::mem::take(self);
+        if let LayoutNode::Split(split) = self {
+          split.children.push(root);
+          split.children.push(LayoutNode::Window(id));
+        }
+        self.normalize();
+      }
+    }
+  }
+
+  /// Split the `target` window in the `direction`, the `new` window is placed before (i.e. above
+  /// or on the left side of) the target.
+  ///
+  /// Returns `false` if the target window is not found.
+  pub fn split(&mut self, target: InodeId, new: InodeId, direction: LayoutDirection) -> bool {
+    let split = match self {
+      LayoutNode::Split(split) => split,
+      LayoutNode::Window(_) => return false,
+    };
+    let target_node = LayoutNode::Window(target);
+    match split
+      .children
+      .iter()
+      .position(|child| *child == target_node)
+    {
+      Some(i) => {
+        if split.direction == direction || split.children.len() == 1 {
+          split.direction = direction;
+          split.children.insert(i, LayoutNode::Window(new));
+        } else {
+          split.children[i] = LayoutNode::Split(LayoutSplit {
+            direction,
+            children: vec![LayoutNode::Window(new), target_node],
+            percent: None,
+          });
+        }
+        true
+      }
+      None => split
+        .children
+        .iter_mut()
+        .any(|child| child.split(target, new, direction)),
+    }
+  }
+
+  /// Remove the window, the splits left with only one child are merged into the parent.
+  ///
+  /// Returns `false` if the window is not found.
+  pub fn remove(&mut self, id: InodeId) -> bool {
+    let removed = self.remove_impl(id);
+    if removed {
+      self.normalize();
+    }
+    removed
+  }
+
+  fn remove_impl(&mut self, id: InodeId) -> bool {
+    match self {
+      LayoutNode::Split(split) => {
+        match split
+          .children
+          .iter()
+          .position(|child| *child == LayoutNode::Window(id))
+        {
+          Some(i) => {
+            split.children.remove(i);
+            true
+          }
+          None => split.children.iter_mut().any(|child| child.remove_impl(id)),
+        }
+      }
+      LayoutNode::Window(_) => false,
+    }
+  }
+
+  // Remove the empty splits, replace the splits with only one child by the child, and merge the
+  // splits in the same direction as the parent.
+  fn normalize(&mut self) {
+    if let LayoutNode::Split(split) = self {
+      let children = std::mem::take(&mut split.children);
+      for mut child in children {
+        child.normalize();
+        while let LayoutNode::Split(child_split) = &mut child {
+          if child_split.children.len() != 1 {
+            break;
+          }
+          let grandchild = child_split.children.pop().unwrap();
+          child = grandchild;
+        }
+        match child {
+          LayoutNode::Split(mut child_split) if child_split.direction == split.direction => {
+            split.children.append(&mut child_split.children);
+          }
+          LayoutNode::Split(child_split) if child_split.children.is_empty() => { /* Skip */ }
+          child => split.children.push(child),
+        }
+      }
+    }
+  }
+
+  /// Get the path (i.e. the child indexes from root) to the window.
+  pub fn path(&self, id: InodeId) -> Option<Vec<usize>> {
+    match self {
+      LayoutNode::Window(window_id) => (*window_id == id).then(Vec::new),
+      LayoutNode::Split(split) => split.children.iter().enumerate().find_map(|(i, child)| {
+        let mut path = child.path(id)?;
+        path.insert(0, i);
+        Some(path)
+      }),
+    }
+  }
+
+  /// Get the node at the `path`.
+  pub fn node_at(&self, path: &[usize]) -> Option<&LayoutNode> {
+    match path.split_first() {
+      None => Some(self),
+      Some((i, rest)) => match self {
+        LayoutNode::Split(split) => split.children.get(*i)?.node_at(rest),
+        LayoutNode::Window(_) => None,
+      },
+    }
+  }
+
+  /// Get the mutable node at the `path`.
+  pub fn node_at_mut(&mut self, path: &[usize]) -> Option<&mut LayoutNode> {
+    match path.split_first() {
+      None => Some(self),
+      Some((i, rest)) => match self {
+        LayoutNode::Split(split) => split.children.get_mut(*i)?.node_at_mut(rest),
+        LayoutNode::Window(_) => None,
+      },
+    }
+  }
+
+  /// Clear the percentage shares of all the splits, i.e. `Ctrl-W =`.
+  pub fn clear_percents(&mut self) {
+    if let LayoutNode::Split(split) = self {
+      split.percent = None;
+      split
+        .children
+        .iter_mut()
+        .for_each(|child| child.clear_percents());
+    }
+  }
+
+  /// Get the size constraint along the `direction`. For a split, it's the percentage share if
+  /// it's resized, otherwise the first non-flexible constraint of its windows, i.e. a sidebar in
+  /// the split.
+  pub fn constraint<F>(&self, direction: LayoutDirection, window_constraint: &F) -> SizeConstraint
+  where
+    F: Fn(InodeId, LayoutDirection) -> SizeConstraint,
+  {
+    match self {
+      LayoutNode::Window(id) => window_constraint(*id, direction),
+      LayoutNode::Split(split) => match split.percent {
+        Some(percent) => SizeConstraint::Percent(percent),
+        None => split
+          .children
+          .iter()
+          .map(|child| child.constraint(direction, window_constraint))
+          .find(|constraint| *constraint != SizeConstraint::Flex)
+          .unwrap_or(SizeConstraint::Flex),
+      },
+    }
+  }
+
+  /// Compute the windows' shapes in the `area`.
+  pub fn compute<F>(&self, area: IRect, window_constraint: &F) -> Vec<(InodeId, IRect)>
+  where
+    F: Fn(InodeId, LayoutDirection) -> SizeConstraint,
+  {
+    match self {
+      LayoutNode::Window(id) => vec![(*id, area)],
+      LayoutNode::Split(split) => {
+        let shapes = split.child_shapes(area, window_constraint);
+        split
+          .children
+          .iter()
+          .zip(shapes)
+          .flat_map(|(child, shape)| child.compute(shape, window_constraint))
+          .collect()
+      }
+    }
+  }
+
+  /// Compute the shape of the node at the `path` in the `area`.
+  pub fn shape_at<F>(&self, area: IRect, path: &[usize], window_constraint: &F) -> Option<IRect>
+  where
+    F: Fn(InodeId, LayoutDirection) -> SizeConstraint,
+  {
+    match path.split_first() {
+      None => Some(area),
+      Some((i, rest)) => match self {
+        LayoutNode::Split(split) => {
+          let shape = *split.child_shapes(area, window_constraint).get(*i)?;
+          split.children[*i].shape_at(shape, rest, window_constraint)
+        }
+        LayoutNode::Window(_) => None,
+      },
+    }
+  }
+}
+
+impl LayoutSplit {
+  fn child_shapes<F>(&self, area: IRect, window_constraint: &F) -> Vec<IRect>
+  where
+    F: Fn(InodeId, LayoutDirection) -> SizeConstraint,
+  {
+    let constraints = self
+      .children
+      .iter()
+      .map(|child| child.constraint(self.direction, window_constraint))
+      .collect::<Vec<_>>();
+    split(area, self.direction, &constraints)
+  }
+}
+
+/// Find the neighbor window of the window `from` in the `direction`, i.e. the window adjacent to
+/// its edge. If there're multiple, the one at the cursor position `pos` (or the nearest one) is
+/// preferred.
+pub fn neighbor(
+  shapes: &[(InodeId, IRect)],
+  from: InodeId,
+  pos: (isize, isize),
+  direction: FocusDirection,
+) -> Option<InodeId> {
+  let from_shape = shapes.iter().find(|(id, _)| *id == from)?.1;
+  let (min, max) = (from_shape.min(), from_shape.max());
+  shapes
+    .iter()
+    .filter(|(id, shape)| {
+      *id != from
+        && shape.width() > 0
+        && shape.height() > 0
+        && match direction {
+          FocusDirection::Left => shape.max().x == min.x,
+          FocusDirection::Right => shape.min().x == max.x,
+          FocusDirection::Up => shape.max().y == min.y,
+          FocusDirection::Down => shape.min().y == max.y,
+        }
+        && match direction {
+          FocusDirection::Left | FocusDirection::Right => {
+            shape.min().y < max.y && shape.max().y > min.y
+          }
+          FocusDirection::Up | FocusDirection::Down => {
+            shape.min().x < max.x && shape.max().x > min.x
+          }
+        }
+    })
+    .min_by_key(|(_, shape)| {
+      let (start, end, p) = match direction {
+        FocusDirection::Left | FocusDirection::Right => (shape.min().y, shape.max().y, pos.1),
+        FocusDirection::Up | FocusDirection::Down => (shape.min().x, shape.max().x, pos.0),
+      };
+      if p < start {
+        start - p
+      } else if p >= end {
+        p - end + 1
+      } else {
+        0
+      }
+    })
+    .map(|(id, _)| *id)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use LayoutDirection::{Column, Row};
+  use SizeConstraint::{Fixed, Flex, Percent};
+
+  #[test]
+  fn distribute1() {
+    assert_eq!(distribute(100, &[]), Vec::<u16>::new());
+    assert_eq!(distribute(100, &[Flex]), vec![100]);
+    assert_eq!(distribute(100, &[Fixed(30), Flex, Flex]), vec![30, 35, 35]);
+    assert_eq!(distribute(101, &[Flex, Flex]), vec![51, 50]);
+    assert_eq!(distribute(100, &[Percent(25), Flex]), vec![25, 75]);
+    // Percentage share persists across resizes.
+    assert_eq!(distribute(40, &[Percent(25), Flex]), vec![10, 30]);
+    // The remaining size goes to the last non-fixed window.
+    assert_eq!(distribute(100, &[Percent(50), Fixed(20)]), vec![80, 20]);
+    assert_eq!(distribute(100, &[Fixed(20), Fixed(30)]), vec![20, 80]);
+  }
+
+  #[test]
+  fn distribute2() {
+    // Shrink the percentage windows first.
+    assert_eq!(
+      distribute(30, &[Fixed(20), Percent(100), Flex]),
+      vec![20, 9, 1]
+    );
+    // Then the fixed windows.
+    assert_eq!(
+      distribute(10, &[Fixed(20), Percent(50), Flex]),
+      vec![8, 1, 1]
+    );
+    // Too small.
+    assert_eq!(distribute(2, &[Fixed(20), Flex, Flex]), vec![1, 1, 0]);
+  }
+
+  #[test]
+  fn split1() {
+    let area = IRect::new((0, 0), (10, 6));
+    assert_eq!(
+      split(area, LayoutDirection::Row, &[Fixed(3), Flex]),
+      vec![IRect::new((0, 0), (3, 6)), IRect::new((3, 0), (10, 6))]
+    );
+    assert_eq!(
+      split(area, LayoutDirection::Column, &[Flex, Percent(50)]),
+      vec![IRect::new((0, 0), (10, 3)), IRect::new((0, 3), (10, 6))]
+    );
+  }
+
+  #[test]
+  fn layout_node1() {
+    let mut root = LayoutNode::default();
+    root.push(1);
+    assert!(root.split(1, 2, Row));
+    assert!(root.split(1, 3, Column));
+    assert!(!root.split(9, 4, Column));
+    // Row[2, Column[3, 1]]
+    assert_eq!(root.window_ids(), vec![2, 3, 1]);
+    assert_eq!(root.path(1), Some(vec![1, 1]));
+    assert_eq!(root.node_at(&[0]), Some(&LayoutNode::Window(2)));
+    assert!(root.contains(3));
+
+    // Split in the same direction is merged.
+    assert!(root.split(2, 4, Row));
+    assert_eq!(root.window_ids(), vec![4, 2, 3, 1]);
+    assert_eq!(root.path(1), Some(vec![2, 1]));
+
+    // Remove and merge.
+    assert!(root.remove(3));
+    assert!(!root.remove(3));
+    assert_eq!(root.path(1), Some(vec![2]));
+    assert!(root.remove(4));
+    assert!(root.remove(2));
+    assert_eq!(root.window_ids(), vec![1]);
+    root.push(5);
+    assert_eq!(root.window_ids(), vec![1, 5]);
+    assert_eq!(root.path(5), Some(vec![1]));
+  }
+
+  #[test]
+  fn compute1() {
+    let mut root = LayoutNode::default();
+    root.push(1);
+    root.split(1, 2, Row);
+    root.split(1, 3, Column);
+    let constraint = |id: InodeId, direction: LayoutDirection| match (id, direction) {
+      (2, Row) => Fixed(4),
+      _ => Flex,
+    };
+    let area = IRect::new((0, 0), (10, 6));
+    assert_eq!(
+      root.compute(area, &constraint),
+      vec![
+        (2, IRect::new((0, 0), (4, 6))),
+        (3, IRect::new((4, 0), (10, 3))),
+        (1, IRect::new((4, 3), (10, 6))),
+      ]
+    );
+    let path = root.path(1).unwrap();
+    assert_eq!(
+      root.shape_at(area, &path[..1], &constraint),
+      Some(IRect::new((4, 0), (10, 6)))
+    );
+
+    // The resized split.
+    let flex = |_id: InodeId, _direction: LayoutDirection| Flex;
+    assert_eq!(
+      root.compute(area, &flex)[0],
+      (2, IRect::new((0, 0), (5, 6)))
+    );
+    if let Some(LayoutNode::Split(split)) = root.node_at_mut(&path[..1]) {
+      split.percent = Some(70);
+    }
+    assert_eq!(
+      root.compute(area, &flex)[0],
+      (2, IRect::new((0, 0), (3, 6)))
+    );
+    root.clear_percents();
+    assert_eq!(
+      root.compute(area, &flex)[0],
+      (2, IRect::new((0, 0), (5, 6)))
+    );
+  }
+
+  #[test]
+  fn neighbor1() {
+    // +---+---+
+    // | 1 | 2 |
+    // |   +---+
+    // |   | 3 |
+    // +---+---+
+    let shapes = vec![
+      (1, IRect::new((0, 0), (5, 6))),
+      (2, IRect::new((5, 0), (10, 3))),
+      (3, IRect::new((5, 3), (10, 6))),
+    ];
+    assert_eq!(neighbor(&shapes, 1, (0, 0), FocusDirection::Right), Some(2));
+    assert_eq!(neighbor(&shapes, 1, (0, 4), FocusDirection::Right), Some(3));
+    assert_eq!(neighbor(&shapes, 1, (0, 4), FocusDirection::Left), None);
+    assert_eq!(neighbor(&shapes, 3, (6, 4), FocusDirection::Left), Some(1));
+    assert_eq!(neighbor(&shapes, 3, (6, 4), FocusDirection::Up), Some(2));
+    assert_eq!(neighbor(&shapes, 2, (6, 1), FocusDirection::Down), Some(3));
+    assert_eq!(neighbor(&shapes, 2, (6, 1), FocusDirection::Up), None);
+  }
+}
@@ -5,10 +5,85 @@
 use crate::defaults;
 
 use regex::Regex;
+use std::fmt::Display;
+use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The 'jumpcenter' option, i.e. whether the viewport is centered on the target line of the
+/// absolute line jumps (`:{N}`, `{N}G`, `{N}gg`).
+pub enum JumpCenter {
+  /// Center the target line when it's not visible.
+  Auto,
+  /// Always center the target line.
+  Always,
+  /// Never center, the viewport scrolls minimally to show the target line.
+  Never,
+}
+
+impl JumpCenter {
+  /// Get the top line of the viewport that centers the `line_idx`, returns `None` if the viewport
+  /// doesn't need to be centered.
+  ///
+  /// The `visible` is the visible lines (left-inclusive, right-exclusive) of the viewport, the
+  /// viewport never scrolls past the end of the buffer.
+  pub fn start_line_idx(
+    &self,
+    line_idx: usize,
+    visible: Range<usize>,
+    height: usize,
+    len_lines: usize,
+  ) -> Option<usize> {
+    let center = match self {
+      JumpCenter::Auto => !visible.contains(&line_idx),
+      JumpCenter::Always => true,
+      JumpCenter::Never => false,
+    };
+    if !center {
+      return None;
+    }
+    let start_line_idx = line_idx.saturating_sub(height / 2);
+    Some(std::cmp::min(
+      start_line_idx,
+      len_lines.saturating_sub(height),
+    ))
+  }
+}
+
+impl Display for JumpCenter {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      JumpCenter::Auto => write!(f, "auto"),
+      JumpCenter::Always => write!(f, "always"),
+      JumpCenter::Never => write!(f, "never"),
+    }
+  }
+}
+
+impl FromStr for JumpCenter {
+  type Err = &'static str;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "auto" => Ok(JumpCenter::Auto),
+      "always" => Ok(JumpCenter::Always),
+      "never" => Ok(JumpCenter::Never),
+      _ => Err("Invalid 'jumpcenter' option value"),
+    }
+  }
+}
 
 #[derive(Debug, Clone)]
 /// Global window options.
-pub struct WindowGlobalOptions {}
+pub struct WindowGlobalOptions {
+  hlsearch: bool,
+  incsearch: bool,
+  autopairs: bool,
+  jump_center: JumpCenter,
+  quickfix_preview: bool,
+  title: bool,
+  titlestring: String,
+}
 
 impl Default for WindowGlobalOptions {
   fn default() -> Self {
@@ -20,15 +95,144 @@ impl WindowGlobalOptions {
   pub fn builder() -> WindowGlobalOptionsBuilder {
     WindowGlobalOptionsBuilder::default()
   }
+
+  /// The 'hlsearch' option, default to `false`.
+  /// See: <https://vimhelp.org/options.txt.html#%27hlsearch%27>.
+  pub fn hlsearch(&self) -> bool {
+    self.hlsearch
+  }
+
+  pub fn set_hlsearch(&mut self, value: bool) {
+    self.hlsearch = value;
+  }
+
+  /// The 'incsearch' option, default to `true`.
+  /// See: <https://vimhelp.org/options.txt.html#%27incsearch%27>.
+  pub fn incsearch(&self) -> bool {
+    self.incsearch
+  }
+
+  pub fn set_incsearch(&mut self, value: bool) {
+    self.incsearch = value;
+  }
+
+  /// The 'autopairs' option, default to `false`.
+  /// See: [`autopair`](crate::state::autopair).
+  pub fn autopairs(&self) -> bool {
+    self.autopairs
+  }
+
+  pub fn set_autopairs(&mut self, value: bool) {
+    self.autopairs = value;
+  }
+
+  /// The 'jumpcenter' option, default to `auto`.
+  /// See: [`JumpCenter`].
+  pub fn jump_center(&self) -> JumpCenter {
+    self.jump_center
+  }
+
+  pub fn set_jump_center(&mut self, value: JumpCenter) {
+    self.jump_center = value;
+  }
+
+  /// The 'quickfixpreview' option, default to `false`.
+  /// See: [`quickfix`](crate::state::ex::quickfix).
+  pub fn quickfix_preview(&self) -> bool {
+    self.quickfix_preview
+  }
+
+  pub fn set_quickfix_preview(&mut self, value: bool) {
+    self.quickfix_preview = value;
+  }
+
+  /// The 'title' option, default to `false`.
+  /// See: <https://vimhelp.org/options.txt.html#%27title%27>.
+  pub fn title(&self) -> bool {
+    self.title
+  }
+
+  pub fn set_title(&mut self, value: bool) {
+    self.title = value;
+  }
+
+  /// The 'titlestring' option, default to empty, i.e. use the
+  /// [`TITLE_FORMAT`](defaults::win::TITLE_FORMAT).
+  /// See: <https://vimhelp.org/options.txt.html#%27titlestring%27>.
+  pub fn titlestring(&self) -> &str {
+    &self.titlestring
+  }
+
+  pub fn set_titlestring(&mut self, value: &str) {
+    self.titlestring = value.to_string();
+  }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 /// Global window options builder.
-pub struct WindowGlobalOptionsBuilder {}
+pub struct WindowGlobalOptionsBuilder {
+  hlsearch: bool,
+  incsearch: bool,
+  autopairs: bool,
+  jump_center: JumpCenter,
+  quickfix_preview: bool,
+  title: bool,
+  titlestring: String,
+}
 
 impl WindowGlobalOptionsBuilder {
+  pub fn hlsearch(&mut self, value: bool) -> &mut Self {
+    self.hlsearch = value;
+    self
+  }
+  pub fn incsearch(&mut self, value: bool) -> &mut Self {
+    self.incsearch = value;
+    self
+  }
+  pub fn autopairs(&mut self, value: bool) -> &mut Self {
+    self.autopairs = value;
+    self
+  }
+  pub fn jump_center(&mut self, value: JumpCenter) -> &mut Self {
+    self.jump_center = value;
+    self
+  }
+  pub fn quickfix_preview(&mut self, value: bool) -> &mut Self {
+    self.quickfix_preview = value;
+    self
+  }
+  pub fn title(&mut self, value: bool) -> &mut Self {
+    self.title = value;
+    self
+  }
+  pub fn titlestring(&mut self, value: &str) -> &mut Self {
+    self.titlestring = value.to_string();
+    self
+  }
   pub fn build(&self) -> WindowGlobalOptions {
-    WindowGlobalOptions {}
+    WindowGlobalOptions {
+      hlsearch: self.hlsearch,
+      incsearch: self.incsearch,
+      autopairs: self.autopairs,
+      jump_center: self.jump_center,
+      quickfix_preview: self.quickfix_preview,
+      title: self.title,
+      titlestring: self.titlestring.clone(),
+    }
+  }
+}
+
+impl Default for WindowGlobalOptionsBuilder {
+  fn default() -> Self {
+    WindowGlobalOptionsBuilder {
+      hlsearch: defaults::win::HLSEARCH,
+      incsearch: defaults::win::INCSEARCH,
+      autopairs: defaults::win::AUTOPAIRS,
+      jump_center: defaults::win::JUMP_CENTER,
+      quickfix_preview: defaults::win::QUICKFIX_PREVIEW,
+      title: defaults::win::TITLE,
+      titlestring: defaults::win::TITLESTRING.to_string(),
+    }
   }
 }
 
@@ -38,7 +242,54 @@ mod tests {
 
   #[test]
   fn default1() {
-    let _opt1 = WindowGlobalOptions::builder().build();
-    let _opt2 = WindowGlobalOptionsBuilder::default().build();
+    let opt1 = WindowGlobalOptions::builder().build();
+    assert!(!opt1.hlsearch());
+    assert!(opt1.incsearch());
+    assert!(!opt1.autopairs());
+    assert!(!opt1.quickfix_preview());
+    assert!(!opt1.title());
+    assert_eq!(opt1.titlestring(), "");
+    let opt2 = WindowGlobalOptionsBuilder::default()
+      .hlsearch(true)
+      .incsearch(false)
+      .autopairs(true)
+      .quickfix_preview(true)
+      .title(true)
+      .titlestring("%f")
+      .build();
+    assert!(opt2.hlsearch());
+    assert!(!opt2.incsearch());
+    assert!(opt2.autopairs());
+    assert!(opt2.quickfix_preview());
+    assert!(opt2.title());
+    assert_eq!(opt2.titlestring(), "%f");
+    assert_eq!(opt1.jump_center(), JumpCenter::Auto);
+  }
+
+  #[test]
+  fn jump_center1() {
+    assert_eq!(JumpCenter::Auto.start_line_idx(15, 10..30, 20, 100), None);
+    assert_eq!(
+      JumpCenter::Auto.start_line_idx(50, 10..30, 20, 100),
+      Some(40)
+    );
+    assert_eq!(
+      JumpCenter::Always.start_line_idx(15, 10..30, 20, 100),
+      Some(5)
+    );
+    assert_eq!(
+      JumpCenter::Always.start_line_idx(3, 10..30, 20, 100),
+      Some(0)
+    );
+    // Don't scroll past the end.
+    assert_eq!(
+      JumpCenter::Always.start_line_idx(99, 10..30, 20, 100),
+      Some(80)
+    );
+    assert_eq!(JumpCenter::Always.start_line_idx(5, 0..10, 20, 10), Some(0));
+    assert_eq!(JumpCenter::Never.start_line_idx(50, 10..30, 20, 100), None);
+    assert_eq!("always".parse::<JumpCenter>(), Ok(JumpCenter::Always));
+    assert!("yes".parse::<JumpCenter>().is_err());
+    assert_eq!(JumpCenter::Never.to_string(), "never");
   }
 }
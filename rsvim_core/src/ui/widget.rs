@@ -5,12 +5,24 @@ use crate::ui::canvas::Canvas;
 // use tracing::trace;
 
 // Re-export
+pub use crate::ui::widget::cmdline::CommandLine;
 pub use crate::ui::widget::cursor::Cursor;
+pub use crate::ui::widget::float::FloatFrame;
+pub use crate::ui::widget::message::MessageBox;
+pub use crate::ui::widget::picker::Picker;
+pub use crate::ui::widget::progress::ProgressNotification;
 pub use crate::ui::widget::root::RootContainer;
+pub use crate::ui::widget::wildmenu::WildMenu;
 pub use crate::ui::widget::window::Window;
 
+pub mod cmdline;
 pub mod cursor;
+pub mod float;
+pub mod message;
+pub mod picker;
+pub mod progress;
 pub mod root;
+pub mod wildmenu;
 pub mod window;
 
 /// Base trait for all UI widgets.
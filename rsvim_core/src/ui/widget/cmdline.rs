@@ -0,0 +1,132 @@
+//! Command-line widget.
+//!
+//! The command-line is a one-row box placed at the bottom of the terminal, it shows the prompt
+//! (i.e. `/` and `?` for search) and the typed text in command-line mode. The cursor in the text
+//! is drawn as the reversed cell, since the terminal cursor stays in current window.
+
+use crate::cart::{IRect, U16Pos, U16Rect, U16Size};
+use crate::inode_generate_impl;
+use crate::ui::canvas::Canvas;
+use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::picker::make_styled_cells;
+use crate::ui::widget::Widgetable;
+
+use crossterm::style::Attribute;
+use geo::point;
+use unicode_width::UnicodeWidthStr;
+
+/// The z-index of the command-line, it floats on top of the windows and the progress
+/// notification, but below the picker.
+pub const CMDLINE_ZINDEX: usize = 95;
+
+/// Calculate the command-line shape, i.e. the last row of the terminal.
+pub fn bottom_shape(terminal_size: U16Size) -> IRect {
+  let width = terminal_size.width() as isize;
+  let height = terminal_size.height() as isize;
+  IRect::new((0, (height - 1).max(0)), (width, height))
+}
+
+#[derive(Debug, Clone)]
+/// The command-line widget.
+pub struct CommandLine {
+  base: InodeBase,
+
+  // The prompt, i.e. `/`.
+  prompt: String,
+
+  // The typed text.
+  text: String,
+
+  // The cursor position in the text, i.e. the byte offset.
+  cursor: usize,
+}
+
+impl CommandLine {
+  pub fn new(shape: IRect, prompt: &str) -> Self {
+    let mut base = InodeBase::new(shape);
+    *base.zindex_mut() = CMDLINE_ZINDEX;
+    CommandLine {
+      base,
+      prompt: prompt.to_string(),
+      text: String::new(),
+      cursor: 0,
+    }
+  }
+
+  pub fn prompt(&self) -> &str {
+    &self.prompt
+  }
+
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  /// Set the typed text, the cursor is moved to the end.
+  pub fn set_text(&mut self, text: &str) {
+    self.text = text.to_string();
+    self.cursor = self.text.len();
+  }
+
+  pub fn cursor(&self) -> usize {
+    self.cursor
+  }
+
+  /// Set the cursor position in the text, i.e. the byte offset. It's clamped to the end of text.
+  pub fn set_cursor(&mut self, cursor: usize) {
+    self.cursor = cursor.min(self.text.len());
+  }
+
+  /// Get the display column of the cursor, i.e. after the prompt.
+  pub fn cursor_column(&self) -> usize {
+    let prefix = self.text.get(..self.cursor).unwrap_or(&self.text);
+    self.prompt.width() + prefix.width()
+  }
+}
+
+inode_generate_impl!(CommandLine, base);
+
+impl Widgetable for CommandLine {
+  fn draw(&self, canvas: &mut Canvas) {
+    let actual_shape = self.actual_shape();
+    if actual_shape.height() == 0 {
+      return;
+    }
+    let upos: U16Pos = actual_shape.min().into();
+    let line = format!("{}{}", self.prompt, self.text);
+    let mut cells = make_styled_cells(
+      &line,
+      actual_shape.width() as usize,
+      &canvas.highlight("MsgArea"),
+    );
+    if let Some(cell) = cells.get_mut(self.cursor_column()) {
+      let mut attrs = cell.attrs();
+      attrs.toggle(Attribute::Reverse);
+      cell.set_attrs(attrs);
+    }
+    canvas
+      .frame_mut()
+      .set_cells_at(point!(x: upos.x(), y: upos.y()), cells);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn bottom_shape1() {
+    let actual = bottom_shape(U16Size::new(20, 10));
+    assert_eq!(actual, IRect::new((0, 9), (20, 10)));
+  }
+
+  #[test]
+  fn cursor_column1() {
+    let mut cmdline = CommandLine::new(bottom_shape(U16Size::new(20, 10)), ":");
+    cmdline.set_text("e 中文");
+    assert_eq!(cmdline.cursor_column(), 7);
+    cmdline.set_cursor(2);
+    assert_eq!(cmdline.cursor_column(), 3);
+    cmdline.set_cursor(100);
+    assert_eq!(cmdline.cursor(), 8);
+  }
+}
@@ -0,0 +1,371 @@
+//! Floating window frame widget.
+//!
+//! A floating window is a normal [`Window`](crate::ui::widget::Window) placed on top of the tiled
+//! windows with a higher z-index, the border and the shadow around it are drawn by a separated
+//! frame widget, which is placed right below the floating window (with the same z-index but
+//! inserted earlier). For example:
+//!
+//! ```text
+//! ┌Title─────────┐
+//! │window content│▒
+//! │...           │▒
+//! └──────────────┘▒
+//!  ▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒▒
+//! ```
+//!
+//! Thus the window (and its viewport) works unchanged inside the float's shape.
+
+use crate::cart::{IRect, U16Pos, U16Rect, U16Size};
+use crate::inode_generate_impl;
+use crate::ui::canvas::{Canvas, Cell};
+use crate::ui::highlight::apply_style;
+use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::picker::make_styled_cells;
+use crate::ui::widget::Widgetable;
+
+use geo::point;
+use std::fmt::Display;
+
+/// The default z-index of the floating windows, they float on top of the tiled windows, but below
+/// the progress notification, command-line and picker.
+pub const FLOAT_ZINDEX: usize = 50;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The anchor position of a floating window, the `row`/`col` offsets are relative to it.
+pub enum FloatAnchor {
+  /// The top-left corner of the terminal.
+  Editor,
+  /// The top-left corner of current window.
+  Window,
+  /// The cursor position.
+  Cursor,
+}
+
+impl Display for FloatAnchor {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      FloatAnchor::Editor => write!(f, "editor"),
+      FloatAnchor::Window => write!(f, "window"),
+      FloatAnchor::Cursor => write!(f, "cursor"),
+    }
+  }
+}
+
+impl TryFrom<&str> for FloatAnchor {
+  type Error = String;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    match value.to_lowercase().as_str() {
+      "editor" => Ok(FloatAnchor::Editor),
+      "window" | "win" => Ok(FloatAnchor::Window),
+      "cursor" => Ok(FloatAnchor::Cursor),
+      _ => Err(format!("Unknown float anchor: {}", value)),
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The border style of a floating window.
+pub enum FloatBorder {
+  None,
+  Single,
+  Double,
+  Rounded,
+}
+
+impl FloatBorder {
+  /// Get the border chars, i.e. top-left, top-right, bottom-left, bottom-right, horizontal and
+  /// vertical.
+  pub fn chars(&self) -> Option<[char; 6]> {
+    match self {
+      FloatBorder::None => None,
+      FloatBorder::Single => Some(['┌', '┐', '└', '┘', '─', '│']),
+      FloatBorder::Double => Some(['╔', '╗', '╚', '╝', '═', '║']),
+      FloatBorder::Rounded => Some(['╭', '╮', '╰', '╯', '─', '│']),
+    }
+  }
+
+  /// The border width on each side.
+  pub fn width(&self) -> isize {
+    match self {
+      FloatBorder::None => 0,
+      _ => 1,
+    }
+  }
+}
+
+impl Display for FloatBorder {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      FloatBorder::None => write!(f, "none"),
+      FloatBorder::Single => write!(f, "single"),
+      FloatBorder::Double => write!(f, "double"),
+      FloatBorder::Rounded => write!(f, "rounded"),
+    }
+  }
+}
+
+impl TryFrom<&str> for FloatBorder {
+  type Error = String;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    match value.to_lowercase().as_str() {
+      "none" => Ok(FloatBorder::None),
+      "single" => Ok(FloatBorder::Single),
+      "double" => Ok(FloatBorder::Double),
+      "rounded" => Ok(FloatBorder::Rounded),
+      _ => Err(format!("Unknown float border: {}", value)),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The floating window config.
+pub struct FloatConfig {
+  /// Anchor position.
+  pub anchor: FloatAnchor,
+  /// Rows offset from the anchor position (to the top-left corner of the border).
+  pub row: isize,
+  /// Columns offset from the anchor position (to the top-left corner of the border).
+  pub col: isize,
+  /// Width of the window content, exclude the border and shadow.
+  pub width: u16,
+  /// Height of the window content, exclude the border and shadow.
+  pub height: u16,
+  /// Z-index.
+  pub zindex: usize,
+  /// Border style.
+  pub border: FloatBorder,
+  /// Whether draws the shadow on the right and bottom side.
+  pub shadow: bool,
+  /// Title on the top border.
+  pub title: String,
+}
+
+impl Default for FloatConfig {
+  fn default() -> Self {
+    FloatConfig {
+      anchor: FloatAnchor::Editor,
+      row: 0,
+      col: 0,
+      width: 40,
+      height: 10,
+      zindex: FLOAT_ZINDEX,
+      border: FloatBorder::Single,
+      shadow: false,
+      title: String::new(),
+    }
+  }
+}
+
+/// Calculate the frame shape and the window shape of a floating window, the `anchor_pos` is the
+/// absolute position `(x, y)` of the anchor.
+///
+/// The frame is moved (or shrunk) to fit inside the terminal, thus the border and shadow are
+/// always visible.
+pub fn float_shapes(
+  config: &FloatConfig,
+  anchor_pos: (isize, isize),
+  terminal_size: U16Size,
+) -> (IRect, IRect) {
+  let border = config.border.width();
+  let shadow = if config.shadow { 1 } else { 0 };
+  let terminal_width = terminal_size.width() as isize;
+  let terminal_height = terminal_size.height() as isize;
+
+  let width = (config.width as isize)
+    .min(terminal_width - border * 2 - shadow)
+    .max(1);
+  let height = (config.height as isize)
+    .min(terminal_height - border * 2 - shadow)
+    .max(1);
+  let frame_width = width + border * 2 + shadow;
+  let frame_height = height + border * 2 + shadow;
+  let x = (anchor_pos.0 + config.col).clamp(0, (terminal_width - frame_width).max(0));
+  let y = (anchor_pos.1 + config.row).clamp(0, (terminal_height - frame_height).max(0));
+
+  let frame_shape = IRect::new((x, y), (x + frame_width, y + frame_height));
+  let window_shape = IRect::new(
+    (x + border, y + border),
+    (x + border + width, y + border + height),
+  );
+  (frame_shape, window_shape)
+}
+
+#[derive(Debug, Clone)]
+/// The floating window frame widget, i.e. the border and shadow.
+pub struct FloatFrame {
+  base: InodeBase,
+
+  // Border style.
+  border: FloatBorder,
+
+  // Whether draws the shadow.
+  shadow: bool,
+
+  // Title on the top border.
+  title: String,
+}
+
+impl FloatFrame {
+  pub fn new(shape: IRect, config: &FloatConfig) -> Self {
+    let mut base = InodeBase::new(shape);
+    *base.zindex_mut() = config.zindex;
+    FloatFrame {
+      base,
+      border: config.border,
+      shadow: config.shadow,
+      title: config.title.clone(),
+    }
+  }
+
+  pub fn border(&self) -> FloatBorder {
+    self.border
+  }
+
+  pub fn shadow(&self) -> bool {
+    self.shadow
+  }
+
+  pub fn title(&self) -> &str {
+    &self.title
+  }
+}
+
+inode_generate_impl!(FloatFrame, base);
+
+impl Widgetable for FloatFrame {
+  fn draw(&self, canvas: &mut Canvas) {
+    let actual_shape = self.actual_shape();
+    let upos: U16Pos = actual_shape.min().into();
+    let shadow = if self.shadow { 1 } else { 0 };
+    let width = actual_shape.width().saturating_sub(shadow);
+    let height = actual_shape.height().saturating_sub(shadow);
+
+    // Border.
+    if let Some([tl, tr, bl, br, h, v]) = self.border.chars() {
+      if width >= 2 && height >= 2 {
+        let inner_width = (width - 2) as usize;
+        let border_style = canvas.highlight("FloatBorder");
+        let border = |c: char| {
+          let mut cell = Cell::from(c);
+          apply_style(&mut cell, &border_style);
+          cell
+        };
+
+        let mut cells = vec![border(tl)];
+        cells.extend(make_styled_cells(
+          &format!("{}{}", self.title, h.to_string().repeat(inner_width)),
+          inner_width,
+          &border_style,
+        ));
+        cells.push(border(tr));
+        canvas.frame_mut().set_cells_at(upos, cells);
+
+        for y in 1..height - 1 {
+          canvas
+            .frame_mut()
+            .set_cell(point!(x: upos.x(), y: upos.y() + y), border(v));
+          canvas
+            .frame_mut()
+            .set_cell(point!(x: upos.x() + width - 1, y: upos.y() + y), border(v));
+        }
+
+        let mut cells = vec![border(bl)];
+        cells.extend((0..inner_width).map(|_| border(h)));
+        cells.push(border(br));
+        canvas
+          .frame_mut()
+          .set_cells_at(point!(x: upos.x(), y: upos.y() + height - 1), cells);
+      }
+    }
+
+    // Shadow, i.e. darken the cells below it on the right and bottom side.
+    if self.shadow {
+      let shadow_style = canvas.highlight_layer("FloatShadow");
+      let right = (1..actual_shape.height()).map(|y| (width, y));
+      let bottom = (1..width).map(|x| (x, height));
+      for (x, y) in right.chain(bottom) {
+        let pos = point!(x: upos.x() + x, y: upos.y() + y);
+        if let Some(cell) = canvas.frame().try_get_cell(pos) {
+          let mut cell = cell.clone();
+          apply_style(&mut cell, &shadow_style);
+          canvas.frame_mut().set_cell(pos, cell);
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use crossterm::style::Color;
+
+  #[test]
+  fn try_from1() {
+    assert_eq!(FloatAnchor::try_from("Cursor"), Ok(FloatAnchor::Cursor));
+    assert!(FloatAnchor::try_from("mouse").is_err());
+    assert_eq!(FloatBorder::try_from("rounded"), Ok(FloatBorder::Rounded));
+    assert_eq!(format!("{}", FloatBorder::Double), "double");
+  }
+
+  #[test]
+  fn float_shapes1() {
+    let terminal_size = U16Size::new(30, 10);
+    let config = FloatConfig {
+      row: 2,
+      col: 3,
+      width: 10,
+      height: 4,
+      shadow: true,
+      ..Default::default()
+    };
+    let (frame, window) = float_shapes(&config, (0, 0), terminal_size);
+    assert_eq!(frame, IRect::new((3, 2), (16, 9)));
+    assert_eq!(window, IRect::new((4, 3), (14, 7)));
+
+    // Moved to fit inside the terminal.
+    let (frame, window) = float_shapes(&config, (25, 5), terminal_size);
+    assert_eq!(frame, IRect::new((17, 3), (30, 10)));
+    assert_eq!(window, IRect::new((18, 4), (28, 8)));
+
+    // Shrunk to fit inside the terminal.
+    let config = FloatConfig {
+      width: 100,
+      height: 4,
+      border: FloatBorder::None,
+      ..Default::default()
+    };
+    let (frame, window) = float_shapes(&config, (5, 0), terminal_size);
+    assert_eq!(frame, IRect::new((0, 0), (30, 4)));
+    assert_eq!(window, frame);
+  }
+
+  #[test]
+  fn draw1() {
+    let terminal_size = U16Size::new(8, 5);
+    let config = FloatConfig {
+      shadow: true,
+      title: "T".to_string(),
+      border: FloatBorder::Rounded,
+      ..Default::default()
+    };
+    let mut frame = FloatFrame::new(IRect::new((0, 0), (8, 5)), &config);
+    *frame.actual_shape_mut() = geo::Rect::new((0_u16, 0_u16), (8_u16, 5_u16));
+    let mut canvas = Canvas::new(terminal_size);
+    frame.draw(&mut canvas);
+    let actual = canvas
+      .frame()
+      .raw_symbols()
+      .iter()
+      .map(|cs| cs.join(""))
+      .collect::<Vec<_>>();
+    assert_eq!(actual, vec!["╭T────╮", "││", "││", "╰─────╯", ""]);
+    let shadow = canvas.frame().get_cell(point!(x: 7_u16, y: 4_u16));
+    assert_eq!(shadow.bg(), Color::Black);
+    let shadow = canvas.frame().get_cell(point!(x: 0_u16, y: 4_u16));
+    assert_eq!(shadow.bg(), Color::Reset);
+  }
+}
@@ -0,0 +1,136 @@
+//! Message widget.
+//!
+//! The message box is placed at the bottom of the terminal, it shows the messages since the last
+//! typed key (see [`Messages`](crate::state::msg::Messages)). A single short message takes only
+//! the last row, i.e. the command-line row. The multi-line (or too long) messages take more rows
+//! above it, with a press-enter prompt on the last row, thus they don't overwrite the statusline
+//! row of the window below.
+
+use crate::cart::{IRect, U16Pos, U16Rect, U16Size};
+use crate::inode_generate_impl;
+use crate::state::msg::MessageLevel;
+use crate::ui::canvas::Canvas;
+use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::picker::make_styled_cells;
+use crate::ui::widget::Widgetable;
+
+use geo::point;
+
+/// The z-index of the message box, it floats on top of the command-line and wildmenu, but below
+/// the picker.
+pub const MESSAGE_ZINDEX: usize = 97;
+
+/// The prompt on the last row of the multi-line messages.
+pub const PRESS_ENTER_PROMPT: &str = "Press ENTER or type command to continue";
+
+/// Calculate the message box shape with the `height` rows at the bottom of the terminal.
+pub fn bottom_shape(terminal_size: U16Size, height: u16) -> IRect {
+  let width = terminal_size.width() as isize;
+  let terminal_height = terminal_size.height() as isize;
+  let height = (height.min(terminal_size.height()) as isize).max(1);
+  IRect::new(
+    (0, (terminal_height - height).max(0)),
+    (width, terminal_height),
+  )
+}
+
+/// The highlight group of the message level.
+pub fn level_highlight_group(level: MessageLevel) -> &'static str {
+  match level {
+    MessageLevel::Info => "MsgArea",
+    MessageLevel::Warn => "WarningMsg",
+    MessageLevel::Error => "ErrorMsg",
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The message box widget.
+pub struct MessageBox {
+  base: InodeBase,
+
+  // The message lines and their levels.
+  lines: Vec<(MessageLevel, String)>,
+
+  // Whether the press-enter prompt is shown on the last row.
+  press_enter: bool,
+}
+
+impl MessageBox {
+  pub fn new(shape: IRect) -> Self {
+    let mut base = InodeBase::new(shape);
+    *base.zindex_mut() = MESSAGE_ZINDEX;
+    MessageBox {
+      base,
+      lines: vec![],
+      press_enter: false,
+    }
+  }
+
+  pub fn lines(&self) -> &Vec<(MessageLevel, String)> {
+    &self.lines
+  }
+
+  pub fn set_lines(&mut self, lines: Vec<(MessageLevel, String)>) {
+    self.lines = lines;
+  }
+
+  pub fn press_enter(&self) -> bool {
+    self.press_enter
+  }
+
+  pub fn set_press_enter(&mut self, value: bool) {
+    self.press_enter = value;
+  }
+}
+
+inode_generate_impl!(MessageBox, base);
+
+impl Widgetable for MessageBox {
+  fn draw(&self, canvas: &mut Canvas) {
+    let actual_shape = self.actual_shape();
+    let height = actual_shape.height() as usize;
+    if height == 0 {
+      return;
+    }
+    let upos: U16Pos = actual_shape.min().into();
+    let width = actual_shape.width() as usize;
+
+    // The last lines are shown if there's not enough room.
+    let rows = if self.press_enter { height - 1 } else { height };
+    let skip = self.lines.len().saturating_sub(rows);
+    for (i, (level, line)) in self.lines.iter().skip(skip).enumerate() {
+      let style = canvas.highlight(level_highlight_group(*level));
+      let cells = make_styled_cells(line, width, &style);
+      canvas
+        .frame_mut()
+        .set_cells_at(point!(x: upos.x(), y: upos.y() + i as u16), cells);
+    }
+    if self.press_enter {
+      let cells = make_styled_cells(PRESS_ENTER_PROMPT, width, &canvas.highlight("MoreMsg"));
+      canvas
+        .frame_mut()
+        .set_cells_at(point!(x: upos.x(), y: upos.y() + height as u16 - 1), cells);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn bottom_shape1() {
+    assert_eq!(
+      bottom_shape(U16Size::new(20, 10), 1),
+      IRect::new((0, 9), (20, 10))
+    );
+    assert_eq!(
+      bottom_shape(U16Size::new(20, 10), 4),
+      IRect::new((0, 6), (20, 10))
+    );
+    assert_eq!(
+      bottom_shape(U16Size::new(20, 10), 30),
+      IRect::new((0, 0), (20, 10))
+    );
+  }
+}
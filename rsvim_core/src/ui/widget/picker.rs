@@ -0,0 +1,358 @@
+//! Picker widget.
+//!
+//! The picker is a floating box placed at the center of the terminal, it shows a query line and a
+//! list of rows (each row has a label and a description), and highlights the selected row. For
+//! example:
+//!
+//! ```text
+//! ┌Command Palette───────────────┐
+//! │> ali                         │
+//! │align   Align lines on the... │
+//! │...                           │
+//! └──────────────────────────────┘
+//! ```
+//!
+//! The picker widget only renders the contents, the filtering and selecting are maintained by the
+//! editing state, see [`CommandPalette`](crate::state::palette::CommandPalette) and
+//! [`Finder`](crate::state::finder::Finder). The fuzzy matched chars of the label are highlighted
+//! with the `PmenuMatch` group.
+
+use crate::buf::highlight::HighlightStyle;
+use crate::cart::{IRect, U16Pos, U16Rect, U16Size};
+use crate::inode_generate_impl;
+use crate::ui::canvas::{Canvas, Cell};
+use crate::ui::highlight::apply_style;
+use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::Widgetable;
+
+use compact_str::{CompactString, ToCompactString};
+use crossterm::style::{Attributes, Color};
+use geo::point;
+use unicode_width::UnicodeWidthChar;
+
+/// The max width of the picker.
+pub const PICKER_MAX_WIDTH: u16 = 80;
+
+/// The max rows (include the border and query line) of the picker.
+pub const PICKER_MAX_HEIGHT: u16 = 20;
+
+/// The z-index of the picker, it floats on top of the windows.
+pub const PICKER_ZINDEX: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The row in picker.
+pub struct PickerRow {
+  pub label: String,
+  pub description: String,
+  /// The char indexes of the matched chars in the label.
+  pub matches: Vec<usize>,
+}
+
+impl PickerRow {
+  pub fn new(label: &str, description: &str) -> Self {
+    PickerRow {
+      label: label.to_string(),
+      description: description.to_string(),
+      matches: vec![],
+    }
+  }
+
+  /// Set the matched chars to highlight, see [`fuzzy_match`](crate::state::fuzzy::fuzzy_match).
+  pub fn with_matches(mut self, matches: Vec<usize>) -> Self {
+    self.matches = matches;
+    self
+  }
+}
+
+/// Calculate the centered picker shape in the terminal.
+pub fn centered_shape(terminal_size: U16Size) -> IRect {
+  let width = terminal_size
+    .width()
+    .saturating_sub(4)
+    .min(PICKER_MAX_WIDTH) as isize;
+  let height = terminal_size
+    .height()
+    .saturating_sub(2)
+    .min(PICKER_MAX_HEIGHT) as isize;
+  let x = (terminal_size.width() as isize - width) / 2;
+  let y = (terminal_size.height() as isize - height) / 2;
+  IRect::new((x, y), (x + width, y + height))
+}
+
+/// Make cells for the `text` within the `width`, the text is truncated or padded with spaces.
+pub fn make_cells(text: &str, width: usize, fg: Color, attrs: Attributes) -> Vec<Cell> {
+  let mut cells = vec![];
+  let mut occupied = 0_usize;
+  for c in text.chars() {
+    let c = if c.is_control() { ' ' } else { c };
+    let c_width = UnicodeWidthChar::width(c).unwrap_or(0);
+    if c_width == 0 {
+      continue;
+    }
+    if occupied + c_width > width {
+      break;
+    }
+    cells.push(Cell::new(c.to_compact_string(), fg, Color::Reset, attrs));
+    for _ in 1..c_width {
+      cells.push(Cell::new(
+        CompactString::const_new(""),
+        fg,
+        Color::Reset,
+        attrs,
+      ));
+    }
+    occupied += c_width;
+  }
+  while occupied < width {
+    cells.push(Cell::new(" ".to_compact_string(), fg, Color::Reset, attrs));
+    occupied += 1;
+  }
+  cells
+}
+
+/// Make cells for the `text` within the `width` with the highlight `style`, the unspecified
+/// colors are reset.
+pub fn make_styled_cells(text: &str, width: usize, style: &HighlightStyle) -> Vec<Cell> {
+  let mut cells = make_cells(text, width, style.fg.unwrap_or(Color::Reset), style.attrs);
+  if let Some(bg) = style.bg {
+    cells.iter_mut().for_each(|c| c.set_bg(bg));
+  }
+  cells
+}
+
+// Get the cell indexes of the matched chars in the `label`, i.e. the wide chars occupy 2 cells.
+fn matched_cells(label: &str, matches: &[usize]) -> Vec<usize> {
+  let mut result = vec![];
+  let mut cell_idx = 0_usize;
+  for (char_idx, c) in label.chars().enumerate() {
+    let c = if c.is_control() { ' ' } else { c };
+    let width = UnicodeWidthChar::width(c).unwrap_or(0);
+    if width > 0 && matches.contains(&char_idx) {
+      result.push(cell_idx);
+    }
+    cell_idx += width;
+  }
+  result
+}
+
+#[derive(Debug, Clone)]
+/// The picker widget.
+pub struct Picker {
+  base: InodeBase,
+
+  // Title on the top border.
+  title: CompactString,
+
+  // Query line.
+  query: String,
+
+  // Rows.
+  rows: Vec<PickerRow>,
+
+  // Selected row index.
+  selected: Option<usize>,
+}
+
+impl Picker {
+  pub fn new(shape: IRect, title: &str) -> Self {
+    let mut base = InodeBase::new(shape);
+    *base.zindex_mut() = PICKER_ZINDEX;
+    Picker {
+      base,
+      title: CompactString::new(title),
+      query: String::new(),
+      rows: vec![],
+      selected: None,
+    }
+  }
+
+  pub fn title(&self) -> &str {
+    &self.title
+  }
+
+  /// Set the title, i.e. the finder shows the count of the matched items.
+  pub fn set_title(&mut self, title: &str) {
+    self.title = CompactString::new(title);
+  }
+
+  pub fn query(&self) -> &str {
+    &self.query
+  }
+
+  pub fn rows(&self) -> &Vec<PickerRow> {
+    &self.rows
+  }
+
+  pub fn selected(&self) -> Option<usize> {
+    self.selected
+  }
+
+  /// Set the query line, rows and selected row.
+  pub fn set_contents(&mut self, query: &str, rows: Vec<PickerRow>, selected: Option<usize>) {
+    self.query = query.to_string();
+    self.rows = rows;
+    self.selected = selected;
+  }
+
+  /// Max rows count can be shown.
+  pub fn visible_rows(&self) -> usize {
+    // Exclude the top/bottom border and the query line.
+    self.actual_shape().height().saturating_sub(3) as usize
+  }
+
+  // The first row shown in the picker, it scrolls to keep the selected row visible.
+  fn first_row(&self) -> usize {
+    let visible_rows = self.visible_rows();
+    match self.selected {
+      Some(selected) if visible_rows > 0 && selected >= visible_rows => selected + 1 - visible_rows,
+      _ => 0,
+    }
+  }
+}
+
+inode_generate_impl!(Picker, base);
+
+impl Widgetable for Picker {
+  fn draw(&self, canvas: &mut Canvas) {
+    let actual_shape = self.actual_shape();
+    let upos: U16Pos = actual_shape.min().into();
+    let height = actual_shape.height();
+    let width = actual_shape.width();
+
+    // Too small to draw the border and query line.
+    if height < 3 || width < 3 {
+      return;
+    }
+    let inner_width = (width - 2) as usize;
+    let border_style = canvas.highlight("FloatBorder");
+    let normal_style = canvas.highlight("Pmenu");
+    let selected_style = canvas.highlight("PmenuSel");
+    let match_style = canvas.highlight("PmenuMatch");
+    let border = |c: char| {
+      let mut cell = Cell::from(c);
+      apply_style(&mut cell, &border_style);
+      cell
+    };
+
+    // Top border with title.
+    let mut cells = vec![border('┌')];
+    cells.extend(make_styled_cells(
+      &format!("{:─<1$}", self.title, inner_width),
+      inner_width,
+      &border_style,
+    ));
+    cells.push(border('┐'));
+    canvas.frame_mut().set_cells_at(upos, cells);
+
+    // Query line.
+    let mut cells = vec![border('│')];
+    cells.extend(make_styled_cells(
+      &format!("> {}", self.query),
+      inner_width,
+      &normal_style,
+    ));
+    cells.push(border('│'));
+    canvas
+      .frame_mut()
+      .set_cells_at(point!(x: upos.x(), y: upos.y() + 1), cells);
+
+    // Rows.
+    let label_width = self
+      .rows
+      .iter()
+      .map(|row| row.label.chars().count())
+      .max()
+      .unwrap_or(0);
+    let first_row = self.first_row();
+    for i in 0..self.visible_rows() {
+      let row_idx = first_row + i;
+      let (text, style, matches) = match self.rows.get(row_idx) {
+        Some(row) => {
+          let text = format!("{:<2$}  {}", row.label, row.description, label_width);
+          let style = if self.selected == Some(row_idx) {
+            &selected_style
+          } else {
+            &normal_style
+          };
+          (text, style, matched_cells(&row.label, &row.matches))
+        }
+        None => (String::new(), &normal_style, vec![]),
+      };
+      let mut cells = vec![border('│')];
+      let mut row_cells = make_styled_cells(&text, inner_width, style);
+      for cell_idx in matches {
+        if let Some(cell) = row_cells.get_mut(cell_idx) {
+          if let Some(fg) = match_style.fg {
+            cell.set_fg(fg);
+          }
+          let mut attrs = cell.attrs();
+          attrs.extend(match_style.attrs);
+          cell.set_attrs(attrs);
+        }
+      }
+      cells.extend(row_cells);
+      cells.push(border('│'));
+      canvas
+        .frame_mut()
+        .set_cells_at(point!(x: upos.x(), y: upos.y() + 2 + i as u16), cells);
+    }
+
+    // Bottom border.
+    let mut cells = vec![border('└')];
+    cells.extend((0..inner_width).map(|_| border('─')));
+    cells.push(border('┘'));
+    canvas
+      .frame_mut()
+      .set_cells_at(point!(x: upos.x(), y: upos.y() + height - 1), cells);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn centered_shape1() {
+    let actual = centered_shape(U16Size::new(100, 40));
+    assert_eq!(actual, IRect::new((10, 10), (90, 30)));
+    let actual = centered_shape(U16Size::new(20, 10));
+    assert_eq!(actual, IRect::new((2, 1), (18, 9)));
+  }
+
+  #[test]
+  fn draw1() {
+    let terminal_size = U16Size::new(20, 6);
+    let mut picker = Picker::new(IRect::new((0, 0), (20, 6)), "Pick");
+    *picker.actual_shape_mut() = geo::Rect::new((0_u16, 0_u16), (20_u16, 6_u16));
+    picker.set_contents(
+      "a",
+      vec![
+        PickerRow::new("align", "Align"),
+        PickerRow::new("ab", "AB"),
+        PickerRow::new("abc", "ABC"),
+        PickerRow::new("abcd", "ABCD"),
+      ],
+      Some(3),
+    );
+    assert_eq!(picker.visible_rows(), 3);
+    let mut canvas = Canvas::new(terminal_size);
+    picker.draw(&mut canvas);
+    let actual = canvas
+      .frame()
+      .raw_symbols()
+      .iter()
+      .map(|cs| cs.join(""))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      actual,
+      vec![
+        "┌Pick──────────────┐",
+        "│> a               │",
+        "│ab     AB         │",
+        "│abc    ABC        │",
+        "│abcd   ABCD       │",
+        "└──────────────────┘",
+      ]
+    );
+  }
+}
@@ -0,0 +1,139 @@
+//! Progress notification widget.
+//!
+//! The notification is a floating box placed at the bottom-right corner of the terminal, it shows
+//! the running tasks' progress, one line for each task. The lines are maintained by the editing
+//! state, see [`ProgressManager`](crate::state::progress::ProgressManager).
+
+use crate::cart::{IRect, U16Pos, U16Rect, U16Size};
+use crate::inode_generate_impl;
+use crate::state::progress::{ProgressId, CANCEL_BUTTON};
+use crate::ui::canvas::Canvas;
+use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::picker::make_styled_cells;
+use crate::ui::widget::Widgetable;
+
+use geo::point;
+use unicode_width::UnicodeWidthStr;
+
+/// The max lines of the notification, the other tasks are not shown.
+pub const PROGRESS_MAX_HEIGHT: u16 = 5;
+
+/// The z-index of the notification, it floats on top of the windows, but below the picker.
+pub const PROGRESS_ZINDEX: usize = 90;
+
+/// Calculate the notification shape at the bottom-right corner of the terminal, it's wide enough
+/// for the longest line.
+pub fn bottom_right_shape(terminal_size: U16Size, lines: &[(ProgressId, String)]) -> IRect {
+  let width = lines
+    .iter()
+    .map(|(_, line)| UnicodeWidthStr::width(line.as_str()))
+    .max()
+    .unwrap_or(0)
+    .min(terminal_size.width() as usize) as isize;
+  let height = (lines.len() as u16)
+    .min(PROGRESS_MAX_HEIGHT)
+    .min(terminal_size.height()) as isize;
+  let x = terminal_size.width() as isize - width;
+  let y = terminal_size.height() as isize - height;
+  IRect::new((x, y), (x + width, y + height))
+}
+
+#[derive(Debug, Clone)]
+/// The progress notification widget.
+pub struct ProgressNotification {
+  base: InodeBase,
+
+  // Progress lines and their task IDs.
+  lines: Vec<(ProgressId, String)>,
+}
+
+impl ProgressNotification {
+  pub fn new(shape: IRect) -> Self {
+    let mut base = InodeBase::new(shape);
+    *base.zindex_mut() = PROGRESS_ZINDEX;
+    ProgressNotification {
+      base,
+      lines: vec![],
+    }
+  }
+
+  pub fn lines(&self) -> &Vec<(ProgressId, String)> {
+    &self.lines
+  }
+
+  pub fn set_lines(&mut self, lines: Vec<(ProgressId, String)>) {
+    self.lines = lines;
+  }
+
+  /// Get the task whose cancel button is at the terminal position `(x, y)`.
+  pub fn cancel_button_at(&self, x: u16, y: u16) -> Option<ProgressId> {
+    let actual_shape = self.actual_shape();
+    if x < actual_shape.min().x
+      || x >= actual_shape.max().x
+      || y < actual_shape.min().y
+      || y >= actual_shape.max().y
+    {
+      return None;
+    }
+    let (id, line) = self.lines.get((y - actual_shape.min().y) as usize)?;
+    if !line.ends_with(CANCEL_BUTTON) {
+      return None;
+    }
+    // The cancel button is at the end of line.
+    let line_width = UnicodeWidthStr::width(line.as_str());
+    let column = (x - actual_shape.min().x) as usize;
+    if column < line_width && column + CANCEL_BUTTON.len() >= line_width {
+      Some(*id)
+    } else {
+      None
+    }
+  }
+}
+
+inode_generate_impl!(ProgressNotification, base);
+
+impl Widgetable for ProgressNotification {
+  fn draw(&self, canvas: &mut Canvas) {
+    let actual_shape = self.actual_shape();
+    let upos: U16Pos = actual_shape.min().into();
+    let width = actual_shape.width() as usize;
+    let style = canvas.highlight("Progress");
+
+    for (i, (_, line)) in self
+      .lines
+      .iter()
+      .take(actual_shape.height() as usize)
+      .enumerate()
+    {
+      let cells = make_styled_cells(line, width, &style);
+      canvas
+        .frame_mut()
+        .set_cells_at(point!(x: upos.x(), y: upos.y() + i as u16), cells);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn bottom_right_shape1() {
+    let lines = vec![(1, "Grep 50%".to_string()), (2, "Load [x]".to_string())];
+    let actual = bottom_right_shape(U16Size::new(20, 10), &lines);
+    assert_eq!(actual, IRect::new((12, 8), (20, 10)));
+  }
+
+  #[test]
+  fn cancel_button_at1() {
+    let lines = vec![(1, "Grep 50%".to_string()), (2, "Load [x]".to_string())];
+    let mut notification = ProgressNotification::new(IRect::new((12, 8), (20, 10)));
+    *notification.actual_shape_mut() = geo::Rect::new((12_u16, 8_u16), (20_u16, 10_u16));
+    notification.set_lines(lines);
+    assert_eq!(notification.cancel_button_at(19, 9), Some(2));
+    assert_eq!(notification.cancel_button_at(17, 9), Some(2));
+    assert_eq!(notification.cancel_button_at(16, 9), None);
+    assert_eq!(notification.cancel_button_at(19, 8), None);
+    assert_eq!(notification.cancel_button_at(0, 0), None);
+  }
+}
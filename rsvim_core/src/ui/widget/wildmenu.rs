@@ -0,0 +1,316 @@
+//! Wildmenu widget.
+//!
+//! The wildmenu shows the command-line completion candidates above the command-line, and
+//! highlights the selected candidate. It's either a horizontal bar (one row, the candidates
+//! overflowing the terminal width are indicated by `<` and `>`), for example:
+//!
+//! ```text
+//! < split  spell  spellgood  >
+//! :sp
+//! ```
+//!
+//! Or a popup menu (if 'wildoptions' contains `pum`) placed at the completed word, for example:
+//!
+//! ```text
+//!  split
+//!  spell
+//! :sp
+//! ```
+//!
+//! The wildmenu widget only renders the contents, the completion is maintained by the editing
+//! state, see [`cmdline`](crate::state::cmdline).
+
+use crate::buf::highlight::HighlightStyle;
+use crate::cart::{IRect, U16Pos, U16Rect, U16Size};
+use crate::inode_generate_impl;
+use crate::ui::canvas::Canvas;
+use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::picker::make_styled_cells;
+use crate::ui::widget::Widgetable;
+
+use geo::point;
+use std::ops::Range;
+use unicode_width::UnicodeWidthStr;
+
+/// The z-index of the wildmenu, it floats on top of the command-line, but below the picker.
+pub const WILDMENU_ZINDEX: usize = 96;
+
+/// The max rows of the popup menu.
+pub const WILDMENU_POPUP_MAX_HEIGHT: usize = 10;
+
+// The separator between the candidates in the bar.
+const SEPARATOR_WIDTH: usize = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The wildmenu style.
+pub enum WildMenuStyle {
+  /// Horizontal bar.
+  Bar,
+  /// Popup menu.
+  Popup,
+}
+
+/// Calculate the bar shape, i.e. the row above the command-line.
+pub fn bar_shape(terminal_size: U16Size) -> IRect {
+  let width = terminal_size.width() as isize;
+  let y = (terminal_size.height() as isize - 2).max(0);
+  IRect::new((0, y), (width, y + 1))
+}
+
+/// Calculate the popup shape, i.e. above the command-line and starts from the `column` of the
+/// completed word.
+pub fn popup_shape(terminal_size: U16Size, column: usize, items: &[String]) -> IRect {
+  let terminal_width = terminal_size.width() as isize;
+  let bottom = (terminal_size.height() as isize - 1).max(0);
+  let height = items.len().min(WILDMENU_POPUP_MAX_HEIGHT) as isize;
+  // Pad one space on both sides.
+  let width = items
+    .iter()
+    .map(|item| item.width() + 2)
+    .max()
+    .unwrap_or(0)
+    .min(terminal_width as usize) as isize;
+  let x = (column as isize).min(terminal_width - width).max(0);
+  let y = (bottom - height).max(0);
+  IRect::new((x, y), (x + width, bottom))
+}
+
+/// Calculate the visible candidates of the bar within the `width`. The candidates are split into
+/// pages, the page contains the selected candidate is visible. Two cells are reserved on both
+/// sides for the `<`/`>` indicators if the candidates don't fit in the width.
+pub fn bar_range(items: &[String], selected: Option<usize>, width: usize) -> Range<usize> {
+  let widths = items.iter().map(|item| item.width()).collect::<Vec<_>>();
+  let total = widths.iter().sum::<usize>() + SEPARATOR_WIDTH * widths.len().saturating_sub(1);
+  if total <= width {
+    return 0..items.len();
+  }
+
+  let available = width.saturating_sub(2 * SEPARATOR_WIDTH);
+  let selected = selected.unwrap_or(0);
+  let mut start = 0_usize;
+  while start < items.len() {
+    let mut end = start;
+    let mut occupied = 0_usize;
+    while end < items.len() {
+      let next = occupied + widths[end] + if end > start { SEPARATOR_WIDTH } else { 0 };
+      // Always show at least one candidate in a page.
+      if next > available && end > start {
+        break;
+      }
+      occupied = next;
+      end += 1;
+    }
+    if selected < end {
+      return start..end;
+    }
+    start = end;
+  }
+  0..0
+}
+
+#[derive(Debug, Clone)]
+/// The wildmenu widget.
+pub struct WildMenu {
+  base: InodeBase,
+
+  // The candidates.
+  items: Vec<String>,
+
+  // The selected candidate.
+  selected: Option<usize>,
+
+  // The style.
+  style: WildMenuStyle,
+}
+
+impl WildMenu {
+  pub fn new(
+    shape: IRect,
+    style: WildMenuStyle,
+    items: Vec<String>,
+    selected: Option<usize>,
+  ) -> Self {
+    let mut base = InodeBase::new(shape);
+    *base.zindex_mut() = WILDMENU_ZINDEX;
+    WildMenu {
+      base,
+      items,
+      selected,
+      style,
+    }
+  }
+
+  pub fn items(&self) -> &Vec<String> {
+    &self.items
+  }
+
+  pub fn selected(&self) -> Option<usize> {
+    self.selected
+  }
+
+  pub fn style(&self) -> WildMenuStyle {
+    self.style
+  }
+
+  // The style of the candidate, the selected one uses the `selected` style.
+  fn item_style<'a>(
+    &self,
+    idx: usize,
+    normal: &'a HighlightStyle,
+    selected: &'a HighlightStyle,
+  ) -> &'a HighlightStyle {
+    if self.selected == Some(idx) {
+      selected
+    } else {
+      normal
+    }
+  }
+
+  fn draw_bar(&self, canvas: &mut Canvas, upos: U16Pos, width: usize) {
+    let range = bar_range(&self.items, self.selected, width);
+    let normal = canvas.highlight("Normal");
+    let selected = canvas.highlight("WildMenu");
+    let mut cells = vec![];
+    if range.start > 0 {
+      cells.extend(make_styled_cells("< ", SEPARATOR_WIDTH, &normal));
+    }
+    for idx in range.clone() {
+      if idx > range.start {
+        cells.extend(make_styled_cells("", SEPARATOR_WIDTH, &normal));
+      }
+      let item = &self.items[idx];
+      let item_width = item.width().min(width.saturating_sub(cells.len()));
+      let style = self.item_style(idx, &normal, &selected);
+      cells.extend(make_styled_cells(item, item_width, style));
+    }
+    let rest = width.saturating_sub(cells.len());
+    if range.end < self.items.len() {
+      cells.extend(make_styled_cells("", rest.saturating_sub(1), &normal));
+      cells.extend(make_styled_cells(">", 1.min(rest), &normal));
+    } else {
+      cells.extend(make_styled_cells("", rest, &normal));
+    }
+    cells.truncate(width);
+    canvas.frame_mut().set_cells_at(upos, cells);
+  }
+
+  fn draw_popup(&self, canvas: &mut Canvas, upos: U16Pos, width: usize, height: usize) {
+    // Scroll the selected candidate into the view.
+    let first = match self.selected {
+      Some(selected) if selected >= height => selected + 1 - height,
+      _ => 0,
+    };
+    let normal = canvas.highlight("Pmenu");
+    let selected = canvas.highlight("PmenuSel");
+    for i in 0..height {
+      let idx = first + i;
+      let (text, style) = match self.items.get(idx) {
+        Some(item) => (
+          format!(" {}", item),
+          self.item_style(idx, &normal, &selected),
+        ),
+        None => (String::new(), &normal),
+      };
+      let cells = make_styled_cells(&text, width, style);
+      canvas
+        .frame_mut()
+        .set_cells_at(point!(x: upos.x(), y: upos.y() + i as u16), cells);
+    }
+  }
+}
+
+inode_generate_impl!(WildMenu, base);
+
+impl Widgetable for WildMenu {
+  fn draw(&self, canvas: &mut Canvas) {
+    let actual_shape = self.actual_shape();
+    let width = actual_shape.width() as usize;
+    let height = actual_shape.height() as usize;
+    if width == 0 || height == 0 {
+      return;
+    }
+    let upos: U16Pos = actual_shape.min().into();
+    match self.style {
+      WildMenuStyle::Bar => self.draw_bar(canvas, upos, width),
+      WildMenuStyle::Popup => self.draw_popup(canvas, upos, width, height),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn items(items: &[&str]) -> Vec<String> {
+    items.iter().map(|item| item.to_string()).collect()
+  }
+
+  fn draw(menu: &mut WildMenu, terminal_size: U16Size) -> Vec<String> {
+    let shape = *menu.shape();
+    *menu.actual_shape_mut() = geo::Rect::new(
+      (shape.min().x as u16, shape.min().y as u16),
+      (shape.max().x as u16, shape.max().y as u16),
+    );
+    let mut canvas = Canvas::new(terminal_size);
+    menu.draw(&mut canvas);
+    canvas
+      .frame()
+      .raw_symbols()
+      .iter()
+      .map(|cs| cs.join(""))
+      .collect()
+  }
+
+  #[test]
+  fn shapes1() {
+    let terminal_size = U16Size::new(20, 10);
+    assert_eq!(bar_shape(terminal_size), IRect::new((0, 8), (20, 9)));
+    let actual = popup_shape(terminal_size, 3, &items(&["split", "spell"]));
+    assert_eq!(actual, IRect::new((3, 7), (10, 9)));
+    let actual = popup_shape(terminal_size, 18, &items(&["split"]));
+    assert_eq!(actual, IRect::new((13, 8), (20, 9)));
+  }
+
+  #[test]
+  fn bar_range1() {
+    let candidates = items(&["aaa", "bbb", "ccc", "ddd"]);
+    assert_eq!(bar_range(&candidates, None, 20), 0..4);
+    // Reserve 4 cells for the indicators, 8 cells are available.
+    assert_eq!(bar_range(&candidates, None, 12), 0..2);
+    assert_eq!(bar_range(&candidates, Some(1), 12), 0..2);
+    assert_eq!(bar_range(&candidates, Some(2), 12), 2..4);
+    assert_eq!(bar_range(&candidates, Some(3), 6), 3..4);
+  }
+
+  #[test]
+  fn draw1() {
+    let terminal_size = U16Size::new(12, 3);
+    let candidates = items(&["aaa", "bbb", "ccc", "ddd"]);
+    let mut menu = WildMenu::new(
+      bar_shape(terminal_size),
+      WildMenuStyle::Bar,
+      candidates.clone(),
+      Some(2),
+    );
+    let actual = draw(&mut menu, terminal_size);
+    assert_eq!(actual[1], "< ccc  ddd  ");
+    let mut menu = WildMenu::new(
+      bar_shape(terminal_size),
+      WildMenuStyle::Bar,
+      candidates.clone(),
+      None,
+    );
+    let actual = draw(&mut menu, terminal_size);
+    assert_eq!(actual[1], "aaa  bbb   >");
+
+    let terminal_size = U16Size::new(12, 6);
+    let mut menu = WildMenu::new(
+      popup_shape(terminal_size, 1, &candidates),
+      WildMenuStyle::Popup,
+      candidates,
+      Some(3),
+    );
+    let actual = draw(&mut menu, terminal_size);
+    assert_eq!(actual, vec!["", " aaa ", " bbb ", " ccc ", " ddd ", ""]);
+  }
+}
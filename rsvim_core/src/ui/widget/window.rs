@@ -1,14 +1,22 @@
 //! Vim window.
 
-use crate::buf::BufferWk;
-use crate::cart::{IRect, U16Rect};
+use crate::buf::{BufferWk, SignDefinition};
+use crate::cart::{IRect, U16Pos, U16Rect};
+use crate::defaults;
 use crate::envar;
+use crate::state::mode::Mode;
 use crate::ui::canvas::Canvas;
 use crate::ui::tree::internal::{InodeId, Inodeable, Itree};
+use crate::ui::tree::layout::SizeConstraint;
 use crate::ui::widget::window::content::WindowContent;
+use crate::ui::widget::window::jumplist::JumpList;
+use crate::ui::widget::window::minimap::{MinimapMark, WindowMinimap};
+use crate::ui::widget::window::number::{number_column_width, WindowNumber};
 use crate::ui::widget::window::root::WindowRootContainer;
+use crate::ui::widget::window::sign::{sign_column_width, WindowSign};
+use crate::ui::widget::window::statusline::{StatusLine, EVAL_PREFIX};
 use crate::ui::widget::Widgetable;
-use crate::wlock;
+use crate::{rlock, wlock};
 
 // Re-export
 pub use crate::ui::widget::window::opt::{
@@ -18,15 +26,52 @@ pub use crate::ui::widget::window::viewport::{
   CursorViewport, LineViewport, RowViewport, Viewport, ViewportArc,
 };
 
+use geo::point;
+use std::collections::BTreeMap;
 use std::convert::From;
 use std::sync::Arc;
 // use tracing::trace;
 
 pub mod content;
+pub mod jumplist;
+pub mod minimap;
+pub mod number;
 pub mod opt;
 pub mod root;
+pub mod sign;
+pub mod statusline;
 pub mod viewport;
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The region of a window at the terminal position, i.e. where the mouse clicks.
+pub enum WindowRegion {
+  /// The window content, the position is relative to the top-left corner of the content.
+  Content(U16Pos),
+  /// The line number gutter or the sign column, the row is relative to the top of the window.
+  Gutter(u16),
+  /// The minimap.
+  Minimap,
+  /// The statusline, the column is relative to the left side of the statusline.
+  StatusLine(u16),
+}
+
+// Whether the terminal position `pos` is inside the `rect`.
+fn contains(rect: &U16Rect, pos: U16Pos) -> bool {
+  pos.x() >= rect.min().x
+    && pos.x() < rect.max().x
+    && pos.y() >= rect.min().y
+    && pos.y() < rect.max().y
+}
+
+// The shapes of the window content, minimap, statusline, line number gutter and sign column.
+type WindowLayout = (
+  IRect,
+  Option<IRect>,
+  Option<IRect>,
+  Option<IRect>,
+  Option<IRect>,
+);
+
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 /// The Vim window, it manages all descendant widget nodes, i.e. all widgets in the
@@ -37,6 +82,22 @@ pub struct Window {
   // The Window content widget ID.
   content_id: InodeId,
 
+  // The Window minimap widget ID (if has).
+  minimap_id: Option<InodeId>,
+
+  // The Window statusline widget ID (if has).
+  statusline_id: Option<InodeId>,
+
+  // The Window line number gutter widget ID (if has).
+  number_id: Option<InodeId>,
+
+  // The Window sign column widget ID (if has).
+  sign_id: Option<InodeId>,
+
+  // The percentage share of the width/height, when the tiled windows are re-layout.
+  width_percent: Option<u16>,
+  height_percent: Option<u16>,
+
   // Buffer.
   buffer: BufferWk,
 
@@ -46,6 +107,9 @@ pub struct Window {
 
   // Viewport.
   viewport: ViewportArc,
+
+  // Jumplist.
+  jumplist: JumpList,
 }
 
 impl Window {
@@ -57,28 +121,248 @@ impl Window {
     let window_root_node = WindowNode::WindowRootContainer(window_root);
     let window_root_actual_shape = *window_root_node.actual_shape();
 
-    let viewport_options = ViewportOptions {
-      wrap: options.wrap(),
-      line_break: options.line_break(),
-    };
+    let viewport_options = ViewportOptions::from(&options);
     let viewport = Viewport::new(&viewport_options, buffer.clone(), &window_root_actual_shape);
     let viewport = Viewport::to_arc(viewport);
 
     let mut base = Itree::new(window_root_node);
 
-    let window_content = WindowContent::new(shape, buffer.clone(), Arc::downgrade(&viewport));
+    let gutter_width = Self::gutter_width(&options, &buffer, shape.height());
+    let sign_width = Self::sign_width(&options, &buffer);
+    let (content_shape, minimap_shape, statusline_shape, number_shape, sign_shape) = Self::layout(
+      shape,
+      options.minimap(),
+      !options.statusline().is_empty(),
+      gutter_width,
+      sign_width,
+    );
+
+    let window_content =
+      WindowContent::new(content_shape, buffer.clone(), Arc::downgrade(&viewport));
     let window_content_id = window_content.id();
     let window_content_node = WindowNode::WindowContent(window_content);
 
     base.bounded_insert(&window_root_id, window_content_node);
 
-    Window {
+    let mut window = Window {
       base,
       content_id: window_content_id,
+      minimap_id: None,
+      statusline_id: None,
+      number_id: None,
+      sign_id: None,
+      width_percent: None,
+      height_percent: None,
       buffer,
       options,
       viewport,
+      jumplist: JumpList::new(),
+    };
+    if let Some(minimap_shape) = minimap_shape {
+      window.insert_minimap(minimap_shape);
+    }
+    if let Some(statusline_shape) = statusline_shape {
+      window.insert_statusline(statusline_shape);
+    }
+    if let Some(number_shape) = number_shape {
+      window.insert_number(number_shape);
+    }
+    if let Some(sign_shape) = sign_shape {
+      window.insert_sign(sign_shape);
+    }
+    if window.minimap_id.is_some()
+      || window.statusline_id.is_some()
+      || window.number_id.is_some()
+      || window.sign_id.is_some()
+    {
+      window.sync_viewport_shape();
+    }
+    window
+  }
+
+  // Calculate the line number gutter width with the 'number'/'relativenumber'/'numberwidth'
+  // options, it's `0` if the gutter is not shown.
+  fn gutter_width(options: &WindowLocalOptions, buffer: &BufferWk, height: isize) -> u16 {
+    let total_lines = buffer
+      .upgrade()
+      .map(|buffer| rlock!(buffer).len_lines())
+      .unwrap_or(0);
+    number_column_width(
+      options.number(),
+      options.relative_number(),
+      options.number_width(),
+      total_lines,
+      height.clamp(0, u16::MAX as isize) as u16,
+    )
+  }
+
+  // Calculate the sign column width with the 'signcolumn'/'signwidth' options, it's `0` if the
+  // sign column is not shown.
+  fn sign_width(options: &WindowLocalOptions, buffer: &BufferWk) -> u16 {
+    let has_signs = buffer
+      .upgrade()
+      .is_some_and(|buffer| !rlock!(buffer).signs().is_empty());
+    sign_column_width(options.sign_column(), options.sign_width(), has_signs)
+  }
+
+  /// Split the window shape into the content shape, the minimap shape (if enabled), the
+  /// statusline shape (if enabled), the line number gutter shape (if `gutter_width` is not zero)
+  /// and the sign column shape (if `sign_width` is not zero), all are relative to the window's
+  /// top-left corner. The sign column is at the left of the line number gutter.
+  ///
+  /// NOTE: The minimap, the line number gutter and the sign column are not shown if the window is
+  /// too narrow, the statusline is not shown if the window is too short.
+  fn layout(
+    shape: IRect,
+    minimap: bool,
+    statusline: bool,
+    gutter_width: u16,
+    sign_width: u16,
+  ) -> WindowLayout {
+    let minimap_width = defaults::win::MINIMAP_WIDTH as isize;
+    let (gutter_width, sign_width) = (gutter_width as isize, sign_width as isize);
+    let (width, mut height) = (shape.width(), shape.height());
+    let statusline_shape = if statusline && height >= 2 {
+      height -= 1;
+      Some(IRect::new((0, height), (width, height + 1)))
+    } else {
+      None
+    };
+    let (number_shape, sign_shape, content_start) =
+      if gutter_width + sign_width > 0 && width >= (gutter_width + sign_width) * 2 {
+        let number_shape = (gutter_width > 0)
+          .then(|| IRect::new((sign_width, 0), (sign_width + gutter_width, height)));
+        let sign_shape = (sign_width > 0).then(|| IRect::new((0, 0), (sign_width, height)));
+        (number_shape, sign_shape, sign_width + gutter_width)
+      } else {
+        (None, None, 0)
+      };
+    if !minimap || width - content_start < minimap_width * 2 {
+      return (
+        IRect::new((content_start, 0), (width, height)),
+        None,
+        statusline_shape,
+        number_shape,
+        sign_shape,
+      );
+    }
+    let content_shape = IRect::new((content_start, 0), (width - minimap_width, height));
+    let minimap_shape = IRect::new((width - minimap_width, 0), (width, height));
+    (
+      content_shape,
+      Some(minimap_shape),
+      statusline_shape,
+      number_shape,
+      sign_shape,
+    )
+  }
+
+  // Re-layout the window content, minimap, statusline, line number gutter and sign column, i.e.
+  // after the options are changed.
+  fn relayout(&mut self) {
+    if let Some(minimap_id) = self.minimap_id.take() {
+      self.base.remove(minimap_id);
+    }
+    if let Some(statusline_id) = self.statusline_id.take() {
+      self.base.remove(statusline_id);
     }
+    if let Some(number_id) = self.number_id.take() {
+      self.base.remove(number_id);
+    }
+    if let Some(sign_id) = self.sign_id.take() {
+      self.base.remove(sign_id);
+    }
+
+    let shape = *self.shape();
+    let gutter_width = Self::gutter_width(&self.options, &self.buffer, shape.height());
+    let sign_width = Self::sign_width(&self.options, &self.buffer);
+    let (content_shape, minimap_shape, statusline_shape, number_shape, sign_shape) = Self::layout(
+      shape,
+      self.options.minimap(),
+      !self.options.statusline().is_empty(),
+      gutter_width,
+      sign_width,
+    );
+    let root_id = self.base.root_id();
+    let mut window_content = self.base.remove(self.content_id).unwrap();
+    *window_content.shape_mut() = content_shape;
+    self.base.bounded_insert(&root_id, window_content);
+
+    if let Some(minimap_shape) = minimap_shape {
+      self.insert_minimap(minimap_shape);
+    }
+    if let Some(statusline_shape) = statusline_shape {
+      self.insert_statusline(statusline_shape);
+    }
+    if let Some(number_shape) = number_shape {
+      self.insert_number(number_shape);
+    }
+    if let Some(sign_shape) = sign_shape {
+      self.insert_sign(sign_shape);
+    }
+    self.sync_viewport_shape();
+  }
+
+  fn insert_minimap(&mut self, minimap_shape: IRect) {
+    let window_minimap = WindowMinimap::new(
+      minimap_shape,
+      self.buffer.clone(),
+      Arc::downgrade(&self.viewport),
+    );
+    self.minimap_id = Some(window_minimap.id());
+    let root_id = self.base.root_id();
+    self
+      .base
+      .bounded_insert(&root_id, WindowNode::WindowMinimap(window_minimap));
+  }
+
+  fn insert_statusline(&mut self, statusline_shape: IRect) {
+    let statusline = StatusLine::new(
+      statusline_shape,
+      self.buffer.clone(),
+      Arc::downgrade(&self.viewport),
+      self.options.statusline(),
+    );
+    self.statusline_id = Some(statusline.id());
+    let root_id = self.base.root_id();
+    self
+      .base
+      .bounded_insert(&root_id, WindowNode::StatusLine(statusline));
+  }
+
+  fn insert_number(&mut self, number_shape: IRect) {
+    let window_number = WindowNumber::new(
+      number_shape,
+      Arc::downgrade(&self.viewport),
+      self.options.number(),
+      self.options.relative_number(),
+    );
+    self.number_id = Some(window_number.id());
+    let root_id = self.base.root_id();
+    self
+      .base
+      .bounded_insert(&root_id, WindowNode::WindowNumber(window_number));
+  }
+
+  fn insert_sign(&mut self, sign_shape: IRect) {
+    let window_sign = WindowSign::new(
+      sign_shape,
+      self.buffer.clone(),
+      Arc::downgrade(&self.viewport),
+    );
+    self.sign_id = Some(window_sign.id());
+    let root_id = self.base.root_id();
+    self
+      .base
+      .bounded_insert(&root_id, WindowNode::WindowSign(window_sign));
+  }
+
+  // The viewport is calculated with the window content's actual shape.
+  fn sync_viewport_shape(&mut self) {
+    let content_actual_shape = *self.base.node(&self.content_id).unwrap().actual_shape();
+    let mut viewport = wlock!(self.viewport);
+    viewport.set_actual_shape(&content_actual_shape);
+    viewport.sync();
   }
 }
 
@@ -203,6 +487,86 @@ impl Window {
     wlock!(self.viewport).set_options(&viewport_options);
   }
 
+  pub fn scroll_off(&self) -> usize {
+    self.options.scroll_off()
+  }
+
+  pub fn set_scroll_off(&mut self, value: usize) {
+    self.options.set_scroll_off(value);
+    let viewport_options = ViewportOptions::from(&self.options);
+    wlock!(self.viewport).set_options(&viewport_options);
+  }
+
+  pub fn side_scroll(&self) -> usize {
+    self.options.side_scroll()
+  }
+
+  pub fn set_side_scroll(&mut self, value: usize) {
+    self.options.set_side_scroll(value);
+    let viewport_options = ViewportOptions::from(&self.options);
+    wlock!(self.viewport).set_options(&viewport_options);
+  }
+
+  pub fn side_scroll_off(&self) -> usize {
+    self.options.side_scroll_off()
+  }
+
+  pub fn set_side_scroll_off(&mut self, value: usize) {
+    self.options.set_side_scroll_off(value);
+    let viewport_options = ViewportOptions::from(&self.options);
+    wlock!(self.viewport).set_options(&viewport_options);
+  }
+
+  pub fn minimap(&self) -> bool {
+    self.options.minimap()
+  }
+
+  /// Set the 'minimap' option, the window content and minimap are re-layout.
+  pub fn set_minimap(&mut self, value: bool) {
+    self.options.set_minimap(value);
+    self.relayout();
+  }
+
+  pub fn statusline(&self) -> &str {
+    self.options.statusline()
+  }
+
+  /// Set the 'statusline' option, the window content and statusline are re-layout.
+  pub fn set_statusline(&mut self, value: &str) {
+    self.options.set_statusline(value);
+    self.relayout();
+  }
+
+  pub fn number(&self) -> bool {
+    self.options.number()
+  }
+
+  /// Set the 'number' option, the window content and line number gutter are re-layout.
+  pub fn set_number(&mut self, value: bool) {
+    self.options.set_number(value);
+    self.relayout();
+  }
+
+  pub fn relative_number(&self) -> bool {
+    self.options.relative_number()
+  }
+
+  /// Set the 'relativenumber' option, the window content and line number gutter are re-layout.
+  pub fn set_relative_number(&mut self, value: bool) {
+    self.options.set_relative_number(value);
+    self.relayout();
+  }
+
+  pub fn number_width(&self) -> u16 {
+    self.options.number_width()
+  }
+
+  /// Set the 'numberwidth' option, the window content and line number gutter are re-layout.
+  pub fn set_number_width(&mut self, value: u16) {
+    self.options.set_number_width(value);
+    self.relayout();
+  }
+
   /// Get viewport.
   pub fn viewport(&self) -> ViewportArc {
     self.viewport.clone()
@@ -212,6 +576,30 @@ impl Window {
   pub fn buffer(&self) -> BufferWk {
     self.buffer.clone()
   }
+
+  /// Set buffer, i.e. show another buffer in this window, the viewport starts from the first
+  /// line.
+  ///
+  /// NOTE: The buffer must not be locked when calling this method.
+  pub fn set_buffer(&mut self, buffer: BufferWk) {
+    self.buffer = buffer.clone();
+    if let Some(WindowNode::WindowContent(content)) = self.base.node_mut(&self.content_id) {
+      content.set_buffer(buffer.clone());
+    }
+    if let Some(minimap_id) = self.minimap_id {
+      if let Some(WindowNode::WindowMinimap(minimap)) = self.base.node_mut(&minimap_id) {
+        minimap.set_buffer(buffer.clone());
+      }
+    }
+    if let Some(statusline_id) = self.statusline_id {
+      if let Some(WindowNode::StatusLine(statusline)) = self.base.node_mut(&statusline_id) {
+        statusline.set_buffer(buffer.clone());
+      }
+    }
+    let mut viewport = wlock!(self.viewport);
+    viewport.set_buffer(buffer);
+    viewport.sync_to_line(0);
+  }
 }
 // Options }
 
@@ -219,11 +607,232 @@ impl Window {
 impl Window {}
 // Viewport }
 
+// Jumplist {
+impl Window {
+  pub fn jumplist(&self) -> &JumpList {
+    &self.jumplist
+  }
+
+  pub fn jumplist_mut(&mut self) -> &mut JumpList {
+    &mut self.jumplist
+  }
+}
+// Jumplist }
+
+// Minimap {
+impl Window {
+  /// Set marked lines in minimap, i.e. diagnostics, search matches.
+  pub fn set_minimap_marks(&mut self, marks: BTreeMap<usize, MinimapMark>) {
+    if let Some(minimap_id) = self.minimap_id {
+      if let Some(WindowNode::WindowMinimap(minimap)) = self.base.node_mut(&minimap_id) {
+        minimap.set_marks(marks);
+      }
+    }
+  }
+
+  /// Scroll the window to show the `line_idx` as the top line, i.e. when the minimap is clicked.
+  pub fn jump_to_line(&mut self, line_idx: usize) {
+    wlock!(self.viewport).sync_to_line(line_idx);
+  }
+
+  /// Get the buffer line at the terminal position `pos` in minimap, i.e. when the minimap is
+  /// clicked.
+  pub fn minimap_line_at(&self, pos: U16Pos) -> Option<usize> {
+    match self.base.node(&self.minimap_id?) {
+      Some(WindowNode::WindowMinimap(minimap)) => minimap.line_at(pos),
+      _ => None,
+    }
+  }
+}
+// Minimap }
+
+// Mouse {
+impl Window {
+  /// Get the region at the terminal position `pos`, returns `None` if it's outside the window.
+  pub fn region_at(&self, pos: U16Pos) -> Option<WindowRegion> {
+    let actual_shape = |id: &Option<InodeId>| {
+      id.and_then(|id| self.base.node(&id))
+        .map(|node| *node.actual_shape())
+    };
+    let content_shape = *self.base.node(&self.content_id)?.actual_shape();
+    if contains(&content_shape, pos) {
+      return Some(WindowRegion::Content(point!(
+        x: pos.x() - content_shape.min().x,
+        y: pos.y() - content_shape.min().y
+      )));
+    }
+    if let Some(statusline_shape) = actual_shape(&self.statusline_id) {
+      if contains(&statusline_shape, pos) {
+        return Some(WindowRegion::StatusLine(pos.x() - statusline_shape.min().x));
+      }
+    }
+    if actual_shape(&self.minimap_id).is_some_and(|shape| contains(&shape, pos)) {
+      return Some(WindowRegion::Minimap);
+    }
+    let gutter = [&self.number_id, &self.sign_id]
+      .into_iter()
+      .filter_map(actual_shape)
+      .any(|shape| contains(&shape, pos));
+    if gutter {
+      return Some(WindowRegion::Gutter(pos.y() - self.actual_shape().min().y));
+    }
+    None
+  }
+}
+// Mouse }
+
+// Layout {
+impl Window {
+  /// Get the percentage share of the width.
+  pub fn width_percent(&self) -> Option<u16> {
+    self.width_percent
+  }
+
+  /// Set the percentage share of the width, it persists when the tiled windows are re-layout.
+  pub fn set_width_percent(&mut self, value: Option<u16>) {
+    self.width_percent = value.map(|v| v.min(100));
+  }
+
+  /// Get the percentage share of the height.
+  pub fn height_percent(&self) -> Option<u16> {
+    self.height_percent
+  }
+
+  /// Set the percentage share of the height, it persists when the tiled windows are re-layout.
+  pub fn set_height_percent(&mut self, value: Option<u16>) {
+    self.height_percent = value.map(|v| v.min(100));
+  }
+
+  pub fn set_winfixwidth(&mut self, value: bool) {
+    self.options.set_winfixwidth(value);
+  }
+
+  pub fn set_winfixheight(&mut self, value: bool) {
+    self.options.set_winfixheight(value);
+  }
+
+  /// Get the width constraint, the 'winfixwidth' option takes precedence over the percentage
+  /// share.
+  pub fn width_constraint(&self) -> SizeConstraint {
+    if self.options.winfixwidth() {
+      SizeConstraint::Fixed(self.actual_shape().width())
+    } else if let Some(percent) = self.width_percent {
+      SizeConstraint::Percent(percent)
+    } else {
+      SizeConstraint::Flex
+    }
+  }
+
+  /// Get the height constraint, the 'winfixheight' option takes precedence over the percentage
+  /// share.
+  pub fn height_constraint(&self) -> SizeConstraint {
+    if self.options.winfixheight() {
+      SizeConstraint::Fixed(self.actual_shape().height())
+    } else if let Some(percent) = self.height_percent {
+      SizeConstraint::Percent(percent)
+    } else {
+      SizeConstraint::Flex
+    }
+  }
+
+  /// Resize the window with the new `shape` and its `actual_shape`, the window content, minimap
+  /// and statusline are re-layout.
+  pub fn resize(&mut self, shape: IRect, actual_shape: U16Rect) {
+    let root_id = self.base.root_id();
+    let root = self.base.node_mut(&root_id).unwrap();
+    *root.shape_mut() = shape;
+    *root.actual_shape_mut() = actual_shape;
+    self.relayout();
+  }
+}
+// Layout }
+
+// Gutter {
+impl Window {
+  /// Get the horizontal offset of the window content to the window's left side, i.e. the width of
+  /// the sign column and the line number gutter. The cursor widget (relative to the window) is shifted by it.
+  pub fn content_offset(&self) -> u16 {
+    let content_x = self
+      .base
+      .node(&self.content_id)
+      .unwrap()
+      .actual_shape()
+      .min()
+      .x;
+    content_x.saturating_sub(self.actual_shape().min().x)
+  }
+
+  /// Re-layout the window if the line number gutter width or the sign column width is changed,
+  /// i.e. the buffer grows to more digits lines, or the first sign is placed in the buffer.
+  /// Returns `true` if it's re-layout.
+  ///
+  /// NOTE: The buffer must not be locked when calling this method.
+  pub fn sync_gutters(&mut self) -> bool {
+    let gutter_width = Self::gutter_width(&self.options, &self.buffer, self.shape().height());
+    let sign_width = Self::sign_width(&self.options, &self.buffer);
+    let current_width = |id: Option<InodeId>| match id {
+      Some(id) => self.base.node(&id).unwrap().shape().width() as u16,
+      None => 0,
+    };
+    let current = (current_width(self.number_id), current_width(self.sign_id));
+    let expected = if (gutter_width + sign_width) as isize * 2 <= self.shape().width() {
+      (gutter_width, sign_width)
+    } else {
+      (0, 0)
+    };
+    if expected == current {
+      return false;
+    }
+    self.relayout();
+    true
+  }
+
+  /// Sync the sign definitions to the sign column, it's called before each redraw.
+  pub fn sync_sign_definitions(&mut self, definitions: &BTreeMap<String, SignDefinition>) {
+    if let Some(sign_id) = self.sign_id {
+      if let Some(WindowNode::WindowSign(window_sign)) = self.base.node_mut(&sign_id) {
+        window_sign.set_definitions(definitions);
+      }
+    }
+  }
+}
+// Gutter }
+
+// Statusline {
+impl Window {
+  /// Whether the 'statusline' is evaluated by the JS callback, i.e. it starts with `%!`.
+  pub fn statusline_evaluated(&self) -> bool {
+    self.statusline_id.is_some() && self.options.statusline().starts_with(EVAL_PREFIX)
+  }
+
+  /// Sync the editing mode, the format evaluated by the JS callback and the cached values of the
+  /// components to statusline, it's called before each redraw.
+  pub fn sync_statusline(
+    &mut self,
+    mode: Mode,
+    evaluated: Option<String>,
+    components: &BTreeMap<String, String>,
+  ) {
+    if let Some(statusline_id) = self.statusline_id {
+      if let Some(WindowNode::StatusLine(statusline)) = self.base.node_mut(&statusline_id) {
+        statusline.set_mode(mode);
+        statusline.set_evaluated(evaluated);
+        statusline.set_components(components);
+      }
+    }
+  }
+}
+// Statusline }
+
 #[derive(Debug, Clone)]
 /// The value holder for each window widget.
 pub enum WindowNode {
   WindowRootContainer(WindowRootContainer),
   WindowContent(WindowContent),
+  WindowMinimap(WindowMinimap),
+  StatusLine(StatusLine),
+  WindowNumber(WindowNumber),
+  WindowSign(WindowSign),
 }
 
 macro_rules! window_node_generate_dispatch {
@@ -231,6 +840,10 @@ macro_rules! window_node_generate_dispatch {
     match $self_name {
       WindowNode::WindowRootContainer(n) => n.$method_name(),
       WindowNode::WindowContent(n) => n.$method_name(),
+      WindowNode::WindowMinimap(n) => n.$method_name(),
+      WindowNode::StatusLine(n) => n.$method_name(),
+      WindowNode::WindowNumber(n) => n.$method_name(),
+      WindowNode::WindowSign(n) => n.$method_name(),
     }
   };
 }
@@ -295,6 +908,10 @@ impl Widgetable for WindowNode {
     match self {
       WindowNode::WindowRootContainer(w) => w.draw(canvas),
       WindowNode::WindowContent(w) => w.draw(canvas),
+      WindowNode::WindowMinimap(w) => w.draw(canvas),
+      WindowNode::StatusLine(w) => w.draw(canvas),
+      WindowNode::WindowNumber(w) => w.draw(canvas),
+      WindowNode::WindowSign(w) => w.draw(canvas),
     }
   }
 }
@@ -315,6 +932,7 @@ mod tests {
 
   use crate::buf::{Buffer, BufferArc};
   use crate::cart::U16Size;
+  use crate::rlock;
   use crate::test::buf::{make_buffer_from_lines, make_empty_buffer};
   #[allow(dead_code)]
   use crate::test::log::init as test_log_init;
@@ -390,4 +1008,193 @@ mod tests {
     window.draw(&mut actual);
     do_test_draw(&actual, &expect);
   }
+
+  #[test]
+  fn draw_minimap1() {
+    test_log_init();
+
+    let buffer =
+      make_buffer_from_lines(vec!["Hello, RSVIM!\n", "This is a test.\n", "  indented\n"]);
+    let expect = vec![
+      "Hello, RSVIM!       ███████▄  ",
+      "This is a test.      ▀▀▀▀     ",
+      "  indented                    ",
+      "                              ",
+    ];
+
+    let terminal_size = U16Size::new(30, 4);
+    let window_local_options = WindowLocalOptions::builder()
+      .wrap(false)
+      .minimap(true)
+      .build();
+    let mut window = make_window_from_size(terminal_size, buffer.clone(), &window_local_options);
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    do_test_draw(&actual, &expect);
+
+    assert_eq!(window.minimap_line_at(point!(x: 25_u16, y: 1_u16)), Some(2));
+    assert_eq!(window.minimap_line_at(point!(x: 5_u16, y: 1_u16)), None);
+
+    // Disable minimap.
+    window.set_minimap(false);
+    assert_eq!(window.minimap_line_at(point!(x: 25_u16, y: 1_u16)), None);
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    assert_eq!(
+      actual.frame().raw_symbols()[0].join(""),
+      "Hello, RSVIM!                 "
+    );
+  }
+
+  #[test]
+  fn draw_number1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec!["Hello, RSVIM!\n", "abc\n"]);
+    let expect = vec!["  1 Hello,", "     RSVIM", "    !     ", "  2 abc   "];
+
+    let terminal_size = U16Size::new(10, 4);
+    let window_local_options = WindowLocalOptions::builder().number(true).build();
+    let mut window = make_window_from_size(terminal_size, buffer.clone(), &window_local_options);
+    assert_eq!(window.content_offset(), 4);
+    assert_eq!(rlock!(window.viewport()).actual_shape().width(), 6);
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    do_test_draw(&actual, &expect);
+
+    // Relative numbers, the cursor line shows the absolute number.
+    window.set_relative_number(true);
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    assert_eq!(actual.frame().raw_symbols()[0].join(""), "1   Hello,");
+    assert_eq!(actual.frame().raw_symbols()[3].join(""), "  1 abc   ");
+
+    // Disable line numbers.
+    window.set_number(false);
+    window.set_relative_number(false);
+    assert_eq!(window.content_offset(), 0);
+    assert!(!window.sync_gutters());
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    assert_eq!(actual.frame().raw_symbols()[0].join(""), "Hello, RSV");
+  }
+
+  #[test]
+  fn draw_sign1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec!["Hello, RSVIM!\n", "abc\n"]);
+    let expect = vec!["  Hello, R", "  SVIM!   ", "+ abc     "];
+
+    let terminal_size = U16Size::new(10, 3);
+    let window_local_options = WindowLocalOptions::builder().build();
+    let mut window = make_window_from_size(terminal_size, buffer.clone(), &window_local_options);
+    // The 'signcolumn' is `auto`, the sign column is shown after the first sign is placed.
+    assert_eq!(window.content_offset(), 0);
+    wlock!(buffer).signs_mut().place("added", 1, "git", 10);
+    assert!(window.sync_gutters());
+    assert!(!window.sync_gutters());
+    assert_eq!(window.content_offset(), 2);
+    let mut definitions = BTreeMap::new();
+    definitions.insert(
+      "added".to_string(),
+      SignDefinition::new("+", Default::default()),
+    );
+    window.sync_sign_definitions(&definitions);
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    do_test_draw(&actual, &expect);
+
+    // The sign column is at the left of the line number gutter.
+    window.set_number(true);
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    assert_eq!(actual.frame().raw_symbols()[0].join(""), "    1 Hell");
+  }
+
+  #[test]
+  fn draw_statusline1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec!["Hello, RSVIM!\n", "This is a test.\n"]);
+    let expect = vec![
+      "Hello, RSVIM!       ",
+      "This is a test.     ",
+      "                    ",
+      "NORMAL [No Name] 1:1",
+    ];
+
+    let terminal_size = U16Size::new(20, 4);
+    let window_local_options = WindowLocalOptions::builder()
+      .wrap(false)
+      .statusline("%s %f%m%=%l:%c")
+      .build();
+    let mut window = make_window_from_size(terminal_size, buffer.clone(), &window_local_options);
+    assert_eq!(rlock!(window.viewport()).actual_shape().height(), 3);
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    do_test_draw(&actual, &expect);
+
+    // Modified, and evaluated by JS callback.
+    wlock!(buffer).insert(0, "a");
+    window.set_statusline("%!");
+    assert!(window.statusline_evaluated());
+    window.sync_statusline(Mode::Insert, Some("%s%m".to_string()), &BTreeMap::new());
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    assert_eq!(
+      actual.frame().raw_symbols()[3].join(""),
+      "INSERT[+]           "
+    );
+
+    // Cached components.
+    window.set_statusline("%s %{git}");
+    let components = BTreeMap::from([("git".to_string(), "main".to_string())]);
+    window.sync_statusline(Mode::Normal, None, &components);
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    assert_eq!(
+      actual.frame().raw_symbols()[3].join(""),
+      "NORMAL main         "
+    );
+
+    // Disable statusline.
+    window.set_statusline("");
+    assert_eq!(rlock!(window.viewport()).actual_shape().height(), 4);
+    let mut actual = Canvas::new(terminal_size);
+    window.draw(&mut actual);
+    assert_eq!(
+      actual.frame().raw_symbols()[3].join(""),
+      "                    "
+    );
+  }
+
+  #[test]
+  fn region_at1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec!["Hello, RSVIM!\n", "This is a test.\n"]);
+    let terminal_size = U16Size::new(20, 4);
+    let window_local_options = WindowLocalOptions::builder()
+      .number(true)
+      .statusline("%f")
+      .build();
+    let window = make_window_from_size(terminal_size, buffer.clone(), &window_local_options);
+    let offset = window.content_offset();
+    assert!(offset > 0);
+
+    assert_eq!(
+      window.region_at(point!(x: offset + 2, y: 1)),
+      Some(WindowRegion::Content(point!(x: 2, y: 1)))
+    );
+    assert_eq!(
+      window.region_at(point!(x: 0, y: 2)),
+      Some(WindowRegion::Gutter(2))
+    );
+    assert_eq!(
+      window.region_at(point!(x: 5, y: 3)),
+      Some(WindowRegion::StatusLine(5))
+    );
+    assert_eq!(window.region_at(point!(x: 5, y: 4)), None);
+  }
 }
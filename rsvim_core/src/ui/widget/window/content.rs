@@ -1,10 +1,15 @@
 //! Vim window's text content widget.
 
+use crate::buf::highlight::resolve;
 use crate::buf::BufferWk;
 use crate::cart::{IRect, U16Pos, U16Rect};
+use crate::defaults::grapheme::grapheme_indices;
+use crate::defaults::highlight::{SEARCH_PRIORITY, SELECTION_PRIORITY};
 use crate::envar;
 use crate::ui::canvas::{Canvas, Cell};
+use crate::ui::highlight::apply_style;
 use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::picker::make_styled_cells;
 use crate::ui::widget::window::viewport::ViewportWk;
 use crate::ui::widget::Widgetable;
 use crate::{inode_generate_impl, rlock};
@@ -12,6 +17,7 @@ use crate::{inode_generate_impl, rlock};
 use geo::point;
 use std::convert::From;
 use tracing::trace;
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Clone)]
 /// The widget contains text contents for Vim window.
@@ -35,6 +41,11 @@ impl WindowContent {
       viewport,
     }
   }
+
+  /// Set buffer.
+  pub fn set_buffer(&mut self, buffer: BufferWk) {
+    self.buffer = buffer;
+  }
 }
 
 inode_generate_impl!(WindowContent, base);
@@ -69,6 +80,17 @@ impl Widgetable for WindowContent {
 
     let buffer = self.buffer.upgrade().unwrap();
     let buffer = rlock!(buffer);
+    let decorations = viewport.extmark_decorations(&buffer);
+    let normal_style = canvas.highlight("Normal");
+    let search_style = canvas.highlight_layer("Search");
+    let selection_style = canvas.highlight_layer("Visual");
+    let cursor_word_style = canvas.highlight_layer("CursorWord");
+    let hint_style = canvas.highlight_layer("HintLabel");
+    let blank = || {
+      let mut cell = Cell::from(' ');
+      apply_style(&mut cell, &normal_style);
+      cell
+    };
 
     let mut row_idx = 0_u16;
     let mut line_idx = viewport.start_line_idx();
@@ -82,6 +104,13 @@ impl Widgetable for WindowContent {
 
       let line_slice = lines_slice.next().unwrap();
       let line_viewport = viewport.lines().get(&line_idx).unwrap();
+      // The virtual texts are only shown when the end of the line is visible.
+      let line_end_char_idx = line_slice.len_chars()
+        - line_slice
+          .chars_at(line_slice.len_chars())
+          .reversed()
+          .take_while(|c| *c == '\n' || *c == '\r')
+          .count();
 
       trace!(
         "0-line_idx:{}, row_idx:{}, line_viewport:{:?}",
@@ -142,18 +171,47 @@ impl Widgetable for WindowContent {
           // Render line content.
           if r.end_char_idx() > r.start_char_idx() {
             let mut total_width = 0_usize;
-            let mut char_idx = r.start_char_idx();
-            let mut chars_slice = line_slice.get_chars_at(r.start_char_idx()).unwrap();
-            while char_idx < r.end_char_idx() {
-              let c = chars_slice.next().unwrap();
-              let (unicode_symbol, unicode_width) = buffer.char_symbol(c);
-
-              let cell = Cell::with_symbol(unicode_symbol);
+            let chars_slice = line_slice.get_chars_at(r.start_char_idx()).unwrap();
+            for (char_idx, c, n) in grapheme_indices(chars_slice, r.start_char_idx())
+              .take_while(|(char_idx, _, _)| *char_idx < r.end_char_idx())
+            {
+              let (mut unicode_symbol, unicode_width) = buffer.char_symbol(c);
+              // The rest chars of the grapheme cluster are rendered in the same cell.
+              for k in 1..n {
+                unicode_symbol.push(line_slice.char(char_idx + k));
+              }
+
+              let hint_label = viewport.hint_label(line_idx, char_idx);
+              let mut cell = match hint_label {
+                Some(label) => Cell::from(label),
+                None => Cell::with_symbol(unicode_symbol),
+              };
+              // Layer the buffer highlights, the extmarks and the window-local highlights.
+              let mut layers = buffer.highlights().styles_at(line_idx, char_idx);
+              layers.extend(decorations.styles_at(line_idx, char_idx));
+              if viewport.is_search_highlighted(line_idx, char_idx) {
+                layers.push((SEARCH_PRIORITY, 0, search_style));
+              }
+              if viewport.is_selected(line_idx, char_idx) {
+                layers.push((SELECTION_PRIORITY, 0, selection_style));
+              }
+              let mut style = normal_style;
+              if let Some(layered) = resolve(layers) {
+                style.merge(&layered);
+              }
+              if viewport.is_word_highlighted(line_idx, char_idx) {
+                style.merge(&cursor_word_style);
+              }
+              // The hint label is always on top.
+              if hint_label.is_some() {
+                style = normal_style;
+                style.merge(&hint_style);
+              }
+              apply_style(&mut cell, &style);
               let cell_upos = point!(x: col_idx + upos.x(), y: row_idx + upos.y());
               canvas.frame_mut().set_cell(cell_upos, cell);
 
               col_idx += unicode_width as u16;
-              char_idx += 1;
               total_width += unicode_width;
             }
             trace!(
@@ -168,15 +226,32 @@ impl Widgetable for WindowContent {
             debug_assert_eq!(total_width, r.end_dcol_idx() - r.start_dcol_idx());
           }
 
-          // Render left empty parts.
+          // Render left empty parts, the virtual texts are shown after the end of the line (with
+          // a space in between).
           let occupied_length =
             (r.end_dcol_idx() - r.start_dcol_idx()) as u16 + start_fills + end_fills;
           if width > occupied_length {
             let left_length = width - occupied_length;
-            let cells = std::iter::repeat(' ')
-              .take(left_length as usize)
-              .map(Cell::from)
-              .collect::<Vec<_>>();
+            let mut cells = vec![];
+            if row_idx == last_row_idx && r.end_char_idx() >= line_end_char_idx {
+              for (text, style) in decorations.virt_texts(line_idx) {
+                let available = (left_length as usize).saturating_sub(cells.len() + 1);
+                if available == 0 {
+                  break;
+                }
+                cells.push(blank());
+                let mut text_style = normal_style;
+                text_style.merge(style);
+                let mut text_cells = make_styled_cells(
+                  text,
+                  UnicodeWidthStr::width(text.as_str()).min(available),
+                  &text_style,
+                );
+                cells.append(&mut text_cells);
+              }
+            }
+            let padding = left_length as usize - cells.len();
+            cells.extend(std::iter::repeat_with(blank).take(padding));
             let cells_upos = point!(x: col_idx + upos.x(), y: row_idx + upos.y());
             canvas.frame_mut().set_cells_at(cells_upos, cells);
             col_idx += left_length;
@@ -214,6 +289,15 @@ impl Widgetable for WindowContent {
 
           row_idx += 1;
         }
+      } else if row_idx < height {
+        // The line is too short to show at current `start_dcolumn` (when 'wrap' is `false`), it
+        // still occupies an empty row, including the last line in viewport.
+        let cells = std::iter::repeat_with(blank)
+          .take(width as usize)
+          .collect::<Vec<_>>();
+        let cells_upos = point!(x: upos.x(), y: row_idx + upos.y());
+        canvas.frame_mut().set_cells_at(cells_upos, cells);
+        row_idx += 1;
       }
 
       line_idx += 1;
@@ -221,9 +305,8 @@ impl Widgetable for WindowContent {
 
     // If buffer has no more lines, render empty spaces to left parts of the window content.
     while row_idx < height {
-      let cells = std::iter::repeat(' ')
+      let cells = std::iter::repeat_with(blank)
         .take(width as usize)
-        .map(Cell::from)
         .collect::<Vec<_>>();
       let cells_upos = point!(x: upos.x(), y: row_idx + upos.y());
       canvas.frame_mut().set_cells_at(cells_upos, cells);
@@ -237,12 +320,14 @@ impl Widgetable for WindowContent {
 mod tests {
   use super::*;
 
-  use crate::buf::BufferArc;
+  use crate::buf::highlight::HighlightStyle;
+  use crate::buf::{BufferArc, Extmark, MarkPos};
   use crate::cart::U16Size;
   use crate::test::buf::{make_buffer_from_lines, make_empty_buffer};
   use crate::test::log::init as test_log_init;
   use crate::ui::tree::Tree;
   use crate::ui::widget::window::{Viewport, ViewportOptions, WindowLocalOptions};
+  use crate::wlock;
 
   use compact_str::ToCompactString;
   use ropey::{Rope, RopeBuilder};
@@ -335,6 +420,64 @@ mod tests {
     do_test_draw_from_top_left(&actual, &expect);
   }
 
+  #[test]
+  fn draw_from_top_left_nowrap_scrolled1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec![
+      "Hello, RSVIM!\n",
+      "Hi\n",
+      "\tThis is a quite simple and small test lines.\n",
+    ]);
+    let expect = vec!["lo, RSVIM!", "          ", ">>>>>This ", "          "];
+
+    let terminal_size = U16Size::new(10, 4);
+    let window_options = WindowLocalOptions::builder().wrap(false).build();
+    let viewport_options = ViewportOptions::from(&window_options);
+    let actual_shape = U16Rect::new((0, 0), (terminal_size.width(), terminal_size.height()));
+    let mut viewport = Viewport::new(&viewport_options, Arc::downgrade(&buffer), &actual_shape);
+    viewport.sync_from_top_left(0, 3);
+    let viewport = Viewport::to_arc(viewport);
+    let shape = IRect::new((0, 0), (10, 4));
+    let window_content =
+      WindowContent::new(shape, Arc::downgrade(&buffer), Arc::downgrade(&viewport));
+    let mut actual = Canvas::new(terminal_size);
+    window_content.draw(&mut actual);
+    do_test_draw_from_top_left(&actual, &expect);
+  }
+
+  #[test]
+  fn draw_from_top_left_nowrap_scrolled2() {
+    test_log_init();
+
+    // The last line in viewport is shorter than the horizontal scroll, and the canvas is drawn
+    // before scrolling.
+    let buffer = make_buffer_from_lines(vec![
+      "Hello, RSVIM!\n",
+      "Hello, world!\n",
+      "Hi\n",
+      "Hello, again!\n",
+    ]);
+    let expect = vec!["lo, RSVIM!", "lo, world!", "          "];
+
+    let terminal_size = U16Size::new(10, 3);
+    let window_options = WindowLocalOptions::builder().wrap(false).build();
+    let viewport_options = ViewportOptions::from(&window_options);
+    let actual_shape = U16Rect::new((0, 0), (terminal_size.width(), terminal_size.height()));
+    let shape = IRect::new((0, 0), (10, 3));
+    let mut actual = Canvas::new(terminal_size);
+    for start_dcol_idx in [0, 3] {
+      let mut viewport = Viewport::new(&viewport_options, Arc::downgrade(&buffer), &actual_shape);
+      viewport.sync_from_top_left(0, start_dcol_idx);
+      let viewport = Viewport::to_arc(viewport);
+      assert_eq!(rlock!(viewport).end_line_idx(), 3);
+      let window_content =
+        WindowContent::new(shape, Arc::downgrade(&buffer), Arc::downgrade(&viewport));
+      window_content.draw(&mut actual);
+    }
+    do_test_draw_from_top_left(&actual, &expect);
+  }
+
   #[test]
   fn draw_from_top_left_nowrap2() {
     test_log_init();
@@ -846,4 +989,29 @@ mod tests {
     let actual = make_window_content_drawn_canvas(terminal_size, window_options, buffer.clone());
     do_test_draw_from_top_left(&actual, &expect);
   }
+
+  #[test]
+  fn draw_extmark1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec!["Hello\n", "World, RSVIM!\n", "abc\n"]);
+    {
+      let mut buffer = wlock!(buffer);
+      let style = HighlightStyle::default();
+      for (line_idx, text) in [(0, "err"), (1, "hidden"), (2, "warning!")] {
+        let start = buffer.pos_to_char(MarkPos::new(line_idx, 0));
+        let mut extmark = Extmark::new(1, start, 100);
+        extmark.virt_text = Some((text.to_string(), style));
+        buffer.extmarks_mut().add(extmark);
+      }
+    }
+    // The virtual text is shown after the end of line, and truncated by the window. It's not shown
+    // if the line end is not visible.
+    let expect = vec!["Hello err ", "World, RSV", "abc warnin", "          "];
+
+    let terminal_size = U16Size::new(10, 4);
+    let window_options = WindowLocalOptions::builder().wrap(false).build();
+    let actual = make_window_content_drawn_canvas(terminal_size, window_options, buffer.clone());
+    do_test_draw_from_top_left(&actual, &expect);
+  }
 }
@@ -0,0 +1,140 @@
+//! Vim window's jumplist.
+//!
+//! The jumps (i.e. search, mark jumps) are recorded in the jumplist of the window, then navigated
+//! with `Ctrl-O` (older) and `Ctrl-I` (newer).
+//! See: <https://vimhelp.org/motion.txt.html#jumplist>.
+
+use crate::buf::{BufferId, MarkPos};
+use crate::defaults;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// A jump position.
+pub struct Jump {
+  pub buffer_id: BufferId,
+  pub pos: MarkPos,
+}
+
+impl Jump {
+  pub fn new(buffer_id: BufferId, pos: MarkPos) -> Self {
+    Jump { buffer_id, pos }
+  }
+
+  // Whether the two jumps are on the same line, only one of them is kept in the jumplist.
+  fn same_line(&self, other: &Jump) -> bool {
+    self.buffer_id == other.buffer_id && self.pos.line_idx == other.pos.line_idx
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The jumplist, from the oldest to the newest.
+pub struct JumpList {
+  jumps: Vec<Jump>,
+  // The current index, it equals to the length when it's at the end (i.e. not navigating).
+  current: usize,
+}
+
+impl JumpList {
+  pub fn new() -> Self {
+    JumpList::default()
+  }
+
+  pub fn len(&self) -> usize {
+    self.jumps.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.jumps.is_empty()
+  }
+
+  pub fn current(&self) -> usize {
+    self.current
+  }
+
+  pub fn iter(&self) -> std::slice::Iter<'_, Jump> {
+    self.jumps.iter()
+  }
+
+  /// Record a jump (i.e. the position before the jump) as the newest, the old jump on the same
+  /// line is removed.
+  pub fn push(&mut self, jump: Jump) {
+    self.jumps.retain(|j| !j.same_line(&jump));
+    self.jumps.push(jump);
+    if self.jumps.len() > defaults::win::JUMPLIST_SIZE {
+      self.jumps.remove(0);
+    }
+    self.current = self.jumps.len();
+  }
+
+  /// Go to the older jump, i.e. `Ctrl-O`. The `from` position is recorded when it's at the end,
+  /// thus `Ctrl-I` can come back.
+  pub fn back(&mut self, from: Jump) -> Option<Jump> {
+    if self.current == self.jumps.len() {
+      self.push(from);
+      // Skip the position just recorded.
+      self.current = self.jumps.len() - 1;
+    }
+    if self.current == 0 {
+      return None;
+    }
+    self.current -= 1;
+    Some(self.jumps[self.current])
+  }
+
+  /// Go to the newer jump, i.e. `Ctrl-I`.
+  pub fn forward(&mut self) -> Option<Jump> {
+    if self.current + 1 >= self.jumps.len() {
+      return None;
+    }
+    self.current += 1;
+    Some(self.jumps[self.current])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn jump(line_idx: usize) -> Jump {
+    Jump::new(1, MarkPos::new(line_idx, 0))
+  }
+
+  #[test]
+  fn push1() {
+    let mut jumplist = JumpList::new();
+    jumplist.push(jump(1));
+    jumplist.push(jump(5));
+    jumplist.push(jump(1));
+    assert_eq!(
+      jumplist.iter().copied().collect::<Vec<_>>(),
+      vec![jump(5), jump(1)]
+    );
+    assert_eq!(jumplist.current(), 2);
+
+    for i in 0..(defaults::win::JUMPLIST_SIZE + 10) {
+      jumplist.push(jump(i + 10));
+    }
+    assert_eq!(jumplist.len(), defaults::win::JUMPLIST_SIZE);
+  }
+
+  #[test]
+  fn back_forward1() {
+    let mut jumplist = JumpList::new();
+    assert_eq!(jumplist.forward(), None);
+    jumplist.push(jump(1));
+    jumplist.push(jump(5));
+
+    // Currently at line 9.
+    assert_eq!(jumplist.back(jump(9)), Some(jump(5)));
+    assert_eq!(jumplist.back(jump(5)), Some(jump(1)));
+    assert_eq!(jumplist.back(jump(1)), None);
+    assert_eq!(jumplist.forward(), Some(jump(5)));
+    assert_eq!(jumplist.forward(), Some(jump(9)));
+    assert_eq!(jumplist.forward(), None);
+
+    // A new jump is appended as the newest, and the navigation is reset.
+    jumplist.back(jump(9));
+    jumplist.push(jump(20));
+    assert_eq!(jumplist.len(), 4);
+    assert_eq!(jumplist.current(), 4);
+  }
+}
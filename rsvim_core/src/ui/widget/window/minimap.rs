@@ -0,0 +1,274 @@
+//! Vim window's minimap widget.
+//!
+//! The minimap is placed at the right side of the window, it renders a downsampled view of the
+//! buffer with block characters:
+//!
+//! - Each row of the minimap covers [`LINES_PER_ROW`] lines of the buffer, i.e. the upper half
+//!   block `▀` is the first line, the lower half block `▄` is the second line.
+//! - Each column of the minimap covers [`CHARS_PER_COLUMN`] chars of the line, a half block is
+//!   filled if there's any non-whitespace char in it.
+//!
+//! The lines shown in the window (i.e. the viewport region) are rendered with a different
+//! background color, the marked lines (i.e. diagnostics, search matches) are rendered with a
+//! different foreground color.
+
+use crate::buf::BufferWk;
+use crate::cart::{IRect, U16Pos, U16Rect};
+use crate::envar;
+use crate::ui::canvas::{Canvas, Cell};
+use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::window::viewport::ViewportWk;
+use crate::ui::widget::Widgetable;
+use crate::{inode_generate_impl, rlock};
+
+use compact_str::ToCompactString;
+use crossterm::style::Color;
+use geo::point;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use tracing::trace;
+
+/// Buffer lines covered by each row of the minimap.
+pub const LINES_PER_ROW: usize = 2;
+
+/// Line chars covered by each column of the minimap.
+pub const CHARS_PER_COLUMN: usize = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// The mark on a buffer line, it's rendered with a different highlight group in minimap.
+///
+/// NOTE: When a line has multiple marks, the most severe one (the smallest one) is rendered.
+pub enum MinimapMark {
+  /// Error diagnostic.
+  Error,
+  /// Warning diagnostic.
+  Warning,
+  /// Information/hint diagnostic.
+  Info,
+  /// Search match.
+  Search,
+}
+
+impl MinimapMark {
+  /// Highlight group name.
+  pub fn highlight_group(&self) -> &'static str {
+    match self {
+      MinimapMark::Error => "MinimapError",
+      MinimapMark::Warning => "MinimapWarning",
+      MinimapMark::Info => "MinimapInfo",
+      MinimapMark::Search => "MinimapSearch",
+    }
+  }
+}
+
+/// Calculate the first buffer line shown in the minimap.
+///
+/// When the buffer is too long to fully show, the minimap scrolls proportionally with the
+/// viewport, thus the viewport region is always visible.
+pub fn minimap_start_line_idx(
+  total_lines: usize,
+  height: u16,
+  viewport_lines: Range<usize>,
+) -> usize {
+  let capacity = height as usize * LINES_PER_ROW;
+  if total_lines <= capacity {
+    return 0;
+  }
+  let scrollable_lines = total_lines - capacity;
+  let viewport_height = viewport_lines.end.saturating_sub(viewport_lines.start);
+  let viewport_scrollable_lines = total_lines.saturating_sub(viewport_height).max(1);
+  let start_line_idx = viewport_lines.start * scrollable_lines / viewport_scrollable_lines;
+  start_line_idx.min(scrollable_lines)
+}
+
+/// Calculate the filled columns of a line, i.e. whether each column (covers
+/// [`CHARS_PER_COLUMN`] chars) has any non-whitespace char.
+pub fn line_filled_columns(line: &str, width: u16) -> Vec<bool> {
+  let mut filled = vec![false; width as usize];
+  for (char_idx, c) in line.chars().enumerate() {
+    let col = char_idx / CHARS_PER_COLUMN;
+    if col >= filled.len() {
+      break;
+    }
+    if !c.is_whitespace() {
+      filled[col] = true;
+    }
+  }
+  filled
+}
+
+#[derive(Debug, Clone)]
+/// The minimap widget for Vim window.
+pub struct WindowMinimap {
+  base: InodeBase,
+
+  // Buffer.
+  buffer: BufferWk,
+
+  // Viewport.
+  viewport: ViewportWk,
+
+  // Marked lines, maps from buffer line index to mark.
+  marks: BTreeMap<usize, MinimapMark>,
+}
+
+impl WindowMinimap {
+  /// Make window minimap.
+  pub fn new(shape: IRect, buffer: BufferWk, viewport: ViewportWk) -> Self {
+    let base = InodeBase::new(shape);
+    WindowMinimap {
+      base,
+      buffer,
+      viewport,
+      marks: BTreeMap::new(),
+    }
+  }
+
+  /// Set buffer, the marks are cleared.
+  pub fn set_buffer(&mut self, buffer: BufferWk) {
+    self.buffer = buffer;
+    self.marks.clear();
+  }
+
+  /// Get marked lines.
+  pub fn marks(&self) -> &BTreeMap<usize, MinimapMark> {
+    &self.marks
+  }
+
+  /// Set marked lines.
+  pub fn set_marks(&mut self, marks: BTreeMap<usize, MinimapMark>) {
+    self.marks = marks;
+  }
+
+  /// Get the first buffer line shown in the minimap, and the viewport region.
+  fn lines_range(&self) -> Option<(usize, Range<usize>)> {
+    let viewport = self.viewport.upgrade()?;
+    let viewport = rlock!(viewport);
+    let buffer = self.buffer.upgrade()?;
+    let buffer = rlock!(buffer);
+    let viewport_lines = viewport.start_line_idx()..viewport.end_line_idx();
+    let start_line_idx = minimap_start_line_idx(
+      buffer.len_lines(),
+      self.actual_shape().height(),
+      viewport_lines.clone(),
+    );
+    Some((start_line_idx, viewport_lines))
+  }
+
+  /// Get the buffer line at the terminal position `pos`, i.e. when the minimap is clicked.
+  ///
+  /// Returns `None` if the position is outside of the minimap, or there's no such line.
+  pub fn line_at(&self, pos: U16Pos) -> Option<usize> {
+    let actual_shape = self.actual_shape();
+    if pos.x() < actual_shape.min().x
+      || pos.x() >= actual_shape.max().x
+      || pos.y() < actual_shape.min().y
+      || pos.y() >= actual_shape.max().y
+    {
+      return None;
+    }
+    let (start_line_idx, _) = self.lines_range()?;
+    let line_idx = start_line_idx + (pos.y() - actual_shape.min().y) as usize * LINES_PER_ROW;
+    let buffer = self.buffer.upgrade()?;
+    let total_lines = rlock!(buffer).len_lines();
+    if line_idx < total_lines {
+      Some(line_idx)
+    } else {
+      None
+    }
+  }
+}
+
+inode_generate_impl!(WindowMinimap, base);
+
+impl Widgetable for WindowMinimap {
+  fn draw(&self, canvas: &mut Canvas) {
+    let actual_shape = self.actual_shape();
+    let upos: U16Pos = actual_shape.min().into();
+    let height = actual_shape.height();
+    let width = actual_shape.width();
+
+    // If size is zero, exit.
+    if height == 0 || width == 0 {
+      trace!("Draw window minimap, actual shape is zero");
+      return;
+    }
+
+    let (start_line_idx, viewport_lines) = match self.lines_range() {
+      Some(range) => range,
+      None => return,
+    };
+
+    let buffer = self.buffer.upgrade().unwrap();
+    let buffer = rlock!(buffer);
+
+    for row_idx in 0..height {
+      let row_lines = (0..LINES_PER_ROW)
+        .map(|i| start_line_idx + row_idx as usize * LINES_PER_ROW + i)
+        .collect::<Vec<_>>();
+      let filled = row_lines
+        .iter()
+        .map(|line_idx| match buffer.get_line_content(*line_idx) {
+          Some(line) => line_filled_columns(&line, width),
+          None => vec![false; width as usize],
+        })
+        .collect::<Vec<_>>();
+      let mut style = canvas.highlight("Normal");
+      if let Some(mark) = row_lines
+        .iter()
+        .filter_map(|line_idx| self.marks.get(line_idx))
+        .min()
+      {
+        style.merge(&canvas.highlight_layer(mark.highlight_group()));
+      }
+      if row_lines
+        .iter()
+        .any(|line_idx| viewport_lines.contains(line_idx))
+      {
+        style.merge(&canvas.highlight_layer("MinimapViewport"));
+      }
+      let fg = style.fg.unwrap_or(Color::Reset);
+      let bg = style.bg.unwrap_or(Color::Reset);
+
+      let cells = (0..width as usize)
+        .map(|col| {
+          let symbol = match (filled[0][col], filled[1][col]) {
+            (true, true) => '█',
+            (true, false) => '▀',
+            (false, true) => '▄',
+            (false, false) => ' ',
+          };
+          Cell::new(symbol.to_compact_string(), fg, bg, style.attrs)
+        })
+        .collect::<Vec<_>>();
+      let cells_upos = point!(x: upos.x(), y: row_idx + upos.y());
+      canvas.frame_mut().set_cells_at(cells_upos, cells);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn minimap_start_line_idx1() {
+    // Buffer is short enough.
+    assert_eq!(minimap_start_line_idx(10, 10, 0..5), 0);
+    assert_eq!(minimap_start_line_idx(20, 10, 10..20), 0);
+    // Buffer is too long, scrolls proportionally.
+    assert_eq!(minimap_start_line_idx(100, 10, 0..10), 0);
+    assert_eq!(minimap_start_line_idx(100, 10, 90..100), 80);
+    assert_eq!(minimap_start_line_idx(100, 10, 45..55), 40);
+  }
+
+  #[test]
+  fn line_filled_columns1() {
+    assert_eq!(
+      line_filled_columns("ab  c   de", 6),
+      vec![true, false, true, false, true, false]
+    );
+    assert_eq!(line_filled_columns("", 2), vec![false, false]);
+    assert_eq!(line_filled_columns("abcdef", 2), vec![true, true]);
+  }
+}
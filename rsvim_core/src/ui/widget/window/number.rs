@@ -0,0 +1,188 @@
+//! Vim window's line number gutter widget.
+//!
+//! The gutter column is placed at the left side of the window, it's controlled by the 'number',
+//! 'relativenumber' and 'numberwidth' options:
+//!
+//! - 'number': Show the absolute line number, starts from 1.
+//! - 'relativenumber': Show the line number relative to the cursor line, the cursor line shows
+//!   `0` (or its absolute line number if 'number' is also set).
+//! - 'numberwidth': The minimal width of the gutter column, including the trailing space
+//!   separator.
+//!
+//! Only the first row of a line shows the number, the wrapped continuation rows are blank. The
+//! relative numbers are re-rendered on every redraw, i.e. when the cursor is moved.
+
+use crate::cart::{IRect, U16Pos, U16Rect};
+use crate::envar;
+use crate::ui::canvas::Canvas;
+use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::picker::make_styled_cells;
+use crate::ui::widget::window::viewport::ViewportWk;
+use crate::ui::widget::Widgetable;
+use crate::{inode_generate_impl, rlock};
+
+use geo::point;
+use tracing::trace;
+
+/// Calculate the gutter column width, returns `0` if neither 'number' nor 'relativenumber' is
+/// set.
+///
+/// The width is at least `number_width`, and wide enough to show the largest number (the total
+/// lines for 'number', the window height for 'relativenumber') with a trailing space separator.
+pub fn number_column_width(
+  number: bool,
+  relative_number: bool,
+  number_width: u16,
+  total_lines: usize,
+  height: u16,
+) -> u16 {
+  if !number && !relative_number {
+    return 0;
+  }
+  let mut largest = if number { total_lines } else { 0 };
+  if relative_number {
+    largest = largest.max(height as usize);
+  }
+  let digits = largest.max(1).to_string().len() as u16;
+  number_width.max(digits + 1)
+}
+
+/// Format the number of the line `line_idx` in the gutter column with `width`, the cursor line is
+/// `cursor_line_idx`.
+///
+/// The numbers are right aligned, except the cursor line's absolute number when both 'number' and
+/// 'relativenumber' are set, it's left aligned as Vim does.
+pub fn format_number(
+  line_idx: usize,
+  cursor_line_idx: usize,
+  number: bool,
+  relative_number: bool,
+  width: u16,
+) -> String {
+  let width = (width as usize).saturating_sub(1);
+  if !relative_number {
+    return format!("{:>width$} ", line_idx + 1);
+  }
+  if line_idx == cursor_line_idx {
+    if number {
+      format!("{:<width$} ", line_idx + 1)
+    } else {
+      format!("{:>width$} ", 0)
+    }
+  } else {
+    format!("{:>width$} ", line_idx.abs_diff(cursor_line_idx))
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The line number gutter widget for Vim window.
+pub struct WindowNumber {
+  base: InodeBase,
+
+  // Viewport.
+  viewport: ViewportWk,
+
+  // The 'number' option.
+  number: bool,
+
+  // The 'relativenumber' option.
+  relative_number: bool,
+}
+
+impl WindowNumber {
+  /// Make window line number gutter.
+  pub fn new(shape: IRect, viewport: ViewportWk, number: bool, relative_number: bool) -> Self {
+    let base = InodeBase::new(shape);
+    WindowNumber {
+      base,
+      viewport,
+      number,
+      relative_number,
+    }
+  }
+}
+
+inode_generate_impl!(WindowNumber, base);
+
+impl Widgetable for WindowNumber {
+  fn draw(&self, canvas: &mut Canvas) {
+    let actual_shape = self.actual_shape();
+    let upos: U16Pos = actual_shape.min().into();
+    let height = actual_shape.height();
+    let width = actual_shape.width();
+
+    // If size is zero, exit.
+    if height == 0 || width == 0 {
+      trace!("Draw window number, actual shape is zero");
+      return;
+    }
+
+    let viewport = match self.viewport.upgrade() {
+      Some(viewport) => viewport,
+      None => return,
+    };
+    let viewport = rlock!(viewport);
+    let cursor_line_idx = viewport.cursor().line_idx();
+
+    // The blank rows, i.e. the wrapped continuation rows and the rows after the last line.
+    let mut rows = vec![None; height as usize];
+    for (line_idx, line_viewport) in viewport.lines().iter() {
+      if let Some(first_row_idx) = line_viewport.rows().keys().next() {
+        if (*first_row_idx as usize) < rows.len() {
+          rows[*first_row_idx as usize] = Some(*line_idx);
+        }
+      }
+    }
+
+    let number_style = canvas.highlight("LineNr");
+    let cursor_number_style = canvas.highlight("CursorLineNr");
+    for (row_idx, line_idx) in rows.into_iter().enumerate() {
+      let cells = match line_idx {
+        Some(line_idx) => {
+          let text = format_number(
+            line_idx,
+            cursor_line_idx,
+            self.number,
+            self.relative_number,
+            width,
+          );
+          let style = if line_idx == cursor_line_idx {
+            &cursor_number_style
+          } else {
+            &number_style
+          };
+          make_styled_cells(&text, width as usize, style)
+        }
+        None => make_styled_cells("", width as usize, &number_style),
+      };
+      let cells_upos = point!(x: upos.x(), y: row_idx as u16 + upos.y());
+      canvas.frame_mut().set_cells_at(cells_upos, cells);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn number_column_width1() {
+    assert_eq!(number_column_width(false, false, 4, 100, 10), 0);
+    assert_eq!(number_column_width(true, false, 4, 100, 10), 4);
+    assert_eq!(number_column_width(true, false, 4, 1000, 10), 5);
+    assert_eq!(number_column_width(true, false, 1, 5, 3), 2);
+    // Relative numbers are bounded by the window height.
+    assert_eq!(number_column_width(false, true, 1, 1000, 10), 3);
+    assert_eq!(number_column_width(true, true, 1, 1000, 10), 5);
+  }
+
+  #[test]
+  fn format_number1() {
+    assert_eq!(format_number(4, 2, true, false, 4), "  5 ");
+    assert_eq!(format_number(4, 2, false, true, 4), "  2 ");
+    assert_eq!(format_number(0, 2, false, true, 4), "  2 ");
+    assert_eq!(format_number(2, 2, false, true, 4), "  0 ");
+    assert_eq!(format_number(2, 2, true, true, 4), "3   ");
+    assert_eq!(format_number(1234, 0, true, false, 4), "1235 ");
+  }
+}
@@ -1,12 +1,25 @@
 //! Window local options.
 
 use crate::defaults;
+use crate::ui::widget::window::sign::SignColumn;
 
 #[derive(Debug, Clone)]
 /// Window options.
 pub struct WindowLocalOptions {
   wrap: bool,
   line_break: bool,
+  scroll_off: usize,
+  side_scroll: usize,
+  side_scroll_off: usize,
+  minimap: bool,
+  statusline: String,
+  winfixwidth: bool,
+  winfixheight: bool,
+  number: bool,
+  relative_number: bool,
+  number_width: u16,
+  sign_column: SignColumn,
+  sign_width: u16,
 }
 
 impl Default for WindowLocalOptions {
@@ -39,12 +52,149 @@ impl WindowLocalOptions {
   pub fn set_line_break(&mut self, value: bool) {
     self.line_break = value;
   }
+
+  /// The 'scrolloff' option, default to `0`, i.e. the minimal rows to keep above and below the
+  /// cursor, a wrapped line counts all its rows.
+  /// See: <https://vimhelp.org/options.txt.html#%27scrolloff%27>.
+  pub fn scroll_off(&self) -> usize {
+    self.scroll_off
+  }
+
+  pub fn set_scroll_off(&mut self, value: usize) {
+    self.scroll_off = value;
+  }
+
+  /// The 'sidescroll' option, default to `1`, i.e. the minimal columns to scroll horizontally when
+  /// 'wrap' is `false`, `0` puts the cursor at the middle of the window.
+  /// See: <https://vimhelp.org/options.txt.html#%27sidescroll%27>.
+  pub fn side_scroll(&self) -> usize {
+    self.side_scroll
+  }
+
+  pub fn set_side_scroll(&mut self, value: usize) {
+    self.side_scroll = value;
+  }
+
+  /// The 'sidescrolloff' option, default to `0`, i.e. the minimal columns to keep on the left and
+  /// right of the cursor when 'wrap' is `false`.
+  /// See: <https://vimhelp.org/options.txt.html#%27sidescrolloff%27>.
+  pub fn side_scroll_off(&self) -> usize {
+    self.side_scroll_off
+  }
+
+  pub fn set_side_scroll_off(&mut self, value: usize) {
+    self.side_scroll_off = value;
+  }
+
+  /// The 'minimap' option, default to `false`.
+  pub fn minimap(&self) -> bool {
+    self.minimap
+  }
+
+  pub fn set_minimap(&mut self, value: bool) {
+    self.minimap = value;
+  }
+
+  /// The 'statusline' option, default to empty, i.e. the statusline is not shown.
+  /// See: <https://vimhelp.org/options.txt.html#%27statusline%27>.
+  pub fn statusline(&self) -> &str {
+    &self.statusline
+  }
+
+  pub fn set_statusline(&mut self, value: &str) {
+    self.statusline = value.to_string();
+  }
+
+  /// The 'winfixwidth' option, default to `false`, i.e. keep the window width when the tiled
+  /// windows are re-layout.
+  /// See: <https://vimhelp.org/options.txt.html#%27winfixwidth%27>.
+  pub fn winfixwidth(&self) -> bool {
+    self.winfixwidth
+  }
+
+  pub fn set_winfixwidth(&mut self, value: bool) {
+    self.winfixwidth = value;
+  }
+
+  /// The 'winfixheight' option, default to `false`, i.e. keep the window height when the tiled
+  /// windows are re-layout.
+  /// See: <https://vimhelp.org/options.txt.html#%27winfixheight%27>.
+  pub fn winfixheight(&self) -> bool {
+    self.winfixheight
+  }
+
+  pub fn set_winfixheight(&mut self, value: bool) {
+    self.winfixheight = value;
+  }
+
+  /// The 'number' option, default to `false`, i.e. show the line numbers in the gutter column.
+  /// See: <https://vimhelp.org/options.txt.html#%27number%27>.
+  pub fn number(&self) -> bool {
+    self.number
+  }
+
+  pub fn set_number(&mut self, value: bool) {
+    self.number = value;
+  }
+
+  /// The 'relativenumber' option, default to `false`, i.e. show the line numbers relative to the
+  /// cursor line in the gutter column.
+  /// See: <https://vimhelp.org/options.txt.html#%27relativenumber%27>.
+  pub fn relative_number(&self) -> bool {
+    self.relative_number
+  }
+
+  pub fn set_relative_number(&mut self, value: bool) {
+    self.relative_number = value;
+  }
+
+  /// The 'numberwidth' option, default to `4`, i.e. the minimal width of the gutter column.
+  /// See: <https://vimhelp.org/options.txt.html#%27numberwidth%27>.
+  pub fn number_width(&self) -> u16 {
+    self.number_width
+  }
+
+  pub fn set_number_width(&mut self, value: u16) {
+    self.number_width = value;
+  }
+
+  /// The 'signcolumn' option, default to `auto`, i.e. only show the sign column when there're
+  /// signs placed in the buffer.
+  /// See: <https://vimhelp.org/options.txt.html#%27signcolumn%27>.
+  pub fn sign_column(&self) -> SignColumn {
+    self.sign_column
+  }
+
+  pub fn set_sign_column(&mut self, value: SignColumn) {
+    self.sign_column = value;
+  }
+
+  /// The 'signwidth' option, default to `2`, i.e. the width of the sign column.
+  pub fn sign_width(&self) -> u16 {
+    self.sign_width
+  }
+
+  pub fn set_sign_width(&mut self, value: u16) {
+    self.sign_width = value;
+  }
 }
 
 /// The builder for [`WindowLocalOptions`].
 pub struct WindowOptionsBuilder {
   wrap: bool,
   line_break: bool,
+  scroll_off: usize,
+  side_scroll: usize,
+  side_scroll_off: usize,
+  minimap: bool,
+  statusline: String,
+  winfixwidth: bool,
+  winfixheight: bool,
+  number: bool,
+  relative_number: bool,
+  number_width: u16,
+  sign_column: SignColumn,
+  sign_width: u16,
 }
 
 impl WindowOptionsBuilder {
@@ -56,10 +206,70 @@ impl WindowOptionsBuilder {
     self.line_break = value;
     self
   }
+  pub fn scroll_off(&mut self, value: usize) -> &mut Self {
+    self.scroll_off = value;
+    self
+  }
+  pub fn side_scroll(&mut self, value: usize) -> &mut Self {
+    self.side_scroll = value;
+    self
+  }
+  pub fn side_scroll_off(&mut self, value: usize) -> &mut Self {
+    self.side_scroll_off = value;
+    self
+  }
+  pub fn minimap(&mut self, value: bool) -> &mut Self {
+    self.minimap = value;
+    self
+  }
+  pub fn statusline(&mut self, value: &str) -> &mut Self {
+    self.statusline = value.to_string();
+    self
+  }
+  pub fn winfixwidth(&mut self, value: bool) -> &mut Self {
+    self.winfixwidth = value;
+    self
+  }
+  pub fn winfixheight(&mut self, value: bool) -> &mut Self {
+    self.winfixheight = value;
+    self
+  }
+  pub fn number(&mut self, value: bool) -> &mut Self {
+    self.number = value;
+    self
+  }
+  pub fn relative_number(&mut self, value: bool) -> &mut Self {
+    self.relative_number = value;
+    self
+  }
+  pub fn number_width(&mut self, value: u16) -> &mut Self {
+    self.number_width = value;
+    self
+  }
+  pub fn sign_column(&mut self, value: SignColumn) -> &mut Self {
+    self.sign_column = value;
+    self
+  }
+  pub fn sign_width(&mut self, value: u16) -> &mut Self {
+    self.sign_width = value;
+    self
+  }
   pub fn build(&self) -> WindowLocalOptions {
     WindowLocalOptions {
       wrap: self.wrap,
       line_break: self.line_break,
+      scroll_off: self.scroll_off,
+      side_scroll: self.side_scroll,
+      side_scroll_off: self.side_scroll_off,
+      minimap: self.minimap,
+      statusline: self.statusline.clone(),
+      winfixwidth: self.winfixwidth,
+      winfixheight: self.winfixheight,
+      number: self.number,
+      relative_number: self.relative_number,
+      number_width: self.number_width,
+      sign_column: self.sign_column,
+      sign_width: self.sign_width,
     }
   }
 }
@@ -69,6 +279,18 @@ impl Default for WindowOptionsBuilder {
     WindowOptionsBuilder {
       wrap: defaults::win::WRAP,
       line_break: defaults::win::LINE_BREAK,
+      scroll_off: defaults::win::SCROLL_OFF,
+      side_scroll: defaults::win::SIDE_SCROLL,
+      side_scroll_off: defaults::win::SIDE_SCROLL_OFF,
+      minimap: defaults::win::MINIMAP,
+      statusline: defaults::win::STATUSLINE.to_string(),
+      winfixwidth: defaults::win::WINFIXWIDTH,
+      winfixheight: defaults::win::WINFIXHEIGHT,
+      number: defaults::win::NUMBER,
+      relative_number: defaults::win::RELATIVE_NUMBER,
+      number_width: defaults::win::NUMBER_WIDTH,
+      sign_column: defaults::win::SIGN_COLUMN,
+      sign_width: defaults::win::SIGN_WIDTH,
     }
   }
 }
@@ -78,6 +300,9 @@ impl Default for WindowOptionsBuilder {
 pub struct ViewportOptions {
   pub wrap: bool,
   pub line_break: bool,
+  pub scroll_off: usize,
+  pub side_scroll: usize,
+  pub side_scroll_off: usize,
 }
 
 impl From<&WindowLocalOptions> for ViewportOptions {
@@ -85,6 +310,9 @@ impl From<&WindowLocalOptions> for ViewportOptions {
     Self {
       wrap: value.wrap(),
       line_break: value.line_break(),
+      scroll_off: value.scroll_off(),
+      side_scroll: value.side_scroll(),
+      side_scroll_off: value.side_scroll_off(),
     }
   }
 }
@@ -103,5 +331,14 @@ mod tests {
     let opt2 = WindowLocalOptions::builder().build();
     assert!(opt2.wrap());
     assert!(!opt2.line_break());
+    assert!(!opt2.minimap());
+    assert!(opt2.statusline().is_empty());
+    assert!(!opt2.winfixwidth());
+    assert!(!opt2.winfixheight());
+    assert!(!opt2.number());
+    assert!(!opt2.relative_number());
+    assert_eq!(opt2.number_width(), 4);
+    assert_eq!(opt2.sign_column(), SignColumn::Auto);
+    assert_eq!(opt2.sign_width(), 2);
   }
 }
@@ -0,0 +1,175 @@
+//! Vim window's sign column widget.
+//!
+//! The sign column is placed at the left side of the window (before the line number gutter), it
+//! shows the signs placed in the buffer (see [`sign`](crate::buf::sign)), it's controlled by the
+//! 'signcolumn' and 'signwidth' options:
+//!
+//! - 'signcolumn': `auto` only shows the sign column when there're signs placed in the buffer,
+//!   `yes` always shows it, `no` never shows it.
+//! - 'signwidth': The width of the sign column, the sign text is truncated to it.
+//!
+//! Only the first row of a line shows the sign, the wrapped continuation rows are blank.
+
+use crate::buf::{BufferWk, SignDefinition};
+use crate::cart::{IRect, U16Pos, U16Rect};
+use crate::envar;
+use crate::ui::canvas::Canvas;
+use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::picker::make_styled_cells;
+use crate::ui::widget::window::viewport::ViewportWk;
+use crate::ui::widget::Widgetable;
+use crate::{inode_generate_impl, rlock};
+
+use geo::point;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The 'signcolumn' option.
+pub enum SignColumn {
+  /// Only show the sign column when there're signs placed in the buffer.
+  Auto,
+  /// Always show the sign column.
+  Yes,
+  /// Never show the sign column.
+  No,
+}
+
+impl Display for SignColumn {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SignColumn::Auto => write!(f, "auto"),
+      SignColumn::Yes => write!(f, "yes"),
+      SignColumn::No => write!(f, "no"),
+    }
+  }
+}
+
+impl FromStr for SignColumn {
+  type Err = &'static str;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "auto" => Ok(SignColumn::Auto),
+      "yes" => Ok(SignColumn::Yes),
+      "no" => Ok(SignColumn::No),
+      _ => Err("Invalid 'signcolumn' option value"),
+    }
+  }
+}
+
+/// Calculate the sign column width, returns `0` if it's not shown.
+pub fn sign_column_width(sign_column: SignColumn, sign_width: u16, has_signs: bool) -> u16 {
+  match sign_column {
+    SignColumn::Yes => sign_width,
+    SignColumn::Auto if has_signs => sign_width,
+    _ => 0,
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The sign column widget for Vim window.
+pub struct WindowSign {
+  base: InodeBase,
+
+  // Buffer.
+  buffer: BufferWk,
+
+  // Viewport.
+  viewport: ViewportWk,
+
+  // The sign definitions, synced before each redraw.
+  definitions: BTreeMap<String, SignDefinition>,
+}
+
+impl WindowSign {
+  /// Make window sign column.
+  pub fn new(shape: IRect, buffer: BufferWk, viewport: ViewportWk) -> Self {
+    let base = InodeBase::new(shape);
+    WindowSign {
+      base,
+      buffer,
+      viewport,
+      definitions: BTreeMap::new(),
+    }
+  }
+
+  /// Set the sign definitions.
+  pub fn set_definitions(&mut self, definitions: &BTreeMap<String, SignDefinition>) {
+    self.definitions.clone_from(definitions);
+  }
+}
+
+inode_generate_impl!(WindowSign, base);
+
+impl Widgetable for WindowSign {
+  fn draw(&self, canvas: &mut Canvas) {
+    let actual_shape = self.actual_shape();
+    let upos: U16Pos = actual_shape.min().into();
+    let height = actual_shape.height();
+    let width = actual_shape.width();
+
+    // If size is zero, exit.
+    if height == 0 || width == 0 {
+      trace!("Draw window sign, actual shape is zero");
+      return;
+    }
+
+    let (buffer, viewport) = match (self.buffer.upgrade(), self.viewport.upgrade()) {
+      (Some(buffer), Some(viewport)) => (buffer, viewport),
+      _ => return,
+    };
+    let viewport = rlock!(viewport);
+    let buffer = rlock!(buffer);
+    let shown = buffer
+      .signs()
+      .shown_in(viewport.start_line_idx(), viewport.end_line_idx());
+
+    // The blank rows, i.e. the lines without sign, the wrapped continuation rows and the rows
+    // after the last line.
+    let mut rows: Vec<Option<&SignDefinition>> = vec![None; height as usize];
+    for (line_idx, line_viewport) in viewport.lines().iter() {
+      if let (Some(first_row_idx), Some(sign)) =
+        (line_viewport.rows().keys().next(), shown.get(line_idx))
+      {
+        if (*first_row_idx as usize) < rows.len() {
+          rows[*first_row_idx as usize] = self.definitions.get(&sign.name);
+        }
+      }
+    }
+
+    let column_style = canvas.highlight("SignColumn");
+    for (row_idx, definition) in rows.into_iter().enumerate() {
+      let cells = match definition {
+        Some(definition) => {
+          let mut style = column_style;
+          style.merge(&definition.style);
+          make_styled_cells(&definition.text, width as usize, &style)
+        }
+        None => make_styled_cells("", width as usize, &column_style),
+      };
+      let cells_upos = point!(x: upos.x(), y: row_idx as u16 + upos.y());
+      canvas.frame_mut().set_cells_at(cells_upos, cells);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sign_column1() {
+    assert_eq!("auto".parse::<SignColumn>(), Ok(SignColumn::Auto));
+    assert_eq!("yes".parse::<SignColumn>(), Ok(SignColumn::Yes));
+    assert!("number".parse::<SignColumn>().is_err());
+    assert_eq!(SignColumn::No.to_string(), "no");
+
+    assert_eq!(sign_column_width(SignColumn::Auto, 2, false), 0);
+    assert_eq!(sign_column_width(SignColumn::Auto, 2, true), 2);
+    assert_eq!(sign_column_width(SignColumn::Yes, 3, false), 3);
+    assert_eq!(sign_column_width(SignColumn::No, 2, true), 0);
+  }
+}
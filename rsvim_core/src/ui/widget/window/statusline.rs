@@ -0,0 +1,279 @@
+//! Vim window's statusline widget.
+//!
+//! The statusline is placed at the bottom row of the window, it's rendered from the 'statusline'
+//! format, which is a string with below items:
+//!
+//! - `%s`: The editing mode, e.g. `NORMAL`.
+//! - `%f`: The buffer name, or `[No Name]` if the buffer doesn't have a file.
+//! - `%m`: The modified flag, i.e. `[+]` if the buffer is modified.
+//! - `%l`: The cursor line number, starts from 1.
+//! - `%c`: The cursor column number (of chars), starts from 1.
+//! - `%p`: The percentage through the buffer of the cursor line.
+//! - `%=`: The separation point between the left-aligned and the right-aligned sections.
+//! - `%{name}`: The cached value of the component `name`, or empty if it's not registered.
+//! - `%%`: The `%` char.
+//!
+//! The same format is used by the 'titlestring' option for the terminal title, see
+//! [`Tree::render_title`](crate::ui::tree::Tree::render_title).
+//!
+//! When the format starts with `%!`, it's evaluated by the JS callback on each redraw, and the
+//! result is used as the format, see: <https://vimhelp.org/options.txt.html#%27statusline%27>.
+//!
+//! The components (i.e. the git branch, diagnostics count) are registered with
+//! `Rsvim.statusline.component(name, render, events)`, each component declares the events that
+//! trigger its update. The `render` callback is only called when these events are emitted, and
+//! its result is cached between them, thus the statusline doesn't call JS on each redraw.
+
+use crate::buf::BufferWk;
+use crate::cart::{IRect, U16Pos, U16Rect};
+use crate::envar;
+use crate::state::mode::Mode;
+use crate::ui::canvas::Canvas;
+use crate::ui::tree::internal::{InodeBase, InodeId, Inodeable};
+use crate::ui::widget::picker::make_styled_cells;
+use crate::ui::widget::window::viewport::ViewportWk;
+use crate::ui::widget::Widgetable;
+use crate::{inode_generate_impl, rlock};
+
+use std::collections::BTreeMap;
+use tracing::trace;
+use unicode_width::UnicodeWidthStr;
+
+/// The 'statusline' format prefix that it's evaluated by the JS callback.
+pub const EVAL_PREFIX: &str = "%!";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The information rendered in the statusline.
+pub struct StatusLineInfo {
+  /// Editing mode.
+  pub mode: String,
+  /// Buffer name.
+  pub name: String,
+  /// Whether the buffer is modified.
+  pub modified: bool,
+  /// Cursor line index, starts from 0.
+  pub line_idx: usize,
+  /// Cursor char index, starts from 0.
+  pub char_idx: usize,
+  /// Total lines of the buffer.
+  pub total_lines: usize,
+  /// The cached values of the components.
+  pub components: BTreeMap<String, String>,
+}
+
+/// Render the statusline `format` with the `info`, and fill it to `width`.
+pub fn render_statusline(format: &str, info: &StatusLineInfo, width: usize) -> String {
+  let mut sections = vec![String::new()];
+  let mut chars = format.chars();
+  while let Some(c) = chars.next() {
+    if c != '%' {
+      sections.last_mut().unwrap().push(c);
+      continue;
+    }
+    let section = sections.last_mut().unwrap();
+    match chars.next() {
+      Some('s') => section.push_str(&info.mode),
+      Some('f') => section.push_str(&info.name),
+      Some('m') => {
+        if info.modified {
+          section.push_str("[+]");
+        }
+      }
+      Some('l') => section.push_str(&(info.line_idx + 1).to_string()),
+      Some('c') => section.push_str(&(info.char_idx + 1).to_string()),
+      Some('p') => {
+        let percentage = (info.line_idx + 1) * 100 / info.total_lines.max(1);
+        section.push_str(&percentage.min(100).to_string());
+      }
+      Some('=') => sections.push(String::new()),
+      Some('{') => {
+        let name = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+        if let Some(value) = info.components.get(&name) {
+          section.push_str(value);
+        }
+      }
+      Some('%') => section.push('%'),
+      Some(c) => {
+        section.push('%');
+        section.push(c);
+      }
+      None => section.push('%'),
+    }
+  }
+
+  // The spaces are evenly filled into the separation points.
+  let used: usize = sections
+    .iter()
+    .map(|s| UnicodeWidthStr::width(s.as_str()))
+    .sum();
+  let separators = sections.len() - 1;
+  let spaces = width.saturating_sub(used);
+  let mut result = String::new();
+  for (i, section) in sections.iter().enumerate() {
+    result.push_str(section);
+    if i < separators {
+      let fill = spaces / separators + if i < spaces % separators { 1 } else { 0 };
+      result.push_str(&" ".repeat(fill));
+    }
+  }
+  result
+}
+
+#[derive(Debug, Clone)]
+/// The statusline widget for Vim window.
+pub struct StatusLine {
+  base: InodeBase,
+
+  // Buffer.
+  buffer: BufferWk,
+
+  // Viewport.
+  viewport: ViewportWk,
+
+  // The 'statusline' format.
+  format: String,
+
+  // Editing mode.
+  mode: Mode,
+
+  // The format evaluated by the JS callback, if the 'statusline' starts with `%!`.
+  evaluated: Option<String>,
+
+  // The cached values of the components.
+  components: BTreeMap<String, String>,
+}
+
+impl StatusLine {
+  /// Make window statusline.
+  pub fn new(shape: IRect, buffer: BufferWk, viewport: ViewportWk, format: &str) -> Self {
+    let base = InodeBase::new(shape);
+    StatusLine {
+      base,
+      buffer,
+      viewport,
+      format: format.to_string(),
+      mode: Mode::Normal,
+      evaluated: None,
+      components: BTreeMap::new(),
+    }
+  }
+
+  /// Set buffer.
+  pub fn set_buffer(&mut self, buffer: BufferWk) {
+    self.buffer = buffer;
+  }
+
+  /// Get the 'statusline' format.
+  pub fn format(&self) -> &str {
+    &self.format
+  }
+
+  /// Set editing mode.
+  pub fn set_mode(&mut self, mode: Mode) {
+    self.mode = mode;
+  }
+
+  /// Set the format evaluated by the JS callback.
+  pub fn set_evaluated(&mut self, evaluated: Option<String>) {
+    self.evaluated = evaluated;
+  }
+
+  /// Set the cached values of the components.
+  pub fn set_components(&mut self, components: &BTreeMap<String, String>) {
+    self.components.clone_from(components);
+  }
+
+  /// Collect the rendered information from buffer and viewport.
+  pub fn info(&self) -> Option<StatusLineInfo> {
+    make_info(&self.buffer, &self.viewport, self.mode, &self.components)
+  }
+}
+
+/// Collect the rendered information from buffer and viewport, i.e. the statusline or the terminal
+/// title (see the 'titlestring' option).
+pub fn make_info(
+  buffer: &BufferWk,
+  viewport: &ViewportWk,
+  mode: Mode,
+  components: &BTreeMap<String, String>,
+) -> Option<StatusLineInfo> {
+  let buffer = buffer.upgrade()?;
+  let buffer = rlock!(buffer);
+  let viewport = viewport.upgrade()?;
+  let viewport = rlock!(viewport);
+  let cursor = viewport.cursor();
+  let name = match buffer.filename() {
+    Some(filename) => filename.to_string_lossy().to_string(),
+    None => "[No Name]".to_string(),
+  };
+  Some(StatusLineInfo {
+    mode: mode.to_string().to_uppercase(),
+    name,
+    modified: buffer.is_modified(),
+    line_idx: cursor.line_idx(),
+    char_idx: cursor.char_idx(),
+    total_lines: buffer.len_lines(),
+    components: components.clone(),
+  })
+}
+
+inode_generate_impl!(StatusLine, base);
+
+impl Widgetable for StatusLine {
+  fn draw(&self, canvas: &mut Canvas) {
+    let actual_shape = self.actual_shape();
+    let upos: U16Pos = actual_shape.min().into();
+    let width = actual_shape.width() as usize;
+
+    // If size is zero, exit.
+    if actual_shape.height() == 0 || width == 0 {
+      trace!("Draw window statusline, actual shape is zero");
+      return;
+    }
+
+    let format = match &self.evaluated {
+      Some(evaluated) => evaluated.as_str(),
+      None if self.format.starts_with(EVAL_PREFIX) => "",
+      None => self.format.as_str(),
+    };
+    let text = match self.info() {
+      Some(info) => render_statusline(format, &info, width),
+      None => String::new(),
+    };
+    let cells = make_styled_cells(&text, width, &canvas.highlight("StatusLine"));
+    canvas.frame_mut().set_cells_at(upos, cells);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn render_statusline1() {
+    let info = StatusLineInfo {
+      mode: "NORMAL".to_string(),
+      name: "a.txt".to_string(),
+      modified: true,
+      line_idx: 4,
+      char_idx: 2,
+      total_lines: 10,
+      components: BTreeMap::from([("git".to_string(), "main".to_string())]),
+    };
+    assert_eq!(
+      render_statusline("%s %f%m%=%l:%c %p%%", &info, 30),
+      "NORMAL a.txt[+]        5:3 50%"
+    );
+    // No separation point.
+    assert_eq!(render_statusline("%f %x", &info, 30), "a.txt %x");
+    // Multiple separation points.
+    assert_eq!(render_statusline("a%=b%=c", &info, 8), "a   b  c");
+    // Too narrow.
+    assert_eq!(render_statusline("%f%=%l", &info, 3), "a.txt5");
+    // Components, the unregistered ones are empty.
+    assert_eq!(
+      render_statusline("%f %{git}%{lsp}%=%l", &info, 12),
+      "a.txt main 5"
+    );
+  }
+}
@@ -1,9 +1,11 @@
 //! Buffer viewport on a window.
 
-use crate::buf::BufferWk;
+use crate::buf::{Buffer, BufferWk, ExtmarkDecorations};
 use crate::cart::U16Rect;
-//use crate::envar;
-//use crate::rlock;
+use crate::defaults;
+use crate::envar;
+use crate::rlock;
+use crate::state::visual::Selection;
 use crate::ui::widget::window::ViewportOptions;
 
 use parking_lot::RwLock;
@@ -461,11 +463,40 @@ pub struct Viewport {
   // End line index in the buffer.
   end_line_idx: usize,
 
+  // Start display column index in the buffer, i.e. the `start_dcolumn` anchor, starts from 0.
+  start_dcol_idx: usize,
+
   // Maps from buffer line index to its displayed rows in the window.
   lines: BTreeMap<usize, LineViewport>,
 
   // Cursor position (if has).
   cursor: CursorViewport,
+
+  // Highlighted occurrences of the word under cursor, maps from buffer line index to char index
+  // ranges.
+  word_highlights: BTreeMap<usize, Vec<Range<usize>>>,
+
+  // Visual mode selection (if has).
+  selection: Option<Selection>,
+
+  // Selected char index ranges, maps from buffer line index to char index range.
+  selection_ranges: BTreeMap<usize, Range<usize>>,
+
+  // Hint jump labels, maps from buffer line index to char index and the label char.
+  hint_labels: BTreeMap<usize, BTreeMap<usize, char>>,
+
+  // Highlighted search matches, maps from buffer line index to char index ranges.
+  search_highlights: BTreeMap<usize, Vec<Range<usize>>>,
+
+  // Prefetched lines around the viewport, maps from buffer line index to its line viewport (the
+  // row indexes start from 0 in each line).
+  prefetched: BTreeMap<usize, LineViewport>,
+
+  // The buffer's changed tick when prefetching.
+  prefetched_tick: usize,
+
+  // Whether the viewport moved since last prefetch.
+  prefetch_pending: bool,
 }
 
 pub type ViewportArc = Arc<RwLock<Viewport>>;
@@ -523,8 +554,17 @@ impl Viewport {
       actual_shape: *actual_shape,
       start_line_idx: line_idx_range.start_line_idx(),
       end_line_idx: line_idx_range.end_line_idx(),
+      start_dcol_idx: 0,
       lines,
       cursor,
+      word_highlights: BTreeMap::new(),
+      selection: None,
+      selection_ranges: BTreeMap::new(),
+      hint_labels: BTreeMap::new(),
+      search_highlights: BTreeMap::new(),
+      prefetched: BTreeMap::new(),
+      prefetched_tick: 0,
+      prefetch_pending: true,
     }
   }
 
@@ -616,6 +656,16 @@ impl Viewport {
     self.end_line_idx
   }
 
+  /// Get start display column index in the buffer, i.e. the `start_dcolumn` anchor, starts from
+  /// 0.
+  ///
+  /// NOTE: When 'wrap' option is `true`, it only applies to the start line, i.e. the display
+  /// columns of the rows skipped above the window. It's 0 unless the start line is taller than the
+  /// window, see [`search_anchor_wrapped`](Viewport::search_anchor_wrapped).
+  pub fn start_dcol_idx(&self) -> usize {
+    self.start_dcol_idx
+  }
+
   /// Get viewport information by lines.
   pub fn lines(&self) -> &BTreeMap<usize, LineViewport> {
     self._internal_check();
@@ -639,18 +689,750 @@ impl Viewport {
     self.cursor = cursor;
   }
 
+  /// Get highlighted occurrences of the word under cursor.
+  pub fn word_highlights(&self) -> &BTreeMap<usize, Vec<Range<usize>>> {
+    &self.word_highlights
+  }
+
+  /// Set highlighted occurrences of the word under cursor.
+  pub fn set_word_highlights(&mut self, word_highlights: BTreeMap<usize, Vec<Range<usize>>>) {
+    self.word_highlights = word_highlights;
+  }
+
+  /// Whether a char is highlighted as an occurrence of the word under cursor.
+  pub fn is_word_highlighted(&self, line_idx: usize, char_idx: usize) -> bool {
+    match self.word_highlights.get(&line_idx) {
+      Some(ranges) => ranges.iter().any(|r| r.contains(&char_idx)),
+      None => false,
+    }
+  }
+
+  /// Get visual mode selection.
+  pub fn selection(&self) -> &Option<Selection> {
+    &self.selection
+  }
+
+  /// Set visual mode selection, and its selected char index ranges of the visible lines.
+  pub fn set_selection(
+    &mut self,
+    selection: Option<Selection>,
+    selection_ranges: BTreeMap<usize, Range<usize>>,
+  ) {
+    self.selection = selection;
+    self.selection_ranges = selection_ranges;
+  }
+
+  /// Whether a char is selected in visual mode.
+  pub fn is_selected(&self, line_idx: usize, char_idx: usize) -> bool {
+    match self.selection_ranges.get(&line_idx) {
+      Some(range) => range.contains(&char_idx),
+      None => false,
+    }
+  }
+
+  /// Set hint jump labels.
+  pub fn set_hint_labels(&mut self, hint_labels: BTreeMap<usize, BTreeMap<usize, char>>) {
+    self.hint_labels = hint_labels;
+  }
+
+  /// Get the hint jump label char displayed on a char (if has).
+  pub fn hint_label(&self, line_idx: usize, char_idx: usize) -> Option<char> {
+    self
+      .hint_labels
+      .get(&line_idx)
+      .and_then(|line| line.get(&char_idx))
+      .copied()
+  }
+
+  /// Get highlighted search matches.
+  pub fn search_highlights(&self) -> &BTreeMap<usize, Vec<Range<usize>>> {
+    &self.search_highlights
+  }
+
+  /// Set highlighted search matches.
+  pub fn set_search_highlights(&mut self, search_highlights: BTreeMap<usize, Vec<Range<usize>>>) {
+    self.search_highlights = search_highlights;
+  }
+
+  /// Whether a char is highlighted as a search match.
+  pub fn is_search_highlighted(&self, line_idx: usize, char_idx: usize) -> bool {
+    match self.search_highlights.get(&line_idx) {
+      Some(ranges) => ranges.iter().any(|r| r.contains(&char_idx)),
+      None => false,
+    }
+  }
+
+  /// Query the decorations of the extmarks intersecting the visible lines.
+  pub fn extmark_decorations(&self, buffer: &Buffer) -> ExtmarkDecorations {
+    buffer.extmark_decorations(self.start_line_idx..self.end_line_idx)
+  }
+
+  /// Whether a char is visible in the viewport.
+  pub fn is_char_visible(&self, line_idx: usize, char_idx: usize) -> bool {
+    match self.lines.get(&line_idx) {
+      Some(line) => line
+        .rows()
+        .values()
+        .any(|row| row.start_char_idx() <= char_idx && char_idx < row.end_char_idx()),
+      None => false,
+    }
+  }
+
+  /// Get the cursor at the char `(line_idx, char_idx)`, the char must be visible. If the char
+  /// doesn't exist (i.e. empty line), the cursor is placed at the first row of the line. If the
+  /// char is inside a grapheme cluster, the cursor is placed at the first char of the cluster.
+  pub fn cursor_of(&self, line_idx: usize, char_idx: usize) -> Option<CursorViewport> {
+    let line = self.lines.get(&line_idx)?;
+    for (row_idx, row) in line.rows() {
+      if let Some((start_dcolumn, end_dcolumn)) = row.char2dcolumns().get(&char_idx) {
+        // Place the cursor on the first char of the grapheme cluster, i.e. all chars in the
+        // cluster are placed in the same cells.
+        let char_idx = if start_dcolumn < end_dcolumn {
+          row
+            .char2dcolumns()
+            .range(..char_idx)
+            .rev()
+            .take_while(|(_, dcols)| **dcols == (*start_dcolumn, *end_dcolumn))
+            .last()
+            .map(|(c, _)| *c)
+            .unwrap_or(char_idx)
+        } else {
+          char_idx
+        };
+        return Some(CursorViewport::new(
+          *start_dcolumn..*end_dcolumn,
+          char_idx,
+          *row_idx,
+          line_idx,
+        ));
+      }
+    }
+    let (row_idx, row) = line.rows().first_key_value()?;
+    Some(CursorViewport::new(
+      row.start_dcol_idx()..row.start_dcol_idx() + 1,
+      char_idx,
+      *row_idx,
+      line_idx,
+    ))
+  }
+
+  /// Get the cursor at the window position `(column, row)`, it's the last char of the row if the
+  /// column is after the end of the row.
+  pub fn cursor_at(&self, column: u16, row: u16) -> Option<CursorViewport> {
+    let (line_idx, row_viewport) = self.lines.iter().find_map(|(line_idx, line)| {
+      line
+        .rows()
+        .get(&row)
+        .map(|row_viewport| (*line_idx, row_viewport))
+    })?;
+    let dcolumn = row_viewport.start_dcol_idx() + column as usize;
+    let char_idx = row_viewport
+      .char2dcolumns()
+      .iter()
+      .find(|(_, (start_dcolumn, end_dcolumn))| *start_dcolumn <= dcolumn && dcolumn < *end_dcolumn)
+      .or_else(|| row_viewport.char2dcolumns().last_key_value())
+      .map(|(char_idx, _)| *char_idx)
+      .unwrap_or(0);
+    self.cursor_of(line_idx, char_idx)
+  }
+
   /// Sync from top-left corner, i.e. `start_line` and `start_dcolumn`.
+  ///
+  /// The prefetched lines (see [`prefetch`](Viewport::prefetch)) are used if they're still valid
+  /// and can fill the viewport.
   pub fn sync_from_top_left(&mut self, start_line: usize, start_dcolumn: usize) {
+    if start_dcolumn != self.start_dcol_idx || !self.is_prefetched_valid() {
+      self.prefetched.clear();
+    }
+    let (line_idx_range, lines) = match self.sync_from_prefetched(start_line) {
+      Some(prefetched) => prefetched,
+      None => sync::from_top_left(
+        &self.options,
+        self.buffer.clone(),
+        &self.actual_shape,
+        start_line,
+        start_dcolumn,
+      ),
+    };
+    self.start_line_idx = line_idx_range.start_line_idx();
+    self.end_line_idx = line_idx_range.end_line_idx();
+    self.start_dcol_idx = start_dcolumn;
+    self.lines = lines;
+    self.prefetch_pending = true;
+  }
+
+  /// Sync from current `start_line` and `start_dcolumn`, i.e. re-calculate the viewport after the
+  /// buffer is changed.
+  pub fn sync(&mut self) {
+    self.prefetched.clear();
+    self.sync_from_top_left(self.start_line_idx, self.start_dcol_idx);
+  }
+
+  /// Whether the viewport moved since last prefetch, i.e. it needs prefetch on idle.
+  pub fn prefetch_pending(&self) -> bool {
+    self.prefetch_pending
+  }
+
+  /// Prefetch the previous and next screenful lines of the viewport, thus a small scroll (i.e.
+  /// moving cursor up/down) doesn't need to re-calculate the lines.
+  ///
+  /// The prefetched rows on each side are capped by
+  /// [`VIEWPORT_PREFETCH_MAX_ROWS`](defaults::win::VIEWPORT_PREFETCH_MAX_ROWS), they're dropped
+  /// when the buffer is edited (see [`Buffer::changed_tick`](crate::buf::Buffer::changed_tick)),
+  /// or the viewport is resized/re-synced.
+  pub fn prefetch(&mut self) {
+    self.prefetch_pending = false;
+    self.prefetched.clear();
+    let height = self.actual_shape.height();
+    if height == 0 || self.actual_shape.width() == 0 {
+      return;
+    }
+    // The skipped rows only apply to the start line when 'wrap' is `true`, i.e. the start line is
+    // taller than the window, the lines around it are not prefetched.
+    if self.options.wrap && self.start_dcol_idx > 0 {
+      return;
+    }
+    let buffer = match self.buffer.upgrade() {
+      Some(buffer) => buffer,
+      None => return,
+    };
+    let (tick, len_lines) = {
+      let buffer = rlock!(buffer);
+      (buffer.changed_tick(), buffer.len_lines())
+    };
+
+    let side = height.min(defaults::win::VIEWPORT_PREFETCH_MAX_ROWS);
+    let start_line = self.start_line_idx.saturating_sub(side as usize);
+    let min = self.actual_shape.min();
+    let prefetch_shape = U16Rect::new(
+      (min.x, min.y),
+      (
+        self.actual_shape.max().x,
+        self
+          .actual_shape
+          .min()
+          .y
+          .saturating_add(height.saturating_add(side.saturating_mul(2))),
+      ),
+    );
     let (line_idx_range, lines) = sync::from_top_left(
       &self.options,
       self.buffer.clone(),
-      &self.actual_shape,
+      &prefetch_shape,
       start_line,
-      start_dcolumn,
+      self.start_dcol_idx,
     );
-    self.start_line_idx = line_idx_range.start_line_idx();
-    self.end_line_idx = line_idx_range.end_line_idx();
-    self.lines = lines;
+
+    for (line_idx, line) in lines.into_iter() {
+      // The last line can be truncated by the window bottom, unless it's the end of buffer.
+      if line_idx + 1 == line_idx_range.end_line_idx() && line_idx + 1 < len_lines {
+        break;
+      }
+      let first_row_idx = line.rows().first_key_value().map(|(r, _)| *r).unwrap_or(0);
+      let rows = line
+        .rows()
+        .iter()
+        .map(|(r, row)| (*r - first_row_idx, row.clone()))
+        .collect();
+      self.prefetched.insert(
+        line_idx,
+        LineViewport::new(rows, line.start_filled_columns(), line.end_filled_columns()),
+      );
+    }
+    self.prefetched_tick = tick;
+  }
+
+  // Whether the prefetched lines are still valid, i.e. the buffer is not changed.
+  fn is_prefetched_valid(&self) -> bool {
+    match self.buffer.upgrade() {
+      Some(buffer) => rlock!(buffer).changed_tick() == self.prefetched_tick,
+      None => false,
+    }
+  }
+
+  // Build the viewport from the prefetched lines, returns `None` if the lines from `start_line`
+  // are not all prefetched, or the last line is partially visible (when 'wrap' is `true`), i.e.
+  // it needs a full sync.
+  fn sync_from_prefetched(
+    &self,
+    start_line: usize,
+  ) -> Option<(sync::ViewportLineRange, BTreeMap<usize, LineViewport>)> {
+    if self.prefetched.is_empty() {
+      return None;
+    }
+    let len_lines = rlock!(self.buffer.upgrade()?).len_lines();
+    if start_line >= len_lines {
+      return None;
+    }
+
+    let height = self.actual_shape.height();
+    let mut lines: BTreeMap<usize, LineViewport> = BTreeMap::new();
+    let mut wrow = 0_u16;
+    let mut line_idx = start_line;
+    while wrow < height && line_idx < len_lines {
+      let line = self.prefetched.get(&line_idx)?;
+      // A line without rows (i.e. the empty last line) still occupies a row.
+      let occupied = line.rows().len().max(1);
+      if wrow as usize + occupied > height as usize {
+        return None;
+      }
+      let rows = line
+        .rows()
+        .iter()
+        .map(|(r, row)| (*r + wrow, row.clone()))
+        .collect();
+      lines.insert(
+        line_idx,
+        LineViewport::new(rows, line.start_filled_columns(), line.end_filled_columns()),
+      );
+      wrow += occupied as u16;
+      line_idx += 1;
+    }
+    Some((sync::ViewportLineRange::new(start_line..line_idx), lines))
+  }
+
+  // Get the first row of a visible line.
+  fn first_row_of(&self, line_idx: usize) -> Option<&RowViewport> {
+    self
+      .lines
+      .get(&line_idx)
+      .and_then(|line| line.rows().first_key_value())
+      .map(|(_, row)| row)
+  }
+
+  /// Search the anchor, i.e. the `start_line` and `start_dcolumn`, that makes the char
+  /// `(line_idx, char_idx)` visible with the minimal movement of current viewport.
+  ///
+  /// The vertical anchor keeps the line outside the 'scrolloff' margins, see
+  /// [`search_anchor_vertically`](Viewport::search_anchor_vertically).
+  ///
+  /// NOTE: The horizontal anchor moves when 'wrap' option is `false`, see
+  /// [`search_anchor_leftward`](Viewport::search_anchor_leftward) and
+  /// [`search_anchor_rightward`](Viewport::search_anchor_rightward). The char is also kept outside
+  /// the 'sidescrolloff' margins. When 'wrap' option is `true`, it only skips the rows of a line
+  /// taller than the window, see [`search_anchor_wrapped`](Viewport::search_anchor_wrapped).
+  pub fn search_anchor(&self, line_idx: usize, char_idx: usize) -> (usize, usize) {
+    let start_line_idx = self.search_anchor_vertically(line_idx);
+    if self.options.wrap {
+      return (
+        start_line_idx,
+        self.search_anchor_wrapped(start_line_idx, line_idx, char_idx),
+      );
+    }
+
+    let off = self.side_scroll_off();
+    let width = self.actual_shape.width() as usize;
+    let start_dcol_idx = match self.first_row_of(line_idx) {
+      Some(row) if char_idx < row.start_char_idx() => {
+        self.search_anchor_leftward(line_idx, char_idx)
+      }
+      Some(row) if char_idx >= row.end_char_idx() => {
+        self.search_anchor_rightward(line_idx, char_idx)
+      }
+      Some(row) => match row.char2dcolumns().get(&char_idx) {
+        // The char is visible, but inside the 'sidescrolloff' margins.
+        Some((start_dcol, _)) if *start_dcol < self.start_dcol_idx + off => {
+          self.search_anchor_leftward(line_idx, char_idx)
+        }
+        Some((_, end_dcol)) if *end_dcol + off > self.start_dcol_idx + width => {
+          self.search_anchor_rightward(line_idx, char_idx)
+        }
+        _ => self.start_dcol_idx,
+      },
+      None => {
+        // The line is not visible, or it's too short to show at current `start_dcolumn`.
+        let start_dcol = self.search_anchor_leftward(line_idx, char_idx);
+        if start_dcol < self.start_dcol_idx {
+          start_dcol
+        } else {
+          self.search_anchor_rightward(line_idx, char_idx)
+        }
+      }
+    };
+    (start_line_idx, start_dcol_idx)
+  }
+
+  /// Search the `start_line` anchor that makes the line `line_idx` fully visible, with at least
+  /// 'scrolloff' rows above and below it (except at the start/end of the buffer). The viewport
+  /// scrolls upward if the line is above the top margin, or downward if it's below the bottom
+  /// margin, otherwise it doesn't move.
+  ///
+  /// The rows are counted by the wrapped rows of the lines, so it only measures the lines between
+  /// current viewport and the line, or the lines within one window height.
+  ///
+  /// NOTE: If the line itself is taller than the window, it's put at the top of the window.
+  pub fn search_anchor_vertically(&self, line_idx: usize) -> usize {
+    let buffer = match self.buffer.upgrade() {
+      Some(buffer) => buffer,
+      None => return self.start_line_idx,
+    };
+    let buffer = rlock!(buffer);
+    let len_lines = buffer.len_lines();
+    let off = self.scroll_off();
+    let height = self.actual_shape.height() as usize;
+    let rows = |l: usize| self.line_rows(&buffer, l);
+
+    // Upward, the rows above the line are less than 'scrolloff'.
+    let mut above = 0_usize;
+    if line_idx >= self.start_line_idx {
+      let mut l = line_idx;
+      while l > self.start_line_idx && above < off {
+        l -= 1;
+        above += rows(l);
+      }
+    }
+    if line_idx < self.start_line_idx || above < off {
+      let mut start_line_idx = line_idx;
+      let mut above = 0_usize;
+      while start_line_idx > 0 && above < off {
+        start_line_idx -= 1;
+        above += rows(start_line_idx);
+      }
+      if start_line_idx < self.start_line_idx {
+        return start_line_idx;
+      }
+    }
+
+    // Downward, the line and the 'scrolloff' rows below it don't fit in the window.
+    let mut needed = rows(line_idx);
+    let mut below = 0_usize;
+    let mut l = line_idx + 1;
+    while l < len_lines && below < off {
+      below += rows(l);
+      l += 1;
+    }
+    needed += std::cmp::min(below, off);
+    if needed > height {
+      return line_idx;
+    }
+    let mut start_line_idx = line_idx;
+    while start_line_idx > self.start_line_idx && needed + rows(start_line_idx - 1) <= height {
+      start_line_idx -= 1;
+      needed += rows(start_line_idx);
+    }
+    start_line_idx
+  }
+
+  // The rows occupied by the line, i.e. always 1 when 'wrap' is `false`. The fully visible lines
+  // are counted by their rows, the others are estimated by their display width (the 'linebreak'
+  // may take a few more rows), see [`estimate_line_rows`](Viewport::estimate_line_rows).
+  fn line_rows(&self, buffer: &Buffer, line_idx: usize) -> usize {
+    if !self.options.wrap {
+      return 1;
+    }
+    if line_idx >= self.start_line_idx && line_idx + 1 < self.end_line_idx {
+      if let Some(line) = self.lines.get(&line_idx) {
+        return line.rows().len().max(1);
+      }
+    }
+    self.estimate_line_rows(buffer, line_idx)
+  }
+
+  // Estimate the rows of the whole line by its display width. It only measures the chars within
+  // one window height, i.e. it returns `height + 1` for a line taller than the window, no matter
+  // how long the line is.
+  fn estimate_line_rows(&self, buffer: &Buffer, line_idx: usize) -> usize {
+    let width = (self.actual_shape.width() as usize).max(1);
+    let height = self.actual_shape.height() as usize;
+    let max_dcolumn = height * width + 1;
+    let (_, line_width) = buffer.char_at_dcolumn(line_idx, max_dcolumn);
+    if line_width >= max_dcolumn {
+      return height + 1;
+    }
+    line_width.div_ceil(width).max(1)
+  }
+
+  /// Search the `start_dcolumn` anchor when 'wrap' option is `true`, i.e. the display columns of
+  /// the rows skipped above the window on the `start_line_idx` (see
+  /// [`search_anchor_vertically`](Viewport::search_anchor_vertically)).
+  ///
+  /// It's only non-zero when the char `(line_idx, char_idx)` is on the start line and the line is
+  /// taller than the window, i.e. a super long line such as the minified code. The rows are
+  /// skipped by the window width, thus the row of the char shows in the window with the minimal
+  /// movement of current viewport, i.e. it's the first row when scrolling upward, or the last row
+  /// when scrolling downward.
+  ///
+  /// If the line is visible, it only measures the chars between the char and the visible rows,
+  /// i.e. it's `O(distance)` instead of `O(line length)`.
+  ///
+  /// NOTE: The rows are estimated by the window width, and only the rows within the window are
+  /// synced to make sure the char is visible, i.e. it's `O(window size)` as well.
+  pub fn search_anchor_wrapped(
+    &self,
+    start_line_idx: usize,
+    line_idx: usize,
+    char_idx: usize,
+  ) -> usize {
+    if start_line_idx != line_idx {
+      // The line is below the start line, the skipped rows are kept if the start line is not
+      // changed.
+      return if start_line_idx == self.start_line_idx {
+        self.start_dcol_idx
+      } else {
+        0
+      };
+    }
+
+    let width = (self.actual_shape.width() as usize).max(1);
+    let height = (self.actual_shape.height() as usize).max(1);
+    let char_dcol = {
+      let buffer = match self.buffer.upgrade() {
+        Some(buffer) => buffer,
+        None => return 0,
+      };
+      let buffer = rlock!(buffer);
+      if self.estimate_line_rows(&buffer, line_idx) <= height {
+        return 0;
+      }
+
+      let rows = self.lines.get(&line_idx).map(|line| line.rows());
+      let first_row = rows
+        .and_then(|rows| rows.first_key_value())
+        .map(|(_, row)| row);
+      let last_row = rows
+        .and_then(|rows| rows.last_key_value())
+        .map(|(_, row)| row);
+      let visible_dcol = rows.and_then(|rows| {
+        rows
+          .values()
+          .find_map(|row| row.char2dcolumns().get(&char_idx).map(|(dcol, _)| *dcol))
+      });
+      match (first_row, last_row, visible_dcol) {
+        (_, _, Some(dcol)) => dcol,
+        (Some(row), _, None) if char_idx < row.start_char_idx() => {
+          row.start_dcol_idx() - buffer.width_between(line_idx, char_idx..row.start_char_idx())
+        }
+        (_, Some(row), None) if char_idx >= row.end_char_idx() => {
+          row.end_dcol_idx() + buffer.width_between(line_idx, row.end_char_idx()..char_idx)
+        }
+        _ => buffer.width_before(line_idx, char_idx),
+      }
+    };
+
+    let char_row = char_dcol / width;
+    let skipped_rows = if line_idx == self.start_line_idx {
+      // Keep the rows skipped by current viewport if the char is still visible.
+      let skipped_rows = self.start_dcol_idx / width;
+      if char_row < skipped_rows {
+        char_row
+      } else if char_row >= skipped_rows + height {
+        char_row + 1 - height
+      } else {
+        skipped_rows
+      }
+    } else {
+      // The line is a new start line, it shows from the first row unless the char is below the
+      // window.
+      char_row.saturating_sub(height - 1)
+    };
+    let start_dcol = skipped_rows * width;
+
+    // The rows synced from the anchor may be shorter than the window width (i.e. the wide chars
+    // or the 'linebreak'), the char shows at the first row if it's not in them.
+    let (_, lines) = sync::from_top_left(
+      &self.options,
+      self.buffer.clone(),
+      &self.actual_shape,
+      line_idx,
+      start_dcol,
+    );
+    let visible = lines.get(&line_idx).is_some_and(|line| {
+      line
+        .rows()
+        .values()
+        .any(|row| row.char2dcolumns().contains_key(&char_idx))
+    });
+    if visible {
+      start_dcol
+    } else {
+      char_dcol
+    }
+  }
+
+  // The 'scrolloff' option, it's at most half of the window height.
+  fn scroll_off(&self) -> usize {
+    let height = self.actual_shape.height() as usize;
+    std::cmp::min(self.options.scroll_off, height.saturating_sub(1) / 2)
+  }
+
+  // The 'sidescrolloff' option, it's at most half of the window width.
+  fn side_scroll_off(&self) -> usize {
+    let width = self.actual_shape.width() as usize;
+    std::cmp::min(self.options.side_scroll_off, width.saturating_sub(1) / 2)
+  }
+
+  /// Search the `start_dcolumn` anchor when the char `(line_idx, char_idx)` is on the left side of
+  /// current viewport, i.e. the char shows at the first column (after the 'sidescrolloff' margin).
+  /// The viewport scrolls at least 'sidescroll' columns, or puts the char at the middle of the
+  /// window if it's `0`.
+  ///
+  /// If the line is visible, it only measures the chars between the char and the first visible
+  /// char on the row, i.e. it's `O(distance)` instead of `O(line length)`. It never moves the
+  /// viewport to the right.
+  pub fn search_anchor_leftward(&self, line_idx: usize, char_idx: usize) -> usize {
+    let buffer = self.buffer.upgrade().unwrap();
+    let buffer = rlock!(buffer);
+    let char_dcol = match self.first_row_of(line_idx) {
+      Some(row) if char_idx < row.start_char_idx() => {
+        row.start_dcol_idx() - buffer.width_between(line_idx, char_idx..row.start_char_idx())
+      }
+      _ => buffer.width_before(line_idx, char_idx),
+    };
+    let off = self.side_scroll_off();
+    let width = self.actual_shape.width() as usize;
+    let start_dcol = char_dcol.saturating_sub(off);
+    if start_dcol >= self.start_dcol_idx {
+      return self.start_dcol_idx;
+    }
+    match self.options.side_scroll {
+      0 => char_dcol.saturating_sub(width / 2).min(start_dcol),
+      side_scroll => std::cmp::max(
+        start_dcol.min(self.start_dcol_idx.saturating_sub(side_scroll)),
+        // Don't scroll the char off the right side.
+        (char_dcol + 1 + off).saturating_sub(width),
+      ),
+    }
+  }
+
+  /// Search the `start_dcolumn` anchor when the char `(line_idx, char_idx)` is on the right side
+  /// of current viewport, i.e. the char shows at the last columns (before the 'sidescrolloff'
+  /// margin). The viewport scrolls at least 'sidescroll' columns, or puts the char at the middle
+  /// of the window if it's `0`.
+  ///
+  /// If the line is visible, it only measures the chars between the last visible char on the row
+  /// and the char, i.e. it's `O(distance)` instead of `O(line length)`. It never moves the
+  /// viewport to the left.
+  pub fn search_anchor_rightward(&self, line_idx: usize, char_idx: usize) -> usize {
+    let buffer = self.buffer.upgrade().unwrap();
+    let buffer = rlock!(buffer);
+    let end_dcol = match self.first_row_of(line_idx) {
+      Some(row) if char_idx >= row.end_char_idx() => {
+        row.end_dcol_idx() + buffer.width_between(line_idx, row.end_char_idx()..char_idx + 1)
+      }
+      _ => buffer.width_before(line_idx, char_idx + 1),
+    };
+    let off = self.side_scroll_off();
+    let width = self.actual_shape.width() as usize;
+    let start_dcol = (end_dcol + off).saturating_sub(width);
+    if start_dcol <= self.start_dcol_idx {
+      return self.start_dcol_idx;
+    }
+    match self.options.side_scroll {
+      0 => end_dcol.saturating_sub(width / 2).max(start_dcol),
+      side_scroll => std::cmp::min(
+        start_dcol.max(self.start_dcol_idx + side_scroll),
+        // Don't scroll the char off the left side.
+        end_dcol.saturating_sub(1 + off).max(start_dcol),
+      ),
+    }
+  }
+
+  /// The nearest char to `char_idx` in the line, that is visible and outside the 'sidescrolloff'
+  /// margins, i.e. where the cursor moves after scrolling horizontally.
+  ///
+  /// Returns `char_idx` if the line is not visible, or none of its chars is visible.
+  pub fn nearest_visible_char(&self, line_idx: usize, char_idx: usize) -> usize {
+    let row = match self.first_row_of(line_idx) {
+      Some(row) => row,
+      None => return char_idx,
+    };
+    let off = self.side_scroll_off();
+    let width = self.actual_shape.width() as usize;
+    let left = self.start_dcol_idx + off;
+    let right = (self.start_dcol_idx + width).saturating_sub(off);
+    let mut visible = row
+      .char2dcolumns()
+      .iter()
+      .filter(|(_, (start_dcol, end_dcol))| *start_dcol >= left && *end_dcol <= right)
+      .map(|(c, _)| *c);
+    match visible.next() {
+      Some(first) => char_idx.clamp(first, visible.next_back().unwrap_or(first)),
+      None => char_idx,
+    }
+  }
+
+  /// Sync from the `line_idx` (as the top line), and put the cursor at the first char of it.
+  pub fn sync_to_line(&mut self, line_idx: usize) {
+    self.sync_from_top_left(line_idx, 0);
+    self.cursor = match self.lines.first_key_value() {
+      Some((first_line_idx, first_line)) => match first_line.rows().first_key_value() {
+        Some((row_idx, first_row)) => {
+          let char_idx = first_row.start_char_idx();
+          match first_row.char2dcolumns().get(&char_idx) {
+            Some((start_dcolumn, end_dcolumn)) => CursorViewport::new(
+              *start_dcolumn..*end_dcolumn,
+              char_idx,
+              *row_idx,
+              *first_line_idx,
+            ),
+            None => CursorViewport::new(0..1, 0, *row_idx, *first_line_idx),
+          }
+        }
+        None => CursorViewport::new(0..1, 0, 0, *first_line_idx),
+      },
+      None => CursorViewport::new(0..1, 0, 0, 0),
+    };
+  }
+
+  /// Scroll the viewport by `lines` (the negative is upward), i.e. the mouse wheel. The last line
+  /// can be scrolled to the top of the window, but not further.
+  ///
+  /// The cursor stays at its char if it's still visible and outside the 'scrolloff' margins,
+  /// otherwise it's moved to the nearest row outside the margins.
+  pub fn scroll_vertically(&mut self, lines: isize) {
+    let len_lines = match self.buffer.upgrade() {
+      Some(buffer) => rlock!(buffer).len_lines(),
+      None => return,
+    };
+    let start_line_idx = (self.start_line_idx as isize + lines)
+      .clamp(0, len_lines.saturating_sub(1) as isize) as usize;
+    if start_line_idx == self.start_line_idx {
+      return;
+    }
+    // The skipped rows of the start line are meaningless for another start line.
+    let start_dcol_idx = if self.options.wrap {
+      0
+    } else {
+      self.start_dcol_idx
+    };
+    self.sync_from_top_left(start_line_idx, start_dcol_idx);
+
+    let (line_idx, char_idx) = (self.cursor.line_idx(), self.cursor.char_idx());
+    let visible = if self.is_char_visible(line_idx, char_idx) {
+      self.cursor_of(line_idx, char_idx)
+    } else {
+      None
+    };
+    let row_idx = match visible {
+      Some(cursor) => cursor.row_idx(),
+      None if line_idx < start_line_idx => 0,
+      None => u16::MAX,
+    };
+    let last_row_idx = self
+      .lines
+      .values()
+      .flat_map(|line| line.rows().keys())
+      .max()
+      .copied()
+      .unwrap_or(0);
+    let off = self.scroll_off() as u16;
+    let top = if self.start_line_idx == 0 {
+      0
+    } else {
+      off.min(last_row_idx)
+    };
+    let bottom = if self.end_line_idx >= len_lines {
+      last_row_idx
+    } else {
+      last_row_idx.saturating_sub(off).max(top)
+    };
+    let cursor = match visible {
+      Some(cursor) if (top..=bottom).contains(&row_idx) => Some(cursor),
+      _ => self.cursor_at(0, row_idx.clamp(top, bottom)),
+    };
+    if let Some(cursor) = cursor {
+      self.cursor = cursor;
+    }
   }
 }
 
@@ -892,6 +1674,7 @@ impl Viewport {
   /// Set options.
   pub fn set_options(&mut self, options: &ViewportOptions) {
     self.options = *options;
+    self.prefetched.clear();
   }
 
   /// Get buffer.
@@ -902,6 +1685,7 @@ impl Viewport {
   /// Set buffer.
   pub fn set_buffer(&mut self, buffer: BufferWk) {
     self.buffer = buffer;
+    self.prefetched.clear();
   }
 
   /// Get actual shape.
@@ -912,6 +1696,7 @@ impl Viewport {
   /// Set actual shape.
   pub fn set_actual_shape(&mut self, actual_shape: &U16Rect) {
     self.actual_shape = *actual_shape;
+    self.prefetched.clear();
   }
 }
 
@@ -922,14 +1707,15 @@ mod tests {
 
   use crate::buf::BufferArc;
   use crate::cart::{IRect, U16Size};
+  use crate::defaults::grapheme::grapheme_indices;
   use crate::envar;
-  use crate::rlock;
   use crate::test::buf::{make_buffer_from_lines, make_empty_buffer};
   #[allow(dead_code)]
   use crate::test::log::init as test_log_init;
   use crate::ui::tree::internal::Inodeable;
   use crate::ui::tree::Tree;
   use crate::ui::widget::window::{Window, WindowLocalOptions};
+  use crate::{rlock, wlock};
 
   use compact_str::ToCompactString;
   use ropey::{Rope, RopeBuilder};
@@ -1060,15 +1846,21 @@ mod tests {
 
         let mut last_char_dcolumn: Option<usize> = None;
         let mut payload = String::new();
-        for c_idx in row.start_char_idx()..row.end_char_idx() {
-          let c = line.get_char(c_idx).unwrap();
+        for (c_idx, c, n) in
+          grapheme_indices(line.chars_at(row.start_char_idx()), row.start_char_idx())
+            .take_while(|(c_idx, _, _)| *c_idx < row.end_char_idx())
+        {
           let c_width = buffer.char_width(c);
           let c_dcols = row.char2dcolumns().get(&c_idx).unwrap();
           assert_eq!(c_dcols.1 - c_dcols.0, c_width);
           if let Some(last_char_docl) = last_char_dcolumn {
             assert_eq!(last_char_docl, c_dcols.0);
           }
-          payload.push(c);
+          // All chars in the grapheme cluster are placed in the same cells.
+          for k in 0..n {
+            assert_eq!(row.char2dcolumns().get(&(c_idx + k)), Some(c_dcols));
+            payload.push(line.char(c_idx + k));
+          }
           last_char_dcolumn = Some(c_dcols.1);
         }
         info!(
@@ -1076,7 +1868,7 @@ mod tests {
           r, payload, expect[*r as usize]
         );
         assert_eq!(payload, expect[*r as usize]);
-        let total_width = payload.chars().map(|c| buffer.char_width(c)).sum::<usize>();
+        let total_width = buffer.str_width(&payload);
         assert_eq!(total_width, row.end_dcol_idx() - row.start_dcol_idx());
       }
     }
@@ -1132,6 +1924,37 @@ mod tests {
     );
   }
 
+  #[test]
+  fn sync_from_top_left_grapheme1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec!["a👩\u{200D}👩\u{200D}👧bcafe\u{0301}s\n", "x👍🏽yz\n"]);
+
+    // The grapheme clusters are never split.
+    let expect = vec!["a👩\u{200D}👩\u{200D}👧bca", "x👍🏽yz\n", ""];
+    let size = U16Size::new(6, 5);
+    let options = WindowLocalOptions::builder().wrap(false).build();
+    let actual = make_viewport_from_size(size, buffer.clone(), &options);
+    let expect_fills: BTreeMap<usize, usize> = vec![(0, 0), (1, 0), (2, 0)].into_iter().collect();
+    do_test_sync_from_top_left(
+      buffer.clone(),
+      &actual,
+      &expect,
+      0,
+      3,
+      &expect_fills,
+      &expect_fills,
+    );
+
+    // The cursor is placed at the first char of the cluster.
+    let cursor = actual.cursor_of(0, 3).unwrap();
+    assert_eq!(cursor.char_idx(), 1);
+    assert_eq!(cursor.start_dcol_idx(), 1);
+    assert_eq!(cursor.end_dcol_idx(), 3);
+    let cursor = actual.cursor_at(2, 1).unwrap();
+    assert_eq!(cursor.char_idx(), 1);
+  }
+
   #[test]
   fn sync_from_top_left_nowrap2() {
     test_log_init();
@@ -2177,4 +3000,330 @@ mod tests {
       &expect_end_fills,
     );
   }
+
+  #[test]
+  fn search_anchor_nowrap1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec![
+      "Hello, RSVIM!\n",
+      "\tThis is a quite simple and small test lines.\n",
+      "Short\n",
+    ]);
+    let size = U16Size::new(10, 2);
+    let options = WindowLocalOptions::builder().wrap(false).build();
+    let mut actual = make_viewport_from_size(size, buffer.clone(), &options);
+    assert_eq!(actual.start_dcol_idx(), 0);
+
+    // Visible.
+    assert_eq!(actual.search_anchor(0, 3), (0, 0));
+    // Rightward, the char `!` ends at the last column.
+    assert_eq!(actual.search_anchor_rightward(0, 12), 3);
+    assert_eq!(actual.search_anchor(0, 12), (0, 3));
+    // Downward.
+    assert_eq!(actual.search_anchor(2, 0), (1, 0));
+
+    actual.sync_from_top_left(0, 3);
+    assert_eq!(actual.start_dcol_idx(), 3);
+    let row = actual.lines().get(&0).unwrap().rows().get(&0).unwrap();
+    assert_eq!(row.start_char_idx(), 3);
+    assert_eq!(row.end_char_idx(), 13);
+    // The tab char starts before the `start_dcolumn`.
+    let line = actual.lines().get(&1).unwrap();
+    assert_eq!(line.start_filled_columns(), 5);
+    assert_eq!(line.rows().get(&1).unwrap().start_char_idx(), 1);
+
+    // Leftward.
+    assert_eq!(actual.search_anchor_leftward(0, 1), 1);
+    assert_eq!(actual.search_anchor(0, 1), (0, 1));
+    assert_eq!(actual.search_anchor(1, 0), (0, 0));
+    assert_eq!(actual.search_anchor(0, 5), (0, 3));
+  }
+
+  #[test]
+  fn search_anchor_sidescroll1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec!["Hello, RSVIM! This is long.\n"]);
+    let size = U16Size::new(10, 1);
+    let viewport = |side_scroll, side_scroll_off| {
+      let options = WindowLocalOptions::builder()
+        .wrap(false)
+        .side_scroll(side_scroll)
+        .side_scroll_off(side_scroll_off)
+        .build();
+      make_viewport_from_size(size, buffer.clone(), &options)
+    };
+
+    // The char is inside the 'sidescrolloff' margin.
+    let mut actual = viewport(1, 2);
+    assert_eq!(actual.search_anchor(0, 7), (0, 0));
+    assert_eq!(actual.search_anchor(0, 8), (0, 1));
+    actual.sync_from_top_left(0, 8);
+    assert_eq!(actual.search_anchor_leftward(0, 9), 7);
+    assert_eq!(actual.nearest_visible_char(0, 0), 10);
+    assert_eq!(actual.nearest_visible_char(0, 20), 15);
+
+    // Scroll at least 'sidescroll' columns.
+    let actual = viewport(5, 0);
+    assert_eq!(actual.search_anchor_rightward(0, 10), 5);
+    // Put the char at the middle.
+    let actual = viewport(0, 0);
+    assert_eq!(actual.search_anchor_rightward(0, 12), 8);
+  }
+
+  #[test]
+  fn search_anchor_scrolloff1() {
+    test_log_init();
+
+    let lines = (0..10).map(|i| format!("Line {}\n", i)).collect::<Vec<_>>();
+    let buffer = make_buffer_from_lines(lines.iter().map(|l| l.as_str()).collect());
+    let size = U16Size::new(10, 4);
+    let options = WindowLocalOptions::builder()
+      .wrap(false)
+      .scroll_off(1)
+      .build();
+    let mut actual = make_viewport_from_size(size, buffer.clone(), &options);
+
+    // Outside the margins.
+    assert_eq!(actual.search_anchor(2, 0), (0, 0));
+    // Downward, keep 1 row below.
+    assert_eq!(actual.search_anchor(3, 0), (1, 0));
+    assert_eq!(actual.search_anchor(6, 0), (4, 0));
+    // Upward, keep 1 row above.
+    actual.sync_from_top_left(5, 0);
+    assert_eq!(actual.search_anchor(5, 0), (4, 0));
+    assert_eq!(actual.search_anchor(6, 0), (5, 0));
+    assert_eq!(actual.search_anchor(1, 0), (0, 0));
+  }
+
+  #[test]
+  fn search_anchor_scrolloff_wrap1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec![
+      "0\n",
+      "1\n",
+      "abcdefghijklmnopqrstuvwxy\n",
+      "3\n",
+      "4\n",
+    ]);
+    let size = U16Size::new(10, 4);
+    let viewport = |scroll_off| {
+      let options = WindowLocalOptions::builder()
+        .wrap(true)
+        .scroll_off(scroll_off)
+        .build();
+      make_viewport_from_size(size, buffer.clone(), &options)
+    };
+
+    // The wrapped line is partially visible, scroll until all its 3 rows are visible.
+    let actual = viewport(0);
+    assert_eq!(actual.end_line_idx(), 3);
+    assert_eq!(actual.search_anchor(1, 0), (0, 0));
+    assert_eq!(actual.search_anchor(2, 0), (1, 0));
+    assert_eq!(actual.search_anchor(3, 0), (2, 0));
+
+    // The rows below the line are counted by the wrapped rows.
+    let actual = viewport(1);
+    assert_eq!(actual.search_anchor(1, 0), (0, 0));
+    assert_eq!(actual.search_anchor(2, 0), (2, 0));
+  }
+
+  #[test]
+  fn sync_from_top_left_wrap_long_line1() {
+    test_log_init();
+
+    // A super long line of the minified code, i.e. 8MB chars.
+    let n = 1_000_000;
+    let minified = format!("{}\n", "var a=1;".repeat(n));
+    let buffer = make_buffer_from_lines(vec![minified.as_str(), "b"]);
+    let rows = |viewport: &Viewport, line_idx: usize| -> Vec<(usize, usize)> {
+      viewport
+        .lines()
+        .get(&line_idx)
+        .unwrap()
+        .rows()
+        .values()
+        .map(|row| (row.start_char_idx(), row.end_char_idx()))
+        .collect()
+    };
+
+    for (line_break, row_chars) in [(false, 10), (true, 8)] {
+      let options = WindowLocalOptions::builder()
+        .wrap(true)
+        .line_break(line_break)
+        .scroll_off(0)
+        .build();
+      let mut actual = make_viewport_from_size(U16Size::new(10, 4), buffer.clone(), &options);
+
+      // Only the rows within the window are synced.
+      assert_eq!(actual.end_line_idx(), 1);
+      assert_eq!(
+        rows(&actual, 0),
+        (0..4)
+          .map(|r| (r * row_chars, (r + 1) * row_chars))
+          .collect::<Vec<_>>()
+      );
+
+      // The rows before `start_dcolumn` are skipped.
+      actual.sync_from_top_left(0, 80);
+      assert_eq!(actual.start_dcol_idx(), 80);
+      assert_eq!(
+        rows(&actual, 0),
+        (0..4)
+          .map(|r| (80 + r * row_chars, 80 + (r + 1) * row_chars))
+          .collect::<Vec<_>>()
+      );
+
+      // The end of the line, the next line shows below it.
+      actual.sync_from_top_left(0, 8 * n - 16);
+      assert_eq!(actual.end_line_idx(), 2);
+      assert_eq!(rows(&actual, 0).first().unwrap().0, 8 * n - 16);
+      assert_eq!(rows(&actual, 0).last().unwrap().1, 8 * n + 1);
+      assert_eq!(rows(&actual, 1), vec![(0, 1)]);
+    }
+  }
+
+  #[test]
+  fn search_anchor_wrap_long_line1() {
+    test_log_init();
+
+    let n = 1_000_000;
+    let minified = format!("{}\n", "var a=1;".repeat(n));
+    let buffer = make_buffer_from_lines(vec![minified.as_str(), "b"]);
+
+    for line_break in [false, true] {
+      let options = WindowLocalOptions::builder()
+        .wrap(true)
+        .line_break(line_break)
+        .scroll_off(0)
+        .build();
+      let mut actual = make_viewport_from_size(U16Size::new(10, 4), buffer.clone(), &options);
+
+      // The visible chars don't move the viewport.
+      assert_eq!(actual.search_anchor(0, 0), (0, 0));
+      assert_eq!(actual.search_anchor(0, 30), (0, 0));
+
+      // Scroll downward to the end of the line, the char is visible.
+      let (start_line_idx, start_dcol_idx) = actual.search_anchor(0, 8 * n - 1);
+      assert_eq!(start_line_idx, 0);
+      assert!(start_dcol_idx >= 8 * n - 40);
+      actual.sync_from_top_left(start_line_idx, start_dcol_idx);
+      assert!(actual.cursor_of(0, 8 * n - 1).is_some());
+      if !line_break {
+        assert_eq!(start_dcol_idx, 8 * n - 40);
+        assert_eq!(actual.cursor_of(0, 8 * n - 1).unwrap().row_idx(), 3);
+      }
+
+      // Scroll upward to the start of the line.
+      assert_eq!(actual.search_anchor(0, 0), (0, 0));
+      let (start_line_idx, start_dcol_idx) = actual.search_anchor(0, 8 * n - 100);
+      assert_eq!(start_line_idx, 0);
+      actual.sync_from_top_left(start_line_idx, start_dcol_idx);
+      assert!(actual.cursor_of(0, 8 * n - 100).is_some());
+
+      // The next line is below the line taller than the window.
+      assert_eq!(actual.search_anchor(1, 0), (1, 0));
+      actual.sync_from_top_left(0, 8 * n - 16);
+      assert_eq!(actual.search_anchor(1, 0), (0, 8 * n - 16));
+    }
+  }
+
+  #[test]
+  fn scroll_vertically1() {
+    test_log_init();
+
+    let lines: Vec<String> = (0..20).map(|i| format!("line{}\n", i)).collect();
+    let buffer = make_buffer_from_lines(lines.iter().map(|l| l.as_str()).collect());
+    let options = WindowLocalOptions::builder()
+      .wrap(false)
+      .scroll_off(1)
+      .build();
+    let mut actual = make_viewport_from_size(U16Size::new(10, 5), buffer.clone(), &options);
+    assert_eq!(actual.cursor().line_idx(), 0);
+
+    // The cursor is scrolled out, it moves to the first row outside the 'scrolloff' margin.
+    actual.scroll_vertically(3);
+    assert_eq!(actual.start_line_idx(), 3);
+    assert_eq!(actual.cursor().line_idx(), 4);
+    assert_eq!(actual.cursor().row_idx(), 1);
+
+    // The cursor is visible and outside the margins, it doesn't move.
+    actual.scroll_vertically(-1);
+    assert_eq!(actual.start_line_idx(), 2);
+    assert_eq!(actual.cursor().line_idx(), 4);
+    assert_eq!(actual.cursor().row_idx(), 2);
+
+    // The start of the buffer has no top margin.
+    actual.scroll_vertically(-100);
+    assert_eq!(actual.start_line_idx(), 0);
+    assert_eq!(actual.cursor().line_idx(), 3);
+    assert_eq!(actual.cursor().row_idx(), 3);
+
+    // The last line can be scrolled to the top.
+    actual.scroll_vertically(100);
+    assert_eq!(actual.start_line_idx(), 20);
+  }
+
+  #[allow(clippy::type_complexity)]
+  fn dump_lines(viewport: &Viewport) -> Vec<(usize, Vec<(u16, usize, usize)>, usize, usize)> {
+    viewport
+      .lines()
+      .iter()
+      .map(|(line_idx, line)| {
+        let rows = line
+          .rows()
+          .iter()
+          .map(|(r, row)| (*r, row.start_char_idx(), row.end_char_idx()))
+          .collect();
+        (
+          *line_idx,
+          rows,
+          line.start_filled_columns(),
+          line.end_filled_columns(),
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn prefetch1() {
+    test_log_init();
+
+    let buffer = make_buffer_from_lines(vec![
+      "Hello, RSVIM!\n",
+      "This is a quite simple and small test lines.\n",
+      "But still it contains several things we want to test:\n",
+      "  1. When the line is small enough to completely put inside a row of the window content widget, then the line-wrap and word-wrap doesn't affect the rendering.\n",
+      "  2. When the line is too long to be completely put in a row of the window content widget, there're multiple cases:\n",
+      "Short\n",
+    ]);
+    let size = U16Size::new(15, 4);
+    let options = WindowLocalOptions::builder().wrap(true).build();
+    let mut actual = make_viewport_from_size(size, buffer.clone(), &options);
+    assert!(actual.prefetch_pending());
+
+    actual.prefetch();
+    assert!(!actual.prefetch_pending());
+    assert!(actual.prefetched.contains_key(&1));
+
+    // Scroll down with the prefetched lines.
+    actual.sync_from_top_left(1, 0);
+    assert!(actual.prefetch_pending());
+    let mut expect = make_viewport_from_size(size, buffer.clone(), &options);
+    expect.sync_from_top_left(1, 0);
+    assert_eq!(actual.start_line_idx(), expect.start_line_idx());
+    assert_eq!(actual.end_line_idx(), expect.end_line_idx());
+    assert_eq!(dump_lines(&actual), dump_lines(&expect));
+
+    // Edits invalidate the prefetched lines.
+    actual.prefetch();
+    wlock!(buffer).insert(0, "Hi\n");
+    actual.sync_from_top_left(0, 0);
+    assert!(actual.prefetched.is_empty());
+    let mut expect = make_viewport_from_size(size, buffer.clone(), &options);
+    expect.sync_from_top_left(0, 0);
+    assert_eq!(dump_lines(&actual), dump_lines(&expect));
+  }
 }
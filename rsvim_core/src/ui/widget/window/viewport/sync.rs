@@ -1,7 +1,8 @@
 //! Internal implementations for Viewport.
 
-use crate::buf::BufferWk;
+use crate::buf::{Buffer, BufferWk};
 use crate::cart::U16Rect;
+use crate::defaults::grapheme::grapheme_indices;
 use crate::envar;
 use crate::rlock;
 use crate::ui::widget::window::viewport::RowViewport;
@@ -144,12 +145,19 @@ fn _sync_from_top_left_nowrap(
         let mut rows: BTreeMap<u16, RowViewport> = BTreeMap::new();
         let mut wcol = 0_u16;
 
-        let mut dcol = 0_usize;
+        // Skip the prefix before `start_dcolumn` without going through each char.
+        let (skip_c_idx, skip_dcol) = if start_dcolumn > 0 {
+          buffer.char_at_dcolumn(current_line, start_dcolumn)
+        } else {
+          (0_usize, 0_usize)
+        };
+
+        let mut dcol = skip_dcol;
         let mut start_dcol = 0_usize;
-        let mut end_dcol = 0_usize;
+        let mut end_dcol = skip_dcol;
 
         let mut start_c_idx = 0_usize;
-        let mut end_c_idx = 0_usize;
+        let mut end_c_idx = skip_c_idx;
         let mut start_c_idx_init = false;
         let mut _end_c_idx_init = false;
 
@@ -158,12 +166,13 @@ fn _sync_from_top_left_nowrap(
         let mut start_fills = 0_usize;
         let mut end_fills = 0_usize;
 
-        // Go through each char in the line.
-        for (i, c) in line.chars().enumerate() {
+        // Go through each grapheme cluster in the line, the `n` chars of a cluster are placed in
+        // the same cells.
+        for (i, c, n) in grapheme_indices(line.chars_at(skip_c_idx), skip_c_idx) {
           let c_width = buffer.char_width(c);
 
           // Prefix width is still before `start_dcolumn`.
-          if dcol + c_width < start_dcolumn {
+          if dcol < start_dcolumn {
             dcol += c_width;
             end_dcol = dcol;
             end_c_idx = i;
@@ -179,6 +188,7 @@ fn _sync_from_top_left_nowrap(
             start_dcol = dcol;
             start_c_idx = i;
             start_fills = dcol - start_dcolumn;
+            wcol = start_fills as u16;
             // trace!(
             //   "2-wrow/wcol:{}/{}, c:{:?}/{:?}, dcol:{}/{}/{}, c_idx:{}/{}, fills:{}/{}, start_dcolumn:{}",
             //   wrow, wcol, c, c_width, dcol, start_dcol, end_dcol, start_c_idx, end_c_idx, start_fills, end_fills, start_dcolumn
@@ -214,10 +224,12 @@ fn _sync_from_top_left_nowrap(
 
           dcol += c_width;
           end_dcol = dcol;
-          end_c_idx = i + 1;
+          end_c_idx = i + n;
           wcol += c_width as u16;
 
-          ch2dcols.insert(saved_c_idx, (saved_start_dcol, end_dcol));
+          for k in 0..n {
+            ch2dcols.insert(saved_c_idx + k, (saved_start_dcol, end_dcol));
+          }
 
           // trace!(
           //   "5-wrow/wcol:{}/{}, c:{:?}/{:?}, dcol:{}/{}/{}, c_idx:{}/{}, fills:{}/{}",
@@ -235,7 +247,7 @@ fn _sync_from_top_left_nowrap(
           // );
 
           // End of the line.
-          if i + 1 == line.len_chars() {
+          if i + n == line.len_chars() {
             // trace!(
             //   "6-wrow/wcol:{}/{}, c:{:?}/{:?}, dcol:{}/{}/{}, c_idx:{}/{}, fills:{}/{}",
             //   wrow,
@@ -377,12 +389,16 @@ fn _sync_from_top_left_wrap_nolinebreak(
         let mut rows: BTreeMap<u16, RowViewport> = BTreeMap::new();
         let mut wcol = 0_u16;
 
-        let mut dcol = 0_usize;
-        let mut start_dcol = 0_usize;
-        let mut end_dcol = 0_usize;
+        // Skip the rows above the window of the first line, see [`skip_first_line`].
+        let (skip_c_idx, skip_dcol, start_dcolumn) =
+          skip_first_line(&buffer, start_line, current_line, start_dcolumn);
 
-        let mut start_c_idx = 0_usize;
-        let mut end_c_idx = 0_usize;
+        let mut dcol = skip_dcol;
+        let mut start_dcol = skip_dcol;
+        let mut end_dcol = skip_dcol;
+
+        let mut start_c_idx = skip_c_idx;
+        let mut end_c_idx = skip_c_idx;
         let mut start_c_idx_init = false;
         let mut _end_c_idx_init = false;
 
@@ -391,26 +407,17 @@ fn _sync_from_top_left_wrap_nolinebreak(
         let mut start_fills = 0_usize;
         let mut end_fills = 0_usize;
 
-        for (i, c) in line.chars().enumerate() {
+        // Only the chars can hold by the rest rows are iterated, i.e. it stops once the rows go
+        // out of the window, no matter how long the line is.
+        for (i, c, n) in grapheme_indices(line.chars_at(skip_c_idx), skip_c_idx) {
           let c_width = buffer.char_width(c);
 
-          // Prefix width is still before `start_dcolumn`.
-          if dcol + c_width < start_dcolumn {
-            dcol += c_width;
-            end_dcol = dcol;
-            end_c_idx = i;
-            // trace!(
-            //   "1-wrow/wcol:{}/{}, c:{}/{:?}, dcol:{}/{}/{}, c_idx:{}/{}, fills:{}/{}, start_dcolumn:{}",
-            //   wrow, wcol, c, c_width, dcol, start_dcol, end_dcol, start_c_idx, end_c_idx, start_fills, end_fills, start_dcolumn
-            // );
-            continue;
-          }
-
           if !start_c_idx_init {
             start_c_idx_init = true;
             start_dcol = dcol;
             start_c_idx = i;
             start_fills = dcol - start_dcolumn;
+            wcol = start_fills as u16;
             // trace!(
             //   "2-wrow/wcol:{}/{}, c:{}/{:?}, dcol:{}/{}/{}, c_idx:{}/{}, fills:{}/{}",
             //   wrow,
@@ -480,10 +487,12 @@ fn _sync_from_top_left_wrap_nolinebreak(
 
           dcol += c_width;
           end_dcol = dcol;
-          end_c_idx = i + 1;
+          end_c_idx = i + n;
           wcol += c_width as u16;
 
-          ch2dcols.insert(saved_c_idx, (saved_start_dcol, end_dcol));
+          for k in 0..n {
+            ch2dcols.insert(saved_c_idx + k, (saved_start_dcol, end_dcol));
+          }
 
           // trace!(
           //   "5-wrow/wcol:{}/{}, c:{}/{:?}, dcol:{}/{}/{}, c_idx:{}/{}, fills:{}/{}",
@@ -501,7 +510,7 @@ fn _sync_from_top_left_wrap_nolinebreak(
           // );
 
           // End of the line.
-          if i + 1 == line.len_chars() {
+          if i + n == line.len_chars() {
             // trace!(
             //   "6-wrow/wcol:{}/{}, c:{}/{:?}, dcol:{}/{}/{}, c_idx:{}/{}, fills:{}/{}",
             //   wrow,
@@ -606,13 +615,29 @@ fn _sync_from_top_left_wrap_nolinebreak(
   }
 }
 
-fn truncate_line(line: &RopeSlice, start_column: usize, max_bytes: usize) -> String {
+// The display columns before `start_dcolumn` are only skipped on the first line when 'wrap' is
+// `true`, i.e. the rows above the window of a line taller than the window (such as a super long
+// line of the minified code). It finds the first char at/after `start_dcolumn` without going
+// through each char, returns the char index, its display column and the `start_dcolumn` of the
+// line.
+fn skip_first_line(
+  buffer: &Buffer,
+  start_line: usize,
+  current_line: usize,
+  start_dcolumn: usize,
+) -> (usize, usize, usize) {
+  if current_line == start_line && start_dcolumn > 0 {
+    let (skip_c_idx, skip_dcol) = buffer.char_at_dcolumn(current_line, start_dcolumn);
+    (skip_c_idx, skip_dcol, start_dcolumn)
+  } else {
+    (0_usize, 0_usize, 0_usize)
+  }
+}
+
+fn truncate_line(line: &RopeSlice, max_bytes: usize) -> String {
   let mut builder = String::new();
   builder.reserve(max_bytes);
-  for (i, c) in line.chars().enumerate() {
-    if i < start_column {
-      continue;
-    }
+  for c in line.chars() {
     if builder.len() > max_bytes {
       return builder;
     }
@@ -672,13 +697,17 @@ fn _sync_from_top_left_wrap_linebreak(
         let mut rows: BTreeMap<u16, RowViewport> = BTreeMap::new();
         let mut wcol = 0_u16;
 
-        let mut bchars = 0_usize;
-        let mut dcol = 0_usize;
-        let mut start_dcol = 0_usize;
-        let mut end_dcol = 0_usize;
+        // Skip the rows above the window of the first line, see [`skip_first_line`].
+        let (skip_c_idx, skip_dcol, start_dcolumn) =
+          skip_first_line(&buffer, start_line, current_line, start_dcolumn);
 
-        let mut start_c_idx = 0_usize;
-        let mut end_c_idx = 0_usize;
+        let mut bchars = skip_c_idx;
+        let mut dcol = skip_dcol;
+        let mut start_dcol = skip_dcol;
+        let mut end_dcol = skip_dcol;
+
+        let mut start_c_idx = skip_c_idx;
+        let mut end_c_idx = skip_c_idx;
         let mut start_c_idx_init = false;
         let mut _end_c_idx_init = false;
 
@@ -687,13 +716,12 @@ fn _sync_from_top_left_wrap_linebreak(
         let mut start_fills = 0_usize;
         let mut end_fills = 0_usize;
 
-        // Chop the line into maximum chars can hold by current window, thus avoid those super
-        // long lines for iteration performance.
-        // NOTE: Use `height * width * 4` simply for a much bigger size for the total characters in
+        // Chop the line (after the skipped chars) into maximum chars can hold by current window,
+        // thus avoid those super long lines for iteration performance.
+        // NOTE: Use `height * width * 2` simply for a much bigger size for the total characters in
         // a viewport.
         let truncated_line = truncate_line(
-          &line,
-          start_dcolumn,
+          &line.slice(skip_c_idx..),
           height as usize * width as usize * 2 + height as usize * 2 + 16,
         );
         let word_boundaries: Vec<&str> = truncated_line.split_word_bounds().collect();
@@ -703,10 +731,12 @@ fn _sync_from_top_left_wrap_linebreak(
         // );
 
         for (i, wd) in word_boundaries.iter().enumerate() {
-          let (wd_chars, wd_width) = wd.chars().map(|c| (1_usize, buffer.char_width(c))).fold(
-            (0_usize, 0_usize),
-            |(init_chars, init_width), (count, width)| (init_chars + count, init_width + width),
-          );
+          let (wd_chars, wd_width) = grapheme_indices(wd.chars(), 0)
+            .map(|(_, c, n)| (n, buffer.char_width(c)))
+            .fold(
+              (0_usize, 0_usize),
+              |(init_chars, init_width), (count, width)| (init_chars + count, init_width + width),
+            );
 
           // trace!(
           //   "1-l:{:?}, line:'{:?}', current_line:{:?}, i:{}, wd:{:?}",
@@ -717,36 +747,12 @@ fn _sync_from_top_left_wrap_linebreak(
           //   wd
           // );
 
-          // Prefix width is still before `start_dcolumn`.
-          if dcol + wd_width < start_dcolumn {
-            dcol += wd_width;
-            bchars += wd_chars;
-            end_dcol = dcol;
-            end_c_idx = bchars;
-            // trace!(
-            //   "2-wrow/wcol:{}/{}, dcol:{}/{}/{}, bchars:{}, c_idx:{}/{}, fills:{}/{}, wd:{}/{}, start_dcolumn:{}",
-            //   wrow,
-            //   wcol,
-            //   dcol,
-            //   start_dcol,
-            //   end_dcol,
-            //   bchars,
-            //   start_c_idx,
-            //   end_c_idx,
-            //   start_fills,
-            //   end_fills,
-            //   wd_chars,
-            //   wd_width,
-            //   start_dcolumn
-            // );
-            continue;
-          }
-
           if !start_c_idx_init {
             start_c_idx_init = true;
             start_dcol = dcol;
             start_c_idx = bchars;
             start_fills = dcol - start_dcolumn;
+            wcol = start_fills as u16;
             // trace!(
             //   "3-wrow/wcol:{}/{}, dcol:{}/{}/{}, bchars:{}, c_idx:{}/{}, fills:{}/{}, wd:{}/{}",
             //   wrow,
@@ -811,7 +817,7 @@ fn _sync_from_top_left_wrap_linebreak(
 
               let saved_end_fills = {
                 let mut tmp_wcol = wcol;
-                for c in wd.chars() {
+                for (_, c, _) in grapheme_indices(wd.chars(), 0) {
                   let c_width = buffer.char_width(c);
 
                   // Column with next char will goes out of the row.
@@ -872,7 +878,7 @@ fn _sync_from_top_left_wrap_linebreak(
               }
             }
 
-            for (j, c) in wd.chars().enumerate() {
+            for (j, c, n) in grapheme_indices(wd.chars(), 0) {
               let c_width = buffer.char_width(c);
 
               // Column with next char will goes out of the row.
@@ -937,12 +943,14 @@ fn _sync_from_top_left_wrap_linebreak(
               let saved_start_dcol = dcol;
 
               dcol += c_width;
-              bchars += 1;
+              bchars += n;
               end_dcol = dcol;
               end_c_idx = bchars;
               wcol += c_width as u16;
 
-              ch2dcols.insert(saved_c_idx, (saved_start_dcol, end_dcol));
+              for k in 0..n {
+                ch2dcols.insert(saved_c_idx + k, (saved_start_dcol, end_dcol));
+              }
 
               // trace!(
               //   "8-wrow/wcol:{}/{}, dcol:{}/{}/{}, bchars:{}, j/c:{}/{:?}, c_idx:{}/{}, fills:{}/{}, wd:{}/{}",
@@ -1028,10 +1036,12 @@ fn _sync_from_top_left_wrap_linebreak(
             wcol += wd_width as u16;
 
             let mut tmp_start_dcol = saved_start_dcol;
-            for (k, c) in wd.chars().enumerate() {
+            for (k, c, n) in grapheme_indices(wd.chars(), 0) {
               let c_width = buffer.char_width(c);
               let tmp_end_dcol = tmp_start_dcol + c_width;
-              ch2dcols.insert(saved_c_idx + k, (tmp_start_dcol, tmp_end_dcol));
+              for m in 0..n {
+                ch2dcols.insert(saved_c_idx + k + m, (tmp_start_dcol, tmp_end_dcol));
+              }
               tmp_start_dcol = tmp_end_dcol;
             }
           }